@@ -0,0 +1,171 @@
+//! Stable pure-Rust API
+//!
+//! Everything needed to use the INSIGHTORA engines from another Rust crate
+//! without PyO3. Build with `default-features = false` to drop the Python
+//! dependency entirely:
+//!
+//! ```toml
+//! [dependencies]
+//! insightora_core = { path = "../insightora_core", default-features = false }
+//! ```
+//!
+//! ```no_run
+//! use insightora_core::api::{ParallelCsvParser, sort_data, Collation};
+//!
+//! # fn main() -> insightora_core::api::Result<()> {
+//! let df = ParallelCsvParser::new().parse("sales.csv")?;
+//! let sorted = sort_data(&df, &["region".to_string()], &[false], &Collation::Natural)?;
+//! println!("{}", sorted.head(Some(5)));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Items re-exported here follow semver for the crate; internal module paths
+//! may move between releases.
+
+pub use polars::prelude::DataFrame;
+
+// Errors and configuration
+pub use crate::error::InsightoraError;
+pub use crate::config::{RustConfig, get_current_config, set_config, check_memory_limit};
+pub use crate::utils::sandbox::{PathPolicy, UrlPolicy, check_path_allowed, check_url_allowed};
+pub use crate::utils::progress::{ProgressReporter, ProgressSink};
+pub use crate::utils::metrics::ExecutionMetrics;
+pub use crate::utils::memory::{watch, watch_with, checkpoint, sample_memory, MemorySample, WatchGuard, WatchdogSettings};
+pub use crate::utils::frame_compare::{
+    assert_frames_equal, frame_diff, FrameCompareOptions, FrameDiff, CellDifference, DtypeMismatch,
+};
+pub use crate::utils::simd::{KernelPath, Moments};
+pub use crate::utils::capabilities::{
+    capabilities, capability, degrade, use_fast_path, degradations, clear_degradations, Capability, Degradation, FastPath,
+    DEGRADATION_LOG_SIZE,
+};
+pub use crate::utils::column_mapper::{map_columns, Alias, ColumnDictionary, ColumnMatch, EXACT_PREFIX, REGEX_PREFIX};
+pub use crate::utils::warnings::{AggregatedWarning, WarningCollector, WarningLevel, DEFAULT_WARNING_SAMPLE_SIZE};
+
+// CSV parsing
+pub use crate::io::csv_parser::{
+    ParallelCsvParser, CsvParserConfig, NullValueTokens, StreamingCsvParser, StreamingCsvConfig, ProgressCallback,
+    BadLines, BadLineReport, BAD_LINE_SAMPLES, BAD_LINE_WARNING,
+    COUNT_BUFFER_BYTES, PARALLEL_COUNT_BYTES,
+    write_csv, write_csv_to, CsvWriterExt, CsvWriteOptions, QuoteStyle, EscapeStyle,
+    write_csv_partitioned, CsvPartitionOptions, CsvPart, PartitionedCsvReport, DEFAULT_PART_TEMPLATE, CSV_MANIFEST_FILE,
+};
+pub use crate::io::csv_glob::{parse_csv_glob, expand_glob, GlobParseResult, SOURCE_FILE_COLUMN};
+pub use crate::io::file_index::{
+    index_path, FileIndex, FileStamp, IndexBlock, ColumnStats, BoundValue, IndexScanReport, DEFAULT_BLOCK_BYTES, INDEX_SUFFIX,
+};
+pub use crate::io::compression::{Compression, decompressed_size, ASSUMED_COMPRESSION_RATIO};
+pub use crate::io::encoding::{CsvEncoding, EncodingErrors};
+pub use crate::io::schema_report::{SchemaReport, ColumnTypeReport, ValueType, OFFENDING_SAMPLES};
+pub use crate::io::sampling::{RowSample, RowSampleReport};
+pub use crate::io::dates::{parse_date_columns, detect as detect_date_format, DateFormat, DATE_WARNING, UNPARSED_DATES};
+pub use crate::io::nullability::{NotNullRules, NullPolicy, NullCheck, NullabilityReport, NullViolations, NULL_SAMPLE_ROWS};
+pub use crate::io::chunk_reader::{ChunkReader, ByteSource, RetryPolicy, DEFAULT_CHUNK_BYTES};
+pub use crate::io::csv_repair::{
+    CsvRepair, CsvRepairOptions, RepairReport, RowRepair, ColumnCountRepairer, REPAIR_FLAG_COLUMN,
+};
+
+// Streaming buffers
+pub use crate::streaming::buffer::{BatchPrefetcher, PrefetchPoll};
+
+// XML and HTML tables
+pub use crate::io::xml_parser::{parse_xml, parse_html_tables, MarkupReport};
+
+// NDJSON input
+pub use crate::io::json_parser::{parse_ndjson, NdjsonOptions};
+
+// Excel workbooks
+pub use crate::io::excel_parser::{
+    parse_excel, list_sheets as list_excel_sheets, ExcelParser, ExcelOptions, CellRange, FormulaCells, MergedCells, SheetInfo,
+    StreamingExcelParser, StreamingExcelConfig,
+};
+
+// pandas interop
+pub use crate::io::pandas_bridge::pandas_dtype;
+
+// Remote files
+pub use crate::io::remote::{
+    parse_remote_many, align_and_concat, RemoteFetchConfig, RemoteParseResult, RemoteFileStatus,
+    OnError, SchemaAlignment,
+};
+
+// ZIP archives
+pub use crate::io::archive::{list_archive, parse_archive, is_junk_member, ArchiveMember, ArchiveParseResult, MemberFormat};
+
+// Automatic parsing
+pub use crate::io::auto::{
+    parse_auto, detect_format, sniff_dialect, AutoParseOptions, AutoParseResult, AutoAttempt, AutoSettings, AutoFormat,
+    TextEncoding, SchemaInference, FailureClass, Concession, sniff_csv, detect_header, CsvDialect, SNIFF_BYTES,
+};
+
+// Chunked datasets
+pub use crate::io::dataset::{
+    Dataset, DatasetBuilder, DatasetBuilderConfig, SchemaPolicy, ColumnIndex, IndexKey,
+};
+
+// JSON export
+pub use crate::io::json_writer::{
+    write_json, write_json_to, to_json_string, write_ndjson, write_ndjson_to, JsonOrient, JsonSource,
+    JsonWriteOptions, NonFinitePolicy,
+};
+
+// Parquet export
+pub use crate::io::parquet_writer::{
+    write_parquet, write_partitioned, ParquetWriteReport, SchemaEnforcement, SchemaEnforcementReport, NULL_PARTITION,
+};
+
+// Arrow interchange
+pub use crate::io::arrow_bridge::{read_ipc, write_ipc, to_arrow, IpcCompressionKind};
+
+// DataFrame operations
+pub use crate::dataframe::operations::{
+    filter_rows, sort_data, sort_rows, natural_cmp, Collation, LocaleCollator, join_dataframes, join_type_from_name, JoinEngine, JoinReport,
+    join_rows, JoinHow, JoinOptions, JoinCounts, concat_frames, ConcatHow, ConcatOptions,
+    FloatPrecision, FloatKeyOptions, QuantizationReport, suggest_join_keys, JoinKeyCandidate, JOIN_KEY_SAMPLE_ROWS,
+    reorder_columns, add_prefix, add_suffix, normalize_column_names, reordered_names, affixed_names, normalized_names,
+    UnlistedColumns, NameStyle, NameCollision, select_columns, drop_columns, ColumnSelection, dtype_matches, DTYPE_NAMES,
+};
+pub use crate::dataframe::transformations::{
+    parse_duration, cast_to_duration, datetime_diff, add_duration, DurationFormat, DURATION_DTYPE,
+    datetime_features, truncate_datetime, DatetimeFeature, TruncateEvery,
+    drop_duplicates, duplicate_mask, keep_strategy_from_name, drop_nulls, fill_nulls, NullRows, FillStrategy,
+    cast_columns, string_op, text_pattern, StringOp,
+    rename_columns, rename_and_project, rename_and_project_schema, projection_indices, ColumnMapping,
+    set_category_order, category_order, category_ranks, UnknownCategory,
+    format_columns, ValueFormat, FormatKind, NumberLocale, SymbolPlacement, FORMATTED_SUFFIX,
+    qcut, apply_bins, fit_quantile_bins, even_quantiles, BinSpec, OutOfRange, NanBin, BIN_SUFFIX,
+    update_where, Assignment, UpdateReport,
+};
+pub use crate::dataframe::expressions::{add_columns, ColumnExpression, Predicate, NullArithmetic, Node, BinaryOp, CompareOp};
+pub use crate::dataframe::pipeline::{dtype_from_name, Pipeline, PipelineStep, PreparedPipeline, PipelineRunReport};
+pub use crate::dataframe::aggregations::{
+    aggregate_duration, DurationAggregation, value_counts, group_by, register_aggregation, clear_aggregations, CustomAggregation,
+    BUILTIN_AGGREGATIONS, group_by_drill_down, GroupContributors, register_drill_down, drill_down, release_drill_down,
+    DEFAULT_DRILL_DOWN_ROWS, rollup, group_by_tree, GroupTree, GroupNode, GroupTreeOptions, TreeOrder, DEFAULT_TREE_NODES,
+};
+
+// Statistics
+pub use crate::stats::descriptive::{RunningStats, ColumnSnapshot, QuantileSketch, describe, Description, CategorySummary};
+pub use crate::stats::identifiers::{detect_identifiers, identifier_columns, IdDetectionConfig, IdentifierDecision};
+pub use crate::stats::comparison::{
+    compare_groups, GroupComparison, ColumnComparison, NumericComparison, NumericSummary,
+    CategoricalComparison, CategoryDelta,
+};
+pub use crate::stats::tests::{t_test, anova, proportion_test, ks_test, TestResult, TestGroup, KS_EXACT_MAX_N};
+pub use crate::stats::outliers::{
+    suggest_thresholds, ThresholdSuggestion, OutlierMethod, DistributionShape, CandidateOutcome,
+    SuggestionConfidence,
+};
+pub use crate::stats::correlation::{
+    correlation, covariance_matrix, CorrelationMethod, CorrelationMatrix, CovarianceMatrix,
+    RobustCovarianceConfig, DEFAULT_BEND,
+};
+pub use crate::stats::downsample::{downsample_for_plot, DownsampleMethod, REPRESENTED_COLUMN};
+
+// SQL queries
+pub use crate::query::executor::{QuerySession, QueryReport, ScanReport, NodeReport, NodeRows};
+pub use crate::query::cache::{QueryCacheConfig, QueryCacheStats, normalize_query, DEFAULT_CACHE_ENTRIES, DEFAULT_CACHE_BYTES};
+
+/// Result type used throughout the public API
+pub type Result<T> = std::result::Result<T, InsightoraError>;
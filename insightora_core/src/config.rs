@@ -0,0 +1,153 @@
+// Global runtime configuration
+// Shared by the Rust engines and the Python configure()/get_config() bindings
+
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use once_cell::sync::Lazy;
+use crate::error::InsightoraError;
+use crate::utils::sandbox::{PathPolicy, UrlPolicy};
+use crate::utils::warnings::{WarningLevel, DEFAULT_WARNING_SAMPLE_SIZE};
+
+/// Global configuration for the Rust module
+pub(crate) static GLOBAL_CONFIG: Lazy<Arc<RwLock<RustConfig>>> = Lazy::new(|| {
+    Arc::new(RwLock::new(RustConfig::default()))
+});
+
+/// Track if thread pool has been initialized
+pub(crate) static THREAD_POOL_INITIALIZED: Lazy<Arc<RwLock<bool>>> = Lazy::new(|| {
+    Arc::new(RwLock::new(false))
+});
+
+/// Configuration structure for Rust module
+#[derive(Debug, Clone)]
+pub struct RustConfig {
+    pub thread_count: usize,
+    pub chunk_size: usize,
+    pub memory_limit_mb: usize,
+    pub enable_simd: bool,
+    pub cache_size: usize,
+    /// Filesystem allow/deny lists enforced by every parser and writer
+    pub path_policy: PathPolicy,
+    /// Scheme/host allow-lists for remote URL access
+    pub url_policy: UrlPolicy,
+    /// Parent of spill directories and other scratch files (system temp
+    /// directory when unset); checked against `path_policy` (see `utils::scratch`)
+    pub temp_dir: Option<PathBuf>,
+    /// Sample memory during long operations and abort them near the limit
+    /// (see `utils::memory::watch`)
+    pub memory_watchdog: bool,
+    /// Share of the memory limit (cgroup or physical) that triggers an abort
+    pub watchdog_threshold_pct: f64,
+    pub watchdog_interval_ms: u64,
+    /// Operations younger than this are never aborted
+    pub watchdog_grace_ms: u64,
+    /// Retries of a failed chunk read before a streaming or chunked parse
+    /// fails (see `io::chunk_reader::ChunkReader`)
+    pub io_retries: usize,
+    /// Wait before the first retry; doubled for each further one
+    pub io_retry_backoff_ms: u64,
+    /// Fail with `FastPathUnavailable` instead of falling back from SIMD,
+    /// memory mapping or parallelism (see `utils::capabilities`)
+    pub require_fast_paths: bool,
+    /// Examples kept per aggregated warning (see `utils::warnings`)
+    pub warning_sample_size: usize,
+    /// Level aggregated warnings are logged at when an operation finishes
+    pub warning_log_level: WarningLevel,
+}
+
+impl Default for RustConfig {
+    fn default() -> Self {
+        Self {
+            thread_count: num_cpus::get(),
+            chunk_size: 100_000,
+            memory_limit_mb: 4096,
+            enable_simd: true,
+            cache_size: 1000,
+            path_policy: PathPolicy::default(),
+            url_policy: UrlPolicy::default(),
+            temp_dir: None,
+            memory_watchdog: false,
+            watchdog_threshold_pct: 90.0,
+            watchdog_interval_ms: 100,
+            watchdog_grace_ms: 1000,
+            io_retries: 3,
+            io_retry_backoff_ms: 100,
+            require_fast_paths: false,
+            warning_sample_size: DEFAULT_WARNING_SAMPLE_SIZE,
+            warning_log_level: WarningLevel::Warning,
+        }
+    }
+}
+
+/// Get the current configuration (internal use)
+pub fn get_current_config() -> RustConfig {
+    GLOBAL_CONFIG.read()
+        .expect("Failed to read config")
+        .clone()
+}
+
+/// Replace the current configuration
+/// 
+/// Intended for Rust callers that don't go through the Python `configure`
+/// binding. The Rayon thread pool is not resized by this call.
+pub fn set_config(config: RustConfig) -> Result<(), InsightoraError> {
+    if config.chunk_size == 0 {
+        return Err(InsightoraError::ConfigError("chunk_size must be greater than 0".to_string()));
+    }
+    if config.memory_limit_mb == 0 {
+        return Err(InsightoraError::ConfigError("memory_limit_mb must be greater than 0".to_string()));
+    }
+    let threshold = config.watchdog_threshold_pct;
+    if threshold.is_nan() || threshold <= 0.0 || threshold > 100.0 {
+        return Err(InsightoraError::ConfigError("watchdog_threshold_pct must be in (0, 100]".to_string()));
+    }
+    
+    let mut current = GLOBAL_CONFIG.write()
+        .map_err(|e| InsightoraError::ConfigError(format!("Failed to acquire config lock: {}", e)))?;
+    *current = config;
+    Ok(())
+}
+
+/// Helper function to validate memory usage against configured limits
+pub fn check_memory_limit(estimated_mb: usize) -> Result<(), InsightoraError> {
+    let config = get_current_config();
+    if estimated_mb > config.memory_limit_mb {
+        return Err(InsightoraError::MemoryLimitExceeded {
+            requested: estimated_mb,
+            limit: config.memory_limit_mb,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config() {
+        let config = RustConfig::default();
+        assert!(config.thread_count > 0);
+        assert_eq!(config.chunk_size, 100_000);
+        assert_eq!(config.memory_limit_mb, 4096);
+        assert!(config.enable_simd);
+    }
+
+    #[test]
+    fn test_memory_limit_check() {
+        let result = check_memory_limit(2048);
+        assert!(result.is_ok());
+        
+        let result = check_memory_limit(5000);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_set_config_rejects_zero_chunk_size() {
+        let config = RustConfig {
+            chunk_size: 0,
+            ..Default::default()
+        };
+        assert!(set_config(config).is_err());
+    }
+}
@@ -1,2 +1,1094 @@
 // Parallel aggregation functions
-// TODO: Implement in task 9
+// Value counts, group-by with built-in and registered custom aggregations, rollups
+// and nested group trees, group drill-down to contributing rows, and aggregations
+// over typed columns (durations are reduced on their physical values)
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
+use once_cell::sync::Lazy;
+use polars::prelude::*;
+use crate::error::InsightoraError;
+use crate::dataframe::operations::{
+    comparison_keys, FloatKeyOptions, KeyRole, QuantizationReport,
+};
+use crate::dataframe::transformations::{categories_from_ranks, category_order};
+
+/// Aggregations supported on Duration columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DurationAggregation {
+    Sum,
+    Mean,
+    Min,
+    Max,
+}
+
+impl DurationAggregation {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "sum" => Ok(DurationAggregation::Sum),
+            "mean" | "avg" => Ok(DurationAggregation::Mean),
+            "min" => Ok(DurationAggregation::Min),
+            "max" => Ok(DurationAggregation::Max),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unsupported duration aggregation '{}'; expected sum, mean, min or max",
+                other
+            ))),
+        }
+    }
+}
+
+/// Aggregate a Duration column, returning microseconds
+///
+/// Nulls are ignored; an all-null column gives None. The mean is rounded to
+/// the nearest microsecond.
+pub fn aggregate_duration(series: &Series, agg: DurationAggregation) -> Result<Option<i64>, InsightoraError> {
+    if !matches!(series.dtype(), DataType::Duration(_)) {
+        return Err(InsightoraError::InvalidDataType {
+            expected: "Duration".to_string(),
+            actual: format!("{} ({})", series.dtype(), series.name()),
+        });
+    }
+    let micros = series.cast(&DataType::Duration(TimeUnit::Microseconds))?;
+    let physical = micros.to_physical_repr();
+    let values = physical.i64()?;
+
+    let result = match agg {
+        DurationAggregation::Sum => {
+            if values.null_count() == values.len() {
+                None
+            } else {
+                Some(values.into_iter().flatten().fold(0i64, |acc, v| acc.saturating_add(v)))
+            }
+        }
+        DurationAggregation::Mean => {
+            // i128 accumulator so long sums can't overflow before dividing
+            let (sum, count) = values
+                .into_iter()
+                .flatten()
+                .fold((0i128, 0i128), |(s, c), v| (s + v as i128, c + 1));
+            (count > 0).then(|| ((sum as f64) / (count as f64)).round() as i64)
+        }
+        DurationAggregation::Min => values.min(),
+        DurationAggregation::Max => values.max(),
+    };
+    Ok(result)
+}
+
+/// Count occurrences of each distinct value in a column
+///
+/// Returns the column and a "count" column, most frequent first (ties keep
+/// first-appearance order); nulls are counted as one value. With a float
+/// precision set, values that quantize to the same key are counted together
+/// under the first stored value seen, and the report counts rows whose stored
+/// value differs from that representative.
+pub fn value_counts(
+    df: &DataFrame,
+    column: &str,
+    float_options: &FloatKeyOptions,
+) -> Result<(DataFrame, QuantizationReport), InsightoraError> {
+    let series = df.column(column)?;
+    let keys = comparison_keys(df, &[column.to_string()], float_options, KeyRole::Grouping, "v")?;
+    let key_names: Vec<Expr> = keys.iter().map(|s| col(s.name())).collect();
+
+    let counted = df
+        .hstack(&keys)?
+        .lazy()
+        .group_by_stable(key_names)
+        .agg([col(column).first(), count().alias("count")])
+        .sort(
+            "count",
+            SortOptions {
+                descending: true,
+                nulls_last: true,
+                multithreaded: true,
+                maintain_order: true,
+            },
+        )
+        .select([col(column), col("count")])
+        .collect()?;
+
+    let quantized = float_options.precision.is_some() && series.dtype().is_float();
+    let rows_affected = if quantized {
+        merged_row_count(series, &keys)?
+    } else {
+        0
+    };
+    Ok((counted, QuantizationReport { rows_affected }))
+}
+
+/// Rows whose stored float differs from the first value sharing its key
+fn merged_row_count(series: &Series, keys: &[Series]) -> Result<usize, InsightoraError> {
+    let values = series.cast(&DataType::Float64)?;
+    let values = values.f64()?;
+    let key_values = keys[0].i64()?;
+    let key_classes = keys[1].u32()?;
+
+    let mut first_seen: HashMap<(Option<i64>, Option<u32>), u64> = HashMap::new();
+    let mut merged = 0;
+    for ((value, key), class) in values.into_iter().zip(key_values).zip(key_classes) {
+        let Some(value) = value else { continue };
+        // NaN bit patterns vary; normalize so equal NaNs compare equal
+        let bits = if value.is_nan() { f64::NAN.to_bits() } else { value.to_bits() };
+        let representative = *first_seen.entry((key, class)).or_insert(bits);
+        if representative != bits {
+            merged += 1;
+        }
+    }
+    Ok(merged)
+}
+
+/// Built-in aggregation names accepted by `group_by`
+pub const BUILTIN_AGGREGATIONS: &[&str] = &[
+    "sum", "mean", "min", "max", "median", "std", "var", "count", "n_unique", "first", "last", "list",
+];
+
+/// Aggregation implemented outside the crate (e.g. registered from Python)
+///
+/// Called once per group with the group's non-null values; returning None
+/// produces a null aggregate.
+pub trait CustomAggregation: Send + Sync {
+    fn aggregate(&self, values: &[f64]) -> Result<Option<f64>, InsightoraError>;
+}
+
+static CUSTOM_AGGREGATIONS: Lazy<RwLock<HashMap<String, Arc<dyn CustomAggregation>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Register a custom aggregation usable by name in `group_by` specs
+///
+/// Registering an existing custom name replaces it; built-in names are
+/// reserved.
+pub fn register_aggregation(name: &str, aggregation: Arc<dyn CustomAggregation>) -> Result<(), InsightoraError> {
+    if name.is_empty() || BUILTIN_AGGREGATIONS.contains(&name) {
+        return Err(InsightoraError::ValidationError(format!(
+            "Cannot register aggregation '{}': name is empty or reserved for a built-in",
+            name
+        )));
+    }
+    CUSTOM_AGGREGATIONS
+        .write()
+        .map_err(|e| InsightoraError::ConfigError(format!("Failed to acquire aggregation registry lock: {}", e)))?
+        .insert(name.to_string(), aggregation);
+    Ok(())
+}
+
+/// Remove every registered custom aggregation, returning how many were removed
+///
+/// Embedders call this before tearing down whatever the aggregations
+/// reference (the Python bindings do so at interpreter shutdown).
+pub fn clear_aggregations() -> Result<usize, InsightoraError> {
+    let mut registry = CUSTOM_AGGREGATIONS
+        .write()
+        .map_err(|e| InsightoraError::ConfigError(format!("Failed to acquire aggregation registry lock: {}", e)))?;
+    let removed = registry.len();
+    registry.clear();
+    Ok(removed)
+}
+
+fn custom_aggregation(name: &str) -> Result<Option<Arc<dyn CustomAggregation>>, InsightoraError> {
+    let registry = CUSTOM_AGGREGATIONS
+        .read()
+        .map_err(|e| InsightoraError::ConfigError(format!("Failed to acquire aggregation registry lock: {}", e)))?;
+    Ok(registry.get(name).cloned())
+}
+
+/// A sum or mean of a Duration column, reduced by `aggregate_duration`
+struct DurationSpec {
+    column: String,
+    output: String,
+    aggregation: DurationAggregation,
+}
+
+/// A custom aggregation resolved for one input column
+struct CustomSpec {
+    column: String,
+    name: String,
+    output: String,
+    aggregation: Arc<dyn CustomAggregation>,
+}
+
+/// Group rows by key columns and aggregate
+///
+/// `aggregations` pairs each input column with aggregation names (built-in
+/// or registered); outputs are named `{column}_{aggregation}`. Groups come out
+/// in first-appearance order and null keys form their own group. `min` and
+/// `max` of an ordered categorical follow its category order.
+///
+/// Null values are skipped by every aggregation except `first`, `last` and
+/// `list`, which take the group's rows as they are. `std` and `var` are
+/// sample statistics (divided by n - 1), and `list` gives each group's values
+/// in row order as a list column. `sum` and `mean` of a Duration column are
+/// Duration(μs), the mean rounded to the microsecond (see
+/// `aggregate_duration`).
+///
+/// # Example
+/// ```no_run
+/// use insightora_core::api::{group_by, DataFrame};
+///
+/// # fn run(df: &DataFrame) -> insightora_core::api::Result<()> {
+/// let specs = vec![("amount".to_string(), vec!["sum".to_string(), "mean".to_string()])];
+/// let summary = group_by(df, &["region".to_string()], &specs)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn group_by(
+    df: &DataFrame,
+    keys: &[String],
+    aggregations: &[(String, Vec<String>)],
+) -> Result<DataFrame, InsightoraError> {
+    if keys.is_empty() {
+        return Err(InsightoraError::ValidationError("group_by requires at least one key column".to_string()));
+    }
+
+    let mut builtin = Vec::new();
+    let mut custom = Vec::new();
+    let mut durations = Vec::new();
+    let mut ordered = Vec::new();
+    let mut output_order: Vec<String> = keys.to_vec();
+    for (column, names) in aggregations {
+        let is_ordered = category_order(df.column(column)?).is_some();
+        let is_duration = matches!(df.column(column)?.dtype(), DataType::Duration(_));
+        for name in names {
+            let output = format!("{}_{}", column, name);
+            let expr = match name.as_str() {
+                "sum" | "mean" if is_duration => {
+                    durations.push(DurationSpec {
+                        column: column.clone(),
+                        output: output.clone(),
+                        aggregation: DurationAggregation::from_name(name)?,
+                    });
+                    output_order.push(output);
+                    continue;
+                }
+                // Ordered categoricals reduce their category ranks
+                "min" | "max" if is_ordered => {
+                    ordered.push((output.clone(), column.clone()));
+                    let ranks = col(column).to_physical();
+                    if name == "min" { ranks.min() } else { ranks.max() }
+                }
+                "sum" => col(column).sum(),
+                "mean" => col(column).mean(),
+                "min" => col(column).min(),
+                "max" => col(column).max(),
+                "median" => col(column).median(),
+                "std" => col(column).std(1),
+                "var" => col(column).var(1),
+                "count" => col(column).is_not_null().sum(),
+                "n_unique" => col(column).drop_nulls().n_unique(),
+                "first" => col(column).first(),
+                "last" => col(column).last(),
+                // An unaggregated column collects each group's values
+                "list" => col(column),
+                other => {
+                    let aggregation = custom_aggregation(other)?.ok_or_else(|| {
+                        InsightoraError::ValidationError(format!(
+                            "Unknown aggregation '{}'; expected one of {} or a registered aggregation",
+                            other,
+                            BUILTIN_AGGREGATIONS.join(", ")
+                        ))
+                    })?;
+                    custom.push(CustomSpec {
+                        column: column.clone(),
+                        name: other.to_string(),
+                        output: output.clone(),
+                        aggregation,
+                    });
+                    output_order.push(output);
+                    continue;
+                }
+            };
+            builtin.push(expr.alias(&output));
+            output_order.push(output);
+        }
+    }
+
+    let key_exprs: Vec<Expr> = keys.iter().map(|k| col(k)).collect();
+    let mut result = df.clone().lazy().group_by_stable(key_exprs).agg(builtin).collect()?;
+    for (output, column) in &ordered {
+        let categories = category_order(df.column(column)?).unwrap_or_default();
+        let restored = categories_from_ranks(output, result.column(output)?, &categories)?;
+        result.replace(output, restored)?;
+    }
+    if !custom.is_empty() || !durations.is_empty() {
+        // Same first-appearance group order as the lazy path above
+        let (key_columns, groups, _) = stable_groups(df, keys, None)?;
+        let mut columns = custom_aggregation_columns(df, &key_columns, &groups, &custom)?;
+        columns.extend(duration_aggregation_columns(df, &groups, &durations)?);
+        result = result.hstack(&columns)?;
+    }
+    Ok(result.select(output_order)?)
+}
+
+/// Evaluate Duration sums and means group by group
+fn duration_aggregation_columns(
+    df: &DataFrame,
+    groups: &[Vec<usize>],
+    specs: &[DurationSpec],
+) -> Result<Vec<Series>, InsightoraError> {
+    specs
+        .iter()
+        .map(|spec| {
+            let series = df.column(&spec.column)?;
+            let out = groups
+                .iter()
+                .map(|rows| {
+                    let indices = IdxCa::from_vec("", rows.iter().map(|&r| r as IdxSize).collect());
+                    aggregate_duration(&series.take(&indices)?, spec.aggregation)
+                })
+                .collect::<Result<Int64Chunked, InsightoraError>>()?;
+            Ok(out.with_name(&spec.output).into_series().cast(&DataType::Duration(TimeUnit::Microseconds))?)
+        })
+        .collect()
+}
+
+/// Evaluate custom aggregations group by group
+fn custom_aggregation_columns(
+    df: &DataFrame,
+    key_columns: &[Series],
+    groups: &[Vec<usize>],
+    specs: &[CustomSpec],
+) -> Result<Vec<Series>, InsightoraError> {
+    specs
+        .iter()
+        .map(|spec| {
+            let series = df.column(&spec.column)?;
+            if !series.dtype().is_numeric() {
+                return Err(InsightoraError::InvalidDataType {
+                    expected: "numeric".to_string(),
+                    actual: format!("{} ({})", series.dtype(), spec.column),
+                });
+            }
+            let values = series.cast(&DataType::Float64)?;
+            let values: Vec<Option<f64>> = values.f64()?.into_iter().collect();
+
+            let mut buffer = Vec::new();
+            let mut out = Vec::with_capacity(groups.len());
+            for (index, rows) in groups.iter().enumerate() {
+                buffer.clear();
+                buffer.extend(rows.iter().filter_map(|&r| values[r]));
+                let value = spec.aggregation.aggregate(&buffer).map_err(|e| {
+                    InsightoraError::ValidationError(format!(
+                        "Aggregation '{}' on column '{}' failed for group {}: {}",
+                        spec.name,
+                        spec.column,
+                        group_label(key_columns, index),
+                        e
+                    ))
+                })?;
+                out.push(value);
+            }
+            Ok(Series::new(&spec.output, out))
+        })
+        .collect()
+}
+
+/// Key columns, kept row indices and full row count of each group
+type StableGroups = (Vec<Series>, Vec<Vec<usize>>, Vec<usize>);
+
+/// Key values and row indices of each group, in first-appearance order
+///
+/// With `cap`, at most that many row indices are kept per group; the third
+/// element always holds the full row count of each group.
+fn stable_groups(
+    df: &DataFrame,
+    keys: &[String],
+    cap: Option<usize>,
+) -> Result<StableGroups, InsightoraError> {
+    let grouped = df.group_by_stable(keys)?;
+    let key_columns = grouped.keys();
+    let cap = cap.unwrap_or(usize::MAX);
+    let (groups, counts) = match grouped.get_groups() {
+        GroupsProxy::Idx(idx) => idx
+            .all()
+            .iter()
+            .map(|rows| (rows.iter().take(cap).map(|&r| r as usize).collect(), rows.len()))
+            .unzip(),
+        GroupsProxy::Slice { groups, .. } => groups
+            .iter()
+            .map(|&[first, len]| {
+                let kept = (len as usize).min(cap);
+                ((first as usize..first as usize + kept).collect(), len as usize)
+            })
+            .unzip(),
+    };
+    Ok((key_columns, groups, counts))
+}
+
+/// Render a group's key values for error messages, e.g. `(region=EU, year=2024)`
+fn group_label(keys: &[Series], index: usize) -> String {
+    let parts: Vec<String> = keys
+        .iter()
+        .map(|key| match key.get(index) {
+            Ok(value) => format!("{}={}", key.name(), value),
+            Err(_) => format!("{}=?", key.name()),
+        })
+        .collect();
+    format!("({})", parts.join(", "))
+}
+
+/// Group-by results at every prefix of `keys`
+///
+/// Element `i` aggregates by `keys[..=i]`, so the last element is
+/// `group_by(df, keys, aggregations)` and the others are its subtotals.
+/// Every level is computed from the rows, so means and custom aggregations
+/// are exact rather than aggregates of aggregates.
+pub fn rollup(
+    df: &DataFrame,
+    keys: &[String],
+    aggregations: &[(String, Vec<String>)],
+) -> Result<Vec<DataFrame>, InsightoraError> {
+    if keys.is_empty() {
+        return Err(InsightoraError::ValidationError("rollup requires at least one key column".to_string()));
+    }
+    (1..=keys.len()).map(|depth| group_by(df, &keys[..depth], aggregations)).collect()
+}
+
+/// Default cap on the nodes of a `group_by_tree` result
+pub const DEFAULT_TREE_NODES: usize = 10_000;
+
+/// How the nodes of one tree level are ordered among their siblings
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum TreeOrder {
+    /// First appearance in the data, as in the flat result
+    #[default]
+    Appearance,
+    /// By key value, nulls last
+    Key { descending: bool },
+    /// By an aggregate output such as "amount_sum", nulls last
+    Aggregate { name: String, descending: bool },
+}
+
+impl TreeOrder {
+    /// "appearance", "key" or an aggregate output name
+    pub fn from_name(name: &str, descending: bool) -> Self {
+        match name {
+            "appearance" => TreeOrder::Appearance,
+            "key" => TreeOrder::Key { descending },
+            aggregate => TreeOrder::Aggregate { name: aggregate.to_string(), descending },
+        }
+    }
+}
+
+/// Options for `group_by_tree`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupTreeOptions {
+    /// Order per level, outermost first; missing levels keep appearance order
+    pub order: Vec<TreeOrder>,
+    /// Give inner nodes the aggregates of all rows beneath them
+    pub subtotals: bool,
+    /// Total nodes kept; the deepest levels are cut first
+    pub max_nodes: usize,
+}
+
+impl Default for GroupTreeOptions {
+    fn default() -> Self {
+        Self { order: Vec::new(), subtotals: false, max_nodes: DEFAULT_TREE_NODES }
+    }
+}
+
+/// One group in a `GroupTree`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupNode {
+    /// Row of this group in its level's frame (`GroupTree::levels[depth]`)
+    pub row: usize,
+    /// Children before truncation
+    pub child_count: usize,
+    pub children: Vec<GroupNode>,
+}
+
+/// Multi-key group-by nested level by level
+#[derive(Debug, Clone)]
+pub struct GroupTree {
+    pub keys: Vec<String>,
+    /// Aggregate output names, as in the flat result
+    pub aggregates: Vec<String>,
+    /// `rollup` frames; the last is the flat `group_by` result
+    pub levels: Vec<DataFrame>,
+    pub roots: Vec<GroupNode>,
+    /// Nodes per level before truncation
+    pub level_counts: Vec<usize>,
+    /// Nodes cut to respect `max_nodes`
+    pub dropped_nodes: usize,
+    pub subtotals: bool,
+}
+
+impl GroupTree {
+    pub fn is_truncated(&self) -> bool {
+        self.dropped_nodes > 0
+    }
+}
+
+/// Group by several keys and nest the groups key by key
+///
+/// Leaves carry the same aggregates as the flat `group_by` result; inner
+/// nodes carry subtotals from `rollup` when `options.subtotals` is set.
+/// When the tree has more than `max_nodes` nodes, nodes are cut from the
+/// deepest level up, keeping the first nodes of each level in tree order.
+///
+/// # Returns
+/// * `Result<GroupTree>` - Nested groups with the frames their rows refer to
+pub fn group_by_tree(
+    df: &DataFrame,
+    keys: &[String],
+    aggregations: &[(String, Vec<String>)],
+    options: &GroupTreeOptions,
+) -> Result<GroupTree, InsightoraError> {
+    if options.max_nodes == 0 {
+        return Err(InsightoraError::ValidationError("max_nodes must be greater than 0".to_string()));
+    }
+    if options.order.len() > keys.len() {
+        return Err(InsightoraError::ValidationError(format!(
+            "{} level orders given for {} key column(s)",
+            options.order.len(),
+            keys.len()
+        )));
+    }
+    let levels = rollup(df, keys, aggregations)?;
+    let aggregates: Vec<String> = levels[0].get_column_names()[1..].iter().map(|name| name.to_string()).collect();
+    for order in &options.order {
+        if let TreeOrder::Aggregate { name, .. } = order {
+            if !aggregates.contains(name) {
+                return Err(InsightoraError::ValidationError(format!(
+                    "Unknown aggregate '{}' to order by; expected 'key', 'appearance' or one of {}",
+                    name,
+                    aggregates.join(", ")
+                )));
+            }
+        }
+    }
+
+    // Children of each node, one list per row of the level above
+    let mut children: Vec<Vec<Vec<usize>>> = Vec::with_capacity(keys.len());
+    for depth in 1..keys.len() {
+        let parents = &levels[depth - 1];
+        let mut parent_rows = HashMap::with_capacity(parents.height());
+        for row in 0..parents.height() {
+            parent_rows.insert(key_tokens(parents, &keys[..depth], row)?, row);
+        }
+        let mut lists = vec![Vec::new(); parents.height()];
+        let level = &levels[depth];
+        for row in 0..level.height() {
+            let parent = parent_rows[&key_tokens(level, &keys[..depth], row)?];
+            lists[parent].push(row);
+        }
+        children.push(lists);
+    }
+
+    let level_counts: Vec<usize> = levels.iter().map(|level| level.height()).collect();
+    let roots: Vec<usize> = (0..levels[0].height()).collect();
+    let mut roots = build_nodes(&levels, keys, &options.order, &children, 0, roots)?;
+
+    let mut remaining = Vec::with_capacity(level_counts.len());
+    let mut budget = options.max_nodes;
+    for count in &level_counts {
+        let kept = (*count).min(budget);
+        remaining.push(kept);
+        budget -= kept;
+    }
+    let dropped_nodes = level_counts.iter().sum::<usize>() - remaining.iter().sum::<usize>();
+    prune(&mut roots, 0, &mut remaining);
+
+    Ok(GroupTree {
+        keys: keys.to_vec(),
+        aggregates,
+        levels,
+        roots,
+        level_counts,
+        dropped_nodes,
+        subtotals: options.subtotals,
+    })
+}
+
+/// Key values of a row, formatted with their type so "1" and 1 stay apart
+fn key_tokens(frame: &DataFrame, keys: &[String], row: usize) -> Result<Vec<String>, InsightoraError> {
+    keys.iter().map(|key| Ok(format!("{:?}", frame.column(key)?.get(row)?))).collect()
+}
+
+fn build_nodes(
+    levels: &[DataFrame],
+    keys: &[String],
+    order: &[TreeOrder],
+    children: &[Vec<Vec<usize>>],
+    depth: usize,
+    mut rows: Vec<usize>,
+) -> Result<Vec<GroupNode>, InsightoraError> {
+    let sort_column = match order.get(depth) {
+        Some(TreeOrder::Key { descending }) => Some((levels[depth].column(&keys[depth])?, *descending)),
+        Some(TreeOrder::Aggregate { name, descending }) => Some((levels[depth].column(name)?, *descending)),
+        Some(TreeOrder::Appearance) | None => None,
+    };
+    if let Some((column, descending)) = sort_column {
+        // Stable, so ties keep appearance order
+        rows.sort_by(|&a, &b| {
+            let (a, b) = (column.get(a).unwrap_or(AnyValue::Null), column.get(b).unwrap_or(AnyValue::Null));
+            match (matches!(a, AnyValue::Null), matches!(b, AnyValue::Null)) {
+                (true, true) => std::cmp::Ordering::Equal,
+                (true, false) => std::cmp::Ordering::Greater,
+                (false, true) => std::cmp::Ordering::Less,
+                (false, false) => {
+                    let ordering = a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal);
+                    if descending { ordering.reverse() } else { ordering }
+                }
+            }
+        });
+    }
+
+    rows.into_iter()
+        .map(|row| {
+            let nested = match children.get(depth) {
+                Some(lists) => build_nodes(levels, keys, order, children, depth + 1, lists[row].clone())?,
+                None => Vec::new(),
+            };
+            Ok(GroupNode { row, child_count: nested.len(), children: nested })
+        })
+        .collect()
+}
+
+/// Keep the first `remaining[depth]` nodes of each level in tree order
+fn prune(nodes: &mut Vec<GroupNode>, depth: usize, remaining: &mut [usize]) {
+    let kept = nodes.len().min(remaining[depth]);
+    nodes.truncate(kept);
+    remaining[depth] -= kept;
+    if depth + 1 < remaining.len() {
+        for node in nodes.iter_mut() {
+            prune(&mut node.children, depth + 1, remaining);
+        }
+    }
+}
+
+/// Default cap on the row indices kept per group
+pub const DEFAULT_DRILL_DOWN_ROWS: usize = 100;
+
+/// Drill-downs retained at once; registering another releases the oldest
+const MAX_DRILL_DOWNS: usize = 16;
+
+/// Rows behind each group of a `group_by` result
+///
+/// Keeps at most `max_rows_per_group` row indices per group (the first rows
+/// in source order) plus each group's full row count, so the cost is bounded
+/// by groups x cap. The source frame is shared with the caller, not copied.
+#[derive(Debug, Clone)]
+pub struct GroupContributors {
+    source: DataFrame,
+    /// Key values, one row per group in result order
+    groups: DataFrame,
+    rows: Vec<Vec<usize>>,
+    row_counts: Vec<usize>,
+    max_rows_per_group: usize,
+}
+
+impl GroupContributors {
+    pub fn group_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn max_rows_per_group(&self) -> usize {
+        self.max_rows_per_group
+    }
+
+    /// Key values of every group, in result order
+    pub fn groups(&self) -> &DataFrame {
+        &self.groups
+    }
+
+    /// Kept source row indices of a group
+    pub fn row_indices(&self, group: usize) -> Option<&[usize]> {
+        self.rows.get(group).map(Vec::as_slice)
+    }
+
+    /// Number of source rows in a group, including those beyond the cap
+    pub fn row_count(&self, group: usize) -> Option<usize> {
+        self.row_counts.get(group).copied()
+    }
+
+    /// Result row of the group whose keys render as `key` (None for a null key)
+    ///
+    /// Keys are compared in their string form, so `"2024"` matches an integer
+    /// key of 2024.
+    pub fn find_group(&self, key: &[Option<String>]) -> Result<Option<usize>, InsightoraError> {
+        if key.len() != self.groups.width() {
+            return Err(InsightoraError::ValidationError(format!(
+                "Group key has {} values but the grouping has {} key columns",
+                key.len(),
+                self.groups.width()
+            )));
+        }
+        let columns = self
+            .groups
+            .get_columns()
+            .iter()
+            .map(|s| s.cast(&DataType::String))
+            .collect::<PolarsResult<Vec<_>>>()?;
+        let columns = columns.iter().map(|s| s.str()).collect::<PolarsResult<Vec<_>>>()?;
+        Ok((0..self.groups.height()).find(|&row| {
+            columns.iter().zip(key).all(|(column, wanted)| column.get(row) == wanted.as_deref())
+        }))
+    }
+
+    /// Source rows contributing to a group (at most `max_rows_per_group`)
+    pub fn contributors(&self, group: usize) -> Result<DataFrame, InsightoraError> {
+        let rows = self.row_indices(group).ok_or_else(|| {
+            InsightoraError::ValidationError(format!(
+                "Group {} is out of range; the result has {} groups",
+                group,
+                self.group_count()
+            ))
+        })?;
+        let indices = IdxCa::from_vec("", rows.iter().map(|&r| r as IdxSize).collect());
+        Ok(self.source.take(&indices)?)
+    }
+}
+
+/// `group_by` that also records the rows behind each output group
+///
+/// # Arguments
+/// * `df` - Source data, retained (shared, not copied) for `contributors`
+/// * `keys` / `aggregations` - As for `group_by`
+/// * `max_rows_per_group` - Row indices kept per group
+///
+/// # Returns
+/// * `Result<(DataFrame, GroupContributors)>` - The `group_by` result and its
+///   provenance, with groups in the same order as the result rows
+pub fn group_by_drill_down(
+    df: &DataFrame,
+    keys: &[String],
+    aggregations: &[(String, Vec<String>)],
+    max_rows_per_group: usize,
+) -> Result<(DataFrame, GroupContributors), InsightoraError> {
+    if max_rows_per_group == 0 {
+        return Err(InsightoraError::ValidationError(
+            "max_rows_per_group must be greater than 0".to_string(),
+        ));
+    }
+    let result = group_by(df, keys, aggregations)?;
+    let (key_columns, rows, row_counts) = stable_groups(df, keys, Some(max_rows_per_group))?;
+    let contributors = GroupContributors {
+        source: df.clone(),
+        groups: DataFrame::new(key_columns)?,
+        rows,
+        row_counts,
+        max_rows_per_group,
+    };
+    Ok((result, contributors))
+}
+
+struct DrillDownRegistry {
+    next_handle: u64,
+    entries: VecDeque<(u64, Arc<GroupContributors>)>,
+}
+
+static DRILL_DOWNS: Lazy<Mutex<DrillDownRegistry>> = Lazy::new(|| {
+    Mutex::new(DrillDownRegistry {
+        next_handle: 1,
+        entries: VecDeque::new(),
+    })
+});
+
+fn drill_down_registry() -> Result<std::sync::MutexGuard<'static, DrillDownRegistry>, InsightoraError> {
+    DRILL_DOWNS
+        .lock()
+        .map_err(|e| InsightoraError::ConfigError(format!("Failed to acquire drill-down registry lock: {}", e)))
+}
+
+/// Keep a drill-down retrievable by handle
+///
+/// At most 16 are retained; the oldest is released when another is added.
+pub fn register_drill_down(contributors: GroupContributors) -> Result<u64, InsightoraError> {
+    let mut registry = drill_down_registry()?;
+    let handle = registry.next_handle;
+    registry.next_handle += 1;
+    if registry.entries.len() == MAX_DRILL_DOWNS {
+        registry.entries.pop_front();
+    }
+    registry.entries.push_back((handle, Arc::new(contributors)));
+    Ok(handle)
+}
+
+/// Look up a registered drill-down
+pub fn drill_down(handle: u64) -> Result<Arc<GroupContributors>, InsightoraError> {
+    drill_down_registry()?
+        .entries
+        .iter()
+        .find(|(h, _)| *h == handle)
+        .map(|(_, contributors)| Arc::clone(contributors))
+        .ok_or_else(|| {
+            InsightoraError::ValidationError(format!(
+                "Unknown drill-down handle {}; it was released or evicted by newer drill-downs",
+                handle
+            ))
+        })
+}
+
+/// Release a drill-down and its reference to the source data
+///
+/// # Returns
+/// * `Result<bool>` - Whether the handle was still registered
+pub fn release_drill_down(handle: u64) -> Result<bool, InsightoraError> {
+    let mut registry = drill_down_registry()?;
+    let before = registry.entries.len();
+    registry.entries.retain(|(h, _)| *h != handle);
+    Ok(registry.entries.len() < before)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::operations::FloatPrecision;
+    use crate::dataframe::pipeline::dtype_from_name;
+    use crate::dataframe::transformations::cast_columns;
+
+    #[test]
+    fn test_value_counts_with_float_precision() {
+        let df = df! {
+            "ratio" => [Some(0.1 + 0.2), Some(0.3), Some(0.3), Some(0.5), None],
+        }
+        .unwrap();
+
+        let (exact, report) = value_counts(&df, "ratio", &FloatKeyOptions::default()).unwrap();
+        assert_eq!(exact.height(), 4);
+        assert_eq!(report.rows_affected, 0);
+
+        let options = FloatKeyOptions {
+            precision: Some(FloatPrecision::Decimals(6)),
+            nan_equal: true,
+        };
+        let (counted, report) = value_counts(&df, "ratio", &options).unwrap();
+        assert_eq!(counted.height(), 3);
+        let counts: Vec<u32> = counted.column("count").unwrap().cast(&DataType::UInt32).unwrap()
+            .u32().unwrap().into_no_null_iter().collect();
+        assert_eq!(counts, vec![3, 1, 1]);
+        // Represented by the first stored value, 0.1 + 0.2
+        assert_eq!(counted.column("ratio").unwrap().f64().unwrap().get(0), Some(0.1 + 0.2));
+        assert_eq!(report.rows_affected, 2);
+    }
+
+    /// Mean after dropping `trim` of the values from each end
+    struct TrimmedMean {
+        trim: f64,
+    }
+
+    impl CustomAggregation for TrimmedMean {
+        fn aggregate(&self, values: &[f64]) -> Result<Option<f64>, InsightoraError> {
+            let mut sorted = values.to_vec();
+            sorted.sort_by(|a, b| a.total_cmp(b));
+            let cut = (sorted.len() as f64 * self.trim).floor() as usize;
+            let kept = &sorted[cut..sorted.len() - cut];
+            Ok((!kept.is_empty()).then(|| kept.iter().sum::<f64>() / kept.len() as f64))
+        }
+    }
+
+    struct FailsOnEmpty;
+
+    impl CustomAggregation for FailsOnEmpty {
+        fn aggregate(&self, values: &[f64]) -> Result<Option<f64>, InsightoraError> {
+            if values.is_empty() {
+                return Err(InsightoraError::ValidationError("no values".to_string()));
+            }
+            Ok(Some(values.len() as f64))
+        }
+    }
+
+    fn sales() -> DataFrame {
+        df! {
+            "region" => [Some("EU"), Some("US"), Some("EU"), None, Some("US"), Some("EU"), Some("EU"), Some("EU")],
+            "amount" => [Some(10.0), Some(4.0), Some(12.0), Some(7.0), None, Some(11.0), Some(100.0), Some(-50.0)],
+        }
+        .unwrap()
+    }
+
+    fn spec(column: &str, names: &[&str]) -> Vec<(String, Vec<String>)> {
+        vec![(column.to_string(), names.iter().map(|n| n.to_string()).collect())]
+    }
+
+    #[test]
+    fn test_group_by_builtin_aggregations() {
+        let result = group_by(&sales(), &["region".to_string()], &spec("amount", &["sum", "count"])).unwrap();
+        assert_eq!(result.get_column_names(), vec!["region", "amount_sum", "amount_count"]);
+        // EU, US, null in first-appearance order
+        assert_eq!(result.height(), 3);
+        assert_eq!(result.column("amount_sum").unwrap().f64().unwrap().get(0), Some(83.0));
+        assert!(matches!(result.column("region").unwrap().get(2).unwrap(), AnyValue::Null));
+    }
+
+    #[test]
+    fn test_group_by_sums_and_averages_parsed_durations() {
+        let sessions = df! {
+            "user" => ["ana", "ben", "ana", "ben", "ana"],
+            "length" => [Some("00:30:00"), Some("1h"), Some("PT45M"), None, Some("20m 30s")],
+        }
+        .unwrap();
+        let targets = [("length".to_string(), dtype_from_name("duration").unwrap())];
+        let (sessions, failures) = cast_columns(&sessions, &targets, None, true).unwrap();
+        assert_eq!(failures[0].1, 0);
+
+        let result = group_by(&sessions, &["user".to_string()], &spec("length", &["sum", "mean", "max"])).unwrap();
+        let micros = |name: &str| -> Vec<Option<i64>> {
+            let column = result.column(name).unwrap();
+            assert_eq!(column.dtype(), &DataType::Duration(TimeUnit::Microseconds), "{}", name);
+            column.to_physical_repr().i64().unwrap().into_iter().collect()
+        };
+        let minutes = 60_000_000i64;
+        // ana: 30m + 45m + 20m30s; ben: 1h and a null
+        assert_eq!(micros("length_sum"), [Some(95 * minutes + 30_000_000), Some(60 * minutes)]);
+        assert_eq!(micros("length_mean"), [Some(31 * minutes + 50_000_000), Some(60 * minutes)]);
+        assert_eq!(micros("length_max"), [Some(45 * minutes), Some(60 * minutes)]);
+    }
+
+    #[test]
+    fn test_group_by_statistics_and_row_aggregations() {
+        let specs = spec("amount", &["median", "std", "var", "n_unique", "first", "last", "list"]);
+        let result = group_by(&sales(), &["region".to_string()], &specs).unwrap();
+        let eu = |name: &str| result.column(name).unwrap().get(0).unwrap().into_static().unwrap();
+        assert_eq!(eu("amount_median"), AnyValue::Float64(11.0));
+        assert_eq!(eu("amount_n_unique"), AnyValue::UInt32(5));
+        assert_eq!((eu("amount_first"), eu("amount_last")), (AnyValue::Float64(10.0), AnyValue::Float64(-50.0)));
+        let var = result.column("amount_var").unwrap().f64().unwrap().get(0).unwrap();
+        let std = result.column("amount_std").unwrap().f64().unwrap().get(0).unwrap();
+        // EU amounts 10, 12, 11, 100, -50: mean 16.6, squared deviations 11487.2 over 4
+        assert!((var - 2871.8).abs() < 1e-9 && (std - var.sqrt()).abs() < 1e-9, "{} {}", var, std);
+        // US has a single null amount, kept by last and list but not counted
+        let us = result.column("amount_list").unwrap().list().unwrap().get_as_series(1).unwrap();
+        assert_eq!((us.len(), us.null_count()), (2, 1));
+        assert_eq!(result.column("amount_n_unique").unwrap().get(1).unwrap(), AnyValue::UInt32(1));
+        assert!(matches!(result.column("amount_last").unwrap().get(1).unwrap(), AnyValue::Null));
+
+        // A million groups over two keys, one holding nulls
+        let rows = 1_000_000;
+        let df = df! {
+            "user" => (0..rows).collect::<Vec<i64>>(),
+            "day" => (0..rows).map(|i| (i % 3 != 0).then_some(i % 2)).collect::<Vec<_>>(),
+            "amount" => (0..rows).map(|i| i as f64).collect::<Vec<_>>(),
+        }
+        .unwrap();
+        let result = group_by(&df, &["user".to_string(), "day".to_string()], &spec("amount", &["sum", "n_unique"])).unwrap();
+        assert_eq!(result.height(), rows as usize);
+        assert_eq!(result.column("day").unwrap().null_count(), 333_334);
+        assert_eq!(result.column("amount_sum").unwrap().f64().unwrap().get(123_456), Some(123_456.0));
+    }
+
+    #[test]
+    fn test_custom_trimmed_mean_matches_builtin_path() {
+        register_aggregation("test_trimmed_mean_0", Arc::new(TrimmedMean { trim: 0.0 })).unwrap();
+        register_aggregation("test_trimmed_mean_20", Arc::new(TrimmedMean { trim: 0.2 })).unwrap();
+        let keys = ["region".to_string()];
+
+        // With no trimming the UDAF must agree with the built-in mean
+        let result = group_by(&sales(), &keys, &spec("amount", &["mean", "test_trimmed_mean_0"])).unwrap();
+        let builtin = result.column("amount_mean").unwrap().f64().unwrap();
+        let custom = result.column("amount_test_trimmed_mean_0").unwrap().f64().unwrap();
+        for (b, c) in builtin.into_iter().zip(custom) {
+            match (b, c) {
+                (Some(b), Some(c)) => assert!((b - c).abs() < 1e-12),
+                (b, c) => assert_eq!(b, c),
+            }
+        }
+
+        // Trimming 20% of 5 EU values drops one from each end: the built-in
+        // mean over the remaining rows gives the same answer
+        let trimmed = group_by(&sales(), &keys, &spec("amount", &["test_trimmed_mean_20"])).unwrap();
+        let expected = df! { "region" => ["EU", "EU", "EU"], "amount" => [10.0, 12.0, 11.0] }.unwrap();
+        let expected = group_by(&expected, &keys, &spec("amount", &["mean"])).unwrap();
+        assert_eq!(
+            trimmed.column("amount_test_trimmed_mean_20").unwrap().f64().unwrap().get(0),
+            expected.column("amount_mean").unwrap().f64().unwrap().get(0),
+        );
+    }
+
+    #[test]
+    fn test_custom_aggregation_errors_name_group() {
+        register_aggregation("test_fails_on_empty", Arc::new(FailsOnEmpty)).unwrap();
+        let df = df! {
+            "region" => [Some("EU"), Some("US"), Some("EU")],
+            "amount" => [Some(10.0), None, Some(12.0)],
+        }
+        .unwrap();
+        let err = group_by(&df, &["region".to_string()], &spec("amount", &["test_fails_on_empty"]))
+            .unwrap_err()
+            .to_string();
+        // US has only a null amount
+        assert!(err.contains("test_fails_on_empty"), "{}", err);
+        assert!(err.contains("region=\"US\"") || err.contains("region=US"), "{}", err);
+    }
+
+    #[test]
+    fn test_register_rejects_builtin_names() {
+        assert!(register_aggregation("sum", Arc::new(FailsOnEmpty)).is_err());
+        assert!(group_by(&sales(), &["region".to_string()], &spec("amount", &["no_such_agg"])).is_err());
+    }
+
+    #[test]
+    fn test_group_tree_matches_flat_result() {
+        let df = df! {
+            "region" => ["EU", "EU", "US", "EU", "US", "EU"],
+            "year" => [2023, 2024, 2024, 2023, 2024, 2024],
+            "channel" => ["web", "web", "shop", "shop", "web", "web"],
+            "amount" => [10.0, 20.0, 5.0, 1.0, 7.0, 30.0],
+        }
+        .unwrap();
+        let keys: Vec<String> = ["region", "year", "channel"].map(String::from).to_vec();
+        let specs = spec("amount", &["sum", "mean"]);
+        let options = GroupTreeOptions {
+            order: vec![
+                TreeOrder::from_name("key", true),
+                TreeOrder::from_name("amount_sum", true),
+            ],
+            subtotals: true,
+            ..Default::default()
+        };
+        let tree = group_by_tree(&df, &keys, &specs, &options).unwrap();
+        let flat = group_by(&df, &keys, &specs).unwrap();
+        assert!(tree.levels[2].equals_missing(&flat));
+        assert_eq!(tree.level_counts, [2, 3, 5]);
+        assert!(!tree.is_truncated());
+
+        let value = |depth: usize, row: usize, column: &str| tree.levels[depth].column(column).unwrap().get(row).unwrap().to_string();
+        // US first (key descending), then EU's years by their total: 2024 (50) before 2023 (11)
+        assert_eq!(tree.levels[0].column("region").unwrap().str().unwrap().get(tree.roots[0].row), Some("US"));
+        let eu = &tree.roots[1];
+        let years: Vec<String> = eu.children.iter().map(|n| value(1, n.row, "year")).collect();
+        assert_eq!(years, ["2024", "2023"]);
+        // Subtotals add up to their children and mean is exact, not a mean of means
+        let sum = |depth: usize, row: usize| tree.levels[depth].column("amount_sum").unwrap().f64().unwrap().get(row).unwrap();
+        assert_eq!(sum(0, eu.row), eu.children.iter().map(|n| sum(1, n.row)).sum::<f64>());
+        assert_eq!(tree.levels[0].column("amount_mean").unwrap().f64().unwrap().get(eu.row), Some(61.0 / 4.0));
+
+        // The deepest level is cut first
+        let capped = group_by_tree(&df, &keys, &specs, &GroupTreeOptions { max_nodes: 6, ..Default::default() }).unwrap();
+        assert_eq!(capped.dropped_nodes, 4);
+        assert_eq!(capped.roots.iter().map(|n| n.children.len()).sum::<usize>(), 3);
+        let leaves: usize = capped.roots.iter().flat_map(|n| &n.children).map(|n| n.children.len()).sum();
+        assert_eq!(leaves, 1);
+        assert_eq!(capped.roots[0].children[1].child_count, 1);
+        assert!(group_by_tree(&df, &keys, &specs, &GroupTreeOptions {
+            order: vec![TreeOrder::from_name("amount_median", false)],
+            ..Default::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_drill_down_keeps_capped_contributors() {
+        let keys = vec!["region".to_string()];
+        let (result, drill) = group_by_drill_down(&sales(), &keys, &spec("amount", &["sum"]), 3).unwrap();
+        assert_eq!(drill.group_count(), result.height());
+
+        // EU has five rows; only the first three indices are kept
+        let eu = drill.find_group(&[Some("EU".to_string())]).unwrap().unwrap();
+        assert_eq!(drill.row_indices(eu), Some(&[0, 2, 5][..]));
+        assert_eq!(drill.row_count(eu), Some(5));
+        let rows = drill.contributors(eu).unwrap();
+        let amounts: Vec<Option<f64>> = rows.column("amount").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(amounts, vec![Some(10.0), Some(12.0), Some(11.0)]);
+
+        let null_group = drill.find_group(&[None]).unwrap().unwrap();
+        assert_eq!(drill.row_indices(null_group), Some(&[3][..]));
+        assert_eq!(drill.find_group(&[Some("APAC".to_string())]).unwrap(), None);
+        assert!(drill.find_group(&[]).is_err());
+        assert!(group_by_drill_down(&sales(), &keys, &spec("amount", &["sum"]), 0).is_err());
+
+        let handle = register_drill_down(drill).unwrap();
+        assert_eq!(drill_down(handle).unwrap().row_count(eu), Some(5));
+        assert!(release_drill_down(handle).unwrap());
+        assert!(!release_drill_down(handle).unwrap());
+        assert!(drill_down(handle).is_err());
+    }
+}
@@ -0,0 +1,1069 @@
+// Column expressions
+// Expression grammar for derived columns and row predicates, with selectable null semantics
+
+use polars::prelude::*;
+use crate::error::InsightoraError;
+
+// ============================================================================
+// Grammar
+// ============================================================================
+//
+//   expression  := conjunction ("or" conjunction)*
+//   conjunction := negation ("and" negation)*
+//   negation    := "not" negation | comparison
+//   comparison  := sum (("==" | "!=" | "<" | "<=" | ">" | ">=") sum
+//                       | "not"? "in" "[" (sum ("," sum)*)? "]"
+//                       | "is" "not"? "null"
+//                       | "contains" 'text')?
+//   sum         := term (("+" | "-") term)*
+//   term        := unary (("*" | "/" | "%") unary)*
+//   unary       := "-" unary | primary
+//   primary     := number | 'text' | "true" | "false" | "null" | column
+//                  | function "(" arguments ")" | "(" expression ")"
+//   column      := identifier | `any text in backticks`
+//   function    := "coalesce" | "nullif" | "fill_null" | "is_null"
+//
+// and, or, not, true, false and null are keywords in any case; columns
+// with those names need backticks. in, is and contains are keywords only
+// after an operand. '' inside a text literal is one quote.
+//
+// Null behaviour:
+//   + - * / %         null if either operand is null under "propagate";
+//                     a null operand counts as 0 under "zero"; "error" fails
+//                     on the first row with a null operand
+//   unary -           null stays null
+//   /                 true division (integers give floats); x / 0 follows
+//                     IEEE rules (inf or NaN), never null
+//   coalesce(a, b..)  first non-null argument, null when all are null
+//   nullif(a, b)      null where a == b, otherwise a
+//   fill_null(a, v)   a with nulls replaced by v
+//   == != < <= > >=   null if either side is null; an ordered categorical
+//                     compared with text compares category positions, and
+//                     text that isn't a category fails the check
+//   and or not        SQL three-valued logic (false and null is false)
+//   a in [b, c]       a == b or a == c, so null when a is null, or when no
+//                     value matches and the list holds a null
+//   is_null(a)        true where a is null, never null itself; also a is null
+//   a contains 't'    true where the text of a holds t; null when a is null
+//   literals          never null, except null
+//
+// Only arithmetic operands are affected by `null_arithmetic`; the arguments
+// of coalesce, nullif and fill_null always see the real nulls.
+
+/// How arithmetic operators treat null operands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NullArithmetic {
+    /// The result is null (SQL semantics)
+    #[default]
+    Propagate,
+    /// A null operand is treated as 0
+    Zero,
+    /// A null operand is an error naming the expression, column and row
+    Error,
+}
+
+impl NullArithmetic {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "propagate" => Ok(NullArithmetic::Propagate),
+            "zero" => Ok(NullArithmetic::Zero),
+            "error" => Ok(NullArithmetic::Error),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown null_arithmetic '{}'; expected 'propagate', 'zero' or 'error'",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NullArithmetic::Propagate => "propagate",
+            NullArithmetic::Zero => "zero",
+            NullArithmetic::Error => "error",
+        }
+    }
+}
+
+/// Arithmetic operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl BinaryOp {
+    fn apply(&self, left: Expr, right: Expr) -> Expr {
+        match self {
+            BinaryOp::Add => left + right,
+            BinaryOp::Sub => left - right,
+            BinaryOp::Mul => left * right,
+            BinaryOp::Div => binary_expr(left, Operator::TrueDivide, right),
+            BinaryOp::Rem => left % right,
+        }
+    }
+}
+
+/// Comparison operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+impl CompareOp {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            CompareOp::Eq => "==",
+            CompareOp::NotEq => "!=",
+            CompareOp::Lt => "<",
+            CompareOp::LtEq => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::GtEq => ">=",
+        }
+    }
+
+    /// The operator with its operands swapped, so `a < b` is `b > a`
+    fn flipped(&self) -> Self {
+        match self {
+            CompareOp::Lt => CompareOp::Gt,
+            CompareOp::LtEq => CompareOp::GtEq,
+            CompareOp::Gt => CompareOp::Lt,
+            CompareOp::GtEq => CompareOp::LtEq,
+            other => *other,
+        }
+    }
+
+    fn apply(&self, left: Expr, right: Expr) -> Expr {
+        match self {
+            CompareOp::Eq => left.eq(right),
+            CompareOp::NotEq => left.neq(right),
+            CompareOp::Lt => left.lt(right),
+            CompareOp::LtEq => left.lt_eq(right),
+            CompareOp::Gt => left.gt(right),
+            CompareOp::GtEq => left.gt_eq(right),
+        }
+    }
+}
+
+/// Parsed expression tree
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Int(i64),
+    Float(f64),
+    Text(String),
+    Bool(bool),
+    Null,
+    Column(String),
+    Negate(Box<Node>),
+    Binary(BinaryOp, Box<Node>, Box<Node>),
+    Compare(CompareOp, Box<Node>, Box<Node>),
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+    Not(Box<Node>),
+    /// Value and the list it is looked up in
+    In(Box<Node>, Vec<Node>),
+    /// Value and the text it must hold
+    Contains(Box<Node>, String),
+    Coalesce(Vec<Node>),
+    NullIf(Box<Node>, Box<Node>),
+    FillNull(Box<Node>, Box<Node>),
+    IsNull(Box<Node>),
+}
+
+impl Node {
+    /// Polars expression for this tree
+    ///
+    /// With the schema it will run on, comparing an ordered categorical
+    /// column with text compares category positions rather than text.
+    fn to_expr(&self, nulls: NullArithmetic, schema: Option<&Schema>) -> Expr {
+        match self {
+            Node::Int(value) => lit(*value),
+            Node::Float(value) => lit(*value),
+            Node::Text(value) => lit(value.as_str()),
+            Node::Bool(value) => lit(*value),
+            Node::Null => lit(Null {}),
+            Node::Column(name) => col(name),
+            Node::Negate(inner) => lit(0) - inner.to_expr(nulls, schema),
+            Node::Binary(op, left, right) => {
+                let operand = |node: &Node| match nulls {
+                    NullArithmetic::Zero => node.to_expr(nulls, schema).fill_null(lit(0)),
+                    _ => node.to_expr(nulls, schema),
+                };
+                op.apply(operand(left), operand(right))
+            }
+            Node::Compare(op, left, right) => match ordered_comparison(*op, left, right, schema) {
+                Some((column, Some(position), op)) => op.apply(col(column).to_physical(), lit(position)),
+                _ => op.apply(left.to_expr(nulls, schema), right.to_expr(nulls, schema)),
+            },
+            Node::And(left, right) => left.to_expr(nulls, schema).and(right.to_expr(nulls, schema)),
+            Node::Or(left, right) => left.to_expr(nulls, schema).or(right.to_expr(nulls, schema)),
+            Node::Not(inner) => inner.to_expr(nulls, schema).not(),
+            Node::In(value, list) => {
+                let value = value.to_expr(nulls, schema);
+                list.iter()
+                    .map(|item| value.clone().eq(item.to_expr(nulls, schema)))
+                    .reduce(|any, item| any.or(item))
+                    .unwrap_or_else(|| lit(false))
+            }
+            Node::Contains(value, text) => {
+                let text = text.clone();
+                value.to_expr(nulls, schema).map(
+                    move |series| {
+                        // Numbers and dates are searched as they print
+                        let values = series.cast(&DataType::String)?;
+                        let found: BooleanChunked = values
+                            .str()?
+                            .into_iter()
+                            .map(|value| value.map(|value| value.contains(text.as_str())))
+                            .collect();
+                        Ok(Some(found.into_series().with_name(series.name())))
+                    },
+                    GetOutput::from_type(DataType::Boolean),
+                )
+            }
+            Node::Coalesce(args) => coalesce(&args.iter().map(|arg| arg.to_expr(nulls, schema)).collect::<Vec<_>>()),
+            Node::NullIf(value, other) => {
+                let value = value.to_expr(nulls, schema);
+                when(value.clone().eq(other.to_expr(nulls, schema)))
+                    .then(lit(Null {}))
+                    .otherwise(value)
+            }
+            Node::FillNull(value, fill) => value.to_expr(nulls, schema).fill_null(fill.to_expr(nulls, schema)),
+            Node::IsNull(value) => value.to_expr(nulls, schema).is_null(),
+        }
+    }
+
+    /// Operands of every arithmetic operator, outermost first
+    fn operands<'a>(&'a self, found: &mut Vec<&'a Node>) {
+        match self {
+            Node::Binary(_, left, right) => {
+                found.push(left);
+                found.push(right);
+                left.operands(found);
+                right.operands(found);
+            }
+            Node::Negate(inner) | Node::Not(inner) | Node::IsNull(inner) | Node::Contains(inner, _) => inner.operands(found),
+            Node::Coalesce(args) => args.iter().for_each(|arg| arg.operands(found)),
+            Node::In(value, list) => {
+                value.operands(found);
+                list.iter().for_each(|item| item.operands(found));
+            }
+            Node::NullIf(a, b) | Node::FillNull(a, b) | Node::Compare(_, a, b) | Node::And(a, b) | Node::Or(a, b) => {
+                a.operands(found);
+                b.operands(found);
+            }
+            Node::Int(_) | Node::Float(_) | Node::Text(_) | Node::Bool(_) | Node::Null | Node::Column(_) => {}
+        }
+    }
+
+    /// Column that introduces nulls into this operand: itself, or the first
+    /// column it reads
+    fn first_column(&self) -> Option<&str> {
+        match self {
+            Node::Column(name) => Some(name),
+            Node::Negate(inner) | Node::Not(inner) | Node::IsNull(inner) | Node::Contains(inner, _) => inner.first_column(),
+            Node::Binary(_, left, right)
+            | Node::Compare(_, left, right)
+            | Node::And(left, right)
+            | Node::Or(left, right)
+            | Node::NullIf(left, right)
+            | Node::FillNull(left, right) => left.first_column().or_else(|| right.first_column()),
+            Node::Coalesce(args) => args.iter().find_map(|arg| arg.first_column()),
+            Node::In(value, list) => value.first_column().or_else(|| list.iter().find_map(|item| item.first_column())),
+            Node::Int(_) | Node::Float(_) | Node::Text(_) | Node::Bool(_) | Node::Null => None,
+        }
+    }
+}
+
+// ============================================================================
+// Parser
+// ============================================================================
+
+/// A derived column definition, e.g. `total = price * quantity`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnExpression {
+    pub name: String,
+    pub source: String,
+    root: Node,
+}
+
+impl ColumnExpression {
+    /// Parse `source` as the definition of column `name`
+    ///
+    /// # Returns
+    /// * `Result<ColumnExpression>` - ValidationError with the position of the
+    ///   first token that doesn't fit the grammar
+    pub fn parse(name: &str, source: &str) -> Result<Self, InsightoraError> {
+        Ok(Self { name: name.to_string(), source: source.to_string(), root: parse_tree(source)?.0 })
+    }
+
+    /// Parsed expression tree
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+}
+
+/// A row condition, e.g. `status == 'open' and amount > 100`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Predicate {
+    pub source: String,
+    root: Node,
+    /// Every column reference with its position in `source`
+    references: Vec<(String, usize)>,
+}
+
+impl Predicate {
+    /// Parse `source` with the expression grammar; whether it is a
+    /// condition is checked against a schema when it is used
+    pub fn parse(source: &str) -> Result<Self, InsightoraError> {
+        let (root, references) = parse_tree(source)?;
+        Ok(Self { source: source.to_string(), root, references })
+    }
+
+    /// Check the condition against the columns it will run on, before any
+    /// row is read
+    ///
+    /// # Returns
+    /// * `Result<()>` - ValidationError with the position of the first
+    ///   unknown column, or saying the condition isn't true or false
+    pub fn check(&self, schema: &Schema) -> Result<(), InsightoraError> {
+        if let Some((name, position)) = self.references.iter().find(|(name, _)| schema.get(name).is_none()) {
+            let columns: Vec<&str> = schema.iter_names().map(|name| name.as_str()).collect();
+            return Err(InsightoraError::ValidationError(format!(
+                "Invalid expression '{}': unknown column '{}' at position {}; the columns are {}",
+                self.source,
+                name,
+                position,
+                columns.join(", ")
+            )));
+        }
+        if let Some((column, category)) = unknown_category(&self.root, schema) {
+            return Err(InsightoraError::ValidationError(format!(
+                "Invalid expression '{}': '{}' is not a category of the ordered column '{}'",
+                self.source, category, column
+            )));
+        }
+        // Evaluated on an empty frame, so nothing runs on the data yet
+        let probe = DataFrame::from(schema).lazy().select([self.expr(schema)]).collect()?;
+        let dtype = probe.get_columns()[0].dtype();
+        if !matches!(dtype, DataType::Boolean | DataType::Null) {
+            return Err(InsightoraError::ValidationError(format!(
+                "Predicate '{}' is not a condition; it evaluates to {}",
+                self.source, dtype
+            )));
+        }
+        Ok(())
+    }
+
+    /// Parsed expression tree
+    pub fn root(&self) -> &Node {
+        &self.root
+    }
+
+    /// Columns the condition reads, in order of appearance
+    pub fn columns(&self) -> Vec<&str> {
+        let mut found = Vec::new();
+        collect_columns(&self.root, &mut found);
+        found
+    }
+
+    /// The condition as written, possibly null, for a frame with `schema`
+    pub(crate) fn expr(&self, schema: &Schema) -> Expr {
+        self.root.to_expr(NullArithmetic::Propagate, Some(schema))
+    }
+
+    /// Rows the condition selects; a null condition does not select the row
+    pub(crate) fn mask(&self, schema: &Schema) -> Expr {
+        self.expr(schema).fill_null(lit(false))
+    }
+}
+
+/// The tree, and every column reference with its position
+fn parse_tree(source: &str) -> Result<(Node, Vec<(String, usize)>), InsightoraError> {
+    let mut parser = Parser { source, tokens: tokenize(source)?, next: 0, references: Vec::new() };
+    let root = parser.expression()?;
+    if let Some((token, position)) = parser.tokens.get(parser.next) {
+        return Err(parser.error(&format!("unexpected {}", token.describe()), *position));
+    }
+    Ok((root, parser.references))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Int(i64),
+    Float(f64),
+    Text(String),
+    Ident(String),
+    Quoted(String),
+    Compare(CompareOp),
+    Symbol(char),
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Int(value) => format!("number {}", value),
+            Token::Float(value) => format!("number {}", value),
+            Token::Text(value) => format!("text '{}'", value),
+            Token::Ident(name) => format!("'{}'", name),
+            Token::Quoted(name) => format!("`{}`", name),
+            Token::Compare(op) => format!("'{}'", op.symbol()),
+            Token::Symbol(c) => format!("'{}'", c),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, InsightoraError> {
+    let error = |message: String| InsightoraError::ValidationError(format!("Invalid expression '{}': {}", source, message));
+    let chars: Vec<(usize, char)> = source.char_indices().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (position, c) = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|(_, d)| d.is_ascii_digit())) {
+            let start = i;
+            while i < chars.len() && (chars[i].1.is_ascii_digit() || chars[i].1 == '.') {
+                i += 1;
+            }
+            // Exponent: 1e6, 2.5E-3
+            if i < chars.len() && matches!(chars[i].1, 'e' | 'E') {
+                let sign = usize::from(chars.get(i + 1).is_some_and(|(_, s)| matches!(s, '+' | '-')));
+                if chars.get(i + 1 + sign).is_some_and(|(_, d)| d.is_ascii_digit()) {
+                    i += 1 + sign;
+                    while i < chars.len() && chars[i].1.is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let text: String = chars[start..i].iter().map(|(_, c)| c).collect();
+            let token = match text.parse::<i64>() {
+                Ok(value) => Token::Int(value),
+                Err(_) => Token::Float(
+                    text.parse()
+                        .map_err(|_| error(format!("invalid number '{}' at position {}", text, position)))?,
+                ),
+            };
+            tokens.push((token, position));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].1.is_alphanumeric() || chars[i].1 == '_') {
+                i += 1;
+            }
+            tokens.push((Token::Ident(chars[start..i].iter().map(|(_, c)| c).collect()), position));
+        } else if c == '`' {
+            let start = i + 1;
+            i = start;
+            while i < chars.len() && chars[i].1 != '`' {
+                i += 1;
+            }
+            if i == chars.len() {
+                return Err(error(format!("unterminated ` at position {}", position)));
+            }
+            tokens.push((Token::Quoted(chars[start..i].iter().map(|(_, c)| c).collect()), position));
+            i += 1;
+        } else if c == '\'' {
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err(error(format!("unterminated ' at position {}", position))),
+                    Some((_, '\'')) if chars.get(i + 1).is_some_and(|(_, next)| *next == '\'') => {
+                        text.push('\'');
+                        i += 2;
+                    }
+                    Some((_, '\'')) => break,
+                    Some((_, other)) => {
+                        text.push(*other);
+                        i += 1;
+                    }
+                }
+            }
+            tokens.push((Token::Text(text), position));
+            i += 1;
+        } else if "=!<>".contains(c) {
+            let equals = chars.get(i + 1).is_some_and(|(_, next)| *next == '=');
+            let op = match (c, equals) {
+                ('=', true) => CompareOp::Eq,
+                ('!', true) => CompareOp::NotEq,
+                ('<', true) => CompareOp::LtEq,
+                ('>', true) => CompareOp::GtEq,
+                ('<', false) => CompareOp::Lt,
+                ('>', false) => CompareOp::Gt,
+                _ => return Err(error(format!("unexpected '{}' at position {}; comparisons use == and !=", c, position))),
+            };
+            tokens.push((Token::Compare(op), position));
+            i += 1 + usize::from(equals);
+        } else if "+-*/%(),[]".contains(c) {
+            tokens.push((Token::Symbol(c), position));
+            i += 1;
+        } else {
+            return Err(error(format!("unexpected '{}' at position {}", c, position)));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<(Token, usize)>,
+    next: usize,
+    references: Vec<(String, usize)>,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str, position: usize) -> InsightoraError {
+        InsightoraError::ValidationError(format!(
+            "Invalid expression '{}': {} at position {}",
+            self.source, message, position
+        ))
+    }
+
+    fn peek_symbol(&self) -> Option<char> {
+        match self.tokens.get(self.next) {
+            Some((Token::Symbol(c), _)) => Some(*c),
+            _ => None,
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: char) -> Result<(), InsightoraError> {
+        match self.tokens.get(self.next) {
+            Some((Token::Symbol(c), _)) if *c == symbol => {
+                self.next += 1;
+                Ok(())
+            }
+            Some((token, position)) => Err(self.error(&format!("expected '{}', found {}", symbol, token.describe()), *position)),
+            None => Err(self.error(&format!("expected '{}'", symbol), self.source.len())),
+        }
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.tokens.get(self.next), Some((Token::Ident(name), _)) if name.eq_ignore_ascii_case(keyword))
+    }
+
+    fn expression(&mut self) -> Result<Node, InsightoraError> {
+        let mut node = self.conjunction()?;
+        while self.peek_keyword("or") {
+            self.next += 1;
+            node = Node::Or(Box::new(node), Box::new(self.conjunction()?));
+        }
+        Ok(node)
+    }
+
+    fn conjunction(&mut self) -> Result<Node, InsightoraError> {
+        let mut node = self.negation()?;
+        while self.peek_keyword("and") {
+            self.next += 1;
+            node = Node::And(Box::new(node), Box::new(self.negation()?));
+        }
+        Ok(node)
+    }
+
+    fn negation(&mut self) -> Result<Node, InsightoraError> {
+        if self.peek_keyword("not") {
+            self.next += 1;
+            return Ok(Node::Not(Box::new(self.negation()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Node, InsightoraError> {
+        let node = self.sum()?;
+        if let Some((Token::Compare(op), _)) = self.tokens.get(self.next) {
+            let op = *op;
+            self.next += 1;
+            return Ok(Node::Compare(op, Box::new(node), Box::new(self.sum()?)));
+        }
+        let negated = self.peek_keyword("not");
+        self.next += usize::from(negated);
+        let node = if self.peek_keyword("in") {
+            self.next += 1;
+            Node::In(Box::new(node), self.list()?)
+        } else if negated {
+            // `not` after an operand only comes before `in`
+            return Err(self.error("expected 'in' after 'not'", self.position()));
+        } else if self.peek_keyword("is") {
+            self.next += 1;
+            let negated = self.peek_keyword("not");
+            self.next += usize::from(negated);
+            if !self.peek_keyword("null") {
+                return Err(self.error("expected 'null' after 'is'", self.position()));
+            }
+            self.next += 1;
+            let test = Node::IsNull(Box::new(node));
+            return Ok(if negated { Node::Not(Box::new(test)) } else { test });
+        } else if self.peek_keyword("contains") {
+            self.next += 1;
+            match self.tokens.get(self.next).cloned() {
+                Some((Token::Text(text), _)) => {
+                    self.next += 1;
+                    Node::Contains(Box::new(node), text)
+                }
+                _ => return Err(self.error("expected a 'text' literal after 'contains'", self.position())),
+            }
+        } else {
+            return Ok(node);
+        };
+        Ok(if negated { Node::Not(Box::new(node)) } else { node })
+    }
+
+    /// `[a, b, ...]` after `in`
+    fn list(&mut self) -> Result<Vec<Node>, InsightoraError> {
+        self.expect_symbol('[')?;
+        let mut items = Vec::new();
+        while self.peek_symbol() != Some(']') {
+            if !items.is_empty() {
+                self.expect_symbol(',')?;
+            }
+            items.push(self.sum()?);
+        }
+        self.next += 1;
+        Ok(items)
+    }
+
+    /// Position of the next token, or the end of the source
+    fn position(&self) -> usize {
+        self.tokens.get(self.next).map_or(self.source.len(), |(_, position)| *position)
+    }
+
+    fn sum(&mut self) -> Result<Node, InsightoraError> {
+        let mut node = self.term()?;
+        while let Some(c @ ('+' | '-')) = self.peek_symbol() {
+            self.next += 1;
+            let op = if c == '+' { BinaryOp::Add } else { BinaryOp::Sub };
+            node = Node::Binary(op, Box::new(node), Box::new(self.term()?));
+        }
+        Ok(node)
+    }
+
+    fn term(&mut self) -> Result<Node, InsightoraError> {
+        let mut node = self.unary()?;
+        while let Some(c @ ('*' | '/' | '%')) = self.peek_symbol() {
+            self.next += 1;
+            let op = match c {
+                '*' => BinaryOp::Mul,
+                '/' => BinaryOp::Div,
+                _ => BinaryOp::Rem,
+            };
+            node = Node::Binary(op, Box::new(node), Box::new(self.unary()?));
+        }
+        Ok(node)
+    }
+
+    fn unary(&mut self) -> Result<Node, InsightoraError> {
+        if self.peek_symbol() == Some('-') {
+            self.next += 1;
+            return Ok(match self.unary()? {
+                Node::Int(value) => Node::Int(-value),
+                Node::Float(value) => Node::Float(-value),
+                other => Node::Negate(Box::new(other)),
+            });
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Node, InsightoraError> {
+        let Some((token, position)) = self.tokens.get(self.next).cloned() else {
+            return Err(self.error("unexpected end", self.source.len()));
+        };
+        self.next += 1;
+        match token {
+            Token::Int(value) => Ok(Node::Int(value)),
+            Token::Float(value) => Ok(Node::Float(value)),
+            Token::Text(value) => Ok(Node::Text(value)),
+            Token::Quoted(name) => {
+                self.references.push((name.clone(), position));
+                Ok(Node::Column(name))
+            }
+            Token::Symbol('(') => {
+                let node = self.expression()?;
+                self.expect_symbol(')')?;
+                Ok(node)
+            }
+            Token::Ident(name) if self.peek_symbol() == Some('(') => self.function(&name, position),
+            Token::Ident(name) => match name.to_ascii_lowercase().as_str() {
+                "true" => Ok(Node::Bool(true)),
+                "false" => Ok(Node::Bool(false)),
+                "null" => Ok(Node::Null),
+                "and" | "or" | "not" => Err(self.error(&format!("unexpected '{}'", name), position)),
+                _ => {
+                    self.references.push((name.clone(), position));
+                    Ok(Node::Column(name))
+                }
+            },
+            other => Err(self.error(&format!("unexpected {}", other.describe()), position)),
+        }
+    }
+
+    fn function(&mut self, name: &str, position: usize) -> Result<Node, InsightoraError> {
+        self.expect_symbol('(')?;
+        let mut args = vec![self.expression()?];
+        while self.peek_symbol() == Some(',') {
+            self.next += 1;
+            args.push(self.expression()?);
+        }
+        self.expect_symbol(')')?;
+
+        let arity = |expected: usize| {
+            if args.len() == expected {
+                Ok(())
+            } else {
+                Err(self.error(&format!("{}() takes {} arguments, got {}", name, expected, args.len()), position))
+            }
+        };
+        match name.to_ascii_lowercase().as_str() {
+            "coalesce" => Ok(Node::Coalesce(args)),
+            "nullif" => {
+                arity(2)?;
+                let mut args = args.into_iter();
+                Ok(Node::NullIf(Box::new(args.next().unwrap()), Box::new(args.next().unwrap())))
+            }
+            "fill_null" => {
+                arity(2)?;
+                let mut args = args.into_iter();
+                Ok(Node::FillNull(Box::new(args.next().unwrap()), Box::new(args.next().unwrap())))
+            }
+            "is_null" => {
+                arity(1)?;
+                Ok(Node::IsNull(Box::new(args.into_iter().next().unwrap())))
+            }
+            _ => Err(self.error(
+                &format!("unknown function '{}'; expected coalesce, nullif, fill_null or is_null", name),
+                position,
+            )),
+        }
+    }
+}
+
+// ============================================================================
+// Evaluation
+// ============================================================================
+
+/// Add derived columns, evaluated in order
+///
+/// Each expression may use the columns added before it. A name that already
+/// exists replaces that column in place; new columns are appended.
+///
+/// # Arguments
+/// * `df` - Input frame
+/// * `expressions` - Column definitions, see the grammar above
+/// * `nulls` - Treatment of null arithmetic operands
+///
+/// # Returns
+/// * `Result<DataFrame>` - ValidationError for unknown columns or, under
+///   `NullArithmetic::Error`, the first null operand
+pub fn add_columns(
+    df: &DataFrame,
+    expressions: &[ColumnExpression],
+    nulls: NullArithmetic,
+) -> Result<DataFrame, InsightoraError> {
+    let mut result = df.clone();
+    for expression in expressions {
+        if let Some(missing) = expression.columns().into_iter().find(|name| result.column(name).is_err()) {
+            return Err(InsightoraError::ValidationError(format!(
+                "Expression '{} = {}' uses unknown column '{}'",
+                expression.name, expression.source, missing
+            )));
+        }
+        result = expression.compile(nulls).apply(result.lazy())?.collect()?;
+    }
+    Ok(result)
+}
+
+impl ColumnExpression {
+    /// Columns the expression reads, in order of appearance
+    pub fn columns(&self) -> Vec<&str> {
+        let mut found = Vec::new();
+        collect_columns(&self.root, &mut found);
+        found
+    }
+
+    /// Polars expression for the value, without the column name
+    pub(crate) fn expr(&self) -> Expr {
+        self.root.to_expr(NullArithmetic::Propagate, None)
+    }
+
+    /// Build the polars expressions once, for repeated evaluation
+    pub(crate) fn compile(&self, nulls: NullArithmetic) -> CompiledExpression {
+        let mut operands = Vec::new();
+        if nulls == NullArithmetic::Error {
+            self.root.operands(&mut operands);
+        }
+        CompiledExpression {
+            label: format!("{} = {}", self.name, self.source),
+            expr: self.root.to_expr(nulls, None).alias(&self.name),
+            checks: operands
+                .iter()
+                .enumerate()
+                .map(|(i, node)| node.to_expr(NullArithmetic::Propagate, None).is_null().alias(&format!("operand_{}", i)))
+                .collect(),
+            check_columns: operands
+                .iter()
+                .map(|node| (node.first_column().unwrap_or("?").to_string(), matches!(node, Node::Column(_))))
+                .collect(),
+        }
+    }
+}
+
+/// A column definition with its polars expressions already built
+#[derive(Clone)]
+pub(crate) struct CompiledExpression {
+    label: String,
+    expr: Expr,
+    /// Null masks of the arithmetic operands (`NullArithmetic::Error` only)
+    checks: Vec<Expr>,
+    /// Column blamed for each operand's nulls, and whether the operand is that column
+    check_columns: Vec<(String, bool)>,
+}
+
+impl CompiledExpression {
+    /// Add the column to `frame`; under `NullArithmetic::Error` the frame is
+    /// materialized first to look for null operands
+    pub(crate) fn apply(&self, frame: LazyFrame) -> Result<LazyFrame, InsightoraError> {
+        if self.checks.is_empty() {
+            return Ok(frame.with_column(self.expr.clone()));
+        }
+        let df = frame.collect()?;
+        self.check_null_operands(&df)?;
+        Ok(df.lazy().with_column(self.expr.clone()))
+    }
+
+    /// Fail on the earliest row where any arithmetic operand is null
+    fn check_null_operands(&self, df: &DataFrame) -> Result<(), InsightoraError> {
+        let masks = df.clone().lazy().select(self.checks.clone()).collect()?;
+
+        // Earliest row wins; on the same row a column operand is more precise
+        // than a sub-expression that inherited its null
+        let mut first: Option<(usize, &(String, bool))> = None;
+        for (mask, operand) in masks.get_columns().iter().zip(&self.check_columns) {
+            if let Some(row) = mask.bool()?.into_iter().position(|v| v == Some(true)) {
+                let better = match first {
+                    None => true,
+                    Some((earliest, current)) => row < earliest || (row == earliest && operand.1 && !current.1),
+                };
+                if better {
+                    first = Some((row, operand));
+                }
+            }
+        }
+        match first {
+            None => Ok(()),
+            Some((row, (column, _))) => Err(InsightoraError::ValidationError(format!(
+                "Expression '{}' has a null operand in column '{}' at row {}; use null_arithmetic='propagate' or 'zero', or coalesce/fill_null",
+                self.label, column, row
+            ))),
+        }
+    }
+}
+
+/// A comparison of an ordered categorical column with a text literal: the
+/// column, the literal's category position (None when it isn't a category)
+/// and the operator with the column on the left
+fn ordered_comparison<'a>(
+    op: CompareOp,
+    left: &'a Node,
+    right: &'a Node,
+    schema: Option<&Schema>,
+) -> Option<(&'a str, Option<u32>, CompareOp)> {
+    let (column, text, op) = match (left, right) {
+        (Node::Column(column), Node::Text(text)) => (column, text, op),
+        (Node::Text(text), Node::Column(column)) => (column, text, op.flipped()),
+        _ => return None,
+    };
+    match schema?.get(column)? {
+        DataType::Categorical(Some(rev_map), CategoricalOrdering::Physical) => {
+            let position = (0..rev_map.len() as u32).find(|&code| rev_map.get(code) == text.as_str());
+            Some((column, position, op))
+        }
+        _ => None,
+    }
+}
+
+/// First comparison of an ordered categorical column with text that isn't
+/// one of its categories, as (column, text)
+fn unknown_category<'a>(node: &'a Node, schema: &Schema) -> Option<(&'a str, &'a str)> {
+    match node {
+        Node::Compare(op, left, right) => match ordered_comparison(*op, left, right, Some(schema)) {
+            Some((column, None, _)) => match (left.as_ref(), right.as_ref()) {
+                (Node::Text(text), _) | (_, Node::Text(text)) => Some((column, text)),
+                _ => None,
+            },
+            _ => unknown_category(left, schema).or_else(|| unknown_category(right, schema)),
+        },
+        Node::Negate(inner) | Node::Not(inner) | Node::IsNull(inner) | Node::Contains(inner, _) => unknown_category(inner, schema),
+        Node::Binary(_, a, b) | Node::And(a, b) | Node::Or(a, b) | Node::NullIf(a, b) | Node::FillNull(a, b) => {
+            unknown_category(a, schema).or_else(|| unknown_category(b, schema))
+        }
+        Node::Coalesce(args) => args.iter().find_map(|arg| unknown_category(arg, schema)),
+        Node::In(value, list) => unknown_category(value, schema)
+            .or_else(|| list.iter().find_map(|item| unknown_category(item, schema))),
+        Node::Int(_) | Node::Float(_) | Node::Text(_) | Node::Bool(_) | Node::Null | Node::Column(_) => None,
+    }
+}
+
+fn collect_columns<'a>(node: &'a Node, found: &mut Vec<&'a str>) {
+    match node {
+        Node::Column(name) => found.push(name),
+        Node::Negate(inner) | Node::Not(inner) | Node::IsNull(inner) | Node::Contains(inner, _) => collect_columns(inner, found),
+        Node::Binary(_, a, b)
+        | Node::Compare(_, a, b)
+        | Node::And(a, b)
+        | Node::Or(a, b)
+        | Node::NullIf(a, b)
+        | Node::FillNull(a, b) => {
+            collect_columns(a, found);
+            collect_columns(b, found);
+        }
+        Node::Coalesce(args) => args.iter().for_each(|arg| collect_columns(arg, found)),
+        Node::In(value, list) => {
+            collect_columns(value, found);
+            list.iter().for_each(|item| collect_columns(item, found));
+        }
+        Node::Int(_) | Node::Float(_) | Node::Text(_) | Node::Bool(_) | Node::Null => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> DataFrame {
+        df!(
+            "price" => [Some(2.5), None, Some(4.0), Some(1.0)],
+            "quantity" => [Some(2i64), Some(3), None, Some(0)],
+            "discount" => [None, Some(1.0), None, Some(0.5)],
+        )
+        .unwrap()
+    }
+
+    fn run(source: &str, nulls: NullArithmetic) -> Result<Vec<Option<f64>>, InsightoraError> {
+        let expression = ColumnExpression::parse("total", source)?;
+        let df = add_columns(&fixture(), &[expression], nulls)?;
+        let total = df.column("total").unwrap().cast(&DataType::Float64).unwrap();
+        let values = total.f64().unwrap().into_iter().collect();
+        Ok(values)
+    }
+
+    #[test]
+    fn test_null_arithmetic_modes() {
+        let source = "price * quantity - discount";
+        assert_eq!(run(source, NullArithmetic::Propagate).unwrap(), vec![None, None, None, Some(-0.5)]);
+        assert_eq!(
+            run(source, NullArithmetic::Zero).unwrap(),
+            vec![Some(5.0), Some(-1.0), Some(0.0), Some(-0.5)]
+        );
+        let err = run(source, NullArithmetic::Error).unwrap_err().to_string();
+        assert!(
+            err.contains("Expression 'total = price * quantity - discount' has a null operand in column 'discount' at row 0"),
+            "{}",
+            err
+        );
+        let err = run("price * quantity", NullArithmetic::Error).unwrap_err().to_string();
+        assert!(err.contains("column 'price' at row 1"), "{}", err);
+    }
+
+    #[test]
+    fn test_null_functions_are_explicit_in_every_mode() {
+        let source = "coalesce(price, 10) * fill_null(quantity, 1) - fill_null(discount, 0)";
+        for nulls in [NullArithmetic::Propagate, NullArithmetic::Zero, NullArithmetic::Error] {
+            assert_eq!(run(source, nulls).unwrap(), vec![Some(5.0), Some(29.0), Some(4.0), Some(-0.5)]);
+        }
+        // nullif turns the zero quantity into a null, so the division is null
+        assert_eq!(
+            run("price / nullif(quantity, 0)", NullArithmetic::Propagate).unwrap(),
+            vec![Some(1.25), None, None, None]
+        );
+        assert_eq!(run("coalesce(discount, price)", NullArithmetic::Zero).unwrap(), vec![Some(2.5), Some(1.0), Some(4.0), Some(0.5)]);
+    }
+
+    #[test]
+    fn test_grammar() {
+        assert_eq!(run("-(1 + 2) * 3 / 4 - -1.5e1", NullArithmetic::Propagate).unwrap()[0], Some(12.75));
+        assert_eq!(run("(quantity + 5) % 3", NullArithmetic::Propagate).unwrap()[0], Some(1.0));
+        let df = add_columns(
+            &fixture(),
+            &[
+                ColumnExpression::parse("unit price", "price / 2").unwrap(),
+                ColumnExpression::parse("double", "`unit price` * 4").unwrap(),
+            ],
+            NullArithmetic::Propagate,
+        )
+        .unwrap();
+        assert_eq!(df.column("double").unwrap().f64().unwrap().get(0), Some(5.0));
+
+        let err = |source: &str| ColumnExpression::parse("x", source).unwrap_err().to_string();
+        assert!(err("price * (quantity").contains("expected ')' at position 17"));
+        assert!(err("price quantity").contains("unexpected 'quantity' at position 6"));
+        assert!(err("nullif(price)").contains("nullif() takes 2 arguments, got 1"));
+        assert!(err("sqrt(price)").contains("unknown function 'sqrt'"));
+        assert!(run("cost * 2", NullArithmetic::Propagate).unwrap_err().to_string().contains("unknown column 'cost'"));
+    }
+
+    #[test]
+    fn test_conditions_use_three_valued_logic() {
+        let matches = |source: &str| -> Vec<Option<bool>> {
+            let predicate = Predicate::parse(source).unwrap();
+            let mask = fixture().lazy().select([predicate.expr(&fixture().schema())]).collect().unwrap();
+            mask.get_columns()[0].bool().unwrap().into_iter().collect()
+        };
+        assert_eq!(
+            matches("price >= 2.5 and not is_null(quantity)"),
+            vec![Some(true), None, Some(false), Some(false)]
+        );
+        assert_eq!(matches("discount == 1 or price < 2"), vec![None, Some(true), None, Some(true)]);
+
+        let root = |source: &str| Predicate::parse(source).unwrap().root().clone();
+        assert_eq!(
+            root("name != 'O''Brien'"),
+            Node::Compare(CompareOp::NotEq, Box::new(Node::Column("name".into())), Box::new(Node::Text("O'Brien".into())))
+        );
+        assert_eq!(
+            root("`and` == TRUE"),
+            Node::Compare(CompareOp::Eq, Box::new(Node::Column("and".into())), Box::new(Node::Bool(true)))
+        );
+        let err = |source: &str| Predicate::parse(source).unwrap_err().to_string();
+        assert!(err("price = 2").contains("comparisons use == and !="), "{}", err("price = 2"));
+        assert!(err("price > 1 and").contains("unexpected end"));
+    }
+
+    #[test]
+    fn test_membership_null_and_text_tests() {
+        let df = df!(
+            "country" => [Some("DE"), Some("FR"), None, Some("AT")],
+            "city" => [Some("Berlin"), Some("Paris"), Some("Bern"), None],
+            "amount" => [150i64, 80, 120, 300],
+        )
+        .unwrap();
+        let matches = |source: &str| -> Vec<Option<bool>> {
+            let predicate = Predicate::parse(source).unwrap();
+            predicate.check(&df.schema()).unwrap();
+            let mask = df.clone().lazy().select([predicate.expr(&df.schema())]).collect().unwrap();
+            mask.get_columns()[0].bool().unwrap().into_iter().collect()
+        };
+        assert_eq!(matches("country in ['DE', 'AT'] and amount > 100"), vec![Some(true), Some(false), None, Some(true)]);
+        assert_eq!(matches("country not in ['DE']"), vec![Some(false), Some(true), None, Some(true)]);
+        assert_eq!(matches("country is null or city is not null"), vec![Some(true), Some(true), Some(true), Some(false)]);
+        assert_eq!(matches("city contains 'Ber' and not amount in []"), vec![Some(true), Some(false), Some(true), None]);
+        assert_eq!(matches("amount contains '50'"), vec![Some(true), Some(false), Some(false), Some(false)]);
+
+        let err = |source: &str| {
+            let predicate = Predicate::parse(source)?;
+            predicate.check(&df.schema())
+        };
+        let message = |source: &str| err(source).unwrap_err().to_string();
+        assert!(message("contry == 'DE'").contains("unknown column 'contry' at position 0"), "{}", message("contry == 'DE'"));
+        assert!(message("amount > 1 and `Country` in ['DE']").contains("unknown column 'Country' at position 15"));
+        assert!(message("city contains Ber").contains("expected a 'text' literal after 'contains' at position 14"));
+        assert!(message("country is 'DE'").contains("expected 'null' after 'is' at position 11"));
+        assert!(message("country in ['DE' 'AT']").contains("expected ',', found text 'AT' at position 17"));
+        assert!(message("amount + 1").contains("is not a condition"));
+    }
+}
@@ -1,6 +1,9 @@
 // DataFrame operations module
-// Provides parallel filter, join, groupby, sort operations
+// Provides parallel filter, join, groupby, sort operations, duration handling, column expressions
+// and prepared pipelines
 
 pub mod operations;
 pub mod aggregations;
 pub mod transformations;
+pub mod expressions;
+pub mod pipeline;
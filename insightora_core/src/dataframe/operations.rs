@@ -1,2 +1,2629 @@
-// DataFrame operations (filter, join, sort)
-// TODO: Implement in tasks 8-11
+// Parallel DataFrame operations
+// Filter, join, groupby and sort operations built on Polars and Rayon
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
+use rayon::prelude::*;
+use polars::prelude::*;
+use regex::Regex;
+use crate::config::get_current_config;
+use crate::utils::memory;
+use crate::utils::scratch::ScratchDir;
+use crate::error::InsightoraError;
+use crate::dataframe::transformations::category_ranks;
+use crate::dataframe::expressions::Predicate;
+use crate::io::remote::{align_and_concat, SchemaAlignment};
+
+// ============================================================================
+// Collation
+// ============================================================================
+
+/// String ordering used when sorting text columns
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Collation {
+    /// Byte-wise (code point) ordering
+    #[default]
+    Binary,
+    /// Digit runs compare numerically, so "item2" sorts before "item10"
+    Natural,
+    /// Language-aware ordering for the given locale
+    Locale(LocaleCollator),
+}
+
+impl Collation {
+    /// Build a collation from the option strings used by the Python bindings
+    ///
+    /// # Arguments
+    /// * `name` - One of "binary", "natural" or "locale"
+    /// * `locale` - Locale identifier such as "de_DE" (required for "locale")
+    pub fn from_options(name: &str, locale: Option<&str>) -> Result<Self, InsightoraError> {
+        match name {
+            "binary" => Ok(Collation::Binary),
+            "natural" => Ok(Collation::Natural),
+            "locale" => {
+                let locale = locale.ok_or_else(|| InsightoraError::ValidationError(
+                    "collation='locale' requires a locale identifier (e.g. 'de_DE')".to_string()
+                ))?;
+                Ok(Collation::Locale(LocaleCollator::new(locale)?))
+            }
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown collation '{}': expected 'binary', 'natural' or 'locale'",
+                other
+            ))),
+        }
+    }
+
+    /// Compare two strings under this collation
+    #[inline]
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        match self {
+            Collation::Binary => a.cmp(b),
+            Collation::Natural => natural_cmp(a, b),
+            Collation::Locale(collator) => collator.compare(a, b),
+        }
+    }
+}
+
+/// Compare two strings treating runs of ASCII digits as numbers
+///
+/// Numbers compare by value regardless of width ("2" < "10"); when two runs
+/// have the same value the one with fewer leading zeros sorts first so the
+/// ordering stays total. Works directly on the byte slices without allocating.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let a = a.as_bytes();
+    let b = b.as_bytes();
+    let (mut i, mut j) = (0, 0);
+
+    while i < a.len() && j < b.len() {
+        if a[i].is_ascii_digit() && b[j].is_ascii_digit() {
+            let (a_start, b_start) = (i, j);
+            while i < a.len() && a[i].is_ascii_digit() {
+                i += 1;
+            }
+            while j < b.len() && b[j].is_ascii_digit() {
+                j += 1;
+            }
+
+            let a_run = &a[a_start..i];
+            let b_run = &b[b_start..j];
+            let a_digits = trim_leading_zeros(a_run);
+            let b_digits = trim_leading_zeros(b_run);
+
+            let ord = a_digits.len().cmp(&b_digits.len())
+                .then_with(|| a_digits.cmp(b_digits))
+                .then_with(|| a_run.len().cmp(&b_run.len()));
+            if ord != Ordering::Equal {
+                return ord;
+            }
+        } else {
+            if a[i] != b[j] {
+                return a[i].cmp(&b[j]);
+            }
+            i += 1;
+            j += 1;
+        }
+    }
+
+    (a.len() - i).cmp(&(b.len() - j))
+}
+
+fn trim_leading_zeros(digits: &[u8]) -> &[u8] {
+    let first_non_zero = digits.iter().position(|&d| d != b'0').unwrap_or(digits.len());
+    &digits[first_non_zero..]
+}
+
+/// Letter placement rules that differ between locales
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tailoring {
+    /// Accented letters sort with their base letter (English, German DIN 5007-1)
+    Root,
+    /// å, ä, ö are separate letters after z (Swedish, Finnish)
+    Swedish,
+    /// æ, ø, å are separate letters after z (Danish, Norwegian)
+    Danish,
+}
+
+/// Locale-aware string comparator
+///
+/// Implements a three-level comparison in the spirit of the Unicode Collation
+/// Algorithm: base letters first, then accents, then case, with a final
+/// byte-wise tie break so the ordering is total. Covers Latin-script locales.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocaleCollator {
+    locale: String,
+    tailoring: Tailoring,
+}
+
+/// Locales accepted by `LocaleCollator::new` (language part of the identifier)
+const SUPPORTED_LOCALES: &[&str] = &["en", "de", "fr", "es", "it", "nl", "pt", "sv", "fi", "da", "nb", "nn", "no"];
+
+impl LocaleCollator {
+    /// Create a collator for a locale identifier such as "de_DE" or "sv-SE"
+    pub fn new(locale: &str) -> Result<Self, InsightoraError> {
+        let language = locale
+            .split(['_', '-', '.'])
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+
+        let tailoring = match language.as_str() {
+            "sv" | "fi" => Tailoring::Swedish,
+            "da" | "nb" | "nn" | "no" => Tailoring::Danish,
+            lang if SUPPORTED_LOCALES.contains(&lang) => Tailoring::Root,
+            _ => {
+                return Err(InsightoraError::ValidationError(format!(
+                    "Unsupported locale '{}': supported languages are {}",
+                    locale,
+                    SUPPORTED_LOCALES.join(", ")
+                )))
+            }
+        };
+
+        Ok(Self {
+            locale: locale.to_string(),
+            tailoring,
+        })
+    }
+
+    /// Locale identifier this collator was created with
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Compare two strings: base letters, then accents, then case, then bytes
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        let primary = PrimaryWeights::new(a, self.tailoring)
+            .cmp(PrimaryWeights::new(b, self.tailoring));
+        if primary != Ordering::Equal {
+            return primary;
+        }
+
+        let secondary = a.chars().map(|c| collation_element(c, self.tailoring).secondary)
+            .cmp(b.chars().map(|c| collation_element(c, self.tailoring).secondary));
+        if secondary != Ordering::Equal {
+            return secondary;
+        }
+
+        let tertiary = a.chars().map(|c| c.is_uppercase())
+            .cmp(b.chars().map(|c| c.is_uppercase()));
+        if tertiary != Ordering::Equal {
+            return tertiary;
+        }
+
+        a.cmp(b)
+    }
+}
+
+/// Collation weights for a single character
+#[derive(Debug, Clone, Copy)]
+struct CollationElement {
+    /// Up to two primary weights (ligatures such as ß expand to "ss")
+    primary: [u32; 2],
+    primary_len: u8,
+    /// Accent weight (0 = unaccented)
+    secondary: u8,
+}
+
+// Weight layout: punctuation < digits < letters a-z < tailored letters < other
+const WEIGHT_DIGIT: u32 = 200;
+const WEIGHT_LETTER: u32 = 300;
+const WEIGHT_AFTER_Z: u32 = WEIGHT_LETTER + 26 * 4;
+const WEIGHT_OTHER: u32 = 10_000;
+
+#[inline]
+fn letter(c: u8) -> u32 {
+    WEIGHT_LETTER + (c - b'a') as u32 * 4
+}
+
+#[inline]
+fn single(primary: u32, secondary: u8) -> CollationElement {
+    CollationElement { primary: [primary, 0], primary_len: 1, secondary }
+}
+
+#[inline]
+fn pair(first: u8, second: u8, secondary: u8) -> CollationElement {
+    CollationElement { primary: [letter(first), letter(second)], primary_len: 2, secondary }
+}
+
+fn collation_element(c: char, tailoring: Tailoring) -> CollationElement {
+    let lower = if c.is_uppercase() {
+        c.to_lowercase().next().unwrap_or(c)
+    } else {
+        c
+    };
+
+    // Locale-specific letters placed after z
+    match (tailoring, lower) {
+        (Tailoring::Swedish, 'å') => return single(WEIGHT_AFTER_Z + 1, 0),
+        (Tailoring::Swedish, 'ä') => return single(WEIGHT_AFTER_Z + 2, 0),
+        (Tailoring::Swedish, 'æ') => return single(WEIGHT_AFTER_Z + 2, 1),
+        (Tailoring::Swedish, 'ö') => return single(WEIGHT_AFTER_Z + 3, 0),
+        (Tailoring::Swedish, 'ø') => return single(WEIGHT_AFTER_Z + 3, 1),
+        (Tailoring::Swedish, 'ü') => return single(letter(b'y'), 5),
+        (Tailoring::Danish, 'æ') => return single(WEIGHT_AFTER_Z + 1, 0),
+        (Tailoring::Danish, 'ä') => return single(WEIGHT_AFTER_Z + 1, 1),
+        (Tailoring::Danish, 'ø') => return single(WEIGHT_AFTER_Z + 2, 0),
+        (Tailoring::Danish, 'ö') => return single(WEIGHT_AFTER_Z + 2, 1),
+        (Tailoring::Danish, 'å') => return single(WEIGHT_AFTER_Z + 3, 0),
+        _ => {}
+    }
+
+    match lower {
+        'a'..='z' => single(letter(lower as u8), 0),
+        '0'..='9' => single(WEIGHT_DIGIT + (lower as u32 - '0' as u32), 0),
+        'à' => single(letter(b'a'), 1),
+        'á' => single(letter(b'a'), 2),
+        'â' => single(letter(b'a'), 3),
+        'ã' => single(letter(b'a'), 4),
+        'ä' => single(letter(b'a'), 5),
+        'å' => single(letter(b'a'), 6),
+        'ç' => single(letter(b'c'), 8),
+        'è' => single(letter(b'e'), 1),
+        'é' => single(letter(b'e'), 2),
+        'ê' => single(letter(b'e'), 3),
+        'ë' => single(letter(b'e'), 5),
+        'ì' => single(letter(b'i'), 1),
+        'í' => single(letter(b'i'), 2),
+        'î' => single(letter(b'i'), 3),
+        'ï' => single(letter(b'i'), 5),
+        'ñ' => single(letter(b'n'), 4),
+        'ò' => single(letter(b'o'), 1),
+        'ó' => single(letter(b'o'), 2),
+        'ô' => single(letter(b'o'), 3),
+        'õ' => single(letter(b'o'), 4),
+        'ö' => single(letter(b'o'), 5),
+        'ø' => single(letter(b'o'), 9),
+        'ù' => single(letter(b'u'), 1),
+        'ú' => single(letter(b'u'), 2),
+        'û' => single(letter(b'u'), 3),
+        'ü' => single(letter(b'u'), 5),
+        'ý' => single(letter(b'y'), 2),
+        'ÿ' => single(letter(b'y'), 5),
+        'ß' => pair(b's', b's', 10),
+        'æ' => pair(b'a', b'e', 10),
+        'œ' => pair(b'o', b'e', 10),
+        _ if (lower as u32) < 128 => single(1 + lower as u32, 0),
+        _ => single(WEIGHT_OTHER + lower as u32, 0),
+    }
+}
+
+/// Lazily yields primary weights for a string without allocating
+struct PrimaryWeights<'a> {
+    chars: std::str::Chars<'a>,
+    tailoring: Tailoring,
+    pending: Option<u32>,
+}
+
+impl<'a> PrimaryWeights<'a> {
+    fn new(s: &'a str, tailoring: Tailoring) -> Self {
+        Self { chars: s.chars(), tailoring, pending: None }
+    }
+}
+
+impl Iterator for PrimaryWeights<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        if let Some(weight) = self.pending.take() {
+            return Some(weight);
+        }
+        let element = collation_element(self.chars.next()?, self.tailoring);
+        if element.primary_len == 2 {
+            self.pending = Some(element.primary[1]);
+        }
+        Some(element.primary[0])
+    }
+}
+
+// ============================================================================
+// Filtering
+// ============================================================================
+
+/// Keep the rows where a condition is true
+///
+/// The condition uses the grammar of the parse-time `row_filter` (see
+/// `expressions`). A comparison with null is null and a row whose condition
+/// is null is dropped, as with SQL `WHERE`; `and` and `or` follow
+/// three-valued logic, so `false and x` drops the row and `true or x` keeps
+/// it whatever `x` is. Columns and the condition's type are checked before
+/// any row is read, then Polars evaluates the condition on all threads.
+///
+/// # Arguments
+/// * `df` - DataFrame to filter
+/// * `predicate` - Row condition
+///
+/// # Returns
+/// * `Result<DataFrame>` - Matching rows in their original order;
+///   ValidationError for unknown columns or a predicate that isn't a condition
+///
+/// # Example
+/// ```no_run
+/// use insightora_core::api::{filter_rows, ParallelCsvParser, Predicate};
+///
+/// let df = ParallelCsvParser::new().parse("orders.csv").unwrap();
+/// let predicate = Predicate::parse("country in ['DE', 'AT'] and amount > 100").unwrap();
+/// let large = filter_rows(&df, &predicate).unwrap();
+/// println!("{} of {} orders", large.height(), df.height());
+/// ```
+pub fn filter_rows(df: &DataFrame, predicate: &Predicate) -> Result<DataFrame, InsightoraError> {
+    let schema = df.schema();
+    predicate.check(&schema)?;
+    Ok(df.clone().lazy().filter(predicate.mask(&schema)).collect()?)
+}
+
+// ============================================================================
+// Sorting
+// ============================================================================
+
+/// Per-column sort key prepared once before the parallel sort
+enum SortKey<'a> {
+    /// String column compared with the collation
+    Text(&'a StringChunked),
+    /// Any other dtype, reduced to dense ranks (None = null)
+    Ranked(Vec<Option<IdxSize>>),
+}
+
+/// Compute dense ranks for a non-string column so rows can be compared by index
+fn dense_ranks(series: &Series) -> Result<Vec<Option<IdxSize>>, InsightoraError> {
+    let order = series.arg_sort(SortOptions {
+        descending: false,
+        nulls_last: true,
+        multithreaded: true,
+        maintain_order: false,
+    });
+    let sorted = series.take(&order)?;
+    let same_as_previous = sorted.equal_missing(&sorted.shift(1))?;
+    let nulls = series.is_null();
+
+    let mut ranks = vec![None; series.len()];
+    let mut rank: IdxSize = 0;
+    for (position, row) in order.into_no_null_iter().enumerate() {
+        if position > 0 && !same_as_previous.get(position).unwrap_or(false) {
+            rank += 1;
+        }
+        if !nulls.get(row as usize).unwrap_or(false) {
+            ranks[row as usize] = Some(rank);
+        }
+    }
+
+    Ok(ranks)
+}
+
+/// Sort a DataFrame by one or more columns using the given string collation
+///
+/// String key columns are compared with the collation, ordered categoricals
+/// (see `set_category_order`) by category position, and every other dtype
+/// uses its natural order. Nulls always sort last and the sort is stable, so ties
+/// keep their original relative order.
+///
+/// # Arguments
+/// * `df` - DataFrame to sort
+/// * `by` - Key column names, in priority order
+/// * `descending` - Per-key direction (a single value applies to all keys)
+/// * `collation` - String ordering for text key columns
+///
+/// # Returns
+/// * `Result<DataFrame>` - Sorted DataFrame or error
+pub fn sort_data(
+    df: &DataFrame,
+    by: &[String],
+    descending: &[bool],
+    collation: &Collation,
+) -> Result<DataFrame, InsightoraError> {
+    let columns = sort_columns(df, by, descending)?;
+    let keys = columns
+        .iter()
+        .map(|series| match series.dtype() {
+            DataType::String => Ok(SortKey::Text(series.str()?)),
+            _ => Ok(SortKey::Ranked(dense_ranks(series)?)),
+        })
+        .collect::<Result<Vec<_>, InsightoraError>>()?;
+
+    let direction = |key: usize| -> bool {
+        match descending.len() {
+            0 => false,
+            1 => descending[0],
+            _ => descending[key],
+        }
+    };
+
+    let mut order: Vec<IdxSize> = (0..df.height() as IdxSize).collect();
+    order.par_sort_by(|&left, &right| {
+        for (position, key) in keys.iter().enumerate() {
+            let ord = match key {
+                SortKey::Text(ca) => compare_nullable(
+                    ca.get(left as usize),
+                    ca.get(right as usize),
+                    direction(position),
+                    |a, b| collation.compare(a, b),
+                ),
+                SortKey::Ranked(ranks) => compare_nullable(
+                    ranks[left as usize],
+                    ranks[right as usize],
+                    direction(position),
+                    |a, b| a.cmp(&b),
+                ),
+            };
+            if ord != Ordering::Equal {
+                return ord;
+            }
+        }
+        Ordering::Equal
+    });
+
+    let indices = IdxCa::from_vec("", order);
+    Ok(df.take(&indices)?)
+}
+
+/// Check the sort columns and directions, and return each key column ready
+/// to compare: ordered (physical) categoricals as their category positions,
+/// lexical categoricals as their string values
+fn sort_columns(df: &DataFrame, by: &[String], descending: &[bool]) -> Result<Vec<Series>, InsightoraError> {
+    if by.is_empty() {
+        return Err(InsightoraError::ValidationError(
+            "At least one sort column is required".to_string()
+        ));
+    }
+    if descending.len() > 1 && descending.len() != by.len() {
+        return Err(InsightoraError::ValidationError(format!(
+            "descending has {} entries but {} sort columns were given",
+            descending.len(),
+            by.len()
+        )));
+    }
+    by.iter()
+        .map(|name| {
+            let series = df.column(name).map_err(|_| {
+                InsightoraError::ValidationError(format!("Sort column '{}' not found", name))
+            })?;
+            match series.dtype() {
+                DataType::Categorical(_, CategoricalOrdering::Physical) => {
+                    Ok(category_ranks(series)?.into_series())
+                }
+                DataType::Categorical(..) => Ok(series.cast(&DataType::String)?),
+                _ => Ok(series.clone()),
+            }
+        })
+        .collect()
+}
+
+/// Prefix of the key columns `sort_rows` sorts by, dropped afterwards
+const SORT_KEY_PREFIX: &str = "__insightora_sort_key_";
+
+/// Sort a DataFrame by one or more columns with Polars' multithreaded sort
+///
+/// Text compares byte-wise (by code point), the same in every locale;
+/// `case_insensitive` compares lowercased text instead, so "apple" and
+/// "Apple" tie. Ordered categoricals sort by category position as in
+/// `sort_data`. The sort is stable: ties keep their original relative order.
+///
+/// # Arguments
+/// * `df` - DataFrame to sort
+/// * `by` - Key column names, in priority order
+/// * `descending` - Per-key direction (a single value applies to all keys)
+/// * `nulls_last` - Put nulls after every value of their key, whatever its
+///   direction; otherwise before
+/// * `case_insensitive` - Ignore case in text keys
+///
+/// # Returns
+/// * `Result<DataFrame>` - Sorted DataFrame; ValidationError naming a
+///   missing sort column
+///
+/// # Example
+/// ```no_run
+/// use insightora_core::api::{sort_rows, ParallelCsvParser};
+///
+/// let df = ParallelCsvParser::new().parse("orders.csv").unwrap();
+/// let by = ["country".to_string(), "amount".to_string()];
+/// let sorted = sort_rows(&df, &by, &[false, true], true, true).unwrap();
+/// ```
+pub fn sort_rows(
+    df: &DataFrame,
+    by: &[String],
+    descending: &[bool],
+    nulls_last: bool,
+    case_insensitive: bool,
+) -> Result<DataFrame, InsightoraError> {
+    let columns = sort_columns(df, by, descending)?;
+    let names = df.get_column_names_owned();
+    let mut keyed = df.clone();
+    let mut keys = Vec::with_capacity(columns.len());
+    for (position, series) in columns.into_iter().enumerate() {
+        let series = match series.dtype() {
+            DataType::String if case_insensitive => {
+                series.str()?.into_iter().map(|value| value.map(str::to_lowercase)).collect::<StringChunked>().into_series()
+            }
+            _ => series,
+        };
+        let key = format!("{}{}", SORT_KEY_PREFIX, position);
+        keyed.with_column(series.with_name(&key))?;
+        keys.push(col(&key));
+    }
+    let descending: Vec<bool> = match descending.len() {
+        0 => vec![false; by.len()],
+        1 => vec![descending[0]; by.len()],
+        _ => descending.to_vec(),
+    };
+    let sorted = keyed.lazy().sort_by_exprs(keys, descending, nulls_last, true).collect()?;
+    Ok(sorted.select(names)?)
+}
+
+/// Compare two optional values with nulls last, reversing only non-null order
+#[inline]
+fn compare_nullable<T>(
+    left: Option<T>,
+    right: Option<T>,
+    descending: bool,
+    compare: impl Fn(T, T) -> Ordering,
+) -> Ordering {
+    match (left, right) {
+        (Some(a), Some(b)) => {
+            let ord = compare(a, b);
+            if descending { ord.reverse() } else { ord }
+        }
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+    }
+}
+
+// ============================================================================
+// Float Key Comparison
+// ============================================================================
+
+/// Quantization applied to float keys before comparing them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FloatPrecision {
+    /// Compare stored values exactly, with the explicit NaN/null/infinity rules
+    Exact,
+    /// Round to N decimal places
+    Decimals(u32),
+    /// Round to the nearest multiple of a step (e.g. 0.25)
+    Step(f64),
+}
+
+impl FloatPrecision {
+    /// Factor that maps a value onto the integer quantization grid
+    fn multiplier(&self) -> Result<f64, InsightoraError> {
+        let multiplier = match *self {
+            FloatPrecision::Exact => 1.0,
+            FloatPrecision::Decimals(n) => 10f64.powi(n as i32),
+            FloatPrecision::Step(step) => 1.0 / step,
+        };
+        if !(multiplier.is_finite() && multiplier > 0.0) {
+            return Err(InsightoraError::ValidationError(format!(
+                "Invalid float precision {:?}: the step must be a positive finite number",
+                self
+            )));
+        }
+        Ok(multiplier)
+    }
+}
+
+/// How float key columns are compared in joins, deduplication and counting
+///
+/// Quantization only changes the comparison keys; stored values are never
+/// modified. Under quantization NaN keys equal each other when `nan_equal`
+/// is set and otherwise never match anything (not even another NaN), and
+/// infinities only equal infinities of the same sign. Null keys follow the
+/// operation's own rule: one group in deduplication and counting, no match
+/// in joins.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FloatKeyOptions {
+    /// None compares floats exactly
+    pub precision: Option<FloatPrecision>,
+    pub nan_equal: bool,
+}
+
+impl Default for FloatKeyOptions {
+    fn default() -> Self {
+        Self {
+            precision: None,
+            nan_equal: true,
+        }
+    }
+}
+
+/// Summary of how many rows quantized matching changed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuantizationReport {
+    /// Rows whose outcome differs from exact comparison
+    pub rows_affected: usize,
+}
+
+/// Whether keys are used to group rows within a frame or to match across frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum KeyRole {
+    Grouping,
+    Join,
+}
+
+/// Key classes stored next to the quantized value
+const KEY_VALUE: u32 = 0;
+const KEY_NAN: u32 = 1;
+const KEY_NULL: u32 = 2;
+const KEY_POS_INF: u32 = 3;
+const KEY_NEG_INF: u32 = 4;
+
+/// Prefix for temporary key columns added during keyed operations
+pub(crate) const KEY_COLUMN_PREFIX: &str = "__insightora_key";
+
+/// Build comparison key columns for the given key columns
+///
+/// Non-float columns (and all columns when no precision is set) are passed
+/// through unchanged. Quantized float columns become a pair of columns: the
+/// rounded value on the integer grid and a class tag separating NaN, null
+/// and infinities from real numbers.
+pub(crate) fn comparison_keys(
+    df: &DataFrame,
+    columns: &[String],
+    options: &FloatKeyOptions,
+    role: KeyRole,
+    tag: &str,
+) -> Result<Vec<Series>, InsightoraError> {
+    let mut keys = Vec::with_capacity(columns.len());
+    for (index, name) in columns.iter().enumerate() {
+        let series = df.column(name)?;
+        let key_name = format!("{}_{}{}", KEY_COLUMN_PREFIX, tag, index);
+        let precision = match options.precision {
+            Some(p) if series.dtype().is_float() => p,
+            _ => {
+                keys.push(series.clone().with_name(&key_name));
+                continue;
+            }
+        };
+
+        let multiplier = precision.multiplier()?;
+        let floats = series.cast(&DataType::Float64)?;
+        let mut values: Vec<Option<i64>> = Vec::with_capacity(series.len());
+        let mut classes: Vec<u32> = Vec::with_capacity(series.len());
+        for (row, value) in floats.f64()?.into_iter().enumerate() {
+            let (v, class) = match value {
+                None => (None, KEY_NULL),
+                Some(v) if v.is_nan() => match (options.nan_equal, role) {
+                    (true, _) => (Some(0), KEY_NAN),
+                    // A unique value per row keeps NaNs apart within a frame
+                    (false, KeyRole::Grouping) => (Some(row as i64), KEY_NAN),
+                    // Null keys never match in joins
+                    (false, KeyRole::Join) => (None, KEY_NAN),
+                },
+                Some(v) if v == f64::INFINITY => (Some(0), KEY_POS_INF),
+                Some(v) if v == f64::NEG_INFINITY => (Some(0), KEY_NEG_INF),
+                // -0.0 and 0.0 share a key
+                Some(v) if precision == FloatPrecision::Exact => {
+                    (Some(if v == 0.0 { 0 } else { v.to_bits() as i64 }), KEY_VALUE)
+                }
+                // Saturating cast: values beyond the i64 grid clamp to its ends
+                Some(v) => (Some((v * multiplier).round() as i64), KEY_VALUE),
+            };
+            values.push(v);
+            classes.push(class);
+        }
+        keys.push(Series::new(&key_name, values));
+        keys.push(Series::new(&format!("{}_class", key_name), classes));
+    }
+    Ok(keys)
+}
+
+/// Names of the temporary key columns in a frame
+pub(crate) fn key_column_names(df: &DataFrame) -> Vec<String> {
+    df.get_column_names()
+        .into_iter()
+        .filter(|name| name.starts_with(KEY_COLUMN_PREFIX))
+        .map(|name| name.to_string())
+        .collect()
+}
+
+// ============================================================================
+// Joins
+// ============================================================================
+
+/// Parse a join type name ("inner", "left", "outer"/"full")
+///
+/// Outer joins coalesce the key columns, so each key appears once as in
+/// inner and left joins.
+pub fn join_type_from_name(name: &str) -> Result<JoinType, InsightoraError> {
+    match name.to_ascii_lowercase().as_str() {
+        "inner" => Ok(JoinType::Inner),
+        "left" => Ok(JoinType::Left),
+        "outer" | "full" => Ok(JoinType::Outer { coalesce: true }),
+        other => Err(InsightoraError::ValidationError(format!(
+            "Unsupported join type '{}'; expected 'inner', 'left' or 'outer'",
+            other
+        ))),
+    }
+}
+
+/// Join execution strategy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JoinEngine {
+    /// Partitioned when the build side exceeds its share of the memory limit
+    #[default]
+    Auto,
+    /// Single hash join held entirely in memory
+    InMemory,
+    /// Hash-partition both sides to spill files under the configured
+    /// `temp_dir` and join partition pairs in turn; `partitions` of None
+    /// derives the count from the memory limit
+    Partitioned { partitions: Option<usize> },
+}
+
+impl JoinEngine {
+    /// Parse an engine name ("auto", "memory"/"in_memory", "partitioned")
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "auto" => Ok(JoinEngine::Auto),
+            "memory" | "in_memory" => Ok(JoinEngine::InMemory),
+            "partitioned" => Ok(JoinEngine::Partitioned { partitions: None }),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown join engine '{}'; expected 'auto', 'in_memory' or 'partitioned'",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            JoinEngine::Auto => "auto",
+            JoinEngine::InMemory => "in_memory",
+            JoinEngine::Partitioned { .. } => "partitioned",
+        }
+    }
+}
+
+/// How a join was executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JoinReport {
+    /// Engine that ran (never `Auto`)
+    pub engine: JoinEngine,
+    /// Partition pairs joined (1 for the in-memory engine)
+    pub partitions: usize,
+    /// Result rows that only matched because of float quantization
+    pub rows_affected: usize,
+}
+
+impl JoinReport {
+    pub fn quantization(&self) -> QuantizationReport {
+        QuantizationReport {
+            rows_affected: self.rows_affected,
+        }
+    }
+}
+
+/// Share of the memory limit a build side may use before `Auto` partitions
+const JOIN_BUILD_FRACTION: f64 = 0.25;
+const MAX_JOIN_PARTITIONS: usize = 1024;
+
+/// Row-position columns carried through partitioned joins to restore order
+const LEFT_ROW_COLUMN: &str = "__insightora_row_left";
+const RIGHT_ROW_COLUMN: &str = "__insightora_row_right";
+
+/// Join two DataFrames on key columns
+///
+/// With a float precision set, float keys are matched on their quantized
+/// values while both sides keep their stored values; the right key columns
+/// are then kept (suffixed "_right" on name clashes) so the matched values
+/// can be inspected. The report counts result rows whose stored float keys
+/// differ, i.e. rows that only matched because of quantization.
+///
+/// # Arguments
+/// * `left` / `right` - Frames to join
+/// * `left_on` / `right_on` - Key columns, pairwise
+/// * `how` - Join type
+/// * `float_options` - Float key comparison options
+/// * `engine` - Execution strategy; see `JoinEngine`
+///
+/// The partitioned engine returns the same rows as the in-memory one,
+/// ordered by left row and then right row (unmatched right rows last).
+/// Each partition pair must fit in memory, so a single very frequent key
+/// still limits how far memory use can be reduced.
+pub fn join_dataframes(
+    left: &DataFrame,
+    right: &DataFrame,
+    left_on: &[String],
+    right_on: &[String],
+    how: JoinType,
+    float_options: &FloatKeyOptions,
+    engine: JoinEngine,
+) -> Result<(DataFrame, JoinReport), InsightoraError> {
+    if left_on.is_empty() || left_on.len() != right_on.len() {
+        return Err(InsightoraError::ValidationError(format!(
+            "Join needs the same non-zero number of left and right keys (got {} and {})",
+            left_on.len(),
+            right_on.len()
+        )));
+    }
+
+    let budget = get_current_config().memory_limit_mb as f64 * 1024.0 * 1024.0 * JOIN_BUILD_FRACTION;
+    let partitions = match engine {
+        JoinEngine::InMemory => None,
+        JoinEngine::Partitioned { partitions: Some(0) } => {
+            return Err(InsightoraError::ValidationError(
+                "A partitioned join needs at least one partition".to_string(),
+            ));
+        }
+        JoinEngine::Partitioned { partitions: Some(k) } => Some(k.min(MAX_JOIN_PARTITIONS)),
+        JoinEngine::Partitioned { partitions: None } => Some(partition_count(left, right, budget)),
+        JoinEngine::Auto => {
+            (right.estimated_size() as f64 > budget).then(|| partition_count(left, right, budget))
+        }
+    };
+
+    match partitions {
+        None => {
+            let (joined, report) = join_in_memory(left, right, left_on, right_on, how, float_options)?;
+            Ok((
+                joined,
+                JoinReport {
+                    engine: JoinEngine::InMemory,
+                    partitions: 1,
+                    rows_affected: report.rows_affected,
+                },
+            ))
+        }
+        Some(partitions) => partitioned_join(left, right, left_on, right_on, how, float_options, partitions),
+    }
+}
+
+/// Partitions needed for each pair to fit within the build budget (at least 2)
+fn partition_count(left: &DataFrame, right: &DataFrame, budget: f64) -> usize {
+    let total = (left.estimated_size() + right.estimated_size()) as f64;
+    ((total / budget.max(1.0)).ceil() as usize).clamp(2, MAX_JOIN_PARTITIONS)
+}
+
+fn join_in_memory(
+    left: &DataFrame,
+    right: &DataFrame,
+    left_on: &[String],
+    right_on: &[String],
+    how: JoinType,
+    float_options: &FloatKeyOptions,
+) -> Result<(DataFrame, QuantizationReport), InsightoraError> {
+    if float_options.precision.is_none() {
+        let joined = left.join(right, left_on, right_on, JoinArgs::new(how))?;
+        return Ok((joined, QuantizationReport::default()));
+    }
+
+    let mut left_keys = comparison_keys(left, left_on, float_options, KeyRole::Join, "l")?;
+    let mut right_keys = comparison_keys(right, right_on, float_options, KeyRole::Join, "r")?;
+    let left_key_names: Vec<String> = left_keys.iter().map(|s| s.name().to_string()).collect();
+    let right_key_names: Vec<String> = right_keys.iter().map(|s| s.name().to_string()).collect();
+
+    // Carry copies of the stored float keys through the join so matches that
+    // rely on quantization can be counted afterwards
+    let mut float_pairs = Vec::new();
+    for (index, (l, r)) in left_on.iter().zip(right_on).enumerate() {
+        let (l, r) = (left.column(l)?, right.column(r)?);
+        if l.dtype().is_float() && r.dtype().is_float() {
+            let names = (
+                format!("{}_lv{}", KEY_COLUMN_PREFIX, index),
+                format!("{}_rv{}", KEY_COLUMN_PREFIX, index),
+            );
+            left_keys.push(l.cast(&DataType::Float64)?.with_name(&names.0));
+            right_keys.push(r.cast(&DataType::Float64)?.with_name(&names.1));
+            float_pairs.push(names);
+        }
+    }
+    let keyed_left = left.hstack(&left_keys)?;
+    let keyed_right = right.hstack(&right_keys)?;
+    let joined = keyed_left.join(&keyed_right, &left_key_names, &right_key_names, JoinArgs::new(how))?;
+
+    let mut affected = vec![false; joined.height()];
+    for (left_name, right_name) in &float_pairs {
+        let l = joined.column(left_name)?.f64()?;
+        let r = joined.column(right_name)?.f64()?;
+        for (row, (a, b)) in l.into_iter().zip(r).enumerate() {
+            if let (Some(a), Some(b)) = (a, b) {
+                if a != b && !(a.is_nan() && b.is_nan()) {
+                    affected[row] = true;
+                }
+            }
+        }
+    }
+
+    let joined = joined.drop_many(&key_column_names(&joined));
+    Ok((
+        joined,
+        QuantizationReport {
+            rows_affected: affected.into_iter().filter(|&a| a).count(),
+        },
+    ))
+}
+
+/// Spill file of one side of a partition
+fn spill_file(dir: &ScratchDir, side: &str, partition: usize) -> PathBuf {
+    dir.join(format!("{}_{}.parquet", side, partition))
+}
+
+/// Partition index of every row, hashed from its comparison keys
+///
+/// Keys are hashed as text so both sides agree even when their integer
+/// widths differ; quantized float keys hash their grid value and class.
+fn partition_ids(
+    df: &DataFrame,
+    on: &[String],
+    options: &FloatKeyOptions,
+    partitions: usize,
+) -> Result<Vec<Vec<IdxSize>>, InsightoraError> {
+    let keys = comparison_keys(df, on, options, KeyRole::Join, "p")?
+        .iter()
+        .map(|key| key.cast(&DataType::String))
+        .collect::<Result<Vec<_>, _>>()?;
+    let keys = keys.iter().map(|key| key.str()).collect::<Result<Vec<_>, _>>()?;
+
+    let mut rows = vec![Vec::new(); partitions];
+    for row in 0..df.height() {
+        let mut hasher = DefaultHasher::new();
+        for key in &keys {
+            key.get(row).hash(&mut hasher);
+        }
+        rows[(hasher.finish() % partitions as u64) as usize].push(row as IdxSize);
+    }
+    Ok(rows)
+}
+
+/// Write each partition of `df` to its spill file
+fn spill_partitions(df: &DataFrame, rows: Vec<Vec<IdxSize>>, dir: &ScratchDir, side: &str) -> Result<(), InsightoraError> {
+    for (partition, rows) in rows.into_iter().enumerate() {
+        let mut part = df.take(&IdxCa::from_vec("", rows))?;
+        let file = File::create(spill_file(dir, side, partition))?;
+        ParquetWriter::new(file).finish(&mut part)?;
+    }
+    Ok(())
+}
+
+fn partitioned_join(
+    left: &DataFrame,
+    right: &DataFrame,
+    left_on: &[String],
+    right_on: &[String],
+    how: JoinType,
+    float_options: &FloatKeyOptions,
+    partitions: usize,
+) -> Result<(DataFrame, JoinReport), InsightoraError> {
+    // Partition on the same keys the join compares, so quantized matches and
+    // NaN/-0.0 handling never straddle two partitions
+    let key_options = match float_options.precision {
+        Some(_) => *float_options,
+        None => FloatKeyOptions {
+            precision: Some(FloatPrecision::Exact),
+            nan_equal: true,
+        },
+    };
+    let _watch = memory::watch("partitioned join");
+    let dir = ScratchDir::create("insightora-join", None)?;
+    {
+        let left = left.hstack(&[Series::new(LEFT_ROW_COLUMN, (0..left.height() as u64).collect::<Vec<_>>())])?;
+        let rows = partition_ids(&left, left_on, &key_options, partitions)?;
+        spill_partitions(&left, rows, &dir, "left")?;
+    }
+    {
+        let right = right.hstack(&[Series::new(RIGHT_ROW_COLUMN, (0..right.height() as u64).collect::<Vec<_>>())])?;
+        let rows = partition_ids(&right, right_on, &key_options, partitions)?;
+        spill_partitions(&right, rows, &dir, "right")?;
+    }
+
+    let mut joined: Option<DataFrame> = None;
+    let mut rows_affected = 0;
+    for partition in 0..partitions {
+        memory::checkpoint()?;
+        let left_part = ParquetReader::new(File::open(spill_file(&dir, "left", partition))?).finish()?;
+        let right_part = ParquetReader::new(File::open(spill_file(&dir, "right", partition))?).finish()?;
+        let (part, report) = join_in_memory(&left_part, &right_part, left_on, right_on, how.clone(), float_options)?;
+        rows_affected += report.rows_affected;
+        match joined.as_mut() {
+            Some(joined) => {
+                joined.vstack_mut(&part)?;
+            }
+            None => joined = Some(part),
+        }
+    }
+
+    let joined = joined
+        .unwrap_or_default()
+        .lazy()
+        .sort_by_exprs([col(LEFT_ROW_COLUMN), col(RIGHT_ROW_COLUMN)], [false, false], true, false)
+        .collect()?
+        .drop_many(&[LEFT_ROW_COLUMN, RIGHT_ROW_COLUMN]);
+
+    Ok((
+        joined,
+        JoinReport {
+            engine: JoinEngine::Partitioned {
+                partitions: Some(partitions),
+            },
+            partitions,
+            rows_affected,
+        },
+    ))
+}
+
+/// Join types accepted by `join_rows`
+///
+/// Semi and anti joins return left rows only: those with at least one
+/// matching right row, and those with none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinHow {
+    Inner,
+    Left,
+    Outer,
+    Semi,
+    Anti,
+}
+
+impl JoinHow {
+    /// Parse a join type name ("inner", "left", "outer"/"full", "semi", "anti")
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "inner" => Ok(JoinHow::Inner),
+            "left" => Ok(JoinHow::Left),
+            "outer" | "full" => Ok(JoinHow::Outer),
+            "semi" => Ok(JoinHow::Semi),
+            "anti" => Ok(JoinHow::Anti),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unsupported join type '{}'; expected 'inner', 'left', 'outer', 'semi' or 'anti'",
+                other
+            ))),
+        }
+    }
+}
+
+/// Options for `join_rows`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JoinOptions {
+    pub how: JoinHow,
+    /// Appended to right non-key columns whose names the left side already has
+    pub suffix: String,
+    /// Reject key pairs of different numeric types instead of casting both
+    /// sides to Int64 (UInt64 past its range), or to Float64 when either is a
+    /// float and the integers fit one exactly (else whole-number floats
+    /// compare as integers)
+    pub strict: bool,
+}
+
+impl Default for JoinOptions {
+    fn default() -> Self {
+        Self {
+            how: JoinHow::Inner,
+            suffix: "_right".to_string(),
+            strict: false,
+        }
+    }
+}
+
+/// Sizes of a join's inputs and result, to spot unexpected fan-out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JoinCounts {
+    pub left_rows: usize,
+    pub right_rows: usize,
+    pub rows: usize,
+    pub columns: usize,
+}
+
+/// Extra key column that stops rows with a null key from matching
+const JOIN_GUARD_COLUMN: &str = "__insightora_join_guard";
+/// Marks right keys in the lookup semi and anti joins are decided by
+const JOIN_MATCH_COLUMN: &str = "__insightora_join_match";
+
+/// Join two DataFrames on key columns, with semi and anti joins
+///
+/// A null in any key column never matches, not even another null, so with
+/// multi-column keys a row matches only when every key is set on both
+/// sides. Key pairs of different numeric types are cast to a common type
+/// that keeps every value exact unless `strict`; integer keys beyond 2^53
+/// against fractional floats, and other type mismatches, are an error. Semi and
+/// anti joins return left rows unchanged. Right
+/// non-key columns named like a left column get `suffix`. Inner, left and
+/// outer joins run on `join_dataframes`, spilling to partitions like it
+/// when the right side is large.
+///
+/// # Arguments
+/// * `left` / `right` - Frames to join
+/// * `left_on` / `right_on` - Key columns, pairwise
+/// * `options` - Join type, suffix and key type strictness
+///
+/// # Returns
+/// * `Result<(DataFrame, JoinCounts)>` - Joined rows with the input and
+///   result sizes; ValidationError naming a missing key column or a key
+///   pair whose types don't match
+///
+/// # Example
+/// ```no_run
+/// use insightora_core::api::{join_rows, JoinHow, JoinOptions, ParallelCsvParser};
+///
+/// let orders = ParallelCsvParser::new().parse("orders.csv").unwrap();
+/// let refunds = ParallelCsvParser::new().parse("refunds.csv").unwrap();
+/// let on = ["order_id".to_string()];
+/// let options = JoinOptions { how: JoinHow::Anti, ..Default::default() };
+/// let (kept, counts) = join_rows(&orders, &refunds, &on, &on, &options).unwrap();
+/// println!("{} of {} orders were never refunded", counts.rows, counts.left_rows);
+/// ```
+pub fn join_rows(
+    left: &DataFrame,
+    right: &DataFrame,
+    left_on: &[String],
+    right_on: &[String],
+    options: &JoinOptions,
+) -> Result<(DataFrame, JoinCounts), InsightoraError> {
+    if left_on.is_empty() || left_on.len() != right_on.len() {
+        return Err(InsightoraError::ValidationError(format!(
+            "Join needs the same non-zero number of left and right keys (got {} and {})",
+            left_on.len(),
+            right_on.len()
+        )));
+    }
+
+    let (mut keyed_left, mut keyed_right) = (left.clone(), right.clone());
+    for (l, r) in left_on.iter().zip(right_on) {
+        let (l_key, r_key) = (join_key(left, l, "left")?, join_key(right, r, "right")?);
+        let (l_type, r_type) = (l_key.dtype().clone(), r_key.dtype().clone());
+        if l_type == r_type {
+            continue;
+        }
+        let numeric = l_type.is_numeric() && r_type.is_numeric();
+        if options.strict || !numeric {
+            let hint = if numeric { "strict matching is on" } else { "cast one side first" };
+            return Err(InsightoraError::ValidationError(format!(
+                "Join keys '{}' ({}) and '{}' ({}) have different types; {}",
+                l, l_type, r, r_type, hint
+            )));
+        }
+        let common = match (l_type.is_float(), r_type.is_float()) {
+            (true, true) => Some(DataType::Float64),
+            (false, false) => {
+                let values = integer_values(l_key)?.into_iter().chain(integer_values(r_key)?);
+                integer_key_type(values)
+            }
+            (left_float, _) => {
+                let (ints, floats) = if left_float { (r_key, l_key) } else { (l_key, r_key) };
+                exact_key_type(ints, floats)?
+            }
+        };
+        let common = common.ok_or_else(|| {
+            InsightoraError::ValidationError(format!(
+                "Join keys '{}' ({}) and '{}' ({}) can't be compared exactly: no integer type holds \
+                 both sides, or integers beyond 2^53 meet floats that aren't whole numbers; cast one side first",
+                l, l_type, r, r_type
+            ))
+        })?;
+        let cast = keyed_left.column(l)?.cast(&common)?;
+        keyed_left.replace(l, cast)?;
+        let cast = keyed_right.column(r)?.cast(&common)?;
+        keyed_right.replace(r, cast)?;
+    }
+
+    // Rows with a null key get a guard value no row of either side shares
+    let (mut left_keys, mut right_keys) = (left_on.to_vec(), right_on.to_vec());
+    let has_nulls = |df: &DataFrame, on: &[String]| on.iter().any(|name| df.column(name).is_ok_and(|s| s.null_count() > 0));
+    if has_nulls(left, left_on) || has_nulls(right, right_on) {
+        keyed_left.with_column(null_key_guard(left, left_on, |row| row as u64 + 1)?)?;
+        keyed_right.with_column(null_key_guard(right, right_on, |row| u64::MAX - row as u64)?)?;
+        left_keys.push(JOIN_GUARD_COLUMN.to_string());
+        right_keys.push(JOIN_GUARD_COLUMN.to_string());
+    }
+
+    let joined = match options.how {
+        JoinHow::Semi | JoinHow::Anti => {
+            // A left join against the distinct right keys keeps one row per
+            // left row, in order, marked where a match exists
+            let mut lookup = keyed_right.select(&right_keys)?.unique(None, UniqueKeepStrategy::Any, None)?;
+            lookup.with_column(Series::new(JOIN_MATCH_COLUMN, vec![true; lookup.height()]))?;
+            let marked = keyed_left.join(&lookup, &left_keys, &right_keys, JoinArgs::new(JoinType::Left))?;
+            let matched = marked.column(JOIN_MATCH_COLUMN)?.is_not_null();
+            let keep = if options.how == JoinHow::Semi { matched } else { !matched };
+            left.filter(&keep)?
+        }
+        how => {
+            let left_names: HashSet<&str> = keyed_left.get_column_names().into_iter().collect();
+            let clashing: Vec<String> = keyed_right
+                .get_column_names()
+                .into_iter()
+                .filter(|name| left_names.contains(name) && !right_keys.iter().any(|key| key == name))
+                .map(|name| name.to_string())
+                .collect();
+            for name in clashing {
+                let renamed = format!("{}{}", name, options.suffix);
+                if left_names.contains(renamed.as_str()) || keyed_right.column(&renamed).is_ok() {
+                    return Err(InsightoraError::ValidationError(format!(
+                        "Suffixing right column '{}' gives '{}', which is already a column; pass another suffix",
+                        name, renamed
+                    )));
+                }
+                keyed_right.rename(&name, &renamed)?;
+            }
+            let how = match how {
+                JoinHow::Inner => JoinType::Inner,
+                JoinHow::Left => JoinType::Left,
+                _ => JoinType::Outer { coalesce: true },
+            };
+            let options = FloatKeyOptions::default();
+            join_dataframes(&keyed_left, &keyed_right, &left_keys, &right_keys, how, &options, JoinEngine::Auto)?.0
+        }
+    };
+
+    let guards: Vec<String> = joined
+        .get_column_names()
+        .into_iter()
+        .filter(|name| name.starts_with(JOIN_GUARD_COLUMN))
+        .map(|name| name.to_string())
+        .collect();
+    let joined = joined.drop_many(&guards);
+    let counts = JoinCounts {
+        left_rows: left.height(),
+        right_rows: right.height(),
+        rows: joined.height(),
+        columns: joined.width(),
+    };
+    Ok((joined, counts))
+}
+
+/// Type an integer and a float join key both compare exactly as
+///
+/// Float64 when every integer is exactly a float (all within 2^53, or
+/// rounding to themselves), else an integer type as `integer_key_type` when
+/// every float is a whole number; None when neither holds, as either cast
+/// would merge distinct keys.
+fn exact_key_type(ints: &Series, floats: &Series) -> Result<Option<DataType>, InsightoraError> {
+    let ints = integer_values(ints)?;
+    if ints.iter().all(|&v| (v as f64) as i128 == v) {
+        return Ok(Some(DataType::Float64));
+    }
+    let floats: Vec<f64> = floats.cast(&DataType::Float64)?.f64()?.into_iter().flatten().collect();
+    if !floats.iter().all(|v| v.fract() == 0.0) {
+        return Ok(None);
+    }
+    Ok(integer_key_type(ints.into_iter().chain(floats.into_iter().map(|v| v as i128))))
+}
+
+/// Non-null values of an integer key, widened so UInt64 values beyond i64::MAX survive
+fn integer_values(series: &Series) -> Result<Vec<i128>, InsightoraError> {
+    Ok(match series.dtype() {
+        DataType::UInt64 => series.u64()?.into_iter().flatten().map(i128::from).collect(),
+        _ => series.cast(&DataType::Int64)?.i64()?.into_iter().flatten().map(i128::from).collect(),
+    })
+}
+
+/// Int64 when it holds every value, else UInt64 when none is negative; None otherwise
+fn integer_key_type(values: impl IntoIterator<Item = i128>) -> Option<DataType> {
+    let (min, max) = values.into_iter().fold((0, 0), |(min, max), v| (v.min(min), v.max(max)));
+    if min >= i128::from(i64::MIN) && max <= i128::from(i64::MAX) {
+        Some(DataType::Int64)
+    } else if min >= 0 && max <= i128::from(u64::MAX) {
+        Some(DataType::UInt64)
+    } else {
+        None
+    }
+}
+
+fn join_key<'a>(df: &'a DataFrame, name: &str, side: &str) -> Result<&'a Series, InsightoraError> {
+    df.column(name).map_err(|_| {
+        InsightoraError::ValidationError(format!("Join column '{}' not found in the {} table", name, side))
+    })
+}
+
+/// Guard key column: 0 where every key is set, `unmatched(row)` where any is null
+fn null_key_guard(df: &DataFrame, on: &[String], unmatched: fn(usize) -> u64) -> Result<Series, InsightoraError> {
+    let mut null_rows = vec![false; df.height()];
+    for name in on {
+        for (row, null) in df.column(name)?.is_null().into_iter().enumerate() {
+            null_rows[row] |= null == Some(true);
+        }
+    }
+    let guard: Vec<u64> = null_rows
+        .into_iter()
+        .enumerate()
+        .map(|(row, null)| if null { unmatched(row) } else { 0 })
+        .collect();
+    Ok(Series::new(JOIN_GUARD_COLUMN, guard))
+}
+
+// ============================================================================
+// Join Key Suggestions
+// ============================================================================
+
+/// Rows read from each side by `suggest_join_keys`
+pub const JOIN_KEY_SAMPLE_ROWS: usize = 10_000;
+
+/// Single columns kept per side when forming two-column keys
+const COMPOSITE_SEED_COLUMNS: usize = 6;
+
+/// Heuristic estimate for one candidate join key
+///
+/// Every figure comes from the sampled rows only, so overlap and match rate
+/// are estimates: a right-side sample can miss keys the full table has,
+/// which biases `estimated_match_rate` low for large tables.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JoinKeyCandidate {
+    pub left: Vec<String>,
+    pub right: Vec<String>,
+    /// Combined ranking score in [0, 1]
+    pub score: f64,
+    /// How alike the column names are, in [0, 1]
+    pub name_similarity: f64,
+    /// Jaccard similarity of the distinct sampled key values
+    pub value_overlap: f64,
+    /// Share of non-null left sample rows whose key appears in the right sample
+    pub estimated_match_rate: f64,
+    /// Distinct / non-null sampled keys on each side (1.0 = unique)
+    pub left_uniqueness: f64,
+    pub right_uniqueness: f64,
+    /// Why the dtypes are compatible ("same type", "integer/text", ...)
+    pub dtype_match: &'static str,
+}
+
+/// Sampled, hashed key values of one column or column pair
+struct KeyProfile {
+    /// Row hashes; None where any key column is null
+    rows: Vec<Option<u64>>,
+    distinct: HashSet<u64>,
+}
+
+impl KeyProfile {
+    fn new(columns: &[&Series]) -> Result<Self, InsightoraError> {
+        let text: Vec<Series> = columns
+            .iter()
+            .map(|series| series.cast(&DataType::String))
+            .collect::<Result<_, _>>()?;
+        let text: Vec<&StringChunked> = text.iter().map(|s| s.str()).collect::<Result<_, _>>()?;
+        let rows: Vec<Option<u64>> = (0..columns.first().map_or(0, |s| s.len()))
+            .map(|row| {
+                let mut hasher = DefaultHasher::new();
+                for column in &text {
+                    // Trimmed so " 42" and "42" match across files
+                    column.get(row)?.trim().hash(&mut hasher);
+                }
+                Some(hasher.finish())
+            })
+            .collect();
+        let distinct = rows.iter().flatten().copied().collect();
+        Ok(Self { rows, distinct })
+    }
+
+    fn uniqueness(&self) -> f64 {
+        let present = self.rows.iter().flatten().count();
+        if present == 0 { 0.0 } else { self.distinct.len() as f64 / present as f64 }
+    }
+
+    fn jaccard(&self, other: &KeyProfile) -> f64 {
+        let shared = self.distinct.intersection(&other.distinct).count();
+        let union = self.distinct.len() + other.distinct.len() - shared;
+        if union == 0 { 0.0 } else { shared as f64 / union as f64 }
+    }
+
+    fn match_rate(&self, other: &KeyProfile) -> f64 {
+        let present: Vec<u64> = self.rows.iter().flatten().copied().collect();
+        if present.is_empty() {
+            return 0.0;
+        }
+        present.iter().filter(|hash| other.distinct.contains(hash)).count() as f64 / present.len() as f64
+    }
+}
+
+/// Evenly spaced rows, at most `JOIN_KEY_SAMPLE_ROWS`
+fn key_sample(df: &DataFrame) -> Result<DataFrame, InsightoraError> {
+    let height = df.height();
+    if height <= JOIN_KEY_SAMPLE_ROWS {
+        return Ok(df.clone());
+    }
+    let indices: Vec<IdxSize> = (0..JOIN_KEY_SAMPLE_ROWS)
+        .map(|i| (i * height / JOIN_KEY_SAMPLE_ROWS) as IdxSize)
+        .collect();
+    Ok(df.take(&IdxCa::from_vec("", indices))?)
+}
+
+/// Dtype compatibility as (label, weight); None rules the pair out
+fn key_dtype_match(left: &DataType, right: &DataType) -> Option<(&'static str, f64)> {
+    let text = |dtype: &DataType| matches!(dtype, DataType::String | DataType::Categorical(..));
+    let temporal = |dtype: &DataType| matches!(dtype, DataType::Date | DataType::Datetime(..));
+    match (left, right) {
+        (DataType::Boolean, _) | (_, DataType::Boolean) => None,
+        (l, r) if l.is_float() || r.is_float() => {
+            // Floats rarely make good keys; allow them only against numbers
+            (l.is_numeric() && r.is_numeric()).then_some(("float", 0.4))
+        }
+        (l, r) if l.is_integer() && r.is_integer() => Some(("same type", 1.0)),
+        (l, r) if text(l) && text(r) => Some(("same type", 1.0)),
+        (l, r) if (l.is_integer() && text(r)) || (text(l) && r.is_integer()) => Some(("integer/text", 0.7)),
+        (l, r) if temporal(l) && temporal(r) => Some(("same type", 1.0)),
+        (l, r) if l == r => Some(("same type", 1.0)),
+        _ => None,
+    }
+}
+
+/// Similarity of two column names, in [0, 1]
+///
+/// Names are compared in snake case, so "CustomerID" and "customer_id" score
+/// 1.0; otherwise the better of word overlap and edit distance is used, with
+/// a bonus for a shared trailing "id"/"key"/"code" word.
+fn column_name_similarity(left: &str, right: &str) -> f64 {
+    let (left, right) = (NameStyle::Snake.apply(left), NameStyle::Snake.apply(right));
+    if left.is_empty() || right.is_empty() {
+        return 0.0;
+    }
+    if left == right {
+        return 1.0;
+    }
+    let words = |name: &str| -> HashSet<String> { name.split('_').map(|w| w.to_string()).collect() };
+    let (lw, rw) = (words(&left), words(&right));
+    let overlap = lw.intersection(&rw).count() as f64 / lw.union(&rw).count() as f64;
+
+    let distance = edit_distance(&left, &right) as f64;
+    let edit = 1.0 - distance / left.chars().count().max(right.chars().count()) as f64;
+
+    let key_word = |name: &str| name.rsplit('_').next().is_some_and(|w| matches!(w, "id" | "key" | "code" | "no" | "number"));
+    let bonus = if key_word(&left) && key_word(&right) { 0.1 } else { 0.0 };
+    (overlap.max(edit) + bonus).min(1.0)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Rank likely join keys between two undocumented tables
+///
+/// Profiles an evenly spaced sample of at most `JOIN_KEY_SAMPLE_ROWS` rows
+/// from each side. Every column pair with compatible dtypes and some shared
+/// sampled values is scored on name similarity, value overlap and
+/// cardinality (a key should be near-unique on at least one side). Pairs of
+/// the strongest single columns are also tried as two-column keys, and kept
+/// when the combination is more unique than either column alone.
+///
+/// The results are heuristic estimates from samples, not verified matches.
+///
+/// # Arguments
+/// * `left` / `right` - Tables to join
+/// * `max_candidates` - Candidates returned, best first
+///
+/// # Returns
+/// * `Result<Vec<JoinKeyCandidate>>` - Empty when no pair shares sampled values
+pub fn suggest_join_keys(
+    left: &DataFrame,
+    right: &DataFrame,
+    max_candidates: usize,
+) -> Result<Vec<JoinKeyCandidate>, InsightoraError> {
+    let (left, right) = (key_sample(left)?, key_sample(right)?);
+    let profiles = |df: &DataFrame| -> Result<Vec<KeyProfile>, InsightoraError> {
+        df.get_columns().par_iter().map(|series| KeyProfile::new(&[series])).collect()
+    };
+    let (left_profiles, right_profiles) = (profiles(&left)?, profiles(&right)?);
+
+    let score = |name: f64, overlap: f64, match_rate: f64, left_unique: f64, right_unique: f64, weight: f64| {
+        (0.25 * name + 0.2 * overlap + 0.3 * match_rate + 0.25 * left_unique.max(right_unique)) * weight
+    };
+
+    let mut singles = Vec::new();
+    for (i, l) in left.get_columns().iter().enumerate() {
+        for (j, r) in right.get_columns().iter().enumerate() {
+            let Some((dtype_match, weight)) = key_dtype_match(l.dtype(), r.dtype()) else {
+                continue;
+            };
+            let (lp, rp) = (&left_profiles[i], &right_profiles[j]);
+            let value_overlap = lp.jaccard(rp);
+            if value_overlap == 0.0 {
+                continue;
+            }
+            let name_similarity = column_name_similarity(l.name(), r.name());
+            let estimated_match_rate = lp.match_rate(rp);
+            let (left_uniqueness, right_uniqueness) = (lp.uniqueness(), rp.uniqueness());
+            singles.push((i, j, weight, JoinKeyCandidate {
+                left: vec![l.name().to_string()],
+                right: vec![r.name().to_string()],
+                score: score(name_similarity, value_overlap, estimated_match_rate, left_uniqueness, right_uniqueness, weight),
+                name_similarity,
+                value_overlap,
+                estimated_match_rate,
+                left_uniqueness,
+                right_uniqueness,
+                dtype_match,
+            }));
+        }
+    }
+    singles.sort_by(|a, b| b.3.score.total_cmp(&a.3.score));
+
+    let seeds = &singles[..singles.len().min(COMPOSITE_SEED_COLUMNS)];
+    let mut composites = Vec::new();
+    for (a, first) in seeds.iter().enumerate() {
+        for second in &seeds[a + 1..] {
+            let (i1, j1, w1, c1) = first;
+            let (i2, j2, w2, c2) = second;
+            if i1 == i2 || j1 == j2 {
+                continue;
+            }
+            let lp = KeyProfile::new(&[&left.get_columns()[*i1], &left.get_columns()[*i2]])?;
+            let rp = KeyProfile::new(&[&right.get_columns()[*j1], &right.get_columns()[*j2]])?;
+            let (left_uniqueness, right_uniqueness) = (lp.uniqueness(), rp.uniqueness());
+            let best_single = c1.left_uniqueness.max(c1.right_uniqueness).max(c2.left_uniqueness.max(c2.right_uniqueness));
+            if left_uniqueness.max(right_uniqueness) <= best_single {
+                continue;
+            }
+            let value_overlap = lp.jaccard(&rp);
+            if value_overlap == 0.0 {
+                continue;
+            }
+            let name_similarity = (c1.name_similarity + c2.name_similarity) / 2.0;
+            let estimated_match_rate = lp.match_rate(&rp);
+            let weight = w1.min(*w2);
+            composites.push(JoinKeyCandidate {
+                left: vec![c1.left[0].clone(), c2.left[0].clone()],
+                right: vec![c1.right[0].clone(), c2.right[0].clone()],
+                score: score(name_similarity, value_overlap, estimated_match_rate, left_uniqueness, right_uniqueness, weight),
+                name_similarity,
+                value_overlap,
+                estimated_match_rate,
+                left_uniqueness,
+                right_uniqueness,
+                dtype_match: if c1.dtype_match == c2.dtype_match { c1.dtype_match } else { "mixed" },
+            });
+        }
+    }
+
+    let mut candidates: Vec<JoinKeyCandidate> = singles.into_iter().map(|(_, _, _, c)| c).chain(composites).collect();
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates.truncate(max_candidates);
+    Ok(candidates)
+}
+
+// ============================================================================
+// Concatenation
+// ============================================================================
+
+/// Direction `concat_frames` stacks frames in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConcatHow {
+    /// Rows of each frame after the previous frame's, columns matched by name
+    #[default]
+    Vertical,
+    /// Columns of each frame beside the previous frame's, rows matched by position
+    Horizontal,
+}
+
+impl ConcatHow {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "vertical" => Ok(ConcatHow::Vertical),
+            "horizontal" => Ok(ConcatHow::Horizontal),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown concat direction '{}'; expected 'vertical' or 'horizontal'",
+                other
+            ))),
+        }
+    }
+}
+
+/// Options for `concat_frames`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConcatOptions {
+    pub how: ConcatHow,
+    /// Vertical: fill columns a frame lacks with nulls, and widen columns
+    /// whose types differ to strings, instead of requiring the same columns
+    /// and types in every frame
+    pub relaxed: bool,
+    /// Horizontal: rename a column already taken by an earlier frame to
+    /// `{name}{suffix}{frame index}` instead of failing
+    pub suffix: Option<String>,
+    /// Gather the result into one contiguous chunk per column
+    pub rechunk: bool,
+}
+
+impl Default for ConcatOptions {
+    fn default() -> Self {
+        Self {
+            how: ConcatHow::Vertical,
+            relaxed: false,
+            suffix: None,
+            rechunk: true,
+        }
+    }
+}
+
+/// Concatenate frames vertically or horizontally
+///
+/// Vertically, columns are matched by name and come out in the first
+/// frame's order (columns first seen in later frames follow when relaxed).
+/// Horizontally, every frame must have the same number of rows.
+///
+/// # Arguments
+/// * `frames` - Frames to combine, in order
+/// * `options` - Direction, schema and duplicate-name handling
+///
+/// # Returns
+/// * `Result<DataFrame>` - Combined frame; ValidationError naming the
+///   columns that differ, the frames whose row counts differ, or a duplicate
+///   column
+///
+/// # Example
+/// ```no_run
+/// use insightora_core::api::{concat_frames, ConcatOptions, ParallelCsvParser};
+///
+/// let parser = ParallelCsvParser::new();
+/// let batches = vec![parser.parse("jan.csv").unwrap(), parser.parse("feb.csv").unwrap()];
+/// let options = ConcatOptions { relaxed: true, ..Default::default() };
+/// let combined = concat_frames(&batches, &options).unwrap();
+/// ```
+pub fn concat_frames(frames: &[DataFrame], options: &ConcatOptions) -> Result<DataFrame, InsightoraError> {
+    let Some(first) = frames.first() else {
+        return Err(InsightoraError::ValidationError("concat needs at least one frame".to_string()));
+    };
+    let mut combined = match options.how {
+        ConcatHow::Vertical if options.relaxed => align_and_concat(frames.to_vec(), SchemaAlignment::Union)?,
+        ConcatHow::Vertical => {
+            let names = first.get_column_names();
+            let mut combined = first.clone();
+            for (index, df) in frames.iter().enumerate().skip(1) {
+                let mut differences: Vec<String> = Vec::new();
+                for series in first.get_columns() {
+                    match df.column(series.name()) {
+                        Err(_) => differences.push(format!("'{}' is missing", series.name())),
+                        Ok(other) if other.dtype() != series.dtype() => differences.push(format!(
+                            "'{}' is {} rather than {}",
+                            series.name(),
+                            other.dtype(),
+                            series.dtype()
+                        )),
+                        Ok(_) => {}
+                    }
+                }
+                for name in df.get_column_names() {
+                    if !names.contains(&name) {
+                        differences.push(format!("'{}' is extra", name));
+                    }
+                }
+                if !differences.is_empty() {
+                    return Err(InsightoraError::ValidationError(format!(
+                        "Frame {} doesn't match the columns of frame 0: {}; pass relaxed to fill missing columns with nulls",
+                        index,
+                        differences.join(", ")
+                    )));
+                }
+                combined.vstack_mut(&df.select(&names)?)?;
+            }
+            combined
+        }
+        ConcatHow::Horizontal => {
+            let mut columns: Vec<Series> = Vec::with_capacity(frames.iter().map(DataFrame::width).sum());
+            for (index, df) in frames.iter().enumerate() {
+                if df.height() != first.height() {
+                    return Err(InsightoraError::ValidationError(format!(
+                        "Frame {} has {} rows but frame 0 has {}; horizontal concat needs equal row counts",
+                        index,
+                        df.height(),
+                        first.height()
+                    )));
+                }
+                for series in df.get_columns() {
+                    let taken = |name: &str| columns.iter().any(|column| column.name() == name);
+                    let name = match &options.suffix {
+                        _ if !taken(series.name()) => series.name().to_string(),
+                        Some(suffix) if !taken(&format!("{}{}{}", series.name(), suffix, index)) => {
+                            format!("{}{}{}", series.name(), suffix, index)
+                        }
+                        _ => {
+                            return Err(InsightoraError::ValidationError(format!(
+                                "Column '{}' of frame {} is already taken; pass a suffix to rename duplicates",
+                                series.name(),
+                                index
+                            )));
+                        }
+                    };
+                    columns.push(series.clone().with_name(&name));
+                }
+            }
+            DataFrame::new(columns)?
+        }
+    };
+    if options.rechunk {
+        combined.as_single_chunk_par();
+    }
+    Ok(combined)
+}
+
+// ============================================================================
+// Column Layout
+// ============================================================================
+
+/// What `reorder_columns` does with columns left out of the order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnlistedColumns {
+    #[default]
+    Error,
+    /// Keep them after the listed columns, in their current order
+    Append,
+}
+
+impl UnlistedColumns {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "error" => Ok(UnlistedColumns::Error),
+            "append" => Ok(UnlistedColumns::Append),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown missing-column policy '{}'; expected 'error' or 'append'",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            UnlistedColumns::Error => "error",
+            UnlistedColumns::Append => "append",
+        }
+    }
+}
+
+/// Naming convention applied by `normalize_column_names`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NameStyle {
+    /// "Order Date" / "OrderDate" -> "order_date"
+    #[default]
+    Snake,
+    /// Lowercased with surrounding whitespace trimmed: "Order Date" -> "order date"
+    Lower,
+    /// "Order Date" / "order_date" -> "orderDate"
+    Camel,
+}
+
+impl NameStyle {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "snake" => Ok(NameStyle::Snake),
+            "lower" => Ok(NameStyle::Lower),
+            "camel" => Ok(NameStyle::Camel),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown name style '{}'; expected 'snake', 'lower' or 'camel'",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NameStyle::Snake => "snake",
+            NameStyle::Lower => "lower",
+            NameStyle::Camel => "camel",
+        }
+    }
+
+    /// Convert one column name
+    ///
+    /// Words are split at any non-alphanumeric character and at case changes
+    /// ("HTTPServer" -> "http", "server"); digits stay with the word before
+    /// them. A name with no letters or digits converts to an empty string.
+    pub fn apply(&self, name: &str) -> String {
+        match self {
+            NameStyle::Lower => name.trim().to_lowercase(),
+            NameStyle::Snake => name_words(name).join("_"),
+            NameStyle::Camel => name_words(name)
+                .iter()
+                .enumerate()
+                .map(|(i, word)| {
+                    if i == 0 {
+                        return word.clone();
+                    }
+                    let mut chars = word.chars();
+                    chars
+                        .next()
+                        .map(|first| first.to_uppercase().chain(chars).collect())
+                        .unwrap_or_default()
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Lowercase words of a column name
+fn name_words(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+        let previous = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1);
+        // "orderDate" splits before 'D'; "HTTPServer" splits before the 'S'
+        // that starts a lowercase run
+        let boundary = c.is_uppercase()
+            && match previous {
+                Some(p) if p.is_lowercase() || p.is_numeric() => true,
+                Some(p) if p.is_uppercase() => next.is_some_and(|n| n.is_lowercase()),
+                _ => false,
+            };
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.extend(c.to_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// How `normalize_column_names` resolves names that normalize to the same value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NameCollision {
+    /// Fail, listing every group of colliding originals
+    #[default]
+    Error,
+    /// Keep the first column's name and number the rest ("_2", "_3", ...)
+    Suffix,
+}
+
+impl NameCollision {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "error" => Ok(NameCollision::Error),
+            "suffix" => Ok(NameCollision::Suffix),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown collision policy '{}'; expected 'error' or 'suffix'",
+                other
+            ))),
+        }
+    }
+}
+
+/// Column names after moving `order` to the front
+///
+/// Every name in `order` must exist and appear once. Columns not listed are
+/// an error under `UnlistedColumns::Error`.
+pub fn reordered_names(names: &[String], order: &[String], unlisted: UnlistedColumns) -> Result<Vec<String>, InsightoraError> {
+    let mut seen = HashSet::new();
+    for name in order {
+        if !names.contains(name) {
+            return Err(InsightoraError::ValidationError(format!("Cannot reorder: column '{}' not found", name)));
+        }
+        if !seen.insert(name.as_str()) {
+            return Err(InsightoraError::ValidationError(format!("Column '{}' is listed more than once", name)));
+        }
+    }
+    let rest: Vec<&String> = names.iter().filter(|name| !seen.contains(name.as_str())).collect();
+    if unlisted == UnlistedColumns::Error && !rest.is_empty() {
+        let rest: Vec<&str> = rest.iter().map(|name| name.as_str()).collect();
+        return Err(InsightoraError::ValidationError(format!(
+            "Columns missing from the order: {} (pass missing='append' to keep them at the end)",
+            rest.join(", ")
+        )));
+    }
+    Ok(order.iter().cloned().chain(rest.into_iter().cloned()).collect())
+}
+
+/// Column names with `prefix` and `suffix` added to `columns` (default: all)
+pub fn affixed_names(
+    names: &[String],
+    prefix: &str,
+    suffix: &str,
+    columns: Option<&[String]>,
+) -> Result<Vec<String>, InsightoraError> {
+    if let Some(missing) = columns.and_then(|columns| columns.iter().find(|c| !names.contains(c))) {
+        return Err(InsightoraError::ValidationError(format!("Column '{}' not found", missing)));
+    }
+    let renamed: Vec<String> = names
+        .iter()
+        .map(|name| match columns {
+            Some(columns) if !columns.contains(name) => name.clone(),
+            _ => format!("{}{}{}", prefix, name, suffix),
+        })
+        .collect();
+    let mut seen = HashSet::new();
+    if let Some(duplicate) = renamed.iter().find(|name| !seen.insert(name.as_str())) {
+        return Err(InsightoraError::ValidationError(format!(
+            "Renaming produces duplicate column '{}'",
+            duplicate
+        )));
+    }
+    Ok(renamed)
+}
+
+/// Column names converted to `style`
+///
+/// Names that convert to nothing (e.g. "#") become `column_<position>`,
+/// counting from 1. Under `NameCollision::Error` the error lists each
+/// colliding group with all of its original names.
+pub fn normalized_names(names: &[String], style: NameStyle, on_collision: NameCollision) -> Result<Vec<String>, InsightoraError> {
+    let converted: Vec<String> = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let normalized = style.apply(name);
+            if normalized.is_empty() { format!("column_{}", i + 1) } else { normalized }
+        })
+        .collect();
+
+    match on_collision {
+        NameCollision::Error => {
+            let mut groups: Vec<(&str, Vec<&str>)> = Vec::new();
+            for (original, normalized) in names.iter().zip(&converted) {
+                match groups.iter_mut().find(|(name, _)| name == normalized) {
+                    Some((_, originals)) => originals.push(original),
+                    None => groups.push((normalized, vec![original])),
+                }
+            }
+            let collisions: Vec<String> = groups
+                .iter()
+                .filter(|(_, originals)| originals.len() > 1)
+                .map(|(name, originals)| {
+                    let originals: Vec<String> = originals.iter().map(|o| format!("'{}'", o)).collect();
+                    format!("'{}' <- {}", name, originals.join(", "))
+                })
+                .collect();
+            if !collisions.is_empty() {
+                return Err(InsightoraError::ValidationError(format!(
+                    "Normalized column names collide: {} (pass on_collision='suffix' to number them)",
+                    collisions.join("; ")
+                )));
+            }
+            Ok(converted)
+        }
+        NameCollision::Suffix => {
+            // Numbered names skip any name another column normalizes to
+            let reserved: HashSet<&str> = converted.iter().map(|name| name.as_str()).collect();
+            let mut taken = HashSet::new();
+            let mut result = Vec::with_capacity(converted.len());
+            for name in &converted {
+                let mut candidate = name.clone();
+                let mut number = 2;
+                while taken.contains(&candidate) || (candidate != *name && reserved.contains(candidate.as_str())) {
+                    candidate = format!("{}_{}", name, number);
+                    number += 1;
+                }
+                taken.insert(candidate.clone());
+                result.push(candidate);
+            }
+            Ok(result)
+        }
+    }
+}
+
+/// Move the `order` columns to the front
+///
+/// # Arguments
+/// * `df` - DataFrame to reorder
+/// * `order` - Column names in their new order
+/// * `unlisted` - Error on columns missing from `order`, or append them
+///
+/// # Returns
+/// * `Result<DataFrame>` - DataFrame with the same columns in the new order
+pub fn reorder_columns(df: &DataFrame, order: &[String], unlisted: UnlistedColumns) -> Result<DataFrame, InsightoraError> {
+    let names: Vec<String> = df.get_column_names().iter().map(|s| s.to_string()).collect();
+    Ok(df.select(reordered_names(&names, order, unlisted)?)?)
+}
+
+/// Columns `select_columns` keeps
+#[derive(Debug, Clone)]
+pub enum ColumnSelection {
+    /// These columns, in this order
+    Names(Vec<String>),
+    /// Columns whose names the pattern matches anywhere, in frame order
+    Pattern(Regex),
+    /// Columns of these types, in frame order; see `dtype_matches`
+    Dtypes(Vec<String>),
+}
+
+/// Type names `ColumnSelection::Dtypes` accepts, as shown in a result's `dtypes`
+pub const DTYPE_NAMES: &[&str] = &[
+    "Boolean", "UInt8", "UInt16", "UInt32", "UInt64", "Int8", "Int16", "Int32", "Int64", "Float32", "Float64",
+    "Decimal", "String", "Binary", "Date", "Datetime", "Duration", "Time", "Categorical", "List", "Struct", "Null",
+];
+
+/// Whether `dtype` has the type `name`: its full name as shown in a result's
+/// `dtypes` ("Datetime(Microseconds, None)") or the name without parameters
+/// ("Datetime" for every datetime column)
+pub fn dtype_matches(dtype: &DataType, name: &str) -> bool {
+    let full = format!("{:?}", dtype);
+    full == name || full.split('(').next() == Some(name)
+}
+
+/// Keep the selected columns
+///
+/// # Returns
+/// * `Result<DataFrame>` - The selected columns; ValidationError for a name
+///   that isn't a column (or is listed twice) or an unknown type name. A
+///   pattern or type list matching nothing selects no columns.
+///
+/// # Example
+/// ```no_run
+/// use insightora_core::api::{select_columns, ColumnSelection, ParallelCsvParser};
+///
+/// let df = ParallelCsvParser::new().parse("metrics.csv").unwrap();
+/// let numeric = ColumnSelection::Dtypes(vec!["Int64".to_string(), "Float64".to_string()]);
+/// let metrics = select_columns(&df, &numeric).unwrap();
+/// ```
+pub fn select_columns(df: &DataFrame, selection: &ColumnSelection) -> Result<DataFrame, InsightoraError> {
+    let columns = df.get_columns();
+    let names: Vec<&str> = match selection {
+        ColumnSelection::Names(names) => {
+            check_columns(df, names)?;
+            let mut seen = HashSet::new();
+            if let Some(twice) = names.iter().find(|name| !seen.insert(name.as_str())) {
+                return Err(InsightoraError::ValidationError(format!("Column '{}' is selected twice", twice)));
+            }
+            names.iter().map(String::as_str).collect()
+        }
+        ColumnSelection::Pattern(pattern) => {
+            columns.iter().map(|series| series.name()).filter(|name| pattern.is_match(name)).collect()
+        }
+        ColumnSelection::Dtypes(dtypes) => {
+            if let Some(unknown) = dtypes.iter().find(|name| !DTYPE_NAMES.contains(&name.split('(').next().unwrap_or_default())) {
+                return Err(InsightoraError::ValidationError(format!(
+                    "Unknown dtype '{}'; expected one of {}",
+                    unknown,
+                    DTYPE_NAMES.join(", ")
+                )));
+            }
+            columns
+                .iter()
+                .filter(|series| dtypes.iter().any(|name| dtype_matches(series.dtype(), name)))
+                .map(|series| series.name())
+                .collect()
+        }
+    };
+    Ok(df.select(names)?)
+}
+
+/// Remove `columns`, every one of which must exist
+pub fn drop_columns(df: &DataFrame, columns: &[String]) -> Result<DataFrame, InsightoraError> {
+    check_columns(df, columns)?;
+    Ok(df.drop_many(columns))
+}
+
+/// ValidationError naming the first of `names` that isn't a column of `df`
+fn check_columns(df: &DataFrame, names: &[String]) -> Result<(), InsightoraError> {
+    match names.iter().find(|name| df.column(name).is_err()) {
+        Some(missing) => Err(InsightoraError::ValidationError(format!(
+            "Column '{}' not found; columns are {}",
+            missing,
+            df.get_column_names().join(", ")
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Prepend `prefix` to the names of `columns` (default: all)
+pub fn add_prefix(df: &DataFrame, prefix: &str, columns: Option<&[String]>) -> Result<DataFrame, InsightoraError> {
+    rename_all(df, |names| affixed_names(names, prefix, "", columns))
+}
+
+/// Append `suffix` to the names of `columns` (default: all)
+pub fn add_suffix(df: &DataFrame, suffix: &str, columns: Option<&[String]>) -> Result<DataFrame, InsightoraError> {
+    rename_all(df, |names| affixed_names(names, "", suffix, columns))
+}
+
+/// Convert every column name to `style`
+///
+/// # Arguments
+/// * `df` - DataFrame to rename
+/// * `style` - Target naming convention
+/// * `on_collision` - Error (listing all colliding originals) or number the duplicates
+///
+/// # Returns
+/// * `Result<DataFrame>` - DataFrame with normalized names
+pub fn normalize_column_names(df: &DataFrame, style: NameStyle, on_collision: NameCollision) -> Result<DataFrame, InsightoraError> {
+    rename_all(df, |names| normalized_names(names, style, on_collision))
+}
+
+fn rename_all(
+    df: &DataFrame,
+    rename: impl FnOnce(&[String]) -> Result<Vec<String>, InsightoraError>,
+) -> Result<DataFrame, InsightoraError> {
+    let names: Vec<String> = df.get_column_names().iter().map(|s| s.to_string()).collect();
+    let renamed = rename(&names)?;
+    let mut df = df.clone();
+    df.set_column_names(&renamed)?;
+    Ok(df)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(values: &[&str], collation: &Collation) -> Vec<String> {
+        let df = df!("name" => values).unwrap();
+        let result = sort_data(&df, &["name".to_string()], &[false], collation).unwrap();
+        result.column("name").unwrap().str().unwrap()
+            .into_no_null_iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_filter_rows_drops_null_conditions() {
+        let df = df!(
+            "id" => [1, 2, 3, 4, 5],
+            "amount" => [Some(50.0), None, Some(150.0), Some(300.0), None],
+            "country" => [Some("DE"), Some("DE"), None, Some("FR"), Some("AT")],
+        )
+        .unwrap();
+        let ids = |condition: &str| -> Vec<i32> {
+            let kept = filter_rows(&df, &Predicate::parse(condition).unwrap()).unwrap();
+            kept.column("id").unwrap().i32().unwrap().into_no_null_iter().collect()
+        };
+
+        // Null amounts and countries never compare equal or unequal
+        assert_eq!(ids("amount > 100"), [3, 4]);
+        assert_eq!(ids("country != 'DE'"), [4, 5]);
+        // false and null is false; true or null is true
+        assert_eq!(ids("country == 'DE' and amount > 10"), [1]);
+        assert_eq!(ids("country == 'AT' or amount > 100"), [3, 4, 5]);
+        assert_eq!(ids("amount is null or country is null"), [2, 3, 5]);
+        assert_eq!(ids("1 == 1").len(), 5);
+
+        let unknown = filter_rows(&df, &Predicate::parse("amount > 1 and total < 3").unwrap()).unwrap_err();
+        assert!(unknown.to_string().contains("unknown column 'total' at position 15"), "{}", unknown);
+        assert!(filter_rows(&df, &Predicate::parse("amount + 1").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_filter_rows_compares_ordered_categories_by_position() {
+        use crate::dataframe::transformations::{set_category_order, UnknownCategory};
+
+        let df = df!(
+            "id" => [1, 2, 3, 4, 5],
+            "severity" => [Some("high"), Some("low"), None, Some("medium"), Some("low")],
+        )
+        .unwrap();
+        let order = ["low".to_string(), "medium".to_string(), "high".to_string()];
+        let df = set_category_order(&df, "severity", &order, UnknownCategory::Error).unwrap();
+        let ids = |condition: &str| -> Vec<i32> {
+            let kept = filter_rows(&df, &Predicate::parse(condition).unwrap()).unwrap();
+            kept.column("id").unwrap().i32().unwrap().into_no_null_iter().collect()
+        };
+
+        // As text, 'high' < 'low' < 'medium'
+        assert_eq!(ids("severity < 'medium'"), [2, 5]);
+        assert_eq!(ids("severity >= 'medium'"), [1, 4]);
+        assert_eq!(ids("'medium' > severity"), [2, 5]);
+        assert_eq!(ids("severity == 'high' or severity in ['low']"), [1, 2, 5]);
+
+        let unknown = filter_rows(&df, &Predicate::parse("severity > 'urgent'").unwrap()).unwrap_err();
+        assert!(unknown.to_string().contains("'urgent' is not a category of the ordered column 'severity'"), "{}", unknown);
+    }
+
+    #[test]
+    fn test_sort_rows_is_stable_with_null_placement() {
+        let df = df!(
+            "id" => [1, 2, 3, 4, 5, 6],
+            "name" => [Some("b"), Some("B"), None, Some("a"), Some("b"), Some("A")],
+            "score" => [Some(2), Some(1), Some(3), None, Some(2), Some(1)],
+        )
+        .unwrap();
+        let ids = |by: &[&str], descending: &[bool], nulls_last: bool, case_insensitive: bool| -> Vec<i32> {
+            let by: Vec<String> = by.iter().map(|name| name.to_string()).collect();
+            let sorted = sort_rows(&df, &by, descending, nulls_last, case_insensitive).unwrap();
+            sorted.column("id").unwrap().i32().unwrap().into_no_null_iter().collect()
+        };
+
+        // Byte order puts capitals first; ties (rows 1 and 5) keep file order
+        assert_eq!(ids(&["name"], &[false], true, false), [6, 2, 4, 1, 5, 3]);
+        assert_eq!(ids(&["name"], &[false], false, false), [3, 6, 2, 4, 1, 5]);
+        assert_eq!(ids(&["name"], &[false], true, true), [4, 6, 1, 2, 5, 3]);
+        // Nulls stay last when descending, and each key has its own direction
+        assert_eq!(ids(&["score"], &[true], true, false), [3, 1, 5, 2, 6, 4]);
+        assert_eq!(ids(&["score", "id"], &[false, true], true, false), [6, 2, 5, 1, 3, 4]);
+
+        let sorted = sort_rows(&df, &["score".to_string()], &[], true, false).unwrap();
+        assert_eq!(sorted.get_column_names(), ["id", "name", "score"]);
+        let missing = sort_rows(&df, &["total".to_string()], &[], true, false).unwrap_err();
+        assert!(missing.to_string().contains("'total'"), "{}", missing);
+    }
+
+    #[test]
+    fn test_natural_sort_mixed_width_numbers() {
+        let values = ["item10", "item2", "item1", "item002", "item100", "item20", "item"];
+        assert_eq!(
+            sorted(&values, &Collation::Natural),
+            vec!["item", "item1", "item2", "item002", "item10", "item20", "item100"]
+        );
+    }
+
+    #[test]
+    fn test_binary_sort_is_bytewise() {
+        let values = ["item10", "item2", "item1"];
+        assert_eq!(sorted(&values, &Collation::Binary), vec!["item1", "item10", "item2"]);
+    }
+
+    #[test]
+    fn test_swedish_locale_sorts_after_z() {
+        let collation = Collation::from_options("locale", Some("sv_SE")).unwrap();
+        let values = ["Öberg", "Zetterlund", "Andersson", "Åström", "Ärlig"];
+        assert_eq!(
+            sorted(&values, &collation),
+            vec!["Andersson", "Zetterlund", "Åström", "Ärlig", "Öberg"]
+        );
+    }
+
+    #[test]
+    fn test_german_locale_folds_umlauts() {
+        let collation = Collation::from_options("locale", Some("de_DE")).unwrap();
+        let values = ["Zoll", "Ärger", "Apfel", "Bär", "Straße", "Strasse"];
+        assert_eq!(
+            sorted(&values, &collation),
+            vec!["Apfel", "Ärger", "Bär", "Strasse", "Straße", "Zoll"]
+        );
+    }
+
+    #[test]
+    fn test_multi_key_sort_with_nulls_last() {
+        let df = df!(
+            "group" => &["b", "a", "b", "a"],
+            "value" => &[Some(2), None, Some(1), Some(3)]
+        ).unwrap();
+        let result = sort_data(
+            &df,
+            &["group".to_string(), "value".to_string()],
+            &[false, true],
+            &Collation::Binary,
+        ).unwrap();
+        let values: Vec<Option<i32>> = result.column("value").unwrap().i32().unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(values, vec![Some(3), None, Some(2), Some(1)]);
+    }
+
+    #[test]
+    fn test_unknown_collation_and_locale() {
+        assert!(Collation::from_options("fuzzy", None).is_err());
+        assert!(Collation::from_options("locale", None).is_err());
+        assert!(Collation::from_options("locale", Some("xx_XX")).is_err());
+    }
+
+    fn prices() -> (DataFrame, DataFrame) {
+        let left = df! {
+            "price" => [Some(0.1 + 0.2), Some(1.0), None, Some(f64::NAN)],
+            "sku" => ["a", "b", "c", "d"],
+        }
+        .unwrap();
+        let right = df! {
+            "price" => [Some(0.3), Some(1.0), None, Some(f64::NAN)],
+            "label" => ["three", "one", "missing", "nan"],
+        }
+        .unwrap();
+        (left, right)
+    }
+
+    fn skus(df: &DataFrame) -> Vec<String> {
+        df.column("sku").unwrap().str().unwrap()
+            .into_no_null_iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_join_gains_matches_only_with_precision() {
+        let (left, right) = prices();
+        let on = vec!["price".to_string()];
+
+        let (exact, report) = join_dataframes(&left, &right, &on, &on, JoinType::Inner, &FloatKeyOptions::default(), JoinEngine::Auto).unwrap();
+        assert!(!skus(&exact).contains(&"a".to_string()));
+        assert_eq!(report.rows_affected, 0);
+
+        let options = FloatKeyOptions {
+            precision: Some(FloatPrecision::Decimals(6)),
+            nan_equal: false,
+        };
+        let (joined, report) = join_dataframes(&left, &right, &on, &on, JoinType::Inner, &options, JoinEngine::Auto).unwrap();
+        // 0.1 + 0.2 now matches 0.3; nulls and NaN (nan_equal=false) still don't match
+        assert_eq!(skus(&joined), vec!["a", "b"]);
+        assert_eq!(report.rows_affected, 1);
+        // Stored values are untouched on both sides
+        let prices = joined.column("price").unwrap().f64().unwrap();
+        assert_eq!(prices.get(0), Some(0.1 + 0.2));
+        let right_prices = joined.column("price_right").unwrap().f64().unwrap();
+        assert_eq!(right_prices.get(0), Some(0.3));
+        assert!(key_column_names(&joined).is_empty());
+    }
+
+    #[test]
+    fn test_join_nan_equal() {
+        let (left, right) = prices();
+        let on = vec!["price".to_string()];
+        let options = FloatKeyOptions {
+            precision: Some(FloatPrecision::Step(0.5)),
+            nan_equal: true,
+        };
+        let (joined, report) = join_dataframes(&left, &right, &on, &on, JoinType::Inner, &options, JoinEngine::Auto).unwrap();
+        assert_eq!(skus(&joined), vec!["a", "b", "d"]);
+        assert_eq!(report.rows_affected, 1);
+    }
+
+    fn canonical(df: &DataFrame) -> DataFrame {
+        let by: Vec<Expr> = df.get_column_names().into_iter().map(col).collect();
+        let descending = vec![false; by.len()];
+        df.clone().lazy().sort_by_exprs(by, descending, true, false).collect().unwrap()
+    }
+
+    #[test]
+    fn test_partitioned_join_matches_in_memory() {
+        let left = df! {
+            "id" => [Some(1i64), Some(2), Some(2), None, Some(5), Some(7), Some(9), Some(9)],
+            "qty" => [10, 20, 21, 30, 50, 70, 90, 91],
+        }
+        .unwrap();
+        let right = df! {
+            "id" => [Some(2i64), Some(2), Some(5), None, Some(8), Some(9)],
+            "name" => ["two", "deux", "five", "none", "eight", "nine"],
+        }
+        .unwrap();
+        let on = vec!["id".to_string()];
+        let options = FloatKeyOptions::default();
+
+        for how in [JoinType::Inner, JoinType::Left, JoinType::Outer { coalesce: true }] {
+            let (expected, memory) = join_dataframes(&left, &right, &on, &on, how.clone(), &options, JoinEngine::InMemory).unwrap();
+            assert_eq!(memory.engine, JoinEngine::InMemory);
+            assert_eq!(memory.partitions, 1);
+
+            let engine = JoinEngine::Partitioned { partitions: Some(4) };
+            let (joined, report) = join_dataframes(&left, &right, &on, &on, how.clone(), &options, engine).unwrap();
+            assert_eq!(report.partitions, 4);
+            assert_eq!(report.engine.name(), "partitioned");
+            assert_eq!(joined.get_column_names(), expected.get_column_names());
+            assert!(canonical(&joined).equals_missing(&canonical(&expected)), "{:?}: {} vs {}", how, joined, expected);
+        }
+    }
+
+    #[test]
+    fn test_partitioned_join_keeps_quantized_matches_together() {
+        let (left, right) = prices();
+        let on = vec!["price".to_string()];
+        let options = FloatKeyOptions {
+            precision: Some(FloatPrecision::Decimals(6)),
+            nan_equal: true,
+        };
+        let (expected, memory) = join_dataframes(&left, &right, &on, &on, JoinType::Inner, &options, JoinEngine::InMemory).unwrap();
+        let engine = JoinEngine::Partitioned { partitions: Some(8) };
+        let (joined, report) = join_dataframes(&left, &right, &on, &on, JoinType::Inner, &options, engine).unwrap();
+
+        assert_eq!(report.rows_affected, memory.rows_affected);
+        assert_eq!(skus(&joined), skus(&expected));
+    }
+
+    #[test]
+    fn test_auto_engine_keeps_small_joins_in_memory() {
+        let (left, right) = prices();
+        let on = vec!["price".to_string()];
+        let (_, report) = join_dataframes(&left, &right, &on, &on, JoinType::Inner, &FloatKeyOptions::default(), JoinEngine::Auto).unwrap();
+        assert_eq!(report.engine, JoinEngine::InMemory);
+
+        let engine = JoinEngine::from_name("partitioned").unwrap();
+        let (_, report) = join_dataframes(&left, &right, &on, &on, JoinType::Inner, &FloatKeyOptions::default(), engine).unwrap();
+        // Partition count derived from the memory limit, never below two
+        assert_eq!(report.partitions, 2);
+        assert!(JoinEngine::from_name("sideways").is_err());
+    }
+
+    #[test]
+    fn test_join_rows_types_null_keys_and_suffix() {
+        let left = df! {
+            "region" => [Some("EU"), Some("EU"), None, Some("US")],
+            "year" => [Some(2024i64), None, Some(2024), Some(2024)],
+            "amount" => [1.0, 2.0, 3.0, 4.0],
+        }
+        .unwrap();
+        let right = df! {
+            "region" => [Some("EU"), Some("EU"), None, None, Some("US")],
+            "year" => [Some(2024.0), None, Some(2024.0), Some(2024.0), Some(2024.0)],
+            "amount" => [10.0, 20.0, 30.0, 40.0, 50.0],
+        }
+        .unwrap();
+        let on = vec!["region".to_string(), "year".to_string()];
+        let join = |how: JoinHow| {
+            let options = JoinOptions { how, ..Default::default() };
+            join_rows(&left, &right, &on, &on, &options).unwrap()
+        };
+        let amounts = |df: &DataFrame| -> Vec<f64> { df.column("amount").unwrap().f64().unwrap().into_no_null_iter().collect() };
+
+        // Int64 and Float64 years compare as Float64; rows with a null key part never match
+        let (inner, counts) = join(JoinHow::Inner);
+        assert_eq!(inner.get_column_names(), ["region", "year", "amount", "amount_right"]);
+        assert_eq!(inner.column("year").unwrap().dtype(), &DataType::Float64);
+        assert_eq!(counts, JoinCounts { left_rows: 4, right_rows: 5, rows: 2, columns: 4 });
+        assert_eq!(join(JoinHow::Left).1.rows, 4);
+        assert_eq!(join(JoinHow::Outer).1.rows, 7);
+        assert_eq!(amounts(&join(JoinHow::Semi).0), [1.0, 4.0]);
+        let (anti, counts) = join(JoinHow::Anti);
+        assert_eq!(amounts(&anti), [2.0, 3.0]);
+        assert_eq!(anti.get_column_names(), ["region", "year", "amount"]);
+        assert_eq!((counts.rows, counts.columns), (2, 3));
+
+        let strict = JoinOptions { strict: true, ..Default::default() };
+        let err = join_rows(&left, &right, &on, &on, &strict).unwrap_err().to_string();
+        assert!(err.contains("'year' (i64)") || err.contains("'year' (Int64)"), "{}", err);
+        let by_text = ["region".to_string()];
+        let by_number = ["amount".to_string()];
+        assert!(join_rows(&left, &right, &by_text, &by_number, &JoinOptions::default()).is_err());
+        let unsuffixed = JoinOptions { suffix: String::new(), ..Default::default() };
+        assert!(join_rows(&left, &right, &on, &on, &unsuffixed).unwrap_err().to_string().contains("'amount'"));
+        assert!(JoinHow::from_name("cross").is_err());
+    }
+
+    #[test]
+    fn test_join_rows_keeps_integer_keys_beyond_float_precision() {
+        let big = 1i64 << 53;
+        let left = df! { "id" => [big, big + 1], "side" => ["a", "b"] }.unwrap();
+        let right = df! { "id" => [big as f64], "label" => ["x"] }.unwrap();
+        let on = ["id".to_string()];
+
+        // 2^53 + 1 would round onto 2^53 as a float; whole floats compare as integers
+        let (joined, counts) = join_rows(&left, &right, &on, &on, &JoinOptions::default()).unwrap();
+        assert_eq!(counts.rows, 1);
+        assert_eq!(joined.column("id").unwrap().dtype(), &DataType::Int64);
+        assert_eq!(joined.column("side").unwrap().str().unwrap().get(0), Some("a"));
+
+        let fractional = df! { "id" => [big as f64, 0.5] }.unwrap();
+        let err = join_rows(&left, &fractional, &on, &on, &JoinOptions::default()).unwrap_err().to_string();
+        assert!(err.contains("2^53"), "{}", err);
+
+        // UInt64 keys beyond i64::MAX keep their values instead of turning null
+        let huge = (1u64 << 63) + 1;
+        let unsigned = df! { "id" => [huge, 5] }.unwrap();
+        let (joined, _) = join_rows(&unsigned, &df! { "id" => [5i64] }.unwrap(), &on, &on, &JoinOptions::default()).unwrap();
+        assert_eq!(joined.column("id").unwrap().u64().unwrap().get(0), Some(5));
+        let floats = df! { "id" => [(1u64 << 63) as f64, 5.0] }.unwrap();
+        let (joined, _) = join_rows(&unsigned, &floats, &on, &on, &JoinOptions::default()).unwrap();
+        assert_eq!(joined.column("id").unwrap().dtype(), &DataType::UInt64);
+        assert_eq!(joined.height(), 1);
+        let negative = df! { "id" => [-1i64] }.unwrap();
+        assert!(join_rows(&unsigned, &negative, &on, &on, &JoinOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_concat_frames_vertical_and_horizontal() {
+        let jan = df! { "id" => [1i64, 2], "amount" => [1.5, 2.5] }.unwrap();
+        let feb = df! { "amount" => [3.5], "id" => [3i64] }.unwrap();
+        let mar = df! { "id" => [4i64], "note" => ["late"] }.unwrap();
+
+        // Columns are matched by name, not position
+        let combined = concat_frames(&[jan.clone(), feb.clone()], &ConcatOptions::default()).unwrap();
+        assert_eq!(combined.get_column_names(), ["id", "amount"]);
+        assert_eq!(combined.column("amount").unwrap().f64().unwrap().get(2), Some(3.5));
+        assert_eq!(combined.n_chunks(), 1);
+
+        let err = concat_frames(&[jan.clone(), mar.clone()], &ConcatOptions::default()).unwrap_err().to_string();
+        assert!(err.contains("Frame 1") && err.contains("'amount' is missing") && err.contains("'note' is extra"), "{}", err);
+        let relaxed = ConcatOptions { relaxed: true, ..Default::default() };
+        let combined = concat_frames(&[jan.clone(), mar.clone()], &relaxed).unwrap();
+        assert_eq!(combined.get_column_names(), ["id", "amount", "note"]);
+        assert_eq!((combined.column("amount").unwrap().null_count(), combined.column("note").unwrap().null_count()), (1, 2));
+
+        let horizontal = ConcatOptions { how: ConcatHow::Horizontal, ..Default::default() };
+        let labels = df! { "label" => ["a", "b"] }.unwrap();
+        let wide = concat_frames(&[jan.clone(), labels], &horizontal).unwrap();
+        assert_eq!(wide.get_column_names(), ["id", "amount", "label"]);
+        assert!(concat_frames(&[jan.clone(), feb], &horizontal).unwrap_err().to_string().contains("equal row counts"));
+        assert!(concat_frames(&[jan.clone(), jan.clone()], &horizontal).unwrap_err().to_string().contains("'id'"));
+        let suffixed = ConcatOptions { suffix: Some("_".to_string()), ..horizontal };
+        let wide = concat_frames(&[jan.clone(), jan.clone(), jan], &suffixed).unwrap();
+        assert_eq!(wide.get_column_names(), ["id", "amount", "id_1", "amount_1", "id_2", "amount_2"]);
+        assert!(concat_frames(&[], &ConcatOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_invalid_float_step() {
+        let (left, right) = prices();
+        let on = vec!["price".to_string()];
+        let options = FloatKeyOptions {
+            precision: Some(FloatPrecision::Step(0.0)),
+            nan_equal: true,
+        };
+        assert!(join_dataframes(&left, &right, &on, &on, JoinType::Inner, &options, JoinEngine::Auto).is_err());
+    }
+
+    fn strings(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_reorder_and_affix_columns() {
+        let df = df!("a" => [1], "b" => [2], "c" => [3]).unwrap();
+        let order = strings(&["c", "a"]);
+        assert!(reorder_columns(&df, &order, UnlistedColumns::Error).is_err());
+        let reordered = reorder_columns(&df, &order, UnlistedColumns::Append).unwrap();
+        assert_eq!(reordered.get_column_names(), &["c", "a", "b"]);
+        assert!(reorder_columns(&df, &strings(&["c", "x"]), UnlistedColumns::Append).is_err());
+
+        let prefixed = add_prefix(&df, "src_", Some(&strings(&["a"]))).unwrap();
+        assert_eq!(prefixed.get_column_names(), &["src_a", "b", "c"]);
+        let suffixed = add_suffix(&df, "_x", None).unwrap();
+        assert_eq!(suffixed.get_column_names(), &["a_x", "b_x", "c_x"]);
+        let clash = df!("a" => [1], "x_a" => [2]).unwrap();
+        assert!(add_prefix(&clash, "x_", Some(&strings(&["a"]))).is_err());
+    }
+
+    #[test]
+    fn test_select_and_drop_columns() {
+        let at = polars::export::chrono::NaiveDate::from_ymd_opt(2024, 5, 1).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        let df = df!(
+            "id" => [1i64],
+            "metric_cpu" => [0.5],
+            "metric_mem" => [2i32],
+            "host" => ["a"],
+            "seen" => [at],
+        )
+        .unwrap();
+        let names = |selection: ColumnSelection| -> Vec<String> {
+            let selected = select_columns(&df, &selection).unwrap();
+            selected.get_column_names().iter().map(|s| s.to_string()).collect()
+        };
+        assert_eq!(names(ColumnSelection::Names(strings(&["host", "id"]))), ["host", "id"]);
+        assert_eq!(names(ColumnSelection::Pattern(Regex::new("^metric_").unwrap())), ["metric_cpu", "metric_mem"]);
+        assert_eq!(names(ColumnSelection::Dtypes(strings(&["Int64", "Float64"]))), ["id", "metric_cpu"]);
+        assert_eq!(names(ColumnSelection::Dtypes(strings(&["Datetime"]))), ["seen"]);
+
+        let err = select_columns(&df, &ColumnSelection::Names(strings(&["cpu"]))).unwrap_err().to_string();
+        assert!(err.contains("'cpu' not found; columns are id, metric_cpu"), "{}", err);
+        assert!(select_columns(&df, &ColumnSelection::Names(strings(&["id", "id"]))).is_err());
+        assert!(select_columns(&df, &ColumnSelection::Dtypes(strings(&["Integer"]))).is_err());
+
+        assert_eq!(drop_columns(&df, &strings(&["seen", "id"])).unwrap().get_column_names(), ["metric_cpu", "metric_mem", "host"]);
+        assert!(drop_columns(&df, &strings(&["gpu"])).is_err());
+    }
+
+    #[test]
+    fn test_normalize_column_names() {
+        let names = strings(&["Order Date", "customerID", "HTTPStatus", "Address2Line", "  Total  ", "#"]);
+        assert_eq!(
+            normalized_names(&names, NameStyle::Snake, NameCollision::Error).unwrap(),
+            strings(&["order_date", "customer_id", "http_status", "address2_line", "total", "column_6"])
+        );
+        assert_eq!(
+            normalized_names(&names[..2], NameStyle::Camel, NameCollision::Error).unwrap(),
+            strings(&["orderDate", "customerId"])
+        );
+        assert_eq!(NameStyle::Lower.apply("  Order Date "), "order date");
+
+        let colliding = strings(&["Order Date", "order_date", "ORDER-DATE", "id", "ID", "order_date_2"]);
+        let message = normalized_names(&colliding, NameStyle::Snake, NameCollision::Error).unwrap_err().to_string();
+        assert!(message.contains("'Order Date', 'order_date', 'ORDER-DATE'"));
+        assert!(message.contains("'id', 'ID'"));
+        // Numbering skips names another column already normalizes to
+        assert_eq!(
+            normalized_names(&colliding, NameStyle::Snake, NameCollision::Suffix).unwrap(),
+            strings(&["order_date", "order_date_3", "order_date_4", "id", "id_2", "order_date_2"])
+        );
+    }
+
+    #[test]
+    fn test_suggest_join_keys() {
+        let orders = df!(
+            "OrderID" => (1..=40).collect::<Vec<i64>>(),
+            "CustomerID" => (0..40).map(|i| format!("C{}", i % 8)).collect::<Vec<_>>(),
+            "status" => (0..40).map(|i| if i % 2 == 0 { "open" } else { "closed" }).collect::<Vec<_>>()
+        )
+        .unwrap();
+        let customers = df!(
+            "customer_id" => (0..10).map(|i| format!("C{}", i)).collect::<Vec<_>>(),
+            "state" => (0..10).map(|i| if i < 5 { "open" } else { "closed" }).collect::<Vec<_>>()
+        )
+        .unwrap();
+
+        let candidates = suggest_join_keys(&orders, &customers, 3).unwrap();
+        assert!(candidates.len() <= 3);
+        let best = &candidates[0];
+        assert_eq!((best.left.as_slice(), best.right.as_slice()), (&["CustomerID".to_string()][..], &["customer_id".to_string()][..]));
+        assert_eq!(best.estimated_match_rate, 1.0);
+        assert_eq!(best.right_uniqueness, 1.0);
+        assert!((best.value_overlap - 0.8).abs() < 1e-9);
+
+        // Nothing shared, nothing suggested
+        let unrelated = df!("code" => ["x", "y"]).unwrap();
+        assert!(suggest_join_keys(&orders, &unrelated, 5).unwrap().is_empty());
+        assert_eq!(column_name_similarity("CustomerID", "customer_id"), 1.0);
+    }
+}
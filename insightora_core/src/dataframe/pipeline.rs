@@ -0,0 +1,716 @@
+// Prepared pipelines
+// Operation lists validated and compiled once against a declared schema, then run against many CSV files
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use polars::prelude::*;
+use crate::dataframe::expressions::{ColumnExpression, CompiledExpression, NullArithmetic, Predicate};
+use crate::dataframe::operations::{self, NameCollision, NameStyle, UnlistedColumns};
+use crate::dataframe::transformations::{self, Assignment, ColumnMapping};
+use crate::error::InsightoraError;
+use crate::io::csv_parser::{
+    write_csv_partitioned, CsvParserConfig, CsvPartitionOptions, CsvWriteOptions, ParallelCsvParser, PartitionedCsvReport,
+};
+use crate::io::parquet_writer::{write_parquet, ParquetWriteReport, SchemaEnforcement, SchemaEnforcementReport};
+use crate::utils::progress::ProgressReporter;
+use crate::utils::sandbox::check_path_allowed;
+use crate::utils::warnings::{AggregatedWarning, WarningCollector};
+
+/// Parse a dtype name as printed in schemas ("i64", "f64", "str", "bool",
+/// "date", "datetime[μs]", "duration[ms]", "decimal[38,2]", ...) or by
+/// `infer_csv_schema` ("Int64", "String", "Datetime(Microseconds, None)", ...)
+pub fn dtype_from_name(name: &str) -> Result<DataType, InsightoraError> {
+    let unit = |text: &str| match text {
+        "ms" | "milliseconds" => Some(TimeUnit::Milliseconds),
+        "" | "us" | "μs" | "microseconds" => Some(TimeUnit::Microseconds),
+        "ns" | "nanoseconds" => Some(TimeUnit::Nanoseconds),
+        _ => None,
+    };
+    let lower = name.trim().to_ascii_lowercase();
+    let bracketed = |prefix: &str| {
+        lower
+            .strip_prefix(prefix)
+            .and_then(|rest| match rest.chars().next() {
+                None => Some(rest),
+                Some('[') => rest[1..].strip_suffix(']'),
+                // Debug form; only zone-less datetimes
+                Some('(') => rest[1..].strip_suffix(')').map(|inner| inner.strip_suffix(", none").unwrap_or(inner)),
+                _ => None,
+            })
+            .and_then(unit)
+    };
+    // decimal[precision,scale]; precision is at most 38
+    let decimal = |text: &str| {
+        let (precision, scale) = text.strip_prefix("decimal[")?.strip_suffix(']')?.split_once(',')?;
+        let precision: usize = precision.trim().parse().ok()?;
+        let scale: usize = scale.trim().parse().ok()?;
+        if precision == 0 || precision > 38 || scale > precision {
+            return None;
+        }
+        Some(DataType::Decimal(Some(precision), Some(scale)))
+    };
+    let dtype = match lower.as_str() {
+        "i8" | "int8" => Some(DataType::Int8),
+        "i16" | "int16" => Some(DataType::Int16),
+        "i32" | "int32" => Some(DataType::Int32),
+        "i64" | "int64" | "int" => Some(DataType::Int64),
+        "u8" | "uint8" => Some(DataType::UInt8),
+        "u16" | "uint16" => Some(DataType::UInt16),
+        "u32" | "uint32" => Some(DataType::UInt32),
+        "u64" | "uint64" => Some(DataType::UInt64),
+        "f32" | "float32" => Some(DataType::Float32),
+        "f64" | "float64" | "float" => Some(DataType::Float64),
+        "str" | "string" | "utf8" => Some(DataType::String),
+        "bool" | "boolean" => Some(DataType::Boolean),
+        "date" => Some(DataType::Date),
+        _ => bracketed("datetime")
+            .map(|unit| DataType::Datetime(unit, None))
+            .or_else(|| bracketed("duration").map(DataType::Duration))
+            .or_else(|| decimal(&lower)),
+    };
+    dtype.ok_or_else(|| {
+        InsightoraError::ValidationError(format!(
+            "Unknown dtype '{}'; expected e.g. 'i64', 'f64', 'str', 'bool', 'date', 'datetime[μs]' or 'decimal[38,2]'",
+            name
+        ))
+    })
+}
+
+// ============================================================================
+// Pipeline
+// ============================================================================
+
+/// One operation of a pipeline
+#[derive(Debug, Clone)]
+pub enum PipelineStep {
+    Rename(ColumnMapping),
+    /// Keep these columns, in this order
+    Select(Vec<String>),
+    Drop(Vec<String>),
+    AddColumns { expressions: Vec<ColumnExpression>, nulls: NullArithmetic },
+    /// Assign to existing columns on the rows matching `predicate` (see
+    /// `transformations::update_where`)
+    UpdateWhere { predicate: Predicate, assignments: Vec<(String, Assignment)> },
+    /// Drop rows with a null in any of these columns (default: any column)
+    DropNulls(Option<Vec<String>>),
+    DropDuplicates { subset: Option<Vec<String>>, keep: UniqueKeepStrategy },
+    Sort { by: Vec<String>, descending: bool },
+    /// Move these columns to the front (see `operations::reorder_columns`)
+    Reorder { order: Vec<String>, unlisted: UnlistedColumns },
+    /// Prefix the names of these columns (default: all)
+    AddPrefix { prefix: String, columns: Option<Vec<String>> },
+    AddSuffix { suffix: String, columns: Option<Vec<String>> },
+    NormalizeNames { style: NameStyle, on_collision: NameCollision },
+}
+
+impl PipelineStep {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PipelineStep::Rename(_) => "rename",
+            PipelineStep::Select(_) => "select",
+            PipelineStep::Drop(_) => "drop",
+            PipelineStep::AddColumns { .. } => "add_columns",
+            PipelineStep::UpdateWhere { .. } => "update_where",
+            PipelineStep::DropNulls(_) => "drop_nulls",
+            PipelineStep::DropDuplicates { .. } => "drop_duplicates",
+            PipelineStep::Sort { .. } => "sort",
+            PipelineStep::Reorder { .. } => "reorder_columns",
+            PipelineStep::AddPrefix { .. } => "add_prefix",
+            PipelineStep::AddSuffix { .. } => "add_suffix",
+            PipelineStep::NormalizeNames { .. } => "normalize_column_names",
+        }
+    }
+}
+
+/// Ordered list of operations applied to a parsed file
+///
+/// # Example
+/// ```no_run
+/// use insightora_core::api::*;
+/// use polars::prelude::DataType;
+///
+/// # fn main() -> insightora_core::api::Result<()> {
+/// let prepared = Pipeline::new()
+///     .then(PipelineStep::AddColumns {
+///         expressions: vec![ColumnExpression::parse("total", "price * quantity")?],
+///         nulls: NullArithmetic::Zero,
+///     })
+///     .then(PipelineStep::Sort { by: vec!["total".into()], descending: true })
+///     .prepare(
+///         &[("price".into(), DataType::Float64), ("quantity".into(), DataType::Int64)],
+///         CsvParserConfig::default(),
+///     )?;
+/// for day in ["orders-01.csv", "orders-02.csv"] {
+///     let (df, report) = prepared.run(day)?;
+///     println!("{}: {} -> {} rows", report.file, report.rows_in, report.rows_out);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Pipeline {
+    steps: Vec<PipelineStep>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a step (builder style)
+    pub fn then(mut self, step: PipelineStep) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    pub fn push(&mut self, step: PipelineStep) {
+        self.steps.push(step);
+    }
+
+    pub fn steps(&self) -> &[PipelineStep] {
+        &self.steps
+    }
+
+    /// Validate every step against `schema` and build its expressions once
+    ///
+    /// Each step is checked against the columns produced by the steps before
+    /// it, then run on an empty frame to catch type errors (e.g. arithmetic
+    /// on a string column) and learn the output schema.
+    ///
+    /// # Arguments
+    /// * `schema` - Columns every input file must have, with the types they
+    ///   are converted to; other columns in a file are ignored
+    /// * `csv` - Reader settings for the input files (`rename` and `columns`
+    ///   are replaced by the pipeline)
+    ///
+    /// # Returns
+    /// * `Result<PreparedPipeline>` - ValidationError naming the first invalid step
+    pub fn prepare(&self, schema: &[(String, DataType)], csv: CsvParserConfig) -> Result<PreparedPipeline, InsightoraError> {
+        let mut seen = HashSet::new();
+        if let Some((name, _)) = schema.iter().find(|(name, _)| !seen.insert(name.as_str())) {
+            return Err(InsightoraError::ValidationError(format!("Column '{}' is declared twice", name)));
+        }
+
+        let mut current = schema.to_vec();
+        let mut compiled = Vec::new();
+        for (number, step) in self.steps.iter().enumerate() {
+            let invalid = |error: InsightoraError| {
+                // A step's own validation message, without a nested "Validation error:" prefix
+                let message = match error {
+                    InsightoraError::ValidationError(message) => message,
+                    other => other.to_string(),
+                };
+                InsightoraError::ValidationError(format!("Pipeline step {} ({}): {}", number + 1, step.name(), message))
+            };
+            let step_compiled = compile_step(step, &current).map_err(invalid)?;
+
+            let mut probe = empty_frame(&current).lazy();
+            for stage in &step_compiled {
+                probe = stage.apply(probe)?;
+            }
+            let output = probe.collect().map_err(|e| invalid(e.into()))?;
+            current = output
+                .get_columns()
+                .iter()
+                .map(|series| (series.name().to_string(), series.dtype().clone()))
+                .collect();
+            compiled.extend(step_compiled);
+        }
+
+        Ok(PreparedPipeline {
+            input_schema: schema.to_vec(),
+            output_schema: current,
+            steps: compiled,
+            csv: CsvParserConfig { rename: None, columns: None, column_indices: None, ..csv },
+            sample_rows: 1000,
+        })
+    }
+}
+
+/// A step with its expressions built
+#[derive(Clone)]
+enum CompiledStep {
+    Select(Vec<Expr>),
+    AddColumn(CompiledExpression),
+    Update(Vec<Expr>),
+    DropNulls(Option<Vec<Expr>>),
+    Unique { subset: Option<Vec<String>>, keep: UniqueKeepStrategy },
+    Sort { by: Vec<Expr>, descending: Vec<bool> },
+}
+
+impl CompiledStep {
+    fn apply(&self, frame: LazyFrame) -> Result<LazyFrame, InsightoraError> {
+        Ok(match self {
+            CompiledStep::Select(exprs) => frame.select(exprs.clone()),
+            CompiledStep::AddColumn(expression) => expression.apply(frame)?,
+            CompiledStep::Update(exprs) => frame.with_columns(exprs.clone()),
+            CompiledStep::DropNulls(subset) => frame.drop_nulls(subset.clone()),
+            CompiledStep::Unique { subset, keep } => frame.unique_stable(subset.clone(), *keep),
+            CompiledStep::Sort { by, descending } => frame.sort_by_exprs(by, descending, false, true),
+        })
+    }
+}
+
+fn compile_step(step: &PipelineStep, schema: &[(String, DataType)]) -> Result<Vec<CompiledStep>, InsightoraError> {
+    let names: Vec<String> = schema.iter().map(|(name, _)| name.clone()).collect();
+    let require = |columns: &[String]| match columns.iter().find(|c| !names.contains(c)) {
+        Some(missing) => Err(InsightoraError::ValidationError(format!("unknown column '{}'", missing))),
+        None => Ok(()),
+    };
+
+    Ok(match step {
+        PipelineStep::Rename(mapping) => {
+            let renamed = mapping.apply_to_names(&names, true)?;
+            let mut seen = HashSet::new();
+            if let Some(duplicate) = renamed.iter().find(|name| !seen.insert(name.as_str())) {
+                return Err(InsightoraError::ValidationError(format!("rename produces duplicate column '{}'", duplicate)));
+            }
+            renaming(&names, renamed)
+        }
+        PipelineStep::Select(columns) => {
+            require(columns)?;
+            vec![CompiledStep::Select(columns.iter().map(|c| col(c)).collect())]
+        }
+        PipelineStep::Drop(columns) => {
+            require(columns)?;
+            vec![CompiledStep::Select(
+                names.iter().filter(|name| !columns.contains(name)).map(|name| col(name)).collect(),
+            )]
+        }
+        PipelineStep::AddColumns { expressions, nulls } => {
+            let mut available = names.clone();
+            let mut steps = Vec::new();
+            for expression in expressions {
+                if let Some(missing) = expression.columns().into_iter().find(|c| !available.iter().any(|a| a == c)) {
+                    return Err(InsightoraError::ValidationError(format!(
+                        "expression '{} = {}' uses unknown column '{}'",
+                        expression.name, expression.source, missing
+                    )));
+                }
+                if !available.contains(&expression.name) {
+                    available.push(expression.name.clone());
+                }
+                steps.push(CompiledStep::AddColumn(expression.compile(*nulls)));
+            }
+            steps
+        }
+        PipelineStep::UpdateWhere { predicate, assignments } => {
+            vec![CompiledStep::Update(transformations::update_exprs(schema, predicate, assignments)?)]
+        }
+        PipelineStep::DropNulls(subset) => {
+            if let Some(subset) = subset {
+                require(subset)?;
+            }
+            vec![CompiledStep::DropNulls(subset.as_ref().map(|columns| columns.iter().map(|c| col(c)).collect()))]
+        }
+        PipelineStep::DropDuplicates { subset, keep } => {
+            if let Some(subset) = subset {
+                require(subset)?;
+            }
+            vec![CompiledStep::Unique { subset: subset.clone(), keep: *keep }]
+        }
+        PipelineStep::Sort { by, descending } => {
+            if by.is_empty() {
+                return Err(InsightoraError::ValidationError("no sort columns".to_string()));
+            }
+            require(by)?;
+            vec![CompiledStep::Sort { by: by.iter().map(|c| col(c)).collect(), descending: vec![*descending; by.len()] }]
+        }
+        PipelineStep::Reorder { order, unlisted } => {
+            let reordered = operations::reordered_names(&names, order, *unlisted)?;
+            vec![CompiledStep::Select(reordered.iter().map(|c| col(c)).collect())]
+        }
+        PipelineStep::AddPrefix { prefix, columns } => {
+            renaming(&names, operations::affixed_names(&names, prefix, "", columns.as_deref())?)
+        }
+        PipelineStep::AddSuffix { suffix, columns } => {
+            renaming(&names, operations::affixed_names(&names, "", suffix, columns.as_deref())?)
+        }
+        PipelineStep::NormalizeNames { style, on_collision } => {
+            renaming(&names, operations::normalized_names(&names, *style, *on_collision)?)
+        }
+    })
+}
+
+fn renaming(names: &[String], renamed: Vec<String>) -> Vec<CompiledStep> {
+    vec![CompiledStep::Select(names.iter().zip(&renamed).map(|(old, new)| col(old).alias(new)).collect())]
+}
+
+fn empty_frame(schema: &[(String, DataType)]) -> DataFrame {
+    DataFrame::new_no_checks(schema.iter().map(|(name, dtype)| Series::new_empty(name, dtype)).collect())
+}
+
+// ============================================================================
+// Prepared Pipeline
+// ============================================================================
+
+/// Per-file outcome of `PreparedPipeline::run`
+#[derive(Debug, Clone)]
+pub struct PipelineRunReport {
+    pub file: String,
+    pub rows_in: usize,
+    pub rows_out: usize,
+    /// Values that didn't convert to the declared type and became null, by column
+    pub violations: Vec<(String, usize)>,
+    /// The violations as aggregated warnings, with sample values
+    pub warnings: Vec<AggregatedWarning>,
+    pub elapsed: Duration,
+}
+
+impl PipelineRunReport {
+    pub fn total_violations(&self) -> usize {
+        self.violations.iter().map(|(_, count)| count).sum()
+    }
+}
+
+/// Pipeline compiled by `Pipeline::prepare`, safe to run from many threads
+pub struct PreparedPipeline {
+    input_schema: Vec<(String, DataType)>,
+    output_schema: Vec<(String, DataType)>,
+    steps: Vec<CompiledStep>,
+    csv: CsvParserConfig,
+    sample_rows: usize,
+}
+
+impl PreparedPipeline {
+    /// Columns and types every input file is converted to
+    pub fn input_schema(&self) -> &[(String, DataType)] {
+        &self.input_schema
+    }
+
+    /// Columns and types of every run's result
+    pub fn output_schema(&self) -> &[(String, DataType)] {
+        &self.output_schema
+    }
+
+    /// Rows read by the conformance check before a file is parsed in full
+    pub fn with_sample_rows(mut self, rows: usize) -> Self {
+        self.sample_rows = rows.max(1);
+        self
+    }
+
+    /// Check a file's header and leading rows against the declared schema
+    ///
+    /// # Returns
+    /// * `Result<()>` - ValidationError listing missing columns, or
+    ///   InvalidDataType when no sampled value of a column converts to its
+    ///   declared type
+    pub fn check_file(&self, file_path: &str) -> Result<(), InsightoraError> {
+        let path = check_path_allowed(file_path)?;
+        let sample = CsvReader::from_path(&path)?
+            .has_header(self.csv.has_header)
+            .with_separator(self.csv.delimiter)
+            .with_quote_char(Some(self.csv.quote_char))
+            .infer_schema(Some(self.sample_rows))
+            .with_n_rows(Some(self.sample_rows))
+            .finish()?;
+
+        let missing: Vec<String> = self
+            .input_schema
+            .iter()
+            .filter(|(name, _)| sample.column(name).is_err())
+            .map(|(name, _)| format!("'{}'", name))
+            .collect();
+        if !missing.is_empty() {
+            return Err(InsightoraError::ValidationError(format!(
+                "File '{}' does not conform to the pipeline schema: missing column(s) {}",
+                file_path,
+                missing.join(", ")
+            )));
+        }
+        for (name, dtype) in &self.input_schema {
+            let series = sample.column(name)?;
+            let present = series.len() - series.null_count();
+            let (_, violations) = convert(series, dtype)?;
+            if present > 0 && violations == present {
+                return Err(InsightoraError::InvalidDataType {
+                    expected: format!("{} for column '{}' in '{}'", dtype, name, file_path),
+                    actual: series.dtype().to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse a file and apply the pipeline
+    ///
+    /// The conformance check runs first, so a non-conforming file fails
+    /// before it is parsed. Values that don't convert to their declared type
+    /// become null and are counted in the report.
+    ///
+    /// # Returns
+    /// * `Result<(DataFrame, PipelineRunReport)>` - Result rows and the run report
+    pub fn run(&self, file_path: &str) -> Result<(DataFrame, PipelineRunReport), InsightoraError> {
+        let start = Instant::now();
+        self.check_file(file_path)?;
+
+        let config = CsvParserConfig {
+            columns: Some(self.input_schema.iter().map(|(name, _)| name.clone()).collect()),
+            ..self.csv.clone()
+        };
+        let parsed = ParallelCsvParser::with_config(config).parse(file_path)?;
+        let rows_in = parsed.height();
+
+        let mut columns = Vec::with_capacity(self.input_schema.len());
+        let mut violations = Vec::new();
+        let mut warnings = WarningCollector::new();
+        for (name, dtype) in &self.input_schema {
+            let source = parsed.column(name)?;
+            let (converted, count) = convert(source, dtype)?;
+            if count > 0 {
+                violations.push((name.clone(), count));
+                let examples = coerced_values(source, &converted, warnings.sample_size())?;
+                warnings.emit_many("coercion", name, COERCED_TO_NULL, count, examples);
+            }
+            columns.push(converted);
+        }
+
+        let mut frame = DataFrame::new(columns)?.lazy();
+        for step in &self.steps {
+            frame = step.apply(frame)?;
+        }
+        let result = frame.collect()?;
+
+        let report = PipelineRunReport {
+            file: file_path.to_string(),
+            rows_in,
+            rows_out: result.height(),
+            violations,
+            warnings: warnings.finish(),
+            elapsed: start.elapsed(),
+        };
+        Ok((result, report))
+    }
+
+    /// Check the output schema against a sink's canonical schema without reading a file
+    pub fn check_sink(&self, schema: &SchemaEnforcement) -> Result<SchemaEnforcementReport, InsightoraError> {
+        schema.apply(&empty_frame(&self.output_schema)).map(|(_, report)| report)
+    }
+
+    /// Run the pipeline on a file and write the result to a Parquet file
+    ///
+    /// With a `schema`, the output schema is checked before the input is
+    /// parsed, so a pipeline that can never conform fails without reading.
+    ///
+    /// # Returns
+    /// * `Result<(PipelineRunReport, ParquetWriteReport)>` - Run and write reports
+    pub fn run_to_parquet(
+        &self,
+        file_path: &str,
+        output_path: &str,
+        schema: Option<&SchemaEnforcement>,
+    ) -> Result<(PipelineRunReport, ParquetWriteReport), InsightoraError> {
+        if let Some(schema) = schema {
+            self.check_sink(schema)?;
+        }
+        let (result, report) = self.run(file_path)?;
+        let written = write_parquet(&result, output_path, schema)?;
+        Ok((report, written))
+    }
+
+    /// Run the pipeline on a file and write the result as CSV part files
+    ///
+    /// See `write_csv_partitioned`; the parts are written in parallel.
+    ///
+    /// # Returns
+    /// * `Result<(PipelineRunReport, PartitionedCsvReport)>` - Run and write reports
+    pub fn run_to_csv_partitioned(
+        &self,
+        file_path: &str,
+        directory: &str,
+        partition: &CsvPartitionOptions,
+        options: &CsvWriteOptions,
+        progress: &ProgressReporter,
+    ) -> Result<(PipelineRunReport, PartitionedCsvReport), InsightoraError> {
+        let (result, report) = self.run(file_path)?;
+        let written = write_csv_partitioned(&result, directory, partition, options, progress)?;
+        Ok((report, written))
+    }
+}
+
+/// Aggregated warning for values nulled by `convert`
+const COERCED_TO_NULL: &str = "{count} values coerced to null in column {column}";
+
+/// Cast to the declared type; values that don't convert become null and are counted
+fn convert(series: &Series, dtype: &DataType) -> Result<(Series, usize), InsightoraError> {
+    if series.dtype() == dtype {
+        return Ok((series.clone(), 0));
+    }
+    let converted = series.cast(dtype)?;
+    let violations = converted.null_count().saturating_sub(series.null_count());
+    Ok((converted, violations))
+}
+
+/// The first `limit` values `convert` turned into nulls, quoted
+fn coerced_values(source: &Series, converted: &Series, limit: usize) -> Result<Vec<String>, InsightoraError> {
+    let lost = converted.is_null() & source.is_not_null();
+    let values = source.filter(&lost)?.head(Some(limit)).cast(&DataType::String)?;
+    Ok(values.str()?.into_iter().flatten().map(|value| format!("'{}'", value)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn pipeline() -> Pipeline {
+        Pipeline::new()
+            .then(PipelineStep::Rename(ColumnMapping::Names(vec![("sku".into(), "product".into())])))
+            .then(PipelineStep::AddColumns {
+                expressions: vec![ColumnExpression::parse("total", "price * qty").unwrap()],
+                nulls: NullArithmetic::Propagate,
+            })
+            .then(PipelineStep::DropNulls(Some(vec!["total".into()])))
+            .then(PipelineStep::Sort { by: vec!["total".into()], descending: true })
+            .then(PipelineStep::Select(vec!["product".into(), "total".into()]))
+    }
+
+    fn schema() -> Vec<(String, DataType)> {
+        vec![
+            ("sku".into(), DataType::String),
+            ("price".into(), DataType::Float64),
+            ("qty".into(), DataType::Int64),
+        ]
+    }
+
+    #[test]
+    fn test_prepare_validates_steps_once() {
+        let prepared = pipeline().prepare(&schema(), CsvParserConfig::default()).unwrap();
+        assert_eq!(
+            prepared.output_schema(),
+            &[("product".to_string(), DataType::String), ("total".to_string(), DataType::Float64)]
+        );
+
+        let unknown = pipeline()
+            .then(PipelineStep::Drop(vec!["price".into()]))
+            .prepare(&schema(), CsvParserConfig::default())
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(unknown.contains("Pipeline step 6 (drop): unknown column 'price'"), "{}", unknown);
+
+        let strings = Pipeline::new()
+            .then(PipelineStep::AddColumns {
+                expressions: vec![ColumnExpression::parse("bad", "sku * 2").unwrap()],
+                nulls: NullArithmetic::Propagate,
+            })
+            .prepare(&schema(), CsvParserConfig::default())
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(strings.contains("Pipeline step 1 (add_columns)"), "{}", strings);
+
+        let update = |source: &str| PipelineStep::UpdateWhere {
+            predicate: Predicate::parse("qty > 10").unwrap(),
+            assignments: vec![("qty".into(), Assignment::Expression(ColumnExpression::parse("qty", source).unwrap()))],
+        };
+        assert!(Pipeline::new().then(update("qty - 1")).prepare(&schema(), CsvParserConfig::default()).is_ok());
+        let fractional = Pipeline::new()
+            .then(update("qty / 2"))
+            .prepare(&schema(), CsvParserConfig::default())
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(fractional.contains("Pipeline step 1 (update_where)"), "{}", fractional);
+
+        assert_eq!(dtype_from_name("datetime[ms]").unwrap(), DataType::Datetime(TimeUnit::Milliseconds, None));
+        assert_eq!(dtype_from_name("Float64").unwrap(), DataType::Float64);
+        assert_eq!(dtype_from_name("Datetime(Microseconds, None)").unwrap(), DataType::Datetime(TimeUnit::Microseconds, None));
+        assert!(dtype_from_name("Datetime(Microseconds, Some(\"UTC\"))").is_err());
+        assert_eq!(dtype_from_name("decimal[38, 2]").unwrap(), DataType::Decimal(Some(38), Some(2)));
+        assert!(dtype_from_name("decimal[4,6]").is_err());
+        assert!(dtype_from_name("decimal").is_err());
+    }
+
+    #[test]
+    fn test_column_layout_steps() {
+        let schema = vec![("Unit Price".to_string(), DataType::Float64), ("SKU".to_string(), DataType::String)];
+        let prepared = Pipeline::new()
+            .then(PipelineStep::NormalizeNames { style: NameStyle::Snake, on_collision: NameCollision::Error })
+            .then(PipelineStep::AddPrefix { prefix: "src_".into(), columns: Some(vec!["sku".into()]) })
+            .then(PipelineStep::Reorder { order: vec!["src_sku".into()], unlisted: UnlistedColumns::Append })
+            .prepare(&schema, CsvParserConfig::default())
+            .unwrap();
+        let names: Vec<&str> = prepared.output_schema().iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["src_sku", "unit_price"]);
+
+        let colliding = vec![("Unit Price".to_string(), DataType::Float64), ("unit_price".to_string(), DataType::Float64)];
+        let error = Pipeline::new()
+            .then(PipelineStep::NormalizeNames { style: NameStyle::Snake, on_collision: NameCollision::Error })
+            .prepare(&colliding, CsvParserConfig::default())
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(error.contains("'Unit Price', 'unit_price'"), "{}", error);
+    }
+
+    #[test]
+    fn test_parquet_sink_checks_schema_before_reading() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("orders.csv");
+        std::fs::write(&input, "sku,price,qty\nA,2.5,2\n").unwrap();
+        let output = dir.path().join("orders.parquet").to_string_lossy().into_owned();
+        let prepared = pipeline().prepare(&schema(), CsvParserConfig::default()).unwrap();
+
+        let canonical = SchemaEnforcement::new(vec![("total".into(), DataType::Float64), ("product".into(), DataType::String)]);
+        let (report, written) = prepared.run_to_parquet(input.to_str().unwrap(), &output, Some(&canonical)).unwrap();
+        assert_eq!((report.rows_out, written.rows), (1, 1));
+
+        let strict = SchemaEnforcement::new(vec![("total".into(), DataType::Float64)]);
+        let err = prepared.run_to_parquet("never-read.csv", &output, Some(&strict)).unwrap_err();
+        assert!(matches!(err, InsightoraError::SchemaError(ref problems) if problems == &["undeclared column 'product'"]));
+    }
+
+    #[test]
+    fn test_prepared_pipeline_runs_across_files_in_parallel() {
+        let dir = TempDir::new().unwrap();
+        let write = |name: &str, text: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, text).unwrap();
+            path.to_string_lossy().to_string()
+        };
+        let files: Vec<String> = (0..6)
+            .map(|day| {
+                // Extra columns are ignored; day 3 has a quantity that isn't a number
+                let qty = if day == 3 { "n/a" } else { "4" };
+                write(
+                    &format!("orders-{}.csv", day),
+                    &format!("sku,region,price,qty\nA{day},north,2.5,2\nB{day},south,1.0,{qty}\nC{day},north,,1\n"),
+                )
+            })
+            .collect();
+        let missing = write("broken.csv", "sku,price\nA,1.0\n");
+        let wrong_type = write("wrong.csv", "sku,price,qty\nA,cheap,1\nB,free,2\n");
+
+        let prepared = Arc::new(pipeline().prepare(&schema(), CsvParserConfig::default()).unwrap());
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = files
+                .iter()
+                .map(|file| {
+                    let prepared = Arc::clone(&prepared);
+                    scope.spawn(move || prepared.run(file).unwrap())
+                })
+                .collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+
+        for (day, (df, report)) in results.iter().enumerate() {
+            assert_eq!(report.rows_in, 3);
+            let products: Vec<Option<&str>> = df.column("product").unwrap().str().unwrap().into_iter().collect();
+            if day == 3 {
+                assert_eq!(report.violations, vec![("qty".to_string(), 1)]);
+                assert_eq!(report.warnings[0].message(), "1 values coerced to null in column qty; examples: 'n/a'");
+                assert_eq!(products, vec![Some("A3")]);
+            } else {
+                assert_eq!(report.total_violations(), 0);
+                assert_eq!(products, vec![Some(format!("A{}", day).as_str()), Some(format!("B{}", day).as_str())]);
+            }
+            assert_eq!(report.rows_out, df.height());
+        }
+
+        let err = prepared.run(&missing).unwrap_err().to_string();
+        assert!(err.contains("does not conform to the pipeline schema: missing column(s) 'qty'"), "{}", err);
+        assert!(matches!(prepared.run(&wrong_type), Err(InsightoraError::InvalidDataType { .. })));
+    }
+}
@@ -1,2 +1,2849 @@
 // Data transformation operations
-// TODO: Implement in task 12
+// Duration parsing, datetime/duration arithmetic, datetime features, deduplication, missing values, type conversion, string operations,
+// column renaming, category ordering, value formatting, binning and conditional updates
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use polars::prelude::*;
+use polars::export::chrono::format::{Item, StrftimeItems};
+use polars::export::chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, Timelike};
+use rayon::prelude::*;
+use regex::{NoExpand, Regex};
+use serde::{Deserialize, Serialize};
+use crate::error::InsightoraError;
+use crate::io::csv_parser::{format_date, format_datetime};
+use crate::io::dates::{self, DateFormat};
+use crate::utils::warnings::WarningCollector;
+use crate::dataframe::expressions::{ColumnExpression, Predicate};
+use crate::utils::column_mapper::ColumnDictionary;
+use crate::dataframe::operations::{
+    comparison_keys, key_column_names, FloatKeyOptions, FloatPrecision, KeyRole, QuantizationReport,
+};
+
+const MICROS_PER_SECOND: i64 = 1_000_000;
+const MICROS_PER_MINUTE: i64 = 60 * MICROS_PER_SECOND;
+const MICROS_PER_HOUR: i64 = 60 * MICROS_PER_MINUTE;
+const MICROS_PER_DAY: i64 = 24 * MICROS_PER_HOUR;
+
+/// Duration dtype used for parsed and computed durations
+pub const DURATION_DTYPE: DataType = DataType::Duration(TimeUnit::Microseconds);
+
+// ============================================================================
+// Duration Parsing
+// ============================================================================
+
+/// Text format hint for duration columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DurationFormat {
+    /// Try every format below
+    #[default]
+    Auto,
+    /// "HH:MM:SS[.ffffff]" or "MM:SS", hours may exceed 24 ("36:00:00")
+    Clock,
+    /// Unit suffixes such as "1d 4h", "2h30m", "45s", "1.5h", "250ms"
+    Units,
+    /// ISO 8601 durations such as "P1DT4H30M" or "PT15.5S"
+    Iso8601,
+}
+
+impl DurationFormat {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "auto" => Ok(DurationFormat::Auto),
+            "clock" | "hh:mm:ss" => Ok(DurationFormat::Clock),
+            "units" => Ok(DurationFormat::Units),
+            "iso" | "iso8601" => Ok(DurationFormat::Iso8601),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown duration format '{}'; expected 'auto', 'clock', 'units' or 'iso8601'",
+                other
+            ))),
+        }
+    }
+}
+
+/// Parse a duration string into microseconds
+///
+/// A leading '-' negates the result. Returns None when the text doesn't
+/// match the format.
+pub fn parse_duration(text: &str, format: DurationFormat) -> Option<i64> {
+    let text = text.trim();
+    let (negative, body) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest.trim_start()),
+        None => (false, text),
+    };
+    if body.is_empty() {
+        return None;
+    }
+
+    let micros = match format {
+        DurationFormat::Clock => parse_clock(body),
+        DurationFormat::Units => parse_units(body),
+        DurationFormat::Iso8601 => parse_iso8601(body),
+        DurationFormat::Auto => parse_clock(body)
+            .or_else(|| parse_iso8601(body))
+            .or_else(|| parse_units(body)),
+    }?;
+    Some(if negative { -micros } else { micros })
+}
+
+/// Seconds with an optional fraction ("07", "07.25") to microseconds
+fn parse_seconds(text: &str) -> Option<i64> {
+    let (whole, frac) = text.split_once('.').unwrap_or((text, ""));
+    if whole.is_empty() || !whole.bytes().all(|b| b.is_ascii_digit()) || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let mut micros = whole.parse::<i64>().ok()?.checked_mul(MICROS_PER_SECOND)?;
+    // Pad or truncate the fraction to six digits
+    let digits: String = frac.chars().chain(std::iter::repeat('0')).take(6).collect();
+    micros += digits.parse::<i64>().ok()?;
+    Some(micros)
+}
+
+fn parse_clock(text: &str) -> Option<i64> {
+    let parts: Vec<&str> = text.split(':').collect();
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [h, m, s] => (*h, *m, *s),
+        [m, s] => ("0", *m, *s),
+        _ => return None,
+    };
+    let int = |t: &str| -> Option<i64> {
+        if t.is_empty() || !t.bytes().all(|b| b.is_ascii_digit()) {
+            None
+        } else {
+            t.parse().ok()
+        }
+    };
+    let (h, m) = (int(hours)?, int(minutes)?);
+    if parts.len() == 3 && m >= 60 {
+        return None;
+    }
+    let s = parse_seconds(seconds)?;
+    if s >= 60 * MICROS_PER_SECOND {
+        return None;
+    }
+    h.checked_mul(MICROS_PER_HOUR)?
+        .checked_add(m.checked_mul(MICROS_PER_MINUTE)?)?
+        .checked_add(s)
+}
+
+fn unit_micros(unit: &str) -> Option<f64> {
+    let micros = match unit.to_ascii_lowercase().as_str() {
+        "w" | "wk" | "week" | "weeks" => 7 * MICROS_PER_DAY,
+        "d" | "day" | "days" => MICROS_PER_DAY,
+        "h" | "hr" | "hrs" | "hour" | "hours" => MICROS_PER_HOUR,
+        "m" | "min" | "mins" | "minute" | "minutes" => MICROS_PER_MINUTE,
+        "s" | "sec" | "secs" | "second" | "seconds" => MICROS_PER_SECOND,
+        "ms" | "millisecond" | "milliseconds" => 1_000,
+        "us" | "µs" | "microsecond" | "microseconds" => 1,
+        _ => return None,
+    };
+    Some(micros as f64)
+}
+
+/// Sum of "<number><unit>" terms separated by optional whitespace or commas
+fn parse_units(text: &str) -> Option<i64> {
+    let mut total = 0f64;
+    let mut rest = text.trim();
+    let mut terms = 0;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        if number_len == 0 {
+            return None;
+        }
+        let value: f64 = rest[..number_len].parse().ok()?;
+        rest = rest[number_len..].trim_start();
+
+        let unit_len = rest
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(rest.len());
+        let unit = unit_micros(&rest[..unit_len])?;
+        total += value * unit;
+        terms += 1;
+        rest = rest[unit_len..].trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+    (terms > 0 && total.is_finite() && total.abs() < i64::MAX as f64).then(|| total.round() as i64)
+}
+
+fn parse_iso8601(text: &str) -> Option<i64> {
+    let body = text.strip_prefix(['P', 'p'])?;
+    if body.is_empty() {
+        return None;
+    }
+    let (date_part, time_part) = match body.split_once(['T', 't']) {
+        Some((d, t)) if !t.is_empty() => (d, Some(t)),
+        Some(_) => return None,
+        None => (body, None),
+    };
+
+    let mut total = 0f64;
+    let mut accumulate = |part: &str, units: &[(char, i64)]| -> Option<()> {
+        let mut number = String::new();
+        for c in part.chars() {
+            if c.is_ascii_digit() || c == '.' || c == ',' {
+                number.push(if c == ',' { '.' } else { c });
+            } else {
+                let upper = c.to_ascii_uppercase();
+                let (_, micros) = units.iter().find(|(u, _)| *u == upper)?;
+                total += number.parse::<f64>().ok()? * *micros as f64;
+                number.clear();
+            }
+        }
+        number.is_empty().then_some(())
+    };
+    // Years and months have no fixed length, so only W and D are accepted
+    accumulate(date_part, &[('W', 7 * MICROS_PER_DAY), ('D', MICROS_PER_DAY)])?;
+    if let Some(time) = time_part {
+        accumulate(time, &[('H', MICROS_PER_HOUR), ('M', MICROS_PER_MINUTE), ('S', MICROS_PER_SECOND)])?;
+    }
+    total.is_finite().then(|| total.round() as i64)
+}
+
+/// Convert a string column to a Duration column
+///
+/// Nulls stay null. Any non-null value that doesn't parse is an error that
+/// names the column and the offending value.
+pub fn cast_to_duration(series: &Series, format: DurationFormat) -> Result<Series, InsightoraError> {
+    if matches!(series.dtype(), DataType::Duration(_)) {
+        return Ok(series.cast(&DURATION_DTYPE)?);
+    }
+    let strings = series.cast(&DataType::String)?;
+    let micros = strings
+        .str()?
+        .into_iter()
+        .map(|value| match value {
+            None => Ok(None),
+            Some(text) => parse_duration(text, format).map(Some).ok_or_else(|| {
+                InsightoraError::ParseError(format!(
+                    "Column '{}': '{}' is not a valid duration",
+                    series.name(),
+                    text
+                ))
+            }),
+        })
+        .collect::<Result<Int64Chunked, InsightoraError>>()?;
+    Ok(micros.with_name(series.name()).into_series().cast(&DURATION_DTYPE)?)
+}
+
+// ============================================================================
+// Datetime / Duration Arithmetic
+// ============================================================================
+
+/// Physical microsecond values of a datetime or duration column
+fn micros_of(series: &Series, target: &DataType) -> Result<Int64Chunked, InsightoraError> {
+    let cast = series.cast(target)?;
+    Ok(cast.to_physical_repr().i64()?.clone())
+}
+
+fn datetime_dtype(series: &Series) -> Result<DataType, InsightoraError> {
+    match series.dtype() {
+        DataType::Datetime(_, tz) => Ok(DataType::Datetime(TimeUnit::Microseconds, tz.clone())),
+        DataType::Date => Ok(DataType::Datetime(TimeUnit::Microseconds, None)),
+        other => Err(InsightoraError::InvalidDataType {
+            expected: "Datetime or Date".to_string(),
+            actual: format!("{} ({})", other, series.name()),
+        }),
+    }
+}
+
+/// datetime - datetime → duration
+pub fn datetime_diff(end: &Series, start: &Series) -> Result<Series, InsightoraError> {
+    let end_micros = micros_of(end, &datetime_dtype(end)?)?;
+    let start_micros = micros_of(start, &datetime_dtype(start)?)?;
+    if end_micros.len() != start_micros.len() {
+        return Err(InsightoraError::ValidationError(format!(
+            "Cannot subtract columns of different lengths ({} and {})",
+            end_micros.len(),
+            start_micros.len()
+        )));
+    }
+    let diff = &end_micros - &start_micros;
+    Ok(diff.with_name(end.name()).into_series().cast(&DURATION_DTYPE)?)
+}
+
+/// datetime + duration → datetime (negative durations subtract)
+pub fn add_duration(datetime: &Series, duration: &Series) -> Result<Series, InsightoraError> {
+    let dtype = datetime_dtype(datetime)?;
+    if !matches!(duration.dtype(), DataType::Duration(_)) {
+        return Err(InsightoraError::InvalidDataType {
+            expected: "Duration".to_string(),
+            actual: format!("{} ({})", duration.dtype(), duration.name()),
+        });
+    }
+    let base = micros_of(datetime, &dtype)?;
+    let offset = micros_of(duration, &DURATION_DTYPE)?;
+    if base.len() != offset.len() {
+        return Err(InsightoraError::ValidationError(format!(
+            "Cannot add columns of different lengths ({} and {})",
+            base.len(),
+            offset.len()
+        )));
+    }
+    let sum = &base + &offset;
+    Ok(sum.with_name(datetime.name()).into_series().cast(&dtype)?)
+}
+
+// ============================================================================
+// Datetime Features
+// ============================================================================
+
+/// Calendar field `datetime_features` adds as a column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatetimeFeature {
+    Year,
+    /// 1 to 4
+    Quarter,
+    /// 1 to 12
+    Month,
+    /// ISO 8601 week of the year, 1 to 53
+    Week,
+    /// 1 (Monday) to 7 (Sunday)
+    Weekday,
+    /// Day of the month
+    Day,
+    Hour,
+    Minute,
+    /// Saturday or Sunday
+    IsWeekend,
+    /// Last day of the month
+    IsMonthEnd,
+    /// Whole seconds since 1970-01-01 UTC, whatever the column's zone
+    EpochSeconds,
+}
+
+impl DatetimeFeature {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "year" => Ok(DatetimeFeature::Year),
+            "quarter" => Ok(DatetimeFeature::Quarter),
+            "month" => Ok(DatetimeFeature::Month),
+            "week" => Ok(DatetimeFeature::Week),
+            "weekday" => Ok(DatetimeFeature::Weekday),
+            "day" => Ok(DatetimeFeature::Day),
+            "hour" => Ok(DatetimeFeature::Hour),
+            "minute" => Ok(DatetimeFeature::Minute),
+            "is_weekend" => Ok(DatetimeFeature::IsWeekend),
+            "is_month_end" => Ok(DatetimeFeature::IsMonthEnd),
+            "epoch_seconds" | "epoch" => Ok(DatetimeFeature::EpochSeconds),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown datetime feature '{}'; expected 'year', 'quarter', 'month', 'week', 'weekday', 'day', 'hour', \
+                 'minute', 'is_weekend', 'is_month_end' or 'epoch_seconds'",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DatetimeFeature::Year => "year",
+            DatetimeFeature::Quarter => "quarter",
+            DatetimeFeature::Month => "month",
+            DatetimeFeature::Week => "week",
+            DatetimeFeature::Weekday => "weekday",
+            DatetimeFeature::Day => "day",
+            DatetimeFeature::Hour => "hour",
+            DatetimeFeature::Minute => "minute",
+            DatetimeFeature::IsWeekend => "is_weekend",
+            DatetimeFeature::IsMonthEnd => "is_month_end",
+            DatetimeFeature::EpochSeconds => "epoch_seconds",
+        }
+    }
+
+    /// Value of a numeric feature at a local time
+    fn number(&self, at: &NaiveDateTime) -> i32 {
+        match self {
+            DatetimeFeature::Year => at.year(),
+            DatetimeFeature::Quarter => at.month0() as i32 / 3 + 1,
+            DatetimeFeature::Month => at.month() as i32,
+            DatetimeFeature::Week => at.iso_week().week() as i32,
+            DatetimeFeature::Weekday => at.weekday().number_from_monday() as i32,
+            DatetimeFeature::Day => at.day() as i32,
+            DatetimeFeature::Hour => at.hour() as i32,
+            DatetimeFeature::Minute => at.minute() as i32,
+            DatetimeFeature::IsWeekend | DatetimeFeature::IsMonthEnd | DatetimeFeature::EpochSeconds => 0,
+        }
+    }
+}
+
+/// Bucket width for `truncate_datetime`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TruncateEvery {
+    /// Microseconds, with buckets counted from 1970-01-01 00:00 local time
+    Fixed(i64),
+    /// Microseconds of whole weeks, with buckets starting on a Monday
+    Weeks(i64),
+    /// Calendar months, with buckets counted from January of year 0, so
+    /// "3mo" gives quarters
+    Months(i32),
+}
+
+impl TruncateEvery {
+    /// Parse a count and unit: "30s", "15m", "1h", "1d", "1w", "1mo", "1q" or "1y"
+    pub fn parse(text: &str) -> Result<Self, InsightoraError> {
+        let invalid = || {
+            InsightoraError::ValidationError(format!(
+                "Invalid truncation interval '{}'; expected a count and unit such as '15m', '1h', '1d', '1w', '1mo', '1q' or '1y'",
+                text
+            ))
+        };
+        let text = text.trim();
+        let digits = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+        let count: i64 = text[..digits].parse().map_err(|_| invalid())?;
+        if count == 0 {
+            return Err(invalid());
+        }
+        let micros = |unit: i64| count.checked_mul(unit).ok_or_else(invalid);
+        let months = |unit: i64| count.checked_mul(unit).and_then(|months| i32::try_from(months).ok()).ok_or_else(invalid);
+        match &text[digits..] {
+            "s" => Ok(TruncateEvery::Fixed(micros(MICROS_PER_SECOND)?)),
+            "m" => Ok(TruncateEvery::Fixed(micros(MICROS_PER_MINUTE)?)),
+            "h" => Ok(TruncateEvery::Fixed(micros(MICROS_PER_HOUR)?)),
+            "d" => Ok(TruncateEvery::Fixed(micros(MICROS_PER_DAY)?)),
+            "w" => Ok(TruncateEvery::Weeks(micros(7 * MICROS_PER_DAY)?)),
+            "mo" => Ok(TruncateEvery::Months(months(1)?)),
+            "q" => Ok(TruncateEvery::Months(months(3)?)),
+            "y" => Ok(TruncateEvery::Months(months(12)?)),
+            _ => Err(invalid()),
+        }
+    }
+
+    fn whole_days(&self) -> bool {
+        match self {
+            TruncateEvery::Fixed(width) => width % MICROS_PER_DAY == 0,
+            TruncateEvery::Weeks(_) | TruncateEvery::Months(_) => true,
+        }
+    }
+
+    /// Start of the bucket holding a local time, in local microseconds
+    fn floor(&self, local: i64) -> Option<i64> {
+        match *self {
+            TruncateEvery::Fixed(width) => Some(local - local.rem_euclid(width)),
+            // 1970-01-05 was the first Monday after the epoch
+            TruncateEvery::Weeks(width) => Some(local - (local - 4 * MICROS_PER_DAY).rem_euclid(width)),
+            TruncateEvery::Months(width) => {
+                let at = naive_at(local)?;
+                let months = at.year() * 12 + at.month0() as i32;
+                let start = months - months.rem_euclid(width);
+                let first = NaiveDate::from_ymd_opt(start.div_euclid(12), start.rem_euclid(12) as u32 + 1, 1)?;
+                Some(first.and_hms_opt(0, 0, 0)?.and_utc().timestamp_micros())
+            }
+        }
+    }
+}
+
+/// Add calendar fields of a date or datetime column as new columns
+///
+/// Each feature becomes `{column}_{feature}`: Int32 for the numbers,
+/// Boolean for `is_weekend` and `is_month_end`, Int64 for epoch seconds.
+/// Fields are read in the column's time zone; nulls stay null.
+///
+/// # Arguments
+/// * `df` - Input frame
+/// * `column` - Date or datetime column
+/// * `features` - Fields to add, in order
+///
+/// # Returns
+/// * `Result<DataFrame>` - Frame with the feature columns after the others;
+///   InvalidDataType for a column that isn't a date or datetime or for
+///   hour or minute of a date column, ValidationError for a feature column
+///   name already in use or a time zone other than UTC or a fixed offset
+///
+/// # Example
+/// ```no_run
+/// use insightora_core::api::{datetime_features, DatetimeFeature, ParallelCsvParser};
+///
+/// let df = ParallelCsvParser::new().parse("orders.csv").unwrap();
+/// let features = [DatetimeFeature::Weekday, DatetimeFeature::Hour, DatetimeFeature::IsWeekend];
+/// let orders = datetime_features(&df, "ordered_at", &features).unwrap();
+/// ```
+pub fn datetime_features(df: &DataFrame, column: &str, features: &[DatetimeFeature]) -> Result<DataFrame, InsightoraError> {
+    let series = df
+        .column(column)
+        .map_err(|_| InsightoraError::ValidationError(format!("Column '{}' not found", column)))?;
+    let (micros, offset) = local_micros(series, None)?;
+    if series.dtype() == &DataType::Date {
+        if let Some(feature) = features.iter().find(|f| matches!(f, DatetimeFeature::Hour | DatetimeFeature::Minute)) {
+            return Err(InsightoraError::InvalidDataType {
+                expected: format!("datetime column for feature '{}'", feature.name()),
+                actual: format!("{} ({})", series.dtype(), column),
+            });
+        }
+    }
+    let local: Vec<Option<NaiveDateTime>> = micros.into_iter().map(|value| naive_at(value? + offset)).collect();
+
+    let mut result = df.clone();
+    for feature in features {
+        let name = format!("{}_{}", column, feature.name());
+        if result.column(&name).is_ok() {
+            return Err(InsightoraError::ValidationError(format!("Feature column '{}' is already in use", name)));
+        }
+        let values = match feature {
+            DatetimeFeature::EpochSeconds => micros
+                .into_iter()
+                .map(|value| value.map(|value| value.div_euclid(MICROS_PER_SECOND)))
+                .collect::<Int64Chunked>()
+                .into_series(),
+            DatetimeFeature::IsWeekend => local
+                .iter()
+                .map(|at| at.map(|at| at.weekday().number_from_monday() >= 6))
+                .collect::<BooleanChunked>()
+                .into_series(),
+            DatetimeFeature::IsMonthEnd => local
+                .iter()
+                .map(|at| at.map(|at| matches!(at.date().succ_opt(), Some(next) if next.day() == 1)))
+                .collect::<BooleanChunked>()
+                .into_series(),
+            _ => local.iter().map(|at| at.map(|at| feature.number(&at))).collect::<Int32Chunked>().into_series(),
+        };
+        result.with_column(values.with_name(&name))?;
+    }
+    Ok(result)
+}
+
+/// Round a date or datetime column down to the start of its bucket
+///
+/// Buckets are laid out in the column's time zone, or in `tz` for a
+/// zone-aware column; the column keeps its type and zone. Only UTC and
+/// fixed offsets such as "+05:30" are supported as zones. Nulls stay null.
+///
+/// # Arguments
+/// * `df` - Input frame
+/// * `column` - Date or datetime column, replaced by the truncated values
+/// * `every` - Bucket width
+/// * `tz` - Zone to lay buckets out in instead of the column's own
+///
+/// # Returns
+/// * `Result<DataFrame>` - Frame with the column truncated; InvalidDataType
+///   for a column that isn't a date or datetime or for buckets shorter than
+///   a day on a date column, ValidationError for `tz` on a column without a
+///   zone or an unsupported zone
+///
+/// # Example
+/// ```no_run
+/// use insightora_core::api::{truncate_datetime, ParallelCsvParser, TruncateEvery};
+///
+/// let df = ParallelCsvParser::new().parse("requests.csv").unwrap();
+/// let every = TruncateEvery::parse("15m").unwrap();
+/// let bucketed = truncate_datetime(&df, "received_at", &every, None).unwrap();
+/// ```
+pub fn truncate_datetime(
+    df: &DataFrame,
+    column: &str,
+    every: &TruncateEvery,
+    tz: Option<&str>,
+) -> Result<DataFrame, InsightoraError> {
+    let series = df
+        .column(column)
+        .map_err(|_| InsightoraError::ValidationError(format!("Column '{}' not found", column)))?;
+    let (micros, offset) = local_micros(series, tz)?;
+    if series.dtype() == &DataType::Date && !every.whole_days() {
+        return Err(InsightoraError::InvalidDataType {
+            expected: "datetime column for buckets shorter than a day".to_string(),
+            actual: format!("{} ({})", series.dtype(), column),
+        });
+    }
+    let truncated: Int64Chunked = micros
+        .into_iter()
+        .map(|value| every.floor(value? + offset).map(|start| start - offset))
+        .collect();
+    let truncated = truncated
+        .with_name(column)
+        .into_series()
+        .cast(&datetime_dtype(series)?)?
+        .cast(series.dtype())?;
+
+    let mut result = df.clone();
+    result.replace(column, truncated)?;
+    Ok(result)
+}
+
+/// Microseconds since the epoch of a date or datetime column and the
+/// offset of its zone, or of `tz`, in microseconds
+fn local_micros(series: &Series, tz: Option<&str>) -> Result<(Int64Chunked, i64), InsightoraError> {
+    let micros = micros_of(series, &datetime_dtype(series)?)?;
+    let zone = match (series.dtype(), tz) {
+        (DataType::Datetime(_, Some(zone)), tz) => Some(tz.unwrap_or(zone)),
+        (_, Some(tz)) => {
+            return Err(InsightoraError::ValidationError(format!(
+                "Column '{}' has no time zone, so tz '{}' doesn't apply",
+                series.name(),
+                tz
+            )))
+        }
+        _ => None,
+    };
+    let offset = zone.map(zone_offset).transpose()?.unwrap_or(0);
+    Ok((micros, offset))
+}
+
+/// Offset from UTC in microseconds of "UTC" or a fixed offset such as "+05:30"
+fn zone_offset(zone: &str) -> Result<i64, InsightoraError> {
+    let unsupported = || {
+        InsightoraError::ValidationError(format!(
+            "Time zone '{}' isn't supported; use 'UTC' or a fixed offset such as '+05:30'",
+            zone
+        ))
+    };
+    if matches!(zone, "UTC" | "Etc/UTC" | "GMT" | "Z") {
+        return Ok(0);
+    }
+    let (sign, rest) = match zone.as_bytes().first() {
+        Some(b'+') => (1, &zone[1..]),
+        Some(b'-') => (-1, &zone[1..]),
+        _ => return Err(unsupported()),
+    };
+    let digits: String = rest.chars().filter(|&c| c != ':').collect();
+    if !matches!(digits.len(), 2 | 4) || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(unsupported());
+    }
+    let hours: i64 = digits[..2].parse().map_err(|_| unsupported())?;
+    let minutes: i64 = if digits.len() == 4 { digits[2..].parse().map_err(|_| unsupported())? } else { 0 };
+    if hours > 23 || minutes > 59 {
+        return Err(unsupported());
+    }
+    Ok(sign * (hours * MICROS_PER_HOUR + minutes * MICROS_PER_MINUTE))
+}
+
+/// Wall-clock time of microseconds since the epoch
+fn naive_at(micros: i64) -> Option<NaiveDateTime> {
+    let seconds = micros.div_euclid(MICROS_PER_SECOND);
+    let nanos = (micros.rem_euclid(MICROS_PER_SECOND) * 1_000) as u32;
+    DateTime::from_timestamp(seconds, nanos).map(|at| at.naive_utc())
+}
+
+// ============================================================================
+// Deduplication
+// ============================================================================
+
+/// Parse a keep strategy name ("first", "last" or "none")
+pub fn keep_strategy_from_name(name: &str) -> Result<UniqueKeepStrategy, InsightoraError> {
+    match name.to_ascii_lowercase().as_str() {
+        "first" => Ok(UniqueKeepStrategy::First),
+        "last" => Ok(UniqueKeepStrategy::Last),
+        "none" => Ok(UniqueKeepStrategy::None),
+        other => Err(InsightoraError::ValidationError(format!(
+            "Unknown keep strategy '{}'; expected 'first', 'last' or 'none'",
+            other
+        ))),
+    }
+}
+
+/// Remove duplicate rows, comparing only the `subset` columns (default: all)
+///
+/// Kept rows stay in their original order and null keys are equal to each
+/// other. With a float precision set, float keys are compared on their
+/// quantized values and the report counts the extra rows removed compared
+/// with exact matching under the same NaN rule.
+pub fn drop_duplicates(
+    df: &DataFrame,
+    subset: Option<&[String]>,
+    keep: UniqueKeepStrategy,
+    float_options: &FloatKeyOptions,
+) -> Result<(DataFrame, QuantizationReport), InsightoraError> {
+    let subset = duplicate_subset(df, subset)?;
+    let Some(precision) = float_options.precision else {
+        let deduped = df.unique_stable(Some(&subset), keep, None)?;
+        return Ok((deduped, QuantizationReport::default()));
+    };
+
+    let dedup_with = |precision: FloatPrecision| -> Result<DataFrame, InsightoraError> {
+        let options = FloatKeyOptions { precision: Some(precision), ..*float_options };
+        let keys = comparison_keys(df, &subset, &options, KeyRole::Grouping, "d")?;
+        let key_names: Vec<String> = keys.iter().map(|s| s.name().to_string()).collect();
+        let deduped = df
+            .hstack(&keys)?
+            .unique_stable(Some(&key_names), keep, None)?;
+        Ok(deduped.drop_many(&key_column_names(&deduped)))
+    };
+
+    let deduped = dedup_with(precision)?;
+    let rows_affected = if precision == FloatPrecision::Exact {
+        0
+    } else {
+        dedup_with(FloatPrecision::Exact)?.height().saturating_sub(deduped.height())
+    };
+    Ok((deduped, QuantizationReport { rows_affected }))
+}
+
+/// Mark the rows `drop_duplicates` removes with the same arguments
+///
+/// With `keep` First every occurrence of a key after its first is marked,
+/// with Last every occurrence before its last, and with None every row whose
+/// key appears more than once. As in `drop_duplicates`, null keys are equal
+/// to each other.
+///
+/// # Returns
+/// * `Result<BooleanChunked>` - An `is_duplicated` mask with one value per row
+///
+/// # Example
+/// ```no_run
+/// use insightora_core::api::{duplicate_mask, keep_strategy_from_name, FloatKeyOptions, ParallelCsvParser};
+///
+/// let df = ParallelCsvParser::new().parse("orders.csv").unwrap();
+/// let subset = ["customer_id".to_string(), "order_date".to_string()];
+/// let keep = keep_strategy_from_name("none").unwrap();
+/// let mask = duplicate_mask(&df, Some(&subset), keep, &FloatKeyOptions::default()).unwrap();
+/// let copies = df.filter(&mask).unwrap();
+/// ```
+pub fn duplicate_mask(
+    df: &DataFrame,
+    subset: Option<&[String]>,
+    keep: UniqueKeepStrategy,
+    float_options: &FloatKeyOptions,
+) -> Result<BooleanChunked, InsightoraError> {
+    let subset = duplicate_subset(df, subset)?;
+    let keys = match float_options.precision {
+        Some(_) => DataFrame::new(comparison_keys(df, &subset, float_options, KeyRole::Grouping, "d")?)?,
+        None => df.select(&subset)?,
+    };
+    let grouped = keys.group_by(keys.get_column_names())?;
+
+    let mut mask = vec![false; df.height()];
+    let mut mark = |rows: &mut dyn Iterator<Item = usize>, first: usize, last: usize| {
+        let kept = match keep {
+            UniqueKeepStrategy::None => None,
+            UniqueKeepStrategy::Last => Some(last),
+            _ => Some(first),
+        };
+        for row in rows {
+            mask[row] = Some(row) != kept;
+        }
+    };
+    match grouped.get_groups() {
+        GroupsProxy::Idx(idx) => {
+            for rows in idx.all().iter().filter(|rows| rows.len() > 1) {
+                let first = rows.iter().min().map_or(0, |&row| row as usize);
+                let last = rows.iter().max().map_or(0, |&row| row as usize);
+                mark(&mut rows.iter().map(|&row| row as usize), first, last);
+            }
+        }
+        GroupsProxy::Slice { groups, .. } => {
+            for &[first, len] in groups.iter().filter(|&&[_, len]| len > 1) {
+                let (first, len) = (first as usize, len as usize);
+                mark(&mut (first..first + len), first, first + len - 1);
+            }
+        }
+    }
+    Ok(BooleanChunked::from_slice("is_duplicated", &mask))
+}
+
+/// Columns compared for duplicates: `subset`, checked to exist, or all columns
+fn duplicate_subset(df: &DataFrame, subset: Option<&[String]>) -> Result<Vec<String>, InsightoraError> {
+    let Some(columns) = subset else {
+        return Ok(df.get_column_names().iter().map(|s| s.to_string()).collect());
+    };
+    if let Some(missing) = columns.iter().find(|name| df.column(name).is_err()) {
+        return Err(InsightoraError::ValidationError(format!("Duplicate subset column '{}' not found", missing)));
+    }
+    Ok(columns.to_vec())
+}
+
+// ============================================================================
+// Missing Values
+// ============================================================================
+
+/// Rows `drop_nulls` drops
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NullRows {
+    /// Rows with a null in any of the columns
+    #[default]
+    Any,
+    /// Rows null in every one of the columns
+    All,
+}
+
+impl NullRows {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "any" => Ok(NullRows::Any),
+            "all" => Ok(NullRows::All),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown drop_nulls mode '{}'; expected 'any' or 'all'",
+                other
+            ))),
+        }
+    }
+}
+
+/// What `fill_nulls` puts in place of nulls
+#[derive(Debug, Clone)]
+pub enum FillStrategy {
+    /// The same value in every column, as a one-element Series whose type
+    /// must fit each column (as for `update_where`)
+    Value(Series),
+    /// The column's mean; numeric columns only
+    Mean,
+    /// The column's median; numeric columns only
+    Median,
+    /// The column's most frequent value, the first seen on ties
+    Mode,
+    /// The last value above
+    Forward,
+    /// The next value below
+    Backward,
+    /// 0; numeric columns only
+    Zero,
+}
+
+impl FillStrategy {
+    /// Parse a strategy name; values are given as `FillStrategy::Value`
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "mean" => Ok(FillStrategy::Mean),
+            "median" => Ok(FillStrategy::Median),
+            "mode" => Ok(FillStrategy::Mode),
+            "forward" | "ffill" => Ok(FillStrategy::Forward),
+            "backward" | "bfill" => Ok(FillStrategy::Backward),
+            "zero" => Ok(FillStrategy::Zero),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown fill strategy '{}'; expected 'mean', 'median', 'mode', 'forward', 'backward' or 'zero'",
+                other
+            ))),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            FillStrategy::Value(_) => "value",
+            FillStrategy::Mean => "mean",
+            FillStrategy::Median => "median",
+            FillStrategy::Mode => "mode",
+            FillStrategy::Forward => "forward",
+            FillStrategy::Backward => "backward",
+            FillStrategy::Zero => "zero",
+        }
+    }
+
+    fn numeric_only(&self) -> bool {
+        matches!(self, FillStrategy::Mean | FillStrategy::Median | FillStrategy::Zero)
+    }
+}
+
+/// Drop rows with nulls in `subset` (default: all columns)
+///
+/// # Returns
+/// * `Result<DataFrame>` - Remaining rows in their original order;
+///   ValidationError naming a subset column that doesn't exist
+pub fn drop_nulls(df: &DataFrame, subset: Option<&[String]>, how: NullRows) -> Result<DataFrame, InsightoraError> {
+    let columns = null_columns(df, subset)?;
+    let mut keep: Option<BooleanChunked> = None;
+    for name in &columns {
+        let set = df.column(name)?.is_not_null();
+        keep = Some(match (keep, how) {
+            (None, _) => set,
+            (Some(keep), NullRows::Any) => &keep & &set,
+            (Some(keep), NullRows::All) => &keep | &set,
+        });
+    }
+    match keep {
+        Some(keep) => Ok(df.filter(&keep)?),
+        None => Ok(df.clone()),
+    }
+}
+
+/// Replace nulls column by column
+///
+/// Without `columns`, mean, median and zero fill the numeric columns and the
+/// other strategies every column; a listed column the strategy can't fill is
+/// an error rather than being left alone. Integer columns filled with a mean
+/// or median get it rounded to the nearest integer and keep their type.
+/// Forward and backward fills run over the whole column, carrying values
+/// across chunk boundaries. A column with no values to take a statistic
+/// from keeps its nulls.
+///
+/// # Arguments
+/// * `df` - Input frame
+/// * `strategy` - Fill value or rule
+/// * `columns` - Columns to fill (default: see above)
+///
+/// # Returns
+/// * `Result<DataFrame>` - Frame with the same columns and types;
+///   InvalidDataType for a statistic on a non-numeric column, ValidationError
+///   for a missing column or a value the column's type can't hold
+///
+/// # Example
+/// ```no_run
+/// use insightora_core::api::{fill_nulls, FillStrategy, ParallelCsvParser};
+///
+/// let df = ParallelCsvParser::new().parse("readings.csv").unwrap();
+/// let sensors = ["temperature".to_string(), "humidity".to_string()];
+/// let filled = fill_nulls(&df, &FillStrategy::Forward, Some(&sensors)).unwrap();
+/// ```
+pub fn fill_nulls(df: &DataFrame, strategy: &FillStrategy, columns: Option<&[String]>) -> Result<DataFrame, InsightoraError> {
+    let names = match columns {
+        Some(_) => null_columns(df, columns)?,
+        None => df
+            .get_columns()
+            .iter()
+            .filter(|series| !strategy.numeric_only() || series.dtype().is_numeric())
+            .map(|series| series.name().to_string())
+            .collect(),
+    };
+    let mut filled = df.clone();
+    for name in &names {
+        let series = df.column(name)?;
+        if series.null_count() == 0 {
+            continue;
+        }
+        if strategy.numeric_only() && !series.dtype().is_numeric() {
+            return Err(InsightoraError::InvalidDataType {
+                expected: format!("numeric column for fill strategy '{}'", strategy.name()),
+                actual: format!("{} ({})", series.dtype(), name),
+            });
+        }
+        let fill = |value: Option<Series>| -> Result<Series, InsightoraError> {
+            let Some(value) = value else {
+                return Ok(series.clone());
+            };
+            let value = value.new_from_index(0, series.len());
+            Ok(series.zip_with(&series.is_not_null(), &value)?)
+        };
+        let statistic = |value: Option<f64>| -> Result<Option<Series>, InsightoraError> {
+            let value = match value {
+                Some(value) if series.dtype().is_integer() => value.round(),
+                Some(value) => value,
+                None => return Ok(None),
+            };
+            Ok(Some(Series::new(name, [value]).cast(series.dtype())?))
+        };
+        let result = match strategy {
+            FillStrategy::Value(value) => {
+                check_assignable(name, value.dtype(), series.dtype())?;
+                let value = value.strict_cast(series.dtype()).map_err(|_| {
+                    InsightoraError::ValidationError(format!(
+                        "Fill value {} does not fit column '{}' of type {}",
+                        value.get(0).map(|v| v.to_string()).unwrap_or_default(),
+                        name,
+                        series.dtype()
+                    ))
+                })?;
+                fill(Some(value))?
+            }
+            FillStrategy::Mean => fill(statistic(series.mean())?)?,
+            FillStrategy::Median => fill(statistic(series.median())?)?,
+            FillStrategy::Mode => fill(mode_value(series)?)?,
+            FillStrategy::Zero => series.fill_null(FillNullStrategy::Zero)?,
+            // On one chunk, so values carry across what were chunk boundaries
+            FillStrategy::Forward => series.rechunk().fill_null(FillNullStrategy::Forward(None))?,
+            FillStrategy::Backward => series.rechunk().fill_null(FillNullStrategy::Backward(None))?,
+        };
+        filled.replace(name, result)?;
+    }
+    Ok(filled)
+}
+
+/// Columns named in `subset`, checked to exist, or all columns
+fn null_columns(df: &DataFrame, subset: Option<&[String]>) -> Result<Vec<String>, InsightoraError> {
+    let Some(columns) = subset else {
+        return Ok(df.get_column_names().iter().map(|s| s.to_string()).collect());
+    };
+    if let Some(missing) = columns.iter().find(|name| df.column(name).is_err()) {
+        return Err(InsightoraError::ValidationError(format!("Column '{}' not found", missing)));
+    }
+    Ok(columns.to_vec())
+}
+
+/// Most frequent non-null value of a column as a one-element Series, the
+/// first seen on ties; None when every value is null
+fn mode_value(series: &Series) -> Result<Option<Series>, InsightoraError> {
+    const MODE_COUNT: &str = "__insightora_mode_count";
+    let counts = series
+        .clone()
+        .into_frame()
+        .lazy()
+        .drop_nulls(None)
+        .group_by_stable([col(series.name())])
+        .agg([count().alias(MODE_COUNT)])
+        .collect()?;
+    let tallies = counts.column(MODE_COUNT)?.cast(&DataType::UInt64)?;
+    let mut best: Option<(usize, u64)> = None;
+    for (index, tally) in tallies.u64()?.into_no_null_iter().enumerate() {
+        match best {
+            Some((_, most)) if tally <= most => {}
+            _ => best = Some((index, tally)),
+        }
+    }
+    Ok(best.map(|(index, _)| counts.get_columns()[0].slice(index as i64, 1)))
+}
+
+// ============================================================================
+// Type Conversion
+// ============================================================================
+
+/// Convert columns to other types
+///
+/// Numeric types widen and narrow both ways (floats to integers truncate
+/// toward zero), numbers and booleans convert to and from text, integers
+/// to and from booleans (non-zero is true), and text becomes a boolean
+/// from "true" or "false" in any case. Text parses as a date or datetime
+/// with `format`, or as ISO 8601 without one, and as a duration in any
+/// layout `parse_duration` reads ("02:35:17", "1d 4h", "PT15S").
+///
+/// A value that doesn't convert - text that isn't a number, a number out
+/// of the target type's range, NaN to an integer - is a ParseError naming
+/// the column, row and value when `strict`; otherwise it becomes null and
+/// is counted.
+///
+/// # Arguments
+/// * `df` - Input frame
+/// * `targets` - (column, type) pairs; other columns are left alone
+/// * `format` - How text is read as dates and datetimes
+/// * `strict` - Fail on the first value that doesn't convert
+///
+/// # Returns
+/// * `Result<(DataFrame, Vec<(String, usize)>)>` - Converted frame and the
+///   values set to null in each target column, in `targets` order;
+///   ValidationError for a missing column or one listed twice
+///
+/// # Example
+/// ```no_run
+/// use insightora_core::api::{cast_columns, ParallelCsvParser};
+/// use polars::prelude::DataType;
+///
+/// let df = ParallelCsvParser::new().parse("orders.csv").unwrap();
+/// let targets = [("quantity".to_string(), DataType::Int32), ("ordered_on".to_string(), DataType::Date)];
+/// let (orders, failures) = cast_columns(&df, &targets, None, false).unwrap();
+/// ```
+pub fn cast_columns(
+    df: &DataFrame,
+    targets: &[(String, DataType)],
+    format: Option<&DateFormat>,
+    strict: bool,
+) -> Result<(DataFrame, Vec<(String, usize)>), InsightoraError> {
+    let mut seen = HashSet::new();
+    for (name, _) in targets {
+        if df.column(name).is_err() {
+            return Err(InsightoraError::ValidationError(format!("Column '{}' not found", name)));
+        }
+        if !seen.insert(name.as_str()) {
+            return Err(InsightoraError::ValidationError(format!("Column '{}' is cast more than once", name)));
+        }
+    }
+
+    let mut cast = df.clone();
+    let mut failures = Vec::with_capacity(targets.len());
+    for (name, dtype) in targets {
+        let series = df.column(name)?;
+        let converted = cast_series(series, dtype, format)?;
+        // Conversions keep nulls, so every new null is a failed value
+        let failed = converted.null_count().saturating_sub(series.null_count());
+        if strict && failed > 0 {
+            let lost = converted.is_null() & series.is_not_null();
+            let row = lost.into_iter().position(|lost| lost == Some(true)).unwrap_or_default();
+            let text = series.slice(row as i64, 1).cast(&DataType::String)?;
+            return Err(InsightoraError::ParseError(format!(
+                "Cannot cast column '{}' from {} to {}: row {} has value '{}' ({} values don't convert)",
+                name,
+                series.dtype(),
+                dtype,
+                row,
+                text.str()?.get(0).unwrap_or_default(),
+                failed
+            )));
+        }
+        cast.replace(name, converted)?;
+        failures.push((name.clone(), failed));
+    }
+    Ok((cast, failures))
+}
+
+/// A column converted to `dtype`, with values that don't convert set to null
+fn cast_series(series: &Series, dtype: &DataType, format: Option<&DateFormat>) -> Result<Series, InsightoraError> {
+    if series.dtype() == dtype {
+        return Ok(series.clone());
+    }
+    match (series.dtype(), dtype) {
+        (DataType::String, DataType::Date | DataType::Datetime(_, _)) => parse_dates(series, dtype, format),
+        (DataType::String, DataType::Duration(_)) => {
+            let micros: Int64Chunked = series
+                .str()?
+                .into_iter()
+                .map(|value| parse_duration(value?, DurationFormat::Auto))
+                .collect();
+            Ok(micros.with_name(series.name()).into_series().cast(&DURATION_DTYPE)?.cast(dtype)?)
+        }
+        (DataType::String, DataType::Boolean) => {
+            let values: BooleanChunked = series
+                .str()?
+                .into_iter()
+                .map(|value| match value?.trim().to_ascii_lowercase().as_str() {
+                    "true" => Some(true),
+                    "false" => Some(false),
+                    _ => None,
+                })
+                .collect();
+            Ok(values.with_name(series.name()).into_series())
+        }
+        // Polars sets values out of the target's range to null rather than wrapping
+        _ => Ok(series.cast(dtype)?),
+    }
+}
+
+/// A text column read as `dtype` with `format`, or with the first ISO 8601
+/// layout each value matches
+fn parse_dates(series: &Series, dtype: &DataType, format: Option<&DateFormat>) -> Result<Series, InsightoraError> {
+    let formats: Vec<DateFormat> = match format {
+        Some(format) => vec![format.clone()],
+        // Zone-less layouts only; a datetime layout can't give a date
+        None => dates::iso_formats()
+            .into_iter()
+            .filter(|layout| match layout.dtype() {
+                DataType::Date => true,
+                DataType::Datetime(_, None) => dtype != &DataType::Date,
+                _ => false,
+            })
+            .collect(),
+    };
+    // Unparsed values are reported as cast failures instead
+    let mut warnings = WarningCollector::new();
+    let mut parsed: Option<Series> = None;
+    for layout in &formats {
+        let next = layout.parse_column(series, &mut warnings)?.cast(dtype)?;
+        parsed = Some(match parsed {
+            Some(done) => done.zip_with(&done.is_not_null(), &next)?,
+            None => next,
+        });
+    }
+    Ok(parsed.unwrap_or_else(|| Series::full_null(series.name(), series.len(), dtype)))
+}
+
+// ============================================================================
+// String Operations
+// ============================================================================
+
+/// Operation `string_op` applies to every value of a text column
+#[derive(Debug, Clone)]
+pub enum StringOp {
+    /// Whether the value holds a match of the pattern, as a Boolean column
+    Contains(Regex),
+    /// Every match replaced; with `literal` the replacement is used as is,
+    /// otherwise `$1` / `${name}` insert capture groups
+    Replace { pattern: Regex, replacement: String, literal: bool },
+    /// Split on `by` into `into` columns `{name}_0`, `{name}_1`, ...; the last
+    /// part keeps any further separators and values with fewer parts are
+    /// null in the remaining columns
+    Split { by: String, into: usize },
+    Lower,
+    Upper,
+    /// Leading and trailing whitespace removed
+    Strip,
+    /// At most `length` characters (default: the rest) from character
+    /// `start`, counted from the end when negative
+    Slice { start: i64, length: Option<usize> },
+    /// Length in characters, as UInt32
+    LenChars,
+}
+
+/// `pattern` as a regex, or matching its text literally when `regex` is false
+///
+/// # Returns
+/// * `Result<Regex>` - ValidationError with the regex crate's description
+///   of what's wrong with the pattern
+pub fn text_pattern(pattern: &str, regex: bool) -> Result<Regex, InsightoraError> {
+    let source = if regex { pattern.to_string() } else { regex::escape(pattern) };
+    Regex::new(&source).map_err(|e| InsightoraError::ValidationError(format!("Invalid regex '{}': {}", pattern, e)))
+}
+
+/// Apply a string operation to a text column
+///
+/// Nulls stay null. The result replaces the column, or is added as
+/// `output` when given; a split adds its columns after the others and
+/// keeps the source.
+///
+/// # Arguments
+/// * `df` - Input frame
+/// * `column` - Text column to read
+/// * `op` - Operation to apply
+/// * `output` - Name of the result column, or of the split columns' prefix
+/// * `cast` - Read a column of another type through its text form instead
+///   of failing
+///
+/// # Returns
+/// * `Result<DataFrame>` - Frame with the result; InvalidDataType for a
+///   column that isn't text without `cast`, ValidationError for a missing
+///   column, a split into no columns or a split column name already in use
+///
+/// # Example
+/// ```no_run
+/// use insightora_core::api::{string_op, text_pattern, ParallelCsvParser, StringOp};
+///
+/// let df = ParallelCsvParser::new().parse("tickets.csv").unwrap();
+/// let urgent = StringOp::Contains(text_pattern(r"(?i)\burgent\b", true).unwrap());
+/// let flagged = string_op(&df, "subject", &urgent, Some("is_urgent"), false).unwrap();
+/// ```
+pub fn string_op(
+    df: &DataFrame,
+    column: &str,
+    op: &StringOp,
+    output: Option<&str>,
+    cast: bool,
+) -> Result<DataFrame, InsightoraError> {
+    let series = df
+        .column(column)
+        .map_err(|_| InsightoraError::ValidationError(format!("Column '{}' not found", column)))?;
+    let series = match series.dtype() {
+        DataType::String => series.clone(),
+        _ if cast => series.cast(&DataType::String)?,
+        dtype => {
+            return Err(InsightoraError::InvalidDataType {
+                expected: "string column".to_string(),
+                actual: format!("{} ({})", dtype, column),
+            })
+        }
+    };
+    let text = series.str()?;
+    let name = output.unwrap_or(column);
+
+    let result = match op {
+        StringOp::Contains(pattern) => {
+            let values: BooleanChunked = text.into_iter().map(|value| value.map(|value| pattern.is_match(value))).collect();
+            values.into_series()
+        }
+        StringOp::Replace { pattern, replacement, literal } => {
+            let values: StringChunked = text
+                .into_iter()
+                .map(|value| {
+                    value.map(|value| {
+                        if *literal {
+                            pattern.replace_all(value, NoExpand(replacement))
+                        } else {
+                            pattern.replace_all(value, replacement.as_str())
+                        }
+                    })
+                })
+                .collect();
+            values.into_series()
+        }
+        StringOp::Split { by, into } => return split_text(df, text, by, *into, name),
+        StringOp::Lower => text.into_iter().map(|value| value.map(str::to_lowercase)).collect::<StringChunked>().into_series(),
+        StringOp::Upper => text.into_iter().map(|value| value.map(str::to_uppercase)).collect::<StringChunked>().into_series(),
+        StringOp::Strip => text.into_iter().map(|value| value.map(str::trim)).collect::<StringChunked>().into_series(),
+        StringOp::Slice { start, length } => {
+            let values: StringChunked = text
+                .into_iter()
+                .map(|value| {
+                    value.map(|value| {
+                        let skip = match usize::try_from(*start) {
+                            Ok(start) => start,
+                            Err(_) => value.chars().count().saturating_sub(start.unsigned_abs() as usize),
+                        };
+                        value.chars().skip(skip).take(length.unwrap_or(usize::MAX)).collect::<String>()
+                    })
+                })
+                .collect();
+            values.into_series()
+        }
+        StringOp::LenChars => {
+            let values: UInt32Chunked = text.into_iter().map(|value| value.map(|value| value.chars().count() as u32)).collect();
+            values.into_series()
+        }
+    };
+
+    let mut result_df = df.clone();
+    result_df.with_column(result.with_name(name))?;
+    Ok(result_df)
+}
+
+/// `df` with the parts of `text` split on `by` added as `{name}_0` .. `{name}_{into - 1}`
+fn split_text(df: &DataFrame, text: &StringChunked, by: &str, into: usize, name: &str) -> Result<DataFrame, InsightoraError> {
+    if into == 0 || by.is_empty() {
+        return Err(InsightoraError::ValidationError(
+            "split needs a non-empty separator and at least one column to split into".to_string(),
+        ));
+    }
+    let mut parts: Vec<Vec<Option<&str>>> = vec![Vec::with_capacity(text.len()); into];
+    for value in text.into_iter() {
+        let mut pieces = value.map(|value| value.splitn(into, by));
+        for part in parts.iter_mut() {
+            part.push(pieces.as_mut().and_then(Iterator::next));
+        }
+    }
+    let mut result_df = df.clone();
+    for (index, part) in parts.into_iter().enumerate() {
+        let part_name = format!("{}_{}", name, index);
+        if df.column(&part_name).is_ok() {
+            return Err(InsightoraError::ValidationError(format!(
+                "Split column '{}' is already in use; pass another output name",
+                part_name
+            )));
+        }
+        result_df.with_column(Series::new(&part_name, part))?;
+    }
+    Ok(result_df)
+}
+
+// ============================================================================
+// Column Renaming
+// ============================================================================
+
+/// How columns are renamed: by source name, by position or through a column dictionary
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnMapping {
+    /// (source, target) name pairs; all renames apply at once, so swaps work
+    Names(Vec<(String, String)>),
+    /// New names for the leading columns in order, e.g. for files without a header
+    Positions(Vec<String>),
+    /// Columns matching a canonical name or alias take the canonical name;
+    /// ambiguous matches are left alone, and missing required names are an error
+    Dictionary(ColumnDictionary),
+}
+
+impl ColumnMapping {
+    /// Names after renaming `names`
+    ///
+    /// With `strict`, a source name that isn't present (or more positional
+    /// names than columns) is an error; otherwise it is ignored. A dictionary
+    /// checks its required names either way, and the result must not
+    /// contain duplicates.
+    pub fn apply_to_names(&self, names: &[String], strict: bool) -> Result<Vec<String>, InsightoraError> {
+        let renamed: Vec<String> = match self {
+            ColumnMapping::Names(pairs) => {
+                let mut sources = HashSet::new();
+                for (source, _) in pairs {
+                    if !sources.insert(source.as_str()) {
+                        return Err(InsightoraError::ValidationError(format!(
+                            "Column '{}' is renamed more than once",
+                            source
+                        )));
+                    }
+                    if strict && !names.contains(source) {
+                        return Err(InsightoraError::ValidationError(format!(
+                            "Cannot rename column '{}': not found",
+                            source
+                        )));
+                    }
+                }
+                names
+                    .iter()
+                    .map(|name| {
+                        pairs
+                            .iter()
+                            .find(|(source, _)| source == name)
+                            .map_or_else(|| name.clone(), |(_, target)| target.clone())
+                    })
+                    .collect()
+            }
+            ColumnMapping::Positions(targets) => {
+                if strict && targets.len() > names.len() {
+                    return Err(InsightoraError::ValidationError(format!(
+                        "{} column names given for {} columns",
+                        targets.len(),
+                        names.len()
+                    )));
+                }
+                names
+                    .iter()
+                    .enumerate()
+                    .map(|(i, name)| targets.get(i).unwrap_or(name).clone())
+                    .collect()
+            }
+            ColumnMapping::Dictionary(dictionary) => {
+                let matched = dictionary.resolve_required(names)?;
+                names
+                    .iter()
+                    .map(|name| {
+                        matched
+                            .renames
+                            .iter()
+                            .find(|(source, _)| source == name)
+                            .map_or_else(|| name.clone(), |(_, canonical)| canonical.clone())
+                    })
+                    .collect()
+            }
+        };
+
+        let mut first_source: HashMap<&str, usize> = HashMap::new();
+        for (index, name) in renamed.iter().enumerate() {
+            if let Some(&first) = first_source.get(name.as_str()) {
+                return Err(InsightoraError::ValidationError(format!(
+                    "Renaming produces duplicate column '{}' from columns '{}' and '{}'",
+                    name,
+                    names[first],
+                    names[index]
+                )));
+            }
+            first_source.insert(name, index);
+        }
+        Ok(renamed)
+    }
+}
+
+/// Indices of the projected `columns` among the renamed columns
+///
+/// Each projected name may be a renamed (target) name or the original
+/// (source) name; target names win when a name is both.
+pub fn projection_indices(
+    original: &[String],
+    renamed: &[String],
+    columns: &[String],
+) -> Result<Vec<usize>, InsightoraError> {
+    columns
+        .iter()
+        .map(|column| {
+            renamed
+                .iter()
+                .position(|name| name == column)
+                .or_else(|| original.iter().position(|name| name == column))
+                .ok_or_else(|| InsightoraError::ValidationError(format!("Column '{}' not found", column)))
+        })
+        .collect()
+}
+
+/// Rename columns, then keep only the projected `columns` (source or target names)
+pub fn rename_and_project(
+    mut df: DataFrame,
+    mapping: Option<&ColumnMapping>,
+    columns: Option<&[String]>,
+) -> Result<DataFrame, InsightoraError> {
+    if mapping.is_none() && columns.is_none() {
+        return Ok(df);
+    }
+    let original: Vec<String> = df.get_column_names().iter().map(|s| s.to_string()).collect();
+    let renamed = match mapping {
+        Some(mapping) => mapping.apply_to_names(&original, true)?,
+        None => original.clone(),
+    };
+    df.set_column_names(&renamed)?;
+    match columns {
+        Some(columns) => {
+            let indices = projection_indices(&original, &renamed, columns)?;
+            Ok(DataFrame::new(indices.into_iter().map(|i| df.get_columns()[i].clone()).collect())?)
+        }
+        None => Ok(df),
+    }
+}
+
+/// Schema counterpart of `rename_and_project`
+pub fn rename_and_project_schema(
+    schema: &Schema,
+    mapping: Option<&ColumnMapping>,
+    columns: Option<&[String]>,
+) -> Result<Schema, InsightoraError> {
+    let original: Vec<String> = schema.iter_names().map(|s| s.to_string()).collect();
+    let renamed = match mapping {
+        Some(mapping) => mapping.apply_to_names(&original, true)?,
+        None => original.clone(),
+    };
+    let dtypes: Vec<DataType> = schema.iter_dtypes().cloned().collect();
+    let indices = match columns {
+        Some(columns) => projection_indices(&original, &renamed, columns)?,
+        None => (0..original.len()).collect(),
+    };
+    Ok(indices
+        .into_iter()
+        .map(|i| Field::new(&renamed[i], dtypes[i].clone()))
+        .collect())
+}
+
+/// Rename columns of in-memory data
+///
+/// With `strict`, every source name must exist (see `ColumnMapping::apply_to_names`).
+pub fn rename_columns(df: &DataFrame, mapping: &ColumnMapping, strict: bool) -> Result<DataFrame, InsightoraError> {
+    let original: Vec<String> = df.get_column_names().iter().map(|s| s.to_string()).collect();
+    let renamed = mapping.apply_to_names(&original, strict)?;
+    let mut renamed_df = df.clone();
+    renamed_df.set_column_names(&renamed)?;
+    Ok(renamed_df)
+}
+
+// ============================================================================
+// Category Ordering
+// ============================================================================
+
+/// What `set_category_order` does with values missing from the declared order
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnknownCategory {
+    /// Reject the column, listing the unknown values
+    #[default]
+    Error,
+    /// Rank unknown values after every declared category, alphabetically
+    Last,
+}
+
+impl UnknownCategory {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "error" => Ok(UnknownCategory::Error),
+            "last" => Ok(UnknownCategory::Last),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown category policy '{}'; expected 'error' or 'last'",
+                other
+            ))),
+        }
+    }
+}
+
+/// Turn a column into an ordered categorical (`low < medium < high`)
+///
+/// The column gets physical ordering with codes assigned in `order`, so
+/// `sort_data`, min/max aggregations and `describe` rank values by their
+/// position in `order` rather than alphabetically. Values are matched as
+/// strings and nulls stay null.
+///
+/// # Arguments
+/// * `df` - DataFrame holding the column
+/// * `column` - Column to convert
+/// * `order` - Categories from lowest to highest; duplicates are rejected
+/// * `unknown` - Whether values outside `order` are an error or rank last
+///
+/// # Returns
+/// * `Result<DataFrame>` - Copy of `df` with the column replaced
+///
+/// # Example
+/// ```no_run
+/// use insightora_core::api::{set_category_order, DataFrame, UnknownCategory};
+///
+/// # fn run(df: &DataFrame) -> insightora_core::api::Result<()> {
+/// let order = ["low", "medium", "high"].map(String::from);
+/// let ordered = set_category_order(df, "severity", &order, UnknownCategory::Error)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn set_category_order(
+    df: &DataFrame,
+    column: &str,
+    order: &[String],
+    unknown: UnknownCategory,
+) -> Result<DataFrame, InsightoraError> {
+    let series = df.column(column)?;
+    let mut declared = HashSet::with_capacity(order.len());
+    for category in order {
+        if !declared.insert(category.as_str()) {
+            return Err(InsightoraError::ValidationError(format!(
+                "Category '{}' appears more than once in the order for '{}'",
+                category, column
+            )));
+        }
+    }
+
+    let values = series.cast(&DataType::String)?;
+    let values = values.str()?;
+    let unknown_values: BTreeSet<&str> = values
+        .into_iter()
+        .flatten()
+        .filter(|value| !declared.contains(value))
+        .collect();
+
+    let mut categories: Vec<&str> = order.iter().map(String::as_str).collect();
+    if !unknown_values.is_empty() {
+        if unknown == UnknownCategory::Error {
+            let examples: Vec<String> = unknown_values.iter().take(5).map(|v| format!("'{}'", v)).collect();
+            return Err(InsightoraError::ValidationError(format!(
+                "Column '{}' has {} value(s) outside the category order: {}{}",
+                column,
+                unknown_values.len(),
+                examples.join(", "),
+                if unknown_values.len() > examples.len() { ", ..." } else { "" }
+            )));
+        }
+        categories.extend(unknown_values);
+    }
+
+    // Local categoricals number categories by first appearance, so seeding
+    // the builder with the categories fixes their codes before the values
+    let mut builder = CategoricalChunkedBuilder::new(
+        column,
+        categories.len() + values.len(),
+        CategoricalOrdering::Physical,
+    );
+    for &category in &categories {
+        builder.append_value(category);
+    }
+    for value in values {
+        match value {
+            Some(value) => builder.append_value(value),
+            None => builder.append_null(),
+        }
+    }
+    let ordered = builder
+        .finish()
+        .into_series()
+        .slice(categories.len() as i64, values.len());
+
+    let mut result = df.clone();
+    result.replace(column, ordered)?;
+    Ok(result)
+}
+
+/// Categories of an ordered categorical, lowest first
+///
+/// None unless the series is a categorical with physical ordering, the kind
+/// `set_category_order` produces. Lexically ordered categoricals rank by
+/// their string values and have no declared order.
+pub fn category_order(series: &Series) -> Option<Vec<String>> {
+    match series.dtype() {
+        DataType::Categorical(_, CategoricalOrdering::Physical) => {
+            let rev_map = series.categorical().ok()?.get_rev_map();
+            Some((0..rev_map.len() as u32).map(|code| rev_map.get(code).to_string()).collect())
+        }
+        _ => None,
+    }
+}
+
+/// Position of each value in the category order (None = null)
+///
+/// Ranks are comparable across rows, so `rank >= rank_of("medium")` selects
+/// medium severity and above.
+pub fn category_ranks(series: &Series) -> Result<UInt32Chunked, InsightoraError> {
+    if category_order(series).is_none() {
+        return Err(InsightoraError::InvalidDataType {
+            expected: "ordered categorical".to_string(),
+            actual: format!("{} ({})", series.dtype(), series.name()),
+        });
+    }
+    Ok(series.categorical()?.physical().clone())
+}
+
+/// Rebuild ordered categoricals from rank codes produced by `category_ranks`
+pub(crate) fn categories_from_ranks(
+    name: &str,
+    ranks: &Series,
+    categories: &[String],
+) -> Result<Series, InsightoraError> {
+    let ranks = ranks.cast(&DataType::UInt32)?;
+    let labels: Vec<Option<&str>> = ranks
+        .u32()?
+        .into_iter()
+        .map(|rank| rank.and_then(|r| categories.get(r as usize)).map(String::as_str))
+        .collect();
+    let df = DataFrame::new(vec![Series::new(name, labels)])?;
+    let df = set_category_order(&df, name, categories, UnknownCategory::Error)?;
+    Ok(df.column(name)?.clone())
+}
+
+// ============================================================================
+// Value Formatting
+// ============================================================================
+
+/// Suffix of the columns `format_columns` adds when not replacing
+pub const FORMATTED_SUFFIX: &str = "_formatted";
+
+/// Separators used when rendering numbers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberLocale {
+    /// Between groups of three integer digits (e.g. "," or "." or a narrow space)
+    pub thousands_separator: String,
+    /// Between the integer and fractional digits
+    pub decimal_separator: String,
+}
+
+impl Default for NumberLocale {
+    fn default() -> Self {
+        Self { thousands_separator: ",".to_string(), decimal_separator: ".".to_string() }
+    }
+}
+
+impl NumberLocale {
+    pub fn new(thousands_separator: &str, decimal_separator: &str) -> Result<Self, InsightoraError> {
+        if decimal_separator.is_empty() || decimal_separator == thousands_separator {
+            return Err(InsightoraError::ValidationError(
+                "decimal_separator must be non-empty and differ from thousands_separator".to_string(),
+            ));
+        }
+        Ok(Self {
+            thousands_separator: thousands_separator.to_string(),
+            decimal_separator: decimal_separator.to_string(),
+        })
+    }
+
+    /// Render an unsigned "1234.5" magnitude with this locale's separators
+    fn render(&self, magnitude: &str, grouped: bool) -> String {
+        let (int_part, frac_part) = match magnitude.split_once('.') {
+            Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+            None => (magnitude, None),
+        };
+        let mut out = String::with_capacity(magnitude.len() + int_part.len() / 3 * self.thousands_separator.len());
+        for (i, digit) in int_part.chars().enumerate() {
+            if grouped && i > 0 && (int_part.len() - i) % 3 == 0 {
+                out.push_str(&self.thousands_separator);
+            }
+            out.push(digit);
+        }
+        if let Some(frac_part) = frac_part {
+            out.push_str(&self.decimal_separator);
+            out.push_str(frac_part);
+        }
+        out
+    }
+}
+
+/// Which side of the amount a currency symbol goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymbolPlacement {
+    /// "$1,234.50"
+    #[default]
+    Prefix,
+    /// "1.234,50 €" (include any space in the symbol)
+    Suffix,
+}
+
+impl SymbolPlacement {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "prefix" | "before" => Ok(SymbolPlacement::Prefix),
+            "suffix" | "after" => Ok(SymbolPlacement::Suffix),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown symbol placement '{}'; expected prefix or suffix",
+                other
+            ))),
+        }
+    }
+}
+
+/// How a column's values are rendered
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatKind {
+    /// Plain number; `decimals` None keeps the shortest exact digits
+    Number { decimals: Option<usize>, thousands: bool },
+    /// Fraction shown as a percentage (0.125 -> "12.5%")
+    Percent { decimals: Option<usize>, thousands: bool },
+    /// Amount with a currency symbol; always grouped
+    Currency { symbol: String, placement: SymbolPlacement, decimals: Option<usize> },
+    /// Date or Datetime rendered with a strftime pattern (datetimes in UTC)
+    Datetime { pattern: String },
+}
+
+impl FormatKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FormatKind::Number { .. } => "number",
+            FormatKind::Percent { .. } => "percent",
+            FormatKind::Currency { .. } => "currency",
+            FormatKind::Datetime { .. } => "datetime",
+        }
+    }
+}
+
+/// Display format for one column
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueFormat {
+    pub kind: FormatKind,
+    /// Shown for nulls; None leaves them null
+    pub null_text: Option<String>,
+}
+
+impl ValueFormat {
+    pub fn new(kind: FormatKind) -> Self {
+        Self { kind, null_text: None }
+    }
+
+    pub fn with_null_text(mut self, text: &str) -> Self {
+        self.null_text = Some(text.to_string());
+        self
+    }
+
+    /// Check that the format fits a column of `dtype`
+    pub fn validate(&self, column: &str, dtype: &DataType) -> Result<(), InsightoraError> {
+        let fits = match &self.kind {
+            FormatKind::Datetime { pattern } => {
+                if StrftimeItems::new(pattern).any(|item| matches!(item, Item::Error)) {
+                    return Err(InsightoraError::ValidationError(format!(
+                        "Invalid datetime pattern '{}' for column '{}'",
+                        pattern, column
+                    )));
+                }
+                matches!(dtype, DataType::Date | DataType::Datetime(_, _))
+            }
+            _ => dtype.is_numeric(),
+        };
+        if fits || matches!(dtype, DataType::Null) {
+            return Ok(());
+        }
+        Err(InsightoraError::InvalidDataType {
+            expected: match self.kind {
+                FormatKind::Datetime { .. } => "Date or Datetime".to_string(),
+                _ => "numeric".to_string(),
+            },
+            actual: format!("{} ({})", dtype, column),
+        })
+    }
+
+    /// Render one number; `value` is the column value cast to Float64 and
+    /// `exact` its integer digits when the column is integral
+    fn render_number(&self, value: f64, exact: Option<i128>, locale: &NumberLocale) -> String {
+        let (decimals, grouped, scale) = match &self.kind {
+            FormatKind::Number { decimals, thousands } => (*decimals, *thousands, 1),
+            FormatKind::Percent { decimals, thousands } => (*decimals, *thousands, 100),
+            FormatKind::Currency { decimals, .. } => (*decimals, true, 1),
+            FormatKind::Datetime { .. } => (None, false, 1),
+        };
+        if !value.is_finite() {
+            return value.to_string();
+        }
+
+        let (negative, magnitude) = match exact {
+            Some(int) => {
+                let mut digits = (int * scale).unsigned_abs().to_string();
+                if let Some(places @ 1..) = decimals {
+                    digits.push('.');
+                    digits.push_str(&"0".repeat(places));
+                }
+                (int < 0, digits)
+            }
+            None => {
+                let scaled = value * scale as f64;
+                let digits = match decimals {
+                    Some(places) => format!("{:.*}", places, scaled.abs()),
+                    None => scaled.abs().to_string(),
+                };
+                (scaled < 0.0, digits)
+            }
+        };
+        // "-0.00" reads as a loss; only show the sign when a digit survives rounding
+        let sign = if negative && magnitude.bytes().any(|b| matches!(b, b'1'..=b'9')) { "-" } else { "" };
+        let body = locale.render(&magnitude, grouped);
+
+        match &self.kind {
+            FormatKind::Percent { .. } => format!("{}{}%", sign, body),
+            FormatKind::Currency { symbol, placement: SymbolPlacement::Prefix, .. } => format!("{}{}{}", sign, symbol, body),
+            FormatKind::Currency { symbol, placement: SymbolPlacement::Suffix, .. } => format!("{}{}{}", sign, body, symbol),
+            _ => format!("{}{}", sign, body),
+        }
+    }
+
+    /// Render a whole column as strings, keeping its name
+    pub fn format_series(&self, series: &Series, locale: &NumberLocale) -> Result<Series, InsightoraError> {
+        self.validate(series.name(), series.dtype())?;
+        let values: Vec<Option<String>> = match (&self.kind, series.dtype()) {
+            (_, DataType::Null) => vec![None; series.len()],
+            (FormatKind::Datetime { pattern }, DataType::Date) => {
+                let days = series.to_physical_repr();
+                days.i32()?.into_iter().map(|v| v.map(|d| format_date(d, Some(pattern)))).collect()
+            }
+            (FormatKind::Datetime { pattern }, DataType::Datetime(unit, _)) => {
+                let unit = *unit;
+                let stamps = series.to_physical_repr();
+                stamps.i64()?.into_iter().map(|v| v.map(|t| format_datetime(t, unit, Some(pattern)))).collect()
+            }
+            (_, DataType::UInt64) => series
+                .u64()?
+                .into_iter()
+                .map(|v| v.map(|x| self.render_number(x as f64, Some(x as i128), locale)))
+                .collect(),
+            (_, dtype) if dtype.is_integer() => {
+                let ints = series.cast(&DataType::Int64)?;
+                ints.i64()?
+                    .into_iter()
+                    .map(|v| v.map(|x| self.render_number(x as f64, Some(x as i128), locale)))
+                    .collect()
+            }
+            _ => {
+                let floats = series.cast(&DataType::Float64)?;
+                floats.f64()?.into_iter().map(|v| v.map(|x| self.render_number(x, None, locale))).collect()
+            }
+        };
+        let values: Vec<Option<String>> = match &self.null_text {
+            Some(text) => values.into_iter().map(|v| Some(v.unwrap_or_else(|| text.clone()))).collect(),
+            None => values,
+        };
+        Ok(Series::new(series.name(), values))
+    }
+}
+
+/// Render columns as display strings for reports
+///
+/// Each listed column is formatted with its `ValueFormat`. With `replace` the
+/// column is swapped for its String rendering in place; otherwise the
+/// original stays numeric and the rendering is appended as
+/// `<column>_formatted`.
+///
+/// # Arguments
+/// * `df` - Input DataFrame
+/// * `formats` - (column, format) pairs
+/// * `locale` - Thousands and decimal separators
+/// * `replace` - Overwrite the formatted columns instead of adding new ones
+///
+/// # Returns
+/// * `Result<DataFrame>` - Frame with the formatted columns
+pub fn format_columns(
+    df: &DataFrame,
+    formats: &[(String, ValueFormat)],
+    locale: &NumberLocale,
+    replace: bool,
+) -> Result<DataFrame, InsightoraError> {
+    let mut seen = HashSet::new();
+    for (column, _) in formats {
+        if !seen.insert(column.as_str()) {
+            return Err(InsightoraError::ValidationError(format!("Column '{}' has more than one format", column)));
+        }
+        if !replace && df.column(&format!("{}{}", column, FORMATTED_SUFFIX)).is_ok() {
+            return Err(InsightoraError::ValidationError(format!(
+                "Column '{}{}' already exists; pass replace=True or rename it first",
+                column, FORMATTED_SUFFIX
+            )));
+        }
+    }
+
+    let rendered = formats
+        .par_iter()
+        .map(|(column, format)| format.format_series(df.column(column)?, locale))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut formatted = df.clone();
+    for mut series in rendered {
+        if !replace {
+            let name = format!("{}{}", series.name(), FORMATTED_SUFFIX);
+            series.rename(&name);
+        }
+        formatted.with_column(series)?;
+    }
+    Ok(formatted)
+}
+
+// ============================================================================
+// Binning
+// ============================================================================
+
+/// Suffix of the bin index column added by `qcut` and `apply_bins`
+pub const BIN_SUFFIX: &str = "_bin";
+
+/// Where values outside the fitted edges go
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutOfRange {
+    /// Below the first edge to the first bin, above the last to the last bin
+    #[default]
+    Clip,
+    /// Null bin
+    Null,
+}
+
+impl OutOfRange {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "clip" => Ok(OutOfRange::Clip),
+            "null" => Ok(OutOfRange::Null),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown out-of-range policy '{}'; expected 'clip' or 'null'",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutOfRange::Clip => "clip",
+            OutOfRange::Null => "null",
+        }
+    }
+}
+
+/// Bin given to NaN values (nulls always stay null)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NanBin {
+    /// Null bin
+    #[default]
+    Null,
+    /// An extra bin after the last one, labelled "NaN"
+    Separate,
+}
+
+impl NanBin {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "null" => Ok(NanBin::Null),
+            "separate" => Ok(NanBin::Separate),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown NaN policy '{}'; expected 'null' or 'separate'",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NanBin::Null => "null",
+            NanBin::Separate => "separate",
+        }
+    }
+}
+
+/// Fitted bins, reusable on other data without refitting
+///
+/// Bin `i` covers `(edges[i], edges[i + 1]]`; the first bin also includes
+/// `edges[0]`. Quantiles that land on the same value (heavily tied data)
+/// share one edge, so there can be fewer bins than requested. The spec
+/// serializes to JSON with `to_json` so it can be stored with a model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BinSpec {
+    pub column: String,
+    /// Quantiles the edges were fitted at
+    pub quantiles: Vec<f64>,
+    /// Strictly increasing bin edges
+    pub edges: Vec<f64>,
+    /// One label per bin, e.g. "[1, 5]", "(5, 9]"
+    pub labels: Vec<String>,
+    pub out_of_range: OutOfRange,
+    pub nan: NanBin,
+}
+
+impl BinSpec {
+    pub fn num_bins(&self) -> usize {
+        self.edges.len().saturating_sub(1)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("bin specs always serialize")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, InsightoraError> {
+        let spec: BinSpec = serde_json::from_str(json)
+            .map_err(|e| InsightoraError::ValidationError(format!("Invalid bin spec: {}", e)))?;
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    /// Check that the edges are finite and strictly increasing, with one label per bin
+    pub fn validate(&self) -> Result<(), InsightoraError> {
+        let invalid = |message: &str| {
+            InsightoraError::ValidationError(format!("Invalid bin spec for '{}': {}", self.column, message))
+        };
+        if self.edges.len() < 2 {
+            return Err(invalid("at least two edges are required"));
+        }
+        if self.edges.iter().any(|e| !e.is_finite()) {
+            return Err(invalid("edges must be finite"));
+        }
+        if self.edges.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(invalid("edges must be strictly increasing"));
+        }
+        if self.labels.len() != self.num_bins() {
+            return Err(invalid(&format!("{} labels for {} bins", self.labels.len(), self.num_bins())));
+        }
+        Ok(())
+    }
+
+    /// Bin of one value; None for null bins
+    pub fn bin_of(&self, value: f64) -> Option<u32> {
+        let last = self.num_bins() - 1;
+        if value.is_nan() {
+            return match self.nan {
+                NanBin::Null => None,
+                NanBin::Separate => Some(self.num_bins() as u32),
+            };
+        }
+        let (first_edge, last_edge) = (self.edges[0], self.edges[self.edges.len() - 1]);
+        if value < first_edge || value > last_edge {
+            return match self.out_of_range {
+                OutOfRange::Null => None,
+                OutOfRange::Clip if value < first_edge => Some(0),
+                OutOfRange::Clip => Some(last as u32),
+            };
+        }
+        // First upper edge >= value
+        Some(self.edges[1..].partition_point(|&edge| edge < value) as u32)
+    }
+}
+
+/// Fit quantile bin edges to a numeric column
+///
+/// Edges are the requested quantiles of the non-null, non-NaN values,
+/// interpolated linearly between neighbouring values.
+///
+/// # Arguments
+/// * `series` - Numeric column
+/// * `quantiles` - Increasing quantiles in 0..=1, including both ends for
+///   bins that cover the whole fitted range (e.g. `[0, 0.25, 0.5, 0.75, 1]`)
+/// * `out_of_range` / `nan` - Policies recorded in the spec for `apply_bins`
+///
+/// # Returns
+/// * `Result<BinSpec>` - ValidationError when the column has fewer than two
+///   distinct values at the quantiles
+pub fn fit_quantile_bins(
+    series: &Series,
+    quantiles: &[f64],
+    out_of_range: OutOfRange,
+    nan: NanBin,
+) -> Result<BinSpec, InsightoraError> {
+    if quantiles.len() < 2
+        || quantiles.iter().any(|q| !(0.0..=1.0).contains(q))
+        || quantiles.windows(2).any(|pair| pair[0] >= pair[1])
+    {
+        return Err(InsightoraError::ValidationError(
+            "quantiles must be at least two increasing values between 0 and 1".to_string(),
+        ));
+    }
+    if !series.dtype().is_numeric() {
+        return Err(InsightoraError::InvalidDataType {
+            expected: "numeric".to_string(),
+            actual: format!("{} ({})", series.dtype(), series.name()),
+        });
+    }
+    let floats = series.cast(&DataType::Float64)?;
+    let mut values: Vec<f64> = floats.f64()?.into_iter().flatten().filter(|v| !v.is_nan()).collect();
+    values.sort_by(f64::total_cmp);
+
+    let mut edges: Vec<f64> = Vec::with_capacity(quantiles.len());
+    if !values.is_empty() {
+        for &q in quantiles {
+            let position = q * (values.len() - 1) as f64;
+            let (lower, upper) = (position.floor() as usize, position.ceil() as usize);
+            let edge = values[lower] + (values[upper] - values[lower]) * (position - lower as f64);
+            if edges.last().is_none_or(|&previous| edge > previous) {
+                edges.push(edge);
+            }
+        }
+    }
+    if edges.len() < 2 {
+        return Err(InsightoraError::ValidationError(format!(
+            "Column '{}' has too few distinct values to bin",
+            series.name()
+        )));
+    }
+
+    let labels = edges
+        .windows(2)
+        .enumerate()
+        .map(|(i, pair)| format!("{}{}, {}]", if i == 0 { "[" } else { "(" }, pair[0], pair[1]))
+        .collect();
+    Ok(BinSpec {
+        column: series.name().to_string(),
+        quantiles: quantiles.to_vec(),
+        edges,
+        labels,
+        out_of_range,
+        nan,
+    })
+}
+
+/// Assign bins from a fitted spec without refitting
+///
+/// Adds `<column>_bin` (UInt32 bin index, null per the spec's policies) to
+/// a copy of `df`, replacing an existing one.
+pub fn apply_bins(df: &DataFrame, spec: &BinSpec) -> Result<DataFrame, InsightoraError> {
+    spec.validate()?;
+    let series = df.column(&spec.column)?;
+    if !series.dtype().is_numeric() {
+        return Err(InsightoraError::InvalidDataType {
+            expected: "numeric".to_string(),
+            actual: format!("{} ({})", series.dtype(), series.name()),
+        });
+    }
+    let floats = series.cast(&DataType::Float64)?;
+    let bins: UInt32Chunked = floats
+        .f64()?
+        .into_iter()
+        .map(|value| value.and_then(|v| spec.bin_of(v)))
+        .collect();
+
+    let mut binned = df.clone();
+    binned.with_column(bins.with_name(&format!("{}{}", spec.column, BIN_SUFFIX)).into_series())?;
+    Ok(binned)
+}
+
+/// Fit quantile bins on a column and assign them
+///
+/// # Returns
+/// * `Result<(DataFrame, BinSpec)>` - Data with `<column>_bin` added, and
+///   the spec to pass to `apply_bins` for other data
+pub fn qcut(
+    df: &DataFrame,
+    column: &str,
+    quantiles: &[f64],
+    out_of_range: OutOfRange,
+    nan: NanBin,
+) -> Result<(DataFrame, BinSpec), InsightoraError> {
+    let spec = fit_quantile_bins(df.column(column)?, quantiles, out_of_range, nan)?;
+    Ok((apply_bins(df, &spec)?, spec))
+}
+
+/// Evenly spaced quantiles for `bins` equal-frequency bins
+pub fn even_quantiles(bins: usize) -> Vec<f64> {
+    (0..=bins).map(|i| i as f64 / bins as f64).collect()
+}
+
+// ============================================================================
+// Conditional Updates
+// ============================================================================
+
+/// New value for the rows an update selects
+#[derive(Debug, Clone)]
+pub enum Assignment {
+    /// The same value for every selected row, as a one-element Series
+    Value(Series),
+    /// Evaluated per row on the values from before the update
+    Expression(ColumnExpression),
+}
+
+/// Rows changed by `update_where`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UpdateReport {
+    /// Rows where the predicate is true
+    pub rows_matched: usize,
+    /// Rows whose value changed, per assigned column in assignment order;
+    /// a selected row already holding the new value is not counted
+    pub modified: Vec<(String, usize)>,
+}
+
+/// `UPDATE ... SET column = value WHERE predicate` for in-memory data
+///
+/// Rows where the predicate is false or null keep their values. The
+/// predicate and every assignment read the values from before the update,
+/// so assigning to a column the predicate or another assignment uses
+/// changes neither which rows are selected nor what they get.
+///
+/// # Arguments
+/// * `df` - Input frame
+/// * `predicate` - Row condition (see the grammar in `expressions`)
+/// * `assignments` - Existing column and its new value, each column once
+///
+/// # Returns
+/// * `Result<(DataFrame, UpdateReport)>` - Updated frame and counts;
+///   ValidationError before any row is updated for unknown columns, a
+///   predicate that isn't a condition, or a value the column's type can't hold
+pub fn update_where(
+    df: &DataFrame,
+    predicate: &Predicate,
+    assignments: &[(String, Assignment)],
+) -> Result<(DataFrame, UpdateReport), InsightoraError> {
+    let schema: Vec<(String, DataType)> = df
+        .get_columns()
+        .iter()
+        .map(|series| (series.name().to_string(), series.dtype().clone()))
+        .collect();
+    let updates = update_exprs(&schema, predicate, assignments)?;
+
+    let mask = df.clone().lazy().select([predicate.mask(&df.schema())]).collect()?;
+    let mask = mask.get_columns()[0].bool()?;
+    let selected = mask.into_iter().filter(|selected| *selected == Some(true)).count();
+    // A predicate without columns is a single value for every row
+    let rows_matched = if mask.len() == df.height() { selected } else { selected * df.height() };
+
+    let updated = df.clone().lazy().with_columns(updates).collect()?;
+    let modified = assignments
+        .iter()
+        .map(|(column, _)| {
+            let changed = df.column(column)?.not_equal_missing(updated.column(column)?)?;
+            Ok((column.clone(), changed.into_iter().filter(|c| *c == Some(true)).count()))
+        })
+        .collect::<Result<Vec<_>, InsightoraError>>()?;
+    Ok((updated, UpdateReport { rows_matched, modified }))
+}
+
+/// Check an update against `schema` and build one expression per assigned column
+///
+/// Shared by `update_where` and the pipeline's update step, which checks
+/// against the declared schema before any file is read.
+pub(crate) fn update_exprs(
+    schema: &[(String, DataType)],
+    predicate: &Predicate,
+    assignments: &[(String, Assignment)],
+) -> Result<Vec<Expr>, InsightoraError> {
+    let dtype_of = |name: &str| schema.iter().find(|(column, _)| column == name).map(|(_, dtype)| dtype);
+    let mut seen = HashSet::new();
+    if let Some((column, _)) = assignments.iter().find(|(column, _)| !seen.insert(column.as_str())) {
+        return Err(InsightoraError::ValidationError(format!("Column '{}' is assigned twice", column)));
+    }
+    let mut used = predicate.columns();
+    for (column, assignment) in assignments {
+        used.push(column.as_str());
+        if let Assignment::Expression(expression) = assignment {
+            used.extend(expression.columns());
+        }
+    }
+    if let Some(missing) = used.into_iter().find(|name| dtype_of(name).is_none()) {
+        return Err(InsightoraError::ValidationError(format!("Column '{}' not found", missing)));
+    }
+
+    // Types come from evaluating on an empty frame, so nothing runs on the data yet
+    let probe = DataFrame::new_no_checks(schema.iter().map(|(name, dtype)| Series::new_empty(name, dtype)).collect());
+    let schema: Schema = schema.iter().map(|(name, dtype)| Field::new(name, dtype.clone())).collect();
+    let dtype_on_probe = |expr: Expr| -> Result<DataType, InsightoraError> {
+        Ok(probe.clone().lazy().select([expr]).collect()?.get_columns()[0].dtype().clone())
+    };
+    let condition = dtype_on_probe(predicate.expr(&schema))?;
+    if !matches!(condition, DataType::Boolean | DataType::Null) {
+        return Err(InsightoraError::ValidationError(format!(
+            "Predicate '{}' is not a condition; it evaluates to {}",
+            predicate.source, condition
+        )));
+    }
+
+    assignments
+        .iter()
+        .map(|(column, assignment)| {
+            let target = dtype_of(column).cloned().unwrap_or(DataType::Null);
+            let value = match assignment {
+                Assignment::Value(value) => {
+                    if value.len() != 1 {
+                        return Err(InsightoraError::ValidationError(format!(
+                            "Value for column '{}' must be a single value, got {}",
+                            column,
+                            value.len()
+                        )));
+                    }
+                    check_assignable(column, value.dtype(), &target)?;
+                    let value = value.strict_cast(&target).map_err(|_| {
+                        InsightoraError::ValidationError(format!(
+                            "Value {} does not fit column '{}' of type {}",
+                            value.get(0).map(|v| v.to_string()).unwrap_or_default(),
+                            column,
+                            target
+                        ))
+                    })?;
+                    lit(value).first()
+                }
+                Assignment::Expression(expression) => {
+                    check_assignable(column, &dtype_on_probe(expression.expr())?, &target)?;
+                    expression.expr().strict_cast(target.clone())
+                }
+            };
+            Ok(when(predicate.mask(&schema)).then(value).otherwise(col(column)).alias(column))
+        })
+        .collect()
+}
+
+/// Whether a `value` can be stored in a `column` without losing its meaning:
+/// integers into any numeric column, floats into floats, dates into datetimes
+fn check_assignable(column: &str, value: &DataType, target: &DataType) -> Result<(), InsightoraError> {
+    let decimal = |dtype: &DataType| matches!(dtype, DataType::Decimal(..));
+    let assignable = value == target
+        || *value == DataType::Null
+        || (value.is_integer() && (target.is_numeric() || decimal(target)))
+        || (value.is_float() && target.is_float())
+        || (decimal(value) && (target.is_float() || decimal(target)))
+        || (matches!(value, DataType::Date | DataType::Datetime(..)) && matches!(target, DataType::Datetime(..)))
+        || (matches!(value, DataType::Duration(_)) && matches!(target, DataType::Duration(_)));
+    if assignable {
+        Ok(())
+    } else {
+        Err(InsightoraError::ValidationError(format!(
+            "Cannot assign a {} value to column '{}' of type {}",
+            value, column, target
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::aggregations::{aggregate_duration, DurationAggregation};
+    use polars::export::chrono::{NaiveDate, NaiveDateTime};
+
+    fn ts(h: u32, m: u32, s: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().and_hms_opt(h, m, s).unwrap()
+    }
+
+    #[test]
+    fn test_parse_duration_formats() {
+        let auto = DurationFormat::Auto;
+        assert_eq!(parse_duration("02:35:17", auto), Some(9317 * MICROS_PER_SECOND));
+        assert_eq!(parse_duration("36:00:00", auto), Some(36 * MICROS_PER_HOUR));
+        assert_eq!(parse_duration("00:00:01.5", auto), Some(1_500_000));
+        assert_eq!(parse_duration("1d 4h", auto), Some(MICROS_PER_DAY + 4 * MICROS_PER_HOUR));
+        assert_eq!(parse_duration("2h30m", auto), Some(150 * MICROS_PER_MINUTE));
+        assert_eq!(parse_duration("1.5 hours", auto), Some(90 * MICROS_PER_MINUTE));
+        assert_eq!(parse_duration("250ms", auto), Some(250_000));
+        assert_eq!(parse_duration("P1DT4H30M", auto), Some(MICROS_PER_DAY + 270 * MICROS_PER_MINUTE));
+        assert_eq!(parse_duration("-PT15.5S", auto), Some(-15_500_000));
+
+        assert_eq!(parse_duration("01:75:00", auto), None);
+        assert_eq!(parse_duration("P1Y", auto), None);
+        assert_eq!(parse_duration("4 parsecs", auto), None);
+        assert_eq!(parse_duration("1d 4h", DurationFormat::Clock), None);
+    }
+
+    #[test]
+    fn test_cast_to_duration() {
+        let s = Series::new("elapsed", &[Some("1h"), None, Some("00:30:00")]);
+        let d = cast_to_duration(&s, DurationFormat::Auto).unwrap();
+        assert_eq!(d.dtype(), &DURATION_DTYPE);
+        assert_eq!(d.null_count(), 1);
+
+        let bad = Series::new("elapsed", &["1h", "soon"]);
+        let err = cast_to_duration(&bad, DurationFormat::Auto).unwrap_err();
+        assert!(err.to_string().contains("'soon'"));
+    }
+
+    #[test]
+    fn test_average_session_length() {
+        let start = Series::new("start", &[ts(9, 0, 0), ts(10, 15, 0), ts(13, 0, 0)]);
+        let end = Series::new("end", &[ts(9, 30, 0), ts(11, 15, 0), ts(13, 45, 0)]);
+
+        let length = datetime_diff(&end, &start).unwrap();
+        let mean = aggregate_duration(&length, DurationAggregation::Mean).unwrap();
+        // (30 + 60 + 45) / 3 minutes
+        assert_eq!(mean, Some(45 * MICROS_PER_MINUTE));
+
+        let total = aggregate_duration(&length, DurationAggregation::Sum).unwrap();
+        assert_eq!(total, Some(135 * MICROS_PER_MINUTE));
+
+        // The shifted column keeps the name of the datetime it was added to
+        let shifted = add_duration(&start, &length).unwrap();
+        assert_eq!(shifted.name(), "start");
+        assert!(shifted.equals(&end.cast(shifted.dtype()).unwrap().with_name("start")));
+    }
+
+    #[test]
+    fn test_datetime_features_and_truncation() {
+        let at = |text: &str| NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap();
+        let micros = |text: &str| at(text).and_utc().timestamp_micros();
+        let day = |y: i32, m: u32, d: u32| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let df = df!(
+            "at" => [Some(at("2024-03-31 23:50")), None, Some(at("2024-01-06 08:05"))],
+            "day" => [Some(day(2024, 2, 29)), None, Some(day(2023, 1, 1))],
+        )
+        .unwrap();
+        let numbers = |df: &DataFrame, name: &str| -> Vec<Option<i32>> { df.column(name).unwrap().i32().unwrap().into_iter().collect() };
+        let flags = |df: &DataFrame, name: &str| -> Vec<Option<bool>> { df.column(name).unwrap().bool().unwrap().into_iter().collect() };
+        let values = |df: &DataFrame, name: &str| -> Vec<Option<i64>> {
+            let series = df.column(name).unwrap();
+            micros_of(series, &datetime_dtype(series).unwrap()).unwrap().into_iter().collect()
+        };
+
+        let names = ["quarter", "week", "weekday", "hour", "minute", "is_weekend", "is_month_end", "epoch_seconds"];
+        let features: Vec<DatetimeFeature> = names.iter().map(|name| DatetimeFeature::from_name(name).unwrap()).collect();
+        let featured = datetime_features(&df, "at", &features).unwrap();
+        assert_eq!(featured.width(), 10);
+        assert_eq!(numbers(&featured, "at_quarter"), [Some(1), None, Some(1)]);
+        assert_eq!(numbers(&featured, "at_week"), [Some(13), None, Some(1)]);
+        assert_eq!(numbers(&featured, "at_weekday"), [Some(7), None, Some(6)]);
+        assert_eq!(numbers(&featured, "at_hour"), [Some(23), None, Some(8)]);
+        assert_eq!(flags(&featured, "at_is_weekend"), [Some(true), None, Some(true)]);
+        assert_eq!(flags(&featured, "at_is_month_end"), [Some(true), None, Some(false)]);
+        assert_eq!(featured.column("at_epoch_seconds").unwrap().i64().unwrap().get(2), Some(1_704_528_300));
+
+        let dated = datetime_features(&df, "day", &[DatetimeFeature::Month, DatetimeFeature::IsMonthEnd]).unwrap();
+        assert_eq!(numbers(&dated, "day_month"), [Some(2), None, Some(1)]);
+        assert_eq!(flags(&dated, "day_is_month_end"), [Some(true), None, Some(false)]);
+        let err = datetime_features(&df, "day", &[DatetimeFeature::Hour]).unwrap_err();
+        assert!(matches!(err, InsightoraError::InvalidDataType { .. }), "{}", err);
+        assert!(datetime_features(&featured, "at", &[DatetimeFeature::Hour]).is_err());
+
+        let truncate = |df: &DataFrame, column: &str, every: &str, tz: Option<&str>| {
+            truncate_datetime(df, column, &TruncateEvery::parse(every).unwrap(), tz)
+        };
+        let quarter_hours = truncate(&df, "at", "15m", None).unwrap();
+        assert_eq!(quarter_hours.column("at").unwrap().dtype(), df.column("at").unwrap().dtype());
+        assert_eq!(values(&quarter_hours, "at"), [Some(micros("2024-03-31 23:45")), None, Some(micros("2024-01-06 08:00"))]);
+        assert_eq!(values(&truncate(&df, "at", "1w", None).unwrap(), "at")[0], Some(micros("2024-03-25 00:00")));
+        assert_eq!(values(&truncate(&df, "at", "1mo", None).unwrap(), "at")[0], Some(micros("2024-03-01 00:00")));
+        assert_eq!(values(&truncate(&df, "at", "1q", None).unwrap(), "at")[2], Some(micros("2024-01-01 00:00")));
+        assert_eq!(values(&truncate(&df, "day", "1mo", None).unwrap(), "day")[0], Some(micros("2024-02-01 00:00")));
+        assert!(matches!(truncate(&df, "day", "1h", None), Err(InsightoraError::InvalidDataType { .. })));
+
+        // 23:50 UTC is already the next day at +02:00
+        let physical = df.column("at").unwrap().cast(&DataType::Datetime(TimeUnit::Microseconds, None)).unwrap();
+        let utc = physical.to_physical_repr().i64().unwrap().clone().into_datetime(TimeUnit::Microseconds, Some("UTC".to_string()));
+        let zoned = DataFrame::new(vec![utc.into_series()]).unwrap();
+        assert_eq!(values(&truncate(&zoned, "at", "1d", None).unwrap(), "at")[0], Some(micros("2024-03-31 00:00")));
+        assert_eq!(values(&truncate(&zoned, "at", "1d", Some("+02:00")).unwrap(), "at")[0], Some(micros("2024-03-31 22:00")));
+        assert!(truncate(&zoned, "at", "1d", Some("Europe/Berlin")).is_err());
+        assert!(truncate(&df, "at", "1d", Some("+02:00")).is_err());
+        assert!(TruncateEvery::parse("0h").is_err() && TruncateEvery::parse("1fortnight").is_err());
+    }
+
+    #[test]
+    fn test_drop_duplicates_with_float_precision() {
+        let df = df! {
+            "amount" => [Some(0.1 + 0.2), Some(0.3), Some(f64::NAN), Some(f64::NAN), None, None],
+            "id" => [1, 2, 3, 4, 5, 6],
+        }
+        .unwrap();
+        let subset = vec!["amount".to_string()];
+
+        let (exact, report) = drop_duplicates(&df, Some(&subset), UniqueKeepStrategy::First, &FloatKeyOptions::default()).unwrap();
+        assert_eq!(exact.column("amount").unwrap().f64().unwrap().get(1), Some(0.3));
+        assert_eq!(report.rows_affected, 0);
+
+        let options = FloatKeyOptions {
+            precision: Some(FloatPrecision::Decimals(9)),
+            nan_equal: true,
+        };
+        let (deduped, report) = drop_duplicates(&df, Some(&subset), UniqueKeepStrategy::First, &options).unwrap();
+        let ids: Vec<i32> = deduped.column("id").unwrap().i32().unwrap().into_no_null_iter().collect();
+        // 0.3 collapses into 0.1 + 0.2; NaNs group together; nulls group together
+        assert_eq!(ids, vec![1, 3, 5]);
+        assert_eq!(report.rows_affected, 1);
+        // The kept value is the stored one
+        assert_eq!(deduped.column("amount").unwrap().f64().unwrap().get(0), Some(0.1 + 0.2));
+
+        let options = FloatKeyOptions { nan_equal: false, ..options };
+        let (deduped, _) = drop_duplicates(&df, Some(&subset), UniqueKeepStrategy::First, &options).unwrap();
+        let ids: Vec<i32> = deduped.column("id").unwrap().i32().unwrap().into_no_null_iter().collect();
+        assert_eq!(ids, vec![1, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_duplicates_treat_null_keys_as_equal() {
+        let df = df! {
+            "region" => [Some("EU"), None, Some("EU"), None, Some("US"), Some("EU")],
+            "year" => [Some(2024), None, Some(2024), None, Some(2024), None],
+            "id" => [1, 2, 3, 4, 5, 6],
+        }
+        .unwrap();
+        let subset = vec!["region".to_string(), "year".to_string()];
+        let exact = FloatKeyOptions::default();
+        let ids = |keep: UniqueKeepStrategy| -> Vec<i32> {
+            let (deduped, _) = drop_duplicates(&df, Some(&subset), keep, &exact).unwrap();
+            deduped.column("id").unwrap().i32().unwrap().into_no_null_iter().collect()
+        };
+        let marked = |keep: UniqueKeepStrategy| -> Vec<bool> {
+            duplicate_mask(&df, Some(&subset), keep, &exact).unwrap().into_no_null_iter().collect()
+        };
+
+        // (null, null) rows 2 and 4 are one key; (EU, null) is its own key
+        assert_eq!(ids(UniqueKeepStrategy::First), [1, 2, 5, 6]);
+        assert_eq!(ids(UniqueKeepStrategy::Last), [3, 4, 5, 6]);
+        assert_eq!(ids(UniqueKeepStrategy::None), [5, 6]);
+        assert_eq!(marked(UniqueKeepStrategy::First), [false, false, true, true, false, false]);
+        assert_eq!(marked(UniqueKeepStrategy::Last), [true, true, false, false, false, false]);
+        assert_eq!(marked(UniqueKeepStrategy::None), [true, true, true, true, false, false]);
+        // Without a subset the ids differ, so nothing is a duplicate
+        assert!(!duplicate_mask(&df, None, UniqueKeepStrategy::None, &exact).unwrap().any());
+
+        let missing = vec!["country".to_string()];
+        let err = drop_duplicates(&df, Some(&missing), UniqueKeepStrategy::First, &exact).unwrap_err();
+        assert!(matches!(err, InsightoraError::ValidationError(ref message) if message.contains("'country'")), "{}", err);
+    }
+
+    #[test]
+    #[ignore = "benchmark; run with --ignored --nocapture"]
+    fn bench_drop_duplicates_by_three_column_key() {
+        let rows = 20_000_000u32;
+        let df = df! {
+            "customer" => (0..rows).map(|i| i % 1_000_003).collect::<Vec<_>>(),
+            "day" => (0..rows).map(|i| (i % 365) as i32).collect::<Vec<_>>(),
+            "channel" => (0..rows).map(|i| ["web", "store", "phone"][(i % 3) as usize]).collect::<Vec<_>>(),
+            "amount" => (0..rows).map(f64::from).collect::<Vec<_>>(),
+        }
+        .unwrap();
+        let subset = vec!["customer".to_string(), "day".to_string(), "channel".to_string()];
+
+        let start = std::time::Instant::now();
+        let (deduped, _) = drop_duplicates(&df, Some(&subset), UniqueKeepStrategy::First, &FloatKeyOptions::default()).unwrap();
+        let elapsed = start.elapsed();
+        println!("drop_duplicates: {} of {} rows kept in {:?}", deduped.height(), rows, elapsed);
+        assert!(elapsed < std::time::Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_cast_columns_strict_and_lenient() {
+        let df = df!(
+            "qty" => [Some("1"), Some("abc"), Some("3"), None],
+            "big" => [1i64, 1 << 40, -5, 7],
+            "flag" => [0i64, 2, 1, 0],
+            "day" => ["2024-03-01", "01/03/2024", "2024-02-30", "2024-03-04"],
+        )
+        .unwrap();
+        let targets = vec![
+            ("qty".to_string(), DataType::Int64),
+            ("big".to_string(), DataType::Int32),
+            ("flag".to_string(), DataType::Boolean),
+            ("day".to_string(), DataType::Date),
+        ];
+
+        let (cast, failures) = cast_columns(&df, &targets, None, false).unwrap();
+        let counts: Vec<(&str, usize)> = failures.iter().map(|(name, count)| (name.as_str(), *count)).collect();
+        assert_eq!(counts, [("qty", 1), ("big", 1), ("flag", 0), ("day", 2)]);
+        let big: Vec<Option<i32>> = cast.column("big").unwrap().i32().unwrap().into_iter().collect();
+        assert_eq!(big, [Some(1), None, Some(-5), Some(7)]);
+        let flag: Vec<Option<bool>> = cast.column("flag").unwrap().bool().unwrap().into_iter().collect();
+        assert_eq!(flag, [Some(false), Some(true), Some(true), Some(false)]);
+        assert_eq!(cast.column("day").unwrap().dtype(), &DataType::Date);
+
+        let err = cast_columns(&df, &targets, None, true).unwrap_err().to_string();
+        assert!(err.contains("column 'qty'") && err.contains("row 1 has value 'abc'"), "{}", err);
+        let err = cast_columns(&df, &targets[1..2], None, true).unwrap_err().to_string();
+        assert!(err.contains("row 1 has value '1099511627776'"), "{}", err);
+
+        // A format replaces the ISO layouts; the datetime keeps the parsed day at midnight
+        let european = DateFormat::from_pattern("%d/%m/%Y").unwrap();
+        let day = [("day".to_string(), DataType::Datetime(TimeUnit::Milliseconds, None))];
+        let (cast, failures) = cast_columns(&df, &day, Some(&european), false).unwrap();
+        assert_eq!((failures[0].1, cast.column("day").unwrap().null_count()), (3, 3));
+
+        let round_trip = [("flag".to_string(), DataType::String)];
+        let (text, _) = cast_columns(&cast_columns(&df, &targets[2..3], None, true).unwrap().0, &round_trip, None, true).unwrap();
+        let words = [("flag".to_string(), DataType::Boolean)];
+        assert_eq!(cast_columns(&text, &words, None, true).unwrap().0.column("flag").unwrap().bool().unwrap().get(1), Some(true));
+        assert!(cast_columns(&df, &vec![("qty".to_string(), DataType::Int8); 2], None, false).is_err());
+    }
+
+    #[test]
+    fn test_string_ops_propagate_nulls() {
+        let df = df!(
+            "subject" => [Some("  Urgent: disk FULL "), None, Some("re: ünïcode  ")],
+            "code" => [Some(12i64), Some(7), None],
+        )
+        .unwrap();
+        let apply = |op: StringOp, output: Option<&str>| string_op(&df, "subject", &op, output, false).unwrap();
+        let text = |df: &DataFrame, name: &str| -> Vec<Option<String>> {
+            df.column(name).unwrap().str().unwrap().into_iter().map(|v| v.map(str::to_string)).collect()
+        };
+        let owned = |values: &[Option<&str>]| -> Vec<Option<String>> { values.iter().map(|v| v.map(str::to_string)).collect() };
+
+        let found = apply(StringOp::Contains(text_pattern(r"(?i)\burgent\b", true).unwrap()), Some("urgent"));
+        let urgent: Vec<Option<bool>> = found.column("urgent").unwrap().bool().unwrap().into_iter().collect();
+        assert_eq!((urgent, found.width()), (vec![Some(true), None, Some(false)], 3));
+
+        let literal = StringOp::Replace { pattern: text_pattern("disk FULL", false).unwrap(), replacement: "$1".to_string(), literal: true };
+        assert_eq!(text(&apply(literal, None), "subject")[0].as_deref(), Some("  Urgent: $1 "));
+        let grouped = StringOp::Replace { pattern: text_pattern(r"(\w+):", true).unwrap(), replacement: "[$1]".to_string(), literal: false };
+        assert_eq!(text(&apply(grouped, None), "subject")[2].as_deref(), Some("[re] ünïcode  "));
+
+        assert_eq!(text(&apply(StringOp::Strip, None), "subject"), owned(&[Some("Urgent: disk FULL"), None, Some("re: ünïcode")]));
+        assert_eq!(text(&apply(StringOp::Upper, None), "subject")[2].as_deref(), Some("RE: ÜNÏCODE  "));
+        let tail = apply(StringOp::Slice { start: -7, length: Some(3) }, None);
+        assert_eq!(text(&tail, "subject"), owned(&[Some("k F"), None, Some("ïco")]));
+        let lengths = apply(StringOp::LenChars, None);
+        let lengths: Vec<Option<u32>> = lengths.column("subject").unwrap().u32().unwrap().into_iter().collect();
+        assert_eq!(lengths, [Some(20), None, Some(13)]);
+
+        let split = apply(StringOp::Split { by: ":".to_string(), into: 3 }, Some("part"));
+        assert_eq!(split.get_column_names(), ["subject", "code", "part_0", "part_1", "part_2"]);
+        assert_eq!(text(&split, "part_1"), owned(&[Some(" disk FULL "), None, Some(" ünïcode  ")]));
+        assert_eq!(text(&split, "part_2"), owned(&[None, None, None]));
+
+        let err = string_op(&df, "code", &StringOp::Lower, None, false).unwrap_err();
+        assert!(matches!(err, InsightoraError::InvalidDataType { .. }));
+        let codes = string_op(&df, "code", &StringOp::LenChars, None, true).unwrap();
+        assert_eq!(codes.column("code").unwrap().u32().unwrap().get(0), Some(2));
+        let err = text_pattern("(unclosed", true).unwrap_err().to_string();
+        assert!(err.contains("Invalid regex '(unclosed'") && err.contains("unclosed group"), "{}", err);
+        assert!(text_pattern("(unclosed", false).unwrap().is_match("a (unclosed bracket"));
+    }
+
+    #[test]
+    fn test_drop_and_fill_nulls() {
+        let df = df! {
+            "city" => [Some("Oslo"), None, Some("Rome"), None, Some("Rome")],
+            "temp" => [Some(4i64), None, Some(21), Some(8), None],
+            "rain" => [Some(1.5), None, None, Some(0.5), Some(2.0)],
+        }
+        .unwrap();
+        let any = drop_nulls(&df, None, NullRows::Any).unwrap();
+        assert_eq!(any.height(), 1);
+        let subset = vec!["city".to_string(), "temp".to_string()];
+        let all = drop_nulls(&df, Some(&subset), NullRows::All).unwrap();
+        assert_eq!(all.column("rain").unwrap().f64().unwrap().into_iter().collect::<Vec<_>>(), [Some(1.5), None, Some(0.5), Some(2.0)]);
+        assert!(drop_nulls(&df, Some(&["wind".to_string()]), NullRows::Any).is_err());
+
+        let column = |strategy: FillStrategy, name: &str| -> Series {
+            fill_nulls(&df, &strategy, Some(&[name.to_string()])).unwrap().column(name).unwrap().clone()
+        };
+        // Mean 11 of 4, 21 and 8, kept as an integer; median of 0.5, 1.5, 2.0
+        assert_eq!(column(FillStrategy::Mean, "temp").i64().unwrap().get(1), Some(11));
+        assert_eq!(column(FillStrategy::Median, "rain").f64().unwrap().get(2), Some(1.5));
+        assert_eq!(column(FillStrategy::Mode, "city").str().unwrap().get(3), Some("Rome"));
+        assert_eq!(column(FillStrategy::Zero, "temp").i64().unwrap().get(4), Some(0));
+        assert_eq!(column(FillStrategy::Value(Series::new("", [3i32])), "rain").f64().unwrap().get(1), Some(3.0));
+        assert!(fill_nulls(&df, &FillStrategy::Value(Series::new("", [2.5])), Some(&["temp".to_string()])).is_err());
+
+        // Without columns, mean skips text; naming a text column is an error
+        let filled = fill_nulls(&df, &FillStrategy::Mean, None).unwrap();
+        assert_eq!((filled.column("city").unwrap().null_count(), filled.column("rain").unwrap().null_count()), (2, 0));
+        let err = fill_nulls(&df, &FillStrategy::Mean, Some(&["city".to_string()])).unwrap_err();
+        assert!(matches!(err, InsightoraError::InvalidDataType { ref actual, .. } if actual.contains("city")), "{}", err);
+
+        // The second chunk starts with nulls filled from the end of the first
+        let mut chunked = df!("v" => [Some(1), None]).unwrap();
+        chunked.vstack_mut(&df!("v" => [None, Some(4), None]).unwrap()).unwrap();
+        assert_eq!(chunked.n_chunks(), 2);
+        let values = |strategy: FillStrategy| -> Vec<Option<i32>> {
+            fill_nulls(&chunked, &strategy, None).unwrap().column("v").unwrap().i32().unwrap().into_iter().collect()
+        };
+        assert_eq!(values(FillStrategy::Forward), [Some(1), Some(1), Some(1), Some(4), Some(4)]);
+        assert_eq!(values(FillStrategy::Backward), [Some(1), Some(4), Some(4), Some(4), None]);
+    }
+
+    fn severities() -> DataFrame {
+        df! {
+            "severity" => [Some("high"), Some("low"), None, Some("critical"), Some("medium"), Some("low")],
+            "team" => ["a", "b", "a", "b", "a", "b"],
+        }
+        .unwrap()
+    }
+
+    fn strings(series: &Series) -> Vec<Option<String>> {
+        let strings = series.cast(&DataType::String).unwrap();
+        strings.str().unwrap().into_iter().map(|v| v.map(String::from)).collect()
+    }
+
+    #[test]
+    fn test_ordered_categoricals_sort_and_aggregate_by_order() {
+        use crate::dataframe::aggregations::group_by;
+        use crate::dataframe::operations::{sort_data, Collation};
+
+        let order = ["low", "medium", "high", "critical"].map(String::from);
+        let df = set_category_order(&severities(), "severity", &order, UnknownCategory::Error).unwrap();
+        assert_eq!(category_order(df.column("severity").unwrap()), Some(order.to_vec()));
+
+        let sorted = sort_data(&df, &["severity".to_string()], &[false], &Collation::Binary).unwrap();
+        let expected = [Some("low"), Some("low"), Some("medium"), Some("high"), Some("critical"), None];
+        assert_eq!(strings(sorted.column("severity").unwrap()), expected.map(|v| v.map(String::from)).to_vec());
+
+        // `severity >= "medium"` as a rank comparison
+        let ranks = category_ranks(df.column("severity").unwrap()).unwrap();
+        let at_least_medium: Vec<Option<bool>> = ranks.into_iter().map(|r| r.map(|r| r >= 1)).collect();
+        assert_eq!(at_least_medium, vec![Some(true), Some(false), None, Some(true), Some(true), Some(false)]);
+
+        let specs = vec![("severity".to_string(), vec!["min".to_string(), "max".to_string()])];
+        let summary = group_by(&df, &["team".to_string()], &specs).unwrap();
+        assert_eq!(category_order(summary.column("severity_max").unwrap()), Some(order.to_vec()));
+        assert_eq!(
+            strings(summary.column("severity_min").unwrap()),
+            vec![Some("medium".to_string()), Some("low".to_string())]
+        );
+        assert_eq!(
+            strings(summary.column("severity_max").unwrap()),
+            vec![Some("high".to_string()), Some("critical".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_unknown_categories_error_or_rank_last() {
+        let order = ["low", "high"].map(String::from);
+        let err = set_category_order(&severities(), "severity", &order, UnknownCategory::Error).unwrap_err();
+        assert!(err.to_string().contains("2 value(s) outside the category order: 'critical', 'medium'"));
+
+        let df = set_category_order(&severities(), "severity", &order, UnknownCategory::Last).unwrap();
+        let categories = category_order(df.column("severity").unwrap()).unwrap();
+        assert_eq!(categories, vec!["low", "high", "critical", "medium"]);
+
+        let duplicated = ["low", "low"].map(String::from);
+        assert!(set_category_order(&severities(), "severity", &duplicated, UnknownCategory::Last).is_err());
+        assert!(UnknownCategory::from_name("sideways").is_err());
+        assert!(category_ranks(severities().column("team").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_rename_swaps_projects_and_rejects_duplicates() {
+        let df = df!("a" => [1, 2], "b" => [3, 4], "c" => [5, 6]).unwrap();
+        let names = |df: &DataFrame| df.get_column_names().iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let pair = |s: &str, t: &str| (s.to_string(), t.to_string());
+
+        let swap = ColumnMapping::Names(vec![pair("a", "b"), pair("b", "a")]);
+        let swapped = rename_columns(&df, &swap, true).unwrap();
+        assert_eq!(names(&swapped), ["b", "a", "c"]);
+        assert_eq!(swapped.column("b").unwrap().i32().unwrap().get(0), Some(1));
+
+        let missing = ColumnMapping::Names(vec![pair("zzz", "d")]);
+        assert!(rename_columns(&df, &missing, true).is_err());
+        assert_eq!(names(&rename_columns(&df, &missing, false).unwrap()), ["a", "b", "c"]);
+        let clash = ColumnMapping::Names(vec![pair("a", "c")]);
+        assert!(rename_columns(&df, &clash, false).unwrap_err().to_string().contains("duplicate column 'c' from columns 'a' and 'c'"));
+
+        // Positional names cover the leading columns; the projection takes either name
+        let positions = ColumnMapping::Positions(vec!["x".to_string(), "y".to_string()]);
+        let columns = ["c".to_string(), "x".to_string(), "b".to_string()];
+        let projected = rename_and_project(df.clone(), Some(&positions), Some(&columns)).unwrap();
+        assert_eq!(names(&projected), ["c", "x", "y"]);
+        let schema = rename_and_project_schema(&df.schema(), Some(&positions), Some(&columns)).unwrap();
+        assert_eq!(schema.iter_names().map(|s| s.as_str()).collect::<Vec<_>>(), ["c", "x", "y"]);
+        let too_many = ColumnMapping::Positions(["p", "q", "r", "s"].map(String::from).to_vec());
+        assert!(rename_columns(&df, &too_many, true).is_err());
+    }
+
+    #[test]
+    fn test_format_columns_renders_report_values() {
+        let df = df!(
+            "revenue" => [Some(1234567.891f64), Some(-0.001), None],
+            "units" => [Some(1_500_000i64), Some(-42), Some(7)],
+            "share" => [0.125f64, 1.0, -0.5],
+            "day" => [Some(19_783i32), None, Some(19_783)],
+        )
+        .unwrap()
+        .lazy()
+        .with_column(col("day").cast(DataType::Date))
+        .collect()
+        .unwrap();
+        let number = |decimals| ValueFormat::new(FormatKind::Number { decimals, thousands: true });
+        let formats = vec![
+            ("revenue".to_string(), number(Some(2)).with_null_text("n/a")),
+            ("units".to_string(), number(None)),
+            ("share".to_string(), ValueFormat::new(FormatKind::Percent { decimals: Some(1), thousands: false })),
+            ("day".to_string(), ValueFormat::new(FormatKind::Datetime { pattern: "%d %b %Y".to_string() }).with_null_text("-")),
+        ];
+        let text = |df: &DataFrame, column: &str| -> Vec<Option<String>> {
+            df.column(column).unwrap().str().unwrap().into_iter().map(|v| v.map(str::to_string)).collect()
+        };
+        let some = |values: &[&str]| values.iter().map(|v| Some(v.to_string())).collect::<Vec<_>>();
+
+        let formatted = format_columns(&df, &formats, &NumberLocale::default(), false).unwrap();
+        assert_eq!(formatted.column("revenue").unwrap().dtype(), &DataType::Float64);
+        assert_eq!(text(&formatted, "revenue_formatted"), some(&["1,234,567.89", "0.00", "n/a"]));
+        assert_eq!(text(&formatted, "units_formatted"), some(&["1,500,000", "-42", "7"]));
+        assert_eq!(text(&formatted, "share_formatted"), some(&["12.5%", "100.0%", "-50.0%"]));
+        assert_eq!(text(&formatted, "day_formatted"), some(&["01 Mar 2024", "-", "01 Mar 2024"]));
+
+        // European separators and a trailing symbol, in place
+        let euro = ValueFormat::new(FormatKind::Currency {
+            symbol: " €".to_string(),
+            placement: SymbolPlacement::Suffix,
+            decimals: Some(2),
+        });
+        let locale = NumberLocale::new(".", ",").unwrap();
+        let replaced = format_columns(&df, &[("revenue".to_string(), euro)], &locale, true).unwrap();
+        assert_eq!(replaced.width(), df.width());
+        assert_eq!(text(&replaced, "revenue")[0].as_deref(), Some("1.234.567,89 €"));
+
+        assert!(format_columns(&df, &[("day".to_string(), number(None))], &locale, false).is_err());
+        assert!(NumberLocale::new(",", ",").is_err());
+    }
+
+    #[test]
+    fn test_fitted_bins_reapply_identically_and_keep_edges() {
+        let bins = |df: &DataFrame| -> Vec<Option<u32>> {
+            df.column("score_bin").unwrap().u32().unwrap().into_iter().collect()
+        };
+        let train = df!("score" => [Some(1.0), Some(2.0), Some(3.0), Some(4.0), Some(5.0), Some(6.0), Some(7.0), Some(8.0), None, Some(f64::NAN)]).unwrap();
+        let (fitted, spec) = qcut(&train, "score", &even_quantiles(4), OutOfRange::Clip, NanBin::Null).unwrap();
+        assert_eq!(spec.edges, [1.0, 2.75, 4.5, 6.25, 8.0]);
+        assert_eq!(spec.labels[0], "[1, 2.75]");
+        assert_eq!(bins(&fitted), [Some(0), Some(0), Some(1), Some(1), Some(2), Some(2), Some(3), Some(3), None, None]);
+
+        // Same values in a different frame get the same bins, not refitted ones
+        assert_eq!(bins(&apply_bins(&train.clone(), &spec).unwrap()), bins(&fitted));
+        let shifted = df!("score" => [0.0, 2.75, 2.76, 100.0, f64::NAN]).unwrap();
+        assert_eq!(bins(&apply_bins(&shifted, &spec).unwrap()), [Some(0), Some(0), Some(1), Some(3), None]);
+
+        let strict = BinSpec { out_of_range: OutOfRange::Null, nan: NanBin::Separate, ..spec.clone() };
+        assert_eq!(bins(&apply_bins(&shifted, &strict).unwrap()), [None, Some(0), Some(1), None, Some(4)]);
+
+        // Specs survive storage unchanged
+        assert_eq!(BinSpec::from_json(&strict.to_json()).unwrap(), strict);
+        let broken = BinSpec { edges: vec![3.0, 1.0], labels: vec!["x".into()], ..spec };
+        assert!(BinSpec::from_json(&broken.to_json()).is_err());
+
+        let constant = df!("score" => [5.0, 5.0, 5.0]).unwrap();
+        assert!(qcut(&constant, "score", &even_quantiles(4), OutOfRange::Clip, NanBin::Null).is_err());
+    }
+
+    #[test]
+    fn test_update_where_reads_values_from_before_the_update() {
+        let df = df!(
+            "status" => ["open", "open", "closed", "open"],
+            "amount" => [Some(50i64), Some(150), Some(300), None],
+            "flagged" => [false, false, false, true],
+        )
+        .unwrap();
+        let expression = |source: &str| Assignment::Expression(ColumnExpression::parse("", source).unwrap());
+        // status is both tested and assigned; the doubled amount is not re-tested
+        let predicate = Predicate::parse("status == 'open' and not is_null(amount)").unwrap();
+        let assignments = vec![
+            ("status".to_string(), Assignment::Value(Series::new("", ["closed"]))),
+            ("amount".to_string(), expression("amount * 2")),
+            ("flagged".to_string(), expression("amount > 100")),
+        ];
+        let (updated, report) = update_where(&df, &predicate, &assignments).unwrap();
+        let text = |column: &str| -> Vec<Option<String>> {
+            updated.column(column).unwrap().str().unwrap().into_iter().map(|v| v.map(String::from)).collect()
+        };
+        assert_eq!(text("status"), [Some("closed"), Some("closed"), Some("closed"), Some("open")].map(|v| v.map(String::from)));
+        assert_eq!(updated.column("amount").unwrap().i64().unwrap().into_iter().collect::<Vec<_>>(), [Some(100), Some(300), Some(300), None]);
+        assert_eq!(updated.column("flagged").unwrap().bool().unwrap().into_iter().collect::<Vec<_>>(), [Some(false), Some(true), Some(false), Some(true)]);
+        assert_eq!(report.rows_matched, 2);
+        // Row 0 already had flagged == false
+        assert_eq!(report.modified, [("status".to_string(), 2), ("amount".to_string(), 2), ("flagged".to_string(), 1)]);
+
+        // Nothing is evaluated when a type doesn't fit
+        let err = |assignments: Vec<(String, Assignment)>| update_where(&df, &predicate, &assignments).unwrap_err().to_string();
+        assert!(err(vec![("amount".to_string(), expression("amount / 2"))]).contains("Cannot assign a f64 value to column 'amount' of type i64"));
+        assert!(err(vec![("amount".to_string(), Assignment::Value(Series::new("", ["many"])))]).contains("column 'amount'"));
+        assert!(err(vec![("bonus".to_string(), expression("1"))]).contains("Column 'bonus' not found"));
+        let not_a_condition = Predicate::parse("amount + 1").unwrap();
+        assert!(update_where(&df, &not_a_condition, &[]).unwrap_err().to_string().contains("is not a condition"));
+    }
+}
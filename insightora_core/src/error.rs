@@ -0,0 +1,76 @@
+// Error types shared by all Rust modules
+// Converted to Python exceptions in python_bindings when the `python` feature is enabled
+
+/// Error types for Rust operations
+#[derive(Debug, thiserror::Error)]
+pub enum InsightoraError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+    
+    #[error("Parse error: {0}")]
+    ParseError(String),
+    
+    #[error("Memory limit exceeded: requested {requested}MB, limit {limit}MB")]
+    MemoryLimitExceeded { requested: usize, limit: usize },
+    
+    /// Raised at a chunk boundary after the memory watchdog fired
+    #[error(
+        "Memory watchdog aborted {operation}: memory in use reached {used_pct:.1}% (threshold {threshold_pct}%); RSS trajectory: {}",
+        crate::utils::memory::format_trajectory(.rss_trajectory_mb)
+    )]
+    MemoryWatchdog {
+        operation: String,
+        used_pct: f64,
+        threshold_pct: f64,
+        /// (ms since the operation started, RSS in MB)
+        rss_trajectory_mb: Vec<(u64, u64)>,
+    },
+    
+    /// A byte range still failing after every configured retry
+    #[error("Failed to read bytes {start}..{end} of {path} after {attempts} attempt(s): {reason}")]
+    ChunkRead {
+        path: String,
+        start: u64,
+        end: u64,
+        attempts: usize,
+        /// OS error number of the last failure, when it was an OS error
+        errno: Option<i32>,
+        reason: String,
+    },
+    
+    /// A null in a column declared NOT NULL, in strict mode
+    #[error("Column '{column}' is declared NOT NULL but data row {row} is null")]
+    NotNullViolation { column: String, row: usize },
+    
+    #[error("Invalid data type: expected {expected}, got {actual}")]
+    InvalidDataType { expected: String, actual: String },
+    
+    #[error("Polars error: {0}")]
+    PolarsError(#[from] polars::error::PolarsError),
+    
+    #[error("Thread pool error: {0}")]
+    ThreadPoolError(String),
+    
+    #[error("Configuration error: {0}")]
+    ConfigError(String),
+    
+    #[error("Validation error: {0}")]
+    ValidationError(String),
+    
+    /// Every way a frame differs from a declared schema
+    #[error("Schema error: {}", .0.join("; "))]
+    SchemaError(Vec<String>),
+    
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+    
+    #[error("Network error: {0}")]
+    NetworkError(String),
+    
+    #[error("Operation cancelled")]
+    Cancelled,
+    
+    /// A fallback refused because `require_fast_paths` is set
+    #[error("{operation} cannot use the {path} fast path: {reason} (require_fast_paths is set)")]
+    FastPathUnavailable { operation: String, path: String, reason: String },
+}
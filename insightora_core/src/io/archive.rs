@@ -0,0 +1,379 @@
+// ZIP archive reading
+// Lists and parses data files inside .zip archives without extracting them to disk
+
+use std::fs::File;
+use std::io::{Cursor, Read};
+use polars::prelude::*;
+use ::zip::result::ZipError;
+use ::zip::ZipArchive;
+use crate::config::check_memory_limit;
+use crate::error::InsightoraError;
+use crate::io::csv_parser::CsvParserConfig;
+use crate::io::remote::{align_and_concat, SchemaAlignment};
+use crate::dataframe::transformations::rename_and_project;
+use crate::utils::sandbox::check_path_allowed;
+
+/// Bytes read from a member to recognise its format
+const SNIFF_BYTES: usize = 8;
+
+// ============================================================================
+// Members
+// ============================================================================
+
+/// What a member holds, from its extension and leading bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemberFormat {
+    Csv,
+    /// Tab-separated text (.tsv / .tab)
+    Tsv,
+    Parquet,
+    /// .xlsx/.xlsm/.xls workbook; not parsed from archives
+    Excel,
+    Json,
+    /// A ZIP (or other compressed) file inside the archive
+    Archive,
+    /// Anything else, described by its extension or signature ("PDF document")
+    Other(String),
+}
+
+impl MemberFormat {
+    /// Recognise a member by name, refined by its first bytes when available
+    pub fn detect(name: &str, head: Option<&[u8]>) -> Self {
+        if let Some(head) = head {
+            let signature = match head {
+                [b'P', b'A', b'R', b'1', ..] => Some(MemberFormat::Parquet),
+                [0xD0, 0xCF, 0x11, 0xE0, ..] => Some(MemberFormat::Excel),
+                [b'%', b'P', b'D', b'F', ..] => Some(MemberFormat::Other("PDF document".to_string())),
+                [0x89, b'P', b'N', b'G', ..] => Some(MemberFormat::Other("PNG image".to_string())),
+                [0xFF, 0xD8, 0xFF, ..] => Some(MemberFormat::Other("JPEG image".to_string())),
+                [0x1F, 0x8B, ..] => Some(MemberFormat::Archive),
+                _ => None,
+            };
+            if let Some(format) = signature {
+                return format;
+            }
+        }
+        let extension = name.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase()).unwrap_or_default();
+        match extension.as_str() {
+            "csv" | "txt" => MemberFormat::Csv,
+            "tsv" | "tab" => MemberFormat::Tsv,
+            "parquet" | "pq" => MemberFormat::Parquet,
+            "xlsx" | "xlsm" | "xls" => MemberFormat::Excel,
+            "json" | "ndjson" | "jsonl" => MemberFormat::Json,
+            "zip" | "gz" | "7z" | "tar" => MemberFormat::Archive,
+            // An unrecognised extension on plain text is most likely delimited data
+            _ if head.is_some_and(|h| !h.is_empty() && std::str::from_utf8(h).is_ok()) => MemberFormat::Csv,
+            "" => MemberFormat::Other("file without extension".to_string()),
+            other => MemberFormat::Other(format!(".{} file", other)),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            MemberFormat::Csv => "csv",
+            MemberFormat::Tsv => "tsv",
+            MemberFormat::Parquet => "parquet",
+            MemberFormat::Excel => "excel",
+            MemberFormat::Json => "json",
+            MemberFormat::Archive => "archive",
+            MemberFormat::Other(_) => "other",
+        }
+    }
+
+    /// Human-readable description for error messages
+    pub fn describe(&self) -> String {
+        match self {
+            MemberFormat::Csv => "CSV text".to_string(),
+            MemberFormat::Tsv => "tab-separated text".to_string(),
+            MemberFormat::Parquet => "Parquet file".to_string(),
+            MemberFormat::Excel => "Excel workbook".to_string(),
+            MemberFormat::Json => "JSON document".to_string(),
+            MemberFormat::Archive => "nested archive".to_string(),
+            MemberFormat::Other(what) => what.clone(),
+        }
+    }
+
+    /// Whether `parse_archive` can read it
+    pub fn is_supported(&self) -> bool {
+        matches!(self, MemberFormat::Csv | MemberFormat::Tsv | MemberFormat::Parquet)
+    }
+}
+
+/// One file inside an archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveMember {
+    /// Path inside the archive, with "/" separators
+    pub name: String,
+    /// Uncompressed size in bytes
+    pub size: u64,
+    pub compressed_size: u64,
+    pub format: MemberFormat,
+    pub encrypted: bool,
+}
+
+/// OS metadata that archivers add alongside real files
+///
+/// macOS resource forks (`__MACOSX/`, `._name`), Finder's `.DS_Store` and
+/// Windows' `Thumbs.db` / `desktop.ini`.
+pub fn is_junk_member(name: &str) -> bool {
+    if name.split('/').any(|part| part == "__MACOSX") {
+        return true;
+    }
+    let file = name.trim_end_matches('/').rsplit('/').next().unwrap_or_default();
+    file.starts_with("._") || matches!(file, ".DS_Store" | "Thumbs.db" | "desktop.ini")
+}
+
+fn open_archive(path: &str) -> Result<ZipArchive<File>, InsightoraError> {
+    let file = File::open(check_path_allowed(path)?)?;
+    ZipArchive::new(file).map_err(|e| archive_error(path, None, e))
+}
+
+fn archive_error(path: &str, member: Option<&str>, err: ZipError) -> InsightoraError {
+    let location = match member {
+        Some(member) => format!("member '{}' of {}", member, path),
+        None => path.to_string(),
+    };
+    match err {
+        ZipError::Io(e) => InsightoraError::IoError(e),
+        ZipError::UnsupportedArchive(reason) if is_encryption_error(reason) => InsightoraError::ValidationError(format!(
+            "{} is encrypted; password-protected archives are not supported, re-create it without a password",
+            location
+        )),
+        ZipError::FileNotFound => InsightoraError::ValidationError(format!("{} was not found in the archive", location)),
+        other => InsightoraError::ParseError(format!("Invalid ZIP archive {}: {}", location, other)),
+    }
+}
+
+fn is_encryption_error(reason: &str) -> bool {
+    reason == ZipError::PASSWORD_REQUIRED || reason.contains("encrypted")
+}
+
+/// Files in a ZIP archive, without reading their contents into memory
+///
+/// Directories and OS junk (see `is_junk_member`) are left out. Members are
+/// in archive order; formats are recognised by extension and, for
+/// unencrypted members, their first bytes.
+///
+/// # Returns
+/// * `Result<Vec<ArchiveMember>>` - Name, sizes, format and encryption flag per member
+pub fn list_archive(path: &str) -> Result<Vec<ArchiveMember>, InsightoraError> {
+    let mut archive = open_archive(path)?;
+    let mut members = Vec::new();
+    for index in 0..archive.len() {
+        let (name, size, compressed_size, is_dir) = {
+            let entry = archive.by_index_raw(index).map_err(|e| archive_error(path, None, e))?;
+            (entry.name().to_string(), entry.size(), entry.compressed_size(), entry.is_dir())
+        };
+        if is_dir || is_junk_member(&name) {
+            continue;
+        }
+        let (head, encrypted) = match archive.by_index(index) {
+            Ok(mut entry) => {
+                let mut head = Vec::with_capacity(SNIFF_BYTES);
+                (&mut entry).take(SNIFF_BYTES as u64).read_to_end(&mut head)?;
+                (Some(head), false)
+            }
+            Err(ZipError::UnsupportedArchive(reason)) if is_encryption_error(reason) => (None, true),
+            Err(e) => return Err(archive_error(path, Some(&name), e)),
+        };
+        let format = MemberFormat::detect(&name, head.as_deref());
+        members.push(ArchiveMember { name, size, compressed_size, format, encrypted });
+    }
+    Ok(members)
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+/// Combined data from an archive plus what was read and skipped
+#[derive(Debug, Clone)]
+pub struct ArchiveParseResult {
+    pub data: DataFrame,
+    /// Members parsed, in archive order
+    pub parsed: Vec<String>,
+    /// (member, reason) for members left out of an all-members parse
+    pub skipped: Vec<(String, String)>,
+}
+
+/// Parse one member, or every supported member, of a ZIP archive
+///
+/// Members are decompressed straight into memory; nothing is written to
+/// disk. CSV members use `options` (TSV members switch the delimiter to a
+/// tab); Parquet members are read as they are, then renamed/projected by
+/// `options` like CSV.
+///
+/// # Arguments
+/// * `path` - Archive path
+/// * `member` - Member to parse (its full path inside the archive); None
+///   parses every supported member and concatenates them with `alignment`,
+///   as `parse_remote_many` does for many files
+/// * `options` - CSV dialect, renames and projection
+/// * `alignment` - How members with different columns are combined
+///
+/// # Returns
+/// * `Result<ArchiveParseResult>` - Data and per-member outcome
+pub fn parse_archive(
+    path: &str,
+    member: Option<&str>,
+    options: &CsvParserConfig,
+    alignment: SchemaAlignment,
+) -> Result<ArchiveParseResult, InsightoraError> {
+    let listed = list_archive(path)?;
+    let mut archive = open_archive(path)?;
+
+    if let Some(name) = member {
+        let Some(entry) = listed.iter().find(|m| m.name == name) else {
+            let names: Vec<&str> = listed.iter().map(|m| m.name.as_str()).collect();
+            return Err(InsightoraError::ValidationError(format!(
+                "Member '{}' not found in {}; it contains: {}",
+                name,
+                path,
+                names.join(", ")
+            )));
+        };
+        if let Some(reason) = unreadable(entry) {
+            return Err(InsightoraError::ValidationError(format!("Member '{}' of {} {}", name, path, reason)));
+        }
+        let data = parse_member(&mut archive, path, entry, options)?;
+        return Ok(ArchiveParseResult { data, parsed: vec![name.to_string()], skipped: Vec::new() });
+    }
+
+    let mut frames = Vec::new();
+    let mut parsed = Vec::new();
+    let mut skipped = Vec::new();
+    for entry in &listed {
+        match unreadable(entry) {
+            Some(reason) => skipped.push((entry.name.clone(), reason)),
+            None => {
+                frames.push(parse_member(&mut archive, path, entry, options)?);
+                parsed.push(entry.name.clone());
+            }
+        }
+    }
+    if frames.is_empty() {
+        let found: Vec<String> = listed
+            .iter()
+            .map(|m| format!("{} ({})", m.name, m.format.describe()))
+            .collect();
+        return Err(InsightoraError::ValidationError(format!(
+            "No CSV, TSV or Parquet files in {}; found: {}",
+            path,
+            if found.is_empty() { "nothing".to_string() } else { found.join(", ") }
+        )));
+    }
+    Ok(ArchiveParseResult { data: align_and_concat(frames, alignment)?, parsed, skipped })
+}
+
+/// Why a member can't be parsed, or None when it can
+fn unreadable(member: &ArchiveMember) -> Option<String> {
+    if member.encrypted {
+        return Some("is encrypted; password-protected archives are not supported".to_string());
+    }
+    if !member.format.is_supported() {
+        return Some(format!(
+            "is a {}, not a supported data file (CSV, TSV or Parquet)",
+            member.format.describe()
+        ));
+    }
+    None
+}
+
+fn parse_member(
+    archive: &mut ZipArchive<File>,
+    path: &str,
+    member: &ArchiveMember,
+    options: &CsvParserConfig,
+) -> Result<DataFrame, InsightoraError> {
+    check_memory_limit((member.size * 2 / (1024 * 1024)) as usize)?;
+    let mut bytes = Vec::with_capacity(member.size as usize);
+    archive
+        .by_name(&member.name)
+        .map_err(|e| archive_error(path, Some(&member.name), e))?
+        .read_to_end(&mut bytes)?;
+
+    let df = match member.format {
+        MemberFormat::Parquet => ParquetReader::new(Cursor::new(bytes)).finish()?,
+        _ => {
+            let delimiter = if member.format == MemberFormat::Tsv { b'\t' } else { options.delimiter };
+            CsvReader::new(Cursor::new(bytes))
+                .has_header(options.has_header)
+                .with_separator(delimiter)
+                .with_quote_char(Some(options.quote_char))
+                .infer_schema(options.infer_schema_length)
+                .finish()?
+        }
+    };
+    rename_and_project(df, options.rename.as_ref(), options.columns.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use ::zip::write::FileOptions;
+
+    fn archive(dir: &tempfile::TempDir, files: &[(&str, &[u8])]) -> String {
+        let path = dir.path().join("upload.zip");
+        let mut writer = ::zip::ZipWriter::new(File::create(&path).unwrap());
+        for (name, contents) in files {
+            if name.ends_with('/') {
+                writer.add_directory(*name, FileOptions::default()).unwrap();
+            } else {
+                writer.start_file(*name, FileOptions::default()).unwrap();
+                writer.write_all(contents).unwrap();
+            }
+        }
+        writer.finish().unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_lists_and_parses_nested_members() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = archive(&dir, &[
+            ("exports/", b""),
+            ("exports/2024/jan.csv", b"id,amount\n1,10\n2,20\n"),
+            ("exports/2024/feb.tsv", b"id\tamount\tnote\n3\t30\tlate\n"),
+            ("__MACOSX/exports/2024/._jan.csv", b"\x00\x05\x16\x07"),
+            ("exports/.DS_Store", b"\x00\x00\x00\x01Bud1"),
+            ("exports/readme.pdf", b"%PDF-1.7 ..."),
+        ]);
+
+        let members = list_archive(&path).unwrap();
+        let names: Vec<(&str, &str)> = members.iter().map(|m| (m.name.as_str(), m.format.name())).collect();
+        assert_eq!(names, [("exports/2024/jan.csv", "csv"), ("exports/2024/feb.tsv", "tsv"), ("exports/readme.pdf", "other")]);
+        assert_eq!(members[0].size, 20);
+
+        let all = parse_archive(&path, None, &CsvParserConfig::default(), SchemaAlignment::Union).unwrap();
+        assert_eq!(all.data.height(), 3);
+        assert_eq!(all.data.get_column_names(), ["id", "amount", "note"]);
+        assert_eq!(all.parsed, ["exports/2024/jan.csv", "exports/2024/feb.tsv"]);
+        assert!(all.skipped[0].1.contains("PDF document"), "{:?}", all.skipped);
+
+        let one = parse_archive(&path, Some("exports/2024/jan.csv"), &CsvParserConfig::default(), SchemaAlignment::Strict).unwrap();
+        assert_eq!(one.data.height(), 2);
+        let err = parse_archive(&path, Some("exports/readme.pdf"), &CsvParserConfig::default(), SchemaAlignment::Union).unwrap_err();
+        assert!(err.to_string().contains("is a PDF document"), "{}", err);
+        assert!(parse_archive(&path, Some("missing.csv"), &CsvParserConfig::default(), SchemaAlignment::Union).is_err());
+    }
+
+    #[test]
+    fn test_encrypted_members_fail_clearly() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = archive(&dir, &[("secret.csv", b"a,b\n1,2\n")]);
+        // Set the "encrypted" flag in the local and central headers
+        let mut bytes = std::fs::read(&path).unwrap();
+        for (signature, flag_offset) in [(*b"PK\x03\x04", 6), (*b"PK\x01\x02", 8)] {
+            let at = bytes.windows(4).position(|w| w == signature).unwrap();
+            bytes[at + flag_offset] |= 1;
+        }
+        std::fs::write(&path, bytes).unwrap();
+
+        assert!(list_archive(&path).unwrap()[0].encrypted);
+        let err = parse_archive(&path, None, &CsvParserConfig::default(), SchemaAlignment::Union).unwrap_err();
+        assert!(err.to_string().contains("secret.csv (CSV text)"), "{}", err);
+        let err = parse_archive(&path, Some("secret.csv"), &CsvParserConfig::default(), SchemaAlignment::Union).unwrap_err();
+        assert!(err.to_string().contains("is encrypted"), "{}", err);
+    }
+}
@@ -1,2 +1,334 @@
-// Arrow format bridge utilities
-// TODO: Implement in task 7
+// Arrow interchange
+// Arrow IPC (Feather v2) files and zero-copy exchange through the Arrow C stream interface, keeping categoricals and timezones intact
+
+use std::fs::File;
+use polars::export::arrow::array::{Array, StructArray};
+use polars::export::arrow::ffi::{self, ArrowArrayStream};
+use polars::prelude::*;
+use crate::config::check_memory_limit;
+use crate::error::InsightoraError;
+use crate::utils::sandbox::check_path_allowed;
+
+/// Buffer compression for written IPC files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IpcCompressionKind {
+    #[default]
+    None,
+    Lz4,
+    Zstd,
+}
+
+impl IpcCompressionKind {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" | "uncompressed" => Ok(IpcCompressionKind::None),
+            "lz4" => Ok(IpcCompressionKind::Lz4),
+            "zstd" => Ok(IpcCompressionKind::Zstd),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown IPC compression '{}'; expected none, lz4 or zstd",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            IpcCompressionKind::None => "none",
+            IpcCompressionKind::Lz4 => "lz4",
+            IpcCompressionKind::Zstd => "zstd",
+        }
+    }
+
+    fn codec(&self) -> Option<IpcCompression> {
+        match self {
+            IpcCompressionKind::None => None,
+            IpcCompressionKind::Lz4 => Some(IpcCompression::LZ4),
+            IpcCompressionKind::Zstd => Some(IpcCompression::ZSTD),
+        }
+    }
+}
+
+/// Read an Arrow IPC (Feather v2) file into a DataFrame
+///
+/// Dictionary-encoded columns become categoricals with the file's category
+/// order, and timestamps keep their unit and timezone. Nested columns
+/// (lists, fixed-size lists and structs) are rejected before any data is
+/// read. With `memory_map` uncompressed files are mapped rather than read,
+/// so only the pages touched are loaded; compressed files are always read,
+/// since their buffers must be decompressed.
+///
+/// # Arguments
+/// * `file_path` - Path to the `.arrow` / `.feather` / `.ipc` file
+/// * `memory_map` - Map the file instead of reading it
+///
+/// # Returns
+/// * `Result<DataFrame>` - The file's data; nested columns are a
+///   `SchemaError` naming each one
+///
+/// # Example
+/// ```no_run
+/// use insightora_core::api::read_ipc;
+///
+/// let df = read_ipc("exports/events.arrow", true).unwrap();
+/// println!("{}", df.schema());
+/// ```
+pub fn read_ipc(file_path: &str, memory_map: bool) -> Result<DataFrame, InsightoraError> {
+    let path = check_path_allowed(file_path)?;
+    if !path.exists() {
+        return Err(InsightoraError::IoError(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("File not found: {}", file_path),
+        )));
+    }
+    let file = File::open(&path)?;
+    // A mapped file is paged in on demand and isn't held in memory up front
+    if !memory_map {
+        check_memory_limit((file.metadata()?.len() / (1024 * 1024)) as usize)?;
+    }
+
+    // The schema comes from its own handle: reading it leaves the file past
+    // the footer, and `finish` reads the footer again from that position
+    let schema = IpcReader::new(File::open(&path)?).schema()?;
+    reject_nested(&Schema::from_iter(schema.fields.iter()))?;
+    Ok(IpcReader::new(file).memory_mapped(memory_map).finish()?)
+}
+
+/// Write a DataFrame as an Arrow IPC (Feather v2) file
+///
+/// Categoricals are written dictionary-encoded in their category order, so
+/// an order set with `set_category_order` survives the round trip. Nested
+/// columns are rejected, as `read_ipc` can't read them back.
+///
+/// # Arguments
+/// * `df` - Data to write
+/// * `file_path` - Output path; an existing file is replaced
+/// * `compression` - Buffer compression
+///
+/// # Returns
+/// * `Result<usize>` - Rows written
+pub fn write_ipc(df: &DataFrame, file_path: &str, compression: IpcCompressionKind) -> Result<usize, InsightoraError> {
+    let path = check_path_allowed(file_path)?;
+    reject_nested(&df.schema())?;
+    let mut frame = df.clone();
+    IpcWriter::new(File::create(path)?)
+        .with_compression(compression.codec())
+        .finish(&mut frame)?;
+    Ok(frame.height())
+}
+
+/// Export a DataFrame as an Arrow C stream of record batches
+///
+/// Each batch is a struct array with one child per column, the layout the
+/// C stream interface uses for tables. Columns whose chunks end at different
+/// rows are re-chunked first; after that every chunk becomes one batch and
+/// no values are copied. The stream holds references to the frame's
+/// buffers, which stay alive until the consumer releases it, however long
+/// `df` lives.
+///
+/// # Arguments
+/// * `df` - Data to export
+///
+/// # Returns
+/// * `ArrowArrayStream` - Stream to hand to a consumer such as pyarrow
+pub fn to_arrow(df: &DataFrame) -> ArrowArrayStream {
+    let mut frame = df.clone();
+    frame.align_chunks();
+    let data_type = ArrowDataType::Struct(frame.schema().to_arrow().fields);
+    let batches: Vec<PolarsResult<Box<dyn Array>>> = frame
+        .iter_chunks()
+        .map(|chunk| Ok(StructArray::new(data_type.clone(), chunk.into_arrays(), None).boxed()))
+        .collect();
+    ffi::export_iterator(Box::new(batches.into_iter()), ArrowField::new("", data_type, false))
+}
+
+/// Import an Arrow C stream of record batches as a DataFrame
+///
+/// The stream must carry struct batches with one child per column, as
+/// pyarrow tables, polars DataFrames and `to_arrow` produce. Arrays in a
+/// layout polars uses as-is (numbers, booleans, large strings, timestamps)
+/// are wrapped without copying and keep the producer's buffers alive;
+/// others, such as strings with 32-bit offsets, are converted once. Each
+/// batch becomes one chunk of the frame.
+///
+/// # Arguments
+/// * `stream` - Stream taken over from its producer; released when read
+///
+/// # Returns
+/// * `Result<DataFrame>` - The stream's rows; a nested column, or one of an
+///   Arrow type polars can't hold, is an `InvalidDataType` naming the column
+///   and its Arrow type
+pub fn import_table(stream: ArrowArrayStream) -> Result<DataFrame, InsightoraError> {
+    // SAFETY: the stream was moved out of its producer, which no longer releases it
+    let mut reader = unsafe { ffi::ArrowArrayStreamReader::try_new(Box::new(stream)) }?;
+    let fields = match reader.field().data_type().to_logical_type() {
+        ArrowDataType::Struct(fields) => fields.clone(),
+        other => {
+            return Err(InsightoraError::InvalidDataType {
+                expected: "an Arrow stream of record batches".to_string(),
+                actual: format!("a stream of {:?}", other),
+            })
+        }
+    };
+    if let Some(field) = fields.iter().find(|field| is_nested_arrow(field.data_type())) {
+        return Err(unsupported_column(field));
+    }
+
+    let mut combined: Option<DataFrame> = None;
+    // SAFETY: the reader checks every batch against the stream's schema
+    while let Some(batch) = unsafe { reader.next() } {
+        let batch = batch?;
+        let batch = batch.as_any().downcast_ref::<StructArray>().expect("batches match the struct field");
+        let columns = fields
+            .iter()
+            .zip(batch.values())
+            .map(|(field, array)| Series::try_from((field.name.as_str(), array.clone())).map_err(|_| unsupported_column(field)))
+            .collect::<Result<Vec<Series>, InsightoraError>>()?;
+        let chunk = DataFrame::new(columns)?;
+        match combined.as_mut() {
+            Some(acc) => {
+                acc.vstack_mut(&chunk)?;
+            }
+            None => combined = Some(chunk),
+        }
+    }
+    match combined {
+        Some(df) => Ok(df),
+        None => Ok(DataFrame::new(
+            fields.iter().map(|field| Series::new_empty(&field.name, &DataType::from(field.data_type()))).collect(),
+        )?),
+    }
+}
+
+/// Whether an Arrow column holds lists, structs, maps or unions
+fn is_nested_arrow(data_type: &ArrowDataType) -> bool {
+    matches!(
+        data_type.to_logical_type(),
+        ArrowDataType::List(_)
+            | ArrowDataType::LargeList(_)
+            | ArrowDataType::FixedSizeList(..)
+            | ArrowDataType::Struct(_)
+            | ArrowDataType::Map(..)
+            | ArrowDataType::Union(..)
+    )
+}
+
+/// Error for an Arrow column that can't be imported
+fn unsupported_column(field: &ArrowField) -> InsightoraError {
+    InsightoraError::InvalidDataType {
+        expected: format!("a flat column type polars supports for column '{}'", field.name),
+        actual: format!("Arrow {:?}", field.data_type()),
+    }
+}
+
+/// Fail with every nested column in `schema`
+fn reject_nested(schema: &Schema) -> Result<(), InsightoraError> {
+    let problems: Vec<String> = schema
+        .iter()
+        .filter(|(_, dtype)| matches!(dtype, DataType::List(_) | DataType::Struct(_)))
+        .map(|(name, dtype)| format!("column '{}' is {}; nested columns aren't supported in Arrow IPC files", name, dtype))
+        .collect();
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(InsightoraError::SchemaError(problems))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::transformations::{category_order, set_category_order, UnknownCategory};
+
+    #[test]
+    fn test_ipc_round_trips_categoricals_and_timezones() {
+        let dir = tempfile::tempdir().unwrap();
+        let utc = DataType::Datetime(TimeUnit::Microseconds, Some("UTC".to_string()));
+        let at = Int64Chunked::new("at", &[Some(1_705_307_400_000_000), None, Some(1_705_305_600_000_000)])
+            .into_datetime(TimeUnit::Microseconds, Some("UTC".to_string()))
+            .into_series();
+        let df = df!("id" => [1i64, 2, 3], "size" => ["large", "small", "large"]).unwrap();
+        let mut df = set_category_order(
+            &df,
+            "size",
+            &["small".to_string(), "medium".to_string(), "large".to_string()],
+            UnknownCategory::Error,
+        )
+        .unwrap();
+        df.with_column(at).unwrap();
+
+        for compression in ["none", "lz4", "zstd"] {
+            let compression = IpcCompressionKind::from_name(compression).unwrap();
+            let path = dir.path().join(format!("data_{}.arrow", compression.name()));
+            let path = path.to_str().unwrap();
+            assert_eq!(write_ipc(&df, path, compression).unwrap(), 3);
+            for memory_map in [false, true] {
+                let read = read_ipc(path, memory_map).unwrap();
+                assert!(read.equals_missing(&df), "{} memory_map={}", compression.name(), memory_map);
+                assert_eq!(read.column("at").unwrap().dtype(), &utc);
+                assert_eq!(
+                    category_order(read.column("size").unwrap()),
+                    Some(vec!["small".to_string(), "medium".to_string(), "large".to_string()])
+                );
+            }
+        }
+
+        let mut nested = df!("id" => [1i64]).unwrap();
+        nested.with_column(Series::new("tags", &[Series::new("", &["a", "b"])])).unwrap();
+        let path = dir.path().join("nested.arrow");
+        let message = write_ipc(&nested, path.to_str().unwrap(), IpcCompressionKind::None).unwrap_err().to_string();
+        assert!(message.contains("column 'tags' is list[str]"), "{}", message);
+        assert!(IpcCompressionKind::from_name("snappy").is_err());
+    }
+
+    #[test]
+    fn test_to_arrow_exports_each_chunk_as_a_batch() {
+        let batch_rows = |df: &DataFrame| {
+            let mut reader = unsafe { ffi::ArrowArrayStreamReader::try_new(Box::new(to_arrow(df))) }.unwrap();
+            assert_eq!(reader.field().data_type, ArrowDataType::Struct(df.schema().to_arrow().fields));
+            let mut rows = Vec::new();
+            while let Some(batch) = unsafe { reader.next() } {
+                let batch = batch.unwrap();
+                let batch = batch.as_any().downcast_ref::<StructArray>().unwrap();
+                assert_eq!(batch.values().len(), 2);
+                rows.push(batch.len());
+            }
+            rows
+        };
+
+        let mut df = df!("id" => [1i64, 2], "name" => ["a", "b"]).unwrap();
+        df.vstack_mut(&df!("id" => [3i64], "name" => ["c"]).unwrap()).unwrap();
+        assert_eq!(batch_rows(&df), vec![2, 1]);
+
+        // Chunks ending at different rows are combined first
+        let id = df.column("id").unwrap().rechunk();
+        df.replace("id", id).unwrap();
+        assert_eq!(batch_rows(&df), vec![3]);
+    }
+
+    #[test]
+    fn test_import_table_round_trips_and_rejects_nested_columns() {
+        let at = Int64Chunked::new("at", &[Some(1_705_307_400_000_000), None])
+            .into_datetime(TimeUnit::Microseconds, Some("UTC".to_string()))
+            .into_series();
+        let mut df = df!("id" => [1i64, 2], "size" => ["large", "small"]).unwrap();
+        df = set_category_order(&df, "size", &["small".to_string(), "large".to_string()], UnknownCategory::Error).unwrap();
+        df.with_column(at).unwrap();
+        let copy = df.clone();
+        df.vstack_mut(&copy).unwrap();
+
+        let imported = import_table(to_arrow(&df)).unwrap();
+        assert!(imported.equals_missing(&df));
+        assert_eq!(imported.dtypes(), df.dtypes());
+        assert_eq!(imported.n_chunks(), 2);
+        assert_eq!(category_order(imported.column("size").unwrap()), Some(vec!["small".to_string(), "large".to_string()]));
+
+        let empty = import_table(to_arrow(&df.head(Some(0)))).unwrap();
+        assert_eq!((empty.height(), empty.get_column_names()), (0, vec!["id", "size", "at"]));
+
+        let mut nested = df!("id" => [1i64]).unwrap();
+        nested.with_column(Series::new("tags", &[Series::new("", &["a", "b"])])).unwrap();
+        let error = import_table(to_arrow(&nested)).unwrap_err();
+        assert!(matches!(error, InsightoraError::InvalidDataType { .. }));
+        assert!(error.to_string().contains("column 'tags', got Arrow LargeList"), "{}", error);
+    }
+}
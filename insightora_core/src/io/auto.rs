@@ -0,0 +1,1071 @@
+// Automatic parsing of unknown files
+// Sniffs format, encoding and dialect, then retries failed parses with progressively more lenient settings
+
+use std::borrow::Cow;
+use std::io::{Cursor, Read};
+use polars::prelude::*;
+use crate::config::{check_memory_limit, get_current_config};
+use crate::error::InsightoraError;
+use crate::io::archive::{parse_archive, MemberFormat};
+use crate::io::compression::{decompressed_size, open_decompressed, read_decompressed, Compression};
+use crate::io::csv_parser::{CsvParserConfig, ParallelCsvParser};
+use crate::io::encoding::CsvEncoding;
+use crate::io::csv_repair::split_fields;
+use crate::io::remote::SchemaAlignment;
+use crate::utils::sandbox::check_path_allowed;
+
+/// Bytes read to recognise the format and sniff the dialect
+pub const SNIFF_BYTES: usize = 64 * 1024;
+
+/// Rows the first attempt infers column types from
+const INFER_ROWS: usize = 1000;
+
+/// Delimiters tried when sniffing, in order of preference on a tie
+const DELIMITERS: [u8; 4] = [b',', b'\t', b';', b'|'];
+
+/// Skipped line numbers listed in a concession
+const LOGGED_LINES: usize = 10;
+
+// ============================================================================
+// Options and Settings
+// ============================================================================
+
+/// Which fallbacks `parse_auto` may use
+///
+/// Every fallback is on by default; deployments that must not alter data
+/// can switch off the lossy ones.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutoParseOptions {
+    /// Parses tried at most, the strict one included
+    pub max_attempts: usize,
+    /// Replace bytes that aren't valid in the sniffed encoding with U+FFFD
+    pub lossy_encoding: bool,
+    /// Skip lines with more fields than the header (CSV), or that aren't
+    /// valid JSON (NDJSON)
+    pub skip_bad_lines: bool,
+    /// Infer types from every row, then read every column as text
+    pub widen_schema: bool,
+}
+
+impl Default for AutoParseOptions {
+    fn default() -> Self {
+        Self { max_attempts: 5, lossy_encoding: true, skip_bad_lines: true, widen_schema: true }
+    }
+}
+
+/// Format `parse_auto` recognised
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoFormat {
+    Csv,
+    Tsv,
+    Parquet,
+    /// One JSON object per line
+    Ndjson,
+    /// A JSON array of objects
+    Json,
+    Zip,
+    /// Recognised but not parsed here: workbooks are read with `parse_excel`
+    Excel,
+}
+
+impl AutoFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AutoFormat::Csv => "csv",
+            AutoFormat::Tsv => "tsv",
+            AutoFormat::Parquet => "parquet",
+            AutoFormat::Ndjson => "ndjson",
+            AutoFormat::Json => "json",
+            AutoFormat::Zip => "zip",
+            AutoFormat::Excel => "excel",
+        }
+    }
+
+    fn is_text(&self) -> bool {
+        matches!(self, AutoFormat::Csv | AutoFormat::Tsv | AutoFormat::Ndjson | AutoFormat::Json)
+    }
+}
+
+/// Text encoding, recognised by its byte order mark
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Utf8,
+    /// UTF-8 with a byte order mark, which is dropped
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl TextEncoding {
+    /// Encoding of `bytes`; UTF-8 unless a byte order mark says otherwise
+    pub fn sniff(bytes: &[u8]) -> Self {
+        match bytes {
+            [0xEF, 0xBB, 0xBF, ..] => TextEncoding::Utf8Bom,
+            [0xFF, 0xFE, ..] => TextEncoding::Utf16Le,
+            [0xFE, 0xFF, ..] => TextEncoding::Utf16Be,
+            _ => TextEncoding::Utf8,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "utf-8",
+            TextEncoding::Utf8Bom => "utf-8-sig",
+            TextEncoding::Utf16Le => "utf-16-le",
+            TextEncoding::Utf16Be => "utf-16-be",
+        }
+    }
+
+    /// Decode `bytes`, returning the text and the invalid sequences replaced
+    ///
+    /// Strict decoding fails at the first invalid sequence, naming its byte
+    /// offset and line; lossy decoding replaces each with U+FFFD.
+    pub fn decode<'a>(&self, bytes: &'a [u8], lossy: bool) -> Result<(Cow<'a, str>, usize), InsightoraError> {
+        match self {
+            TextEncoding::Utf8 | TextEncoding::Utf8Bom => {
+                let start = if *self == TextEncoding::Utf8Bom { 3 } else { 0 };
+                decode_utf8(&bytes[start.min(bytes.len())..], start, lossy)
+            }
+            TextEncoding::Utf16Le | TextEncoding::Utf16Be => {
+                let units = bytes[2.min(bytes.len())..].chunks(2).map(|pair| match (pair, self) {
+                    ([low, high], TextEncoding::Utf16Le) => Ok(u16::from_le_bytes([*low, *high])),
+                    ([high, low], _) => Ok(u16::from_be_bytes([*high, *low])),
+                    _ => Err(()),
+                });
+                // A trailing odd byte decodes as an unpaired surrogate
+                let units = units.map(|unit| unit.unwrap_or(0xDC00));
+                let mut text = String::with_capacity(bytes.len() / 2);
+                let (mut replaced, mut decoded_units) = (0, 0);
+                for unit in char::decode_utf16(units) {
+                    match unit {
+                        Ok(c) => {
+                            decoded_units += c.len_utf16();
+                            text.push(c);
+                        }
+                        Err(_) if lossy => {
+                            decoded_units += 1;
+                            replaced += 1;
+                            text.push(char::REPLACEMENT_CHARACTER);
+                        }
+                        Err(_) => {
+                            return Err(InsightoraError::ParseError(format!(
+                                "invalid {} at byte {} (line {})",
+                                self.name(),
+                                2 + decoded_units * 2,
+                                text.matches('\n').count() + 1
+                            )));
+                        }
+                    }
+                }
+                Ok((Cow::Owned(text), replaced))
+            }
+        }
+    }
+}
+
+fn decode_utf8(bytes: &[u8], offset: usize, lossy: bool) -> Result<(Cow<'_, str>, usize), InsightoraError> {
+    let error = match std::str::from_utf8(bytes) {
+        Ok(text) => return Ok((Cow::Borrowed(text), 0)),
+        Err(e) => e,
+    };
+    if !lossy {
+        let at = error.valid_up_to();
+        let line = bytes[..at].iter().filter(|&&b| b == b'\n').count() + 1;
+        return Err(InsightoraError::ParseError(format!(
+            "invalid UTF-8 at byte {} (line {}); the file may be Latin-1 or Windows-1252 encoded",
+            offset + at,
+            line
+        )));
+    }
+    let mut text = String::with_capacity(bytes.len() + 16);
+    let mut replaced = 0;
+    let mut rest = bytes;
+    loop {
+        match std::str::from_utf8(rest) {
+            Ok(valid) => {
+                text.push_str(valid);
+                break;
+            }
+            Err(e) => {
+                let (valid, invalid) = rest.split_at(e.valid_up_to());
+                text.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                text.push(char::REPLACEMENT_CHARACTER);
+                replaced += 1;
+                rest = &invalid[e.error_len().unwrap_or(invalid.len())..];
+            }
+        }
+    }
+    Ok((Cow::Owned(text), replaced))
+}
+
+/// How column types are inferred
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaInference {
+    /// From the first rows
+    Sampled(usize),
+    /// From every row
+    Full,
+    /// Every column read as text
+    Text,
+}
+
+impl SchemaInference {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SchemaInference::Sampled(_) => "sampled",
+            SchemaInference::Full => "full",
+            SchemaInference::Text => "text",
+        }
+    }
+}
+
+/// Settings of one parse attempt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutoSettings {
+    pub format: AutoFormat,
+    pub compression: Compression,
+    /// Text formats only
+    pub encoding: Option<TextEncoding>,
+    /// CSV and TSV only
+    pub delimiter: Option<u8>,
+    pub quote_char: Option<u8>,
+    pub lossy_encoding: bool,
+    pub skip_bad_lines: bool,
+    pub schema: SchemaInference,
+}
+
+impl AutoSettings {
+    /// One-line summary, e.g. "csv, utf-8, delimiter ';', types from the first 1000 rows"
+    pub fn describe(&self) -> String {
+        let mut parts = vec![self.format.name().to_string()];
+        if self.compression.is_compressed() {
+            parts.push(self.compression.name().to_string());
+        }
+        if let Some(encoding) = self.encoding {
+            parts.push(encoding.name().to_string());
+        }
+        if let Some(delimiter) = self.delimiter {
+            parts.push(format!("delimiter {:?}", delimiter as char));
+        }
+        if self.format.is_text() {
+            parts.push(match self.schema {
+                SchemaInference::Sampled(rows) => format!("types from the first {} rows", rows),
+                SchemaInference::Full => "types from every row".to_string(),
+                SchemaInference::Text => "every column as text".to_string(),
+            });
+        }
+        if self.lossy_encoding {
+            parts.push("lossy encoding".to_string());
+        }
+        if self.skip_bad_lines {
+            parts.push("bad lines skipped".to_string());
+        }
+        parts.join(", ")
+    }
+}
+
+// ============================================================================
+// Results
+// ============================================================================
+
+/// Why an attempt failed, which decides the next fallback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureClass {
+    /// Bytes not valid in the encoding; retried with `lossy_encoding`
+    Encoding,
+    /// Lines that don't fit the header; retried with `skip_bad_lines`
+    BadLines,
+    /// Values not matching the inferred types; retried with `widen_schema`
+    Schema,
+    /// Anything else; not retried
+    Other,
+}
+
+impl FailureClass {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FailureClass::Encoding => "encoding",
+            FailureClass::BadLines => "bad_lines",
+            FailureClass::Schema => "schema",
+            FailureClass::Other => "other",
+        }
+    }
+
+    /// Option enabling the fallback for this failure
+    fn fallback(&self) -> Option<&'static str> {
+        match self {
+            FailureClass::Encoding => Some("lossy_encoding"),
+            FailureClass::BadLines => Some("skip_bad_lines"),
+            FailureClass::Schema => Some("widen_schema"),
+            FailureClass::Other => None,
+        }
+    }
+}
+
+/// One parse attempt
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoAttempt {
+    pub settings: AutoSettings,
+    /// None for the attempt that succeeded
+    pub error: Option<String>,
+    pub failure: Option<FailureClass>,
+}
+
+/// A way the returned data may differ from the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Concession {
+    /// Fallback that made it: "lossy_encoding", "skip_bad_lines" or "widen_schema"
+    pub fallback: &'static str,
+    pub detail: String,
+}
+
+/// Data from `parse_auto` plus how it was read
+#[derive(Debug, Clone)]
+pub struct AutoParseResult {
+    pub data: DataFrame,
+    /// Settings of the attempt that succeeded
+    pub settings: AutoSettings,
+    /// Every attempt, the successful one last
+    pub attempts: Vec<AutoAttempt>,
+    pub concessions: Vec<Concession>,
+}
+
+// ============================================================================
+// Detection
+// ============================================================================
+
+/// Recognise a file by its name and first bytes
+///
+/// Content wins over the extension: a ZIP signature is an archive (or a
+/// workbook when named .xlsx / .xlsm), text starting with `{` is NDJSON and
+/// JSON files starting with `[` are JSON arrays.
+pub fn detect_format(name: &str, head: &[u8]) -> Result<AutoFormat, InsightoraError> {
+    let extension = name.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase()).unwrap_or_default();
+    if head.starts_with(b"PK\x03\x04") {
+        return Ok(if matches!(extension.as_str(), "xlsx" | "xlsm") { AutoFormat::Excel } else { AutoFormat::Zip });
+    }
+    let first = head.iter().copied().skip_while(|b| b.is_ascii_whitespace() || matches!(b, 0xEF | 0xBB | 0xBF));
+    let starts_with = first.take(1).next();
+    let signature = &head[..head.len().min(8)];
+    // Named .csv, a file only detects as something else by its signature
+    let signed = MemberFormat::detect("signature.csv", Some(signature)) != MemberFormat::Csv;
+    match MemberFormat::detect(name, Some(signature)) {
+        MemberFormat::Parquet => Ok(AutoFormat::Parquet),
+        MemberFormat::Excel => Ok(AutoFormat::Excel),
+        MemberFormat::Json if starts_with == Some(b'[') => Ok(AutoFormat::Json),
+        MemberFormat::Json => Ok(AutoFormat::Ndjson),
+        MemberFormat::Csv | MemberFormat::Tsv if starts_with == Some(b'{') => Ok(AutoFormat::Ndjson),
+        MemberFormat::Csv => Ok(AutoFormat::Csv),
+        MemberFormat::Tsv => Ok(AutoFormat::Tsv),
+        // Latin-1 or UTF-16 text under an unknown extension
+        MemberFormat::Other(_) if !signed && is_text(head) => Ok(AutoFormat::Csv),
+        other => Err(InsightoraError::ValidationError(format!(
+            "{} is a {}, not a supported data file (CSV, TSV, Parquet, NDJSON, JSON or ZIP)",
+            name,
+            other.describe()
+        ))),
+    }
+}
+
+/// Whether leading bytes look like text in some encoding: a UTF-16 byte
+/// order mark, or no control characters other than tab and line breaks
+fn is_text(head: &[u8]) -> bool {
+    TextEncoding::sniff(head) != TextEncoding::Utf8
+        || head.iter().take(1024).all(|&b| b >= 0x20 || matches!(b, b'\t' | b'\n' | b'\r'))
+}
+
+/// Delimiter and quote character of delimited text
+///
+/// The quote character is `"` unless `'` opens more fields. The delimiter
+/// is the candidate (`,` tab `;` `|`) splitting the most records into the
+/// same number of fields, above one; `,` when none does.
+pub fn sniff_dialect(text: &str) -> (u8, u8) {
+    let opens = |quote: char| -> usize {
+        text.lines()
+            .map(|line| {
+                let after_delimiter: usize = DELIMITERS
+                    .iter()
+                    .map(|&d| line.matches(&format!("{}{}", d as char, quote)).count())
+                    .sum();
+                line.starts_with(quote) as usize + after_delimiter
+            })
+            .sum()
+    };
+    let quote_char = if opens('\'') > opens('"') { b'\'' } else { b'"' };
+
+    let mut best = (b',', 0);
+    for delimiter in DELIMITERS {
+        // A delimiter that never splits a record counts every record as one field
+        let (fields, consistent, _) = field_counts(text, delimiter, quote_char);
+        if fields > 1 && consistent > best.1 {
+            best = (delimiter, consistent);
+        }
+    }
+    (best.0, quote_char)
+}
+
+/// (fields, records with that many, records) for the most common field
+/// count above one; records of a single field count towards the total only
+fn field_counts(text: &str, delimiter: u8, quote_char: u8) -> (usize, usize, usize) {
+    let mut counts = std::collections::HashMap::new();
+    let mut records = 0;
+    for (_, record) in Records::new(text, quote_char) {
+        let record = record.trim_end_matches(['\r', '\n']);
+        if record.is_empty() {
+            continue;
+        }
+        records += 1;
+        let fields = split_fields(record, delimiter, quote_char).len();
+        if fields > 1 {
+            *counts.entry(fields).or_insert(0usize) += 1;
+        }
+    }
+    // The larger field count wins a tie, so a short trailing line doesn't decide
+    let (fields, consistent) = counts.into_iter().max_by_key(|&(fields, count)| (count, fields)).unwrap_or((1, records));
+    (fields, consistent, records)
+}
+
+/// Kind of a value, for telling a header from data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueKind {
+    Empty,
+    Number,
+    Boolean,
+    Text,
+}
+
+impl ValueKind {
+    fn of(value: &str) -> Self {
+        let value = value.trim();
+        if value.is_empty() {
+            ValueKind::Empty
+        } else if value.parse::<f64>().is_ok_and(f64::is_finite) {
+            ValueKind::Number
+        } else if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+            ValueKind::Boolean
+        } else {
+            ValueKind::Text
+        }
+    }
+}
+
+/// Whether the first record of delimited text is a header
+///
+/// Every column whose later values are all numbers, or all booleans,
+/// votes: for a header when its first value is other text, against one
+/// when the first value is of the same kind. Without votes (all-text
+/// columns, or a single record) the first record is a header when its
+/// values are non-empty, distinct and not numbers.
+pub fn detect_header(text: &str, delimiter: u8, quote_char: u8) -> bool {
+    let rows: Vec<Vec<ValueKind>> = Records::new(text, quote_char)
+        .map(|(_, record)| record.trim_end_matches(['\r', '\n']))
+        .filter(|record| !record.is_empty())
+        .map(|record| split_fields(record, delimiter, quote_char).iter().map(|v| ValueKind::of(v)).collect())
+        .collect();
+    let Some((first, rest)) = rows.split_first() else {
+        return false;
+    };
+    let mut votes = 0i64;
+    for (column, &head) in first.iter().enumerate() {
+        let mut kinds = rest.iter().filter_map(|row| row.get(column)).filter(|&&kind| kind != ValueKind::Empty);
+        let Some(&kind) = kinds.next() else { continue };
+        if kind == ValueKind::Text || !kinds.all(|&other| other == kind) {
+            continue;
+        }
+        match head {
+            ValueKind::Text => votes += 1,
+            _ if head == kind => votes -= 1,
+            _ => {}
+        }
+    }
+    if votes != 0 {
+        return votes > 0;
+    }
+    let names: Vec<String> = Records::new(text, quote_char)
+        .next()
+        .map(|(_, record)| split_fields(record.trim_end_matches(['\r', '\n']), delimiter, quote_char))
+        .unwrap_or_default();
+    let distinct: std::collections::HashSet<&String> = names.iter().collect();
+    distinct.len() == names.len() && first.iter().all(|&kind| kind == ValueKind::Text)
+}
+
+/// Dialect of a CSV file, as `sniff_csv` found it in the file's first bytes
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvDialect {
+    pub delimiter: u8,
+    pub quote_char: u8,
+    /// Whether the first record is a header, by `detect_header`
+    pub has_header: bool,
+    /// Fields in most sampled records
+    pub column_count: usize,
+    /// Share of sampled records with `column_count` fields
+    pub consistency: f64,
+    /// Records sampled, the first included; a record cut by the end of the sample isn't
+    pub records: usize,
+    /// Encoding named by a byte order mark, if the file starts with one
+    pub byte_order_mark: Option<TextEncoding>,
+    /// `CsvParserConfig::encoding` to read the file with: UTF-8 unless the
+    /// sample has invalid UTF-8, then Windows-1252 when bytes 0x80-0x9F
+    /// occur and Latin-1 otherwise; None for UTF-16, which isn't read
+    pub encoding: Option<CsvEncoding>,
+    /// Byte offset of the first invalid UTF-8 sequence in the sample
+    pub invalid_utf8_at: Option<usize>,
+}
+
+/// Sniff the delimiter, quote character, header and encoding of a CSV file
+///
+/// Reads at most `sample_bytes` (decompressing gzip and zstd files) and
+/// drops a record cut by the end of the sample. The delimiter is the
+/// candidate (`,` tab `;` `|`) giving the most records the same field count,
+/// with quoted values, including ones spanning lines, kept whole.
+///
+/// # Arguments
+/// * `file_path` - File to sniff
+/// * `sample_bytes` - Bytes read from the start of the file, e.g. `SNIFF_BYTES`
+///
+/// # Returns
+/// * `Result<CsvDialect>` - The dialect; ParseError for an empty file
+///
+/// # Example
+/// ```no_run
+/// use insightora_core::api::{sniff_csv, SNIFF_BYTES};
+///
+/// let dialect = sniff_csv("upload.csv", SNIFF_BYTES).unwrap();
+/// println!("{:?} with header: {}", dialect.delimiter as char, dialect.has_header);
+/// ```
+pub fn sniff_csv(file_path: &str, sample_bytes: usize) -> Result<CsvDialect, InsightoraError> {
+    if sample_bytes == 0 {
+        return Err(InsightoraError::ValidationError("sample_bytes must be at least 1".to_string()));
+    }
+    let path = check_path_allowed(file_path)?;
+    if !path.exists() {
+        return Err(InsightoraError::IoError(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("File not found: {}", file_path),
+        )));
+    }
+    let mut head = Vec::with_capacity(sample_bytes.min(SNIFF_BYTES));
+    open_decompressed(&path, Compression::detect(&path)?)?.take(sample_bytes as u64).read_to_end(&mut head)?;
+
+    let bom = TextEncoding::sniff(&head);
+    let (text, _) = bom.decode(&head, true)?;
+    let sample = match text.rfind('\n') {
+        Some(end) if head.len() == sample_bytes => &text[..end],
+        _ => &text[..],
+    };
+    if sample.trim().is_empty() {
+        return Err(InsightoraError::ParseError(format!("{} is empty", file_path)));
+    }
+    let (delimiter, quote_char) = sniff_dialect(sample);
+    let (column_count, consistent, records) = field_counts(sample, delimiter, quote_char);
+
+    let invalid_utf8_at = match bom {
+        TextEncoding::Utf8 | TextEncoding::Utf8Bom => {
+            let start = if bom == TextEncoding::Utf8Bom { 3 } else { 0 };
+            // A sequence cut by the end of the sample isn't invalid
+            std::str::from_utf8(&head[start..]).err().filter(|e| e.error_len().is_some()).map(|e| start + e.valid_up_to())
+        }
+        TextEncoding::Utf16Le | TextEncoding::Utf16Be => None,
+    };
+    let encoding = match (bom, invalid_utf8_at) {
+        (TextEncoding::Utf16Le | TextEncoding::Utf16Be, _) => None,
+        (_, None) => Some(CsvEncoding::Utf8),
+        (_, Some(_)) if head.iter().any(|b| (0x80..=0x9F).contains(b)) => Some(CsvEncoding::Windows1252),
+        (_, Some(_)) => Some(CsvEncoding::Latin1),
+    };
+    Ok(CsvDialect {
+        delimiter,
+        quote_char,
+        has_header: detect_header(sample, delimiter, quote_char),
+        column_count,
+        consistency: if records == 0 { 0.0 } else { consistent as f64 / records as f64 },
+        records,
+        byte_order_mark: (bom != TextEncoding::Utf8).then_some(bom),
+        encoding,
+        invalid_utf8_at,
+    })
+}
+
+/// Records of delimited text with their 1-based starting line
+///
+/// A record ends at a line break outside quotes, so quoted values may span
+/// lines. Each record keeps its line break.
+pub(crate) struct Records<'a> {
+    text: &'a str,
+    quote: u8,
+    line: usize,
+}
+
+impl<'a> Records<'a> {
+    pub(crate) fn new(text: &'a str, quote: u8) -> Self {
+        Self { text, quote, line: 1 }
+    }
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.text.is_empty() {
+            return None;
+        }
+        let mut in_quotes = false;
+        let mut end = self.text.len();
+        let mut breaks = 0;
+        for (i, b) in self.text.bytes().enumerate() {
+            if b == self.quote {
+                in_quotes = !in_quotes;
+            } else if b == b'\n' {
+                breaks += 1;
+                if !in_quotes {
+                    end = i + 1;
+                    break;
+                }
+            }
+        }
+        let (record, rest) = self.text.split_at(end);
+        let line = self.line;
+        self.text = rest;
+        self.line += breaks;
+        Some((line, record))
+    }
+}
+
+/// The text without records that have more fields than the header, and
+/// the lines those records started on
+fn drop_bad_lines(text: &str, delimiter: u8, quote_char: u8) -> (String, Vec<usize>) {
+    let fields = |record: &str| split_fields(record.trim_end_matches(['\r', '\n']), delimiter, quote_char).len();
+    let mut records = Records::new(text, quote_char);
+    let mut kept = String::with_capacity(text.len());
+    let mut skipped = Vec::new();
+    let Some((_, header)) = records.next() else {
+        return (kept, skipped);
+    };
+    let expected = fields(header);
+    kept.push_str(header);
+    for (line, record) in records {
+        if fields(record) > expected {
+            skipped.push(line);
+        } else {
+            kept.push_str(record);
+        }
+    }
+    (kept, skipped)
+}
+
+/// Non-blank NDJSON lines that aren't JSON objects, and the text without them
+fn drop_bad_json_lines(text: &str) -> (String, Vec<usize>) {
+    let mut kept = String::with_capacity(text.len());
+    let mut skipped = Vec::new();
+    for (index, line) in text.split_inclusive('\n').enumerate() {
+        let trimmed = line.trim();
+        let valid = trimmed.is_empty() || matches!(serde_json::from_str(trimmed), Ok(serde_json::Value::Object(_)));
+        if valid {
+            kept.push_str(line);
+        } else {
+            skipped.push(index + 1);
+        }
+    }
+    (kept, skipped)
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+/// Parse a file of unknown format, falling back to more lenient settings
+///
+/// The format is recognised by `detect_format` (gzip and zstd files are
+/// decompressed first), the encoding by its byte order mark and the
+/// delimiter by `sniff_dialect`. The first attempt is strict; each failure
+/// is classified and retried with the matching fallback, in the order the
+/// failures occur:
+///
+/// * invalid bytes - `lossy_encoding` replaces them with U+FFFD
+/// * lines with surplus fields (CSV) or invalid JSON (NDJSON) -
+///   `skip_bad_lines` drops them
+/// * values not matching the types inferred from the first 1000 rows -
+///   `widen_schema` infers from every row, then reads every column as text
+///
+/// ZIP archives are parsed with `parse_archive`, member by member; Parquet
+/// and ZIP files get a single attempt. Files are assumed to have a header.
+///
+/// # Arguments
+/// * `file_path` - File to parse
+/// * `options` - Attempt limit and the fallbacks allowed
+///
+/// # Returns
+/// * `Result<AutoParseResult>` - Data, the settings that worked, every
+///   attempt and the concessions made; on failure, the last error with the
+///   attempts made and any fallback that was disabled
+pub fn parse_auto(file_path: &str, options: &AutoParseOptions) -> Result<AutoParseResult, InsightoraError> {
+    if options.max_attempts == 0 {
+        return Err(InsightoraError::ValidationError("max_attempts must be at least 1".to_string()));
+    }
+    let path = check_path_allowed(file_path)?;
+    if !path.exists() {
+        return Err(InsightoraError::IoError(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("File not found: {}", file_path),
+        )));
+    }
+    let compression = Compression::detect(&path)?;
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    open_decompressed(&path, compression)?.take(SNIFF_BYTES as u64).read_to_end(&mut head)?;
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let name = match compression {
+        Compression::None => name.as_str(),
+        _ => name.rsplit_once('.').map_or(name.as_str(), |(stem, _)| stem),
+    };
+    let format = detect_format(name, &head)?;
+
+    let mut settings = AutoSettings {
+        format,
+        compression,
+        encoding: None,
+        delimiter: None,
+        quote_char: None,
+        lossy_encoding: false,
+        skip_bad_lines: false,
+        schema: SchemaInference::Sampled(INFER_ROWS),
+    };
+    match format {
+        AutoFormat::Excel => {
+            return Err(InsightoraError::ValidationError(format!(
+                "{} is an Excel workbook; read it with parse_excel",
+                file_path
+            )));
+        }
+        AutoFormat::Zip if compression.is_compressed() => {
+            return Err(InsightoraError::ValidationError(format!(
+                "{} is a {}-compressed ZIP archive; decompress it first",
+                file_path,
+                compression.name()
+            )));
+        }
+        AutoFormat::Csv | AutoFormat::Tsv => {
+            let encoding = TextEncoding::sniff(&head);
+            let (sample, _) = encoding.decode(&head, true)?;
+            // The sample may end inside a record; drop the partial last line
+            let sample = match sample.rfind('\n') {
+                Some(end) if head.len() == SNIFF_BYTES => &sample[..end],
+                _ => &sample[..],
+            };
+            let (delimiter, quote_char) = sniff_dialect(sample);
+            settings.format = if delimiter == b'\t' { AutoFormat::Tsv } else { AutoFormat::Csv };
+            settings.encoding = Some(encoding);
+            settings.delimiter = Some(delimiter);
+            settings.quote_char = Some(quote_char);
+        }
+        AutoFormat::Ndjson | AutoFormat::Json => settings.encoding = Some(TextEncoding::sniff(&head)),
+        AutoFormat::Parquet | AutoFormat::Zip => {}
+    }
+
+    let bytes = if format == AutoFormat::Zip {
+        Vec::new()
+    } else {
+        // The bytes, the decoded text and the parsed frame
+        check_memory_limit(((decompressed_size(&path, compression)? * 3) / (1024 * 1024)) as usize)?;
+        read_decompressed(&path, compression)?
+    };
+
+    let mut attempts = Vec::new();
+    loop {
+        let failure = match attempt(file_path, &bytes, &settings) {
+            Ok((data, concessions)) => {
+                attempts.push(AutoAttempt { settings: settings.clone(), error: None, failure: None });
+                return Ok(AutoParseResult { data, settings, attempts, concessions });
+            }
+            Err(failure) => failure,
+        };
+        let (class, error) = failure;
+        attempts.push(AutoAttempt { settings: settings.clone(), error: Some(error.to_string()), failure: Some(class) });
+
+        let next = fallback(&settings, class, options);
+        if let (Some(next), true) = (&next, attempts.len() < options.max_attempts) {
+            settings = next.clone();
+            continue;
+        }
+        let mut message = format!(
+            "Could not parse {} as {} after {} attempt(s): {}",
+            file_path,
+            settings.format.name(),
+            attempts.len(),
+            error
+        );
+        match (next, class.fallback()) {
+            (Some(_), _) => message.push_str(&format!("; max_attempts ({}) reached", options.max_attempts)),
+            (None, Some(fallback)) if !enabled(options, fallback) => {
+                message.push_str(&format!("; the {} fallback is disabled", fallback))
+            }
+            _ => {}
+        }
+        let tried: Vec<String> = attempts.iter().map(|a| a.settings.describe()).collect();
+        message.push_str(&format!(" (tried: {})", tried.join("; ")));
+        return Err(InsightoraError::ParseError(message));
+    }
+}
+
+fn enabled(options: &AutoParseOptions, fallback: &str) -> bool {
+    match fallback {
+        "lossy_encoding" => options.lossy_encoding,
+        "skip_bad_lines" => options.skip_bad_lines,
+        "widen_schema" => options.widen_schema,
+        _ => false,
+    }
+}
+
+/// Settings for the retry after a failure of `class`, if any fallback applies
+fn fallback(settings: &AutoSettings, class: FailureClass, options: &AutoParseOptions) -> Option<AutoSettings> {
+    let mut next = settings.clone();
+    match class {
+        FailureClass::Encoding if options.lossy_encoding && !settings.lossy_encoding => next.lossy_encoding = true,
+        FailureClass::BadLines if options.skip_bad_lines && !settings.skip_bad_lines => next.skip_bad_lines = true,
+        FailureClass::Schema if options.widen_schema => {
+            next.schema = match (settings.schema, settings.format) {
+                (SchemaInference::Sampled(_), _) => SchemaInference::Full,
+                (SchemaInference::Full, AutoFormat::Csv | AutoFormat::Tsv) => SchemaInference::Text,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    }
+    Some(next)
+}
+
+/// Parse once with `settings`, returning the concessions made or the failure and its class
+fn attempt(
+    file_path: &str,
+    bytes: &[u8],
+    settings: &AutoSettings,
+) -> Result<(DataFrame, Vec<Concession>), (FailureClass, InsightoraError)> {
+    let other = |e: InsightoraError| (FailureClass::Other, e);
+    match settings.format {
+        AutoFormat::Parquet => {
+            let data = ParquetReader::new(Cursor::new(bytes)).finish().map_err(|e| other(e.into()))?;
+            return Ok((data, Vec::new()));
+        }
+        AutoFormat::Zip => {
+            let config = CsvParserConfig { chunk_size: get_current_config().chunk_size, ..Default::default() };
+            let result = parse_archive(file_path, None, &config, SchemaAlignment::Union).map_err(other)?;
+            return Ok((result.data, Vec::new()));
+        }
+        _ => {}
+    }
+
+    let mut concessions = Vec::new();
+    let encoding = settings.encoding.unwrap_or(TextEncoding::Utf8);
+    let (text, replaced) = encoding
+        .decode(bytes, settings.lossy_encoding)
+        .map_err(|e| (FailureClass::Encoding, e))?;
+    if replaced > 0 {
+        concessions.push(Concession {
+            fallback: "lossy_encoding",
+            detail: format!("{} invalid {} sequences replaced with U+FFFD", replaced, encoding.name()),
+        });
+    }
+
+    let (delimiter, quote_char) = (settings.delimiter.unwrap_or(b','), settings.quote_char.unwrap_or(b'"'));
+    let (text, skipped): (Cow<str>, Vec<usize>) = match (settings.skip_bad_lines, settings.format) {
+        (true, AutoFormat::Ndjson) => {
+            let (kept, skipped) = drop_bad_json_lines(&text);
+            (Cow::Owned(kept), skipped)
+        }
+        (true, _) => {
+            let (kept, skipped) = drop_bad_lines(&text, delimiter, quote_char);
+            (Cow::Owned(kept), skipped)
+        }
+        (false, _) => (text, Vec::new()),
+    };
+    if !skipped.is_empty() {
+        let lines: Vec<String> = skipped.iter().take(LOGGED_LINES).map(usize::to_string).collect();
+        concessions.push(Concession {
+            fallback: "skip_bad_lines",
+            detail: format!(
+                "{} lines skipped as {}; lines {}{}",
+                skipped.len(),
+                if settings.format == AutoFormat::Ndjson { "invalid JSON" } else { "having more fields than the header" },
+                lines.join(", "),
+                if skipped.len() > LOGGED_LINES { ", ..." } else { "" }
+            ),
+        });
+    }
+    match settings.schema {
+        SchemaInference::Sampled(_) => {}
+        SchemaInference::Full => concessions.push(Concession {
+            fallback: "widen_schema",
+            detail: format!("column types inferred from every row rather than the first {}", INFER_ROWS),
+        }),
+        SchemaInference::Text => concessions.push(Concession {
+            fallback: "widen_schema",
+            detail: "every column read as text".to_string(),
+        }),
+    }
+
+    let infer_rows = match settings.schema {
+        SchemaInference::Sampled(rows) => Some(rows),
+        SchemaInference::Full => None,
+        SchemaInference::Text => Some(0),
+    };
+    let parsed = match settings.format {
+        AutoFormat::Ndjson | AutoFormat::Json => {
+            let json_format = if settings.format == AutoFormat::Json { JsonFormat::Json } else { JsonFormat::JsonLines };
+            JsonReader::new(Cursor::new(text.as_bytes()))
+                .with_json_format(json_format)
+                .infer_schema_len(infer_rows)
+                .finish()
+                .map_err(InsightoraError::from)
+        }
+        _ => ParallelCsvParser::with_config(CsvParserConfig {
+            chunk_size: get_current_config().chunk_size,
+            delimiter,
+            quote_char,
+            infer_schema_length: infer_rows,
+            ..Default::default()
+        })
+        .parse_bytes(text.as_bytes()),
+    };
+    match parsed {
+        Ok(data) => Ok((data, concessions)),
+        Err(error) => Err((classify(&text, settings, &error), error)),
+    }
+}
+
+/// Class of a failed parse of `text`
+///
+/// Surplus fields and invalid JSON lines are found by scanning the text;
+/// type mismatches by the reader's "could not parse" message.
+fn classify(text: &str, settings: &AutoSettings, error: &InsightoraError) -> FailureClass {
+    let (delimiter, quote_char) = (settings.delimiter.unwrap_or(b','), settings.quote_char.unwrap_or(b'"'));
+    let bad_lines = match settings.format {
+        _ if settings.skip_bad_lines => false,
+        AutoFormat::Csv | AutoFormat::Tsv => !drop_bad_lines(text, delimiter, quote_char).1.is_empty(),
+        AutoFormat::Ndjson => !drop_bad_json_lines(text).1.is_empty(),
+        _ => false,
+    };
+    if bad_lines {
+        return FailureClass::BadLines;
+    }
+    let message = error.to_string();
+    match settings.format {
+        AutoFormat::Csv | AutoFormat::Tsv if message.contains("could not parse") => FailureClass::Schema,
+        // JSON type conflicts surface as deserialisation errors
+        AutoFormat::Ndjson | AutoFormat::Json if settings.schema != SchemaInference::Full => FailureClass::Schema,
+        _ => FailureClass::Other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messy_csv() -> Vec<u8> {
+        let mut bytes = b"id;name;score\n".to_vec();
+        for row in 1..=1200 {
+            let line = match row {
+                // Latin-1 e with diaeresis
+                5 => b"5;Zo\xEB;5\n".to_vec(),
+                10 => b"10;Ann;10;surplus\n".to_vec(),
+                1200 => b"1200;Late;n/a\n".to_vec(),
+                _ => format!("{};\"Name; {}\";{}\n", row, row, row).into_bytes(),
+            };
+            bytes.extend(line);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_fallbacks_apply_in_order_and_can_be_disabled() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("upload.dat");
+        std::fs::write(&path, messy_csv()).unwrap();
+        let path = path.to_string_lossy().into_owned();
+
+        let result = parse_auto(&path, &AutoParseOptions::default()).unwrap();
+        assert_eq!(result.data.shape(), (1199, 3));
+        assert_eq!(result.data.column("score").unwrap().dtype(), &DataType::String);
+        assert_eq!((result.settings.format, result.settings.delimiter), (AutoFormat::Csv, Some(b';')));
+        let failures: Vec<Option<&str>> = result.attempts.iter().map(|a| a.failure.map(|f| f.name())).collect();
+        assert_eq!(failures, [Some("encoding"), Some("bad_lines"), Some("schema"), None]);
+        let fallbacks: Vec<&str> = result.concessions.iter().map(|c| c.fallback).collect();
+        assert_eq!(fallbacks, ["lossy_encoding", "skip_bad_lines", "widen_schema"]);
+        assert_eq!(result.concessions[1].detail, "1 lines skipped as having more fields than the header; lines 11");
+        let name = result.data.column("name").unwrap().str().unwrap().get(4).map(str::to_string);
+        assert_eq!(name.as_deref(), Some("Zo\u{FFFD}"));
+
+        let strict = AutoParseOptions { skip_bad_lines: false, ..Default::default() };
+        let err = parse_auto(&path, &strict).unwrap_err().to_string();
+        assert!(err.contains("after 2 attempt(s)") && err.contains("the skip_bad_lines fallback is disabled"), "{}", err);
+        let err = parse_auto(&path, &AutoParseOptions { max_attempts: 1, ..Default::default() }).unwrap_err().to_string();
+        assert!(err.contains("invalid UTF-8 at byte 74 (line 6)") && err.contains("max_attempts (1) reached"), "{}", err);
+    }
+
+    #[test]
+    fn test_detects_formats_and_dialects() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let write = |name: &str, bytes: &[u8]| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, bytes).unwrap();
+            path.to_string_lossy().into_owned()
+        };
+
+        let ndjson = write("events.log", b"{\"id\": 1, \"kind\": \"open\"}\n{\"id\": 2, \"kind\": \nnot json\n{\"id\": 3, \"kind\": \"close\"}\n");
+        let result = parse_auto(&ndjson, &AutoParseOptions::default()).unwrap();
+        assert_eq!(result.settings.format, AutoFormat::Ndjson);
+        assert_eq!(result.data.height(), 2);
+        assert!(result.settings.skip_bad_lines);
+
+        let mut df = df!("id" => [1i64, 2], "kind" => ["a", "b"]).unwrap();
+        let mut parquet = Vec::new();
+        ParquetWriter::new(&mut parquet).finish(&mut df).unwrap();
+        let result = parse_auto(&write("export.bin", &parquet), &AutoParseOptions::default()).unwrap();
+        assert_eq!((result.settings.format, result.data.height()), (AutoFormat::Parquet, 2));
+
+        let utf16: Vec<u8> = [0xFF, 0xFE].into_iter()
+            .chain("a\tb\n1\t'x, y'\n".encode_utf16().flat_map(u16::to_le_bytes))
+            .collect();
+        let result = parse_auto(&write("export.txt", &utf16), &AutoParseOptions::default()).unwrap();
+        assert_eq!(result.settings.format, AutoFormat::Tsv);
+        assert_eq!(result.settings.encoding, Some(TextEncoding::Utf16Le));
+        assert_eq!(result.attempts.len(), 1);
+        assert!(result.concessions.is_empty());
+
+        assert_eq!(sniff_dialect("a|'b|c'|d\n1|'2|3'|4\n"), (b'|', b'\''));
+        assert_eq!(detect_format("report.xlsx", b"PK\x03\x04\x14\x00").unwrap(), AutoFormat::Excel);
+        assert!(detect_format("scan.pdf", b"%PDF-1.7").unwrap_err().to_string().contains("PDF document"));
+    }
+
+    #[test]
+    fn test_sniff_csv_finds_delimiter_header_and_encoding() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let write = |name: &str, bytes: &[u8]| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, bytes).unwrap();
+            path.to_string_lossy().into_owned()
+        };
+
+        // Commas inside quotes outnumber the semicolons, which alone split every record alike
+        let headerless = write("orders.csv", b"1001;\"Smith, J, Jr\";12.50\n1002;\"Lee, A\";8\n1003;\"Ng, B, C, D\";3.25\n");
+        let dialect = sniff_csv(&headerless, SNIFF_BYTES).unwrap();
+        assert_eq!((dialect.delimiter, dialect.quote_char), (b';', b'"'));
+        assert_eq!((dialect.column_count, dialect.records, dialect.consistency), (3, 3, 1.0));
+        assert!(!dialect.has_header);
+        assert_eq!((dialect.encoding, dialect.byte_order_mark, dialect.invalid_utf8_at), (Some(CsvEncoding::Utf8), None, None));
+
+        let text: String = std::iter::once("id|name|active\n".to_string())
+            .chain((0..100).map(|i| format!("{}|caf\u{e9} {}|{}\n", i, i, i % 2 == 0)))
+            .collect();
+        let with_header = write("export.psv", format!("\u{FEFF}{}", text).as_bytes());
+        let dialect = sniff_csv(&with_header, 200).unwrap();
+        assert_eq!((dialect.delimiter, dialect.column_count), (b'|', 3));
+        assert!(dialect.has_header);
+        assert_eq!(dialect.byte_order_mark, Some(TextEncoding::Utf8Bom));
+        // The cut record at the end of the 200-byte sample isn't counted
+        assert!(dialect.records < 20 && dialect.consistency == 1.0, "{:?}", dialect);
+
+        let cp1252 = write("legacy.csv", b"name,quote\nRen\xE9,\x93hi\x94\n");
+        let dialect = sniff_csv(&cp1252, SNIFF_BYTES).unwrap();
+        assert_eq!((dialect.encoding, dialect.invalid_utf8_at), (Some(CsvEncoding::Windows1252), Some(14)));
+        assert!(dialect.has_header);
+    }
+}
@@ -0,0 +1,381 @@
+// Chunked file reading
+// Record-aligned byte-range reads with retry, backoff and sanity checks for flaky network filesystems
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Duration;
+use crate::config::get_current_config;
+use crate::error::InsightoraError;
+
+/// Bytes requested per chunk read
+pub const DEFAULT_CHUNK_BYTES: usize = 8 << 20;
+
+/// Longest wait between two attempts, however many retries are configured
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Random-access source of bytes
+///
+/// Implemented for `File`; tests wrap it to inject failures.
+pub trait ByteSource: Send {
+    /// Total size in bytes
+    fn size(&mut self) -> io::Result<u64>;
+    /// Read into `buf` from `offset`; may return fewer bytes than asked
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<usize>;
+}
+
+impl ByteSource for File {
+    fn size(&mut self) -> io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.seek(SeekFrom::Start(offset))?;
+        self.read(buf)
+    }
+}
+
+/// How often and how patiently a failed chunk read is retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts after the first before giving up
+    pub retries: usize,
+    /// Wait before the first retry; doubled for each further one
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { retries: 3, backoff: Duration::from_millis(100) }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy from `configure(io_retries=..., io_retry_backoff_ms=...)`
+    pub fn from_config() -> Self {
+        let config = get_current_config();
+        Self {
+            retries: config.io_retries,
+            backoff: Duration::from_millis(config.io_retry_backoff_ms),
+        }
+    }
+
+    fn delay(&self, retry: usize) -> Duration {
+        self.backoff.saturating_mul(1 << retry.min(16)).min(MAX_BACKOFF)
+    }
+}
+
+/// Reads a CSV file as runs of whole records
+///
+/// Each chunk is a byte range ending on a record terminator (a newline
+/// outside quotes), so it parses on its own. A chunk is only accepted once
+/// it passes sanity checks: the full range was read (no short read before
+/// the known end of file) and it holds no NUL bytes, which is how some
+/// network filesystems surface unfetched pages. IO errors and failed checks
+/// retry the same range with exponential backoff; after the last retry the
+/// read fails with `InsightoraError::ChunkRead`, naming the byte range and
+/// the underlying errno.
+///
+/// Also implements `Read` over the accepted chunks, for line-based readers.
+pub struct ChunkReader<S: ByteSource> {
+    source: S,
+    label: String,
+    size: u64,
+    offset: u64,
+    chunk_bytes: usize,
+    quote_char: Option<u8>,
+    policy: RetryPolicy,
+    retried_reads: usize,
+    current: Vec<u8>,
+    position: usize,
+}
+
+impl ChunkReader<File> {
+    /// Open a file with the configured retry policy
+    pub fn open(path: &Path, quote_char: Option<u8>) -> Result<Self, InsightoraError> {
+        let file = File::open(path)?;
+        Self::new(file, &path.display().to_string(), DEFAULT_CHUNK_BYTES, quote_char, RetryPolicy::from_config())
+    }
+}
+
+impl<S: ByteSource> ChunkReader<S> {
+    /// # Arguments
+    /// * `source` - Bytes to read
+    /// * `label` - Name used in errors (usually the path)
+    /// * `chunk_bytes` - Bytes requested per read; grown for records longer than this
+    /// * `quote_char` - Quote character, so newlines inside quoted fields don't
+    ///   end a chunk; None to cut at any newline (for line-based readers)
+    /// * `policy` - Retries for failed reads
+    pub fn new(
+        mut source: S,
+        label: &str,
+        chunk_bytes: usize,
+        quote_char: Option<u8>,
+        policy: RetryPolicy,
+    ) -> Result<Self, InsightoraError> {
+        let size = source.size()?;
+        Ok(Self {
+            source,
+            label: label.to_string(),
+            size,
+            offset: 0,
+            chunk_bytes: chunk_bytes.max(1),
+            quote_char,
+            policy,
+            retried_reads: 0,
+            current: Vec::new(),
+            position: 0,
+        })
+    }
+
+    /// Reads that failed and were tried again
+    pub fn retried_reads(&self) -> usize {
+        self.retried_reads
+    }
+
+    /// Bytes accepted so far
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Next run of whole records, or None at end of input
+    ///
+    /// The final chunk runs to the end of the file, with or without a
+    /// trailing terminator.
+    pub fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, InsightoraError> {
+        if self.offset >= self.size {
+            return Ok(None);
+        }
+        let mut want = self.chunk_bytes as u64;
+        loop {
+            let end = self.offset.saturating_add(want).min(self.size);
+            let mut bytes = self.read_range(self.offset, end)?;
+            if end == self.size {
+                self.offset = end;
+                return Ok(Some(bytes));
+            }
+            // A record longer than the chunk: read a wider range
+            if let Some(cut) = last_record_end(&bytes, self.quote_char) {
+                bytes.truncate(cut);
+                self.offset += cut as u64;
+                return Ok(Some(bytes));
+            }
+            want = want.saturating_mul(2);
+        }
+    }
+
+    /// Read `start..end`, retrying until it passes the sanity checks
+    fn read_range(&mut self, start: u64, end: u64) -> Result<Vec<u8>, InsightoraError> {
+        let mut retry = 0;
+        loop {
+            match self.read_once(start, end) {
+                Ok(bytes) => return Ok(bytes),
+                Err(_) if retry < self.policy.retries => {
+                    std::thread::sleep(self.policy.delay(retry));
+                    retry += 1;
+                    self.retried_reads += 1;
+                }
+                Err(err) => {
+                    return Err(InsightoraError::ChunkRead {
+                        path: self.label.clone(),
+                        start,
+                        end,
+                        attempts: retry + 1,
+                        errno: err.raw_os_error(),
+                        reason: err.to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    fn read_once(&mut self, start: u64, end: u64) -> io::Result<Vec<u8>> {
+        let mut bytes = vec![0u8; (end - start) as usize];
+        let mut filled = 0;
+        while filled < bytes.len() {
+            match self.source.read_at(start + filled as u64, &mut bytes[filled..]) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        format!("short read: {} of {} bytes before the end of the file", filled, bytes.len()),
+                    ));
+                }
+                Ok(n) => filled += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        if let Some(at) = bytes.iter().position(|&b| b == 0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("NUL byte at offset {}", start + at as u64),
+            ));
+        }
+        Ok(bytes)
+    }
+}
+
+impl<S: ByteSource> Read for ChunkReader<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.current.len() {
+            match self.next_chunk() {
+                Ok(Some(chunk)) => {
+                    self.current = chunk;
+                    self.position = 0;
+                }
+                Ok(None) => return Ok(0),
+                Err(err) => return Err(io::Error::other(err)),
+            }
+        }
+        let n = buf.len().min(self.current.len() - self.position);
+        buf[..n].copy_from_slice(&self.current[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+}
+
+/// Recover the `ChunkRead` error carried through `Read` by a `ChunkReader`
+pub(crate) fn from_read_error(err: io::Error) -> InsightoraError {
+    if !err.get_ref().is_some_and(|inner| inner.is::<InsightoraError>()) {
+        return InsightoraError::IoError(err);
+    }
+    match err.into_inner().map(|inner| inner.downcast::<InsightoraError>()) {
+        Some(Ok(inner)) => *inner,
+        Some(Err(other)) => InsightoraError::IoError(io::Error::other(other)),
+        None => unreachable!("checked for an inner error above"),
+    }
+}
+
+/// Offset just past the last newline outside quotes
+///
+/// `bytes` must start on a record boundary. Doubled quotes toggle twice,
+/// so escaped quotes leave the state unchanged. Without a quote character
+/// every newline ends a record.
+pub(crate) fn last_record_end(bytes: &[u8], quote_char: Option<u8>) -> Option<usize> {
+    let mut in_quotes = false;
+    let mut end = None;
+    for (i, &b) in bytes.iter().enumerate() {
+        if Some(b) == quote_char {
+            in_quotes = !in_quotes;
+        } else if b == b'\n' && !in_quotes {
+            end = Some(i + 1);
+        }
+    }
+    end
+}
+
+/// Offset just past the first newline outside quotes
+pub(crate) fn first_record_end(bytes: &[u8], quote_char: Option<u8>) -> Option<usize> {
+    let mut in_quotes = false;
+    for (i, &b) in bytes.iter().enumerate() {
+        if Some(b) == quote_char {
+            in_quotes = !in_quotes;
+        } else if b == b'\n' && !in_quotes {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::ops::Range;
+
+    /// Wraps bytes and fails reads overlapping a range a set number of times
+    pub(crate) struct FlakySource {
+        bytes: Vec<u8>,
+        /// (range, failures left, errno; None for a short read)
+        faults: Vec<(Range<u64>, usize, Option<i32>)>,
+    }
+
+    impl FlakySource {
+        pub(crate) fn new(bytes: &[u8]) -> Self {
+            Self { bytes: bytes.to_vec(), faults: Vec::new() }
+        }
+
+        /// Fail `times` reads touching `range` with `errno`, or cut them short
+        pub(crate) fn fail(mut self, range: Range<u64>, times: usize, errno: Option<i32>) -> Self {
+            self.faults.push((range, times, errno));
+            self
+        }
+    }
+
+    impl ByteSource for FlakySource {
+        fn size(&mut self) -> io::Result<u64> {
+            Ok(self.bytes.len() as u64)
+        }
+
+        fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+            let end = offset + buf.len() as u64;
+            for (range, left, errno) in &mut self.faults {
+                if *left > 0 && offset < range.end && range.start < end {
+                    *left -= 1;
+                    return match errno {
+                        Some(errno) => Err(io::Error::from_raw_os_error(*errno)),
+                        // Stop at the fault as a truncated NFS read would
+                        None => Ok(0),
+                    };
+                }
+            }
+            let start = (offset as usize).min(self.bytes.len());
+            let n = buf.len().min(self.bytes.len() - start);
+            buf[..n].copy_from_slice(&self.bytes[start..start + n]);
+            Ok(n)
+        }
+    }
+
+    pub(crate) fn quick_retries(retries: usize) -> RetryPolicy {
+        RetryPolicy { retries, backoff: Duration::from_millis(1) }
+    }
+
+    fn read_all<S: ByteSource>(reader: &mut ChunkReader<S>) -> Result<Vec<Vec<u8>>, InsightoraError> {
+        let mut chunks = Vec::new();
+        while let Some(chunk) = reader.next_chunk()? {
+            chunks.push(chunk);
+        }
+        Ok(chunks)
+    }
+
+    #[test]
+    fn test_chunks_end_on_records_and_survive_transient_faults() {
+        let text = b"id,note\n1,\"two\nlines\"\n2,plain\n3,a much longer record than one chunk\n4,end";
+        let source = FlakySource::new(text)
+            .fail(0..4, 2, Some(5))
+            .fail(30..31, 1, None);
+        let mut reader = ChunkReader::new(source, "flaky.csv", 12, Some(b'"'), quick_retries(3)).unwrap();
+
+        let chunks = read_all(&mut reader).unwrap();
+        assert_eq!(chunks.concat(), text.to_vec());
+        assert_eq!(reader.retried_reads(), 3);
+        // Every chunk but the last ends on a record outside quotes
+        for chunk in &chunks[..chunks.len() - 1] {
+            assert_eq!(last_record_end(chunk, Some(b'"')), Some(chunk.len()));
+        }
+        assert!(chunks.iter().all(|chunk| !chunk.starts_with(b"lines")));
+    }
+
+    #[test]
+    fn test_exhausted_retries_report_range_and_errno() {
+        let source = FlakySource::new(b"a,b\n1,2\n3,4\n").fail(8..9, 10, Some(5));
+        let mut reader = ChunkReader::new(source, "nfs/data.csv", 8, None, quick_retries(2)).unwrap();
+        assert_eq!(reader.next_chunk().unwrap().unwrap(), b"a,b\n1,2\n");
+
+        let err = reader.next_chunk().unwrap_err();
+        let InsightoraError::ChunkRead { start, end, attempts, errno, .. } = &err else {
+            panic!("expected a chunk read error, got {}", err);
+        };
+        assert_eq!((*start, *end, *attempts, *errno), (8, 12, 3, Some(5)));
+        assert!(err.to_string().contains("bytes 8..12 of nfs/data.csv"), "{}", err);
+
+        // The same error comes back out of the Read adapter
+        let source = FlakySource::new(b"a,b\n1,2\n").fail(0..1, 10, None);
+        let reader = ChunkReader::new(source, "short.csv", 64, None, quick_retries(1)).unwrap();
+        let err = io::read_to_string(reader).unwrap_err();
+        assert!(matches!(from_read_error(err), InsightoraError::ChunkRead { attempts: 2, errno: None, .. }));
+    }
+}
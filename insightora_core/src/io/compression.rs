@@ -0,0 +1,181 @@
+// Compressed input files
+// Detects gzip and zstd files and decompresses them as a stream, with decompressed size estimates for memory checks
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use flate2::read::MultiGzDecoder;
+use crate::error::InsightoraError;
+
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// Slack for gzip headers (file name, comment) when checking a size trailer
+const GZIP_OVERHEAD_BYTES: u64 = 1024;
+
+/// Largest zstd frame header, enough to read its content size
+const ZSTD_FRAME_HEADER_BYTES: usize = 18;
+
+/// Decompressed-to-compressed ratio assumed when a file doesn't record its
+/// decompressed size (zstd written as a stream); typical for CSV text
+pub const ASSUMED_COMPRESSION_RATIO: u64 = 8;
+
+/// Compression of an input file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Recognise a file by its leading bytes, or by its `.gz` / `.zst`
+    /// extension when it is too short to carry a signature
+    pub fn detect(path: &Path) -> Result<Self, InsightoraError> {
+        let mut head = [0u8; 4];
+        let mut file = File::open(path)?;
+        let mut read = 0;
+        while read < head.len() {
+            match file.read(&mut head[read..])? {
+                0 => break,
+                n => read += n,
+            }
+        }
+        let head = &head[..read];
+        if head.starts_with(&GZIP_MAGIC) {
+            return Ok(Compression::Gzip);
+        }
+        if head.starts_with(&ZSTD_MAGIC) {
+            return Ok(Compression::Zstd);
+        }
+        if read == 4 {
+            // A full header without a signature is plain text, whatever the name
+            return Ok(Compression::None);
+        }
+        let extension = path.extension().map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+        Ok(match extension.as_deref() {
+            Some("gz") | Some("gzip") => Compression::Gzip,
+            Some("zst") | Some("zstd") => Compression::Zstd,
+            _ => Compression::None,
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Compression::None => "none",
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+        }
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        *self != Compression::None
+    }
+}
+
+/// Open a file for reading, decompressing it on the fly
+///
+/// Concatenated gzip members (as written by `cat a.gz b.gz`) are read as
+/// one stream.
+pub fn open_decompressed(path: &Path, compression: Compression) -> Result<Box<dyn Read + Send>, InsightoraError> {
+    let file = BufReader::new(File::open(path)?);
+    Ok(match compression {
+        Compression::None => Box::new(file),
+        Compression::Gzip => Box::new(MultiGzDecoder::new(file)),
+        Compression::Zstd => Box::new(zstd::stream::read::Decoder::with_buffer(file)?),
+    })
+}
+
+/// Expected size of the file's contents once decompressed
+///
+/// Plain files report their size. gzip records the decompressed size
+/// modulo 4 GiB in its trailer; files whose trailer is implausibly small for
+/// their compressed size are assumed to have wrapped. zstd uses the content
+/// size from the frame header, or `ASSUMED_COMPRESSION_RATIO` when the
+/// writer didn't record it. For multi-member gzip files only the last
+/// member's size is recorded, so the estimate is low.
+pub fn decompressed_size(path: &Path, compression: Compression) -> Result<u64, InsightoraError> {
+    let mut file = File::open(path)?;
+    let compressed = file.metadata()?.len();
+    match compression {
+        Compression::None => Ok(compressed),
+        Compression::Gzip => {
+            if compressed < 18 {
+                return Ok(0);
+            }
+            let mut trailer = [0u8; 4];
+            file.seek(SeekFrom::End(-4))?;
+            file.read_exact(&mut trailer)?;
+            let mut size = u32::from_le_bytes(trailer) as u64;
+            // Incompressible data only grows by headers and a few bytes per block
+            while size + GZIP_OVERHEAD_BYTES < compressed / 2 {
+                size += 1 << 32;
+            }
+            Ok(size)
+        }
+        Compression::Zstd => {
+            let mut header = Vec::with_capacity(ZSTD_FRAME_HEADER_BYTES);
+            file.take(ZSTD_FRAME_HEADER_BYTES as u64).read_to_end(&mut header)?;
+            match zstd::zstd_safe::get_frame_content_size(&header) {
+                Ok(Some(size)) => Ok(size),
+                _ => Ok(compressed.saturating_mul(ASSUMED_COMPRESSION_RATIO)),
+            }
+        }
+    }
+}
+
+/// Decompress a whole file into memory
+pub fn read_decompressed(path: &Path, compression: Compression) -> Result<Vec<u8>, InsightoraError> {
+    let mut bytes = Vec::with_capacity(decompressed_size(path, compression)?.min(1 << 30) as usize);
+    open_decompressed(path, compression)?.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Decompress only the first `lines` lines of a file
+///
+/// Used for schema inference, so a large archive is not inflated just to
+/// look at its first rows. The last line may be cut inside a quoted field;
+/// callers read one line more than they parse.
+pub fn read_decompressed_lines(path: &Path, compression: Compression, lines: usize) -> Result<Vec<u8>, InsightoraError> {
+    let mut reader = BufReader::new(open_decompressed(path, compression)?);
+    let mut bytes = Vec::new();
+    for _ in 0..lines {
+        if reader.read_until(b'\n', &mut bytes)? == 0 {
+            break;
+        }
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detects_and_sizes_compressed_files() {
+        let dir = TempDir::new().unwrap();
+        let text: String = (0..1000).map(|i| format!("{},region_{}\n", i, i % 7)).collect();
+
+        let gzip = dir.path().join("rows.csv.gz");
+        let mut encoder = flate2::write::GzEncoder::new(File::create(&gzip).unwrap(), flate2::Compression::default());
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+        // zstd content is recognised by its signature, not the name
+        let zstd_path = dir.path().join("rows.bin");
+        std::fs::write(&zstd_path, zstd::bulk::compress(text.as_bytes(), 3).unwrap()).unwrap();
+        let plain = dir.path().join("rows.csv");
+        std::fs::write(&plain, &text).unwrap();
+
+        for (path, expected) in [(&gzip, Compression::Gzip), (&zstd_path, Compression::Zstd), (&plain, Compression::None)] {
+            let compression = Compression::detect(path).unwrap();
+            assert_eq!(compression, expected);
+            assert_eq!(decompressed_size(path, compression).unwrap(), text.len() as u64);
+            assert_eq!(read_decompressed(path, compression).unwrap(), text.as_bytes());
+        }
+        let head = read_decompressed_lines(&gzip, Compression::Gzip, 2).unwrap();
+        assert_eq!(head, b"0,region_0\n1,region_1\n");
+    }
+}
@@ -0,0 +1,241 @@
+// Multi-file CSV parsing
+// Expands a glob pattern and parses the matching CSV files in parallel into one table
+
+use std::path::PathBuf;
+use polars::prelude::*;
+use rayon::prelude::*;
+use crate::config::check_memory_limit;
+use crate::error::InsightoraError;
+use crate::io::csv_parser::{parse_memory_mb, CsvParserConfig, ParallelCsvParser};
+use crate::utils::sandbox::check_path_allowed;
+
+/// Column added with `include_filename_column`: the path each row was read from
+pub const SOURCE_FILE_COLUMN: &str = "source_file";
+
+/// Combined data from the files a glob pattern matched
+#[derive(Debug, Clone)]
+pub struct GlobParseResult {
+    pub data: DataFrame,
+    /// (path, rows) per file, in path order
+    pub files: Vec<(String, usize)>,
+}
+
+/// Files matching a glob pattern, sorted by path
+///
+/// `*`, `?` and `[...]` match within one path component and `**` matches
+/// any number of directories. Directories that match are left out; every
+/// file must be allowed by the path policy.
+///
+/// # Arguments
+/// * `pattern` - Glob pattern, e.g. "exports/events_2024-*.csv"
+///
+/// # Returns
+/// * `Result<Vec<PathBuf>>` - Matching files; none is a ValidationError
+pub fn expand_glob(pattern: &str) -> Result<Vec<PathBuf>, InsightoraError> {
+    let paths = glob::glob(pattern)
+        .map_err(|e| InsightoraError::ValidationError(format!("Invalid glob pattern '{}': {}", pattern, e)))?;
+    let mut files = Vec::new();
+    for path in paths {
+        let path = path.map_err(std::io::Error::from)?;
+        if path.is_file() {
+            check_path_allowed(&path)?;
+            files.push(path);
+        }
+    }
+    if files.is_empty() {
+        return Err(InsightoraError::ValidationError(format!("No files match '{}'", pattern)));
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Parse every CSV file matching a glob pattern into one DataFrame
+///
+/// Files are parsed in parallel on the Rayon pool with `config`, then
+/// concatenated in path order. They must have the same columns; a column's
+/// type may differ between files only where it widens: integer widths to
+/// Int64, integers and floats to Float64, and a column with no values in a
+/// file (or a file with no rows) takes the other files' type. Columns are
+/// ordered as in the first file.
+///
+/// # Arguments
+/// * `pattern` - Glob pattern, as in `expand_glob`
+/// * `config` - Options for every file; `max_rows` applies per file
+/// * `include_filename_column` - Append `SOURCE_FILE_COLUMN`
+///
+/// # Returns
+/// * `Result<GlobParseResult>` - Combined data and rows per file; files
+///   whose columns conflict are a `SchemaError` naming both files and the
+///   column
+///
+/// # Example
+/// ```no_run
+/// use insightora_core::api::{parse_csv_glob, CsvParserConfig};
+///
+/// let result = parse_csv_glob("exports/events_2024-*.csv", &CsvParserConfig::default(), true).unwrap();
+/// println!("{} rows from {} files", result.data.height(), result.files.len());
+/// ```
+pub fn parse_csv_glob(
+    pattern: &str,
+    config: &CsvParserConfig,
+    include_filename_column: bool,
+) -> Result<GlobParseResult, InsightoraError> {
+    let files = expand_glob(pattern)?;
+    let names: Vec<String> = files.iter().map(|path| path.to_string_lossy().into_owned()).collect();
+
+    // Every file is held at once until they are concatenated
+    let lines = ParallelCsvParser::with_config(config.clone()).head_lines()?;
+    let memory_mb = files
+        .iter()
+        .map(|path| parse_memory_mb(path, lines, 2))
+        .sum::<Result<usize, InsightoraError>>()?;
+    check_memory_limit(memory_mb)?;
+
+    let frames = names
+        .par_iter()
+        .map(|name| {
+            ParallelCsvParser::with_config(config.clone()).parse(name).map_err(|error| match error {
+                InsightoraError::ParseError(message) => InsightoraError::ParseError(format!("{}: {}", name, message)),
+                other => other,
+            })
+        })
+        .collect::<Result<Vec<DataFrame>, InsightoraError>>()?;
+
+    let schema = combined_schema(&names, &frames)?;
+    if include_filename_column && schema.iter().any(|(name, _)| name == SOURCE_FILE_COLUMN) {
+        return Err(InsightoraError::ValidationError(format!(
+            "The files already have a '{}' column; rename it to include the filename column",
+            SOURCE_FILE_COLUMN
+        )));
+    }
+
+    let mut combined: Option<DataFrame> = None;
+    for (name, df) in names.iter().zip(&frames) {
+        let mut columns = schema
+            .iter()
+            .map(|(column, dtype)| df.column(column)?.cast(dtype))
+            .collect::<PolarsResult<Vec<Series>>>()?;
+        if include_filename_column {
+            columns.push(StringChunked::full(SOURCE_FILE_COLUMN, name, df.height()).into_series());
+        }
+        let aligned = DataFrame::new(columns)?;
+        match combined.as_mut() {
+            Some(acc) => {
+                acc.vstack_mut(&aligned)?;
+            }
+            None => combined = Some(aligned),
+        }
+    }
+    let mut data = combined.unwrap_or_default();
+    data.align_chunks();
+
+    Ok(GlobParseResult {
+        data,
+        files: names.into_iter().zip(frames.iter().map(DataFrame::height)).collect(),
+    })
+}
+
+/// Column types for the frames combined, in the first frame's order
+///
+/// Stops at the first file whose columns conflict with the ones before it,
+/// listing every conflicting column.
+fn combined_schema(names: &[String], frames: &[DataFrame]) -> Result<Vec<(String, DataType)>, InsightoraError> {
+    // (column, type so far, file the type was taken from)
+    let mut columns: Vec<(String, DataType, usize)> = frames[0]
+        .get_columns()
+        .iter()
+        .map(|series| (series.name().to_string(), observed_dtype(series), 0))
+        .collect();
+
+    for (index, df) in frames.iter().enumerate().skip(1) {
+        let mut problems = Vec::new();
+        for (column, ..) in &columns {
+            if df.column(column).is_err() {
+                problems.push(format!("column '{}' is in {} but not in {}", column, names[0], names[index]));
+            }
+        }
+        for series in df.get_columns() {
+            let Some((column, dtype, source)) = columns.iter_mut().find(|(column, ..)| column == series.name()) else {
+                problems.push(format!("column '{}' is in {} but not in {}", series.name(), names[index], names[0]));
+                continue;
+            };
+            let other = observed_dtype(series);
+            match promoted(dtype, &other) {
+                Some(wider) => {
+                    if *dtype == DataType::Null {
+                        *source = index;
+                    }
+                    *dtype = wider;
+                }
+                None => problems.push(format!(
+                    "column '{}' is {} in {} but {} in {}",
+                    column, dtype, names[*source], other, names[index]
+                )),
+            }
+        }
+        if !problems.is_empty() {
+            return Err(InsightoraError::SchemaError(problems));
+        }
+    }
+
+    // Columns without a value in any file stay text, as a CSV reader types them
+    Ok(columns
+        .into_iter()
+        .map(|(column, dtype, _)| (column, if dtype == DataType::Null { DataType::String } else { dtype }))
+        .collect())
+}
+
+/// A column's type, or Null when it holds no values and so fits any type
+fn observed_dtype(series: &Series) -> DataType {
+    if series.null_count() == series.len() {
+        DataType::Null
+    } else {
+        series.dtype().clone()
+    }
+}
+
+/// The type both `left` and `right` values fit without loss of meaning
+fn promoted(left: &DataType, right: &DataType) -> Option<DataType> {
+    match (left, right) {
+        _ if left == right => Some(left.clone()),
+        (DataType::Null, other) | (other, DataType::Null) => Some(other.clone()),
+        _ if left.is_integer() && right.is_integer() => Some(DataType::Int64),
+        _ if left.is_numeric() && right.is_numeric() => Some(DataType::Float64),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv_glob_widens_and_rejects_conflicts() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, text: &str| std::fs::write(dir.path().join(name), text).unwrap();
+        write("events_2024-02.csv", "id,amount,note\n3,2.5,\n");
+        write("events_2024-01.csv", "id,amount,note\n1,10,a\n2,20,b\n");
+        write("events_2024-03.txt", "not,a,match\n");
+        write("other_1.csv", "id,amount\n1,10\n");
+        write("other_2.csv", "id,amount\nx,1\n");
+
+        let pattern = dir.path().join("events_2024-*.csv").to_string_lossy().into_owned();
+        let result = parse_csv_glob(&pattern, &CsvParserConfig::default(), true).unwrap();
+        assert_eq!(result.files.iter().map(|(_, rows)| *rows).collect::<Vec<_>>(), vec![2, 1]);
+        assert!(result.files[0].0.ends_with("events_2024-01.csv"));
+        let df = &result.data;
+        assert_eq!(df.get_column_names(), vec!["id", "amount", "note", SOURCE_FILE_COLUMN]);
+        assert_eq!(df.column("amount").unwrap().dtype(), &DataType::Float64);
+        assert_eq!(df.column("amount").unwrap().f64().unwrap().get(2), Some(2.5));
+        assert_eq!(df.column("note").unwrap().str().unwrap().get(1), Some("b"));
+        assert!(df.column(SOURCE_FILE_COLUMN).unwrap().str().unwrap().get(2).unwrap().ends_with("events_2024-02.csv"));
+
+        let conflict = dir.path().join("other_*.csv").to_string_lossy().into_owned();
+        let message = parse_csv_glob(&conflict, &CsvParserConfig::default(), false).unwrap_err().to_string();
+        assert!(message.contains("column 'id' is i64 in"), "{}", message);
+        assert!(message.contains("other_1.csv but str in") && message.ends_with("other_2.csv"), "{}", message);
+
+        let none = dir.path().join("missing_*.csv").to_string_lossy().into_owned();
+        assert!(matches!(parse_csv_glob(&none, &CsvParserConfig::default(), false), Err(InsightoraError::ValidationError(_))));
+    }
+}
@@ -3,10 +3,29 @@
 
 use rayon::prelude::*;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Cursor, Read, Seek, SeekFrom};
 use std::path::Path;
 use polars::prelude::*;
-use crate::python_bindings::{InsightoraError, get_current_config, check_memory_limit};
+use polars::io::mmap::MmapBytesReader;
+use crate::error::InsightoraError;
+use crate::config::{get_current_config, check_memory_limit};
+use crate::utils::memory;
+use crate::utils::progress::ProgressReporter;
+use crate::stats::identifiers::{detect_identifiers, IdDetectionConfig, IdentifierDecision};
+use crate::utils::sandbox::check_path_allowed;
+use crate::io::schema_report::{scan_types, SchemaReport};
+use crate::io::sampling::{RowSample, RowSampleReport, RowSampler};
+use crate::io::csv_repair::{repair_file, split_fields, CsvRepairOptions, RepairReport, REPAIR_FLAG_COLUMN};
+use crate::io::chunk_reader::{first_record_end, ByteSource, ChunkReader, RetryPolicy};
+use crate::io::file_index::{ColumnStats, FileIndex, FileStamp, IndexBlock, IndexScanReport};
+use crate::dataframe::expressions::Predicate;
+use crate::io::nullability::{NotNullRules, NullCheck, NullabilityReport};
+use crate::io::dates::{parse_date_columns, DateFormat};
+use crate::utils::warnings::{AggregatedWarning, WarningCollector};
+use crate::utils::capabilities::{degrade, use_fast_path, FastPath};
+use crate::io::encoding::{transcode_bytes, utf8_error, CsvEncoding, EncodingErrors, TranscodedFile};
+use crate::io::compression::{decompressed_size, open_decompressed, read_decompressed, read_decompressed_lines, Compression};
+use crate::dataframe::transformations::{cast_to_duration, projection_indices, rename_and_project, ColumnMapping, DurationFormat};
 
 /// Configuration for CSV parsing
 #[derive(Debug, Clone)]
@@ -16,6 +35,52 @@ pub struct CsvParserConfig {
     pub delimiter: u8,
     pub quote_char: u8,
     pub infer_schema_length: Option<usize>,
+    /// Rewrites rows split by unescaped delimiters before parsing (off by default)
+    pub repair: CsvRepairOptions,
+    /// Renames applied as soon as the header is read (see `ColumnMapping`)
+    pub rename: Option<ColumnMapping>,
+    /// Columns to keep, by source or renamed name, in this order (default: all)
+    pub columns: Option<Vec<String>>,
+    /// Columns to keep by zero-based position in the file, in this order;
+    /// an alternative to `columns` (default: all)
+    pub column_indices: Option<Vec<usize>>,
+    /// Lines skipped before the header (or the first row without one), e.g. a vendor preamble
+    pub skip_rows: usize,
+    /// Rows read at most; the rest of the file isn't read (default: all)
+    pub max_rows: Option<usize>,
+    /// Text read as null, e.g. "NA" or "-" (default: none)
+    pub null_values: Option<NullValueTokens>,
+    /// Read empty fields as null (default) rather than empty text
+    pub empty_as_null: bool,
+    /// Columns declared NOT NULL, checked once parsed (default: none)
+    pub not_null: NotNullRules,
+    /// Parse text columns whose first `infer_schema_length` values are all
+    /// ISO 8601 dates or datetimes as Date / Datetime (off by default)
+    pub try_parse_dates: bool,
+    /// (column, format) pairs for dates in other formats, by source or
+    /// renamed name; the columns are read as text and then parsed
+    pub date_formats: Vec<(String, DateFormat)>,
+    /// Lines with more fields than the header: fail naming the line
+    /// (default), or skip them and count them in a `BadLineReport`
+    pub on_bad_lines: BadLines,
+    /// Encoding of the input for `parse`, `parse_checked` and `parse_bytes`;
+    /// anything but strict UTF-8 is converted to a temporary UTF-8 copy
+    /// under the configured `temp_dir`, a buffer at a time (default:
+    /// UTF-8). A leading byte order mark is dropped whatever the encoding
+    pub encoding: CsvEncoding,
+    /// Invalid byte sequences: fail naming the first one's byte offset
+    /// (default), or replace each with U+FFFD
+    pub encoding_errors: EncodingErrors,
+    /// Rows kept by `parse` and `parse_checked`, applied to each batch as it
+    /// is read so only matching rows are held (default: all rows)
+    pub row_filter: Option<Predicate>,
+    /// Rows kept by `parse` and `parse_checked` from those `row_filter`
+    /// keeps, drawn batch by batch so the whole file is never held
+    /// (default: all rows)
+    pub sample: Option<RowSample>,
+    /// Seed for `sample`, so the same rows are drawn again (default: random,
+    /// and reported)
+    pub sample_seed: Option<u64>,
 }
 
 impl Default for CsvParserConfig {
@@ -26,13 +91,411 @@ impl Default for CsvParserConfig {
             delimiter: b',',
             quote_char: b'"',
             infer_schema_length: Some(1000),
+            repair: CsvRepairOptions::default(),
+            rename: None,
+            columns: None,
+            column_indices: None,
+            skip_rows: 0,
+            max_rows: None,
+            null_values: None,
+            empty_as_null: true,
+            not_null: NotNullRules::default(),
+            try_parse_dates: false,
+            date_formats: Vec::new(),
+            on_bad_lines: BadLines::Error,
+            encoding: CsvEncoding::Utf8,
+            encoding_errors: EncodingErrors::Strict,
+            row_filter: None,
+            sample: None,
+            sample_seed: None,
         }
     }
 }
 
+/// Handling of lines with more fields than the header, e.g. from an
+/// unquoted delimiter inside a value
+///
+/// Lines with fewer fields are not bad: their missing fields are null.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BadLines {
+    /// Fail with the line number and its field count
+    #[default]
+    Error,
+    /// Leave the lines out
+    Skip,
+    /// Leave the lines out and log one aggregated warning
+    Warn,
+}
+
+impl BadLines {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "error" => Ok(BadLines::Error),
+            "skip" => Ok(BadLines::Skip),
+            "warn" => Ok(BadLines::Warn),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown on_bad_lines '{}'; expected 'error', 'skip' or 'warn'",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BadLines::Error => "error",
+            BadLines::Skip => "skip",
+            BadLines::Warn => "warn",
+        }
+    }
+}
+
+/// Line numbers of bad lines kept in a `BadLineReport`
+pub const BAD_LINE_SAMPLES: usize = 10;
+
+/// Category of the warning for skipped bad lines
+pub const BAD_LINE_WARNING: &str = "bad_lines";
+
+/// Warning for lines skipped with `BadLines::Warn`
+const SKIPPED_LINES: &str = "{count} lines with more fields than the header were skipped";
+
+/// Lines left out with `BadLines::Skip` or `BadLines::Warn`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BadLineReport {
+    pub mode: BadLines,
+    /// Fields in the header, or in the first row without one
+    pub expected_fields: usize,
+    pub count: usize,
+    /// 1-based line numbers of the first `BAD_LINE_SAMPLES` bad lines
+    pub sample_lines: Vec<usize>,
+}
+
+impl BadLineReport {
+    /// The skipped lines as one aggregated warning with `BadLines::Warn`,
+    /// with sample line numbers as examples
+    pub fn warnings(&self) -> Vec<AggregatedWarning> {
+        if self.mode != BadLines::Warn || self.count == 0 {
+            return Vec::new();
+        }
+        let mut warnings = WarningCollector::new();
+        let examples = self.sample_lines.iter().map(|line| format!("line {}", line));
+        warnings.emit_many(BAD_LINE_WARNING, "", SKIPPED_LINES, self.count, examples);
+        warnings.finish()
+    }
+}
+
+/// Outcome of scanning CSV text for bad lines
+pub(crate) struct BadLineScan {
+    pub(crate) report: BadLineReport,
+    /// (line, fields) of the first bad line
+    pub(crate) first: Option<(usize, usize)>,
+    /// The text without bad lines, when copied
+    pub(crate) kept: Vec<u8>,
+}
+
+impl CsvParserConfig {
+    /// Find the records with more fields than the header
+    ///
+    /// Reads line by line; a record continues while it has an odd number of
+    /// quote characters, so quoted values may span lines. The `skip_rows`
+    /// preamble is not checked, and the scan ends after `max_rows` good rows.
+    ///
+    /// # Arguments
+    /// * `reader` - CSV text
+    /// * `copy` - Also keep the text without the bad lines
+    /// * `limit` - Stop after this many bad lines
+    pub(crate) fn scan_bad_lines<R: BufRead>(&self, mut reader: R, copy: bool, limit: Option<usize>) -> Result<BadLineScan, InsightoraError> {
+        let mut scan = BadLineScan {
+            report: BadLineReport { mode: self.on_bad_lines, ..Default::default() },
+            first: None,
+            kept: Vec::new(),
+        };
+        let (mut line, mut record_line, mut rows) = (0, 1, 0);
+        let mut expected = None;
+        let (mut buffer, mut record) = (Vec::new(), Vec::new());
+        loop {
+            buffer.clear();
+            let read = reader.read_until(b'\n', &mut buffer)?;
+            if read > 0 {
+                line += 1;
+                if line <= self.skip_rows {
+                    if copy {
+                        scan.kept.extend_from_slice(&buffer);
+                    }
+                    continue;
+                }
+                if record.is_empty() {
+                    record_line = line;
+                }
+                record.extend_from_slice(&buffer);
+                let quotes = record.iter().filter(|&&b| b == self.quote_char).count();
+                if quotes % 2 == 1 {
+                    continue;
+                }
+            }
+            if record.is_empty() {
+                break;
+            }
+
+            let text = String::from_utf8_lossy(&record);
+            let fields = split_fields(text.trim_end_matches(['\r', '\n']), self.delimiter, self.quote_char).len();
+            let bad = matches!(expected, Some(expected) if fields > expected);
+            if bad {
+                scan.report.count += 1;
+                if scan.report.sample_lines.len() < BAD_LINE_SAMPLES {
+                    scan.report.sample_lines.push(record_line);
+                }
+                scan.first.get_or_insert((record_line, fields));
+            } else {
+                if expected.is_none() {
+                    expected = Some(fields);
+                    rows += !self.has_header as usize;
+                } else if !text.trim().is_empty() {
+                    rows += 1;
+                }
+                if copy {
+                    scan.kept.extend_from_slice(&record);
+                }
+            }
+            record.clear();
+            if limit.is_some_and(|limit| scan.report.count >= limit) || self.max_rows.is_some_and(|max| rows >= max) {
+                break;
+            }
+        }
+        scan.report.expected_fields = expected.unwrap_or(0);
+        Ok(scan)
+    }
+
+    /// Whether input is converted to UTF-8 before parsing; strict UTF-8 is read as it is
+    pub(crate) fn transcodes(&self) -> bool {
+        self.encoding != CsvEncoding::Utf8 || self.encoding_errors == EncodingErrors::Replace
+    }
+
+    /// Error naming the first invalid UTF-8 sequence or bad line of a
+    /// source, in place of the reader's `error`; the error itself when there
+    /// is neither or bad lines are skipped
+    ///
+    /// Polars doesn't say where invalid UTF-8 is, so the source is scanned
+    /// again for its byte offset.
+    fn read_error(&self, source: CsvSource, error: PolarsError) -> InsightoraError {
+        if let Some(invalid) = source.lines().ok().and_then(utf8_error) {
+            return invalid;
+        }
+        // Skipped lines can't be the cause
+        if self.on_bad_lines != BadLines::Error {
+            return error.into();
+        }
+        let first = source.lines().and_then(|reader| self.scan_bad_lines(reader, false, Some(1)));
+        match first {
+            Ok(BadLineScan { first: Some((line, fields)), report, .. }) => InsightoraError::ParseError(format!(
+                "Line {} has {} fields; expected {} as in the {}. Set on_bad_lines to 'skip' or 'warn' to leave such lines out",
+                line,
+                fields,
+                report.expected_fields,
+                if self.has_header { "header" } else { "first row" }
+            )),
+            _ => error.into(),
+        }
+    }
+}
+
+/// Text read as null while parsing, in every column or per column
+///
+/// Fields are compared whole, without trimming. Empty fields are governed
+/// by `CsvParserConfig::empty_as_null` instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NullValueTokens {
+    /// Tokens read as null in every column
+    All(Vec<String>),
+    /// (column, tokens) pairs, by source or renamed name; the columns are
+    /// read as text and then typed as integer, float or text
+    Columns(Vec<(String, Vec<String>)>),
+}
+
+/// Bytes handed to the Polars CSV reader
+///
+/// Plain files are passed as files so Polars can memory-map them;
+/// gzip and zstd files are decompressed into memory first.
+pub(crate) enum CsvInput {
+    File(File),
+    Memory(Cursor<Vec<u8>>),
+}
+
+impl CsvInput {
+    /// Open a CSV file for reading its first `lines` lines, or all of them
+    ///
+    /// Compressed files are only decompressed that far; plain files are
+    /// opened whole, as Polars stops reading at `with_n_rows` by itself.
+    pub(crate) fn head(path: &Path, lines: Option<usize>) -> Result<Self, InsightoraError> {
+        let compression = Compression::detect(path)?;
+        Ok(match (compression, lines) {
+            (Compression::None, _) => CsvInput::File(File::open(path)?),
+            (_, Some(lines)) => CsvInput::Memory(Cursor::new(read_decompressed_lines(path, compression, lines)?)),
+            (_, None) => CsvInput::Memory(Cursor::new(read_decompressed(path, compression)?)),
+        })
+    }
+
+    /// Polars reader over the input
+    pub(crate) fn reader(self) -> CsvReader<'static, Self> {
+        CsvReader::new(self)
+    }
+
+    /// Record that `operation` reads from memory rather than a mapped file
+    pub(crate) fn check_mmap(&self, operation: &str) -> Result<(), InsightoraError> {
+        match self {
+            CsvInput::File(_) => use_fast_path(operation, FastPath::Mmap, "in_memory").map(|_| ()),
+            CsvInput::Memory(_) => {
+                degrade(operation, FastPath::Mmap, "in_memory", "compressed input is decompressed into memory")
+            }
+        }
+    }
+}
+
+impl Read for CsvInput {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            CsvInput::File(file) => file.read(buf),
+            CsvInput::Memory(bytes) => bytes.read(buf),
+        }
+    }
+}
+
+impl Seek for CsvInput {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            CsvInput::File(file) => file.seek(pos),
+            CsvInput::Memory(bytes) => bytes.seek(pos),
+        }
+    }
+}
+
+impl MmapBytesReader for CsvInput {
+    fn to_file(&self) -> Option<&File> {
+        match self {
+            CsvInput::File(file) => Some(file),
+            CsvInput::Memory(_) => None,
+        }
+    }
+
+    fn to_bytes(&self) -> Option<&[u8]> {
+        match self {
+            CsvInput::File(_) => None,
+            CsvInput::Memory(bytes) => Some(bytes.get_ref()),
+        }
+    }
+}
+
+/// CSV text a parse reads: a file, or a buffer already in memory
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum CsvSource<'a> {
+    File(&'a Path),
+    Memory(&'a [u8]),
+}
+
+impl CsvSource<'_> {
+    /// Input for the first `lines` lines, or all of it
+    ///
+    /// For a buffer only those lines are copied, so reading the header of a
+    /// large payload stays cheap.
+    pub(crate) fn head(&self, lines: Option<usize>) -> Result<CsvInput, InsightoraError> {
+        match *self {
+            CsvSource::File(path) => CsvInput::head(path, lines),
+            CsvSource::Memory(bytes) => {
+                let end = match lines {
+                    Some(lines) => bytes.split_inclusive(|&b| b == b'\n').take(lines).map(<[u8]>::len).sum(),
+                    None => bytes.len(),
+                };
+                Ok(CsvInput::Memory(Cursor::new(bytes[..end].to_vec())))
+            }
+        }
+    }
+
+    /// Buffered reader over the whole text, decompressing a file as it goes
+    pub(crate) fn lines(&self) -> Result<Box<dyn BufRead + '_>, InsightoraError> {
+        match *self {
+            CsvSource::File(path) => Ok(Box::new(BufReader::new(open_decompressed(path, Compression::detect(path)?)?))),
+            CsvSource::Memory(bytes) => Ok(Box::new(Cursor::new(bytes))),
+        }
+    }
+}
+
+/// Estimated memory in MB to parse a file, or its first `lines` lines:
+/// `factor` times those contents, plus the decompressed contents themselves
+/// for gzip and zstd files
+pub(crate) fn parse_memory_mb(path: &Path, lines: Option<usize>, factor: u64) -> Result<usize, InsightoraError> {
+    let compression = Compression::detect(path)?;
+    let factor = factor + compression.is_compressed() as u64;
+    let size = match lines {
+        Some(lines) => read_decompressed_lines(path, compression, lines)?.len() as u64,
+        None => decompressed_size(path, compression)?,
+    };
+    Ok(((size * factor) / (1024 * 1024)) as usize)
+}
+
+/// Bytes read at a time by `count_lines`
+pub const COUNT_BUFFER_BYTES: usize = 1 << 20;
+
+/// Uncompressed files from this size are line-counted in ranges across the Rayon pool
+pub const PARALLEL_COUNT_BYTES: u64 = 64 << 20;
+
+/// Newlines in a run of bytes, kept so runs counted apart can be combined
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct NewlineCount {
+    /// Newlines outside quotes, for a run starting outside quotes
+    unquoted: usize,
+    total: usize,
+    /// Whether the run holds an odd number of quote characters
+    odd_quotes: bool,
+}
+
+impl NewlineCount {
+    fn of(bytes: &[u8], quote_char: Option<u8>) -> Self {
+        let mut count = Self::default();
+        for &byte in bytes {
+            if Some(byte) == quote_char {
+                count.odd_quotes = !count.odd_quotes;
+            } else if byte == b'\n' {
+                count.total += 1;
+                count.unquoted += !count.odd_quotes as usize;
+            }
+        }
+        count
+    }
+
+    /// The count of `self` followed by `next`
+    ///
+    /// After an odd number of quotes `next` starts inside quotes, so its
+    /// newlines outside quotes are the ones it counted as inside.
+    fn then(self, next: Self) -> Self {
+        Self {
+            unquoted: self.unquoted + if self.odd_quotes { next.total - next.unquoted } else { next.unquoted },
+            total: self.total + next.total,
+            odd_quotes: self.odd_quotes != next.odd_quotes,
+        }
+    }
+}
+
+/// Newlines read from `reader` in `COUNT_BUFFER_BYTES` buffers, and the last byte read
+fn count_newlines(mut reader: impl Read, quote_char: Option<u8>) -> Result<(NewlineCount, Option<u8>), InsightoraError> {
+    let mut buffer = vec![0; COUNT_BUFFER_BYTES];
+    let (mut count, mut last) = (NewlineCount::default(), None);
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => return Ok((count, last)),
+            Ok(read) => read,
+            Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error.into()),
+        };
+        count = count.then(NewlineCount::of(&buffer[..read], quote_char));
+        last = Some(buffer[read - 1]);
+    }
+}
+
 /// Parallel CSV parser that leverages Rayon for multi-threaded processing
 pub struct ParallelCsvParser {
     config: CsvParserConfig,
+    progress: ProgressReporter,
+    /// Checked between batches and parse phases; once set the parse stops with `Cancelled`
+    cancel: Option<Arc<AtomicBool>>,
 }
 
 impl ParallelCsvParser {
@@ -44,18 +507,49 @@ impl ParallelCsvParser {
                 chunk_size: global_config.chunk_size,
                 ..Default::default()
             },
+            progress: ProgressReporter::disabled(),
+            cancel: None,
         }
     }
 
     /// Create a new parallel CSV parser with custom configuration
     pub fn with_config(config: CsvParserConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            progress: ProgressReporter::disabled(),
+            cancel: None,
+        }
+    }
+
+    /// Report parse progress (stage start and completion) to `reporter`
+    pub fn with_progress(mut self, reporter: ProgressReporter) -> Self {
+        self.progress = reporter;
+        self
+    }
+
+    /// Stop with `InsightoraError::Cancelled` at the next batch or phase boundary once `flag` is set
+    ///
+    /// With a flag the file is read in rounds of `chunk_size`-row batches,
+    /// one per thread, and the flag is checked after every round as well as
+    /// before the read and the NOT NULL check.
+    pub fn with_cancel_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(flag);
+        self
+    }
+
+    fn check_cancelled(&self) -> Result<(), InsightoraError> {
+        match &self.cancel {
+            Some(flag) if flag.load(Ordering::Relaxed) => Err(InsightoraError::Cancelled),
+            _ => Ok(()),
+        }
     }
 
     /// Parse a CSV file in parallel and return a Polars DataFrame
     /// 
     /// This method uses Polars' built-in parallel CSV reader which is highly optimized
-    /// and leverages Rayon for parallel processing under the hood.
+    /// and leverages Rayon for parallel processing under the hood. gzip and
+    /// zstd files (by signature, or a `.gz` / `.zst` extension) are
+    /// decompressed as they are read.
     /// 
     /// # Arguments
     /// * `file_path` - Path to the CSV file
@@ -63,8 +557,45 @@ impl ParallelCsvParser {
     /// # Returns
     /// * `Result<DataFrame>` - Parsed DataFrame or error
     pub fn parse(&self, file_path: &str) -> Result<DataFrame, InsightoraError> {
-        // Validate file path
-        let path = Path::new(file_path);
+        self.parse_checked(file_path).map(|(df, ..)| df)
+    }
+
+    /// Parse a CSV file, then check the columns declared in `config.not_null`
+    ///
+    /// Runs `parse_repaired`. With the strict null policy the first null in a
+    /// NOT NULL column is a `NotNullViolation` naming the column and data
+    /// row; otherwise violations are counted in the returned report. Values
+    /// set to null because they didn't parse as dates (see `try_parse_dates`
+    /// and `date_formats`) are returned as aggregated warnings, one per
+    /// column, followed by the skipped lines with `BadLines::Warn`. NOT NULL
+    /// columns are checked in the sampled rows only.
+    ///
+    /// # Returns
+    /// * `Result<(DataFrame, RepairReport, NullabilityReport, Vec<AggregatedWarning>, BadLineReport, Option<RowSampleReport>)>` - Parsed
+    ///   data, what was repaired, NOT NULL violations, warnings, the lines
+    ///   left out by `on_bad_lines` and, with `sample`, the rows read and kept
+    #[allow(clippy::type_complexity)]
+    pub fn parse_checked(
+        &self,
+        file_path: &str,
+    ) -> Result<(DataFrame, RepairReport, NullabilityReport, Vec<AggregatedWarning>, BadLineReport, Option<RowSampleReport>), InsightoraError> {
+        self.check_cancelled()?;
+        let (df, repairs, warnings, bad_lines, sample) = if self.config.repair.is_enabled() {
+            let (df, repairs) = self.read_repaired(file_path)?;
+            (df, repairs, Vec::new(), BadLineReport::default(), None)
+        } else {
+            let (df, warnings, bad_lines, sample) = self.read(file_path)?;
+            (df, RepairReport::default(), warnings, bad_lines, sample)
+        };
+        self.check_cancelled()?;
+        let nulls = self.config.not_null.check(&df)?;
+        Ok((df, repairs, nulls, warnings, bad_lines, sample))
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn read(&self, file_path: &str) -> Result<(DataFrame, Vec<AggregatedWarning>, BadLineReport, Option<RowSampleReport>), InsightoraError> {
+        // Validate file path against the access policy
+        let path = check_path_allowed(file_path)?;
         if !path.exists() {
             return Err(InsightoraError::IoError(
                 std::io::Error::new(
@@ -74,30 +605,332 @@ impl ParallelCsvParser {
             ));
         }
 
-        // Estimate memory usage (rough estimate: file size * 2 for parsing overhead)
-        let file_size = std::fs::metadata(file_path)
-            .map_err(InsightoraError::IoError)?
-            .len();
-        let estimated_memory_mb = (file_size * 2) / (1024 * 1024);
-        check_memory_limit(estimated_memory_mb as usize)?;
+        // Other encodings are read from a UTF-8 copy on disk
+        let transcoded = self.transcoded(&path)?;
+        let path = transcoded.as_ref().map_or(path.as_path(), TranscodedFile::path);
 
-        // Use Polars' parallel CSV reader
-        let df = CsvReader::from_path(file_path)?
-            .has_header(self.config.has_header)
-            .with_separator(self.config.delimiter)
-            .with_quote_char(Some(self.config.quote_char))
-            .infer_schema(self.config.infer_schema_length)
-            .with_chunk_size(self.config.chunk_size)
-            .finish()?;
+        // Estimate memory usage (rough estimate: decompressed size * 2 for parsing overhead);
+        // a filtered or sampled read holds only the rows kept, watched batch by batch instead
+        let lines = self.head_lines()?;
+        let mut sampler = self.config.sample.map(|sample| RowSampler::new(sample, self.config.sample_seed)).transpose()?;
+        let batched = self.config.row_filter.is_some() || sampler.is_some();
+        if !batched {
+            check_memory_limit(parse_memory_mb(path, lines, 2)?)?;
+        }
+        use_fast_path("parse_csv", FastPath::Parallel, "single_thread")?;
+        let source = CsvSource::File(path);
+        let projection = self.projection(source)?;
+        let nulls = self.column_nulls(source)?;
+        let dates = self.date_columns(source)?;
+        let (kept, bad_lines) = self.skip_bad_lines(source)?;
+        let input = match kept {
+            Some(kept) => {
+                degrade("parse_csv", FastPath::Mmap, "in_memory", "lines with too many fields are left out of an in-memory copy")?;
+                CsvInput::Memory(Cursor::new(kept))
+            }
+            None => {
+                let input = CsvInput::head(path, lines)?;
+                input.check_mmap("parse_csv")?;
+                input
+            }
+        };
+        self.progress.report(0.0, file_path);
+        self.check_cancelled()?;
+
+        // Use Polars' parallel CSV reader; unprojected columns are skipped, not parsed
+        let df = if batched {
+            self.read_batched(input, source, sampler.as_mut(), projection.as_ref(), &nulls, &dates)?
+        } else {
+            let df = match self.cancel {
+                Some(_) => self.read_cancellable(input, source, projection.as_ref(), &nulls, &dates)?,
+                None => self.configure_reader(input.reader(), projection.as_ref(), &nulls, &dates)
+                    .finish()
+                    .map_err(|e| self.config.read_error(source, e))?,
+            };
+            self.check_cancelled()?;
+            replace_null_tokens(self.project(df, projection.as_ref())?, &nulls)?
+        };
+        let (df, mut warnings) = self.parse_dates(df, &dates)?;
+        warnings.extend(bad_lines.warnings());
+
+        self.progress.finish(file_path);
+        Ok((df, warnings, bad_lines, sampler.map(|sampler| sampler.report())))
+    }
+
+    /// Read all of `input` as `finish` would, a round of `chunk_size`-row
+    /// batches per thread at a time, stopping between rounds once cancelled
+    fn read_cancellable(
+        &self,
+        input: CsvInput,
+        source: CsvSource,
+        projection: Option<&Projection>,
+        nulls: &[ColumnNulls],
+        dates: &[DateColumn],
+    ) -> Result<DataFrame, InsightoraError> {
+        let reader = CsvReader::new(Box::new(input) as Box<dyn MmapBytesReader>);
+        let mut batches = self.configure_reader(reader, projection, nulls, dates)
+            .batched_mmap(None)
+            .map_err(|e| self.config.read_error(source, e))?;
+        let threads = get_current_config().thread_count.max(1);
+
+        let mut read: Option<DataFrame> = None;
+        while let Some(frames) = batches.next_batches(threads).map_err(|e| self.config.read_error(source, e))? {
+            self.check_cancelled()?;
+            for frame in frames {
+                match read.as_mut() {
+                    Some(df) => {
+                        df.vstack_mut(&frame)?;
+                    }
+                    None => read = Some(frame),
+                }
+            }
+        }
+        if let Some(mut df) = read {
+            df.align_chunks();
+            return Ok(df);
+        }
+        // No data rows: the header's columns in file order, as text like Polars reads them
+        let (names, _) = self.header(source)?;
+        let columns = names
+            .iter()
+            .filter(|name| projection.is_none_or(|projection| projection.source.contains(name)))
+            .map(|name| Series::new_empty(name, &DataType::String))
+            .collect();
+        Ok(DataFrame::new(columns)?)
+    }
 
+    /// Read `input` a batch of `chunk_size` rows at a time, keeping the rows
+    /// `row_filter` selects and `sampler` draws
+    ///
+    /// The filter sees every column it reads under its `rename` name, with
+    /// null tokens applied and before dates are parsed; it is checked against
+    /// the first batch, and columns it reads that the projection leaves out
+    /// are dropped once it has run. The sample is drawn from the rows the
+    /// filter keeps. `max_rows` counts the rows read, not the rows kept.
+    fn read_batched(
+        &self,
+        input: CsvInput,
+        source: CsvSource,
+        mut sampler: Option<&mut RowSampler>,
+        projection: Option<&Projection>,
+        nulls: &[ColumnNulls],
+        dates: &[DateColumn],
+    ) -> Result<DataFrame, InsightoraError> {
+        let _watch = memory::watch("parse_csv");
+        let filter = self.config.row_filter.as_ref();
+        let (names, output) = self.header(source)?;
+        // The projected columns and the ones the filter reads
+        let read = projection.map(|projection| {
+            let used = filter.map_or_else(Vec::new, Predicate::columns);
+            let mut indices = projection.reader_indices();
+            indices.extend((0..output.len()).filter(|&i| used.contains(&output[i].as_str())));
+            indices.sort_unstable();
+            indices.dedup();
+            indices
+        });
+        let reader = CsvReader::new(Box::new(input) as Box<dyn MmapBytesReader>);
+        let mut batches = self.configure_reader(reader, None, nulls, dates)
+            .with_projection(read)
+            .batched_mmap(None)
+            .map_err(|e| self.config.read_error(source, e))?;
+
+        let mut kept: Option<DataFrame> = None;
+        let mut first = true;
+        while let Some(frames) = batches.next_batches(1).map_err(|e| self.config.read_error(source, e))? {
+            for mut frame in frames {
+                self.check_cancelled()?;
+                let renamed: Vec<String> = frame
+                    .get_column_names()
+                    .iter()
+                    .map(|name| names.iter().position(|n| n == name).map_or_else(|| name.to_string(), |i| output[i].clone()))
+                    .collect();
+                frame.set_column_names(&renamed)?;
+                let frame = replace_null_tokens(frame, nulls)?;
+                let frame = match filter {
+                    Some(filter) => {
+                        let schema = frame.schema();
+                        if first {
+                            filter.check(&schema)?;
+                        }
+                        frame.lazy().filter(filter.mask(&schema)).collect()?
+                    }
+                    None => frame,
+                };
+                first = false;
+                let frame = match projection {
+                    Some(projection) => frame.select(&projection.output)?,
+                    None => frame,
+                };
+                let frame = match sampler.as_deref_mut() {
+                    Some(sampler) => sampler.draw(frame)?,
+                    None => Some(frame),
+                };
+                if let Some(frame) = frame {
+                    match kept.as_mut() {
+                        Some(df) => {
+                            df.vstack_mut(&frame)?;
+                        }
+                        None => kept = Some(frame),
+                    }
+                }
+                memory::checkpoint()?;
+            }
+        }
+        if let Some(reservoir) = sampler.map(RowSampler::finish).transpose()?.flatten() {
+            kept = Some(reservoir);
+        }
+        Ok(kept.unwrap_or_else(DataFrame::empty))
+    }
+
+    /// Parse CSV text already in memory, e.g. a payload received over HTTP
+    ///
+    /// Honours the same options as `parse` except column-count repair, and
+    /// checks `not_null` the same way; values that don't parse as dates are
+    /// set to null, and bad lines left out, without a warning. The memory
+    /// limit is checked against the buffer length, and the buffer is read in
+    /// place rather than copied unless it has bad lines to leave out.
+    ///
+    /// # Arguments
+    /// * `data` - CSV text, uncompressed
+    ///
+    /// # Returns
+    /// * `Result<DataFrame>` - Parsed DataFrame; ParseError for empty input
+    pub fn parse_bytes(&self, data: &[u8]) -> Result<DataFrame, InsightoraError> {
+        if data.iter().all(u8::is_ascii_whitespace) {
+            return Err(InsightoraError::ParseError("CSV input is empty".to_string()));
+        }
+        if self.config.repair.is_enabled() {
+            return Err(InsightoraError::ValidationError(
+                "Column-count repair is not supported for in-memory input".to_string()
+            ));
+        }
+        let transcoded = self
+            .config
+            .transcodes()
+            .then(|| transcode_bytes(data, self.config.encoding, self.config.encoding_errors))
+            .transpose()?;
+        let data = transcoded.as_deref().unwrap_or(data);
+        if self.config.row_filter.is_some() || self.config.sample.is_some() {
+            return Err(InsightoraError::ValidationError(
+                "row_filter and sampling are not supported for in-memory input; filter the parsed frame".to_string()
+            ));
+        }
+        // Rejects max_rows=0; a buffer needs no line count
+        self.head_lines()?;
+        check_memory_limit(((data.len() as u64 * 2) / (1024 * 1024)) as usize)?;
+        use_fast_path("parse_csv", FastPath::Parallel, "single_thread")?;
+        let source = CsvSource::Memory(data);
+        let projection = self.projection(source)?;
+        let nulls = self.column_nulls(source)?;
+        let dates = self.date_columns(source)?;
+        let (kept, _) = self.skip_bad_lines(source)?;
+        self.progress.report(0.0, "<memory>");
+
+        let reader = CsvReader::new(Cursor::new(kept.as_deref().unwrap_or(data)));
+        let df = self.configure_reader(reader, projection.as_ref(), &nulls, &dates)
+            .finish()
+            .map_err(|e| self.config.read_error(source, e))?;
+        let df = replace_null_tokens(self.project(df, projection.as_ref())?, &nulls)?;
+        let (df, _) = self.parse_dates(df, &dates)?;
+        self.config.not_null.check(&df)?;
+
+        self.progress.finish("<memory>");
         Ok(df)
     }
 
+    /// Read `reader` to the end, then parse it as `parse_bytes` does
+    ///
+    /// # Returns
+    /// * `Result<DataFrame>` - Parsed DataFrame; ParseError for empty input
+    pub fn parse_reader<R: Read>(&self, mut reader: R) -> Result<DataFrame, InsightoraError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        self.parse_bytes(&data)
+    }
+
+    /// Parse a CSV file, first repairing rows split by unescaped delimiters
+    ///
+    /// With `config.repair` off this is `parse` with an empty report. With
+    /// column-count repair, over-split rows are re-joined (see
+    /// `ColumnCountRepairer`) before the whole file is parsed, and
+    /// `flag_repairs` appends a boolean `_repaired` column.
+    ///
+    /// # Arguments
+    /// * `file_path` - Path to the CSV file (must have a header row)
+    ///
+    /// # Returns
+    /// * `Result<(DataFrame, RepairReport)>` - Parsed data and what was repaired
+    pub fn parse_repaired(&self, file_path: &str) -> Result<(DataFrame, RepairReport), InsightoraError> {
+        self.parse_checked(file_path).map(|(df, repairs, ..)| (df, repairs))
+    }
+
+    fn read_repaired(&self, file_path: &str) -> Result<(DataFrame, RepairReport), InsightoraError> {
+        if !self.config.has_header {
+            return Err(InsightoraError::ValidationError(
+                "Column-count repair needs a header row for the expected column count".to_string()
+            ));
+        }
+        if self.config.skip_rows > 0 || self.config.max_rows.is_some() {
+            return Err(InsightoraError::ValidationError(
+                "skip_rows and max_rows are not supported with column-count repair".to_string()
+            ));
+        }
+        if self.config.null_values.is_some() || !self.config.empty_as_null {
+            return Err(InsightoraError::ValidationError(
+                "null_values and empty_as_null are not supported with column-count repair".to_string()
+            ));
+        }
+        if self.config.try_parse_dates || !self.config.date_formats.is_empty() {
+            return Err(InsightoraError::ValidationError(
+                "try_parse_dates and date_formats are not supported with column-count repair".to_string()
+            ));
+        }
+        if self.config.on_bad_lines != BadLines::Error {
+            return Err(InsightoraError::ValidationError(
+                "on_bad_lines 'skip' and 'warn' are not supported with column-count repair, which keeps those lines".to_string()
+            ));
+        }
+        if self.config.row_filter.is_some() || self.config.sample.is_some() {
+            return Err(InsightoraError::ValidationError(
+                "row_filter and sampling are not supported with column-count repair".to_string()
+            ));
+        }
+        let path = check_path_allowed(file_path)?;
+        if !path.exists() {
+            return Err(InsightoraError::IoError(
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("File not found: {}", file_path)
+                )
+            ));
+        }
+
+        let transcoded = self.transcoded(&path)?;
+        let path = transcoded.as_ref().map_or(path.as_path(), TranscodedFile::path);
+
+        // The repaired text is held alongside the parsed frame
+        check_memory_limit(parse_memory_mb(path, None, 3)?)?;
+        let projection = self.projection(CsvSource::File(path))?;
+        self.progress.report(0.0, file_path);
+
+        let mut parsed = None;
+        let report = repair_file(path, self.config.delimiter, self.config.quote_char, &self.config.repair, usize::MAX, |text, flags| {
+            self.check_cancelled()?;
+            let df = read_repaired_batch(text, self.config.delimiter, self.config.quote_char, self.config.infer_schema_length, None)?;
+            let df = self.project(df, projection.as_ref())?;
+            parsed = Some(with_repair_flags(df, flags, &self.config.repair)?);
+            Ok(())
+        })?;
+
+        self.progress.finish(file_path);
+        Ok((parsed.unwrap_or_else(DataFrame::empty), report))
+    }
+
     /// Parse CSV with automatic data type inference
     /// 
     /// This method performs more aggressive type inference by sampling more rows
     pub fn parse_with_inference(&self, file_path: &str, sample_size: usize) -> Result<DataFrame, InsightoraError> {
-        let path = Path::new(file_path);
+        let path = check_path_allowed(file_path)?;
         if !path.exists() {
             return Err(InsightoraError::IoError(
                 std::io::Error::new(
@@ -108,61 +941,756 @@ impl ParallelCsvParser {
         }
 
         // Check memory limits
-        let file_size = std::fs::metadata(file_path)
-            .map_err(InsightoraError::IoError)?
-            .len();
-        let estimated_memory_mb = (file_size * 2) / (1024 * 1024);
-        check_memory_limit(estimated_memory_mb as usize)?;
+        let lines = self.head_lines()?;
+        check_memory_limit(parse_memory_mb(&path, lines, 2)?)?;
+        use_fast_path("parse_csv", FastPath::Parallel, "single_thread")?;
+        let projection = self.projection(CsvSource::File(&path))?;
+        let nulls = self.column_nulls(CsvSource::File(&path))?;
+        let input = CsvInput::head(&path, lines)?;
+        input.check_mmap("parse_csv")?;
 
-        let df = CsvReader::from_path(file_path)?
+        let df = self.with_nulls(input.reader(), &nulls)
             .has_header(self.config.has_header)
             .with_separator(self.config.delimiter)
             .with_quote_char(Some(self.config.quote_char))
             .infer_schema(Some(sample_size))
             .with_chunk_size(self.config.chunk_size)
+            .with_skip_rows(self.config.skip_rows)
+            .with_n_rows(self.config.max_rows)
+            .with_projection(projection.as_ref().map(Projection::reader_indices))
             .finish()?;
 
+        replace_null_tokens(self.project(df, projection.as_ref())?, &nulls)
+    }
+
+    /// Parse a CSV file with known column types instead of inferring them
+    ///
+    /// `schema` lists every column of the file in order, by its name after
+    /// `rename`, e.g. as `infer_schema` returned it for an earlier file of
+    /// the same export. Nothing is sampled, so a directory of daily files
+    /// parses faster and every day gets the same types; a value that doesn't
+    /// parse as its column's type fails the read instead of changing it.
+    /// Per-column `null_values` aren't supported, as those columns are read
+    /// as text. Duration columns are read as text and parsed with
+    /// `parse_duration` ("02:35:17", "1d 4h", "PT15S").
+    ///
+    /// # Arguments
+    /// * `file_path` - Path to the CSV file
+    /// * `schema` - Names and types of the file's columns
+    ///
+    /// # Returns
+    /// * `Result<DataFrame>` - Parsed DataFrame; a SchemaError listing both
+    ///   the schema's and the file's columns when they differ
+    ///
+    /// # Example
+    /// ```no_run
+    /// use insightora_core::api::ParallelCsvParser;
+    ///
+    /// let parser = ParallelCsvParser::new();
+    /// let schema = parser.infer_schema("exports/2024-06-01.csv").unwrap();
+    /// let df = parser.parse_with_schema("exports/2024-06-02.csv", &schema).unwrap();
+    /// println!("{:?}", df.schema());
+    /// ```
+    pub fn parse_with_schema(&self, file_path: &str, schema: &Schema) -> Result<DataFrame, InsightoraError> {
+        if matches!(self.config.null_values, Some(NullValueTokens::Columns(_))) {
+            return Err(InsightoraError::ValidationError(
+                "Per-column null_values are not supported with a schema; pass tokens for every column".to_string()
+            ));
+        }
+        let path = check_path_allowed(file_path)?;
+        if !path.exists() {
+            return Err(InsightoraError::IoError(
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("File not found: {}", file_path)
+                )
+            ));
+        }
+
+        let transcoded = self.transcoded(&path)?;
+        let path = transcoded.as_ref().map_or(path.as_path(), TranscodedFile::path);
+        let source = CsvSource::File(path);
+        let (names, output) = self.header(source)?;
+        let expected: Vec<&str> = schema.iter_names().map(|name| name.as_str()).collect();
+        if expected != output {
+            let problem = if expected.len() != output.len() {
+                format!("the schema has {} columns but the file has {}", expected.len(), output.len())
+            } else {
+                "the schema's columns don't match the file's".to_string()
+            };
+            return Err(InsightoraError::SchemaError(vec![format!(
+                "{}; schema: [{}]; file: [{}]",
+                problem,
+                expected.join(", "),
+                output.join(", ")
+            )]));
+        }
+        // The reader sees the header names, before rename
+        let reader_schema: Schema = names
+            .iter()
+            .zip(schema.iter_dtypes())
+            .map(|(name, dtype)| match dtype {
+                DataType::Duration(_) => Field::new(name, DataType::String),
+                _ => Field::new(name, dtype.clone()),
+            })
+            .collect();
+
+        let lines = self.head_lines()?;
+        check_memory_limit(parse_memory_mb(path, lines, 2)?)?;
+        use_fast_path("parse_csv", FastPath::Parallel, "single_thread")?;
+        let projection = self.projection(source)?;
+        let input = CsvInput::head(path, lines)?;
+        input.check_mmap("parse_csv")?;
+        self.progress.report(0.0, file_path);
+        self.check_cancelled()?;
+
+        let df = self.with_nulls(input.reader(), &[])
+            .has_header(self.config.has_header)
+            .with_separator(self.config.delimiter)
+            .with_quote_char(Some(self.config.quote_char))
+            .with_schema(Some(Arc::new(reader_schema)))
+            .with_chunk_size(self.config.chunk_size)
+            .with_skip_rows(self.config.skip_rows)
+            .with_n_rows(self.config.max_rows)
+            .with_projection(projection.as_ref().map(Projection::reader_indices))
+            .finish()
+            .map_err(|e| self.config.read_error(source, e))?;
+        let mut df = self.project(df, projection.as_ref())?;
+        for (name, dtype) in schema.iter() {
+            if matches!(dtype, DataType::Duration(_)) && df.column(name).is_ok() {
+                let parsed = cast_to_duration(df.column(name)?, DurationFormat::Auto)?.cast(dtype)?;
+                df.replace(name, parsed)?;
+            }
+        }
+
+        self.progress.finish(file_path);
         Ok(df)
     }
 
-    /// Count lines in CSV file in parallel (useful for progress tracking)
-    pub fn count_lines(&self, file_path: &str) -> Result<usize, InsightoraError> {
-        let file = File::open(file_path)
-            .map_err(InsightoraError::IoError)?;
-        let reader = BufReader::new(file);
-        
-        // Read all lines into chunks
-        let lines: Vec<_> = reader.lines()
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(InsightoraError::IoError)?;
-        
-        // Count in parallel
-        let count = lines.par_iter().count();
-        
-        Ok(count)
+    /// Count the lines in a CSV file (useful for progress tracking)
+    ///
+    /// Newline bytes are counted `COUNT_BUFFER_BYTES` at a time, so memory
+    /// stays flat whatever the file size; a last line without a trailing
+    /// newline counts too. Uncompressed files of `PARALLEL_COUNT_BYTES` or
+    /// more are split into byte ranges counted across the Rayon pool. With
+    /// `respect_quotes`, newlines inside `quote_char` quotes belong to their
+    /// field, so the count is of records (the header included).
+    ///
+    /// # Arguments
+    /// * `file_path` - Path to the CSV file, optionally gzip or zstd compressed
+    /// * `respect_quotes` - Count records rather than physical lines
+    pub fn count_lines(&self, file_path: &str, respect_quotes: bool) -> Result<usize, InsightoraError> {
+        let path = check_path_allowed(file_path)?;
+        let quote_char = respect_quotes.then_some(self.config.quote_char);
+        let compression = Compression::detect(&path)?;
+        let size = std::fs::metadata(&path)?.len();
+
+        let parallel = !compression.is_compressed()
+            && size >= PARALLEL_COUNT_BYTES
+            && use_fast_path("count_lines", FastPath::Parallel, "single_thread")?;
+        let ranges = if parallel {
+            let step = size.div_ceil(rayon::current_num_threads() as u64).max(COUNT_BUFFER_BYTES as u64);
+            let starts: Vec<u64> = (0..size).step_by(step as usize).collect();
+            starts
+                .into_par_iter()
+                .map(|start| {
+                    let mut file = File::open(&path)?;
+                    file.seek(SeekFrom::Start(start))?;
+                    count_newlines(file.take(step), quote_char)
+                })
+                .collect::<Result<Vec<_>, InsightoraError>>()?
+        } else {
+            vec![count_newlines(open_decompressed(&path, compression)?, quote_char)?]
+        };
+
+        let count = ranges.iter().fold(NewlineCount::default(), |count, (range, _)| count.then(*range));
+        let last = ranges.iter().rev().find_map(|(_, last)| *last);
+        Ok(count.unquoted + last.is_some_and(|byte| byte != b'\n') as usize)
+    }
+
+    /// Get schema information from CSV file, with `rename` and the projection applied
+    ///
+    /// Compressed files are only decompressed as far as the rows used for
+    /// inference.
+    pub fn infer_schema(&self, file_path: &str) -> Result<Schema, InsightoraError> {
+        let path = check_path_allowed(file_path)?;
+        if !path.exists() {
+            return Err(InsightoraError::IoError(
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("File not found: {}", file_path)
+                )
+            ));
+        }
+
+        // Use Polars to infer schema
+        let projection = self.projection(CsvSource::File(&path))?;
+        let nulls = self.column_nulls(CsvSource::File(&path))?;
+        let sample = self.with_nulls(self.sample(&path)?, &nulls)
+            .has_header(self.config.has_header)
+            .with_separator(self.config.delimiter)
+            .infer_schema(self.config.infer_schema_length)
+            .with_n_rows(self.config.infer_schema_length)
+            .with_projection(projection.as_ref().map(Projection::reader_indices))
+            .finish()?;
+
+        Ok(replace_null_tokens(self.project(sample, projection.as_ref())?, &nulls)?.schema())
+    }
+
+    /// Get schema information and flag identifier-like columns
+    ///
+    /// Detection runs on the first `infer_schema_length` rows read as text,
+    /// so zip codes and other zero-padded values keep their leading zeros.
+    ///
+    /// # Returns
+    /// * `Result<(Schema, Vec<IdentifierDecision>)>` - Inferred schema and one decision per column
+    pub fn infer_schema_with_identifiers(
+        &self,
+        file_path: &str,
+        id_detection: &IdDetectionConfig,
+    ) -> Result<(Schema, Vec<IdentifierDecision>), InsightoraError> {
+        let schema = self.infer_schema(file_path)?;
+        let path = check_path_allowed(file_path)?;
+
+        // An inference length of zero reads every column as String
+        let projection = self.projection(CsvSource::File(&path))?;
+        let sample = self.with_nulls(self.sample(&path)?, &[])
+            .has_header(self.config.has_header)
+            .with_separator(self.config.delimiter)
+            .with_quote_char(Some(self.config.quote_char))
+            .infer_schema(Some(0))
+            .with_n_rows(self.config.infer_schema_length)
+            .with_projection(projection.as_ref().map(Projection::reader_indices))
+            .finish()?;
+        let sample = self.project(sample, projection.as_ref())?;
+
+        let decisions = detect_identifiers(&sample, id_detection)?;
+        Ok((schema, decisions))
+    }
+
+    /// Get the schema with null counts, types seen and a confidence per column
+    ///
+    /// With `infer_schema_length` set, Polars infers the types from that
+    /// many rows as in `infer_schema`, and the same rows are classified for
+    /// the report. With None every row is classified in one streaming pass,
+    /// decompressing as it goes, and the types come from that pass; Polars
+    /// would hold the whole file to infer from every row. Columns follow
+    /// `rename` and the projection.
+    ///
+    /// # Returns
+    /// * `Result<SchemaReport>` - One `ColumnTypeReport` per column, with
+    ///   example values for columns made text by a few non-numeric values
+    pub fn infer_schema_report(&self, file_path: &str) -> Result<SchemaReport, InsightoraError> {
+        let path = check_path_allowed(file_path)?;
+        if !path.exists() {
+            return Err(InsightoraError::IoError(
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("File not found: {}", file_path)
+                )
+            ));
+        }
+
+        let source = CsvSource::File(&path);
+        let (_, names) = self.header(source)?;
+        let mut report = scan_types(
+            source.lines()?,
+            &names,
+            self.config.delimiter,
+            self.config.quote_char,
+            self.config.skip_rows,
+            self.config.has_header,
+            self.config.infer_schema_length,
+        )?;
+        if let Some(projection) = self.projection(source)? {
+            report.columns = projection.indices.iter().map(|&i| report.columns[i].clone()).collect();
+        }
+        if self.config.infer_schema_length.is_some() {
+            let schema = self.infer_schema(file_path)?;
+            for (column, dtype) in report.columns.iter_mut().zip(schema.iter_dtypes()) {
+                column.dtype = dtype.clone();
+            }
+        }
+        Ok(report)
+    }
+
+    /// Build the sidecar block index used by `parse_filtered`
+    ///
+    /// Reads the file once in record-aligned blocks of about `block_bytes`
+    /// and writes the min, max and null count of each of `columns` (header
+    /// names) per block to `<file>.idx.json`, with the file's size,
+    /// modification time and a hash of its ends. Only uncompressed files
+    /// can be indexed. When the path policy doesn't allow the sidecar, the
+    /// index is kept in memory for this process instead.
+    ///
+    /// # Returns
+    /// * `Result<FileIndex>` - The index as written
+    pub fn build_index(&self, file_path: &str, columns: &[String], block_bytes: usize) -> Result<FileIndex, InsightoraError> {
+        let path = check_path_allowed(file_path)?;
+        if Compression::detect(&path)?.is_compressed() {
+            return Err(InsightoraError::ValidationError(
+                "Only uncompressed CSV files can be indexed; block offsets are positions in the file".to_string()
+            ));
+        }
+        if self.config.skip_rows > 0 {
+            return Err(InsightoraError::ValidationError("skip_rows is not supported for indexed files".to_string()));
+        }
+        let stamp = FileStamp::of(&path)?;
+        let schema = self.raw_schema(&path, &[])?;
+        if let Some(missing) = columns.iter().find(|column| schema.get(column).is_none()) {
+            return Err(InsightoraError::ValidationError(format!("Column '{}' not found", missing)));
+        }
+
+        let mut index = FileIndex::new(stamp, self.config.has_header, self.config.delimiter, self.config.quote_char, columns.to_vec());
+        let file = File::open(&path)?;
+        let mut reader = ChunkReader::new(file, file_path, block_bytes, Some(self.config.quote_char), RetryPolicy::from_config())?;
+        let mut header: Vec<u8> = Vec::new();
+        loop {
+            let mut offset = reader.offset();
+            let Some(mut block) = reader.next_chunk()? else {
+                break;
+            };
+            if self.config.has_header && offset == 0 {
+                let cut = first_record_end(&block, Some(self.config.quote_char)).unwrap_or(block.len());
+                header = block.drain(..cut).collect();
+                offset = cut as u64;
+                index.data_offset = offset;
+            }
+            if block.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            let df = self.read_block(&header, &block, &schema)?;
+            let stats = columns
+                .iter()
+                .map(|column| ColumnStats::of(df.column(column)?))
+                .collect::<Result<Vec<_>, InsightoraError>>()?;
+            index.blocks.push(IndexBlock { offset, len: block.len() as u64, rows: df.height(), stats });
+        }
+        index.save(&path)?;
+        Ok(index)
+    }
+
+    /// Parse the rows of a CSV file where `predicate` holds
+    ///
+    /// The predicate names columns after `rename`, and `columns` or
+    /// `column_indices` pick the output columns once rows are filtered.
+    /// With a sidecar index from `build_index` that still matches the file,
+    /// blocks whose statistics rule the predicate out are not read;
+    /// otherwise the whole file is parsed and filtered. Rows must also
+    /// satisfy `row_filter` when one is set.
+    ///
+    /// # Returns
+    /// * `Result<(DataFrame, IndexScanReport)>` - Matching rows, and the blocks read and skipped
+    pub fn parse_filtered(&self, file_path: &str, predicate: &Predicate) -> Result<(DataFrame, IndexScanReport), InsightoraError> {
+        if self.config.repair.is_enabled() {
+            return Err(InsightoraError::ValidationError(
+                "Column-count repair is not supported for filtered parses".to_string()
+            ));
+        }
+        if self.config.sample.is_some() {
+            return Err(InsightoraError::ValidationError(
+                "Sampling is not supported for filtered parses; use row_filter with parse".to_string()
+            ));
+        }
+        let path = check_path_allowed(file_path)?;
+        if !path.exists() {
+            return Err(InsightoraError::IoError(
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("File not found: {}", file_path)
+                )
+            ));
+        }
+        let projection = self.projection(CsvSource::File(&path))?;
+        // Every column is read, as the predicate may use columns left out of the output
+        let whole = ParallelCsvParser::with_config(CsvParserConfig {
+            columns: None,
+            column_indices: None,
+            ..self.config.clone()
+        })
+        .with_progress(self.progress.clone());
+
+        let index = match (self.config.skip_rows, self.config.max_rows) {
+            (0, None) => FileIndex::load(&path, self.config.has_header, self.config.delimiter, self.config.quote_char)?,
+            _ => Err("skip_rows and max_rows are read without the index".to_string()),
+        };
+        let (df, report) = match index {
+            Ok(index) => whole.read_indexed(&path, &index, predicate)?,
+            Err(reason) => (whole.read(file_path)?.0, IndexScanReport { reason: Some(reason), ..Default::default() }),
+        };
+        let schema = df.schema();
+        let df = df.lazy().filter(predicate.mask(&schema)).collect()?;
+        // An unindexed read has applied row_filter already; blocks read by the index haven't
+        let df = match &self.config.row_filter {
+            Some(filter) if report.index_used => {
+                let schema = df.schema();
+                filter.check(&schema)?;
+                df.lazy().filter(filter.mask(&schema)).collect()?
+            }
+            _ => df,
+        };
+        let df = match projection {
+            Some(projection) => df.select(&projection.output)?,
+            None => df,
+        };
+        Ok((df, report))
+    }
+
+    /// Read the blocks of an indexed file whose rows may match `predicate`
+    fn read_indexed(&self, path: &Path, index: &FileIndex, predicate: &Predicate) -> Result<(DataFrame, IndexScanReport), InsightoraError> {
+        // The predicate uses names after rename, the index header names
+        let (source, output) = self.header(CsvSource::File(path))?;
+        let column = |name: &str| {
+            let position = output.iter().position(|n| n == name)?;
+            index.columns.iter().position(|c| *c == source[position])
+        };
+        let blocks: Vec<&IndexBlock> = index.blocks.iter().filter(|block| index.may_match(block, predicate, &column)).collect();
+        let bytes: u64 = blocks.iter().map(|block| block.len).sum();
+        check_memory_limit((bytes * 2 / (1024 * 1024)) as usize)?;
+
+        let nulls = self.column_nulls(CsvSource::File(path))?;
+        let schema = self.raw_schema(path, &nulls)?;
+        let mut file = File::open(path)?;
+        let mut header = vec![0; index.data_offset as usize];
+        file.read_exact(&mut header)?;
+        let mut combined: Option<DataFrame> = None;
+        for block in &blocks {
+            let mut bytes = vec![0; block.len as usize];
+            file.seek(SeekFrom::Start(block.offset))?;
+            file.read_exact(&mut bytes)?;
+            let df = self.read_block(&header, &bytes, &schema)?;
+            match combined.as_mut() {
+                Some(all) => {
+                    all.vstack_mut(&df)?;
+                }
+                None => combined = Some(df),
+            }
+        }
+        // No block can match: no rows, with the file's columns
+        let df = combined.unwrap_or_else(|| DataFrame::from(schema.as_ref()));
+        let df = replace_null_tokens(self.project(df, None)?, &nulls)?;
+        Ok((df, IndexScanReport {
+            index_used: true,
+            reason: None,
+            blocks_total: index.blocks.len(),
+            blocks_read: blocks.len(),
+            blocks_skipped: index.blocks.len() - blocks.len(),
+        }))
+    }
+
+    /// Schema of the file as read, before `rename`; columns with their own
+    /// null tokens are text
+    fn raw_schema(&self, path: &Path, nulls: &[ColumnNulls]) -> Result<SchemaRef, InsightoraError> {
+        let sample = self.with_nulls(self.sample(path)?, nulls)
+            .has_header(self.config.has_header)
+            .with_separator(self.config.delimiter)
+            .with_quote_char(Some(self.config.quote_char))
+            .infer_schema(self.config.infer_schema_length)
+            .with_n_rows(self.config.infer_schema_length)
+            .finish()?;
+        Ok(Arc::new(sample.schema()))
+    }
+
+    /// Parse one record-aligned block of a file with its header and schema
+    fn read_block(&self, header: &[u8], block: &[u8], schema: &SchemaRef) -> Result<DataFrame, InsightoraError> {
+        let mut bytes = Vec::with_capacity(header.len() + block.len());
+        bytes.extend_from_slice(header);
+        bytes.extend_from_slice(block);
+        let reader = CsvReader::new(Cursor::new(bytes))
+            .has_header(self.config.has_header)
+            .with_separator(self.config.delimiter)
+            .with_quote_char(Some(self.config.quote_char))
+            .with_schema(Some(schema.clone()));
+        Ok(self.with_nulls(reader, &[]).finish()?)
+    }
+
+    /// Reader over enough of a file for schema inference
+    fn sample(&self, path: &Path) -> Result<CsvReader<'static, CsvInput>, InsightoraError> {
+        // The skipped lines, the header, the inferred rows and one line that may be cut inside quotes
+        let lines = self
+            .config
+            .infer_schema_length
+            .map(|rows| self.config.skip_rows + self.config.has_header as usize + rows + 1);
+        Ok(CsvInput::head(path, lines)?.reader().with_skip_rows(self.config.skip_rows))
+    }
+
+    /// UTF-8 copy of a file in another encoding, or None to read it as it is
+    ///
+    /// The copy is converted a buffer at a time and written uncompressed, so
+    /// it can be memory-mapped; invalid sequences fail here with
+    /// `EncodingErrors::Strict`, before anything is parsed.
+    fn transcoded(&self, path: &Path) -> Result<Option<TranscodedFile>, InsightoraError> {
+        if !self.config.transcodes() {
+            return Ok(None);
+        }
+        TranscodedFile::create(path, self.config.encoding, self.config.encoding_errors).map(Some)
+    }
+
+    /// Text without bad lines for `BadLines::Skip` and `BadLines::Warn`, or
+    /// None to read the source as it is
+    ///
+    /// Clean text costs one scan; text with bad lines is scanned again and
+    /// copied into memory without them.
+    fn skip_bad_lines(&self, source: CsvSource) -> Result<(Option<Vec<u8>>, BadLineReport), InsightoraError> {
+        if self.config.on_bad_lines == BadLines::Error {
+            return Ok((None, BadLineReport::default()));
+        }
+        let scan = self.config.scan_bad_lines(source.lines()?, false, None)?;
+        if scan.report.count == 0 {
+            return Ok((None, scan.report));
+        }
+        // The copy is held alongside the parsed frame
+        let memory_mb = match source {
+            CsvSource::File(path) => parse_memory_mb(path, self.head_lines()?, 3)?,
+            CsvSource::Memory(bytes) => ((bytes.len() as u64 * 3) / (1024 * 1024)) as usize,
+        };
+        check_memory_limit(memory_mb)?;
+        let scan = self.config.scan_bad_lines(source.lines()?, true, None)?;
+        Ok((Some(scan.kept), scan.report))
+    }
+
+    /// Lines to read for `max_rows`, or None for the whole file
+    pub(crate) fn head_lines(&self) -> Result<Option<usize>, InsightoraError> {
+        match self.config.max_rows {
+            Some(0) => Err(InsightoraError::ValidationError(
+                "max_rows must be at least 1; use infer_schema for the columns alone".to_string()
+            )),
+            // The skipped lines, the header, the rows and one line that may be cut inside quotes
+            Some(rows) => Ok(Some(self.config.skip_rows + self.config.has_header as usize + rows + 1)),
+            None => Ok(None),
+        }
+    }
+
+    /// Resolve `columns` or `column_indices` against the file's header
+    fn projection(&self, source: CsvSource) -> Result<Option<Projection>, InsightoraError> {
+        let (columns, indices) = (self.config.columns.as_deref(), self.config.column_indices.as_deref());
+        if columns.is_some() && indices.is_some() {
+            return Err(InsightoraError::ValidationError(
+                "Pass either columns or column_indices, not both".to_string()
+            ));
+        }
+        if columns.is_none() && indices.is_none() {
+            return Ok(None);
+        }
+        let (source, output) = self.header(source)?;
+        let indices = match (columns, indices) {
+            (Some(columns), _) => projection_indices(&source, &output, columns)?,
+            (_, Some(indices)) => {
+                if let Some(index) = indices.iter().find(|&&index| index >= source.len()) {
+                    return Err(InsightoraError::ValidationError(format!(
+                        "Column index {} not found; the file has {} columns", index, source.len()
+                    )));
+                }
+                indices.to_vec()
+            }
+            (None, None) => unreachable!(),
+        };
+        Ok(Some(Projection {
+            source: indices.iter().map(|&i| source[i].clone()).collect(),
+            output: indices.iter().map(|&i| output[i].clone()).collect(),
+            indices,
+        }))
+    }
+
+    /// Header names as in the file and after `rename`
+    fn header(&self, source: CsvSource) -> Result<(Vec<String>, Vec<String>), InsightoraError> {
+        // Just the header line; without one Polars names the columns column_1, column_2, ...
+        let source: Vec<String> = source.head(Some(self.config.skip_rows + 1))?.reader()
+            .has_header(self.config.has_header)
+            .with_skip_rows(self.config.skip_rows)
+            .with_separator(self.config.delimiter)
+            .with_quote_char(Some(self.config.quote_char))
+            .infer_schema(Some(0))
+            .finish()?
+            .get_column_names()
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        let output = match self.config.rename.as_ref() {
+            Some(mapping) => mapping.apply_to_names(&source, true)?,
+            None => source.clone(),
+        };
+        Ok((source, output))
+    }
+
+    /// Resolve per-column `null_values` against the file's header
+    fn column_nulls(&self, source: CsvSource) -> Result<Vec<ColumnNulls>, InsightoraError> {
+        let Some(NullValueTokens::Columns(columns)) = &self.config.null_values else {
+            return Ok(Vec::new());
+        };
+        let (source, output) = self.header(source)?;
+        let names: Vec<String> = columns.iter().map(|(name, _)| name.clone()).collect();
+        Ok(projection_indices(&source, &output, &names)?
+            .into_iter()
+            .zip(columns)
+            .map(|(i, (_, tokens))| ColumnNulls {
+                source: source[i].clone(),
+                output: output[i].clone(),
+                tokens: tokens.clone(),
+            })
+            .collect())
+    }
+
+    /// Resolve `date_formats` against the file's header
+    fn date_columns(&self, source: CsvSource) -> Result<Vec<DateColumn>, InsightoraError> {
+        if self.config.date_formats.is_empty() {
+            return Ok(Vec::new());
+        }
+        let (source, output) = self.header(source)?;
+        let names: Vec<String> = self.config.date_formats.iter().map(|(name, _)| name.clone()).collect();
+        Ok(projection_indices(&source, &output, &names)?
+            .into_iter()
+            .zip(&self.config.date_formats)
+            .map(|(i, (_, format))| DateColumn {
+                source: source[i].clone(),
+                output: output[i].clone(),
+                format: format.clone(),
+            })
+            .collect())
+    }
+
+    /// Parse the `date_formats` columns and, with `try_parse_dates`, ISO 8601 text columns
+    fn parse_dates(&self, df: DataFrame, dates: &[DateColumn]) -> Result<(DataFrame, Vec<AggregatedWarning>), InsightoraError> {
+        if !self.config.try_parse_dates && dates.is_empty() {
+            return Ok((df, Vec::new()));
+        }
+        let formats: Vec<(String, DateFormat)> = dates.iter().map(|c| (c.output.clone(), c.format.clone())).collect();
+        let sample_rows = self.config.infer_schema_length.unwrap_or(usize::MAX);
+        let mut warnings = WarningCollector::new();
+        let df = parse_date_columns(df, self.config.try_parse_dates, &formats, sample_rows, &mut warnings)?;
+        Ok((df, warnings.finish()))
+    }
+
+    /// Set up a reader for every parse option: dialect, inference, row
+    /// window, projection, nulls and date columns
+    fn configure_reader<'a, R: MmapBytesReader + 'a>(
+        &self,
+        reader: CsvReader<'a, R>,
+        projection: Option<&Projection>,
+        nulls: &[ColumnNulls],
+        dates: &[DateColumn],
+    ) -> CsvReader<'a, R> {
+        let reader = self.with_nulls(reader, nulls);
+        // Date columns are read as text so a format like %Y%m%d isn't typed
+        // as integers; the null-token columns are listed again as with_dtypes
+        // replaces the earlier text types
+        let reader = match dates.is_empty() {
+            true => reader,
+            false => reader.with_dtypes(Some(Arc::new(text_schema(
+                nulls.iter().map(|c| c.source.as_str()).chain(dates.iter().map(|c| c.source.as_str())),
+            )))),
+        };
+        reader
+            .has_header(self.config.has_header)
+            .with_separator(self.config.delimiter)
+            .with_quote_char(Some(self.config.quote_char))
+            .infer_schema(self.config.infer_schema_length)
+            .with_chunk_size(self.config.chunk_size)
+            .with_skip_rows(self.config.skip_rows)
+            .with_n_rows(self.config.max_rows)
+            .with_projection(projection.map(Projection::reader_indices))
+    }
+
+    /// Set up a reader for `null_values` and `empty_as_null`
+    ///
+    /// Columns with their own tokens are read as text; `replace_null_tokens`
+    /// then nulls the tokens and types them.
+    fn with_nulls<'a, R: MmapBytesReader + 'a>(&self, reader: CsvReader<'a, R>, columns: &[ColumnNulls]) -> CsvReader<'a, R> {
+        let reader = reader.with_missing_is_null(self.config.empty_as_null);
+        let reader = match &self.config.null_values {
+            Some(NullValueTokens::All(tokens)) => reader.with_null_values(Some(NullValues::AllColumns(tokens.clone()))),
+            _ => reader,
+        };
+        if columns.is_empty() {
+            return reader;
+        }
+        reader.with_dtypes(Some(Arc::new(text_schema(columns.iter().map(|c| c.source.as_str())))))
     }
 
-    /// Get schema information from CSV file
-    pub fn infer_schema(&self, file_path: &str) -> Result<Schema, InsightoraError> {
-        let path = Path::new(file_path);
-        if !path.exists() {
-            return Err(InsightoraError::IoError(
-                std::io::Error::new(
-                    std::io::ErrorKind::NotFound,
-                    format!("File not found: {}", file_path)
-                )
-            ));
+    /// Apply `rename` and the projection to a parsed frame
+    fn project(&self, df: DataFrame, projection: Option<&Projection>) -> Result<DataFrame, InsightoraError> {
+        match projection {
+            Some(projection) => projection.apply(&df),
+            None => rename_and_project(df, self.config.rename.as_ref(), None),
         }
+    }
+}
 
-        // Use Polars to infer schema
-        let schema = CsvReader::from_path(file_path)?
-            .has_header(self.config.has_header)
-            .with_separator(self.config.delimiter)
-            .infer_schema(self.config.infer_schema_length)
-            .finish()?
-            .schema();
+/// Null tokens for one column, resolved against a file's header
+struct ColumnNulls {
+    source: String,
+    output: String,
+    tokens: Vec<String>,
+}
+
+/// Date format for one column, resolved against a file's header
+struct DateColumn {
+    source: String,
+    output: String,
+    format: DateFormat,
+}
+
+/// Schema reading the named columns as text
+fn text_schema<'a>(columns: impl Iterator<Item = &'a str>) -> Schema {
+    Schema::from_iter(columns.map(|name| Field::new(name, DataType::String)))
+}
 
-        Ok(schema)
+/// Null the per-column tokens of a parsed frame, then type those columns
+///
+/// Each becomes Int64 or Float64 when every remaining value parses as one,
+/// and stays text otherwise. Columns left out by a projection are skipped.
+fn replace_null_tokens(mut df: DataFrame, columns: &[ColumnNulls]) -> Result<DataFrame, InsightoraError> {
+    for column in columns {
+        let Ok(series) = df.column(&column.output) else {
+            continue;
+        };
+        let text: StringChunked = series
+            .str()?
+            .into_iter()
+            .map(|value| value.filter(|v| !column.tokens.iter().any(|token| token == v)))
+            .collect();
+        let text = text.into_series();
+        let typed = [DataType::Int64, DataType::Float64]
+            .iter()
+            .find_map(|dtype| text.strict_cast(dtype).ok())
+            .unwrap_or(text);
+        df.replace(&column.output, typed)?;
+    }
+    Ok(df)
+}
+
+/// Projected columns resolved against a file's header, in the requested order
+struct Projection {
+    /// Positions in the file
+    indices: Vec<usize>,
+    /// Header names of the projected columns
+    source: Vec<String>,
+    /// Their names after `rename`
+    output: Vec<String>,
+}
+
+impl Projection {
+    /// Positions for the Polars reader, which reads them in file order
+    fn reader_indices(&self) -> Vec<usize> {
+        let mut indices = self.indices.clone();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+
+    /// Pick, rename and order the projected columns of a frame with the file's header names
+    fn apply(&self, df: &DataFrame) -> Result<DataFrame, InsightoraError> {
+        let columns = self
+            .source
+            .iter()
+            .zip(&self.output)
+            .map(|(source, output)| {
+                let mut column = df.column(source)?.clone();
+                column.rename(output);
+                Ok(column)
+            })
+            .collect::<Result<Vec<_>, InsightoraError>>()?;
+        Ok(DataFrame::new(columns)?)
     }
 }
 
@@ -217,15 +1745,553 @@ mod tests {
         assert_eq!(schema.len(), 3);
     }
 
+    #[test]
+    fn test_infer_schema_flags_zero_padded_identifiers() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "store,zip,sales").unwrap();
+        for i in 0..12 {
+            writeln!(file, "{},{:05},{}", i % 3, 1_000 + i, i * 7).unwrap();
+        }
+        let parser = ParallelCsvParser::new();
+        let (schema, decisions) = parser
+            .infer_schema_with_identifiers(file.path().to_str().unwrap(), &IdDetectionConfig::default())
+            .unwrap();
+
+        // Type inference alone reads the zips as integers
+        assert_eq!(schema.get("zip"), Some(&DataType::Int64));
+        let flagged: Vec<&str> = decisions.iter().filter(|d| d.is_identifier).map(|d| d.column.as_str()).collect();
+        assert_eq!(flagged, vec!["zip"]);
+    }
+
+    #[test]
+    fn test_parse_with_schema_reuses_another_files_types() {
+        let monday = create_test_csv();
+        let mut tuesday = NamedTempFile::new().unwrap();
+        writeln!(tuesday, "name,age,salary").unwrap();
+        writeln!(tuesday, "Dana,41,52000.5").unwrap();
+        let parser = ParallelCsvParser::new();
+        let schema = parser.infer_schema(monday.path().to_str().unwrap()).unwrap();
+        assert_eq!(schema.get("salary"), Some(&DataType::Int64));
+
+        // A value the type doesn't fit fails rather than changing the type
+        assert!(parser.parse_with_schema(tuesday.path().to_str().unwrap(), &schema).is_err());
+        let mut schema = schema;
+        schema.with_column("salary".into(), DataType::Float64);
+        schema.with_column("age".into(), DataType::Float64);
+        let df = parser.parse_with_schema(tuesday.path().to_str().unwrap(), &schema).unwrap();
+        assert_eq!(df.schema(), schema);
+        assert_eq!(df.column("age").unwrap().f64().unwrap().get(0), Some(41.0));
+
+        let narrow: Schema = schema.iter_fields().take(2).collect();
+        let err = parser.parse_with_schema(tuesday.path().to_str().unwrap(), &narrow).unwrap_err();
+        let InsightoraError::SchemaError(problems) = err else { panic!("expected SchemaError") };
+        assert_eq!(problems, ["the schema has 2 columns but the file has 3; schema: [name, age]; file: [name, age, salary]"]);
+    }
+
+    #[test]
+    fn test_parse_with_schema_parses_durations() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "session,length").unwrap();
+        writeln!(file, "a,02:35:17").unwrap();
+        writeln!(file, "b,1d 4h").unwrap();
+        writeln!(file, "c,").unwrap();
+        let path = file.path().to_str().unwrap().to_string();
+        let schema = Schema::from_iter([
+            Field::new("session", DataType::String),
+            Field::new("length", DataType::Duration(TimeUnit::Milliseconds)),
+        ]);
+
+        let df = ParallelCsvParser::new().parse_with_schema(&path, &schema).unwrap();
+        assert_eq!(df.schema(), schema);
+        let millis: Vec<Option<i64>> = df.column("length").unwrap().to_physical_repr().i64().unwrap().into_iter().collect();
+        assert_eq!(millis, [Some(9_317_000), Some(100_800_000), None]);
+
+        writeln!(file, "d,soon").unwrap();
+        let err = ParallelCsvParser::new().parse_with_schema(&path, &schema).unwrap_err().to_string();
+        assert!(err.contains("'soon' is not a valid duration"), "{}", err);
+    }
+
+    #[test]
+    fn test_on_bad_lines_modes() {
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "id,comment,score\n1,fine,5\n2,\"quoted, with\nline break\",4\n3,late, extra,3\n4,ok,2\n5,a,b,c,1\n").unwrap();
+        let path = file.path().to_str().unwrap();
+
+        let err = ParallelCsvParser::new().parse(path).unwrap_err().to_string();
+        assert!(err.contains("Line 5 has 4 fields; expected 3 as in the header"), "{}", err);
+
+        for mode in [BadLines::Skip, BadLines::Warn] {
+            let parser = ParallelCsvParser::with_config(CsvParserConfig { on_bad_lines: mode, ..Default::default() });
+            let (df, _, _, warnings, bad_lines, _) = parser.parse_checked(path).unwrap();
+            let ids: Vec<Option<i64>> = df.column("id").unwrap().i64().unwrap().into_iter().collect();
+            assert_eq!(ids, vec![Some(1), Some(2), Some(4)]);
+            assert_eq!((bad_lines.count, bad_lines.sample_lines.clone(), bad_lines.expected_fields), (2, vec![5, 7], 3));
+            assert_eq!(warnings.len(), (mode == BadLines::Warn) as usize);
+        }
+        let warn = ParallelCsvParser::with_config(CsvParserConfig { on_bad_lines: BadLines::Warn, ..Default::default() });
+        let (_, _, _, warnings, ..) = warn.parse_checked(path).unwrap();
+        assert_eq!(warnings[0].message(), "2 lines with more fields than the header were skipped; examples: line 5, line 7");
+
+        let bytes = std::fs::read(path).unwrap();
+        let skip = ParallelCsvParser::with_config(CsvParserConfig { on_bad_lines: BadLines::Skip, ..Default::default() });
+        assert_eq!(skip.parse_bytes(&bytes).unwrap().height(), 3);
+        assert!(BadLines::from_name("ignore").is_err());
+    }
+
     #[test]
     fn test_count_lines() {
         let file = create_test_csv();
         let parser = ParallelCsvParser::new();
-        let result = parser.count_lines(file.path().to_str().unwrap());
+        let result = parser.count_lines(file.path().to_str().unwrap(), false);
         
         assert!(result.is_ok());
         let count = result.unwrap();
         assert_eq!(count, 4); // Header + 3 data rows
+
+        // No trailing newline, and a quoted field spanning two lines
+        let mut file = NamedTempFile::new().unwrap();
+        write!(file, "id,note\n1,\"first\nsecond\"\n2,\"say \"\"hi\"\"\"\n3,plain").unwrap();
+        let path = file.path().to_str().unwrap();
+        assert_eq!(parser.count_lines(path, false).unwrap(), 5);
+        assert_eq!(parser.count_lines(path, true).unwrap(), 4);
+
+        // Several buffers, with a quoted newline just past the first boundary
+        let mut long = NamedTempFile::new().unwrap();
+        write!(long, "id,note\n1,{}\"a\nb\"\n2,c\n", "x".repeat(COUNT_BUFFER_BYTES - 11)).unwrap();
+        let long_path = long.path().to_str().unwrap();
+        assert_eq!(parser.count_lines(long_path, false).unwrap(), 4);
+        assert_eq!(parser.count_lines(long_path, true).unwrap(), 3);
+
+        // Ranges counted apart combine to the count of the whole
+        let bytes = std::fs::read(path).unwrap();
+        let whole = NewlineCount::of(&bytes, Some(b'"'));
+        for split in 0..=bytes.len() {
+            let (head, tail) = bytes.split_at(split);
+            assert_eq!(NewlineCount::of(head, Some(b'"')).then(NewlineCount::of(tail, Some(b'"'))), whole);
+        }
+    }
+
+    #[test]
+    fn test_parse_batches_reads_a_bounded_window() {
+        use crate::io::chunk_reader::tests::{quick_retries, FlakySource};
+
+        // Fixed-width rows, so the bytes behind each delivered batch are known
+        let header = "id,region,amount\n";
+        let row = |i: usize| format!("{:07},region_{},{:03}.25\n", i, i % 7, i % 1000);
+        let width = row(0).len();
+        let (rows, chunk_size, chunk_bytes) = (20_000, 500, 16 << 10);
+        let text: String = std::iter::once(header.to_string()).chain((0..rows).map(row)).collect();
+
+        let reported = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reported);
+        let parser = StreamingCsvParser::with_config(StreamingCsvConfig { chunk_size, ..Default::default() })
+            .with_progress_callback(Arc::new(move |read, total| sink.lock().unwrap().push((read, total))));
+        let reader = ChunkReader::new(FlakySource::new(text.as_bytes()), "large.csv", chunk_bytes, Some(b'"'), quick_retries(0)).unwrap();
+        let mut delivered = Vec::new();
+        parser
+            .parse_chunks(reader, |batch| {
+                delivered.push(batch.height());
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(delivered, vec![chunk_size; rows / chunk_size]);
+        let reported = reported.lock().unwrap();
+        assert_eq!(reported.len(), delivered.len());
+        assert_eq!(reported.last(), Some(&(text.len(), text.len())));
+        // After each batch the reader is at most one chunk plus one batch
+        // ahead of the rows handed out, however large the file
+        for (index, &(read, total)) in reported.iter().enumerate() {
+            assert_eq!(total, text.len());
+            let consumed = header.len() + (index + 1) * chunk_size * width;
+            assert!(read >= consumed);
+            assert!(read - consumed <= chunk_bytes + chunk_size * width, "batch {}: {} bytes read ahead", index, read - consumed);
+        }
+    }
+
+    #[test]
+    fn test_parse_gzip_and_zstd_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let text: String = std::iter::once("id,region,amount\n".to_string())
+            .chain((0..50_000).map(|i| format!("{},region_{},{}.5\n", i, i % 5, i % 100)))
+            .collect();
+        let plain = dir.path().join("orders.csv");
+        std::fs::write(&plain, &text).unwrap();
+        let gzip = dir.path().join("orders.csv.gz");
+        let mut encoder = flate2::write::GzEncoder::new(File::create(&gzip).unwrap(), flate2::Compression::default());
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+        let zstd_path = dir.path().join("orders.csv.zst");
+        std::fs::write(&zstd_path, zstd::bulk::compress(text.as_bytes(), 3).unwrap()).unwrap();
+
+        let parser = ParallelCsvParser::new();
+        let expected = parser.parse(plain.to_str().unwrap()).unwrap();
+        for path in [&gzip, &zstd_path] {
+            let path = path.to_str().unwrap();
+            assert!(parser.parse(path).unwrap().equals_missing(&expected));
+            assert_eq!(parser.infer_schema(path).unwrap(), expected.schema());
+            assert_eq!(parser.count_lines(path, true).unwrap(), 50_001);
+        }
+
+        // The limit check sees the decompressed size, not the few compressed bytes
+        let compressed = std::fs::metadata(&gzip).unwrap().len();
+        assert!(compressed < text.len() as u64 / 4);
+        assert_eq!(parse_memory_mb(&gzip, None, 2).unwrap(), text.len() * 3 / (1024 * 1024));
+    }
+
+    #[test]
+    fn test_rename_at_parse_time() {
+        let mut headerless = NamedTempFile::new().unwrap();
+        writeln!(headerless, "Alice,30,50000").unwrap();
+        writeln!(headerless, "Bob,25,45000").unwrap();
+        let parser = ParallelCsvParser::with_config(CsvParserConfig {
+            has_header: false,
+            rename: Some(ColumnMapping::Positions(["name", "age", "salary"].map(String::from).to_vec())),
+            columns: Some(vec!["salary".to_string(), "name".to_string()]),
+            ..Default::default()
+        });
+        let df = parser.parse(headerless.path().to_str().unwrap()).unwrap();
+        assert_eq!(df.get_column_names(), ["salary", "name"]);
+        assert_eq!(df.height(), 2);
+
+        // Schema inference reports the renamed columns; projections accept source names
+        let file = create_test_csv();
+        let parser = ParallelCsvParser::with_config(CsvParserConfig {
+            rename: Some(ColumnMapping::Names(vec![("salary".to_string(), "pay".to_string())])),
+            columns: Some(vec!["salary".to_string()]),
+            ..Default::default()
+        });
+        let schema = parser.infer_schema(file.path().to_str().unwrap()).unwrap();
+        assert_eq!(schema.iter_names().map(|s| s.as_str()).collect::<Vec<_>>(), ["pay"]);
+
+        let clash = ParallelCsvParser::with_config(CsvParserConfig {
+            rename: Some(ColumnMapping::Names(vec![("salary".to_string(), "age".to_string())])),
+            ..Default::default()
+        });
+        assert!(clash.parse(file.path().to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_projection_keeps_requested_order() {
+        let file = create_test_csv();
+        let path = file.path().to_str().unwrap();
+        let by_name = ParallelCsvParser::with_config(CsvParserConfig {
+            columns: Some(vec!["salary".to_string(), "name".to_string()]),
+            ..Default::default()
+        });
+        let by_index = ParallelCsvParser::with_config(CsvParserConfig {
+            column_indices: Some(vec![2, 0]),
+            ..Default::default()
+        });
+        for parser in [&by_name, &by_index] {
+            let df = parser.parse(path).unwrap();
+            assert_eq!(df.get_column_names(), ["salary", "name"]);
+            assert_eq!(df.column("salary").unwrap().i64().unwrap().get(0), Some(50000));
+            let schema = parser.infer_schema(path).unwrap();
+            assert_eq!(schema.iter_names().map(|s| s.as_str()).collect::<Vec<_>>(), ["salary", "name"]);
+        }
+
+        let missing = ParallelCsvParser::with_config(CsvParserConfig {
+            columns: Some(vec!["name".to_string(), "bonus".to_string()]),
+            ..Default::default()
+        });
+        let err = missing.parse(path).unwrap_err();
+        assert!(matches!(err, InsightoraError::ValidationError(ref msg) if msg.contains("'bonus'")), "{}", err);
+        let out_of_range = ParallelCsvParser::with_config(CsvParserConfig {
+            column_indices: Some(vec![3]),
+            ..Default::default()
+        });
+        assert!(matches!(out_of_range.parse(path), Err(InsightoraError::ValidationError(_))));
+    }
+
+    #[test]
+    fn test_row_filter_keeps_matching_rows_batch_by_batch() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "country,city,amount").unwrap();
+        for (country, city, amount) in [("DE", "Berlin", 150), ("FR", "Paris", 80), ("DE", "Bonn", 90), ("AT", "Wien", 300), ("FR", "Lyon", 40)] {
+            writeln!(file, "{},{},{}", country, city, amount).unwrap();
+        }
+        let path = file.path().to_str().unwrap();
+        let config = |filter: &str| CsvParserConfig {
+            chunk_size: 2,
+            rename: Some(ColumnMapping::Names(vec![("country".to_string(), "land".to_string())])),
+            columns: Some(vec!["amount".to_string(), "city".to_string()]),
+            row_filter: Some(Predicate::parse(filter).unwrap()),
+            ..Default::default()
+        };
+
+        // The filter reads a renamed column the projection leaves out
+        let df = ParallelCsvParser::with_config(config("land == 'DE' or amount > 200")).parse(path).unwrap();
+        assert_eq!(df.get_column_names(), ["amount", "city"]);
+        let cities: Vec<Option<&str>> = df.column("city").unwrap().str().unwrap().into_iter().collect();
+        assert_eq!(cities, [Some("Berlin"), Some("Bonn"), Some("Wien")]);
+        let none = ParallelCsvParser::with_config(config("amount > 1000")).parse(path).unwrap();
+        assert_eq!((none.height(), none.width()), (0, 2));
+
+        let err = ParallelCsvParser::with_config(config("country == 'DE'")).parse(path).unwrap_err();
+        assert!(err.to_string().contains("unknown column 'country' at position 0"), "{}", err);
+
+        let streaming = StreamingCsvParser::with_config(StreamingCsvConfig {
+            chunk_size: 2,
+            row_filter: Some(Predicate::parse("city contains 'o' and amount < 100").unwrap()),
+            ..Default::default()
+        });
+        let mut kept = Vec::new();
+        streaming.parse_batches(path, |batch| {
+            kept.extend(batch.column("city")?.str()?.into_iter().map(|city| city.unwrap_or_default().to_string()));
+            Ok(())
+        }).unwrap();
+        assert_eq!(kept, ["Bonn", "Lyon"]);
+    }
+
+    #[test]
+    fn test_sample_draws_from_filtered_rows_reproducibly() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "id,even").unwrap();
+        for id in 0..1000 {
+            writeln!(file, "{},{}", id, id % 2 == 0).unwrap();
+        }
+        let path = file.path().to_str().unwrap();
+        let parse = |sample: RowSample, seed: Option<u64>| {
+            ParallelCsvParser::with_config(CsvParserConfig {
+                chunk_size: 64,
+                columns: Some(vec!["id".to_string()]),
+                row_filter: Some(Predicate::parse("even == true").unwrap()),
+                sample: Some(sample),
+                sample_seed: seed,
+                ..Default::default()
+            })
+            .parse_checked(path)
+            .map(|(df, .., report)| (df, report.unwrap()))
+        };
+
+        let (df, report) = parse(RowSample::Count(20), Some(42)).unwrap();
+        assert_eq!(df.get_column_names(), ["id"]);
+        let ids: Vec<i64> = df.column("id").unwrap().i64().unwrap().into_no_null_iter().collect();
+        assert!(ids.len() == 20 && ids.iter().all(|id| id % 2 == 0) && ids.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!((report.rows_read, report.rows_kept, report.seed), (500, 20, 42));
+        assert!((report.rate() - 0.04).abs() < 1e-12);
+        assert!(parse(RowSample::Count(20), Some(42)).unwrap().0.equals(&df));
+
+        // The seed drawn is reported, and drawing with it again gives the same rows
+        let (df, report) = parse(RowSample::Fraction(0.1), None).unwrap();
+        assert_eq!((report.rows_read, report.rows_kept, report.rate()), (500, df.height(), 0.1));
+        assert!(parse(RowSample::Fraction(0.1), Some(report.seed)).unwrap().0.equals(&df));
+
+        assert!(matches!(parse(RowSample::Fraction(1.5), None), Err(InsightoraError::ValidationError(_))));
+        let bytes = ParallelCsvParser::with_config(CsvParserConfig { sample: Some(RowSample::Count(5)), ..Default::default() });
+        assert!(matches!(bytes.parse_bytes(b"id\n1\n"), Err(InsightoraError::ValidationError(_))));
+    }
+
+    #[test]
+    fn test_skip_rows_and_max_rows() {
+        let mut text = String::from("Exported by VendorTool\nreport date: 2024-01-31\nid,amount\n");
+        for i in 0..1000 {
+            text.push_str(&format!("{},{}\n", i, i * 10));
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let plain = dir.path().join("preamble.csv");
+        std::fs::write(&plain, &text).unwrap();
+        let gzip = dir.path().join("preamble.csv.gz");
+        let mut encoder = flate2::write::GzEncoder::new(File::create(&gzip).unwrap(), flate2::Compression::default());
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let preview = ParallelCsvParser::with_config(CsvParserConfig {
+            skip_rows: 2,
+            max_rows: Some(5),
+            ..Default::default()
+        });
+        for path in [&plain, &gzip] {
+            let path = path.to_str().unwrap();
+            let df = preview.parse(path).unwrap();
+            assert_eq!(df.get_column_names(), ["id", "amount"]);
+            assert_eq!(df.height(), 5);
+            assert_eq!(df.column("amount").unwrap().i64().unwrap().get(4), Some(40));
+            let schema = preview.infer_schema(path).unwrap();
+            assert_eq!(schema.iter_names().map(|s| s.as_str()).collect::<Vec<_>>(), ["id", "amount"]);
+        }
+        // The preamble, header, rows and one spare line are all that is read
+        assert_eq!(preview.head_lines().unwrap(), Some(9));
+
+        let empty = ParallelCsvParser::with_config(CsvParserConfig { max_rows: Some(0), ..Default::default() });
+        let err = empty.parse(plain.to_str().unwrap()).unwrap_err();
+        assert!(matches!(err, InsightoraError::ValidationError(ref msg) if msg.contains("max_rows")), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_bytes_honours_options() {
+        let payload = b"# exported 2024-01-31\nsku;qty;price\nA;2;NA\nB;-;1.5\nC;4;2.0\n";
+        let parser = ParallelCsvParser::with_config(CsvParserConfig {
+            delimiter: b';',
+            skip_rows: 1,
+            max_rows: Some(2),
+            null_values: Some(NullValueTokens::Columns(vec![("qty".to_string(), vec!["-".to_string()])])),
+            columns: Some(vec!["qty".to_string(), "sku".to_string()]),
+            ..Default::default()
+        });
+        let df = parser.parse_bytes(payload).unwrap();
+        assert_eq!(df.get_column_names(), ["qty", "sku"]);
+        assert_eq!(df.column("qty").unwrap().i64().unwrap().into_iter().collect::<Vec<_>>(), [Some(2), None]);
+        assert!(parser.parse_reader(&payload[..]).unwrap().equals_missing(&df));
+
+        let header_only = ParallelCsvParser::new().parse_bytes(b"a,b\n").unwrap();
+        assert_eq!((header_only.width(), header_only.height()), (2, 0));
+        for empty in [&b""[..], b"\n  \n"] {
+            let err = ParallelCsvParser::new().parse_bytes(empty).unwrap_err();
+            assert!(matches!(err, InsightoraError::ParseError(_)), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_legacy_encodings_are_converted_before_parsing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let text = b"\xEF\xBB\xBFcustomer,note\nRen\xE9,\x93d\xE9j\xE0 vu\x94\n";
+        let path = dir.path().join("export.csv.gz");
+        let mut encoder = flate2::write::GzEncoder::new(File::create(&path).unwrap(), flate2::Compression::default());
+        encoder.write_all(text).unwrap();
+        encoder.finish().unwrap();
+
+        let cp1252 = ParallelCsvParser::with_config(CsvParserConfig { encoding: CsvEncoding::Windows1252, ..Default::default() });
+        let df = cp1252.parse(path.to_str().unwrap()).unwrap();
+        assert_eq!(df.get_column_names(), ["customer", "note"]);
+        assert_eq!(df.column("customer").unwrap().str().unwrap().get(0), Some("René"));
+        assert_eq!(df.column("note").unwrap().str().unwrap().get(0), Some("\u{201C}déjà vu\u{201D}"));
+        assert!(cp1252.parse_bytes(text).unwrap().equals_missing(&df));
+
+        // Read as UTF-8, the first invalid byte is the é of René, after the BOM and header
+        let err = ParallelCsvParser::new().parse(path.to_str().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("at byte 20 (line 2)"), "{}", err);
+        let lossy = ParallelCsvParser::with_config(CsvParserConfig { encoding_errors: EncodingErrors::Replace, ..Default::default() });
+        let df = lossy.parse(path.to_str().unwrap()).unwrap();
+        assert_eq!(df.column("customer").unwrap().str().unwrap().get(0), Some("Ren\u{FFFD}"));
+    }
+
+    #[test]
+    fn test_try_parse_dates_and_date_formats() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "day,at,booked,note").unwrap();
+        writeln!(file, "2024-01-15,2024-01-15T08:30:00Z,20240115,ok").unwrap();
+        writeln!(file, "2024-01-16,2024-01-16T09:00:00Z,20240231,-").unwrap();
+        writeln!(file, "2024-13-01,,20240117,later").unwrap();
+        let path = file.path().to_str().unwrap();
+
+        let parser = ParallelCsvParser::with_config(CsvParserConfig {
+            try_parse_dates: true,
+            date_formats: vec![("booked".to_string(), DateFormat::from_pattern("%Y%m%d").unwrap())],
+            infer_schema_length: Some(2),
+            ..Default::default()
+        });
+        let (df, _, _, warnings, ..) = parser.parse_checked(path).unwrap();
+        assert_eq!(df.column("day").unwrap().dtype(), &DataType::Date);
+        assert_eq!(df.column("day").unwrap().null_count(), 1);
+        assert!(matches!(df.column("at").unwrap().dtype(), DataType::Datetime(_, Some(zone)) if zone == "UTC"));
+        assert_eq!(df.column("at").unwrap().null_count(), 1);
+        // Read as text rather than integers, then parsed with the format
+        assert_eq!(df.column("booked").unwrap().dtype(), &DataType::Date);
+        assert_eq!(df.column("note").unwrap().dtype(), &DataType::String);
+        let counts: Vec<(&str, usize)> = warnings.iter().map(|w| (w.column.as_str(), w.count)).collect();
+        assert_eq!(counts, vec![("day", 1), ("booked", 1)]);
+
+        let plain = ParallelCsvParser::new().parse(path).unwrap();
+        assert_eq!(plain.column("day").unwrap().dtype(), &DataType::String);
+        assert_eq!(plain.column("booked").unwrap().dtype(), &DataType::Int64);
+    }
+
+    #[test]
+    fn test_null_values_keep_columns_numeric() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "id,amount,rate,note").unwrap();
+        writeln!(file, "1,10,0.5,ok").unwrap();
+        writeln!(file, "2,NA,-,").unwrap();
+        writeln!(file, "3,N/A,n/a,NULL").unwrap();
+        let path = file.path().to_str().unwrap();
+        let tokens = ["NA", "N/A", "-", "n/a", "NULL"].map(String::from).to_vec();
+
+        let everywhere = ParallelCsvParser::with_config(CsvParserConfig {
+            null_values: Some(NullValueTokens::All(tokens.clone())),
+            ..Default::default()
+        });
+        let df = everywhere.parse(path).unwrap();
+        assert_eq!(df.column("amount").unwrap().dtype(), &DataType::Int64);
+        assert_eq!(df.column("rate").unwrap().dtype(), &DataType::Float64);
+        assert_eq!(df.column("note").unwrap().null_count(), 2);
+        assert_eq!(everywhere.infer_schema(path).unwrap().get("amount"), Some(&DataType::Int64));
+
+        // Per column, with several tokens each; "NULL" stays text in note
+        let per_column = ParallelCsvParser::with_config(CsvParserConfig {
+            null_values: Some(NullValueTokens::Columns(vec![
+                ("amount".to_string(), vec!["NA".to_string(), "N/A".to_string()]),
+                ("rate".to_string(), vec!["-".to_string(), "n/a".to_string()]),
+            ])),
+            empty_as_null: false,
+            ..Default::default()
+        });
+        let df = per_column.parse(path).unwrap();
+        assert_eq!(df.column("amount").unwrap().i64().unwrap().into_iter().collect::<Vec<_>>(), [Some(10), None, None]);
+        assert_eq!(df.column("rate").unwrap().dtype(), &DataType::Float64);
+        let note = df.column("note").unwrap().str().unwrap().into_iter().collect::<Vec<_>>();
+        assert_eq!(note, [Some("ok"), Some(""), Some("NULL")]);
+
+        let unknown = ParallelCsvParser::with_config(CsvParserConfig {
+            null_values: Some(NullValueTokens::Columns(vec![("price".to_string(), vec!["NA".to_string()])])),
+            ..Default::default()
+        });
+        assert!(matches!(unknown.parse(path), Err(InsightoraError::ValidationError(_))));
+    }
+
+    #[test]
+    fn test_column_count_repair_in_full_and_streaming_parses() {
+        use crate::io::csv_repair::{CsvRepair, CsvRepairOptions};
+
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "id,comment,score").unwrap();
+        for i in 0..30 {
+            if i % 10 == 3 {
+                writeln!(file, "{},late, damaged, refunded,{}", i, i % 5).unwrap();
+            } else {
+                writeln!(file, "{},fine,{}", i, i % 5).unwrap();
+            }
+        }
+        let path = file.path().to_str().unwrap();
+        let repair = CsvRepairOptions {
+            mode: CsvRepair::ColumnCount,
+            flag_repairs: true,
+            ..Default::default()
+        };
+
+        let parser = ParallelCsvParser::with_config(CsvParserConfig { repair: repair.clone(), ..Default::default() });
+        let (df, report) = parser.parse_repaired(path).unwrap();
+        assert_eq!(df.shape(), (30, 4));
+        assert_eq!(report.absorber.as_deref(), Some("comment"));
+        assert_eq!(report.rows_repaired, 3);
+        assert_eq!(report.repairs[0].line, 5);
+        assert_eq!(df.column("comment").unwrap().str().unwrap().get(3), Some("late, damaged, refunded"));
+        assert_eq!(df.column("score").unwrap().i64().unwrap().get(3), Some(3));
+        let flags = df.column("_repaired").unwrap().bool().unwrap().clone();
+        assert_eq!(flags.into_iter().filter(|f| *f == Some(true)).count(), 3);
+
+        // Per batch in the streaming path, with the same outcome
+        let streaming = StreamingCsvParser::with_config(StreamingCsvConfig {
+            chunk_size: 7,
+            repair,
+            ..Default::default()
+        });
+        let mut heights = Vec::new();
+        let report = streaming
+            .parse_batches_repaired(path, |batch| {
+                assert_eq!(batch.width(), 4);
+                heights.push(batch.height());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(heights, vec![7, 7, 7, 7, 2]);
+        assert_eq!(report.rows_repaired, 3);
+        let (combined, _) = streaming.parse_streaming_repaired(path).unwrap();
+        assert!(combined.equals(&df));
+
+        // Without the opt-in the ragged rows are not rewritten
+        assert!(ParallelCsvParser::new().parse_repaired(path).map(|(_, r)| r.rows_repaired == 0).unwrap_or(true));
     }
 }
 
@@ -235,7 +2301,8 @@ mod tests {
 
 use std::sync::Arc;
 
-/// Callback function type for progress reporting
+/// Callback for streaming progress: `(bytes_read, total_bytes)`, with a
+/// total of 0 when the size isn't known up front (compressed input)
 pub type ProgressCallback = Arc<dyn Fn(usize, usize) + Send + Sync>;
 
 /// Streaming CSV parser configuration
@@ -245,6 +2312,17 @@ pub struct StreamingCsvConfig {
     pub memory_limit_mb: usize,
     pub has_header: bool,
     pub delimiter: u8,
+    /// Repair applied to every batch (off by default)
+    pub repair: CsvRepairOptions,
+    /// Renames applied as soon as the header is read (see `ColumnMapping`)
+    pub rename: Option<ColumnMapping>,
+    /// Columns to keep, by source or renamed name (default: all)
+    pub columns: Option<Vec<String>>,
+    /// Columns declared NOT NULL, checked on every batch (default: none)
+    pub not_null: NotNullRules,
+    /// Rows kept from each batch before it is processed or combined; the
+    /// filter uses `rename` names and may read columns not kept (default: all rows)
+    pub row_filter: Option<Predicate>,
 }
 
 impl Default for StreamingCsvConfig {
@@ -254,6 +2332,11 @@ impl Default for StreamingCsvConfig {
             memory_limit_mb: 1024, // 1GB default for streaming
             has_header: true,
             delimiter: b',',
+            repair: CsvRepairOptions::default(),
+            rename: None,
+            columns: None,
+            not_null: NotNullRules::default(),
+            row_filter: None,
         }
     }
 }
@@ -262,6 +2345,8 @@ impl Default for StreamingCsvConfig {
 pub struct StreamingCsvParser {
     config: StreamingCsvConfig,
     progress_callback: Option<ProgressCallback>,
+    /// Checked after each progress report; once set the parse stops with `Cancelled`
+    cancel: Option<Arc<AtomicBool>>,
 }
 
 impl StreamingCsvParser {
@@ -270,6 +2355,7 @@ impl StreamingCsvParser {
         Self {
             config: StreamingCsvConfig::default(),
             progress_callback: None,
+            cancel: None,
         }
     }
 
@@ -278,6 +2364,7 @@ impl StreamingCsvParser {
         Self {
             config,
             progress_callback: None,
+            cancel: None,
         }
     }
 
@@ -287,6 +2374,34 @@ impl StreamingCsvParser {
         self
     }
 
+    /// Stop with `InsightoraError::Cancelled` at the next chunk or progress report once `flag` is set
+    ///
+    /// Lets a progress callback, or another thread, abort the parse; small
+    /// files parsed in one pass check it as `ParallelCsvParser` does.
+    pub fn with_cancel_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(flag);
+        self
+    }
+
+    pub fn config(&self) -> &StreamingCsvConfig {
+        &self.config
+    }
+
+    /// Report `bytes_read` of `total_bytes`, then fail if cancelled
+    fn report(&self, bytes_read: usize, total_bytes: usize) -> Result<(), InsightoraError> {
+        if let Some(callback) = &self.progress_callback {
+            callback(bytes_read, total_bytes);
+        }
+        self.check_cancelled()
+    }
+
+    fn check_cancelled(&self) -> Result<(), InsightoraError> {
+        match &self.cancel {
+            Some(flag) if flag.load(Ordering::Relaxed) => Err(InsightoraError::Cancelled),
+            _ => Ok(()),
+        }
+    }
+
     /// Parse CSV file in streaming mode with memory limits
     /// 
     /// This method processes the file in chunks to avoid loading the entire
@@ -298,7 +2413,33 @@ impl StreamingCsvParser {
     /// # Returns
     /// * `Result<DataFrame>` - Parsed DataFrame or error
     pub fn parse_streaming(&self, file_path: &str) -> Result<DataFrame, InsightoraError> {
-        let path = Path::new(file_path);
+        self.parse_streaming_checked(file_path).map(|(df, _, _)| df)
+    }
+
+    /// Parse in streaming mode, checking `config.not_null` batch by batch
+    ///
+    /// With the strict null policy the parse stops at the batch holding the
+    /// first violation, so a null near the start of a huge file fails
+    /// without reading the rest.
+    ///
+    /// # Returns
+    /// * `Result<(DataFrame, RepairReport, NullabilityReport)>` - Combined batches, what was repaired and NOT NULL violations
+    pub fn parse_streaming_checked(&self, file_path: &str) -> Result<(DataFrame, RepairReport, NullabilityReport), InsightoraError> {
+        if self.config.repair.is_enabled() {
+            let mut combined: Option<DataFrame> = None;
+            let (repairs, nulls) = self.batches_repaired(file_path, |batch| {
+                match combined.as_mut() {
+                    Some(df) => {
+                        df.vstack_mut(&batch)?;
+                    }
+                    None => combined = Some(batch),
+                }
+                Ok(())
+            })?;
+            return Ok((combined.unwrap_or_else(DataFrame::empty), repairs, nulls));
+        }
+
+        let path = check_path_allowed(file_path)?;
         if !path.exists() {
             return Err(InsightoraError::IoError(
                 std::io::Error::new(
@@ -309,7 +2450,7 @@ impl StreamingCsvParser {
         }
 
         // Get file size for progress tracking
-        let file_size = std::fs::metadata(file_path)
+        let file_size = std::fs::metadata(&path)
             .map_err(InsightoraError::IoError)?
             .len();
 
@@ -317,45 +2458,224 @@ impl StreamingCsvParser {
         let file_size_mb = file_size / (1024 * 1024);
         if file_size_mb < 100 {
             // For smaller files, use regular parsing
-            let parser = ParallelCsvParser::with_config(CsvParserConfig {
+            let mut parser = ParallelCsvParser::with_config(CsvParserConfig {
                 chunk_size: self.config.chunk_size,
                 has_header: self.config.has_header,
                 delimiter: self.config.delimiter,
                 quote_char: b'"',
                 infer_schema_length: Some(1000),
+                repair: CsvRepairOptions::default(),
+                rename: self.config.rename.clone(),
+                columns: self.config.columns.clone(),
+                column_indices: None,
+                skip_rows: 0,
+                max_rows: None,
+                null_values: None,
+                empty_as_null: true,
+                not_null: self.config.not_null.clone(),
+                try_parse_dates: false,
+                date_formats: Vec::new(),
+                on_bad_lines: BadLines::Error,
+                encoding: CsvEncoding::Utf8,
+                encoding_errors: EncodingErrors::Strict,
+                row_filter: self.config.row_filter.clone(),
+                sample: None,
+                sample_seed: None,
             });
-            return parser.parse(file_path);
+            if let Some(flag) = &self.cancel {
+                parser = parser.with_cancel_flag(Arc::clone(flag));
+            }
+            let (df, repairs, nulls, ..) = parser.parse_checked(file_path)?;
+            self.report(file_size as usize, file_size as usize)?;
+            return Ok((df, repairs, nulls));
         }
 
-        // Use Polars' streaming mode with low_memory option
-        let df = CsvReader::from_path(file_path)?
-            .has_header(self.config.has_header)
-            .with_separator(self.config.delimiter)
-            .with_chunk_size(self.config.chunk_size)
-            .low_memory(true) // Enable low memory mode for streaming
-            .finish()?;
+        // Read through the retried chunk reader so a transient IO error
+        // doesn't lose the whole parse
+        let mut combined: Option<DataFrame> = None;
+        let nulls = self.parse_chunks(ChunkReader::open(&path, Some(b'"'))?, |batch| {
+            match combined.as_mut() {
+                Some(df) => {
+                    df.vstack_mut(&batch)?;
+                }
+                None => combined = Some(batch),
+            }
+            Ok(())
+        })?;
+        let df = combined.unwrap_or_else(DataFrame::empty);
+        Ok((df, RepairReport::default(), nulls))
+    }
 
-        // Report completion if callback is set
-        if let Some(callback) = &self.progress_callback {
-            callback(file_size as usize, file_size as usize);
+    /// Parse CSV in batches and process each batch with a callback
+    /// 
+    /// This method allows processing data in batches without loading
+    /// the entire dataset into memory. The file is read from disk in
+    /// record-aligned byte chunks as batches are needed, so memory is
+    /// bounded by `chunk_size` rows plus one chunk; failed reads are retried
+    /// per `configure(io_retries=...)` (see `ChunkReader`). The first chunk
+    /// fixes the schema for the rest. After each batch the progress callback
+    /// gets the bytes read so far and the file size.
+    /// 
+    /// # Arguments
+    /// * `file_path` - Path to the CSV file
+    /// * `batch_processor` - Function to process each batch
+    pub fn parse_batches<F>(&self, file_path: &str, batch_processor: F) -> Result<(), InsightoraError>
+    where
+        F: FnMut(DataFrame) -> Result<(), InsightoraError>,
+    {
+        self.parse_batches_checked(file_path, batch_processor).map(|_| ())
+    }
+
+    /// `parse_batches`, checking `config.not_null` on each batch before it is processed
+    ///
+    /// # Returns
+    /// * `Result<(RepairReport, NullabilityReport)>` - What was repaired and NOT NULL violations
+    pub fn parse_batches_checked<F>(&self, file_path: &str, batch_processor: F) -> Result<(RepairReport, NullabilityReport), InsightoraError>
+    where
+        F: FnMut(DataFrame) -> Result<(), InsightoraError>,
+    {
+        if self.config.repair.is_enabled() {
+            return self.batches_repaired(file_path, batch_processor);
+        }
+
+        let path = check_path_allowed(file_path)?;
+        if !path.exists() {
+            return Err(InsightoraError::IoError(
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("File not found: {}", file_path)
+                )
+            ));
+        }
+
+        let nulls = self.parse_chunks(ChunkReader::open(&path, Some(b'"'))?, batch_processor)?;
+        Ok((RepairReport::default(), nulls))
+    }
+
+    /// Parse record-aligned chunks from `reader` into `chunk_size`-row batches
+    fn parse_chunks<S, F>(&self, mut reader: ChunkReader<S>, mut batch_processor: F) -> Result<NullabilityReport, InsightoraError>
+    where
+        S: ByteSource,
+        F: FnMut(DataFrame) -> Result<(), InsightoraError>,
+    {
+        let _watch = memory::watch("parse_batches");
+        let chunk_size = self.config.chunk_size.max(1);
+        let mut nulls = NullCheck::new(&self.config.not_null);
+        let mut header: Vec<u8> = Vec::new();
+        let mut schema: Option<SchemaRef> = None;
+        let mut pending: Option<DataFrame> = None;
+
+        while let Some(mut chunk) = reader.next_chunk()? {
+            self.check_cancelled()?;
+            if self.config.has_header && header.is_empty() {
+                let cut = first_record_end(&chunk, Some(b'"')).unwrap_or(chunk.len());
+                header = chunk.drain(..cut).collect();
+                if header.last() != Some(&b'\n') {
+                    header.push(b'\n');
+                }
+            }
+            if chunk.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+
+            let mut bytes = Vec::with_capacity(header.len() + chunk.len());
+            bytes.extend_from_slice(&header);
+            bytes.extend_from_slice(&chunk);
+            let mut csv = CsvReader::new(Cursor::new(bytes))
+                .has_header(self.config.has_header)
+                .with_separator(self.config.delimiter);
+            if schema.is_some() {
+                csv = csv.with_schema(schema.clone());
+            }
+            let frame = csv.finish()?;
+            let first = schema.is_none();
+            if first {
+                schema = Some(Arc::new(frame.schema()));
+            }
+            let frame = self.filter_rows(frame, first)?;
+            let frame = rename_and_project(frame, self.config.rename.as_ref(), self.config.columns.as_deref())?;
+            let mut buffered = match pending.take() {
+                Some(mut df) => {
+                    df.vstack_mut(&frame)?;
+                    df
+                }
+                None => frame,
+            };
+
+            while buffered.height() >= chunk_size {
+                let rest = buffered.slice(chunk_size as i64, buffered.height() - chunk_size);
+                let batch = buffered.head(Some(chunk_size));
+                nulls.batch(&batch)?;
+                batch_processor(batch)?;
+                memory::checkpoint()?;
+                self.report(reader.offset() as usize, reader.size() as usize)?;
+                buffered = rest;
+            }
+            pending = Some(buffered);
+        }
+
+        if let Some(batch) = pending.filter(|df| df.height() > 0) {
+            nulls.batch(&batch)?;
+            batch_processor(batch)?;
+            memory::checkpoint()?;
+            self.report(reader.offset() as usize, reader.size() as usize)?;
+        }
+        Ok(nulls.finish())
+    }
+
+    /// Keep the rows of a parsed batch that `row_filter` selects, checking
+    /// the filter against the `first` batch's columns
+    ///
+    /// The filter sees the columns renamed, and the batch keeps its header names.
+    fn filter_rows(&self, frame: DataFrame, first: bool) -> Result<DataFrame, InsightoraError> {
+        let Some(filter) = &self.config.row_filter else {
+            return Ok(frame);
+        };
+        let renamed = rename_and_project(frame.clone(), self.config.rename.as_ref(), None)?;
+        let schema = renamed.schema();
+        if first {
+            filter.check(&schema)?;
         }
+        let mask = renamed.lazy().select([filter.mask(&schema)]).collect()?;
+        Ok(frame.filter(mask.get_columns()[0].bool()?)?)
+    }
+
+    /// Parse in streaming mode with `config.repair` applied batch by batch
+    ///
+    /// # Returns
+    /// * `Result<(DataFrame, RepairReport)>` - Combined batches and what was repaired
+    pub fn parse_streaming_repaired(&self, file_path: &str) -> Result<(DataFrame, RepairReport), InsightoraError> {
+        self.parse_streaming_checked(file_path).map(|(df, repairs, _)| (df, repairs))
+    }
 
-        Ok(df)
+    /// Read `chunk_size` lines at a time, repair them and parse each batch
+    ///
+    /// Only one batch of text is held at once. The first batch fixes the
+    /// schema for the rest, so every batch has the same columns and dtypes.
+    /// The progress callback gets the bytes of text read so far, against
+    /// the file size (0 for compressed files, whose size isn't known).
+    pub fn parse_batches_repaired<F>(&self, file_path: &str, batch_processor: F) -> Result<RepairReport, InsightoraError>
+    where
+        F: FnMut(DataFrame) -> Result<(), InsightoraError>,
+    {
+        self.batches_repaired(file_path, batch_processor).map(|(repairs, _)| repairs)
     }
 
-    /// Parse CSV in batches and process each batch with a callback
-    /// 
-    /// This method allows processing data in batches without loading
-    /// the entire dataset into memory.
-    /// 
-    /// # Arguments
-    /// * `file_path` - Path to the CSV file
-    /// * `batch_processor` - Function to process each batch
-    pub fn parse_batches<F>(&self, file_path: &str, mut batch_processor: F) -> Result<(), InsightoraError>
+    fn batches_repaired<F>(&self, file_path: &str, mut batch_processor: F) -> Result<(RepairReport, NullabilityReport), InsightoraError>
     where
         F: FnMut(DataFrame) -> Result<(), InsightoraError>,
     {
-        let path = Path::new(file_path);
+        if !self.config.has_header {
+            return Err(InsightoraError::ValidationError(
+                "Column-count repair needs a header row for the expected column count".to_string()
+            ));
+        }
+        if self.config.row_filter.is_some() {
+            return Err(InsightoraError::ValidationError(
+                "row_filter is not supported with column-count repair".to_string()
+            ));
+        }
+        let path = check_path_allowed(file_path)?;
         if !path.exists() {
             return Err(InsightoraError::IoError(
                 std::io::Error::new(
@@ -365,41 +2685,32 @@ impl StreamingCsvParser {
             ));
         }
 
-        // Create a batched reader
-        let reader = CsvReader::from_path(file_path)?
-            .has_header(self.config.has_header)
-            .with_separator(self.config.delimiter)
-            .with_chunk_size(self.config.chunk_size)
-            .low_memory(true);
-
-        // Process the entire file as one batch for now
-        // In a more advanced implementation, we could use Polars' batched reading
-        let df = reader.finish()?;
-        
-        // Process in chunks
-        let total_rows = df.height();
-        let mut start = 0;
-        
-        while start < total_rows {
-            let end = (start + self.config.chunk_size).min(total_rows);
-            let batch = df.slice(start as i64, end - start);
-            
-            batch_processor(batch)?;
-            
-            // Report progress
-            if let Some(callback) = &self.progress_callback {
-                callback(end, total_rows);
+        let _watch = memory::watch("parse_batches_repaired");
+        let mut nulls = NullCheck::new(&self.config.not_null);
+        let mut schema: Option<SchemaRef> = None;
+        let total = if Compression::detect(&path)?.is_compressed() { 0 } else { std::fs::metadata(&path)?.len() as usize };
+        let mut bytes = 0;
+        let repairs = repair_file(&path, self.config.delimiter, b'"', &self.config.repair, self.config.chunk_size, |text, flags| {
+            bytes += text.len();
+            let batch = read_repaired_batch(text, self.config.delimiter, b'"', Some(1000), schema.clone())?;
+            if schema.is_none() {
+                schema = Some(Arc::new(batch.schema()));
             }
-            
-            start = end;
-        }
+            let batch = rename_and_project(batch, self.config.rename.as_ref(), self.config.columns.as_deref())?;
+            nulls.batch(&batch)?;
+            batch_processor(with_repair_flags(batch, flags, &self.config.repair)?)?;
+            memory::checkpoint()?;
+            // Batches repeat the header, so the count can run past the file size
+            self.report(if total > 0 { bytes.min(total) } else { bytes }, total)
 
-        Ok(())
+        })?;
+        Ok((repairs, nulls.finish()))
     }
 
     /// Estimate memory usage for parsing a CSV file
     pub fn estimate_memory_usage(&self, file_path: &str) -> Result<usize, InsightoraError> {
-        let file_size = std::fs::metadata(file_path)
+        let path = check_path_allowed(file_path)?;
+        let file_size = std::fs::metadata(path)
             .map_err(InsightoraError::IoError)?
             .len();
         
@@ -422,6 +2733,33 @@ impl Default for StreamingCsvParser {
     }
 }
 
+/// Parse repaired CSV text (with header); `schema` pins the dtypes of later batches
+fn read_repaired_batch(
+    text: String,
+    delimiter: u8,
+    quote_char: u8,
+    infer_schema_length: Option<usize>,
+    schema: Option<SchemaRef>,
+) -> Result<DataFrame, InsightoraError> {
+    let mut reader = CsvReader::new(Cursor::new(text.into_bytes()))
+        .has_header(true)
+        .with_separator(delimiter)
+        .with_quote_char(Some(quote_char))
+        .infer_schema(infer_schema_length);
+    if schema.is_some() {
+        reader = reader.with_schema(schema);
+    }
+    Ok(reader.finish()?)
+}
+
+/// Append the per-row repaired flags when `flag_repairs` is set
+fn with_repair_flags(mut df: DataFrame, flags: Vec<bool>, repair: &CsvRepairOptions) -> Result<DataFrame, InsightoraError> {
+    if repair.flag_repairs {
+        df.with_column(Series::new(REPAIR_FLAG_COLUMN, flags))?;
+    }
+    Ok(df)
+}
+
 #[cfg(test)]
 mod streaming_tests {
     use super::*;
@@ -477,16 +2815,119 @@ mod streaming_tests {
         assert!(batch_count >= 10); // Should have at least 10 batches
     }
 
+    #[test]
+    fn test_cancel_flag_stops_both_parsers() {
+        let file = create_large_test_csv();
+        let path = file.path().to_str().unwrap();
+        let flag = Arc::new(AtomicBool::new(false));
+        let parser = StreamingCsvParser::with_config(StreamingCsvConfig {
+            chunk_size: 100,
+            ..Default::default()
+        })
+        .with_cancel_flag(Arc::clone(&flag));
+
+        let mut batches = 0;
+        let err = parser.parse_batches(path, |_| {
+            batches += 1;
+            flag.store(true, Ordering::Relaxed);
+            Ok(())
+        }).unwrap_err();
+        assert!(matches!(err, InsightoraError::Cancelled), "{}", err);
+        assert_eq!(batches, 1);
+        assert!(matches!(parser.parse_streaming(path), Err(InsightoraError::Cancelled)));
+
+        let parallel = ParallelCsvParser::new().with_cancel_flag(Arc::clone(&flag));
+        assert!(matches!(parallel.parse(path), Err(InsightoraError::Cancelled)));
+        flag.store(false, Ordering::Relaxed);
+        assert_eq!(parallel.parse(path).unwrap().height(), 1000);
+    }
+
+    #[test]
+    fn test_parse_batches_retries_flaky_chunks() {
+        use crate::io::chunk_reader::tests::{quick_retries, FlakySource};
+
+        let file = create_large_test_csv();
+        let text = std::fs::read(file.path()).unwrap();
+        let parser = StreamingCsvParser::with_config(StreamingCsvConfig {
+            chunk_size: 64,
+            ..Default::default()
+        });
+        let clean = parser.parse_streaming(file.path().to_str().unwrap()).unwrap();
+
+        let source = FlakySource::new(&text).fail(100..101, 2, Some(5)).fail(5_000..5_001, 1, None);
+        let reader = ChunkReader::new(source, "flaky.csv", 1024, Some(b'"'), quick_retries(3)).unwrap();
+        let mut batches: Vec<DataFrame> = Vec::new();
+        parser.parse_chunks(reader, |batch| {
+            batches.push(batch);
+            Ok(())
+        }).unwrap();
+        assert!(batches[..batches.len() - 1].iter().all(|batch| batch.height() == 64));
+        let mut combined = batches[0].clone();
+        for batch in &batches[1..] {
+            combined.vstack_mut(batch).unwrap();
+        }
+        assert!(combined.equals_missing(&clean));
+
+        let failing = FlakySource::new(&text).fail(5_000..5_001, 10, Some(5));
+        let reader = ChunkReader::new(failing, "flaky.csv", 1024, Some(b'"'), quick_retries(1)).unwrap();
+        let err = parser.parse_chunks(reader, |_| Ok(())).unwrap_err();
+        assert!(matches!(err, InsightoraError::ChunkRead { errno: Some(5), attempts: 2, .. }), "{}", err);
+    }
+
+    #[test]
+    fn test_not_null_checked_per_batch() {
+        use crate::io::nullability::{NotNullRules, NullPolicy};
+
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "id,value,category").unwrap();
+        for i in 0..1000 {
+            match i {
+                49 => writeln!(file, "{},,NULL", i).unwrap(),
+                700 => writeln!(file, "{},,{}", i, i % 5).unwrap(),
+                _ => writeln!(file, "{},{},{}", i, i * 10, i % 5).unwrap(),
+            }
+        }
+        let path = file.path().to_str().unwrap();
+        let rules = NotNullRules::new(vec!["value".into(), "category".into()]).with_null_tokens(vec!["NULL".into()]);
+
+        let parser = StreamingCsvParser::with_config(StreamingCsvConfig {
+            chunk_size: 10,
+            not_null: rules.clone(),
+            ..Default::default()
+        });
+        let (_, nulls) = parser.parse_batches_checked(path, |_| Ok(())).unwrap();
+        assert_eq!(nulls.rows_checked, 1000);
+        assert_eq!(nulls.violations[0].sample_rows, [50, 701]);
+        assert_eq!(nulls.violations[1].sample_rows, [50]);
+
+        // Strict mode stops at the batch holding row 50 instead of reading on
+        let strict = StreamingCsvParser::with_config(StreamingCsvConfig {
+            chunk_size: 10,
+            not_null: rules.clone().with_policy(NullPolicy::Strict),
+            ..Default::default()
+        });
+        let mut processed = 0;
+        let err = strict.parse_batches(path, |_| {
+            processed += 1;
+            Ok(())
+        }).unwrap_err();
+        assert!(matches!(err, InsightoraError::NotNullViolation { ref column, row: 50 } if column == "value"), "{}", err);
+        assert_eq!(processed, 4);
+
+        // The in-memory path agrees
+        let parallel = ParallelCsvParser::with_config(CsvParserConfig { not_null: rules, ..Default::default() });
+        let (_, _, whole, ..) = parallel.parse_checked(path).unwrap();
+        assert_eq!(whole, nulls);
+    }
+
     #[test]
     fn test_estimate_memory() {
         let file = create_large_test_csv();
         let parser = StreamingCsvParser::new();
         let result = parser.estimate_memory_usage(file.path().to_str().unwrap());
         
-        assert!(result.is_ok());
-        let estimated_mb = result.unwrap();
         // File is small, so estimated memory might be 0 MB
-        assert!(estimated_mb >= 0);
+        assert!(result.is_ok());
     }
 
     #[test]
@@ -501,3 +2942,789 @@ mod streaming_tests {
         assert!(result.is_ok());
     }
 }
+
+// ============================================================================
+// CSV Writing
+// ============================================================================
+
+use std::collections::HashMap;
+use std::io::{BufWriter, Write};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use polars::export::chrono::format::{Item, StrftimeItems};
+use polars::export::chrono::{DateTime, NaiveDate};
+use crate::dataframe::transformations::{NumberLocale, ValueFormat};
+
+/// Days from 0001-01-01 (CE) to 1970-01-01
+const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+/// When fields are wrapped in the quote character
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuoteStyle {
+    /// Only fields that would otherwise not read back: containing the
+    /// delimiter, quote character or a line break, or equal to the null token
+    #[default]
+    Necessary,
+    /// Every field, including the header
+    Always,
+    /// No field; values containing the delimiter or line breaks won't read back
+    Never,
+    /// Every non-null value of non-numeric columns, and the header
+    NonNumeric,
+}
+
+impl QuoteStyle {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "necessary" => Ok(QuoteStyle::Necessary),
+            "always" => Ok(QuoteStyle::Always),
+            "never" => Ok(QuoteStyle::Never),
+            "non_numeric" => Ok(QuoteStyle::NonNumeric),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown quote_style '{}'; expected necessary, always, never or non_numeric",
+                other
+            ))),
+        }
+    }
+}
+
+/// How quote characters inside quoted fields are escaped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EscapeStyle {
+    /// Double the quote character (RFC 4180; what our parser reads)
+    #[default]
+    Double,
+    /// Prefix quote characters and backslashes with a backslash
+    Backslash,
+}
+
+impl EscapeStyle {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "double" => Ok(EscapeStyle::Double),
+            "backslash" => Ok(EscapeStyle::Backslash),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown escape '{}'; expected double or backslash",
+                other
+            ))),
+        }
+    }
+}
+
+/// CSV dialect for writing
+#[derive(Debug, Clone)]
+pub struct CsvWriteOptions {
+    pub delimiter: u8,
+    pub quote_char: u8,
+    pub include_header: bool,
+    pub quote_style: QuoteStyle,
+    pub escape: EscapeStyle,
+    /// Written for null values
+    pub null_value: String,
+    pub line_terminator: String,
+    /// strftime-style formats for Date/Datetime columns, by column name
+    /// (unlisted columns use ISO 8601). Datetimes are written in UTC.
+    pub datetime_formats: HashMap<String, String>,
+    /// Digits after the decimal point for float columns
+    pub float_precision: Option<usize>,
+    /// Display formats by column name, applied at write time only; formatted
+    /// fields are text, so `QuoteStyle::NonNumeric` quotes them
+    pub value_formats: HashMap<String, ValueFormat>,
+    /// Separators used by `value_formats`
+    pub number_locale: NumberLocale,
+    /// Rows serialized per batch; columns within a batch are serialized in parallel
+    pub batch_rows: usize,
+    /// Add the rows to an existing file instead of replacing it; the header
+    /// is only written when the file is new or empty
+    pub append: bool,
+}
+
+impl Default for CsvWriteOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote_char: b'"',
+            include_header: true,
+            quote_style: QuoteStyle::Necessary,
+            escape: EscapeStyle::Double,
+            null_value: String::new(),
+            line_terminator: "\n".to_string(),
+            datetime_formats: HashMap::new(),
+            float_precision: None,
+            value_formats: HashMap::new(),
+            number_locale: NumberLocale::default(),
+            batch_rows: 65_536,
+            append: false,
+        }
+    }
+}
+
+impl CsvWriteOptions {
+    fn validate(&self, df: &DataFrame) -> Result<(), InsightoraError> {
+        if !self.delimiter.is_ascii() || !self.quote_char.is_ascii() || self.delimiter == self.quote_char {
+            return Err(InsightoraError::ValidationError(
+                "delimiter and quote_char must be distinct ASCII characters".to_string(),
+            ));
+        }
+        if self.line_terminator.is_empty() {
+            return Err(InsightoraError::ValidationError("line_terminator must not be empty".to_string()));
+        }
+        for (column, format) in &self.datetime_formats {
+            let dtype = df.column(column)?.dtype();
+            if !matches!(dtype, DataType::Date | DataType::Datetime(_, _)) {
+                return Err(InsightoraError::InvalidDataType {
+                    expected: "Date or Datetime".to_string(),
+                    actual: format!("{} ({})", dtype, column),
+                });
+            }
+            if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+                return Err(InsightoraError::ValidationError(format!(
+                    "Invalid datetime format '{}' for column '{}'",
+                    format, column
+                )));
+            }
+        }
+        for (column, format) in &self.value_formats {
+            format.validate(column, df.column(column)?.dtype())?;
+        }
+        Ok(())
+    }
+}
+
+/// Write a DataFrame to a CSV file with the given dialect
+///
+/// A path ending in `.gz` is written gzip-compressed; appending to one adds
+/// a gzip member, which gzip readers (and our parsers) read as one stream.
+/// With `options.append`, an existing file's header must match the
+/// DataFrame's columns.
+///
+/// # Returns
+/// * `Result<usize>` - Number of data rows written
+pub fn write_csv(df: &DataFrame, file_path: &str, options: &CsvWriteOptions) -> Result<usize, InsightoraError> {
+    options.validate(df)?;
+    let path = check_path_allowed(file_path)?;
+    let gzip = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("gz"));
+    let existing = options.append && std::fs::metadata(&path).is_ok_and(|meta| meta.len() > 0);
+    if existing {
+        check_appended_header(df, &path, options)?;
+    }
+    let appended = existing.then(|| CsvWriteOptions { include_header: false, ..options.clone() });
+    let options = appended.as_ref().unwrap_or(options);
+
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(options.append)
+        .truncate(!options.append)
+        .open(&path)?;
+    let mut writer = BufWriter::new(file);
+    if gzip {
+        let mut encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+        write_csv_to(df, &mut encoder, options)?;
+        encoder.finish()?.flush()?;
+    } else {
+        write_csv_to(df, &mut writer, options)?;
+        writer.flush()?;
+    }
+    Ok(df.height())
+}
+
+/// Fail unless the first line of the file at `path` is the header `df` would be written with
+fn check_appended_header(df: &DataFrame, path: &Path, options: &CsvWriteOptions) -> Result<(), InsightoraError> {
+    let first = read_decompressed_lines(path, Compression::detect(path)?, 1)?;
+    let found = String::from_utf8_lossy(&first);
+    let expected = csv_header(df, &CsvWriteOptions { include_header: true, ..options.clone() });
+    if found.trim_end_matches(['\r', '\n']) != expected.trim_end_matches(['\r', '\n']) {
+        return Err(InsightoraError::ValidationError(format!(
+            "Can't append to {}: its header is '{}' but the data's columns are '{}'",
+            path.display(),
+            found.trim_end_matches(['\r', '\n']),
+            expected.trim_end_matches(['\r', '\n'])
+        )));
+    }
+    Ok(())
+}
+
+/// `write_csv` as a DataFrame method
+pub trait CsvWriterExt {
+    /// Write to `file_path` as `write_csv` does, returning the rows written
+    fn write(&self, file_path: &str, options: &CsvWriteOptions) -> Result<usize, InsightoraError>;
+}
+
+impl CsvWriterExt for DataFrame {
+    fn write(&self, file_path: &str, options: &CsvWriteOptions) -> Result<usize, InsightoraError> {
+        write_csv(self, file_path, options)
+    }
+}
+
+/// Serialize a DataFrame as CSV into any writer
+///
+/// Rows are processed in batches of `batch_rows`; within a batch each column
+/// is formatted on the Rayon pool, then rows are assembled in order.
+pub fn write_csv_to<W: Write>(df: &DataFrame, writer: &mut W, options: &CsvWriteOptions) -> Result<(), InsightoraError> {
+    options.validate(df)?;
+    let delimiter = options.delimiter as char;
+
+    if options.include_header {
+        writer.write_all(csv_header(df, options).as_bytes())?;
+    }
+
+    let batch_rows = options.batch_rows.max(1);
+    let mut line = String::new();
+    let mut offset = 0;
+    while offset < df.height() {
+        let len = batch_rows.min(df.height() - offset);
+        let batch = df.slice(offset as i64, len);
+        let columns = batch
+            .get_columns()
+            .par_iter()
+            .map(|series| serialize_column(series, options))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for row in 0..len {
+            line.clear();
+            for (i, column) in columns.iter().enumerate() {
+                if i > 0 {
+                    line.push(delimiter);
+                }
+                line.push_str(&column[row]);
+            }
+            line.push_str(&options.line_terminator);
+            writer.write_all(line.as_bytes())?;
+        }
+        offset += len;
+    }
+    Ok(())
+}
+
+/// Default part file name; `{index}` is replaced by the zero-padded part number
+pub const DEFAULT_PART_TEMPLATE: &str = "part_{index}.csv";
+
+/// Manifest written next to the part files
+pub const CSV_MANIFEST_FILE: &str = "manifest.json";
+
+/// How `write_csv_partitioned` splits its output
+#[derive(Debug, Clone)]
+pub struct CsvPartitionOptions {
+    pub max_rows_per_file: usize,
+    /// Part file name containing `{index}`, padded to at least 4 digits
+    pub filename_template: String,
+    /// Also concatenate the parts into this file once they are written
+    pub combine_to: Option<String>,
+}
+
+impl CsvPartitionOptions {
+    pub fn new(max_rows_per_file: usize) -> Self {
+        Self {
+            max_rows_per_file,
+            filename_template: DEFAULT_PART_TEMPLATE.to_string(),
+            combine_to: None,
+        }
+    }
+}
+
+/// One part file written by `write_csv_partitioned`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvPart {
+    /// Path under the directory as given
+    pub file: String,
+    pub rows: usize,
+    pub bytes: u64,
+}
+
+/// Files written by `write_csv_partitioned`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PartitionedCsvReport {
+    /// Parts in row order: the first part holds the first rows
+    pub parts: Vec<CsvPart>,
+    pub rows: usize,
+    pub manifest: String,
+    pub combined: Option<String>,
+}
+
+/// Write a DataFrame as numbered CSV part files in parallel
+///
+/// Rows are sliced in order into parts of at most `max_rows_per_file` rows,
+/// and each part is serialized by its own Rayon task, with the header when
+/// `options.include_header` is set. A `manifest.json` listing every part
+/// (file name, rows, bytes) is written last. With `combine_to` the parts are
+/// then concatenated, header once, into a single file; only that copy is
+/// sequential. An empty frame still gets one (header-only) part.
+///
+/// # Arguments
+/// * `df` - Data to write
+/// * `directory` - Output directory, created if needed (subject to the path policy)
+/// * `partition` - Part size, file names and optional combined file
+/// * `options` - CSV dialect shared by every part
+/// * `progress` - Reports rows written, then rows concatenated
+///
+/// # Returns
+/// * `Result<PartitionedCsvReport>` - Parts in row order, manifest and combined file
+pub fn write_csv_partitioned(
+    df: &DataFrame,
+    directory: &str,
+    partition: &CsvPartitionOptions,
+    options: &CsvWriteOptions,
+    progress: &ProgressReporter,
+) -> Result<PartitionedCsvReport, InsightoraError> {
+    if partition.max_rows_per_file == 0 {
+        return Err(InsightoraError::ValidationError("max_rows_per_file must be at least 1".to_string()));
+    }
+    let template = &partition.filename_template;
+    if !template.contains("{index}") || template.contains(['/', '\\']) {
+        return Err(InsightoraError::ValidationError(format!(
+            "filename_template '{}' must be a file name containing '{{index}}'",
+            template
+        )));
+    }
+    if template.replace("{index}", "") == CSV_MANIFEST_FILE {
+        return Err(InsightoraError::ValidationError(format!("filename_template must not produce '{}'", CSV_MANIFEST_FILE)));
+    }
+    options.validate(df)?;
+    let root = check_path_allowed(directory)?;
+    let combined = partition.combine_to.as_deref().map(check_path_allowed).transpose()?;
+    std::fs::create_dir_all(&root)?;
+
+    let rows_per_part = partition.max_rows_per_file;
+    let count = df.height().div_ceil(rows_per_part).max(1);
+    let width = (count - 1).to_string().len().max(4);
+    let names: Vec<String> = (0..count)
+        .map(|index| template.replace("{index}", &format!("{:0width$}", index, width = width)))
+        .collect();
+
+    let total = df.height() as f64;
+    let stages = progress.stages(&[
+        ("write parts", total),
+        ("concatenate", if combined.is_some() { total } else { 0.0 }),
+    ]);
+    let written = AtomicUsize::new(0);
+    let parts = names
+        .par_iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let part = df.slice((index * rows_per_part) as i64, rows_per_part);
+            let path = root.join(name);
+            let mut writer = BufWriter::new(File::create(&path)?);
+            write_csv_to(&part, &mut writer, options)?;
+            writer.flush()?;
+            drop(writer);
+
+            let done = written.fetch_add(part.height(), Ordering::Relaxed) + part.height();
+            stages[0].report(done as f64 / total.max(1.0), name);
+            Ok(CsvPart {
+                file: Path::new(directory).join(name).to_string_lossy().into_owned(),
+                rows: part.height(),
+                bytes: std::fs::metadata(&path)?.len(),
+            })
+        })
+        .collect::<Result<Vec<_>, InsightoraError>>()?;
+    stages[0].finish(&format!("{} parts", parts.len()));
+
+    let manifest = serde_json::json!({
+        "columns": df.get_column_names(),
+        "rows": df.height(),
+        "header": options.include_header,
+        "parts": names.iter().zip(&parts).map(|(name, part)| serde_json::json!({
+            "file": name,
+            "rows": part.rows,
+            "bytes": part.bytes,
+        })).collect::<Vec<_>>(),
+        "combined": partition.combine_to,
+    });
+    let mut writer = BufWriter::new(File::create(root.join(CSV_MANIFEST_FILE))?);
+    serde_json::to_writer_pretty(&mut writer, &manifest).map_err(std::io::Error::from)?;
+    writer.flush()?;
+
+    if let Some(target) = &combined {
+        // Every part starts with the same header; keep only the first
+        let header = if options.include_header { csv_header(df, options) } else { String::new() };
+        let mut writer = BufWriter::new(File::create(target)?);
+        writer.write_all(header.as_bytes())?;
+        let mut copied = 0;
+        for (name, part) in names.iter().zip(&parts) {
+            let mut reader = File::open(root.join(name))?;
+            reader.seek(SeekFrom::Start(header.len() as u64))?;
+            std::io::copy(&mut reader, &mut writer)?;
+            copied += part.rows;
+            stages[1].report(copied as f64 / total.max(1.0), name);
+        }
+        writer.flush()?;
+        stages[1].finish("combined");
+    }
+
+    Ok(PartitionedCsvReport {
+        rows: df.height(),
+        manifest: Path::new(directory).join(CSV_MANIFEST_FILE).to_string_lossy().into_owned(),
+        combined: partition.combine_to.clone(),
+        parts,
+    })
+}
+
+/// Header line of `df` including the line terminator
+fn csv_header(df: &DataFrame, options: &CsvWriteOptions) -> String {
+    let header: Vec<String> = df
+        .get_column_names()
+        .iter()
+        .map(|name| quote_field(name, false, options))
+        .collect();
+    let mut line = header.join(&(options.delimiter as char).to_string());
+    line.push_str(&options.line_terminator);
+    line
+}
+
+/// Format every value of a column as a finished (quoted/escaped) field
+fn serialize_column(series: &Series, options: &CsvWriteOptions) -> Result<Vec<String>, InsightoraError> {
+    if let Some(format) = options.value_formats.get(series.name()) {
+        let rendered = format.format_series(series, &options.number_locale)?;
+        return Ok(rendered
+            .str()?
+            .into_iter()
+            .map(|value| match value {
+                Some(text) => quote_field(text, false, options),
+                None => options.null_value.clone(),
+            })
+            .collect());
+    }
+
+    let format = options.datetime_formats.get(series.name()).map(String::as_str);
+    let values: Vec<Option<String>> = match series.dtype() {
+        DataType::String => series.str()?.into_iter().map(|v| v.map(str::to_string)).collect(),
+        DataType::Boolean => series.bool()?.into_iter().map(|v| v.map(|b| b.to_string())).collect(),
+        DataType::Float32 | DataType::Float64 => {
+            let floats = series.cast(&DataType::Float64)?;
+            floats.f64()?.into_iter().map(|v| v.map(|x| format_float(x, options.float_precision))).collect()
+        }
+        DataType::UInt64 => series.u64()?.into_iter().map(|v| v.map(|x| x.to_string())).collect(),
+        dtype if dtype.is_integer() => {
+            let ints = series.cast(&DataType::Int64)?;
+            ints.i64()?.into_iter().map(|v| v.map(|x| x.to_string())).collect()
+        }
+        DataType::Date => {
+            let days = series.to_physical_repr();
+            days.i32()?.into_iter().map(|v| v.map(|d| format_date(d, format))).collect()
+        }
+        DataType::Datetime(unit, _) => {
+            let unit = *unit;
+            let stamps = series.to_physical_repr();
+            stamps.i64()?.into_iter().map(|v| v.map(|t| format_datetime(t, unit, format))).collect()
+        }
+        _ => {
+            let strings = series.cast(&DataType::String)?;
+            strings.str()?.into_iter().map(|v| v.map(str::to_string)).collect()
+        }
+    };
+
+    let numeric = series.dtype().is_numeric();
+    Ok(values
+        .into_iter()
+        .map(|value| match value {
+            Some(text) => quote_field(&text, numeric, options),
+            None => options.null_value.clone(),
+        })
+        .collect())
+}
+
+/// Shortest round-tripping form (always with a decimal point or exponent), or fixed digits
+fn format_float(value: f64, precision: Option<usize>) -> String {
+    match precision {
+        Some(digits) => format!("{:.*}", digits, value),
+        None => format!("{:?}", value),
+    }
+}
+
+pub(crate) fn format_date(days: i32, format: Option<&str>) -> String {
+    match NaiveDate::from_num_days_from_ce_opt(days.saturating_add(UNIX_EPOCH_DAYS_FROM_CE)) {
+        Some(date) => date.format(format.unwrap_or("%Y-%m-%d")).to_string(),
+        None => days.to_string(),
+    }
+}
+
+pub(crate) fn format_datetime(value: i64, unit: TimeUnit, format: Option<&str>) -> String {
+    let per_second: i64 = match unit {
+        TimeUnit::Nanoseconds => 1_000_000_000,
+        TimeUnit::Microseconds => 1_000_000,
+        TimeUnit::Milliseconds => 1_000,
+    };
+    let seconds = value.div_euclid(per_second);
+    let nanos = (value.rem_euclid(per_second) * (1_000_000_000 / per_second)) as u32;
+    match DateTime::from_timestamp(seconds, nanos) {
+        Some(datetime) => datetime
+            .naive_utc()
+            .format(format.unwrap_or("%Y-%m-%dT%H:%M:%S%.f"))
+            .to_string(),
+        None => value.to_string(),
+    }
+}
+
+/// Apply the quoting and escaping policy to one field
+fn quote_field(text: &str, numeric: bool, options: &CsvWriteOptions) -> String {
+    let quote = options.quote_char as char;
+    let delimiter = options.delimiter as char;
+    let backslash = options.escape == EscapeStyle::Backslash;
+    let needs_quotes = match options.quote_style {
+        QuoteStyle::Always => true,
+        QuoteStyle::Never => false,
+        QuoteStyle::NonNumeric => !numeric,
+        QuoteStyle::Necessary => {
+            text == options.null_value
+                || text.contains([delimiter, quote, '\n', '\r'])
+                || (backslash && text.contains('\\'))
+        }
+    };
+    if !needs_quotes {
+        return text.to_string();
+    }
+
+    let mut field = String::with_capacity(text.len() + 2);
+    field.push(quote);
+    for c in text.chars() {
+        if c == quote {
+            field.push(if backslash { '\\' } else { quote });
+        } else if backslash && c == '\\' {
+            field.push('\\');
+        }
+        field.push(c);
+    }
+    field.push(quote);
+    field
+}
+
+#[cfg(test)]
+mod writer_tests {
+    use super::*;
+    use proptest::prelude::*;
+    use tempfile::NamedTempFile;
+
+    fn to_text(df: &DataFrame, options: &CsvWriteOptions) -> String {
+        let mut out = Vec::new();
+        write_csv_to(df, &mut out, options).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn sample() -> DataFrame {
+        df! {
+            "name" => [Some("plain"), Some("a,b"), Some("say \"hi\""), None],
+            "n" => [Some(1i64), None, Some(3), Some(4)],
+        }
+        .unwrap()
+    }
+
+    #[test]
+    fn test_quote_styles_and_null_token() {
+        let necessary = to_text(&sample(), &CsvWriteOptions::default());
+        assert_eq!(necessary, "name,n\nplain,1\n\"a,b\",\n\"say \"\"hi\"\"\",3\n,4\n");
+
+        let options = CsvWriteOptions {
+            quote_style: QuoteStyle::NonNumeric,
+            null_value: "NULL".to_string(),
+            line_terminator: "\r\n".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_text(&sample(), &options),
+            "\"name\",\"n\"\r\n\"plain\",1\r\n\"a,b\",NULL\r\n\"say \"\"hi\"\"\",3\r\nNULL,4\r\n"
+        );
+
+        let options = CsvWriteOptions {
+            quote_style: QuoteStyle::Never,
+            escape: EscapeStyle::Backslash,
+            include_header: false,
+            ..Default::default()
+        };
+        assert!(to_text(&sample(), &options).starts_with("plain,1\na,b,\n"));
+    }
+
+    #[test]
+    fn test_write_csv_round_trips_gzip_and_appends() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let source = dir.path().join("orders.csv");
+        std::fs::write(&source, "id,note,amount\n1,\"a,b\",2.5\n2,\"two\nlines\",\n").unwrap();
+        let parsed = ParallelCsvParser::new().parse(source.to_str().unwrap()).unwrap();
+
+        for name in ["copy.csv", "copy.csv.gz"] {
+            let target = dir.path().join(name).to_string_lossy().into_owned();
+            assert_eq!(parsed.write(&target, &CsvWriteOptions::default()).unwrap(), 2);
+            let again = ParallelCsvParser::new().parse(&target).unwrap();
+            assert_eq!(again.get_column_names(), parsed.get_column_names());
+            assert!(again.equals_missing(&parsed), "{} != {}", again, parsed);
+        }
+
+        let appended = dir.path().join("log.csv.gz").to_string_lossy().into_owned();
+        let append = CsvWriteOptions { append: true, ..Default::default() };
+        write_csv(&parsed, &appended, &append).unwrap();
+        write_csv(&parsed, &appended, &append).unwrap();
+        assert_eq!(ParallelCsvParser::new().parse(&appended).unwrap().height(), 4);
+
+        let other = df! { "id" => [3i64] }.unwrap();
+        let message = write_csv(&other, &appended, &append).unwrap_err().to_string();
+        assert!(message.contains("its header is 'id,note,amount'"), "{}", message);
+    }
+
+    #[test]
+    fn test_backslash_escape_and_null_lookalikes() {
+        let df = df! { "s" => ["NULL", "back\\slash", "q\"q"] }.unwrap();
+        let options = CsvWriteOptions {
+            escape: EscapeStyle::Backslash,
+            null_value: "NULL".to_string(),
+            include_header: false,
+            ..Default::default()
+        };
+        // A real string equal to the null token is quoted so it stays distinct
+        assert_eq!(to_text(&df, &options), "\"NULL\"\n\"back\\\\slash\"\n\"q\\\"q\"\n");
+    }
+
+    #[test]
+    fn test_per_column_datetime_formats() {
+        let df = df! {
+            "day" => [Some(19_723i32), None],
+            "at" => [Some(1_704_067_200_000_000i64 + 1_500_000), Some(0)],
+        }
+        .unwrap()
+        .lazy()
+        .with_columns([
+            col("day").cast(DataType::Date),
+            col("at").cast(DataType::Datetime(TimeUnit::Microseconds, None)),
+        ])
+        .collect()
+        .unwrap();
+
+        let options = CsvWriteOptions {
+            datetime_formats: HashMap::from([("day".to_string(), "%d/%m/%Y".to_string())]),
+            include_header: false,
+            ..Default::default()
+        };
+        assert_eq!(to_text(&df, &options), "01/01/2024,2024-01-01T00:00:01.500\n,1970-01-01T00:00:00\n");
+
+        let bad = CsvWriteOptions {
+            datetime_formats: HashMap::from([("day".to_string(), "%Q".to_string())]),
+            ..Default::default()
+        };
+        assert!(write_csv_to(&df, &mut Vec::new(), &bad).is_err());
+    }
+
+    #[test]
+    fn test_value_formats_apply_at_write_time_only() {
+        use crate::dataframe::transformations::FormatKind;
+
+        let df = df! { "amount" => [Some(1234.5f64), None], "qty" => [3i64, 4] }.unwrap();
+        let dollars = ValueFormat::new(FormatKind::Currency {
+            symbol: "$".to_string(),
+            placement: Default::default(),
+            decimals: Some(2),
+        });
+        let options = CsvWriteOptions {
+            value_formats: HashMap::from([("amount".to_string(), dollars.with_null_text("-"))]),
+            include_header: false,
+            ..Default::default()
+        };
+        assert_eq!(to_text(&df, &options), "\"$1,234.50\",3\n-,4\n");
+        assert_eq!(df.column("amount").unwrap().dtype(), &DataType::Float64);
+
+        let on_text = df! { "s" => ["x"] }.unwrap();
+        let options = CsvWriteOptions {
+            value_formats: HashMap::from([("s".to_string(), ValueFormat::new(FormatKind::Percent { decimals: None, thousands: false }))]),
+            ..Default::default()
+        };
+        assert!(write_csv_to(&on_text, &mut Vec::new(), &options).is_err());
+    }
+
+    #[test]
+    fn test_always_quoted_strings_round_trip() {
+        let df = df! { "text" => ["x,y", "multi\nline", "q\"uote"] }.unwrap();
+        let file = NamedTempFile::new().unwrap();
+        let path = file.path().to_str().unwrap();
+        let options = CsvWriteOptions { quote_style: QuoteStyle::Always, ..Default::default() };
+        write_csv(&df, path, &options).unwrap();
+
+        let parsed = ParallelCsvParser::with_config(CsvParserConfig::default()).parse(path).unwrap();
+        assert!(parsed.equals_missing(&df));
+    }
+
+    fn dialect() -> impl Strategy<Value = CsvWriteOptions> {
+        (
+            prop::sample::select(vec![b',', b';', b'|', b'\t']),
+            prop::sample::select(vec![b'"', b'\'']),
+            prop::sample::select(vec![QuoteStyle::Necessary, QuoteStyle::NonNumeric]),
+            prop::sample::select(vec!["\n", "\r\n"]),
+        )
+            .prop_map(|(delimiter, quote_char, quote_style, terminator)| CsvWriteOptions {
+                delimiter,
+                quote_char,
+                quote_style,
+                line_terminator: terminator.to_string(),
+                batch_rows: 7,
+                ..Default::default()
+            })
+    }
+
+    #[test]
+    fn test_partitioned_parts_follow_row_order() {
+        let df = df! {
+            "id" => (0..10i64).collect::<Vec<_>>(),
+            "label" => (0..10).map(|i| format!("row {}", i)).collect::<Vec<_>>(),
+        }
+        .unwrap();
+        let dir = tempfile::TempDir::new().unwrap();
+        let directory = dir.path().join("export").to_string_lossy().into_owned();
+        let combined = dir.path().join("all.csv").to_string_lossy().into_owned();
+        let partition = CsvPartitionOptions {
+            combine_to: Some(combined.clone()),
+            ..CsvPartitionOptions::new(3)
+        };
+        let options = CsvWriteOptions { batch_rows: 2, ..Default::default() };
+
+        let report = write_csv_partitioned(&df, &directory, &partition, &options, &ProgressReporter::disabled()).unwrap();
+        assert_eq!(report.parts.iter().map(|p| p.rows).collect::<Vec<_>>(), [3, 3, 3, 1]);
+        assert!(report.parts[0].file.ends_with("part_0000.csv"));
+
+        let manifest: serde_json::Value =
+            serde_json::from_reader(File::open(dir.path().join("export").join(CSV_MANIFEST_FILE)).unwrap()).unwrap();
+        let files: Vec<&str> = manifest["parts"].as_array().unwrap().iter().map(|p| p["file"].as_str().unwrap()).collect();
+        assert_eq!(files, ["part_0000.csv", "part_0001.csv", "part_0002.csv", "part_0003.csv"]);
+
+        // Reassembling the parts in manifest order gives back the input
+        let mut reassembled = DataFrame::default();
+        for (file, part) in files.iter().zip(&report.parts) {
+            let path = dir.path().join("export").join(file);
+            assert_eq!(std::fs::metadata(&path).unwrap().len(), part.bytes);
+            let parsed = ParallelCsvParser::new().parse(path.to_str().unwrap()).unwrap();
+            reassembled = if reassembled.width() == 0 { parsed } else { reassembled.vstack(&parsed).unwrap() };
+        }
+        assert!(reassembled.equals(&df), "{:?}", reassembled);
+        assert_eq!(std::fs::read_to_string(&combined).unwrap(), to_text(&df, &options));
+
+        let bad = CsvPartitionOptions { filename_template: "part.csv".into(), ..CsvPartitionOptions::new(3) };
+        assert!(write_csv_partitioned(&df, &directory, &bad, &options, &ProgressReporter::disabled()).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_round_trips_through_parser(
+            options in dialect(),
+            // Leading "s" keeps type inference on strings (never "true"/"1.5")
+            texts in prop::collection::vec("s[a-z ,;|\t'\"\n]{0,12}", 1..40),
+            numbers in prop::collection::vec(prop::option::of(-1_000_000i64..1_000_000), 1..40),
+        ) {
+            let rows = texts.len().min(numbers.len());
+            let mut numbers = numbers[..rows].to_vec();
+            numbers[0] = Some(numbers[0].unwrap_or(0));
+            let df = df! {
+                "text" => &texts[..rows],
+                "number" => numbers,
+            }
+            .unwrap();
+
+            let file = NamedTempFile::new().unwrap();
+            let path = file.path().to_str().unwrap();
+            write_csv(&df, path, &options).unwrap();
+
+            let parsed = ParallelCsvParser::with_config(CsvParserConfig {
+                delimiter: options.delimiter,
+                quote_char: options.quote_char,
+                ..Default::default()
+            })
+            .parse(path)
+            .unwrap();
+            prop_assert!(parsed.equals_missing(&df), "{:?}\n{:?}", parsed, df);
+        }
+    }
+}
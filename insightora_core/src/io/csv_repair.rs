@@ -0,0 +1,431 @@
+// CSV column-count repair
+// Re-joins fields split by unescaped delimiters in "almost CSV" files
+
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use crate::error::InsightoraError;
+use crate::io::chunk_reader::{from_read_error, ChunkReader};
+use crate::io::compression::{open_decompressed, Compression};
+use crate::utils::warnings::{AggregatedWarning, WarningCollector};
+
+/// Name of the per-row flag column added with `flag_repairs`
+pub const REPAIR_FLAG_COLUMN: &str = "_repaired";
+
+/// Aggregated warning emitted for each repaired row
+const REPAIRED_ROWS: &str = "{count} rows had surplus fields merged into column {column}";
+
+// ============================================================================
+// Options and Report
+// ============================================================================
+
+/// Repair applied to rows whose field count doesn't match the header
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CsvRepair {
+    /// Parse rows as they are
+    #[default]
+    None,
+    /// Merge surplus fields back into one absorber column
+    ColumnCount,
+}
+
+impl CsvRepair {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" => Ok(CsvRepair::None),
+            "column_count" => Ok(CsvRepair::ColumnCount),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown CSV repair '{}'; expected 'none' or 'column_count'",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CsvRepair::None => "none",
+            CsvRepair::ColumnCount => "column_count",
+        }
+    }
+}
+
+/// Settings for CSV repair
+#[derive(Debug, Clone)]
+pub struct CsvRepairOptions {
+    pub mode: CsvRepair,
+    /// Column receiving the surplus fields (default: last text column in the sample)
+    pub absorber: Option<String>,
+    /// Append a boolean `REPAIR_FLAG_COLUMN` marking repaired rows
+    pub flag_repairs: bool,
+    /// Leading rows inspected to choose the default absorber
+    pub sample_rows: usize,
+    /// Repairs recorded individually in the report (all are counted)
+    pub max_logged: usize,
+}
+
+impl Default for CsvRepairOptions {
+    fn default() -> Self {
+        Self {
+            mode: CsvRepair::None,
+            absorber: None,
+            flag_repairs: false,
+            sample_rows: 1000,
+            max_logged: 100,
+        }
+    }
+}
+
+impl CsvRepairOptions {
+    /// True when rows are rewritten before parsing
+    pub fn is_enabled(&self) -> bool {
+        self.mode != CsvRepair::None
+    }
+}
+
+/// One repaired row
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowRepair {
+    /// 1-based line number in the file (the header is line 1)
+    pub line: usize,
+    /// Fields found before the repair
+    pub fields: usize,
+    /// Value the absorber column received
+    pub value: String,
+}
+
+/// Outcome of a repair pass
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepairReport {
+    pub mode: CsvRepair,
+    /// Column that received surplus fields
+    pub absorber: Option<String>,
+    pub rows_repaired: usize,
+    /// The first `max_logged` repairs, in file order
+    pub repairs: Vec<RowRepair>,
+    /// Repaired rows as one aggregated warning, with sample line numbers
+    pub warnings: Vec<AggregatedWarning>,
+}
+
+// ============================================================================
+// Column-Count Repair
+// ============================================================================
+
+/// Rewrites over-split rows so each has the header's field count
+///
+/// Rows with more fields than the header had unescaped delimiters inside a
+/// free-text value; the surplus fields (with the delimiters between them)
+/// are joined back into the absorber column. Rows with the expected number
+/// of fields, or fewer, pass through unchanged. Quoted fields are honoured,
+/// but a quoted value spanning several lines is not supported.
+pub struct ColumnCountRepairer {
+    columns: Vec<String>,
+    absorber: usize,
+    delimiter: u8,
+    quote_char: u8,
+    max_logged: usize,
+    report: RepairReport,
+    warnings: WarningCollector,
+}
+
+impl ColumnCountRepairer {
+    /// Build a repairer from the header line and a sample of data lines
+    ///
+    /// # Arguments
+    /// * `header` - Header line; it fixes the expected field count
+    /// * `sample` - Leading data lines, used to find the last text column
+    ///   when `options.absorber` is not set
+    /// * `delimiter` / `quote_char` - CSV dialect
+    /// * `options` - Repair options
+    pub fn new(
+        header: &str,
+        sample: &[String],
+        delimiter: u8,
+        quote_char: u8,
+        options: &CsvRepairOptions,
+    ) -> Result<Self, InsightoraError> {
+        let columns = split_fields(header, delimiter, quote_char);
+        if options.flag_repairs && columns.iter().any(|c| c == REPAIR_FLAG_COLUMN) {
+            return Err(InsightoraError::ValidationError(format!(
+                "Cannot add repair flags: the file already has a '{}' column",
+                REPAIR_FLAG_COLUMN
+            )));
+        }
+
+        let absorber = match &options.absorber {
+            Some(name) => columns.iter().position(|c| c == name).ok_or_else(|| {
+                InsightoraError::ValidationError(format!(
+                    "Absorber column '{}' not found; columns are {}",
+                    name,
+                    columns.join(", ")
+                ))
+            })?,
+            None => last_text_column(&columns, sample, delimiter, quote_char),
+        };
+
+        Ok(Self {
+            report: RepairReport {
+                mode: CsvRepair::ColumnCount,
+                absorber: Some(columns[absorber].clone()),
+                ..Default::default()
+            },
+            columns,
+            absorber,
+            delimiter,
+            quote_char,
+            max_logged: options.max_logged,
+            warnings: WarningCollector::new(),
+        })
+    }
+
+    /// Header field names
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Repair `(line number, line)` pairs into CSV text with a header row
+    ///
+    /// # Returns
+    /// * `(String, Vec<bool>)` - CSV text ready for the reader and a repaired
+    ///   flag per emitted row (blank lines are dropped)
+    pub fn repair_batch(&mut self, lines: &[(usize, String)]) -> (String, Vec<bool>) {
+        let delimiter = self.delimiter as char;
+        let mut text = String::new();
+        let header: Vec<String> = self.columns.iter().map(|c| self.render(c)).collect();
+        text.push_str(&header.join(&delimiter.to_string()));
+        text.push('\n');
+
+        let mut flags = Vec::with_capacity(lines.len());
+        for (line_number, line) in lines {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let mut fields = split_fields(line, self.delimiter, self.quote_char);
+            let found = fields.len();
+            let repaired = found > self.columns.len();
+            if repaired {
+                let surplus = found - self.columns.len();
+                let merged = fields
+                    .drain(self.absorber..=self.absorber + surplus)
+                    .collect::<Vec<_>>()
+                    .join(&delimiter.to_string());
+                if self.report.repairs.len() < self.max_logged {
+                    self.report.repairs.push(RowRepair {
+                        line: *line_number,
+                        fields: found,
+                        value: merged.clone(),
+                    });
+                }
+                fields.insert(self.absorber, merged);
+                self.report.rows_repaired += 1;
+                self.warnings.emit("repair", &self.columns[self.absorber], REPAIRED_ROWS, format_args!("line {}", line_number));
+            }
+            let rendered: Vec<String> = fields.iter().map(|f| self.render(f)).collect();
+            text.push_str(&rendered.join(&delimiter.to_string()));
+            text.push('\n');
+            flags.push(repaired);
+        }
+        (text, flags)
+    }
+
+    /// Repairs so far; the aggregated warnings are added by `into_report`
+    pub fn report(&self) -> &RepairReport {
+        &self.report
+    }
+
+    pub fn into_report(mut self) -> RepairReport {
+        self.report.warnings = self.warnings.finish();
+        self.report
+    }
+
+    /// Quote a field if it holds the delimiter, the quote character or a line break
+    fn render(&self, field: &str) -> String {
+        let quote = self.quote_char as char;
+        if field.contains(self.delimiter as char) || field.contains(quote) || field.contains(['\r', '\n']) {
+            let doubled = field.replace(quote, &format!("{}{}", quote, quote));
+            format!("{}{}{}", quote, doubled, quote)
+        } else {
+            field.to_string()
+        }
+    }
+}
+
+/// Repair a file batch by batch
+///
+/// Reads the header and up to `options.sample_rows` lines to set up the
+/// repairer, then hands `batch_rows` lines at a time to `on_batch` as repaired
+/// CSV text (with header) plus per-row repaired flags.
+///
+/// # Returns
+/// * `Result<RepairReport>` - Repair counts and the logged repairs
+pub fn repair_file<F>(
+    path: &Path,
+    delimiter: u8,
+    quote_char: u8,
+    options: &CsvRepairOptions,
+    batch_rows: usize,
+    mut on_batch: F,
+) -> Result<RepairReport, InsightoraError>
+where
+    F: FnMut(String, Vec<bool>) -> Result<(), InsightoraError>,
+{
+    // Lines are re-split by the reader, so chunks may cut inside quotes
+    let compression = Compression::detect(path)?;
+    let source: Box<dyn Read + Send> = if compression.is_compressed() {
+        open_decompressed(path, compression)?
+    } else {
+        Box::new(ChunkReader::open(path, None)?)
+    };
+    let mut lines = BufReader::new(source)
+        .lines()
+        .enumerate()
+        .map(|(index, line)| line.map(|l| (index + 1, l.trim_end_matches('\r').to_string())));
+
+    let header = match lines.next() {
+        Some(line) => line.map_err(from_read_error)?.1,
+        None => return Ok(RepairReport { mode: options.mode, ..Default::default() }),
+    };
+
+    let batch_rows = batch_rows.max(1);
+    let mut pending = Vec::new();
+    for line in lines.by_ref().take(options.sample_rows.max(batch_rows)) {
+        pending.push(line.map_err(from_read_error)?);
+    }
+    let sample: Vec<String> = pending.iter().take(options.sample_rows).map(|(_, l)| l.clone()).collect();
+    let mut repairer = ColumnCountRepairer::new(&header, &sample, delimiter, quote_char, options)?;
+
+    loop {
+        while pending.len() < batch_rows {
+            match lines.next() {
+                Some(line) => pending.push(line.map_err(from_read_error)?),
+                None => break,
+            }
+        }
+        if pending.is_empty() {
+            break;
+        }
+        let take = pending.len().min(batch_rows);
+        let batch: Vec<(usize, String)> = pending.drain(..take).collect();
+        let (text, flags) = repairer.repair_batch(&batch);
+        if !flags.is_empty() {
+            on_batch(text, flags)?;
+        }
+    }
+    Ok(repairer.into_report())
+}
+
+/// Split a line into fields, removing quotes around quoted fields
+pub(crate) fn split_fields(line: &str, delimiter: u8, quote_char: u8) -> Vec<String> {
+    let (delimiter, quote) = (delimiter as char, quote_char as char);
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c == quote {
+                if chars.peek() == Some(&quote) {
+                    field.push(quote);
+                    chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                field.push(c);
+            }
+        } else if c == quote && field.is_empty() {
+            in_quotes = true;
+        } else if c == delimiter {
+            fields.push(std::mem::take(&mut field));
+        } else {
+            field.push(c);
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// Index of the last column holding text in the well-formed sample rows
+///
+/// A column is text when any non-empty value is neither a number nor a
+/// boolean. Falls back to the last column when the sample shows none.
+fn last_text_column(columns: &[String], sample: &[String], delimiter: u8, quote_char: u8) -> usize {
+    let mut is_text = vec![false; columns.len()];
+    for line in sample {
+        let fields = split_fields(line, delimiter, quote_char);
+        if fields.len() != columns.len() {
+            continue;
+        }
+        for (flag, value) in is_text.iter_mut().zip(&fields) {
+            let value = value.trim();
+            if !value.is_empty()
+                && value.parse::<f64>().is_err()
+                && !value.eq_ignore_ascii_case("true")
+                && !value.eq_ignore_ascii_case("false")
+            {
+                *flag = true;
+            }
+        }
+    }
+    is_text.iter().rposition(|&t| t).unwrap_or(columns.len() - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> CsvRepairOptions {
+        CsvRepairOptions {
+            mode: CsvRepair::ColumnCount,
+            ..Default::default()
+        }
+    }
+
+    fn numbered(lines: &[&str]) -> Vec<(usize, String)> {
+        lines.iter().enumerate().map(|(i, l)| (i + 2, l.to_string())).collect()
+    }
+
+    #[test]
+    fn test_surplus_fields_merge_into_last_text_column() {
+        let sample = vec!["1,north,great service,5".to_string()];
+        let mut repairer = ColumnCountRepairer::new("id,region,comment,score", &sample, b',', b'"', &options()).unwrap();
+        assert_eq!(repairer.report().absorber.as_deref(), Some("comment"));
+
+        let (text, flags) = repairer.repair_batch(&numbered(&[
+            "1,north,great service,5",
+            "2,south,slow, but friendly, staff,3",
+            "",
+            "3,east,\"quoted, fine\",4",
+        ]));
+        assert_eq!(
+            text,
+            "id,region,comment,score\n1,north,great service,5\n2,south,\"slow, but friendly, staff\",3\n3,east,\"quoted, fine\",4\n"
+        );
+        assert_eq!(flags, vec![false, true, false]);
+
+        let report = repairer.into_report();
+        assert_eq!(report.rows_repaired, 1);
+        assert_eq!(report.repairs, vec![RowRepair { line: 3, fields: 6, value: "slow, but friendly, staff".to_string() }]);
+        assert_eq!(report.warnings[0].message(), "1 rows had surplus fields merged into column comment; examples: line 3");
+    }
+
+    #[test]
+    fn test_explicit_absorber_and_validation() {
+        let opts = CsvRepairOptions { absorber: Some("region".to_string()), ..options() };
+        let mut repairer = ColumnCountRepairer::new("id;region;score", &[], b';', b'"', &opts).unwrap();
+        let (text, _) = repairer.repair_batch(&numbered(&["1;north;west;5"]));
+        assert!(text.ends_with("1;\"north;west\";5\n"));
+
+        let missing = CsvRepairOptions { absorber: Some("nope".to_string()), ..options() };
+        assert!(ColumnCountRepairer::new("id,region", &[], b',', b'"', &missing).is_err());
+
+        let clash = CsvRepairOptions { flag_repairs: true, ..options() };
+        assert!(ColumnCountRepairer::new("id,_repaired", &[], b',', b'"', &clash).is_err());
+        assert!(CsvRepair::from_name("guess").is_err());
+    }
+
+    #[test]
+    fn test_numeric_sample_falls_back_to_last_column() {
+        let sample = vec!["1,2.5,true".to_string(), "2,3,false".to_string()];
+        let columns: Vec<String> = ["a", "b", "c"].map(String::from).to_vec();
+        assert_eq!(last_text_column(&columns, &sample, b',', b'"'), 2);
+    }
+}
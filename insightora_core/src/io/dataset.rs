@@ -0,0 +1,1025 @@
+// Chunked datasets
+// Tables built batch by batch that spill to temporary IPC files under memory pressure,
+// with optional hash indexes for point lookups
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use polars::prelude::*;
+use crate::config::{check_memory_limit, get_current_config};
+use crate::error::InsightoraError;
+use crate::utils::sandbox::check_path_allowed;
+use crate::utils::scratch::ScratchDir;
+
+/// How batches whose column types differ from the first batch are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchemaPolicy {
+    /// A batch must match the first batch's columns and types
+    #[default]
+    Error,
+    /// Numeric types widen (integers to Int64, mixed with floats to Float64),
+    /// other disagreements widen to strings, and missing columns are null
+    Widen,
+}
+
+impl SchemaPolicy {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "error" => Ok(SchemaPolicy::Error),
+            "widen" => Ok(SchemaPolicy::Widen),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown schema policy '{}'; expected 'error' or 'widen'",
+                other
+            ))),
+        }
+    }
+}
+
+/// Configuration for building a Dataset incrementally
+#[derive(Debug, Clone)]
+pub struct DatasetBuilderConfig {
+    /// Memory budget for chunks held in memory (default: the global memory limit)
+    pub memory_limit_mb: Option<usize>,
+    pub schema_policy: SchemaPolicy,
+    /// Parent of the spill directory (default: the configured `temp_dir`)
+    pub spill_dir: Option<PathBuf>,
+    /// Fraction of the budget at which in-memory chunks are spilled
+    pub spill_threshold: f64,
+}
+
+impl Default for DatasetBuilderConfig {
+    fn default() -> Self {
+        Self {
+            memory_limit_mb: None,
+            schema_policy: SchemaPolicy::Error,
+            spill_dir: None,
+            spill_threshold: 0.8,
+        }
+    }
+}
+
+// ============================================================================
+// Dataset
+// ============================================================================
+
+enum Chunk {
+    Memory(DataFrame),
+    Spilled { path: PathBuf, bytes: usize, rows: usize },
+}
+
+impl Chunk {
+    fn rows(&self) -> usize {
+        match self {
+            Chunk::Memory(df) => df.height(),
+            Chunk::Spilled { rows, .. } => *rows,
+        }
+    }
+}
+
+/// Table stored as ordered chunks, in memory or spilled to IPC files
+///
+/// Chunks are read back one at a time and cast to the final schema, so a
+/// dataset larger than memory can be processed with `chunk` / `iter_chunks`.
+/// Spill files are deleted when the dataset is dropped.
+pub struct Dataset {
+    schema: Vec<(String, DataType)>,
+    chunks: Vec<Chunk>,
+    rows: usize,
+    indexes: RwLock<HashMap<String, Arc<ColumnIndex>>>,
+    _spill: Option<ScratchDir>,
+}
+
+impl Dataset {
+    pub fn num_rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Column names and types, in column order
+    pub fn schema(&self) -> &[(String, DataType)] {
+        &self.schema
+    }
+
+    pub fn num_chunks(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Chunks stored in spill files rather than memory
+    pub fn spilled_chunks(&self) -> usize {
+        self.chunks.iter().filter(|c| matches!(c, Chunk::Spilled { .. })).count()
+    }
+
+    /// Estimated in-memory size of the whole dataset
+    pub fn estimated_bytes(&self) -> usize {
+        self.chunks
+            .iter()
+            .map(|chunk| match chunk {
+                Chunk::Memory(df) => df.estimated_size(),
+                Chunk::Spilled { bytes, .. } => *bytes,
+            })
+            .sum()
+    }
+
+    /// Load one chunk, cast to the dataset schema
+    pub fn chunk(&self, index: usize) -> Result<DataFrame, InsightoraError> {
+        conform(&self.raw_chunk(index)?, &self.schema)
+    }
+
+    /// Load one chunk as it was stored
+    fn raw_chunk(&self, index: usize) -> Result<DataFrame, InsightoraError> {
+        match self.chunks.get(index) {
+            Some(Chunk::Memory(df)) => Ok(df.clone()),
+            Some(Chunk::Spilled { path, .. }) => Ok(IpcReader::new(File::open(path)?).finish()?),
+            None => Err(InsightoraError::ValidationError(format!(
+                "Chunk {} is out of range; the dataset has {} chunks",
+                index,
+                self.chunks.len()
+            ))),
+        }
+    }
+
+    /// Load one column of one chunk, cast to the dataset schema
+    ///
+    /// Spilled chunks are read with a projection, so other columns stay on disk.
+    fn chunk_column(&self, index: usize, column: &str, dtype: &DataType) -> Result<Series, InsightoraError> {
+        let series = match &self.chunks[index] {
+            Chunk::Memory(df) => df.column(column).ok().cloned(),
+            Chunk::Spilled { path, .. } => {
+                let mut reader = IpcReader::new(File::open(path)?);
+                match reader.schema()?.fields.iter().any(|field| field.name == column) {
+                    true => Some(reader.with_columns(Some(vec![column.to_string()])).finish()?.column(column)?.clone()),
+                    false => None,
+                }
+            }
+        };
+        Ok(match series {
+            Some(series) if series.dtype() == dtype => series,
+            Some(series) => series.cast(dtype)?,
+            None => Series::full_null(column, self.chunks[index].rows(), dtype),
+        })
+    }
+
+    /// Every chunk in order, loaded one at a time
+    pub fn iter_chunks(&self) -> impl Iterator<Item = Result<DataFrame, InsightoraError>> + '_ {
+        (0..self.chunks.len()).map(|index| self.chunk(index))
+    }
+
+    /// Load the whole dataset into one DataFrame
+    ///
+    /// # Returns
+    /// * `Result<DataFrame>` - Combined data, or MemoryLimitExceeded when the
+    ///   estimated size is over the global memory limit
+    pub fn collect(&self) -> Result<DataFrame, InsightoraError> {
+        check_memory_limit(self.estimated_bytes() / (1024 * 1024))?;
+        let mut combined = conform(&DataFrame::default(), &self.schema)?;
+        for chunk in self.iter_chunks() {
+            combined.vstack_mut(&chunk?)?;
+        }
+        combined.align_chunks();
+        Ok(combined)
+    }
+
+    /// Type of `column`
+    ///
+    /// # Returns
+    /// * `Result<&DataType>` - ValidationError listing the dataset's columns
+    ///   when there is no such column
+    pub fn column_dtype(&self, column: &str) -> Result<&DataType, InsightoraError> {
+        self.schema.iter().find(|(name, _)| name == column).map(|(_, dtype)| dtype).ok_or_else(|| {
+            let names: Vec<&str> = self.schema.iter().map(|(name, _)| name.as_str()).collect();
+            InsightoraError::ValidationError(format!("Column '{}' not found; columns are {}", column, names.join(", ")))
+        })
+    }
+
+    /// Check that `row` is a row position of the dataset
+    ///
+    /// # Returns
+    /// * `Result<()>` - ValidationError naming the valid range
+    pub fn check_row(&self, row: usize) -> Result<(), InsightoraError> {
+        match self.rows {
+            rows if row < rows => Ok(()),
+            0 => Err(InsightoraError::ValidationError(format!("Row {} is out of range; the dataset is empty", row))),
+            rows => Err(InsightoraError::ValidationError(format!(
+                "Row {} is out of range; valid rows are 0 to {}",
+                row,
+                rows - 1
+            ))),
+        }
+    }
+
+    /// Values of `column` from row `offset`, at most `len` of them
+    ///
+    /// Only that column is read, and only from the chunks holding the rows.
+    pub fn column_range(&self, column: &str, offset: usize, len: usize) -> Result<Series, InsightoraError> {
+        let dtype = self.column_dtype(column)?;
+        let end = offset.saturating_add(len).min(self.rows);
+        let mut values = Series::new_empty(column, dtype);
+        let mut start = 0;
+        for (index, chunk) in self.chunks.iter().enumerate() {
+            if start >= end {
+                break;
+            }
+            let chunk_end = start + chunk.rows();
+            if chunk_end > offset {
+                let series = self.chunk_column(index, column, dtype)?;
+                let from = offset.saturating_sub(start);
+                values.append(&series.slice(from as i64, end.min(chunk_end) - start - from))?;
+            }
+            start = chunk_end;
+        }
+        Ok(values)
+    }
+
+    /// One column, or its first `limit` values, without loading the others
+    ///
+    /// # Returns
+    /// * `Result<Series>` - ValidationError for an unknown column,
+    ///   MemoryLimitExceeded when the column's estimated size is over the limit
+    pub fn column(&self, column: &str, limit: Option<usize>) -> Result<Series, InsightoraError> {
+        let rows = limit.unwrap_or(self.rows).min(self.rows);
+        // Columns are assumed to be of similar size
+        let share = self.estimated_bytes() / self.schema.len().max(1);
+        if let Some(per_row) = share.checked_div(self.rows) {
+            check_memory_limit(per_row * rows / (1024 * 1024))?;
+        }
+        self.column_range(column, 0, rows)
+    }
+
+    /// Value of `column` at `row`; only that column of one chunk is read
+    pub fn cell(&self, row: usize, column: &str) -> Result<AnyValue<'static>, InsightoraError> {
+        self.check_row(row)?;
+        Ok(self.column_range(column, row, 1)?.get(0)?.into_static()?)
+    }
+
+    /// One row as a one-row frame; only the chunk holding it is read
+    pub fn row(&self, row: usize) -> Result<DataFrame, InsightoraError> {
+        self.check_row(row)?;
+        let mut start = 0;
+        for (index, chunk) in self.chunks.iter().enumerate() {
+            if row < start + chunk.rows() {
+                return self.gather(&[RowRef { chunk: index as u32, row: (row - start) as u32 }]);
+            }
+            start += chunk.rows();
+        }
+        unreachable!("row {} was checked against {} rows", row, self.rows)
+    }
+
+    /// Distinct values of `column` in first-seen order, nulls included
+    ///
+    /// Chunks are read one column at a time; with a `limit` reading stops
+    /// once that many distinct values are found.
+    pub fn unique(&self, column: &str, limit: Option<usize>) -> Result<Series, InsightoraError> {
+        let dtype = self.column_dtype(column)?;
+        let mut distinct = Series::new_empty(column, dtype);
+        for index in 0..self.chunks.len() {
+            distinct.append(&self.chunk_column(index, column, dtype)?.unique_stable()?)?;
+            distinct = distinct.unique_stable()?;
+            if limit.is_some_and(|limit| distinct.len() >= limit) {
+                break;
+            }
+        }
+        Ok(match limit {
+            Some(limit) => distinct.head(Some(limit)),
+            None => distinct,
+        })
+    }
+
+    /// Build a hash index on `column` for repeated point lookups
+    ///
+    /// The index is kept on this Dataset, replacing any earlier index on the
+    /// column; datasets derived from it start without indexes. Nulls are not
+    /// indexed.
+    ///
+    /// # Arguments
+    /// * `column` - Integer, string, categorical or boolean column
+    /// * `unique` - Fail when a value appears more than once; otherwise every
+    ///   matching row is kept
+    ///
+    /// # Returns
+    /// * `Result<Arc<ColumnIndex>>` - ValidationError naming the first repeated
+    ///   value when `unique` is set, InvalidDataType for other column types
+    pub fn create_index(&self, column: &str, unique: bool) -> Result<Arc<ColumnIndex>, InsightoraError> {
+        let dtype = self.column_dtype(column)?;
+        let kind = IndexKind::of(dtype).ok_or_else(|| InsightoraError::InvalidDataType {
+            expected: "integer, string or boolean column".to_string(),
+            actual: dtype.to_string(),
+        })?;
+
+        let mut postings = if unique {
+            Postings::Unique(HashMap::with_capacity(self.rows))
+        } else {
+            Postings::Multi(HashMap::new())
+        };
+        let mut offsets = Vec::with_capacity(self.chunks.len());
+        let mut offset = 0;
+        for chunk in 0..self.chunks.len() {
+            offsets.push(offset);
+            let df = self.raw_chunk(chunk)?;
+            offset += df.height();
+            // Columns added by schema widening are null in earlier chunks
+            let Ok(series) = df.column(column) else { continue };
+            for (row, key) in kind.keys(series)?.into_iter().enumerate() {
+                let Some(key) = key else { continue };
+                let at = RowRef { chunk: chunk as u32, row: row as u32 };
+                match &mut postings {
+                    Postings::Unique(map) => match map.entry(key) {
+                        Entry::Occupied(entry) => {
+                            let first = entry.get();
+                            return Err(InsightoraError::ValidationError(format!(
+                                "Column '{}' is not unique: {} appears at rows {} and {}; use unique=False to keep every match",
+                                column,
+                                entry.key(),
+                                offsets[first.chunk as usize] + first.row as usize,
+                                offsets[chunk] + row
+                            )));
+                        }
+                        Entry::Vacant(entry) => {
+                            entry.insert(at);
+                        }
+                    },
+                    Postings::Multi(map) => map.entry(key).or_default().push(at),
+                }
+            }
+        }
+
+        let index = Arc::new(ColumnIndex { column: column.to_string(), kind, postings });
+        self.indexes
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(column.to_string(), Arc::clone(&index));
+        Ok(index)
+    }
+
+    /// Index previously built on `column`
+    pub fn index(&self, column: &str) -> Option<Arc<ColumnIndex>> {
+        self.indexes.read().unwrap_or_else(|e| e.into_inner()).get(column).cloned()
+    }
+
+    /// Columns with an index, sorted by name
+    pub fn indexed_columns(&self) -> Vec<String> {
+        let mut columns: Vec<String> = self.indexes.read().unwrap_or_else(|e| e.into_inner()).keys().cloned().collect();
+        columns.sort();
+        columns
+    }
+
+    /// Remove the index on `column`; returns whether one existed
+    pub fn drop_index(&self, column: &str) -> bool {
+        self.indexes.write().unwrap_or_else(|e| e.into_inner()).remove(column).is_some()
+    }
+
+    /// Rows whose indexed `column` equals `key`, in dataset order
+    pub fn lookup(&self, column: &str, key: &IndexKey) -> Result<DataFrame, InsightoraError> {
+        self.lookup_many(column, std::slice::from_ref(key))
+    }
+
+    /// Rows matching each key in turn
+    ///
+    /// Only the chunks holding matches are read, and only the matching rows
+    /// are cast to the dataset schema. Keys without matches contribute no rows.
+    ///
+    /// # Returns
+    /// * `Result<DataFrame>` - ValidationError when `column` has no index,
+    ///   InvalidDataType when a key's type doesn't match the column
+    pub fn lookup_many(&self, column: &str, keys: &[IndexKey]) -> Result<DataFrame, InsightoraError> {
+        let index = self.index(column).ok_or_else(|| {
+            InsightoraError::ValidationError(format!("No index on column '{}'; call create_index first", column))
+        })?;
+        let mut rows = Vec::new();
+        for key in keys {
+            rows.extend_from_slice(index.rows(key)?);
+        }
+        self.gather(&rows)
+    }
+
+    /// Load the given rows, in the given order
+    fn gather(&self, rows: &[RowRef]) -> Result<DataFrame, InsightoraError> {
+        let mut order: Vec<usize> = (0..rows.len()).collect();
+        order.sort_by_key(|&i| (rows[i].chunk, rows[i].row));
+
+        let mut gathered = conform(&DataFrame::default(), &self.schema)?;
+        let mut start = 0;
+        while start < order.len() {
+            let chunk = rows[order[start]].chunk;
+            let end = start + order[start..].iter().take_while(|&&i| rows[i].chunk == chunk).count();
+            let positions = IdxCa::from_vec("", order[start..end].iter().map(|&i| rows[i].row as IdxSize).collect());
+            let taken = self.raw_chunk(chunk as usize)?.take(&positions)?;
+            gathered.vstack_mut(&conform(&taken, &self.schema)?)?;
+            start = end;
+        }
+
+        // Back from chunk order to the order the rows were requested in
+        let mut inverse = vec![0 as IdxSize; order.len()];
+        for (position, &i) in order.iter().enumerate() {
+            inverse[i] = position as IdxSize;
+        }
+        Ok(gathered.take(&IdxCa::from_vec("", inverse))?)
+    }
+}
+
+// ============================================================================
+// Column Indexes
+// ============================================================================
+
+/// Value looked up in a column index
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum IndexKey {
+    Int(i64),
+    Str(String),
+    Bool(bool),
+}
+
+impl From<i64> for IndexKey {
+    fn from(value: i64) -> Self {
+        IndexKey::Int(value)
+    }
+}
+
+impl From<&str> for IndexKey {
+    fn from(value: &str) -> Self {
+        IndexKey::Str(value.to_string())
+    }
+}
+
+impl From<String> for IndexKey {
+    fn from(value: String) -> Self {
+        IndexKey::Str(value)
+    }
+}
+
+impl From<bool> for IndexKey {
+    fn from(value: bool) -> Self {
+        IndexKey::Bool(value)
+    }
+}
+
+impl fmt::Display for IndexKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexKey::Int(value) => write!(f, "{}", value),
+            IndexKey::Str(value) => write!(f, "{:?}", value),
+            IndexKey::Bool(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// Key type of an indexed column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IndexKind {
+    Int,
+    Str,
+    Bool,
+}
+
+impl IndexKind {
+    fn of(dtype: &DataType) -> Option<Self> {
+        match dtype {
+            DataType::String | DataType::Categorical(_, _) => Some(IndexKind::Str),
+            DataType::Boolean => Some(IndexKind::Bool),
+            dtype if dtype.is_integer() => Some(IndexKind::Int),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            IndexKind::Int => "integer",
+            IndexKind::Str => "string",
+            IndexKind::Bool => "boolean",
+        }
+    }
+
+    fn matches(&self, key: &IndexKey) -> bool {
+        matches!(
+            (self, key),
+            (IndexKind::Int, IndexKey::Int(_)) | (IndexKind::Str, IndexKey::Str(_)) | (IndexKind::Bool, IndexKey::Bool(_))
+        )
+    }
+
+    /// Keys of a stored column, cast the way `conform` would present it
+    fn keys(&self, series: &Series) -> Result<Vec<Option<IndexKey>>, InsightoraError> {
+        Ok(match self {
+            IndexKind::Int => series.cast(&DataType::Int64)?.i64()?.into_iter().map(|v| v.map(IndexKey::Int)).collect(),
+            IndexKind::Str => series.cast(&DataType::String)?.str()?.into_iter().map(|v| v.map(IndexKey::from)).collect(),
+            IndexKind::Bool => series.cast(&DataType::Boolean)?.bool()?.into_iter().map(|v| v.map(IndexKey::Bool)).collect(),
+        })
+    }
+}
+
+/// Chunk number and row within the chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RowRef {
+    chunk: u32,
+    row: u32,
+}
+
+enum Postings {
+    Unique(HashMap<IndexKey, RowRef>),
+    Multi(HashMap<IndexKey, Vec<RowRef>>),
+}
+
+/// Hash index from the values of one Dataset column to the rows holding them
+///
+/// Built and owned by `Dataset::create_index`; lookups go through the
+/// Dataset so an index can't be applied to another dataset's rows.
+pub struct ColumnIndex {
+    column: String,
+    kind: IndexKind,
+    postings: Postings,
+}
+
+impl ColumnIndex {
+    pub fn column(&self) -> &str {
+        &self.column
+    }
+
+    pub fn is_unique(&self) -> bool {
+        matches!(self.postings, Postings::Unique(_))
+    }
+
+    /// Number of distinct indexed values
+    pub fn len(&self) -> usize {
+        match &self.postings {
+            Postings::Unique(map) => map.len(),
+            Postings::Multi(map) => map.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn rows(&self, key: &IndexKey) -> Result<&[RowRef], InsightoraError> {
+        if !self.kind.matches(key) {
+            return Err(InsightoraError::InvalidDataType {
+                expected: format!("{} lookup value for column '{}'", self.kind.name(), self.column),
+                actual: key.to_string(),
+            });
+        }
+        Ok(match &self.postings {
+            Postings::Unique(map) => map.get(key).map(std::slice::from_ref).unwrap_or(&[]),
+            Postings::Multi(map) => map.get(key).map(Vec::as_slice).unwrap_or(&[]),
+        })
+    }
+}
+
+// ============================================================================
+// Dataset Builder
+// ============================================================================
+
+/// Accumulates batches into a Dataset within a memory budget
+///
+/// The first batch fixes the column set and order. When the chunks held in
+/// memory reach `spill_threshold` of the budget they are concatenated and
+/// written to one IPC file, so peak memory stays near the budget however
+/// many batches arrive.
+///
+/// # Example
+/// ```no_run
+/// use insightora_core::api::{DatasetBuilder, DatasetBuilderConfig, SchemaPolicy};
+/// use polars::prelude::*;
+///
+/// # fn main() -> insightora_core::api::Result<()> {
+/// let mut builder = DatasetBuilder::new(DatasetBuilderConfig {
+///     memory_limit_mb: Some(64),
+///     schema_policy: SchemaPolicy::Widen,
+///     ..Default::default()
+/// })?;
+/// for page in 0..100i64 {
+///     builder.append(df!("page" => [page], "value" => [1.5])?)?;
+/// }
+/// let dataset = builder.finish()?;
+/// println!("{} rows in {} chunks", dataset.num_rows(), dataset.num_chunks());
+/// # Ok(())
+/// # }
+/// ```
+pub struct DatasetBuilder {
+    config: DatasetBuilderConfig,
+    limit_bytes: usize,
+    schema: Vec<(String, DataType)>,
+    chunks: Vec<Chunk>,
+    rows: usize,
+    batches: usize,
+    memory_bytes: usize,
+    peak_memory_bytes: usize,
+    spill: Option<ScratchDir>,
+}
+
+impl DatasetBuilder {
+    pub fn new(config: DatasetBuilderConfig) -> Result<Self, InsightoraError> {
+        if config.spill_threshold.is_nan() || config.spill_threshold <= 0.0 || config.spill_threshold > 1.0 {
+            return Err(InsightoraError::ValidationError(
+                "spill_threshold must be in (0, 1]".to_string(),
+            ));
+        }
+        if let Some(dir) = &config.spill_dir {
+            check_path_allowed(dir)?;
+        }
+        let limit_mb = config.memory_limit_mb.unwrap_or_else(|| get_current_config().memory_limit_mb);
+        Ok(Self {
+            limit_bytes: limit_mb * 1024 * 1024,
+            config,
+            schema: Vec::new(),
+            chunks: Vec::new(),
+            rows: 0,
+            batches: 0,
+            memory_bytes: 0,
+            peak_memory_bytes: 0,
+            spill: None,
+        })
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Chunks written to spill files so far
+    pub fn spilled_chunks(&self) -> usize {
+        self.chunks.iter().filter(|c| matches!(c, Chunk::Spilled { .. })).count()
+    }
+
+    /// Largest estimated size of the in-memory chunks seen so far
+    pub fn peak_memory_bytes(&self) -> usize {
+        self.peak_memory_bytes
+    }
+
+    /// Add a batch, checking it against the schema of the first batch
+    ///
+    /// # Returns
+    /// * `Result<()>` - ValidationError when the batch doesn't fit the schema
+    ///   under the configured policy
+    pub fn append(&mut self, batch: DataFrame) -> Result<(), InsightoraError> {
+        let batch = self.check_schema(batch)?;
+        self.batches += 1;
+        if batch.height() == 0 {
+            return Ok(());
+        }
+
+        let bytes = batch.estimated_size();
+        self.rows += batch.height();
+        self.chunks.push(Chunk::Memory(batch));
+        self.memory_bytes += bytes;
+        self.peak_memory_bytes = self.peak_memory_bytes.max(self.memory_bytes);
+        if self.memory_bytes as f64 >= self.limit_bytes as f64 * self.config.spill_threshold {
+            self.spill_memory_chunks()?;
+        }
+        Ok(())
+    }
+
+    /// Complete the dataset
+    pub fn finish(self) -> Result<Dataset, InsightoraError> {
+        if self.batches == 0 {
+            return Err(InsightoraError::ValidationError(
+                "No batches were appended to the DatasetBuilder".to_string(),
+            ));
+        }
+        Ok(Dataset {
+            schema: self.schema,
+            chunks: self.chunks,
+            rows: self.rows,
+            indexes: RwLock::new(HashMap::new()),
+            _spill: self.spill,
+        })
+    }
+
+    /// Check a batch against the schema, widening the schema when allowed, and
+    /// return it with columns in schema order
+    fn check_schema(&mut self, batch: DataFrame) -> Result<DataFrame, InsightoraError> {
+        if batch.width() == 0 {
+            return Err(InsightoraError::ValidationError("Batch has no columns".to_string()));
+        }
+        if self.schema.is_empty() {
+            self.schema = batch
+                .get_columns()
+                .iter()
+                .map(|s| (s.name().to_string(), s.dtype().clone()))
+                .collect();
+            return Ok(batch);
+        }
+
+        let number = self.batches + 1;
+        if let Some(extra) = batch.get_columns().iter().find(|s| !self.schema.iter().any(|(n, _)| n == s.name())) {
+            return Err(InsightoraError::ValidationError(format!(
+                "Batch {} has column '{}' which is not in the first batch",
+                number,
+                extra.name()
+            )));
+        }
+        for (name, dtype) in self.schema.iter_mut() {
+            let Ok(series) = batch.column(name) else {
+                if self.config.schema_policy == SchemaPolicy::Error {
+                    return Err(InsightoraError::ValidationError(format!(
+                        "Batch {} is missing column '{}'",
+                        number, name
+                    )));
+                }
+                continue;
+            };
+            let actual = series.dtype();
+            if actual == dtype || *actual == DataType::Null {
+                continue;
+            }
+            if *dtype == DataType::Null {
+                *dtype = actual.clone();
+                continue;
+            }
+            match self.config.schema_policy {
+                SchemaPolicy::Error => {
+                    return Err(InsightoraError::ValidationError(format!(
+                        "Column '{}' is {} in batch {} but {} in earlier batches",
+                        name, actual, number, dtype
+                    )))
+                }
+                SchemaPolicy::Widen => *dtype = widen(dtype, actual),
+            }
+        }
+        conform(&batch, &self.schema)
+    }
+
+    /// Write the in-memory chunks (always the newest ones) to one IPC file
+    fn spill_memory_chunks(&mut self) -> Result<(), InsightoraError> {
+        let first_memory = self
+            .chunks
+            .iter()
+            .position(|c| matches!(c, Chunk::Memory(_)))
+            .unwrap_or(self.chunks.len());
+        let pending: Vec<Chunk> = self.chunks.drain(first_memory..).collect();
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        let mut combined: Option<DataFrame> = None;
+        for chunk in pending {
+            let Chunk::Memory(df) = chunk else { continue };
+            let df = conform(&df, &self.schema)?;
+            match combined.as_mut() {
+                Some(acc) => {
+                    acc.vstack_mut(&df)?;
+                }
+                None => combined = Some(df),
+            }
+        }
+        let Some(mut combined) = combined else {
+            return Ok(());
+        };
+        combined.align_chunks();
+
+        if self.spill.is_none() {
+            self.spill = Some(ScratchDir::create("insightora-spill", self.config.spill_dir.as_deref())?);
+        }
+        let dir = self.spill.as_ref().expect("spill directory created above");
+        let path = dir.join(format!("chunk-{:05}.ipc", self.chunks.len()));
+        IpcWriter::new(File::create(&path)?).finish(&mut combined)?;
+
+        self.chunks.push(Chunk::Spilled {
+            path,
+            bytes: combined.estimated_size(),
+            rows: combined.height(),
+        });
+        self.memory_bytes = 0;
+        Ok(())
+    }
+}
+
+/// Common type for two disagreeing column types
+fn widen(current: &DataType, other: &DataType) -> DataType {
+    if current.is_numeric() && other.is_numeric() {
+        if current.is_float() || other.is_float() {
+            DataType::Float64
+        } else {
+            DataType::Int64
+        }
+    } else {
+        DataType::String
+    }
+}
+
+/// Cast and reorder a frame to the schema, adding missing columns as nulls
+fn conform(df: &DataFrame, schema: &[(String, DataType)]) -> Result<DataFrame, InsightoraError> {
+    let columns = schema
+        .iter()
+        .map(|(name, dtype)| match df.column(name) {
+            Ok(series) if series.dtype() == dtype => Ok(series.clone()),
+            Ok(series) => series.cast(dtype),
+            Err(_) => Ok(Series::full_null(name, df.height(), dtype)),
+        })
+        .collect::<PolarsResult<Vec<Series>>>()?;
+    Ok(DataFrame::new(columns)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn builder(dir: &TempDir, limit_mb: usize, policy: SchemaPolicy) -> DatasetBuilder {
+        DatasetBuilder::new(DatasetBuilderConfig {
+            memory_limit_mb: Some(limit_mb),
+            schema_policy: policy,
+            spill_dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_builds_large_dataset_within_memory_limit() {
+        let dir = TempDir::new().unwrap();
+        let limit_mb = 4;
+        let mut builder = builder(&dir, limit_mb, SchemaPolicy::Error);
+
+        let batch_rows = 10_000i64;
+        for batch in 0..500i64 {
+            let start = batch * batch_rows;
+            let ids: Vec<i64> = (start..start + batch_rows).collect();
+            let values: Vec<f64> = ids.iter().map(|&id| id as f64 * 0.5).collect();
+            builder.append(df!("id" => ids, "value" => values).unwrap()).unwrap();
+        }
+        assert!(builder.peak_memory_bytes() <= limit_mb * 1024 * 1024);
+        assert!(builder.spilled_chunks() > 10);
+
+        let dataset = builder.finish().unwrap();
+        assert_eq!(dataset.num_rows(), 5_000_000);
+        assert!(dataset.estimated_bytes() > limit_mb * 1024 * 1024);
+
+        // Stream the chunks back: every id once, in order
+        let (mut rows, mut sum, mut next) = (0usize, 0i64, 0i64);
+        for chunk in dataset.iter_chunks() {
+            let chunk = chunk.unwrap();
+            let ids = chunk.column("id").unwrap().i64().unwrap();
+            assert_eq!(ids.get(0), Some(next));
+            next += chunk.height() as i64;
+            rows += chunk.height();
+            sum += ids.sum().unwrap();
+        }
+        assert_eq!(rows, 5_000_000);
+        assert_eq!(sum, 4_999_999 * 5_000_000 / 2);
+
+        let spill_dirs = || std::fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(spill_dirs(), 1);
+        drop(dataset);
+        assert_eq!(spill_dirs(), 0);
+    }
+
+    #[test]
+    fn test_schema_policies() {
+        let dir = TempDir::new().unwrap();
+        let first = df!("id" => [1i64, 2], "score" => [10i64, 20], "name" => ["a", "b"]).unwrap();
+        let floats = df!("score" => [2.5], "id" => [3i64], "name" => ["c"]).unwrap();
+        let missing = df!("id" => [4i64], "score" => [7i64]).unwrap();
+
+        let mut strict = builder(&dir, 64, SchemaPolicy::Error);
+        strict.append(first.clone()).unwrap();
+        let err = strict.append(floats.clone()).unwrap_err().to_string();
+        assert!(err.contains("Column 'score' is f64 in batch 2 but i64"), "{}", err);
+        assert!(strict.append(missing.clone()).unwrap_err().to_string().contains("missing column 'name'"));
+        assert!(strict.append(df!("other" => [1]).unwrap()).is_err());
+
+        // Tiny budget: the first batch is spilled before the schema widens
+        let mut widening = DatasetBuilder::new(DatasetBuilderConfig {
+            memory_limit_mb: Some(0),
+            schema_policy: SchemaPolicy::Widen,
+            spill_dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        })
+        .unwrap();
+        widening.append(first).unwrap();
+        widening.append(floats).unwrap();
+        widening.append(missing).unwrap();
+        let dataset = widening.finish().unwrap();
+        assert_eq!(dataset.spilled_chunks(), 3);
+
+        let df = dataset.collect().unwrap();
+        assert_eq!(df.get_column_names(), vec!["id", "score", "name"]);
+        assert_eq!(df.column("score").unwrap().dtype(), &DataType::Float64);
+        let score: Vec<Option<f64>> = df.column("score").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(score, vec![Some(10.0), Some(20.0), Some(2.5), Some(7.0)]);
+        assert_eq!(df.column("name").unwrap().null_count(), 1);
+
+        let empty = builder(&dir, 64, SchemaPolicy::Error);
+        assert!(empty.finish().is_err());
+    }
+
+    #[test]
+    fn test_index_lookups() {
+        let dir = TempDir::new().unwrap();
+        let mut builder = DatasetBuilder::new(DatasetBuilderConfig {
+            memory_limit_mb: Some(0),
+            schema_policy: SchemaPolicy::Widen,
+            spill_dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        })
+        .unwrap();
+        let no_tags: [Option<&str>; 3] = [None, None, None];
+        builder.append(df!("sku" => [10i64, 11, 12], "price" => [1.0, 2.0, 3.0], "tag" => no_tags).unwrap()).unwrap();
+        builder.append(df!("sku" => [13i64, 11], "price" => [4.0, 5.0], "tag" => ["x", "y"]).unwrap()).unwrap();
+        let dataset = builder.finish().unwrap();
+
+        let err = dataset.create_index("sku", true).err().unwrap().to_string();
+        assert!(err.contains("Column 'sku' is not unique: 11 appears at rows 1 and 4"), "{}", err);
+        assert!(dataset.index("sku").is_none());
+
+        let index = dataset.create_index("sku", false).unwrap();
+        assert_eq!((index.len(), index.is_unique()), (4, false));
+        let rows = dataset.lookup("sku", &IndexKey::from(11i64)).unwrap();
+        let prices: Vec<Option<f64>> = rows.column("price").unwrap().f64().unwrap().into_iter().collect();
+        assert_eq!(prices, vec![Some(2.0), Some(5.0)]);
+        assert_eq!(rows.get_column_names(), vec!["sku", "price", "tag"]);
+
+        // Rows come back in key order, across chunks
+        let keys = [IndexKey::from(13i64), IndexKey::from(99i64), IndexKey::from(10i64)];
+        let rows = dataset.lookup_many("sku", &keys).unwrap();
+        let skus: Vec<Option<i64>> = rows.column("sku").unwrap().i64().unwrap().into_iter().collect();
+        assert_eq!(skus, vec![Some(13), Some(10)]);
+        assert_eq!(rows.column("tag").unwrap().str().unwrap().get(0), Some("x"));
+        assert_eq!(dataset.lookup("sku", &IndexKey::from(99i64)).unwrap().height(), 0);
+
+        // The tag column is null throughout the first chunk, and nulls aren't indexed
+        let tags = dataset.create_index("tag", true).unwrap();
+        assert_eq!(tags.len(), 2);
+        assert_eq!(dataset.indexed_columns(), vec!["sku", "tag"]);
+        assert!(matches!(
+            dataset.lookup("tag", &IndexKey::from(1i64)),
+            Err(InsightoraError::InvalidDataType { .. })
+        ));
+        assert!(matches!(dataset.create_index("price", false), Err(InsightoraError::InvalidDataType { .. })));
+
+        assert!(dataset.drop_index("tag"));
+        assert!(dataset.lookup("tag", &IndexKey::from("x")).unwrap_err().to_string().contains("No index on column 'tag'"));
+    }
+
+    #[test]
+    fn test_column_cell_row_and_unique_accessors() {
+        let dir = TempDir::new().unwrap();
+        let mut builder = DatasetBuilder::new(DatasetBuilderConfig {
+            memory_limit_mb: Some(0),
+            schema_policy: SchemaPolicy::Widen,
+            spill_dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        })
+        .unwrap();
+        let no_tags: [Option<&str>; 3] = [None, None, None];
+        builder.append(df!("sku" => [10i64, 11, 12], "region" => ["n", "s", "n"], "tag" => no_tags).unwrap()).unwrap();
+        builder.append(df!("sku" => [13i64, 14], "region" => ["e", "s"], "tag" => ["x", "y"]).unwrap()).unwrap();
+        let dataset = builder.finish().unwrap();
+
+        let skus: Vec<Option<i64>> = dataset.column("sku", None).unwrap().i64().unwrap().into_iter().collect();
+        assert_eq!(skus, vec![Some(10), Some(11), Some(12), Some(13), Some(14)]);
+        let head: Vec<Option<i64>> = dataset.column("sku", Some(4)).unwrap().i64().unwrap().into_iter().collect();
+        assert_eq!(head, vec![Some(10), Some(11), Some(12), Some(13)]);
+        let tags = dataset.column_range("tag", 2, 2).unwrap();
+        assert_eq!(tags.str().unwrap().into_iter().collect::<Vec<_>>(), vec![None, Some("x")]);
+
+        assert_eq!(dataset.cell(4, "sku").unwrap(), AnyValue::Int64(14));
+        assert_eq!(dataset.cell(0, "tag").unwrap(), AnyValue::Null);
+        let row = dataset.row(3).unwrap();
+        assert_eq!((row.height(), row.get_column_names()), (1, vec!["sku", "region", "tag"]));
+        assert_eq!(row.column("region").unwrap().str().unwrap().get(0), Some("e"));
+
+        let regions = dataset.unique("region", None).unwrap();
+        assert_eq!(regions.str().unwrap().into_iter().collect::<Vec<_>>(), vec![Some("n"), Some("s"), Some("e")]);
+        assert_eq!(dataset.unique("region", Some(2)).unwrap().len(), 2);
+        assert_eq!(dataset.unique("tag", None).unwrap().len(), 3);
+
+        let err = dataset.cell(5, "sku").unwrap_err().to_string();
+        assert!(err.contains("Row 5 is out of range; valid rows are 0 to 4"), "{}", err);
+        let err = dataset.column("price", None).unwrap_err().to_string();
+        assert!(err.contains("Column 'price' not found; columns are sku, region, tag"), "{}", err);
+    }
+
+    #[test]
+    #[ignore = "benchmark; run with --ignored --nocapture"]
+    fn bench_index_lookups_beat_filter_scans() {
+        let mut builder = DatasetBuilder::new(DatasetBuilderConfig::default()).unwrap();
+        let batch_rows = 100_000i64;
+        for batch in 0..10i64 {
+            let skus: Vec<String> = (batch * batch_rows..(batch + 1) * batch_rows).map(|i| format!("SKU-{:07}", i)).collect();
+            let prices: Vec<f64> = (0..batch_rows).map(|i| i as f64).collect();
+            builder.append(df!("sku" => skus, "price" => prices).unwrap()).unwrap();
+        }
+        let dataset = builder.finish().unwrap();
+        let wanted: Vec<String> = (0..1_000).map(|i| format!("SKU-{:07}", i * 997)).collect();
+
+        let start = std::time::Instant::now();
+        let scans = 20;
+        for sku in wanted.iter().take(scans) {
+            for chunk in dataset.iter_chunks() {
+                let chunk = chunk.unwrap();
+                let mask = chunk.column("sku").unwrap().str().unwrap().equal(sku.as_str());
+                chunk.filter(&mask).unwrap();
+            }
+        }
+        let per_scan = start.elapsed() / scans as u32;
+
+        dataset.create_index("sku", true).unwrap();
+        let start = std::time::Instant::now();
+        for sku in &wanted {
+            assert_eq!(dataset.lookup("sku", &IndexKey::from(sku.as_str())).unwrap().height(), 1);
+        }
+        let per_lookup = start.elapsed() / wanted.len() as u32;
+
+        println!("filter scan: {:?}/lookup, index: {:?}/lookup", per_scan, per_lookup);
+        assert!(per_lookup * 100 < per_scan);
+    }
+}
@@ -0,0 +1,213 @@
+// Date and datetime parsing for text columns
+// Detects ISO 8601 columns and applies strftime formats, setting values that don't parse to null
+
+use polars::prelude::*;
+use polars::export::chrono::format::{Item, StrftimeItems};
+use polars::export::chrono::{DateTime, NaiveDate, NaiveDateTime};
+use crate::error::InsightoraError;
+use crate::utils::warnings::WarningCollector;
+
+/// Category of the warnings for values that didn't parse as dates
+pub const DATE_WARNING: &str = "dates";
+
+/// Warning for values set to null because they didn't parse
+pub const UNPARSED_DATES: &str = "{count} values in column {column} could not be parsed as dates and were set to null";
+
+/// How the text of a date column is read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DateFormat {
+    /// strftime format without a time of day, read as Date
+    Date(String),
+    /// strftime format with a time of day but no offset, read as Datetime
+    Datetime(String),
+    /// strftime format with an offset (`%z`), read as Datetime in UTC
+    Zoned(String),
+    /// RFC 3339 timestamps such as 2024-01-15T08:30:00Z, read as Datetime in UTC
+    Rfc3339,
+}
+
+impl DateFormat {
+    /// Classify a strftime format by the fields it holds
+    ///
+    /// # Example
+    /// ```
+    /// use insightora_core::api::DateFormat;
+    ///
+    /// assert_eq!(DateFormat::from_pattern("%d/%m/%Y").unwrap(), DateFormat::Date("%d/%m/%Y".to_string()));
+    /// assert!(DateFormat::from_pattern("%Y-%m").is_err());
+    /// ```
+    pub fn from_pattern(pattern: &str) -> Result<Self, InsightoraError> {
+        if StrftimeItems::new(pattern).any(|item| matches!(item, Item::Error)) {
+            return Err(InsightoraError::ValidationError(format!("Invalid date format '{}'", pattern)));
+        }
+        let has = |fields: &[&str]| fields.iter().any(|field| pattern.contains(field));
+        if has(&["%z", "%:z", "%#z"]) {
+            Ok(DateFormat::Zoned(pattern.to_string()))
+        } else if has(&["%H", "%I", "%k", "%l", "%M", "%S", "%T", "%R", "%X", "%r", "%s"]) {
+            Ok(DateFormat::Datetime(pattern.to_string()))
+        } else if has(&["%d", "%e", "%j", "%F", "%D", "%x", "%v"]) {
+            Ok(DateFormat::Date(pattern.to_string()))
+        } else {
+            Err(InsightoraError::ValidationError(format!(
+                "Date format '{}' has no day or time of day; expected e.g. '%d/%m/%Y' or '%Y-%m-%d %H:%M'",
+                pattern
+            )))
+        }
+    }
+
+    /// Type of the parsed column
+    pub fn dtype(&self) -> DataType {
+        match self {
+            DateFormat::Date(_) => DataType::Date,
+            DateFormat::Datetime(_) => DataType::Datetime(TimeUnit::Microseconds, None),
+            DateFormat::Zoned(_) | DateFormat::Rfc3339 => {
+                DataType::Datetime(TimeUnit::Microseconds, Some("UTC".to_string()))
+            }
+        }
+    }
+
+    /// Days since 1970-01-01 for dates, microseconds since the epoch (UTC) otherwise
+    fn parse(&self, text: &str) -> Option<i64> {
+        match self {
+            // NaiveDate's default is the epoch
+            DateFormat::Date(format) => NaiveDate::parse_from_str(text, format)
+                .ok()
+                .map(|date| (date - NaiveDate::default()).num_days()),
+            DateFormat::Datetime(format) => NaiveDateTime::parse_from_str(text, format)
+                .ok()
+                .map(|at| at.and_utc().timestamp_micros()),
+            DateFormat::Zoned(format) => DateTime::parse_from_str(text, format).ok().map(|at| at.timestamp_micros()),
+            DateFormat::Rfc3339 => DateTime::parse_from_rfc3339(text).ok().map(|at| at.timestamp_micros()),
+        }
+    }
+
+    /// Parse a text column
+    ///
+    /// Values that don't parse, such as "2024-02-30", become null and are
+    /// counted in `warnings` with the first few as examples. Columns typed
+    /// otherwise are read through their text form.
+    pub fn parse_column(&self, series: &Series, warnings: &mut WarningCollector) -> Result<Series, InsightoraError> {
+        let text = series.cast(&DataType::String)?;
+        let values = text.str()?.into_iter().map(|value| {
+            let value = value?;
+            let parsed = self.parse(value);
+            if parsed.is_none() {
+                warnings.emit(DATE_WARNING, series.name(), UNPARSED_DATES, format_args!("'{}'", value));
+            }
+            parsed
+        });
+        let parsed = match self.dtype() {
+            DataType::Datetime(unit, zone) => values.collect::<Int64Chunked>().into_datetime(unit, zone).into_series(),
+            _ => values.map(|days| days.map(|days| days as i32)).collect::<Int32Chunked>().into_date().into_series(),
+        };
+        Ok(parsed.with_name(series.name()))
+    }
+}
+
+/// ISO 8601 layouts tried by `detect`, in order
+pub(crate) fn iso_formats() -> [DateFormat; 6] {
+    [
+        DateFormat::Date("%Y-%m-%d".to_string()),
+        DateFormat::Datetime("%Y-%m-%dT%H:%M:%S%.f".to_string()),
+        DateFormat::Datetime("%Y-%m-%d %H:%M:%S%.f".to_string()),
+        DateFormat::Datetime("%Y-%m-%dT%H:%M".to_string()),
+        DateFormat::Datetime("%Y-%m-%d %H:%M".to_string()),
+        DateFormat::Rfc3339,
+    ]
+}
+
+/// ISO 8601 layout of a text column, if it has one
+///
+/// The first `sample_rows` non-null values must all parse with the same
+/// layout; a column mixing dates with datetimes, or with offsets and
+/// without, stays text.
+pub fn detect(series: &Series, sample_rows: usize) -> Option<DateFormat> {
+    let sample: Vec<&str> = series.str().ok()?.into_iter().flatten().take(sample_rows).collect();
+    if sample.is_empty() {
+        return None;
+    }
+    iso_formats().into_iter().find(|format| sample.iter().all(|value| format.parse(value).is_some()))
+}
+
+/// Parse the date columns of a frame
+///
+/// Columns listed in `formats` are parsed with their format; with
+/// `detect_iso`, other text columns are parsed when `detect` finds an
+/// ISO 8601 layout in their first `sample_rows` values.
+///
+/// # Arguments
+/// * `df` - Parsed frame
+/// * `detect_iso` - Look for ISO 8601 dates in text columns
+/// * `formats` - (column, format) pairs; columns not in the frame are skipped
+/// * `sample_rows` - Values sampled per column by `detect`
+/// * `warnings` - Receives a count of the values set to null, per column
+///
+/// # Returns
+/// * `Result<DataFrame>` - The frame with Date and Datetime columns
+pub fn parse_date_columns(
+    mut df: DataFrame,
+    detect_iso: bool,
+    formats: &[(String, DateFormat)],
+    sample_rows: usize,
+    warnings: &mut WarningCollector,
+) -> Result<DataFrame, InsightoraError> {
+    let names: Vec<String> = df.get_column_names().iter().map(|name| name.to_string()).collect();
+    for name in &names {
+        let series = df.column(name)?;
+        let format = match formats.iter().find(|(column, _)| column == name) {
+            Some((_, format)) => Some(format.clone()),
+            None if detect_iso && series.dtype() == &DataType::String => detect(series, sample_rows),
+            None => None,
+        };
+        if let Some(format) = format {
+            let parsed = format.parse_column(series, warnings)?;
+            df.replace(name, parsed)?;
+        }
+    }
+    Ok(df)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_iso_columns_and_nulls_unparsed_values() {
+        let df = df!(
+            "day" => ["2024-01-15", "2024-03-01", "2024-02-30"],
+            "at" => ["2024-01-15T08:30:00Z", "2024-01-15T10:00:00+02:00", "soon"],
+            "local" => ["2024-01-15 08:30:00.250", "2024-01-16 09:00:00", "2024-01-17 10:15:00"],
+            "mixed" => ["2024-01-15", "2024-01-15T08:30:00", "2024-01-16"],
+            "uk" => ["15/01/2024", "31/12/2023", "12/31/2023"]
+        )
+        .unwrap();
+        let formats = [("uk".to_string(), DateFormat::from_pattern("%d/%m/%Y").unwrap())];
+        let mut warnings = WarningCollector::with_sample_size(3);
+        // Two rows sampled, so the bad values in the third row are parsed to null
+        let df = parse_date_columns(df, true, &formats, 2, &mut warnings).unwrap();
+
+        assert_eq!(df.column("day").unwrap().dtype(), &DataType::Date);
+        let days: Vec<Option<i32>> = df.column("day").unwrap().date().unwrap().into_iter().collect();
+        assert_eq!(days, vec![Some(19737), Some(19783), None]);
+        let utc = DataType::Datetime(TimeUnit::Microseconds, Some("UTC".to_string()));
+        assert_eq!(df.column("at").unwrap().dtype(), &utc);
+        let at: Vec<Option<i64>> = df.column("at").unwrap().datetime().unwrap().into_iter().collect();
+        assert_eq!(at, vec![Some(1_705_307_400_000_000), Some(1_705_305_600_000_000), None]);
+        let local: Vec<Option<i64>> = df.column("local").unwrap().datetime().unwrap().into_iter().collect();
+        assert_eq!(local[0], Some(1_705_307_400_250_000));
+        assert_eq!(df.column("mixed").unwrap().dtype(), &DataType::String);
+        assert_eq!(df.column("uk").unwrap().null_count(), 1);
+
+        let warnings = warnings.finish();
+        let counts: Vec<(&str, usize)> = warnings.iter().map(|w| (w.column.as_str(), w.count)).collect();
+        assert_eq!(counts, vec![("day", 1), ("at", 1), ("uk", 1)]);
+        assert_eq!(
+            warnings[0].message(),
+            "1 values in column day could not be parsed as dates and were set to null; examples: '2024-02-30'"
+        );
+
+        assert_eq!(DateFormat::from_pattern("%d.%m.%Y %H:%M").unwrap().dtype(), DataType::Datetime(TimeUnit::Microseconds, None));
+        assert!(matches!(DateFormat::from_pattern("%Y-%m-%dT%H:%M:%S%z"), Ok(DateFormat::Zoned(_))));
+        assert!(DateFormat::from_pattern("%Q").is_err());
+    }
+}
@@ -0,0 +1,320 @@
+// Character encodings for CSV input
+// Converts UTF-8 (strict or lossy), Latin-1 and Windows-1252 text to UTF-8 chunk by chunk, dropping a leading byte order mark
+
+use std::io::{BufWriter, Read, Write};
+use std::path::Path;
+use crate::error::InsightoraError;
+use crate::io::compression::{open_decompressed, Compression};
+use crate::utils::scratch::ScratchFile;
+
+/// UTF-8 byte order mark
+const BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+
+/// Bytes read at a time when converting a file
+const TRANSCODE_BUFFER_BYTES: usize = 1 << 20;
+
+/// Characters for bytes 0x80-0x9F in Windows-1252; None where the code page leaves a byte undefined
+const WINDOWS_1252_HIGH: [Option<char>; 32] = [
+    Some('\u{20AC}'), None, Some('\u{201A}'), Some('\u{0192}'),
+    Some('\u{201E}'), Some('\u{2026}'), Some('\u{2020}'), Some('\u{2021}'),
+    Some('\u{02C6}'), Some('\u{2030}'), Some('\u{0160}'), Some('\u{2039}'),
+    Some('\u{0152}'), None, Some('\u{017D}'), None,
+    None, Some('\u{2018}'), Some('\u{2019}'), Some('\u{201C}'),
+    Some('\u{201D}'), Some('\u{2022}'), Some('\u{2013}'), Some('\u{2014}'),
+    Some('\u{02DC}'), Some('\u{2122}'), Some('\u{0161}'), Some('\u{203A}'),
+    Some('\u{0153}'), None, Some('\u{017E}'), Some('\u{0178}'),
+];
+
+/// Encoding of CSV input; anything but strict UTF-8 is converted before Polars reads it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CsvEncoding {
+    #[default]
+    Utf8,
+    /// UTF-8 with invalid sequences replaced by U+FFFD, whatever `EncodingErrors` says
+    Utf8Lossy,
+    /// ISO-8859-1: each byte is the code point of the same value
+    Latin1,
+    /// Latin-1 with €, curly quotes and dashes in 0x80-0x9F, where five bytes are undefined
+    Windows1252,
+}
+
+impl CsvEncoding {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().replace('_', "-").as_str() {
+            "utf8" | "utf-8" => Ok(CsvEncoding::Utf8),
+            "utf8-lossy" | "utf-8-lossy" => Ok(CsvEncoding::Utf8Lossy),
+            "latin1" | "latin-1" | "iso-8859-1" => Ok(CsvEncoding::Latin1),
+            "windows-1252" | "cp1252" => Ok(CsvEncoding::Windows1252),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown encoding '{}'; expected 'utf8', 'utf8-lossy', 'latin1' or 'windows-1252'",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CsvEncoding::Utf8 => "utf8",
+            CsvEncoding::Utf8Lossy => "utf8-lossy",
+            CsvEncoding::Latin1 => "latin1",
+            CsvEncoding::Windows1252 => "windows-1252",
+        }
+    }
+}
+
+/// Handling of byte sequences that aren't valid in the input encoding
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EncodingErrors {
+    /// Fail with the byte offset and line of the first one
+    #[default]
+    Strict,
+    /// Replace each with U+FFFD
+    Replace,
+}
+
+impl EncodingErrors {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "strict" => Ok(EncodingErrors::Strict),
+            "replace" => Ok(EncodingErrors::Replace),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown errors '{}'; expected 'strict' or 'replace'",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EncodingErrors::Strict => "strict",
+            EncodingErrors::Replace => "replace",
+        }
+    }
+}
+
+/// Converts input to UTF-8 one chunk at a time
+///
+/// A UTF-8 sequence cut at the end of a chunk is held back and completed
+/// from the next one, so chunks can be split anywhere.
+pub(crate) struct Transcoder {
+    encoding: CsvEncoding,
+    replace: bool,
+    /// Input bytes converted so far, for error offsets
+    offset: u64,
+    /// Newlines converted so far, for error line numbers
+    lines: usize,
+    /// Bytes held back: the start of a possible byte order mark, or of a cut UTF-8 sequence
+    pending: Vec<u8>,
+    at_start: bool,
+}
+
+impl Transcoder {
+    pub(crate) fn new(encoding: CsvEncoding, errors: EncodingErrors) -> Self {
+        Self {
+            encoding,
+            replace: errors == EncodingErrors::Replace || encoding == CsvEncoding::Utf8Lossy,
+            offset: 0,
+            lines: 0,
+            pending: Vec::new(),
+            at_start: true,
+        }
+    }
+
+    /// Convert the next chunk, appending the UTF-8 text to `out`
+    ///
+    /// # Arguments
+    /// * `chunk` - Input bytes following the previous chunk
+    /// * `last` - No input follows; bytes held back are converted or rejected
+    /// * `out` - Receives the converted text
+    pub(crate) fn push(&mut self, chunk: &[u8], last: bool, out: &mut Vec<u8>) -> Result<(), InsightoraError> {
+        let mut bytes = std::mem::take(&mut self.pending);
+        bytes.extend_from_slice(chunk);
+        let mut start = 0;
+        if self.at_start {
+            if !last && bytes.len() < BOM.len() && BOM.starts_with(&bytes) {
+                self.pending = bytes;
+                return Ok(());
+            }
+            self.at_start = false;
+            if bytes.starts_with(BOM) {
+                start = BOM.len();
+                self.offset += BOM.len() as u64;
+            }
+        }
+        let converted = match self.encoding {
+            CsvEncoding::Utf8 | CsvEncoding::Utf8Lossy => self.utf8(&bytes[start..], last, out)?,
+            CsvEncoding::Latin1 | CsvEncoding::Windows1252 => self.single_byte(&bytes[start..], out)?,
+        };
+        self.pending = bytes[start + converted..].to_vec();
+        Ok(())
+    }
+
+    /// Validate UTF-8, returning the bytes consumed
+    fn utf8(&mut self, mut bytes: &[u8], last: bool, out: &mut Vec<u8>) -> Result<usize, InsightoraError> {
+        let total = bytes.len();
+        loop {
+            let error = match std::str::from_utf8(bytes) {
+                Ok(_) => {
+                    self.emit(bytes, out);
+                    return Ok(total);
+                }
+                Err(e) => e,
+            };
+            let (valid, rest) = bytes.split_at(error.valid_up_to());
+            self.emit(valid, out);
+            let length = match error.error_len() {
+                Some(length) => length,
+                // Cut by the end of the chunk; completed by the next one
+                None if !last => return Ok(total - rest.len()),
+                None => rest.len(),
+            };
+            self.invalid(rest[0], out)?;
+            self.offset += length as u64;
+            bytes = &rest[length..];
+        }
+    }
+
+    /// Map Latin-1 or Windows-1252 bytes to characters; every byte is consumed
+    fn single_byte(&mut self, bytes: &[u8], out: &mut Vec<u8>) -> Result<usize, InsightoraError> {
+        out.reserve(bytes.len());
+        let mut buffer = [0; 4];
+        for &byte in bytes {
+            let c = match (self.encoding, byte) {
+                (CsvEncoding::Windows1252, 0x80..=0x9F) => WINDOWS_1252_HIGH[(byte - 0x80) as usize],
+                _ => Some(byte as char),
+            };
+            match c {
+                Some(c) => {
+                    out.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+                    self.lines += (c == '\n') as usize;
+                }
+                None => self.invalid(byte, out)?,
+            }
+            self.offset += 1;
+        }
+        Ok(bytes.len())
+    }
+
+    fn emit(&mut self, text: &[u8], out: &mut Vec<u8>) {
+        self.offset += text.len() as u64;
+        self.lines += text.iter().filter(|&&b| b == b'\n').count();
+        out.extend_from_slice(text);
+    }
+
+    /// Replace the invalid sequence starting with `byte` at the current offset, or fail naming it
+    fn invalid(&self, byte: u8, out: &mut Vec<u8>) -> Result<(), InsightoraError> {
+        if self.replace {
+            out.extend_from_slice(char::REPLACEMENT_CHARACTER.to_string().as_bytes());
+            return Ok(());
+        }
+        let hint = match self.encoding {
+            CsvEncoding::Windows1252 => "set errors to 'replace', or read it as latin1",
+            _ => "the file may be Latin-1 or Windows-1252 encoded; set encoding, or errors to 'replace'",
+        };
+        Err(InsightoraError::ParseError(format!(
+            "invalid {} (0x{:02X}) at byte {} (line {}); {}",
+            self.encoding.name(),
+            byte,
+            self.offset,
+            self.lines + 1,
+            hint
+        )))
+    }
+}
+
+/// Convert text in memory to UTF-8
+pub(crate) fn transcode_bytes(bytes: &[u8], encoding: CsvEncoding, errors: EncodingErrors) -> Result<Vec<u8>, InsightoraError> {
+    let mut out = Vec::with_capacity(bytes.len());
+    Transcoder::new(encoding, errors).push(bytes, true, &mut out)?;
+    Ok(out)
+}
+
+/// Temporary UTF-8 copy of a file, removed on drop
+pub(crate) struct TranscodedFile(ScratchFile);
+
+impl TranscodedFile {
+    /// Convert a file, decompressing it if needed, `TRANSCODE_BUFFER_BYTES` at a time
+    ///
+    /// Only one buffer of the input is held in memory; the copy is written
+    /// uncompressed so Polars can memory-map it. It is created exclusively,
+    /// with a random name, under the configured `temp_dir`.
+    pub(crate) fn create(path: &Path, encoding: CsvEncoding, errors: EncodingErrors) -> Result<Self, InsightoraError> {
+        let mut copy = ScratchFile::create("insightora-utf8", ".csv")?;
+        let mut input = open_decompressed(path, Compression::detect(path)?)?;
+        let mut output = BufWriter::new(copy.as_file_mut());
+        let mut transcoder = Transcoder::new(encoding, errors);
+        let (mut buffer, mut text) = (vec![0; TRANSCODE_BUFFER_BYTES], Vec::with_capacity(TRANSCODE_BUFFER_BYTES));
+        loop {
+            let read = input.read(&mut buffer)?;
+            text.clear();
+            transcoder.push(&buffer[..read], read == 0, &mut text)?;
+            output.write_all(&text)?;
+            if read == 0 {
+                break;
+            }
+        }
+        output.flush()?;
+        drop(output);
+        Ok(Self(copy))
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        self.0.path()
+    }
+}
+
+/// The first invalid UTF-8 sequence in `input`, as a ParseError naming its
+/// byte offset; None when the text is valid or can't be read
+pub(crate) fn utf8_error(mut input: impl Read) -> Option<InsightoraError> {
+    let mut transcoder = Transcoder::new(CsvEncoding::Utf8, EncodingErrors::Strict);
+    let (mut buffer, mut text) = (vec![0; TRANSCODE_BUFFER_BYTES], Vec::new());
+    loop {
+        let read = input.read(&mut buffer).ok()?;
+        text.clear();
+        if let Err(e) = transcoder.push(&buffer[..read], read == 0, &mut text) {
+            return Some(e);
+        }
+        if read == 0 {
+            return None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transcoder_handles_split_sequences_bom_and_invalid_bytes() {
+        // "é" cut between chunks and a BOM cut after its first byte
+        let text = "\u{FEFF}name,city\nRené,Zürich\n".as_bytes();
+        for split in 0..text.len() {
+            let mut transcoder = Transcoder::new(CsvEncoding::Utf8, EncodingErrors::Strict);
+            let mut out = Vec::new();
+            transcoder.push(&text[..split], false, &mut out).unwrap();
+            transcoder.push(&text[split..], true, &mut out).unwrap();
+            assert_eq!(out, "name,city\nRené,Zürich\n".as_bytes(), "split at {}", split);
+        }
+
+        let cp1252 = b"\xEF\xBB\xBFitem,price\nCaf\xE9 \x93cr\xE8me\x94,\x804\n";
+        let out = transcode_bytes(cp1252, CsvEncoding::Windows1252, EncodingErrors::Strict).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "item,price\nCafé \u{201C}crème\u{201D},€4\n");
+        let out = transcode_bytes(b"a\x80b", CsvEncoding::Latin1, EncodingErrors::Strict).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "a\u{80}b");
+
+        let undefined = transcode_bytes(b"id\n1\n\x81\n", CsvEncoding::Windows1252, EncodingErrors::Strict);
+        match undefined {
+            Err(InsightoraError::ParseError(message)) => assert!(message.contains("at byte 5 (line 3)"), "{}", message),
+            other => panic!("expected a ParseError, got {:?}", other.map(|_| ())),
+        }
+        let latin1_as_utf8 = b"name\nRen\xE9\n";
+        match transcode_bytes(latin1_as_utf8, CsvEncoding::Utf8, EncodingErrors::Strict) {
+            Err(InsightoraError::ParseError(message)) => assert!(message.contains("at byte 8 (line 2)"), "{}", message),
+            other => panic!("expected a ParseError, got {:?}", other.map(|_| ())),
+        }
+        for (encoding, errors) in [(CsvEncoding::Utf8, EncodingErrors::Replace), (CsvEncoding::Utf8Lossy, EncodingErrors::Strict)] {
+            let out = transcode_bytes(latin1_as_utf8, encoding, errors).unwrap();
+            assert_eq!(String::from_utf8(out).unwrap(), "name\nRen\u{FFFD}\n");
+        }
+    }
+}
@@ -1,2 +1,1338 @@
 // Excel file parser implementation
-// TODO: Implement in task 6
+// Reads .xlsx/.xlsm/.xlsb/.xls/.ods worksheets into typed DataFrames with calamine
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use calamine::{open_workbook_auto, Cell, Data, DataRef, Dimensions, Range, Reader, SheetVisible, Sheets};
+use polars::export::chrono::{NaiveDate, NaiveDateTime, Timelike};
+use polars::prelude::*;
+use rayon::prelude::*;
+use crate::config::check_memory_limit;
+use crate::error::InsightoraError;
+use crate::io::compression::ASSUMED_COMPRESSION_RATIO;
+use crate::io::csv_parser::ProgressCallback;
+use crate::utils::memory;
+use crate::utils::sandbox::check_path_allowed;
+
+/// What formula cells hold in the parsed data
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FormulaCells {
+    /// The value Excel computed when the workbook was last saved; null for
+    /// workbooks written by tools that never calculated them
+    #[default]
+    Value,
+    /// The formula as text, e.g. "=SUM(B2:B9)"; other cells keep their values
+    Formula,
+}
+
+impl FormulaCells {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "value" => Ok(FormulaCells::Value),
+            "formula" => Ok(FormulaCells::Formula),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown formulas '{}'; expected 'value' or 'formula'",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FormulaCells::Value => "value",
+            FormulaCells::Formula => "formula",
+        }
+    }
+}
+
+/// How the cells a merged region covers are filled
+///
+/// Excel keeps a merged region's value in its top-left cell only.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergedCells {
+    /// Leave the other cells null, as Excel stores them
+    #[default]
+    Null,
+    /// Repeat the top-left value in every cell of the region (.xlsx and
+    /// .xlsm only; other formats don't expose their merged regions)
+    Repeat,
+}
+
+impl MergedCells {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "null" => Ok(MergedCells::Null),
+            "repeat" => Ok(MergedCells::Repeat),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown merged_cells '{}'; expected 'null' or 'repeat'",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MergedCells::Null => "null",
+            MergedCells::Repeat => "repeat",
+        }
+    }
+}
+
+/// A rectangular block of cells, e.g. "B5:J5000"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellRange {
+    /// (row, column) of the top-left cell, 0-based
+    pub start: (u32, u32),
+    /// (row, column) of the bottom-right cell, 0-based
+    pub end: (u32, u32),
+}
+
+impl CellRange {
+    /// Parse an A1-style range; the corners may be given in either order
+    pub fn parse(text: &str) -> Result<Self, InsightoraError> {
+        let invalid = || {
+            InsightoraError::ValidationError(format!(
+                "Invalid cell range '{}'; expected two cells such as 'B5:J5000'",
+                text
+            ))
+        };
+        let (first, last) = text.split_once(':').ok_or_else(invalid)?;
+        let first = parse_cell(first.trim()).ok_or_else(invalid)?;
+        let last = parse_cell(last.trim()).ok_or_else(invalid)?;
+        Ok(Self {
+            start: (first.0.min(last.0), first.1.min(last.1)),
+            end: (first.0.max(last.0), first.1.max(last.1)),
+        })
+    }
+
+    /// Whether `position` (row, column) lies inside the range
+    pub fn contains(&self, position: (u32, u32)) -> bool {
+        (self.start.0..=self.end.0).contains(&position.0) && (self.start.1..=self.end.1).contains(&position.1)
+    }
+}
+
+impl std::fmt::Display for CellRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", cell_name(self.start), cell_name(self.end))
+    }
+}
+
+/// A1-style cell name to (row, column), both 0-based
+fn parse_cell(name: &str) -> Option<(u32, u32)> {
+    let split = name.find(|c: char| !c.is_ascii_alphabetic())?;
+    let (letters, digits) = name.split_at(split);
+    if letters.is_empty() || letters.len() > 3 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let column = letters
+        .bytes()
+        .fold(0u32, |column, letter| column * 26 + u32::from(letter.to_ascii_uppercase() - b'A') + 1);
+    let row: u32 = digits.parse().ok()?;
+    Some((row.checked_sub(1)?, column - 1))
+}
+
+/// (row, column), both 0-based, to an A1-style cell name
+fn cell_name((row, column): (u32, u32)) -> String {
+    let mut letters = Vec::new();
+    let mut rest = column + 1;
+    while rest > 0 {
+        letters.push(b'A' + ((rest - 1) % 26) as u8);
+        rest = (rest - 1) / 26;
+    }
+    letters.reverse();
+    format!("{}{}", String::from_utf8_lossy(&letters), row + 1)
+}
+
+/// Options for reading a worksheet
+///
+/// Rows are counted from the top of the selection: the first row of
+/// `cell_range`, or the sheet's first row. With neither `skip_rows` nor
+/// `header_row`, leading blank rows are skipped and the first non-empty
+/// row is the header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExcelOptions {
+    /// Take column names from the first row; blank names become `column_N`
+    /// and repeated ones get a `_N` suffix
+    pub has_header: bool,
+    /// Row holding the column names, 0-based after `skip_rows`; rows above
+    /// it are skipped. Needs `has_header`
+    pub header_row: Option<usize>,
+    /// Rows skipped at the top of the selection, e.g. a title block
+    pub skip_rows: usize,
+    /// Only read these cells; cells outside are never converted
+    pub cell_range: Option<CellRange>,
+    pub formulas: FormulaCells,
+    pub merged_cells: MergedCells,
+}
+
+impl Default for ExcelOptions {
+    fn default() -> Self {
+        Self {
+            has_header: true,
+            header_row: None,
+            skip_rows: 0,
+            cell_range: None,
+            formulas: FormulaCells::default(),
+            merged_cells: MergedCells::default(),
+        }
+    }
+}
+
+/// A worksheet's name and the size of its used range
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SheetInfo {
+    pub name: String,
+    /// Rows from the first to the last non-empty row, header included
+    pub rows: usize,
+    /// Columns from the first to the last non-empty column
+    pub columns: usize,
+    /// Hidden in Excel's sheet tabs (hidden or very hidden)
+    pub hidden: bool,
+}
+
+/// An open workbook, read sheet by sheet
+///
+/// Opening reads the workbook's sheet list and shared strings once, so
+/// reading several sheets through one parser doesn't re-read them.
+pub struct ExcelParser {
+    file_path: String,
+    workbook: Sheets<BufReader<File>>,
+}
+
+impl ExcelParser {
+    /// Open a workbook after the path and memory checks
+    ///
+    /// Sheets are compressed XML (or binary records for .xls and .xlsb) and
+    /// a sheet is held whole as calamine cells while it is read, so the
+    /// memory estimate is `ASSUMED_COMPRESSION_RATIO` times the file size.
+    ///
+    /// # Arguments
+    /// * `file_path` - Path to an .xlsx, .xlsm, .xlsb, .xls or .ods file
+    pub fn open(file_path: &str) -> Result<Self, InsightoraError> {
+        let path = check_path_allowed(file_path)?;
+        if !path.exists() {
+            return Err(InsightoraError::IoError(
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("File not found: {}", file_path)
+                )
+            ));
+        }
+        let file_size = std::fs::metadata(&path)?.len();
+        check_memory_limit((file_size.saturating_mul(ASSUMED_COMPRESSION_RATIO) / (1024 * 1024)) as usize)?;
+        let workbook = open_workbook_auto(&path)
+            .map_err(|e| InsightoraError::ParseError(format!("Failed to open workbook {}: {}", file_path, e)))?;
+        Ok(Self { file_path: file_path.to_string(), workbook })
+    }
+
+    /// Sheet names in workbook order
+    pub fn sheet_names(&self) -> Vec<String> {
+        self.workbook.sheet_names()
+    }
+
+    fn is_hidden(&self, sheet: &str) -> bool {
+        self.workbook
+            .sheets_metadata()
+            .iter()
+            .any(|meta| meta.name == sheet && meta.visible != SheetVisible::Visible)
+    }
+
+    /// Read one worksheet's used range
+    fn read_range(&mut self, sheet: &str) -> Result<Range<Data>, InsightoraError> {
+        self.workbook
+            .worksheet_range(sheet)
+            .map_err(|e| InsightoraError::ParseError(format!("Failed to read sheet '{}': {}", sheet, e)))
+    }
+
+    /// Read the cells of a worksheet inside `bounds`, and the sheet's whole used range
+    ///
+    /// .xlsx and .xlsm sheets are streamed and cells outside `bounds` are
+    /// dropped as they are read; other formats are read whole, then cut.
+    fn read_bounded(&mut self, sheet: &str, bounds: &CellRange) -> Result<(Range<Data>, Option<CellRange>), InsightoraError> {
+        let read_error = |e: &dyn std::fmt::Display| InsightoraError::ParseError(format!("Failed to read sheet '{}': {}", sheet, e));
+        match &mut self.workbook {
+            Sheets::Xlsx(workbook) => {
+                let mut reader = workbook.worksheet_cells_reader(sheet).map_err(|e| read_error(&e))?;
+                let mut cells = Vec::new();
+                let mut used = None;
+                while let Some(cell) = reader.next_cell().map_err(|e| read_error(&e))? {
+                    if *cell.get_value() == DataRef::Empty {
+                        continue;
+                    }
+                    used = Some(extended(used, cell.get_position()));
+                    if bounds.contains(cell.get_position()) {
+                        cells.push(Cell::new(cell.get_position(), Data::from(cell.get_value().clone())));
+                    }
+                }
+                Ok((Range::from_sparse(cells), used))
+            }
+            _ => {
+                let range = self.read_range(sheet)?;
+                let Some(used) = range.start().zip(range.end()).map(|(start, end)| CellRange { start, end }) else {
+                    return Ok((range, None));
+                };
+                let start = (used.start.0.max(bounds.start.0), used.start.1.max(bounds.start.1));
+                let end = (used.end.0.min(bounds.end.0), used.end.1.min(bounds.end.1));
+                let cut = if start.0 > end.0 || start.1 > end.1 {
+                    Range::empty()
+                } else if start == used.start && end == used.end {
+                    range
+                } else {
+                    range.range(start, end)
+                };
+                Ok((cut, Some(used)))
+            }
+        }
+    }
+
+    /// Read the table of a worksheet selected by `options`, with formulas
+    /// and merged cells handled as they ask
+    fn read_cells(&mut self, sheet: &str, options: &ExcelOptions) -> Result<Range<Data>, InsightoraError> {
+        if options.header_row.is_some() && !options.has_header {
+            return Err(InsightoraError::ValidationError("header_row needs has_header".to_string()));
+        }
+        let selection = options.cell_range.unwrap_or(CellRange { start: (0, 0), end: (u32::MAX, u32::MAX) });
+        let offset = options.skip_rows.saturating_add(options.header_row.unwrap_or(0));
+        let first_row = selection.start.0.saturating_add(u32::try_from(offset).unwrap_or(u32::MAX));
+        let bounds = CellRange { start: (first_row, selection.start.1), end: selection.end };
+        let (mut range, used) = self.read_bounded(sheet, &bounds)?;
+
+        let targeted = options.cell_range.is_some() || offset > 0;
+        match used {
+            None if targeted => {
+                return Err(InsightoraError::ValidationError(format!("Sheet '{}' is empty", sheet)));
+            }
+            Some(used) if options.cell_range.is_some_and(|requested| requested.start.0 > used.end.0 || requested.start.1 > used.end.1) => {
+                return Err(InsightoraError::ValidationError(format!(
+                    "Cell range {} is outside the used range {} of sheet '{}'",
+                    selection, used, sheet
+                )));
+            }
+            Some(used) if first_row > used.end.0 => {
+                let what = match options.header_row {
+                    Some(header_row) => format!("header_row {}", header_row),
+                    None => format!("skip_rows {}", options.skip_rows),
+                };
+                return Err(InsightoraError::ValidationError(format!(
+                    "{} selects row {}, past the last row of sheet '{}' (used range {})",
+                    what,
+                    first_row + 1,
+                    sheet,
+                    used
+                )));
+            }
+            _ => {}
+        }
+
+        if options.formulas == FormulaCells::Formula {
+            let formulas = self
+                .workbook
+                .worksheet_formula(sheet)
+                .map_err(|e| InsightoraError::ParseError(format!("Failed to read formulas of sheet '{}': {}", sheet, e)))?;
+            let cells = match formulas.start() {
+                Some((top, left)) => formulas
+                    .used_cells()
+                    .map(|(row, col, formula)| ((top + row as u32, left + col as u32), Data::String(format!("={}", formula))))
+                    .filter(|(position, _)| bounds.contains(*position))
+                    .collect(),
+                None => Vec::new(),
+            };
+            range = with_cells(range, cells);
+        }
+        if options.merged_cells == MergedCells::Repeat {
+            let cells = self
+                .merged_regions(sheet)?
+                .into_iter()
+                // A region whose top-left cell is outside the selection has no value to repeat
+                .filter_map(|region| range.get_value(region.start).cloned().map(|value| (region, value)))
+                .flat_map(|(region, value)| {
+                    (region.start.0..=region.end.0)
+                        .flat_map(move |row| (region.start.1..=region.end.1).map(move |col| (row, col)))
+                        .filter(move |&position| position != region.start && bounds.contains(position))
+                        .map(move |position| (position, value.clone()))
+                })
+                .collect();
+            range = with_cells(range, cells);
+        }
+        Ok(trimmed(range, options.header_row.map(|_| first_row)))
+    }
+
+    /// Merged regions of a sheet; only .xlsx and .xlsm workbooks record them for calamine
+    fn merged_regions(&mut self, sheet: &str) -> Result<Vec<Dimensions>, InsightoraError> {
+        match &mut self.workbook {
+            Sheets::Xlsx(workbook) => {
+                workbook
+                    .load_merged_regions()
+                    .map_err(|e| InsightoraError::ParseError(format!("Failed to read merged cells: {}", e)))?;
+                Ok(workbook.merged_regions_by_sheet(sheet).into_iter().map(|(_, _, region)| *region).collect())
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Name, dimensions and visibility of every sheet, in workbook order
+    ///
+    /// Each sheet is read to measure its used range, one at a time.
+    pub fn sheets(&mut self) -> Result<Vec<SheetInfo>, InsightoraError> {
+        self.sheet_names()
+            .into_iter()
+            .map(|name| {
+                let (rows, columns) = self.read_range(&name)?.get_size();
+                let hidden = self.is_hidden(&name);
+                Ok(SheetInfo { name, rows, columns, hidden })
+            })
+            .collect()
+    }
+
+    /// Parse one worksheet into a DataFrame, as `parse_excel` does
+    pub fn parse(&mut self, sheet: Option<&str>, options: &ExcelOptions) -> Result<DataFrame, InsightoraError> {
+        let name = resolve_sheet(&self.file_path, &self.sheet_names(), sheet)?;
+        let range = self.read_cells(&name, options)?;
+        range_to_dataframe(&range, options.has_header)
+    }
+
+    /// Parse every worksheet, each on its own
+    ///
+    /// The workbook reader is sequential, so sheets are decompressed one
+    /// after another; their cells are then typed into DataFrames in
+    /// parallel on the Rayon pool. A sheet that fails to read or convert
+    /// gets its error without affecting the others.
+    ///
+    /// # Arguments
+    /// * `options` - As in `parse_excel`, for every sheet
+    /// * `include_hidden` - Also parse sheets hidden in Excel
+    ///
+    /// # Returns
+    /// * `Vec<(String, Result<DataFrame>)>` - Sheet name and its data or
+    ///   error, in workbook order
+    ///
+    /// # Example
+    /// ```no_run
+    /// use insightora_core::api::{ExcelOptions, ExcelParser};
+    ///
+    /// let mut parser = ExcelParser::open("budget.xlsx").unwrap();
+    /// for (sheet, result) in parser.parse_all(&ExcelOptions::default(), false) {
+    ///     match result {
+    ///         Ok(df) => println!("{}: {} rows", sheet, df.height()),
+    ///         Err(e) => println!("{}: {}", sheet, e),
+    ///     }
+    /// }
+    /// ```
+    pub fn parse_all(&mut self, options: &ExcelOptions, include_hidden: bool) -> Vec<(String, Result<DataFrame, InsightoraError>)> {
+        let names: Vec<String> = self
+            .sheet_names()
+            .into_iter()
+            .filter(|name| include_hidden || !self.is_hidden(name))
+            .collect();
+        let ranges: Vec<(String, Result<Range<Data>, InsightoraError>)> = names
+            .into_iter()
+            .map(|name| {
+                let range = self.read_cells(&name, options);
+                (name, range)
+            })
+            .collect();
+        ranges
+            .into_par_iter()
+            .map(|(name, range)| {
+                let df = range.and_then(|range| range_to_dataframe(&range, options.has_header));
+                (name, df)
+            })
+            .collect()
+    }
+}
+
+/// List the worksheets of a workbook in workbook order
+///
+/// Each sheet is read to measure its used range, one at a time.
+///
+/// # Arguments
+/// * `file_path` - Path to an .xlsx, .xlsm, .xlsb, .xls or .ods file
+///
+/// # Returns
+/// * `Result<Vec<SheetInfo>>` - Name, dimensions and visibility per sheet
+pub fn list_sheets(file_path: &str) -> Result<Vec<SheetInfo>, InsightoraError> {
+    ExcelParser::open(file_path)?.sheets()
+}
+
+/// Parse a worksheet into a DataFrame
+///
+/// The sheet's used range is read from its first non-empty cell, so
+/// leading blank rows and columns are skipped. Column types come from the
+/// cells: all booleans become Boolean, all whole numbers Int64, other
+/// numbers Float64, dates with no time of day Date, other dates Datetime
+/// (ms) and durations Duration (ms). Columns mixing these are text, with
+/// numbers and dates written out. Empty and error cells (`#N/A`) are null.
+///
+/// Numbers in a date or time format are dates, counted from the workbook's
+/// own epoch (1900, or 1904 for workbooks made on older Macs); ISO 8601
+/// dates in .ods files are dates too. Formula cells hold their cached
+/// value, or their formula with `FormulaCells::Formula`.
+///
+/// `options.cell_range`, `skip_rows` and `header_row` pick the table out of
+/// a sheet with titles or notes around it. A range that starts past the
+/// sheet's used range, or a header row past its last row, is a
+/// ValidationError quoting the used range.
+///
+/// # Arguments
+/// * `file_path` - Path to an .xlsx, .xlsm, .xlsb, .xls or .ods file
+/// * `sheet` - Sheet name; None reads the first sheet
+/// * `options` - Header row, formula and merged-cell handling
+///
+/// # Returns
+/// * `Result<DataFrame>` - Parsed sheet; an unknown sheet name is a
+///   ValidationError listing the sheets
+///
+/// # Example
+/// ```no_run
+/// use insightora_core::api::{parse_excel, ExcelOptions};
+///
+/// let df = parse_excel("budget.xlsx", Some("2024"), &ExcelOptions::default()).unwrap();
+/// println!("{}", df.head(Some(5)));
+/// ```
+pub fn parse_excel(file_path: &str, sheet: Option<&str>, options: &ExcelOptions) -> Result<DataFrame, InsightoraError> {
+    ExcelParser::open(file_path)?.parse(sheet, options)
+}
+
+/// The sheet to read: `sheet` if the workbook has it, else the first sheet
+fn resolve_sheet(file_path: &str, names: &[String], sheet: Option<&str>) -> Result<String, InsightoraError> {
+    match sheet {
+        Some(sheet) if names.iter().any(|name| name == sheet) => Ok(sheet.to_string()),
+        Some(sheet) => Err(InsightoraError::ValidationError(format!(
+            "Sheet '{}' not found in {}; available sheets: {}",
+            sheet,
+            file_path,
+            names.join(", ")
+        ))),
+        None => names
+            .first()
+            .cloned()
+            .ok_or_else(|| InsightoraError::ParseError(format!("{} has no worksheets", file_path))),
+    }
+}
+
+/// `range` with `cells`, at absolute positions, written over it
+///
+/// The range grows to hold cells outside it, e.g. a formula whose cached
+/// value was never saved.
+fn with_cells(mut range: Range<Data>, cells: Vec<((u32, u32), Data)>) -> Range<Data> {
+    if cells.is_empty() {
+        return range;
+    }
+    let positions = || cells.iter().map(|(position, _)| *position).chain(range.start()).chain(range.end());
+    let start = positions().fold((u32::MAX, u32::MAX), |(row, col), (r, c)| (row.min(r), col.min(c)));
+    let end = positions().fold((0, 0), |(row, col), (r, c)| (row.max(r), col.max(c)));
+    if range.start() != Some(start) || range.end() != Some(end) {
+        let mut grown = Range::new(start, end);
+        if let Some((top, left)) = range.start() {
+            for (row, col, value) in range.used_cells() {
+                grown.set_value((top + row as u32, left + col as u32), value.clone());
+            }
+        }
+        range = grown;
+    }
+    for (position, value) in cells {
+        range.set_value(position, value);
+    }
+    range
+}
+
+/// The bounds `used` grown to hold `position`
+fn extended(used: Option<CellRange>, position: (u32, u32)) -> CellRange {
+    match used {
+        Some(used) => CellRange {
+            start: (used.start.0.min(position.0), used.start.1.min(position.1)),
+            end: (used.end.0.max(position.0), used.end.1.max(position.1)),
+        },
+        None => CellRange { start: position, end: position },
+    }
+}
+
+/// `range` cut to its non-empty cells, starting at `first_row` when given
+/// so that a blank header row is kept
+fn trimmed(range: Range<Data>, first_row: Option<u32>) -> Range<Data> {
+    let Some((top, left)) = range.start() else {
+        return range;
+    };
+    let used = range
+        .used_cells()
+        .fold(None, |used, (row, col, _)| Some(extended(used, (top + row as u32, left + col as u32))));
+    match used {
+        Some(used) => {
+            let start = (first_row.unwrap_or(used.start.0).min(used.start.0), used.start.1);
+            if range.start() == Some(start) && range.end() == Some(used.end) {
+                range
+            } else {
+                range.range(start, used.end)
+            }
+        }
+        None => Range::empty(),
+    }
+}
+
+/// Build a DataFrame from a sheet's cells, one typed column per range column
+fn range_to_dataframe(range: &Range<Data>, has_header: bool) -> Result<DataFrame, InsightoraError> {
+    let width = range.width();
+    let mut rows = range.rows();
+    let header: Vec<String> = if has_header {
+        rows.next().map(|row| row.iter().map(cell_text).collect()).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    let body: Vec<&[Data]> = rows.collect();
+
+    let mut seen = HashSet::new();
+    let columns = (0..width)
+        .map(|index| {
+            let name = column_name(header.get(index).map(String::as_str), index, &mut seen);
+            column_series(&name, body.iter().copied().map(move |row| &row[index]))
+        })
+        .collect::<Result<Vec<Series>, InsightoraError>>()?;
+    Ok(DataFrame::new(columns)?)
+}
+
+/// A unique column name: the header text, or `column_N` (1-based) when blank
+fn column_name(header: Option<&str>, index: usize, seen: &mut HashSet<String>) -> String {
+    let base = match header.map(str::trim) {
+        Some(text) if !text.is_empty() => text.to_string(),
+        _ => format!("column_{}", index + 1),
+    };
+    let mut name = base.clone();
+    let mut suffix = 1;
+    while !seen.insert(name.clone()) {
+        name = format!("{}_{}", base, suffix);
+        suffix += 1;
+    }
+    name
+}
+
+/// The type every non-null cell of a column fits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CellKind {
+    Null,
+    Boolean,
+    Integer,
+    Float,
+    Date,
+    Datetime,
+    Duration,
+    Text,
+}
+
+impl CellKind {
+    fn of(cell: &Data) -> Self {
+        match cell {
+            Data::Empty | Data::Error(_) => CellKind::Null,
+            Data::Bool(_) => CellKind::Boolean,
+            Data::Int(_) => CellKind::Integer,
+            // Excel stores every number as a double
+            Data::Float(value) if value.fract() == 0.0 && value.abs() < 9.0e15 => CellKind::Integer,
+            Data::Float(_) => CellKind::Float,
+            Data::DateTime(value) if value.is_duration() => CellKind::Duration,
+            Data::DateTime(value) => match value.as_datetime() {
+                Some(at) if at.time().num_seconds_from_midnight() == 0 && at.time().nanosecond() == 0 => CellKind::Date,
+                Some(_) => CellKind::Datetime,
+                None => CellKind::Text,
+            },
+            Data::DateTimeIso(text) => match parse_iso_datetime(text) {
+                Some((_, false)) => CellKind::Date,
+                Some((_, true)) => CellKind::Datetime,
+                None => CellKind::Text,
+            },
+            Data::String(_) | Data::DurationIso(_) => CellKind::Text,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            CellKind::Null => "empty",
+            CellKind::Boolean => "boolean",
+            CellKind::Integer => "integer",
+            CellKind::Float => "float",
+            CellKind::Date => "date",
+            CellKind::Datetime => "datetime",
+            CellKind::Duration => "duration",
+            CellKind::Text => "text",
+        }
+    }
+
+    /// The kind a column holding both `self` and `other` cells takes
+    fn widen(self, other: Self) -> Self {
+        match (self, other) {
+            _ if self == other => self,
+            (CellKind::Null, kind) | (kind, CellKind::Null) => kind,
+            (CellKind::Integer, CellKind::Float) | (CellKind::Float, CellKind::Integer) => CellKind::Float,
+            (CellKind::Date, CellKind::Datetime) | (CellKind::Datetime, CellKind::Date) => CellKind::Datetime,
+            _ => CellKind::Text,
+        }
+    }
+}
+
+fn column_series<'a>(name: &str, cells: impl Iterator<Item = &'a Data> + Clone) -> Result<Series, InsightoraError> {
+    typed_series(name, cells.clone(), column_kind(cells))
+}
+
+/// The kind every cell fits
+fn column_kind<'a>(cells: impl Iterator<Item = &'a Data>) -> CellKind {
+    cells.fold(CellKind::Null, |kind, cell| kind.widen(CellKind::of(cell)))
+}
+
+/// A column of `kind` from cells that fit it
+fn typed_series<'a>(name: &str, cells: impl Iterator<Item = &'a Data>, kind: CellKind) -> Result<Series, InsightoraError> {
+    let series = match kind {
+        CellKind::Boolean => Series::new(name, cells.map(|cell| match cell {
+            Data::Bool(value) => Some(*value),
+            _ => None,
+        }).collect::<Vec<_>>()),
+        CellKind::Integer => Series::new(name, cells.map(cell_integer).collect::<Vec<_>>()),
+        CellKind::Float => Series::new(name, cells.map(cell_float).collect::<Vec<_>>()),
+        CellKind::Date => Series::new(name, cells.map(|cell| cell_datetime(cell).map(|at| days_since_epoch(&at))).collect::<Vec<_>>())
+            .cast(&DataType::Date)?,
+        CellKind::Datetime => Series::new(name, cells.map(|cell| cell_datetime(cell).map(|at| millis_since_epoch(&at))).collect::<Vec<_>>())
+            .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))?,
+        CellKind::Duration => Series::new(name, cells.map(cell_duration_ms).collect::<Vec<_>>())
+            .cast(&DataType::Duration(TimeUnit::Milliseconds))?,
+        CellKind::Null | CellKind::Text => Series::new(name, cells.map(|cell| match cell {
+            Data::Empty | Data::Error(_) => None,
+            other => Some(cell_text(other)),
+        }).collect::<Vec<_>>()),
+    };
+    Ok(series)
+}
+
+fn cell_integer(cell: &Data) -> Option<i64> {
+    match cell {
+        Data::Int(value) => Some(*value),
+        Data::Float(value) => Some(*value as i64),
+        _ => None,
+    }
+}
+
+fn cell_float(cell: &Data) -> Option<f64> {
+    match cell {
+        Data::Int(value) => Some(*value as f64),
+        Data::Float(value) => Some(*value),
+        _ => None,
+    }
+}
+
+fn cell_datetime(cell: &Data) -> Option<NaiveDateTime> {
+    match cell {
+        Data::DateTime(value) => value.as_datetime(),
+        Data::DateTimeIso(text) => parse_iso_datetime(text).map(|(at, _)| at),
+        _ => None,
+    }
+}
+
+/// An ISO 8601 date or date time, and whether it had a time part
+fn parse_iso_datetime(text: &str) -> Option<(NaiveDateTime, bool)> {
+    if let Ok(at) = NaiveDateTime::parse_from_str(text, "%Y-%m-%dT%H:%M:%S%.f") {
+        return Some((at, true));
+    }
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0).map(|at| (at, false))
+}
+
+fn cell_duration_ms(cell: &Data) -> Option<i64> {
+    match cell {
+        Data::DateTime(value) => value.as_duration().map(|duration| duration.num_milliseconds()),
+        _ => None,
+    }
+}
+
+fn unix_epoch() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(1970, 1, 1).unwrap_or_default().and_hms_opt(0, 0, 0).unwrap_or_default()
+}
+
+fn days_since_epoch(at: &NaiveDateTime) -> i32 {
+    (*at - unix_epoch()).num_days() as i32
+}
+
+fn millis_since_epoch(at: &NaiveDateTime) -> i64 {
+    (*at - unix_epoch()).num_milliseconds()
+}
+
+/// A cell as text: whole numbers without a fraction, dates in ISO 8601
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Empty => String::new(),
+        Data::Float(value) if CellKind::of(cell) == CellKind::Integer => format!("{}", *value as i64),
+        Data::DateTime(_) | Data::DateTimeIso(_) => match (CellKind::of(cell), cell_datetime(cell)) {
+            (CellKind::Date, Some(at)) => at.format("%Y-%m-%d").to_string(),
+            (CellKind::Datetime, Some(at)) => at.format("%Y-%m-%d %H:%M:%S").to_string(),
+            _ => cell.to_string(),
+        },
+        other => other.to_string(),
+    }
+}
+
+// ============================================================================
+// Streaming Excel Reader for Large Workbooks
+// ============================================================================
+
+/// Streaming Excel reader configuration
+#[derive(Debug, Clone)]
+pub struct StreamingExcelConfig {
+    /// Sheet name; None reads the first sheet
+    pub sheet: Option<String>,
+    /// Rows per batch
+    pub chunk_size: usize,
+    pub memory_limit_mb: usize,
+    pub has_header: bool,
+}
+
+impl Default for StreamingExcelConfig {
+    fn default() -> Self {
+        Self {
+            sheet: None,
+            chunk_size: 100_000,
+            memory_limit_mb: 1024,
+            has_header: true,
+        }
+    }
+}
+
+/// Streaming reader for worksheets too large to hold in memory
+///
+/// `parse_excel` holds every cell of a sheet at once. This reader walks
+/// the sheet's XML row by row and keeps only the rows of the batch being
+/// built, plus the workbook's shared strings, so it reads .xlsx and .xlsm
+/// workbooks only. The first batch fixes each column's type, as the first
+/// chunk of a streamed CSV does; a later value that doesn't fit is a
+/// ParseError. Formula cells hold their cached values and merged regions
+/// their top-left value only.
+pub struct StreamingExcelParser {
+    config: StreamingExcelConfig,
+    progress_callback: Option<ProgressCallback>,
+    /// Checked before each batch; once set the parse stops with `Cancelled`
+    cancel: Option<Arc<AtomicBool>>,
+}
+
+impl StreamingExcelParser {
+    pub fn new() -> Self {
+        Self::with_config(StreamingExcelConfig::default())
+    }
+
+    pub fn with_config(config: StreamingExcelConfig) -> Self {
+        Self {
+            config,
+            progress_callback: None,
+            cancel: None,
+        }
+    }
+
+    /// Report `(rows_read, total_rows)` after each batch; the total comes
+    /// from the sheet's declared dimension and is 0 when it has none
+    pub fn with_progress_callback(mut self, callback: ProgressCallback) -> Self {
+        self.progress_callback = Some(callback);
+        self
+    }
+
+    /// Stop with `InsightoraError::Cancelled` at the next batch once `flag` is set
+    pub fn with_cancel_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(flag);
+        self
+    }
+
+    pub fn config(&self) -> &StreamingExcelConfig {
+        &self.config
+    }
+
+    fn report(&self, rows_read: usize, total_rows: usize) -> Result<(), InsightoraError> {
+        if let Some(callback) = &self.progress_callback {
+            callback(rows_read, total_rows);
+        }
+        self.check_cancelled()
+    }
+
+    fn check_cancelled(&self) -> Result<(), InsightoraError> {
+        match &self.cancel {
+            Some(flag) if flag.load(Ordering::Relaxed) => Err(InsightoraError::Cancelled),
+            _ => Ok(()),
+        }
+    }
+
+    /// Read a worksheet batch by batch into one DataFrame
+    ///
+    /// The combined data is checked against the memory limit after every
+    /// batch, so a sheet too large for it fails early instead of after
+    /// reading every row.
+    ///
+    /// # Arguments
+    /// * `file_path` - Path to an .xlsx or .xlsm file
+    ///
+    /// # Returns
+    /// * `Result<DataFrame>` - Parsed sheet
+    pub fn parse_streaming(&self, file_path: &str) -> Result<DataFrame, InsightoraError> {
+        let mut combined: Option<DataFrame> = None;
+        self.parse_batches(file_path, |batch| {
+            let df = match combined.as_mut() {
+                Some(df) => {
+                    df.vstack_mut(&batch)?;
+                    df
+                }
+                None => combined.insert(batch),
+            };
+            check_memory_limit(df.estimated_size() / (1024 * 1024))
+        })?;
+        let mut df = combined.unwrap_or_else(DataFrame::empty);
+        df.align_chunks();
+        Ok(df)
+    }
+
+    /// Read a worksheet in batches of `chunk_size` rows and process each with a callback
+    ///
+    /// Each batch is checked against the memory limit before it is
+    /// processed. The header is the sheet's first non-empty row; columns
+    /// span the sheet's declared dimension, or its first row when it
+    /// declares none.
+    ///
+    /// # Arguments
+    /// * `file_path` - Path to an .xlsx or .xlsm file
+    /// * `batch_processor` - Function to process each batch
+    ///
+    /// # Example
+    /// ```no_run
+    /// use insightora_core::api::{StreamingExcelConfig, StreamingExcelParser};
+    ///
+    /// let parser = StreamingExcelParser::with_config(StreamingExcelConfig { chunk_size: 50_000, ..Default::default() });
+    /// let mut rows = 0;
+    /// parser.parse_batches("ledger.xlsx", |batch| {
+    ///     rows += batch.height();
+    ///     Ok(())
+    /// }).unwrap();
+    /// ```
+    pub fn parse_batches<F>(&self, file_path: &str, mut batch_processor: F) -> Result<(), InsightoraError>
+    where
+        F: FnMut(DataFrame) -> Result<(), InsightoraError>,
+    {
+        let path = check_path_allowed(file_path)?;
+        if !path.exists() {
+            return Err(InsightoraError::IoError(
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("File not found: {}", file_path)
+                )
+            ));
+        }
+        let mut workbook = match open_workbook_auto(&path)
+            .map_err(|e| InsightoraError::ParseError(format!("Failed to open workbook {}: {}", file_path, e)))?
+        {
+            Sheets::Xlsx(workbook) => workbook,
+            _ => {
+                return Err(InsightoraError::ValidationError(format!(
+                    "Streaming reads .xlsx and .xlsm workbooks only; read {} with parse_excel",
+                    file_path
+                )));
+            }
+        };
+        let sheet = resolve_sheet(file_path, &workbook.sheet_names(), self.config.sheet.as_deref())?;
+        let read_error = |e: calamine::XlsxError| InsightoraError::ParseError(format!("Failed to read sheet '{}': {}", sheet, e));
+
+        let _watch = memory::watch("parse_excel_batches");
+        let mut reader = workbook.worksheet_cells_reader(&sheet).map_err(read_error)?;
+        // Sheets without a <dimension> element report A1
+        let declared = Some(reader.dimensions()).filter(|dimensions| *dimensions != Dimensions::default());
+        let total_rows = declared.map_or(0, |dimensions| {
+            (dimensions.end.0 - dimensions.start.0 + 1) as usize - usize::from(self.config.has_header)
+        });
+        let mut rows = RowBatches::new(
+            self.config.has_header,
+            self.config.chunk_size.max(1),
+            declared.map(|dimensions| (dimensions.start.1, dimensions.end.1)),
+        );
+        let mut rows_read = 0;
+        loop {
+            let cell = reader.next_cell().map_err(read_error)?;
+            let finished = cell.is_none();
+            match cell {
+                Some(cell) if *cell.get_value() == DataRef::Empty => continue,
+                Some(cell) => rows.add(cell.get_position(), Data::from(cell.get_value().clone()))?,
+                None => rows.finish_row()?,
+            }
+            while let Some(batch) = rows.next_batch(finished)? {
+                self.check_cancelled()?;
+                check_memory_limit(batch.estimated_size() / (1024 * 1024))?;
+                rows_read += batch.height();
+                batch_processor(batch)?;
+                memory::checkpoint()?;
+                let total = if finished && rows.is_empty() { rows_read } else { total_rows.max(rows_read) };
+                self.report(rows_read, total)?;
+            }
+            if finished {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Estimated memory in MB to read the largest worksheet of an .xlsx
+    /// or .xlsm workbook whole
+    ///
+    /// Sheets are compressed in the file, so this is from the uncompressed
+    /// size of each sheet's XML rather than the file size: about twice it,
+    /// for calamine's cells and the typed columns built from them.
+    pub fn estimate_memory_usage(&self, file_path: &str) -> Result<usize, InsightoraError> {
+        let path = check_path_allowed(file_path)?;
+        let zip_error = |e: ::zip::result::ZipError| InsightoraError::ParseError(format!("Failed to open workbook {}: {}", file_path, e));
+        let mut archive = ::zip::ZipArchive::new(File::open(&path)?).map_err(zip_error)?;
+        let mut largest = 0u64;
+        for index in 0..archive.len() {
+            let entry = archive.by_index_raw(index).map_err(zip_error)?;
+            if entry.name().starts_with("xl/worksheets/") && entry.name().ends_with(".xml") {
+                largest = largest.max(entry.size());
+            }
+        }
+        Ok((largest.saturating_mul(2) / (1024 * 1024)) as usize)
+    }
+
+    /// Check if streaming is recommended for a workbook
+    pub fn should_use_streaming(&self, file_path: &str) -> Result<bool, InsightoraError> {
+        Ok(self.estimate_memory_usage(file_path)? > self.config.memory_limit_mb)
+    }
+}
+
+impl Default for StreamingExcelParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Rows of a sheet, gathered cell by cell into batches
+struct RowBatches {
+    has_header: bool,
+    chunk_size: usize,
+    /// First and last column read
+    columns: Option<(u32, u32)>,
+    names: Option<Vec<String>>,
+    /// Column types, fixed by the first batch
+    kinds: Option<Vec<CellKind>>,
+    /// The row being read: its index and cells (column, value)
+    current: Option<(u32, Vec<(u32, Data)>)>,
+    last_row: Option<u32>,
+    rows: Vec<Vec<Data>>,
+    rows_batched: usize,
+}
+
+impl RowBatches {
+    fn new(has_header: bool, chunk_size: usize, columns: Option<(u32, u32)>) -> Self {
+        Self {
+            has_header,
+            chunk_size,
+            columns,
+            names: None,
+            kinds: None,
+            current: None,
+            last_row: None,
+            rows: Vec::new(),
+            rows_batched: 0,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Add a non-empty cell; cells arrive in row order
+    fn add(&mut self, (row, col): (u32, u32), value: Data) -> Result<(), InsightoraError> {
+        if self.current.as_ref().is_some_and(|(index, _)| *index != row) {
+            self.finish_row()?;
+        }
+        self.current.get_or_insert_with(|| (row, Vec::new())).1.push((col, value));
+        Ok(())
+    }
+
+    /// Move the row being read into the batch, after blank rows for any gap
+    /// before it; the first row is the header when there is one
+    fn finish_row(&mut self) -> Result<(), InsightoraError> {
+        let Some((index, cells)) = self.current.take() else {
+            return Ok(());
+        };
+        let (first, last) = *self.columns.get_or_insert_with(|| {
+            let columns = cells.iter().map(|(col, _)| *col);
+            (columns.clone().min().unwrap_or(0), columns.max().unwrap_or(0))
+        });
+        let width = (last - first + 1) as usize;
+        let mut row = vec![Data::Empty; width];
+        for (col, value) in cells {
+            if col < first || col > last {
+                return Err(InsightoraError::ParseError(format!(
+                    "Cell {} is outside the sheet's columns {} to {}",
+                    cell_name((index, col)),
+                    cell_name((index, first)).trim_end_matches(char::is_numeric),
+                    cell_name((index, last)).trim_end_matches(char::is_numeric)
+                )));
+            }
+            row[(col - first) as usize] = value;
+        }
+        if self.has_header && self.names.is_none() {
+            let mut seen = HashSet::new();
+            self.names = Some(row.iter().enumerate().map(|(i, cell)| column_name(Some(&cell_text(cell)), i, &mut seen)).collect());
+        } else {
+            if let Some(last_row) = self.last_row {
+                for _ in last_row + 1..index {
+                    self.rows.push(vec![Data::Empty; width]);
+                }
+            }
+            self.rows.push(row);
+        }
+        self.last_row = Some(index);
+        Ok(())
+    }
+
+    /// The next `chunk_size` rows as a DataFrame once that many are read;
+    /// with `finished`, the rest as a shorter last batch
+    fn next_batch(&mut self, finished: bool) -> Result<Option<DataFrame>, InsightoraError> {
+        if self.rows.is_empty() || (!finished && self.rows.len() < self.chunk_size) {
+            return Ok(None);
+        }
+        let rows: Vec<Vec<Data>> = self.rows.drain(..self.rows.len().min(self.chunk_size)).collect();
+        let width = rows[0].len();
+        let names = self.names.get_or_insert_with(|| {
+            let mut seen = HashSet::new();
+            (0..width).map(|i| column_name(None, i, &mut seen)).collect()
+        });
+        let kinds = self.kinds.get_or_insert_with(|| {
+            (0..width)
+                .map(|i| match column_kind(rows.iter().map(|row| &row[i])) {
+                    CellKind::Null => CellKind::Text,
+                    kind => kind,
+                })
+                .collect()
+        });
+        let mut columns = Vec::with_capacity(width);
+        for (i, (name, kind)) in names.iter().zip(kinds.iter()).enumerate() {
+            let cells = rows.iter().map(|row| &row[i]);
+            let found = column_kind(cells.clone());
+            if kind.widen(found) != *kind {
+                return Err(InsightoraError::ParseError(format!(
+                    "Column '{}' holds {} values in data rows {} to {}, but its first batch made it {}; \
+                     read the sheet with parse_excel to type it from every row",
+                    name,
+                    found.name(),
+                    self.rows_batched + 1,
+                    self.rows_batched + rows.len(),
+                    kind.name()
+                )));
+            }
+            columns.push(typed_series(name, cells, *kind)?);
+        }
+        self.rows_batched += rows.len();
+        Ok(Some(DataFrame::new(columns)?))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::Write;
+    use ::zip::write::FileOptions;
+
+    /// A minimal .xlsx with inline strings; style 1 is a date format
+    pub(crate) fn workbook(dir: &tempfile::TempDir, sheets: &[(&str, &str)]) -> String {
+        build_workbook(dir, sheets, &[], false)
+    }
+
+    /// `workbook` with the sheets named in `hidden` hidden
+    fn hidden_workbook(dir: &tempfile::TempDir, sheets: &[(&str, &str)], hidden: &[&str]) -> String {
+        build_workbook(dir, sheets, hidden, false)
+    }
+
+    /// Sheets are `<row>` elements, or a whole worksheet body when they start
+    /// with `<sheetData>`; `date1904` counts dates from 1904
+    fn build_workbook(dir: &tempfile::TempDir, sheets: &[(&str, &str)], hidden: &[&str], date1904: bool) -> String {
+        let path = dir.path().join("book.xlsx");
+        let mut writer = ::zip::ZipWriter::new(File::create(&path).unwrap());
+        let mut add = |name: &str, text: &str| {
+            writer.start_file(name, FileOptions::default()).unwrap();
+            writer.write_all(text.as_bytes()).unwrap();
+        };
+        let overrides: String = (1..=sheets.len())
+            .map(|i| format!(r#"<Override PartName="/xl/worksheets/sheet{}.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#, i))
+            .collect();
+        add("[Content_Types].xml", &format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/>{}</Types>"#,
+            overrides
+        ));
+        add("_rels/.rels", r#"<?xml version="1.0" encoding="UTF-8"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#);
+        let entries: String = sheets
+            .iter()
+            .enumerate()
+            .map(|(i, (name, _))| {
+                let state = if hidden.contains(name) { r#" state="hidden""# } else { "" };
+                format!(r#"<sheet name="{}" sheetId="{}"{} r:id="rId{}"/>"#, name, i + 1, state, i + 1)
+            })
+            .collect();
+        let properties = if date1904 { r#"<workbookPr date1904="1"/>"# } else { "" };
+        add("xl/workbook.xml", &format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">{}<sheets>{}</sheets></workbook>"#,
+            properties, entries
+        ));
+        let relationships: String = (1..=sheets.len())
+            .map(|i| format!(r#"<Relationship Id="rId{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet{}.xml"/>"#, i, i))
+            .collect();
+        add("xl/_rels/workbook.xml.rels", &format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">{}<Relationship Id="rIdStyles" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/></Relationships>"#,
+            relationships
+        ));
+        add("xl/styles.xml", r#"<?xml version="1.0" encoding="UTF-8"?><styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><cellXfs count="2"><xf numFmtId="0"/><xf numFmtId="14" applyNumberFormat="1"/></cellXfs></styleSheet>"#);
+        for (i, (_, rows)) in sheets.iter().enumerate() {
+            let body = if rows.starts_with("<sheetData>") { rows.to_string() } else { format!("<sheetData>{}</sheetData>", rows) };
+            add(&format!("xl/worksheets/sheet{}.xml", i + 1), &format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?><worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">{}</worksheet>"#,
+                body
+            ));
+        }
+        writer.finish().unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_parse_excel_infers_types_and_lists_sheets() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let text = |cell: &str, value: &str| format!(r#"<c r="{}" t="inlineStr"><is><t>{}</t></is></c>"#, cell, value);
+        let rows = [
+            format!("<row r=\"1\">{}{}{}{}{}</row>", text("A1", "id"), text("B1", "amount"), text("C1", "paid"), text("D1", "due"), text("E1", "id")),
+            format!("<row r=\"2\"><c r=\"A2\"><v>1</v></c><c r=\"B2\"><v>2.5</v></c><c r=\"C2\" t=\"b\"><v>1</v></c><c r=\"D2\" s=\"1\"><v>45352</v></c>{}</row>", text("E2", "x")),
+            "<row r=\"3\"><c r=\"A3\"><v>2</v></c><c r=\"B3\"><v>4</v></c><c r=\"C3\" t=\"b\"><v>0</v></c><c r=\"E3\"><v>7</v></c></row>".to_string(),
+        ];
+        let path = workbook(&dir, &[("Orders", &rows.concat()), ("Notes", &format!("<row r=\"2\">{}</row>", text("B2", "hello")))]);
+
+        let sheets = list_sheets(&path).unwrap();
+        assert_eq!(sheets, vec![
+            SheetInfo { name: "Orders".to_string(), rows: 3, columns: 5, hidden: false },
+            SheetInfo { name: "Notes".to_string(), rows: 1, columns: 1, hidden: false },
+        ]);
+
+        let df = parse_excel(&path, None, &ExcelOptions::default()).unwrap();
+        assert_eq!(df.get_column_names(), vec!["id", "amount", "paid", "due", "id_1"]);
+        assert_eq!(df.column("id").unwrap().dtype(), &DataType::Int64);
+        assert_eq!(df.column("amount").unwrap().f64().unwrap().get(1), Some(4.0));
+        assert_eq!(df.column("paid").unwrap().bool().unwrap().get(1), Some(false));
+        assert_eq!(df.column("due").unwrap().dtype(), &DataType::Date);
+        assert_eq!(df.column("due").unwrap().null_count(), 1);
+        assert_eq!(df.column("id_1").unwrap().str().unwrap().get(1), Some("7"));
+
+        let no_header = ExcelOptions { has_header: false, ..Default::default() };
+        let notes = parse_excel(&path, Some("Notes"), &no_header).unwrap();
+        assert_eq!(notes.get_column_names(), vec!["column_1"]);
+        assert_eq!(notes.column("column_1").unwrap().str().unwrap().get(0), Some("hello"));
+
+        let message = parse_excel(&path, Some("Summary"), &ExcelOptions::default()).unwrap_err().to_string();
+        assert!(message.contains("available sheets: Orders, Notes"), "{}", message);
+    }
+
+    #[test]
+    fn test_parse_all_reports_failures_and_skips_hidden() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let header = r#"<row r="1"><c r="A1" t="inlineStr"><is><t>amount</t></is></c></row>"#;
+        let path = hidden_workbook(&dir, &[
+            ("January", &format!(r#"{}<row r="2"><c r="A2"><v>10</v></c></row>"#, header)),
+            ("Broken", &format!(r#"{}<row r="2"><c r="A2" t="n"><v>ten</v></c></row>"#, header)),
+            ("Lookup", &format!(r#"{}<row r="2"><c r="A2"><v>1.5</v></c></row>"#, header)),
+        ], &["Lookup"]);
+
+        let mut parser = ExcelParser::open(&path).unwrap();
+        let results = parser.parse_all(&ExcelOptions::default(), true);
+        assert_eq!(results.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), vec!["January", "Broken", "Lookup"]);
+        assert_eq!(results[0].1.as_ref().unwrap().column("amount").unwrap().i64().unwrap().get(0), Some(10));
+        assert!(matches!(results[1].1, Err(InsightoraError::ParseError(_))));
+        assert_eq!(results[2].1.as_ref().unwrap().column("amount").unwrap().f64().unwrap().get(0), Some(1.5));
+
+        let visible = parser.parse_all(&ExcelOptions::default(), false);
+        assert_eq!(visible.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), vec!["January", "Broken"]);
+    }
+
+    #[test]
+    fn test_excel_dates_formulas_and_merged_cells() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let text = |cell: &str, value: &str| format!(r#"<c r="{}" t="inlineStr"><is><t>{}</t></is></c>"#, cell, value);
+        let sheet = format!(
+            "<sheetData><row r=\"1\">{}{}{}{}</row><row r=\"2\"><c r=\"A2\" s=\"1\"><v>0</v></c><c r=\"B2\"><v>10</v></c><c r=\"C2\"><f>B2*2</f><v>20</v></c>{}</row><row r=\"3\"><c r=\"A3\" s=\"1\"><v>366</v></c><c r=\"B3\"><v>5</v></c><c r=\"C3\"><f>B3*2</f></c></row></sheetData><mergeCells count=\"1\"><mergeCell ref=\"D2:D3\"/></mergeCells>",
+            text("A1", "opened"), text("B1", "amount"), text("C1", "total"), text("D1", "region"), text("D2", "North"),
+        );
+        let path = build_workbook(&dir, &[("Ledger", &sheet)], &[], true);
+        let days = |y, m, d| (NaiveDate::from_ymd_opt(y, m, d).unwrap() - NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()).num_days() as i32;
+
+        let df = parse_excel(&path, None, &ExcelOptions::default()).unwrap();
+        let opened = df.column("opened").unwrap().date().unwrap();
+        assert_eq!((opened.get(0), opened.get(1)), (Some(days(1904, 1, 1)), Some(days(1905, 1, 1))));
+        let total = df.column("total").unwrap().i64().unwrap();
+        assert_eq!((total.get(0), total.get(1)), (Some(20), None));
+        let region = df.column("region").unwrap().str().unwrap();
+        assert_eq!((region.get(0), region.get(1)), (Some("North"), None));
+
+        let options = ExcelOptions { formulas: FormulaCells::Formula, merged_cells: MergedCells::Repeat, ..Default::default() };
+        let df = parse_excel(&path, None, &options).unwrap();
+        let total = df.column("total").unwrap().str().unwrap();
+        assert_eq!((total.get(0), total.get(1)), (Some("=B2*2"), Some("=B3*2")));
+        assert_eq!(df.column("amount").unwrap().i64().unwrap().get(1), Some(5));
+        let region = df.column("region").unwrap().str().unwrap();
+        assert_eq!((region.get(0), region.get(1)), (Some("North"), Some("North")));
+
+        assert!(matches!(MergedCells::from_name("first"), Err(InsightoraError::ValidationError(_))));
+        assert_eq!(FormulaCells::from_name("Formula").unwrap(), FormulaCells::Formula);
+    }
+
+    #[test]
+    fn test_cell_range_and_header_row_select_the_table() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let text = |cell: &str, value: &str| format!(r#"<c r="{}" t="inlineStr"><is><t>{}</t></is></c>"#, cell, value);
+        let rows = [
+            format!(r#"<row r="1">{}</row>"#, text("A1", "Quarterly report")),
+            format!(r#"<row r="4">{}{}{}{}</row>"#, text("B4", "item"), text("C4", "qty"), text("D4", "price"), text("F4", "notes")),
+            format!(r#"<row r="5">{}<c r="C5"><v>3</v></c><c r="D5"><v>2.5</v></c>{}</row>"#, text("B5", "pen"), text("F5", "late")),
+            format!(r#"<row r="6">{}<c r="C6"><v>1</v></c><c r="D6"><v>9</v></c></row>"#, text("B6", "ink")),
+        ];
+        let path = workbook(&dir, &[("Report", &rows.concat())]);
+        let parse = |options: ExcelOptions| parse_excel(&path, None, &options);
+
+        let ranged = parse(ExcelOptions { cell_range: Some(CellRange::parse("D100:B4").unwrap()), ..Default::default() }).unwrap();
+        assert_eq!(ranged.get_column_names(), vec!["item", "qty", "price"]);
+        assert_eq!(ranged.column("qty").unwrap().i64().unwrap().get(1), Some(1));
+
+        let headed = parse(ExcelOptions { skip_rows: 1, header_row: Some(2), ..Default::default() }).unwrap();
+        assert_eq!(headed.get_column_names(), vec!["item", "qty", "price", "column_4", "notes"]);
+        assert_eq!(headed.height(), 2);
+
+        let message = parse(ExcelOptions { header_row: Some(10), ..Default::default() }).unwrap_err().to_string();
+        assert!(message.contains("header_row 10 selects row 11") && message.contains("used range A1:F6"), "{}", message);
+        let message = parse(ExcelOptions { cell_range: Some(CellRange::parse("H1:J5").unwrap()), ..Default::default() })
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("Cell range H1:J5 is outside the used range A1:F6"), "{}", message);
+        assert!(matches!(CellRange::parse("B5"), Err(InsightoraError::ValidationError(_))));
+    }
+
+    #[test]
+    fn test_streaming_reader_batches_rows_and_fixes_types() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let text = |cell: &str, value: &str| format!(r#"<c r="{}" t="inlineStr"><is><t>{}</t></is></c>"#, cell, value);
+        let row = |index: usize, id: &str| format!(r#"<row r="{0}"><c r="A{0}"><v>{1}</v></c>{2}</row>"#, index, id, text(&format!("B{}", index), "x"));
+        let rows = format!("<row r=\"1\">{}{}</row>{}{}{}{}", text("A1", "id"), text("B1", "name"), row(2, "1"), row(3, "2"), row(5, "4"), row(6, "5"));
+        let mixed = format!("<row r=\"1\">{}{}</row>{}{}<row r=\"4\">{}</row>", text("A1", "id"), text("B1", "name"), row(2, "1"), row(3, "2"), text("A4", "n/a"));
+        let path = workbook(&dir, &[("Ledger", &rows), ("Mixed", &mixed)]);
+
+        let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&reports);
+        let parser = StreamingExcelParser::with_config(StreamingExcelConfig { chunk_size: 2, ..Default::default() })
+            .with_progress_callback(Arc::new(move |read, total| recorded.lock().unwrap().push((read, total))));
+        let mut heights = Vec::new();
+        parser.parse_batches(&path, |batch| {
+            heights.push(batch.height());
+            Ok(())
+        }).unwrap();
+        assert_eq!(heights, vec![2, 2, 1]);
+        assert_eq!(reports.lock().unwrap().last(), Some(&(5, 5)));
+
+        let streamed = parser.parse_streaming(&path).unwrap();
+        let whole = parse_excel(&path, None, &ExcelOptions::default()).unwrap();
+        assert!(streamed.equals_missing(&whole), "{} != {}", streamed, whole);
+        assert_eq!(streamed.column("id").unwrap().i64().unwrap().get(2), None);
+
+        let mixed = StreamingExcelParser::with_config(StreamingExcelConfig { sheet: Some("Mixed".to_string()), chunk_size: 2, ..Default::default() });
+        let message = mixed.parse_streaming(&path).unwrap_err().to_string();
+        assert!(message.contains("Column 'id' holds text values in data rows 3 to 3"), "{}", message);
+        assert!(!parser.should_use_streaming(&path).unwrap());
+    }
+}
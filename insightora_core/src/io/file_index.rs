@@ -0,0 +1,468 @@
+// Block min/max index files
+// Sidecar statistics per byte range of a CSV file, so filtered reads skip blocks the filter rules out
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+use once_cell::sync::Lazy;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::config::get_current_config;
+use crate::error::InsightoraError;
+use crate::dataframe::expressions::{CompareOp, Node, Predicate};
+use crate::utils::sandbox::PathPolicy;
+
+/// Appended to the CSV file name to name its index
+pub const INDEX_SUFFIX: &str = ".idx.json";
+
+/// Bytes per indexed block unless another size is given
+pub const DEFAULT_BLOCK_BYTES: usize = 4 << 20;
+
+/// Format version written to new indexes; others are ignored as stale
+const INDEX_VERSION: u32 = 1;
+
+/// Bytes hashed at each end of the file
+const HASHED_BYTES: u64 = 64 << 10;
+
+/// Path of the sidecar index for a CSV file
+pub fn index_path(csv_path: &Path) -> PathBuf {
+    let mut name = csv_path.as_os_str().to_owned();
+    name.push(INDEX_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Where the index of a CSV file is stored, or None when the path policy
+/// doesn't allow its sidecar and the index is kept in memory instead
+pub fn stored_index_path(csv_path: &Path) -> Result<Option<PathBuf>, InsightoraError> {
+    sidecar_path(csv_path, &get_current_config().path_policy)
+}
+
+fn sidecar_path(csv_path: &Path, policy: &PathPolicy) -> Result<Option<PathBuf>, InsightoraError> {
+    match policy.check(&index_path(csv_path)) {
+        Ok(path) => Ok(Some(path)),
+        Err(InsightoraError::PermissionDenied(_)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Indexes of CSV files whose sidecar the path policy doesn't allow
+static MEMORY_INDEXES: Lazy<Mutex<HashMap<PathBuf, FileIndex>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn memory_indexes() -> Result<std::sync::MutexGuard<'static, HashMap<PathBuf, FileIndex>>, InsightoraError> {
+    MEMORY_INDEXES
+        .lock()
+        .map_err(|e| InsightoraError::ConfigError(format!("Failed to acquire file index cache lock: {}", e)))
+}
+
+/// A minimum or maximum recorded for a block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BoundValue {
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl BoundValue {
+    /// Order of `self` and `other`, or None when they aren't comparable
+    fn compare(&self, other: &BoundValue) -> Option<std::cmp::Ordering> {
+        match (self, other) {
+            (BoundValue::Int(a), BoundValue::Int(b)) => Some(a.cmp(b)),
+            (BoundValue::Text(a), BoundValue::Text(b)) => Some(a.cmp(b)),
+            (BoundValue::Int(a), BoundValue::Float(b)) => (*a as f64).partial_cmp(b),
+            (BoundValue::Float(a), BoundValue::Int(b)) => a.partial_cmp(&(*b as f64)),
+            (BoundValue::Float(a), BoundValue::Float(b)) => a.partial_cmp(b),
+            _ => None,
+        }
+    }
+
+    /// A literal of a filter expression
+    fn from_node(node: &Node) -> Option<Self> {
+        match node {
+            Node::Int(value) => Some(BoundValue::Int(*value)),
+            Node::Float(value) => Some(BoundValue::Float(*value)),
+            Node::Text(value) => Some(BoundValue::Text(value.clone())),
+            Node::Negate(inner) => match inner.as_ref() {
+                Node::Int(value) => value.checked_neg().map(BoundValue::Int),
+                Node::Float(value) => Some(BoundValue::Float(-value)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Statistics of one indexed column in one block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnStats {
+    /// None when the block has no non-null values, or the column's type
+    /// has no recorded bounds
+    pub min: Option<BoundValue>,
+    pub max: Option<BoundValue>,
+    pub null_count: usize,
+}
+
+impl ColumnStats {
+    /// Bounds and nulls of a parsed column; integers, floats and text have bounds
+    pub fn of(series: &Series) -> Result<Self, InsightoraError> {
+        let (min, max) = match series.dtype() {
+            dtype if dtype.is_integer() => {
+                let values = series.cast(&DataType::Int64)?;
+                let values = values.i64()?;
+                (values.min().map(BoundValue::Int), values.max().map(BoundValue::Int))
+            }
+            dtype if dtype.is_float() => {
+                let values = series.cast(&DataType::Float64)?;
+                let values = values.f64()?;
+                // NaN has no place in an ordering (nor in JSON)
+                let finite = |value: Option<f64>| value.filter(|v| v.is_finite()).map(BoundValue::Float);
+                (finite(values.min()), finite(values.max()))
+            }
+            DataType::String => {
+                let values = series.str()?;
+                let bound = |pick: fn(&str, &str) -> bool| {
+                    values
+                        .into_iter()
+                        .flatten()
+                        .reduce(|best, v| if pick(v, best) { v } else { best })
+                        .map(|v| BoundValue::Text(v.to_string()))
+                };
+                (bound(|v, best| v < best), bound(|v, best| v > best))
+            }
+            _ => (None, None),
+        };
+        Ok(Self { min, max, null_count: series.null_count() })
+    }
+}
+
+/// One record-aligned byte range of the file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexBlock {
+    pub offset: u64,
+    pub len: u64,
+    pub rows: usize,
+    /// One entry per indexed column, in `FileIndex::columns` order
+    pub stats: Vec<ColumnStats>,
+}
+
+/// Size, modification time and a content hash of the indexed file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    pub size: u64,
+    pub modified_secs: u64,
+    pub modified_nanos: u32,
+    /// FNV-1a of the first and last 64 KiB
+    pub hash: u64,
+}
+
+impl FileStamp {
+    pub fn of(path: &Path) -> Result<Self, InsightoraError> {
+        let metadata = std::fs::metadata(path)?;
+        let modified = metadata.modified()?.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let size = metadata.len();
+        let mut file = File::open(path)?;
+        let mut bytes = Vec::new();
+        (&mut file).take(HASHED_BYTES).read_to_end(&mut bytes)?;
+        if size > HASHED_BYTES {
+            file.seek(SeekFrom::Start(size.saturating_sub(HASHED_BYTES).max(HASHED_BYTES)))?;
+            file.take(HASHED_BYTES).read_to_end(&mut bytes)?;
+        }
+        Ok(Self {
+            size,
+            modified_secs: modified.as_secs(),
+            modified_nanos: modified.subsec_nanos(),
+            hash: fnv1a(&bytes),
+        })
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// Min/max statistics per block of a CSV file, stored beside it
+///
+/// Built by `ParallelCsvParser::build_index` and used by
+/// `ParallelCsvParser::parse_filtered` while the file is unchanged and is
+/// read with the same header and delimiter settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileIndex {
+    pub version: u32,
+    pub stamp: FileStamp,
+    pub has_header: bool,
+    pub delimiter: u8,
+    pub quote_char: u8,
+    /// Byte offset of the first data row
+    pub data_offset: u64,
+    /// Indexed columns, by header name
+    pub columns: Vec<String>,
+    pub blocks: Vec<IndexBlock>,
+}
+
+impl FileIndex {
+    pub fn new(stamp: FileStamp, has_header: bool, delimiter: u8, quote_char: u8, columns: Vec<String>) -> Self {
+        Self {
+            version: INDEX_VERSION,
+            stamp,
+            has_header,
+            delimiter,
+            quote_char,
+            data_offset: 0,
+            columns,
+            blocks: Vec::new(),
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.blocks.iter().map(|block| block.rows).sum()
+    }
+
+    /// Write the index beside `csv_path`
+    ///
+    /// When the path policy doesn't allow the sidecar, nothing is written
+    /// and the index is cached in memory for this process instead.
+    ///
+    /// # Returns
+    /// * `Result<Option<PathBuf>>` - The sidecar written, or None when cached in memory
+    pub fn save(&self, csv_path: &Path) -> Result<Option<PathBuf>, InsightoraError> {
+        self.save_under(csv_path, &get_current_config().path_policy)
+    }
+
+    fn save_under(&self, csv_path: &Path, policy: &PathPolicy) -> Result<Option<PathBuf>, InsightoraError> {
+        let Some(path) = sidecar_path(csv_path, policy)? else {
+            memory_indexes()?.insert(csv_path.to_path_buf(), self.clone());
+            return Ok(None);
+        };
+        std::fs::write(&path, serde_json::to_vec(self).map_err(std::io::Error::from)?)?;
+        memory_indexes()?.remove(csv_path);
+        Ok(Some(path))
+    }
+
+    /// The index of `csv_path`, if there is one that still describes it
+    ///
+    /// Read from the sidecar, or from memory when the path policy doesn't
+    /// allow the sidecar (see `save`).
+    ///
+    /// # Returns
+    /// * `Result<Result<FileIndex, String>>` - The index, or why none can be
+    ///   used (missing, unreadable, built for other settings or a changed file)
+    pub fn load(csv_path: &Path, has_header: bool, delimiter: u8, quote_char: u8) -> Result<Result<Self, String>, InsightoraError> {
+        Self::load_under(csv_path, has_header, delimiter, quote_char, &get_current_config().path_policy)
+    }
+
+    fn load_under(
+        csv_path: &Path,
+        has_header: bool,
+        delimiter: u8,
+        quote_char: u8,
+        policy: &PathPolicy,
+    ) -> Result<Result<Self, String>, InsightoraError> {
+        let index = match sidecar_path(csv_path, policy)? {
+            Some(path) => {
+                if !path.exists() {
+                    return Ok(Err("no index file".to_string()));
+                }
+                match serde_json::from_slice(&std::fs::read(&path)?) {
+                    Ok(index) => index,
+                    Err(e) => return Ok(Err(format!("index file is unreadable: {}", e))),
+                }
+            }
+            None => match memory_indexes()?.get(csv_path) {
+                Some(index) => index.clone(),
+                None => return Ok(Err("no index file".to_string())),
+            },
+        };
+        Self::check_current(index, csv_path, has_header, delimiter, quote_char)
+    }
+
+    /// The index if it was built for these settings and the file is unchanged
+    fn check_current(
+        index: FileIndex,
+        csv_path: &Path,
+        has_header: bool,
+        delimiter: u8,
+        quote_char: u8,
+    ) -> Result<Result<Self, String>, InsightoraError> {
+        if index.version != INDEX_VERSION {
+            return Ok(Err(format!("index format {} is not supported", index.version)));
+        }
+        if (index.has_header, index.delimiter, index.quote_char) != (has_header, delimiter, quote_char) {
+            return Ok(Err("index was built with other header, delimiter or quote settings".to_string()));
+        }
+        let stamp = FileStamp::of(csv_path)?;
+        let changed = [
+            (index.stamp.size != stamp.size, "size"),
+            (
+                (index.stamp.modified_secs, index.stamp.modified_nanos) != (stamp.modified_secs, stamp.modified_nanos),
+                "modification time",
+            ),
+            (index.stamp.hash != stamp.hash, "content hash"),
+        ];
+        let changed: Vec<&str> = changed.iter().filter(|(differs, _)| *differs).map(|(_, what)| *what).collect();
+        if !changed.is_empty() {
+            return Ok(Err(format!("file changed since the index was built ({})", changed.join(", "))));
+        }
+        Ok(Ok(index))
+    }
+
+    /// Whether rows of `block` may satisfy `predicate`
+    ///
+    /// `column` maps a name used by the predicate to its position in
+    /// `columns`, or None when it isn't indexed. Only comparisons of an
+    /// indexed column with a literal, null tests, `and` and `or` rule
+    /// blocks out; anything else may match.
+    pub fn may_match(&self, block: &IndexBlock, predicate: &Predicate, column: &dyn Fn(&str) -> Option<usize>) -> bool {
+        may_match(predicate.root(), block, column)
+    }
+}
+
+/// Statistics of an indexed column operand
+fn column_stats<'a>(node: &Node, block: &'a IndexBlock, column: &dyn Fn(&str) -> Option<usize>) -> Option<&'a ColumnStats> {
+    match node {
+        Node::Column(name) => column(name).and_then(|i| block.stats.get(i)),
+        _ => None,
+    }
+}
+
+fn may_match(node: &Node, block: &IndexBlock, column: &dyn Fn(&str) -> Option<usize>) -> bool {
+    let stats = |node: &Node| column_stats(node, block, column);
+    match node {
+        Node::Bool(false) | Node::Null => false,
+        Node::And(left, right) => may_match(left, block, column) && may_match(right, block, column),
+        Node::Or(left, right) => may_match(left, block, column) || may_match(right, block, column),
+        Node::IsNull(inner) => stats(inner).is_none_or(|s| s.null_count > 0),
+        Node::Not(inner) => match inner.as_ref() {
+            Node::IsNull(inner) => stats(inner).is_none_or(|s| s.null_count < block.rows),
+            _ => true,
+        },
+        Node::Compare(op, left, right) => {
+            let (op, stats, value) = match (stats(left), BoundValue::from_node(right), stats(right), BoundValue::from_node(left)) {
+                (Some(stats), Some(value), _, _) => (*op, stats, value),
+                (_, _, Some(stats), Some(value)) => (flip(*op), stats, value),
+                _ => return true,
+            };
+            // A null comparison doesn't select the row
+            if stats.null_count == block.rows {
+                return false;
+            }
+            let (Some(min), Some(max)) = (&stats.min, &stats.max) else {
+                return true;
+            };
+            let (Some(low), Some(high)) = (min.compare(&value), max.compare(&value)) else {
+                return true;
+            };
+            use std::cmp::Ordering::*;
+            match op {
+                CompareOp::Eq => low != Greater && high != Less,
+                CompareOp::NotEq => !(low == Equal && high == Equal),
+                CompareOp::Lt => low == Less,
+                CompareOp::LtEq => low != Greater,
+                CompareOp::Gt => high == Greater,
+                CompareOp::GtEq => high != Less,
+            }
+        }
+        _ => true,
+    }
+}
+
+/// The operator with its operands swapped (`5 < x` is `x > 5`)
+fn flip(op: CompareOp) -> CompareOp {
+    match op {
+        CompareOp::Lt => CompareOp::Gt,
+        CompareOp::LtEq => CompareOp::GtEq,
+        CompareOp::Gt => CompareOp::Lt,
+        CompareOp::GtEq => CompareOp::LtEq,
+        other => other,
+    }
+}
+
+/// Blocks read and skipped by one filtered parse
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexScanReport {
+    pub index_used: bool,
+    /// Why the index wasn't used
+    pub reason: Option<String>,
+    pub blocks_total: usize,
+    pub blocks_read: usize,
+    pub blocks_skipped: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use crate::io::csv_parser::{CsvParserConfig, ParallelCsvParser};
+
+    #[test]
+    fn test_narrow_date_filter_reads_few_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.csv");
+        let mut file = File::create(&path).unwrap();
+        writeln!(file, "date,region,amount").unwrap();
+        for month in 1..=12 {
+            for day in 1..=28 {
+                for i in 0..6 {
+                    writeln!(file, "2024-{:02}-{:02},r{},{}", month, day, i, month * 100 + day).unwrap();
+                }
+            }
+        }
+        drop(file);
+        let csv = path.to_str().unwrap();
+
+        let parser = ParallelCsvParser::with_config(CsvParserConfig::default());
+        let predicate = Predicate::parse("date >= '2024-06-10' and date < '2024-06-13'").unwrap();
+        let (unindexed, report) = parser.parse_filtered(csv, &predicate).unwrap();
+        assert_eq!(unindexed.height(), 18);
+        assert_eq!(report.reason.as_deref(), Some("no index file"));
+
+        let index = parser.build_index(csv, &["date".to_string(), "amount".to_string()], 1024).unwrap();
+        assert_eq!(index.rows(), 12 * 28 * 6);
+        assert!(index.blocks.len() > 30, "{} blocks", index.blocks.len());
+        let (filtered, report) = parser.parse_filtered(csv, &predicate).unwrap();
+        assert!(report.index_used, "{:?}", report.reason);
+        assert!(report.blocks_read * 10 < report.blocks_total, "{:?}", report);
+        assert_eq!(report.blocks_read + report.blocks_skipped, report.blocks_total);
+        assert!(filtered.equals_missing(&unindexed));
+
+        // Literal on the left, numeric column, and nothing to match
+        let (_, report) = parser.parse_filtered(csv, &Predicate::parse("1300 < amount").unwrap()).unwrap();
+        assert_eq!(report.blocks_read, 0);
+
+        // Appending a row invalidates the index
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "2024-06-11,r9,0").unwrap();
+        drop(file);
+        let (filtered, report) = parser.parse_filtered(csv, &predicate).unwrap();
+        assert!(!report.index_used);
+        assert!(report.reason.unwrap().contains("size"));
+        assert_eq!(filtered.height(), 19);
+    }
+
+    #[test]
+    fn test_sidecar_outside_policy_is_kept_in_memory() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("amounts.csv");
+        std::fs::write(&path, "amount\n1\n2\n3\n").unwrap();
+        let path = path.canonicalize().unwrap();
+        // Only the CSV itself is allowed, not its sidecar
+        let policy = PathPolicy::new(std::slice::from_ref(&path), &[]).unwrap();
+
+        let mut index = FileIndex::new(FileStamp::of(&path).unwrap(), true, b',', b'"', vec!["amount".to_string()]);
+        index.data_offset = 7;
+        assert_eq!(index.save_under(&path, &policy).unwrap(), None);
+        assert!(!index_path(&path).exists());
+
+        let loaded = FileIndex::load_under(&path, true, b',', b'"', &policy).unwrap();
+        assert_eq!(loaded, Ok(index.clone()));
+        // Without the policy the sidecar is used, and there is none yet
+        let unrestricted = PathPolicy::default();
+        assert_eq!(
+            FileIndex::load_under(&path, true, b',', b'"', &unrestricted).unwrap(),
+            Err("no index file".to_string())
+        );
+        assert_eq!(index.save_under(&path, &unrestricted).unwrap(), Some(index_path(&path)));
+        assert!(index_path(&path).exists());
+    }
+}
@@ -0,0 +1,246 @@
+// NDJSON parsing
+// Reads newline-delimited JSON (plain, gzip or zstd) into a table, null-filling missing keys and flattening or stringifying nested values
+
+use std::fs::File;
+use std::io::Cursor;
+use polars::io::mmap::MmapBytesReader;
+use polars::prelude::*;
+use crate::config::check_memory_limit;
+use crate::error::InsightoraError;
+use crate::io::compression::{read_decompressed, read_decompressed_lines, Compression};
+use crate::io::csv_parser::parse_memory_mb;
+use crate::utils::sandbox::check_path_allowed;
+
+/// Options for reading an NDJSON file
+#[derive(Debug, Clone)]
+pub struct NdjsonOptions {
+    /// Read at most this many records
+    pub n_rows: Option<usize>,
+    /// Column types to cast to after reading, by (possibly dotted) column name
+    pub schema_overrides: Vec<(String, DataType)>,
+    /// Expand one level of nested objects into `parent.child` columns
+    pub flatten: bool,
+    /// Records scanned for keys and types; None scans them all
+    pub infer_schema_length: Option<usize>,
+}
+
+impl Default for NdjsonOptions {
+    fn default() -> Self {
+        Self {
+            n_rows: None,
+            schema_overrides: Vec::new(),
+            flatten: false,
+            infer_schema_length: Some(1000),
+        }
+    }
+}
+
+/// Parse a newline-delimited JSON file into a DataFrame
+///
+/// Every key seen in the first `infer_schema_length` records becomes a
+/// column, and records without a key are null in it. Nested objects and
+/// arrays are kept as their JSON text in a string column; with `flatten`
+/// the fields of a nested object become `parent.child` columns instead,
+/// with anything nested below them kept as JSON text. gzip and zstd files
+/// are decompressed as with CSV input, and the global memory limit is
+/// checked against the decompressed size before anything is parsed.
+///
+/// # Arguments
+/// * `file_path` - Path to the `.ndjson` / `.jsonl` file, optionally `.gz` or `.zst`
+/// * `options` - Row limit, type overrides and flattening
+///
+/// # Returns
+/// * `Result<DataFrame>` - One row per record; an override naming a missing
+///   column is a ValidationError and values it can't convert a ParseError
+///
+/// # Example
+/// ```no_run
+/// use insightora_core::api::{parse_ndjson, NdjsonOptions};
+///
+/// let options = NdjsonOptions { flatten: true, ..Default::default() };
+/// let events = parse_ndjson("logs/events.ndjson.gz", &options).unwrap();
+/// println!("{:?}", events.get_column_names());
+/// ```
+pub fn parse_ndjson(file_path: &str, options: &NdjsonOptions) -> Result<DataFrame, InsightoraError> {
+    let path = check_path_allowed(file_path)?;
+    if !path.exists() {
+        return Err(InsightoraError::IoError(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("File not found: {}", file_path),
+        )));
+    }
+    // JSON text is at least as large as the values parsed from it
+    check_memory_limit(parse_memory_mb(&path, options.n_rows, 2)?)?;
+
+    // With a row limit only those lines are read, so keys first seen after
+    // them don't become columns whatever the compression
+    let compression = Compression::detect(&path)?;
+    let df = match (compression, options.n_rows) {
+        (_, Some(rows)) => read_records(Cursor::new(read_decompressed_lines(&path, compression, rows)?), options)?,
+        (Compression::None, None) => read_records(File::open(&path)?, options)?,
+        (_, None) => read_records(Cursor::new(read_decompressed(&path, compression)?), options)?,
+    };
+
+    let df = flattened(df, options.flatten)?;
+    apply_overrides(df, &options.schema_overrides)
+}
+
+/// Read records with Polars' JSON-lines reader
+fn read_records<R: MmapBytesReader + 'static>(input: R, options: &NdjsonOptions) -> PolarsResult<DataFrame> {
+    JsonLineReader::new(input)
+        .with_n_rows(options.n_rows)
+        .infer_schema_len(options.infer_schema_length)
+        .finish()
+}
+
+/// Replace nested columns with their JSON text, or with their fields when flattening
+fn flattened(df: DataFrame, flatten: bool) -> Result<DataFrame, InsightoraError> {
+    let mut columns: Vec<Series> = Vec::with_capacity(df.width());
+    for series in df.get_columns() {
+        match series.dtype() {
+            DataType::Struct(_) if flatten => {
+                for field in series.struct_()?.fields() {
+                    let name = format!("{}.{}", series.name(), field.name());
+                    let child = if matches!(field.dtype(), DataType::List(_) | DataType::Struct(_)) { json_text(field)? } else { field.clone() };
+                    columns.push(child.with_name(&name));
+                }
+            }
+            DataType::List(_) | DataType::Struct(_) => columns.push(json_text(series)?),
+            _ => columns.push(series.clone()),
+        }
+    }
+    for (index, series) in columns.iter().enumerate() {
+        if columns[..index].iter().any(|other| other.name() == series.name()) {
+            return Err(InsightoraError::ValidationError(format!(
+                "Flattening gives two columns named '{}'; rename the key holding the dot or read without flatten",
+                series.name()
+            )));
+        }
+    }
+    Ok(DataFrame::new(columns)?)
+}
+
+/// Cast the overridden columns, failing on values that don't convert
+fn apply_overrides(mut df: DataFrame, overrides: &[(String, DataType)]) -> Result<DataFrame, InsightoraError> {
+    for (name, dtype) in overrides {
+        let series = df.column(name).map_err(|_| {
+            InsightoraError::ValidationError(format!("schema_overrides names column '{}', which isn't in the file", name))
+        })?;
+        let cast = series.strict_cast(dtype).map_err(|_| {
+            InsightoraError::ParseError(format!("Column '{}' has {} values that don't convert to {}", name, series.dtype(), dtype))
+        })?;
+        df.replace(name, cast)?;
+    }
+    Ok(df)
+}
+
+/// A nested column as a string column of JSON text, nulls kept
+fn json_text(series: &Series) -> Result<Series, InsightoraError> {
+    let values: Vec<Option<String>> = json_fragments(series)?
+        .into_iter()
+        .map(|text| (text != "null").then_some(text))
+        .collect();
+    Ok(Series::new(series.name(), values))
+}
+
+/// JSON text of every value in a column, in row order
+///
+/// Struct columns don't record nulls of their own, so a row whose fields
+/// are all null is written as `null`.
+fn json_fragments(series: &Series) -> Result<Vec<String>, InsightoraError> {
+    match series.dtype() {
+        DataType::Struct(_) => {
+            let fields = series.struct_()?.fields();
+            let children = fields.iter().map(json_fragments).collect::<Result<Vec<_>, _>>()?;
+            Ok((0..series.len())
+                .map(|row| {
+                    if children.iter().all(|child| child[row] == "null") {
+                        return "null".to_string();
+                    }
+                    let members: Vec<String> = fields
+                        .iter()
+                        .zip(&children)
+                        .map(|(field, child)| format!("{}:{}", serde_json::Value::from(field.name()), child[row]))
+                        .collect();
+                    format!("{{{}}}", members.join(","))
+                })
+                .collect())
+        }
+        DataType::List(_) => series
+            .list()?
+            .into_iter()
+            .map(|item| match item {
+                Some(inner) => Ok(format!("[{}]", json_fragments(&inner)?.join(","))),
+                None => Ok("null".to_string()),
+            })
+            .collect(),
+        // Series::iter walks a single chunk
+        _ => Ok(series.rechunk().iter().map(|value| scalar_json(&value)).collect()),
+    }
+}
+
+/// JSON text of a single value; non-finite floats become null as JSON has no literal for them
+fn scalar_json(value: &AnyValue) -> String {
+    match value {
+        AnyValue::Null => "null".to_string(),
+        AnyValue::Boolean(flag) => flag.to_string(),
+        AnyValue::String(text) => serde_json::Value::from(*text).to_string(),
+        AnyValue::Float32(number) => serde_json::Value::from(*number as f64).to_string(),
+        AnyValue::Float64(number) => serde_json::Value::from(*number).to_string(),
+        AnyValue::Int8(_)
+        | AnyValue::Int16(_)
+        | AnyValue::Int32(_)
+        | AnyValue::Int64(_)
+        | AnyValue::UInt8(_)
+        | AnyValue::UInt16(_)
+        | AnyValue::UInt32(_)
+        | AnyValue::UInt64(_) => value.to_string(),
+        other => serde_json::Value::from(other.to_string()).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_parse_ndjson_fills_missing_keys_and_handles_nesting() {
+        let dir = tempfile::tempdir().unwrap();
+        let text = concat!(
+            r#"{"id": 1, "user": {"name": "ana", "tags": ["a", "b"]}, "amount": 2.5}"#, "\n",
+            r#"{"id": 2, "amount": 4}"#, "\n",
+            r#"{"id": 3, "user": {"name": "bo", "tags": []}, "note": "late"}"#, "\n",
+        );
+        let plain = dir.path().join("events.ndjson");
+        std::fs::write(&plain, text).unwrap();
+        let gzip = dir.path().join("events.ndjson.gz");
+        let mut encoder = flate2::write::GzEncoder::new(File::create(&gzip).unwrap(), flate2::Compression::default());
+        encoder.write_all(text.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        for path in [&plain, &gzip] {
+            let df = parse_ndjson(path.to_str().unwrap(), &NdjsonOptions::default()).unwrap();
+            assert_eq!(df.get_column_names(), vec!["id", "user", "amount", "note"]);
+            assert_eq!(df.column("amount").unwrap().dtype(), &DataType::Float64);
+            let user: Vec<Option<&str>> = df.column("user").unwrap().str().unwrap().into_iter().collect();
+            assert_eq!(user, vec![Some(r#"{"name":"ana","tags":["a","b"]}"#), None, Some(r#"{"name":"bo","tags":[]}"#)]);
+            assert_eq!(df.column("note").unwrap().null_count(), 2);
+        }
+
+        let options = NdjsonOptions {
+            n_rows: Some(2),
+            flatten: true,
+            schema_overrides: vec![("id".to_string(), DataType::String)],
+            ..Default::default()
+        };
+        let df = parse_ndjson(gzip.to_str().unwrap(), &options).unwrap();
+        assert_eq!(df.height(), 2);
+        assert_eq!(df.get_column_names(), vec!["id", "user.name", "user.tags", "amount"]);
+        assert_eq!(df.column("id").unwrap().str().unwrap().get(1), Some("2"));
+        assert_eq!(df.column("user.tags").unwrap().str().unwrap().get(0), Some(r#"["a","b"]"#));
+
+        let missing = NdjsonOptions { schema_overrides: vec![("total".to_string(), DataType::Int64)], ..Default::default() };
+        assert!(matches!(parse_ndjson(plain.to_str().unwrap(), &missing), Err(InsightoraError::ValidationError(_))));
+    }
+}
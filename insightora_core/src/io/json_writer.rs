@@ -0,0 +1,508 @@
+// JSON export
+// Serializes DataFrames and chunked datasets as JSON (records, columns or split) or JSON Lines
+
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use polars::prelude::*;
+use rayon::prelude::*;
+use crate::error::InsightoraError;
+use crate::io::csv_parser::{format_date, format_datetime};
+use crate::io::dataset::Dataset;
+use crate::utils::sandbox::check_path_allowed;
+
+/// Layout of the JSON document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JsonOrient {
+    /// `[{"a": 1, "b": "x"}, ...]`
+    #[default]
+    Records,
+    /// `{"a": [1, ...], "b": ["x", ...]}`
+    Columns,
+    /// `{"columns": ["a", "b"], "data": [[1, "x"], ...]}`
+    Split,
+}
+
+impl JsonOrient {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "records" => Ok(JsonOrient::Records),
+            "columns" => Ok(JsonOrient::Columns),
+            "split" => Ok(JsonOrient::Split),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown orient '{}'; expected records, columns or split",
+                other
+            ))),
+        }
+    }
+}
+
+/// How NaN and infinite floats are written (JSON has no literal for them)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NonFinitePolicy {
+    /// Write `null`
+    #[default]
+    Null,
+    /// Write the strings "NaN", "Infinity" and "-Infinity"
+    String,
+    /// Fail on the first non-finite value
+    Error,
+}
+
+impl NonFinitePolicy {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "null" => Ok(NonFinitePolicy::Null),
+            "string" => Ok(NonFinitePolicy::String),
+            "error" => Ok(NonFinitePolicy::Error),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown non_finite policy '{}'; expected null, string or error",
+                other
+            ))),
+        }
+    }
+}
+
+/// Options for JSON and JSON Lines output
+#[derive(Debug, Clone)]
+pub struct JsonWriteOptions {
+    /// Document layout (JSON Lines always writes one record per line)
+    pub orient: JsonOrient,
+    pub non_finite: NonFinitePolicy,
+    /// Rows serialized per batch; columns within a batch are serialized in parallel
+    pub batch_rows: usize,
+}
+
+impl Default for JsonWriteOptions {
+    fn default() -> Self {
+        Self {
+            orient: JsonOrient::Records,
+            non_finite: NonFinitePolicy::Null,
+            batch_rows: 65_536,
+        }
+    }
+}
+
+/// Data to serialize: one DataFrame or every chunk of a Dataset, in order
+#[derive(Clone, Copy)]
+pub enum JsonSource<'a> {
+    Frame(&'a DataFrame),
+    Dataset(&'a Dataset),
+}
+
+impl<'a> From<&'a DataFrame> for JsonSource<'a> {
+    fn from(df: &'a DataFrame) -> Self {
+        JsonSource::Frame(df)
+    }
+}
+
+impl<'a> From<&'a Dataset> for JsonSource<'a> {
+    fn from(dataset: &'a Dataset) -> Self {
+        JsonSource::Dataset(dataset)
+    }
+}
+
+impl<'a> JsonSource<'a> {
+    fn columns(&self) -> Vec<String> {
+        match self {
+            JsonSource::Frame(df) => df.get_column_names().iter().map(|s| s.to_string()).collect(),
+            JsonSource::Dataset(dataset) => dataset.schema().iter().map(|(name, _)| name.clone()).collect(),
+        }
+    }
+
+    fn num_rows(&self) -> usize {
+        match self {
+            JsonSource::Frame(df) => df.height(),
+            JsonSource::Dataset(dataset) => dataset.num_rows(),
+        }
+    }
+
+    fn num_chunks(&self) -> usize {
+        match self {
+            JsonSource::Frame(_) => 1,
+            JsonSource::Dataset(dataset) => dataset.num_chunks(),
+        }
+    }
+
+    fn chunk(&self, index: usize) -> Result<Cow<'a, DataFrame>, InsightoraError> {
+        match self {
+            JsonSource::Frame(df) => Ok(Cow::Borrowed(*df)),
+            JsonSource::Dataset(dataset) => Ok(Cow::Owned(dataset.chunk(index)?)),
+        }
+    }
+}
+
+/// Write data as a JSON document to a file
+///
+/// # Returns
+/// * `Result<usize>` - Number of rows written
+pub fn write_json<'a>(
+    source: impl Into<JsonSource<'a>>,
+    file_path: &str,
+    options: &JsonWriteOptions,
+) -> Result<usize, InsightoraError> {
+    let source = source.into();
+    let path = check_path_allowed(file_path)?;
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_json_to(source, &mut writer, options)?;
+    writer.flush()?;
+    Ok(source.num_rows())
+}
+
+/// Serialize data as a JSON document in memory
+pub fn to_json_string<'a>(source: impl Into<JsonSource<'a>>, options: &JsonWriteOptions) -> Result<String, InsightoraError> {
+    let mut buffer = Vec::new();
+    write_json_to(source, &mut buffer, options)?;
+    // Every token is built from Rust strings, so the output is valid UTF-8
+    String::from_utf8(buffer).map_err(|e| InsightoraError::ValidationError(e.to_string()))
+}
+
+/// Serialize data as a JSON document into any writer
+///
+/// Floats use the shortest form that round-trips, dates and datetimes are
+/// ISO 8601 strings (datetimes in UTC, with a `Z` suffix when the column has
+/// a time zone) and durations are ISO 8601 durations such as "PT1H30M".
+/// Rows are processed in batches of `batch_rows`, so a Dataset is streamed
+/// one chunk at a time except with the columns orient, which reads every
+/// chunk once per column.
+pub fn write_json_to<'a, W: Write>(
+    source: impl Into<JsonSource<'a>>,
+    writer: &mut W,
+    options: &JsonWriteOptions,
+) -> Result<(), InsightoraError> {
+    let source = source.into();
+    let columns = source.columns();
+    let keys: Vec<String> = columns.iter().map(|name| quote(name)).collect();
+
+    match options.orient {
+        JsonOrient::Records | JsonOrient::Split => {
+            let split = options.orient == JsonOrient::Split;
+            if split {
+                writer.write_all(b"{\"columns\":[")?;
+                writer.write_all(keys.join(",").as_bytes())?;
+                writer.write_all(b"],\"data\":[")?;
+            } else {
+                writer.write_all(b"[")?;
+            }
+            let mut first = true;
+            for_each_batch(source, options, |batch| {
+                let mut text = String::new();
+                for row in 0..batch[0].len() {
+                    if !first {
+                        text.push(',');
+                    }
+                    first = false;
+                    if split {
+                        push_array_row(&mut text, batch, row);
+                    } else {
+                        push_object_row(&mut text, &keys, batch, row);
+                    }
+                }
+                writer.write_all(text.as_bytes())?;
+                Ok(())
+            })?;
+            writer.write_all(if split { b"]}" } else { b"]" })?;
+        }
+        JsonOrient::Columns => {
+            writer.write_all(b"{")?;
+            for (index, key) in keys.iter().enumerate() {
+                if index > 0 {
+                    writer.write_all(b",")?;
+                }
+                writer.write_all(key.as_bytes())?;
+                writer.write_all(b":[")?;
+                let mut first = true;
+                for chunk in 0..source.num_chunks() {
+                    let df = source.chunk(chunk)?;
+                    let series = &df.get_columns()[index];
+                    let mut offset = 0;
+                    while offset < series.len() {
+                        let len = options.batch_rows.max(1).min(series.len() - offset);
+                        let values = render_column(&series.slice(offset as i64, len), options.non_finite)?;
+                        let mut text = String::new();
+                        for value in &values {
+                            if !first {
+                                text.push(',');
+                            }
+                            first = false;
+                            text.push_str(value);
+                        }
+                        writer.write_all(text.as_bytes())?;
+                        offset += len;
+                    }
+                }
+                writer.write_all(b"]")?;
+            }
+            writer.write_all(b"}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Write data as JSON Lines (one record object per line) to a file
+///
+/// # Returns
+/// * `Result<usize>` - Number of rows written
+pub fn write_ndjson<'a>(
+    source: impl Into<JsonSource<'a>>,
+    file_path: &str,
+    options: &JsonWriteOptions,
+) -> Result<usize, InsightoraError> {
+    let source = source.into();
+    let path = check_path_allowed(file_path)?;
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_ndjson_to(source, &mut writer, options)?;
+    writer.flush()?;
+    Ok(source.num_rows())
+}
+
+/// Serialize data as JSON Lines into any writer; `orient` is ignored
+pub fn write_ndjson_to<'a, W: Write>(
+    source: impl Into<JsonSource<'a>>,
+    writer: &mut W,
+    options: &JsonWriteOptions,
+) -> Result<(), InsightoraError> {
+    let source = source.into();
+    let keys: Vec<String> = source.columns().iter().map(|name| quote(name)).collect();
+    for_each_batch(source, options, |batch| {
+        let mut text = String::new();
+        for row in 0..batch[0].len() {
+            push_object_row(&mut text, &keys, batch, row);
+            text.push('\n');
+        }
+        writer.write_all(text.as_bytes())?;
+        Ok(())
+    })
+}
+
+/// Render the source batch by batch, handing each batch's columns to `emit`
+fn for_each_batch<F>(source: JsonSource, options: &JsonWriteOptions, mut emit: F) -> Result<(), InsightoraError>
+where
+    F: FnMut(&[Vec<String>]) -> Result<(), InsightoraError>,
+{
+    let batch_rows = options.batch_rows.max(1);
+    for chunk in 0..source.num_chunks() {
+        let df = source.chunk(chunk)?;
+        if df.width() == 0 {
+            continue;
+        }
+        let mut offset = 0;
+        while offset < df.height() {
+            let len = batch_rows.min(df.height() - offset);
+            let batch = df.slice(offset as i64, len);
+            let columns = batch
+                .get_columns()
+                .par_iter()
+                .map(|series| render_column(series, options.non_finite))
+                .collect::<Result<Vec<_>, _>>()?;
+            emit(&columns)?;
+            offset += len;
+        }
+    }
+    Ok(())
+}
+
+fn push_object_row(text: &mut String, keys: &[String], columns: &[Vec<String>], row: usize) {
+    text.push('{');
+    for (i, (key, column)) in keys.iter().zip(columns).enumerate() {
+        if i > 0 {
+            text.push(',');
+        }
+        text.push_str(key);
+        text.push(':');
+        text.push_str(&column[row]);
+    }
+    text.push('}');
+}
+
+fn push_array_row(text: &mut String, columns: &[Vec<String>], row: usize) {
+    text.push('[');
+    for (i, column) in columns.iter().enumerate() {
+        if i > 0 {
+            text.push(',');
+        }
+        text.push_str(&column[row]);
+    }
+    text.push(']');
+}
+
+/// JSON string literal
+fn quote(text: &str) -> String {
+    serde_json::to_string(text).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+/// Every value of a column as a JSON token (`null` for nulls)
+fn render_column(series: &Series, non_finite: NonFinitePolicy) -> Result<Vec<String>, InsightoraError> {
+    let null = || "null".to_string();
+    let values = match series.dtype() {
+        DataType::String => series.str()?.into_iter().map(|v| v.map_or_else(null, quote)).collect(),
+        DataType::Boolean => series.bool()?.into_iter().map(|v| v.map_or_else(null, |b| b.to_string())).collect(),
+        DataType::Float32 | DataType::Float64 => {
+            let floats = series.cast(&DataType::Float64)?;
+            floats
+                .f64()?
+                .into_iter()
+                .enumerate()
+                .map(|(row, v)| match v {
+                    None => Ok(null()),
+                    Some(x) if x.is_finite() => Ok(format!("{:?}", x)),
+                    Some(x) => match non_finite {
+                        NonFinitePolicy::Null => Ok(null()),
+                        NonFinitePolicy::String => Ok(quote(if x.is_nan() {
+                            "NaN"
+                        } else if x > 0.0 {
+                            "Infinity"
+                        } else {
+                            "-Infinity"
+                        })),
+                        NonFinitePolicy::Error => Err(InsightoraError::ValidationError(format!(
+                            "Column '{}' has non-finite value {} at row {}; use non_finite='null' or 'string'",
+                            series.name(),
+                            x,
+                            row
+                        ))),
+                    },
+                })
+                .collect::<Result<_, _>>()?
+        }
+        DataType::UInt64 => series.u64()?.into_iter().map(|v| v.map_or_else(null, |x| x.to_string())).collect(),
+        dtype if dtype.is_integer() => {
+            let ints = series.cast(&DataType::Int64)?;
+            ints.i64()?.into_iter().map(|v| v.map_or_else(null, |x| x.to_string())).collect()
+        }
+        DataType::Date => {
+            let days = series.to_physical_repr();
+            days.i32()?.into_iter().map(|v| v.map_or_else(null, |d| quote(&format_date(d, None)))).collect()
+        }
+        DataType::Datetime(unit, zone) => {
+            let (unit, suffix) = (*unit, if zone.is_some() { "Z" } else { "" });
+            let stamps = series.to_physical_repr();
+            stamps
+                .i64()?
+                .into_iter()
+                .map(|v| v.map_or_else(null, |t| quote(&format!("{}{}", format_datetime(t, unit, None), suffix))))
+                .collect()
+        }
+        DataType::Duration(_) => {
+            let micros = series.cast(&DataType::Duration(TimeUnit::Microseconds))?.to_physical_repr().into_owned();
+            micros.i64()?.into_iter().map(|v| v.map_or_else(null, |us| quote(&iso_duration(us)))).collect()
+        }
+        _ => {
+            let strings = series.cast(&DataType::String)?;
+            strings.str()?.into_iter().map(|v| v.map_or_else(null, quote)).collect()
+        }
+    };
+    Ok(values)
+}
+
+/// ISO 8601 duration ("P1DT2H3M4.5S"), read back by `parse_duration`
+fn iso_duration(micros: i64) -> String {
+    let sign = if micros < 0 { "-" } else { "" };
+    let total = micros.unsigned_abs();
+    let (days, rest) = (total / 86_400_000_000, total % 86_400_000_000);
+    let (hours, rest) = (rest / 3_600_000_000, rest % 3_600_000_000);
+    let (minutes, rest) = (rest / 60_000_000, rest % 60_000_000);
+    let (seconds, fraction) = (rest / 1_000_000, rest % 1_000_000);
+
+    let mut text = format!("{}P", sign);
+    if days > 0 {
+        text.push_str(&format!("{}D", days));
+    }
+    if hours > 0 || minutes > 0 || seconds > 0 || fraction > 0 || days == 0 {
+        text.push('T');
+        if hours > 0 {
+            text.push_str(&format!("{}H", hours));
+        }
+        if minutes > 0 {
+            text.push_str(&format!("{}M", minutes));
+        }
+        if fraction > 0 {
+            let digits = format!("{:06}", fraction);
+            text.push_str(&format!("{}.{}S", seconds, digits.trim_end_matches('0')));
+        } else if seconds > 0 || (hours == 0 && minutes == 0) {
+            text.push_str(&format!("{}S", seconds));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::transformations::{parse_duration, DurationFormat};
+    use crate::io::dataset::{DatasetBuilder, DatasetBuilderConfig};
+    use polars::export::chrono::NaiveDate;
+
+    fn sample() -> DataFrame {
+        let day = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        df!(
+            "id" => [1i64, 2, 3],
+            "name" => [Some("a \"quoted\"\nline"), None, Some("ü")],
+            "score" => [1.5, f64::NAN, f64::INFINITY],
+            "day" => [day, day, day],
+            "at" => [day.and_hms_opt(9, 30, 0).unwrap(), day.and_hms_micro_opt(0, 0, 1, 250_000).unwrap(), day.and_hms_opt(0, 0, 0).unwrap()],
+        )
+        .unwrap()
+    }
+
+    fn json(df: &DataFrame, orient: JsonOrient, non_finite: NonFinitePolicy) -> Result<serde_json::Value, InsightoraError> {
+        let options = JsonWriteOptions { orient, non_finite, batch_rows: 2 };
+        Ok(serde_json::from_str(&to_json_string(df, &options)?).unwrap())
+    }
+
+    #[test]
+    fn test_orients_and_value_formats() {
+        let records = json(&sample(), JsonOrient::Records, NonFinitePolicy::Null).unwrap();
+        assert_eq!(
+            records[0],
+            serde_json::json!({
+                "id": 1, "name": "a \"quoted\"\nline", "score": 1.5,
+                "day": "2024-03-01", "at": "2024-03-01T09:30:00",
+            })
+        );
+        assert_eq!(records[1]["score"], serde_json::Value::Null);
+        assert_eq!(records[1]["name"], serde_json::Value::Null);
+        assert_eq!(records[1]["at"], "2024-03-01T00:00:01.250");
+        assert_eq!(records.as_array().unwrap().len(), 3);
+
+        let columns = json(&sample(), JsonOrient::Columns, NonFinitePolicy::String).unwrap();
+        assert_eq!(columns["score"], serde_json::json!([1.5, "NaN", "Infinity"]));
+        assert_eq!(columns["id"], serde_json::json!([1, 2, 3]));
+
+        let split = json(&sample(), JsonOrient::Split, NonFinitePolicy::Null).unwrap();
+        assert_eq!(split["columns"], serde_json::json!(["id", "name", "score", "day", "at"]));
+        assert_eq!(split["data"][2][2], serde_json::Value::Null);
+
+        let err = json(&sample(), JsonOrient::Records, NonFinitePolicy::Error).unwrap_err();
+        assert!(err.to_string().contains("Column 'score' has non-finite value NaN at row 1"));
+        assert!(JsonOrient::from_name("index").is_err());
+    }
+
+    #[test]
+    fn test_ndjson_streams_dataset_chunks() {
+        let mut builder = DatasetBuilder::new(DatasetBuilderConfig::default()).unwrap();
+        for start in [0i64, 3, 6] {
+            builder.append(df!("n" => [start, start + 1, start + 2]).unwrap()).unwrap();
+        }
+        let dataset = builder.finish().unwrap();
+
+        let mut out = Vec::new();
+        write_ndjson_to(&dataset, &mut out, &JsonWriteOptions { batch_rows: 2, ..Default::default() }).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 9);
+        assert_eq!(lines[4], "{\"n\":4}");
+
+        let columns = JsonWriteOptions { orient: JsonOrient::Columns, ..Default::default() };
+        assert_eq!(to_json_string(&dataset, &columns).unwrap(), "{\"n\":[0,1,2,3,4,5,6,7,8]}");
+    }
+
+    #[test]
+    fn test_iso_durations_round_trip() {
+        for micros in [0, 1_500_000, 5_400_000_000, 90_061_000_001, -3_600_000_000] {
+            let text = iso_duration(micros);
+            assert_eq!(parse_duration(&text, DurationFormat::Iso8601), Some(micros), "{}", text);
+        }
+        assert_eq!(iso_duration(5_400_000_000), "PT1H30M");
+    }
+}
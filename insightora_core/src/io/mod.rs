@@ -1,6 +1,25 @@
 // I/O module for parallel file processing
-// Handles CSV, Excel parsing and Arrow format conversion
+// Handles multi-file CSV globs, automatic format detection with fallbacks, retried chunked reads, gzip/zstd input, CSV in legacy encodings with NOT NULL checks, row sampling, schema inference reports, date parsing and block indexes, Excel, XML/HTML and NDJSON parsing, JSON and Parquet export, remote fetching, ZIP archives, chunked datasets and Arrow format conversion
 
 pub mod csv_parser;
+pub mod csv_glob;
+pub mod file_index;
+pub mod nullability;
+pub mod dates;
+pub mod chunk_reader;
+pub mod compression;
+pub mod encoding;
+pub mod csv_repair;
+pub mod schema_report;
+pub mod sampling;
+pub mod json_writer;
+pub mod json_parser;
+pub mod parquet_writer;
+pub mod remote;
+pub mod archive;
+pub mod auto;
+pub mod dataset;
+pub mod xml_parser;
 pub mod excel_parser;
 pub mod arrow_bridge;
+pub mod pandas_bridge;
@@ -0,0 +1,227 @@
+// NOT NULL enforcement for parsed data
+// Counts nulls and null tokens in declared columns batch by batch, or aborts at the first one
+
+use polars::prelude::*;
+use crate::error::InsightoraError;
+
+/// Row numbers kept per column in a nullability report
+pub const NULL_SAMPLE_ROWS: usize = 10;
+
+/// What happens when a NOT NULL column holds a null
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NullPolicy {
+    /// Count violations and keep sample row numbers (default)
+    #[default]
+    Report,
+    /// Abort the parse at the first violation
+    Strict,
+}
+
+impl NullPolicy {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name {
+            "report" => Ok(NullPolicy::Report),
+            "strict" => Ok(NullPolicy::Strict),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown null policy '{}'; expected 'report' or 'strict'",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NullPolicy::Report => "report",
+            NullPolicy::Strict => "strict",
+        }
+    }
+}
+
+/// Nullability declared for parsed columns
+///
+/// Columns are named as they come out of the parser, i.e. after `rename`.
+/// Besides real nulls (empty fields), text equal to one of `null_tokens`
+/// ("NULL", "N/A", ...) counts as null in a NOT NULL column.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NotNullRules {
+    pub columns: Vec<String>,
+    pub null_tokens: Vec<String>,
+    pub policy: NullPolicy,
+}
+
+impl NotNullRules {
+    pub fn new(columns: Vec<String>) -> Self {
+        Self { columns, ..Default::default() }
+    }
+
+    pub fn with_null_tokens(mut self, tokens: Vec<String>) -> Self {
+        self.null_tokens = tokens;
+        self
+    }
+
+    pub fn with_policy(mut self, policy: NullPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// True when no column is declared NOT NULL
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    /// Check a whole frame at once
+    pub fn check(&self, df: &DataFrame) -> Result<NullabilityReport, InsightoraError> {
+        let mut check = NullCheck::new(self);
+        check.batch(df)?;
+        Ok(check.finish())
+    }
+}
+
+/// Null count and sample rows for one NOT NULL column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NullViolations {
+    pub column: String,
+    pub count: usize,
+    /// First `NULL_SAMPLE_ROWS` offending data rows, 1-based, header excluded
+    pub sample_rows: Vec<usize>,
+}
+
+/// Outcome of the NOT NULL checks over a parse
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NullabilityReport {
+    pub policy: NullPolicy,
+    pub rows_checked: usize,
+    /// Columns with at least one violation, in declaration order
+    pub violations: Vec<NullViolations>,
+}
+
+impl NullabilityReport {
+    pub fn total(&self) -> usize {
+        self.violations.iter().map(|v| v.count).sum()
+    }
+}
+
+/// Running NOT NULL check over consecutive batches of one parse
+///
+/// Row numbers continue across batches, so a violation is located in the
+/// whole file rather than its batch.
+pub struct NullCheck<'a> {
+    rules: &'a NotNullRules,
+    report: NullabilityReport,
+    counts: Vec<(usize, Vec<usize>)>,
+}
+
+impl<'a> NullCheck<'a> {
+    pub fn new(rules: &'a NotNullRules) -> Self {
+        Self {
+            rules,
+            report: NullabilityReport { policy: rules.policy, ..Default::default() },
+            counts: vec![(0, Vec::new()); rules.columns.len()],
+        }
+    }
+
+    /// Check the next batch; in strict mode the first violation is an error
+    pub fn batch(&mut self, batch: &DataFrame) -> Result<(), InsightoraError> {
+        if self.rules.is_empty() {
+            return Ok(());
+        }
+        let missing: Vec<String> = self
+            .rules
+            .columns
+            .iter()
+            .filter(|name| batch.column(name).is_err())
+            .map(|name| format!("NOT NULL column '{}' is not in the data", name))
+            .collect();
+        if !missing.is_empty() {
+            return Err(InsightoraError::SchemaError(missing));
+        }
+
+        let offset = self.report.rows_checked;
+        // Strict mode reports the earliest row, whichever column it is in
+        let mut first: Option<(usize, &String)> = None;
+        for (name, (count, samples)) in self.rules.columns.iter().zip(self.counts.iter_mut()) {
+            let mask = null_mask(batch.column(name)?, &self.rules.null_tokens)?;
+            if !mask.any() {
+                continue;
+            }
+            for (i, is_null) in mask.into_iter().enumerate() {
+                if is_null != Some(true) {
+                    continue;
+                }
+                let row = offset + i + 1;
+                if self.rules.policy == NullPolicy::Strict {
+                    if first.is_none_or(|(earliest, _)| row < earliest) {
+                        first = Some((row, name));
+                    }
+                    break;
+                }
+                *count += 1;
+                if samples.len() < NULL_SAMPLE_ROWS {
+                    samples.push(row);
+                }
+            }
+        }
+        if let Some((row, column)) = first {
+            return Err(InsightoraError::NotNullViolation { column: column.clone(), row });
+        }
+        self.report.rows_checked += batch.height();
+        Ok(())
+    }
+
+    pub fn finish(self) -> NullabilityReport {
+        let mut report = self.report;
+        report.violations = self
+            .rules
+            .columns
+            .iter()
+            .zip(self.counts)
+            .filter(|(_, (count, _))| *count > 0)
+            .map(|(column, (count, sample_rows))| NullViolations { column: column.clone(), count, sample_rows })
+            .collect();
+        report
+    }
+}
+
+/// True where the value is null or, for text, one of `tokens`
+fn null_mask(series: &Series, tokens: &[String]) -> Result<BooleanChunked, InsightoraError> {
+    let nulls = series.is_null();
+    if tokens.is_empty() || series.dtype() != &DataType::String {
+        return Ok(nulls);
+    }
+    let tokened: BooleanChunked = series
+        .str()?
+        .into_iter()
+        .map(|value| value.is_some_and(|v| tokens.iter().any(|t| t == v.trim())))
+        .collect();
+    Ok(&nulls | &tokened)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_nulls_and_tokens_across_batches() {
+        let rules = NotNullRules::new(vec!["id".into(), "name".into()]).with_null_tokens(vec!["N/A".into()]);
+        let first = df!("id" => [Some(1), None, Some(3)], "name" => ["a", "N/A", "c"], "note" => [None::<&str>, None, None]).unwrap();
+        let second = df!("id" => [Some(4), Some(5)], "name" => [Some(" N/A "), None], "note" => [None::<&str>, None]).unwrap();
+
+        let mut check = NullCheck::new(&rules);
+        check.batch(&first).unwrap();
+        check.batch(&second).unwrap();
+        let report = check.finish();
+        assert_eq!(report.rows_checked, 5);
+        assert_eq!(report.violations, [
+            NullViolations { column: "id".into(), count: 1, sample_rows: vec![2] },
+            NullViolations { column: "name".into(), count: 3, sample_rows: vec![2, 4, 5] },
+        ]);
+
+        let strict = rules.clone().with_policy(NullPolicy::Strict);
+        let mut check = NullCheck::new(&strict);
+        let err = check.batch(&first).unwrap_err();
+        assert!(matches!(err, InsightoraError::NotNullViolation { ref column, row: 2 } if column == "id"), "{}", err);
+
+        let missing = NotNullRules::new(vec!["email".into()]).check(&first).unwrap_err();
+        assert!(matches!(missing, InsightoraError::SchemaError(_)));
+    }
+}
@@ -0,0 +1,60 @@
+// pandas dtype mapping
+// Chooses the pandas dtype each column should take when results are returned as pandas
+
+use polars::prelude::*;
+
+/// pandas dtype for a column, or None to let pandas infer from the values
+///
+/// Integer, boolean and string columns holding nulls would otherwise be
+/// coerced by pandas (integers to float64, losing precision above 2^53, and
+/// booleans/strings to object). With `nullable` set those columns get the
+/// pandas extension dtypes (`Int64`, `boolean`, `string`); columns without
+/// nulls always get the plain NumPy dtype.
+pub fn pandas_dtype(series: &Series, nullable: bool) -> Option<&'static str> {
+    let has_nulls = series.null_count() > 0;
+    let (numpy, extension) = match series.dtype() {
+        DataType::Int8 => ("int8", "Int8"),
+        DataType::Int16 => ("int16", "Int16"),
+        DataType::Int32 => ("int32", "Int32"),
+        DataType::Int64 => ("int64", "Int64"),
+        DataType::UInt8 => ("uint8", "UInt8"),
+        DataType::UInt16 => ("uint16", "UInt16"),
+        DataType::UInt32 => ("uint32", "UInt32"),
+        DataType::UInt64 => ("uint64", "UInt64"),
+        DataType::Boolean => ("bool", "boolean"),
+        // Nulls already map to NaN without loss
+        DataType::Float32 => return Some("float32"),
+        DataType::Float64 => return Some("float64"),
+        DataType::String => {
+            return (nullable && has_nulls).then_some("string");
+        }
+        _ => return None,
+    };
+    match (has_nulls, nullable) {
+        (false, _) => Some(numpy),
+        (true, true) => Some(extension),
+        // Legacy behaviour: pandas picks float64 / object
+        (true, false) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nullable_dtypes_only_for_columns_with_nulls() {
+        let ids = Series::new("id", [Some(9_007_199_254_740_993i64), None, Some(3)]);
+        let dense = Series::new("n", [1i64, 2, 3]);
+        let flags = Series::new("flag", [Some(true), None]);
+        let names = Series::new("name", [Some("a"), None]);
+
+        assert_eq!(pandas_dtype(&ids, true), Some("Int64"));
+        assert_eq!(pandas_dtype(&ids, false), None);
+        assert_eq!(pandas_dtype(&dense, true), Some("int64"));
+        assert_eq!(pandas_dtype(&dense, false), Some("int64"));
+        assert_eq!(pandas_dtype(&flags, true), Some("boolean"));
+        assert_eq!(pandas_dtype(&names, true), Some("string"));
+        assert_eq!(pandas_dtype(&names, false), None);
+    }
+}
@@ -0,0 +1,344 @@
+// Parquet export
+// Writes single and hive-partitioned Parquet files, optionally enforcing a canonical schema and column order
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::Path;
+use polars::prelude::*;
+use crate::error::InsightoraError;
+use crate::utils::sandbox::check_path_allowed;
+
+/// Directory name used for null partition values
+pub const NULL_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+// ============================================================================
+// Schema Enforcement
+// ============================================================================
+
+/// Canonical schema every written file must have
+///
+/// Output columns are rewritten to exactly `columns`, in that order. Columns
+/// of another dtype are cast when the cast is lossless (integer and float
+/// widening, integers into a float wide enough for them, date to datetime,
+/// finer datetime units, categorical and text either way); any other dtype
+/// difference is a violation.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaEnforcement {
+    pub columns: Vec<(String, DataType)>,
+    /// Fill declared columns absent from the frame with typed nulls instead of failing
+    pub allow_missing: bool,
+    /// Drop undeclared columns (listed in the report) instead of failing
+    pub drop_extra: bool,
+}
+
+/// What enforcement changed to make a frame conform
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaEnforcementReport {
+    /// Declared columns filled with nulls
+    pub filled_missing: Vec<String>,
+    /// Undeclared columns dropped
+    pub dropped_extra: Vec<String>,
+    /// (column, from, to) for each cast column
+    pub cast: Vec<(String, DataType, DataType)>,
+}
+
+impl SchemaEnforcement {
+    pub fn new(columns: Vec<(String, DataType)>) -> Self {
+        Self { columns, ..Default::default() }
+    }
+
+    /// Conform `df` to the declared schema
+    ///
+    /// Every violation is collected before failing, so one error lists all
+    /// of them.
+    ///
+    /// # Returns
+    /// * `Result<(DataFrame, SchemaEnforcementReport)>` - SchemaError listing every violation
+    pub fn apply(&self, df: &DataFrame) -> Result<(DataFrame, SchemaEnforcementReport), InsightoraError> {
+        let mut problems = Vec::new();
+        let mut report = SchemaEnforcementReport::default();
+        let mut columns = Vec::with_capacity(self.columns.len());
+
+        for (name, dtype) in &self.columns {
+            let Ok(series) = df.column(name) else {
+                if self.allow_missing {
+                    report.filled_missing.push(name.clone());
+                    columns.push(Series::full_null(name, df.height(), dtype));
+                } else {
+                    problems.push(format!("missing column '{}' ({})", name, dtype));
+                }
+                continue;
+            };
+            if series.dtype() == dtype {
+                columns.push(series.clone());
+            } else if is_lossless_cast(series.dtype(), dtype) {
+                report.cast.push((name.clone(), series.dtype().clone(), dtype.clone()));
+                columns.push(series.cast(dtype)?);
+            } else {
+                problems.push(format!("column '{}' is {}, declared {} (no lossless cast)", name, series.dtype(), dtype));
+            }
+        }
+        for series in df.get_columns() {
+            if !self.columns.iter().any(|(name, _)| name == series.name()) {
+                if self.drop_extra {
+                    report.dropped_extra.push(series.name().to_string());
+                } else {
+                    problems.push(format!("undeclared column '{}'", series.name()));
+                }
+            }
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for (name, _) in &self.columns {
+            if !seen.insert(name.as_str()) {
+                problems.push(format!("column '{}' is declared twice", name));
+            }
+        }
+        if !problems.is_empty() {
+            return Err(InsightoraError::SchemaError(problems));
+        }
+        Ok((DataFrame::new(columns)?, report))
+    }
+}
+
+/// Whether every value of `from` is represented exactly in `to`
+fn is_lossless_cast(from: &DataType, to: &DataType) -> bool {
+    let integer = |dtype: &DataType| match dtype {
+        DataType::Int8 => Some((true, 8)),
+        DataType::Int16 => Some((true, 16)),
+        DataType::Int32 => Some((true, 32)),
+        DataType::Int64 => Some((true, 64)),
+        DataType::UInt8 => Some((false, 8)),
+        DataType::UInt16 => Some((false, 16)),
+        DataType::UInt32 => Some((false, 32)),
+        DataType::UInt64 => Some((false, 64)),
+        _ => None,
+    };
+    // Integer widths a float mantissa holds exactly
+    let mantissa = |dtype: &DataType| match dtype {
+        DataType::Float32 => Some(24),
+        DataType::Float64 => Some(53),
+        _ => None,
+    };
+    let unit_rank = |unit: &TimeUnit| match unit {
+        TimeUnit::Milliseconds => 0,
+        TimeUnit::Microseconds => 1,
+        TimeUnit::Nanoseconds => 2,
+    };
+
+    match (from, to) {
+        (DataType::Null, _) => true,
+        (DataType::Float32, DataType::Float64) => true,
+        (DataType::String, DataType::Categorical(..)) | (DataType::Categorical(..), DataType::String) => true,
+        (DataType::Date, DataType::Datetime(..)) => true,
+        (DataType::Datetime(a, tz_a), DataType::Datetime(b, tz_b)) => tz_a == tz_b && unit_rank(a) <= unit_rank(b),
+        (DataType::Duration(a), DataType::Duration(b)) => unit_rank(a) <= unit_rank(b),
+        _ => match (integer(from), integer(to), mantissa(to)) {
+            (Some((fs, fb)), Some((ts, tb)), _) => match (fs, ts) {
+                (true, true) | (false, false) => tb >= fb,
+                (false, true) => tb > fb,
+                (true, false) => false,
+            },
+            (Some((signed, bits)), None, Some(digits)) => bits - u32::from(signed) <= digits,
+            _ => false,
+        },
+    }
+}
+
+// ============================================================================
+// Writers
+// ============================================================================
+
+/// Outcome of a Parquet write
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParquetWriteReport {
+    /// Files written, in partition order
+    pub files: Vec<String>,
+    pub rows: usize,
+    pub schema: SchemaEnforcementReport,
+}
+
+/// Write a DataFrame to one Parquet file
+///
+/// # Arguments
+/// * `df` - Data to write
+/// * `file_path` - Output file (subject to the path policy)
+/// * `schema` - Canonical schema to enforce before writing
+///
+/// # Returns
+/// * `Result<ParquetWriteReport>` - SchemaError listing every violation;
+///   nothing is written in that case
+pub fn write_parquet(
+    df: &DataFrame,
+    file_path: &str,
+    schema: Option<&SchemaEnforcement>,
+) -> Result<ParquetWriteReport, InsightoraError> {
+    let path = check_path_allowed(file_path)?;
+    let (mut frame, report) = conform(df, schema)?;
+    ParquetWriter::new(File::create(path)?).finish(&mut frame)?;
+    Ok(ParquetWriteReport { files: vec![file_path.to_string()], rows: frame.height(), schema: report })
+}
+
+/// Write a DataFrame as hive-partitioned Parquet files
+///
+/// Rows are split by the values of `partition_by` into
+/// `directory/col=value/.../part-0.parquet`; values are percent-encoded and
+/// nulls use `NULL_PARTITION`. As in hive layouts the partition columns are
+/// stored in the directory names only, so `schema` declares the columns of
+/// each file without them. The schema is enforced once, before any file is
+/// written.
+///
+/// # Returns
+/// * `Result<ParquetWriteReport>` - Files written and what enforcement changed
+pub fn write_partitioned(
+    df: &DataFrame,
+    directory: &str,
+    partition_by: &[String],
+    schema: Option<&SchemaEnforcement>,
+) -> Result<ParquetWriteReport, InsightoraError> {
+    if partition_by.is_empty() {
+        return Err(InsightoraError::ValidationError("At least one partition column is required".to_string()));
+    }
+    let root = check_path_allowed(directory)?;
+    let keys = partition_by
+        .iter()
+        .map(|name| Ok(df.column(name)?.cast(&DataType::String)?))
+        .collect::<Result<Vec<Series>, InsightoraError>>()?;
+    let keys: Vec<&StringChunked> = keys.iter().map(|s| s.str()).collect::<Result<_, _>>()?;
+    let (data, report) = conform(&df.drop_many(partition_by), schema)?;
+
+    // Partition directories in first-seen order
+    let mut order: Vec<String> = Vec::new();
+    let mut rows: HashMap<String, Vec<IdxSize>> = HashMap::new();
+    for row in 0..df.height() {
+        let relative = partition_by
+            .iter()
+            .zip(&keys)
+            .map(|(name, values)| {
+                let value = values.get(row).map_or_else(|| NULL_PARTITION.to_string(), percent_encode);
+                format!("{}={}", percent_encode(name), value)
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        rows.entry(relative.clone()).or_insert_with(|| {
+            order.push(relative);
+            Vec::new()
+        }).push(row as IdxSize);
+    }
+
+    let mut files = Vec::with_capacity(order.len());
+    for relative in order {
+        let dir = root.join(&relative);
+        fs::create_dir_all(&dir)?;
+        let mut part = data.take(&IdxCa::from_vec("", rows.remove(&relative).unwrap_or_default()))?;
+        ParquetWriter::new(File::create(dir.join("part-0.parquet"))?).finish(&mut part)?;
+        // Reported under the directory as given, not its resolved form
+        let file = Path::new(directory).join(&relative).join("part-0.parquet");
+        files.push(file.to_string_lossy().into_owned());
+    }
+    Ok(ParquetWriteReport { files, rows: data.height(), schema: report })
+}
+
+fn conform(df: &DataFrame, schema: Option<&SchemaEnforcement>) -> Result<(DataFrame, SchemaEnforcementReport), InsightoraError> {
+    match schema {
+        Some(schema) => schema.apply(df),
+        None => Ok((df.clone(), SchemaEnforcementReport::default())),
+    }
+}
+
+/// Encode everything but ASCII letters, digits and `-_.` for use in a directory name
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn canonical() -> Vec<(String, DataType)> {
+        vec![
+            ("id".to_string(), DataType::Int64),
+            ("amount".to_string(), DataType::Float64),
+            ("region".to_string(), DataType::String),
+            ("day".to_string(), DataType::Date),
+            ("flag".to_string(), DataType::Boolean),
+        ]
+    }
+
+    #[test]
+    fn test_written_schema_matches_declaration() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("feed.parquet").to_string_lossy().into_owned();
+        // Out of order, narrower types and no 'flag'
+        let df = df!(
+            "region" => ["n", "s"],
+            "amount" => [1.5f32, 2.0],
+            "id" => [1i32, 2],
+            "day" => [19000i32, 19001]
+        )
+        .unwrap();
+        let df = df.lazy().with_column(col("day").cast(DataType::Date)).collect().unwrap();
+        let enforcement = SchemaEnforcement { allow_missing: true, ..SchemaEnforcement::new(canonical()) };
+
+        let report = write_parquet(&df, &path, Some(&enforcement)).unwrap();
+        assert_eq!(report.schema.filled_missing, vec!["flag"]);
+        assert_eq!(report.schema.cast.len(), 2);
+
+        let written = ParquetReader::new(File::open(&path).unwrap()).finish().unwrap();
+        let schema: Vec<(String, DataType)> = written
+            .get_columns()
+            .iter()
+            .map(|s| (s.name().to_string(), s.dtype().clone()))
+            .collect();
+        assert_eq!(schema, canonical());
+        assert_eq!(written.column("flag").unwrap().null_count(), 2);
+    }
+
+    #[test]
+    fn test_every_violation_is_reported() {
+        let df = df!("id" => ["a"], "extra" => [1], "other" => [2]).unwrap();
+        let err = SchemaEnforcement::new(canonical()).apply(&df).unwrap_err();
+        let InsightoraError::SchemaError(problems) = err else { panic!("expected SchemaError") };
+        assert_eq!(problems.len(), 7, "{:?}", problems);
+        assert!(problems[0].contains("column 'id' is str, declared i64"));
+        assert!(problems.iter().any(|p| p == "undeclared column 'other'"));
+
+        let relaxed = SchemaEnforcement { allow_missing: true, drop_extra: true, ..SchemaEnforcement::new(canonical()) };
+        let err = relaxed.apply(&df).unwrap_err().to_string();
+        assert!(err.contains("'id'") && !err.contains("extra"), "{}", err);
+    }
+
+    #[test]
+    fn test_partitioned_layout() {
+        let dir = TempDir::new().unwrap();
+        let df = df!(
+            "region" => [Some("north"), Some("south/east"), None, Some("north")],
+            "id" => [1i64, 2, 3, 4],
+            "amount" => [1.0, 2.0, 3.0, 4.0]
+        )
+        .unwrap();
+        let enforcement = SchemaEnforcement::new(vec![("amount".into(), DataType::Float64), ("id".into(), DataType::Int64)]);
+        let root = dir.path().to_string_lossy().into_owned();
+        let report = write_partitioned(&df, &root, &["region".to_string()], Some(&enforcement)).unwrap();
+
+        assert_eq!(report.rows, 4);
+        let suffixes: Vec<String> = report.files.iter().map(|f| f[root.len() + 1..].to_string()).collect();
+        assert_eq!(suffixes, vec![
+            "region=north/part-0.parquet".to_string(),
+            "region=south%2Feast/part-0.parquet".to_string(),
+            format!("region={}/part-0.parquet", NULL_PARTITION),
+        ]);
+        let north = ParquetReader::new(File::open(&report.files[0]).unwrap()).finish().unwrap();
+        assert_eq!(north.get_column_names(), &["amount", "id"]);
+        assert_eq!(north.height(), 2);
+    }
+}
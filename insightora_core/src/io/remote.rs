@@ -0,0 +1,505 @@
+// Remote file fetching
+// Bounded-concurrency download + parse for lists of HTTP(S) CSV files
+
+use std::io::{Cursor, Read};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use polars::prelude::*;
+use crate::error::InsightoraError;
+use crate::io::csv_parser::CsvParserConfig;
+use crate::dataframe::transformations::rename_and_project;
+use crate::utils::progress::ProgressReporter;
+use crate::utils::sandbox::check_url_allowed;
+
+/// What to do when a URL still fails after all retries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnError {
+    /// Abort the whole fetch and return the error
+    #[default]
+    Raise,
+    /// Record the failure in the per-URL status and continue
+    Skip,
+}
+
+impl OnError {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "raise" => Ok(OnError::Raise),
+            "skip" => Ok(OnError::Skip),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown on_error policy '{}'; expected 'raise' or 'skip'",
+                other
+            ))),
+        }
+    }
+}
+
+/// How frames with different columns are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchemaAlignment {
+    /// Every frame must have the same column names in the same order
+    Strict,
+    /// Columns are matched by name; missing columns are filled with nulls and
+    /// columns whose types disagree are widened to strings
+    #[default]
+    Union,
+}
+
+impl SchemaAlignment {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "strict" => Ok(SchemaAlignment::Strict),
+            "union" => Ok(SchemaAlignment::Union),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown schema alignment '{}'; expected 'strict' or 'union'",
+                other
+            ))),
+        }
+    }
+}
+
+/// Configuration for fetching many remote files
+#[derive(Debug, Clone)]
+pub struct RemoteFetchConfig {
+    /// Maximum number of downloads in flight
+    pub max_concurrent: usize,
+    /// Retries per URL after the first attempt
+    pub max_retries: u32,
+    /// Delay before the first retry; doubles on each further retry
+    pub backoff_base: Duration,
+    /// Timeout for a single HTTP request
+    pub request_timeout: Duration,
+    /// Deadline for the whole operation
+    pub total_deadline: Option<Duration>,
+    pub on_error: OnError,
+    pub alignment: SchemaAlignment,
+    pub parse_options: CsvParserConfig,
+    /// Progress across the download and align stages
+    pub progress: ProgressReporter,
+}
+
+impl Default for RemoteFetchConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 8,
+            max_retries: 3,
+            backoff_base: Duration::from_millis(250),
+            request_timeout: Duration::from_secs(30),
+            total_deadline: None,
+            on_error: OnError::Raise,
+            alignment: SchemaAlignment::Union,
+            parse_options: CsvParserConfig::default(),
+            progress: ProgressReporter::disabled(),
+        }
+    }
+}
+
+/// Outcome of fetching one URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteFileStatus {
+    pub url: String,
+    /// Last HTTP status code received, if any response arrived
+    pub http_status: Option<u16>,
+    pub bytes: u64,
+    pub rows: usize,
+    pub retries: u32,
+    pub elapsed_ms: u64,
+    /// Failure reason when the file was not included
+    pub error: Option<String>,
+}
+
+/// Combined data plus per-URL status, in input order
+#[derive(Debug, Clone)]
+pub struct RemoteParseResult {
+    pub data: DataFrame,
+    pub files: Vec<RemoteFileStatus>,
+}
+
+/// Status of one URL and its rows, when they were included
+type FetchOutcome = (RemoteFileStatus, Option<DataFrame>);
+
+/// Download and parse many CSV URLs with bounded concurrency
+///
+/// Downloads run on dedicated threads (separate from the Rayon compute pool)
+/// and each payload is parsed as soon as it arrives. Transient failures
+/// (connection errors, timeouts, 408/425/429/5xx) are retried with
+/// exponential backoff. Every URL is checked against the configured URL
+/// policy before any request is made.
+///
+/// # Returns
+/// * `Result<RemoteParseResult>` - Frames concatenated in input order
+pub fn parse_remote_many(urls: &[String], config: &RemoteFetchConfig) -> Result<RemoteParseResult, InsightoraError> {
+    if config.max_concurrent == 0 {
+        return Err(InsightoraError::ValidationError(
+            "max_concurrent must be greater than 0".to_string(),
+        ));
+    }
+    for url in urls {
+        check_url_allowed(url)?;
+    }
+
+    let started = Instant::now();
+    let deadline = config.total_deadline.map(|d| started + d);
+    let agent = ureq::AgentBuilder::new().build();
+
+    // Sizes aren't known before download; aligning is cheap next to the network
+    let stages = config.progress.stages(&[("download", 0.9), ("align", 0.1)]);
+    let (download_progress, align_progress) = (&stages[0], &stages[1]);
+    download_progress.report(0.0, "");
+
+    let next = AtomicUsize::new(0);
+    let completed = AtomicUsize::new(0);
+    let abort = AtomicBool::new(false);
+    let slots: Mutex<Vec<Option<FetchOutcome>>> = Mutex::new(vec![None; urls.len()]);
+
+    let workers = config.max_concurrent.min(urls.len());
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                if abort.load(Ordering::Relaxed) {
+                    break;
+                }
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= urls.len() {
+                    break;
+                }
+                let outcome = fetch_and_parse(&agent, &urls[index], config, deadline);
+                if outcome.1.is_none() && config.on_error == OnError::Raise {
+                    abort.store(true, Ordering::Relaxed);
+                }
+                if let Ok(mut slots) = slots.lock() {
+                    slots[index] = Some(outcome);
+                }
+                let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
+                download_progress.report(done as f64 / urls.len() as f64, &urls[index]);
+            });
+        }
+    });
+
+    let slots = slots
+        .into_inner()
+        .map_err(|e| InsightoraError::ThreadPoolError(format!("Download worker panicked: {}", e)))?;
+
+    let mut files = Vec::with_capacity(urls.len());
+    let mut frames = Vec::new();
+    for (url, slot) in urls.iter().zip(slots) {
+        // Empty slots were never started because another URL failed under Raise
+        let Some((status, frame)) = slot else {
+            continue;
+        };
+        if let (OnError::Raise, Some(err)) = (config.on_error, &status.error) {
+            return Err(InsightoraError::NetworkError(format!("{}: {}", url, err)));
+        }
+        frames.extend(frame);
+        files.push(status);
+    }
+
+    align_progress.report(0.0, "");
+    let data = align_and_concat(frames, config.alignment)?;
+    align_progress.finish("");
+
+    Ok(RemoteParseResult { data, files })
+}
+
+/// Whether an HTTP status is worth retrying
+fn is_transient_status(code: u16) -> bool {
+    matches!(code, 408 | 425 | 429 | 500 | 502 | 503 | 504)
+}
+
+/// Backoff before retry number `retry` (0-based): base * 2^retry
+fn backoff_delay(base: Duration, retry: u32) -> Duration {
+    base.saturating_mul(1u32 << retry.min(16))
+}
+
+fn fetch_and_parse(
+    agent: &ureq::Agent,
+    url: &str,
+    config: &RemoteFetchConfig,
+    deadline: Option<Instant>,
+) -> FetchOutcome {
+    let started = Instant::now();
+    let mut status = RemoteFileStatus {
+        url: url.to_string(),
+        http_status: None,
+        bytes: 0,
+        rows: 0,
+        retries: 0,
+        elapsed_ms: 0,
+        error: None,
+    };
+
+    let result = download(agent, url, config, deadline, &mut status)
+        .and_then(|payload| {
+            status.bytes = payload.len() as u64;
+            parse_payload(payload, &config.parse_options)
+        });
+    status.elapsed_ms = started.elapsed().as_millis() as u64;
+
+    match result {
+        Ok(df) => {
+            status.rows = df.height();
+            (status, Some(df))
+        }
+        Err(e) => {
+            status.error = Some(e.to_string());
+            (status, None)
+        }
+    }
+}
+
+fn download(
+    agent: &ureq::Agent,
+    url: &str,
+    config: &RemoteFetchConfig,
+    deadline: Option<Instant>,
+    status: &mut RemoteFileStatus,
+) -> Result<Vec<u8>, InsightoraError> {
+    let remaining = |now: Instant| deadline.map(|d| d.saturating_duration_since(now));
+
+    loop {
+        let mut timeout = config.request_timeout;
+        if let Some(left) = remaining(Instant::now()) {
+            if left.is_zero() {
+                return Err(InsightoraError::NetworkError("total deadline exceeded".to_string()));
+            }
+            timeout = timeout.min(left);
+        }
+
+        let error = match agent.get(url).timeout(timeout).call() {
+            Ok(response) => {
+                status.http_status = Some(response.status());
+                let mut payload = Vec::new();
+                match response.into_reader().read_to_end(&mut payload) {
+                    Ok(_) => return Ok(payload),
+                    // Truncated body: treat like a dropped connection
+                    Err(e) => format!("failed to read body: {}", e),
+                }
+            }
+            Err(ureq::Error::Status(code, _)) => {
+                status.http_status = Some(code);
+                if !is_transient_status(code) {
+                    return Err(InsightoraError::NetworkError(format!("HTTP {}", code)));
+                }
+                format!("HTTP {}", code)
+            }
+            Err(ureq::Error::Transport(e)) => e.to_string(),
+        };
+
+        if status.retries >= config.max_retries {
+            return Err(InsightoraError::NetworkError(format!(
+                "{} (gave up after {} retries)",
+                error, status.retries
+            )));
+        }
+        let delay = backoff_delay(config.backoff_base, status.retries);
+        if let Some(left) = remaining(Instant::now()) {
+            if delay >= left {
+                return Err(InsightoraError::NetworkError(format!(
+                    "{} (total deadline exceeded before retry)",
+                    error
+                )));
+            }
+        }
+        std::thread::sleep(delay);
+        status.retries += 1;
+    }
+}
+
+/// Download one URL with the retry and timeout settings of `config`
+///
+/// Used by readers of non-CSV remote sources (e.g. HTML tables).
+pub(crate) fn fetch_bytes(url: &str, config: &RemoteFetchConfig) -> Result<Vec<u8>, InsightoraError> {
+    check_url_allowed(url)?;
+    let agent = ureq::AgentBuilder::new().build();
+    let mut status = RemoteFileStatus {
+        url: url.to_string(),
+        http_status: None,
+        bytes: 0,
+        rows: 0,
+        retries: 0,
+        elapsed_ms: 0,
+        error: None,
+    };
+    let deadline = config.total_deadline.map(|d| Instant::now() + d);
+    download(&agent, url, config, deadline, &mut status)
+}
+
+fn parse_payload(payload: Vec<u8>, options: &CsvParserConfig) -> Result<DataFrame, InsightoraError> {
+    let df = CsvReader::new(Cursor::new(payload))
+        .has_header(options.has_header)
+        .with_separator(options.delimiter)
+        .with_quote_char(Some(options.quote_char))
+        .infer_schema(options.infer_schema_length)
+        .finish()?;
+    rename_and_project(df, options.rename.as_ref(), options.columns.as_deref())
+}
+
+/// Concatenate frames vertically according to the alignment policy
+pub fn align_and_concat(frames: Vec<DataFrame>, alignment: SchemaAlignment) -> Result<DataFrame, InsightoraError> {
+    let mut frames = frames.into_iter();
+    let Some(first) = frames.next() else {
+        return Ok(DataFrame::default());
+    };
+    let rest: Vec<DataFrame> = frames.collect();
+
+    match alignment {
+        SchemaAlignment::Strict => {
+            let names = first.get_column_names_owned();
+            let mut combined = first;
+            for df in &rest {
+                if df.get_column_names_owned() != names {
+                    return Err(InsightoraError::ValidationError(format!(
+                        "Schema mismatch: expected columns {:?}, got {:?}",
+                        names,
+                        df.get_column_names()
+                    )));
+                }
+                combined.vstack_mut(df)?;
+            }
+            combined.align_chunks();
+            Ok(combined)
+        }
+        SchemaAlignment::Union => {
+            let all: Vec<&DataFrame> = std::iter::once(&first).chain(rest.iter()).collect();
+
+            // Union of columns in first-seen order, with a dtype per column
+            let mut columns: Vec<(String, DataType)> = Vec::new();
+            for df in &all {
+                for series in df.get_columns() {
+                    match columns.iter_mut().find(|(name, _)| name == series.name()) {
+                        Some((_, dtype)) if dtype != series.dtype() => *dtype = DataType::String,
+                        Some(_) => {}
+                        None => columns.push((series.name().to_string(), series.dtype().clone())),
+                    }
+                }
+            }
+
+            let mut combined: Option<DataFrame> = None;
+            for df in all {
+                let aligned = columns
+                    .iter()
+                    .map(|(name, dtype)| match df.column(name) {
+                        Ok(series) => series.cast(dtype),
+                        Err(_) => Ok(Series::full_null(name, df.height(), dtype)),
+                    })
+                    .collect::<PolarsResult<Vec<Series>>>()?;
+                let aligned = DataFrame::new(aligned)?;
+                match combined.as_mut() {
+                    Some(acc) => {
+                        acc.vstack_mut(&aligned)?;
+                    }
+                    None => combined = Some(aligned),
+                }
+            }
+            let mut combined = combined.unwrap_or_default();
+            combined.align_chunks();
+            Ok(combined)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::sync::Arc;
+
+    /// Serve canned responses in order, one per connection
+    fn serve(responses: Vec<(u16, &'static str)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for (code, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                let response = format!(
+                    "HTTP/1.1 {} X\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    code,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        format!("http://{}/data.csv", addr)
+    }
+
+    fn fast_config() -> RemoteFetchConfig {
+        RemoteFetchConfig {
+            backoff_base: Duration::from_millis(1),
+            request_timeout: Duration::from_secs(5),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_retries_transient_failure() {
+        let url = serve(vec![(503, ""), (200, "a,b\n1,2\n3,4\n")]);
+        let result = parse_remote_many(&[url], &fast_config()).unwrap();
+
+        assert_eq!(result.data.height(), 2);
+        let status = &result.files[0];
+        assert_eq!(status.http_status, Some(200));
+        assert_eq!(status.retries, 1);
+        assert_eq!(status.rows, 2);
+        assert!(status.bytes > 0);
+    }
+
+    #[test]
+    fn test_permanent_failure_policies() {
+        let url = serve(vec![(404, ""), (404, "")]);
+        let err = parse_remote_many(std::slice::from_ref(&url), &fast_config()).unwrap_err();
+        assert!(err.to_string().contains("HTTP 404"));
+
+        let config = RemoteFetchConfig { on_error: OnError::Skip, ..fast_config() };
+        let result = parse_remote_many(&[url], &config).unwrap();
+        assert_eq!(result.data.height(), 0);
+        assert_eq!(result.files[0].http_status, Some(404));
+        assert_eq!(result.files[0].retries, 0);
+    }
+
+    #[test]
+    fn test_progress_spans_download_and_align() {
+        let url = serve(vec![(200, "a\n1\n")]);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink_events = Arc::clone(&events);
+        let config = RemoteFetchConfig {
+            progress: ProgressReporter::new(Arc::new(move |percent, stage, _detail| {
+                sink_events.lock().unwrap().push((percent, stage.to_string()));
+            })),
+            ..fast_config()
+        };
+        parse_remote_many(&[url], &config).unwrap();
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.first().unwrap(), &(0.0, "download".to_string()));
+        assert!(events.iter().any(|(p, s)| s == "download" && (*p - 90.0).abs() < 1e-9));
+        assert_eq!(events.last().unwrap(), &(100.0, "align".to_string()));
+    }
+
+    #[test]
+    fn test_backoff_doubles() {
+        let base = Duration::from_millis(100);
+        assert_eq!(backoff_delay(base, 0), Duration::from_millis(100));
+        assert_eq!(backoff_delay(base, 3), Duration::from_millis(800));
+        assert!(is_transient_status(429));
+        assert!(!is_transient_status(404));
+    }
+
+    #[test]
+    fn test_union_alignment() {
+        let a = df! { "id" => [1i64, 2], "name" => ["x", "y"] }.unwrap();
+        let b = df! { "id" => ["3"], "score" => [0.5] }.unwrap();
+
+        let combined = align_and_concat(vec![a.clone(), b.clone()], SchemaAlignment::Union).unwrap();
+        assert_eq!(combined.get_column_names(), &["id", "name", "score"]);
+        assert_eq!(combined.height(), 3);
+        assert_eq!(combined.column("id").unwrap().dtype(), &DataType::String);
+        assert_eq!(combined.column("score").unwrap().null_count(), 2);
+
+        assert!(align_and_concat(vec![a, b], SchemaAlignment::Strict).is_err());
+    }
+}
@@ -0,0 +1,223 @@
+// Row sampling during CSV parsing
+// Bernoulli sampling per batch and reservoir sampling across batches, seeded for reproducible samples
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use polars::prelude::*;
+use crate::error::InsightoraError;
+
+/// Rows kept by a sampled parse
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RowSample {
+    /// Keep each row independently with this probability, in (0, 1]
+    Fraction(f64),
+    /// Keep this many rows, every set of that size equally likely; a file
+    /// with fewer rows is kept whole
+    Count(usize),
+}
+
+impl RowSample {
+    pub fn validate(&self) -> Result<(), InsightoraError> {
+        match *self {
+            RowSample::Fraction(fraction) if !(fraction > 0.0 && fraction <= 1.0) => Err(InsightoraError::ValidationError(
+                format!("sample_fraction must be greater than 0 and at most 1, got {}", fraction)
+            )),
+            RowSample::Count(0) => Err(InsightoraError::ValidationError(
+                "sample_n must be at least 1".to_string()
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// What a sampled parse read and kept
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RowSampleReport {
+    pub sample: RowSample,
+    /// Seed the sample was drawn with; passing it again draws the same rows
+    pub seed: u64,
+    /// Rows the sample was drawn from, after any `row_filter`
+    pub rows_read: usize,
+    pub rows_kept: usize,
+}
+
+impl RowSampleReport {
+    /// Chance each row read had of being kept: the fraction asked for with
+    /// `Fraction`, and rows kept / rows read with `Count`
+    ///
+    /// Dividing a count over the sample by the rate estimates the count over
+    /// the rows read.
+    pub fn rate(&self) -> f64 {
+        match self.sample {
+            RowSample::Fraction(fraction) => fraction,
+            RowSample::Count(_) if self.rows_read == 0 => 1.0,
+            RowSample::Count(_) => self.rows_kept as f64 / self.rows_read as f64,
+        }
+    }
+}
+
+/// Draws a `RowSample` from batches of rows in file order
+///
+/// A fraction is applied to each batch as it arrives. A count keeps a
+/// reservoir (Algorithm R): the first n rows, then row i (0-based) replaces
+/// a random one with probability n / (i + 1), so at most n rows and one
+/// batch are held at a time.
+pub(crate) struct RowSampler {
+    sample: RowSample,
+    seed: u64,
+    state: u64,
+    rows_read: usize,
+    rows_kept: usize,
+    reservoir: Option<DataFrame>,
+    /// Row number in the input of each reservoir row, to return them in file order
+    positions: Vec<usize>,
+}
+
+impl RowSampler {
+    /// A sampler seeded with `seed`, or a random seed reported afterwards
+    pub(crate) fn new(sample: RowSample, seed: Option<u64>) -> Result<Self, InsightoraError> {
+        sample.validate()?;
+        let seed = seed.unwrap_or_else(|| RandomState::new().build_hasher().finish());
+        Ok(Self {
+            sample,
+            seed,
+            state: seed,
+            rows_read: 0,
+            rows_kept: 0,
+            reservoir: None,
+            positions: Vec::new(),
+        })
+    }
+
+    /// SplitMix64, so neighbouring seeds give unrelated streams
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// The rows of `batch` kept with a fraction; with a count they go to the
+    /// reservoir and None is returned
+    pub(crate) fn draw(&mut self, batch: DataFrame) -> Result<Option<DataFrame>, InsightoraError> {
+        let size = match self.sample {
+            RowSample::Fraction(fraction) => {
+                let mask: BooleanChunked = (0..batch.height()).map(|_| self.next_f64() < fraction).collect();
+                self.rows_read += batch.height();
+                let kept = batch.filter(&mask)?;
+                self.rows_kept += kept.height();
+                return Ok(Some(kept));
+            }
+            RowSample::Count(size) => size,
+        };
+
+        // Reservoir slots as rows of the held rows stacked over the batch
+        let held = self.positions.len();
+        let mut slots: Vec<IdxSize> = (0..held as IdxSize).collect();
+        let mut changed = self.reservoir.is_none();
+        for row in 0..batch.height() {
+            let position = self.rows_read + row;
+            let taken = (held + row) as IdxSize;
+            if slots.len() < size {
+                slots.push(taken);
+                self.positions.push(position);
+                changed = true;
+            } else {
+                let slot = (self.next_u64() % (position as u64 + 1)) as usize;
+                if slot < size {
+                    slots[slot] = taken;
+                    self.positions[slot] = position;
+                    changed = true;
+                }
+            }
+        }
+        self.rows_read += batch.height();
+        if changed {
+            let stacked = match self.reservoir.take() {
+                Some(mut reservoir) => {
+                    reservoir.vstack_mut(&batch)?;
+                    reservoir
+                }
+                None => batch,
+            };
+            self.reservoir = Some(stacked.take(&IdxCa::from_vec("", slots))?);
+        }
+        Ok(None)
+    }
+
+    /// The reservoir in file order once every batch is drawn; None with a fraction
+    pub(crate) fn finish(&mut self) -> Result<Option<DataFrame>, InsightoraError> {
+        let Some(reservoir) = self.reservoir.take() else {
+            return Ok(None);
+        };
+        let mut order: Vec<IdxSize> = (0..self.positions.len() as IdxSize).collect();
+        order.sort_unstable_by_key(|&slot| self.positions[slot as usize]);
+        self.rows_kept = reservoir.height();
+        Ok(Some(reservoir.take(&IdxCa::from_vec("", order))?))
+    }
+
+    pub(crate) fn report(&self) -> RowSampleReport {
+        RowSampleReport {
+            sample: self.sample,
+            seed: self.seed,
+            rows_read: self.rows_read,
+            rows_kept: self.rows_kept,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batches(rows: i64, size: i64) -> Vec<DataFrame> {
+        (0..rows)
+            .step_by(size as usize)
+            .map(|start| df!("id" => (start..(start + size).min(rows)).collect::<Vec<i64>>()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_reservoir_and_fraction_samples() {
+        let draw = |sample: RowSample, seed: u64| {
+            let mut sampler = RowSampler::new(sample, Some(seed)).unwrap();
+            let mut kept: Vec<i64> = Vec::new();
+            for batch in batches(1000, 64) {
+                if let Some(rows) = sampler.draw(batch).unwrap() {
+                    kept.extend(rows.column("id").unwrap().i64().unwrap().into_no_null_iter());
+                }
+            }
+            if let Some(rows) = sampler.finish().unwrap() {
+                kept.extend(rows.column("id").unwrap().i64().unwrap().into_no_null_iter());
+            }
+            (kept, sampler.report())
+        };
+
+        let (ids, report) = draw(RowSample::Count(50), 7);
+        assert_eq!((ids.len(), report.rows_read, report.rows_kept), (50, 1000, 50));
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]), "reservoir rows come back in file order");
+        assert!((report.rate() - 0.05).abs() < 1e-12);
+        assert_eq!(draw(RowSample::Count(50), 7).0, ids);
+        assert_ne!(draw(RowSample::Count(50), 8).0, ids);
+        // Rows late in the file are as likely to be kept as early ones
+        let late = (0..200).map(|seed| draw(RowSample::Count(50), seed).0.iter().filter(|&&id| id >= 500).count()).sum::<usize>();
+        assert!((4000..6000).contains(&late), "{} of 10000 kept rows in the second half", late);
+
+        let (whole, report) = draw(RowSample::Count(5000), 1);
+        assert_eq!((whole.len(), report.rate()), (1000, 1.0));
+
+        let (ids, report) = draw(RowSample::Fraction(0.1), 3);
+        assert!((60..140).contains(&ids.len()) && report.rows_kept == ids.len());
+        assert_eq!(report.rate(), 0.1);
+
+        assert!(RowSampler::new(RowSample::Fraction(0.0), None).is_err());
+        assert!(RowSampler::new(RowSample::Fraction(f64::NAN), None).is_err());
+        assert!(RowSampler::new(RowSample::Count(0), None).is_err());
+    }
+}
@@ -0,0 +1,280 @@
+// CSV schema inference reports
+// Classifies values as Polars' CSV type inference does, counting nulls and value types per column in one streaming pass
+
+use std::io::BufRead;
+use polars::prelude::*;
+use crate::error::InsightoraError;
+use crate::io::csv_repair::split_fields;
+
+/// Values kept per column that made an otherwise numeric column text
+pub const OFFENDING_SAMPLES: usize = 5;
+
+/// Kind of a non-empty CSV value, as Polars infers column types
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    /// An optional minus sign and digits
+    Integer,
+    /// Decimals, exponents, `inf` and `NaN`
+    Float,
+    /// `true` or `false` in any case
+    Boolean,
+    Text,
+}
+
+impl ValueType {
+    const ALL: [ValueType; 4] = [ValueType::Integer, ValueType::Float, ValueType::Boolean, ValueType::Text];
+
+    pub fn of(value: &str) -> Self {
+        if is_integer(value) {
+            ValueType::Integer
+        } else if is_float(value) {
+            ValueType::Float
+        } else if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+            ValueType::Boolean
+        } else {
+            ValueType::Text
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ValueType::Integer => "integer",
+            ValueType::Float => "float",
+            ValueType::Boolean => "boolean",
+            ValueType::Text => "text",
+        }
+    }
+
+    fn is_numeric(&self) -> bool {
+        matches!(self, ValueType::Integer | ValueType::Float)
+    }
+}
+
+fn is_integer(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// `[-+]?((\d*\.\d+)([eE][-+]?\d+)?|inf|NaN|(\d+)[eE][-+]?\d+|\d+\.)`, the pattern Polars infers floats by
+fn is_float(value: &str) -> bool {
+    let digits = |text: &str| !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit());
+    let unsigned = value.strip_prefix(['-', '+']).unwrap_or(value);
+    if unsigned == "inf" || unsigned == "NaN" {
+        return true;
+    }
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent.strip_prefix(['-', '+']).unwrap_or(exponent))),
+        None => (unsigned, None),
+    };
+    if exponent.is_some_and(|exponent| !digits(exponent)) {
+        return false;
+    }
+    match mantissa.split_once('.') {
+        Some((whole, fraction)) if !fraction.is_empty() => (whole.is_empty() || digits(whole)) && digits(fraction),
+        Some((whole, _)) => exponent.is_none() && digits(whole),
+        None => exponent.is_some() && digits(mantissa),
+    }
+}
+
+/// How a column's type was inferred and how far to trust it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnTypeReport {
+    /// Name after `rename`
+    pub name: String,
+    pub dtype: DataType,
+    /// Empty and missing fields among the rows scanned
+    pub null_count: usize,
+    /// Non-null values of each `ValueType` seen, in `ValueType` order, omitting types not seen
+    pub type_counts: Vec<(ValueType, usize)>,
+    /// Share of non-null values of the column's main kind (numbers,
+    /// booleans or text), reduced for a partial scan; see `SchemaReport`
+    pub confidence: f64,
+    /// (1-based data row, value) of the first values that made a mostly
+    /// numeric column text, at most `OFFENDING_SAMPLES`
+    pub offending_values: Vec<(usize, String)>,
+}
+
+impl ColumnTypeReport {
+    pub fn distinct_types(&self) -> usize {
+        self.type_counts.len()
+    }
+}
+
+/// Types, nulls and confidence for every column of a CSV file
+///
+/// A column's confidence is the share of its non-null values of the most
+/// common kind, numbers (integers and floats together), booleans or text.
+/// When the scan stopped before the end of the file it is further reduced
+/// by 3/n for n values scanned, the rule-of-three bound on the share of
+/// values of another kind the unread rows may hold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaReport {
+    pub columns: Vec<ColumnTypeReport>,
+    /// Data rows read, the header excluded
+    pub rows_scanned: usize,
+    /// Whether every row was read, so the types hold for the whole file
+    pub complete: bool,
+}
+
+impl SchemaReport {
+    pub fn schema(&self) -> Schema {
+        self.columns.iter().map(|column| Field::new(&column.name, column.dtype.clone())).collect()
+    }
+}
+
+/// Counts for one column during a scan
+#[derive(Debug, Clone, Default)]
+struct ColumnCounts {
+    nulls: usize,
+    types: [usize; 4],
+    /// The first non-numeric values, kept in case the column turns out mostly numeric
+    non_numeric: Vec<(usize, String)>,
+}
+
+/// Classify the values of CSV text record by record
+///
+/// A record continues while it has an odd number of quote characters, so
+/// quoted values may span lines. `skip_lines` lines are skipped first, then
+/// the header when there is one.
+///
+/// # Arguments
+/// * `reader` - CSV text
+/// * `names` - Column names, in file order
+/// * `max_rows` - Stop after this many data rows; None reads to the end
+pub(crate) fn scan_types<R: BufRead>(
+    mut reader: R,
+    names: &[String],
+    delimiter: u8,
+    quote_char: u8,
+    skip_lines: usize,
+    has_header: bool,
+    max_rows: Option<usize>,
+) -> Result<SchemaReport, InsightoraError> {
+    let mut columns = vec![ColumnCounts::default(); names.len()];
+    let (mut line, mut rows, mut header) = (0, 0, has_header);
+    let (mut buffer, mut record) = (Vec::new(), Vec::new());
+    let complete = loop {
+        if max_rows.is_some_and(|max| rows >= max) {
+            // Complete only if nothing but blank lines follows
+            break loop {
+                buffer.clear();
+                if reader.read_until(b'\n', &mut buffer)? == 0 {
+                    break true;
+                }
+                if !buffer.iter().all(u8::is_ascii_whitespace) {
+                    break false;
+                }
+            };
+        }
+        buffer.clear();
+        let read = reader.read_until(b'\n', &mut buffer)?;
+        if read > 0 {
+            line += 1;
+            if line <= skip_lines {
+                continue;
+            }
+            record.extend_from_slice(&buffer);
+            if record.iter().filter(|&&b| b == quote_char).count() % 2 == 1 {
+                continue;
+            }
+        }
+        if record.is_empty() {
+            break true;
+        }
+        let text = String::from_utf8_lossy(&record);
+        let text = text.trim_end_matches(['\r', '\n']);
+        if text.trim().is_empty() || std::mem::take(&mut header) {
+            record.clear();
+            continue;
+        }
+        rows += 1;
+        let mut fields = split_fields(text, delimiter, quote_char).into_iter();
+        for counts in columns.iter_mut() {
+            let value = fields.next().unwrap_or_default();
+            if value.is_empty() {
+                counts.nulls += 1;
+                continue;
+            }
+            let kind = ValueType::of(&value);
+            counts.types[kind as usize] += 1;
+            if !kind.is_numeric() && counts.non_numeric.len() < OFFENDING_SAMPLES {
+                counts.non_numeric.push((rows, value));
+            }
+        }
+        record.clear();
+    };
+
+    let columns = names
+        .iter()
+        .zip(columns)
+        .map(|(name, counts)| column_report(name, counts, complete))
+        .collect();
+    Ok(SchemaReport { columns, rows_scanned: rows, complete })
+}
+
+fn column_report(name: &str, counts: ColumnCounts, complete: bool) -> ColumnTypeReport {
+    let [integers, floats, booleans, texts] = counts.types;
+    let numbers = integers + floats;
+    let values = numbers + booleans + texts;
+    let dtype = if texts > 0 || (booleans > 0 && numbers > 0) || values == 0 {
+        DataType::String
+    } else if booleans > 0 {
+        DataType::Boolean
+    } else if floats > 0 {
+        DataType::Float64
+    } else {
+        DataType::Int64
+    };
+    let main = numbers.max(booleans).max(texts);
+    let purity = if values == 0 { 1.0 } else { main as f64 / values as f64 };
+    let confidence = if complete { purity } else { purity * (1.0 - (3.0 / values.max(1) as f64).min(1.0)) };
+    let demoted = dtype == DataType::String && numbers == main && numbers > 0;
+    ColumnTypeReport {
+        name: name.to_string(),
+        dtype,
+        null_count: counts.nulls,
+        type_counts: ValueType::ALL.into_iter().zip(counts.types).filter(|&(_, count)| count > 0).collect(),
+        confidence,
+        offending_values: if demoted { counts.non_numeric } else { Vec::new() },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_scan_types_reports_demotions_nulls_and_confidence() {
+        let mut text = String::from("# export\nid,amount,flag,note\n");
+        for row in 1..=1000 {
+            text.push_str(&format!("{},{}.5,{},\"line {}\nwrapped\"\n", row, row, row % 2 == 0, row));
+        }
+        text.push_str("1001,N/A,,\n1002,1e3,true,x\n");
+        let names: Vec<String> = ["id", "amount", "flag", "note"].iter().map(|s| s.to_string()).collect();
+
+        let full = scan_types(Cursor::new(&text), &names, b',', b'"', 1, true, None).unwrap();
+        assert!(full.complete);
+        assert_eq!(full.rows_scanned, 1002);
+        let dtypes: Vec<DataType> = full.columns.iter().map(|c| c.dtype.clone()).collect();
+        assert_eq!(dtypes, [DataType::Int64, DataType::String, DataType::Boolean, DataType::String]);
+        let amount = &full.columns[1];
+        assert_eq!(amount.type_counts, [(ValueType::Float, 1001), (ValueType::Text, 1)]);
+        assert_eq!(amount.distinct_types(), 2);
+        assert_eq!(amount.offending_values, [(1001, "N/A".to_string())]);
+        assert!((amount.confidence - 1001.0 / 1002.0).abs() < 1e-12);
+        assert_eq!((full.columns[2].null_count, full.columns[3].null_count), (1, 1));
+        assert!(full.columns[3].offending_values.is_empty() && full.columns[0].confidence == 1.0);
+
+        // The first 1000 rows miss the N/A; a partial scan is less sure of the clean types
+        let sampled = scan_types(Cursor::new(&text), &names, b',', b'"', 1, true, Some(1000)).unwrap();
+        assert!(!sampled.complete);
+        assert_eq!(sampled.columns[1].dtype, DataType::Float64);
+        assert!((sampled.columns[1].confidence - 0.997).abs() < 1e-12);
+        let whole = scan_types(Cursor::new(&text), &names, b',', b'"', 1, true, Some(1002)).unwrap();
+        assert!(whole.complete);
+
+        assert!(is_float("-.5") && is_float("3.") && is_float("1E-7") && is_float("NaN") && is_float("+inf"));
+        assert!(!is_float("1") && !is_float(".") && !is_float("3.e5") && !is_float("e5") && !is_float("1e"));
+    }
+}
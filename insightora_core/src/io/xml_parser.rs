@@ -0,0 +1,793 @@
+// XML and HTML table parsing
+// Streams repeating XML records into rows and extracts HTML <table> elements
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use polars::prelude::*;
+use quick_xml::escape::unescape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use crate::error::InsightoraError;
+use crate::io::remote::{fetch_bytes, RemoteFetchConfig};
+use crate::utils::sandbox::check_path_allowed;
+
+/// Largest colspan honoured; larger values are clamped
+const MAX_COLSPAN: usize = 1000;
+
+/// Counts of irregular markup handled while parsing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MarkupReport {
+    /// Records (XML) or rows (HTML) kept after repairing irregular markup
+    pub recovered: usize,
+    /// Records discarded because they could not be repaired
+    pub dropped: usize,
+}
+
+// ============================================================================
+// Column Building
+// ============================================================================
+
+/// Accumulates sparse text rows into columns in first-seen order
+#[derive(Default)]
+struct ColumnBuilder {
+    names: Vec<String>,
+    index: HashMap<String, usize>,
+    columns: Vec<Vec<Option<String>>>,
+    rows: usize,
+}
+
+impl ColumnBuilder {
+    /// Add a row; the first value given for a column wins, blank values are null
+    fn push_row(&mut self, fields: Vec<(String, String)>) {
+        for (name, value) in fields {
+            let idx = match self.index.get(&name) {
+                Some(&idx) => idx,
+                None => {
+                    let idx = self.names.len();
+                    self.names.push(name.clone());
+                    self.index.insert(name, idx);
+                    self.columns.push(vec![None; self.rows]);
+                    idx
+                }
+            };
+            let column = &mut self.columns[idx];
+            if column.len() == self.rows && !value.trim().is_empty() {
+                column.push(Some(value));
+            }
+        }
+        self.rows += 1;
+        for column in &mut self.columns {
+            if column.len() < self.rows {
+                column.push(None);
+            }
+        }
+    }
+
+    fn finish(self) -> Result<DataFrame, InsightoraError> {
+        let series = self
+            .names
+            .iter()
+            .zip(self.columns)
+            .map(|(name, values)| infer_series(name, values))
+            .collect();
+        Ok(DataFrame::new(series)?)
+    }
+}
+
+/// Build a column from text cells, narrowing to Int64 or Float64 when every value parses
+fn infer_series(name: &str, values: Vec<Option<String>>) -> Series {
+    let present = || values.iter().flatten().map(|v| v.trim());
+    if values.iter().any(Option::is_some) {
+        if present().all(|v| v.parse::<i64>().is_ok()) {
+            let parsed: Vec<Option<i64>> = values.iter().map(|v| v.as_deref().and_then(|v| v.trim().parse().ok())).collect();
+            return Series::new(name, parsed);
+        }
+        if present().all(|v| v.parse::<f64>().is_ok()) {
+            let parsed: Vec<Option<f64>> = values.iter().map(|v| v.as_deref().and_then(|v| v.trim().parse().ok())).collect();
+            return Series::new(name, parsed);
+        }
+    }
+    Series::new(name, values)
+}
+
+// ============================================================================
+// XML Records
+// ============================================================================
+
+/// Element path selecting record elements
+///
+/// Accepts a bare tag (`item`), a relative path (`items/item`), a descendant
+/// path (`//items/item`) or an absolute path (`/filing/items/item`). `*`
+/// matches any single element; predicates and axes are not supported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RecordPath {
+    segments: Vec<String>,
+    anchored: bool,
+}
+
+impl RecordPath {
+    fn parse(spec: &str) -> Result<Self, InsightoraError> {
+        let (anchored, rest) = if let Some(rest) = spec.strip_prefix("//") {
+            (false, rest)
+        } else if let Some(rest) = spec.strip_prefix('/') {
+            (true, rest)
+        } else {
+            (false, spec)
+        };
+        let segments: Vec<String> = rest.split('/').map(str::to_string).collect();
+        let unsupported = |s: &String| s.is_empty() || s.contains(['[', ']', '(', ')', '@', ':']);
+        if segments.iter().any(unsupported) {
+            return Err(InsightoraError::ValidationError(format!(
+                "Unsupported record path '{}'; expected a tag or a simple path like 'items/item'",
+                spec
+            )));
+        }
+        Ok(Self { segments, anchored })
+    }
+
+    fn matches(&self, stack: &[String]) -> bool {
+        if stack.len() < self.segments.len() || (self.anchored && stack.len() != self.segments.len()) {
+            return false;
+        }
+        let tail = &stack[stack.len() - self.segments.len()..];
+        tail.iter().zip(&self.segments).all(|(name, segment)| segment == "*" || name == segment)
+    }
+}
+
+/// The record element currently being flattened
+struct RecordState {
+    /// Stack depth of the record element itself
+    depth: usize,
+    fields: Vec<(String, String)>,
+    /// Text buffers for the record element and each open descendant
+    texts: Vec<String>,
+    irregular: bool,
+}
+
+/// Parse repeating XML records into a DataFrame
+///
+/// The file is read as a stream of events, so only the record being flattened
+/// is held in memory. Each matching element becomes one row: its attributes
+/// and its children's text become columns named by their path relative to the
+/// record (`name`, `address/city`, `address@type`). A repeated child keeps its
+/// first value. Columns whose values all parse as numbers are typed.
+///
+/// Mismatched or stray end tags and bad entities are repaired and counted as
+/// recovered; a record cut short by a syntax error or end of file is dropped.
+///
+/// # Arguments
+/// * `file_path` - Path to the XML file
+/// * `record` - Tag or simple path of the repeating record element
+/// * `field_mapping` - Optional source field -> output column names; when
+///   given, only mapped fields are kept
+pub fn parse_xml(
+    file_path: &str,
+    record: &str,
+    field_mapping: Option<&HashMap<String, String>>,
+) -> Result<(DataFrame, MarkupReport), InsightoraError> {
+    let record_path = RecordPath::parse(record)?;
+    let path = check_path_allowed(file_path)?;
+    let mut reader = Reader::from_reader(BufReader::new(File::open(path)?));
+    reader.trim_text(true);
+    // End tags are matched leniently below
+    reader.check_end_names(false);
+
+    let mut stack: Vec<String> = Vec::new();
+    let mut current: Option<RecordState> = None;
+    let mut builder = ColumnBuilder::default();
+    let mut report = MarkupReport::default();
+    let mut buf = Vec::new();
+    let mut last_error_position = None;
+
+    let mut close_top = |stack: &mut Vec<String>, current: &mut Option<RecordState>, report: &mut MarkupReport| {
+        if let Some(record) = current.as_mut() {
+            let depth = stack.len();
+            if depth > record.depth {
+                let text = record.texts.pop().unwrap_or_default();
+                if !text.is_empty() {
+                    record.fields.push((stack[record.depth..].join("/"), text));
+                }
+            } else if depth == record.depth {
+                let text = record.texts.pop().unwrap_or_default();
+                if !text.is_empty() {
+                    record.fields.push((stack[depth - 1].clone(), text));
+                }
+                let finished = current.take().expect("record is open");
+                if finished.irregular {
+                    report.recovered += 1;
+                }
+                builder.push_row(apply_mapping(finished.fields, field_mapping));
+            }
+        }
+        stack.pop();
+    };
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(element)) => {
+                open_element(&element, &record_path, &mut stack, &mut current);
+            }
+            Ok(Event::Empty(element)) => {
+                open_element(&element, &record_path, &mut stack, &mut current);
+                close_top(&mut stack, &mut current, &mut report);
+            }
+            Ok(Event::End(element)) => {
+                let name = String::from_utf8_lossy(element.local_name().as_ref()).into_owned();
+                match stack.iter().rposition(|open| *open == name) {
+                    Some(position) => {
+                        if position + 1 != stack.len() {
+                            mark_irregular(&mut current);
+                        }
+                        while stack.len() > position {
+                            close_top(&mut stack, &mut current, &mut report);
+                        }
+                    }
+                    // Stray end tag
+                    None => mark_irregular(&mut current),
+                }
+            }
+            Ok(Event::Text(text)) => {
+                if let Some(record) = current.as_mut() {
+                    let value = match text.unescape() {
+                        Ok(value) => value.into_owned(),
+                        Err(_) => {
+                            record.irregular = true;
+                            String::from_utf8_lossy(&text).into_owned()
+                        }
+                    };
+                    append_text(record, &value);
+                }
+            }
+            Ok(Event::CData(data)) => {
+                if let Some(record) = current.as_mut() {
+                    let value = String::from_utf8_lossy(&data.into_inner()).into_owned();
+                    append_text(record, &value);
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(_) => {
+                if let Some(record) = current.take() {
+                    report.dropped += 1;
+                    stack.truncate(record.depth - 1);
+                }
+                // No progress since the last error: the rest of the file is unreadable
+                let position = reader.buffer_position();
+                if last_error_position == Some(position) {
+                    break;
+                }
+                last_error_position = Some(position);
+            }
+        }
+        buf.clear();
+    }
+
+    // Record still open at end of file
+    if current.is_some() {
+        report.dropped += 1;
+    }
+    Ok((builder.finish()?, report))
+}
+
+fn open_element(
+    element: &BytesStart,
+    record_path: &RecordPath,
+    stack: &mut Vec<String>,
+    current: &mut Option<RecordState>,
+) {
+    stack.push(String::from_utf8_lossy(element.local_name().as_ref()).into_owned());
+
+    match current.as_mut() {
+        Some(record) => {
+            record.texts.push(String::new());
+            let prefix = stack[record.depth..].join("/");
+            let mut irregular = false;
+            for (name, value) in element_attributes(element, &mut irregular) {
+                record.fields.push((format!("{}@{}", prefix, name), value));
+            }
+            record.irregular |= irregular;
+        }
+        None if record_path.matches(stack) => {
+            let mut irregular = false;
+            let fields = element_attributes(element, &mut irregular);
+            *current = Some(RecordState {
+                depth: stack.len(),
+                fields,
+                texts: vec![String::new()],
+                irregular,
+            });
+        }
+        None => {}
+    }
+}
+
+fn element_attributes(element: &BytesStart, irregular: &mut bool) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    for attribute in element.attributes().with_checks(false) {
+        let Ok(attribute) = attribute else {
+            *irregular = true;
+            continue;
+        };
+        let name = String::from_utf8_lossy(attribute.key.local_name().as_ref()).into_owned();
+        let raw = String::from_utf8_lossy(&attribute.value);
+        let value = match unescape(&raw) {
+            Ok(value) => value.into_owned(),
+            Err(_) => {
+                *irregular = true;
+                raw.to_string()
+            }
+        };
+        fields.push((name, value));
+    }
+    fields
+}
+
+fn append_text(record: &mut RecordState, value: &str) {
+    if let Some(text) = record.texts.last_mut() {
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(value);
+    }
+}
+
+fn mark_irregular(current: &mut Option<RecordState>) {
+    if let Some(record) = current.as_mut() {
+        record.irregular = true;
+    }
+}
+
+fn apply_mapping(
+    fields: Vec<(String, String)>,
+    mapping: Option<&HashMap<String, String>>,
+) -> Vec<(String, String)> {
+    match mapping {
+        Some(mapping) => fields
+            .into_iter()
+            .filter_map(|(name, value)| mapping.get(&name).map(|target| (target.clone(), value)))
+            .collect(),
+        None => fields,
+    }
+}
+
+// ============================================================================
+// HTML Tables
+// ============================================================================
+
+struct HtmlCell {
+    text: String,
+    header: bool,
+    colspan: usize,
+}
+
+#[derive(Default)]
+struct HtmlRow {
+    cells: Vec<HtmlCell>,
+    in_head: bool,
+}
+
+#[derive(Default)]
+struct HtmlTable {
+    rows: Vec<HtmlRow>,
+    /// Rows implied or padded while reading this table
+    recovered: usize,
+}
+
+/// Parser state for one open `<table>` (tables may nest)
+struct OpenTable {
+    index: usize,
+    in_head: bool,
+    row: Option<HtmlRow>,
+    cell: Option<HtmlCell>,
+}
+
+impl OpenTable {
+    fn finish_cell(&mut self) {
+        if let Some(cell) = self.cell.take() {
+            if let Some(row) = self.row.as_mut() {
+                row.cells.push(cell);
+            }
+        }
+    }
+
+    fn finish_row(&mut self, tables: &mut [HtmlTable]) {
+        self.finish_cell();
+        if let Some(row) = self.row.take() {
+            if !row.cells.is_empty() {
+                tables[self.index].rows.push(row);
+            }
+        }
+    }
+}
+
+/// Extract `<table>` elements from an HTML file or http(s) URL
+///
+/// Header rows are those inside `<thead>` or made only of `<th>` cells;
+/// stacked header rows are joined with " / ". Cells spanning several columns
+/// repeat their value under each column. Missing end tags are implied, and
+/// rows with fewer cells than the table are padded with nulls (counted as
+/// recovered). Columns whose values all parse as numbers are typed.
+///
+/// # Arguments
+/// * `source` - Path to an HTML file, or an http(s) URL
+/// * `table_index` - Return only this table (0-based, document order)
+///
+/// # Returns
+/// * One DataFrame and repair report per table
+pub fn parse_html_tables(
+    source: &str,
+    table_index: Option<usize>,
+) -> Result<Vec<(DataFrame, MarkupReport)>, InsightoraError> {
+    let bytes = if source.starts_with("http://") || source.starts_with("https://") {
+        fetch_bytes(source, &RemoteFetchConfig::default())?
+    } else {
+        std::fs::read(check_path_allowed(source)?)?
+    };
+    let html = String::from_utf8_lossy(&bytes);
+
+    let tables = extract_tables(&html);
+    let selected: Vec<HtmlTable> = match table_index {
+        Some(index) => {
+            let found = tables.len();
+            let table = tables.into_iter().nth(index).ok_or_else(|| {
+                InsightoraError::ValidationError(format!(
+                    "table_index {} is out of range; found {} tables",
+                    index, found
+                ))
+            })?;
+            vec![table]
+        }
+        None => tables,
+    };
+
+    selected.into_iter().map(table_to_dataframe).collect()
+}
+
+/// Scan HTML for tables, returning them in document (opening tag) order
+fn extract_tables(html: &str) -> Vec<HtmlTable> {
+    // ASCII lowercasing keeps byte offsets aligned with `html`
+    let lower = html.to_ascii_lowercase();
+    let mut tables: Vec<HtmlTable> = Vec::new();
+    let mut open: Vec<OpenTable> = Vec::new();
+    let mut pos = 0;
+
+    while pos < html.len() {
+        let Some(offset) = html[pos..].find('<') else {
+            append_cell_text(&mut open, &html[pos..]);
+            break;
+        };
+        append_cell_text(&mut open, &html[pos..pos + offset]);
+        pos += offset;
+
+        if lower[pos..].starts_with("<!--") {
+            pos = lower[pos..].find("-->").map_or(html.len(), |end| pos + end + 3);
+            continue;
+        }
+        // Unterminated tag: nothing more can be read
+        let Some(end) = find_tag_end(&html[pos..]) else {
+            break;
+        };
+        let tag = &lower[pos + 1..pos + end];
+        pos += end + 1;
+
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect();
+
+        match (name.as_str(), closing) {
+            ("script" | "style", false) => {
+                let terminator = format!("</{}", name);
+                pos = lower[pos..].find(&terminator).map_or(html.len(), |end| pos + end);
+            }
+            ("table", false) => {
+                open.push(OpenTable {
+                    index: tables.len(),
+                    in_head: false,
+                    row: None,
+                    cell: None,
+                });
+                tables.push(HtmlTable::default());
+            }
+            ("table", true) => {
+                if let Some(mut table) = open.pop() {
+                    table.finish_row(&mut tables);
+                }
+            }
+            ("thead", closing) => {
+                if let Some(table) = open.last_mut() {
+                    table.finish_row(&mut tables);
+                    table.in_head = !closing;
+                }
+            }
+            ("tbody" | "tfoot", false) => {
+                if let Some(table) = open.last_mut() {
+                    table.finish_row(&mut tables);
+                    table.in_head = false;
+                }
+            }
+            ("tr", false) => {
+                if let Some(table) = open.last_mut() {
+                    table.finish_row(&mut tables);
+                    table.row = Some(HtmlRow { cells: Vec::new(), in_head: table.in_head });
+                }
+            }
+            ("tr", true) => {
+                if let Some(table) = open.last_mut() {
+                    table.finish_row(&mut tables);
+                }
+            }
+            ("td" | "th", false) => {
+                if let Some(table) = open.last_mut() {
+                    table.finish_cell();
+                    if table.row.is_none() {
+                        // Cell outside any <tr>: open an implied row
+                        tables[table.index].recovered += 1;
+                        table.row = Some(HtmlRow { cells: Vec::new(), in_head: table.in_head });
+                    }
+                    table.cell = Some(HtmlCell {
+                        text: String::new(),
+                        header: name == "th",
+                        colspan: attribute_value(tag, "colspan")
+                            .and_then(|v| v.parse::<usize>().ok())
+                            .unwrap_or(1)
+                            .clamp(1, MAX_COLSPAN),
+                    });
+                }
+            }
+            ("td" | "th", true) => {
+                if let Some(table) = open.last_mut() {
+                    table.finish_cell();
+                }
+            }
+            ("br", _) => append_cell_text(&mut open, " "),
+            _ => {}
+        }
+    }
+
+    // Tables left open at end of input
+    while let Some(mut table) = open.pop() {
+        table.finish_row(&mut tables);
+    }
+    tables
+}
+
+/// Offset of the `>` closing the tag at the start of `s`, skipping quoted values
+fn find_tag_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in s.char_indices().skip(1) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Value of attribute `name` in a lowercased tag body
+fn attribute_value(tag: &str, name: &str) -> Option<String> {
+    let start = tag.find(name)? + name.len();
+    let rest = tag[start..].trim_start().strip_prefix('=')?.trim_start();
+    let value = match rest.chars().next()? {
+        quote @ ('"' | '\'') => rest[1..].split(quote).next()?,
+        _ => rest.split(|c: char| c.is_whitespace() || c == '/').next()?,
+    };
+    Some(value.trim().to_string())
+}
+
+fn append_cell_text(open: &mut [OpenTable], text: &str) {
+    if let Some(cell) = open.last_mut().and_then(|table| table.cell.as_mut()) {
+        cell.text.push_str(text);
+    }
+}
+
+/// Decode the common named and numeric character references
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            }?;
+            Some((c, end + 1))
+        });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn table_to_dataframe(table: HtmlTable) -> Result<(DataFrame, MarkupReport), InsightoraError> {
+    let mut report = MarkupReport {
+        recovered: table.recovered,
+        dropped: 0,
+    };
+    let rows: Vec<(bool, Vec<String>)> = table
+        .rows
+        .into_iter()
+        .map(|row| {
+            let header = row.in_head || row.cells.iter().all(|cell| cell.header);
+            let values = row
+                .cells
+                .into_iter()
+                .flat_map(|cell| {
+                    let text = decode_entities(&cell.text).split_whitespace().collect::<Vec<_>>().join(" ");
+                    std::iter::repeat_n(text, cell.colspan)
+                })
+                .collect();
+            (header, values)
+        })
+        .collect();
+
+    let width = rows.iter().map(|(_, values)| values.len()).max().unwrap_or(0);
+    let header_rows = rows.iter().take_while(|(header, _)| *header).count();
+
+    let mut names = Vec::with_capacity(width);
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for column in 0..width {
+        let mut parts: Vec<&str> = Vec::new();
+        for (_, values) in &rows[..header_rows] {
+            if let Some(part) = values.get(column).filter(|p| !p.is_empty()) {
+                if parts.last() != Some(&part.as_str()) {
+                    parts.push(part);
+                }
+            }
+        }
+        let base = if parts.is_empty() {
+            format!("column_{}", column)
+        } else {
+            parts.join(" / ")
+        };
+        let count = seen.entry(base.clone()).or_insert(0);
+        names.push(if *count == 0 { base } else { format!("{}_{}", base, count) });
+        *count += 1;
+    }
+
+    let mut columns: Vec<Vec<Option<String>>> = vec![Vec::new(); width];
+    for (_, values) in rows.into_iter().skip(header_rows) {
+        if values.len() < width {
+            report.recovered += 1;
+        }
+        let mut values = values.into_iter();
+        for column in columns.iter_mut() {
+            column.push(values.next().filter(|v| !v.is_empty()));
+        }
+    }
+
+    let series = names
+        .iter()
+        .zip(columns)
+        .map(|(name, values)| infer_series(name, values))
+        .collect();
+    Ok((DataFrame::new(series)?, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn file_with(content: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_parse_xml_flattens_records() {
+        let file = file_with(
+            r#"<filing><items>
+                <item id="1" kind="a"><name>Widget &amp; Co</name><price>9.5</price>
+                    <address type="hq"><city>Oslo</city></address></item>
+                <item id="2"><name>Gadget</name><price>12</price></item>
+            </items><item id="ignored"/></filing>"#,
+        );
+        let (df, report) = parse_xml(file.path().to_str().unwrap(), "items/item", None).unwrap();
+
+        assert_eq!(df.height(), 2);
+        assert_eq!(report, MarkupReport::default());
+        assert_eq!(
+            df.get_column_names(),
+            vec!["id", "kind", "name", "price", "address@type", "address/city"]
+        );
+        assert_eq!(df.column("id").unwrap().dtype(), &DataType::Int64);
+        assert_eq!(df.column("price").unwrap().f64().unwrap().get(1), Some(12.0));
+        assert_eq!(df.column("name").unwrap().str().unwrap().get(0), Some("Widget & Co"));
+        assert_eq!(df.column("address/city").unwrap().str().unwrap().get(1), None);
+    }
+
+    #[test]
+    fn test_parse_xml_field_mapping() {
+        let file = file_with("<rows><row code='X1'><v>3</v><extra>skip</extra></row></rows>");
+        let mapping = HashMap::from([
+            ("code".to_string(), "sku".to_string()),
+            ("v".to_string(), "value".to_string()),
+        ]);
+        let (df, _) = parse_xml(file.path().to_str().unwrap(), "row", Some(&mapping)).unwrap();
+        assert_eq!(df.get_column_names(), vec!["sku", "value"]);
+    }
+
+    #[test]
+    fn test_parse_xml_lenient_recovery() {
+        // Record 1 closes an inner element implicitly, record 3 is cut off
+        let file = file_with("<r><rec><a>1<b>x</a></rec><rec><a>2</a></rec><rec><a>3</a>");
+        let (df, report) = parse_xml(file.path().to_str().unwrap(), "rec", None).unwrap();
+        assert_eq!(df.height(), 2);
+        assert_eq!(report.recovered, 1);
+        assert_eq!(report.dropped, 1);
+    }
+
+    #[test]
+    fn test_record_path_rejects_predicates() {
+        assert!(RecordPath::parse("item[@id='1']").is_err());
+        assert!(RecordPath::parse("/a/b").unwrap().matches(&["a".to_string(), "b".to_string()]));
+        assert!(!RecordPath::parse("/b").unwrap().matches(&["a".to_string(), "b".to_string()]));
+    }
+
+    #[test]
+    fn test_parse_html_tables_headers_and_colspan() {
+        let file = file_with(
+            r#"<html><body><script>var t = "<table>";</script>
+            <table>
+              <thead><tr><th colspan="2">Region</th><th>Sales</th></tr>
+                     <tr><th>Name</th><th>Code</th><th></th></tr></thead>
+              <tbody><tr><td>North &amp; East</td><td>NE</td><td>1,000</td></tr>
+                     <tr><td colspan=2>Total</td><td>5</td></tr>
+                     <tr><td>Short</td></tbody>
+            </table>
+            <table><tr><td>1<td>2</tr><tr><td>3<td>4</table>
+            </body></html>"#,
+        );
+        let path = file.path().to_str().unwrap();
+        let tables = parse_html_tables(path, None).unwrap();
+
+        assert_eq!(tables.len(), 2);
+        let (first, report) = &tables[0];
+        assert_eq!(first.get_column_names(), vec!["Region / Name", "Region / Code", "Sales"]);
+        assert_eq!(first.height(), 3);
+        assert_eq!(first.column("Region / Name").unwrap().str().unwrap().get(0), Some("North & East"));
+        assert_eq!(first.column("Region / Code").unwrap().str().unwrap().get(1), Some("Total"));
+        // Short row padded with nulls
+        assert_eq!(report.recovered, 1);
+
+        // No header row: generated names, integer columns
+        let second = parse_html_tables(path, Some(1)).unwrap();
+        let (second, report) = &second[0];
+        assert_eq!(second.get_column_names(), vec!["column_0", "column_1"]);
+        assert_eq!(second.column("column_1").unwrap().i64().unwrap().get(1), Some(4));
+        assert_eq!(report.recovered, 0);
+
+        assert!(parse_html_tables(path, Some(5)).is_err());
+    }
+}
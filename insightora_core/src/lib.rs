@@ -1,9 +1,12 @@
 // INSIGHTORA Core - Rust Performance Optimization Module
 // High-performance data processing operations for Python integration
 
+#[cfg(feature = "python")]
 use pyo3::prelude::*;
 
 // Module declarations
+pub mod error;
+pub mod config;
 pub mod io;
 pub mod dataframe;
 pub mod stats;
@@ -11,32 +14,179 @@ pub mod streaming;
 pub mod query;
 pub mod utils;
 
+// Pure-Rust facade for embedding without Python
+pub mod api;
+
 // Python bindings module
+// pyo3 0.20 expands `#[new]` into impls nested inside functions, which
+// newer compilers report as non-local definitions
+#[cfg(feature = "python")]
+#[allow(non_local_definitions)]
 pub mod python_bindings;
 
 // Re-export commonly used types
-pub use python_bindings::{InsightoraError, RustConfig};
+pub use error::InsightoraError;
+pub use config::RustConfig;
 
 /// PyO3 module initialization
 /// This function is called when the module is imported in Python
+#[cfg(feature = "python")]
 #[pymodule]
-fn insightora_core(_py: Python, m: &PyModule) -> PyResult<()> {
+fn insightora_core(py: Python, m: &PyModule) -> PyResult<()> {
+    // Refuses sub-interpreters; a repeat import in the same interpreter reuses the process state
+    python_bindings::initialize_module(py)?;
+    
     // Module metadata
     m.add("__version__", env!("CARGO_PKG_VERSION"))?;
     m.add("__author__", "INSIGHTORA Team")?;
+    m.add("RESULT_SCHEMA_VERSION", python_bindings::RESULT_SCHEMA_VERSION)?;
+    m.add("MemoryLimitError", py.get_type::<python_bindings::MemoryLimitError>())?;
+    m.add("OperationCancelled", py.get_type::<python_bindings::OperationCancelled>())?;
+    m.add_class::<python_bindings::PyCancellationToken>()?;
     
     // Configuration functions
     m.add_function(wrap_pyfunction!(python_bindings::configure, m)?)?;
     m.add_function(wrap_pyfunction!(python_bindings::get_config, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::get_degradations, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::result_schema, m)?)?;
     
     // CSV parsing functions
     m.add_function(wrap_pyfunction!(python_bindings::parse_csv, m)?)?;
     m.add_function(wrap_pyfunction!(python_bindings::parse_csv_with_options, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::parse_csv_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::parse_csv_string, m)?)?;
     m.add_function(wrap_pyfunction!(python_bindings::infer_csv_schema, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::parse_csv_with_schema, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::parse_csv_with_inference, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::build_file_index, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::parse_csv_filtered, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::parse_csv_glob, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::count_csv_rows, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::write_csv, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::write_csv_partitioned, m)?)?;
     
     // Streaming CSV parsing functions
     m.add_function(wrap_pyfunction!(python_bindings::parse_csv_streaming, m)?)?;
     m.add_function(wrap_pyfunction!(python_bindings::should_use_streaming, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::parse_csv_batches, m)?)?;
+    m.add_class::<python_bindings::PyCsvBatchIterator>()?;
+    m.add_class::<python_bindings::PyAsyncCsvBatchIterator>()?;
+    
+    // NDJSON input
+    m.add_function(wrap_pyfunction!(python_bindings::parse_ndjson, m)?)?;
+    
+    // XML and HTML tables
+    m.add_function(wrap_pyfunction!(python_bindings::parse_xml, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::parse_html_tables, m)?)?;
+    
+    // Excel workbooks
+    m.add_function(wrap_pyfunction!(python_bindings::parse_excel, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::list_excel_sheets, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::parse_excel_all_sheets, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::parse_excel_streaming, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::parse_excel_batches, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::should_use_excel_streaming, m)?)?;
+    m.add_class::<python_bindings::PyExcelBatchIterator>()?;
+    
+    // Remote files
+    m.add_function(wrap_pyfunction!(python_bindings::parse_remote_many, m)?)?;
+    
+    // ZIP archives
+    m.add_function(wrap_pyfunction!(python_bindings::list_archive, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::parse_archive, m)?)?;
+    
+    // Automatic parsing
+    m.add_function(wrap_pyfunction!(python_bindings::parse_auto, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::sniff_csv, m)?)?;
+    
+    // pandas interop
+    m.add_function(wrap_pyfunction!(python_bindings::to_pandas, m)?)?;
+    
+    // Chunked datasets
+    m.add_class::<python_bindings::PyDatasetBuilder>()?;
+    m.add_class::<python_bindings::PyDataset>()?;
+    m.add_class::<python_bindings::PyDatasetBatches>()?;
+    m.add_function(wrap_pyfunction!(python_bindings::from_records, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::from_columns, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::to_records, m)?)?;
+    
+    // JSON export
+    m.add_function(wrap_pyfunction!(python_bindings::to_json, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::to_ndjson, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::write_json, m)?)?;
+    
+    // Parquet export
+    m.add_function(wrap_pyfunction!(python_bindings::write_parquet, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::write_partitioned, m)?)?;
+    m.add("SchemaError", py.get_type::<python_bindings::SchemaError>())?;
+    
+    // Arrow interchange
+    m.add_function(wrap_pyfunction!(python_bindings::read_ipc, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::write_ipc, m)?)?;
+    m.add_class::<python_bindings::PyArrowTable>()?;
+    m.add_class::<python_bindings::PyRustDataFrame>()?;
+    
+    // DataFrame operations
+    m.add_function(wrap_pyfunction!(python_bindings::sort_data, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::sort_rows, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::set_category_order, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::format_values, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::qcut, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::apply_bins, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::drop_duplicates, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::is_duplicated, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::drop_nulls, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::fill_nulls, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::cast_columns, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::string_ops, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::datetime_features, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::truncate_datetime, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::rename_columns, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::select_columns, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::rename, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::drop_columns, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::reorder_columns, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::add_prefix, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::add_suffix, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::normalize_column_names, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::map_columns, m)?)?;
+    m.add_class::<python_bindings::PyColumnDictionary>()?;
+    m.add_function(wrap_pyfunction!(python_bindings::add_columns, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::update_where, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::filter_data, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::join_rows, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::join_data, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::concat, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::suggest_join_keys, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::value_counts, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::group_by, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::register_aggregation, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::get_contributors, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::release_drill_down, m)?)?;
+    
+    // Prepared pipelines
+    m.add_class::<python_bindings::PyPipeline>()?;
+    m.add_class::<python_bindings::PyPreparedPipeline>()?;
+    
+    // Statistics
+    m.add_class::<python_bindings::PyRunningStats>()?;
+    m.add_function(wrap_pyfunction!(python_bindings::compare_groups, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::t_test, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::anova, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::proportion_test, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::ks_test, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::describe, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::suggest_outlier_params, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::correlation, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::covariance_matrix, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::downsample, m)?)?;
+    
+    // Frame comparison
+    m.add_function(wrap_pyfunction!(python_bindings::frames_equal, m)?)?;
+    m.add_function(wrap_pyfunction!(python_bindings::frame_diff, m)?)?;
+    
+    // SQL queries
+    m.add_class::<python_bindings::PyQuerySession>()?;
     
     Ok(())
 }
@@ -2,41 +2,17 @@
 // Provides Python bindings for all Rust performance modules
 
 use pyo3::prelude::*;
-use pyo3::exceptions::{PyRuntimeError, PyMemoryError, PyTypeError, PyValueError};
-use std::sync::{Arc, RwLock};
-use once_cell::sync::Lazy;
-
-/// Global configuration for the Rust module
-static GLOBAL_CONFIG: Lazy<Arc<RwLock<RustConfig>>> = Lazy::new(|| {
-    Arc::new(RwLock::new(RustConfig::default()))
-});
-
-/// Track if thread pool has been initialized
-static THREAD_POOL_INITIALIZED: Lazy<Arc<RwLock<bool>>> = Lazy::new(|| {
-    Arc::new(RwLock::new(false))
-});
-
-/// Configuration structure for Rust module
-#[derive(Debug, Clone)]
-pub struct RustConfig {
-    pub thread_count: usize,
-    pub chunk_size: usize,
-    pub memory_limit_mb: usize,
-    pub enable_simd: bool,
-    pub cache_size: usize,
-}
-
-impl Default for RustConfig {
-    fn default() -> Self {
-        Self {
-            thread_count: num_cpus::get(),
-            chunk_size: 100_000,
-            memory_limit_mb: 4096,
-            enable_simd: true,
-            cache_size: 1000,
-        }
-    }
-}
+use pyo3::exceptions::{
+    PyConnectionError, PyIndexError, PyKeyError, PyRuntimeError, PyMemoryError, PyOSError, PyPermissionError, PyTypeError,
+    PyValueError,
+};
+pub use crate::config::{RustConfig, get_current_config, check_memory_limit};
+pub use crate::error::InsightoraError;
+use crate::config::{GLOBAL_CONFIG, THREAD_POOL_INITIALIZED};
+use crate::utils::sandbox::{PathPolicy, UrlPolicy};
+use crate::utils::simd::KernelPath;
+use crate::utils::capabilities::{self, Degradation};
+use crate::utils::warnings::{AggregatedWarning, WarningLevel};
 
 /// Configure the Rust module with custom settings
 /// 
@@ -44,22 +20,78 @@ impl Default for RustConfig {
 /// * `thread_count` - Number of threads to use (0 = auto-detect)
 /// * `chunk_size` - Size of data chunks for parallel processing
 /// * `memory_limit_mb` - Maximum memory usage in megabytes
-/// * `enable_simd` - Enable SIMD optimizations
+/// * `enable_simd` - Use vectorized (AVX2) kernels when the CPU supports them;
+///   `get_config()["simd_kernels"]` reports the active path
 /// * `cache_size` - Size of internal caches
+/// * `allowed_paths` - Directories/files that may be read or written (empty = any)
+/// * `denied_paths` - Directories/files that may never be accessed
+/// * `allowed_url_schemes` - URL schemes permitted for remote access
+/// * `allowed_url_hosts` - Hosts permitted for remote access ("*.example.com"
+///   matches subdomains; empty = any)
+/// * `temp_dir` - Directory that join and dataset spill files and UTF-8
+///   copies of other encodings are created under (default: system temp
+///   directory). Must be inside `allowed_paths`; pass "" to restore the default
+/// * `memory_watchdog` - Sample process RSS and available memory during long
+///   operations and abort them at the next chunk boundary with
+///   `MemoryLimitError` (including the RSS trajectory) before the OS kills
+///   the process (default: False)
+/// * `watchdog_threshold_pct` - Share of the memory limit (the container's
+///   cgroup limit when set, else physical memory) that triggers an abort (default: 90)
+/// * `watchdog_interval_ms` - Time between samples (default: 100)
+/// * `watchdog_grace_ms` - Operations shorter than this are never aborted (default: 1000)
+/// * `io_retries` - Times a failed or short chunk read in the streaming and
+///   chunked parsers is retried before the parse fails with OSError
+///   naming the byte range and errno (default: 3)
+/// * `io_retry_backoff_ms` - Wait before the first retry, doubled for each
+///   further one (default: 100)
+/// * `require_fast_paths` - Raise instead of silently falling back to scalar
+///   kernels, in-memory input or a single thread when a fast path is
+///   unavailable, for benchmarking environments (default: False). Fallbacks
+///   are otherwise listed by `get_degradations()`
+/// * `warning_sample_size` - Examples kept per aggregated warning (default: 5).
+///   Repeated per-row warnings (values coerced to null, repaired rows) are
+///   counted by category, column and message and reported once per operation
+/// * `warning_log_level` - Level aggregated warnings are logged at on the
+///   `insightora_core` logger when an operation finishes: "debug", "info",
+///   "warning" (default), "error" or "off" (still listed in summaries and reports)
+/// 
+/// Paths are canonicalized before matching, so `..` segments and symlinks
+/// cannot escape the allowed directories. Violations raise PermissionError.
 /// 
 /// # Example
 /// ```python
 /// import insightora_core
 /// insightora_core.configure(thread_count=8, memory_limit_mb=8192)
+/// insightora_core.configure(allowed_paths=["/srv/tenant-42"], denied_paths=["/srv/tenant-42/.secrets"])
+/// insightora_core.configure(allowed_paths=["/srv/tenant-42"], temp_dir="/srv/tenant-42/tmp")
+/// insightora_core.configure(memory_watchdog=True, watchdog_threshold_pct=85)
+/// insightora_core.configure(io_retries=5, io_retry_backoff_ms=500)
+/// insightora_core.configure(require_fast_paths=True)
+/// insightora_core.configure(warning_sample_size=3, warning_log_level="info")
 /// ```
 #[pyfunction]
-#[pyo3(signature = (thread_count=None, chunk_size=None, memory_limit_mb=None, enable_simd=None, cache_size=None))]
+#[pyo3(signature = (thread_count=None, chunk_size=None, memory_limit_mb=None, enable_simd=None, cache_size=None, allowed_paths=None, denied_paths=None, allowed_url_schemes=None, allowed_url_hosts=None, temp_dir=None, memory_watchdog=None, watchdog_threshold_pct=None, watchdog_interval_ms=None, watchdog_grace_ms=None, io_retries=None, io_retry_backoff_ms=None, require_fast_paths=None, warning_sample_size=None, warning_log_level=None))]
+#[allow(clippy::too_many_arguments)]
 pub fn configure(
     thread_count: Option<usize>,
     chunk_size: Option<usize>,
     memory_limit_mb: Option<usize>,
     enable_simd: Option<bool>,
     cache_size: Option<usize>,
+    allowed_paths: Option<Vec<String>>,
+    denied_paths: Option<Vec<String>>,
+    allowed_url_schemes: Option<Vec<String>>,
+    allowed_url_hosts: Option<Vec<String>>,
+    temp_dir: Option<String>,
+    memory_watchdog: Option<bool>,
+    watchdog_threshold_pct: Option<f64>,
+    watchdog_interval_ms: Option<u64>,
+    watchdog_grace_ms: Option<u64>,
+    io_retries: Option<usize>,
+    io_retry_backoff_ms: Option<u64>,
+    require_fast_paths: Option<bool>,
+    warning_sample_size: Option<usize>,
+    warning_log_level: Option<&str>,
 ) -> PyResult<()> {
     let mut config = GLOBAL_CONFIG.write()
         .map_err(|e| PyRuntimeError::new_err(format!("Failed to acquire config lock: {}", e)))?;
@@ -111,6 +143,66 @@ pub fn configure(
         config.cache_size = cs;
     }
     
+    if allowed_paths.is_some() || denied_paths.is_some() {
+        let allowed = allowed_paths.unwrap_or_default();
+        let denied = denied_paths.unwrap_or_default();
+        config.path_policy = PathPolicy::new(&allowed, &denied)?;
+    }
+    
+    if allowed_url_schemes.is_some() || allowed_url_hosts.is_some() {
+        let schemes = allowed_url_schemes
+            .unwrap_or_else(|| config.url_policy.allowed_schemes().to_vec());
+        let hosts = allowed_url_hosts
+            .unwrap_or_else(|| config.url_policy.allowed_hosts().to_vec());
+        config.url_policy = UrlPolicy::new(schemes, hosts);
+    }
+    
+    if let Some(dir) = temp_dir {
+        config.temp_dir = if dir.is_empty() { None } else { Some(dir.into()) };
+    }
+    
+    if let Some(enabled) = memory_watchdog {
+        config.memory_watchdog = enabled;
+    }
+    
+    if let Some(pct) = watchdog_threshold_pct {
+        if pct.is_nan() || pct <= 0.0 || pct > 100.0 {
+            return Err(PyValueError::new_err("watchdog_threshold_pct must be in (0, 100]"));
+        }
+        config.watchdog_threshold_pct = pct;
+    }
+    
+    if let Some(ms) = watchdog_interval_ms {
+        if ms == 0 {
+            return Err(PyValueError::new_err("watchdog_interval_ms must be greater than 0"));
+        }
+        config.watchdog_interval_ms = ms;
+    }
+    
+    if let Some(ms) = watchdog_grace_ms {
+        config.watchdog_grace_ms = ms;
+    }
+    
+    if let Some(retries) = io_retries {
+        config.io_retries = retries;
+    }
+    
+    if let Some(ms) = io_retry_backoff_ms {
+        config.io_retry_backoff_ms = ms;
+    }
+    
+    if let Some(required) = require_fast_paths {
+        config.require_fast_paths = required;
+    }
+    
+    if let Some(size) = warning_sample_size {
+        config.warning_sample_size = size;
+    }
+    
+    if let Some(level) = warning_log_level {
+        config.warning_log_level = WarningLevel::from_name(level)?;
+    }
+    
     Ok(())
 }
 
@@ -132,50 +224,136 @@ pub fn get_config() -> PyResult<PyObject> {
     
     Python::with_gil(|py| {
         let dict = pyo3::types::PyDict::new(py);
+        dict.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
         dict.set_item("thread_count", config.thread_count)?;
         dict.set_item("chunk_size", config.chunk_size)?;
         dict.set_item("memory_limit_mb", config.memory_limit_mb)?;
         dict.set_item("enable_simd", config.enable_simd)?;
+        let kernels = if config.enable_simd { KernelPath::best_available() } else { KernelPath::Scalar };
+        dict.set_item("simd_kernels", kernels.name())?;
         dict.set_item("cache_size", config.cache_size)?;
+        dict.set_item("path_policy_active", !config.path_policy.is_unrestricted())?;
+        dict.set_item("allowed_url_schemes", config.url_policy.allowed_schemes())?;
+        dict.set_item("allowed_url_hosts", config.url_policy.allowed_hosts())?;
+        let temp_dir = config.temp_dir.clone().unwrap_or_else(std::env::temp_dir);
+        dict.set_item("temp_dir", temp_dir.to_string_lossy().to_string())?;
+        dict.set_item("memory_watchdog", config.memory_watchdog)?;
+        dict.set_item("watchdog_threshold_pct", config.watchdog_threshold_pct)?;
+        dict.set_item("watchdog_interval_ms", config.watchdog_interval_ms)?;
+        dict.set_item("watchdog_grace_ms", config.watchdog_grace_ms)?;
+        dict.set_item("io_retries", config.io_retries)?;
+        dict.set_item("io_retry_backoff_ms", config.io_retry_backoff_ms)?;
+        dict.set_item("require_fast_paths", config.require_fast_paths)?;
+        dict.set_item("warning_sample_size", config.warning_sample_size)?;
+        dict.set_item("warning_log_level", config.warning_log_level.name())?;
         Ok(dict.into())
     })
 }
 
-/// Get the current configuration (internal use)
-pub fn get_current_config() -> RustConfig {
-    GLOBAL_CONFIG.read()
-        .expect("Failed to read config")
-        .clone()
+/// Helper function to convert degradations to a list of dictionaries
+fn degradations_to_py(py: Python, degradations: &[Degradation]) -> PyResult<PyObject> {
+    let list = pyo3::types::PyList::empty(py);
+    for degradation in degradations {
+        let dict = PyDict::new(py);
+        dict.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+        dict.set_item("operation", &degradation.operation)?;
+        dict.set_item("path", degradation.path.name())?;
+        dict.set_item("fallback", &degradation.fallback)?;
+        dict.set_item("reason", &degradation.reason)?;
+        list.append(dict)?;
+    }
+    Ok(list.into())
 }
 
-/// Error types for Rust operations
-#[derive(Debug, thiserror::Error)]
-pub enum InsightoraError {
-    #[error("IO error: {0}")]
-    IoError(#[from] std::io::Error),
-    
-    #[error("Parse error: {0}")]
-    ParseError(String),
-    
-    #[error("Memory limit exceeded: requested {requested}MB, limit {limit}MB")]
-    MemoryLimitExceeded { requested: usize, limit: usize },
-    
-    #[error("Invalid data type: expected {expected}, got {actual}")]
-    InvalidDataType { expected: String, actual: String },
-    
-    #[error("Polars error: {0}")]
-    PolarsError(#[from] polars::error::PolarsError),
-    
-    #[error("Thread pool error: {0}")]
-    ThreadPoolError(String),
-    
-    #[error("Configuration error: {0}")]
-    ConfigError(String),
-    
-    #[error("Validation error: {0}")]
-    ValidationError(String),
+/// Helper function to log aggregated warnings on the `insightora_core` logger
+/// 
+/// One record per warning at the configured `warning_log_level`, so a
+/// million coerced values cost one log call.
+fn log_warnings(py: Python, warnings: &[AggregatedWarning]) -> PyResult<()> {
+    let level = match get_current_config().warning_log_level.python_level() {
+        Some(level) if !warnings.is_empty() => level,
+        _ => return Ok(()),
+    };
+    let logger = py.import("logging")?.call_method1("getLogger", ("insightora_core",))?;
+    for warning in warnings {
+        logger.call_method1("log", (level, warning.message()))?;
+    }
+    Ok(())
+}
+
+/// Helper function to convert aggregated warnings to a list of dictionaries
+/// 
+/// Examples are data values, so they are left out unless `include_examples`.
+fn warnings_to_py<'py>(py: Python<'py>, warnings: &[AggregatedWarning], include_examples: bool) -> PyResult<&'py PyList> {
+    let list = PyList::empty(py);
+    for warning in warnings {
+        let record = PyDict::new(py);
+        record.set_item("category", warning.category)?;
+        record.set_item("column", &warning.column)?;
+        record.set_item("count", warning.count)?;
+        if include_examples {
+            record.set_item("message", warning.message())?;
+            record.set_item("examples", &warning.examples)?;
+        } else {
+            record.set_item("message", warning.summary())?;
+        }
+        list.append(record)?;
+    }
+    Ok(list)
+}
+
+/// List recent fallbacks from fast paths
+/// 
+/// Operations record each time they run without a fast path: scalar
+/// instead of SIMD kernels ("simd"), in-memory instead of memory-mapped
+/// input ("mmap") or a single thread ("parallel"). The last 256 are kept.
+/// 
+/// # Arguments
+/// * `clear` - Empty the list after reading it (default: False)
+/// 
+/// # Returns
+/// * List of dicts with `operation`, `path`, `fallback` and `reason`, oldest first
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// insightora_core.configure(enable_simd=False)
+/// insightora_core.describe(data)
+/// for d in insightora_core.get_degradations(clear=True):
+///     print(d["operation"], d["path"], "->", d["fallback"], d["reason"])
+/// ```
+#[pyfunction]
+#[pyo3(signature = (clear=false))]
+pub fn get_degradations(py: Python, clear: bool) -> PyResult<PyObject> {
+    let degradations = capabilities::degradations();
+    if clear {
+        capabilities::clear_degradations();
+    }
+    degradations_to_py(py, &degradations)
 }
 
+pyo3::create_exception!(
+    insightora_core,
+    MemoryLimitError,
+    PyMemoryError,
+    "A watched operation was aborted by the memory watchdog; the message includes the RSS trajectory"
+);
+
+pyo3::create_exception!(
+    insightora_core,
+    OperationCancelled,
+    PyRuntimeError,
+    "A parse was stopped through its CancellationToken or by a progress callback"
+);
+
+pyo3::create_exception!(
+    insightora_core,
+    SchemaError,
+    PyValueError,
+    "Data does not match a declared schema; the message lists every violation"
+);
+
 /// Convert Rust errors to Python exceptions
 impl From<InsightoraError> for PyErr {
     fn from(err: InsightoraError) -> PyErr {
@@ -186,6 +364,9 @@ impl From<InsightoraError> for PyErr {
                     requested, limit
                 ))
             }
+            err @ InsightoraError::MemoryWatchdog { .. } => {
+                MemoryLimitError::new_err(err.to_string())
+            }
             InsightoraError::InvalidDataType { expected, actual } => {
                 PyTypeError::new_err(format!(
                     "Invalid data type: expected {}, got {}",
@@ -195,6 +376,21 @@ impl From<InsightoraError> for PyErr {
             InsightoraError::ValidationError(msg) => {
                 PyValueError::new_err(msg)
             }
+            InsightoraError::SchemaError(problems) => {
+                SchemaError::new_err(format!("Schema mismatch: {}", problems.join("; ")))
+            }
+            err @ InsightoraError::NotNullViolation { .. } => {
+                SchemaError::new_err(err.to_string())
+            }
+            InsightoraError::PermissionDenied(msg) => {
+                PyPermissionError::new_err(msg)
+            }
+            InsightoraError::NetworkError(msg) => {
+                PyConnectionError::new_err(msg)
+            }
+            InsightoraError::Cancelled => {
+                OperationCancelled::new_err("Operation cancelled")
+            }
             InsightoraError::ConfigError(msg) => {
                 PyValueError::new_err(format!("Configuration error: {}", msg))
             }
@@ -207,42 +403,733 @@ impl From<InsightoraError> for PyErr {
             InsightoraError::IoError(e) => {
                 PyRuntimeError::new_err(format!("IO error: {}", e))
             }
+            // OSError(errno, message) so callers can branch on `exc.errno`
+            err @ InsightoraError::ChunkRead { errno: Some(errno), .. } => {
+                PyOSError::new_err((errno, err.to_string()))
+            }
+            err @ InsightoraError::ChunkRead { .. } => {
+                PyOSError::new_err(err.to_string())
+            }
             InsightoraError::PolarsError(e) => {
                 PyRuntimeError::new_err(format!("Polars error: {}", e))
             }
+            err @ InsightoraError::FastPathUnavailable { .. } => {
+                PyRuntimeError::new_err(err.to_string())
+            }
         }
     }
 }
 
-/// Helper function to validate memory usage against configured limits
-pub fn check_memory_limit(estimated_mb: usize) -> Result<(), InsightoraError> {
-    let config = get_current_config();
-    if estimated_mb > config.memory_limit_mb {
-        return Err(InsightoraError::MemoryLimitExceeded {
-            requested: estimated_mb,
-            limit: config.memory_limit_mb,
-        });
+// ============================================================================
+// Result Schemas
+// ============================================================================
+
+/// Version of the result dictionary shapes declared in `RESULT_SCHEMAS`
+/// 
+/// Bump when a guaranteed key is removed, renamed or changes type. Every
+/// fixed-key result dictionary carries it as `schema_version`.
+pub const RESULT_SCHEMA_VERSION: u32 = 1;
+
+/// One key of a result dictionary
+/// 
+/// Types use Python names: "int", "float", "str", "bool", "list", "dict",
+/// "list[T]" for homogeneous lists, and a " | None" suffix for nullable values.
+pub struct ResultField {
+    pub key: &'static str,
+    pub type_name: &'static str,
+    /// The key may be absent (it depends on the call's options)
+    pub optional: bool,
+    /// Keys of each record for "list[dict]" fields (empty when records vary)
+    pub items: &'static [ResultField],
+}
+
+const fn required(key: &'static str, type_name: &'static str) -> ResultField {
+    ResultField { key, type_name, optional: false, items: &[] }
+}
+
+const fn optional(key: &'static str, type_name: &'static str) -> ResultField {
+    ResultField { key, type_name, optional: true, items: &[] }
+}
+
+const fn records(key: &'static str, optional: bool, items: &'static [ResultField]) -> ResultField {
+    ResultField { key, type_name: "list[dict]", optional, items }
+}
+
+/// Declared shape of one binding's result
+pub struct ResultSchema {
+    /// Binding name; methods are written "Class.method" and alternative
+    /// result shapes "function[mode]"
+    pub function: &'static str,
+    /// "dict", or "list[dict]" for bindings returning one dictionary per item
+    pub returns: &'static str,
+    /// Field groups, concatenated
+    pub fields: &'static [&'static [ResultField]],
+}
+
+impl ResultSchema {
+    pub fn all_fields(&self) -> impl Iterator<Item = &ResultField> {
+        self.fields.iter().flat_map(|group| group.iter())
+    }
+}
+
+const VERSION_FIELDS: &[ResultField] = &[required("schema_version", "int")];
+
+/// The standard table result built by `dataframe_to_pydict`
+const TABLE_FIELDS: &[ResultField] = &[
+    required("schema_version", "int"),
+    required("columns", "list[str]"),
+    // Polars dtype per column, e.g. "Int64", "String", "Datetime(Microseconds, None)"
+    required("dtypes", "list[str]"),
+    required("num_rows", "int"),
+    required("num_columns", "int"),
+    required("data", "list[list]"),
+    // {column: [category, ...]} for ordered categorical columns
+    optional("categories", "dict"),
+];
+
+const PRECISION_FIELDS: &[ResultField] = &[required("rows_affected_by_precision", "int")];
+
+const MARKUP_FIELDS: &[ResultField] = &[
+    required("recovered_records", "int"),
+    required("dropped_records", "int"),
+];
+
+/// Present when a CSV repair mode was requested
+const REPAIR_FIELDS: &[ResultField] = &[optional("repair", "dict")];
+
+/// Present when NOT NULL columns were declared
+const NULLABILITY_FIELDS: &[ResultField] = &[optional("nullability", "dict")];
+
+/// Present when dates were parsed: values that didn't parse, per column
+const DATE_FIELDS: &[ResultField] = &[records("parse_warnings", true, &[
+    required("category", "str"),
+    required("column", "str"),
+    required("count", "int"),
+    required("message", "str"),
+])];
+
+/// Present with `on_bad_lines` "skip" or "warn"
+const BAD_LINE_FIELDS: &[ResultField] = &[optional("bad_line_count", "int"), optional("bad_lines", "list[int]")];
+
+/// A sniffed CSV dialect, as from `sniff_csv`
+const DIALECT_FIELDS: &[ResultField] = &[
+    required("delimiter", "str"),
+    required("quote_char", "str"),
+    required("has_header", "bool"),
+    required("column_count", "int"),
+    required("consistency", "float"),
+    required("records", "int"),
+    required("encoding", "str | None"),
+    required("byte_order_mark", "str | None"),
+    required("invalid_utf8_at", "int | None"),
+];
+
+/// Present when the delimiter or header was sniffed
+const SNIFFED_FIELDS: &[ResultField] = &[optional("dialect", "dict")];
+
+/// Present when rows were sampled
+const SAMPLE_FIELDS: &[ResultField] = &[
+    optional("sampled", "bool"),
+    optional("sampling_rate", "float"),
+    optional("sample_rows_read", "int"),
+    optional("sample_seed", "int"),
+];
+
+/// Present with `include_summary=True`
+const SUMMARY_FIELDS: &[ResultField] = &[optional("summary", "dict")];
+
+const PARQUET_WRITE_FIELDS: &[ResultField] = &[
+    required("schema_version", "int"),
+    required("files", "list[str]"),
+    required("rows", "int"),
+    required("filled_missing", "list[str]"),
+    required("dropped_extra", "list[str]"),
+    records("cast", false, &[required("column", "str"), required("from", "str"), required("to", "str")]),
+];
+
+const CSV_PARTS_FIELDS: &[ResultField] = &[
+    required("schema_version", "int"),
+    records("parts", false, &[required("file", "str"), required("rows", "int"), required("bytes", "int")]),
+    required("rows", "int"),
+    required("manifest", "str"),
+    required("combined", "str | None"),
+];
+
+const JOIN_KEY_FIELDS: &[ResultField] = &[
+    required("left", "list[str]"),
+    required("right", "list[str]"),
+    required("score", "float"),
+    required("name_similarity", "float"),
+    required("value_overlap", "float"),
+    required("estimated_match_rate", "float"),
+    required("left_uniqueness", "float"),
+    required("right_uniqueness", "float"),
+    required("dtype_match", "str"),
+];
+
+const ID_DECISION_FIELDS: &[ResultField] = &[
+    required("column", "str"),
+    required("is_identifier", "bool"),
+    required("score", "int"),
+    required("overridden", "bool"),
+    required("reasons", "list[str]"),
+];
+
+/// Shared by t_test, anova, proportion_test and ks_test
+const TEST_FIELDS: &[ResultField] = &[
+    required("test", "str"),
+    required("statistic", "float | None"),
+    required("degrees_of_freedom", "list[float]"),
+    required("p_value", "float | None"),
+    records("groups", false, &[
+        required("group", "str"),
+        required("count", "int"),
+        required("null_count", "int"),
+        required("mean", "float | None"),
+    ]),
+    required("null_groups", "int"),
+];
+
+/// Central registry of result shapes, one entry per fixed-key result
+pub static RESULT_SCHEMAS: &[ResultSchema] = &[
+    ResultSchema {
+        function: "get_config",
+        returns: "dict",
+        fields: &[VERSION_FIELDS, &[
+            required("thread_count", "int"),
+            required("chunk_size", "int"),
+            required("memory_limit_mb", "int"),
+            required("enable_simd", "bool"),
+            required("simd_kernels", "str"),
+            required("cache_size", "int"),
+            required("path_policy_active", "bool"),
+            required("allowed_url_schemes", "list[str]"),
+            required("allowed_url_hosts", "list[str]"),
+            required("temp_dir", "str"),
+            required("memory_watchdog", "bool"),
+            required("watchdog_threshold_pct", "float"),
+            required("watchdog_interval_ms", "int"),
+            required("watchdog_grace_ms", "int"),
+            required("io_retries", "int"),
+            required("io_retry_backoff_ms", "int"),
+            required("require_fast_paths", "bool"),
+            required("warning_sample_size", "int"),
+            required("warning_log_level", "str"),
+        ]],
+    },
+    ResultSchema {
+        function: "get_degradations",
+        returns: "list[dict]",
+        fields: &[VERSION_FIELDS, &[
+            required("operation", "str"),
+            required("path", "str"),
+            required("fallback", "str"),
+            required("reason", "str"),
+        ]],
+    },
+    ResultSchema { function: "parse_csv", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "parse_csv_bytes", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "parse_csv_string", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema {
+        function: "parse_csv_with_options",
+        returns: "dict",
+        fields: &[TABLE_FIELDS, REPAIR_FIELDS, NULLABILITY_FIELDS, DATE_FIELDS, BAD_LINE_FIELDS, SNIFFED_FIELDS, SAMPLE_FIELDS, SUMMARY_FIELDS],
+    },
+    ResultSchema {
+        function: "build_file_index",
+        returns: "dict",
+        fields: &[VERSION_FIELDS, &[
+            required("index_path", "str | None"),
+            required("columns", "list[str]"),
+            required("blocks", "int"),
+            required("rows", "int"),
+        ]],
+    },
+    ResultSchema { function: "parse_csv_filtered", returns: "dict", fields: &[TABLE_FIELDS, &[required("index", "dict")]] },
+    ResultSchema {
+        function: "parse_csv_glob",
+        returns: "dict",
+        fields: &[TABLE_FIELDS, &[records("files", false, &[required("path", "str"), required("rows", "int")])]],
+    },
+    ResultSchema { function: "parse_csv_with_schema", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "parse_csv_with_inference", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema {
+        function: "infer_csv_schema",
+        returns: "dict",
+        fields: &[VERSION_FIELDS, &[
+            required("columns", "list[str]"),
+            required("dtypes", "list[str]"),
+            required("num_columns", "int"),
+            required("rows_scanned", "int"),
+            required("complete", "bool"),
+            required("null_counts", "list[int]"),
+            required("distinct_types", "list[int]"),
+            required("observed_types", "list[list[str]]"),
+            required("confidence", "list[float]"),
+            records("demoted", false, &[required("column", "str"), required("values", "list[str]"), required("rows", "list[int]")]),
+            optional("identifiers", "list[str]"),
+            records("id_detection", true, ID_DECISION_FIELDS),
+        ]],
+    },
+    ResultSchema {
+        function: "parse_csv_streaming",
+        returns: "dict",
+        fields: &[TABLE_FIELDS, REPAIR_FIELDS, NULLABILITY_FIELDS, SUMMARY_FIELDS],
+    },
+    ResultSchema {
+        function: "should_use_streaming",
+        returns: "dict",
+        fields: &[VERSION_FIELDS, &[
+            required("recommended", "bool"),
+            required("estimated_memory_mb", "int"),
+            required("memory_limit_mb", "int"),
+        ]],
+    },
+    ResultSchema {
+        function: "parse_remote_many",
+        returns: "dict",
+        fields: &[TABLE_FIELDS, &[records("files", false, &[
+            required("url", "str"),
+            required("http_status", "int | None"),
+            required("bytes", "int"),
+            required("rows", "int"),
+            required("retries", "int"),
+            required("elapsed_ms", "int"),
+            required("error", "str | None"),
+        ])]],
+    },
+    ResultSchema {
+        function: "list_archive",
+        returns: "dict",
+        fields: &[VERSION_FIELDS, &[records("members", false, &[
+            required("name", "str"),
+            required("size", "int"),
+            required("compressed_size", "int"),
+            required("format", "str"),
+            required("description", "str"),
+            required("encrypted", "bool"),
+        ])]],
+    },
+    ResultSchema {
+        function: "parse_archive",
+        returns: "dict",
+        fields: &[TABLE_FIELDS, &[
+            required("members", "list[str]"),
+            records("skipped", false, &[required("name", "str"), required("reason", "str")]),
+        ]],
+    },
+    ResultSchema {
+        function: "parse_auto",
+        returns: "dict",
+        fields: &[TABLE_FIELDS, &[
+            required("settings", "dict"),
+            records("attempts", false, &[
+                required("settings", "dict"),
+                required("error", "str | None"),
+                required("failure", "str | None"),
+            ]),
+            records("concessions", false, &[required("fallback", "str"), required("detail", "str")]),
+        ]],
+    },
+    ResultSchema {
+        function: "sniff_csv",
+        returns: "dict",
+        fields: &[VERSION_FIELDS, DIALECT_FIELDS],
+    },
+    ResultSchema { function: "parse_ndjson", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "parse_xml", returns: "dict", fields: &[TABLE_FIELDS, MARKUP_FIELDS] },
+    ResultSchema { function: "parse_excel", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema {
+        function: "list_excel_sheets",
+        returns: "dict",
+        fields: &[VERSION_FIELDS, &[records("sheets", false, &[
+            required("name", "str"),
+            required("rows", "int"),
+            required("columns", "int"),
+            required("hidden", "bool"),
+        ])]],
+    },
+    ResultSchema {
+        function: "parse_excel_all_sheets",
+        returns: "dict",
+        fields: &[VERSION_FIELDS, &[
+            // Sheet name -> table result, as from parse_excel
+            required("sheets", "dict"),
+            // Sheet name -> error message
+            required("errors", "dict"),
+        ]],
+    },
+    ResultSchema { function: "parse_excel_streaming", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema {
+        function: "should_use_excel_streaming",
+        returns: "dict",
+        fields: &[VERSION_FIELDS, &[
+            required("recommended", "bool"),
+            required("estimated_memory_mb", "int"),
+            required("memory_limit_mb", "int"),
+        ]],
+    },
+    ResultSchema { function: "parse_html_tables", returns: "list[dict]", fields: &[TABLE_FIELDS, MARKUP_FIELDS] },
+    ResultSchema { function: "sort_data", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "sort", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "set_category_order", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "format_values", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema {
+        function: "qcut",
+        returns: "dict",
+        fields: &[TABLE_FIELDS, &[required("bin_spec", "dict")]],
+    },
+    ResultSchema { function: "apply_bins", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "drop_nulls", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "fill_nulls", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "cast", returns: "dict", fields: &[TABLE_FIELDS, &[required("cast_failures", "dict")]] },
+    ResultSchema { function: "string_ops", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "datetime_features", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "truncate_datetime", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "drop_duplicates", returns: "dict", fields: &[TABLE_FIELDS, PRECISION_FIELDS, SUMMARY_FIELDS] },
+    ResultSchema { function: "rename_columns", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "select", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "rename", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "drop", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "reorder_columns", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "add_prefix", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "add_suffix", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "normalize_column_names", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema {
+        function: "map_columns",
+        returns: "dict",
+        fields: &[VERSION_FIELDS, &[
+            required("mapping", "dict"),
+            required("columns", "list[str]"),
+            required("unmapped", "list[str]"),
+            required("missing", "list[str]"),
+            records("ambiguous", false, &[required("canonical", "str"), required("sources", "list[str]")]),
+            records("conflicts", false, &[required("source", "str"), required("canonical", "list[str]")]),
+        ]],
+    },
+    ResultSchema { function: "add_columns", returns: "dict", fields: &[TABLE_FIELDS, &[required("null_counts", "dict")]] },
+    ResultSchema {
+        function: "update_where",
+        returns: "dict",
+        fields: &[TABLE_FIELDS, &[required("rows_matched", "int"), required("rows_modified", "dict")]],
+    },
+    ResultSchema { function: "filter", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "PreparedPipeline.run", returns: "dict", fields: &[TABLE_FIELDS, &[required("report", "dict")]] },
+    ResultSchema {
+        function: "PreparedPipeline.run_to_parquet",
+        returns: "dict",
+        fields: &[PARQUET_WRITE_FIELDS, &[required("report", "dict")]],
+    },
+    ResultSchema {
+        function: "PreparedPipeline.run_to_csv_partitioned",
+        returns: "dict",
+        fields: &[CSV_PARTS_FIELDS, &[required("report", "dict")]],
+    },
+    ResultSchema { function: "write_csv_partitioned", returns: "dict", fields: &[CSV_PARTS_FIELDS] },
+    ResultSchema { function: "write_parquet", returns: "dict", fields: &[PARQUET_WRITE_FIELDS] },
+    ResultSchema { function: "write_partitioned", returns: "dict", fields: &[PARQUET_WRITE_FIELDS] },
+    ResultSchema { function: "read_ipc", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "concat", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema {
+        function: "join",
+        returns: "dict",
+        fields: &[TABLE_FIELDS, &[required("left_rows", "int"), required("right_rows", "int")]],
+    },
+    ResultSchema {
+        function: "join_data",
+        returns: "dict",
+        fields: &[TABLE_FIELDS, PRECISION_FIELDS, SUMMARY_FIELDS, &[
+            required("join_engine", "str"),
+            required("join_partitions", "int"),
+        ]],
+    },
+    ResultSchema {
+        function: "suggest_join_keys",
+        returns: "dict",
+        fields: &[VERSION_FIELDS, &[
+            required("heuristic", "bool"),
+            required("sample_rows", "dict"),
+            records("candidates", false, JOIN_KEY_FIELDS),
+        ]],
+    },
+    ResultSchema { function: "value_counts", returns: "dict", fields: &[TABLE_FIELDS, PRECISION_FIELDS] },
+    ResultSchema { function: "group_by", returns: "dict", fields: &[TABLE_FIELDS, &[optional("drill_down", "dict")]] },
+    ResultSchema {
+        function: "group_by[tree]",
+        returns: "dict",
+        fields: &[VERSION_FIELDS, &[
+            required("keys", "list[str]"),
+            required("aggregates", "list[str]"),
+            required("level_counts", "list[int]"),
+            required("truncated", "bool"),
+            required("dropped_nodes", "int"),
+            // Nested nodes: key, values, child_count, children
+            required("nodes", "list[dict]"),
+        ]],
+    },
+    ResultSchema {
+        function: "get_contributors",
+        returns: "dict",
+        fields: &[TABLE_FIELDS, &[
+            required("group_row", "int"),
+            required("row_count", "int"),
+            required("truncated", "bool"),
+            required("row_indices", "list[int]"),
+        ]],
+    },
+    ResultSchema {
+        function: "describe",
+        returns: "dict",
+        fields: &[VERSION_FIELDS, &[
+            required("numeric", "dict"),
+            required("categorical", "dict"),
+            required("identifiers", "list[str]"),
+            records("id_detection", false, ID_DECISION_FIELDS),
+        ]],
+    },
+    ResultSchema {
+        function: "compare_groups",
+        returns: "dict",
+        fields: &[VERSION_FIELDS, &[
+            required("split_column", "str"),
+            required("group_a", "str"),
+            required("group_b", "str"),
+            required("rows_a", "int"),
+            required("rows_b", "int"),
+            // Numeric and categorical records carry different keys
+            records("columns", false, &[]),
+        ]],
+    },
+    ResultSchema { function: "t_test", returns: "dict", fields: &[VERSION_FIELDS, TEST_FIELDS] },
+    ResultSchema { function: "anova", returns: "dict", fields: &[VERSION_FIELDS, TEST_FIELDS] },
+    ResultSchema { function: "proportion_test", returns: "dict", fields: &[VERSION_FIELDS, TEST_FIELDS] },
+    ResultSchema { function: "ks_test", returns: "dict", fields: &[VERSION_FIELDS, TEST_FIELDS] },
+    ResultSchema {
+        function: "suggest_outlier_params",
+        returns: "dict",
+        fields: &[VERSION_FIELDS, &[records("columns", false, &[
+            required("column", "str"),
+            required("count", "int"),
+            required("method", "str | None"),
+            required("parameter", "float | None"),
+            required("lower", "float | None"),
+            required("upper", "float | None"),
+            required("flagged", "int | None"),
+            required("confidence", "str"),
+            required("note", "str"),
+            required("skewness", "float | None"),
+            required("kurtosis", "float | None"),
+            required("dip", "float | None"),
+            required("multimodal", "bool | None"),
+            required("tail_weight", "float | None"),
+            records("candidates", false, &[
+                required("method", "str"),
+                required("parameter", "float"),
+                required("lower", "float | None"),
+                required("upper", "float | None"),
+                required("flagged", "int"),
+            ]),
+        ])]],
+    },
+    ResultSchema {
+        function: "correlation",
+        returns: "dict",
+        fields: &[VERSION_FIELDS, &[
+            required("method", "str"),
+            required("columns", "list[str]"),
+            required("matrix", "list[list[float | None]]"),
+            required("rows_used", "int"),
+            required("influential_rows", "list[int]"),
+        ]],
+    },
+    ResultSchema {
+        function: "covariance_matrix",
+        returns: "dict",
+        fields: &[VERSION_FIELDS, &[
+            required("columns", "list[str]"),
+            required("matrix", "list[list[float]]"),
+            required("location", "list[float]"),
+            required("rows_used", "int"),
+            required("robust", "bool"),
+            required("support_fraction", "float | None"),
+            required("excluded_rows", "list[int]"),
+        ]],
+    },
+    ResultSchema {
+        function: "downsample",
+        returns: "dict",
+        fields: &[TABLE_FIELDS, &[required("method", "str"), required("source_points", "int")]],
+    },
+    ResultSchema {
+        function: "frame_diff",
+        returns: "dict",
+        fields: &[VERSION_FIELDS, &[
+            required("equal", "bool"),
+            required("left_shape", "list[int]"),
+            required("right_shape", "list[int]"),
+            required("left_only_columns", "list[str]"),
+            required("right_only_columns", "list[str]"),
+            required("column_order_differs", "bool"),
+            records("dtype_mismatches", false, &[
+                required("column", "str"),
+                required("left", "str"),
+                required("right", "str"),
+            ]),
+            records("cells", false, &[
+                required("row", "int"),
+                required("right_row", "int"),
+                required("column", "str"),
+                required("left", "str | None"),
+                required("right", "str | None"),
+            ]),
+            required("differing_cells", "int"),
+            required("report", "str"),
+        ]],
+    },
+    ResultSchema { function: "Dataset.to_dict", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "RustDataFrame.to_dict", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "Dataset.lookup", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema { function: "Dataset.lookup_many", returns: "dict", fields: &[TABLE_FIELDS] },
+    ResultSchema {
+        function: "QuerySession.sql",
+        returns: "dict",
+        fields: &[TABLE_FIELDS, &[optional("report", "dict")]],
+    },
+    ResultSchema {
+        function: "QuerySession.cache_stats",
+        returns: "dict",
+        fields: &[VERSION_FIELDS, &[
+            required("hits", "int"),
+            required("misses", "int"),
+            required("evictions", "int"),
+            required("entries", "int"),
+            required("bytes", "int"),
+            required("max_entries", "int"),
+            required("max_bytes", "int"),
+        ]],
+    },
+];
+
+/// Look up the declared result shape of a binding
+pub fn find_result_schema(function: &str) -> Option<&'static ResultSchema> {
+    RESULT_SCHEMAS.iter().find(|schema| schema.function == function)
+}
+
+/// Helper function to convert a field list into `{key: {"type", "optional"[, "items"]}}`
+fn result_fields_to_py<'a>(py: Python, fields: impl Iterator<Item = &'a ResultField>) -> PyResult<PyObject> {
+    let result = PyDict::new(py);
+    for field in fields {
+        let entry = PyDict::new(py);
+        entry.set_item("type", field.type_name)?;
+        entry.set_item("optional", field.optional)?;
+        if !field.items.is_empty() {
+            entry.set_item("items", result_fields_to_py(py, field.items.iter())?)?;
+        }
+        result.set_item(field.key, entry)?;
+    }
+    Ok(result.into())
+}
+
+/// Helper function to convert one registry entry into a dictionary
+fn result_schema_to_py(py: Python, schema: &ResultSchema) -> PyResult<PyObject> {
+    let result = PyDict::new(py);
+    result.set_item("function", schema.function)?;
+    result.set_item("returns", schema.returns)?;
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    result.set_item("fields", result_fields_to_py(py, schema.all_fields())?)?;
+    Ok(result.into())
+}
+
+/// Describe the keys guaranteed in a binding's result dictionary
+/// 
+/// Generated from the same registry the test suite checks real results
+/// against, so it always matches what the bindings return.
+/// 
+/// # Arguments
+/// * `function_name` - Binding name (methods as "QuerySession.sql"), or None
+///   for every registered binding
+/// 
+/// # Returns
+/// * Dictionary with `function`, `returns`, `schema_version` and `fields`
+///   (key -> {"type", "optional", and "items" for lists of records}); without
+///   a name, a dictionary of those keyed by function name
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// schema = insightora_core.result_schema("join_data")
+/// print(schema["fields"]["join_engine"])
+/// # {'type': 'str', 'optional': False}
+/// ```
+#[pyfunction]
+#[pyo3(signature = (function_name=None))]
+pub fn result_schema(py: Python, function_name: Option<&str>) -> PyResult<PyObject> {
+    match function_name {
+        Some(name) => {
+            let schema = find_result_schema(name).ok_or_else(|| {
+                let known: Vec<&str> = RESULT_SCHEMAS.iter().map(|s| s.function).collect();
+                PyValueError::new_err(format!(
+                    "No result schema registered for '{}'; known: {}",
+                    name,
+                    known.join(", ")
+                ))
+            })?;
+            result_schema_to_py(py, schema)
+        }
+        None => {
+            let result = PyDict::new(py);
+            for schema in RESULT_SCHEMAS {
+                result.set_item(schema.function, result_schema_to_py(py, schema)?)?;
+            }
+            Ok(result.into())
+        }
     }
-    Ok(())
 }
 
 // ============================================================================
 // CSV Parsing Python Bindings
 // ============================================================================
 
-use crate::io::csv_parser::{ParallelCsvParser, CsvParserConfig, StreamingCsvParser, StreamingCsvConfig};
+use crate::io::csv_parser::{self, ParallelCsvParser, CsvParserConfig, NullValueTokens, StreamingCsvParser, StreamingCsvConfig, BadLines};
+use crate::io::dates::DateFormat;
+use crate::io::encoding::{CsvEncoding, EncodingErrors};
+use crate::io::sampling::RowSample;
+use crate::io::file_index;
+use crate::io::csv_glob;
+use crate::io::csv_parser::{CsvWriteOptions, QuoteStyle, EscapeStyle};
+use crate::dataframe::transformations::ColumnMapping;
+use crate::io::csv_repair::{CsvRepair, CsvRepairOptions, RepairReport};
+use crate::io::nullability::{NotNullRules, NullPolicy, NullabilityReport};
+use crate::stats::identifiers::{identifier_columns, IdDetectionConfig, IdentifierDecision};
+use crate::utils::progress::ProgressReporter;
+use crate::utils::metrics::ExecutionMetrics;
 use pyo3::types::PyDict;
 
 /// Parse a CSV file and return a dictionary with data
 /// 
 /// This function provides a simple interface for parsing CSV files from Python.
-/// It uses parallel processing for improved performance on large files.
+/// It uses parallel processing for improved performance on large files. The
+/// GIL is released while the file is parsed, so other Python threads keep
+/// running; only building the result dictionary holds it. Ctrl-C stops the
+/// parse with KeyboardInterrupt.
 /// 
 /// # Arguments
-/// * `file_path` - Path to the CSV file
+/// * `file_path` - Path to the CSV file; gzip (`.gz`) and zstd (`.zst`) files are
+///   decompressed as they are read
+/// * `on_progress` - Optional callable `(percent, stage, detail)` for progress
+/// * `cancel_token` - Optional `CancellationToken`; cancelling it raises
+///   `OperationCancelled`
+/// * `return_format` - "dict" (default) or "arrow" for an `ArrowTable`
+///   that pyarrow, polars and pandas read without copying; much faster for
+///   large files, since no value is converted to a Python object
+/// * `return_handle` - Return a `RustDataFrame` keeping the table in Rust,
+///   for passing on to other bindings without converting it (default: False)
 /// 
 /// # Returns
-/// * Dictionary with 'columns' (list of column names) and 'data' (list of lists)
+/// * Dictionary with 'columns' (list of column names) and 'data' (list of
+///   lists), an `ArrowTable` with `return_format="arrow"` or a
+///   `RustDataFrame` with `return_handle=True`
 /// 
 /// # Example
 /// ```python
@@ -254,15 +1141,247 @@ use pyo3::types::PyDict;
 /// 
 /// # Convert to pandas DataFrame
 /// df = pd.DataFrame(result['data'], columns=result['columns'])
+/// 
+/// # Or skip the Python lists entirely
+/// import pyarrow as pa
+/// df = pa.table(insightora_core.parse_csv("data.csv", return_format="arrow")).to_pandas()
+/// 
+/// # Keep the table in Rust between steps
+/// handle = insightora_core.parse_csv("data.csv", return_handle=True)
+/// ordered = insightora_core.sort_data(handle, "amount")
+/// print(ordered.shape, ordered.head(5).to_dict())
 /// ```
 #[pyfunction]
-pub fn parse_csv(py: Python, file_path: &str) -> PyResult<PyObject> {
-    let parser = ParallelCsvParser::new();
-    let df = parser.parse(file_path)
-        .map_err(|e| PyRuntimeError::new_err(format!("Failed to parse CSV: {}", e)))?;
+#[pyo3(signature = (file_path, on_progress=None, cancel_token=None, return_format="dict", return_handle=false))]
+pub fn parse_csv(
+    py: Python,
+    file_path: &str,
+    on_progress: Option<PyObject>,
+    cancel_token: Option<PyRef<PyCancellationToken>>,
+    return_format: &str,
+    return_handle: bool,
+) -> PyResult<PyObject> {
+    let format = match (ReturnFormat::from_name(return_format)?, return_handle) {
+        (format, false) => format,
+        (ReturnFormat::Dict, true) => ReturnFormat::Handle,
+        (_, true) => return Err(PyValueError::new_err("return_handle=True can't be combined with return_format='arrow'")),
+    };
+    let progress = progress_reporter(on_progress);
+    // Both stages scale with the file size; exporting to Python costs about as much as parsing
+    let stages = progress.stages(&[("parse", 1.0), ("export", 1.0)]);
+    let cancel = cancel_flag(cancel_token);
+    let parser = ParallelCsvParser::new().with_progress(stages[0].clone()).with_cancel_flag(Arc::clone(&cancel));
+    let path = file_path.to_string();
+    let df = run_cancellable(py, &cancel, move || parser.parse(&path))?
+        .map_err(|e| operation_error("Failed to parse CSV", e))?;
     
-    // Convert DataFrame to dictionary format
+    let result = table_result(py, &df, format, &stages[1])?;
+    finish_table(py, df, format, result)
+}
+
+/// Helper function to add context to an operation error
+/// 
+/// Permission, schema, watchdog and cancellation errors keep their own
+/// exception types so sandbox violations surface as PermissionError, schema
+/// violations as SchemaError, watchdog aborts as MemoryLimitError and
+/// cancelled parses as OperationCancelled rather than a generic RuntimeError.
+fn operation_error(context: &str, err: InsightoraError) -> PyErr {
+    match err {
+        InsightoraError::PermissionDenied(_)
+        | InsightoraError::SchemaError(_)
+        | InsightoraError::NotNullViolation { .. }
+        | InsightoraError::MemoryWatchdog { .. }
+        | InsightoraError::ChunkRead { .. }
+        | InsightoraError::Cancelled => err.into(),
+        other => PyRuntimeError::new_err(format!("{}: {}", context, other)),
+    }
+}
+
+/// Helper function for the error raised by any operation on a closed handle
+/// 
+/// Every pyclass with a `close()` method raises ValueError with the same
+/// wording, as Python file objects do after `close()`.
+fn closed_handle(class: &str) -> PyErr {
+    PyValueError::new_err(format!("{} handle is closed", class))
+}
+
+/// Flag for stopping a parse from another thread
+///
+/// Pass it as `cancel_token` to `parse_csv`, `parse_csv_with_options`,
+/// `parse_csv_streaming` or `parse_csv_batches`, then call `cancel()` from
+/// any thread, a signal handler or a UI callback. The parse raises
+/// `OperationCancelled` once the parse has stopped and released its file
+/// and memory. Both parsers stop within one round of batches. A token
+/// stays cancelled, so use a new one per operation.
+///
+/// # Example
+/// ```python
+/// import threading
+/// import insightora_core
+///
+/// token = insightora_core.CancellationToken()
+/// threading.Timer(5.0, token.cancel).start()
+/// try:
+///     result = insightora_core.parse_csv_streaming("huge.csv", cancel_token=token)
+/// except insightora_core.OperationCancelled:
+///     print("gave up after 5 seconds")
+/// ```
+#[pyclass(name = "CancellationToken")]
+pub struct PyCancellationToken {
+    flag: Arc<AtomicBool>,
+}
+
+#[pymethods]
+impl PyCancellationToken {
+    #[new]
+    fn new() -> Self {
+        Self { flag: Arc::new(AtomicBool::new(false)) }
+    }
+
+    /// Stop the operations using this token (idempotent)
+    fn cancel(&self) {
+        self.flag.store(true, AtomicOrdering::Relaxed);
+    }
+
+    #[getter]
+    fn cancelled(&self) -> bool {
+        self.flag.load(AtomicOrdering::Relaxed)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("CancellationToken(cancelled={})", if self.cancelled() { "True" } else { "False" })
+    }
+}
+
+/// Helper function for the flag behind an optional `cancel_token`
+fn cancel_flag(token: Option<PyRef<PyCancellationToken>>) -> Arc<AtomicBool> {
+    match token {
+        Some(token) => Arc::clone(&token.flag),
+        None => Arc::new(AtomicBool::new(false)),
+    }
+}
+
+/// How often a thread waiting on a parse checks for Ctrl-C and cancellation
+const SIGNAL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Helper function to run `work` on its own thread while this one waits with the GIL released
+///
+/// Every `SIGNAL_POLL_INTERVAL` the waiting thread runs Python's signal
+/// handlers, so Ctrl-C reaches a long parse. An exception from a handler
+/// (KeyboardInterrupt) sets `cancel`; once `cancel` is set, by a handler or
+/// a `CancellationToken`, the work stops at its next cancellation check
+/// and the result is `Cancelled` (or the handler's exception). The worker
+/// is always joined, so its file and memory are released when this returns.
+fn run_cancellable<T, F>(py: Python, cancel: &Arc<AtomicBool>, work: F) -> PyResult<Result<T, InsightoraError>>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, InsightoraError> + Send + 'static,
+{
+    let (sender, mut receiver) = std::sync::mpsc::channel();
+    let worker = std::thread::Builder::new()
+        .name("insightora-parse".to_string())
+        .spawn(move || {
+            // The caller has gone when this fails, and the result is dropped here
+            let _ = sender.send(work());
+        })
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to start parse thread: {}", e)))?;
+
+    let outcome = loop {
+        // Receivers aren't Sync, so the receiver moves into the GIL-free closure and back
+        let (returned, outcome) = py.allow_threads(move || {
+            let outcome = receiver.recv_timeout(SIGNAL_POLL_INTERVAL);
+            (receiver, outcome)
+        });
+        receiver = returned;
+        match outcome {
+            Ok(result) => break Ok(result),
+            Err(RecvTimeoutError::Disconnected) => break Err(PyRuntimeError::new_err("Parse thread stopped without a result")),
+            Err(RecvTimeoutError::Timeout) => {
+                if let Err(interrupt) = py.check_signals() {
+                    cancel.store(true, AtomicOrdering::Relaxed);
+                    break Err(interrupt);
+                }
+                if cancel.load(AtomicOrdering::Relaxed) {
+                    break Ok(Err(InsightoraError::Cancelled));
+                }
+            }
+        }
+    };
+    // A cancelled worker stops at its next check; a panicked one has already stopped
+    let _ = py.allow_threads(move || worker.join());
+    outcome
+}
+
+/// Helper function to build a progress reporter from an optional Python callable
+/// 
+/// The callable is invoked as `callback(percent, stage, detail)` with the GIL
+/// held only for the call. Exceptions it raises are printed and otherwise
+/// ignored so a broken progress bar never fails the operation.
+fn progress_reporter(callback: Option<PyObject>) -> ProgressReporter {
+    match callback {
+        Some(callback) => ProgressReporter::new(Arc::new(move |percent: f64, stage: &str, detail: &str| {
+            if !python_available() {
+                return;
+            }
+            Python::with_gil(|py| {
+                if let Err(err) = callback.call1(py, (percent, stage, detail)) {
+                    err.print(py);
+                }
+            })
+        })),
+        None => ProgressReporter::disabled(),
+    }
+}
+
+/// Helper function to split a binding's progress into load, compute and
+/// export stages
+/// 
+/// Converting Python values dominates these calls, so stages are weighted by
+/// the number of cells they touch; the Rust compute step counts as half the
+/// input cells.
+fn binding_stages(progress: &ProgressReporter, input_cells: f64, output_cells: f64) -> Vec<ProgressReporter> {
+    progress.stages(&[
+        ("load", input_cells),
+        ("compute", input_cells * 0.5),
+        ("export", output_cells),
+    ])
+}
+
+/// Helper function to count the cells in a result dictionary (for progress weights)
+/// 
+/// Arrow input isn't converted value by value, so it counts as one cell.
+fn dict_cells(data: &PyAny) -> f64 {
+    if let Ok(handle) = data.extract::<PyRef<PyRustDataFrame>>() {
+        return (handle.data.height() * handle.data.width()).max(1) as f64;
+    }
+    let get = |key: &str| -> usize {
+        data.downcast::<PyDict>().ok()
+            .and_then(|dict| dict.get_item(key).ok().flatten())
+            .and_then(|v| v.extract().ok())
+            .unwrap_or(0)
+    };
+    (get("num_rows") * get("num_columns")).max(1) as f64
+}
+
+/// Helper function to convert a DataFrame into the standard result dictionary
+/// 
+/// The dictionary contains 'schema_version', 'columns', 'dtypes', 'num_rows',
+/// 'num_columns' and 'data' (column-major nested lists of native Python
+/// values), plus 'categories' when ordered categorical columns are present;
+/// see `TABLE_FIELDS`.
+fn dataframe_to_pydict(py: Python, df: &polars::prelude::DataFrame) -> PyResult<PyObject> {
+    dataframe_to_pydict_reporting(py, df, &ProgressReporter::disabled())
+}
+
+/// Helper function to convert a DataFrame into a result dictionary, reporting
+/// progress column by column
+fn dataframe_to_pydict_reporting(
+    py: Python,
+    df: &polars::prelude::DataFrame,
+    progress: &ProgressReporter,
+) -> PyResult<PyObject> {
     let result = PyDict::new(py);
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
     
     // Get column names
     let columns: Vec<String> = df.get_column_names()
@@ -271,78 +1390,500 @@ pub fn parse_csv(py: Python, file_path: &str) -> PyResult<PyObject> {
         .collect();
     result.set_item("columns", columns)?;
     
+    // Dtypes are named as in `infer_csv_schema`
+    let dtypes: Vec<String> = df.dtypes().iter().map(|dt| format!("{:?}", dt)).collect();
+    result.set_item("dtypes", dtypes)?;
+    
     // Get shape
     result.set_item("num_rows", df.height())?;
     result.set_item("num_columns", df.width())?;
     
     // Convert data to nested lists (column-major format)
+    progress.report(0.0, "");
     let mut data_columns = Vec::new();
-    for col in df.get_columns() {
+    for (i, col) in df.get_columns().iter().enumerate() {
         let col_data = series_to_python_list(py, col)?;
         data_columns.push(col_data);
+        progress.report((i + 1) as f64 / df.width() as f64, col.name());
     }
     result.set_item("data", data_columns)?;
     
+    // Category orders travel with the data so ordered categoricals round-trip
+    let categories = PyDict::new(py);
+    for col in df.get_columns() {
+        if let Some(order) = transformations::category_order(col) {
+            categories.set_item(col.name(), order)?;
+        }
+    }
+    if !categories.is_empty() {
+        result.set_item("categories", categories)?;
+    }
+    
     Ok(result.into())
 }
 
-/// Helper function to convert a Polars Series to a Python list
-fn series_to_python_list(py: Python, series: &polars::prelude::Series) -> PyResult<PyObject> {
-    use polars::prelude::*;
+/// Helper function to convert a result dictionary back into a DataFrame
+/// 
+/// Accepts the dictionary shape produced by `dataframe_to_pydict`: a 'columns'
+/// list of names, a 'data' list holding one list of values per column and an
+/// optional 'categories' dict restoring ordered categorical columns. Any
+/// object implementing `__arrow_c_stream__` (an `ArrowTable`, a pyarrow
+/// Table, a polars DataFrame, ...) is imported through Arrow instead, and a
+/// `RustDataFrame` handle is used as it is, without converting anything.
+fn pydict_to_dataframe(data: &PyAny) -> PyResult<polars::prelude::DataFrame> {
+    pydict_to_dataframe_reporting(data, &ProgressReporter::disabled())
+}
+
+/// Helper function to convert a result dictionary into a DataFrame, reporting
+/// progress column by column
+fn pydict_to_dataframe_reporting(
+    data: &PyAny,
+    progress: &ProgressReporter,
+) -> PyResult<polars::prelude::DataFrame> {
     use pyo3::types::PyList;
     
-    let list = PyList::empty(py);
+    if let Ok(handle) = data.extract::<PyRef<PyRustDataFrame>>() {
+        progress.finish("");
+        return Ok(handle.data.as_ref().clone());
+    }
+    if data.hasattr("__arrow_c_stream__")? {
+        let df = arrow_stream_to_dataframe(data)?;
+        progress.finish("");
+        return Ok(df);
+    }
+    let data: &PyDict = data.downcast().map_err(|_| {
+        PyTypeError::new_err(format!(
+            "Unsupported data type '{}'; expected a result dictionary, a RustDataFrame or an object implementing __arrow_c_stream__ (pyarrow Table, polars DataFrame)",
+            data.get_type().name().unwrap_or("?")
+        ))
+    })?;
     
-    // Convert series to string representation for simplicity
-    // This ensures compatibility across all data types
-    let string_series = series.cast(&DataType::String)
-        .map_err(|e| PyRuntimeError::new_err(format!("Failed to cast series: {}", e)))?;
-    
-    let ca = string_series.str()
-        .map_err(|e| PyRuntimeError::new_err(format!("Failed to get string array: {}", e)))?;
-    
-    // Iterate through values
-    for i in 0..ca.len() {
-        let opt_val = ca.get(i);
-        match opt_val {
-            Some(val) => {
-                // Try to parse as number if possible, otherwise keep as string
-                if let Ok(num) = val.parse::<i64>() {
-                    list.append(num)?;
-                } else if let Ok(num) = val.parse::<f64>() {
-                    list.append(num)?;
-                } else if val == "true" || val == "false" {
-                    list.append(val == "true")?;
-                } else {
-                    list.append(val)?;
-                }
+    let columns: Vec<String> = data.get_item("columns")?
+        .ok_or_else(|| PyValueError::new_err("Data dictionary is missing 'columns'"))?
+        .extract()?;
+    let values: &PyList = data.get_item("data")?
+        .ok_or_else(|| PyValueError::new_err("Data dictionary is missing 'data'"))?
+        .downcast()?;
+    
+    if columns.len() != values.len() {
+        return Err(PyValueError::new_err(format!(
+            "Data dictionary has {} column names but {} data columns",
+            columns.len(),
+            values.len()
+        )));
+    }
+    
+    progress.report(0.0, "");
+    let series = columns.iter()
+        .zip(values.iter())
+        .enumerate()
+        .map(|(i, (name, column))| {
+            let series = python_list_to_series(name, column.downcast()?)?;
+            progress.report((i + 1) as f64 / columns.len() as f64, name);
+            Ok(series)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    
+    let mut df = polars::prelude::DataFrame::new(series)
+        .map_err(|e| PyValueError::new_err(format!("Invalid data dictionary: {}", e)))?;
+    
+    if let Some(categories) = data.get_item("categories")? {
+        let categories: HashMap<String, Vec<String>> = categories.extract()?;
+        for (column, order) in categories {
+            df = transformations::set_category_order(&df, &column, &order, transformations::UnknownCategory::Error)
+                .map_err(|e| PyValueError::new_err(format!("Invalid 'categories' entry for '{}': {}", column, e)))?;
+        }
+    }
+    Ok(df)
+}
+
+/// Helper function to build a Series from a Python list, inferring the dtype
+/// 
+/// Booleans, integers and floats map to Boolean, Int64 and Float64 (integers
+/// mixed with floats widen to Float64, integers beyond Int64 read as UInt64)
+/// and timedeltas map to Duration. Dates, datetimes and Decimals map as in
+/// `from_records`, so values from `series_to_python_list` keep their dtype;
+/// anything else becomes String.
+fn python_list_to_series(name: &str, values: &pyo3::types::PyList) -> PyResult<polars::prelude::Series> {
+    use polars::prelude::*;
+    use pyo3::types::{PyBool, PyDate, PyDelta, PyDeltaAccess, PyFloat, PyLong};
+    
+    let (mut has_bool, mut has_int, mut has_float, mut has_other) = (false, false, false, false);
+    let mut has_delta = false;
+    let mut decimal_type: Option<&PyAny> = None;
+    for value in values.iter() {
+        if value.is_none() {
+            continue;
+        } else if value.is_instance_of::<PyDate>() {
+            // datetime is a subclass of date
+            return literal_series(name, &values.iter().collect::<Vec<_>>(), None, SchemaPolicy::Widen);
+        } else if value.is_instance_of::<PyBool>() {
+            has_bool = true;
+        } else if value.is_instance_of::<PyLong>() {
+            has_int = true;
+        } else if value.is_instance_of::<PyFloat>() {
+            has_float = true;
+        } else if value.is_instance_of::<PyDelta>() {
+            has_delta = true;
+        } else {
+            let decimal_type = match decimal_type {
+                Some(decimal_type) => decimal_type,
+                None => *decimal_type.insert(values.py().import("decimal")?.getattr("Decimal")?),
+            };
+            if value.is_instance(decimal_type)? {
+                return literal_series(name, &values.iter().collect::<Vec<_>>(), None, SchemaPolicy::Widen);
             }
-            None => list.append(py.None())?,
+            has_other = true;
         }
     }
     
-    Ok(list.into())
+    let has_scalar = has_bool || has_int || has_float;
+    let series = if has_delta && !has_other && !has_scalar {
+        let micros = values.iter()
+            .map(|v| {
+                if v.is_none() {
+                    return Ok(None);
+                }
+                let delta: &PyDelta = v.downcast()?;
+                Ok(Some(
+                    delta.get_days() as i64 * 86_400_000_000
+                        + delta.get_seconds() as i64 * 1_000_000
+                        + delta.get_microseconds() as i64,
+                ))
+            })
+            .collect::<PyResult<Vec<Option<i64>>>>()?;
+        Series::new(name, micros)
+            .cast(&DataType::Duration(TimeUnit::Microseconds))
+            .map_err(|e| PyValueError::new_err(format!("Invalid duration column '{}': {}", name, e)))?
+    } else if has_other || has_delta || (has_bool && (has_int || has_float)) {
+        let strings = values.iter()
+            .map(|v| if v.is_none() { Ok(None) } else { Ok(Some(v.str()?.to_string())) })
+            .collect::<PyResult<Vec<Option<String>>>>()?;
+        Series::new(name, strings)
+    } else if has_float {
+        let floats = values.iter()
+            .map(|v| v.extract::<Option<f64>>())
+            .collect::<PyResult<Vec<_>>>()?;
+        Series::new(name, floats)
+    } else if has_int {
+        let ints = values.iter()
+            .map(|v| v.extract::<Option<i64>>())
+            .collect::<PyResult<Vec<_>>>();
+        match ints {
+            Ok(ints) => Series::new(name, ints),
+            Err(_) => {
+                let unsigned = values.iter()
+                    .map(|v| v.extract::<Option<u64>>())
+                    .collect::<PyResult<Vec<_>>>()?;
+                Series::new(name, unsigned)
+            }
+        }
+    } else if has_bool {
+        let bools = values.iter()
+            .map(|v| v.extract::<Option<bool>>())
+            .collect::<PyResult<Vec<_>>>()?;
+        Series::new(name, bools)
+    } else {
+        Series::full_null(name, values.len(), &DataType::Null)
+    };
+    
+    Ok(series)
 }
 
-/// Parse a CSV file with custom options
-/// 
-/// Provides fine-grained control over CSV parsing behavior.
+/// Helper function to convert a Polars Series to a Python list
 /// 
-/// # Arguments
-/// * `file_path` - Path to the CSV file
-/// * `has_header` - Whether the CSV has a header row (default: True)
-/// * `delimiter` - Field delimiter character (default: ',')
+/// Values keep their dtype: integers become int (unsigned 64-bit values
+/// stay exact), floats become float with NaN kept apart from null, strings
+/// stay str verbatim ("0123" is not read as a number), Date and Datetime
+/// become `datetime.date` / `datetime.datetime` (timezone-aware when the
+/// column has a timezone), Duration becomes `datetime.timedelta` and Decimal
+/// becomes `decimal.Decimal`. Nulls are None. Other dtypes (categoricals,
+/// nested types) are returned as their text form.
+fn series_to_python_list(py: Python, series: &polars::prelude::Series) -> PyResult<PyObject> {
+    use polars::prelude::*;
+    use polars::export::chrono::{Datelike, Timelike};
+    use pyo3::types::{PyDate, PyDateTime, PyList};
+    
+    let list = PyList::empty(py);
+    let read = |dtype: &DataType| {
+        series.cast(dtype)
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to read column '{}': {}", series.name(), e)))
+    };
+    let physical = |e: PolarsError| PyRuntimeError::new_err(format!("Failed to read column '{}': {}", series.name(), e));
+    
+    match series.dtype() {
+        DataType::Boolean => {
+            for value in series.bool().map_err(physical)? {
+                list.append(value)?;
+            }
+        }
+        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 => {
+            for value in read(&DataType::Int64)?.i64().map_err(physical)? {
+                list.append(value)?;
+            }
+        }
+        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => {
+            for value in read(&DataType::UInt64)?.u64().map_err(physical)? {
+                list.append(value)?;
+            }
+        }
+        DataType::Float32 | DataType::Float64 => {
+            for value in read(&DataType::Float64)?.f64().map_err(physical)? {
+                list.append(value)?;
+            }
+        }
+        DataType::String => {
+            for value in series.str().map_err(physical)? {
+                list.append(value)?;
+            }
+        }
+        DataType::Null => {
+            for _ in 0..series.len() {
+                list.append(py.None())?;
+            }
+        }
+        DataType::Date => {
+            for value in series.date().map_err(physical)?.as_date_iter() {
+                match value {
+                    Some(date) => list.append(PyDate::new(py, date.year(), date.month() as u8, date.day() as u8)?)?,
+                    None => list.append(py.None())?,
+                }
+            }
+        }
+        DataType::Datetime(_, timezone) => {
+            // Timestamps are stored in UTC; aware values are shown in the column's zone
+            let zone = match timezone.as_deref() {
+                Some(tz) => Some(py.import("zoneinfo")?.getattr("ZoneInfo")?.call1((tz,))?),
+                None => None,
+            };
+            let utc = zone.map(|_| pyo3::types::timezone_utc(py));
+            for value in series.datetime().map_err(physical)?.as_datetime_iter() {
+                let Some(at) = value else {
+                    list.append(py.None())?;
+                    continue;
+                };
+                // Python datetimes stop at microseconds
+                let datetime = PyDateTime::new(
+                    py,
+                    at.year(),
+                    at.month() as u8,
+                    at.day() as u8,
+                    at.hour() as u8,
+                    at.minute() as u8,
+                    at.second() as u8,
+                    at.nanosecond() / 1_000 % 1_000_000,
+                    utc,
+                )?;
+                match zone {
+                    Some(zone) => list.append(datetime.call_method1("astimezone", (zone,))?)?,
+                    None => list.append(datetime)?,
+                }
+            }
+        }
+        DataType::Duration(_) => {
+            use pyo3::types::PyDelta;
+            let micros = series.cast(&DataType::Duration(TimeUnit::Microseconds))
+                .and_then(|s| s.to_physical_repr().i64().cloned())
+                .map_err(|e| PyRuntimeError::new_err(format!("Failed to read durations: {}", e)))?;
+            for value in micros.into_iter() {
+                match value {
+                    Some(us) => {
+                        let days = us.div_euclid(86_400_000_000);
+                        let rem = us.rem_euclid(86_400_000_000);
+                        let days = i32::try_from(days)
+                            .map_err(|_| PyValueError::new_err("Duration out of range for timedelta"))?;
+                        let delta = PyDelta::new(py, days, (rem / 1_000_000) as i32, (rem % 1_000_000) as i32, false)?;
+                        list.append(delta)?;
+                    }
+                    None => list.append(py.None())?,
+                }
+            }
+        }
+        DataType::List(_) => {
+            for value in series.list().map_err(physical)? {
+                match value {
+                    Some(inner) => list.append(series_to_python_list(py, &inner)?)?,
+                    None => list.append(py.None())?,
+                }
+            }
+        }
+        DataType::Decimal(_, _) => {
+            // Through the exact unscaled digits rather than float; Decimal
+            // columns don't cast to strings
+            let decimal_type = py.import("decimal")?.getattr("Decimal")?;
+            let decimals = series.decimal().map_err(physical)?;
+            let scale = decimals.scale();
+            for value in decimals.into_iter() {
+                match value {
+                    Some(digits) => list.append(decimal_type.call1((format!("{}E-{}", digits, scale),))?)?,
+                    None => list.append(py.None())?,
+                }
+            }
+        }
+        _ => {
+            for value in read(&DataType::String)?.str().map_err(physical)? {
+                list.append(value)?;
+            }
+        }
+    }
+    
+    Ok(list.into())
+}
+
+/// Parse a CSV file with custom options
+/// 
+/// Provides fine-grained control over CSV parsing behavior. As with
+/// `parse_csv`, other Python threads run while the file is parsed.
+/// 
+/// # Arguments
+/// * `file_path` - Path to the CSV file, optionally gzip or zstd compressed
+/// * `has_header` - Whether the CSV has a header row (default: True); None
+///   detects it as `sniff_csv` does
+/// * `delimiter` - Field delimiter character (default: ','), or "auto" for
+///   the delimiter and quote character `sniff_csv` finds in the first 64 KiB
 /// * `chunk_size` - Number of rows to process per chunk (default: 100000)
 /// * `infer_schema_length` - Number of rows to use for schema inference (default: 1000)
+/// * `on_progress` - Optional callable `(percent, stage, detail)` for progress
+/// * `repair` - "none" (default) or "column_count": rows with more fields than
+///   the header (unescaped delimiters in free text) have the surplus fields
+///   merged back into the absorber column
+/// * `absorber` - Column receiving surplus fields (default: the last text column)
+/// * `flag_repairs` - Add a boolean `_repaired` column marking repaired rows
+/// * `rename` - Dict of source -> new column name, a list of names by
+///   position (for files without a header), or a ColumnDictionary (or dict of
+///   canonical name -> list of aliases) mapping feed headers to canonical names;
+///   applied as the header is read
+/// * `columns` - Columns to keep, by source or new name, in the order given
+///   (default: all). Other columns are skipped while reading rather than
+///   parsed and dropped; a name not in the file raises ValueError
+/// * `column_indices` - Columns to keep by zero-based position in the file,
+///   in the order given; an alternative to `columns`
+/// * `skip_rows` - Lines skipped before the header (or the first row with
+///   `has_header=False`), e.g. a vendor preamble (default: 0)
+/// * `max_rows` - Read at most this many rows and stop, so previewing a huge
+///   file doesn't scan it (default: all); 0 raises ValueError. Not supported
+///   with a repair mode
+/// * `null_values` - Text read as null, e.g. ["NA", "N/A", "-", "NULL"], or
+///   a dict of column -> token or list of tokens. Columns holding numbers
+///   and tokens are still typed as numbers (default: none)
+/// * `empty_as_null` - Read empty fields as null (default: True) or, in
+///   text columns, as empty strings; independent of `null_values`
+/// * `include_summary` - Attach a `summary` dict: options in effect, engine,
+///   threads, chunks, rows read/kept/dropped with reasons, per-phase times,
+///   peak estimated memory and aggregated `warnings`. It holds no data
+///   values, so it is safe to paste into a bug report
+/// * `include_samples` - Also add `summary["samples"]` (repaired values and
+///   the first row); these are raw data values
+/// * `not_null` - Columns declared NOT NULL (named after `rename`); nulls in
+///   them are counted with sample row numbers
+/// * `null_tokens` - Text also treated as null in NOT NULL columns (e.g.
+///   ["NULL", "N/A"]); the values are kept, unlike with `null_values`
+/// * `null_policy` - "report" (default) or "strict": raise `SchemaError` at
+///   the first null in a NOT NULL column, naming the column and data row
+/// * `try_parse_dates` - Return text columns whose first
+///   `infer_schema_length` values are all ISO 8601 dates ("2024-01-15") or
+///   datetimes ("2024-01-15T08:30:00Z", offsets converted to UTC) as
+///   `datetime.date` / `datetime.datetime` values (default: False). Later
+///   values that don't parse become None. Not supported with a repair mode
+/// * `date_formats` - Dict of column -> strftime format for dates in other
+///   formats, e.g. {"booked": "%d/%m/%Y"}; parsed whether or not
+///   `try_parse_dates` is set
+/// * `on_bad_lines` - Lines with more fields than the header: "error"
+///   (default) raises ValueError naming the first one's line number and
+///   field count, "skip" leaves them out and "warn" also logs one warning.
+///   Not supported with a repair mode
 /// 
 /// # Returns
-/// * Dictionary with 'columns' and 'data'
+/// * Dictionary with 'columns' and 'data'; with a repair mode, also 'repair'
+///   (`mode`, `absorber`, `rows_repaired` and `repairs`, the first 100
+///   repaired rows with `line`, `fields` and the merged `value`); with
+///   `not_null`, also 'nullability' (`policy`, `rows_checked` and
+///   `violations`: `column`, `count` and up to 10 `sample_rows`, 1-based
+///   data rows); with `try_parse_dates` or `date_formats`, also
+///   'parse_warnings' (`category`, `column`, `count` and `message` per
+///   column with values set to None because they didn't parse); with
+///   `on_bad_lines` "skip" or "warn", also 'bad_line_count' and 'bad_lines',
+///   the 1-based line numbers of the first 10 skipped lines, and with "warn"
+///   the warning in 'parse_warnings'; with `delimiter="auto"` or
+///   `has_header=None`, also 'dialect', the `sniff_csv` result; with
+///   `sample_fraction` or `sample_n`, also 'sampled' (True),
+///   'sampling_rate', 'sample_rows_read' and 'sample_seed', so counts over
+///   the sample aren't taken for counts over the file
+/// * `cancel_token` - Optional `CancellationToken`; cancelling it raises
+///   `OperationCancelled`, and Ctrl-C raises KeyboardInterrupt, as in
+///   `parse_csv`
+/// * `return_format` - "dict" (default) or "arrow" for an `ArrowTable`, as
+///   in `parse_csv`; the keys above other than the table are then in its
+///   `report`
+/// * `encoding` - "utf8" (default), "utf8-lossy", "latin1" or
+///   "windows-1252". Anything but UTF-8 is converted a buffer at a time to
+///   a temporary UTF-8 copy under `configure(temp_dir=...)`, not held in
+///   memory. A leading UTF-8 byte order mark is dropped whatever the encoding
+/// * `errors` - Invalid byte sequences: "strict" (default) raises naming
+///   the byte offset and line of the first one, "replace" reads each as
+///   U+FFFD. "utf8-lossy" always replaces
+/// * `row_filter` - Keep only the rows where a condition holds, e.g.
+///   "country == 'DE' and amount > 100". Applied to each batch of
+///   `chunk_size` rows as it is read, so memory follows the matching rows
+///   rather than the file size. Besides comparisons, `and`, `or` and `not`
+///   it takes `in ['DE', 'AT']`, `not in [...]`, `is null`, `is not null`
+///   and `contains 'text'`; columns are named after `rename` and may be
+///   left out by `columns`. `max_rows` counts rows read, not rows kept. A
+///   malformed condition or unknown column raises ValueError giving the
+///   position of the offending token. Not supported with a repair mode
+/// * `sample_fraction` - Keep each row with this probability (0 < p <= 1),
+///   drawn batch by batch so the whole file is never held; 'sampling_rate'
+///   is p. Rows are sampled from those `row_filter` keeps
+/// * `sample_n` - Keep this many rows, chosen uniformly from the whole file
+///   by reservoir sampling across batches and returned in file order; a
+///   file with fewer rows is returned whole. 'sampling_rate' is rows kept /
+///   'sample_rows_read'. Only one of `sample_fraction` and `sample_n` may be
+///   given, and neither with a repair mode
+/// * `seed` - Seed for the sample, so the same rows are drawn again; without
+///   one a random seed is used and returned as 'sample_seed'
 /// 
 /// # Example
 /// ```python
 /// import insightora_core
 /// import pandas as pd
 /// 
+/// result = insightora_core.parse_csv_with_options("legacy_export.csv", encoding="windows-1252")
+/// 
+/// result = insightora_core.parse_csv_with_options("tickets.csv", repair="column_count", flag_repairs=True)
+/// print(result["repair"]["rows_repaired"], result["repair"]["absorber"])
+/// 
+/// result = insightora_core.parse_csv_with_options("tickets.csv", include_summary=True)
+/// print(result["summary"]["rows"], result["summary"]["phases_ms"])
+/// 
+/// result = insightora_core.parse_csv_with_options("orders.csv", not_null=["order_id"], null_tokens=["NULL"])
+/// print(result["nullability"]["violations"])
+/// 
+/// result = insightora_core.parse_csv_with_options("wide.csv", columns=["region", "order_id", "amount"])
+/// result = insightora_core.parse_csv_with_options("wide.csv", column_indices=[3, 0, 17])
+/// 
+/// result = insightora_core.parse_csv_with_options("feed.csv", null_values=["NA", "N/A", "-"])
+/// 
+/// result = insightora_core.parse_csv_with_options("bookings.csv", try_parse_dates=True, date_formats={"booked": "%d/%m/%Y"})
+/// for warning in result["parse_warnings"]:
+///     print(warning["message"])
+/// 
+/// result = insightora_core.parse_csv_with_options("export.csv", on_bad_lines="warn")
+/// print(result["bad_line_count"], result["bad_lines"])
+/// 
+/// # First 100 rows, below a two-line preamble
+/// preview = insightora_core.parse_csv_with_options("huge.csv", skip_rows=2, max_rows=100)
+/// 
+/// # German orders over 100 from a file too big to hold
+/// result = insightora_core.parse_csv_with_options("orders_40gb.csv", row_filter="country == 'DE' and amount > 100")
+/// 
+/// # A reproducible 1% sample; scale counts back up by the rate
+/// sample = insightora_core.parse_csv_with_options("orders_40gb.csv", sample_fraction=0.01, seed=7)
+/// estimated_rows = sample["num_rows"] / sample["sampling_rate"]
+/// 
+/// # A ".csv" upload that may be semicolon-delimited and headerless
+/// result = insightora_core.parse_csv_with_options("upload.csv", delimiter="auto", has_header=None)
+/// print(result["dialect"]["delimiter"], result["dialect"]["has_header"])
+/// 
 /// # Parse CSV with custom delimiter
 /// result = insightora_core.parse_csv_with_options(
 ///     "data.tsv",
@@ -352,20 +1893,69 @@ fn series_to_python_list(py: Python, series: &polars::prelude::Series) -> PyResu
 /// df = pd.DataFrame(result['data'], columns=result['columns'])
 /// ```
 #[pyfunction]
-#[pyo3(signature = (file_path, has_header=true, delimiter=",", chunk_size=None, infer_schema_length=None))]
+#[pyo3(signature = (file_path, has_header=true, delimiter=",", chunk_size=None, infer_schema_length=None, on_progress=None, repair="none", absorber=None, flag_repairs=false, rename=None, columns=None, column_indices=None, skip_rows=0, max_rows=None, null_values=None, empty_as_null=true, include_summary=false, include_samples=false, not_null=None, null_tokens=None, null_policy="report", try_parse_dates=false, date_formats=None, on_bad_lines="error", cancel_token=None, return_format="dict", encoding="utf8", errors="strict", row_filter=None, sample_fraction=None, sample_n=None, seed=None))]
+#[allow(clippy::too_many_arguments)]
 pub fn parse_csv_with_options(
     py: Python,
     file_path: &str,
-    has_header: bool,
+    has_header: Option<bool>,
     delimiter: &str,
     chunk_size: Option<usize>,
     infer_schema_length: Option<usize>,
+    on_progress: Option<PyObject>,
+    repair: &str,
+    absorber: Option<String>,
+    flag_repairs: bool,
+    rename: Option<&PyAny>,
+    columns: Option<Vec<String>>,
+    column_indices: Option<Vec<usize>>,
+    skip_rows: usize,
+    max_rows: Option<usize>,
+    null_values: Option<&PyAny>,
+    empty_as_null: bool,
+    include_summary: bool,
+    include_samples: bool,
+    not_null: Option<Vec<String>>,
+    null_tokens: Option<Vec<String>>,
+    null_policy: &str,
+    try_parse_dates: bool,
+    date_formats: Option<HashMap<String, String>>,
+    on_bad_lines: &str,
+    cancel_token: Option<PyRef<PyCancellationToken>>,
+    return_format: &str,
+    encoding: &str,
+    errors: &str,
+    row_filter: Option<&str>,
+    sample_fraction: Option<f64>,
+    sample_n: Option<usize>,
+    seed: Option<u64>,
 ) -> PyResult<PyObject> {
+    let format = ReturnFormat::from_name(return_format)?;
+    let sample = match (sample_fraction, sample_n) {
+        (Some(_), Some(_)) => return Err(PyValueError::new_err("Pass sample_fraction or sample_n, not both")),
+        (Some(fraction), None) => Some(RowSample::Fraction(fraction)),
+        (None, Some(count)) => Some(RowSample::Count(count)),
+        (None, None) => None,
+    };
     // Validate delimiter
-    if delimiter.len() != 1 {
-        return Err(PyValueError::new_err("Delimiter must be a single character"));
+    let auto_delimiter = delimiter.eq_ignore_ascii_case("auto");
+    if delimiter.len() != 1 && !auto_delimiter {
+        return Err(PyValueError::new_err("Delimiter must be a single character or 'auto'"));
     }
-    let delimiter_byte = delimiter.as_bytes()[0];
+    let dialect = if auto_delimiter || has_header.is_none() {
+        let dialect = py.allow_threads(|| auto::sniff_csv(file_path, SNIFF_BYTES)).map_err(|e| match e {
+            InsightoraError::ValidationError(_) => e.into(),
+            other => operation_error("Failed to sniff CSV", other),
+        })?;
+        Some(dialect)
+    } else {
+        None
+    };
+    let (delimiter_byte, quote_char) = match &dialect {
+        Some(dialect) if auto_delimiter => (dialect.delimiter, dialect.quote_char),
+        _ => (delimiter.as_bytes()[0], b'"'),
+    };
+    let has_header = has_header.or(dialect.as_ref().map(|d| d.has_header)).unwrap_or(true);
     
     // Get global config for defaults
     let global_config = get_current_config();
@@ -375,225 +1965,8878 @@ pub fn parse_csv_with_options(
         chunk_size: chunk_size.unwrap_or(global_config.chunk_size),
         has_header,
         delimiter: delimiter_byte,
-        quote_char: b'"',
+        quote_char,
         infer_schema_length: Some(infer_schema_length.unwrap_or(1000)),
+        repair: repair_options(repair, absorber, flag_repairs)?,
+        rename: rename.map(column_mapping).transpose()?,
+        columns,
+        column_indices,
+        skip_rows,
+        max_rows,
+        null_values: null_values.map(null_value_tokens).transpose()?,
+        empty_as_null,
+        not_null: not_null_rules(not_null, null_tokens, null_policy)?,
+        try_parse_dates,
+        date_formats: date_formats
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(column, format)| Ok((column, DateFormat::from_pattern(&format)?)))
+            .collect::<Result<_, InsightoraError>>()?,
+        on_bad_lines: BadLines::from_name(on_bad_lines)?,
+        encoding: CsvEncoding::from_name(encoding)?,
+        encoding_errors: EncodingErrors::from_name(errors)?,
+        row_filter: row_filter.map(Predicate::parse).transpose()?,
+        sample,
+        sample_seed: seed,
     };
+    let repair_enabled = config.repair.is_enabled();
+    let nulls_checked = !config.not_null.is_empty();
+    let dates_parsed = config.try_parse_dates || !config.date_formats.is_empty();
+    let bad_lines_mode = config.on_bad_lines;
+    let mut metrics = ExecutionMetrics::new("parse_csv_with_options");
+    csv_options_in_effect(&mut metrics, file_path, &config.repair, config.rename.as_ref(), config.columns.as_deref());
+    metrics.option("has_header", has_header);
+    metrics.option("delimiter", (delimiter_byte as char).to_string());
+    if dialect.is_some() {
+        metrics.option("sniffed", if auto_delimiter { "delimiter, quote_char, has_header" } else { "has_header" });
+    }
+    metrics.option("chunk_size", config.chunk_size);
+    metrics.option("infer_schema_length", infer_schema_length.unwrap_or(1000));
+    if let Some(indices) = &config.column_indices {
+        metrics.option("column_indices", indices.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(", "));
+    }
+    metrics.option("skip_rows", skip_rows);
+    if let Some(max_rows) = max_rows {
+        metrics.option("max_rows", max_rows);
+    }
+    match &config.null_values {
+        Some(NullValueTokens::All(tokens)) => metrics.option("null_values", tokens.join(", ")),
+        Some(NullValueTokens::Columns(columns)) => metrics.option("null_values", format!("{} columns", columns.len())),
+        None => {}
+    }
+    metrics.option("empty_as_null", empty_as_null);
+    metrics.option("try_parse_dates", try_parse_dates);
+    if !config.date_formats.is_empty() {
+        metrics.option("date_formats", format!("{} columns", config.date_formats.len()));
+    }
+    metrics.option("on_bad_lines", bad_lines_mode.name());
+    metrics.option("encoding", config.encoding.name());
+    metrics.option("errors", config.encoding_errors.name());
+    if let Some(filter) = &config.row_filter {
+        metrics.option("row_filter", &filter.source);
+    }
+    match sample {
+        Some(RowSample::Fraction(fraction)) => metrics.option("sample_fraction", fraction),
+        Some(RowSample::Count(count)) => metrics.option("sample_n", count),
+        None => {}
+    }
+    metrics.engine("parallel");
     
-    let parser = ParallelCsvParser::with_config(config);
-    let df = parser.parse(file_path)
-        .map_err(|e| PyRuntimeError::new_err(format!("Failed to parse CSV: {}", e)))?;
-    
-    // Convert DataFrame to dictionary format
-    let result = PyDict::new(py);
-    
-    // Get column names
-    let columns: Vec<String> = df.get_column_names()
-        .iter()
-        .map(|s| s.to_string())
-        .collect();
-    result.set_item("columns", columns)?;
-    
-    // Get shape
-    result.set_item("num_rows", df.height())?;
-    result.set_item("num_columns", df.width())?;
+    let progress = progress_reporter(on_progress);
+    let stages = progress.stages(&[("parse", 1.0), ("export", 1.0)]);
+    let cancel = cancel_flag(cancel_token);
+    let parser = ParallelCsvParser::with_config(config).with_progress(stages[0].clone()).with_cancel_flag(Arc::clone(&cancel));
+    let path = file_path.to_string();
+    let (df, report, nulls, dates, bad_lines, sampled) = metrics.time("parse", || run_cancellable(py, &cancel, move || parser.parse_checked(&path)))?
+        .map_err(|e| match e {
+            // Unknown projected columns, conflicting options, max_rows=0, row_filter and sample errors
+            InsightoraError::ValidationError(_) => e.into(),
+            other => operation_error("Failed to parse CSV", other),
+        })?;
     
-    // Convert data to nested lists (column-major format)
-    let mut data_columns = Vec::new();
-    for col in df.get_columns() {
-        let col_data = series_to_python_list(py, col)?;
-        data_columns.push(col_data);
+    log_warnings(py, &report.warnings)?;
+    log_warnings(py, &dates)?;
+    let skipped = bad_lines.warnings();
+    log_warnings(py, &skipped)?;
+    let result = metrics.time("export", || table_result(py, &df, format, &stages[1]))?;
+    let result = if repair_enabled { with_repair_report(py, result, &report)? } else { result };
+    let result = if nulls_checked { with_nullability_report(py, result, &nulls)? } else { result };
+    let dict = result.as_ref(py).downcast::<PyDict>()?;
+    if dates_parsed || !skipped.is_empty() {
+        let warnings: Vec<_> = dates.iter().chain(&skipped).cloned().collect();
+        dict.set_item("parse_warnings", warnings_to_py(py, &warnings, false)?)?;
     }
-    result.set_item("data", data_columns)?;
-    
-    Ok(result.into())
+    if bad_lines_mode != BadLines::Error {
+        dict.set_item("bad_line_count", bad_lines.count)?;
+        dict.set_item("bad_lines", &bad_lines.sample_lines)?;
+    }
+    if let Some(dialect) = &dialect {
+        dict.set_item("dialect", csv_dialect_to_py(py, dialect)?)?;
+    }
+    if let Some(sampled) = &sampled {
+        dict.set_item("sampled", true)?;
+        dict.set_item("sampling_rate", sampled.rate())?;
+        dict.set_item("sample_rows_read", sampled.rows_read)?;
+        dict.set_item("sample_seed", sampled.seed)?;
+    }
+    let result = if include_summary {
+        parse_metrics(&mut metrics, &df, &report, include_samples);
+        metrics.warn(&dates);
+        metrics.warn(&skipped);
+        with_summary(py, result, &metrics, include_samples)?
+    } else {
+        result
+    };
+    finish_table(py, df, format, result)
 }
 
-/// Infer schema from a CSV file without loading all data
+/// Parse CSV data held in a bytes object
 /// 
-/// This function quickly analyzes the CSV file structure and returns
-/// schema information including column names and data types.
+/// For payloads received over HTTP or read from a socket, without writing
+/// them to a temporary file first. The bytes are parsed in place; the
+/// memory limit is checked against their length.
 /// 
 /// # Arguments
-/// * `file_path` - Path to the CSV file
-/// * `_sample_size` - Number of rows to sample for inference (default: 1000, currently unused)
+/// * `data` - Uncompressed CSV text as bytes
+/// * `has_header`, `delimiter`, `chunk_size`, `infer_schema_length`,
+///   `rename`, `columns`, `column_indices`, `skip_rows`, `max_rows`,
+///   `null_values`, `empty_as_null` - As in `parse_csv_with_options`
 /// 
 /// # Returns
-/// * Dictionary with schema information
+/// * Dictionary with 'columns' and 'data'; empty input raises ValueError
 /// 
 /// # Example
 /// ```python
 /// import insightora_core
+/// import requests
 /// 
-/// schema = insightora_core.infer_csv_schema("data.csv")
-/// print(schema)
-/// # {'columns': ['name', 'age', 'salary'], 'dtypes': ['String', 'Int64', 'Float64']}
+/// response = requests.get("https://example.com/export.csv")
+/// result = insightora_core.parse_csv_bytes(response.content, delimiter=";")
 /// ```
 #[pyfunction]
-#[pyo3(signature = (file_path, _sample_size=1000))]
-pub fn infer_csv_schema(py: Python, file_path: &str, _sample_size: usize) -> PyResult<PyObject> {
-    let parser = ParallelCsvParser::new();
-    let schema = parser.infer_schema(file_path)
-        .map_err(|e| PyRuntimeError::new_err(format!("Failed to infer schema: {}", e)))?;
-    
-    // Build result dictionary
-    let result = PyDict::new(py);
-    
-    // Extract column names
-    let columns: Vec<String> = schema.iter_names().map(|s| s.to_string()).collect();
-    result.set_item("columns", columns)?;
-    
-    // Extract data types
-    let dtypes: Vec<String> = schema.iter_dtypes()
-        .map(|dt| format!("{:?}", dt))
-        .collect();
-    result.set_item("dtypes", dtypes)?;
-    
-    result.set_item("num_columns", schema.len())?;
-    
-    Ok(result.into())
+#[pyo3(signature = (data, has_header=true, delimiter=",", chunk_size=None, infer_schema_length=None, rename=None, columns=None, column_indices=None, skip_rows=0, max_rows=None, null_values=None, empty_as_null=true))]
+#[allow(clippy::too_many_arguments)]
+pub fn parse_csv_bytes(
+    py: Python,
+    data: &[u8],
+    has_header: bool,
+    delimiter: &str,
+    chunk_size: Option<usize>,
+    infer_schema_length: Option<usize>,
+    rename: Option<&PyAny>,
+    columns: Option<Vec<String>>,
+    column_indices: Option<Vec<usize>>,
+    skip_rows: usize,
+    max_rows: Option<usize>,
+    null_values: Option<&PyAny>,
+    empty_as_null: bool,
+) -> PyResult<PyObject> {
+    parse_csv_buffer(
+        py, data, has_header, delimiter, chunk_size, infer_schema_length, rename, columns, column_indices, skip_rows,
+        max_rows, null_values, empty_as_null,
+    )
 }
 
-// ============================================================================
-// Streaming CSV Parser Python Bindings
-// ============================================================================
+/// Parse CSV data held in a string
+/// 
+/// As `parse_csv_bytes`, for text that is already decoded.
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// result = insightora_core.parse_csv_string("region,amount\nnorth,10\nsouth,20\n")
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, has_header=true, delimiter=",", chunk_size=None, infer_schema_length=None, rename=None, columns=None, column_indices=None, skip_rows=0, max_rows=None, null_values=None, empty_as_null=true))]
+#[allow(clippy::too_many_arguments)]
+pub fn parse_csv_string(
+    py: Python,
+    data: &str,
+    has_header: bool,
+    delimiter: &str,
+    chunk_size: Option<usize>,
+    infer_schema_length: Option<usize>,
+    rename: Option<&PyAny>,
+    columns: Option<Vec<String>>,
+    column_indices: Option<Vec<usize>>,
+    skip_rows: usize,
+    max_rows: Option<usize>,
+    null_values: Option<&PyAny>,
+    empty_as_null: bool,
+) -> PyResult<PyObject> {
+    parse_csv_buffer(
+        py, data.as_bytes(), has_header, delimiter, chunk_size, infer_schema_length, rename, columns, column_indices,
+        skip_rows, max_rows, null_values, empty_as_null,
+    )
+}
 
-/// Parse a large CSV file using streaming mode for memory efficiency
+/// Helper function behind `parse_csv_bytes` and `parse_csv_string`
+#[allow(clippy::too_many_arguments)]
+fn parse_csv_buffer(
+    py: Python,
+    data: &[u8],
+    has_header: bool,
+    delimiter: &str,
+    chunk_size: Option<usize>,
+    infer_schema_length: Option<usize>,
+    rename: Option<&PyAny>,
+    columns: Option<Vec<String>>,
+    column_indices: Option<Vec<usize>>,
+    skip_rows: usize,
+    max_rows: Option<usize>,
+    null_values: Option<&PyAny>,
+    empty_as_null: bool,
+) -> PyResult<PyObject> {
+    if delimiter.len() != 1 {
+        return Err(PyValueError::new_err("Delimiter must be a single character"));
+    }
+    let config = CsvParserConfig {
+        chunk_size: chunk_size.unwrap_or(get_current_config().chunk_size),
+        has_header,
+        delimiter: delimiter.as_bytes()[0],
+        infer_schema_length: Some(infer_schema_length.unwrap_or(1000)),
+        rename: rename.map(column_mapping).transpose()?,
+        columns,
+        column_indices,
+        skip_rows,
+        max_rows,
+        null_values: null_values.map(null_value_tokens).transpose()?,
+        empty_as_null,
+        ..Default::default()
+    };
+    let df = ParallelCsvParser::with_config(config).parse_bytes(data)
+        .map_err(|e| match e {
+            // Empty input, unknown projected columns and max_rows=0
+            InsightoraError::ParseError(_) | InsightoraError::ValidationError(_) => e.into(),
+            other => operation_error("Failed to parse CSV data", other),
+        })?;
+    dataframe_to_pydict(py, &df)
+}
+
+/// Build a sidecar min/max index for repeated filtered reads of a CSV file
 /// 
-/// This function is optimized for files larger than 1GB and uses
-/// memory-efficient streaming to avoid loading the entire file at once.
+/// The file is read once in record-aligned blocks; the min, max and null
+/// count of each indexed column per block go to `<file_path>.idx.json`.
+/// `parse_csv_filtered` uses the index while the file's size, modification
+/// time and content hash are unchanged.
 /// 
 /// # Arguments
-/// * `file_path` - Path to the CSV file
-/// * `chunk_size` - Number of rows to process per chunk (default: 100000)
-/// * `memory_limit_mb` - Memory limit in MB (default: 1024)
+/// * `file_path` - Path to an uncompressed CSV file
+/// * `columns` - Columns to index, by header name; ISO dates are indexed as text
+/// * `block_bytes` - Approximate block size in bytes (default: 4 MiB)
+/// * `has_header` - Whether the CSV has a header row (default: True)
+/// * `delimiter` - Field delimiter character (default: ',')
 /// 
 /// # Returns
-/// * Dictionary with 'columns' and 'data'
+/// * Dictionary with `index_path`, `columns`, `blocks` and `rows`;
+///   `index_path` is None when the configured path policy doesn't allow the
+///   sidecar file, and the index is kept in memory for this process instead
 /// 
 /// # Example
 /// ```python
 /// import insightora_core
-/// import pandas as pd
 /// 
-/// # Parse large CSV file with streaming
-/// result = insightora_core.parse_csv_streaming(
-///     "large_file.csv",
-///     chunk_size=50000,
-///     memory_limit_mb=512
-/// )
-/// df = pd.DataFrame(result['data'], columns=result['columns'])
+/// insightora_core.build_file_index("events.csv", ["date"])
+/// result = insightora_core.parse_csv_filtered("events.csv", "date >= '2024-06-01' and date < '2024-06-08'")
+/// print(result["index"]["blocks_skipped"], "of", result["index"]["blocks_total"], "blocks skipped")
 /// ```
 #[pyfunction]
-#[pyo3(signature = (file_path, chunk_size=100000, memory_limit_mb=1024))]
-pub fn parse_csv_streaming(
+#[pyo3(signature = (file_path, columns, block_bytes=file_index::DEFAULT_BLOCK_BYTES, has_header=true, delimiter=","))]
+pub fn build_file_index(
     py: Python,
     file_path: &str,
-    chunk_size: usize,
-    memory_limit_mb: usize,
+    columns: Vec<String>,
+    block_bytes: usize,
+    has_header: bool,
+    delimiter: &str,
 ) -> PyResult<PyObject> {
-    let config = StreamingCsvConfig {
-        chunk_size,
-        memory_limit_mb,
-        has_header: true,
-        delimiter: b',',
-    };
-    
-    let parser = StreamingCsvParser::with_config(config);
-    let df = parser.parse_streaming(file_path)
-        .map_err(|e| PyRuntimeError::new_err(format!("Failed to parse CSV in streaming mode: {}", e)))?;
-    
-    // Convert DataFrame to dictionary format
-    let result = PyDict::new(py);
-    
-    // Get column names
-    let columns: Vec<String> = df.get_column_names()
-        .iter()
-        .map(|s| s.to_string())
-        .collect();
-    result.set_item("columns", columns)?;
-    
-    // Get shape
-    result.set_item("num_rows", df.height())?;
-    result.set_item("num_columns", df.width())?;
-    
-    // Convert data to nested lists (column-major format)
-    let mut data_columns = Vec::new();
-    for col in df.get_columns() {
-        let col_data = series_to_python_list(py, col)?;
-        data_columns.push(col_data);
+    if delimiter.len() != 1 {
+        return Err(PyValueError::new_err("Delimiter must be a single character"));
     }
-    result.set_item("data", data_columns)?;
+    let parser = ParallelCsvParser::with_config(CsvParserConfig {
+        has_header,
+        delimiter: delimiter.as_bytes()[0],
+        ..Default::default()
+    });
+    let index = py.allow_threads(|| parser.build_index(file_path, &columns, block_bytes))
+        .map_err(|e| match e {
+            InsightoraError::ValidationError(_) => e.into(),
+            other => operation_error("Failed to build file index", other),
+        })?;
     
+    let result = PyDict::new(py);
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    let index_path = file_index::stored_index_path(std::path::Path::new(file_path))?;
+    result.set_item("index_path", index_path.map(|path| path.to_string_lossy().to_string()))?;
+    result.set_item("columns", &index.columns)?;
+    result.set_item("blocks", index.blocks.len())?;
+    result.set_item("rows", index.rows())?;
     Ok(result.into())
 }
 
-/// Check if streaming mode is recommended for a CSV file
+/// Parse the rows of a CSV file matching a condition
 /// 
-/// This function analyzes the file size and estimates memory usage
-/// to determine if streaming mode should be used.
+/// With an index from `build_file_index` that still matches the file,
+/// blocks whose min/max statistics rule the condition out are skipped
+/// without being read; otherwise the whole file is parsed and filtered.
 /// 
 /// # Arguments
 /// * `file_path` - Path to the CSV file
-/// * `memory_limit_mb` - Memory limit in MB (default: 1024)
+/// * `predicate` - Condition in the `update_where` grammar, e.g.
+///   "date >= '2024-06-01' and amount > 100"; comparisons of an indexed
+///   column with a literal, null tests, `and` and `or` can skip blocks
+/// * `has_header` - Whether the CSV has a header row (default: True)
+/// * `delimiter` - Field delimiter character (default: ',')
+/// * `rename` / `columns` / `null_values` - As in `parse_csv_with_options`;
+///   the condition uses the new names and may use columns not kept
 /// 
 /// # Returns
-/// * Dictionary with recommendation and estimated memory usage
+/// * Result dictionary plus `index`: `used`, `reason` (why it wasn't used,
+///   or None), `blocks_total`, `blocks_read` and `blocks_skipped`
+#[pyfunction]
+#[pyo3(signature = (file_path, predicate, has_header=true, delimiter=",", rename=None, columns=None, null_values=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn parse_csv_filtered(
+    py: Python,
+    file_path: &str,
+    predicate: &str,
+    has_header: bool,
+    delimiter: &str,
+    rename: Option<&PyAny>,
+    columns: Option<Vec<String>>,
+    null_values: Option<&PyAny>,
+) -> PyResult<PyObject> {
+    if delimiter.len() != 1 {
+        return Err(PyValueError::new_err("Delimiter must be a single character"));
+    }
+    let predicate = Predicate::parse(predicate)?;
+    let parser = ParallelCsvParser::with_config(CsvParserConfig {
+        chunk_size: get_current_config().chunk_size,
+        has_header,
+        delimiter: delimiter.as_bytes()[0],
+        rename: rename.map(column_mapping).transpose()?,
+        columns,
+        null_values: null_values.map(null_value_tokens).transpose()?,
+        ..Default::default()
+    });
+    let (df, report) = py.allow_threads(|| parser.parse_filtered(file_path, &predicate))
+        .map_err(|e| match e {
+            InsightoraError::ValidationError(_) => e.into(),
+            other => operation_error("Failed to parse CSV", other),
+        })?;
+    
+    let result = dataframe_to_pydict(py, &df)?;
+    let index = PyDict::new(py);
+    index.set_item("used", report.index_used)?;
+    index.set_item("reason", report.reason)?;
+    index.set_item("blocks_total", report.blocks_total)?;
+    index.set_item("blocks_read", report.blocks_read)?;
+    index.set_item("blocks_skipped", report.blocks_skipped)?;
+    result.as_ref(py).downcast::<PyDict>()?.set_item("index", index)?;
+    Ok(result)
+}
+
+/// Parse every CSV file matching a glob pattern into one table
+/// 
+/// The pattern is expanded in Rust and the files are parsed in parallel
+/// with the GIL released, then concatenated in path order before anything
+/// is converted to Python. Files must have the same columns; a column's
+/// type may differ only where it widens (integers to floats, or no values
+/// in one file). Faster than calling `parse_csv` per file in a loop.
+/// 
+/// # Arguments
+/// * `pattern` - Glob pattern, e.g. "exports/events_2024-*.csv"; `**`
+///   matches any number of directories
+/// * `include_filename_column` - Append a `source_file` column with the
+///   path each row came from (default: False)
+/// * `**options` - has_header, delimiter, quote_char, infer_schema_length,
+///   rename, columns, column_indices, skip_rows, max_rows, null_values and
+///   empty_as_null, as in `parse_csv_with_options`; max_rows applies per file
+/// 
+/// # Returns
+/// * Dictionary with 'columns' and 'data' plus 'files': `path` and `rows`
+///   per file. A pattern matching no files raises ValueError; files whose
+///   columns conflict raise SchemaError naming both files and the column
 /// 
 /// # Example
 /// ```python
 /// import insightora_core
 /// 
-/// info = insightora_core.should_use_streaming("large_file.csv")
-/// if info['recommended']:
-///     print(f"Streaming recommended. Estimated memory: {info['estimated_memory_mb']}MB")
+/// result = insightora_core.parse_csv_glob("exports/events_2024-*.csv", include_filename_column=True, delimiter=";")
+/// print(len(result["files"]), "files")
 /// ```
 #[pyfunction]
-#[pyo3(signature = (file_path, memory_limit_mb=1024))]
-pub fn should_use_streaming(
+#[pyo3(signature = (pattern, include_filename_column=false, **options))]
+pub fn parse_csv_glob(
     py: Python,
-    file_path: &str,
-    memory_limit_mb: usize,
+    pattern: &str,
+    include_filename_column: bool,
+    options: Option<&PyDict>,
 ) -> PyResult<PyObject> {
-    let config = StreamingCsvConfig {
-        memory_limit_mb,
-        ..Default::default()
-    };
-    
-    let parser = StreamingCsvParser::with_config(config);
-    
-    let estimated_memory = parser.estimate_memory_usage(file_path)
-        .map_err(|e| PyRuntimeError::new_err(format!("Failed to estimate memory: {}", e)))?;
-    
-    let recommended = parser.should_use_streaming(file_path)
-        .map_err(|e| PyRuntimeError::new_err(format!("Failed to check streaming recommendation: {}", e)))?;
+    let config = parse_options_from_dict(options)?;
+    let result = py.allow_threads(|| csv_glob::parse_csv_glob(pattern, &config, include_filename_column))
+        .map_err(|e| match e {
+            InsightoraError::ValidationError(_) => e.into(),
+            other => operation_error("Failed to parse CSV files", other),
+        })?;
     
-    let result = PyDict::new(py);
-    result.set_item("recommended", recommended)?;
-    result.set_item("estimated_memory_mb", estimated_memory)?;
+    let output = dataframe_to_pydict(py, &result.data)?;
+    let files = PyList::empty(py);
+    for (path, rows) in &result.files {
+        let entry = PyDict::new(py);
+        entry.set_item("path", path)?;
+        entry.set_item("rows", rows)?;
+        files.append(entry)?;
+    }
+    output.as_ref(py).downcast::<PyDict>()?.set_item("files", files)?;
+    Ok(output)
+}
+
+/// Count the data rows in a CSV file without parsing it
+/// 
+/// Newlines are counted in 1 MB buffers with the GIL released, so this is
+/// cheap enough to size a progress bar before a long parse; large
+/// uncompressed files are counted in parallel. Blank lines count as rows.
+/// 
+/// # Arguments
+/// * `file_path` - Path to the CSV file, optionally gzip or zstd compressed
+/// * `has_header` - Leave the first record out of the count (default: True)
+/// * `respect_quotes` - Treat newlines inside double quotes as part of the
+///   field (default: True); False counts physical lines and is slightly
+///   faster
+/// 
+/// # Returns
+/// * Number of rows
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// total = insightora_core.count_csv_rows("large_file.csv")
+/// print(f"Parsing {total:,} rows")
+/// ```
+#[pyfunction]
+#[pyo3(signature = (file_path, has_header=true, respect_quotes=true))]
+pub fn count_csv_rows(py: Python, file_path: &str, has_header: bool, respect_quotes: bool) -> PyResult<usize> {
+    let parser = ParallelCsvParser::new();
+    let records = py.allow_threads(|| parser.count_lines(file_path, respect_quotes))
+        .map_err(|e| operation_error("Failed to count CSV rows", e))?;
+    Ok(records.saturating_sub(has_header as usize))
+}
+
+/// Helper function to read `null_values`: tokens for every column, or a dict
+/// of column -> token or list of tokens
+fn null_value_tokens(value: &PyAny) -> PyResult<NullValueTokens> {
+    match value.downcast::<PyDict>() {
+        Ok(dict) => Ok(NullValueTokens::Columns(
+            dict.iter()
+                .map(|(column, tokens)| Ok((column.extract()?, extract_one_or_many(tokens)?)))
+                .collect::<PyResult<_>>()?,
+        )),
+        Err(_) => Ok(NullValueTokens::All(extract_one_or_many(value)?)),
+    }
+}
+
+/// Helper function to build CSV repair options from binding arguments
+fn repair_options(repair: &str, absorber: Option<String>, flag_repairs: bool) -> PyResult<CsvRepairOptions> {
+    let mode = CsvRepair::from_name(repair)?;
+    if mode == CsvRepair::None && (absorber.is_some() || flag_repairs) {
+        return Err(PyValueError::new_err("absorber and flag_repairs require repair='column_count'"));
+    }
+    Ok(CsvRepairOptions {
+        mode,
+        absorber,
+        flag_repairs,
+        ..Default::default()
+    })
+}
+
+/// Helper function to build NOT NULL rules from binding arguments
+fn not_null_rules(not_null: Option<Vec<String>>, null_tokens: Option<Vec<String>>, null_policy: &str) -> PyResult<NotNullRules> {
+    let policy = NullPolicy::from_name(null_policy)?;
+    let Some(columns) = not_null else {
+        if null_tokens.is_some() || policy != NullPolicy::Report {
+            return Err(PyValueError::new_err("null_tokens and null_policy require not_null columns"));
+        }
+        return Ok(NotNullRules::default());
+    };
+    Ok(NotNullRules::new(columns)
+        .with_null_tokens(null_tokens.unwrap_or_default())
+        .with_policy(policy))
+}
+
+/// Helper function to attach a NOT NULL report to a result dictionary
+fn with_nullability_report(py: Python, result: PyObject, report: &NullabilityReport) -> PyResult<PyObject> {
+    let violations = PyList::empty(py);
+    for violation in &report.violations {
+        let record = PyDict::new(py);
+        record.set_item("column", &violation.column)?;
+        record.set_item("count", violation.count)?;
+        record.set_item("sample_rows", &violation.sample_rows)?;
+        violations.append(record)?;
+    }
+    let summary = PyDict::new(py);
+    summary.set_item("policy", report.policy.name())?;
+    summary.set_item("rows_checked", report.rows_checked)?;
+    summary.set_item("violations", violations)?;
+    result.as_ref(py).downcast::<PyDict>()?.set_item("nullability", summary)?;
+    Ok(result)
+}
+
+/// Helper function to read a rename mapping: a dict of source -> target
+/// names, a list of names by position, or a column dictionary (a
+/// ColumnDictionary, or a dict of canonical name -> list of aliases)
+fn column_mapping(value: &PyAny) -> PyResult<ColumnMapping> {
+    if let Ok(dictionary) = value.extract::<PyRef<PyColumnDictionary>>() {
+        return Ok(ColumnMapping::Dictionary(dictionary.inner.clone()));
+    }
+    if let Ok(dict) = value.downcast::<PyDict>() {
+        if dict.values().iter().any(|target| !target.is_instance_of::<pyo3::types::PyString>()) {
+            return Ok(ColumnMapping::Dictionary(column_dictionary(dict, None)?));
+        }
+        let pairs = dict
+            .iter()
+            .map(|(source, target)| Ok((source.extract()?, target.extract()?)))
+            .collect::<PyResult<Vec<(String, String)>>>()?;
+        return Ok(ColumnMapping::Names(pairs));
+    }
+    match value.extract::<Vec<String>>() {
+        Ok(names) if !value.is_instance_of::<pyo3::types::PyString>() => Ok(ColumnMapping::Positions(names)),
+        _ => Err(PyTypeError::new_err(
+            "rename must be a dict of column names, a list of names by position or a column dictionary",
+        )),
+    }
+}
+
+/// Helper function to attach a CSV repair report to a result dictionary
+fn with_repair_report(py: Python, result: PyObject, report: &RepairReport) -> PyResult<PyObject> {
+    let repairs = PyList::empty(py);
+    for repair in &report.repairs {
+        let record = PyDict::new(py);
+        record.set_item("line", repair.line)?;
+        record.set_item("fields", repair.fields)?;
+        record.set_item("value", &repair.value)?;
+        repairs.append(record)?;
+    }
+    let summary = PyDict::new(py);
+    summary.set_item("mode", report.mode.name())?;
+    summary.set_item("absorber", &report.absorber)?;
+    summary.set_item("rows_repaired", report.rows_repaired)?;
+    summary.set_item("repairs", repairs)?;
+    result.as_ref(py).downcast::<PyDict>()?.set_item("repair", summary)?;
+    Ok(result)
+}
+
+/// Helper function to record the CSV options shared by the parse bindings
+fn csv_options_in_effect(
+    metrics: &mut ExecutionMetrics,
+    file_path: &str,
+    repair: &CsvRepairOptions,
+    rename: Option<&ColumnMapping>,
+    columns: Option<&[String]>,
+) {
+    metrics.option("file_path", file_path);
+    metrics.option("repair", repair.mode.name());
+    let renamed = match rename {
+        Some(ColumnMapping::Names(pairs)) => pairs.len(),
+        Some(ColumnMapping::Positions(names)) => names.len(),
+        Some(ColumnMapping::Dictionary(dictionary)) => dictionary.len(),
+        None => 0,
+    };
+    metrics.option("renamed_columns", renamed);
+    match columns {
+        Some(columns) => metrics.option("columns", columns.join(", ")),
+        None => metrics.option("columns", "all"),
+    }
+}
+
+/// Helper function to record row accounting for a finished parse
+fn parse_metrics(metrics: &mut ExecutionMetrics, df: &polars::prelude::DataFrame, report: &RepairReport, include_samples: bool) {
+    metrics.rows(df.height(), df.height());
+    metrics.chunks(df.n_chunks());
+    metrics.observe_frame(df);
+    metrics.adjusted("rows_repaired", report.rows_repaired);
+    metrics.warn(&report.warnings);
+    // Empty and unparseable fields are read as null
+    metrics.adjusted("null_cells", df.get_columns().iter().map(|s| s.null_count()).sum());
+    if include_samples {
+        for repair in report.repairs.iter().take(SUMMARY_SAMPLES) {
+            metrics.sample(&format!("repaired_line_{}", repair.line), &repair.value);
+        }
+        sample_first_row(metrics, df);
+    }
+}
+
+/// Values shown per sample kind with `include_samples=True`
+const SUMMARY_SAMPLES: usize = 5;
+
+/// Helper function to record the first result row as samples
+fn sample_first_row(metrics: &mut ExecutionMetrics, df: &polars::prelude::DataFrame) {
+    if let Ok(row) = df.get_row(0) {
+        for (name, value) in df.get_column_names().iter().zip(row.0.iter()).take(SUMMARY_SAMPLES * 4) {
+            metrics.sample(&format!("first_row.{}", name), value);
+        }
+    }
+}
+
+/// Helper function to attach an execution summary to a result dictionary
+/// 
+/// The summary holds options, counts, timings and sizes only, so it can be
+/// pasted into a support ticket. `fast_paths` maps each fast path to whether
+/// it is available and `fallbacks` lists those the call fell back from.
+/// `warnings` lists aggregated warnings (`category`, `column`, `count`,
+/// `message`). Data values appear under `samples`, and as warning
+/// `examples`, only when `include_samples` is set.
+fn with_summary(py: Python, result: PyObject, metrics: &ExecutionMetrics, include_samples: bool) -> PyResult<PyObject> {
+    let pairs = |items: &[(String, usize)]| -> PyResult<&PyDict> {
+        let dict = PyDict::new(py);
+        for (key, value) in items {
+            dict.set_item(key, value)?;
+        }
+        Ok(dict)
+    };
+    let millis = |duration: std::time::Duration| duration.as_secs_f64() * 1000.0;
+    
+    let options = PyDict::new(py);
+    for (name, value) in metrics.options() {
+        options.set_item(name, value)?;
+    }
+    let rows = PyDict::new(py);
+    rows.set_item("read", metrics.rows_read())?;
+    rows.set_item("kept", metrics.rows_kept())?;
+    rows.set_item("dropped", metrics.rows_dropped())?;
+    rows.set_item("dropped_by_reason", pairs(metrics.drop_reasons())?)?;
+    rows.set_item("adjusted", pairs(metrics.adjustments())?)?;
+    let phases = PyDict::new(py);
+    for (name, duration) in metrics.phases() {
+        phases.set_item(name, millis(*duration))?;
+    }
+    
+    let summary = PyDict::new(py);
+    summary.set_item("operation", metrics.operation())?;
+    summary.set_item("engine", metrics.engine_name())?;
+    summary.set_item("threads", rayon::current_num_threads())?;
+    summary.set_item("options", options)?;
+    summary.set_item("chunks", metrics.chunk_count())?;
+    summary.set_item("rows", rows)?;
+    summary.set_item("phases_ms", phases)?;
+    summary.set_item("elapsed_ms", millis(metrics.elapsed()))?;
+    summary.set_item("peak_memory_bytes", metrics.peak_memory_bytes())?;
+    let fast_paths = PyDict::new(py);
+    for capability in capabilities::capabilities() {
+        fast_paths.set_item(capability.path.name(), capability.available)?;
+    }
+    summary.set_item("fast_paths", fast_paths)?;
+    summary.set_item("fallbacks", degradations_to_py(py, &metrics.degradations())?)?;
+    summary.set_item("warnings", warnings_to_py(py, metrics.warnings(), include_samples)?)?;
+    if include_samples {
+        let samples = PyDict::new(py);
+        for (label, value) in metrics.samples() {
+            samples.set_item(label, value)?;
+        }
+        summary.set_item("samples", samples)?;
+    }
+    result.as_ref(py).downcast::<PyDict>()?.set_item("summary", summary)?;
+    Ok(result)
+}
+
+/// Infer schema from a CSV file without loading all data
+/// 
+/// This function quickly analyzes the CSV file structure and returns
+/// schema information including column names and data types. With
+/// `id_detection` enabled, ID-like columns (order numbers, zip codes) are
+/// flagged so they can be kept out of numeric summaries.
+/// 
+/// # Arguments
+/// * `file_path` - Path to the CSV file; compressed files are only decompressed as
+///   far as the sampled rows
+/// * `sample_size` - Number of rows sampled for type inference and identifier
+///   detection (default: 1000). 0 classifies every row in one streaming
+///   pass, without holding the file in memory, so a "N/A" two million rows
+///   in is seen; identifiers are then detected from the first 1000 rows
+/// * `id_detection` - Flag identifier-like columns (default: True)
+/// * `identifiers` - Optional dict of column name to bool overriding the detection
+/// * `rename` - Rename mapping as in `parse_csv_with_options`; the schema and
+///   identifier decisions use the new names
+/// 
+/// # Returns
+/// * Dictionary with schema information: `columns`, `dtypes` and
+///   `num_columns`; `rows_scanned` and `complete` (whether every row was
+///   read); per column, in the same order, `null_counts` (empty fields in
+///   the rows scanned), `distinct_types` (how many of integer, float,
+///   boolean and text were seen), `observed_types` (their names) and
+///   `confidence` (the share of values of the column's main kind, numbers,
+///   booleans or text, lowered by 3/n for n values when the scan stopped
+///   early); and `demoted`, one record per mostly numeric column read as
+///   text, with `column`, and `values` and `rows` (1-based data rows) of up
+///   to 5 values that made it text. With `id_detection`, also
+///   `identifiers` (flagged column names) and `id_detection` (one record per
+///   column with `is_identifier`, `score`, `overridden` and `reasons`)
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// schema = insightora_core.infer_csv_schema("orders.csv")
+/// print(schema["dtypes"])
+/// # ['Int64', 'Int64', 'Float64']
+/// print(schema["identifiers"])
+/// # ['order_id', 'zip']
+/// 
+/// # Every row, to find the values that would break a typed parse
+/// schema = insightora_core.infer_csv_schema("huge.csv", sample_size=0)
+/// for demoted in schema["demoted"]:
+///     print(demoted["column"], list(zip(demoted["rows"], demoted["values"])))
+/// ```
+#[pyfunction]
+#[pyo3(signature = (file_path, sample_size=1000, id_detection=true, identifiers=None, rename=None))]
+pub fn infer_csv_schema(
+    py: Python,
+    file_path: &str,
+    sample_size: usize,
+    id_detection: bool,
+    identifiers: Option<HashMap<String, bool>>,
+    rename: Option<&PyAny>,
+) -> PyResult<PyObject> {
+    let config = CsvParserConfig {
+        infer_schema_length: (sample_size > 0).then_some(sample_size),
+        rename: rename.map(column_mapping).transpose()?,
+        ..Default::default()
+    };
+    // A full scan streams; identifiers are still detected from a sample
+    let id_parser = ParallelCsvParser::with_config(CsvParserConfig {
+        infer_schema_length: Some(config.infer_schema_length.unwrap_or(1000)),
+        ..config.clone()
+    });
+    let parser = ParallelCsvParser::with_config(config);
+    let inferred = py.allow_threads(|| {
+        let report = parser.infer_schema_report(file_path)?;
+        let decisions = if id_detection {
+            Some(id_parser.infer_schema_with_identifiers(file_path, &id_detection_config(identifiers))?.1)
+        } else {
+            None
+        };
+        Ok::<_, InsightoraError>((report, decisions))
+    });
+    let (report, decisions) = inferred.map_err(|e| operation_error("Failed to infer schema", e))?;
+    
+    // Build result dictionary
+    let result = PyDict::new(py);
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    
+    // Extract column names
+    let columns: Vec<&str> = report.columns.iter().map(|c| c.name.as_str()).collect();
+    result.set_item("columns", columns)?;
+    
+    // Extract data types
+    let dtypes: Vec<String> = report.columns.iter()
+        .map(|c| format!("{:?}", c.dtype))
+        .collect();
+    result.set_item("dtypes", dtypes)?;
+    
+    result.set_item("num_columns", report.columns.len())?;
+    result.set_item("rows_scanned", report.rows_scanned)?;
+    result.set_item("complete", report.complete)?;
+    result.set_item("null_counts", report.columns.iter().map(|c| c.null_count).collect::<Vec<_>>())?;
+    result.set_item("distinct_types", report.columns.iter().map(|c| c.distinct_types()).collect::<Vec<_>>())?;
+    let observed: Vec<Vec<&str>> = report.columns.iter()
+        .map(|c| c.type_counts.iter().map(|(kind, _)| kind.name()).collect())
+        .collect();
+    result.set_item("observed_types", observed)?;
+    result.set_item("confidence", report.columns.iter().map(|c| c.confidence).collect::<Vec<_>>())?;
+    let demoted = PyList::empty(py);
+    for column in report.columns.iter().filter(|c| !c.offending_values.is_empty()) {
+        let record = PyDict::new(py);
+        record.set_item("column", &column.name)?;
+        record.set_item("values", column.offending_values.iter().map(|(_, value)| value).collect::<Vec<_>>())?;
+        record.set_item("rows", column.offending_values.iter().map(|(row, _)| *row).collect::<Vec<_>>())?;
+        demoted.append(record)?;
+    }
+    result.set_item("demoted", demoted)?;
+    
+    if let Some(decisions) = decisions {
+        result.set_item("identifiers", identifier_columns(&decisions))?;
+        result.set_item("id_detection", id_decisions_to_py(py, &decisions)?)?;
+    }
+    
+    Ok(result.into())
+}
+
+/// Helper function to build identifier detection settings from user overrides
+fn id_detection_config(overrides: Option<HashMap<String, bool>>) -> IdDetectionConfig {
+    IdDetectionConfig {
+        overrides: overrides.unwrap_or_default(),
+        ..Default::default()
+    }
+}
+
+/// Helper function to convert identifier decisions to a list of records
+fn id_decisions_to_py<'py>(py: Python<'py>, decisions: &[IdentifierDecision]) -> PyResult<&'py PyList> {
+    let records = PyList::empty(py);
+    for decision in decisions {
+        let record = PyDict::new(py);
+        record.set_item("column", &decision.column)?;
+        record.set_item("is_identifier", decision.is_identifier)?;
+        record.set_item("score", decision.score)?;
+        record.set_item("overridden", decision.overridden)?;
+        record.set_item("reasons", &decision.reasons)?;
+        records.append(record)?;
+    }
+    Ok(records)
+}
+
+/// Parse a CSV file with the types of an earlier inference
+/// 
+/// Reading a directory of daily exports this way skips type inference on
+/// every file after the first, and keeps each column's type from drifting
+/// between days, e.g. a column that is integers one day and has a decimal
+/// the next. A value that doesn't fit its column's type raises instead.
+/// 
+/// # Arguments
+/// * `file_path` - Path to the CSV file
+/// * `schema` - The dict returned by `infer_csv_schema` (or any result with
+///   `columns` and `dtypes`), or a dict mapping column names to dtype names
+///   ("i64", "Float64", "str", ...); it must list every column of the file,
+///   in order, by its name after `rename`
+/// * `**options` - has_header, delimiter, quote_char, rename, columns,
+///   column_indices, skip_rows, max_rows, null_values and empty_as_null, as
+///   in `parse_csv_with_options`; null_values must be one list for every column
+/// 
+/// # Returns
+/// * Dictionary with 'columns' and 'data'. A schema whose columns differ from
+///   the file's raises SchemaError listing both
+/// 
+/// # Example
+/// ```python
+/// import glob
+/// import insightora_core
+/// 
+/// days = sorted(glob.glob("exports/*.csv"))
+/// schema = insightora_core.infer_csv_schema(days[0], sample_size=0, id_detection=False)
+/// tables = [insightora_core.parse_csv_with_schema(day, schema) for day in days]
+/// ```
+#[pyfunction]
+#[pyo3(signature = (file_path, schema, **options))]
+pub fn parse_csv_with_schema(py: Python, file_path: &str, schema: &PyDict, options: Option<&PyDict>) -> PyResult<PyObject> {
+    let columns = match (schema.get_item("columns")?, schema.get_item("dtypes")?) {
+        (Some(names), Some(dtypes)) => {
+            let (names, dtypes): (Vec<String>, Vec<String>) = (names.extract()?, dtypes.extract()?);
+            if names.len() != dtypes.len() {
+                return Err(PyValueError::new_err(format!(
+                    "schema has {} columns but {} dtypes", names.len(), dtypes.len()
+                )));
+            }
+            names
+                .into_iter()
+                .zip(dtypes)
+                .map(|(name, dtype)| Ok((name, dtype_from_name(&dtype)?)))
+                .collect::<PyResult<Vec<_>>>()?
+        }
+        _ => declared_schema(schema)?,
+    };
+    let schema: polars::prelude::Schema = columns
+        .into_iter()
+        .map(|(name, dtype)| polars::prelude::Field::new(&name, dtype))
+        .collect();
+    let parser = ParallelCsvParser::with_config(parse_options_from_dict(options)?);
+    let df = py.allow_threads(|| parser.parse_with_schema(file_path, &schema))
+        .map_err(|e| match e {
+            InsightoraError::ValidationError(_) => e.into(),
+            other => operation_error("Failed to parse CSV", other),
+        })?;
+    dataframe_to_pydict(py, &df)
+}
+
+/// Parse a CSV file, inferring column types from more rows than usual
+/// 
+/// The default inference looks at the first 1000 rows; a larger sample
+/// catches a column that turns to text or decimals further in, at the cost
+/// of a slower parse.
+/// 
+/// # Arguments
+/// * `file_path` - Path to the CSV file
+/// * `sample_size` - Rows used for type inference (default: 10000)
+/// * `**options` - As for `parse_csv_with_schema`
+/// 
+/// # Returns
+/// * Dictionary with 'columns' and 'data'
+#[pyfunction]
+#[pyo3(signature = (file_path, sample_size=10_000, **options))]
+pub fn parse_csv_with_inference(py: Python, file_path: &str, sample_size: usize, options: Option<&PyDict>) -> PyResult<PyObject> {
+    let parser = ParallelCsvParser::with_config(parse_options_from_dict(options)?);
+    let df = py.allow_threads(|| parser.parse_with_inference(file_path, sample_size))
+        .map_err(|e| match e {
+            InsightoraError::ValidationError(_) => e.into(),
+            other => operation_error("Failed to parse CSV", other),
+        })?;
+    dataframe_to_pydict(py, &df)
+}
+
+/// Write data to a CSV file with a configurable dialect
+/// 
+/// Columns are formatted in parallel. With the default `escape="double"` and
+/// `quote_style` of "necessary", "always" or "non_numeric", the output reads
+/// back through `parse_csv_with_options` with the same delimiter.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary (as returned by `parse_csv`)
+/// * `file_path` - Destination path
+/// * `delimiter` - Field delimiter character (default: ',')
+/// * `include_header` - Write the column names first (default: True)
+/// * `float_precision` - Fixed digits after the decimal point for floats (default: shortest exact form)
+/// * `quote_style` - "necessary", "always", "never" or "non_numeric" (default: "necessary")
+/// * `escape` - "double" (`""`) or "backslash" (`\"`) for quotes inside fields (default: "double")
+/// * `null_value` - Text written for nulls (default: empty)
+/// * `line_terminator` - Row separator (default: "\n")
+/// * `datetime_format` - strftime format for every date/datetime column, or a
+///   dict mapping column names to formats (default: ISO 8601)
+/// * `quote_char` - Quote character (default: '"')
+/// * `formats` - Dict mapping columns to display format specs (see
+///   `format_values`); applied to the written text only
+/// * `thousands_separator` - Digit group separator for `formats` (default: ",")
+/// * `decimal_separator` - Decimal mark for `formats` (default: ".")
+/// * `append` - Add the rows to the end of an existing file, without a
+///   second header; its header must match the columns (default: False)
+/// 
+/// A `file_path` ending in ".gz" is written gzip-compressed.
+/// 
+/// # Returns
+/// * Number of rows written
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// result = insightora_core.parse_csv("orders.csv")
+/// insightora_core.write_csv(
+///     result,
+///     "orders_export.csv",
+///     delimiter=";",
+///     null_value="NULL",
+///     line_terminator="\r\n",
+///     datetime_format={"shipped_at": "%d/%m/%Y %H:%M"},
+///     formats={"amount": {"type": "currency", "decimals": 2}},
+/// )
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, file_path, delimiter=",", include_header=true, float_precision=None, quote_style="necessary", escape="double", null_value="", line_terminator="\n", datetime_format=None, quote_char="\"", formats=None, thousands_separator=",", decimal_separator=".", append=false))]
+#[allow(clippy::too_many_arguments)]
+pub fn write_csv(
+    py: Python,
+    data: &PyAny,
+    file_path: &str,
+    delimiter: &str,
+    include_header: bool,
+    float_precision: Option<usize>,
+    quote_style: &str,
+    escape: &str,
+    null_value: &str,
+    line_terminator: &str,
+    datetime_format: Option<&PyAny>,
+    quote_char: &str,
+    formats: Option<&PyDict>,
+    thousands_separator: &str,
+    decimal_separator: &str,
+    append: bool,
+) -> PyResult<usize> {
+    if delimiter.len() != 1 {
+        return Err(PyValueError::new_err("Delimiter must be a single character"));
+    }
+    if quote_char.len() != 1 {
+        return Err(PyValueError::new_err("quote_char must be a single character"));
+    }
+    let df = pydict_to_dataframe(data)?;
+
+    use polars::prelude::DataType;
+
+    let datetime_formats: HashMap<String, String> = match datetime_format {
+        None => HashMap::new(),
+        Some(format) => match format.extract::<String>() {
+            Ok(format) => df
+                .get_columns()
+                .iter()
+                .filter(|s| matches!(s.dtype(), DataType::Date | DataType::Datetime(_, _)))
+                .map(|s| (s.name().to_string(), format.clone()))
+                .collect(),
+            Err(_) => format.extract()?,
+        },
+    };
+
+    let options = CsvWriteOptions {
+        delimiter: delimiter.as_bytes()[0],
+        quote_char: quote_char.as_bytes()[0],
+        include_header,
+        quote_style: QuoteStyle::from_name(quote_style)?,
+        escape: EscapeStyle::from_name(escape)?,
+        null_value: null_value.to_string(),
+        line_terminator: line_terminator.to_string(),
+        datetime_formats,
+        float_precision,
+        value_formats: formats.map(value_formats).transpose()?.unwrap_or_default().into_iter().collect(),
+        number_locale: transformations::NumberLocale::new(thousands_separator, decimal_separator)?,
+        append,
+        ..Default::default()
+    };
+
+    py.allow_threads(|| csv_parser::write_csv(&df, file_path, &options))
+        .map_err(|e| match e {
+            // A header that doesn't match the file appended to
+            InsightoraError::ValidationError(_) => e.into(),
+            other => operation_error("Failed to write CSV", other),
+        })
+}
+
+/// Helper function to convert a partitioned CSV write report to a result dictionary
+fn partitioned_csv_report_to_pydict(py: Python, report: &csv_parser::PartitionedCsvReport) -> PyResult<PyObject> {
+    let parts = PyList::empty(py);
+    for part in &report.parts {
+        let record = PyDict::new(py);
+        record.set_item("file", &part.file)?;
+        record.set_item("rows", part.rows)?;
+        record.set_item("bytes", part.bytes)?;
+        parts.append(record)?;
+    }
+    let result = PyDict::new(py);
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    result.set_item("parts", parts)?;
+    result.set_item("rows", report.rows)?;
+    result.set_item("manifest", &report.manifest)?;
+    result.set_item("combined", &report.combined)?;
+    Ok(result.into())
+}
+
+/// Helper function to build CSV part options from binding arguments
+fn csv_partition_options(
+    max_rows_per_file: usize,
+    filename_template: &str,
+    combine_to: Option<String>,
+) -> csv_parser::CsvPartitionOptions {
+    csv_parser::CsvPartitionOptions {
+        max_rows_per_file,
+        filename_template: filename_template.to_string(),
+        combine_to,
+    }
+}
+
+/// Write data as numbered CSV part files in parallel
+/// 
+/// Rows are split in order into parts of at most `max_rows_per_file` rows
+/// (`part_0000.csv` holds the first rows); each part is serialized on its
+/// own thread and starts with the header. A `manifest.json` in `directory`
+/// lists every part with its row count and size in bytes.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary, Dataset, pyarrow Table or pandas DataFrame
+/// * `directory` - Output directory, created if needed
+/// * `max_rows_per_file` - Rows per part file
+/// * `filename_template` - Part file name; `{index}` becomes the zero-padded
+///   part number (default: "part_{index}.csv")
+/// * `combine_to` - Also concatenate the parts into this file, header once
+/// * `on_progress` - Optional callable `(percent, stage, detail)`
+/// * `delimiter` / `include_header` / `float_precision` / `quote_style` /
+///   `null_value` / `line_terminator` - As in `write_csv`
+/// 
+/// # Returns
+/// * Dictionary with `parts` (`file`, `rows`, `bytes` per part, in row
+///   order), `rows`, `manifest` and `combined` (path or None)
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// written = insightora_core.write_csv_partitioned(
+///     result,
+///     "exports/orders",
+///     max_rows_per_file=5_000_000,
+///     combine_to="exports/orders.csv",
+///     on_progress=lambda percent, stage, detail: print(f"{percent:5.1f}% {stage}"),
+/// )
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, directory, max_rows_per_file, filename_template=csv_parser::DEFAULT_PART_TEMPLATE, combine_to=None, on_progress=None, delimiter=",", include_header=true, float_precision=None, quote_style="necessary", null_value="", line_terminator="\n"))]
+#[allow(clippy::too_many_arguments)]
+pub fn write_csv_partitioned(
+    py: Python,
+    data: &PyAny,
+    directory: &str,
+    max_rows_per_file: usize,
+    filename_template: &str,
+    combine_to: Option<String>,
+    on_progress: Option<PyObject>,
+    delimiter: &str,
+    include_header: bool,
+    float_precision: Option<usize>,
+    quote_style: &str,
+    null_value: &str,
+    line_terminator: &str,
+) -> PyResult<PyObject> {
+    if delimiter.len() != 1 {
+        return Err(PyValueError::new_err("Delimiter must be a single character"));
+    }
+    let df = match data.extract::<PyRef<PyDataset>>() {
+        Ok(dataset) => {
+            let dataset = Arc::clone(dataset.dataset()?);
+            py.allow_threads(move || dataset.collect())?
+        }
+        Err(_) => batch_to_dataframe(data)?,
+    };
+    let options = CsvWriteOptions {
+        delimiter: delimiter.as_bytes()[0],
+        include_header,
+        quote_style: QuoteStyle::from_name(quote_style)?,
+        null_value: null_value.to_string(),
+        line_terminator: line_terminator.to_string(),
+        float_precision,
+        ..Default::default()
+    };
+    let partition = csv_partition_options(max_rows_per_file, filename_template, combine_to);
+    let progress = progress_reporter(on_progress);
+    
+    let report = py.allow_threads(|| csv_parser::write_csv_partitioned(&df, directory, &partition, &options, &progress))
+        .map_err(|e| operation_error("Failed to write partitioned CSV", e))?;
+    partitioned_csv_report_to_pydict(py, &report)
+}
+
+// ============================================================================
+// Streaming CSV Parser Python Bindings
+// ============================================================================
+
+/// Parse a large CSV file using streaming mode for memory efficiency
+/// 
+/// This function is optimized for files larger than 1GB and uses
+/// memory-efficient streaming to avoid loading the entire file at once.
+/// Batches are read with the GIL released.
+/// 
+/// # Arguments
+/// * `file_path` - Path to the CSV file
+/// * `chunk_size` - Number of rows to process per chunk (default: 100000)
+/// * `memory_limit_mb` - Memory limit in MB (default: 1024)
+/// * `repair` / `absorber` / `flag_repairs` - Column-count repair, applied
+///   batch by batch (see `parse_csv_with_options`)
+/// * `rename` / `columns` - Renaming and projection as in `parse_csv_with_options`
+/// * `include_summary` / `include_samples` - Execution summary as in `parse_csv_with_options`
+/// * `not_null` / `null_tokens` / `null_policy` - NOT NULL checks as in
+///   `parse_csv_with_options`, run on every batch as it is read: in strict
+///   mode a violation stops the parse without reading the rest of the file
+/// * `progress_callback` - Optional callable `(bytes_read, total_bytes)`,
+///   called at most 4 times a second and once at the end; `total_bytes` is
+///   0 for compressed files
+/// * `on_callback_error` - "report" (default) prints exceptions raised by
+///   `progress_callback` and keeps parsing; "raise" stops the parse and
+///   raises the exception
+/// * `cancel_token` - Optional `CancellationToken`; cancelling it stops the
+///   parse within one batch and raises `OperationCancelled`. Ctrl-C stops
+///   it the same way and raises KeyboardInterrupt
+/// * `return_format` - "dict" (default) or "arrow" for an `ArrowTable`, as
+///   in `parse_csv`; the batches are exported as they were read, one Arrow
+///   record batch each
+/// * `row_filter` - Condition rows must meet, as in `parse_csv_with_options`;
+///   each batch is filtered before it is combined with the others
+/// 
+/// # Returns
+/// * Dictionary with 'columns' and 'data', plus 'repair' with a repair mode
+///   and 'nullability' with `not_null`; with `return_format="arrow"` an
+///   `ArrowTable` with those keys in its `report`
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// import pandas as pd
+/// 
+/// # Parse large CSV file with streaming
+/// result = insightora_core.parse_csv_streaming(
+///     "large_file.csv",
+///     chunk_size=50000,
+///     memory_limit_mb=512
+/// )
+/// df = pd.DataFrame(result['data'], columns=result['columns'])
+/// 
+/// # Drive a tqdm progress bar
+/// from tqdm import tqdm
+/// with tqdm(unit="B", unit_scale=True) as bar:
+///     def advance(read, total):
+///         bar.total = total
+///         bar.update(read - bar.n)
+///     result = insightora_core.parse_csv_streaming("large_file.csv", progress_callback=advance)
+/// ```
+#[pyfunction]
+#[pyo3(signature = (file_path, chunk_size=100000, memory_limit_mb=1024, repair="none", absorber=None, flag_repairs=false, rename=None, columns=None, include_summary=false, include_samples=false, not_null=None, null_tokens=None, null_policy="report", progress_callback=None, on_callback_error="report", cancel_token=None, return_format="dict", row_filter=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn parse_csv_streaming(
+    py: Python,
+    file_path: &str,
+    chunk_size: usize,
+    memory_limit_mb: usize,
+    repair: &str,
+    absorber: Option<String>,
+    flag_repairs: bool,
+    rename: Option<&PyAny>,
+    columns: Option<Vec<String>>,
+    include_summary: bool,
+    include_samples: bool,
+    not_null: Option<Vec<String>>,
+    null_tokens: Option<Vec<String>>,
+    null_policy: &str,
+    progress_callback: Option<PyObject>,
+    on_callback_error: &str,
+    cancel_token: Option<PyRef<PyCancellationToken>>,
+    return_format: &str,
+    row_filter: Option<&str>,
+) -> PyResult<PyObject> {
+    let format = ReturnFormat::from_name(return_format)?;
+    let config = StreamingCsvConfig {
+        chunk_size,
+        memory_limit_mb,
+        has_header: true,
+        delimiter: b',',
+        repair: repair_options(repair, absorber, flag_repairs)?,
+        rename: rename.map(column_mapping).transpose()?,
+        columns,
+        not_null: not_null_rules(not_null, null_tokens, null_policy)?,
+        row_filter: row_filter.map(Predicate::parse).transpose()?,
+    };
+    let repair_enabled = config.repair.is_enabled();
+    let nulls_checked = !config.not_null.is_empty();
+    let mut metrics = ExecutionMetrics::new("parse_csv_streaming");
+    csv_options_in_effect(&mut metrics, file_path, &config.repair, config.rename.as_ref(), config.columns.as_deref());
+    metrics.option("chunk_size", chunk_size);
+    metrics.option("memory_limit_mb", memory_limit_mb);
+    metrics.option("has_header", config.has_header);
+    metrics.option("delimiter", config.delimiter as char);
+    if let Some(filter) = &config.row_filter {
+        metrics.option("row_filter", &filter.source);
+    }
+    metrics.engine("streaming");
+    
+    let progress = ByteProgress::new(progress_callback, on_callback_error, cancel_flag(cancel_token))?;
+    let parser = progress.attach(StreamingCsvParser::with_config(config));
+    let path = file_path.to_string();
+    let (df, report, nulls) = metrics.time("parse", || run_cancellable(py, &progress.cancel, move || parser.parse_streaming_checked(&path)))?
+        .map_err(|e| match e {
+            InsightoraError::ValidationError(_) => e.into(),
+            other => progress.error("Failed to parse CSV in streaming mode", other),
+        })?;
+    
+    log_warnings(py, &report.warnings)?;
+    let result = metrics.time("export", || table_result(py, &df, format, &ProgressReporter::disabled()))?;
+    let result = if repair_enabled { with_repair_report(py, result, &report)? } else { result };
+    let result = if nulls_checked { with_nullability_report(py, result, &nulls)? } else { result };
+    let result = if include_summary {
+        parse_metrics(&mut metrics, &df, &report, include_samples);
+        with_summary(py, result, &metrics, include_samples)?
+    } else {
+        result
+    };
+    finish_table(py, df, format, result)
+}
+
+/// Check if streaming mode is recommended for a CSV file
+/// 
+/// This function analyzes the file size and estimates memory usage
+/// to determine if streaming mode should be used.
+/// 
+/// # Arguments
+/// * `file_path` - Path to the CSV file
+/// * `memory_limit_mb` - Memory limit in MB (default: 1024)
+/// 
+/// # Returns
+/// * Dictionary with recommendation and estimated memory usage
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// info = insightora_core.should_use_streaming("large_file.csv")
+/// if info['recommended']:
+///     print(f"Streaming recommended. Estimated memory: {info['estimated_memory_mb']}MB")
+/// ```
+#[pyfunction]
+#[pyo3(signature = (file_path, memory_limit_mb=1024))]
+pub fn should_use_streaming(
+    py: Python,
+    file_path: &str,
+    memory_limit_mb: usize,
+) -> PyResult<PyObject> {
+    let config = StreamingCsvConfig {
+        memory_limit_mb,
+        ..Default::default()
+    };
+    
+    let parser = StreamingCsvParser::with_config(config);
+    
+    let estimated_memory = parser.estimate_memory_usage(file_path)
+        .map_err(|e| operation_error("Failed to estimate memory", e))?;
+    
+    let recommended = parser.should_use_streaming(file_path)
+        .map_err(|e| operation_error("Failed to check streaming recommendation", e))?;
+    
+    let result = PyDict::new(py);
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    result.set_item("recommended", recommended)?;
+    result.set_item("estimated_memory_mb", estimated_memory)?;
+    result.set_item("memory_limit_mb", memory_limit_mb)?;
+    
+    Ok(result.into())
+}
+
+use crate::streaming::buffer::{BatchPrefetcher, PrefetchPoll};
+use pyo3::exceptions::PyStopAsyncIteration;
+use std::time::{Duration, Instant};
+use pyo3::types::{PyCFunction, PyTuple};
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::RecvTimeoutError;
+
+/// Shortest time between two calls of a `progress_callback`
+const BYTE_PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A Python `progress_callback(bytes_read, total_bytes)` adapted for `StreamingCsvParser`
+/// 
+/// `StreamingExcelParser` reports `(rows_read, total_rows)` through the same
+/// callable.
+/// 
+/// The callable runs with the GIL held only for the call, at most every
+/// `BYTE_PROGRESS_INTERVAL` plus once when the last byte is read. With
+/// `on_callback_error="report"` its exceptions are printed and the parse
+/// goes on; with "raise" the first one is kept and the parse stops at its
+/// next progress report, raising that exception in place of the
+/// cancellation. `cancel` is the caller's `CancellationToken` flag when one
+/// was given, so a raising callback also cancels the token.
+struct ByteProgress {
+    callback: Option<csv_parser::ProgressCallback>,
+    cancel: Arc<AtomicBool>,
+    error: Arc<Mutex<Option<PyErr>>>,
+}
+
+impl ByteProgress {
+    fn new(callback: Option<PyObject>, on_callback_error: &str, cancel: Arc<AtomicBool>) -> PyResult<Self> {
+        let raise = match on_callback_error.to_ascii_lowercase().as_str() {
+            "report" => false,
+            "raise" => true,
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown on_callback_error '{}'; expected 'report' or 'raise'",
+                    other
+                )));
+            }
+        };
+        let error = Arc::new(Mutex::new(None));
+        let callback = callback.map(|callback| {
+            let (cancel, error) = (Arc::clone(&cancel), Arc::clone(&error));
+            let last_call: Mutex<Option<Instant>> = Mutex::new(None);
+            Arc::new(move |read: usize, total: usize| {
+                {
+                    let mut last_call = last_call.lock().unwrap_or_else(|e| e.into_inner());
+                    let finished = total > 0 && read >= total;
+                    if !finished && last_call.is_some_and(|at| at.elapsed() < BYTE_PROGRESS_INTERVAL) {
+                        return;
+                    }
+                    *last_call = Some(Instant::now());
+                }
+                if cancel.load(AtomicOrdering::Relaxed) || !python_available() {
+                    return;
+                }
+                Python::with_gil(|py| {
+                    if let Err(err) = callback.call1(py, (read, total)) {
+                        if raise {
+                            *error.lock().unwrap_or_else(|e| e.into_inner()) = Some(err);
+                            cancel.store(true, AtomicOrdering::Relaxed);
+                        } else {
+                            err.print(py);
+                        }
+                    }
+                })
+            }) as csv_parser::ProgressCallback
+        });
+        Ok(Self { callback, cancel, error })
+    }
+    
+    /// `parser` reporting to the callable, if there is one, and stopped by `cancel`
+    fn attach(&self, parser: StreamingCsvParser) -> StreamingCsvParser {
+        let parser = parser.with_cancel_flag(Arc::clone(&self.cancel));
+        match &self.callback {
+            Some(callback) => parser.with_progress_callback(Arc::clone(callback)),
+            None => parser,
+        }
+    }
+    
+    /// `attach` for a `StreamingExcelParser`
+    fn attach_excel(&self, parser: StreamingExcelParser) -> StreamingExcelParser {
+        let parser = parser.with_cancel_flag(Arc::clone(&self.cancel));
+        match &self.callback {
+            Some(callback) => parser.with_progress_callback(Arc::clone(callback)),
+            None => parser,
+        }
+    }
+    
+    fn is_cancelled(&self) -> bool {
+        self.cancel.load(AtomicOrdering::Relaxed)
+    }
+    
+    /// The callable's exception when it stopped the parse, otherwise `err` with `context`
+    fn error(&self, context: &str, err: InsightoraError) -> PyErr {
+        self.take_error().unwrap_or_else(|| operation_error(context, err))
+    }
+    
+    fn take_error(&self) -> Option<PyErr> {
+        self.error.lock().unwrap_or_else(|e| e.into_inner()).take()
+    }
+}
+
+/// Asynchronous iterator over CSV batches for asyncio applications
+/// 
+/// Batches are read on a dedicated Rust thread and handed to awaiting
+/// coroutines, so the event loop never blocks on file I/O or parsing. Up to
+/// `prefetch` batches are read ahead; a slow consumer pauses the reader once
+/// that many are buffered. Cancelling the consuming task, calling `close()`
+/// or leaving a `with` block stops the reader and releases the file.
+/// `repair`, `absorber` and
+/// `flag_repairs` apply column-count repair to each batch as it is read (see
+/// `parse_csv_with_options`).
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// async def load(path):
+///     async for batch in insightora_core.AsyncCsvBatchIterator(path, batch_size=50000, prefetch=4):
+///         await store(batch)
+/// ```
+#[pyclass(name = "AsyncCsvBatchIterator")]
+pub struct PyAsyncCsvBatchIterator {
+    prefetcher: Arc<BatchPrefetcher>,
+    /// Set by `close()`; a cancelled task only ends the iteration
+    closed: bool,
+}
+
+#[pymethods]
+impl PyAsyncCsvBatchIterator {
+    #[new]
+    #[pyo3(signature = (file_path, batch_size=100000, prefetch=2, has_header=true, delimiter=",", repair="none", absorber=None, flag_repairs=false))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        file_path: &str,
+        batch_size: usize,
+        prefetch: usize,
+        has_header: bool,
+        delimiter: &str,
+        repair: &str,
+        absorber: Option<String>,
+        flag_repairs: bool,
+    ) -> PyResult<Self> {
+        if delimiter.len() != 1 {
+            return Err(PyValueError::new_err("Delimiter must be a single character"));
+        }
+        if batch_size == 0 {
+            return Err(PyValueError::new_err("batch_size must be greater than 0"));
+        }
+        let config = StreamingCsvConfig {
+            chunk_size: batch_size,
+            has_header,
+            delimiter: delimiter.as_bytes()[0],
+            repair: repair_options(repair, absorber, flag_repairs)?,
+            ..Default::default()
+        };
+        Ok(Self {
+            prefetcher: Arc::new(BatchPrefetcher::spawn(file_path, config, prefetch)?),
+            closed: false,
+        })
+    }
+    
+    fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+    
+    /// Return an asyncio future resolving to the next batch dictionary
+    ///
+    /// Always `Some`: the end of the file is raised by awaiting the future.
+    fn __anext__(&self, py: Python) -> PyResult<Option<PyObject>> {
+        if self.closed {
+            return Err(closed_handle("AsyncCsvBatchIterator"));
+        }
+        let event_loop = py.import("asyncio")?.call_method0("get_running_loop")?;
+        let future = event_loop.call_method0("create_future")?;
+        
+        // Stop background reads as soon as the awaiting task is cancelled
+        let prefetcher = Arc::clone(&self.prefetcher);
+        let on_done = PyCFunction::new_closure(py, None, None, move |args: &PyTuple, _kwargs: Option<&PyDict>| -> PyResult<()> {
+            if args.get_item(0)?.call_method0("cancelled")?.is_true()? {
+                let prefetcher = Arc::clone(&prefetcher);
+                args.py().allow_threads(move || prefetcher.cancel());
+            }
+            Ok(())
+        })?;
+        future.call_method1("add_done_callback", (on_done,))?;
+        
+        // Fast path: a batch is already buffered (or the file is exhausted)
+        match self.prefetcher.poll(Duration::ZERO) {
+            PrefetchPoll::Pending => {}
+            ready => {
+                let (value, is_error) = prefetch_outcome_to_py(py, ready)?;
+                settle_future(future, value, is_error)?;
+                return Ok(Some(future.into()));
+            }
+        }
+        
+        // Otherwise wait off the event loop and resolve on the loop thread
+        let prefetcher = Arc::clone(&self.prefetcher);
+        let future_ref: PyObject = future.into();
+        let loop_ref: PyObject = event_loop.into();
+        let waiter_future = future_ref.clone_ref(py);
+        std::thread::Builder::new()
+            .name("insightora-async-wait".to_string())
+            .spawn(move || {
+                let outcome = loop {
+                    if prefetcher.is_cancelled() {
+                        break PrefetchPoll::Finished;
+                    }
+                    match prefetcher.poll(Duration::from_millis(50)) {
+                        PrefetchPoll::Pending => continue,
+                        other => break other,
+                    }
+                };
+                if !python_available() {
+                    return;
+                }
+                Python::with_gil(|py| -> PyResult<()> {
+                    let (value, is_error) = prefetch_outcome_to_py(py, outcome)?;
+                    let settle = PyCFunction::new_closure(py, None, None, move |args: &PyTuple, _kwargs: Option<&PyDict>| -> PyResult<()> {
+                        let py = args.py();
+                        settle_future(waiter_future.as_ref(py), value.clone_ref(py), is_error)
+                    })?;
+                    // Raises if the loop is already closed; nothing is awaiting then
+                    let _ = loop_ref.call_method1(py, "call_soon_threadsafe", (settle,));
+                    Ok(())
+                })
+                .ok();
+            })
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to start batch waiter: {}", e)))?;
+        
+        Ok(Some(future_ref))
+    }
+    
+    /// Stop the background reader and release the file (idempotent)
+    fn close(&mut self, py: Python) {
+        self.closed = true;
+        let prefetcher = Arc::clone(&self.prefetcher);
+        py.allow_threads(move || prefetcher.cancel());
+    }
+    
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+    
+    fn __exit__(&mut self, py: Python, _exc_type: &PyAny, _exc_value: &PyAny, _traceback: &PyAny) -> bool {
+        self.close(py);
+        false
+    }
+    
+    /// Number of batches read from the file so far
+    #[getter]
+    fn batches_read(&self) -> usize {
+        self.prefetcher.batches_read()
+    }
+    
+    fn __repr__(&self) -> String {
+        format!(
+            "AsyncCsvBatchIterator(batches_read={}, closed={})",
+            self.prefetcher.batches_read(),
+            self.prefetcher.is_cancelled()
+        )
+    }
+}
+
+/// Parse a CSV file into an iterator of batch dictionaries
+/// 
+/// Unlike `parse_csv_streaming`, which combines every batch into one
+/// result, each batch is handed over as soon as it is read, so memory stays
+/// around one batch whatever the file size: a reader thread parses at most
+/// one batch ahead of the consumer, with the GIL released. Breaking out of
+/// the loop, calling `close()` or leaving a `with` block stops the reader
+/// and releases the file, as does cancelling `cancel_token` (the next batch
+/// raises `OperationCancelled`) or Ctrl-C while waiting for a batch
+/// (KeyboardInterrupt).
+/// 
+/// # Arguments
+/// * `file_path` - Path to the CSV file
+/// * `chunk_size` - Rows per batch (default: 100000); the last may be shorter
+/// * `has_header` - Whether the CSV has a header row (default: True)
+/// * `delimiter` - Field delimiter character (default: ',')
+/// * `repair` / `absorber` / `flag_repairs` - Column-count repair, applied
+///   batch by batch (see `parse_csv_with_options`)
+/// * `rename` / `columns` - Renaming and projection as in `parse_csv_with_options`
+/// * `progress_callback` / `on_callback_error` - Progress in bytes, as in
+///   `parse_csv_streaming`; called from the reader thread
+/// * `cancel_token` - Optional `CancellationToken` stopping the iteration
+/// 
+/// # Returns
+/// * `CsvBatchIterator` yielding dictionaries with 'columns', 'data' and
+///   'batch_index' (0-based); the first batch fixes the column types
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// totals = {}
+/// for batch in insightora_core.parse_csv_batches("large_file.csv", chunk_size=50000):
+///     columns = dict(zip(batch["columns"], batch["data"]))
+///     for region, amount in zip(columns["region"], columns["amount"]):
+///         totals[region] = totals.get(region, 0) + amount
+/// ```
+#[pyfunction]
+#[pyo3(signature = (file_path, chunk_size=100000, has_header=true, delimiter=",", repair="none", absorber=None, flag_repairs=false, rename=None, columns=None, progress_callback=None, on_callback_error="report", cancel_token=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn parse_csv_batches(
+    file_path: &str,
+    chunk_size: usize,
+    has_header: bool,
+    delimiter: &str,
+    repair: &str,
+    absorber: Option<String>,
+    flag_repairs: bool,
+    rename: Option<&PyAny>,
+    columns: Option<Vec<String>>,
+    progress_callback: Option<PyObject>,
+    on_callback_error: &str,
+    cancel_token: Option<PyRef<PyCancellationToken>>,
+) -> PyResult<PyCsvBatchIterator> {
+    if delimiter.len() != 1 {
+        return Err(PyValueError::new_err("Delimiter must be a single character"));
+    }
+    if chunk_size == 0 {
+        return Err(PyValueError::new_err("chunk_size must be greater than 0"));
+    }
+    let config = StreamingCsvConfig {
+        chunk_size,
+        has_header,
+        delimiter: delimiter.as_bytes()[0],
+        repair: repair_options(repair, absorber, flag_repairs)?,
+        rename: rename.map(column_mapping).transpose()?,
+        columns,
+        ..Default::default()
+    };
+    let progress = ByteProgress::new(progress_callback, on_callback_error, cancel_flag(cancel_token))?;
+    let parser = progress.attach(StreamingCsvParser::with_config(config));
+    Ok(PyCsvBatchIterator {
+        prefetcher: Some(BatchPrefetcher::spawn_parser(file_path, parser, 1)?),
+        progress,
+        next_index: 0,
+    })
+}
+
+/// Iterator over the batches of a CSV file, returned by `parse_csv_batches`
+#[pyclass(name = "CsvBatchIterator")]
+pub struct PyCsvBatchIterator {
+    /// None once closed or exhausted; dropping it stops the reader and closes the file
+    prefetcher: Option<BatchPrefetcher>,
+    progress: ByteProgress,
+    next_index: usize,
+}
+
+#[pymethods]
+impl PyCsvBatchIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+    
+    fn __next__(&mut self, py: Python) -> PyResult<Option<PyObject>> {
+        next_batch_dict(py, &mut self.prefetcher, &self.progress, &mut self.next_index, "Failed to read CSV batch")
+    }
+    
+    /// Stop the reader and release the file (idempotent)
+    fn close(&mut self, py: Python) {
+        if let Some(prefetcher) = self.prefetcher.take() {
+            py.allow_threads(move || drop(prefetcher));
+        }
+    }
+    
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+    
+    fn __exit__(&mut self, py: Python, _exc_type: &PyAny, _exc_value: &PyAny, _traceback: &PyAny) -> bool {
+        self.close(py);
+        false
+    }
+    
+    /// Number of batches returned so far
+    #[getter]
+    fn batches_returned(&self) -> usize {
+        self.next_index
+    }
+    
+    fn __repr__(&self) -> String {
+        format!("CsvBatchIterator(batches_returned={}, closed={})", self.next_index, self.prefetcher.is_none())
+    }
+}
+
+impl Drop for PyCsvBatchIterator {
+    fn drop(&mut self) {
+        // The reader may be waiting for the GIL to report progress
+        if let Some(prefetcher) = self.prefetcher.take() {
+            if python_available() {
+                Python::with_gil(|py| py.allow_threads(move || drop(prefetcher)));
+            }
+        }
+    }
+}
+
+/// Helper function to take the next prefetched batch as a dictionary with 'batch_index'
+/// 
+/// The prefetcher is stopped and dropped once the batches end, fail or are
+/// cancelled; a callback's exception takes precedence over the error.
+fn next_batch_dict(
+    py: Python,
+    prefetcher: &mut Option<BatchPrefetcher>,
+    progress: &ByteProgress,
+    next_index: &mut usize,
+    context: &str,
+) -> PyResult<Option<PyObject>> {
+    let Some(active) = prefetcher.as_ref() else {
+        return Ok(None);
+    };
+    let batch = match wait_for_batch(py, active, &progress.cancel) {
+        Ok(Some(Ok(batch))) => batch,
+        outcome => {
+            if let Some(finished) = prefetcher.take() {
+                py.allow_threads(move || drop(finished));
+            }
+            return match (progress.take_error(), outcome) {
+                (Some(err), _) => Err(err),
+                (None, Err(interrupt)) => Err(interrupt),
+                (None, Ok(Some(Err(e)))) => Err(operation_error(context, e)),
+                (None, Ok(_)) if progress.is_cancelled() => Err(InsightoraError::Cancelled.into()),
+                (None, Ok(_)) => Ok(None),
+            };
+        }
+    };
+    let result = dataframe_to_pydict(py, &batch)?;
+    result.as_ref(py).downcast::<PyDict>()?.set_item("batch_index", *next_index)?;
+    *next_index += 1;
+    Ok(Some(result))
+}
+
+/// Helper function to wait for the next prefetched batch, running Python's signal handlers between polls
+/// 
+/// None once the reader has finished or `cancel` is set, so batches already
+/// buffered aren't returned after a cancellation. An exception raised by a
+/// signal handler (KeyboardInterrupt on Ctrl-C) is returned as the error.
+fn wait_for_batch(
+    py: Python,
+    prefetcher: &BatchPrefetcher,
+    cancel: &AtomicBool,
+) -> PyResult<Option<Result<polars::prelude::DataFrame, InsightoraError>>> {
+    loop {
+        if cancel.load(AtomicOrdering::Relaxed) {
+            return Ok(None);
+        }
+        match py.allow_threads(|| prefetcher.poll(SIGNAL_POLL_INTERVAL)) {
+            PrefetchPoll::Batch(batch) => return Ok(Some(Ok(batch))),
+            PrefetchPoll::Error(e) => return Ok(Some(Err(e))),
+            PrefetchPoll::Finished => return Ok(None),
+            PrefetchPoll::Pending => py.check_signals()?,
+        }
+    }
+}
+
+/// Helper function to convert a prefetch outcome into a future result or exception
+fn prefetch_outcome_to_py(py: Python, outcome: PrefetchPoll) -> PyResult<(PyObject, bool)> {
+    match outcome {
+        PrefetchPoll::Batch(df) => Ok((dataframe_to_pydict(py, &df)?, false)),
+        PrefetchPoll::Error(e) => Ok((PyErr::from(e).into_value(py).into(), true)),
+        PrefetchPoll::Finished | PrefetchPoll::Pending => {
+            Ok((PyStopAsyncIteration::new_err(()).into_value(py).into(), true))
+        }
+    }
+}
+
+/// Helper function to resolve an asyncio future unless it was already cancelled
+fn settle_future(future: &PyAny, value: PyObject, is_error: bool) -> PyResult<()> {
+    if future.call_method0("done")?.is_true()? {
+        return Ok(());
+    }
+    if is_error {
+        future.call_method1("set_exception", (value,))?;
+    } else {
+        future.call_method1("set_result", (value,))?;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Remote Files Python Bindings
+// ============================================================================
+
+use crate::io::remote::{RemoteFetchConfig, OnError, SchemaAlignment};
+
+/// Download and parse many remote CSV files concurrently
+/// 
+/// Downloads run on their own threads with bounded concurrency and each file
+/// is parsed as soon as it arrives. Transient HTTP failures are retried with
+/// exponential backoff.
+/// 
+/// # Arguments
+/// * `urls` - List of http(s) URLs
+/// * `max_concurrent` - Maximum downloads in flight (default: 8)
+/// * `parse_options` - Optional dict with has_header, delimiter, quote_char,
+///   infer_schema_length, rename and columns (see `parse_csv_with_options`)
+/// * `on_error` - "raise" (default) or "skip" failed URLs
+/// * `timeout` - Per-request timeout in seconds (default: 30)
+/// * `deadline` - Optional total deadline in seconds
+/// * `max_retries` - Retries per URL for transient failures (default: 3)
+/// * `schema_alignment` - "union" (default) or "strict"
+/// * `on_progress` - Optional callable `(percent, stage, detail)` for progress
+/// 
+/// # Returns
+/// * Result dictionary plus `files`: per-URL http_status, bytes, rows,
+///   retries, elapsed_ms and error
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// result = insightora_core.parse_remote_many(urls, max_concurrent=16, on_error="skip", deadline=600)
+/// failed = [f["url"] for f in result["files"] if f["error"]]
+/// ```
+#[pyfunction]
+#[pyo3(signature = (
+    urls,
+    max_concurrent=8,
+    parse_options=None,
+    on_error="raise",
+    timeout=30.0,
+    deadline=None,
+    max_retries=3,
+    schema_alignment="union",
+    on_progress=None
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn parse_remote_many(
+    py: Python,
+    urls: Vec<String>,
+    max_concurrent: usize,
+    parse_options: Option<&PyDict>,
+    on_error: &str,
+    timeout: f64,
+    deadline: Option<f64>,
+    max_retries: u32,
+    schema_alignment: &str,
+    on_progress: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let seconds = |name: &str, value: f64| {
+        if value.is_finite() && value > 0.0 {
+            Ok(Duration::from_secs_f64(value))
+        } else {
+            Err(PyValueError::new_err(format!("{} must be a positive number of seconds", name)))
+        }
+    };
+    
+    let config = RemoteFetchConfig {
+        max_concurrent,
+        max_retries,
+        request_timeout: seconds("timeout", timeout)?,
+        total_deadline: deadline.map(|d| seconds("deadline", d)).transpose()?,
+        on_error: OnError::from_name(on_error)?,
+        alignment: SchemaAlignment::from_name(schema_alignment)?,
+        parse_options: parse_options_from_dict(parse_options)?,
+        ..Default::default()
+    };
+    let progress = progress_reporter(on_progress);
+    // Downloading dominates; exporting is weighted like the align step
+    let stages = progress.stages(&[("fetch", 0.85), ("export", 0.15)]);
+    let config = RemoteFetchConfig { progress: stages[0].clone(), ..config };
+    
+    let result = py.allow_threads(|| crate::io::remote::parse_remote_many(&urls, &config))?;
+    
+    let output = dataframe_to_pydict_reporting(py, &result.data, &stages[1])?;
+    let files = PyList::empty(py);
+    for status in &result.files {
+        let entry = PyDict::new(py);
+        entry.set_item("url", &status.url)?;
+        entry.set_item("http_status", status.http_status)?;
+        entry.set_item("bytes", status.bytes)?;
+        entry.set_item("rows", status.rows)?;
+        entry.set_item("retries", status.retries)?;
+        entry.set_item("elapsed_ms", status.elapsed_ms)?;
+        entry.set_item("error", &status.error)?;
+        files.append(entry)?;
+    }
+    output.as_ref(py).downcast::<PyDict>()?.set_item("files", files)?;
+    
+    Ok(output)
+}
+
+/// Helper function to build CSV parser options from an optional dict
+fn parse_options_from_dict(options: Option<&PyDict>) -> PyResult<CsvParserConfig> {
+    let mut config = CsvParserConfig {
+        chunk_size: get_current_config().chunk_size,
+        ..Default::default()
+    };
+    let Some(options) = options else {
+        return Ok(config);
+    };
+    
+    let single_byte = |name: &str, value: String| -> PyResult<u8> {
+        if value.len() != 1 {
+            return Err(PyValueError::new_err(format!("{} must be a single character", name)));
+        }
+        Ok(value.as_bytes()[0])
+    };
+    for (key, value) in options.iter() {
+        let key: String = key.extract()?;
+        match key.as_str() {
+            "has_header" => config.has_header = value.extract()?,
+            "delimiter" => config.delimiter = single_byte("delimiter", value.extract()?)?,
+            "quote_char" => config.quote_char = single_byte("quote_char", value.extract()?)?,
+            "infer_schema_length" => config.infer_schema_length = value.extract()?,
+            "rename" => config.rename = Some(column_mapping(value)?),
+            "columns" => config.columns = Some(value.extract()?),
+            "column_indices" => config.column_indices = Some(value.extract()?),
+            "skip_rows" => config.skip_rows = value.extract()?,
+            "max_rows" => config.max_rows = value.extract()?,
+            "null_values" => config.null_values = Some(null_value_tokens(value)?),
+            "empty_as_null" => config.empty_as_null = value.extract()?,
+            other => {
+                return Err(PyValueError::new_err(format!("Unknown parse option '{}'", other)));
+            }
+        }
+    }
+    Ok(config)
+}
+
+// ============================================================================
+// ZIP Archive Python Bindings
+// ============================================================================
+
+use crate::io::archive::{self, ArchiveMember};
+
+/// Helper function to convert archive members to a list of dicts
+fn members_to_pylist<'py>(py: Python<'py>, members: &[ArchiveMember]) -> PyResult<&'py PyList> {
+    let list = PyList::empty(py);
+    for member in members {
+        let entry = PyDict::new(py);
+        entry.set_item("name", &member.name)?;
+        entry.set_item("size", member.size)?;
+        entry.set_item("compressed_size", member.compressed_size)?;
+        entry.set_item("format", member.format.name())?;
+        entry.set_item("description", member.format.describe())?;
+        entry.set_item("encrypted", member.encrypted)?;
+        list.append(entry)?;
+    }
+    Ok(list)
+}
+
+/// List the files inside a ZIP archive
+/// 
+/// Directories and OS metadata (`__MACOSX/`, `.DS_Store`, `._*`) are left
+/// out. Nothing is extracted.
+/// 
+/// # Arguments
+/// * `file_path` - Path to the .zip file
+/// 
+/// # Returns
+/// * Dictionary with `members`: name, size, compressed_size, format
+///   ("csv", "tsv", "parquet", "excel", "json", "archive" or "other"),
+///   description and encrypted per file
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// listing = insightora_core.list_archive("upload.zip")
+/// csvs = [m["name"] for m in listing["members"] if m["format"] == "csv"]
+/// ```
+#[pyfunction]
+pub fn list_archive(py: Python, file_path: &str) -> PyResult<PyObject> {
+    let members = py.allow_threads(|| archive::list_archive(file_path))?;
+    let result = PyDict::new(py);
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    result.set_item("members", members_to_pylist(py, &members)?)?;
+    Ok(result.into())
+}
+
+/// Parse data files straight out of a ZIP archive
+/// 
+/// Members are decompressed in memory, never written to disk. With no
+/// `member`, every CSV, TSV and Parquet file is parsed and the results are
+/// concatenated like `parse_remote_many`; other files are skipped and
+/// reported.
+/// 
+/// # Arguments
+/// * `file_path` - Path to the .zip file
+/// * `member` - Optional member path inside the archive (e.g. "exports/jan.csv")
+/// * `parse_options` - Optional dict with has_header, delimiter, quote_char,
+///   infer_schema_length, rename and columns (see `parse_csv_with_options`)
+/// * `schema_alignment` - "union" (default) or "strict"
+/// 
+/// # Returns
+/// * Result dictionary plus `members` (names parsed) and `skipped`
+///   (name and reason per file left out)
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// result = insightora_core.parse_archive("upload.zip")
+/// jan = insightora_core.parse_archive("upload.zip", member="exports/jan.csv")
+/// ```
+#[pyfunction]
+#[pyo3(signature = (file_path, member=None, parse_options=None, schema_alignment="union"))]
+pub fn parse_archive(
+    py: Python,
+    file_path: &str,
+    member: Option<&str>,
+    parse_options: Option<&PyDict>,
+    schema_alignment: &str,
+) -> PyResult<PyObject> {
+    let options = parse_options_from_dict(parse_options)?;
+    let alignment = SchemaAlignment::from_name(schema_alignment)?;
+    let result = py.allow_threads(|| archive::parse_archive(file_path, member, &options, alignment))?;
+    
+    let output = dataframe_to_pydict(py, &result.data)?;
+    let skipped = PyList::empty(py);
+    for (name, reason) in &result.skipped {
+        let entry = PyDict::new(py);
+        entry.set_item("name", name)?;
+        entry.set_item("reason", reason)?;
+        skipped.append(entry)?;
+    }
+    let dict = output.as_ref(py).downcast::<PyDict>()?;
+    dict.set_item("members", &result.parsed)?;
+    dict.set_item("skipped", skipped)?;
+    Ok(output)
+}
+
+// ============================================================================
+// Automatic Parsing Python Bindings
+// ============================================================================
+
+use crate::io::auto::{self, AutoParseOptions, AutoSettings, CsvDialect, SNIFF_BYTES};
+
+/// Helper function to convert the settings of a parse attempt to a dict
+fn auto_settings_to_py<'py>(py: Python<'py>, settings: &AutoSettings) -> PyResult<&'py PyDict> {
+    let dict = PyDict::new(py);
+    dict.set_item("format", settings.format.name())?;
+    dict.set_item("compression", settings.compression.name())?;
+    dict.set_item("encoding", settings.encoding.map(|e| e.name()))?;
+    dict.set_item("delimiter", settings.delimiter.map(|d| (d as char).to_string()))?;
+    dict.set_item("quote_char", settings.quote_char.map(|q| (q as char).to_string()))?;
+    dict.set_item("lossy_encoding", settings.lossy_encoding)?;
+    dict.set_item("skip_bad_lines", settings.skip_bad_lines)?;
+    dict.set_item("schema", settings.schema.name())?;
+    Ok(dict)
+}
+
+/// Parse a file of unknown format, retrying with more lenient settings
+/// 
+/// Sniffs the format (CSV, TSV, Parquet, NDJSON, JSON array or ZIP; gzip
+/// and zstd files are decompressed first), the encoding and the delimiter,
+/// then parses strictly. Failures are retried with the matching fallback:
+/// `lossy_encoding` replaces invalid bytes with U+FFFD, `skip_bad_lines`
+/// drops lines with surplus fields (or invalid JSON lines), and
+/// `widen_schema` infers types from every row, then reads every column as
+/// text. Files are assumed to have a header row.
+/// 
+/// # Arguments
+/// * `file_path` - File to parse
+/// * `max_attempts` - Parses tried at most, the strict one included (default: 5)
+/// * `options` - Optional dict switching fallbacks off, e.g.
+///   {"lossy_encoding": False}; all are on by default
+/// 
+/// # Returns
+/// * Result dictionary plus `settings` (format, compression, encoding,
+///   delimiter, quote_char, lossy_encoding, skip_bad_lines and schema of the
+///   attempt that succeeded), `attempts` (settings, error and failure class
+///   per attempt, the successful one last) and `concessions` (fallback and
+///   detail per way the data may differ from the file)
+/// 
+/// # Raises
+/// * `ValueError` - The last error, the attempts made and any fallback that
+///   was disabled
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// result = insightora_core.parse_auto("upload.bin")
+/// print(result["settings"]["format"], result["settings"]["delimiter"])
+/// for concession in result["concessions"]:
+///     print(concession["fallback"], concession["detail"])
+/// 
+/// # Never alter values: fail rather than replace bytes or drop lines
+/// strict = insightora_core.parse_auto("upload.bin", options={"lossy_encoding": False, "skip_bad_lines": False})
+/// ```
+#[pyfunction]
+#[pyo3(signature = (file_path, max_attempts=5, options=None))]
+pub fn parse_auto(py: Python, file_path: &str, max_attempts: usize, options: Option<&PyDict>) -> PyResult<PyObject> {
+    let mut config = AutoParseOptions { max_attempts, ..Default::default() };
+    for (key, value) in options.into_iter().flat_map(|o| o.iter()) {
+        let key: String = key.extract()?;
+        match key.as_str() {
+            "lossy_encoding" => config.lossy_encoding = value.extract()?,
+            "skip_bad_lines" => config.skip_bad_lines = value.extract()?,
+            "widen_schema" => config.widen_schema = value.extract()?,
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown parse_auto option '{}'; expected 'lossy_encoding', 'skip_bad_lines' or 'widen_schema'",
+                    other
+                )));
+            }
+        }
+    }
+    let result = py.allow_threads(|| auto::parse_auto(file_path, &config))?;
+    
+    let attempts = PyList::empty(py);
+    for attempt in &result.attempts {
+        let entry = PyDict::new(py);
+        entry.set_item("settings", auto_settings_to_py(py, &attempt.settings)?)?;
+        entry.set_item("error", &attempt.error)?;
+        entry.set_item("failure", attempt.failure.map(|f| f.name()))?;
+        attempts.append(entry)?;
+    }
+    let concessions = PyList::empty(py);
+    for concession in &result.concessions {
+        let entry = PyDict::new(py);
+        entry.set_item("fallback", concession.fallback)?;
+        entry.set_item("detail", &concession.detail)?;
+        concessions.append(entry)?;
+    }
+    
+    let output = dataframe_to_pydict(py, &result.data)?;
+    let dict = output.as_ref(py).downcast::<PyDict>()?;
+    dict.set_item("settings", auto_settings_to_py(py, &result.settings)?)?;
+    dict.set_item("attempts", attempts)?;
+    dict.set_item("concessions", concessions)?;
+    Ok(output)
+}
+
+/// Convert a sniffed CSV dialect to a Python dictionary
+fn csv_dialect_to_py<'py>(py: Python<'py>, dialect: &CsvDialect) -> PyResult<&'py PyDict> {
+    let dict = PyDict::new(py);
+    dict.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    dict.set_item("delimiter", (dialect.delimiter as char).to_string())?;
+    dict.set_item("quote_char", (dialect.quote_char as char).to_string())?;
+    dict.set_item("has_header", dialect.has_header)?;
+    dict.set_item("column_count", dialect.column_count)?;
+    dict.set_item("consistency", dialect.consistency)?;
+    dict.set_item("records", dialect.records)?;
+    dict.set_item("encoding", dialect.encoding.map(|e| e.name()))?;
+    dict.set_item("byte_order_mark", dialect.byte_order_mark.map(|e| e.name()))?;
+    dict.set_item("invalid_utf8_at", dialect.invalid_utf8_at)?;
+    Ok(dict)
+}
+
+/// Sniff the delimiter, quote character, header row and encoding of a CSV file
+/// 
+/// For uploads named .csv that are really semicolon-, tab- or
+/// pipe-delimited, or have no header. Only the first `sample_bytes` are
+/// read (gzip and zstd files are decompressed that far), and a record cut
+/// by the end of the sample is left out.
+/// 
+/// # Arguments
+/// * `file_path` - File to sniff
+/// * `sample_bytes` - Bytes read from the start of the file (default: 65536)
+/// 
+/// # Returns
+/// * Dictionary with `delimiter` (",", "\t", ";" or "|": the one giving
+///   the most records the same field count, values in quotes kept whole),
+///   `quote_char`, `has_header` (true when the first row's values are text
+///   where the rest of a column is numbers or booleans), `column_count`,
+///   `consistency` (share of sampled records with `column_count` fields),
+///   `records` sampled, `encoding` (the `encoding` to pass to
+///   `parse_csv_with_options`: "utf8" unless the sample has invalid UTF-8,
+///   None for UTF-16), `byte_order_mark` ("utf-8-sig", "utf-16-le",
+///   "utf-16-be" or None) and `invalid_utf8_at`, the byte offset of the
+///   first invalid UTF-8 sequence
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// dialect = insightora_core.sniff_csv("upload.csv")
+/// result = insightora_core.parse_csv_with_options(
+///     "upload.csv",
+///     delimiter=dialect["delimiter"],
+///     has_header=dialect["has_header"],
+///     encoding=dialect["encoding"],
+/// )
+/// ```
+#[pyfunction]
+#[pyo3(signature = (file_path, sample_bytes=65536))]
+pub fn sniff_csv(py: Python, file_path: &str, sample_bytes: usize) -> PyResult<PyObject> {
+    let dialect = py.allow_threads(|| auto::sniff_csv(file_path, sample_bytes)).map_err(|e| match e {
+        InsightoraError::ValidationError(_) => e.into(),
+        other => operation_error("Failed to sniff CSV", other),
+    })?;
+    Ok(csv_dialect_to_py(py, &dialect)?.into())
+}
+
+// ============================================================================
+// NDJSON Python Bindings
+// ============================================================================
+
+use crate::io::json_parser::{self, NdjsonOptions};
+
+/// Parse a newline-delimited JSON (NDJSON / JSON Lines) file
+/// 
+/// Every key becomes a column and records missing a key are None in it.
+/// Nested objects and arrays are returned as their JSON text unless
+/// `flatten` is set.
+/// 
+/// # Arguments
+/// * `file_path` - Path to the file; gzip (`.gz`) and zstd (`.zst`) files
+///   are decompressed as they are read
+/// * `n_rows` - Read only the first `n_rows` records
+/// * `schema_overrides` - Dict of column -> dtype name ("i64", "f64",
+///   "str", "date", ...) to cast to after reading; with `flatten` use the
+///   dotted names
+/// * `flatten` - Expand one level of nested objects into `parent.child`
+///   columns; deeper nesting stays JSON text
+/// * `infer_schema_length` - Records scanned for keys and types (default
+///   1000); None scans them all, so keys that first appear late aren't missed
+/// * `return_format` - "dict" (default) or "arrow" for an `ArrowTable`, as
+///   in `parse_csv`
+/// 
+/// # Returns
+/// * Dictionary with 'columns' and 'data', or an `ArrowTable`
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// events = insightora_core.parse_ndjson("logs/events.ndjson.gz", flatten=True)
+/// # {"user": {"id": 7, "plan": "pro"}} -> columns "user.id", "user.plan"
+/// ```
+#[pyfunction]
+#[pyo3(signature = (file_path, n_rows=None, schema_overrides=None, flatten=false, infer_schema_length=Some(1000), return_format="dict"))]
+pub fn parse_ndjson(
+    py: Python,
+    file_path: &str,
+    n_rows: Option<usize>,
+    schema_overrides: Option<&PyDict>,
+    flatten: bool,
+    infer_schema_length: Option<usize>,
+    return_format: &str,
+) -> PyResult<PyObject> {
+    let format = ReturnFormat::from_name(return_format)?;
+    let options = NdjsonOptions {
+        n_rows,
+        schema_overrides: schema_overrides.map(declared_schema).transpose()?.unwrap_or_default(),
+        flatten,
+        infer_schema_length,
+    };
+    let df = py.allow_threads(|| json_parser::parse_ndjson(file_path, &options))
+        .map_err(|e| match e {
+            // Overrides naming missing columns and duplicate flattened names
+            InsightoraError::ValidationError(_) => e.into(),
+            other => operation_error("Failed to parse NDJSON", other),
+        })?;
+    let result = table_result(py, &df, format, &ProgressReporter::disabled())?;
+    finish_table(py, df, format, result)
+}
+
+// ============================================================================
+// XML and HTML Python Bindings
+// ============================================================================
+
+use crate::io::xml_parser::{self, MarkupReport};
+use std::collections::HashMap;
+
+/// Helper function to attach markup repair counts to a result dictionary
+fn with_markup_report(py: Python, result: PyObject, report: &MarkupReport) -> PyResult<PyObject> {
+    let dict = result.as_ref(py).downcast::<PyDict>()?;
+    dict.set_item("recovered_records", report.recovered)?;
+    dict.set_item("dropped_records", report.dropped)?;
+    Ok(result)
+}
+
+/// Parse repeating XML records into the standard result dictionary
+/// 
+/// The file is streamed, so large files are never loaded as a whole document.
+/// 
+/// # Arguments
+/// * `file_path` - Path to the XML file
+/// * `record` - Tag or simple path of the record element, e.g. "item" or
+///   "//filing/items/item"
+/// * `field_mapping` - Optional dict of source field -> column name; only
+///   mapped fields are kept. Fields are named by their path relative to the
+///   record: "name", "address/city", "address@type"
+/// * `on_progress` - Optional callable `(percent, stage, detail)` for progress
+/// 
+/// # Returns
+/// * Result dictionary plus `recovered_records` and `dropped_records`
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// result = insightora_core.parse_xml("filing.xml", "items/item", {"@id": "id", "amount": "amount"})
+/// ```
+#[pyfunction]
+#[pyo3(signature = (file_path, record, field_mapping=None, on_progress=None))]
+pub fn parse_xml(
+    py: Python,
+    file_path: &str,
+    record: &str,
+    field_mapping: Option<HashMap<String, String>>,
+    on_progress: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let progress = progress_reporter(on_progress);
+    let stages = progress.stages(&[("parse", 1.0), ("export", 1.0)]);
+    stages[0].report(0.0, file_path);
+    let (df, report) = py
+        .allow_threads(|| xml_parser::parse_xml(file_path, record, field_mapping.as_ref()))
+        .map_err(|e| operation_error("Failed to parse XML", e))?;
+    stages[0].finish(file_path);
+    
+    with_markup_report(py, dataframe_to_pydict_reporting(py, &df, &stages[1])?, &report)
+}
+
+/// Extract `<table>` elements from an HTML file or URL
+/// 
+/// # Arguments
+/// * `source` - Path to an HTML file, or an http(s) URL
+/// * `table_index` - Return only this table (0-based)
+/// 
+/// # Returns
+/// * List of result dictionaries (one per table), or a single dictionary when
+///   `table_index` is given. Each carries `recovered_records`, the number of
+///   rows padded to the table width.
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// tables = insightora_core.parse_html_tables("report.html")
+/// summary = insightora_core.parse_html_tables("https://example.com/filing.html", table_index=2)
+/// ```
+#[pyfunction]
+#[pyo3(signature = (source, table_index=None))]
+pub fn parse_html_tables(py: Python, source: &str, table_index: Option<usize>) -> PyResult<PyObject> {
+    let tables = py
+        .allow_threads(|| xml_parser::parse_html_tables(source, table_index))
+        .map_err(|e| match e {
+            InsightoraError::ValidationError(_) => e.into(),
+            other => operation_error("Failed to parse HTML tables", other),
+        })?;
+    
+    let mut results = tables
+        .iter()
+        .map(|(df, report)| with_markup_report(py, dataframe_to_pydict(py, df)?, report))
+        .collect::<PyResult<Vec<_>>>()?;
+    match table_index {
+        Some(_) => Ok(results.remove(0)),
+        None => Ok(results.into_py(py)),
+    }
+}
+
+// ============================================================================
+// Excel Python Bindings
+// ============================================================================
+
+use crate::io::excel_parser;
+use crate::io::excel_parser::{StreamingExcelConfig, StreamingExcelParser};
+
+/// Parse a worksheet of an Excel or OpenDocument workbook
+/// 
+/// Reads .xlsx, .xlsm, .xlsb, .xls and .ods files natively, without pandas
+/// or openpyxl, with the GIL released. Column types come from the cells:
+/// booleans, whole numbers (Int64), other numbers (Float64), dates, date
+/// times and durations; columns mixing types are text. Empty and error
+/// cells are None. Dates follow the workbook's 1900 or 1904 date system.
+/// 
+/// # Arguments
+/// * `file_path` - Path to the workbook
+/// * `sheet` - Sheet name (default: the first sheet); an unknown name raises
+///   ValueError listing the sheets
+/// * `has_header` - Take column names from the first row (default: True)
+/// * `header_row` - 0-based row holding the column names, counted after
+///   `skip_rows` from the top of `cell_range` or the sheet (default: the
+///   first non-empty row); a row past the data raises ValueError
+/// * `skip_rows` - Rows skipped at the top, e.g. a title block (default: 0)
+/// * `cell_range` - Only read these cells, e.g. "B5:J5000" (default: the
+///   whole sheet); a range past the sheet's used range raises ValueError
+/// * `formulas` - What formula cells hold: "value" (default), the value
+///   Excel last calculated, or "formula", the formula text such as "=A2*2"
+/// * `merged_cells` - Cells a merged region covers beyond its top-left one:
+///   "null" (default) or "repeat" the top-left value (.xlsx and .xlsm only)
+/// 
+/// # Returns
+/// * Dictionary with 'columns' and 'data', as from `parse_csv`
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// result = insightora_core.parse_excel("budget.xlsx", sheet="2024", cell_range="B5:J5000")
+/// ```
+#[pyfunction]
+#[pyo3(signature = (file_path, sheet=None, has_header=true, header_row=None, skip_rows=0, cell_range=None, formulas="value", merged_cells="null"))]
+#[allow(clippy::too_many_arguments)]
+pub fn parse_excel(
+    py: Python,
+    file_path: &str,
+    sheet: Option<&str>,
+    has_header: bool,
+    header_row: Option<usize>,
+    skip_rows: usize,
+    cell_range: Option<&str>,
+    formulas: &str,
+    merged_cells: &str,
+) -> PyResult<PyObject> {
+    let options = excel_options(has_header, header_row, skip_rows, cell_range, formulas, merged_cells)?;
+    let df = py
+        .allow_threads(|| excel_parser::parse_excel(file_path, sheet, &options))
+        .map_err(|e| match e {
+            // Unknown sheet names, and ranges or header rows outside the sheet
+            InsightoraError::ValidationError(_) => e.into(),
+            other => operation_error("Failed to parse Excel workbook", other),
+        })?;
+    
+    dataframe_to_pydict(py, &df)
+}
+
+/// List the sheets of an Excel or OpenDocument workbook
+/// 
+/// Each sheet is read to measure it, so this costs about as much as
+/// parsing every sheet once.
+/// 
+/// # Arguments
+/// * `file_path` - Path to the workbook
+/// 
+/// # Returns
+/// * Dictionary with 'sheets': `name`, `rows` and `columns` of each sheet's
+///   used range (header row included) and `hidden`, in workbook order
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// for sheet in insightora_core.list_excel_sheets("budget.xlsx")["sheets"]:
+///     print(sheet["name"], sheet["rows"], sheet["columns"])
+/// ```
+#[pyfunction]
+pub fn list_excel_sheets(py: Python, file_path: &str) -> PyResult<PyObject> {
+    let sheets = py
+        .allow_threads(|| excel_parser::list_sheets(file_path))
+        .map_err(|e| operation_error("Failed to list Excel sheets", e))?;
+    
+    let records = PyList::empty(py);
+    for sheet in &sheets {
+        let record = PyDict::new(py);
+        record.set_item("name", &sheet.name)?;
+        record.set_item("rows", sheet.rows)?;
+        record.set_item("columns", sheet.columns)?;
+        record.set_item("hidden", sheet.hidden)?;
+        records.append(record)?;
+    }
+    let result = PyDict::new(py);
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    result.set_item("sheets", records)?;
+    Ok(result.into())
+}
+
+/// Parse every sheet of a workbook in one call
+/// 
+/// The workbook is opened and decompressed once, rather than once per
+/// sheet as repeated `parse_excel` calls would; the sheets' cells are then
+/// typed in parallel on the Rayon pool, with the GIL released.
+/// 
+/// # Arguments
+/// * `file_path` - Path to the workbook
+/// * `has_header` - Take column names from each sheet's first row (default: True)
+/// * `strict` - Raise the first sheet's error (in workbook order) instead of
+///   reporting it in 'errors' (default: False)
+/// * `include_hidden` - Also parse sheets hidden in Excel (default: True)
+/// * `header_row`, `skip_rows`, `cell_range` - As in `parse_excel`, for
+///   every sheet
+/// * `formulas` - As in `parse_excel` (default: "value")
+/// * `merged_cells` - As in `parse_excel` (default: "null")
+/// 
+/// # Returns
+/// * Dictionary with 'sheets', sheet name -> dictionary with 'columns' and
+///   'data' as from `parse_csv`, and 'errors', sheet name -> error message
+///   for the sheets that failed; both in workbook order
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// result = insightora_core.parse_excel_all_sheets("budget.xlsx", include_hidden=False)
+/// for name, sheet in result["sheets"].items():
+///     print(name, sheet["num_rows"])
+/// for name, message in result["errors"].items():
+///     print("skipped", name, message)
+/// ```
+#[pyfunction]
+#[pyo3(signature = (file_path, has_header=true, strict=false, include_hidden=true, header_row=None, skip_rows=0, cell_range=None, formulas="value", merged_cells="null"))]
+#[allow(clippy::too_many_arguments)]
+pub fn parse_excel_all_sheets(
+    py: Python,
+    file_path: &str,
+    has_header: bool,
+    strict: bool,
+    include_hidden: bool,
+    header_row: Option<usize>,
+    skip_rows: usize,
+    cell_range: Option<&str>,
+    formulas: &str,
+    merged_cells: &str,
+) -> PyResult<PyObject> {
+    let options = excel_options(has_header, header_row, skip_rows, cell_range, formulas, merged_cells)?;
+    let results = py
+        .allow_threads(|| {
+            excel_parser::ExcelParser::open(file_path).map(|mut parser| parser.parse_all(&options, include_hidden))
+        })
+        .map_err(|e| operation_error("Failed to open Excel workbook", e))?;
+    
+    let sheets = PyDict::new(py);
+    let errors = PyDict::new(py);
+    for (name, result) in results {
+        match result {
+            Ok(df) => sheets.set_item(&name, dataframe_to_pydict(py, &df)?)?,
+            // header_row or cell_range outside this sheet
+            Err(e @ InsightoraError::ValidationError(_)) if strict => return Err(e.into()),
+            Err(e) if strict => {
+                return Err(operation_error(&format!("Failed to parse sheet '{}'", name), e));
+            }
+            Err(e) => errors.set_item(&name, e.to_string())?,
+        }
+    }
+    let result = PyDict::new(py);
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    result.set_item("sheets", sheets)?;
+    result.set_item("errors", errors)?;
+    Ok(result.into())
+}
+
+/// Parse a large .xlsx worksheet row by row, without holding the whole sheet
+/// 
+/// `parse_excel` reads every cell of a sheet into memory before building
+/// columns; this reads the sheet's XML in batches of `chunk_size` rows with
+/// the GIL released and combines them, checking the memory limit after each
+/// batch. The first batch fixes the column types: a later value that
+/// doesn't fit raises an error naming the column and rows. Only .xlsx and
+/// .xlsm workbooks can be streamed; others raise ValueError.
+/// 
+/// # Arguments
+/// * `file_path` - Path to the workbook
+/// * `sheet` - Sheet name (default: the first sheet)
+/// * `chunk_size` - Rows per batch (default: 100000)
+/// * `memory_limit_mb` - Memory limit in MB (default: 1024)
+/// * `has_header` - Take column names from the first row (default: True)
+/// * `progress_callback` - Optional callable `(rows_read, total_rows)`,
+///   called at most 4 times a second and once at the end; `total_rows` is
+///   0 when the sheet doesn't declare its size
+/// * `on_callback_error` - As in `parse_csv_streaming` (default: "report")
+/// * `cancel_token` - Optional `CancellationToken`, as in `parse_csv_streaming`
+/// 
+/// # Returns
+/// * Dictionary with 'columns' and 'data', as from `parse_excel`
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// if insightora_core.should_use_excel_streaming("ledger.xlsx")["recommended"]:
+///     result = insightora_core.parse_excel_streaming("ledger.xlsx", chunk_size=50000)
+/// ```
+#[pyfunction]
+#[pyo3(signature = (file_path, sheet=None, chunk_size=100000, memory_limit_mb=1024, has_header=true, progress_callback=None, on_callback_error="report", cancel_token=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn parse_excel_streaming(
+    py: Python,
+    file_path: &str,
+    sheet: Option<String>,
+    chunk_size: usize,
+    memory_limit_mb: usize,
+    has_header: bool,
+    progress_callback: Option<PyObject>,
+    on_callback_error: &str,
+    cancel_token: Option<PyRef<PyCancellationToken>>,
+) -> PyResult<PyObject> {
+    let config = StreamingExcelConfig { sheet, chunk_size, memory_limit_mb, has_header };
+    let progress = ByteProgress::new(progress_callback, on_callback_error, cancel_flag(cancel_token))?;
+    let parser = progress.attach_excel(StreamingExcelParser::with_config(config));
+    let path = file_path.to_string();
+    let df = run_cancellable(py, &progress.cancel, move || parser.parse_streaming(&path))?
+        .map_err(|e| match e {
+            // Unknown sheets and workbooks other than .xlsx
+            InsightoraError::ValidationError(_) => e.into(),
+            other => progress.error("Failed to parse Excel workbook in streaming mode", other),
+        })?;
+    
+    dataframe_to_pydict(py, &df)
+}
+
+/// Parse a large .xlsx worksheet into an iterator of batch dictionaries
+/// 
+/// As `parse_csv_batches` does for CSV: a reader thread walks the sheet's
+/// XML at most one batch ahead of the consumer, so memory stays around one
+/// batch (plus the workbook's shared strings) whatever the sheet size.
+/// Breaking out of the loop, `close()` or leaving a `with` block stops the
+/// reader and releases the file.
+/// 
+/// # Arguments
+/// * `file_path` - Path to an .xlsx or .xlsm workbook
+/// * `sheet` - Sheet name (default: the first sheet)
+/// * `chunk_size` - Rows per batch (default: 100000); the last may be shorter
+/// * `has_header` - Take column names from the first row (default: True)
+/// * `progress_callback` / `on_callback_error` - Progress in rows, as in
+///   `parse_excel_streaming`; called from the reader thread
+/// * `cancel_token` - Optional `CancellationToken` stopping the iteration
+/// 
+/// # Returns
+/// * `ExcelBatchIterator` yielding dictionaries with 'columns', 'data' and
+///   'batch_index' (0-based); the first batch fixes the column types
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// with insightora_core.parse_excel_batches("ledger.xlsx", sheet="2024") as batches:
+///     for batch in batches:
+///         print(batch["batch_index"], batch["num_rows"])
+/// ```
+#[pyfunction]
+#[pyo3(signature = (file_path, sheet=None, chunk_size=100000, has_header=true, progress_callback=None, on_callback_error="report", cancel_token=None))]
+#[allow(clippy::too_many_arguments)]
+pub fn parse_excel_batches(
+    file_path: &str,
+    sheet: Option<String>,
+    chunk_size: usize,
+    has_header: bool,
+    progress_callback: Option<PyObject>,
+    on_callback_error: &str,
+    cancel_token: Option<PyRef<PyCancellationToken>>,
+) -> PyResult<PyExcelBatchIterator> {
+    if chunk_size == 0 {
+        return Err(PyValueError::new_err("chunk_size must be greater than 0"));
+    }
+    let config = StreamingExcelConfig { sheet, chunk_size, has_header, ..Default::default() };
+    let progress = ByteProgress::new(progress_callback, on_callback_error, cancel_flag(cancel_token))?;
+    let parser = progress.attach_excel(StreamingExcelParser::with_config(config));
+    Ok(PyExcelBatchIterator {
+        prefetcher: Some(BatchPrefetcher::spawn_excel(file_path, parser, 1)?),
+        progress,
+        next_index: 0,
+    })
+}
+
+/// Iterator over the batches of a worksheet, returned by `parse_excel_batches`
+#[pyclass(name = "ExcelBatchIterator")]
+pub struct PyExcelBatchIterator {
+    /// None once closed or exhausted; dropping it stops the reader and closes the file
+    prefetcher: Option<BatchPrefetcher>,
+    progress: ByteProgress,
+    next_index: usize,
+}
+
+#[pymethods]
+impl PyExcelBatchIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+    
+    fn __next__(&mut self, py: Python) -> PyResult<Option<PyObject>> {
+        next_batch_dict(py, &mut self.prefetcher, &self.progress, &mut self.next_index, "Failed to read Excel batch")
+    }
+    
+    /// Stop the reader and release the file (idempotent)
+    fn close(&mut self, py: Python) {
+        if let Some(prefetcher) = self.prefetcher.take() {
+            py.allow_threads(move || drop(prefetcher));
+        }
+    }
+    
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+    
+    fn __exit__(&mut self, py: Python, _exc_type: &PyAny, _exc_value: &PyAny, _traceback: &PyAny) -> bool {
+        self.close(py);
+        false
+    }
+    
+    /// Number of batches returned so far
+    #[getter]
+    fn batches_returned(&self) -> usize {
+        self.next_index
+    }
+    
+    fn __repr__(&self) -> String {
+        format!("ExcelBatchIterator(batches_returned={}, closed={})", self.next_index, self.prefetcher.is_none())
+    }
+}
+
+impl Drop for PyExcelBatchIterator {
+    fn drop(&mut self) {
+        // The reader may be waiting for the GIL to report progress
+        if let Some(prefetcher) = self.prefetcher.take() {
+            if python_available() {
+                Python::with_gil(|py| py.allow_threads(move || drop(prefetcher)));
+            }
+        }
+    }
+}
+
+/// Check if streaming is recommended for an .xlsx workbook
+/// 
+/// Workbooks are compressed, so the file size says little about the memory
+/// a sheet needs; this measures the uncompressed XML of the largest sheet.
+/// 
+/// # Arguments
+/// * `file_path` - Path to an .xlsx or .xlsm workbook
+/// * `memory_limit_mb` - Memory limit in MB (default: 1024)
+/// 
+/// # Returns
+/// * Dictionary with recommendation and estimated memory usage, as from
+///   `should_use_streaming`
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// info = insightora_core.should_use_excel_streaming("ledger.xlsx", memory_limit_mb=512)
+/// print(info["recommended"], info["estimated_memory_mb"])
+/// ```
+#[pyfunction]
+#[pyo3(signature = (file_path, memory_limit_mb=1024))]
+pub fn should_use_excel_streaming(py: Python, file_path: &str, memory_limit_mb: usize) -> PyResult<PyObject> {
+    let parser = StreamingExcelParser::with_config(StreamingExcelConfig { memory_limit_mb, ..Default::default() });
+    let estimated_memory = parser
+        .estimate_memory_usage(file_path)
+        .map_err(|e| operation_error("Failed to estimate memory", e))?;
+    
+    let result = PyDict::new(py);
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    result.set_item("recommended", estimated_memory > memory_limit_mb)?;
+    result.set_item("estimated_memory_mb", estimated_memory)?;
     result.set_item("memory_limit_mb", memory_limit_mb)?;
+    Ok(result.into())
+}
+
+/// Build ExcelOptions from the Python arguments; unknown names raise ValueError
+fn excel_options(
+    has_header: bool,
+    header_row: Option<usize>,
+    skip_rows: usize,
+    cell_range: Option<&str>,
+    formulas: &str,
+    merged_cells: &str,
+) -> PyResult<excel_parser::ExcelOptions> {
+    Ok(excel_parser::ExcelOptions {
+        has_header,
+        header_row,
+        skip_rows,
+        cell_range: cell_range.map(excel_parser::CellRange::parse).transpose()?,
+        formulas: excel_parser::FormulaCells::from_name(formulas)?,
+        merged_cells: excel_parser::MergedCells::from_name(merged_cells)?,
+    })
+}
+
+// ============================================================================
+// pandas Interop Python Bindings
+// ============================================================================
+
+use crate::io::pandas_bridge::pandas_dtype;
+
+/// Helper function to convert a DataFrame into a pandas DataFrame
+/// 
+/// Each column is built with the dtype chosen by `pandas_dtype`, so integer
+/// columns with nulls become `Int64` instead of float64.
+fn dataframe_to_pandas(py: Python, df: &polars::prelude::DataFrame, nullable_dtypes: bool) -> PyResult<PyObject> {
+    let pandas = py.import("pandas")?;
+    let columns = PyDict::new(py);
+    for series in df.get_columns() {
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("name", series.name())?;
+        if let Some(dtype) = pandas_dtype(series, nullable_dtypes) {
+            kwargs.set_item("dtype", dtype)?;
+        }
+        let values = series_to_python_list(py, series)?;
+        columns.set_item(series.name(), pandas.call_method("Series", (values,), Some(kwargs))?)?;
+    }
+    Ok(pandas.call_method1("DataFrame", (columns,))?.into())
+}
+
+/// Convert a result dictionary into a pandas DataFrame
+/// 
+/// Building a DataFrame directly from the result lists lets pandas coerce
+/// integer columns with nulls to float64, corrupting large IDs. This keeps
+/// them exact.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary
+/// * `nullable_dtypes` - Use pandas nullable dtypes (`Int64`, `boolean`,
+///   `string`) for columns containing nulls (default: True). Columns without
+///   nulls always get plain NumPy dtypes.
+/// 
+/// # Returns
+/// * pandas DataFrame
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// df = insightora_core.to_pandas(insightora_core.parse_csv("users.csv"))
+/// df["user_id"].dtype   # Int64 even when some IDs are missing
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, nullable_dtypes=true))]
+pub fn to_pandas(py: Python, data: &PyAny, nullable_dtypes: bool) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    dataframe_to_pandas(py, &df, nullable_dtypes)
+}
+
+// ============================================================================
+// Dataset Python Bindings
+// ============================================================================
+
+use crate::io::dataset::{Dataset, DatasetBuilder, DatasetBuilderConfig, IndexKey, SchemaPolicy};
+
+/// Helper function to convert a batch given to `DatasetBuilder.append`
+/// 
+/// Accepts a result dictionary, a plain `{column: list}` dict, any object
+/// implementing `__arrow_c_stream__` (imported without copying), a pyarrow
+/// RecordBatch (via `to_pydict`) or a pandas DataFrame (via
+/// `to_dict("list")`).
+fn batch_to_dataframe(batch: &PyAny) -> PyResult<polars::prelude::DataFrame> {
+    if batch.hasattr("__arrow_c_stream__")? {
+        return arrow_stream_to_dataframe(batch);
+    }
+    let columns: &PyDict = if let Ok(dict) = batch.downcast::<PyDict>() {
+        if dict.contains("columns")? && dict.contains("data")? {
+            return pydict_to_dataframe(dict);
+        }
+        dict
+    } else if batch.hasattr("to_pydict")? {
+        batch.call_method0("to_pydict")?.downcast()?
+    } else if batch.hasattr("to_dict")? && batch.hasattr("columns")? {
+        batch.call_method1("to_dict", ("list",))?.downcast()?
+    } else {
+        return Err(PyTypeError::new_err(format!(
+            "Unsupported batch type '{}'; expected a dict, pyarrow Table/RecordBatch or pandas DataFrame",
+            batch.get_type().name()?
+        )));
+    };
+    
+    let series = columns.iter()
+        .map(|(name, values)| {
+            let values: &PyList = values.downcast()
+                .map_err(|_| PyTypeError::new_err(format!("Column '{}' of the batch is not a list", name)))?;
+            python_list_to_series(&name.str()?.to_string(), values)
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    polars::prelude::DataFrame::new(series)
+        .map_err(|e| PyValueError::new_err(format!("Invalid batch: {}", e)))
+}
+
+/// Build a Dataset incrementally from Python batches
+/// 
+/// The first batch fixes the columns. Chunks are spilled to temporary IPC
+/// files when the memory budget is nearly used, so large datasets can be
+/// assembled from API pages without holding them all in Python or memory.
+/// 
+/// # Arguments
+/// * `memory_limit_mb` - Budget for chunks held in memory (default: the
+///   configured memory limit)
+/// * `schema_policy` - "error" (default) rejects batches whose columns or
+///   types differ from the first; "widen" widens types and fills missing
+///   columns with None
+/// * `spill_dir` - Directory a private spill directory is created in (default:
+///   `configure(temp_dir=...)`, else the system temp directory)
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// builder = insightora_core.DatasetBuilder(memory_limit_mb=256, schema_policy="widen")
+/// for page in fetch_pages():
+///     builder.append(page)          # dict, pyarrow Table or pandas DataFrame
+/// dataset = builder.finish()
+/// for batch in dataset.batches():
+///     process(batch)
+/// ```
+#[pyclass(name = "DatasetBuilder")]
+pub struct PyDatasetBuilder {
+    /// None once `finish` or `close` has been called
+    inner: Option<DatasetBuilder>,
+    closed: bool,
+}
+
+#[pymethods]
+impl PyDatasetBuilder {
+    #[new]
+    #[pyo3(signature = (memory_limit_mb=None, schema_policy="error", spill_dir=None))]
+    fn new(memory_limit_mb: Option<usize>, schema_policy: &str, spill_dir: Option<String>) -> PyResult<Self> {
+        let config = DatasetBuilderConfig {
+            memory_limit_mb,
+            schema_policy: SchemaPolicy::from_name(schema_policy)?,
+            spill_dir: spill_dir.map(Into::into),
+            ..Default::default()
+        };
+        Ok(Self {
+            inner: Some(DatasetBuilder::new(config)?),
+            closed: false,
+        })
+    }
+    
+    /// Add a batch (dict, pyarrow Table/RecordBatch or pandas DataFrame)
+    fn append(&mut self, py: Python, batch: &PyAny) -> PyResult<()> {
+        let builder = self.builder()?;
+        let df = batch_to_dataframe(batch)?;
+        py.allow_threads(|| builder.append(df))?;
+        Ok(())
+    }
+    
+    /// Complete the builder and return the Dataset; the builder can't be used afterwards
+    fn finish(&mut self) -> PyResult<PyDataset> {
+        self.builder()?;
+        let builder = self.inner.take().expect("checked above");
+        Ok(PyDataset::new(builder.finish()?))
+    }
+    
+    /// Discard the appended batches and delete their spill files (idempotent)
+    fn close(&mut self) {
+        self.inner = None;
+        self.closed = true;
+    }
+    
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+    
+    fn __exit__(&mut self, _exc_type: &PyAny, _exc_value: &PyAny, _traceback: &PyAny) -> bool {
+        self.close();
+        false
+    }
+    
+    /// Rows appended so far
+    #[getter]
+    fn num_rows(&self) -> usize {
+        self.inner.as_ref().map_or(0, |b| b.num_rows())
+    }
+    
+    /// Chunks written to spill files so far
+    #[getter]
+    fn spilled_chunks(&self) -> usize {
+        self.inner.as_ref().map_or(0, |b| b.spilled_chunks())
+    }
+    
+    fn __repr__(&self) -> String {
+        match &self.inner {
+            Some(builder) => format!("DatasetBuilder(num_rows={})", builder.num_rows()),
+            None if self.closed => "DatasetBuilder(closed)".to_string(),
+            None => "DatasetBuilder(finished)".to_string(),
+        }
+    }
+}
+
+impl PyDatasetBuilder {
+    fn builder(&mut self) -> PyResult<&mut DatasetBuilder> {
+        if self.closed {
+            return Err(closed_handle("DatasetBuilder"));
+        }
+        self.inner.as_mut().ok_or_else(|| {
+            PyRuntimeError::new_err("DatasetBuilder is finished; create a new DatasetBuilder to append more batches")
+        })
+    }
+}
+
+/// Table built by `DatasetBuilder`, stored in memory and spill files
+/// 
+/// Spill files are deleted by `close()` (or at the end of a `with` block),
+/// and otherwise when the Dataset is garbage collected. Iterators from
+/// `batches()` share the data, so the files go once they are closed too.
+#[pyclass(name = "Dataset")]
+pub struct PyDataset {
+    /// None once closed
+    inner: Option<Arc<Dataset>>,
+}
+
+impl PyDataset {
+    fn new(dataset: Dataset) -> Self {
+        Self { inner: Some(Arc::new(dataset)) }
+    }
+    
+    fn dataset(&self) -> PyResult<&Arc<Dataset>> {
+        self.inner.as_ref().ok_or_else(|| closed_handle("Dataset"))
+    }
+    
+    /// KeyError listing the columns when `column` isn't one of them
+    fn check_column(&self, column: &str) -> PyResult<()> {
+        match self.dataset()?.column_dtype(column) {
+            Ok(_) => Ok(()),
+            Err(InsightoraError::ValidationError(msg)) => Err(PyKeyError::new_err(msg)),
+            Err(e) => Err(e.into()),
+        }
+    }
+    
+    /// Row position for a Python index, counting from the end when negative
+    fn row_position(&self, row: i64) -> PyResult<usize> {
+        let rows = self.dataset()?.num_rows() as i64;
+        let position = if row < 0 { row + rows } else { row };
+        if (0..rows).contains(&position) {
+            return Ok(position as usize);
+        }
+        Err(PyIndexError::new_err(match rows {
+            0 => format!("Row {} is out of range; the dataset is empty", row),
+            _ => format!("Row {} is out of range; valid rows are {} to {}", row, -rows, rows - 1),
+        }))
+    }
+}
+
+#[pymethods]
+impl PyDataset {
+    #[getter]
+    fn num_rows(&self) -> PyResult<usize> {
+        Ok(self.dataset()?.num_rows())
+    }
+    
+    #[getter]
+    fn columns(&self) -> PyResult<Vec<String>> {
+        Ok(self.dataset()?.schema().iter().map(|(name, _)| name.clone()).collect())
+    }
+    
+    #[getter]
+    fn num_chunks(&self) -> PyResult<usize> {
+        Ok(self.dataset()?.num_chunks())
+    }
+    
+    #[getter]
+    fn spilled_chunks(&self) -> PyResult<usize> {
+        Ok(self.dataset()?.spilled_chunks())
+    }
+    
+    /// Column name -> dtype name
+    fn schema(&self) -> PyResult<HashMap<String, String>> {
+        Ok(self.dataset()?.schema().iter().map(|(name, dtype)| (name.clone(), dtype.to_string())).collect())
+    }
+    
+    /// Load the whole dataset as a result dictionary (subject to the memory limit)
+    fn to_dict(&self, py: Python) -> PyResult<PyObject> {
+        let dataset = Arc::clone(self.dataset()?);
+        let df = py.allow_threads(move || dataset.collect())?;
+        dataframe_to_pydict(py, &df)
+    }
+    
+    /// Iterate over the chunks as result dictionaries, loading one at a time
+    fn batches(&self) -> PyResult<PyDatasetBatches> {
+        Ok(PyDatasetBatches {
+            dataset: Some(Arc::clone(self.dataset()?)),
+            next: 0,
+        })
+    }
+    
+    /// Build a hash index on a column for repeated point lookups
+    /// 
+    /// The index lives on this Dataset; datasets derived from it start
+    /// without indexes. Raises ValueError when `unique` is set and a value
+    /// repeats; with unique=False every matching row is kept.
+    /// 
+    /// # Example
+    /// ```python
+    /// products.create_index("sku")
+    /// row = products.lookup("SKU-0042")
+    /// rows = products.lookup_many(["SKU-0042", "SKU-0077"])
+    /// ```
+    #[pyo3(signature = (column, unique=true))]
+    fn create_index(&self, py: Python, column: &str, unique: bool) -> PyResult<()> {
+        let dataset = Arc::clone(self.dataset()?);
+        py.allow_threads(move || dataset.create_index(column, unique))?;
+        Ok(())
+    }
+    
+    /// Remove the index on a column; returns whether one existed
+    fn drop_index(&self, column: &str) -> PyResult<bool> {
+        Ok(self.dataset()?.drop_index(column))
+    }
+    
+    /// Columns with an index
+    #[getter]
+    fn indexes(&self) -> PyResult<Vec<String>> {
+        Ok(self.dataset()?.indexed_columns())
+    }
+    
+    /// Rows whose indexed column equals `value`, as a result dictionary
+    /// 
+    /// `column` may be omitted when the Dataset has exactly one index.
+    #[pyo3(signature = (value, column=None))]
+    fn lookup(&self, py: Python, value: &PyAny, column: Option<&str>) -> PyResult<PyObject> {
+        self.lookup_many(py, vec![value], column)
+    }
+    
+    /// Rows matching each value in turn, as one result dictionary
+    #[pyo3(signature = (values, column=None))]
+    fn lookup_many(&self, py: Python, values: Vec<&PyAny>, column: Option<&str>) -> PyResult<PyObject> {
+        let column = match column {
+            Some(column) => column.to_string(),
+            None => match self.dataset()?.indexed_columns().as_slice() {
+                [only] => only.clone(),
+                [] => return Err(PyValueError::new_err("Dataset has no index; call create_index first")),
+                _ => return Err(PyValueError::new_err("Dataset has several indexes; pass column=")),
+            },
+        };
+        let keys = values.into_iter().map(index_key).collect::<PyResult<Vec<_>>>()?;
+        let dataset = Arc::clone(self.dataset()?);
+        let df = py.allow_threads(move || dataset.lookup_many(&column, &keys))?;
+        dataframe_to_pydict(py, &df)
+    }
+    
+    /// One column as a list, or its first `limit` values
+    /// 
+    /// Only this column is read from the chunks; the others are not loaded
+    /// or converted. Values keep their dtype as in result dictionaries. With
+    /// numpy=True a NumPy array is returned instead: bool, int64 and uint64
+    /// columns without nulls keep their type, other numeric columns become
+    /// float64 with NaN for null, and anything else is an object array.
+    /// Raises KeyError for an unknown column.
+    /// 
+    /// # Example
+    /// ```python
+    /// prices = dataset.column("price", numpy=True)
+    /// first_skus = dataset.column("sku", limit=10)
+    /// ```
+    #[pyo3(signature = (name, limit=None, numpy=false))]
+    fn column(&self, py: Python, name: &str, limit: Option<usize>, numpy: bool) -> PyResult<PyObject> {
+        self.check_column(name)?;
+        let dataset = Arc::clone(self.dataset()?);
+        let series = py.allow_threads(move || dataset.column(name, limit))?;
+        match numpy {
+            true => series_to_numpy(py, &series),
+            false => series_to_python_list(py, &series),
+        }
+    }
+    
+    /// Value of `column` at `row` as a Python scalar
+    /// 
+    /// Negative rows count from the end. Raises IndexError for a row out of
+    /// range and KeyError for an unknown column.
+    /// 
+    /// # Example
+    /// ```python
+    /// last_price = dataset.cell(-1, "price")
+    /// ```
+    fn cell(&self, py: Python, row: i64, column: &str) -> PyResult<PyObject> {
+        let row = self.row_position(row)?;
+        self.check_column(column)?;
+        let dataset = Arc::clone(self.dataset()?);
+        let series = py.allow_threads(move || dataset.column_range(column, row, 1))?;
+        Ok(series_to_python_list(py, &series)?.as_ref(py).get_item(0)?.into())
+    }
+    
+    /// One row as a dictionary of column -> value
+    /// 
+    /// Only the chunk holding the row is read. Negative rows count from the
+    /// end; raises IndexError for a row out of range.
+    fn row(&self, py: Python, row: i64) -> PyResult<PyObject> {
+        let row = self.row_position(row)?;
+        let dataset = Arc::clone(self.dataset()?);
+        let df = py.allow_threads(move || dataset.row(row))?;
+        let values = PyDict::new(py);
+        for series in df.get_columns() {
+            values.set_item(series.name(), series_to_python_list(py, series)?.as_ref(py).get_item(0)?)?;
+        }
+        Ok(values.into())
+    }
+    
+    /// Distinct values of a column in first-seen order, None included
+    /// 
+    /// With a `limit`, chunks stop being read once that many values are
+    /// found. Raises KeyError for an unknown column.
+    /// 
+    /// # Example
+    /// ```python
+    /// regions = dataset.unique("region")
+    /// ```
+    #[pyo3(signature = (column, limit=None))]
+    fn unique(&self, py: Python, column: &str, limit: Option<usize>) -> PyResult<PyObject> {
+        self.check_column(column)?;
+        let dataset = Arc::clone(self.dataset()?);
+        let series = py.allow_threads(move || dataset.unique(column, limit))?;
+        series_to_python_list(py, &series)
+    }
+    
+    /// Release the data and delete spill files not shared with open iterators (idempotent)
+    fn close(&mut self) {
+        self.inner = None;
+    }
+    
+    #[getter]
+    fn closed(&self) -> bool {
+        self.inner.is_none()
+    }
+    
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+    
+    fn __exit__(&mut self, _exc_type: &PyAny, _exc_value: &PyAny, _traceback: &PyAny) -> bool {
+        self.close();
+        false
+    }
+    
+    fn __len__(&self) -> PyResult<usize> {
+        self.num_rows()
+    }
+    
+    fn __repr__(&self) -> String {
+        match &self.inner {
+            Some(dataset) => format!(
+                "Dataset(num_rows={}, columns={:?}, chunks={})",
+                dataset.num_rows(),
+                dataset.schema().iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(),
+                dataset.num_chunks()
+            ),
+            None => "Dataset(closed)".to_string(),
+        }
+    }
+}
+
+/// Helper function to convert a column to a NumPy array
+/// 
+/// Bool and integer columns without nulls keep their type (as int64 or
+/// uint64), numeric columns with nulls become float64 with NaN, and other
+/// dtypes become object arrays of the values `series_to_python_list` gives.
+fn series_to_numpy(py: Python, series: &polars::prelude::Series) -> PyResult<PyObject> {
+    use polars::prelude::*;
+    
+    let numpy = py.import("numpy")?;
+    let failed = |e: PolarsError| PyRuntimeError::new_err(format!("Failed to read column '{}': {}", series.name(), e));
+    let no_nulls = series.null_count() == 0;
+    let (bytes, dtype): (Vec<u8>, &str) = match series.dtype() {
+        DataType::Boolean if no_nulls => {
+            (series.bool().map_err(failed)?.into_no_null_iter().map(u8::from).collect(), "bool")
+        }
+        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64 if no_nulls => {
+            let values = series.cast(&DataType::Int64).map_err(failed)?;
+            (values.i64().map_err(failed)?.into_no_null_iter().flat_map(i64::to_ne_bytes).collect(), "int64")
+        }
+        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 if no_nulls => {
+            let values = series.cast(&DataType::UInt64).map_err(failed)?;
+            (values.u64().map_err(failed)?.into_no_null_iter().flat_map(u64::to_ne_bytes).collect(), "uint64")
+        }
+        dtype if dtype.is_numeric() => {
+            let values = series.cast(&DataType::Float64).map_err(failed)?;
+            let values = values.f64().map_err(failed)?;
+            (values.into_iter().flat_map(|v| v.unwrap_or(f64::NAN).to_ne_bytes()).collect(), "float64")
+        }
+        _ => return Ok(numpy.call_method1("array", (series_to_python_list(py, series)?, "object"))?.into()),
+    };
+    // frombuffer shares the read-only bytes; the copy is writable
+    let array = numpy.call_method1("frombuffer", (PyBytes::new(py, &bytes), dtype))?;
+    Ok(array.call_method0("copy")?.into())
+}
+
+/// Helper function to convert a lookup value to an index key
+fn index_key(value: &PyAny) -> PyResult<IndexKey> {
+    use pyo3::types::{PyBool, PyLong, PyString};
+    if value.is_instance_of::<PyBool>() {
+        Ok(IndexKey::Bool(value.extract()?))
+    } else if value.is_instance_of::<PyLong>() {
+        Ok(IndexKey::Int(value.extract()?))
+    } else if value.is_instance_of::<PyString>() {
+        Ok(IndexKey::Str(value.extract()?))
+    } else {
+        Err(PyTypeError::new_err(format!(
+            "Unsupported lookup value type '{}'; expected int, str or bool",
+            value.get_type().name()?
+        )))
+    }
+}
+
+/// Iterator over the chunks of a Dataset
+#[pyclass(name = "DatasetBatches")]
+pub struct PyDatasetBatches {
+    /// None once closed
+    dataset: Option<Arc<Dataset>>,
+    next: usize,
+}
+
+#[pymethods]
+impl PyDatasetBatches {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+    
+    fn __next__(&mut self, py: Python) -> PyResult<Option<PyObject>> {
+        let dataset = self.dataset.as_ref().ok_or_else(|| closed_handle("DatasetBatches"))?;
+        if self.next >= dataset.num_chunks() {
+            return Ok(None);
+        }
+        let (dataset, index) = (Arc::clone(dataset), self.next);
+        let df = py.allow_threads(move || dataset.chunk(index))?;
+        self.next += 1;
+        Ok(Some(dataframe_to_pydict(py, &df)?))
+    }
+    
+    /// Stop iterating and release this iterator's share of the Dataset (idempotent)
+    fn close(&mut self) {
+        self.dataset = None;
+    }
+    
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+    
+    fn __exit__(&mut self, _exc_type: &PyAny, _exc_value: &PyAny, _traceback: &PyAny) -> bool {
+        self.close();
+        false
+    }
+}
+
+// ============================================================================
+// Inline Data Python Bindings
+// ============================================================================
+
+/// Python type of one literal value, for dtype inference
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LiteralKind {
+    Bool,
+    Int,
+    Float,
+    Decimal,
+    Datetime,
+    Date,
+    Duration,
+    Text,
+}
+
+impl LiteralKind {
+    fn of(value: &PyAny, decimal_type: &PyAny) -> PyResult<Self> {
+        use pyo3::types::{PyBool, PyDate, PyDateTime, PyDelta, PyFloat, PyLong};
+        // bool is a subclass of int and datetime of date, so check them first
+        Ok(if value.is_instance_of::<PyBool>() {
+            LiteralKind::Bool
+        } else if value.is_instance_of::<PyLong>() {
+            LiteralKind::Int
+        } else if value.is_instance_of::<PyFloat>() {
+            LiteralKind::Float
+        } else if value.is_instance(decimal_type)? {
+            LiteralKind::Decimal
+        } else if value.is_instance_of::<PyDateTime>() {
+            LiteralKind::Datetime
+        } else if value.is_instance_of::<PyDate>() {
+            LiteralKind::Date
+        } else if value.is_instance_of::<PyDelta>() {
+            LiteralKind::Duration
+        } else {
+            LiteralKind::Text
+        })
+    }
+    
+    fn name(&self) -> &'static str {
+        match self {
+            LiteralKind::Bool => "bool",
+            LiteralKind::Int => "int",
+            LiteralKind::Float => "float",
+            LiteralKind::Decimal => "Decimal",
+            LiteralKind::Datetime => "datetime",
+            LiteralKind::Date => "date",
+            LiteralKind::Duration => "timedelta",
+            LiteralKind::Text => "str",
+        }
+    }
+}
+
+/// Helper function to build a Series from Python literals
+/// 
+/// None is null. bool, int, float and str map as in result dicts;
+/// datetime maps to Datetime (aware values are converted to UTC), date to
+/// Date, timedelta to Duration and Decimal to Decimal with the largest
+/// scale in the column. Integers mixed with floats widen to Float64 and
+/// integers mixed with Decimals become Decimals. Other mixes follow
+/// `policy`: `Error` rejects the column and `Widen` reads it as text. A
+/// declared dtype skips inference and is cast to strictly.
+fn literal_series(
+    name: &str,
+    values: &[&PyAny],
+    declared: Option<&polars::prelude::DataType>,
+    policy: SchemaPolicy,
+) -> PyResult<polars::prelude::Series> {
+    use polars::prelude::*;
+    use pyo3::types::IntoPyDict;
+    
+    let py = match values.first() {
+        Some(value) => value.py(),
+        None => {
+            return Ok(Series::new_empty(name, &declared.cloned().unwrap_or(DataType::Null)));
+        }
+    };
+    let decimal_type = py.import("decimal")?.getattr("Decimal")?;
+    let mut kinds: Vec<LiteralKind> = Vec::new();
+    for value in values.iter().filter(|v| !v.is_none()) {
+        let kind = LiteralKind::of(value, decimal_type)?;
+        if !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+    }
+    
+    let inferred = match kinds.as_slice() {
+        [] => None,
+        [only] => Some(*only),
+        mixed if mixed.iter().all(|k| matches!(k, LiteralKind::Int | LiteralKind::Float)) => Some(LiteralKind::Float),
+        mixed if mixed.iter().all(|k| matches!(k, LiteralKind::Int | LiteralKind::Decimal)) => Some(LiteralKind::Decimal),
+        mixed => match policy {
+            SchemaPolicy::Widen => Some(LiteralKind::Text),
+            SchemaPolicy::Error => {
+                let names: Vec<&str> = mixed.iter().map(|k| k.name()).collect();
+                return Err(PyValueError::new_err(format!(
+                    "Column '{}' mixes {} values; declare its dtype or pass schema_policy='widen' to read it as text",
+                    name,
+                    names.join(", ")
+                )));
+            }
+        },
+    };
+    let invalid = |e: PolarsError| PyValueError::new_err(format!("Invalid values for column '{}': {}", name, e));
+    
+    // Decimals, and ints bound for a Decimal column, are scaled exactly
+    // rather than passing through float
+    let declared_scale = match declared {
+        Some(DataType::Decimal(_, Some(scale))) => Some(*scale),
+        _ => None,
+    };
+    
+    let series = match inferred {
+        Some(LiteralKind::Decimal) => {
+            let scale = match declared_scale {
+                Some(scale) => scale,
+                None => decimal_scale(name, values, decimal_type)?,
+            };
+            decimal_series(name, values, scale)?
+        }
+        Some(LiteralKind::Int) if declared_scale.is_some() => {
+            decimal_series(name, values, declared_scale.unwrap_or_default())?
+        }
+        None => Series::full_null(name, values.len(), &DataType::Null),
+        Some(LiteralKind::Datetime) => {
+            let datetime = py.import("datetime")?;
+            let naive_epoch = datetime.getattr("datetime")?.call1((1970, 1, 1))?;
+            let aware_epoch = naive_epoch.call_method("replace", (), Some([("tzinfo", datetime.getattr("timezone")?.getattr("utc")?)].into_py_dict(py)))?;
+            let microsecond = datetime.getattr("timedelta")?.call((), Some([("microseconds", 1)].into_py_dict(py)))?;
+            let is_aware = |v: &PyAny| -> PyResult<bool> { Ok(!v.call_method0("utcoffset")?.is_none()) };
+            let aware = match values.iter().find(|v| !v.is_none()) {
+                Some(first) => is_aware(first)?,
+                None => false,
+            };
+            let epoch = if aware { aware_epoch } else { naive_epoch };
+            let micros = values.iter()
+                .map(|v| {
+                    if v.is_none() {
+                        return Ok(None);
+                    }
+                    if is_aware(v)? != aware {
+                        return Err(PyValueError::new_err(format!(
+                            "Column '{}' mixes timezone-aware and naive datetimes",
+                            name
+                        )));
+                    }
+                    Ok(Some(v.call_method1("__sub__", (epoch,))?.call_method1("__floordiv__", (microsecond,))?.extract::<i64>()?))
+                })
+                .collect::<PyResult<Vec<Option<i64>>>>()?;
+            let timezone = aware.then(|| "UTC".to_string());
+            Series::new(name, micros)
+                .cast(&DataType::Datetime(TimeUnit::Microseconds, timezone))
+                .map_err(invalid)?
+        }
+        Some(LiteralKind::Date) => {
+            // date.toordinal() of 1970-01-01
+            const EPOCH_ORDINAL: i32 = 719_163;
+            let days = values.iter()
+                .map(|v| if v.is_none() { Ok(None) } else { Ok(Some(v.call_method0("toordinal")?.extract::<i32>()? - EPOCH_ORDINAL)) })
+                .collect::<PyResult<Vec<Option<i32>>>>()?;
+            Series::new(name, days).cast(&DataType::Date).map_err(invalid)?
+        }
+        Some(LiteralKind::Duration) => {
+            python_list_to_series(name, PyList::new(py, values))?
+        }
+        Some(LiteralKind::Text) => {
+            let strings = values.iter()
+                .map(|v| if v.is_none() { Ok(None) } else { Ok(Some(v.str()?.to_string())) })
+                .collect::<PyResult<Vec<Option<String>>>>()?;
+            Series::new(name, strings)
+        }
+        Some(LiteralKind::Float) => {
+            Series::new(name, values.iter().map(|v| v.extract::<Option<f64>>()).collect::<PyResult<Vec<_>>>()?)
+        }
+        Some(LiteralKind::Int) => {
+            Series::new(name, values.iter().map(|v| v.extract::<Option<i64>>()).collect::<PyResult<Vec<_>>>()?)
+        }
+        Some(LiteralKind::Bool) => {
+            Series::new(name, values.iter().map(|v| v.extract::<Option<bool>>()).collect::<PyResult<Vec<_>>>()?)
+        }
+    };
+    
+    match declared {
+        Some(dtype) if series.dtype() != dtype => series.strict_cast(dtype).map_err(invalid),
+        _ => Ok(series),
+    }
+}
+
+/// Helper function to find the largest number of decimal places in a column
+fn decimal_scale(name: &str, values: &[&PyAny], decimal_type: &PyAny) -> PyResult<usize> {
+    let mut scale = 0;
+    for value in values.iter().filter(|v| v.is_instance(decimal_type).unwrap_or(false)) {
+        let exponent: i64 = value.call_method0("as_tuple")?.getattr("exponent")?.extract()
+            .map_err(|_| PyValueError::new_err(format!("Column '{}' holds a non-finite Decimal ({})", name, value)))?;
+        scale = scale.max((-exponent).max(0) as usize);
+    }
+    if scale > 38 {
+        return Err(PyValueError::new_err(format!("Column '{}' has Decimals with more than 38 decimal places", name)));
+    }
+    Ok(scale)
+}
+
+/// Helper function to build a Decimal series from ints and Decimals
+fn decimal_series(name: &str, values: &[&PyAny], scale: usize) -> PyResult<polars::prelude::Series> {
+    use polars::prelude::*;
+    
+    let scaled = values.iter()
+        .map(|v| if v.is_none() { Ok(None) } else { scaled_decimal(name, v, scale).map(Some) })
+        .collect::<PyResult<Vec<Option<i128>>>>()?;
+    Ok(Int128Chunked::from_iter_options(name, scaled.into_iter())
+        .into_decimal_unchecked(Some(38), scale)
+        .into_series())
+}
+
+/// Helper function to read an int or Decimal as an integer scaled by 10^scale
+fn scaled_decimal(name: &str, value: &PyAny, scale: usize) -> PyResult<i128> {
+    let overflow = || PyValueError::new_err(format!("Decimal {} in column '{}' does not fit 38 digits", value, name));
+    let decimal = value.py().import("decimal")?.getattr("Decimal")?.call1((value,))?;
+    let parts = decimal.call_method0("as_tuple")?;
+    let exponent: i64 = parts.getattr("exponent")?.extract()
+        .map_err(|_| PyValueError::new_err(format!("Column '{}' holds a non-finite Decimal ({})", name, value)))?;
+    let digits: Vec<u8> = parts.getattr("digits")?.extract()?;
+    let mut scaled: i128 = 0;
+    for digit in digits {
+        scaled = scaled.checked_mul(10).and_then(|s| s.checked_add(digit as i128)).ok_or_else(overflow)?;
+    }
+    let shift = scale as i64 + exponent;
+    if shift < 0 {
+        return Err(PyValueError::new_err(format!(
+            "Decimal {} in column '{}' has more than {} decimal places",
+            value, name, scale
+        )));
+    }
+    for _ in 0..shift {
+        scaled = scaled.checked_mul(10).ok_or_else(overflow)?;
+    }
+    let sign: i64 = parts.getattr("sign")?.extract()?;
+    Ok(if sign == 1 { -scaled } else { scaled })
+}
+
+/// Helper function to build a single-batch Dataset from named columns
+fn literal_dataset(
+    columns: Vec<(String, Vec<&PyAny>)>,
+    schema: Option<&PyDict>,
+    schema_policy: &str,
+) -> PyResult<PyDataset> {
+    let policy = SchemaPolicy::from_name(schema_policy)?;
+    let declared = schema.map(declared_schema).transpose()?.unwrap_or_default();
+    if !declared.is_empty() {
+        if let Some((name, _)) = columns.iter().find(|(name, _)| !declared.iter().any(|(d, _)| d == name)) {
+            return Err(PyValueError::new_err(format!("Column '{}' is not in the declared schema", name)));
+        }
+    }
+    let height = columns.first().map_or(0, |(_, values)| values.len());
+    if let Some((name, values)) = columns.iter().find(|(_, values)| values.len() != height) {
+        return Err(PyValueError::new_err(format!(
+            "Column '{}' has {} values but the first column has {}",
+            name,
+            values.len(),
+            height
+        )));
+    }
+    
+    let mut series = Vec::new();
+    for (name, dtype) in &declared {
+        match columns.iter().find(|(column, _)| column == name) {
+            Some((_, values)) => series.push(literal_series(name, values, Some(dtype), policy)?),
+            None => series.push(polars::prelude::Series::full_null(name, height, dtype)),
+        }
+    }
+    if declared.is_empty() {
+        for (name, values) in &columns {
+            series.push(literal_series(name, values, None, policy)?);
+        }
+    }
+    if series.is_empty() {
+        return Err(PyValueError::new_err("No columns to build a Dataset from; pass schema= for empty input"));
+    }
+    
+    let df = polars::prelude::DataFrame::new(series)
+        .map_err(|e| PyValueError::new_err(format!("Invalid data: {}", e)))?;
+    let mut builder = DatasetBuilder::new(DatasetBuilderConfig { schema_policy: policy, ..Default::default() })?;
+    builder.append(df)?;
+    Ok(PyDataset::new(builder.finish()?))
+}
+
+/// Build a Dataset from a list of dicts, one per row
+/// 
+/// Columns appear in first-seen key order; a key missing from a row is
+/// null. Types are inferred from the values (see `from_columns`).
+/// 
+/// # Arguments
+/// * `records` - List of dicts
+/// * `schema` - Optional `{column: dtype name}` ("i64", "f64", "str",
+///   "bool", "date", "datetime[μs]", "decimal[38,2]", ...); fixes the
+///   columns and their order, and is required for an empty list
+/// * `schema_policy` - "error" (default) rejects a column mixing types that
+///   don't widen numerically; "widen" reads it as text
+/// 
+/// # Returns
+/// * Dataset
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// from decimal import Decimal
+/// 
+/// rates = insightora_core.from_records([
+///     {"currency": "EUR", "rate": Decimal("1.0845")},
+///     {"currency": "GBP", "rate": Decimal("1.2712")},
+/// ])
+/// empty = insightora_core.from_records([], schema={"currency": "str", "rate": "decimal[38,4]"})
+/// ```
+#[pyfunction]
+#[pyo3(signature = (records, schema=None, schema_policy="error"))]
+pub fn from_records(records: &PyList, schema: Option<&PyDict>, schema_policy: &str) -> PyResult<PyDataset> {
+    let rows = records.iter()
+        .enumerate()
+        .map(|(i, record)| {
+            record.downcast::<PyDict>()
+                .map_err(|_| PyTypeError::new_err(format!("Record {} is a {}, not a dict", i, record.get_type().name().unwrap_or("?"))))
+        })
+        .collect::<PyResult<Vec<&PyDict>>>()?;
+    
+    let mut names: Vec<String> = Vec::new();
+    for row in &rows {
+        for key in row.keys() {
+            let key: String = key.extract()?;
+            if !names.contains(&key) {
+                names.push(key);
+            }
+        }
+    }
+    let none = records.py().None().into_ref(records.py());
+    let columns = names.into_iter()
+        .map(|name| {
+            let values = rows.iter()
+                .map(|row| Ok(row.get_item(&name)?.unwrap_or(none)))
+                .collect::<PyResult<Vec<&PyAny>>>()?;
+            Ok((name, values))
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    literal_dataset(columns, schema, schema_policy)
+}
+
+/// Build a Dataset from a dict of column name -> list of values
+/// 
+/// None is null; bool, int, float and str keep their types; datetime
+/// becomes Datetime (aware values in UTC), date Date, timedelta Duration
+/// and Decimal Decimal with the column's largest scale. Integers mixed with
+/// floats widen to float, and with Decimals to Decimal. Empty lists give a
+/// zero-row Dataset (null-typed columns unless `schema` declares them).
+/// 
+/// # Arguments
+/// * `columns` - Dict of column name to list of values, all the same length
+/// * `schema` / `schema_policy` - As in `from_records`
+/// 
+/// # Returns
+/// * Dataset
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// from datetime import datetime
+/// 
+/// lookup = insightora_core.from_columns({
+///     "sku": ["A-1", "B-2"],
+///     "launched": [datetime(2024, 1, 5), None],
+/// })
+/// lookup.create_index("sku")
+/// ```
+#[pyfunction]
+#[pyo3(signature = (columns, schema=None, schema_policy="error"))]
+pub fn from_columns(columns: &PyDict, schema: Option<&PyDict>, schema_policy: &str) -> PyResult<PyDataset> {
+    let columns = columns.iter()
+        .map(|(name, values)| {
+            let name: String = name.extract()?;
+            let values = values.iter()
+                .map_err(|_| PyTypeError::new_err(format!("Column '{}' is not a list of values", name)))?
+                .collect::<PyResult<Vec<&PyAny>>>()?;
+            Ok((name, values))
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    literal_dataset(columns, schema, schema_policy)
+}
+
+/// Convert data to a list of dicts, one per row
+/// 
+/// # Arguments
+/// * `data` - Dataset, result dictionary, pyarrow Table or pandas DataFrame
+/// * `limit` - Optional maximum number of rows
+/// 
+/// # Returns
+/// * List of `{column: value}` dicts; values are converted as in result
+///   dictionaries
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// rows = insightora_core.to_records(result, limit=5)
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, limit=None))]
+pub fn to_records(py: Python, data: &PyAny, limit: Option<usize>) -> PyResult<PyObject> {
+    let df = match data.extract::<PyRef<PyDataset>>() {
+        Ok(dataset) => {
+            let dataset = Arc::clone(dataset.dataset()?);
+            py.allow_threads(move || -> Result<_, InsightoraError> {
+                let Some(limit) = limit else {
+                    return dataset.collect();
+                };
+                // Load only the chunks the limit reaches
+                let mut rows: Option<polars::prelude::DataFrame> = None;
+                for chunk in dataset.iter_chunks() {
+                    let chunk = chunk?;
+                    let taken = rows.as_ref().map_or(0, |df| df.height());
+                    if taken >= limit {
+                        break;
+                    }
+                    let chunk = chunk.head(Some(limit - taken));
+                    match rows.as_mut() {
+                        Some(df) => {
+                            df.vstack_mut(&chunk)?;
+                        }
+                        None => rows = Some(chunk),
+                    }
+                }
+                match rows {
+                    Some(df) => Ok(df),
+                    None => dataset.collect(),
+                }
+            })?
+        }
+        Err(_) => batch_to_dataframe(data)?,
+    };
+    let df = match limit {
+        Some(limit) => df.head(Some(limit)),
+        None => df,
+    };
+    
+    let names = df.get_column_names();
+    let columns = df.get_columns()
+        .iter()
+        .map(|series| series_to_python_list(py, series))
+        .collect::<PyResult<Vec<PyObject>>>()?;
+    let records = PyList::empty(py);
+    for row in 0..df.height() {
+        let record = PyDict::new(py);
+        for (name, column) in names.iter().zip(&columns) {
+            record.set_item(name, column.as_ref(py).get_item(row)?)?;
+        }
+        records.append(record)?;
+    }
+    Ok(records.into())
+}
+
+// ============================================================================
+// JSON Export Python Bindings
+// ============================================================================
+
+use crate::io::json_writer::{self, JsonOrient, JsonSource, JsonWriteOptions, NonFinitePolicy};
+
+/// Data accepted by the JSON exporters: a result dictionary or a Dataset
+enum JsonInput {
+    Frame(polars::prelude::DataFrame),
+    Dataset(Arc<Dataset>),
+}
+
+impl JsonInput {
+    fn extract(data: &PyAny) -> PyResult<Self> {
+        if let Ok(dataset) = data.extract::<PyRef<PyDataset>>() {
+            return Ok(JsonInput::Dataset(Arc::clone(dataset.dataset()?)));
+        }
+        if data.is_instance_of::<PyDict>() || data.hasattr("__arrow_c_stream__")? {
+            return Ok(JsonInput::Frame(pydict_to_dataframe(data)?));
+        }
+        Err(PyTypeError::new_err(format!(
+            "Unsupported data type '{}'; expected a result dictionary, Dataset or Arrow table",
+            data.get_type().name()?
+        )))
+    }
+
+    fn source(&self) -> JsonSource<'_> {
+        match self {
+            JsonInput::Frame(df) => JsonSource::Frame(df),
+            JsonInput::Dataset(dataset) => JsonSource::Dataset(dataset),
+        }
+    }
+}
+
+/// Serialize a result or Dataset as JSON without building Python objects
+/// 
+/// Values are written by Rust in parallel batches: floats in their shortest
+/// round-tripping form, dates and datetimes as ISO 8601 strings and
+/// durations as ISO 8601 durations ("PT1H30M"). A Dataset is streamed one
+/// chunk at a time.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary or Dataset
+/// * `orient` - "records" (`[{col: value}]`), "columns" (`{col: [values]}`)
+///   or "split" (`{"columns": [...], "data": [[...]]}`) (default: "records")
+/// * `path` - Write to this file instead of returning the document
+/// * `non_finite` - NaN/Infinity handling: "null", "string" ("NaN",
+///   "Infinity", "-Infinity") or "error" (default: "null")
+/// * `as_bytes` - Return bytes instead of str (default: False)
+/// 
+/// # Returns
+/// * The JSON document as str (or bytes), or the number of rows written when `path` is given
+/// 
+/// # Example
+/// ```python
+/// import json
+/// import insightora_core
+/// 
+/// result = insightora_core.parse_csv("orders.csv")
+/// rows = json.loads(insightora_core.to_json(result))
+/// insightora_core.to_json(result, orient="split", path="orders.json")
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, orient="records", path=None, non_finite="null", as_bytes=false))]
+pub fn to_json(
+    py: Python,
+    data: &PyAny,
+    orient: &str,
+    path: Option<&str>,
+    non_finite: &str,
+    as_bytes: bool,
+) -> PyResult<PyObject> {
+    let input = JsonInput::extract(data)?;
+    let options = JsonWriteOptions {
+        orient: JsonOrient::from_name(orient)?,
+        non_finite: NonFinitePolicy::from_name(non_finite)?,
+        ..Default::default()
+    };
+
+    if let Some(path) = path {
+        let rows = py
+            .allow_threads(|| json_writer::write_json(input.source(), path, &options))
+            .map_err(|e| operation_error("Failed to write JSON", e))?;
+        return Ok(rows.into_py(py));
+    }
+    let text = py
+        .allow_threads(|| json_writer::to_json_string(input.source(), &options))
+        .map_err(|e| operation_error("Failed to serialize JSON", e))?;
+    Ok(if as_bytes {
+        PyBytes::new(py, text.as_bytes()).into_py(py)
+    } else {
+        text.into_py(py)
+    })
+}
+
+/// Write a result or Dataset as JSON Lines (one record object per line)
+/// 
+/// Values are formatted as in `to_json`.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary or Dataset
+/// * `path` - Output file
+/// * `non_finite` - "null", "string" or "error" (default: "null")
+/// 
+/// # Returns
+/// * Number of rows written
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// dataset = builder.finish()
+/// insightora_core.to_ndjson(dataset, "events.jsonl")
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, path, non_finite="null"))]
+pub fn to_ndjson(py: Python, data: &PyAny, path: &str, non_finite: &str) -> PyResult<usize> {
+    let input = JsonInput::extract(data)?;
+    let options = JsonWriteOptions {
+        non_finite: NonFinitePolicy::from_name(non_finite)?,
+        ..Default::default()
+    };
+    py.allow_threads(|| json_writer::write_ndjson(input.source(), path, &options))
+        .map_err(|e| operation_error("Failed to write JSON Lines", e))
+}
+
+/// Write a result or Dataset to a JSON or JSON Lines file
+/// 
+/// The file counterpart of `to_json` / `to_ndjson`. Rows are rendered and
+/// written a batch at a time, so even with `lines=False` the document is
+/// never held in memory as one string; with `lines=True` a Dataset is also
+/// read one chunk at a time. Nulls are written as `null`, dates and
+/// datetimes as ISO 8601 strings.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary, Dataset or Arrow table
+/// * `file_path` - Output file
+/// * `orient` - "records" (`[{col: value}]`), "columns" (`{col: [values]}`)
+///   or "split", as in `to_json`; ignored with `lines=True`
+/// * `lines` - Write one record object per line (NDJSON) instead of one document
+/// * `nan_policy` - NaN/Infinity handling: "null", "string" ("NaN",
+///   "Infinity", "-Infinity") or "error" (default: "null")
+/// 
+/// # Returns
+/// * Number of rows written
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// result = insightora_core.parse_csv("orders.csv")
+/// insightora_core.write_json(result, "orders.json", orient="columns")
+/// insightora_core.write_json(result, "orders.ndjson", lines=True, nan_policy="error")
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, file_path, orient="records", lines=false, nan_policy="null"))]
+pub fn write_json(py: Python, data: &PyAny, file_path: &str, orient: &str, lines: bool, nan_policy: &str) -> PyResult<usize> {
+    let input = JsonInput::extract(data)?;
+    let options = JsonWriteOptions {
+        orient: JsonOrient::from_name(orient)?,
+        non_finite: NonFinitePolicy::from_name(nan_policy)?,
+        ..Default::default()
+    };
+    py.allow_threads(|| {
+        if lines {
+            json_writer::write_ndjson(input.source(), file_path, &options)
+        } else {
+            json_writer::write_json(input.source(), file_path, &options)
+        }
+    })
+    .map_err(|e| operation_error("Failed to write JSON", e))
+}
+
+// ============================================================================
+// Parquet Export Python Bindings
+// ============================================================================
+
+use crate::io::parquet_writer::{self, ParquetWriteReport, SchemaEnforcement};
+
+/// Helper function to read a `{column: dtype name}` dict, keeping its order
+fn declared_schema(schema: &PyDict) -> PyResult<Vec<(String, polars::prelude::DataType)>> {
+    schema
+        .iter()
+        .map(|(name, dtype)| Ok((name.extract::<String>()?, dtype_from_name(dtype.extract()?)?)))
+        .collect()
+}
+
+/// Helper function to build schema enforcement from binding arguments
+fn schema_enforcement(schema: Option<&PyDict>, allow_missing: bool, drop_extra: bool) -> PyResult<Option<SchemaEnforcement>> {
+    schema
+        .map(|schema| Ok(SchemaEnforcement { columns: declared_schema(schema)?, allow_missing, drop_extra }))
+        .transpose()
+}
+
+/// Helper function to convert a Parquet write report to a result dictionary
+fn parquet_report_to_pydict(py: Python, report: &ParquetWriteReport) -> PyResult<PyObject> {
+    let cast = PyList::empty(py);
+    for (column, from, to) in &report.schema.cast {
+        let record = PyDict::new(py);
+        record.set_item("column", column)?;
+        record.set_item("from", from.to_string())?;
+        record.set_item("to", to.to_string())?;
+        cast.append(record)?;
+    }
+    let result = PyDict::new(py);
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    result.set_item("files", &report.files)?;
+    result.set_item("rows", report.rows)?;
+    result.set_item("filled_missing", &report.schema.filled_missing)?;
+    result.set_item("dropped_extra", &report.schema.dropped_extra)?;
+    result.set_item("cast", cast)?;
+    Ok(result.into())
+}
+
+/// Write a result dictionary to a Parquet file
+/// 
+/// # Arguments
+/// * `data` - Result dictionary
+/// * `path` - Output file
+/// * `schema` - Canonical `{column: dtype}` dict ("i64", "f64", "str", ...);
+///   the file gets exactly these columns in this order, with lossless casts
+///   (e.g. i32 -> i64) applied
+/// * `allow_missing` - Fill declared columns absent from `data` with nulls
+/// * `drop_extra` - Drop undeclared columns instead of raising
+/// 
+/// # Returns
+/// * Dictionary with `files`, `rows`, `filled_missing`, `dropped_extra` and
+///   `cast` (`column`, `from`, `to` per cast column)
+/// 
+/// # Raises
+/// * `SchemaError` listing every violation at once; nothing is written
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// insightora_core.write_parquet(
+///     data,
+///     "feed/2024-01-01.parquet",
+///     schema={"order_id": "i64", "region": "str", "amount": "f64"},
+///     drop_extra=True,
+/// )
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, path, schema=None, allow_missing=false, drop_extra=false))]
+pub fn write_parquet(
+    py: Python,
+    data: &PyAny,
+    path: &str,
+    schema: Option<&PyDict>,
+    allow_missing: bool,
+    drop_extra: bool,
+) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let enforcement = schema_enforcement(schema, allow_missing, drop_extra)?;
+    let report = py.allow_threads(|| parquet_writer::write_parquet(&df, path, enforcement.as_ref()))
+        .map_err(|e| operation_error("Failed to write Parquet", e))?;
+    parquet_report_to_pydict(py, &report)
+}
+
+/// Write a result dictionary as hive-partitioned Parquet files
+/// 
+/// Files are written to `directory/col=value/part-0.parquet`; the partition
+/// columns live in the directory names only, so `schema` declares the
+/// remaining columns.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary
+/// * `directory` - Output root
+/// * `partition_by` - Column name or list of columns to partition by
+/// * `schema` / `allow_missing` / `drop_extra` - As in `write_parquet`
+/// 
+/// # Returns
+/// * Dictionary as returned by `write_parquet`, with one entry in `files` per partition
+#[pyfunction]
+#[pyo3(signature = (data, directory, partition_by, schema=None, allow_missing=false, drop_extra=false))]
+pub fn write_partitioned(
+    py: Python,
+    data: &PyAny,
+    directory: &str,
+    partition_by: &PyAny,
+    schema: Option<&PyDict>,
+    allow_missing: bool,
+    drop_extra: bool,
+) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let partition_by: Vec<String> = extract_one_or_many(partition_by)?;
+    let enforcement = schema_enforcement(schema, allow_missing, drop_extra)?;
+    let report = py.allow_threads(|| parquet_writer::write_partitioned(&df, directory, &partition_by, enforcement.as_ref()))
+        .map_err(|e| operation_error("Failed to write partitioned Parquet", e))?;
+    parquet_report_to_pydict(py, &report)
+}
+
+// ============================================================================
+// Arrow IPC Python Bindings
+// ============================================================================
+
+use crate::io::arrow_bridge::{self, IpcCompressionKind};
+
+/// Read an Arrow IPC (Feather v2) file, e.g. one written by pyarrow
+/// 
+/// Dictionary columns come back as categoricals with their category order
+/// in the result's `categories` key, and timestamps keep their timezone.
+/// 
+/// # Arguments
+/// * `file_path` - Path to the `.arrow` / `.feather` file
+/// * `memory_map` - Map the file instead of reading it, so only the pages
+///   used are loaded; compressed files are always read
+/// 
+/// # Returns
+/// * Dictionary with 'columns' and 'data'
+/// 
+/// # Raises
+/// * `SchemaError` listing the nested (list or struct) columns, which
+///   aren't supported
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// result = insightora_core.read_ipc("exports/events.arrow", memory_map=True)
+/// ```
+#[pyfunction]
+#[pyo3(signature = (file_path, memory_map=false))]
+pub fn read_ipc(py: Python, file_path: &str, memory_map: bool) -> PyResult<PyObject> {
+    let df = py.allow_threads(|| arrow_bridge::read_ipc(file_path, memory_map))
+        .map_err(|e| operation_error("Failed to read Arrow IPC file", e))?;
+    dataframe_to_pydict(py, &df)
+}
+
+/// Write a result dictionary as an Arrow IPC (Feather v2) file
+/// 
+/// The file can be read with `pyarrow.feather.read_table` or
+/// `pyarrow.ipc.open_file`; ordered categoricals are written
+/// dictionary-encoded in their category order.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary
+/// * `file_path` - Output file
+/// * `compression` - None, "lz4" or "zstd"
+/// 
+/// # Returns
+/// * Number of rows written
+/// 
+/// # Raises
+/// * `SchemaError` listing the nested columns; nothing is written
+#[pyfunction]
+#[pyo3(signature = (data, file_path, compression=None))]
+pub fn write_ipc(py: Python, data: &PyAny, file_path: &str, compression: Option<&str>) -> PyResult<usize> {
+    let df = pydict_to_dataframe(data)?;
+    let compression = compression.map(IpcCompressionKind::from_name).transpose()?.unwrap_or_default();
+    py.allow_threads(|| arrow_bridge::write_ipc(&df, file_path, compression))
+        .map_err(|e| operation_error("Failed to write Arrow IPC file", e))
+}
+
+// ============================================================================
+// Arrow Export Python Bindings
+// ============================================================================
+
+use std::ffi::CString;
+use pyo3::types::PyCapsule;
+use polars::export::arrow::ffi::ArrowArrayStream;
+
+/// Layout of a parsed table returned to Python, chosen with `return_format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum ReturnFormat {
+    /// Result dictionary with the values as Python lists
+    #[default]
+    Dict,
+    /// `ArrowTable` sharing the parsed buffers
+    Arrow,
+    /// `RustDataFrame` holding the parsed table, chosen with `return_handle`
+    Handle,
+}
+
+impl ReturnFormat {
+    fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "dict" => Ok(ReturnFormat::Dict),
+            "arrow" => Ok(ReturnFormat::Arrow),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown return_format '{}'; expected dict or arrow",
+                other
+            ))),
+        }
+    }
+}
+
+/// Stream moved into a capsule; the consumer takes it over by clearing its
+/// release callback, otherwise dropping the capsule releases it
+#[repr(transparent)]
+struct CapsuleStream(ArrowArrayStream);
+
+// The stream only refers to immutable, reference-counted Arrow buffers
+unsafe impl Send for CapsuleStream {}
+
+/// Parsed table returned with `return_format="arrow"`
+///
+/// Implements the Arrow PyCapsule interface (`__arrow_c_stream__`), so
+/// `pyarrow.table(result)`, `polars.DataFrame(result)` and other Arrow
+/// consumers read the parsed columns without copying or converting a
+/// single value to a Python object. The buffers are shared with the table
+/// and stay alive as long as it or anything built from it does. Keys a
+/// result dictionary would carry besides the table (`repair`, `summary`,
+/// ...) are in `report`.
+///
+/// # Example
+/// ```python
+/// import pyarrow as pa
+/// import insightora_core
+///
+/// result = insightora_core.parse_csv("events.csv", return_format="arrow")
+/// table = pa.table(result)
+/// df = table.to_pandas()
+/// ```
+#[pyclass(name = "ArrowTable")]
+pub struct PyArrowTable {
+    data: polars::prelude::DataFrame,
+    report: PyObject,
+}
+
+#[pymethods]
+impl PyArrowTable {
+    /// Export the table as an Arrow C stream capsule, one batch per chunk
+    ///
+    /// `requested_schema` is accepted as the protocol requires but not
+    /// applied; consumers cast the columns they need afterwards.
+    #[pyo3(signature = (requested_schema=None))]
+    fn __arrow_c_stream__(&self, py: Python, requested_schema: Option<PyObject>) -> PyResult<PyObject> {
+        let _ = requested_schema;
+        let stream = CapsuleStream(arrow_bridge::to_arrow(&self.data));
+        let name = CString::new("arrow_array_stream").expect("capsule name has no NUL");
+        Ok(PyCapsule::new(py, stream, Some(name))?.into())
+    }
+
+    /// Column names in order
+    #[getter]
+    fn columns(&self) -> Vec<String> {
+        self.data.get_column_names().iter().map(|name| name.to_string()).collect()
+    }
+
+    #[getter]
+    fn num_rows(&self) -> usize {
+        self.data.height()
+    }
+
+    #[getter]
+    fn num_columns(&self) -> usize {
+        self.data.width()
+    }
+
+    /// Dictionary with the keys a dict result has besides the table
+    #[getter]
+    fn report(&self, py: Python) -> PyObject {
+        self.report.clone_ref(py)
+    }
+
+    /// Convert to the result dictionary `return_format="dict"` returns
+    fn to_dict(&self, py: Python) -> PyResult<PyObject> {
+        let result = dataframe_to_pydict(py, &self.data)?;
+        let dict = result.as_ref(py).downcast::<PyDict>()?;
+        for (key, value) in self.report.as_ref(py).downcast::<PyDict>()? {
+            dict.set_item(key, value)?;
+        }
+        Ok(result)
+    }
+
+    fn __len__(&self) -> usize {
+        self.data.height()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ArrowTable(rows={}, columns={})", self.data.height(), self.data.width())
+    }
+}
+
+/// Handle to a table held in Rust, returned with `return_handle=True`
+///
+/// Every binding that takes a result dictionary takes a handle too and
+/// uses its table directly, so a pipeline of steps converts values to
+/// Python objects only when `to_dict()` is called. Bindings returning just
+/// a table (`sort_data`, `rename_columns`, ...) return a handle when given
+/// one. The table can't be changed through the handle, so copies share it
+/// and any number of Python threads may use it at once.
+///
+/// # Example
+/// ```python
+/// import insightora_core
+///
+/// handle = insightora_core.parse_csv("orders.csv", return_handle=True)
+/// if handle.estimated_size_mb() > insightora_core.get_config()["memory_limit_mb"] / 2:
+///     handle = handle.head(100_000)
+/// ordered = insightora_core.sort_data(handle, "amount", descending=True)
+/// top = ordered.head(10).to_dict()
+/// ```
+#[pyclass(name = "RustDataFrame", frozen)]
+pub struct PyRustDataFrame {
+    data: Arc<polars::prelude::DataFrame>,
+    report: PyObject,
+}
+
+impl PyRustDataFrame {
+    /// Handle to `df` with a report holding only `schema_version`
+    fn new(py: Python, df: polars::prelude::DataFrame) -> PyResult<Self> {
+        let report = PyDict::new(py);
+        report.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+        Ok(Self { data: Arc::new(df), report: report.into() })
+    }
+}
+
+#[pymethods]
+impl PyRustDataFrame {
+    /// (rows, columns)
+    #[getter]
+    fn shape(&self) -> (usize, usize) {
+        self.data.shape()
+    }
+
+    /// Column names in order
+    #[getter]
+    fn columns(&self) -> Vec<String> {
+        self.data.get_column_names().iter().map(|name| name.to_string()).collect()
+    }
+
+    /// Polars dtype of each column, named as in a result dictionary's 'dtypes'
+    #[getter]
+    fn dtypes(&self) -> Vec<String> {
+        self.data.dtypes().iter().map(|dtype| format!("{:?}", dtype)).collect()
+    }
+
+    /// Dictionary with the keys a dict result has besides the table
+    #[getter]
+    fn report(&self, py: Python) -> PyObject {
+        self.report.clone_ref(py)
+    }
+
+    /// Handle to the first `n` rows, sharing their buffers
+    #[pyo3(signature = (n=5))]
+    fn head(&self, py: Python, n: usize) -> PyResult<Self> {
+        Self::new(py, self.data.head(Some(n)))
+    }
+
+    /// Approximate memory held by the table, in MB, as `memory_limit_mb` counts it
+    fn estimated_size_mb(&self) -> f64 {
+        self.data.estimated_size() as f64 / (1024.0 * 1024.0)
+    }
+
+    /// Convert to the result dictionary `return_format="dict"` returns
+    fn to_dict(&self, py: Python) -> PyResult<PyObject> {
+        let result = dataframe_to_pydict(py, &self.data)?;
+        let dict = result.as_ref(py).downcast::<PyDict>()?;
+        for (key, value) in self.report.as_ref(py).downcast::<PyDict>()? {
+            dict.set_item(key, value)?;
+        }
+        Ok(result)
+    }
+
+    /// Export as an `ArrowTable` sharing the table's buffers, for pyarrow,
+    /// polars or pandas
+    fn to_arrow(&self, py: Python) -> PyArrowTable {
+        PyArrowTable { data: self.data.as_ref().clone(), report: self.report.clone_ref(py) }
+    }
+
+    /// Another handle to the same table
+    fn __copy__(&self, py: Python) -> Self {
+        Self { data: Arc::clone(&self.data), report: self.report.clone_ref(py) }
+    }
+
+    fn __len__(&self) -> usize {
+        self.data.height()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("RustDataFrame(rows={}, columns={})", self.data.height(), self.data.width())
+    }
+}
+
+/// Helper function to import an object implementing the Arrow PyCapsule
+/// stream interface (`__arrow_c_stream__`) as a DataFrame
+/// 
+/// pyarrow Tables, polars DataFrames and pandas DataFrames converted with
+/// `pyarrow.table(df)` share their buffers with the result instead of being
+/// copied; an `ArrowTable` is unwrapped directly. Nested and unsupported
+/// column types raise TypeError naming the column and Arrow type.
+fn arrow_stream_to_dataframe(data: &PyAny) -> PyResult<polars::prelude::DataFrame> {
+    if let Ok(table) = data.extract::<PyRef<PyArrowTable>>() {
+        return Ok(table.data.clone());
+    }
+    let capsule: &PyCapsule = data.call_method0("__arrow_c_stream__")?.downcast()?;
+    if capsule.name()?.map(|name| name.to_bytes()) != Some(b"arrow_array_stream".as_slice()) {
+        return Err(PyTypeError::new_err(format!(
+            "__arrow_c_stream__ of '{}' didn't return an arrow_array_stream capsule",
+            data.get_type().name()?
+        )));
+    }
+    // Take the stream over, leaving a released one for the capsule's destructor
+    let stream = unsafe { std::ptr::replace(capsule.pointer() as *mut ArrowArrayStream, ArrowArrayStream::empty()) };
+    Ok(arrow_bridge::import_table(stream)?)
+}
+
+/// Helper function to start a parse result in the requested format
+///
+/// A dict result holds the table; an Arrow or handle result starts as the
+/// report dictionary, which `finish_table` wraps with the table. Report keys
+/// are added to either in between.
+fn table_result(
+    py: Python,
+    df: &polars::prelude::DataFrame,
+    format: ReturnFormat,
+    progress: &ProgressReporter,
+) -> PyResult<PyObject> {
+    match format {
+        ReturnFormat::Dict => dataframe_to_pydict_reporting(py, df, progress),
+        ReturnFormat::Arrow | ReturnFormat::Handle => {
+            // Nothing to convert: the table keeps the parsed buffers
+            let report = PyDict::new(py);
+            report.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+            progress.finish("export");
+            Ok(report.into())
+        }
+    }
+}
+
+/// Helper function to finish a result started with `table_result`
+fn finish_table(py: Python, df: polars::prelude::DataFrame, format: ReturnFormat, result: PyObject) -> PyResult<PyObject> {
+    match format {
+        ReturnFormat::Dict => Ok(result),
+        ReturnFormat::Arrow => Ok(Py::new(py, PyArrowTable { data: df, report: result })?.into_py(py)),
+        ReturnFormat::Handle => Ok(Py::new(py, PyRustDataFrame { data: Arc::new(df), report: result })?.into_py(py)),
+    }
+}
+
+/// Helper function to return a table in the form the input table came in:
+/// a `RustDataFrame` when `input` was one, otherwise the result dictionary
+fn frame_result(
+    py: Python,
+    input: &PyAny,
+    df: polars::prelude::DataFrame,
+    progress: &ProgressReporter,
+) -> PyResult<PyObject> {
+    if input.is_instance_of::<PyRustDataFrame>() {
+        progress.finish("");
+        return Ok(Py::new(py, PyRustDataFrame::new(py, df)?)?.into_py(py));
+    }
+    dataframe_to_pydict_reporting(py, &df, progress)
+}
+
+// ============================================================================
+// DataFrame Operations Python Bindings
+// ============================================================================
+
+use crate::dataframe::operations::{self, Collation, FloatKeyOptions, FloatPrecision, QuantizationReport};
+use crate::dataframe::{aggregations, expressions, transformations};
+use crate::dataframe::expressions::{ColumnExpression, NullArithmetic, Predicate};
+use crate::dataframe::transformations::Assignment;
+use crate::utils::column_mapper::{self, ColumnDictionary};
+
+/// Extract a value that may be given either as a single item or a list
+fn extract_one_or_many<'a, T: FromPyObject<'a>>(value: &'a PyAny) -> PyResult<Vec<T>> {
+    match value.extract::<T>() {
+        Ok(single) => Ok(vec![single]),
+        Err(_) => value.extract::<Vec<T>>(),
+    }
+}
+
+/// Sort data by one or more columns with a configurable string collation
+/// 
+/// # Arguments
+/// * `data` - Result dictionary or `RustDataFrame` (as returned by `parse_csv`)
+/// * `by` - Column name or list of column names to sort by
+/// * `descending` - Bool or list of bools matching `by` (default: False)
+/// * `collation` - "binary" (byte order), "natural" (digit runs compare
+///   numerically) or "locale" (language-aware, requires `locale`)
+/// * `locale` - Locale identifier for collation="locale", e.g. "de_DE", "sv_SE"
+/// * `on_progress` - Optional callable `(percent, stage, detail)` for progress
+/// 
+/// # Returns
+/// * Dictionary with 'columns' and 'data', sorted, or a `RustDataFrame`
+///   when `data` is one
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// result = insightora_core.parse_csv("products.csv")
+/// ordered = insightora_core.sort_data(result, "sku", collation="natural")
+/// # item1, item2, item10 instead of item1, item10, item2
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, by, descending=None, collation="binary", locale=None, on_progress=None))]
+pub fn sort_data(
+    py: Python,
+    data: &PyAny,
+    by: &PyAny,
+    descending: Option<&PyAny>,
+    collation: &str,
+    locale: Option<&str>,
+    on_progress: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let progress = progress_reporter(on_progress);
+    let cells = dict_cells(data);
+    let stages = binding_stages(&progress, cells, cells);
+    let df = pydict_to_dataframe_reporting(data, &stages[0])?;
+    let by: Vec<String> = extract_one_or_many(by)?;
+    let descending: Vec<bool> = match descending {
+        Some(value) => extract_one_or_many(value)?,
+        None => vec![false],
+    };
+    let collation = Collation::from_options(collation, locale)?;
+    
+    stages[1].report(0.0, "sort");
+    let sorted = py.allow_threads(|| operations::sort_data(&df, &by, &descending, &collation))?;
+    stages[1].finish("sort");
+    
+    frame_result(py, data, sorted, &stages[2])
+}
+
+/// Sort rows by one or more columns, natively and in parallel
+/// 
+/// Text sorts by byte (code point) order, the same whatever the system
+/// locale, so "Zebra" comes before "apple"; `case_insensitive=True` ignores
+/// case instead. Ordered categoricals sort by their declared order. Rows
+/// with equal keys keep their original relative order.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary or `RustDataFrame`
+/// * `by` - Column name or list of column names, highest priority first
+/// * `descending` - Bool or list of bools matching `by` (default: False)
+/// * `nulls_last` - Put nulls after the values of their column, in either
+///   direction (default: True); False puts them first
+/// * `case_insensitive` - Compare text columns ignoring case (default: False)
+/// 
+/// # Returns
+/// * Dictionary with 'columns' and 'data', sorted, or a `RustDataFrame`
+///   when `data` is one
+/// 
+/// # Raises
+/// * `ValueError` - A column in `by` doesn't exist, or `descending` doesn't
+///   match `by`
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// handle = insightora_core.parse_csv("orders.csv", return_handle=True)
+/// ranked = insightora_core.sort(handle, ["country", "amount"], descending=[False, True])
+/// ```
+#[pyfunction]
+#[pyo3(name = "sort", signature = (data, by, descending=None, nulls_last=true, case_insensitive=false))]
+pub fn sort_rows(
+    py: Python,
+    data: &PyAny,
+    by: &PyAny,
+    descending: Option<&PyAny>,
+    nulls_last: bool,
+    case_insensitive: bool,
+) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let by: Vec<String> = extract_one_or_many(by)?;
+    let descending: Vec<bool> = match descending {
+        Some(value) => extract_one_or_many(value)?,
+        None => vec![false],
+    };
+    let sorted = py.allow_threads(|| operations::sort_rows(&df, &by, &descending, nulls_last, case_insensitive))?;
+    frame_result(py, data, sorted, &ProgressReporter::disabled())
+}
+
+/// Give a column an explicit category order
+/// 
+/// The column becomes an ordered categorical: `sort_data`, min/max in
+/// `group_by` and `describe` follow the declared order instead of the
+/// alphabet. The order is kept in the result's `categories` key, so passing
+/// the result on to other functions preserves it.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary
+/// * `column` - Column to order
+/// * `order` - Categories from lowest to highest
+/// * `unknown` - "error" to reject values missing from `order`, or "last" to
+///   rank them after the declared categories (alphabetically)
+/// 
+/// # Returns
+/// * Result dictionary with `categories` set for the column
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// data = insightora_core.set_category_order(data, "severity", ["low", "medium", "high"])
+/// worst_first = insightora_core.sort_data(data, "severity", descending=True)
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, column, order, unknown="error"))]
+pub fn set_category_order(
+    py: Python,
+    data: &PyAny,
+    column: &str,
+    order: Vec<String>,
+    unknown: &str,
+) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let unknown = transformations::UnknownCategory::from_name(unknown)?;
+    let ordered = py.allow_threads(|| transformations::set_category_order(&df, column, &order, unknown))?;
+    frame_result(py, data, ordered, &ProgressReporter::disabled())
+}
+
+/// Helper function to build per-column display formats from spec dicts
+fn value_formats(formats: &PyDict) -> PyResult<Vec<(String, transformations::ValueFormat)>> {
+    use transformations::{FormatKind, SymbolPlacement, ValueFormat};
+
+    const KEYS: [&str; 7] = ["type", "decimals", "thousands", "symbol", "placement", "pattern", "null"];
+    formats
+        .iter()
+        .map(|(column, spec)| {
+            let column: String = column.extract()?;
+            let spec: &PyDict = spec.downcast()?;
+            for key in spec.keys() {
+                let key: String = key.extract()?;
+                if !KEYS.contains(&key.as_str()) {
+                    return Err(PyValueError::new_err(format!(
+                        "Unknown format key '{}' for column '{}'; expected {}",
+                        key, column, KEYS.join(", ")
+                    )));
+                }
+            }
+            let field = |key: &str| spec.get_item(key);
+            let decimals: Option<usize> = field("decimals")?.map(|v| v.extract()).transpose()?;
+            let thousands: bool = field("thousands")?.map(|v| v.extract()).transpose()?.unwrap_or(true);
+            let kind: String = field("type")?
+                .ok_or_else(|| PyValueError::new_err(format!("Format for column '{}' needs a 'type'", column)))?
+                .extract()?;
+            let kind = match kind.to_ascii_lowercase().as_str() {
+                "number" => FormatKind::Number { decimals, thousands },
+                "percent" => FormatKind::Percent { decimals, thousands },
+                "currency" => FormatKind::Currency {
+                    symbol: field("symbol")?.map(|v| v.extract()).transpose()?.unwrap_or_else(|| "$".to_string()),
+                    placement: match field("placement")? {
+                        Some(placement) => SymbolPlacement::from_name(placement.extract()?)?,
+                        None => SymbolPlacement::Prefix,
+                    },
+                    decimals: decimals.or(Some(2)),
+                },
+                "datetime" => FormatKind::Datetime {
+                    pattern: field("pattern")?
+                        .ok_or_else(|| PyValueError::new_err(format!("Datetime format for column '{}' needs a 'pattern'", column)))?
+                        .extract()?,
+                },
+                other => {
+                    return Err(PyValueError::new_err(format!(
+                        "Unknown format type '{}' for column '{}'; expected number, percent, currency or datetime",
+                        other, column
+                    )));
+                }
+            };
+            let null_text: Option<String> = field("null")?.map(|v| v.extract()).transpose()?;
+            Ok((column, ValueFormat { kind, null_text }))
+        })
+        .collect()
+}
+
+/// Render columns as report-ready strings
+/// 
+/// Each spec is a dict with a `type` of "number", "percent" (fractions,
+/// 0.125 -> "12.5%"), "currency" or "datetime", plus optional keys:
+/// `decimals` (default: as stored; 2 for currency), `thousands` (group
+/// digits, default True), `symbol` (default "$") and `placement`
+/// ("prefix" or "suffix") for currency, `pattern` (strftime, required) for
+/// datetime, and `null` for the text shown in place of nulls.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary
+/// * `formats` - Dict mapping column names to format specs
+/// * `replace` - Overwrite the columns instead of adding `<column>_formatted` (default: False)
+/// * `thousands_separator` - Digit group separator (default: ",")
+/// * `decimal_separator` - Decimal mark (default: ".")
+/// 
+/// # Returns
+/// * Result dictionary with the formatted String columns
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// report = insightora_core.format_values(
+///     data,
+///     {
+///         "revenue": {"type": "currency", "symbol": " €", "placement": "suffix"},
+///         "margin": {"type": "percent", "decimals": 1, "null": "n/a"},
+///         "closed_at": {"type": "datetime", "pattern": "%d %b %Y"},
+///     },
+///     thousands_separator=".",
+///     decimal_separator=",",
+/// )
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, formats, replace=false, thousands_separator=",", decimal_separator="."))]
+pub fn format_values(
+    py: Python,
+    data: &PyAny,
+    formats: &PyDict,
+    replace: bool,
+    thousands_separator: &str,
+    decimal_separator: &str,
+) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let formats = value_formats(formats)?;
+    let locale = transformations::NumberLocale::new(thousands_separator, decimal_separator)?;
+    let formatted = py.allow_threads(|| transformations::format_columns(&df, &formats, &locale, replace))?;
+    dataframe_to_pydict(py, &formatted)
+}
+
+/// Helper function to convert a bin spec to a Python dictionary
+fn bin_spec_to_pydict(py: Python, spec: &transformations::BinSpec) -> PyResult<PyObject> {
+    let dict = PyDict::new(py);
+    dict.set_item("column", &spec.column)?;
+    dict.set_item("quantiles", &spec.quantiles)?;
+    dict.set_item("edges", &spec.edges)?;
+    dict.set_item("labels", &spec.labels)?;
+    dict.set_item("out_of_range", spec.out_of_range.name())?;
+    dict.set_item("nan", spec.nan.name())?;
+    Ok(dict.into())
+}
+
+/// Helper function to read a bin spec dict returned by `qcut`
+fn bin_spec_from_pydict(spec: &PyDict) -> PyResult<transformations::BinSpec> {
+    let field = |key: &str| -> PyResult<&PyAny> {
+        spec.get_item(key)?
+            .ok_or_else(|| PyValueError::new_err(format!("Bin spec is missing '{}'", key)))
+    };
+    let spec = transformations::BinSpec {
+        column: field("column")?.extract()?,
+        quantiles: field("quantiles")?.extract()?,
+        edges: field("edges")?.extract()?,
+        labels: field("labels")?.extract()?,
+        out_of_range: transformations::OutOfRange::from_name(field("out_of_range")?.extract()?)?,
+        nan: transformations::NanBin::from_name(field("nan")?.extract()?)?,
+    };
+    spec.validate()?;
+    Ok(spec)
+}
+
+/// Bin a numeric column into equal-frequency (quantile) bins
+/// 
+/// Adds `<column>_bin` with the 0-based bin index and returns the fitted
+/// edges as `bin_spec`. Pass the spec to `apply_bins` to bin other data
+/// (scoring data, later batches) with exactly the same edges. The spec is
+/// plain JSON-serializable data, so it can be stored with a model.
+/// 
+/// Bin `i` covers `(edges[i], edges[i+1]]`, the first bin also including
+/// its lower edge. Tied quantiles share an edge, so heavily repeated values
+/// can give fewer bins than requested.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary
+/// * `column` - Numeric column to bin
+/// * `q` - Number of equal-frequency bins (default: 4)
+/// * `quantiles` - Explicit increasing quantiles in [0, 1] instead of `q`
+/// * `out_of_range` - "clip" to put values outside the edges in the first or
+///   last bin, or "null" (applies in `apply_bins`)
+/// * `nan` - "null" for a null bin, or "separate" for an extra "NaN" bin
+///   numbered after the last one; nulls always stay null
+/// 
+/// # Returns
+/// * Result dictionary plus `bin_spec` (`column`, `quantiles`, `edges`,
+///   `labels`, `out_of_range`, `nan`)
+/// 
+/// # Example
+/// ```python
+/// import json
+/// import insightora_core
+/// 
+/// train = insightora_core.qcut(train, "income", q=10)
+/// json.dump(train["bin_spec"], open("income_bins.json", "w"))
+/// 
+/// spec = json.load(open("income_bins.json"))
+/// scoring = insightora_core.apply_bins(scoring, spec)
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, column, q=4, quantiles=None, out_of_range="clip", nan="null"))]
+pub fn qcut(
+    py: Python,
+    data: &PyAny,
+    column: &str,
+    q: usize,
+    quantiles: Option<Vec<f64>>,
+    out_of_range: &str,
+    nan: &str,
+) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    if quantiles.is_none() && q == 0 {
+        return Err(PyValueError::new_err("q must be at least 1"));
+    }
+    let quantiles = quantiles.unwrap_or_else(|| transformations::even_quantiles(q));
+    let out_of_range = transformations::OutOfRange::from_name(out_of_range)?;
+    let nan = transformations::NanBin::from_name(nan)?;
+    let (binned, spec) = py.allow_threads(|| transformations::qcut(&df, column, &quantiles, out_of_range, nan))?;
+
+    let result = dataframe_to_pydict(py, &binned)?;
+    result.as_ref(py).downcast::<PyDict>()?.set_item("bin_spec", bin_spec_to_pydict(py, &spec)?)?;
+    Ok(result)
+}
+
+/// Bin a column with edges fitted earlier by `qcut`
+/// 
+/// Nothing is refitted: values are placed by the spec's edges, and values
+/// outside them and NaNs follow the spec's `out_of_range` and `nan` policies.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary
+/// * `spec` - The `bin_spec` dict from `qcut`
+/// 
+/// # Returns
+/// * Result dictionary with `<column>_bin` added
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// fitted = insightora_core.qcut(train, "age", q=5)
+/// scoring = insightora_core.apply_bins(scoring, fitted["bin_spec"])
+/// ```
+#[pyfunction]
+pub fn apply_bins(py: Python, data: &PyAny, spec: &PyDict) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let spec = bin_spec_from_pydict(spec)?;
+    let binned = py.allow_threads(|| transformations::apply_bins(&df, &spec))?;
+    frame_result(py, data, binned, &ProgressReporter::disabled())
+}
+
+/// Helper function to build float key options from binding arguments
+fn float_key_options(
+    float_precision: Option<u32>,
+    float_step: Option<f64>,
+    nan_equal: bool,
+) -> PyResult<FloatKeyOptions> {
+    let precision = match (float_precision, float_step) {
+        (Some(_), Some(_)) => {
+            return Err(PyValueError::new_err("Pass either float_precision or float_step, not both"));
+        }
+        (Some(decimals), None) => Some(FloatPrecision::Decimals(decimals)),
+        (None, Some(step)) => Some(FloatPrecision::Step(step)),
+        (None, None) => None,
+    };
+    Ok(FloatKeyOptions { precision, nan_equal })
+}
+
+/// Helper function to attach the quantization report to a result dictionary
+fn with_quantization_report(py: Python, result: PyObject, report: &QuantizationReport) -> PyResult<PyObject> {
+    result.as_ref(py).downcast::<PyDict>()?
+        .set_item("rows_affected_by_precision", report.rows_affected)?;
+    Ok(result)
+}
+
+/// Remove duplicate rows
+/// 
+/// Kept rows stay in their original order. Null keys are equal to each
+/// other, so rows whose subset values are all null are duplicates of one
+/// another.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary or `RustDataFrame`
+/// * `subset` - Column name or list of columns to compare (default: all)
+/// * `keep` - "first", "last" or "none" (drop every copy)
+/// * `float_precision` - Compare float keys rounded to N decimal places
+/// * `float_step` - Compare float keys rounded to a multiple of this step
+/// * `nan_equal` - Under quantization, whether NaN keys equal each other
+/// * `on_progress` - Optional callable `(percent, stage, detail)` for progress
+/// * `include_summary` / `include_samples` - Execution summary as in `parse_csv_with_options`
+/// 
+/// # Returns
+/// * Result dictionary plus `rows_affected_by_precision`, or a
+///   `RustDataFrame` when `data` is one (summaries need a dictionary)
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// unique = insightora_core.drop_duplicates(data, subset="amount", float_precision=6)
+/// latest = insightora_core.drop_duplicates(data, subset=["customer_id", "sku"], keep="last")
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, subset=None, keep="first", float_precision=None, float_step=None, nan_equal=true, on_progress=None, include_summary=false, include_samples=false))]
+#[allow(clippy::too_many_arguments)]
+pub fn drop_duplicates(
+    py: Python,
+    data: &PyAny,
+    subset: Option<&PyAny>,
+    keep: &str,
+    float_precision: Option<u32>,
+    float_step: Option<f64>,
+    nan_equal: bool,
+    on_progress: Option<PyObject>,
+    include_summary: bool,
+    include_samples: bool,
+) -> PyResult<PyObject> {
+    if include_summary && data.is_instance_of::<PyRustDataFrame>() {
+        return Err(PyValueError::new_err("include_summary needs a result dictionary, not a RustDataFrame"));
+    }
+    let mut metrics = ExecutionMetrics::new("drop_duplicates");
+    metrics.option("keep", keep);
+    metrics.option("float_precision", float_precision.map_or("exact".to_string(), |p| p.to_string()));
+    metrics.option("float_step", float_step.map_or("none".to_string(), |s| s.to_string()));
+    metrics.option("nan_equal", nan_equal);
+    
+    let progress = progress_reporter(on_progress);
+    let cells = dict_cells(data);
+    let stages = binding_stages(&progress, cells, cells);
+    let df = metrics.time("load", || pydict_to_dataframe_reporting(data, &stages[0]))?;
+    let subset: Option<Vec<String>> = subset.map(extract_one_or_many).transpose()?;
+    metrics.option("subset", subset.as_ref().map_or("all".to_string(), |s| s.join(", ")));
+    let keep = transformations::keep_strategy_from_name(keep)?;
+    let options = float_key_options(float_precision, float_step, nan_equal)?;
+    
+    stages[1].report(0.0, "deduplicate");
+    let (deduped, report) = metrics.time("deduplicate", || py.allow_threads(|| {
+        transformations::drop_duplicates(&df, subset.as_deref(), keep, &options)
+    }))?;
+    stages[1].finish("deduplicate");
+    
+    let result = metrics.time("export", || frame_result(py, data, deduped.clone(), &stages[2]))?;
+    if data.is_instance_of::<PyRustDataFrame>() {
+        return Ok(result);
+    }
+    let result = with_quantization_report(py, result, &report)?;
+    if include_summary {
+        metrics.rows(df.height(), deduped.height());
+        metrics.dropped("duplicates", df.height() - deduped.height());
+        metrics.adjusted("rows_affected_by_precision", report.rows_affected);
+        metrics.chunks(df.n_chunks());
+        metrics.observe_frame(&df);
+        metrics.observe_frame(&deduped);
+        if include_samples {
+            sample_first_row(&mut metrics, &deduped);
+        }
+        with_summary(py, result, &metrics, include_samples)
+    } else {
+        Ok(result)
+    }
+}
+
+/// Mark duplicate rows without removing them
+/// 
+/// With the same arguments, the marked rows are exactly those
+/// `drop_duplicates` removes, so they can be inspected first. Null keys are
+/// equal to each other.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary or `RustDataFrame`
+/// * `subset` - Column name or list of columns to compare (default: all)
+/// * `keep` - "first" (default) marks every copy after the first, "last"
+///   every copy before the last, "none" every row whose key repeats
+/// 
+/// # Returns
+/// * List of bools, one per row
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// mask = insightora_core.is_duplicated(data, ["customer_id", "sku"], keep="none")
+/// rows = list(zip(*data["data"]))
+/// copies = [row for row, duplicated in zip(rows, mask) if duplicated]
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, subset=None, keep="first"))]
+pub fn is_duplicated(py: Python, data: &PyAny, subset: Option<&PyAny>, keep: &str) -> PyResult<Vec<bool>> {
+    let df = pydict_to_dataframe(data)?;
+    let subset: Option<Vec<String>> = subset.map(extract_one_or_many).transpose()?;
+    let keep = transformations::keep_strategy_from_name(keep)?;
+    let mask = py.allow_threads(|| {
+        transformations::duplicate_mask(&df, subset.as_deref(), keep, &FloatKeyOptions::default())
+    })?;
+    Ok(mask.into_no_null_iter().collect())
+}
+
+/// Drop rows with missing values
+/// 
+/// # Arguments
+/// * `data` - Result dictionary or `RustDataFrame`
+/// * `subset` - Column name or list of columns to check (default: all)
+/// * `how` - "any" (default) drops rows with a null in any of the columns,
+///   "all" rows null in every one of them
+/// 
+/// # Returns
+/// * Result dictionary, or a `RustDataFrame` when `data` is one
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// complete = insightora_core.drop_nulls(data, subset=["customer_id", "amount"])
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, subset=None, how="any"))]
+pub fn drop_nulls(py: Python, data: &PyAny, subset: Option<&PyAny>, how: &str) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let subset: Option<Vec<String>> = subset.map(extract_one_or_many).transpose()?;
+    let how = transformations::NullRows::from_name(how)?;
+    let kept = py.allow_threads(|| transformations::drop_nulls(&df, subset.as_deref(), how))?;
+    frame_result(py, data, kept, &ProgressReporter::disabled())
+}
+
+/// Replace missing values, column by column
+/// 
+/// Pass either a `strategy` or a literal `value`. A value must fit each
+/// column's type as in `update_where` (an int fills a float column, a float
+/// doesn't fill an int column). Without `columns`, "mean", "median" and
+/// "zero" fill the numeric columns and everything else fills every column;
+/// naming a text column for "mean" raises instead of leaving it unfilled.
+/// Integer columns filled with a mean or median get it rounded and stay
+/// integers.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary or `RustDataFrame`
+/// * `strategy` - "mean", "median", "mode" (most frequent value),
+///   "forward" (last value above), "backward" (next value below) or "zero"
+/// * `value` - Literal to fill with: number, string, bool, date or datetime
+/// * `columns` - Column name or list of columns to fill (default: see above)
+/// 
+/// # Returns
+/// * Result dictionary, or a `RustDataFrame` when `data` is one
+/// 
+/// # Raises
+/// * `TypeError` - A statistic was asked of a non-numeric column
+/// * `ValueError` - Unknown column or strategy, or a value the column's type can't hold
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// readings = insightora_core.fill_nulls(readings, strategy="forward", columns=["temperature"])
+/// orders = insightora_core.fill_nulls(orders, value="unknown", columns="channel")
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, strategy=None, value=None, columns=None))]
+pub fn fill_nulls(
+    py: Python,
+    data: &PyAny,
+    strategy: Option<&str>,
+    value: Option<&PyAny>,
+    columns: Option<&PyAny>,
+) -> PyResult<PyObject> {
+    let strategy = match (strategy, value) {
+        (Some(name), None) => transformations::FillStrategy::from_name(name)?,
+        (None, Some(value)) => {
+            transformations::FillStrategy::Value(literal_series("value", &[value], None, SchemaPolicy::Widen)?)
+        }
+        (Some(_), Some(_)) => return Err(PyValueError::new_err("Pass strategy or value, not both")),
+        (None, None) => return Err(PyValueError::new_err("Pass a fill strategy or a value")),
+    };
+    let df = pydict_to_dataframe(data)?;
+    let columns: Option<Vec<String>> = columns.map(extract_one_or_many).transpose()?;
+    let filled = py.allow_threads(|| transformations::fill_nulls(&df, &strategy, columns.as_deref()))?;
+    frame_result(py, data, filled, &ProgressReporter::disabled())
+}
+
+/// Convert columns to other types
+/// 
+/// Numeric types widen and narrow both ways (floats to integers truncate
+/// toward zero), numbers and booleans convert to and from text, integers to
+/// and from booleans (non-zero is True), and text becomes a boolean from
+/// "true" or "false" in any case. Text is read as a date or datetime with
+/// `format`, or as ISO 8601 without one.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary or `RustDataFrame`
+/// * `mapping` - Dict of column -> dtype name ("i32", "f64", "str", "bool",
+///   "date", "datetime[ms]", ...); other columns are left alone
+/// * `strict` - Raise on the first value that doesn't convert (default:
+///   True); with False such values become null and are counted
+/// * `format` - strftime format for text read as dates, e.g. "%d/%m/%Y"
+/// 
+/// # Returns
+/// * Result dictionary plus `cast_failures`, column -> values set to null
+///   (all 0 when strict). A `RustDataFrame` when `data` is one
+/// 
+/// # Raises
+/// * `ValueError` - A value doesn't convert with `strict`; the message
+///   names the column, row and value. Also an unknown column or dtype name
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// orders = insightora_core.cast(orders, {"quantity": "i32", "ordered_on": "date"}, strict=False, format="%d/%m/%Y")
+/// for column, failed in orders["cast_failures"].items():
+///     if failed:
+///         print(f"{column}: {failed} values set to null")
+/// ```
+#[pyfunction]
+#[pyo3(name = "cast", signature = (data, mapping, strict=true, format=None))]
+pub fn cast_columns(py: Python, data: &PyAny, mapping: &PyDict, strict: bool, format: Option<&str>) -> PyResult<PyObject> {
+    use pyo3::types::IntoPyDict;
+    
+    let targets = declared_schema(mapping)?;
+    let format = format.map(DateFormat::from_pattern).transpose()?;
+    let df = pydict_to_dataframe(data)?;
+    let (cast, failures) = py.allow_threads(|| transformations::cast_columns(&df, &targets, format.as_ref(), strict))?;
+
+    let result = frame_result(py, data, cast, &ProgressReporter::disabled())?;
+    if let Ok(dict) = result.as_ref(py).downcast::<PyDict>() {
+        dict.set_item("cast_failures", failures.into_py_dict(py))?;
+    }
+    Ok(result)
+}
+
+/// Apply a string operation to a text column
+/// 
+/// Runs over the whole column in Rust without holding the GIL; nulls stay
+/// null. The result replaces the column unless `output` names a new one.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary or `RustDataFrame`
+/// * `column` - Text column to read
+/// * `op` - One of:
+///   - "contains": whether each value holds a match of `pattern` (a bool column)
+///   - "replace": every match of `pattern` replaced with `replacement`; with
+///     `regex` the replacement may use `$1` / `${name}` for capture groups
+///   - "split": split on the literal `by` into `into_n` columns
+///     `{output or column}_0`, `_1`, ...; the last part keeps any further
+///     separators and missing parts are null. The source column is kept
+///   - "lower", "upper", "strip" (leading and trailing whitespace)
+///   - "slice": at most `length` characters (default: the rest) from
+///     character `start`, counted from the end when negative
+///   - "len_chars": length in characters
+/// * `pattern` - Pattern for "contains" and "replace"
+/// * `replacement` - Replacement text for "replace"
+/// * `regex` - Read `pattern` as a regex (default: True) or as plain text
+/// * `by` / `into_n` - Separator and number of columns for "split"
+/// * `start` / `length` - Character range for "slice"
+/// * `output` - Name of the result column, or prefix of the split columns
+/// * `cast` - Read a column of another type through its text form instead
+///   of raising TypeError
+/// 
+/// # Returns
+/// * Result dictionary, or a `RustDataFrame` when `data` is one
+/// 
+/// # Raises
+/// * `TypeError` - The column isn't text and `cast` is False
+/// * `ValueError` - Unknown op, a missing argument, a missing column or a
+///   pattern that isn't a valid regex (the message says what's wrong with it)
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// tickets = insightora_core.string_ops(tickets, "subject", "contains", pattern=r"(?i)\burgent\b", output="is_urgent")
+/// tickets = insightora_core.string_ops(tickets, "assignee", "split", by="@", into_n=2, output="assignee")
+/// ```
+#[pyfunction]
+#[pyo3(signature = (
+    data,
+    column,
+    op,
+    pattern=None,
+    replacement=None,
+    regex=true,
+    by=None,
+    into_n=None,
+    start=None,
+    length=None,
+    output=None,
+    cast=false
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn string_ops(
+    py: Python,
+    data: &PyAny,
+    column: &str,
+    op: &str,
+    pattern: Option<&str>,
+    replacement: Option<String>,
+    regex: bool,
+    by: Option<String>,
+    into_n: Option<usize>,
+    start: Option<i64>,
+    length: Option<usize>,
+    output: Option<&str>,
+    cast: bool,
+) -> PyResult<PyObject> {
+    let missing = |argument: &str| PyValueError::new_err(format!("String op '{}' needs {}", op, argument));
+    let compiled = || -> PyResult<regex::Regex> {
+        Ok(transformations::text_pattern(pattern.ok_or_else(|| missing("pattern"))?, regex)?)
+    };
+    let op = match op.to_ascii_lowercase().as_str() {
+        "contains" => transformations::StringOp::Contains(compiled()?),
+        "replace" => transformations::StringOp::Replace {
+            pattern: compiled()?,
+            replacement: replacement.ok_or_else(|| missing("replacement"))?,
+            literal: !regex,
+        },
+        "split" => transformations::StringOp::Split {
+            by: by.ok_or_else(|| missing("by"))?,
+            into: into_n.ok_or_else(|| missing("into_n"))?,
+        },
+        "lower" => transformations::StringOp::Lower,
+        "upper" => transformations::StringOp::Upper,
+        "strip" => transformations::StringOp::Strip,
+        "slice" => transformations::StringOp::Slice { start: start.ok_or_else(|| missing("start"))?, length },
+        "len_chars" => transformations::StringOp::LenChars,
+        other => {
+            return Err(PyValueError::new_err(format!(
+                "Unknown string op '{}'; expected 'contains', 'replace', 'split', 'lower', 'upper', 'strip', 'slice' or 'len_chars'",
+                other
+            )))
+        }
+    };
+    let df = pydict_to_dataframe(data)?;
+    let result = py.allow_threads(|| transformations::string_op(&df, column, &op, output, cast))?;
+    frame_result(py, data, result, &ProgressReporter::disabled())
+}
+
+/// Add calendar fields of a date or datetime column as new columns
+/// 
+/// Each feature becomes a column `{column}_{feature}`, read in the column's
+/// time zone; nulls stay null.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary or `RustDataFrame`
+/// * `column` - Date or datetime column
+/// * `features` - Feature name or list of names: "year", "quarter",
+///   "month", "week" (ISO 8601), "weekday" (1 = Monday to 7 = Sunday),
+///   "day", "hour", "minute", "is_weekend", "is_month_end" or
+///   "epoch_seconds" (seconds since 1970-01-01 UTC)
+/// 
+/// # Returns
+/// * Result dictionary, or a `RustDataFrame` when `data` is one
+/// 
+/// # Raises
+/// * `TypeError` - The column isn't a date or datetime, or "hour" or
+///   "minute" was asked of a date column
+/// * `ValueError` - Unknown feature or column, or a feature column name
+///   already in use
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// orders = insightora_core.datetime_features(orders, "ordered_at", ["weekday", "hour", "is_weekend"])
+/// by_hour = insightora_core.group_by(orders, "ordered_at_hour", {"amount": ["sum"]})
+/// ```
+#[pyfunction]
+pub fn datetime_features(py: Python, data: &PyAny, column: &str, features: &PyAny) -> PyResult<PyObject> {
+    let names: Vec<String> = extract_one_or_many(features)?;
+    let features = names
+        .iter()
+        .map(|name| transformations::DatetimeFeature::from_name(name))
+        .collect::<Result<Vec<_>, _>>()?;
+    let df = pydict_to_dataframe(data)?;
+    let featured = py.allow_threads(|| transformations::datetime_features(&df, column, &features))?;
+    frame_result(py, data, featured, &ProgressReporter::disabled())
+}
+
+/// Round a date or datetime column down to the start of its time bucket
+/// 
+/// For bucketing before `group_by`. Buckets of fixed width count from
+/// 1970-01-01, weeks start on Monday and months from January, so "3mo"
+/// gives calendar quarters. The column keeps its type and time zone.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary or `RustDataFrame`
+/// * `column` - Date or datetime column, replaced by the truncated values
+/// * `every` - Count and unit: "30s", "15m", "1h", "1d", "1w", "1mo",
+///   "1q" or "1y"
+/// * `tz` - For a zone-aware column, the zone to lay buckets out in instead
+///   of its own: "UTC" or a fixed offset such as "+05:30"
+/// 
+/// # Returns
+/// * Result dictionary, or a `RustDataFrame` when `data` is one
+/// 
+/// # Raises
+/// * `TypeError` - The column isn't a date or datetime, or a bucket
+///   shorter than a day was asked of a date column
+/// * `ValueError` - Invalid `every`, unknown column, or `tz` given for a
+///   column without a zone or naming an unsupported zone
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// requests = insightora_core.truncate_datetime(requests, "received_at", "15m")
+/// per_slot = insightora_core.group_by(requests, "received_at", {"latency_ms": ["mean", "max"]})
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, column, every, tz=None))]
+pub fn truncate_datetime(py: Python, data: &PyAny, column: &str, every: &str, tz: Option<&str>) -> PyResult<PyObject> {
+    let every = transformations::TruncateEvery::parse(every)?;
+    let df = pydict_to_dataframe(data)?;
+    let truncated = py.allow_threads(|| transformations::truncate_datetime(&df, column, &every, tz))?;
+    frame_result(py, data, truncated, &ProgressReporter::disabled())
+}
+
+/// Rename columns of a result dictionary
+/// 
+/// # Arguments
+/// * `data` - Result dictionary
+/// * `mapping` - Dict of old -> new column name, or a list of new names by
+///   position; renames apply together, so `{"a": "b", "b": "a"}` swaps columns.
+///   A column dictionary (see `map_columns`) renames matching columns to
+///   their canonical names
+/// * `strict` - Raise if a mapped column doesn't exist (default: True); a
+///   rename that produces duplicate names always raises
+/// 
+/// # Returns
+/// * Result dictionary with the new column names
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// tidy = insightora_core.rename_columns(data, {"Cust ID": "customer_id"})
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, mapping, strict=true))]
+pub fn rename_columns(py: Python, data: &PyAny, mapping: &PyAny, strict: bool) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let mapping = column_mapping(mapping)?;
+    let renamed = transformations::rename_columns(&df, &mapping, strict)?;
+    frame_result(py, data, renamed, &ProgressReporter::disabled())
+}
+
+/// Keep some columns of a table
+/// 
+/// Pass exactly one of `columns`, `pattern` or `dtypes`.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary or `RustDataFrame`
+/// * `columns` - Column name or list of columns, in the order wanted
+/// * `pattern` - Regex; columns whose names it matches anywhere are kept in
+///   their current order (anchor it with `^...$` to match whole names)
+/// * `dtypes` - Type name or list of names as shown in a result's `dtypes`:
+///   "Int64", "Float64", "String", "Boolean", "Date", ... A name without
+///   parameters such as "Datetime" matches every datetime column
+/// 
+/// # Returns
+/// * Result dictionary, or a `RustDataFrame` when `data` is one
+/// 
+/// # Raises
+/// * `ValueError` - A listed column doesn't exist or is listed twice, the
+///   pattern doesn't compile or a type name is unknown
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// metrics = insightora_core.select(data, pattern="^metric_")
+/// numeric = insightora_core.select(data, dtypes=["Int64", "Float64"])
+/// ```
+#[pyfunction]
+#[pyo3(name = "select", signature = (data, columns=None, pattern=None, dtypes=None))]
+pub fn select_columns(
+    py: Python,
+    data: &PyAny,
+    columns: Option<&PyAny>,
+    pattern: Option<&str>,
+    dtypes: Option<&PyAny>,
+) -> PyResult<PyObject> {
+    let selection = match (columns, pattern, dtypes) {
+        (Some(columns), None, None) => operations::ColumnSelection::Names(extract_one_or_many(columns)?),
+        (None, Some(pattern), None) => operations::ColumnSelection::Pattern(
+            regex::Regex::new(pattern)
+                .map_err(|e| PyValueError::new_err(format!("Invalid column pattern '{}': {}", pattern, e)))?,
+        ),
+        (None, None, Some(dtypes)) => operations::ColumnSelection::Dtypes(extract_one_or_many(dtypes)?),
+        _ => return Err(PyValueError::new_err("Pass exactly one of columns, pattern or dtypes")),
+    };
+    let df = pydict_to_dataframe(data)?;
+    let selected = operations::select_columns(&df, &selection)?;
+    frame_result(py, data, selected, &ProgressReporter::disabled())
+}
+
+/// Rename columns, raising on any name that doesn't exist
+/// 
+/// Like `rename_columns` with `strict=True`, for a `RustDataFrame` as
+/// well as a result dictionary.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary or `RustDataFrame`
+/// * `mapping` - Dict of old -> new column name, or a list of new names by
+///   position; renames apply together, so `{"a": "b", "b": "a"}` swaps columns
+/// 
+/// # Returns
+/// * Result dictionary, or a `RustDataFrame` when `data` is one
+/// 
+/// # Raises
+/// * `ValueError` - A mapped column doesn't exist, or two columns would end
+///   up with the same name; the message names both of them
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// tidy = insightora_core.rename(data, {"Cust ID": "customer_id", "Amt": "amount"})
+/// ```
+#[pyfunction]
+pub fn rename(py: Python, data: &PyAny, mapping: &PyAny) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let mapping = column_mapping(mapping)?;
+    let renamed = transformations::rename_columns(&df, &mapping, true)?;
+    frame_result(py, data, renamed, &ProgressReporter::disabled())
+}
+
+/// Remove columns from a table
+/// 
+/// # Arguments
+/// * `data` - Result dictionary or `RustDataFrame`
+/// * `columns` - Column name or list of columns to remove
+/// 
+/// # Returns
+/// * Result dictionary, or a `RustDataFrame` when `data` is one
+/// 
+/// # Raises
+/// * `ValueError` - A column doesn't exist
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// public = insightora_core.drop(customers, ["email", "phone"])
+/// ```
+#[pyfunction]
+#[pyo3(name = "drop")]
+pub fn drop_columns(py: Python, data: &PyAny, columns: &PyAny) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let columns: Vec<String> = extract_one_or_many(columns)?;
+    let kept = operations::drop_columns(&df, &columns)?;
+    frame_result(py, data, kept, &ProgressReporter::disabled())
+}
+
+/// Move columns to the front of a result dictionary
+/// 
+/// # Arguments
+/// * `data` - Result dictionary
+/// * `order` - Column names in their new order
+/// * `missing` - Columns left out of `order`: "error" (default) or "append"
+///   (kept after the listed columns in their current order)
+/// 
+/// # Returns
+/// * Result dictionary with reordered columns
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// result = insightora_core.reorder_columns(data, ["order_id", "region"], missing="append")
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, order, missing="error"))]
+pub fn reorder_columns(py: Python, data: &PyAny, order: Vec<String>, missing: &str) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let unlisted = operations::UnlistedColumns::from_name(missing)?;
+    let reordered = operations::reorder_columns(&df, &order, unlisted)?;
+    frame_result(py, data, reordered, &ProgressReporter::disabled())
+}
+
+/// Prepend a prefix to column names
+/// 
+/// # Arguments
+/// * `data` - Result dictionary
+/// * `prefix` - Text added before each name
+/// * `columns` - Column name or list of columns to rename (default: all)
+/// 
+/// # Returns
+/// * Result dictionary with the new column names
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// left = insightora_core.add_prefix(orders, "order_", columns=["id", "date"])
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, prefix, columns=None))]
+pub fn add_prefix(py: Python, data: &PyAny, prefix: &str, columns: Option<&PyAny>) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let columns: Option<Vec<String>> = columns.map(extract_one_or_many).transpose()?;
+    let renamed = operations::add_prefix(&df, prefix, columns.as_deref())?;
+    frame_result(py, data, renamed, &ProgressReporter::disabled())
+}
+
+/// Append a suffix to column names
+/// 
+/// # Arguments
+/// * `data` - Result dictionary
+/// * `suffix` - Text added after each name
+/// * `columns` - Column name or list of columns to rename (default: all)
+/// 
+/// # Returns
+/// * Result dictionary with the new column names
+#[pyfunction]
+#[pyo3(signature = (data, suffix, columns=None))]
+pub fn add_suffix(py: Python, data: &PyAny, suffix: &str, columns: Option<&PyAny>) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let columns: Option<Vec<String>> = columns.map(extract_one_or_many).transpose()?;
+    let renamed = operations::add_suffix(&df, suffix, columns.as_deref())?;
+    frame_result(py, data, renamed, &ProgressReporter::disabled())
+}
+
+/// Convert every column name to one naming convention
+/// 
+/// # Arguments
+/// * `data` - Result dictionary
+/// * `style` - "snake" ("Order Date" -> "order_date", the default), "lower"
+///   ("order date") or "camel" ("orderDate")
+/// * `on_collision` - "error" (default) raises listing every original name
+///   in each colliding group; "suffix" numbers the later ones ("_2", "_3", ...)
+/// 
+/// # Returns
+/// * Result dictionary with normalized column names
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// tidy = insightora_core.normalize_column_names(data)
+/// tidy = insightora_core.normalize_column_names(data, style="camel", on_collision="suffix")
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, style="snake", on_collision="error"))]
+pub fn normalize_column_names(py: Python, data: &PyAny, style: &str, on_collision: &str) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let style = operations::NameStyle::from_name(style)?;
+    let on_collision = operations::NameCollision::from_name(on_collision)?;
+    let renamed = operations::normalize_column_names(&df, style, on_collision)?;
+    frame_result(py, data, renamed, &ProgressReporter::disabled())
+}
+
+/// Canonical column names with their aliases, reusable across calls
+/// 
+/// Accepted wherever a `rename` mapping is, so parsers map headers as they
+/// read them.
+/// 
+/// # Arguments
+/// * `dictionary` - Dict of canonical name -> alias or list of aliases.
+///   Aliases match ignoring case and surrounding whitespace; prefix with
+///   "exact:" to match case included, or "re:" for a regex
+/// * `require` - Canonical names that must each match exactly one column
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// columns = insightora_core.ColumnDictionary(
+///     {"quantity": ["qty", r"re:(?i)^qty\s*ordered$"], "sku": ["item code"]},
+///     require=["quantity"],
+/// )
+/// result = insightora_core.parse_csv_with_options("feed.csv", rename=columns)
+/// ```
+#[pyclass(name = "ColumnDictionary")]
+pub struct PyColumnDictionary {
+    inner: ColumnDictionary,
+}
+
+#[pymethods]
+impl PyColumnDictionary {
+    #[new]
+    #[pyo3(signature = (dictionary, require=None))]
+    fn new(dictionary: &PyDict, require: Option<Vec<String>>) -> PyResult<Self> {
+        Ok(Self { inner: column_dictionary(dictionary, require.as_deref())? })
+    }
+    
+    /// Canonical names in the order they were given
+    #[getter]
+    fn canonical_names(&self) -> Vec<String> {
+        self.inner.canonical_names().map(str::to_string).collect()
+    }
+    
+    #[getter]
+    fn required(&self) -> Vec<String> {
+        self.inner.required().to_vec()
+    }
+    
+    fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+/// Helper function to build a column dictionary from canonical -> aliases
+fn column_dictionary(dictionary: &PyDict, require: Option<&[String]>) -> PyResult<ColumnDictionary> {
+    let mut built = ColumnDictionary::new();
+    for (canonical, aliases) in dictionary.iter() {
+        let aliases: Vec<String> = extract_one_or_many(aliases)?;
+        built = built.entry(canonical.extract()?, &aliases)?;
+    }
+    Ok(built.require(require.unwrap_or_default())?)
+}
+
+/// Match column names against a dictionary of canonical names and aliases
+/// 
+/// A canonical name matched by several columns, or a column matching
+/// several canonical names, is reported rather than guessed; those columns
+/// keep their names.
+/// 
+/// # Arguments
+/// * `data_or_schema` - Result dictionary, schema (as returned by
+///   `infer_csv_schema`) or list of column names
+/// * `dictionary` - ColumnDictionary, or dict of canonical name -> alias or
+///   list of aliases (see `ColumnDictionary`)
+/// * `require` - Canonical names that must each match exactly one column,
+///   added to those of the dictionary; raises ValueError listing the
+///   aliases tried otherwise
+/// 
+/// # Returns
+/// * Dictionary with `mapping` ({source: canonical}, ready for
+///   `rename_columns`), `columns` (names after renaming), `unmapped` (source
+///   columns matching nothing), `missing` (canonical names matching nothing),
+///   `ambiguous` ([{canonical, sources}]) and `conflicts` ([{source, canonical}])
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// matched = insightora_core.map_columns(data, {"quantity": ["qty", "QTY ordered"]}, require=["quantity"])
+/// data = insightora_core.rename_columns(data, matched["mapping"])
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data_or_schema, dictionary, require=None))]
+pub fn map_columns(py: Python, data_or_schema: &PyAny, dictionary: &PyAny, require: Option<Vec<String>>) -> PyResult<PyObject> {
+    let names: Vec<String> = match data_or_schema.downcast::<PyDict>() {
+        Ok(dict) => dict
+            .get_item("columns")?
+            .ok_or_else(|| PyValueError::new_err("Expected a result or schema dictionary with a 'columns' key"))?
+            .extract()?,
+        Err(_) => data_or_schema.extract()?,
+    };
+    let dictionary = match dictionary.extract::<PyRef<PyColumnDictionary>>() {
+        Ok(dictionary) => dictionary.inner.clone(),
+        Err(_) => column_dictionary(dictionary.downcast()?, None)?,
+    };
+    let dictionary = dictionary.require(require.as_deref().unwrap_or_default())?;
+    let matched = column_mapper::map_columns(&names, &dictionary)?;
+    let renamed = ColumnMapping::Names(matched.renames.clone()).apply_to_names(&names, true)?;
+    
+    let result = PyDict::new(py);
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    let mapping = PyDict::new(py);
+    for (source, canonical) in &matched.renames {
+        mapping.set_item(source, canonical)?;
+    }
+    result.set_item("mapping", mapping)?;
+    result.set_item("columns", renamed)?;
+    result.set_item("unmapped", &matched.unmapped)?;
+    result.set_item("missing", &matched.missing)?;
+    let ambiguous = PyList::empty(py);
+    for (canonical, sources) in &matched.ambiguous {
+        let record = PyDict::new(py);
+        record.set_item("canonical", canonical)?;
+        record.set_item("sources", sources)?;
+        ambiguous.append(record)?;
+    }
+    result.set_item("ambiguous", ambiguous)?;
+    let conflicts = PyList::empty(py);
+    for (source, canonical) in &matched.conflicts {
+        let record = PyDict::new(py);
+        record.set_item("source", source)?;
+        record.set_item("canonical", canonical)?;
+        conflicts.append(record)?;
+    }
+    result.set_item("conflicts", conflicts)?;
+    Ok(result.into())
+}
+
+/// Add columns computed from arithmetic expressions
+/// 
+/// Expressions use `+ - * / %`, parentheses, numbers, column names
+/// (backticks for names with spaces) and the functions `coalesce(a, b, ...)`,
+/// `nullif(a, b)` and `fill_null(a, value)`. Each expression may use the
+/// columns defined before it; an existing name is replaced in place.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary
+/// * `expressions` - Dict of new column name -> expression, applied in order
+/// * `null_arithmetic` - Null operands of arithmetic operators: "propagate"
+///   (result is null), "zero" (treated as 0) or "error" (raise naming the
+///   expression, column and first row) (default: "propagate")
+/// 
+/// # Returns
+/// * Result dictionary plus `null_counts` ({new column: null rows})
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// result = insightora_core.add_columns(
+///     data,
+///     {"total": "price * quantity", "net": "total - coalesce(discount, 0)"},
+///     null_arithmetic="zero",
+/// )
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, expressions, null_arithmetic="propagate"))]
+pub fn add_columns(py: Python, data: &PyAny, expressions: &PyDict, null_arithmetic: &str) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let nulls = NullArithmetic::from_name(null_arithmetic)?;
+    let definitions = expressions
+        .iter()
+        .map(|(name, source)| Ok(ColumnExpression::parse(name.extract()?, source.extract()?)?))
+        .collect::<PyResult<Vec<_>>>()?;
+    
+    let computed = py.allow_threads(|| expressions::add_columns(&df, &definitions, nulls))?;
+    let result = dataframe_to_pydict(py, &computed)?;
+    let null_counts = PyDict::new(py);
+    for expression in &definitions {
+        null_counts.set_item(&expression.name, computed.column(&expression.name).map_err(InsightoraError::from)?.null_count())?;
+    }
+    result.as_ref(py).downcast::<PyDict>()?.set_item("null_counts", null_counts)?;
+    Ok(result)
+}
+
+/// Helper function to build update assignments from a dict of column -> value
+/// 
+/// Strings are expressions; any other value, None included, is a literal.
+fn update_assignments(assignments: &PyDict) -> PyResult<Vec<(String, Assignment)>> {
+    assignments
+        .iter()
+        .map(|(column, value)| {
+            let column: String = column.extract()?;
+            let assignment = if value.is_instance_of::<pyo3::types::PyString>() {
+                Assignment::Expression(ColumnExpression::parse(&column, value.extract()?)?)
+            } else {
+                Assignment::Value(literal_series(&column, &[value], None, SchemaPolicy::Widen)?)
+            };
+            Ok((column, assignment))
+        })
+        .collect()
+}
+
+/// Set columns on the rows matching a condition, like SQL `UPDATE ... SET ... WHERE`
+/// 
+/// The condition and every new value are evaluated on the data as it was
+/// before the update, so a column can be both tested and assigned. Rows
+/// where the condition is false or null are untouched. Types are checked
+/// before any row is updated: integers fit any numeric column, floats only
+/// float columns, dates datetime columns; other values need the column's type.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary
+/// * `predicate` - Condition in the `add_columns` grammar extended with
+///   `== != < <= > >=`, `and`, `or`, `not`, `is_null(a)` and the literals
+///   `'text'`, `true`, `false` and `null`
+/// * `assignments` - Dict of existing column -> new value. A string is an
+///   expression (so text is quoted: `"'closed'"`); any other value (number,
+///   bool, date, datetime, Decimal, None) is used as is
+/// 
+/// # Returns
+/// * Result dictionary plus `rows_matched` and `rows_modified` ({column:
+///   rows whose value changed})
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// result = insightora_core.update_where(
+///     data,
+///     "status == 'open' and amount > 1000",
+///     {"status": "'review'", "amount": "amount * 0.9", "flagged": True},
+/// )
+/// print(result["rows_matched"], result["rows_modified"])
+/// ```
+#[pyfunction]
+pub fn update_where(py: Python, data: &PyAny, predicate: &str, assignments: &PyDict) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let predicate = Predicate::parse(predicate)?;
+    let assignments = update_assignments(assignments)?;
+    
+    let (updated, report) = py.allow_threads(|| transformations::update_where(&df, &predicate, &assignments))?;
+    let result = dataframe_to_pydict(py, &updated)?;
+    let dict = result.as_ref(py).downcast::<PyDict>()?;
+    dict.set_item("rows_matched", report.rows_matched)?;
+    let modified = PyDict::new(py);
+    for (column, rows) in &report.modified {
+        modified.set_item(column, rows)?;
+    }
+    dict.set_item("rows_modified", modified)?;
+    Ok(result)
+}
+
+/// Keep the rows where a condition is true
+/// 
+/// The condition is evaluated by Polars on all threads with the GIL
+/// released. Any comparison with null is null, and rows where the
+/// condition is null are dropped as with SQL `WHERE`; `and` / `or` follow
+/// three-valued logic, so `amount > 100 or vip` keeps a VIP row whose
+/// amount is null.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary or `RustDataFrame`
+/// * `expression` - Condition in the `row_filter` grammar of
+///   `parse_csv_with_options`: comparisons, `and`, `or`, `not`,
+///   `in [...]`, `is null`, `is not null` and `contains 'text'`. Unknown
+///   columns and malformed conditions raise ValueError giving the position
+///   of the offending token
+/// 
+/// # Returns
+/// * Result dictionary with the matching rows in their original order, or
+///   a `RustDataFrame` when `data` is one
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// orders = insightora_core.parse_csv("orders.csv", return_handle=True)
+/// large = insightora_core.filter(orders, "country in ['DE', 'AT'] and amount > 100")
+/// print(large.shape)
+/// ```
+#[pyfunction]
+#[pyo3(name = "filter")]
+pub fn filter_data(py: Python, data: &PyAny, expression: &str) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let predicate = Predicate::parse(expression)?;
+    let filtered = py.allow_threads(|| operations::filter_rows(&df, &predicate))?;
+    frame_result(py, data, filtered, &ProgressReporter::disabled())
+}
+
+/// Helper function to read join keys given as `on` or as `left_on` and `right_on`
+fn join_keys(
+    on: Option<&PyAny>,
+    left_on: Option<&PyAny>,
+    right_on: Option<&PyAny>,
+) -> PyResult<(Vec<String>, Vec<String>)> {
+    match (on, left_on, right_on) {
+        (Some(on), None, None) => {
+            let keys: Vec<String> = extract_one_or_many(on)?;
+            Ok((keys.clone(), keys))
+        }
+        (None, Some(l), Some(r)) => Ok((extract_one_or_many(l)?, extract_one_or_many(r)?)),
+        _ => Err(PyValueError::new_err("Pass either 'on' or both 'left_on' and 'right_on'")),
+    }
+}
+
+/// Join two tables on key columns, including semi and anti joins
+/// 
+/// A null in any key column never matches, not even another null. Numeric
+/// keys of different types (Int64 and Float64) are compared as a common
+/// type unless `strict=True`: as floats when every integer fits one
+/// exactly, else as integers when every float is whole, else they raise.
+/// Other type mismatches raise. The join runs natively with other Python
+/// threads free.
+/// 
+/// # Arguments
+/// * `left` / `right` - Result dictionaries or `RustDataFrame`s
+/// * `on` - Key column(s) present in both inputs
+/// * `left_on` / `right_on` - Key column(s) when names differ
+/// * `how` - "inner" (default), "left", "outer", "semi" (left rows with a
+///   match) or "anti" (left rows without one)
+/// * `suffix` - Appended to right non-key columns whose names the left side
+///   already has (default: "_right")
+/// * `strict` - Raise instead of casting when numeric key types differ
+/// 
+/// # Returns
+/// * Result dictionary plus `left_rows` and `right_rows`; compare them with
+///   `num_rows` to catch keys that fan out. A `RustDataFrame` when `left` is one
+/// 
+/// # Raises
+/// * `ValueError` - A key column is missing, key types don't match, or the
+///   suffix gives a name already in use
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// joined = insightora_core.join(orders, customers, on=["region", "customer_id"], how="left")
+/// assert joined["num_rows"] == joined["left_rows"], "customer keys are not unique"
+/// 
+/// unpaid = insightora_core.join(orders, payments, on="order_id", how="anti")
+/// ```
+#[pyfunction]
+#[pyo3(name = "join", signature = (left, right, on=None, left_on=None, right_on=None, how="inner", suffix="_right", strict=false))]
+#[allow(clippy::too_many_arguments)]
+pub fn join_rows(
+    py: Python,
+    left: &PyAny,
+    right: &PyAny,
+    on: Option<&PyAny>,
+    left_on: Option<&PyAny>,
+    right_on: Option<&PyAny>,
+    how: &str,
+    suffix: &str,
+    strict: bool,
+) -> PyResult<PyObject> {
+    let (left_on, right_on) = join_keys(on, left_on, right_on)?;
+    let options = operations::JoinOptions {
+        how: operations::JoinHow::from_name(how)?,
+        suffix: suffix.to_string(),
+        strict,
+    };
+    let (left_df, right_df) = (pydict_to_dataframe(left)?, pydict_to_dataframe(right)?);
+    let (joined, counts) = py.allow_threads(|| operations::join_rows(&left_df, &right_df, &left_on, &right_on, &options))?;
+    
+    let result = frame_result(py, left, joined, &ProgressReporter::disabled())?;
+    if let Ok(dict) = result.as_ref(py).downcast::<PyDict>() {
+        dict.set_item("left_rows", counts.left_rows)?;
+        dict.set_item("right_rows", counts.right_rows)?;
+    }
+    Ok(result)
+}
+
+/// Join two result dictionaries on key columns
+/// 
+/// # Arguments
+/// * `left` / `right` - Result dictionaries
+/// * `on` - Key column(s) present in both inputs
+/// * `left_on` / `right_on` - Key column(s) when names differ
+/// * `how` - "inner", "left" or "outer"
+/// * `float_precision` / `float_step` / `nan_equal` - As in `drop_duplicates`
+/// * `engine` - "auto" (partition when the right side exceeds a quarter of the
+///   memory limit), "in_memory" or "partitioned" (default: "auto")
+/// * `partitions` - Partition count for the partitioned engine (default: derived from the memory limit);
+///   spill files go to a private directory under `configure(temp_dir=...)`
+/// * `on_progress` - Optional callable `(percent, stage, detail)` for progress
+/// * `include_summary` / `include_samples` - Execution summary as in `parse_csv_with_options`;
+///   unmatched left rows count as dropped for inner joins
+/// 
+/// # Returns
+/// * Result dictionary plus `rows_affected_by_precision`, `join_engine`
+///   ("in_memory" or "partitioned") and `join_partitions`
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// joined = insightora_core.join_data(orders, prices, on="unit_price", float_precision=2)
+/// 
+/// # Spill both sides to 16 partitions regardless of size
+/// joined = insightora_core.join_data(events, users, on="user_id", engine="partitioned", partitions=16)
+/// print(joined["join_engine"], joined["join_partitions"])
+/// ```
+#[pyfunction]
+#[pyo3(signature = (
+    left,
+    right,
+    on=None,
+    left_on=None,
+    right_on=None,
+    how="inner",
+    float_precision=None,
+    float_step=None,
+    nan_equal=true,
+    engine="auto",
+    partitions=None,
+    on_progress=None,
+    include_summary=false,
+    include_samples=false
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn join_data(
+    py: Python,
+    left: &PyAny,
+    right: &PyAny,
+    on: Option<&PyAny>,
+    left_on: Option<&PyAny>,
+    right_on: Option<&PyAny>,
+    how: &str,
+    float_precision: Option<u32>,
+    float_step: Option<f64>,
+    nan_equal: bool,
+    engine: &str,
+    partitions: Option<usize>,
+    on_progress: Option<PyObject>,
+    include_summary: bool,
+    include_samples: bool,
+) -> PyResult<PyObject> {
+    let mut metrics = ExecutionMetrics::new("join_data");
+    metrics.option("how", how);
+    metrics.option("engine", engine);
+    metrics.option("float_precision", float_precision.map_or("exact".to_string(), |p| p.to_string()));
+    metrics.option("float_step", float_step.map_or("none".to_string(), |s| s.to_string()));
+    metrics.option("nan_equal", nan_equal);
+    let (left_on, right_on) = join_keys(on, left_on, right_on)?;
+    let progress = progress_reporter(on_progress);
+    let (left_cells, right_cells) = (dict_cells(left), dict_cells(right));
+    // Output size is unknown up front; assume about the size of both inputs
+    let stages = progress.stages(&[
+        ("load left", left_cells),
+        ("load right", right_cells),
+        ("join", (left_cells + right_cells) * 0.5),
+        ("export", left_cells + right_cells),
+    ]);
+    metrics.option("left_on", left_on.join(", "));
+    metrics.option("right_on", right_on.join(", "));
+    let (left, right) = metrics.time("load", || -> PyResult<_> {
+        Ok((
+            pydict_to_dataframe_reporting(left, &stages[0])?,
+            pydict_to_dataframe_reporting(right, &stages[1])?,
+        ))
+    })?;
+    let how_name = how;
+    let how = operations::join_type_from_name(how)?;
+    let options = float_key_options(float_precision, float_step, nan_equal)?;
+    let engine = match (operations::JoinEngine::from_name(engine)?, partitions) {
+        (operations::JoinEngine::Partitioned { .. }, Some(k)) => operations::JoinEngine::Partitioned { partitions: Some(k) },
+        (_, Some(_)) => {
+            return Err(PyValueError::new_err("'partitions' requires engine='partitioned'"));
+        }
+        (engine, None) => engine,
+    };
+    
+    stages[2].report(0.0, "join");
+    let (joined, report) = metrics.time("join", || py.allow_threads(|| {
+        operations::join_dataframes(&left, &right, &left_on, &right_on, how, &options, engine)
+    }))?;
+    stages[2].finish(report.engine.name());
+    
+    let table = metrics.time("export", || dataframe_to_pydict_reporting(py, &joined, &stages[3]))?;
+    let result = with_quantization_report(py, table, &report.quantization())?;
+    let dict = result.as_ref(py).downcast::<PyDict>()?;
+    dict.set_item("join_engine", report.engine.name())?;
+    dict.set_item("join_partitions", report.partitions)?;
+    if include_summary {
+        metrics.engine(report.engine.name());
+        metrics.chunks(report.partitions);
+        metrics.rows(left.height() + right.height(), joined.height());
+        // Matches can fan out, so only a shrinking inner join has a drop count
+        if how_name == "inner" {
+            metrics.dropped("unmatched", left.height().saturating_sub(joined.height()));
+        }
+        metrics.adjusted("rows_affected_by_precision", report.rows_affected);
+        metrics.observe_memory(left.estimated_size() + right.estimated_size());
+        metrics.observe_frame(&joined);
+        if include_samples {
+            sample_first_row(&mut metrics, &joined);
+        }
+        with_summary(py, result, &metrics, include_samples)
+    } else {
+        Ok(result)
+    }
+}
+
+/// Stitch tables together, one under another or side by side
+/// 
+/// Batches collected from `parse_csv_glob` or a batched reader can be
+/// combined here without going through pandas; `RustDataFrame` inputs are
+/// combined without converting their values.
+/// 
+/// # Arguments
+/// * `frames` - List of result dictionaries or `RustDataFrame`s
+/// * `how` - "vertical" (default): rows appended, columns matched by name;
+///   or "horizontal": columns appended, rows matched by position
+/// * `rechunk` - Store each result column contiguously (default: True)
+/// * `relaxed` - Vertical only: fill columns a frame lacks with nulls and
+///   widen columns whose types differ to strings (default: False, which
+///   requires the same columns and types in every frame)
+/// * `suffix` - Horizontal only: rename a column name already used by an
+///   earlier frame to `{name}{suffix}{frame index}`, e.g. "amount_2" with
+///   suffix "_" (default: None, which rejects duplicates)
+/// 
+/// # Returns
+/// * Result dictionary, or a `RustDataFrame` when the first frame is one
+/// 
+/// # Raises
+/// * `ValueError` - Columns differ (naming them), row counts differ for a
+///   horizontal concat, or a column name is duplicated without `suffix`
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// batches = [insightora_core.parse_csv(path, return_handle=True) for path in paths]
+/// combined = insightora_core.concat(batches, relaxed=True)
+/// 
+/// wide = insightora_core.concat([features, predictions], how="horizontal", suffix="_")
+/// ```
+#[pyfunction]
+#[pyo3(signature = (frames, how="vertical", rechunk=true, relaxed=false, suffix=None))]
+pub fn concat(
+    py: Python,
+    frames: Vec<&PyAny>,
+    how: &str,
+    rechunk: bool,
+    relaxed: bool,
+    suffix: Option<String>,
+) -> PyResult<PyObject> {
+    let Some(&first) = frames.first() else {
+        return Err(PyValueError::new_err("concat needs at least one frame"));
+    };
+    let options = operations::ConcatOptions {
+        how: operations::ConcatHow::from_name(how)?,
+        relaxed,
+        suffix,
+        rechunk,
+    };
+    let frames = frames.iter().map(|frame| pydict_to_dataframe(frame)).collect::<PyResult<Vec<_>>>()?;
+    let combined = py.allow_threads(|| operations::concat_frames(&frames, &options))?;
+    frame_result(py, first, combined, &ProgressReporter::disabled())
+}
+
+/// Suggest join keys between two undocumented result dictionaries
+/// 
+/// Profiles at most 10,000 evenly spaced rows of each side, scoring column
+/// pairs (and two-column combinations) on name similarity, dtype
+/// compatibility, overlap of hashed sample values and cardinality. Results
+/// are heuristic estimates from samples: verify a key before relying on it.
+/// 
+/// # Arguments
+/// * `left` / `right` - Result dictionaries
+/// * `max_candidates` - Candidates returned, best first (default: 5)
+/// 
+/// # Returns
+/// * Dictionary with `heuristic` (always True), `sample_rows` ({"left", "right"})
+///   and `candidates`, each with `left` / `right` column lists, `score`,
+///   `name_similarity`, `value_overlap`, `estimated_match_rate`,
+///   `left_uniqueness`, `right_uniqueness` and `dtype_match`
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// suggestions = insightora_core.suggest_join_keys(orders, customers)
+/// for candidate in suggestions["candidates"]:
+///     print(candidate["left"], candidate["right"], candidate["estimated_match_rate"])
+/// ```
+#[pyfunction]
+#[pyo3(signature = (left, right, max_candidates=5))]
+pub fn suggest_join_keys(py: Python, left: &PyAny, right: &PyAny, max_candidates: usize) -> PyResult<PyObject> {
+    let left = pydict_to_dataframe(left)?;
+    let right = pydict_to_dataframe(right)?;
+    let candidates = py.allow_threads(|| operations::suggest_join_keys(&left, &right, max_candidates))?;
+    
+    let records = PyList::empty(py);
+    for candidate in &candidates {
+        let record = PyDict::new(py);
+        record.set_item("left", &candidate.left)?;
+        record.set_item("right", &candidate.right)?;
+        record.set_item("score", candidate.score)?;
+        record.set_item("name_similarity", candidate.name_similarity)?;
+        record.set_item("value_overlap", candidate.value_overlap)?;
+        record.set_item("estimated_match_rate", candidate.estimated_match_rate)?;
+        record.set_item("left_uniqueness", candidate.left_uniqueness)?;
+        record.set_item("right_uniqueness", candidate.right_uniqueness)?;
+        record.set_item("dtype_match", candidate.dtype_match)?;
+        records.append(record)?;
+    }
+    let sample_rows = PyDict::new(py);
+    sample_rows.set_item("left", left.height().min(operations::JOIN_KEY_SAMPLE_ROWS))?;
+    sample_rows.set_item("right", right.height().min(operations::JOIN_KEY_SAMPLE_ROWS))?;
+    
+    let result = PyDict::new(py);
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    result.set_item("heuristic", true)?;
+    result.set_item("sample_rows", sample_rows)?;
+    result.set_item("candidates", records)?;
+    Ok(result.into())
+}
+
+/// Count occurrences of each distinct value in a column
+/// 
+/// # Arguments
+/// * `data` - Result dictionary
+/// * `column` - Column to count
+/// * `float_precision` / `float_step` / `nan_equal` - As in `drop_duplicates`
+/// * `on_progress` - Optional callable `(percent, stage, detail)` for progress
+/// 
+/// # Returns
+/// * Result dictionary with the column and a `count` column, most frequent
+///   first, plus `rows_affected_by_precision`
+#[pyfunction]
+#[pyo3(signature = (data, column, float_precision=None, float_step=None, nan_equal=true, on_progress=None))]
+pub fn value_counts(
+    py: Python,
+    data: &PyAny,
+    column: &str,
+    float_precision: Option<u32>,
+    float_step: Option<f64>,
+    nan_equal: bool,
+    on_progress: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let progress = progress_reporter(on_progress);
+    let cells = dict_cells(data);
+    // Counts are usually far smaller than the input
+    let stages = binding_stages(&progress, cells, cells * 0.1);
+    let df = pydict_to_dataframe_reporting(data, &stages[0])?;
+    let options = float_key_options(float_precision, float_step, nan_equal)?;
+    
+    stages[1].report(0.0, "count");
+    let (counts, report) = py.allow_threads(|| aggregations::value_counts(&df, column, &options))?;
+    stages[1].finish("count");
+    
+    with_quantization_report(py, dataframe_to_pydict_reporting(py, &counts, &stages[2])?, &report)
+}
+
+/// Rows passed to a Python aggregation callable per call
+const UDAF_CHUNK_ROWS: usize = 65_536;
+
+/// Aggregation backed by Python init/update/merge/finalize callables
+struct PyAggregation {
+    init: PyObject,
+    update: PyObject,
+    merge: PyObject,
+    finalize: PyObject,
+}
+
+impl PyAggregation {
+    fn run(&self, py: Python, values: &[f64]) -> PyResult<Option<f64>> {
+        let numpy = py.import("numpy")?;
+        let mut state: Option<PyObject> = None;
+        for chunk in values.chunks(UDAF_CHUNK_ROWS) {
+            let bytes: Vec<u8> = chunk.iter().flat_map(|v| v.to_ne_bytes()).collect();
+            let array = numpy.call_method1("frombuffer", (PyBytes::new(py, &bytes), "float64"))?;
+            let partial = self.update.call1(py, (self.init.call0(py)?, array))?;
+            state = Some(match state {
+                Some(previous) => self.merge.call1(py, (previous, partial))?,
+                None => partial,
+            });
+        }
+        let state = match state {
+            Some(state) => state,
+            None => self.init.call0(py)?,
+        };
+        let result = self.finalize.call1(py, (state,))?;
+        if result.is_none(py) {
+            Ok(None)
+        } else {
+            Ok(Some(result.extract::<f64>(py)?))
+        }
+    }
+}
+
+impl aggregations::CustomAggregation for PyAggregation {
+    fn aggregate(&self, values: &[f64]) -> Result<Option<f64>, InsightoraError> {
+        if !python_available() {
+            return Err(InsightoraError::Cancelled);
+        }
+        Python::with_gil(|py| {
+            self.run(py, values)
+                .map_err(|e| InsightoraError::ValidationError(e.to_string()))
+        })
+    }
+}
+
+/// Register a custom aggregation for use in `group_by`
+/// 
+/// Each group's non-null values are handed to Python as read-only float64
+/// NumPy arrays, in chunks of up to 65,536 rows, so the GIL is taken per chunk
+/// rather than per row. For every chunk `update(init(), chunk)` produces a
+/// partial state; partial states are combined with `merge(a, b)` and
+/// `finalize(state)` returns a float (or None for a null result).
+/// 
+/// # Arguments
+/// * `name` - Name to use in `group_by` specs (built-in names are reserved)
+/// * `init` - Callable returning an empty state
+/// * `update` - Callable `(state, values) -> state`
+/// * `merge` - Callable `(state, state) -> state`
+/// * `finalize` - Callable `(state) -> float | None`
+/// 
+/// # Example
+/// ```python
+/// import numpy as np
+/// import insightora_core
+/// 
+/// def finalize(parts):
+///     values = np.sort(np.concatenate(parts)) if parts else np.array([])
+///     cut = int(len(values) * 0.1)
+///     kept = values[cut:len(values) - cut]
+///     return float(kept.mean()) if len(kept) else None
+/// 
+/// insightora_core.register_aggregation(
+///     "trimmed_mean",
+///     init=list,
+///     update=lambda state, values: state + [values],
+///     merge=lambda a, b: a + b,
+///     finalize=finalize,
+/// )
+/// summary = insightora_core.group_by(data, "region", {"amount": ["mean", "trimmed_mean"]})
+/// ```
+#[pyfunction]
+pub fn register_aggregation(
+    name: &str,
+    init: PyObject,
+    update: PyObject,
+    merge: PyObject,
+    finalize: PyObject,
+) -> PyResult<()> {
+    let aggregation = PyAggregation { init, update, merge, finalize };
+    aggregations::register_aggregation(name, Arc::new(aggregation))?;
+    Ok(())
+}
+
+/// Group rows by key columns and aggregate
+/// 
+/// # Arguments
+/// * `data` - Result dictionary
+/// * `keys` - Key column name or list of names
+/// * `aggregations` - Dict mapping column to an aggregation name or list of
+///   names: built-ins (sum, mean, min, max, median, std, var, count,
+///   n_unique, first, last, list) or registered aggregations. Nulls are
+///   skipped except by first, last and list; std and var are sample
+///   statistics; list gives each group's values as a Python list
+/// * `on_progress` - Optional callable `(percent, stage, detail)` for progress
+/// * `drill_down` - Also record the source rows behind each group (default: False)
+/// * `max_rows_per_group` - Row indices kept per group when drilling down
+/// * `output` - "flat" (default) or "tree": groups nested key by key
+/// * `level_order` - Tree only: per key level, "appearance" (default), "key"
+///   or an aggregate output such as "amount_sum"
+/// * `level_descending` - Tree only: bool or list of bools matching `level_order`
+/// * `subtotals` - Tree only: give inner nodes the aggregates of all rows
+///   beneath them (computed from the rows, so means are exact)
+/// * `max_nodes` - Tree only: total nodes kept (default: 10000); the deepest
+///   levels are cut first
+/// * `sort_groups` - Flat only: order groups by their keys, nulls last,
+///   instead of by first appearance (default: False)
+/// 
+/// # Returns
+/// * Result dictionary with the keys and `{column}_{aggregation}` columns;
+///   with `drill_down`, a `drill_down` dict holding `handle` (for
+///   `get_contributors`), `max_rows_per_group`, `row_counts` and `row_indices`
+///   (one list per result row, capped)
+/// * With `output="tree"`: `keys`, `aggregates`, `level_counts` (nodes per
+///   level before truncation), `truncated`, `dropped_nodes` and `nodes`.
+///   Each node has `key`, `child_count` and `children` (inner levels), and
+///   `values` ({aggregate: value}) on leaves and, with `subtotals`, inner nodes
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// summary = insightora_core.group_by(data, ["region", "year"], {"amount": ["sum", "mean"]})
+/// 
+/// by_user = insightora_core.group_by(
+///     data, "user_id", {"amount": ["sum", "median", "list"], "order_id": "n_unique"}, sort_groups=True,
+/// )
+/// 
+/// summary = insightora_core.group_by(data, "region", {"amount": "sum"}, drill_down=True)
+/// rows = insightora_core.get_contributors(summary["drill_down"]["handle"], key="EU")
+/// 
+/// tree = insightora_core.group_by(
+///     data, ["region", "year", "channel"], {"amount": "sum"},
+///     output="tree", level_order=["key", "amount_sum"], level_descending=[False, True], subtotals=True,
+/// )
+/// for region in tree["nodes"]:
+///     print(region["key"], region["values"]["amount_sum"], region["child_count"])
+/// ```
+#[pyfunction]
+#[pyo3(signature = (
+    data,
+    keys,
+    aggregations,
+    on_progress=None,
+    drill_down=false,
+    max_rows_per_group=aggregations::DEFAULT_DRILL_DOWN_ROWS,
+    output="flat",
+    level_order=None,
+    level_descending=None,
+    subtotals=false,
+    max_nodes=aggregations::DEFAULT_TREE_NODES,
+    sort_groups=false
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn group_by(
+    py: Python,
+    data: &PyAny,
+    keys: &PyAny,
+    aggregations: &PyDict,
+    on_progress: Option<PyObject>,
+    drill_down: bool,
+    max_rows_per_group: usize,
+    output: &str,
+    level_order: Option<Vec<String>>,
+    level_descending: Option<&PyAny>,
+    subtotals: bool,
+    max_nodes: usize,
+    sort_groups: bool,
+) -> PyResult<PyObject> {
+    if sort_groups && (output != "flat" || drill_down) {
+        return Err(PyValueError::new_err(
+            "sort_groups requires output='flat' without drill_down; order tree levels with level_order"
+        ));
+    }
+    let tree = match output {
+        "flat" => {
+            if level_order.is_some() || level_descending.is_some() || subtotals {
+                return Err(PyValueError::new_err("level_order, level_descending and subtotals require output='tree'"));
+            }
+            None
+        }
+        "tree" => {
+            if drill_down {
+                return Err(PyValueError::new_err("drill_down requires output='flat'"));
+            }
+            let names = level_order.unwrap_or_default();
+            let descending: Vec<bool> = match level_descending {
+                Some(value) => extract_one_or_many(value)?,
+                None => Vec::new(),
+            };
+            let descending = match descending.len() {
+                0 => vec![false; names.len()],
+                1 => vec![descending[0]; names.len()],
+                n if n == names.len() => descending,
+                _ => return Err(PyValueError::new_err("level_descending must be a bool or match level_order")),
+            };
+            Some(aggregations::GroupTreeOptions {
+                order: names.iter().zip(descending).map(|(name, desc)| aggregations::TreeOrder::from_name(name, desc)).collect(),
+                subtotals,
+                max_nodes,
+            })
+        }
+        other => return Err(PyValueError::new_err(format!("Unknown output '{}'; expected 'flat' or 'tree'", other))),
+    };
+
+    let progress = progress_reporter(on_progress);
+    let cells = dict_cells(data);
+    // Groups are usually far fewer than rows
+    let stages = binding_stages(&progress, cells, cells * 0.1);
+    let df = pydict_to_dataframe_reporting(data, &stages[0])?;
+    let keys: Vec<String> = extract_one_or_many(keys)?;
+    let specs = aggregations
+        .iter()
+        .map(|(column, names)| Ok((column.extract::<String>()?, extract_one_or_many::<String>(names)?)))
+        .collect::<PyResult<Vec<_>>>()?;
+    
+    stages[1].report(0.0, "aggregate");
+    if let Some(options) = tree {
+        let tree = py.allow_threads(|| aggregations::group_by_tree(&df, &keys, &specs, &options))?;
+        stages[1].finish("aggregate");
+        let result = group_tree_to_pydict(py, &tree)?;
+        stages[2].finish("export");
+        return Ok(result);
+    }
+    if !drill_down {
+        let grouped = py.allow_threads(|| {
+            let grouped = aggregations::group_by(&df, &keys, &specs)?;
+            if sort_groups {
+                operations::sort_rows(&grouped, &keys, &[], true, false)
+            } else {
+                Ok(grouped)
+            }
+        })?;
+        stages[1].finish("aggregate");
+        return dataframe_to_pydict_reporting(py, &grouped, &stages[2]);
+    }
+    let (grouped, contributors) = py.allow_threads(|| {
+        aggregations::group_by_drill_down(&df, &keys, &specs, max_rows_per_group)
+    })?;
+    stages[1].finish("aggregate");
+    
+    let result = dataframe_to_pydict_reporting(py, &grouped, &stages[2])?;
+    let provenance = PyDict::new(py);
+    provenance.set_item("max_rows_per_group", contributors.max_rows_per_group())?;
+    let groups = 0..contributors.group_count();
+    provenance.set_item("row_counts", groups.clone().map(|g| contributors.row_count(g).unwrap_or(0)).collect::<Vec<_>>())?;
+    provenance.set_item("row_indices", groups.map(|g| contributors.row_indices(g).unwrap_or_default()).collect::<Vec<_>>())?;
+    provenance.set_item("handle", aggregations::register_drill_down(contributors)?)?;
+    result.as_ref(py).set_item("drill_down", provenance)?;
+    Ok(result)
+}
+
+/// Helper function to convert a group tree to nested node dicts
+/// 
+/// Key and aggregate columns go through `series_to_python_list` once per
+/// level, so tree values match the flat result exactly.
+fn group_tree_to_pydict(py: Python, tree: &aggregations::GroupTree) -> PyResult<PyObject> {
+    let column = |level: &polars::prelude::DataFrame, name: &str| -> PyResult<PyObject> {
+        series_to_python_list(py, level.column(name).map_err(InsightoraError::from)?)
+    };
+    let mut key_values = Vec::with_capacity(tree.levels.len());
+    let mut aggregate_values = Vec::with_capacity(tree.levels.len());
+    for (level, key) in tree.levels.iter().zip(&tree.keys) {
+        key_values.push(column(level, key)?);
+        aggregate_values.push(tree.aggregates.iter().map(|name| column(level, name)).collect::<PyResult<Vec<_>>>()?);
+    }
+    
+    fn node_dict<'py>(
+        py: Python<'py>,
+        tree: &aggregations::GroupTree,
+        key_values: &[PyObject],
+        aggregate_values: &[Vec<PyObject>],
+        node: &aggregations::GroupNode,
+        depth: usize,
+    ) -> PyResult<&'py PyDict> {
+        let dict = PyDict::new(py);
+        dict.set_item("key", key_values[depth].as_ref(py).get_item(node.row)?)?;
+        let is_leaf = depth + 1 == tree.levels.len();
+        if is_leaf || tree.subtotals {
+            let values = PyDict::new(py);
+            for (name, column) in tree.aggregates.iter().zip(&aggregate_values[depth]) {
+                values.set_item(name, column.as_ref(py).get_item(node.row)?)?;
+            }
+            dict.set_item("values", values)?;
+        }
+        if !is_leaf {
+            let children = PyList::empty(py);
+            for child in &node.children {
+                children.append(node_dict(py, tree, key_values, aggregate_values, child, depth + 1)?)?;
+            }
+            dict.set_item("child_count", node.child_count)?;
+            dict.set_item("children", children)?;
+        }
+        Ok(dict)
+    }
+    
+    let nodes = PyList::empty(py);
+    for root in &tree.roots {
+        nodes.append(node_dict(py, tree, &key_values, &aggregate_values, root, 0)?)?;
+    }
+    let result = PyDict::new(py);
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    result.set_item("keys", &tree.keys)?;
+    result.set_item("aggregates", &tree.aggregates)?;
+    result.set_item("level_counts", &tree.level_counts)?;
+    result.set_item("truncated", tree.is_truncated())?;
+    result.set_item("dropped_nodes", tree.dropped_nodes)?;
+    result.set_item("nodes", nodes)?;
+    Ok(result.into())
+}
+
+/// Fetch the source rows behind one group of a drilled-down `group_by`
+/// 
+/// # Arguments
+/// * `handle` - `result["drill_down"]["handle"]` from `group_by(..., drill_down=True)`
+/// * `key` - Group key: a value for a single key column, a list/tuple of
+///   values, or a dict of key column to value (matched by string form)
+/// * `row` - Alternatively, the group's row in the `group_by` result
+/// 
+/// # Returns
+/// * Result dictionary with the contributing rows (at most
+///   `max_rows_per_group`), plus `group_row`, `row_count` (all rows in the
+///   group), `truncated` and `row_indices` (positions in the source data)
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// summary = insightora_core.group_by(data, ["region", "year"], {"amount": "sum"}, drill_down=True)
+/// handle = summary["drill_down"]["handle"]
+/// rows = insightora_core.get_contributors(handle, key={"region": "EU", "year": 2024})
+/// if rows["truncated"]:
+///     print(f"showing {rows['num_rows']} of {rows['row_count']} rows")
+/// insightora_core.release_drill_down(handle)
+/// ```
+#[pyfunction]
+#[pyo3(signature = (handle, key=None, row=None))]
+pub fn get_contributors(py: Python, handle: u64, key: Option<&PyAny>, row: Option<usize>) -> PyResult<PyObject> {
+    let contributors = aggregations::drill_down(handle)?;
+    let group = match (key, row) {
+        (Some(_), Some(_)) | (None, None) => {
+            return Err(PyValueError::new_err("Pass exactly one of 'key' or 'row'"));
+        }
+        (None, Some(row)) => row,
+        (Some(key), None) => {
+            let values: Vec<&PyAny> = if let Ok(dict) = key.downcast::<PyDict>() {
+                contributors.groups().get_column_names().into_iter()
+                    .map(|name| dict.get_item(name)?.ok_or_else(|| {
+                        PyValueError::new_err(format!("Group key is missing key column '{}'", name))
+                    }))
+                    .collect::<PyResult<_>>()?
+            } else if key.is_instance_of::<PyList>() || key.is_instance_of::<pyo3::types::PyTuple>() {
+                key.iter()?.collect::<PyResult<_>>()?
+            } else {
+                vec![key]
+            };
+            let key = values.into_iter()
+                .map(|v| if v.is_none() { Ok(None) } else { group_value_to_string(v).map(Some) })
+                .collect::<PyResult<Vec<_>>>()?;
+            contributors.find_group(&key)?
+                .ok_or_else(|| PyValueError::new_err(format!("No group with key {:?}", key)))?
+        }
+    };
+    
+    let rows = py.allow_threads(|| contributors.contributors(group))?;
+    let row_count = contributors.row_count(group).unwrap_or(0);
+    let result = dataframe_to_pydict(py, &rows)?;
+    let dict = result.as_ref(py).downcast::<PyDict>()?;
+    dict.set_item("group_row", group)?;
+    dict.set_item("row_count", row_count)?;
+    dict.set_item("truncated", row_count > rows.height())?;
+    dict.set_item("row_indices", contributors.row_indices(group).unwrap_or_default())?;
+    Ok(result)
+}
+
+/// Release a drill-down handle and its reference to the source data
+/// 
+/// At most 16 drill-downs are retained; older ones are released
+/// automatically. Returns whether the handle was still registered.
+#[pyfunction]
+pub fn release_drill_down(handle: u64) -> PyResult<bool> {
+    Ok(aggregations::release_drill_down(handle)?)
+}
+
+// ============================================================================
+// Pipeline Python Bindings
+// ============================================================================
+
+use crate::dataframe::pipeline::{dtype_from_name, Pipeline, PipelineRunReport, PipelineStep, PreparedPipeline};
+
+/// Cleaning steps recorded once and compiled with `prepare()`
+/// 
+/// Step methods return the pipeline, so calls chain.
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// prepared = (
+///     insightora_core.Pipeline()
+///     .rename({"Cust ID": "customer_id"})
+///     .add_columns({"total": "price * qty"}, null_arithmetic="zero")
+///     .drop_duplicates(["customer_id"])
+///     .prepare({"Cust ID": "str", "price": "f64", "qty": "i64"})
+/// )
+/// for path in daily_files:
+///     result = prepared.run(path)
+///     print(result["report"])
+/// ```
+#[pyclass(name = "Pipeline")]
+#[derive(Default)]
+pub struct PyPipeline {
+    inner: Pipeline,
+}
+
+#[pymethods]
+impl PyPipeline {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Rename columns (dict of old -> new, or new names by position)
+    fn rename<'p>(mut slf: PyRefMut<'p, Self>, mapping: &PyAny) -> PyResult<PyRefMut<'p, Self>> {
+        slf.inner.push(PipelineStep::Rename(column_mapping(mapping)?));
+        Ok(slf)
+    }
+    
+    /// Keep these columns, in this order
+    fn select(mut slf: PyRefMut<'_, Self>, columns: Vec<String>) -> PyRefMut<'_, Self> {
+        slf.inner.push(PipelineStep::Select(columns));
+        slf
+    }
+    
+    #[pyo3(name = "drop")]
+    fn drop_columns(mut slf: PyRefMut<'_, Self>, columns: Vec<String>) -> PyRefMut<'_, Self> {
+        slf.inner.push(PipelineStep::Drop(columns));
+        slf
+    }
+    
+    /// Add computed columns (see `add_columns`)
+    #[pyo3(signature = (expressions, null_arithmetic="propagate"))]
+    fn add_columns<'p>(mut slf: PyRefMut<'p, Self>, expressions: &PyDict, null_arithmetic: &str) -> PyResult<PyRefMut<'p, Self>> {
+        let nulls = NullArithmetic::from_name(null_arithmetic)?;
+        let expressions = expressions
+            .iter()
+            .map(|(name, source)| Ok(ColumnExpression::parse(name.extract()?, source.extract()?)?))
+            .collect::<PyResult<Vec<_>>>()?;
+        slf.inner.push(PipelineStep::AddColumns { expressions, nulls });
+        Ok(slf)
+    }
+    
+    /// Set columns on the rows matching `predicate` (see `update_where`);
+    /// types are checked at `prepare`
+    fn update_where<'p>(mut slf: PyRefMut<'p, Self>, predicate: &str, assignments: &PyDict) -> PyResult<PyRefMut<'p, Self>> {
+        let predicate = Predicate::parse(predicate)?;
+        let assignments = update_assignments(assignments)?;
+        slf.inner.push(PipelineStep::UpdateWhere { predicate, assignments });
+        Ok(slf)
+    }
+    
+    /// Drop rows with a null in any of `subset` (default: any column)
+    #[pyo3(signature = (subset=None))]
+    fn drop_nulls(mut slf: PyRefMut<'_, Self>, subset: Option<Vec<String>>) -> PyRefMut<'_, Self> {
+        slf.inner.push(PipelineStep::DropNulls(subset));
+        slf
+    }
+    
+    #[pyo3(signature = (subset=None, keep="first"))]
+    fn drop_duplicates<'p>(mut slf: PyRefMut<'p, Self>, subset: Option<Vec<String>>, keep: &str) -> PyResult<PyRefMut<'p, Self>> {
+        let keep = transformations::keep_strategy_from_name(keep)?;
+        slf.inner.push(PipelineStep::DropDuplicates { subset, keep });
+        Ok(slf)
+    }
+    
+    #[pyo3(signature = (by, descending=false))]
+    fn sort<'p>(mut slf: PyRefMut<'p, Self>, by: &PyAny, descending: bool) -> PyResult<PyRefMut<'p, Self>> {
+        let by = extract_one_or_many(by)?;
+        slf.inner.push(PipelineStep::Sort { by, descending });
+        Ok(slf)
+    }
+    
+    /// Move columns to the front (see `reorder_columns`)
+    #[pyo3(signature = (order, missing="error"))]
+    fn reorder_columns<'p>(mut slf: PyRefMut<'p, Self>, order: Vec<String>, missing: &str) -> PyResult<PyRefMut<'p, Self>> {
+        let unlisted = operations::UnlistedColumns::from_name(missing)?;
+        slf.inner.push(PipelineStep::Reorder { order, unlisted });
+        Ok(slf)
+    }
+    
+    #[pyo3(signature = (prefix, columns=None))]
+    fn add_prefix<'p>(mut slf: PyRefMut<'p, Self>, prefix: String, columns: Option<&PyAny>) -> PyResult<PyRefMut<'p, Self>> {
+        let columns = columns.map(extract_one_or_many).transpose()?;
+        slf.inner.push(PipelineStep::AddPrefix { prefix, columns });
+        Ok(slf)
+    }
+    
+    #[pyo3(signature = (suffix, columns=None))]
+    fn add_suffix<'p>(mut slf: PyRefMut<'p, Self>, suffix: String, columns: Option<&PyAny>) -> PyResult<PyRefMut<'p, Self>> {
+        let columns = columns.map(extract_one_or_many).transpose()?;
+        slf.inner.push(PipelineStep::AddSuffix { suffix, columns });
+        Ok(slf)
+    }
+    
+    /// Convert column names to one style (see `normalize_column_names`);
+    /// collisions fail at `prepare`
+    #[pyo3(signature = (style="snake", on_collision="error"))]
+    fn normalize_column_names<'p>(mut slf: PyRefMut<'p, Self>, style: &str, on_collision: &str) -> PyResult<PyRefMut<'p, Self>> {
+        let style = operations::NameStyle::from_name(style)?;
+        let on_collision = operations::NameCollision::from_name(on_collision)?;
+        slf.inner.push(PipelineStep::NormalizeNames { style, on_collision });
+        Ok(slf)
+    }
+    
+    /// Validate and compile the steps against the columns every input file must have
+    /// 
+    /// # Arguments
+    /// * `schema` - Dict of column -> dtype ("i64", "f64", "str", "bool",
+    ///   "date", "datetime[μs]", ...), as returned by `infer_csv_schema`
+    /// * `delimiter` / `has_header` - CSV reader settings
+    /// * `sample_rows` - Leading rows checked before a file is parsed (default: 1000)
+    #[pyo3(signature = (schema, delimiter=",", has_header=true, sample_rows=1000))]
+    fn prepare(&self, schema: &PyDict, delimiter: &str, has_header: bool, sample_rows: usize) -> PyResult<PyPreparedPipeline> {
+        if delimiter.len() != 1 {
+            return Err(PyValueError::new_err("Delimiter must be a single character"));
+        }
+        let schema = declared_schema(schema)?;
+        let csv = CsvParserConfig {
+            delimiter: delimiter.as_bytes()[0],
+            has_header,
+            ..Default::default()
+        };
+        let prepared = self.inner.prepare(&schema, csv)?.with_sample_rows(sample_rows);
+        Ok(PyPreparedPipeline { inner: Arc::new(prepared) })
+    }
+    
+    fn __len__(&self) -> usize {
+        self.inner.steps().len()
+    }
+    
+    fn __repr__(&self) -> String {
+        let names: Vec<&str> = self.inner.steps().iter().map(|step| step.name()).collect();
+        format!("Pipeline({})", names.join(" -> "))
+    }
+}
+
+/// Pipeline compiled by `Pipeline.prepare()`; `run` may be called from many threads
+#[pyclass(name = "PreparedPipeline")]
+pub struct PyPreparedPipeline {
+    inner: Arc<PreparedPipeline>,
+}
+
+/// Helper function to convert a pipeline run report to a dictionary
+fn run_report_to_pydict<'py>(py: Python<'py>, report: &PipelineRunReport) -> PyResult<&'py PyDict> {
+    let summary = PyDict::new(py);
+    summary.set_item("file", &report.file)?;
+    summary.set_item("rows_in", report.rows_in)?;
+    summary.set_item("rows_out", report.rows_out)?;
+    let violations = PyDict::new(py);
+    for (column, count) in &report.violations {
+        violations.set_item(column, count)?;
+    }
+    summary.set_item("violations", violations)?;
+    summary.set_item("warnings", warnings_to_py(py, &report.warnings, true)?)?;
+    summary.set_item("elapsed_ms", report.elapsed.as_secs_f64() * 1000.0)?;
+    Ok(summary)
+}
+
+#[pymethods]
+impl PyPreparedPipeline {
+    /// Parse a CSV file and apply the pipeline
+    /// 
+    /// The header and leading rows are checked first, so a file that lacks a
+    /// declared column, or whose column holds no convertible values, raises
+    /// before it is parsed.
+    /// 
+    /// # Returns
+    /// * Result dictionary plus `report`: `file`, `rows_in`, `rows_out`,
+    ///   `violations` ({column: values that didn't convert and became null}),
+    ///   `warnings` (the same as aggregated warnings: `category`, `column`,
+    ///   `count`, `message` and sample `examples`, also logged) and `elapsed_ms`
+    fn run(&self, py: Python, file_path: &str) -> PyResult<PyObject> {
+        let prepared = Arc::clone(&self.inner);
+        let (df, report) = py.allow_threads(move || prepared.run(file_path))?;
+        
+        let result = dataframe_to_pydict(py, &df)?;
+        log_warnings(py, &report.warnings)?;
+        result.as_ref(py).downcast::<PyDict>()?.set_item("report", run_report_to_pydict(py, &report)?)?;
+        Ok(result)
+    }
+    
+    /// Parse a CSV file, apply the pipeline and write the result to Parquet
+    /// 
+    /// # Arguments
+    /// * `file_path` - Input CSV file
+    /// * `output_path` - Parquet file to write
+    /// * `schema` / `allow_missing` / `drop_extra` - Canonical schema as in
+    ///   `write_parquet`; the output schema is checked before the input is read
+    /// 
+    /// # Returns
+    /// * Dictionary as returned by `write_parquet`, plus `report` as in `run`
+    #[pyo3(signature = (file_path, output_path, schema=None, allow_missing=false, drop_extra=false))]
+    fn run_to_parquet(
+        &self,
+        py: Python,
+        file_path: &str,
+        output_path: &str,
+        schema: Option<&PyDict>,
+        allow_missing: bool,
+        drop_extra: bool,
+    ) -> PyResult<PyObject> {
+        let prepared = Arc::clone(&self.inner);
+        let enforcement = schema_enforcement(schema, allow_missing, drop_extra)?;
+        let (report, written) = py.allow_threads(move || prepared.run_to_parquet(file_path, output_path, enforcement.as_ref()))?;
+        
+        let result = parquet_report_to_pydict(py, &written)?;
+        log_warnings(py, &report.warnings)?;
+        result.as_ref(py).downcast::<PyDict>()?.set_item("report", run_report_to_pydict(py, &report)?)?;
+        Ok(result)
+    }
+    
+    /// Parse a CSV file, apply the pipeline and write the result as CSV part files
+    /// 
+    /// # Arguments
+    /// * `file_path` - Input CSV file
+    /// * `directory` / `max_rows_per_file` / `filename_template` /
+    ///   `combine_to` / `on_progress` - As in `write_csv_partitioned`
+    /// 
+    /// # Returns
+    /// * Dictionary as returned by `write_csv_partitioned`, plus `report` as in `run`
+    #[pyo3(signature = (file_path, directory, max_rows_per_file, filename_template=csv_parser::DEFAULT_PART_TEMPLATE, combine_to=None, on_progress=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn run_to_csv_partitioned(
+        &self,
+        py: Python,
+        file_path: &str,
+        directory: &str,
+        max_rows_per_file: usize,
+        filename_template: &str,
+        combine_to: Option<String>,
+        on_progress: Option<PyObject>,
+    ) -> PyResult<PyObject> {
+        let prepared = Arc::clone(&self.inner);
+        let partition = csv_partition_options(max_rows_per_file, filename_template, combine_to);
+        let progress = progress_reporter(on_progress);
+        let (report, written) = py.allow_threads(move || {
+            prepared.run_to_csv_partitioned(file_path, directory, &partition, &CsvWriteOptions::default(), &progress)
+        })?;
+        
+        let result = partitioned_csv_report_to_pydict(py, &written)?;
+        log_warnings(py, &report.warnings)?;
+        result.as_ref(py).downcast::<PyDict>()?.set_item("report", run_report_to_pydict(py, &report)?)?;
+        Ok(result)
+    }
+    
+    /// Column -> dtype name of every run's result
+    #[getter]
+    fn output_schema(&self) -> Vec<(String, String)> {
+        self.inner.output_schema().iter().map(|(name, dtype)| (name.clone(), dtype.to_string())).collect()
+    }
+}
+
+// ============================================================================
+// Statistics Python Bindings
+// ============================================================================
+
+use crate::stats::descriptive::RunningStats;
+use crate::stats::comparison::ColumnComparison;
+use crate::stats::tests::TestResult;
+use crate::stats::correlation::{CorrelationMethod, RobustCovarianceConfig};
+use crate::stats::downsample::{DownsampleMethod, REPRESENTED_COLUMN};
+use pyo3::types::{PyBytes, PyList};
+
+/// Running statistics over a stream of batches without storing history
+/// 
+/// Tracks count, mean, variance, min, max and approximate quantiles for the
+/// configured numeric columns. Shards can be combined with `merge`, and the
+/// state can be checkpointed with `to_bytes` / `RunningStats.from_bytes`.
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// stats = insightora_core.RunningStats(["amount"], quantiles=[0.5, 0.99])
+/// for batch in batches:
+///     stats.update(batch)
+/// print(stats.snapshot()["amount"]["mean"])
+/// ```
+#[pyclass(name = "RunningStats")]
+pub struct PyRunningStats {
+    inner: RunningStats,
+}
+
+#[pymethods]
+impl PyRunningStats {
+    #[new]
+    #[pyo3(signature = (columns, quantiles=None, relative_accuracy=0.01))]
+    fn new(columns: Vec<String>, quantiles: Option<Vec<f64>>, relative_accuracy: f64) -> PyResult<Self> {
+        let quantiles = quantiles.unwrap_or_else(|| vec![0.25, 0.5, 0.75]);
+        Ok(Self {
+            inner: RunningStats::new(columns, quantiles, relative_accuracy)?,
+        })
+    }
+    
+    /// Accumulate a batch in the standard result dictionary format
+    fn update(&mut self, py: Python, batch: &PyAny) -> PyResult<()> {
+        let df = pydict_to_dataframe(batch)?;
+        let inner = &mut self.inner;
+        py.allow_threads(|| inner.update(&df))?;
+        Ok(())
+    }
+    
+    /// Merge the state of another RunningStats over the same columns
+    fn merge(&mut self, other: PyRef<PyRunningStats>) -> PyResult<()> {
+        self.inner.merge(&other.inner)?;
+        Ok(())
+    }
+    
+    /// Current statistics as a dict keyed by column name
+    fn snapshot(&self, py: Python) -> PyResult<PyObject> {
+        let result = PyDict::new(py);
+        for snap in self.inner.snapshot() {
+            let column = PyDict::new(py);
+            column.set_item("count", snap.count)?;
+            column.set_item("null_count", snap.null_count)?;
+            column.set_item("mean", snap.mean)?;
+            column.set_item("variance", snap.variance)?;
+            column.set_item("std", snap.std)?;
+            column.set_item("min", snap.min)?;
+            column.set_item("max", snap.max)?;
+            let quantiles = PyDict::new(py);
+            for (q, value) in snap.quantiles {
+                quantiles.set_item(q, value)?;
+            }
+            column.set_item("quantiles", quantiles)?;
+            result.set_item(snap.column, column)?;
+        }
+        Ok(result.into())
+    }
+    
+    /// Discard accumulated state, keeping columns and quantiles
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+    
+    /// Serialize the state to bytes for checkpointing
+    fn to_bytes<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+        PyBytes::new(py, &self.inner.to_bytes())
+    }
+    
+    /// Restore a RunningStats from bytes produced by `to_bytes`
+    #[staticmethod]
+    fn from_bytes(data: &[u8]) -> PyResult<Self> {
+        Ok(Self {
+            inner: RunningStats::from_bytes(data)?,
+        })
+    }
+    
+    fn __repr__(&self) -> String {
+        format!("RunningStats(columns={:?})", self.inner.columns())
+    }
+}
+
+/// Summary statistics for the numeric columns of the data
+/// 
+/// Identifier-like columns (order numbers, zip codes) are detected and listed
+/// under `identifiers` instead of getting meaningless means and sums.
+/// 
+/// # Arguments
+/// * `data` - Dictionary in the standard result format
+/// * `id_detection` - Flag identifier-like columns and exclude them (default: True)
+/// * `identifiers` - Optional dict of column name to bool overriding the detection
+/// 
+/// # Returns
+/// * Dictionary with `numeric` (statistics keyed by column), `categorical`
+///   (count, min, max and categories of ordered categoricals), `identifiers`
+///   (excluded column names) and `id_detection` (per-column decisions with
+///   `score` and `reasons`; empty when detection is off)
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// summary = insightora_core.describe(data, identifiers={"store_number": False})
+/// print(summary["numeric"]["amount"]["mean"])
+/// for decision in summary["id_detection"]:
+///     print(decision["column"], decision["is_identifier"], decision["reasons"])
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, id_detection=true, identifiers=None))]
+pub fn describe(
+    py: Python,
+    data: &PyAny,
+    id_detection: bool,
+    identifiers: Option<HashMap<String, bool>>,
+) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let config = id_detection.then(|| id_detection_config(identifiers));
+    let description = py.allow_threads(|| crate::stats::descriptive::describe(&df, config.as_ref()))
+        .map_err(|e| operation_error("Failed to describe data", e))?;
+    
+    let numeric = PyDict::new(py);
+    for snap in description.numeric {
+        let column = PyDict::new(py);
+        column.set_item("count", snap.count)?;
+        column.set_item("null_count", snap.null_count)?;
+        column.set_item("mean", snap.mean)?;
+        column.set_item("std", snap.std)?;
+        column.set_item("min", snap.min)?;
+        for (q, value) in snap.quantiles {
+            column.set_item(format!("{}%", (q * 100.0).round()), value)?;
+        }
+        column.set_item("max", snap.max)?;
+        numeric.set_item(snap.column, column)?;
+    }
+    
+    let categorical = PyDict::new(py);
+    for summary in description.categorical {
+        let column = PyDict::new(py);
+        column.set_item("count", summary.count)?;
+        column.set_item("null_count", summary.null_count)?;
+        column.set_item("min", summary.min)?;
+        column.set_item("max", summary.max)?;
+        column.set_item("categories", summary.categories)?;
+        categorical.set_item(summary.column, column)?;
+    }
+    
+    let result = PyDict::new(py);
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    result.set_item("numeric", numeric)?;
+    result.set_item("categorical", categorical)?;
+    result.set_item("identifiers", description.identifiers)?;
+    result.set_item("id_detection", id_decisions_to_py(py, &description.id_decisions)?)?;
+    Ok(result.into())
+}
+
+/// Compare two subsets of the data side by side
+/// 
+/// # Arguments
+/// * `data` - Dictionary in the standard result format
+/// * `split_column` - Column whose values define the groups
+/// * `group_a` / `group_b` - Values selecting each side
+/// * `columns` - Optional list of columns to compare (default: all others)
+/// * `on_progress` - Optional callable `(percent, stage, detail)` for progress
+/// 
+/// # Returns
+/// * Dictionary with group sizes and a `columns` list holding one record per
+///   compared column
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// result = insightora_core.compare_groups(data, "segment", "enterprise", "smb")
+/// for col in result["columns"]:
+///     print(col["column"], col["kind"], col["p_value"])
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, split_column, group_a, group_b, columns=None, on_progress=None))]
+pub fn compare_groups(
+    py: Python,
+    data: &PyAny,
+    split_column: &str,
+    group_a: &PyAny,
+    group_b: &PyAny,
+    columns: Option<Vec<String>>,
+    on_progress: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let progress = progress_reporter(on_progress);
+    let cells = dict_cells(data);
+    // The output is one small record per column
+    let stages = progress.stages(&[("load", cells), ("compare", cells * 0.5)]);
+    let df = pydict_to_dataframe_reporting(data, &stages[0])?;
+    let group_a = group_value_to_string(group_a)?;
+    let group_b = group_value_to_string(group_b)?;
+    
+    stages[1].report(0.0, split_column);
+    let comparison = py.allow_threads(|| {
+        crate::stats::comparison::compare_groups(&df, split_column, &group_a, &group_b, columns.as_deref())
+    })?;
+    stages[1].finish(split_column);
+    
+    let result = PyDict::new(py);
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    result.set_item("split_column", &comparison.split_column)?;
+    result.set_item("group_a", &comparison.group_a)?;
+    result.set_item("group_b", &comparison.group_b)?;
+    result.set_item("rows_a", comparison.rows_a)?;
+    result.set_item("rows_b", comparison.rows_b)?;
+    
+    let records = PyList::empty(py);
+    for column in &comparison.columns {
+        let record = PyDict::new(py);
+        record.set_item("column", column.column())?;
+        match column {
+            ColumnComparison::Numeric(c) => {
+                record.set_item("kind", "numeric")?;
+                for (prefix, side) in [("a", &c.group_a), ("b", &c.group_b)] {
+                    record.set_item(format!("count_{}", prefix), side.count)?;
+                    record.set_item(format!("null_count_{}", prefix), side.null_count)?;
+                    record.set_item(format!("mean_{}", prefix), side.mean)?;
+                    record.set_item(format!("std_{}", prefix), side.std)?;
+                    record.set_item(format!("min_{}", prefix), side.min)?;
+                    record.set_item(format!("median_{}", prefix), side.median)?;
+                    record.set_item(format!("max_{}", prefix), side.max)?;
+                }
+                record.set_item("mean_diff", c.mean_diff)?;
+                record.set_item("cohens_d", c.cohens_d)?;
+                record.set_item("statistic", c.mann_whitney_u)?;
+                record.set_item("test", "mann_whitney_u")?;
+                record.set_item("p_value", c.mann_whitney_p)?;
+            }
+            ColumnComparison::Categorical(c) => {
+                record.set_item("kind", "categorical")?;
+                record.set_item("null_count_a", c.null_count_a)?;
+                record.set_item("null_count_b", c.null_count_b)?;
+                let categories = PyList::empty(py);
+                for cat in &c.categories {
+                    let entry = PyDict::new(py);
+                    entry.set_item("value", &cat.value)?;
+                    entry.set_item("count_a", cat.count_a)?;
+                    entry.set_item("count_b", cat.count_b)?;
+                    entry.set_item("freq_a", cat.freq_a)?;
+                    entry.set_item("freq_b", cat.freq_b)?;
+                    entry.set_item("delta", cat.delta)?;
+                    categories.append(entry)?;
+                }
+                record.set_item("categories", categories)?;
+                record.set_item("statistic", c.chi_square)?;
+                record.set_item("test", "chi_square")?;
+                record.set_item("degrees_of_freedom", c.degrees_of_freedom)?;
+                record.set_item("p_value", c.p_value)?;
+            }
+        }
+        records.append(record)?;
+    }
+    result.set_item("columns", records)?;
+    
+    Ok(result.into())
+}
+
+/// Two-sample t-test of a numeric column between the two groups of another
+/// 
+/// Welch's test by default, Student's with `equal_var=True`; matches
+/// `scipy.stats.ttest_ind`. Null and NaN values are left out and counted.
+/// 
+/// # Arguments
+/// * `data` - Dictionary in the standard result format
+/// * `value_column` - Numeric column tested
+/// * `group_column` - Column with exactly two distinct non-null values
+/// * `equal_var` - Assume equal variances (default: False)
+/// * `on_progress` - Optional callable `(percent, stage, detail)` for progress
+/// 
+/// # Returns
+/// * Dictionary with `statistic`, `degrees_of_freedom`, `p_value` and one
+///   record per group, in sorted order
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// result = insightora_core.t_test(data, "conversion", "variant")
+/// print(result["statistic"], result["p_value"])
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, value_column, group_column, equal_var=false, on_progress=None))]
+pub fn t_test(
+    py: Python,
+    data: &PyAny,
+    value_column: &str,
+    group_column: &str,
+    equal_var: bool,
+    on_progress: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let df = grouped_test_data(data, on_progress)?;
+    let result = py.allow_threads(|| crate::stats::tests::t_test(&df, value_column, group_column, equal_var))?;
+    test_result_to_py(py, &result)
+}
+
+/// One-way ANOVA of a numeric column across the groups of another
+/// 
+/// Matches `scipy.stats.f_oneway`. Null and NaN values are left out and
+/// counted.
+/// 
+/// # Returns
+/// * Dictionary with the F `statistic`, `degrees_of_freedom` as
+///   [between, within], `p_value` and one record per group
+/// 
+/// # Example
+/// ```python
+/// result = insightora_core.anova(data, "amount", "region")
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, value_column, group_column, on_progress=None))]
+pub fn anova(
+    py: Python,
+    data: &PyAny,
+    value_column: &str,
+    group_column: &str,
+    on_progress: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let df = grouped_test_data(data, on_progress)?;
+    let result = py.allow_threads(|| crate::stats::tests::anova(&df, value_column, group_column))?;
+    test_result_to_py(py, &result)
+}
+
+/// Two-proportion z-test with the pooled proportion
+/// 
+/// # Returns
+/// * Dictionary with the z `statistic`, `p_value` and groups "a" and "b"
+///   with their success rate as `mean`
+/// 
+/// # Example
+/// ```python
+/// result = insightora_core.proportion_test(45, 100, 30, 90)
+/// ```
+#[pyfunction]
+pub fn proportion_test(py: Python, successes_a: u64, n_a: u64, successes_b: u64, n_b: u64) -> PyResult<PyObject> {
+    let result = crate::stats::tests::proportion_test(successes_a, n_a, successes_b, n_b)?;
+    test_result_to_py(py, &result)
+}
+
+/// Two-sample Kolmogorov-Smirnov test of a column between the two groups of another
+/// 
+/// The p-value is exact, as in `scipy.stats.ks_2samp`, for groups of up to
+/// 10,000 values and asymptotic beyond.
+/// 
+/// # Example
+/// ```python
+/// result = insightora_core.ks_test(data, "latency", "release")
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, column, group_column, on_progress=None))]
+pub fn ks_test(
+    py: Python,
+    data: &PyAny,
+    column: &str,
+    group_column: &str,
+    on_progress: Option<PyObject>,
+) -> PyResult<PyObject> {
+    let df = grouped_test_data(data, on_progress)?;
+    let result = py.allow_threads(|| crate::stats::tests::ks_test(&df, column, group_column))?;
+    test_result_to_py(py, &result)
+}
+
+/// Helper function to load the input of a hypothesis test; the test itself
+/// is quick next to the conversion
+fn grouped_test_data(data: &PyAny, on_progress: Option<PyObject>) -> PyResult<polars::prelude::DataFrame> {
+    let progress = progress_reporter(on_progress);
+    let stages = progress.stages(&[("load", dict_cells(data))]);
+    pydict_to_dataframe_reporting(data, &stages[0])
+}
+
+/// Helper function to convert a hypothesis test result to a Python dict
+fn test_result_to_py(py: Python, test: &TestResult) -> PyResult<PyObject> {
+    let groups = PyList::empty(py);
+    for group in &test.groups {
+        let record = PyDict::new(py);
+        record.set_item("group", &group.group)?;
+        record.set_item("count", group.count)?;
+        record.set_item("null_count", group.null_count)?;
+        record.set_item("mean", group.mean)?;
+        groups.append(record)?;
+    }
+    
+    let result = PyDict::new(py);
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    result.set_item("test", test.test)?;
+    result.set_item("statistic", test.statistic)?;
+    result.set_item("degrees_of_freedom", &test.degrees_of_freedom)?;
+    result.set_item("p_value", test.p_value)?;
+    result.set_item("groups", groups)?;
+    result.set_item("null_groups", test.null_groups)?;
+    Ok(result.into())
+}
+
+/// Helper function to match a Python group value against the string form of
+/// a column (booleans cast to "true"/"false" in Polars)
+fn group_value_to_string(value: &PyAny) -> PyResult<String> {
+    if let Ok(b) = value.downcast::<pyo3::types::PyBool>() {
+        return Ok(b.is_true().to_string());
+    }
+    Ok(value.str()?.to_string())
+}
+
+/// Suggest an outlier rule and parameters for each numeric column
+/// 
+/// Each column's distribution (skewness, kurtosis, modality via an
+/// approximate dip test, tail weight) picks a default among IQR, z-score,
+/// modified z-score and log-scale IQR rules. The result is deterministic.
+/// 
+/// # Arguments
+/// * `data` - Dictionary in the standard result format
+/// * `columns` - Optional list of columns to inspect (default: all numeric)
+/// 
+/// # Returns
+/// * Dictionary with a `columns` list holding one record per column: the
+///   recommended `method`, `parameter`, `lower`/`upper` bounds and `flagged`
+///   count (None when nothing is recommended), `confidence`, `note`, the shape
+///   diagnostics, and `candidates` with bounds and flagged count for every rule
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// result = insightora_core.suggest_outlier_params(data, ["amount"])
+/// for col in result["columns"]:
+///     print(col["column"], col["method"], col["parameter"], col["note"])
+///     for candidate in col["candidates"]:
+///         print("  ", candidate["method"], candidate["parameter"], candidate["flagged"])
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, columns=None))]
+pub fn suggest_outlier_params(py: Python, data: &PyAny, columns: Option<Vec<String>>) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let suggestions = py.allow_threads(|| crate::stats::outliers::suggest_thresholds(&df, columns.as_deref()))?;
+    
+    let records = PyList::empty(py);
+    for suggestion in &suggestions {
+        let record = PyDict::new(py);
+        record.set_item("column", &suggestion.column)?;
+        record.set_item("count", suggestion.count)?;
+        let outcome = suggestion.recommended_outcome();
+        record.set_item("method", suggestion.recommended.map(|m| m.name()))?;
+        record.set_item("parameter", suggestion.recommended.map(|m| m.parameter()))?;
+        record.set_item("lower", outcome.and_then(|o| o.bounds).map(|b| b.0))?;
+        record.set_item("upper", outcome.and_then(|o| o.bounds).map(|b| b.1))?;
+        record.set_item("flagged", outcome.map(|o| o.flagged))?;
+        record.set_item("confidence", suggestion.confidence.name())?;
+        record.set_item("note", &suggestion.note)?;
+        let shape = suggestion.shape.as_ref();
+        record.set_item("skewness", shape.map(|s| s.skewness))?;
+        record.set_item("kurtosis", shape.map(|s| s.kurtosis))?;
+        record.set_item("dip", shape.map(|s| s.dip))?;
+        record.set_item("multimodal", shape.map(|s| s.multimodal))?;
+        record.set_item("tail_weight", shape.map(|s| s.tail_weight))?;
+        
+        let candidates = PyList::empty(py);
+        for candidate in &suggestion.candidates {
+            let entry = PyDict::new(py);
+            entry.set_item("method", candidate.method.name())?;
+            entry.set_item("parameter", candidate.method.parameter())?;
+            entry.set_item("lower", candidate.bounds.map(|b| b.0))?;
+            entry.set_item("upper", candidate.bounds.map(|b| b.1))?;
+            entry.set_item("flagged", candidate.flagged)?;
+            candidates.append(entry)?;
+        }
+        record.set_item("candidates", candidates)?;
+        records.append(record)?;
+    }
+    
+    let result = PyDict::new(py);
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    result.set_item("columns", records)?;
+    Ok(result.into())
+}
+
+/// Correlation matrix of numeric columns
+/// 
+/// Rows with a missing value in any selected column are dropped first.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary
+/// * `method` - "pearson" (default), "spearman" or "pb" (percentage bend,
+///   which limits how far any single value can pull the estimate)
+/// * `columns` - Columns to correlate (default: every numeric column)
+/// * `beta` - Bend constant for "pb", in (0, 0.5] (default: 0.2)
+/// 
+/// # Returns
+/// * Dictionary with `method`, `columns`, `matrix` (rows of coefficients,
+///   None for columns without spread), `rows_used` and `influential_rows`
+///   (positions of rows the "pb" estimator bent)
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// result = insightora_core.correlation(data, method="pb")
+/// print(result["matrix"], result["influential_rows"])
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, method="pearson", columns=None, beta=0.2))]
+pub fn correlation(
+    py: Python,
+    data: &PyAny,
+    method: &str,
+    columns: Option<Vec<String>>,
+    beta: f64,
+) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let method = match CorrelationMethod::from_name(method)? {
+        CorrelationMethod::PercentageBend { .. } => CorrelationMethod::PercentageBend { beta },
+        other => other,
+    };
+    let matrix = py.allow_threads(|| crate::stats::correlation::correlation(&df, columns.as_deref(), method))?;
+    
+    let result = PyDict::new(py);
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    result.set_item("method", matrix.method.name())?;
+    result.set_item("columns", &matrix.columns)?;
+    result.set_item("matrix", &matrix.values)?;
+    result.set_item("rows_used", matrix.rows_used)?;
+    result.set_item("influential_rows", &matrix.influential_rows)?;
+    Ok(result.into())
+}
+
+/// Covariance matrix of numeric columns, optionally outlier-robust
+/// 
+/// # Arguments
+/// * `data` - Result dictionary
+/// * `columns` - Columns to include (default: every numeric column)
+/// * `robust` - Use the minimum covariance determinant estimator (default: False)
+/// * `support_fraction` - Share of rows the robust fit is based on, in
+///   [0.5, 1] (default: 0.75)
+/// 
+/// # Returns
+/// * Dictionary with `columns`, `matrix`, `location` (means, robust with
+///   `robust=True`), `rows_used`, `robust`, `support_fraction` and
+///   `excluded_rows` (positions of rows flagged as outliers by the robust fit)
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// cov = insightora_core.covariance_matrix(data, robust=True, support_fraction=0.75)
+/// print(cov["matrix"], len(cov["excluded_rows"]))
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, columns=None, robust=false, support_fraction=0.75))]
+pub fn covariance_matrix(
+    py: Python,
+    data: &PyAny,
+    columns: Option<Vec<String>>,
+    robust: bool,
+    support_fraction: f64,
+) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let config = robust.then(|| RobustCovarianceConfig { support_fraction, ..Default::default() });
+    let matrix = py.allow_threads(|| {
+        crate::stats::correlation::covariance_matrix(&df, columns.as_deref(), config.as_ref())
+    })?;
+    
+    let result = PyDict::new(py);
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    result.set_item("columns", &matrix.columns)?;
+    result.set_item("matrix", &matrix.values)?;
+    result.set_item("location", &matrix.location)?;
+    result.set_item("rows_used", matrix.rows_used)?;
+    result.set_item("robust", matrix.robust.is_some())?;
+    result.set_item("support_fraction", matrix.robust.map(|c| c.support_fraction))?;
+    result.set_item("excluded_rows", &matrix.excluded_rows)?;
+    Ok(result.into())
+}
+
+/// Downsample an (x, y) series for plotting
+/// 
+/// Rows are sorted by `x`; rows missing either value are skipped. Series
+/// longer than `target_points` come back with exactly that many points,
+/// always including the first and last.
+/// 
+/// # Arguments
+/// * `data` - Result dictionary
+/// * `x` - Numeric, date or datetime column
+/// * `y` - Numeric column
+/// * `target_points` - Points to keep per series (at least 2)
+/// * `method` - "lttb" (Largest-Triangle-Three-Buckets, default), "minmax"
+///   (lowest and highest point of each bucket, keeps spikes) or "mean"
+///   (bucket averages)
+/// * `group_by` - Column whose values are downsampled separately
+/// 
+/// # Returns
+/// * Result dictionary with the group column (if any), `x`, `y` and
+///   `represented` (source points behind each output point), plus `method`
+///   and `source_points`
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// points = insightora_core.downsample(data, "timestamp", "latency_ms", 2000, method="minmax", group_by="host")
+/// ```
+#[pyfunction]
+#[pyo3(signature = (data, x, y, target_points, method="lttb", group_by=None))]
+pub fn downsample(
+    py: Python,
+    data: &PyAny,
+    x: &str,
+    y: &str,
+    target_points: usize,
+    method: &str,
+    group_by: Option<&str>,
+) -> PyResult<PyObject> {
+    let df = pydict_to_dataframe(data)?;
+    let method = DownsampleMethod::from_name(method)?;
+    let reduced = py.allow_threads(|| {
+        crate::stats::downsample::downsample_for_plot(&df, x, y, target_points, method, group_by)
+    })?;
+    let represented = reduced.column(REPRESENTED_COLUMN).map_err(InsightoraError::from)?;
+    let source_points: u64 = represented.u64().map_err(InsightoraError::from)?.into_iter().flatten().sum();
+    
+    let result = dataframe_to_pydict(py, &reduced)?;
+    let dict: &PyDict = result.downcast(py)?;
+    dict.set_item("method", method.name())?;
+    dict.set_item("source_points", source_points)?;
+    Ok(result)
+}
+
+// ============================================================================
+// Frame Comparison Python Bindings
+// ============================================================================
+
+use crate::utils::frame_compare::FrameCompareOptions;
+
+/// Helper function to build comparison options from keyword arguments
+#[allow(clippy::too_many_arguments)]
+fn frame_compare_options(
+    check_dtypes: bool,
+    check_order: bool,
+    float_tolerance: f64,
+    check_column_order: bool,
+    nan_equals_null: bool,
+    categorical_as_string: bool,
+    max_differences: usize,
+) -> PyResult<FrameCompareOptions> {
+    if float_tolerance.is_nan() || float_tolerance < 0.0 {
+        return Err(PyValueError::new_err("float_tolerance must be a non-negative number"));
+    }
+    Ok(FrameCompareOptions {
+        check_dtypes,
+        check_order,
+        check_column_order,
+        float_tolerance,
+        nan_equals_null,
+        categorical_as_string,
+        max_cell_differences: max_differences,
+    })
+}
+
+/// Check whether two results hold the same data
+/// 
+/// # Arguments
+/// * `left` / `right` - Dictionaries in the standard result format
+/// * `check_dtypes` - Columns must have the same dtype (default: True)
+/// * `check_order` - Rows must be in the same order (default: True)
+/// * `float_tolerance` - Numbers match within `tolerance * max(1, |a|, |b|)`
+/// * `check_column_order` - Shared columns must be in the same order (default: True)
+/// * `nan_equals_null` - Treat NaN and None as equal (default: False)
+/// * `categorical_as_string` - Compare categorical and string columns by label
+///   (default: False, which reports them as a dtype mismatch)
+/// 
+/// # Returns
+/// * True when nothing differs; use `frame_diff` to see what does
+#[pyfunction]
+#[pyo3(signature = (
+    left,
+    right,
+    check_dtypes=true,
+    check_order=true,
+    float_tolerance=1e-9,
+    check_column_order=true,
+    nan_equals_null=false,
+    categorical_as_string=false
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn frames_equal(
+    py: Python,
+    left: &PyAny,
+    right: &PyAny,
+    check_dtypes: bool,
+    check_order: bool,
+    float_tolerance: f64,
+    check_column_order: bool,
+    nan_equals_null: bool,
+    categorical_as_string: bool,
+) -> PyResult<bool> {
+    let options = frame_compare_options(
+        check_dtypes, check_order, float_tolerance, check_column_order, nan_equals_null, categorical_as_string, 0,
+    )?;
+    let left = pydict_to_dataframe(left)?;
+    let right = pydict_to_dataframe(right)?;
+    Ok(py.allow_threads(|| crate::utils::frame_compare::frame_diff(&left, &right, &options).is_equal()))
+}
+
+/// Structured difference between two results
+/// 
+/// Takes the same arguments as `frames_equal`, plus `max_differences`, the
+/// number of differing cells to list (all of them are counted).
+/// 
+/// # Returns
+/// * Dictionary with `equal`, `left_shape` / `right_shape` ([rows, columns]),
+///   `left_only_columns`, `right_only_columns`, `column_order_differs`,
+///   `dtype_mismatches` (column, left, right), `cells` (row, right_row,
+///   column, left, right; values as text, None for null), `differing_cells`
+///   and `report`, a readable summary for test failure messages
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// diff = insightora_core.frame_diff(actual, expected, check_order=False)
+/// assert diff["equal"], diff["report"]
+/// ```
+#[pyfunction]
+#[pyo3(signature = (
+    left,
+    right,
+    check_dtypes=true,
+    check_order=true,
+    float_tolerance=1e-9,
+    check_column_order=true,
+    nan_equals_null=false,
+    categorical_as_string=false,
+    max_differences=20
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn frame_diff(
+    py: Python,
+    left: &PyAny,
+    right: &PyAny,
+    check_dtypes: bool,
+    check_order: bool,
+    float_tolerance: f64,
+    check_column_order: bool,
+    nan_equals_null: bool,
+    categorical_as_string: bool,
+    max_differences: usize,
+) -> PyResult<PyObject> {
+    let options = frame_compare_options(
+        check_dtypes, check_order, float_tolerance, check_column_order, nan_equals_null, categorical_as_string,
+        max_differences,
+    )?;
+    let left = pydict_to_dataframe(left)?;
+    let right = pydict_to_dataframe(right)?;
+    let diff = py.allow_threads(|| crate::utils::frame_compare::frame_diff(&left, &right, &options));
+    
+    let result = PyDict::new(py);
+    result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+    result.set_item("equal", diff.is_equal())?;
+    result.set_item("left_shape", vec![diff.left_shape.0, diff.left_shape.1])?;
+    result.set_item("right_shape", vec![diff.right_shape.0, diff.right_shape.1])?;
+    result.set_item("left_only_columns", &diff.left_only_columns)?;
+    result.set_item("right_only_columns", &diff.right_only_columns)?;
+    result.set_item("column_order_differs", diff.column_order_differs)?;
+    
+    let mismatches = PyList::empty(py);
+    for mismatch in &diff.dtype_mismatches {
+        let entry = PyDict::new(py);
+        entry.set_item("column", &mismatch.column)?;
+        entry.set_item("left", &mismatch.left)?;
+        entry.set_item("right", &mismatch.right)?;
+        mismatches.append(entry)?;
+    }
+    result.set_item("dtype_mismatches", mismatches)?;
+    
+    let cells = PyList::empty(py);
+    for cell in &diff.cells {
+        let entry = PyDict::new(py);
+        entry.set_item("row", cell.row)?;
+        entry.set_item("right_row", cell.right_row)?;
+        entry.set_item("column", &cell.column)?;
+        entry.set_item("left", &cell.left)?;
+        entry.set_item("right", &cell.right)?;
+        cells.append(entry)?;
+    }
+    result.set_item("cells", cells)?;
+    result.set_item("differing_cells", diff.differing_cells)?;
+    result.set_item("report", diff.to_string())?;
+    Ok(result.into())
+}
+
+// ============================================================================
+// Query Python Bindings
+// ============================================================================
+
+use crate::query::cache::{QueryCacheConfig, DEFAULT_CACHE_ENTRIES};
+use crate::query::executor::{QuerySession, QueryReport};
+
+/// SQL session over registered CSV files and in-memory data
+/// 
+/// CSV files are scanned lazily, so only the columns and rows a query needs
+/// are read from disk. Results of `sql` are cached per session (at most
+/// `cache_entries` results and `cache_mb` megabytes, least recently used
+/// first out); re-registering a table or editing a registered file makes
+/// queries over it run again. `cache_entries=0` disables the cache.
+/// 
+/// The outermost ORDER BY compares text with `collation` ("binary",
+/// "natural" or "locale" with a `locale` such as "sv_SE", as in `sort_data`).
+/// Under a non-binary collation its keys must be output columns.
+/// 
+/// # Example
+/// ```python
+/// import insightora_core
+/// 
+/// session = insightora_core.QuerySession()
+/// session.register_csv("orders", "orders.csv")
+/// print(session.explain("SELECT customer FROM orders WHERE amount > 100"))
+/// result = session.sql("SELECT customer FROM orders WHERE amount > 100", report=True)
+/// print(result["report"]["scans"])
+/// ```
+#[pyclass(name = "QuerySession")]
+pub struct PyQuerySession {
+    /// None once closed
+    inner: Option<QuerySession>,
+}
+
+impl PyQuerySession {
+    fn session(&mut self) -> PyResult<&mut QuerySession> {
+        self.inner.as_mut().ok_or_else(|| closed_handle("QuerySession"))
+    }
+}
+
+#[pymethods]
+impl PyQuerySession {
+    #[new]
+    #[pyo3(signature = (cache_entries=DEFAULT_CACHE_ENTRIES, cache_mb=256, collation="binary", locale=None))]
+    fn new(cache_entries: usize, cache_mb: usize, collation: &str, locale: Option<&str>) -> PyResult<Self> {
+        let mut session = QuerySession::with_cache(QueryCacheConfig {
+            max_entries: cache_entries,
+            max_bytes: cache_mb.saturating_mul(1024 * 1024),
+        });
+        session.set_collation(Collation::from_options(collation, locale)?);
+        Ok(Self { inner: Some(session) })
+    }
+    
+    /// Register a CSV file as a lazily scanned table
+    #[pyo3(signature = (name, file_path, has_header=true, delimiter=","))]
+    fn register_csv(&mut self, name: &str, file_path: &str, has_header: bool, delimiter: &str) -> PyResult<()> {
+        if delimiter.len() != 1 {
+            return Err(PyValueError::new_err("Delimiter must be a single character"));
+        }
+        self.session()?.register_csv(name, file_path, has_header, delimiter.as_bytes()[0])?;
+        Ok(())
+    }
+    
+    /// Register a result dictionary as an in-memory table
+    fn register(&mut self, name: &str, data: &PyAny) -> PyResult<()> {
+        let df = pydict_to_dataframe(data)?;
+        self.session()?.register_frame(name, df)?;
+        Ok(())
+    }
+    
+    /// Names of the registered tables
+    fn tables(&mut self) -> PyResult<Vec<String>> {
+        Ok(self.session()?.tables().map(|t| t.name.clone()).collect())
+    }
+    
+    /// Return the logical plan of a query as text
+    #[pyo3(signature = (query, optimized=true))]
+    fn explain(&mut self, query: &str, optimized: bool) -> PyResult<String> {
+        Ok(self.session()?.explain(query, optimized)?)
+    }
+    
+    /// Execute a SQL query
+    /// 
+    /// With `report=True` the result also carries a 'report' dict listing, per
+    /// scan, the table, columns read and predicates pushed down, plus node
+    /// timings and `node_rows` (`node`, `depth`, `rows` for each plan node,
+    /// children first). No extra bookkeeping happens when `report=False`.
+    /// 
+    /// `on_progress` receives `(percent, stage, detail)` across the query and
+    /// export stages.
+    /// 
+    /// With `use_cache=False` the query always runs and its result is not
+    /// stored. Report queries always run, since timings are part of the result.
+    #[pyo3(signature = (query, report=false, on_progress=None, use_cache=true))]
+    fn sql(&mut self, py: Python, query: &str, report: bool, on_progress: Option<PyObject>, use_cache: bool) -> PyResult<PyObject> {
+        let inner = self.session()?;
+        let progress = progress_reporter(on_progress);
+        let stages = progress.stages(&[("query", 1.0), ("export", 1.0)]);
+        stages[0].report(0.0, query);
+        if !report {
+            let df = py.allow_threads(|| if use_cache { inner.sql(query) } else { inner.sql_uncached(query) })?;
+            stages[0].finish(query);
+            return dataframe_to_pydict_reporting(py, &df, &stages[1]);
+        }
+        
+        let (df, query_report) = py.allow_threads(|| inner.sql_with_report(query))?;
+        stages[0].finish(query);
+        let result = dataframe_to_pydict_reporting(py, &df, &stages[1])?;
+        result.as_ref(py)
+            .downcast::<PyDict>()?
+            .set_item("report", query_report_to_pydict(py, &query_report)?)?;
+        Ok(result)
+    }
+    
+    /// Result cache counters: `hits`, `misses`, `evictions`, `entries`,
+    /// `bytes`, and the `max_entries` / `max_bytes` limits
+    fn cache_stats(&mut self, py: Python) -> PyResult<PyObject> {
+        let session = self.session()?;
+        let (stats, config) = (session.cache_stats(), session.cache_config());
+        let result = PyDict::new(py);
+        result.set_item("schema_version", RESULT_SCHEMA_VERSION)?;
+        result.set_item("hits", stats.hits)?;
+        result.set_item("misses", stats.misses)?;
+        result.set_item("evictions", stats.evictions)?;
+        result.set_item("entries", stats.entries)?;
+        result.set_item("bytes", stats.bytes)?;
+        result.set_item("max_entries", config.max_entries)?;
+        result.set_item("max_bytes", config.max_bytes)?;
+        Ok(result.into())
+    }
+    
+    /// Drop every cached result; the counters are kept
+    fn clear_cache(&mut self) -> PyResult<()> {
+        self.session()?.clear_cache();
+        Ok(())
+    }
+    
+    /// Drop every registered table and cached result (idempotent)
+    fn close(&mut self) {
+        self.inner = None;
+    }
+    
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+    
+    fn __exit__(&mut self, _exc_type: &PyAny, _exc_value: &PyAny, _traceback: &PyAny) -> bool {
+        self.close();
+        false
+    }
+}
+
+/// Helper function to convert a QueryReport into a Python dictionary
+fn query_report_to_pydict(py: Python, report: &QueryReport) -> PyResult<PyObject> {
+    let result = PyDict::new(py);
+    result.set_item("optimized_plan", &report.optimized_plan)?;
+    result.set_item("output_rows", report.output_rows)?;
+    
+    let mut scans = Vec::new();
+    for scan in &report.scans {
+        let entry = PyDict::new(py);
+        entry.set_item("table", &scan.table)?;
+        entry.set_item("scan_type", &scan.scan_type)?;
+        entry.set_item("columns_projected", scan.columns_projected)?;
+        entry.set_item("total_columns", scan.total_columns)?;
+        entry.set_item("columns_read", &scan.columns_read)?;
+        entry.set_item("predicates", &scan.predicates)?;
+        scans.push(entry);
+    }
+    result.set_item("scans", scans)?;
+    
+    let mut nodes = Vec::new();
+    for node in &report.nodes {
+        let entry = PyDict::new(py);
+        entry.set_item("node", &node.node)?;
+        entry.set_item("start_us", node.start_us)?;
+        entry.set_item("end_us", node.end_us)?;
+        nodes.push(entry);
+    }
+    result.set_item("nodes", nodes)?;
+    
+    let mut node_rows = Vec::new();
+    for node in &report.node_rows {
+        let entry = PyDict::new(py);
+        entry.set_item("node", &node.node)?;
+        entry.set_item("depth", node.depth)?;
+        entry.set_item("rows", node.rows)?;
+        node_rows.push(entry);
+    }
+    result.set_item("node_rows", node_rows)?;
     
     Ok(result.into())
 }
 
+// ============================================================================
+// Module Lifecycle
+// ============================================================================
+
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering as AtomicOrdering};
+use pyo3::exceptions::PyImportError;
+
+/// Owner value while no interpreter has initialized the module
+const NO_INTERPRETER: i64 = -1;
+
+/// Interpreter whose import initialized the module
+static OWNER_INTERPRETER: AtomicI64 = AtomicI64::new(NO_INTERPRETER);
+
+/// Set by the atexit hook; background threads stop calling into Python
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// Prepare process-wide state for an import of the module
+/// 
+/// Configuration, the aggregation registry and the Rayon pool live once per
+/// process, so only one interpreter may use the module at a time:
+/// * importing again in the owning interpreter (after removing it from
+///   `sys.modules`, or `importlib.reload`) keeps the existing state and does
+///   not register the shutdown hook twice
+/// * importing from a sub-interpreter raises ImportError rather than sharing
+///   Python objects between interpreters
+/// * after the owning interpreter has shut down, a newly initialized
+///   interpreter (embedders that finalize and restart Python) may claim it
+pub(crate) fn initialize_module(py: Python) -> PyResult<()> {
+    let interpreter = current_interpreter_id(py);
+    match claim_interpreter(&OWNER_INTERPRETER, interpreter) {
+        Ok(true) => {
+            SHUTTING_DOWN.store(false, AtomicOrdering::SeqCst);
+            py.import("atexit")?
+                .call_method1("register", (wrap_pyfunction!(shutdown_module, py)?,))?;
+            Ok(())
+        }
+        Ok(false) => Ok(()),
+        Err(owner) => Err(PyImportError::new_err(format!(
+            "insightora_core is already initialized in interpreter {} and cannot be imported in interpreter {}; \
+             its configuration, aggregation registry and thread pool are process-wide, so sub-interpreters are not supported",
+            owner, interpreter
+        ))),
+    }
+}
+
+/// ID of the calling thread's interpreter (0 = main interpreter)
+fn current_interpreter_id(_py: Python) -> i64 {
+    // SAFETY: the GIL is held, so the thread has a current interpreter
+    unsafe { pyo3::ffi::PyInterpreterState_GetID(pyo3::ffi::PyInterpreterState_Get()) }
+}
+
+/// Record `interpreter` as the module's owner
+/// 
+/// Ok(true) when newly claimed, Ok(false) when it already owns the module
+/// and Err(owner) when another interpreter does.
+fn claim_interpreter(owner: &AtomicI64, interpreter: i64) -> Result<bool, i64> {
+    match owner.compare_exchange(NO_INTERPRETER, interpreter, AtomicOrdering::SeqCst, AtomicOrdering::SeqCst) {
+        Ok(_) => Ok(true),
+        Err(current) if current == interpreter => Ok(false),
+        Err(current) => Err(current),
+    }
+}
+
+/// True while Python code may still be called from Rust threads
+/// 
+/// Progress callbacks, custom aggregations and async batch waiters check this
+/// before taking the GIL: acquiring it once finalization has begun crashes or
+/// hangs the process.
+pub(crate) fn python_available() -> bool {
+    // SAFETY: Py_IsInitialized may be called at any time, with or without the GIL
+    !SHUTTING_DOWN.load(AtomicOrdering::SeqCst) && unsafe { pyo3::ffi::Py_IsInitialized() } != 0
+}
+
+/// atexit hook releasing the Python objects held in process-wide state
+/// 
+/// atexit hooks run before finalization starts, so the registered
+/// aggregations' callables are dropped while they can still be freed. The
+/// Rayon pool is left running: its workers hold no Python objects, work in
+/// flight sees `python_available()` turn false, and the threads end with the
+/// process.
+#[pyfunction]
+fn shutdown_module() -> PyResult<()> {
+    SHUTTING_DOWN.store(true, AtomicOrdering::SeqCst);
+    aggregations::clear_aggregations()?;
+    OWNER_INTERPRETER.store(NO_INTERPRETER, AtomicOrdering::SeqCst);
+    Ok(())
+}
+
 #[cfg(test)]
-mod tests {
+mod result_schema_tests {
     use super::*;
-
+    use pyo3::types::IntoPyDict;
+    use std::io::Write;
+    use tempfile::TempDir;
+    
+    /// Check a value against a registry type name
+    fn conforms(value: &PyAny, type_name: &str) -> bool {
+        if let Some(inner) = type_name.strip_suffix(" | None") {
+            return value.is_none() || conforms(value, inner);
+        }
+        if let Some(item) = type_name.strip_prefix("list[").and_then(|t| t.strip_suffix(']')) {
+            return match value.downcast::<PyList>() {
+                Ok(list) => list.iter().all(|v| conforms(v, item)),
+                Err(_) => false,
+            };
+        }
+        match type_name {
+            "int" => value.is_instance_of::<pyo3::types::PyLong>() && !value.is_instance_of::<pyo3::types::PyBool>(),
+            "float" => value.is_instance_of::<pyo3::types::PyFloat>(),
+            "str" => value.is_instance_of::<pyo3::types::PyString>(),
+            "bool" => value.is_instance_of::<pyo3::types::PyBool>(),
+            "list" => value.is_instance_of::<PyList>(),
+            "dict" => value.is_instance_of::<PyDict>(),
+            other => panic!("Unknown type '{}' in result schema registry", other),
+        }
+    }
+    
+    /// Every required key present, no undeclared keys, every value of its declared type
+    fn check_fields<'a>(
+        context: &str,
+        dict: &PyDict,
+        fields: impl Iterator<Item = &'a ResultField>,
+    ) -> Vec<String> {
+        let fields: Vec<&ResultField> = fields.collect();
+        let mut problems = Vec::new();
+        for (key, _) in dict.iter() {
+            let key = key.to_string();
+            if !fields.iter().any(|f| f.key == key) {
+                problems.push(format!("{}: undeclared key '{}'", context, key));
+            }
+        }
+        for field in fields {
+            let Some(value) = dict.get_item(field.key).unwrap() else {
+                if !field.optional {
+                    problems.push(format!("{}: missing key '{}'", context, field.key));
+                }
+                continue;
+            };
+            if !conforms(value, field.type_name) {
+                problems.push(format!("{}: '{}' is not {}", context, field.key, field.type_name));
+                continue;
+            }
+            if !field.items.is_empty() {
+                for (i, record) in value.downcast::<PyList>().unwrap().iter().enumerate() {
+                    let record = record.downcast::<PyDict>().unwrap();
+                    let context = format!("{}.{}[{}]", context, field.key, i);
+                    problems.extend(check_fields(&context, record, field.items.iter()));
+                }
+            }
+        }
+        problems
+    }
+    
+    fn write(dir: &TempDir, name: &str, content: &str) -> String {
+        let path = dir.path().join(name);
+        std::fs::File::create(&path).unwrap().write_all(content.as_bytes()).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+    
+    /// Real output of every registered binding on small fixtures
+    fn fixture_results(py: Python, dir: &TempDir) -> PyResult<Vec<(&'static str, PyObject)>> {
+        let csv = write(dir, "orders.csv", "order_id,region,amount\n1,north,10.5\n2,south,20.0\n3,north,7.25\n3,north,7.25\n");
+        let events = write(dir, "events.csv", "day,kind\n1,open\n2,click\n3,click\n4,close\n");
+        write(dir, "part_1.csv", "day,kind\n1,open\n");
+        write(dir, "part_2.csv", "day,kind\n2,close\n");
+        let parts = dir.path().join("part_*.csv").to_string_lossy().into_owned();
+        let ndjson = write(dir, "events.ndjson", "{\"id\": 1, \"user\": {\"plan\": \"pro\"}}\n{\"id\": 2}\n");
+        let xml = write(dir, "items.xml", "<items><item id=\"1\"><name>a</name></item><item id=\"2\"><name>b</name></item></items>");
+        let html = write(dir, "report.html", "<table><tr><th>a</th><th>b</th></tr><tr><td>1</td><td>2</td></tr></table>");
+        let archive = dir.path().join("upload.zip").to_string_lossy().into_owned();
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&archive).unwrap());
+        zip.start_file("exports/orders.csv", zip::write::FileOptions::default()).unwrap();
+        zip.write_all(std::fs::read(&csv).unwrap().as_slice()).unwrap();
+        zip.start_file("exports/notes.json", zip::write::FileOptions::default()).unwrap();
+        zip.write_all(b"{}").unwrap();
+        zip.finish().unwrap();
+        let workbook = crate::io::excel_parser::tests::workbook(dir, &[("Orders", r#"<row r="1"><c r="A1" t="inlineStr"><is><t>amount</t></is></c></row><row r="2"><c r="A2"><v>10</v></c></row>"#)]);
+        
+        let parsed = parse_csv(py, &csv, None, None, "dict", false)?;
+        let data: &PyDict = parsed.downcast(py)?;
+        let trial = parse_csv(py, &write(dir, "trial.csv", "variant,value\na,1.5\na,2.5\na,\nb,3.0\nb,4.5\nb,5.0\n"), None, None, "dict", false)?;
+        let trial: &PyDict = trial.downcast(py)?;
+        let sorted = sort_data(py, data, "amount".to_object(py).as_ref(py), None, "binary", None, None)?;
+        let aggregations = PyDict::new(py);
+        aggregations.set_item("amount", vec!["sum", "mean"])?;
+        let region = "region".to_object(py);
+        let region = region.as_ref(py);
+        let stamped = cast_columns(py, data, [("order_id", "datetime[ms]")].into_py_dict(py), true, None)?;
+        let stamped = stamped.as_ref(py);
+        
+        let mut session = PyQuerySession::new(DEFAULT_CACHE_ENTRIES, 256, "binary", None)?;
+        session.register("orders", data)?;
+        
+        let grouped = group_by(py, data, region, aggregations, None, true, 10, "flat", None, None, false, 100, false)?;
+        let drill_down_handle: u64 = grouped.as_ref(py).get_item("drill_down")?.get_item("handle")?.extract()?;
+        
+        let mut builder = PyDatasetBuilder::new(None, "error", None)?;
+        builder.append(py, data)?;
+        let dataset = builder.finish()?;
+        dataset.create_index(py, "order_id", false)?;
+        let prepared = PyPreparedPipeline {
+            inner: Arc::new(
+                Pipeline::new()
+                    .then(PipelineStep::Sort { by: vec!["amount".into()], descending: true })
+                    .prepare(&[("amount".into(), polars::prelude::DataType::Float64)], CsvParserConfig::default())?,
+            ),
+        };
+        dataset.create_index(py, "region", false)?;
+        let (south, north): (&PyAny, &PyAny) = (pyo3::types::PyString::new(py, "south"), pyo3::types::PyString::new(py, "north"));
+        let out = |name: &str| dir.path().join(name).to_string_lossy().into_owned();
+        let canonical = [("amount", "f64"), ("order_id", "i64")].into_py_dict(py);
+        let ipc = out("orders.arrow");
+        write_ipc(py, data, &ipc, Some("zstd"))?;
+        let binned = qcut(py, data, "amount", 4, None, "clip", "null")?;
+        let bin_spec: &PyDict = binned.as_ref(py).get_item("bin_spec")?.downcast()?;
+        let inferred = infer_csv_schema(py, &csv, 1000, false, None, None)?;
+        let inferred: &PyDict = inferred.as_ref(py).downcast()?;
+        // Fast paths may all be available here, so record one fallback to list
+        capabilities::degrade("fixture", capabilities::FastPath::Simd, "scalar", "schema fixture")?;
+        
+        Ok(vec![
+            ("get_config", get_config()?),
+            ("get_degradations", get_degradations(py, false)?),
+            ("parse_csv", parse_csv(py, &csv, None, None, "dict", false)?),
+            ("parse_csv_with_options", parse_csv_with_options(py, &csv, Some(true), ",", None, None, None, "column_count", None, true, None, None, None, 0, None, None, true, true, false, Some(vec!["region".to_string()]), None, "report", false, None, "error", None, "dict", "utf8", "strict", None, None, None, None)?),
+            ("parse_csv_bytes", parse_csv_bytes(py, b"region,amount\nnorth,10\n", true, ",", None, None, None, None, None, 0, None, None, true)?),
+            ("parse_csv_string", parse_csv_string(py, "region;amount\nnorth;10\n", true, ";", None, None, None, None, None, 0, None, None, true)?),
+            ("infer_csv_schema", infer_csv_schema(py, &csv, 1000, true, None, None)?),
+            ("parse_csv_with_schema", parse_csv_with_schema(py, &csv, inferred, None)?),
+            ("parse_csv_with_inference", parse_csv_with_inference(py, &csv, 10_000, None)?),
+            ("build_file_index", build_file_index(py, &events, vec!["day".to_string()], 16, true, ",")?),
+            ("parse_csv_filtered", parse_csv_filtered(py, &events, "day >= 3", true, ",", None, None, None)?),
+            ("parse_csv_glob", parse_csv_glob(py, &parts, true, None)?),
+            ("parse_csv_streaming", parse_csv_streaming(py, &csv, 2, 1024, "none", None, false, None, None, true, true, Some(vec!["amount".to_string()]), None, "report", None, "report", None, "dict", None)?),
+            ("should_use_streaming", should_use_streaming(py, &csv, 1024)?),
+            (
+                "parse_remote_many",
+                // Nothing listens on the discard port; the failure is reported per file
+                parse_remote_many(py, vec!["http://127.0.0.1:9/orders.csv".to_string()], 1, None, "skip", 1.0, None, 0, "union", None)?,
+            ),
+            ("list_archive", list_archive(py, &archive)?),
+            ("parse_archive", parse_archive(py, &archive, None, None, "union")?),
+            ("parse_auto", parse_auto(py, &csv, 5, None)?),
+            ("sniff_csv", sniff_csv(py, &csv, 65536)?),
+            ("parse_ndjson", parse_ndjson(py, &ndjson, None, None, true, Some(1000), "dict")?),
+            ("parse_xml", parse_xml(py, &xml, "item", None, None)?),
+            ("parse_html_tables", parse_html_tables(py, &html, None)?),
+            ("parse_excel", parse_excel(py, &workbook, None, true, None, 0, None, "value", "null")?),
+            ("list_excel_sheets", list_excel_sheets(py, &workbook)?),
+            ("parse_excel_all_sheets", parse_excel_all_sheets(py, &workbook, true, false, true, None, 0, None, "value", "null")?),
+            ("parse_excel_streaming", parse_excel_streaming(py, &workbook, None, 1, 1024, true, None, "report", None)?),
+            ("should_use_excel_streaming", should_use_excel_streaming(py, &workbook, 1024)?),
+            ("sort_data", sort_data(py, data, region, None, "binary", None, None)?),
+            ("sort", sort_rows(py, data, region, None, true, false)?),
+            ("set_category_order", set_category_order(py, data, "region", vec!["south".to_string(), "north".to_string()], "error")?),
+            (
+                "format_values",
+                format_values(py, data, [("amount", [("type", "currency")].into_py_dict(py))].into_py_dict(py), false, ",", ".")?,
+            ),
+            ("qcut", binned.clone_ref(py)),
+            ("apply_bins", apply_bins(py, data, bin_spec)?),
+            ("drop_nulls", drop_nulls(py, data, None, "any")?),
+            ("fill_nulls", fill_nulls(py, data, Some("forward"), None, None)?),
+            ("cast", cast_columns(py, data, [("amount", "f32")].into_py_dict(py), false, None)?),
+            (
+                "string_ops",
+                string_ops(py, data, "region", "upper", None, None, true, None, None, None, None, None, false)?,
+            ),
+            (
+                "datetime_features",
+                datetime_features(py, stamped, "order_id", vec!["year", "is_weekend"].into_py(py).as_ref(py))?,
+            ),
+            ("truncate_datetime", truncate_datetime(py, stamped, "order_id", "1d", None)?),
+            ("drop_duplicates", drop_duplicates(py, data, None, "first", None, None, true, None, false, false)?),
+            ("rename_columns", rename_columns(py, data, vec!["area"].into_py(py).as_ref(py), true)?),
+            ("select", select_columns(py, data, None, None, Some(vec!["Int64", "Float64"].into_py(py).as_ref(py)))?),
+            ("rename", rename(py, data, [("region", "area")].into_py_dict(py))?),
+            ("drop", drop_columns(py, data, region)?),
+            ("reorder_columns", reorder_columns(py, data, vec!["amount".to_string()], "append")?),
+            ("add_prefix", add_prefix(py, data, "src_", None)?),
+            ("add_suffix", add_suffix(py, data, "_raw", Some(region))?),
+            ("normalize_column_names", normalize_column_names(py, data, "camel", "error")?),
+            (
+                "map_columns",
+                map_columns(py, data, [("area", vec!["re:^reg"]), ("total", vec!["amount_total"])].into_py_dict(py), None)?,
+            ),
+            ("add_columns", add_columns(py, data, [("double", "amount * 2")].into_py_dict(py), "propagate")?),
+            ("update_where", update_where(py, data, "region == 'north'", [("amount", "amount + 1")].into_py_dict(py))?),
+            ("filter", filter_data(py, data, "region == 'north' or amount > 8")?),
+            ("PreparedPipeline.run", prepared.run(py, &csv)?),
+            ("PreparedPipeline.run_to_parquet", prepared.run_to_parquet(py, &csv, &out("sorted.parquet"), None, false, false)?),
+            (
+                "PreparedPipeline.run_to_csv_partitioned",
+                prepared.run_to_csv_partitioned(py, &csv, &out("sorted_parts"), 2, "part_{index}.csv", None, None)?,
+            ),
+            (
+                "write_csv_partitioned",
+                write_csv_partitioned(py, data, &out("parts"), 3, "part_{index}.csv", Some(out("parts.csv")), None, ",", true, None, "necessary", "", "\n")?,
+            ),
+            ("write_parquet", write_parquet(py, data, &out("orders.parquet"), Some(canonical), false, true)?),
+            ("write_partitioned", write_partitioned(py, data, &out("by_region"), region, Some(canonical), false, false)?),
+            ("read_ipc", read_ipc(py, &ipc, false)?),
+            ("concat", concat(py, vec![data, data], "vertical", true, false, None)?),
+            ("join", join_rows(py, data, data, Some(region), None, None, "left", "_right", false)?),
+            ("join_data", join_data(py, data, data, Some(region), None, None, "inner", None, None, true, "auto", None, None, true, false)?),
+            ("suggest_join_keys", suggest_join_keys(py, data, data, 5)?),
+            ("value_counts", value_counts(py, data, "region", None, None, true, None)?),
+            ("group_by", grouped.clone_ref(py)),
+            (
+                "group_by[tree]",
+                group_by(py, data, ["region", "order_id"].into_py(py).as_ref(py), aggregations, None, false, 10, "tree", None, None, true, 100, false)?,
+            ),
+            (
+                "get_contributors",
+                get_contributors(py, drill_down_handle, Some("north".to_object(py).as_ref(py)), None)?,
+            ),
+            ("describe", describe(py, data, true, None)?),
+            ("compare_groups", compare_groups(py, data, "region", "north".to_object(py).as_ref(py), "south".to_object(py).as_ref(py), None, None)?),
+            ("t_test", t_test(py, trial, "value", "variant", false, None)?),
+            ("anova", anova(py, trial, "value", "variant", None)?),
+            ("proportion_test", proportion_test(py, 45, 100, 30, 90)?),
+            ("ks_test", ks_test(py, trial, "value", "variant", None)?),
+            ("suggest_outlier_params", suggest_outlier_params(py, data, None)?),
+            ("correlation", correlation(py, data, "pb", None, 0.2)?),
+            ("covariance_matrix", covariance_matrix(py, data, None, false, 0.75)?),
+            ("downsample", downsample(py, data, "order_id", "amount", 2, "lttb", Some("region"))?),
+            ("frame_diff", frame_diff(py, data, sorted.downcast(py)?, true, true, 1e-9, true, false, false, 20)?),
+            ("Dataset.to_dict", dataset.to_dict(py)?),
+            ("RustDataFrame.to_dict", PyRustDataFrame::new(py, pydict_to_dataframe(data)?)?.to_dict(py)?),
+            ("Dataset.lookup", dataset.lookup(py, 3i64.into_py(py).as_ref(py), Some("order_id"))?),
+            (
+                "Dataset.lookup_many",
+                dataset.lookup_many(py, vec![south, north], Some("region"))?,
+            ),
+            ("QuerySession.sql", session.sql(py, "SELECT region, amount FROM orders WHERE amount > 8", true, None, true)?),
+            ("QuerySession.cache_stats", session.cache_stats(py)?),
+        ])
+    }
+    
+    #[test]
+    fn test_results_conform_to_registered_schemas() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let dir = TempDir::new().unwrap();
+            let results = fixture_results(py, &dir).unwrap();
+            
+            let mut problems = Vec::new();
+            for schema in RESULT_SCHEMAS {
+                let Some((_, result)) = results.iter().find(|(name, _)| *name == schema.function) else {
+                    problems.push(format!("{}: registered but has no fixture", schema.function));
+                    continue;
+                };
+                let dicts: Vec<&PyDict> = match schema.returns {
+                    "dict" => vec![result.downcast(py).unwrap()],
+                    "list[dict]" => result.downcast::<PyList>(py).unwrap()
+                        .iter()
+                        .map(|item| item.downcast().unwrap())
+                        .collect(),
+                    other => panic!("Unknown return kind '{}'", other),
+                };
+                assert!(!dicts.is_empty(), "{} returned no results", schema.function);
+                for dict in dicts {
+                    let version: u32 = dict.get_item("schema_version").unwrap().unwrap().extract().unwrap();
+                    assert_eq!(version, RESULT_SCHEMA_VERSION);
+                    problems.extend(check_fields(schema.function, dict, schema.all_fields()));
+                }
+            }
+            for (name, _) in &results {
+                if find_result_schema(name).is_none() {
+                    problems.push(format!("{}: fixture without a registered schema", name));
+                }
+            }
+            assert!(problems.is_empty(), "{}", problems.join("\n"));
+        });
+    }
+    
     #[test]
-    fn test_default_config() {
-        let config = RustConfig::default();
-        assert!(config.thread_count > 0);
-        assert_eq!(config.chunk_size, 100_000);
-        assert_eq!(config.memory_limit_mb, 4096);
-        assert!(config.enable_simd);
+    fn test_result_schema_lookup() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let schema = result_schema(py, Some("join_data")).unwrap();
+            let fields = schema.as_ref(py).get_item("fields").unwrap();
+            let engine = fields.get_item("join_engine").unwrap();
+            assert_eq!(engine.get_item("type").unwrap().extract::<String>().unwrap(), "str");
+            assert!(!engine.get_item("optional").unwrap().extract::<bool>().unwrap());
+            
+            let all = result_schema(py, None).unwrap();
+            assert_eq!(all.as_ref(py).len().unwrap(), RESULT_SCHEMAS.len());
+            assert!(result_schema(py, Some("not_a_binding")).is_err());
+        });
     }
+}
 
+#[cfg(test)]
+mod lifecycle_tests {
+    use super::*;
+    
     #[test]
-    fn test_memory_limit_check() {
-        let result = check_memory_limit(2048);
-        assert!(result.is_ok());
+    fn test_interpreter_claims() {
+        let owner = AtomicI64::new(NO_INTERPRETER);
+        assert_eq!(claim_interpreter(&owner, 0), Ok(true));
+        assert_eq!(claim_interpreter(&owner, 0), Ok(false));
+        // A sub-interpreter is refused while the main interpreter owns the module
+        assert_eq!(claim_interpreter(&owner, 3), Err(0));
         
-        let result = check_memory_limit(5000);
-        assert!(result.is_err());
+        owner.store(NO_INTERPRETER, AtomicOrdering::SeqCst);
+        assert_eq!(claim_interpreter(&owner, 3), Ok(true));
+    }
+    
+    #[test]
+    fn test_repeated_import_use_and_unload() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            let sys_modules = py.import("sys")?.getattr("modules")?;
+            let atexit = py.import("atexit")?;
+            let gc = py.import("gc")?;
+            let mut hooks = None;
+            
+            for _ in 0..25 {
+                let module = PyModule::new(py, "insightora_core")?;
+                crate::insightora_core(py, module)?;
+                sys_modules.set_item("insightora_core", module)?;
+                
+                let data = PyDict::new(py);
+                data.set_item("columns", vec!["rank", "name"])?;
+                let columns = [PyList::new(py, [3, 1, 2]).to_object(py), PyList::new(py, ["c", "a", "b"]).to_object(py)];
+                data.set_item("data", PyList::new(py, columns))?;
+                let sorted = module.getattr("sort_data")?.call1((data, "rank"))?;
+                let names: Vec<String> = sorted.get_item("data")?.get_item(1)?.extract()?;
+                assert_eq!(names, vec!["a", "b", "c"]);
+                module.getattr("get_config")?.call0()?;
+                
+                sys_modules.del_item("insightora_core")?;
+                gc.call_method0("collect")?;
+                
+                // Only the first import registers the shutdown hook
+                let count: usize = atexit.call_method0("_ncallbacks")?.extract()?;
+                assert_eq!(*hooks.get_or_insert(count), count);
+            }
+            assert_eq!(OWNER_INTERPRETER.load(AtomicOrdering::SeqCst), current_interpreter_id(py));
+            assert!(python_available());
+            Ok(())
+        })
+        .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod handle_tests {
+    use super::*;
+    use pyo3::types::IntoPyDict;
+    use std::io::Write;
+    
+    fn write_rows(path: &std::path::Path) {
+        let mut file = std::fs::File::create(path).unwrap();
+        writeln!(file, "id,value").unwrap();
+        for i in 0..1000 {
+            writeln!(file, "{},{}", i, i * 2).unwrap();
+        }
+    }
+    
+    #[test]
+    fn test_files_deletable_after_with_block() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            let dir = tempfile::TempDir::new()?;
+            let path = dir.path().join("rows.csv");
+            let file_path = path.to_str().unwrap();
+            let none = py.None().into_ref(py);
+            let closed = |err: PyErr, class: &str| {
+                assert!(err.to_string().contains(&format!("{} handle is closed", class)), "{}", err);
+            };
+            
+            // Removing a file that is still open fails on Windows
+            write_rows(&path);
+            let mut batches = PyAsyncCsvBatchIterator::new(file_path, 5, 1, true, ",", "none", None, false)?;
+            assert!(!batches.__exit__(py, none, none, none));
+            std::fs::remove_file(&path)?;
+            closed(batches.__anext__(py).unwrap_err(), "AsyncCsvBatchIterator");
+            
+            // Dropping an iterator part way through closes the file too
+            write_rows(&path);
+            let mut batches = parse_csv_batches(file_path, 100, true, ",", "none", None, false, None, None, None, "report", None)?;
+            let first = batches.__next__(py)?.unwrap();
+            assert_eq!(first.as_ref(py).get_item("batch_index")?.extract::<usize>()?, 0);
+            assert_eq!(first.as_ref(py).get_item("data")?.get_item(0)?.len()?, 100);
+            drop(batches);
+            std::fs::remove_file(&path)?;
+            
+            write_rows(&path);
+            let mut session = PyQuerySession::new(DEFAULT_CACHE_ENTRIES, 256, "binary", None)?;
+            session.register_csv("rows", file_path, true, ",")?;
+            session.sql(py, "SELECT SUM(value) AS total FROM rows", false, None, true)?;
+            session.__exit__(none, none, none);
+            std::fs::remove_file(&path)?;
+            closed(session.sql(py, "SELECT 1", false, None, true).unwrap_err(), "QuerySession");
+            
+            let columns = [("id", vec![1, 2, 3])].into_py_dict(py);
+            let mut dataset = from_columns(columns, None, "error")?;
+            let mut batches = dataset.batches()?;
+            dataset.__exit__(none, none, none);
+            closed(dataset.to_dict(py).unwrap_err(), "Dataset");
+            // The iterator keeps its own share of the data until it is closed
+            assert!(batches.__next__(py)?.is_some());
+            batches.close();
+            closed(batches.__next__(py).unwrap_err(), "DatasetBatches");
+            Ok(())
+        })
+        .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod progress_tests {
+    use super::*;
+    
+    #[test]
+    fn test_streaming_progress_callback_reports_and_raises() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            let dir = tempfile::TempDir::new()?;
+            let path = dir.path().join("rows.csv");
+            let text: String = std::iter::once("id,value\n".to_string()).chain((0..500).map(|i| format!("{},{}\n", i, i * 2))).collect();
+            std::fs::write(&path, &text)?;
+            let file_path = path.to_str().unwrap();
+            // The functions look up `calls` in their globals
+            let globals = PyDict::new(py);
+            py.run("calls = []\ndef record(read, total):\n    calls.append((read, total))\ndef fail(read, total):\n    raise KeyError('stop')\n", Some(globals), None)?;
+            let parse = |callback: &str, on_error: &str| -> PyResult<PyObject> {
+                let callback: PyObject = py.eval(callback, Some(globals), None)?.into();
+                parse_csv_streaming(py, file_path, 100, 1024, "none", None, false, None, None, false, false, None, None, "report", Some(callback), on_error, None, "dict", None)
+            };
+            
+            parse("record", "report")?;
+            let calls: Vec<(usize, usize)> = py.eval("calls", Some(globals), None)?.extract()?;
+            assert_eq!(calls.last(), Some(&(text.len(), text.len())));
+            
+            // Reported exceptions leave the parse running; raised ones stop it
+            assert!(parse("fail", "report").is_ok());
+            let err = parse("fail", "raise").unwrap_err();
+            assert!(err.is_instance_of::<pyo3::exceptions::PyKeyError>(py), "{}", err);
+            assert!(parse("record", "ignore").is_err());
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_parse_csv_lets_other_threads_take_the_gil() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            let dir = tempfile::TempDir::new()?;
+            let path = dir.path().join("rows.csv");
+            let text: String = std::iter::once("id,value\n".to_string()).chain((0..200_000).map(|i| format!("{},{}\n", i, i * 2))).collect();
+            std::fs::write(&path, &text)?;
+            
+            // This thread holds the GIL from here on and runs no Python code,
+            // so the waiter only gets it if the parse releases it
+            let ran = Arc::new(AtomicBool::new(false));
+            let (ready, started) = std::sync::mpsc::channel();
+            let flag = Arc::clone(&ran);
+            let waiter = std::thread::spawn(move || {
+                ready.send(()).unwrap();
+                Python::with_gil(|_| flag.store(true, AtomicOrdering::Relaxed));
+            });
+            started.recv().unwrap();
+            std::thread::sleep(Duration::from_millis(50));
+            assert!(!ran.load(AtomicOrdering::Relaxed));
+            
+            parse_csv(py, path.to_str().unwrap(), None, None, "dict", false)?;
+            assert!(ran.load(AtomicOrdering::Relaxed));
+            py.allow_threads(|| waiter.join()).unwrap();
+            Ok(())
+        })
+        .unwrap();
+    }
+    
+    #[test]
+    fn test_cancellation_token_stops_parallel_and_batched_parses() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            let dir = tempfile::TempDir::new()?;
+            let path = dir.path().join("rows.csv");
+            let text: String = std::iter::once("id,value\n".to_string()).chain((0..500).map(|i| format!("{},{}\n", i, i * 2))).collect();
+            std::fs::write(&path, &text)?;
+            let file_path = path.to_str().unwrap();
+
+            let token = PyCell::new(py, PyCancellationToken::new())?;
+            token.borrow().cancel();
+            let err = parse_csv(py, file_path, None, Some(token.borrow()), "dict", false).unwrap_err();
+            assert!(err.is_instance_of::<OperationCancelled>(py), "{}", err);
+
+            let token = PyCell::new(py, PyCancellationToken::new())?;
+            let mut batches = parse_csv_batches(file_path, 100, true, ",", "none", None, false, None, None, None, "report", Some(token.borrow()))?;
+            assert!(batches.__next__(py)?.is_some());
+            token.borrow().cancel();
+            let err = batches.__next__(py).unwrap_err();
+            assert!(err.is_instance_of::<OperationCancelled>(py), "{}", err);
+            assert!(batches.prefetcher.is_none());
+            assert!(batches.__next__(py)?.is_none());
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_cancelled_parse_joins_its_worker() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            let dir = tempfile::TempDir::new()?;
+            let path = dir.path().join("rows.csv");
+            let text: String = std::iter::once("id,value\n".to_string()).chain((0..50_000).map(|i| format!("{},{}\n", i, i * 2))).collect();
+            std::fs::write(&path, &text)?;
+
+            // The parse cancels itself as the read starts, then takes a while to clean up
+            let cancel = Arc::new(AtomicBool::new(false));
+            let flag = Arc::clone(&cancel);
+            let reporter = ProgressReporter::new(Arc::new(move |_, _, _| flag.store(true, AtomicOrdering::Relaxed)));
+            let parser = ParallelCsvParser::new().with_progress(reporter).with_cancel_flag(Arc::clone(&cancel));
+            let exited = Arc::new(AtomicBool::new(false));
+            let worker_exited = Arc::clone(&exited);
+            let path = path.to_str().unwrap().to_string();
+            let result = run_cancellable(py, &cancel, move || {
+                let result = parser.parse(&path);
+                std::thread::sleep(SIGNAL_POLL_INTERVAL * 3);
+                worker_exited.store(true, AtomicOrdering::Relaxed);
+                result
+            })?;
+            assert!(matches!(result, Err(InsightoraError::Cancelled)));
+            assert!(exited.load(AtomicOrdering::Relaxed));
+            Ok(())
+        })
+        .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod batch_iterator_tests {
+    use super::*;
+    
+    /// Row counts and first ids of every batch, checking the indices count up from 0
+    fn read_batches(py: Python, file_path: &str, chunk_size: usize) -> PyResult<Vec<(usize, Option<i64>)>> {
+        let mut batches = parse_csv_batches(file_path, chunk_size, true, ",", "none", None, false, None, None, None, "report", None)?;
+        let mut seen = Vec::new();
+        while let Some(batch) = batches.__next__(py)? {
+            let batch = batch.as_ref(py);
+            assert_eq!(batch.get_item("batch_index")?.extract::<usize>()?, seen.len());
+            assert_eq!(batch.get_item("columns")?.extract::<Vec<String>>()?, ["id", "value"]);
+            let ids: Vec<Option<i64>> = batch.get_item("data")?.get_item(0)?.extract()?;
+            seen.push((ids.len(), ids.first().copied().flatten()));
+        }
+        // Exhausted iterators stay exhausted
+        assert!(batches.__next__(py)?.is_none());
+        assert_eq!(batches.batches_returned(), seen.len());
+        Ok(seen)
+    }
+    
+    #[test]
+    fn test_parse_csv_batches_splits_on_chunk_size() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            let dir = tempfile::TempDir::new()?;
+            let write = |name: &str, rows: usize| -> PyResult<String> {
+                let path = dir.path().join(name);
+                let text: String = std::iter::once("id,value\n".to_string()).chain((0..rows).map(|i| format!("{},{}\n", i, i * 2))).collect();
+                std::fs::write(&path, text)?;
+                Ok(path.to_str().unwrap().to_string())
+            };
+            
+            // A final partial batch, and rows ending exactly on a batch boundary
+            assert_eq!(read_batches(py, &write("partial.csv", 250)?, 100)?, [(100, Some(0)), (100, Some(100)), (50, Some(200))]);
+            assert_eq!(read_batches(py, &write("exact.csv", 200)?, 100)?, [(100, Some(0)), (100, Some(100))]);
+            assert_eq!(read_batches(py, &write("single.csv", 1)?, 100)?, [(1, Some(0))]);
+            
+            // A header without rows and an empty file give no batches
+            assert!(read_batches(py, &write("header.csv", 0)?, 100)?.is_empty());
+            let empty = dir.path().join("empty.csv");
+            std::fs::write(&empty, "")?;
+            assert!(read_batches(py, empty.to_str().unwrap(), 100)?.is_empty());
+            
+            assert!(parse_csv_batches(&write("zero.csv", 1)?, 0, true, ",", "none", None, false, None, None, None, "report", None).is_err());
+            Ok(())
+        })
+        .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod conversion_tests {
+    use super::*;
+    use polars::prelude::{DataType, NamedFrom, Series, TimeUnit};
+    use pyo3::types::IntoPyDict;
+    
+    #[test]
+    fn test_results_keep_native_values_and_round_trip() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            let dir = tempfile::TempDir::new()?;
+            let path = dir.path().join("codes.csv");
+            std::fs::write(&path, "code,amount\n0123,1.5\nA7,\n")?;
+            let parsed = parse_csv(py, path.to_str().unwrap(), None, None, "dict", false)?;
+            let parsed = parsed.as_ref(py);
+            assert_eq!(parsed.get_item("dtypes")?.extract::<Vec<String>>()?, ["String", "Float64"]);
+            let data = parsed.get_item("data")?;
+            assert_eq!(data.get_item(0)?.extract::<Vec<String>>()?, ["0123", "A7"]);
+            assert!(data.get_item(1)?.get_item(1)?.is_none());
+            
+            let df = polars::prelude::DataFrame::new(vec![
+                Series::new("big", [Some(u64::MAX), Some(1), None]),
+                Series::new("ratio", [Some(0.5), Some(f64::NAN), None]),
+                Series::new("day", [Some(19_783i32), None, Some(0)]).cast(&DataType::Date).unwrap(),
+                Series::new("at", [Some(1_700_000_000_000_000i64), None, Some(0)])
+                    .cast(&DataType::Datetime(TimeUnit::Microseconds, None))
+                    .unwrap(),
+            ])
+            .unwrap();
+            let result = dataframe_to_pydict(py, &df)?;
+            let result: &PyDict = result.downcast(py)?;
+            assert_eq!(
+                result.get_item("dtypes")?.unwrap().extract::<Vec<String>>()?,
+                ["UInt64", "Float64", "Date", "Datetime(Microseconds, None)"]
+            );
+            let data = result.get_item("data")?.unwrap();
+            assert_eq!(data.get_item(0)?.get_item(0)?.extract::<u64>()?, u64::MAX);
+            assert!(data.get_item(1)?.get_item(1)?.extract::<f64>()?.is_nan());
+            assert!(data.get_item(1)?.get_item(2)?.is_none());
+            assert_eq!(data.get_item(2)?.get_item(0)?.str()?.to_str()?, "2024-03-01");
+            assert_eq!(data.get_item(3)?.get_item(0)?.str()?.to_str()?, "2023-11-14 22:13:20");
+            
+            let restored = pydict_to_dataframe(result)?;
+            assert_eq!(restored.dtypes(), df.dtypes());
+            Ok(())
+        })
+        .unwrap();
+    }
+    
+    #[test]
+    fn test_literal_datasets_round_trip_through_to_records() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            let globals = PyDict::new(py);
+            py.run("from datetime import date\nfrom decimal import Decimal\n", Some(globals), None)?;
+            let eval = |code: &str| py.eval(code, Some(globals), None);
+            
+            // Keys in first-seen order, missing keys null, int widened by float
+            let records: &PyList = eval("[{'sku': 'A-1', 'qty': 2, 'price': Decimal('1.50')}, \
+                {'qty': 3.5, 'sku': None}, {'sku': 'C-3', 'launched': date(2024, 1, 5), 'qty': None}]")?.downcast()?;
+            let dataset = Py::new(py, from_records(records, None, "error")?)?;
+            let rows = to_records(py, dataset.as_ref(py), None)?;
+            let expected = eval("[{'sku': 'A-1', 'qty': 2.0, 'price': Decimal('1.50'), 'launched': None}, \
+                {'sku': None, 'qty': 3.5, 'price': None, 'launched': None}, \
+                {'sku': 'C-3', 'qty': None, 'price': None, 'launched': date(2024, 1, 5)}]")?;
+            assert!(rows.as_ref(py).eq(expected)?, "{}", rows.as_ref(py));
+            let keys: Vec<String> = rows.as_ref(py).get_item(0)?.downcast::<PyDict>()?.keys().extract()?;
+            assert_eq!(keys, ["sku", "qty", "price", "launched"]);
+            assert_eq!(to_records(py, dataset.as_ref(py), Some(1))?.as_ref(py).len()?, 1);
+            
+            // The same rows given as columns
+            let columns: &PyDict = eval("{'sku': ['A-1', None, 'C-3'], 'qty': [2, 3.5, None], \
+                'price': [Decimal('1.50'), None, None], 'launched': [None, None, date(2024, 1, 5)]}")?.downcast()?;
+            let dataset = Py::new(py, from_columns(columns, None, "error")?)?;
+            assert!(to_records(py, dataset.as_ref(py), None)?.as_ref(py).eq(expected)?);
+            
+            // A schema fixes the column order and adds undeclared columns as nulls
+            let schema = [("region", "str"), ("sku", "str"), ("qty", "f64")].into_py_dict(py);
+            let dataset = Py::new(py, from_columns(eval("{'qty': [1, None], 'sku': ['A-1', 'B-2']}")?.downcast()?, Some(schema), "error")?)?;
+            let rows = to_records(py, dataset.as_ref(py), None)?;
+            assert!(rows.as_ref(py).eq(eval("[{'region': None, 'sku': 'A-1', 'qty': 1.0}, {'region': None, 'sku': 'B-2', 'qty': None}]")?)?, "{}", rows.as_ref(py));
+            let keys: Vec<String> = rows.as_ref(py).get_item(0)?.downcast::<PyDict>()?.keys().extract()?;
+            assert_eq!(keys, ["region", "sku", "qty"]);
+            
+            // Types that don't widen are rejected unless read as text
+            let mixed: &PyList = eval("[{'code': 1}, {'code': 'A7'}]")?.downcast()?;
+            assert!(from_records(mixed, None, "error").is_err());
+            let dataset = Py::new(py, from_records(mixed, None, "widen")?)?;
+            assert!(to_records(py, dataset.as_ref(py), None)?.as_ref(py).eq(eval("[{'code': '1'}, {'code': 'A7'}]")?)?);
+            Ok(())
+        })
+        .unwrap();
+    }
+    
+    #[test]
+    fn test_write_json_lines_read_back_with_parse_ndjson() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            let dir = tempfile::TempDir::new()?;
+            let csv = dir.path().join("orders.csv");
+            std::fs::write(&csv, "id,amount\n1,2.5\n2,\n")?;
+            let parsed = parse_csv(py, csv.to_str().unwrap(), None, None, "dict", false)?;
+            
+            let lines = dir.path().join("orders.ndjson").to_string_lossy().into_owned();
+            assert_eq!(write_json(py, parsed.as_ref(py), &lines, "records", true, "null")?, 2);
+            assert_eq!(std::fs::read_to_string(&lines)?, "{\"id\":1,\"amount\":2.5}\n{\"id\":2,\"amount\":null}\n");
+            let read = parse_ndjson(py, &lines, None, None, false, Some(1000), "dict")?;
+            assert_eq!(read.as_ref(py).get_item("num_rows")?.extract::<usize>()?, 2);
+            
+            let columns = dir.path().join("orders.json").to_string_lossy().into_owned();
+            write_json(py, parsed.as_ref(py), &columns, "columns", false, "null")?;
+            assert_eq!(std::fs::read_to_string(&columns)?, "{\"id\":[1,2],\"amount\":[2.5,null]}");
+            assert!(write_json(py, parsed.as_ref(py), &columns, "records", false, "drop").is_err());
+            Ok(())
+        })
+        .unwrap();
+    }
+    
+    #[test]
+    fn test_arrow_return_format_exports_a_c_stream() {
+        use polars::export::arrow::ffi::ArrowArrayStreamReader;
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            let dir = tempfile::TempDir::new()?;
+            let path = dir.path().join("ids.csv");
+            std::fs::write(&path, "id,name\n1,a\n2,b\n")?;
+            let table = parse_csv(py, path.to_str().unwrap(), None, None, "arrow", false)?;
+            let table: &PyCell<PyArrowTable> = table.as_ref(py).downcast()?;
+            assert_eq!(table.borrow().columns(), ["id", "name"]);
+            assert_eq!(table.borrow().report(py).as_ref(py).get_item("schema_version")?.extract::<u32>()?, RESULT_SCHEMA_VERSION);
+            
+            // Take the stream over as a consumer does, leaving a released one behind
+            let capsule = table.borrow().__arrow_c_stream__(py, None)?;
+            let capsule: &PyCapsule = capsule.as_ref(py).downcast()?;
+            assert_eq!(capsule.name()?.unwrap().to_str().unwrap(), "arrow_array_stream");
+            let stream = unsafe { std::ptr::replace(capsule.pointer() as *mut ArrowArrayStream, ArrowArrayStream::empty()) };
+            let mut reader = unsafe { ArrowArrayStreamReader::try_new(Box::new(stream)) }.unwrap();
+            assert_eq!(unsafe { reader.next() }.unwrap().unwrap().len(), 2);
+            assert!(unsafe { reader.next() }.is_none());
+            
+            let result = table.borrow().to_dict(py)?;
+            assert_eq!(result.as_ref(py).get_item("num_rows")?.extract::<usize>()?, 2);
+            assert!(ReturnFormat::from_name("pandas").is_err());
+            
+            // Bindings taking a result dictionary take Arrow tables too
+            let reordered = reorder_columns(py, table.as_ref(), vec!["name".to_string(), "id".to_string()], "error")?;
+            assert_eq!(reordered.as_ref(py).get_item("columns")?.extract::<Vec<String>>()?, ["name", "id"]);
+            let err = reorder_columns(py, 5.to_object(py).as_ref(py), vec![], "error").unwrap_err();
+            assert!(err.is_instance_of::<PyTypeError>(py), "{}", err);
+            Ok(())
+        })
+        .unwrap();
+    }
+    
+    #[test]
+    fn test_handles_pass_between_bindings_without_conversion() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            let dir = tempfile::TempDir::new()?;
+            let path = dir.path().join("orders.csv");
+            std::fs::write(&path, "id,amount\n3,1.5\n1,2.5\n2,\n")?;
+            let handle = parse_csv(py, path.to_str().unwrap(), None, None, "dict", true)?;
+            let handle: &PyCell<PyRustDataFrame> = handle.as_ref(py).downcast()?;
+            assert_eq!((handle.borrow().shape(), handle.borrow().columns()), ((3, 2), vec!["id".to_string(), "amount".to_string()]));
+            assert!(handle.borrow().estimated_size_mb() > 0.0);
+            
+            // A table-only binding hands back a handle sharing no Python values
+            let sorted = sort_data(py, handle.as_ref(), "id".to_object(py).as_ref(py), None, "binary", None, None)?;
+            let sorted: &PyCell<PyRustDataFrame> = sorted.as_ref(py).downcast()?;
+            let head = sorted.borrow().head(py, 2)?;
+            assert_eq!(head.__len__(), 2);
+            let dict = head.to_dict(py)?;
+            assert_eq!(dict.as_ref(py).get_item("data")?.get_item(0)?.extract::<Vec<i64>>()?, [1, 2]);
+            assert_eq!(dict.as_ref(py).get_item("schema_version")?.extract::<u32>()?, RESULT_SCHEMA_VERSION);
+            let copy = sorted.borrow().__copy__(py);
+            assert!(Arc::ptr_eq(&copy.data, &sorted.borrow().data));
+            assert_eq!(sorted.borrow().to_arrow(py).num_rows(), 3);
+            
+            // Bindings returning more than a table still return dictionaries
+            let summary = describe(py, handle.as_ref(), false, None)?;
+            assert!(summary.as_ref(py).downcast::<PyDict>().is_ok());
+            assert!(parse_csv(py, path.to_str().unwrap(), None, None, "arrow", true).is_err());
+            Ok(())
+        })
+        .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod json_export_tests {
+    use super::*;
+    use pyo3::types::IntoPyDict;
+    use std::time::Instant;
+    
+    fn result_dict<'py>(py: Python<'py>, rows: usize) -> PyResult<&'py PyDict> {
+        let data = PyDict::new(py);
+        data.set_item("columns", vec!["id", "label", "score"])?;
+        let ids: Vec<i64> = (0..rows as i64).collect();
+        let labels: Vec<String> = (0..rows).map(|i| format!("row \"{}\"", i)).collect();
+        let scores: Vec<f64> = (0..rows).map(|i| i as f64 / 7.0).collect();
+        let columns = [ids.to_object(py), labels.to_object(py), scores.to_object(py)];
+        data.set_item("data", PyList::new(py, columns))?;
+        Ok(data)
+    }
+    
+    #[test]
+    fn test_to_json_round_trips_through_json_loads() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            let json = py.import("json")?;
+            let data = result_dict(py, 3)?;
+            
+            let text = to_json(py, data, "records", None, "null", false)?;
+            let records = json.call_method1("loads", (text,))?;
+            assert_eq!(records.len()?, 3);
+            assert_eq!(records.get_item(1)?.get_item("label")?.extract::<String>()?, "row \"1\"");
+            assert_eq!(records.get_item(2)?.get_item("score")?.extract::<f64>()?, 2.0 / 7.0);
+            
+            let bytes = to_json(py, data, "split", None, "null", true)?;
+            assert!(bytes.as_ref(py).is_instance_of::<PyBytes>());
+            let split = json.call_method1("loads", (bytes,))?;
+            assert_eq!(split.get_item("columns")?.extract::<Vec<String>>()?, vec!["id", "label", "score"]);
+            
+            assert!(to_json(py, data, "index", None, "null", false).is_err());
+            Ok(())
+        })
+        .unwrap();
+    }
+    
+    #[test]
+    #[ignore = "benchmark; run with --ignored --nocapture"]
+    fn bench_to_json_beats_json_dumps() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            let json = py.import("json")?;
+            let data = result_dict(py, 1_000_000)?;
+            
+            let start = Instant::now();
+            to_json(py, data, "records", None, "null", false)?;
+            let native = start.elapsed();
+            
+            let start = Instant::now();
+            let records = py.eval(
+                "[dict(zip(d['columns'], row)) for row in zip(*d['data'])]",
+                None,
+                Some([("d", data)].into_py_dict(py)),
+            )?;
+            json.call_method1("dumps", (records,))?;
+            let python = start.elapsed();
+            
+            println!("to_json: {:?}, json.dumps: {:?}", native, python);
+            assert!(native < python);
+            Ok(())
+        })
+        .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod filter_tests {
+    use super::*;
+    use pyo3::types::IntoPyDict;
+    use std::time::Instant;
+    
+    fn orders(rows: usize) -> polars::prelude::DataFrame {
+        use polars::prelude::*;
+        df!(
+            "id" => (0..rows as i64).collect::<Vec<_>>(),
+            "amount" => (0..rows).map(|i| (i % 7 != 0).then_some((i % 1000) as f64)).collect::<Vec<_>>(),
+            "country" => (0..rows).map(|i| ["DE", "FR", "AT"][i % 3]).collect::<Vec<_>>(),
+        )
+        .unwrap()
+    }
+    
+    #[test]
+    fn test_filter_takes_dicts_and_handles() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            let data = dataframe_to_pydict(py, &orders(21))?;
+            let kept = filter_data(py, data.as_ref(py), "country == 'DE' and amount > 5")?;
+            // Row 0 is German but its amount is null, so it is dropped
+            assert_eq!(kept.as_ref(py).get_item("data")?.get_item(0)?.extract::<Vec<i64>>()?, [6, 9, 12, 15, 18]);
+            
+            let handle = Py::new(py, PyRustDataFrame::new(py, orders(21))?)?;
+            let kept = filter_data(py, handle.as_ref(py), "amount is null or country in ['AT']")?;
+            let kept: &PyCell<PyRustDataFrame> = kept.as_ref(py).downcast()?;
+            assert_eq!(kept.borrow().shape(), (9, 3));
+            
+            let err = filter_data(py, data.as_ref(py), "country = 'DE'").unwrap_err();
+            assert!(err.is_instance_of::<PyValueError>(py), "{}", err);
+            assert!(err.to_string().contains("position 8"), "{}", err);
+            Ok(())
+        })
+        .unwrap();
+    }
+    
+    #[test]
+    #[ignore = "benchmark; run with --ignored --nocapture"]
+    fn bench_filter_beats_a_list_comprehension() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            let df = orders(5_000_000);
+            let data = dataframe_to_pydict(py, &df)?;
+            let handle = Py::new(py, PyRustDataFrame::new(py, df)?)?;
+            
+            let start = Instant::now();
+            let kept = filter_data(py, handle.as_ref(py), "country == 'DE' and amount > 500")?;
+            let native = start.elapsed();
+            
+            let start = Instant::now();
+            let rows = py.eval(
+                "[row for row in zip(*d['data']) if row[2] == 'DE' and row[1] is not None and row[1] > 500]",
+                None,
+                Some([("d", data.as_ref(py))].into_py_dict(py)),
+            )?;
+            let python = start.elapsed();
+            
+            let kept: &PyCell<PyRustDataFrame> = kept.as_ref(py).downcast()?;
+            assert_eq!(kept.borrow().__len__(), rows.len()?);
+            println!("filter: {:?}, list comprehension: {:?}", native, python);
+            assert!(native * 10 < python);
+            Ok(())
+        })
+        .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod summary_tests {
+    use super::*;
+    
+    #[test]
+    fn test_summary_keeps_data_values_out_unless_asked() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| -> PyResult<()> {
+            let data = PyDict::new(py);
+            data.set_item("columns", vec!["email", "amount"])?;
+            let columns = [
+                PyList::new(py, ["a@example.com", "a@example.com", "b@example.com"]).to_object(py),
+                PyList::new(py, [5, 5, 7]).to_object(py),
+            ];
+            data.set_item("data", PyList::new(py, columns))?;
+            
+            let plain = drop_duplicates(py, data, None, "first", None, None, true, None, true, false)?;
+            let summary = plain.as_ref(py).get_item("summary")?;
+            let rows = summary.get_item("rows")?;
+            assert_eq!(rows.get_item("read")?.extract::<usize>()?, 3);
+            assert_eq!(rows.get_item("kept")?.extract::<usize>()?, 2);
+            assert_eq!(rows.get_item("dropped_by_reason")?.get_item("duplicates")?.extract::<usize>()?, 1);
+            assert!(summary.get_item("phases_ms")?.get_item("deduplicate").is_ok());
+            assert!(!summary.downcast::<PyDict>()?.contains("samples")?);
+            assert!(!summary.str()?.to_str()?.contains("example.com"));
+            
+            let sampled = drop_duplicates(py, data, None, "first", None, None, true, None, true, true)?;
+            let samples = sampled.as_ref(py).get_item("summary")?.get_item("samples")?;
+            assert!(samples.get_item("first_row.email")?.extract::<String>()?.contains("a@example.com"));
+            Ok(())
+        })
+        .unwrap();
     }
 }
@@ -0,0 +1,267 @@
+// Query result cache
+// LRU cache of query results keyed by normalized SQL and the versions of the tables it reads
+
+use std::collections::HashMap;
+use std::time::SystemTime;
+use polars::prelude::*;
+
+/// Default number of cached results per session
+pub const DEFAULT_CACHE_ENTRIES: usize = 64;
+
+/// Default memory budget for cached results per session
+pub const DEFAULT_CACHE_BYTES: usize = 256 * 1024 * 1024;
+
+/// Limits for a session's result cache
+///
+/// Either limit at zero disables caching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryCacheConfig {
+    pub max_entries: usize,
+    /// Estimated in-memory size of all cached results together
+    pub max_bytes: usize,
+}
+
+impl Default for QueryCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: DEFAULT_CACHE_ENTRIES,
+            max_bytes: DEFAULT_CACHE_BYTES,
+        }
+    }
+}
+
+impl QueryCacheConfig {
+    pub fn disabled() -> Self {
+        Self { max_entries: 0, max_bytes: 0 }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_entries > 0 && self.max_bytes > 0
+    }
+}
+
+/// Hit/miss counters and current contents of a result cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries dropped to stay within the limits
+    pub evictions: u64,
+    pub entries: usize,
+    pub bytes: usize,
+}
+
+/// Identity of a cached result
+///
+/// `tables` holds every registered table the query mentions with its
+/// content version, so re-registering a table changes the key of every
+/// query over it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QueryCacheKey {
+    pub query: String,
+    pub tables: Vec<TableVersion>,
+}
+
+/// Content version of one registered table
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TableVersion {
+    pub name: String,
+    /// Bumped every time a table is registered under any name
+    pub version: u64,
+    /// Modification time and size of a lazily scanned source file, so an
+    /// edited file is not served from the cache
+    pub file_stamp: Option<(SystemTime, u64)>,
+}
+
+struct CachedResult {
+    df: DataFrame,
+    bytes: usize,
+    last_used: u64,
+}
+
+/// Least-recently-used cache of query results
+pub struct QueryCache {
+    config: QueryCacheConfig,
+    entries: HashMap<QueryCacheKey, CachedResult>,
+    clock: u64,
+    stats: QueryCacheStats,
+}
+
+impl QueryCache {
+    pub fn new(config: QueryCacheConfig) -> Self {
+        Self {
+            config,
+            entries: HashMap::new(),
+            clock: 0,
+            stats: QueryCacheStats::default(),
+        }
+    }
+
+    pub fn config(&self) -> QueryCacheConfig {
+        self.config
+    }
+
+    pub fn stats(&self) -> QueryCacheStats {
+        self.stats
+    }
+
+    /// Cached result for `key`, counting a hit or a miss
+    pub fn get(&mut self, key: &QueryCacheKey) -> Option<DataFrame> {
+        self.clock += 1;
+        match self.entries.get_mut(key) {
+            Some(entry) => {
+                entry.last_used = self.clock;
+                self.stats.hits += 1;
+                Some(entry.df.clone())
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Store a result, evicting least recently used entries to make room
+    ///
+    /// Results larger than the whole byte budget are not cached.
+    pub fn insert(&mut self, key: QueryCacheKey, df: &DataFrame) {
+        let bytes = df.estimated_size();
+        if !self.config.is_enabled() || bytes > self.config.max_bytes {
+            return;
+        }
+        self.remove(&key);
+        while self.entries.len() >= self.config.max_entries || self.stats.bytes + bytes > self.config.max_bytes {
+            if !self.evict_oldest() {
+                break;
+            }
+        }
+        self.clock += 1;
+        self.stats.bytes += bytes;
+        self.entries.insert(key, CachedResult { df: df.clone(), bytes, last_used: self.clock });
+        self.stats.entries = self.entries.len();
+    }
+
+    /// Drop every entry that reads `table`
+    pub fn invalidate_table(&mut self, table: &str) {
+        let stale: Vec<QueryCacheKey> = self
+            .entries
+            .keys()
+            .filter(|key| key.tables.iter().any(|t| t.name == table))
+            .cloned()
+            .collect();
+        for key in stale {
+            self.remove(&key);
+        }
+    }
+
+    /// Drop every entry; the hit/miss counters are kept
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.stats.entries = 0;
+        self.stats.bytes = 0;
+    }
+
+    /// Change the limits, evicting as needed
+    pub fn reconfigure(&mut self, config: QueryCacheConfig) {
+        self.config = config;
+        if !config.is_enabled() {
+            self.clear();
+            return;
+        }
+        while self.entries.len() > config.max_entries || self.stats.bytes > config.max_bytes {
+            if !self.evict_oldest() {
+                break;
+            }
+        }
+    }
+
+    /// Drop the least recently used entry; false when the cache is empty
+    fn evict_oldest(&mut self) -> bool {
+        let oldest = self
+            .entries
+            .iter()
+            .min_by_key(|(_, entry)| entry.last_used)
+            .map(|(key, _)| key.clone());
+        match oldest {
+            Some(oldest) => {
+                self.remove(&oldest);
+                self.stats.evictions += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn remove(&mut self, key: &QueryCacheKey) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.stats.bytes -= entry.bytes;
+            self.stats.entries = self.entries.len();
+        }
+    }
+}
+
+/// Query text with whitespace runs collapsed outside literals and any
+/// trailing semicolon dropped, so formatting differences share an entry
+pub fn normalize_query(query: &str) -> String {
+    let mut normalized = String::with_capacity(query.len());
+    let mut quote: Option<char> = None;
+    let mut pending_space = false;
+    for c in query.trim().trim_end_matches(';').trim_end().chars() {
+        match quote {
+            Some(open) => {
+                normalized.push(c);
+                if c == open {
+                    quote = None;
+                }
+            }
+            None if c.is_whitespace() => pending_space = true,
+            None => {
+                if pending_space && !normalized.is_empty() {
+                    normalized.push(' ');
+                }
+                pending_space = false;
+                if c == '\'' || c == '"' {
+                    quote = Some(c);
+                }
+                normalized.push(c);
+            }
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(query: &str, version: u64) -> QueryCacheKey {
+        QueryCacheKey {
+            query: normalize_query(query),
+            tables: vec![TableVersion { name: "orders".to_string(), version, file_stamp: None }],
+        }
+    }
+
+    #[test]
+    fn test_lru_eviction_and_normalization() {
+        assert_eq!(
+            normalize_query("SELECT  a\n FROM t\tWHERE b = 'x  y' ;"),
+            "SELECT a FROM t WHERE b = 'x  y'"
+        );
+
+        let df = df!("a" => [1i64, 2, 3]).unwrap();
+        let mut cache = QueryCache::new(QueryCacheConfig { max_entries: 2, ..Default::default() });
+        assert!(cache.get(&key("SELECT 1", 1)).is_none());
+        cache.insert(key("SELECT 1", 1), &df);
+        cache.insert(key("SELECT 2", 1), &df);
+        assert!(cache.get(&key("SELECT   1", 1)).is_some());
+        // "SELECT 2" is now the least recently used
+        cache.insert(key("SELECT 3", 1), &df);
+        assert!(cache.get(&key("SELECT 2", 1)).is_none());
+        assert!(cache.get(&key("SELECT 1", 2)).is_none());
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.evictions, stats.entries), (1, 3, 1, 2));
+        cache.invalidate_table("orders");
+        assert_eq!((cache.stats().entries, cache.stats().bytes), (0, 0));
+    }
+}
@@ -1,2 +1,923 @@
-// Parallel query executor
-// TODO: Implement as needed
+// Query execution engine
+// SQL sessions over registered lazy tables with plan introspection
+
+use std::collections::{BTreeMap, BTreeSet};
+use polars::prelude::*;
+use polars::sql::SQLContext;
+use crate::dataframe::operations::{sort_data, Collation};
+use crate::error::InsightoraError;
+use crate::query::cache::{normalize_query, QueryCache, QueryCacheConfig, QueryCacheKey, QueryCacheStats, TableVersion};
+use crate::utils::sandbox::check_path_allowed;
+
+/// A table registered in a query session
+#[derive(Debug, Clone)]
+pub struct RegisteredTable {
+    pub name: String,
+    /// Source file for lazily scanned tables, None for in-memory frames
+    pub source: Option<String>,
+    pub schema: SchemaRef,
+    /// Content version; a re-registered table gets a new one
+    pub version: u64,
+}
+
+/// What a single scan in the optimized plan reads
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanReport {
+    /// Registered table name, when the scan could be attributed to one
+    pub table: Option<String>,
+    /// Scan kind as printed by Polars ("Csv", "DF", ...)
+    pub scan_type: String,
+    /// Number of columns the scan reads after projection pushdown
+    pub columns_projected: Option<usize>,
+    pub total_columns: Option<usize>,
+    /// Names of the columns read (all columns when nothing was pruned)
+    pub columns_read: Vec<String>,
+    /// Predicates pushed down into the scan
+    pub predicates: Vec<String>,
+}
+
+/// Timing of one executed plan node
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeReport {
+    pub node: String,
+    pub start_us: u64,
+    pub end_us: u64,
+}
+
+/// Output row count of one logical plan node
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeRows {
+    /// Node kind: "scan", "filter", "select", "join", "group_by", ...
+    pub node: String,
+    /// Nesting level below the root (0); children follow their subtree
+    pub depth: usize,
+    pub rows: usize,
+}
+
+/// Pushdown and execution report for one query
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryReport {
+    pub optimized_plan: String,
+    pub scans: Vec<ScanReport>,
+    pub nodes: Vec<NodeReport>,
+    /// Row counts per plan node, children before their parent
+    pub node_rows: Vec<NodeRows>,
+    pub output_rows: usize,
+}
+
+/// SQL session over lazily scanned files and in-memory frames
+///
+/// Files are registered as lazily scanned tables, so Polars' projection and
+/// predicate pushdown decide which columns and rows are actually read.
+///
+/// `sql` results are cached, keyed by the normalized query text and the
+/// versions of the tables it mentions. Re-registering a table, or editing
+/// a registered file, means the next query misses.
+///
+/// The outermost ORDER BY compares text with the session collation; under
+/// the default binary collation Polars sorts as usual.
+pub struct QuerySession {
+    context: SQLContext,
+    tables: BTreeMap<String, RegisteredTable>,
+    next_version: u64,
+    cache: QueryCache,
+    collation: Collation,
+}
+
+impl QuerySession {
+    /// Create an empty session with the default result cache
+    pub fn new() -> Self {
+        Self::with_cache(QueryCacheConfig::default())
+    }
+
+    /// Create an empty session with the given result cache limits
+    pub fn with_cache(config: QueryCacheConfig) -> Self {
+        Self {
+            context: SQLContext::new(),
+            tables: BTreeMap::new(),
+            next_version: 0,
+            cache: QueryCache::new(config),
+            collation: Collation::Binary,
+        }
+    }
+
+    pub fn collation(&self) -> &Collation {
+        &self.collation
+    }
+
+    /// Change the string ordering of ORDER BY; cached results are dropped
+    pub fn set_collation(&mut self, collation: Collation) {
+        if collation != self.collation {
+            self.cache.clear();
+            self.collation = collation;
+        }
+    }
+
+    /// Record a (re-)registered table and drop its cached results
+    fn add_table(&mut self, name: &str, source: Option<String>, schema: SchemaRef) {
+        self.next_version += 1;
+        self.cache.invalidate_table(name);
+        self.tables.insert(name.to_string(), RegisteredTable {
+            name: name.to_string(),
+            source,
+            schema,
+            version: self.next_version,
+        });
+    }
+
+    /// Register a CSV file as a lazily scanned table
+    pub fn register_csv(
+        &mut self,
+        name: &str,
+        file_path: &str,
+        has_header: bool,
+        delimiter: u8,
+    ) -> Result<(), InsightoraError> {
+        let path = check_path_allowed(file_path)?;
+        if !path.exists() {
+            return Err(InsightoraError::IoError(
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("File not found: {}", file_path)
+                )
+            ));
+        }
+
+        let lf = LazyCsvReader::new(&path)
+            .has_header(has_header)
+            .with_separator(delimiter)
+            .finish()?;
+        let schema = lf.schema()?;
+
+        self.context.register(name, lf);
+        self.add_table(name, Some(file_path.to_string()), schema);
+        Ok(())
+    }
+
+    /// Register an in-memory DataFrame as a table
+    pub fn register_frame(&mut self, name: &str, df: DataFrame) -> Result<(), InsightoraError> {
+        let schema = Arc::new(df.schema());
+        self.context.register(name, df.lazy());
+        self.add_table(name, None, schema);
+        Ok(())
+    }
+
+    /// Registered tables in name order
+    pub fn tables(&self) -> impl Iterator<Item = &RegisteredTable> {
+        self.tables.values()
+    }
+
+    /// Build the lazy plan for a query without executing it
+    fn plan(&mut self, query: &str) -> Result<LazyFrame, InsightoraError> {
+        self.context
+            .execute(query)
+            .map_err(|e| InsightoraError::ValidationError(format!("Invalid SQL query: {}", e)))
+    }
+
+    /// Execute a query, answering from the result cache when possible
+    pub fn sql(&mut self, query: &str) -> Result<DataFrame, InsightoraError> {
+        if !self.cache.config().is_enabled() {
+            return self.sql_uncached(query);
+        }
+        let key = self.cache_key(query);
+        if let Some(df) = self.cache.get(&key) {
+            return Ok(df);
+        }
+        let df = self.sql_uncached(query)?;
+        self.cache.insert(key, &df);
+        Ok(df)
+    }
+
+    /// Execute a query without reading or filling the result cache
+    pub fn sql_uncached(&mut self, query: &str) -> Result<DataFrame, InsightoraError> {
+        match self.collated_order(query)? {
+            Some(order) => {
+                let df = self.plan(&order.body)?.collect()?;
+                order.apply(df, &self.collation)
+            }
+            None => Ok(self.plan(query)?.collect()?),
+        }
+    }
+
+    /// The outermost ORDER BY of a query, when it has to run with a
+    /// non-binary collation instead of Polars' byte-wise sort
+    fn collated_order(&self, query: &str) -> Result<Option<OrderClause>, InsightoraError> {
+        if self.collation == Collation::Binary {
+            return Ok(None);
+        }
+        split_order_by(query)
+    }
+
+    /// Cache counters and current size
+    pub fn cache_stats(&self) -> QueryCacheStats {
+        self.cache.stats()
+    }
+
+    pub fn cache_config(&self) -> QueryCacheConfig {
+        self.cache.config()
+    }
+
+    /// Change the cache limits; entries over the new limits are evicted
+    pub fn set_cache_config(&mut self, config: QueryCacheConfig) {
+        self.cache.reconfigure(config);
+    }
+
+    /// Drop every cached result
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+
+    /// Cache key of a query: its normalized text and every registered table it mentions
+    fn cache_key(&self, query: &str) -> QueryCacheKey {
+        let referenced = referenced_identifiers(query);
+        let tables = self
+            .tables
+            .values()
+            .filter(|table| referenced.contains(&table.name.to_lowercase()))
+            .map(|table| TableVersion {
+                name: table.name.clone(),
+                version: table.version,
+                file_stamp: table.source.as_ref().and_then(|path| {
+                    let metadata = std::fs::metadata(path).ok()?;
+                    Some((metadata.modified().ok()?, metadata.len()))
+                }),
+            })
+            .collect();
+        QueryCacheKey { query: normalize_query(query), tables }
+    }
+
+    /// Return the logical plan of a query as text
+    ///
+    /// With `optimized` the plan is shown after projection/predicate pushdown,
+    /// which is what actually runs.
+    pub fn explain(&mut self, query: &str, optimized: bool) -> Result<String, InsightoraError> {
+        let lf = self.plan(query)?;
+        if optimized {
+            Ok(lf.describe_optimized_plan()?)
+        } else {
+            Ok(lf.describe_plan())
+        }
+    }
+
+    /// Execute a query and report what the optimizer pushed into each scan
+    ///
+    /// Columns and predicates are taken from the optimized plan, one entry per
+    /// scan, so joins and subqueries over the same table appear separately.
+    /// Node timings come from Polars' profiler; a collated ORDER BY is
+    /// reported as a final "collated sort" node.
+    ///
+    /// Row counts come from running the logical plan once more, node by node
+    /// over the materialized output of each node's inputs.
+    pub fn sql_with_report(&mut self, query: &str) -> Result<(DataFrame, QueryReport), InsightoraError> {
+        let order = self.collated_order(query)?;
+        let lf = self.plan(order.as_ref().map_or(query, |order| order.body.as_str()))?;
+        let optimized_plan = lf.clone().describe_optimized_plan()?;
+        let logical_plan = lf.logical_plan.clone();
+        let (df, timings) = lf.profile()?;
+
+        let mut node_rows = Vec::new();
+        count_node_rows(logical_plan, usize::from(order.is_some()), &mut node_rows)?;
+
+        let referenced = referenced_identifiers(query);
+        let scans = parse_scans(&optimized_plan)
+            .into_iter()
+            .map(|scan| self.attribute_scan(scan, &referenced))
+            .collect();
+
+        let names = timings.column("node")?.str()?;
+        let starts = timings.column("start")?.cast(&DataType::UInt64)?;
+        let ends = timings.column("end")?.cast(&DataType::UInt64)?;
+        let mut nodes: Vec<NodeReport> = names
+            .into_iter()
+            .zip(starts.u64()?)
+            .zip(ends.u64()?)
+            .map(|((node, start), end)| NodeReport {
+                node: node.unwrap_or_default().to_string(),
+                start_us: start.unwrap_or(0),
+                end_us: end.unwrap_or(0),
+            })
+            .collect();
+
+        let df = match order {
+            Some(order) => {
+                let start_us = nodes.iter().map(|n| n.end_us).max().unwrap_or(0);
+                let started = std::time::Instant::now();
+                let sorted = order.apply(df, &self.collation)?;
+                nodes.push(NodeReport {
+                    node: "collated sort".to_string(),
+                    start_us,
+                    end_us: start_us + started.elapsed().as_micros() as u64,
+                });
+                node_rows.push(NodeRows {
+                    node: "collated sort".to_string(),
+                    depth: 0,
+                    rows: sorted.height(),
+                });
+                sorted
+            }
+            None => df,
+        };
+
+        let report = QueryReport {
+            optimized_plan,
+            scans,
+            nodes,
+            node_rows,
+            output_rows: df.height(),
+        };
+        Ok((df, report))
+    }
+
+    /// Match a parsed scan to its registered table and resolve column names
+    fn attribute_scan(&self, scan: ParsedScan, referenced: &BTreeSet<String>) -> ScanReport {
+        let table = match &scan.source {
+            ScanSource::File(path) => self
+                .tables
+                .values()
+                .find(|t| t.source.as_deref() == Some(path.as_str())),
+            ScanSource::Memory(leading_columns) => {
+                // Polars prints the first few column names of in-memory frames
+                let mut candidates = self.tables.values().filter(|t| {
+                    t.source.is_none()
+                        && t.schema.len() == scan.total_columns.unwrap_or(t.schema.len())
+                        && t.schema.iter_names().zip(leading_columns).all(|(a, b)| a.as_str() == b.as_str())
+                });
+                match (candidates.next(), candidates.next()) {
+                    (Some(only), None) => Some(only),
+                    _ => None,
+                }
+            }
+        };
+
+        let columns_read = match table {
+            Some(table) => {
+                let all: Vec<String> = table.schema.iter_names().map(|n| n.to_string()).collect();
+                let pruned = scan.columns_projected.is_some()
+                    && scan.columns_projected != scan.total_columns;
+                if pruned {
+                    all.into_iter().filter(|c| referenced.contains(&c.to_lowercase())).collect()
+                } else {
+                    all
+                }
+            }
+            None => Vec::new(),
+        };
+
+        ScanReport {
+            table: table.map(|t| t.name.clone()),
+            scan_type: scan.scan_type,
+            columns_projected: scan.columns_projected,
+            total_columns: scan.total_columns,
+            columns_read,
+            predicates: scan.predicates,
+        }
+    }
+}
+
+impl Default for QuerySession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================================================
+// Node Row Counts
+// ============================================================================
+
+/// Execute a logical plan bottom-up, recording each node's output height
+///
+/// Every input is replaced by its materialized result before the node runs,
+/// so each node executes once. Returns the node's output.
+fn count_node_rows(
+    mut plan: LogicalPlan,
+    depth: usize,
+    counts: &mut Vec<NodeRows>,
+) -> Result<DataFrame, InsightoraError> {
+    let node = plan_node_name(&plan);
+    for input in plan_inputs(&mut plan) {
+        let output = count_node_rows(input.clone(), depth + 1, counts)?;
+        *input = output.lazy().logical_plan;
+    }
+    let df = LazyFrame::from(plan).collect()?;
+    counts.push(NodeRows { node: node.to_string(), depth, rows: df.height() });
+    Ok(df)
+}
+
+fn plan_node_name(plan: &LogicalPlan) -> &'static str {
+    match plan {
+        LogicalPlan::Scan { .. } | LogicalPlan::DataFrameScan { .. } => "scan",
+        LogicalPlan::Selection { .. } => "filter",
+        LogicalPlan::Projection { .. } => "select",
+        LogicalPlan::HStack { .. } => "with_columns",
+        LogicalPlan::Aggregate { .. } => "group_by",
+        LogicalPlan::Join { .. } => "join",
+        LogicalPlan::Distinct { .. } => "distinct",
+        LogicalPlan::Sort { .. } => "sort",
+        LogicalPlan::Slice { .. } => "slice",
+        LogicalPlan::MapFunction { .. } => "map",
+        LogicalPlan::Union { .. } => "union",
+        LogicalPlan::Cache { .. } => "cache",
+        LogicalPlan::ExtContext { .. } => "context",
+        _ => "other",
+    }
+}
+
+/// Inputs of a plan node; nodes not listed here run as one unit with their
+/// whole subtree
+fn plan_inputs(plan: &mut LogicalPlan) -> Vec<&mut LogicalPlan> {
+    match plan {
+        LogicalPlan::Selection { input, .. }
+        | LogicalPlan::Projection { input, .. }
+        | LogicalPlan::HStack { input, .. }
+        | LogicalPlan::Aggregate { input, .. }
+        | LogicalPlan::Distinct { input, .. }
+        | LogicalPlan::Sort { input, .. }
+        | LogicalPlan::Slice { input, .. }
+        | LogicalPlan::MapFunction { input, .. }
+        | LogicalPlan::Cache { input, .. }
+        | LogicalPlan::ExtContext { input, .. } => vec![input.as_mut()],
+        LogicalPlan::Join { input_left, input_right, .. } => vec![input_left.as_mut(), input_right.as_mut()],
+        LogicalPlan::Union { inputs, .. } => inputs.iter_mut().collect(),
+        _ => Vec::new(),
+    }
+}
+
+// ============================================================================
+// Collated ORDER BY
+// ============================================================================
+
+/// A query's outermost ORDER BY, split off so it can run with a collation
+#[derive(Debug, Clone, PartialEq)]
+struct OrderClause {
+    /// Query text before ORDER BY
+    body: String,
+    /// Sort keys as written (output column name or 1-based position) and
+    /// whether each is descending
+    keys: Vec<(String, bool)>,
+    limit: Option<usize>,
+    offset: usize,
+}
+
+impl OrderClause {
+    /// Sort the result of `body` by the keys, then apply OFFSET and LIMIT
+    fn apply(&self, df: DataFrame, collation: &Collation) -> Result<DataFrame, InsightoraError> {
+        let names = df.get_column_names();
+        let by = self
+            .keys
+            .iter()
+            .map(|(key, _)| resolve_order_key(&names, key))
+            .collect::<Result<Vec<_>, _>>()?;
+        let descending: Vec<bool> = self.keys.iter().map(|(_, descending)| *descending).collect();
+        let sorted = sort_data(&df, &by, &descending, collation)?;
+        Ok(sorted.slice(self.offset as i64, self.limit.unwrap_or(usize::MAX)))
+    }
+}
+
+/// Map an ORDER BY key onto an output column: by 1-based position, exact
+/// name, or a name differing only in case
+fn resolve_order_key(names: &[&str], key: &str) -> Result<String, InsightoraError> {
+    if let Ok(position) = key.parse::<usize>() {
+        return match position.checked_sub(1).and_then(|index| names.get(index)) {
+            Some(name) => Ok(name.to_string()),
+            None => Err(InsightoraError::ValidationError(format!(
+                "ORDER BY position {} is out of range for {} output columns",
+                position,
+                names.len()
+            ))),
+        };
+    }
+    names
+        .iter()
+        .find(|name| **name == key)
+        .or_else(|| names.iter().find(|name| name.eq_ignore_ascii_case(key)))
+        .map(|name| name.to_string())
+        .ok_or_else(|| InsightoraError::ValidationError(format!(
+            "ORDER BY key '{}' must be an output column when a collation is set",
+            key
+        )))
+}
+
+/// Split the outermost ORDER BY (with any LIMIT/OFFSET after it) off a query
+///
+/// Returns None when the query has no top-level ORDER BY. Keys must be
+/// output column names (optionally table-qualified or double-quoted) or
+/// positions, each optionally followed by ASC or DESC.
+fn split_order_by(query: &str) -> Result<Option<OrderClause>, InsightoraError> {
+    let query = query.trim().trim_end_matches(';').trim_end();
+    let words = top_level_words(query);
+    let Some(order) = words.windows(2).rposition(|pair| {
+        pair[0].1.eq_ignore_ascii_case("order") && pair[1].1.eq_ignore_ascii_case("by")
+    }) else {
+        return Ok(None);
+    };
+
+    let keys_start = words[order + 1].0 + "by".len();
+    let mut keys_end = query.len();
+    let mut limit = None;
+    let mut offset = 0;
+    let tail = &words[order + 2..];
+    for (index, &(position, word)) in tail.iter().enumerate() {
+        let is_limit = word.eq_ignore_ascii_case("limit");
+        if !is_limit && !word.eq_ignore_ascii_case("offset") {
+            continue;
+        }
+        keys_end = keys_end.min(position);
+        let rows = tail
+            .get(index + 1)
+            .and_then(|(_, value)| value.parse::<usize>().ok())
+            .ok_or_else(|| InsightoraError::ValidationError(format!(
+                "{} must be followed by a row count",
+                word.to_uppercase()
+            )))?;
+        if is_limit {
+            limit = Some(rows);
+        } else {
+            offset = rows;
+        }
+    }
+
+    let keys = split_unquoted(&query[keys_start..keys_end], ',')
+        .into_iter()
+        .map(parse_order_key)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Some(OrderClause {
+        body: query[..words[order].0].to_string(),
+        keys,
+        limit,
+        offset,
+    }))
+}
+
+/// Parse `name [ASC|DESC]` into the key and its direction
+fn parse_order_key(key: &str) -> Result<(String, bool), InsightoraError> {
+    let key = key.trim();
+    let (name, descending) = match key.rsplit_once(char::is_whitespace) {
+        Some((name, direction)) if direction.eq_ignore_ascii_case("desc") => (name.trim_end(), true),
+        Some((name, direction)) if direction.eq_ignore_ascii_case("asc") => (name.trim_end(), false),
+        _ => (key, false),
+    };
+    if let Some(quoted) = name.strip_prefix('"').and_then(|n| n.strip_suffix('"')) {
+        return Ok((quoted.to_string(), descending));
+    }
+    let unqualified = name.rsplit('.').next().unwrap_or(name);
+    if unqualified.is_empty() || !unqualified.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(InsightoraError::ValidationError(format!(
+            "ORDER BY key '{}' must be an output column name or position when a collation is set",
+            key
+        )));
+    }
+    Ok((unqualified.to_string(), descending))
+}
+
+/// Bare words outside parentheses, string literals and quoted identifiers,
+/// with their byte offsets
+fn top_level_words(query: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut start: Option<usize> = None;
+
+    for (index, c) in query.char_indices() {
+        if let Some(open) = quote {
+            if c == open {
+                quote = None;
+            }
+            continue;
+        }
+        if c.is_alphanumeric() || c == '_' {
+            if start.is_none() && depth == 0 {
+                start = Some(index);
+            }
+            continue;
+        }
+        if let Some(word_start) = start.take() {
+            words.push((word_start, &query[word_start..index]));
+        }
+        match c {
+            '\'' | '"' => quote = Some(c),
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    if let Some(word_start) = start {
+        words.push((word_start, &query[word_start..]));
+    }
+    words
+}
+
+/// Split on a separator that is not inside quotes
+fn split_unquoted(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        match quote {
+            Some(open) if c == open => quote = None,
+            Some(_) => {}
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None if c == separator => {
+                parts.push(&text[start..index]);
+                start = index + c.len_utf8();
+            }
+            None => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+// ============================================================================
+// Plan Parsing
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum ScanSource {
+    File(String),
+    Memory(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ParsedScan {
+    scan_type: String,
+    source: ScanSource,
+    columns_projected: Option<usize>,
+    total_columns: Option<usize>,
+    predicates: Vec<String>,
+}
+
+/// Extract scans from Polars' plan text
+///
+/// File scans print as `<Type> SCAN <path>` followed by optional
+/// `PROJECT n/m COLUMNS` and `SELECTION: <expr>` lines; in-memory frames print
+/// on one line as `DF [...]; PROJECT n/m COLUMNS; SELECTION: <expr>`.
+fn parse_scans(plan: &str) -> Vec<ParsedScan> {
+    let mut scans: Vec<ParsedScan> = Vec::new();
+    // Index of the file scan whose detail lines are being read
+    let mut open_scan: Option<usize> = None;
+
+    for line in plan.lines().map(str::trim) {
+        if let Some(rest) = line.strip_prefix("DF ") {
+            let mut parts = rest.split("; ");
+            let columns = parts.next().map(parse_column_list).unwrap_or_default();
+            let mut scan = ParsedScan {
+                scan_type: "DF".to_string(),
+                source: ScanSource::Memory(columns),
+                columns_projected: None,
+                total_columns: None,
+                predicates: Vec::new(),
+            };
+            for part in parts {
+                apply_scan_detail(&mut scan, part);
+            }
+            scans.push(scan);
+            open_scan = None;
+        } else if let Some(position) = line.find(" SCAN ") {
+            scans.push(ParsedScan {
+                scan_type: line[..position].to_string(),
+                source: ScanSource::File(line[position + " SCAN ".len()..].trim().to_string()),
+                columns_projected: None,
+                total_columns: None,
+                predicates: Vec::new(),
+            });
+            open_scan = Some(scans.len() - 1);
+        } else if let Some(index) = open_scan {
+            if line.starts_with("PROJECT ") || line.starts_with("SELECTION:") {
+                apply_scan_detail(&mut scans[index], line);
+            } else {
+                open_scan = None;
+            }
+        }
+    }
+
+    scans
+}
+
+fn apply_scan_detail(scan: &mut ParsedScan, detail: &str) {
+    if let Some(counts) = detail.strip_prefix("PROJECT ").and_then(|d| d.strip_suffix(" COLUMNS")) {
+        if let Some((projected, total)) = counts.split_once('/') {
+            scan.columns_projected = projected.parse().ok();
+            scan.total_columns = total.parse().ok();
+        }
+    } else if let Some(predicate) = detail.strip_prefix("SELECTION:") {
+        let predicate = predicate.trim();
+        let predicate = predicate
+            .strip_prefix("Some(")
+            .and_then(|p| p.strip_suffix(')'))
+            .unwrap_or(predicate);
+        if !predicate.is_empty() && predicate != "None" {
+            scan.predicates.push(predicate.to_string());
+        }
+    }
+}
+
+fn parse_column_list(list: &str) -> Vec<String> {
+    list.trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(',')
+        .map(|c| c.trim().trim_matches('"').to_string())
+        .filter(|c| !c.is_empty() && c != "...")
+        .collect()
+}
+
+/// Lower-cased identifiers (bare or double-quoted) appearing in a query
+fn referenced_identifiers(query: &str) -> BTreeSet<String> {
+    let mut identifiers = BTreeSet::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut in_string = false;
+
+    for c in query.chars() {
+        match c {
+            '\'' if !in_quotes => in_string = !in_string,
+            '"' if !in_string => {
+                in_quotes = !in_quotes;
+                if !in_quotes && !current.is_empty() {
+                    identifiers.insert(std::mem::take(&mut current).to_lowercase());
+                }
+            }
+            _ if in_string => {}
+            _ if in_quotes => current.push(c),
+            _ if c.is_alphanumeric() || c == '_' => current.push(c),
+            _ => {
+                if !current.is_empty() {
+                    identifiers.insert(std::mem::take(&mut current).to_lowercase());
+                }
+            }
+        }
+    }
+    if !current.is_empty() {
+        identifiers.insert(current.to_lowercase());
+    }
+    identifiers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn create_orders_csv() -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "order_id,customer,amount,country,notes").unwrap();
+        writeln!(file, "1,alice,120,DE,x").unwrap();
+        writeln!(file, "2,bob,80,FR,y").unwrap();
+        writeln!(file, "3,carol,300,DE,z").unwrap();
+        file
+    }
+
+    #[test]
+    fn test_parse_scans_from_plan_text() {
+        let plan = "\
+SELECT [col(\"amount\")] FROM
+  Csv SCAN /data/orders.csv
+  PROJECT 2/5 COLUMNS
+  SELECTION: [(col(\"country\")) == (String(DE))]
+  DF [\"id\", \"name\"]; PROJECT */2 COLUMNS; SELECTION: None";
+        let scans = parse_scans(plan);
+        assert_eq!(scans.len(), 2);
+        assert_eq!(scans[0].source, ScanSource::File("/data/orders.csv".to_string()));
+        assert_eq!(scans[0].columns_projected, Some(2));
+        assert_eq!(scans[0].total_columns, Some(5));
+        assert_eq!(scans[0].predicates, vec!["[(col(\"country\")) == (String(DE))]"]);
+        assert_eq!(scans[1].source, ScanSource::Memory(vec!["id".to_string(), "name".to_string()]));
+        assert!(scans[1].predicates.is_empty());
+    }
+
+    #[test]
+    fn test_sql_and_explain() {
+        let file = create_orders_csv();
+        let mut session = QuerySession::new();
+        session.register_csv("orders", file.path().to_str().unwrap(), true, b',').unwrap();
+
+        let df = session.sql("SELECT customer FROM orders WHERE country = 'DE'").unwrap();
+        assert_eq!(df.height(), 2);
+
+        let plan = session.explain("SELECT customer FROM orders WHERE country = 'DE'", true).unwrap();
+        assert!(plan.contains("SCAN"));
+    }
+
+    #[test]
+    fn test_report_pushdown_for_join() {
+        let file = create_orders_csv();
+        let mut session = QuerySession::new();
+        session.register_csv("orders", file.path().to_str().unwrap(), true, b',').unwrap();
+        session.register_frame("customers", df!(
+            "customer" => &["alice", "bob", "carol"],
+            "tier" => &["gold", "silver", "gold"]
+        ).unwrap()).unwrap();
+
+        let (df, report) = session.sql_with_report(
+            "SELECT o.customer, c.tier FROM orders o JOIN customers c ON o.customer = c.customer WHERE o.amount > 100"
+        ).unwrap();
+
+        assert_eq!(df.height(), 2);
+        assert_eq!(report.output_rows, 2);
+        let orders_scan = report.scans.iter()
+            .find(|s| s.table.as_deref() == Some("orders"))
+            .expect("orders scan reported");
+        assert!(orders_scan.columns_read.contains(&"customer".to_string()));
+        assert!(!orders_scan.columns_read.contains(&"notes".to_string()));
+        assert!(!orders_scan.predicates.is_empty());
+        assert!(!report.nodes.is_empty());
+    }
+
+    #[test]
+    fn test_report_counts_rows_per_node() {
+        let file = create_orders_csv();
+        let mut session = QuerySession::new();
+        session.register_csv("orders", file.path().to_str().unwrap(), true, b',').unwrap();
+        session.register_frame("customers", df!(
+            "customer" => &["alice", "bob", "carol", "dave"],
+            "tier" => &["gold", "silver", "gold", "bronze"]
+        ).unwrap()).unwrap();
+
+        let (_, report) = session.sql_with_report(
+            "SELECT o.customer, c.tier FROM orders o JOIN customers c ON o.customer = c.customer WHERE o.amount > 100"
+        ).unwrap();
+        let rows = |node: &str| -> Vec<usize> {
+            report.node_rows.iter().filter(|n| n.node == node).map(|n| n.rows).collect()
+        };
+
+        let mut scans = rows("scan");
+        scans.sort_unstable();
+        assert_eq!(scans, [3, 4]);
+        assert_eq!(rows("join"), [3]);
+        assert_eq!(rows("filter"), [2]);
+        let root = report.node_rows.last().unwrap();
+        assert_eq!((root.depth, root.rows), (0, report.output_rows));
+        assert_eq!(report.node_rows.iter().filter(|n| n.depth == 0).count(), 1);
+    }
+
+    #[test]
+    fn test_cache_misses_after_reregistering() {
+        let mut session = QuerySession::new();
+        session.register_frame("orders", df!("amount" => [10i64, 20]).unwrap()).unwrap();
+        let total = |session: &mut QuerySession, query: &str| {
+            session.sql(query).unwrap().column("total").unwrap().i64().unwrap().get(0)
+        };
+
+        assert_eq!(total(&mut session, "SELECT SUM(amount) AS total FROM orders"), Some(30));
+        assert_eq!(total(&mut session, "SELECT SUM(amount)  AS total\nFROM orders;"), Some(30));
+        assert_eq!((session.cache_stats().hits, session.cache_stats().misses), (1, 1));
+
+        session.register_frame("orders", df!("amount" => [1i64, 2, 3]).unwrap()).unwrap();
+        assert_eq!(session.cache_stats().entries, 0);
+        assert_eq!(total(&mut session, "SELECT SUM(amount) AS total FROM orders"), Some(6));
+        assert_eq!((session.cache_stats().hits, session.cache_stats().misses), (1, 2));
+
+        session.sql_uncached("SELECT SUM(amount) AS total FROM orders").unwrap();
+        assert_eq!((session.cache_stats().hits, session.cache_stats().misses), (1, 2));
+    }
+
+    #[test]
+    fn test_order_by_uses_session_collation() {
+        let mut session = QuerySession::new();
+        session.register_frame("people", df!(
+            "name" => &["Zoe", "Äsa", "anna", "Örjan", "bertil", "Ola"],
+            "score" => &[1i64, 2, 3, 4, 5, 6]
+        ).unwrap()).unwrap();
+        let names = |session: &mut QuerySession, query: &str| -> Vec<String> {
+            session.sql(query).unwrap().column("name").unwrap().str().unwrap()
+                .into_no_null_iter().map(String::from).collect()
+        };
+        let query = "SELECT name, score FROM people ORDER BY name";
+
+        assert_eq!(names(&mut session, query), ["Ola", "Zoe", "anna", "bertil", "Äsa", "Örjan"]);
+
+        session.set_collation(Collation::from_options("locale", Some("sv_SE")).unwrap());
+        assert_eq!(names(&mut session, query), ["anna", "bertil", "Ola", "Zoe", "Äsa", "Örjan"]);
+        assert_eq!(
+            names(&mut session, "SELECT name FROM people ORDER BY 1 DESC LIMIT 2 OFFSET 1;"),
+            ["Äsa", "Zoe"]
+        );
+
+        session.set_collation(Collation::from_options("locale", Some("de_DE")).unwrap());
+        let (df, report) = session.sql_with_report(query).unwrap();
+        let names_de: Vec<&str> = df.column("name").unwrap().str().unwrap().into_no_null_iter().collect();
+        assert_eq!(names_de, ["anna", "Äsa", "bertil", "Ola", "Örjan", "Zoe"]);
+        assert_eq!(report.nodes.last().unwrap().node, "collated sort");
+
+        assert!(session.sql("SELECT name FROM people ORDER BY score").is_err());
+        assert!(session.sql("SELECT name FROM people ORDER BY LOWER(name)").is_err());
+    }
+
+    #[test]
+    fn test_split_order_by_keeps_inner_clauses() {
+        let order = split_order_by(
+            "SELECT * FROM (SELECT a FROM t ORDER BY a LIMIT 5) s WHERE b = 'order by' ORDER BY s.a DESC, \"B c\" LIMIT 3"
+        ).unwrap().unwrap();
+        assert_eq!(order.body, "SELECT * FROM (SELECT a FROM t ORDER BY a LIMIT 5) s WHERE b = 'order by' ");
+        assert_eq!(order.keys, vec![("a".to_string(), true), ("B c".to_string(), false)]);
+        assert_eq!((order.limit, order.offset), (Some(3), 0));
+        assert_eq!(split_order_by("SELECT a FROM (SELECT a FROM t ORDER BY a)").unwrap(), None);
+    }
+
+    #[test]
+    fn test_invalid_query() {
+        let mut session = QuerySession::new();
+        assert!(session.sql("SELECT * FROM missing").is_err());
+    }
+}
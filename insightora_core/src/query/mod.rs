@@ -1,5 +1,6 @@
 // Query execution module
 // Provides parallel query execution and optimization
 
+pub mod cache;
 pub mod executor;
 pub mod optimizer;
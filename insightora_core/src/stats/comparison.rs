@@ -0,0 +1,583 @@
+// Group comparisons
+// Side-by-side statistics and difference tests for two subsets of a DataFrame
+
+use std::collections::BTreeMap;
+use polars::prelude::*;
+use crate::error::InsightoraError;
+
+// ============================================================================
+// Result Types
+// ============================================================================
+
+/// Descriptive statistics for one side of a numeric comparison
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericSummary {
+    pub count: usize,
+    pub null_count: usize,
+    pub mean: Option<f64>,
+    pub std: Option<f64>,
+    pub min: Option<f64>,
+    pub median: Option<f64>,
+    pub max: Option<f64>,
+}
+
+/// Comparison of a numeric column between the two groups
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericComparison {
+    pub column: String,
+    pub group_a: NumericSummary,
+    pub group_b: NumericSummary,
+    /// mean(a) - mean(b)
+    pub mean_diff: Option<f64>,
+    /// Cohen's d using the size-weighted pooled standard deviation
+    pub cohens_d: Option<f64>,
+    /// Mann-Whitney U statistic for group a
+    pub mann_whitney_u: Option<f64>,
+    /// Two-sided p-value from the tie-corrected normal approximation
+    pub mann_whitney_p: Option<f64>,
+}
+
+/// Frequency of one category in each group
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategoryDelta {
+    pub value: String,
+    pub count_a: u64,
+    pub count_b: u64,
+    pub freq_a: f64,
+    pub freq_b: f64,
+    /// freq_a - freq_b
+    pub delta: f64,
+}
+
+/// Comparison of a categorical column between the two groups
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategoricalComparison {
+    pub column: String,
+    pub null_count_a: usize,
+    pub null_count_b: usize,
+    /// One entry per category, sorted by absolute delta (largest first)
+    pub categories: Vec<CategoryDelta>,
+    pub chi_square: Option<f64>,
+    pub degrees_of_freedom: usize,
+    pub p_value: Option<f64>,
+}
+
+/// Per-column comparison result
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnComparison {
+    Numeric(NumericComparison),
+    Categorical(CategoricalComparison),
+}
+
+impl ColumnComparison {
+    pub fn column(&self) -> &str {
+        match self {
+            ColumnComparison::Numeric(c) => &c.column,
+            ColumnComparison::Categorical(c) => &c.column,
+        }
+    }
+}
+
+/// Full comparison of two subsets
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupComparison {
+    pub split_column: String,
+    pub group_a: String,
+    pub group_b: String,
+    pub rows_a: usize,
+    pub rows_b: usize,
+    pub columns: Vec<ColumnComparison>,
+}
+
+// ============================================================================
+// Group Comparison
+// ============================================================================
+
+/// Compare two subsets of a DataFrame defined by values of `split_column`
+///
+/// Groups are matched against the string form of the split column, so
+/// `"1"` selects rows where an integer column equals 1. Rows with a null
+/// split value belong to neither group.
+///
+/// Numeric columns get describe statistics for each side, the mean
+/// difference, Cohen's d and a Mann-Whitney U test. String, boolean and
+/// categorical columns get per-category frequency deltas and a chi-square
+/// test of independence. When `columns` is None every other column with a
+/// supported type is compared.
+///
+/// # Arguments
+/// * `df` - Input DataFrame
+/// * `split_column` - Column whose values define the groups
+/// * `group_a` / `group_b` - Values selecting each side
+/// * `columns` - Optional subset of columns to compare
+///
+/// # Returns
+/// * `Result<GroupComparison>` - Per-column comparison, or an error when a
+///   group is empty or a requested column can't be compared
+pub fn compare_groups(
+    df: &DataFrame,
+    split_column: &str,
+    group_a: &str,
+    group_b: &str,
+    columns: Option<&[String]>,
+) -> Result<GroupComparison, InsightoraError> {
+    let split = df.column(split_column)?.cast(&DataType::String)?;
+    let split = split.str()?;
+    let frame_a = df.filter(&split.equal(group_a))?;
+    let frame_b = df.filter(&split.equal(group_b))?;
+
+    for (group, frame) in [(group_a, &frame_a), (group_b, &frame_b)] {
+        if frame.height() == 0 {
+            return Err(InsightoraError::ValidationError(format!(
+                "Group '{}' has no rows in column '{}'",
+                group, split_column
+            )));
+        }
+    }
+
+    let selected: Vec<String> = match columns {
+        Some(cols) => cols.to_vec(),
+        None => df
+            .get_columns()
+            .iter()
+            .filter(|s| s.name() != split_column && comparison_kind(s.dtype()).is_some())
+            .map(|s| s.name().to_string())
+            .collect(),
+    };
+
+    let mut results = Vec::with_capacity(selected.len());
+    for name in &selected {
+        let a = frame_a.column(name)?;
+        let b = frame_b.column(name)?;
+        let comparison = match comparison_kind(a.dtype()) {
+            Some(Kind::Numeric) => ColumnComparison::Numeric(compare_numeric(name, a, b)?),
+            Some(Kind::Categorical) => ColumnComparison::Categorical(compare_categorical(name, a, b)?),
+            None => {
+                return Err(InsightoraError::InvalidDataType {
+                    expected: "numeric, string, boolean or categorical".to_string(),
+                    actual: format!("{} ({})", a.dtype(), name),
+                })
+            }
+        };
+        results.push(comparison);
+    }
+
+    Ok(GroupComparison {
+        split_column: split_column.to_string(),
+        group_a: group_a.to_string(),
+        group_b: group_b.to_string(),
+        rows_a: frame_a.height(),
+        rows_b: frame_b.height(),
+        columns: results,
+    })
+}
+
+enum Kind {
+    Numeric,
+    Categorical,
+}
+
+fn comparison_kind(dtype: &DataType) -> Option<Kind> {
+    if dtype.is_numeric() {
+        Some(Kind::Numeric)
+    } else {
+        match dtype {
+            DataType::String | DataType::Boolean | DataType::Categorical(_, _) => Some(Kind::Categorical),
+            _ => None,
+        }
+    }
+}
+
+/// Non-null, non-NaN values of a numeric series as f64
+fn finite_values(series: &Series) -> Result<Vec<f64>, InsightoraError> {
+    let cast = series.cast(&DataType::Float64)?;
+    Ok(cast.f64()?.into_iter().flatten().filter(|v| !v.is_nan()).collect())
+}
+
+fn summarize(values: &[f64], total: usize) -> NumericSummary {
+    let count = values.len();
+    let mut summary = NumericSummary {
+        count,
+        null_count: total - count,
+        mean: None,
+        std: None,
+        min: None,
+        median: None,
+        max: None,
+    };
+    if count == 0 {
+        return summary;
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mean = sorted.iter().sum::<f64>() / count as f64;
+    summary.mean = Some(mean);
+    if count > 1 {
+        let ss: f64 = sorted.iter().map(|v| (v - mean) * (v - mean)).sum();
+        summary.std = Some((ss / (count - 1) as f64).sqrt());
+    }
+    summary.min = sorted.first().copied();
+    summary.max = sorted.last().copied();
+    summary.median = Some(if count % 2 == 1 {
+        sorted[count / 2]
+    } else {
+        (sorted[count / 2 - 1] + sorted[count / 2]) / 2.0
+    });
+    summary
+}
+
+fn compare_numeric(name: &str, a: &Series, b: &Series) -> Result<NumericComparison, InsightoraError> {
+    let values_a = finite_values(a)?;
+    let values_b = finite_values(b)?;
+    let group_a = summarize(&values_a, a.len());
+    let group_b = summarize(&values_b, b.len());
+
+    let mean_diff = match (group_a.mean, group_b.mean) {
+        (Some(ma), Some(mb)) => Some(ma - mb),
+        _ => None,
+    };
+
+    // Pool variances weighted by degrees of freedom so a small group doesn't
+    // count as much as a large one
+    let (na, nb) = (values_a.len() as f64, values_b.len() as f64);
+    let cohens_d = match (mean_diff, group_a.std, group_b.std) {
+        (Some(diff), Some(sa), Some(sb)) => {
+            let pooled = (((na - 1.0) * sa * sa + (nb - 1.0) * sb * sb) / (na + nb - 2.0)).sqrt();
+            (pooled > 0.0).then_some(diff / pooled)
+        }
+        _ => None,
+    };
+
+    let (mann_whitney_u, mann_whitney_p) = match mann_whitney(&values_a, &values_b) {
+        Some((u, p)) => (Some(u), Some(p)),
+        None => (None, None),
+    };
+
+    Ok(NumericComparison {
+        column: name.to_string(),
+        group_a,
+        group_b,
+        mean_diff,
+        cohens_d,
+        mann_whitney_u,
+        mann_whitney_p,
+    })
+}
+
+/// Mann-Whitney U for sample a and its two-sided p-value
+///
+/// Uses average ranks for ties, the tie-corrected variance and a continuity
+/// correction on the normal approximation.
+fn mann_whitney(a: &[f64], b: &[f64]) -> Option<(f64, f64)> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+    let (na, nb) = (a.len() as f64, b.len() as f64);
+    let n = na + nb;
+
+    let mut combined: Vec<(f64, bool)> = a.iter().map(|&v| (v, true))
+        .chain(b.iter().map(|&v| (v, false)))
+        .collect();
+    combined.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+    let mut rank_sum_a = 0.0;
+    let mut tie_term = 0.0;
+    let mut i = 0;
+    while i < combined.len() {
+        let mut j = i;
+        while j + 1 < combined.len() && combined[j + 1].0 == combined[i].0 {
+            j += 1;
+        }
+        // Ranks are 1-based; tied values share the average rank
+        let avg_rank = (i + j + 2) as f64 / 2.0;
+        let ties = (j - i + 1) as f64;
+        tie_term += ties * ties * ties - ties;
+        rank_sum_a += combined[i..=j].iter().filter(|(_, in_a)| *in_a).count() as f64 * avg_rank;
+        i = j + 1;
+    }
+
+    let u = rank_sum_a - na * (na + 1.0) / 2.0;
+    let mu = na * nb / 2.0;
+    let variance = na * nb / 12.0 * ((n + 1.0) - tie_term / (n * (n - 1.0)));
+    if variance <= 0.0 {
+        // Every value is identical
+        return Some((u, 1.0));
+    }
+    let z = ((u - mu).abs() - 0.5).max(0.0) / variance.sqrt();
+    Some((u, (2.0 * normal_sf(z)).min(1.0)))
+}
+
+fn category_counts(series: &Series) -> Result<(BTreeMap<String, u64>, usize), InsightoraError> {
+    let cast = series.cast(&DataType::String)?;
+    let mut counts = BTreeMap::new();
+    let mut nulls = 0;
+    for value in cast.str()?.into_iter() {
+        match value {
+            Some(v) => *counts.entry(v.to_string()).or_insert(0) += 1,
+            None => nulls += 1,
+        }
+    }
+    Ok((counts, nulls))
+}
+
+fn compare_categorical(name: &str, a: &Series, b: &Series) -> Result<CategoricalComparison, InsightoraError> {
+    let (counts_a, null_count_a) = category_counts(a)?;
+    let (counts_b, null_count_b) = category_counts(b)?;
+    let total_a: u64 = counts_a.values().sum();
+    let total_b: u64 = counts_b.values().sum();
+
+    let mut values: Vec<&String> = counts_a.keys().chain(counts_b.keys()).collect();
+    values.sort();
+    values.dedup();
+
+    let freq = |count: u64, total: u64| if total == 0 { 0.0 } else { count as f64 / total as f64 };
+    let mut categories: Vec<CategoryDelta> = values
+        .iter()
+        .map(|value| {
+            let count_a = counts_a.get(*value).copied().unwrap_or(0);
+            let count_b = counts_b.get(*value).copied().unwrap_or(0);
+            let freq_a = freq(count_a, total_a);
+            let freq_b = freq(count_b, total_b);
+            CategoryDelta {
+                value: (*value).clone(),
+                count_a,
+                count_b,
+                freq_a,
+                freq_b,
+                delta: freq_a - freq_b,
+            }
+        })
+        .collect();
+
+    // 2 x k contingency table test; undefined when either side is empty or
+    // there is only one category
+    let degrees_of_freedom = categories.len().saturating_sub(1);
+    let (chi_square, p_value) = if total_a == 0 || total_b == 0 || degrees_of_freedom == 0 {
+        (None, None)
+    } else {
+        let grand = (total_a + total_b) as f64;
+        let chi: f64 = categories
+            .iter()
+            .map(|c| {
+                let col_total = (c.count_a + c.count_b) as f64;
+                let exp_a = total_a as f64 * col_total / grand;
+                let exp_b = total_b as f64 * col_total / grand;
+                (c.count_a as f64 - exp_a).powi(2) / exp_a + (c.count_b as f64 - exp_b).powi(2) / exp_b
+            })
+            .sum();
+        (Some(chi), Some(chi_square_sf(chi, degrees_of_freedom as f64)))
+    };
+
+    categories.sort_by(|x, y| y.delta.abs().total_cmp(&x.delta.abs()).then_with(|| x.value.cmp(&y.value)));
+
+    Ok(CategoricalComparison {
+        column: name.to_string(),
+        null_count_a,
+        null_count_b,
+        categories,
+        chi_square,
+        degrees_of_freedom,
+        p_value,
+    })
+}
+
+// ============================================================================
+// Distribution Functions
+// ============================================================================
+
+/// Upper tail of the standard normal distribution
+fn normal_sf(z: f64) -> f64 {
+    0.5 * erfc(z / std::f64::consts::SQRT_2)
+}
+
+/// Complementary error function (Chebyshev fit, relative error < 1.2e-7)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t * (-z * z - 1.265_512_23
+        + t * (1.000_023_68
+        + t * (0.374_091_96
+        + t * (0.096_784_18
+        + t * (-0.186_288_06
+        + t * (0.278_868_07
+        + t * (-1.135_203_98
+        + t * (1.488_515_87
+        + t * (-0.822_152_23
+        + t * 0.170_872_77)))))))))
+        .exp();
+    if x >= 0.0 { r } else { 2.0 - r }
+}
+
+/// Upper tail of the chi-square distribution
+pub(crate) fn chi_square_sf(x: f64, dof: f64) -> f64 {
+    regularized_gamma_q(dof / 2.0, x / 2.0)
+}
+
+/// Natural log of the gamma function (Lanczos approximation)
+pub(crate) fn ln_gamma(x: f64) -> f64 {
+    const COEFFS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let a = COEFFS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFS[0], |acc, (i, c)| acc + c / (x + i as f64 + 1.0));
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + a.ln()
+}
+
+/// Regularized upper incomplete gamma function Q(a, x)
+fn regularized_gamma_q(a: f64, x: f64) -> f64 {
+    const EPS: f64 = 1e-14;
+    const TINY: f64 = 1e-300;
+    const MAX_ITER: usize = 500;
+
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let log_prefix = -x + a * x.ln() - ln_gamma(a);
+
+    if x < a + 1.0 {
+        // Series for P(a, x)
+        let mut ap = a;
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        for _ in 0..MAX_ITER {
+            ap += 1.0;
+            term *= x / ap;
+            sum += term;
+            if term.abs() < sum.abs() * EPS {
+                break;
+            }
+        }
+        (1.0 - sum * log_prefix.exp()).max(0.0)
+    } else {
+        // Continued fraction for Q(a, x) (modified Lentz)
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / TINY;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..MAX_ITER {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = b + an / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < EPS {
+                break;
+            }
+        }
+        (log_prefix.exp() * h).min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> DataFrame {
+        df! {
+            "segment" => ["a", "a", "a", "b", "b", "b", "b", "b"],
+            "spend" => [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0],
+            "plan" => ["pro", "pro", "free", "free", "free", "free", "pro", "free"],
+        }
+        .unwrap()
+    }
+
+    fn numeric(result: &GroupComparison, column: &str) -> NumericComparison {
+        match result.columns.iter().find(|c| c.column() == column) {
+            Some(ColumnComparison::Numeric(c)) => c.clone(),
+            other => panic!("expected numeric comparison, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_numeric_comparison_with_unequal_sizes() {
+        let result = compare_groups(&sample(), "segment", "a", "b", None).unwrap();
+        assert_eq!((result.rows_a, result.rows_b), (3, 5));
+
+        let spend = numeric(&result, "spend");
+        assert_eq!(spend.group_a.mean, Some(2.0));
+        assert_eq!(spend.group_b.mean, Some(6.0));
+        assert_eq!(spend.group_b.median, Some(6.0));
+        assert_eq!(spend.mean_diff, Some(-4.0));
+        // Pooled variance (2 * 1.0 + 4 * 2.5) / 6 = 2
+        let d = spend.cohens_d.unwrap();
+        assert!((d - (-4.0 / 2f64.sqrt())).abs() < 1e-12);
+        // Every value in a ranks below every value in b
+        assert_eq!(spend.mann_whitney_u, Some(0.0));
+    }
+
+    #[test]
+    fn test_mann_whitney_p_value() {
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let b = [6.0, 7.0, 8.0, 9.0, 10.0];
+        let (u, p) = mann_whitney(&a, &b).unwrap();
+        assert_eq!(u, 0.0);
+        assert!(p > 0.01 && p < 0.015, "p = {}", p);
+
+        let (_, p_same) = mann_whitney(&a, &a).unwrap();
+        assert!(p_same > 0.9);
+    }
+
+    #[test]
+    fn test_categorical_comparison() {
+        let mut segment = vec!["a"; 40];
+        segment.extend(vec!["b"; 40]);
+        let mut plan = vec!["x"; 30];
+        plan.extend(vec!["y"; 10]);
+        plan.extend(vec!["x"; 10]);
+        plan.extend(vec!["y"; 30]);
+        let df = df! { "segment" => segment, "plan" => plan }.unwrap();
+
+        let result = compare_groups(&df, "segment", "a", "b", None).unwrap();
+        let plan = match &result.columns[0] {
+            ColumnComparison::Categorical(c) => c,
+            other => panic!("expected categorical comparison, got {:?}", other),
+        };
+        assert_eq!(plan.degrees_of_freedom, 1);
+        assert!((plan.chi_square.unwrap() - 20.0).abs() < 1e-9);
+        assert!(plan.p_value.unwrap() < 1e-4);
+        assert!((plan.categories[0].delta.abs() - 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_distribution_functions() {
+        assert!((normal_sf(1.959_964) - 0.025).abs() < 1e-6);
+        assert!((chi_square_sf(3.841_459, 1.0) - 0.05).abs() < 1e-6);
+        assert!((chi_square_sf(18.307_04, 10.0) - 0.05).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_empty_group_and_column_errors() {
+        let df = sample();
+        let err = compare_groups(&df, "segment", "a", "missing", None).unwrap_err();
+        assert!(err.to_string().contains("'missing' has no rows"));
+
+        let cols = vec!["nope".to_string()];
+        assert!(compare_groups(&df, "segment", "a", "b", Some(&cols)).is_err());
+        assert!(compare_groups(&df, "nope", "a", "b", None).is_err());
+    }
+}
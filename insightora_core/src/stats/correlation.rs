@@ -1,2 +1,702 @@
-// Correlation analysis implementation
-// TODO: Implement in task 14
+// Correlation analysis
+// Pearson, Spearman and percentage-bend correlation; classical and MCD robust covariance
+
+use polars::prelude::*;
+use crate::error::InsightoraError;
+use crate::stats::comparison::chi_square_sf;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Default bend constant of the percentage-bend correlation
+pub const DEFAULT_BEND: f64 = 0.2;
+
+/// Random (p + 1)-point starts tried by the MCD search
+const RANDOM_STARTS: usize = 30;
+
+/// Best starts (by determinant) refined until convergence
+const REFINED_STARTS: usize = 5;
+
+/// Concentration steps applied to every start before picking the best
+const INITIAL_C_STEPS: usize = 2;
+
+const MAX_C_STEPS: usize = 100;
+
+/// Rows beyond this chi-square quantile of the robust distance are excluded
+const REWEIGHT_QUANTILE: f64 = 0.975;
+
+// ============================================================================
+// Result Types
+// ============================================================================
+
+/// Correlation coefficient
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CorrelationMethod {
+    Pearson,
+    /// Pearson on average ranks
+    Spearman,
+    /// Wilcox's percentage-bend correlation; `beta` (0 < beta <= 0.5) is the
+    /// share of values per column allowed to be bent towards the center
+    PercentageBend { beta: f64 },
+}
+
+impl CorrelationMethod {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "pearson" => Ok(CorrelationMethod::Pearson),
+            "spearman" => Ok(CorrelationMethod::Spearman),
+            "pb" | "percentage_bend" => Ok(CorrelationMethod::PercentageBend { beta: DEFAULT_BEND }),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown correlation method '{}'; expected 'pearson', 'spearman' or 'pb'",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CorrelationMethod::Pearson => "pearson",
+            CorrelationMethod::Spearman => "spearman",
+            CorrelationMethod::PercentageBend { .. } => "pb",
+        }
+    }
+}
+
+/// Pairwise correlations over the rows where every selected column has a value
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorrelationMatrix {
+    pub columns: Vec<String>,
+    pub method: CorrelationMethod,
+    /// Row-major coefficients; None where a column has no spread
+    pub values: Vec<Vec<Option<f64>>>,
+    pub rows_used: usize,
+    /// Rows (by original position) with at least one value bent by the
+    /// percentage-bend estimator; empty for other methods
+    pub influential_rows: Vec<usize>,
+}
+
+impl CorrelationMatrix {
+    /// Coefficient between two columns
+    pub fn get(&self, a: &str, b: &str) -> Option<f64> {
+        let i = self.columns.iter().position(|c| c == a)?;
+        let j = self.columns.iter().position(|c| c == b)?;
+        self.values[i][j]
+    }
+}
+
+/// Settings for the minimum covariance determinant estimator
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RobustCovarianceConfig {
+    /// Share of rows (0.5 ..= 1.0) whose covariance determinant is minimized
+    pub support_fraction: f64,
+    /// Seed for the random starting subsets, so results are reproducible
+    pub seed: u64,
+}
+
+impl Default for RobustCovarianceConfig {
+    fn default() -> Self {
+        Self {
+            support_fraction: 0.75,
+            seed: 0x5eed_1234_abcd_0001,
+        }
+    }
+}
+
+/// Location and covariance over the rows where every selected column has a value
+#[derive(Debug, Clone, PartialEq)]
+pub struct CovarianceMatrix {
+    pub columns: Vec<String>,
+    /// Column means (robust location with MCD)
+    pub location: Vec<f64>,
+    /// Row-major covariances (n - 1 denominator)
+    pub values: Vec<Vec<f64>>,
+    pub rows_used: usize,
+    /// The MCD settings, or None for the classical estimate
+    pub robust: Option<RobustCovarianceConfig>,
+    /// Rows (by original position) left out of the reweighted robust estimate
+    pub excluded_rows: Vec<usize>,
+}
+
+impl CovarianceMatrix {
+    /// Covariance between two columns
+    pub fn get(&self, a: &str, b: &str) -> Option<f64> {
+        let i = self.columns.iter().position(|c| c == a)?;
+        let j = self.columns.iter().position(|c| c == b)?;
+        Some(self.values[i][j])
+    }
+
+    /// Correlations implied by the covariance; None where a variance is zero
+    pub fn to_correlation(&self) -> Vec<Vec<Option<f64>>> {
+        let sd: Vec<f64> = (0..self.columns.len()).map(|i| self.values[i][i].sqrt()).collect();
+        self.values
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(j, &c)| (sd[i] > 0.0 && sd[j] > 0.0).then(|| (c / (sd[i] * sd[j])).clamp(-1.0, 1.0)))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+// ============================================================================
+// Correlation
+// ============================================================================
+
+/// Correlation matrix of numeric columns
+///
+/// Rows with a null or non-finite value in any selected column are dropped
+/// first, so every coefficient is computed over the same rows.
+///
+/// # Arguments
+/// * `columns` - Columns to correlate (default: every numeric column)
+/// * `method` - Pearson, Spearman or percentage bend
+///
+/// # Returns
+/// * `Result<CorrelationMatrix>` - Coefficients plus the rows the estimator downweighted
+pub fn correlation(
+    df: &DataFrame,
+    columns: Option<&[String]>,
+    method: CorrelationMethod,
+) -> Result<CorrelationMatrix, InsightoraError> {
+    if let CorrelationMethod::PercentageBend { beta } = method {
+        if beta.is_nan() || beta <= 0.0 || beta > 0.5 {
+            return Err(InsightoraError::ValidationError(format!(
+                "Bend constant must be in (0, 0.5], got {}",
+                beta
+            )));
+        }
+    }
+    let (names, rows, data) = complete_cases(df, columns)?;
+
+    // Every method reduces to the cosine similarity of per-column scores
+    let mut bent = vec![false; rows.len()];
+    let scores: Vec<Option<Vec<f64>>> = data
+        .iter()
+        .map(|values| match method {
+            CorrelationMethod::Pearson => Some(centered(values)),
+            CorrelationMethod::Spearman => Some(centered(&average_ranks(values))),
+            CorrelationMethod::PercentageBend { beta } => {
+                let (scores, flags) = bend_scores(values, beta)?;
+                bent.iter_mut().zip(flags).for_each(|(b, f)| *b |= f);
+                Some(scores)
+            }
+        })
+        .collect();
+
+    let values = (0..names.len())
+        .map(|i| {
+            (0..names.len())
+                .map(|j| match (&scores[i], &scores[j]) {
+                    (Some(a), Some(b)) if i == j => cosine(a, b).map(|_| 1.0),
+                    (Some(a), Some(b)) => cosine(a, b),
+                    _ => None,
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(CorrelationMatrix {
+        columns: names,
+        method,
+        values,
+        rows_used: rows.len(),
+        influential_rows: rows.iter().zip(&bent).filter(|(_, b)| **b).map(|(r, _)| *r).collect(),
+    })
+}
+
+/// Column names, original row positions and per-column values of the complete rows
+type CompleteCases = (Vec<String>, Vec<usize>, Vec<Vec<f64>>);
+
+/// Selected numeric columns as f64 over complete rows, with the original row positions
+fn complete_cases(
+    df: &DataFrame,
+    columns: Option<&[String]>,
+) -> Result<CompleteCases, InsightoraError> {
+    let selected: Vec<&Series> = match columns {
+        Some(cols) => cols.iter().map(|c| df.column(c)).collect::<Result<_, _>>()?,
+        None => df.get_columns().iter().filter(|s| s.dtype().is_numeric()).collect(),
+    };
+    if selected.len() < 2 {
+        return Err(InsightoraError::ValidationError(
+            "Correlation needs at least two numeric columns".to_string()
+        ));
+    }
+
+    let mut raw = Vec::with_capacity(selected.len());
+    for series in &selected {
+        if !series.dtype().is_numeric() {
+            return Err(InsightoraError::InvalidDataType {
+                expected: "numeric".to_string(),
+                actual: format!("{} ({})", series.dtype(), series.name()),
+            });
+        }
+        let cast = series.cast(&DataType::Float64)?;
+        raw.push(cast.f64()?.into_iter().collect::<Vec<Option<f64>>>());
+    }
+
+    let rows: Vec<usize> = (0..df.height())
+        .filter(|&r| raw.iter().all(|col| col[r].is_some_and(f64::is_finite)))
+        .collect();
+    let data = raw
+        .iter()
+        .map(|col| rows.iter().map(|&r| col[r].unwrap_or_default()).collect())
+        .collect();
+    let names = selected.iter().map(|s| s.name().to_string()).collect();
+    Ok((names, rows, data))
+}
+
+fn centered(values: &[f64]) -> Vec<f64> {
+    let mean = values.iter().sum::<f64>() / values.len().max(1) as f64;
+    values.iter().map(|v| v - mean).collect()
+}
+
+/// 1-based ranks with ties sharing their average rank
+fn average_ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end + 1) as f64 / 2.0;
+        for &i in &order[start..end] {
+            ranks[i] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+/// Cosine similarity of two score vectors; None if either has no spread
+fn cosine(a: &[f64], b: &[f64]) -> Option<f64> {
+    let (mut ab, mut aa, mut bb) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        ab += x * y;
+        aa += x * x;
+        bb += y * y;
+    }
+    (aa > 0.0 && bb > 0.0).then(|| (ab / (aa * bb).sqrt()).clamp(-1.0, 1.0))
+}
+
+/// Percentage-bend scores of one column and which values were bent
+///
+/// Follows Wilcox (1994): the scale is the floor((1 - beta) n)-th smallest
+/// absolute deviation from the median, the location a one-step M-estimate,
+/// and standardized values beyond +-1 are clipped.
+fn bend_scores(values: &[f64], beta: f64) -> Option<(Vec<f64>, Vec<bool>)> {
+    let n = values.len();
+    if n < 2 {
+        return None;
+    }
+    let median = median(values.to_vec());
+    let mut deviations: Vec<f64> = values.iter().map(|v| (v - median).abs()).collect();
+    deviations.sort_by(f64::total_cmp);
+    let m = (((1.0 - beta) * n as f64).floor() as usize).clamp(1, n);
+    let omega = deviations[m - 1];
+    if omega <= 0.0 {
+        return None;
+    }
+
+    let (mut below, mut above, mut inner_sum) = (0usize, 0usize, 0.0);
+    for &v in values {
+        let psi = (v - median) / omega;
+        if psi < -1.0 {
+            below += 1;
+        } else if psi > 1.0 {
+            above += 1;
+        } else {
+            inner_sum += v;
+        }
+    }
+    let location = (inner_sum + omega * (above as f64 - below as f64)) / (n - below - above) as f64;
+
+    Some(values
+        .iter()
+        .map(|v| {
+            let a = (v - location) / omega;
+            (a.clamp(-1.0, 1.0), a.abs() > 1.0)
+        })
+        .unzip())
+}
+
+// ============================================================================
+// Covariance
+// ============================================================================
+
+/// Covariance matrix of numeric columns, optionally with the MCD estimator
+///
+/// The robust estimate searches for the `support_fraction` share of rows
+/// whose covariance has the smallest determinant (concentration steps from a
+/// deterministic start near the coordinate-wise median plus seeded random
+/// starts, as in FAST-MCD). It is rescaled for consistency at the normal
+/// distribution and reweighted. Rows whose robust distance exceeds the 97.5%
+/// chi-square quantile are left out and reported.
+///
+/// # Arguments
+/// * `columns` - Columns to include (default: every numeric column)
+/// * `robust` - MCD settings, or None for the classical estimate
+///
+/// # Returns
+/// * `Result<CovarianceMatrix>` - Location, covariance and the excluded rows
+pub fn covariance_matrix(
+    df: &DataFrame,
+    columns: Option<&[String]>,
+    robust: Option<&RobustCovarianceConfig>,
+) -> Result<CovarianceMatrix, InsightoraError> {
+    let (names, rows, data) = complete_cases(df, columns)?;
+    let p = names.len();
+    let points: Vec<Vec<f64>> = (0..rows.len()).map(|r| data.iter().map(|col| col[r]).collect()).collect();
+    let all: Vec<usize> = (0..points.len()).collect();
+
+    let Some(config) = robust else {
+        if points.len() < 2 {
+            return Err(InsightoraError::ValidationError(
+                "Covariance needs at least two complete rows".to_string()
+            ));
+        }
+        let (location, values) = mean_covariance(&points, &all);
+        return Ok(CovarianceMatrix {
+            columns: names,
+            location,
+            values,
+            rows_used: rows.len(),
+            robust: None,
+            excluded_rows: Vec::new(),
+        });
+    };
+
+    if config.support_fraction.is_nan() || !(0.5..=1.0).contains(&config.support_fraction) {
+        return Err(InsightoraError::ValidationError(format!(
+            "support_fraction must be between 0.5 and 1, got {}",
+            config.support_fraction
+        )));
+    }
+    let h = ((config.support_fraction * points.len() as f64).ceil() as usize).min(points.len());
+    if h <= p + 1 {
+        return Err(InsightoraError::ValidationError(format!(
+            "Robust covariance of {} columns needs more than {} supporting rows, got {}",
+            p,
+            p + 1,
+            h
+        )));
+    }
+
+    let raw = fast_mcd(&points, h, config.seed).ok_or_else(|| {
+        InsightoraError::ValidationError(
+            "Robust covariance failed: the supporting rows are collinear".to_string()
+        )
+    })?;
+
+    // Consistency correction: squared distances of normal data follow chi2(p)
+    let chi2_median = chi_square_quantile(0.5, p as f64);
+    let distances: Vec<f64> = points.iter().map(|x| raw.distance(x)).collect();
+    let correction = median(distances.clone()) / chi2_median;
+    let cutoff = chi_square_quantile(REWEIGHT_QUANTILE, p as f64);
+
+    let kept: Vec<usize> = all
+        .iter()
+        .copied()
+        .filter(|&i| correction > 0.0 && distances[i] / correction <= cutoff)
+        .collect();
+    let reweighted = match McdFit::new(&points, kept) {
+        Some(fit) if fit.support.len() > p + 1 => fit,
+        _ => raw,
+    };
+    // Trimming the tails shrinks the covariance, so it is corrected again
+    let (location, mut values) = mean_covariance(&points, &reweighted.support);
+    let rescale = median(points.iter().map(|x| reweighted.distance(x)).collect()) / chi2_median;
+    if rescale > 0.0 {
+        values.iter_mut().flatten().for_each(|v| *v *= rescale);
+    }
+    let kept = reweighted.support;
+
+    let mut is_kept = vec![false; points.len()];
+    kept.iter().for_each(|&i| is_kept[i] = true);
+    Ok(CovarianceMatrix {
+        columns: names,
+        location,
+        values,
+        rows_used: rows.len(),
+        robust: Some(*config),
+        excluded_rows: rows.iter().zip(&is_kept).filter(|(_, k)| !**k).map(|(r, _)| *r).collect(),
+    })
+}
+
+/// Mean and covariance (n - 1 denominator) of the indexed points
+fn mean_covariance(points: &[Vec<f64>], indices: &[usize]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let p = points.first().map_or(0, Vec::len);
+    let n = indices.len() as f64;
+    let mut mean = vec![0.0; p];
+    for &i in indices {
+        mean.iter_mut().zip(&points[i]).for_each(|(m, v)| *m += v / n);
+    }
+    let mut cov = vec![vec![0.0; p]; p];
+    for &i in indices {
+        let d: Vec<f64> = points[i].iter().zip(&mean).map(|(v, m)| v - m).collect();
+        for (row, &da) in cov.iter_mut().zip(&d) {
+            for (cell, &db) in row.iter_mut().zip(&d) {
+                *cell += da * db;
+            }
+        }
+    }
+    let denominator = (n - 1.0).max(1.0);
+    cov.iter_mut().flatten().for_each(|c| *c /= denominator);
+    (mean, cov)
+}
+
+/// A candidate MCD fit over a subset of the points
+struct McdFit {
+    support: Vec<usize>,
+    mean: Vec<f64>,
+    /// Lower Cholesky factor of the covariance
+    chol: Vec<Vec<f64>>,
+    log_det: f64,
+}
+
+impl McdFit {
+    fn new(points: &[Vec<f64>], support: Vec<usize>) -> Option<Self> {
+        let (mean, cov) = mean_covariance(points, &support);
+        let chol = cholesky(&cov)?;
+        let log_det = 2.0 * chol.iter().enumerate().map(|(i, row)| row[i].ln()).sum::<f64>();
+        Some(Self { support, mean, chol, log_det })
+    }
+
+    /// Squared Mahalanobis distance of a point
+    fn distance(&self, x: &[f64]) -> f64 {
+        // Forward substitution: L z = x - mean
+        let mut z = vec![0.0; x.len()];
+        for i in 0..x.len() {
+            let partial: f64 = (0..i).map(|k| self.chol[i][k] * z[k]).sum();
+            z[i] = (x[i] - self.mean[i] - partial) / self.chol[i][i];
+        }
+        z.iter().map(|v| v * v).sum()
+    }
+
+    /// Concentration step: refit on the h points closest under this fit
+    fn concentrate(&self, points: &[Vec<f64>], h: usize) -> Option<Self> {
+        let mut order: Vec<(f64, usize)> = points.iter().enumerate().map(|(i, x)| (self.distance(x), i)).collect();
+        order.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        let mut support: Vec<usize> = order[..h].iter().map(|(_, i)| *i).collect();
+        support.sort_unstable();
+        Self::new(points, support)
+    }
+}
+
+/// Lower Cholesky factor of a symmetric matrix; None unless positive definite
+fn cholesky(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let p = matrix.len();
+    let scale = (0..p).map(|i| matrix[i][i].abs()).fold(0.0, f64::max);
+    let mut l = vec![vec![0.0; p]; p];
+    for i in 0..p {
+        for j in 0..=i {
+            let partial: f64 = (0..j).map(|k| l[i][k] * l[j][k]).sum();
+            if i == j {
+                let pivot = matrix[i][i] - partial;
+                if pivot.is_nan() || pivot <= 1e-12 * scale.max(f64::MIN_POSITIVE) {
+                    return None;
+                }
+                l[i][i] = pivot.sqrt();
+            } else {
+                l[i][j] = (matrix[i][j] - partial) / l[j][j];
+            }
+        }
+    }
+    Some(l)
+}
+
+/// Minimum covariance determinant search over subsets of `h` points
+fn fast_mcd(points: &[Vec<f64>], h: usize, seed: u64) -> Option<McdFit> {
+    let n = points.len();
+    let p = points[0].len();
+    let mut starts = vec![median_start(points, h)];
+
+    let mut state = seed | 1;
+    let mut next = move |bound: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % bound as u64) as usize
+    };
+    for _ in 0..RANDOM_STARTS {
+        // Partial Fisher-Yates; grow the subset until its covariance is invertible
+        let mut pool: Vec<usize> = (0..n).collect();
+        let mut size = 0;
+        let start = loop {
+            let j = size + next(n - size);
+            pool.swap(size, j);
+            size += 1;
+            if size > p {
+                if let Some(fit) = McdFit::new(points, pool[..size].to_vec()) {
+                    break Some(fit);
+                }
+            }
+            if size == h {
+                break None;
+            }
+        };
+        if let Some(fit) = start {
+            starts.push(fit.support);
+        }
+    }
+
+    let mut candidates: Vec<McdFit> = starts
+        .into_iter()
+        .filter_map(|support| {
+            let mut fit = McdFit::new(points, support)?.concentrate(points, h)?;
+            for _ in 1..INITIAL_C_STEPS {
+                fit = fit.concentrate(points, h)?;
+            }
+            Some(fit)
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.log_det.total_cmp(&b.log_det));
+    candidates.truncate(REFINED_STARTS);
+
+    candidates
+        .into_iter()
+        .filter_map(|mut fit| {
+            for _ in 0..MAX_C_STEPS {
+                let refined = fit.concentrate(points, h)?;
+                if refined.support == fit.support || refined.log_det >= fit.log_det {
+                    break;
+                }
+                fit = refined;
+            }
+            Some(fit)
+        })
+        .min_by(|a, b| a.log_det.total_cmp(&b.log_det))
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let n = values.len();
+    if n % 2 == 1 { values[n / 2] } else { (values[n / 2 - 1] + values[n / 2]) / 2.0 }
+}
+
+/// The h points closest to the coordinate-wise median, scaled by each column's MAD
+fn median_start(points: &[Vec<f64>], h: usize) -> Vec<usize> {
+    let p = points[0].len();
+    let centers: Vec<(f64, f64)> = (0..p)
+        .map(|j| {
+            let center = median(points.iter().map(|x| x[j]).collect());
+            let mad = median(points.iter().map(|x| (x[j] - center).abs()).collect());
+            (center, if mad > 0.0 { mad } else { 1.0 })
+        })
+        .collect();
+    let mut order: Vec<(f64, usize)> = points
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            let d = x.iter().zip(&centers).map(|(v, (c, s))| ((v - c) / s).powi(2)).sum::<f64>();
+            (d, i)
+        })
+        .collect();
+    order.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+    order[..h].iter().map(|(_, i)| *i).collect()
+}
+
+/// Quantile of the chi-square distribution (bisection on the upper tail)
+fn chi_square_quantile(q: f64, dof: f64) -> f64 {
+    let target = 1.0 - q;
+    let mut hi = dof.max(1.0);
+    while chi_square_sf(hi, dof) > target {
+        hi *= 2.0;
+    }
+    let mut lo = 0.0;
+    for _ in 0..100 {
+        let mid = (lo + hi) / 2.0;
+        if chi_square_sf(mid, dof) > target {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bivariate normal with correlation `rho`, then `outliers` gross points
+    /// placed against the trend at the end
+    fn contaminated(n: usize, rho: f64, outliers: usize) -> DataFrame {
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        let mut uniform = move || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((state >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+        };
+        let mut normal = move || {
+            let (u, v) = (uniform(), uniform());
+            (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+        };
+        let (mut x, mut y) = (Vec::new(), Vec::new());
+        for _ in 0..n {
+            let (a, b) = (normal(), normal());
+            x.push(a);
+            y.push(rho * a + (1.0 - rho * rho).sqrt() * b);
+        }
+        for i in 0..outliers {
+            x.push(8.0 + i as f64 * 0.1);
+            y.push(-8.0 - i as f64 * 0.1);
+        }
+        df!("x" => x, "y" => y).unwrap()
+    }
+
+    #[test]
+    fn test_percentage_bend_matches_reference() {
+        // Expected values computed independently following pingouin's
+        // percbend (Wilcox's pbcor, beta = 0.2) and scipy's spearmanr
+        let df = df!(
+            "x" => [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 40.0],
+            "y" => [2.1, 3.9, 6.2, 8.1, 9.8, 12.2, 13.8, 16.1, 18.0, -30.0],
+        )
+        .unwrap();
+        let pearson = correlation(&df, None, CorrelationMethod::Pearson).unwrap();
+        let bend = correlation(&df, None, CorrelationMethod::PercentageBend { beta: 0.2 }).unwrap();
+        let spearman = correlation(&df, None, CorrelationMethod::Spearman).unwrap();
+
+        assert!((pearson.get("x", "y").unwrap() - -0.816_651_963_935_497).abs() < 1e-9);
+        assert!((bend.get("x", "y").unwrap() - 0.573_450_609_457_326).abs() < 1e-9);
+        assert!((spearman.get("x", "y").unwrap() - 0.454_545_454_545_454_5).abs() < 1e-9);
+        assert_eq!(bend.influential_rows, vec![0, 8, 9]);
+        assert_eq!(bend.get("x", "x"), Some(1.0));
+    }
+
+    #[test]
+    fn test_robust_estimates_on_contaminated_data() {
+        // 5% gross outliers flip Pearson's sign on data correlated at 0.8
+        let df = contaminated(380, 0.8, 20);
+        let pearson = correlation(&df, None, CorrelationMethod::Pearson).unwrap().get("x", "y").unwrap();
+        assert!(pearson < 0.0, "pearson {}", pearson);
+
+        // Bending caps each outlier's pull, so the sign survives (around 0.55)
+        let bend = correlation(&df, None, CorrelationMethod::from_name("pb").unwrap()).unwrap();
+        let pb = bend.get("x", "y").unwrap();
+        assert!(pb > 0.4, "pb {}", pb);
+        assert!((380..400).all(|i| bend.influential_rows.contains(&i)));
+
+        let classical = covariance_matrix(&df, None, None).unwrap();
+        assert!(classical.excluded_rows.is_empty());
+        assert!(classical.to_correlation()[0][1].unwrap() < 0.0);
+
+        let robust = covariance_matrix(&df, None, Some(&RobustCovarianceConfig::default())).unwrap();
+        let r = robust.to_correlation()[0][1].unwrap();
+        assert!((r - 0.8).abs() < 0.08, "mcd correlation {}", r);
+        assert!(robust.location.iter().all(|m| m.abs() < 0.2), "{:?}", robust.location);
+        assert!((robust.values[0][0] - 1.0).abs() < 0.3, "{:?}", robust.values);
+        // Every planted outlier is excluded, along with only a few clean rows
+        assert!((380..400).all(|i| robust.excluded_rows.contains(&i)));
+        assert!(robust.excluded_rows.len() < 40, "{}", robust.excluded_rows.len());
+
+        assert!(covariance_matrix(&df, None, Some(&RobustCovarianceConfig { support_fraction: 0.3, ..Default::default() })).is_err());
+        assert!(CorrelationMethod::from_name("kendall").is_err());
+    }
+}
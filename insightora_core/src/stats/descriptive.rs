@@ -1,2 +1,784 @@
-// Descriptive statistics implementation
-// TODO: Implement in task 13
+// Descriptive statistics
+// Running (incremental) statistics with mergeable state for sharded streams
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use polars::prelude::*;
+use crate::error::InsightoraError;
+use crate::utils::simd::{self, KernelPath};
+use crate::dataframe::transformations::{category_order, category_ranks};
+use crate::stats::identifiers::{detect_identifiers, identifier_columns, IdDetectionConfig, IdentifierDecision};
+
+// ============================================================================
+// Quantile Sketch
+// ============================================================================
+
+/// Mergeable quantile sketch with bounded relative error (DDSketch)
+///
+/// Values are mapped to logarithmically sized buckets so any quantile estimate
+/// is within `relative_accuracy` of the true value. Bucket counts simply add
+/// when merging, so merged shards give exactly the same sketch as one pass.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantileSketch {
+    relative_accuracy: f64,
+    gamma_ln: f64,
+    positive: BTreeMap<i32, u64>,
+    negative: BTreeMap<i32, u64>,
+    zero_count: u64,
+    count: u64,
+}
+
+/// Values closer to zero than this are counted in the zero bucket
+const SKETCH_MIN_VALUE: f64 = 1e-9;
+
+impl QuantileSketch {
+    /// Create a sketch with the given relative accuracy (e.g. 0.01 for 1%)
+    pub fn new(relative_accuracy: f64) -> Result<Self, InsightoraError> {
+        if !(relative_accuracy > 0.0 && relative_accuracy < 1.0) {
+            return Err(InsightoraError::ValidationError(format!(
+                "relative_accuracy must be between 0 and 1, got {}",
+                relative_accuracy
+            )));
+        }
+        let gamma = (1.0 + relative_accuracy) / (1.0 - relative_accuracy);
+        Ok(Self {
+            relative_accuracy,
+            gamma_ln: gamma.ln(),
+            positive: BTreeMap::new(),
+            negative: BTreeMap::new(),
+            zero_count: 0,
+            count: 0,
+        })
+    }
+
+    #[inline]
+    fn bucket(&self, magnitude: f64) -> i32 {
+        (magnitude.ln() / self.gamma_ln).ceil() as i32
+    }
+
+    #[inline]
+    fn bucket_value(&self, index: i32) -> f64 {
+        // Midpoint (in relative terms) of the bucket (gamma^(i-1), gamma^i]
+        let gamma = self.gamma_ln.exp();
+        2.0 * gamma.powi(index) / (gamma + 1.0)
+    }
+
+    /// Add a single finite value
+    pub fn insert(&mut self, value: f64) {
+        if value > SKETCH_MIN_VALUE {
+            *self.positive.entry(self.bucket(value)).or_insert(0) += 1;
+        } else if value < -SKETCH_MIN_VALUE {
+            *self.negative.entry(self.bucket(-value)).or_insert(0) += 1;
+        } else {
+            self.zero_count += 1;
+        }
+        self.count += 1;
+    }
+
+    /// Merge another sketch built with the same accuracy into this one
+    pub fn merge(&mut self, other: &QuantileSketch) -> Result<(), InsightoraError> {
+        if self.relative_accuracy != other.relative_accuracy {
+            return Err(InsightoraError::ValidationError(format!(
+                "Cannot merge sketches with different accuracy ({} vs {})",
+                self.relative_accuracy, other.relative_accuracy
+            )));
+        }
+        for (&index, &count) in &other.positive {
+            *self.positive.entry(index).or_insert(0) += count;
+        }
+        for (&index, &count) in &other.negative {
+            *self.negative.entry(index).or_insert(0) += count;
+        }
+        self.zero_count += other.zero_count;
+        self.count += other.count;
+        Ok(())
+    }
+
+    /// Estimate the q-th quantile (0.0 ..= 1.0); None when the sketch is empty
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.count == 0 || !(0.0..=1.0).contains(&q) {
+            return None;
+        }
+        let rank = (q * (self.count - 1) as f64).floor() as u64;
+        let mut seen = 0u64;
+
+        // Most negative values first: largest magnitude bucket first
+        for (&index, &count) in self.negative.iter().rev() {
+            seen += count;
+            if seen > rank {
+                return Some(-self.bucket_value(index));
+            }
+        }
+        seen += self.zero_count;
+        if seen > rank {
+            return Some(0.0);
+        }
+        for (&index, &count) in &self.positive {
+            seen += count;
+            if seen > rank {
+                return Some(self.bucket_value(index));
+            }
+        }
+        None
+    }
+
+    /// Number of values inserted
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    fn clear(&mut self) {
+        self.positive.clear();
+        self.negative.clear();
+        self.zero_count = 0;
+        self.count = 0;
+    }
+}
+
+// ============================================================================
+// Running Statistics
+// ============================================================================
+
+/// Incremental statistics for one column
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnAccumulator {
+    pub count: u64,
+    pub null_count: u64,
+    pub mean: f64,
+    /// Sum of squared deviations from the mean (M2)
+    pub m2: f64,
+    pub min: f64,
+    pub max: f64,
+    sketch: QuantileSketch,
+}
+
+impl ColumnAccumulator {
+    fn new(relative_accuracy: f64) -> Result<Self, InsightoraError> {
+        Ok(Self {
+            count: 0,
+            null_count: 0,
+            mean: 0.0,
+            m2: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            sketch: QuantileSketch::new(relative_accuracy)?,
+        })
+    }
+
+    /// Add a slice of non-NaN values
+    ///
+    /// Moments of the slice come from the vectorized kernels on `path` and are
+    /// folded in with the same parallel combine as `merge`.
+    fn push_slice(&mut self, values: &[f64], path: KernelPath) {
+        let Some((min, max)) = simd::min_max(values, path) else {
+            return;
+        };
+        let batch = simd::moments(values, path);
+        self.combine(batch.count, batch.mean, batch.m2);
+        self.min = self.min.min(min);
+        self.max = self.max.max(max);
+        for &value in values {
+            self.sketch.insert(value);
+        }
+    }
+
+    /// Fold in the moments of a disjoint set of values (Chan et al. parallel variance)
+    fn combine(&mut self, count: u64, mean: f64, m2: f64) {
+        if count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            self.count = count;
+            self.mean = mean;
+            self.m2 = m2;
+            return;
+        }
+        let total = self.count + count;
+        let delta = mean - self.mean;
+        self.mean += delta * count as f64 / total as f64;
+        self.m2 += m2 + delta * delta * self.count as f64 * count as f64 / total as f64;
+        self.count = total;
+    }
+
+    /// Combine with another accumulator
+    fn merge(&mut self, other: &ColumnAccumulator) -> Result<(), InsightoraError> {
+        self.sketch.merge(&other.sketch)?;
+        self.null_count += other.null_count;
+        self.combine(other.count, other.mean, other.m2);
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        Ok(())
+    }
+
+    /// Sample variance (n - 1 denominator); None with fewer than two values
+    pub fn variance(&self) -> Option<f64> {
+        if self.count < 2 {
+            None
+        } else {
+            Some(self.m2 / (self.count - 1) as f64)
+        }
+    }
+
+    /// Approximate quantile from the sketch
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        self.sketch.quantile(q)
+    }
+}
+
+/// Point-in-time statistics for one column
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnSnapshot {
+    pub column: String,
+    pub count: u64,
+    pub null_count: u64,
+    pub mean: Option<f64>,
+    pub variance: Option<f64>,
+    pub std: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// (quantile, estimate) pairs in the configured order
+    pub quantiles: Vec<(f64, Option<f64>)>,
+}
+
+/// Running statistics over a stream of batches without keeping history
+///
+/// Tracks count, mean, variance (parallel moments), min, max and approximate quantiles
+/// for a fixed set of numeric columns. States computed over disjoint shards can
+/// be merged, and the whole state round-trips through `to_bytes`/`from_bytes`
+/// for checkpointing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunningStats {
+    columns: Vec<String>,
+    quantiles: Vec<f64>,
+    relative_accuracy: f64,
+    accumulators: Vec<ColumnAccumulator>,
+}
+
+impl RunningStats {
+    /// Create running statistics for the given columns
+    ///
+    /// # Arguments
+    /// * `columns` - Numeric columns to track
+    /// * `quantiles` - Quantiles reported by `snapshot` (each in 0.0 ..= 1.0)
+    /// * `relative_accuracy` - Relative error bound of quantile estimates
+    pub fn new(
+        columns: Vec<String>,
+        quantiles: Vec<f64>,
+        relative_accuracy: f64,
+    ) -> Result<Self, InsightoraError> {
+        if columns.is_empty() {
+            return Err(InsightoraError::ValidationError(
+                "RunningStats requires at least one column".to_string()
+            ));
+        }
+        if let Some(q) = quantiles.iter().find(|q| !(0.0..=1.0).contains(*q)) {
+            return Err(InsightoraError::ValidationError(format!(
+                "Quantiles must be between 0 and 1, got {}",
+                q
+            )));
+        }
+        let accumulators = columns
+            .iter()
+            .map(|_| ColumnAccumulator::new(relative_accuracy))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            columns,
+            quantiles,
+            relative_accuracy,
+            accumulators,
+        })
+    }
+
+    /// Tracked column names
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Accumulate a batch; every tracked column must be present and numeric
+    ///
+    /// Nulls and NaN values are counted in `null_count` and otherwise ignored.
+    /// Reductions use the vectorized kernels unless `enable_simd` is turned off.
+    pub fn update(&mut self, batch: &DataFrame) -> Result<(), InsightoraError> {
+        // Validate all columns before touching the state so a bad batch is a no-op
+        let values = self
+            .columns
+            .iter()
+            .map(|name| {
+                let series = batch.column(name).map_err(|_| {
+                    InsightoraError::ValidationError(format!("Column '{}' not found in batch", name))
+                })?;
+                if !series.dtype().is_numeric() {
+                    return Err(InsightoraError::InvalidDataType {
+                        expected: "numeric".to_string(),
+                        actual: format!("{} ({})", series.dtype(), name),
+                    });
+                }
+                Ok(series.cast(&DataType::Float64)?)
+            })
+            .collect::<Result<Vec<_>, InsightoraError>>()?;
+
+        let path = KernelPath::select("running_stats")?;
+        for (accumulator, series) in self.accumulators.iter_mut().zip(values.iter()) {
+            for array in series.f64()?.downcast_iter() {
+                let slice = array.values().as_slice();
+                // Null slots hold arbitrary values, so drop them before the kernels
+                let mut finite = match array.validity().filter(|mask| mask.unset_bits() > 0) {
+                    Some(mask) => {
+                        accumulator.null_count += mask.unset_bits() as u64;
+                        Cow::Owned(slice.iter().zip(mask.iter()).filter(|(_, valid)| *valid).map(|(v, _)| *v).collect())
+                    }
+                    None => Cow::Borrowed(slice),
+                };
+                let nan_count = simd::count_nan(&finite, path);
+                if nan_count > 0 {
+                    accumulator.null_count += nan_count as u64;
+                    finite.to_mut().retain(|v| !v.is_nan());
+                }
+                accumulator.push_slice(&finite, path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Merge statistics from another shard tracking the same columns
+    pub fn merge(&mut self, other: &RunningStats) -> Result<(), InsightoraError> {
+        if self.columns != other.columns {
+            return Err(InsightoraError::ValidationError(format!(
+                "Cannot merge RunningStats over different columns ({:?} vs {:?})",
+                self.columns, other.columns
+            )));
+        }
+        for (mine, theirs) in self.accumulators.iter_mut().zip(other.accumulators.iter()) {
+            mine.merge(theirs)?;
+        }
+        Ok(())
+    }
+
+    /// Current statistics for every tracked column
+    pub fn snapshot(&self) -> Vec<ColumnSnapshot> {
+        self.columns
+            .iter()
+            .zip(self.accumulators.iter())
+            .map(|(name, acc)| {
+                let has_values = acc.count > 0;
+                let variance = acc.variance();
+                ColumnSnapshot {
+                    column: name.clone(),
+                    count: acc.count,
+                    null_count: acc.null_count,
+                    mean: has_values.then_some(acc.mean),
+                    variance,
+                    std: variance.map(f64::sqrt),
+                    min: has_values.then_some(acc.min),
+                    max: has_values.then_some(acc.max),
+                    quantiles: self.quantiles.iter().map(|&q| (q, acc.quantile(q))).collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// Accumulator for a tracked column
+    pub fn accumulator(&self, column: &str) -> Option<&ColumnAccumulator> {
+        self.columns
+            .iter()
+            .position(|c| c == column)
+            .map(|i| &self.accumulators[i])
+    }
+
+    /// Discard all accumulated state, keeping the configuration
+    pub fn reset(&mut self) {
+        for acc in &mut self.accumulators {
+            acc.count = 0;
+            acc.null_count = 0;
+            acc.mean = 0.0;
+            acc.m2 = 0.0;
+            acc.min = f64::INFINITY;
+            acc.max = f64::NEG_INFINITY;
+            acc.sketch.clear();
+        }
+    }
+
+    /// Serialize the full state for checkpointing
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(STATE_MAGIC);
+        out.push(STATE_VERSION);
+        write_f64(&mut out, self.relative_accuracy);
+        write_u64(&mut out, self.quantiles.len() as u64);
+        for &q in &self.quantiles {
+            write_f64(&mut out, q);
+        }
+        write_u64(&mut out, self.columns.len() as u64);
+        for (name, acc) in self.columns.iter().zip(self.accumulators.iter()) {
+            write_u64(&mut out, name.len() as u64);
+            out.extend_from_slice(name.as_bytes());
+            write_u64(&mut out, acc.count);
+            write_u64(&mut out, acc.null_count);
+            write_f64(&mut out, acc.mean);
+            write_f64(&mut out, acc.m2);
+            write_f64(&mut out, acc.min);
+            write_f64(&mut out, acc.max);
+            write_u64(&mut out, acc.sketch.zero_count);
+            write_u64(&mut out, acc.sketch.count);
+            for bins in [&acc.sketch.positive, &acc.sketch.negative] {
+                write_u64(&mut out, bins.len() as u64);
+                for (&index, &count) in bins {
+                    out.extend_from_slice(&index.to_le_bytes());
+                    write_u64(&mut out, count);
+                }
+            }
+        }
+        out
+    }
+
+    /// Restore state produced by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, InsightoraError> {
+        let mut reader = StateReader { bytes, offset: 0 };
+        if reader.take(STATE_MAGIC.len())? != STATE_MAGIC {
+            return Err(InsightoraError::ParseError(
+                "Not a RunningStats checkpoint".to_string()
+            ));
+        }
+        let version = reader.take(1)?[0];
+        if version != STATE_VERSION {
+            return Err(InsightoraError::ParseError(format!(
+                "Unsupported RunningStats checkpoint version {}",
+                version
+            )));
+        }
+
+        let relative_accuracy = reader.f64()?;
+        let quantiles = (0..reader.u64()?)
+            .map(|_| reader.f64())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let column_count = reader.u64()?;
+        let mut columns = Vec::new();
+        let mut accumulators = Vec::new();
+        for _ in 0..column_count {
+            let name_len = reader.u64()? as usize;
+            let name = String::from_utf8(reader.take(name_len)?.to_vec())
+                .map_err(|e| InsightoraError::ParseError(format!("Invalid column name: {}", e)))?;
+
+            let mut acc = ColumnAccumulator::new(relative_accuracy)?;
+            acc.count = reader.u64()?;
+            acc.null_count = reader.u64()?;
+            acc.mean = reader.f64()?;
+            acc.m2 = reader.f64()?;
+            acc.min = reader.f64()?;
+            acc.max = reader.f64()?;
+            acc.sketch.zero_count = reader.u64()?;
+            acc.sketch.count = reader.u64()?;
+            for bins in [&mut acc.sketch.positive, &mut acc.sketch.negative] {
+                for _ in 0..reader.u64()? {
+                    let index = i32::from_le_bytes(reader.array()?);
+                    let count = reader.u64()?;
+                    bins.insert(index, count);
+                }
+            }
+
+            columns.push(name);
+            accumulators.push(acc);
+        }
+
+        if reader.offset != bytes.len() {
+            return Err(InsightoraError::ParseError(
+                "Trailing data after RunningStats checkpoint".to_string()
+            ));
+        }
+
+        Ok(Self {
+            columns,
+            quantiles,
+            relative_accuracy,
+            accumulators,
+        })
+    }
+}
+
+const STATE_MAGIC: &[u8] = b"ISRS";
+const STATE_VERSION: u8 = 1;
+
+fn write_u64(out: &mut Vec<u8>, value: u64) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_f64(out: &mut Vec<u8>, value: f64) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Cursor over a checkpoint byte buffer
+struct StateReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> StateReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], InsightoraError> {
+        let end = self.offset.checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| InsightoraError::ParseError(
+                "Truncated RunningStats checkpoint".to_string()
+            ))?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], InsightoraError> {
+        let mut buf = [0u8; N];
+        buf.copy_from_slice(self.take(N)?);
+        Ok(buf)
+    }
+
+    fn u64(&mut self) -> Result<u64, InsightoraError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn f64(&mut self) -> Result<f64, InsightoraError> {
+        Ok(f64::from_le_bytes(self.array()?))
+    }
+}
+
+// ============================================================================
+// Describe
+// ============================================================================
+
+/// Summary of an ordered categorical column
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategorySummary {
+    pub column: String,
+    pub count: u64,
+    pub null_count: u64,
+    /// Lowest and highest category present, by category order
+    pub min: Option<String>,
+    pub max: Option<String>,
+    /// Declared categories, lowest first
+    pub categories: Vec<String>,
+}
+
+/// Summary of a DataFrame's numeric and ordered categorical columns
+#[derive(Debug, Clone, PartialEq)]
+pub struct Description {
+    /// Statistics for numeric columns not flagged as identifiers
+    pub numeric: Vec<ColumnSnapshot>,
+    /// Ordered categoricals (see `set_category_order`), in column order
+    pub categorical: Vec<CategorySummary>,
+    /// Columns excluded from `numeric` because they hold identifiers
+    pub identifiers: Vec<String>,
+    /// Identifier detection decisions for every column (empty when detection is off)
+    pub id_decisions: Vec<IdentifierDecision>,
+}
+
+/// Describe the numeric columns of a DataFrame
+///
+/// With identifier detection enabled, ID-like columns (order numbers, zip
+/// codes) are listed under `identifiers` instead of receiving means and sums
+/// that have no meaning.
+///
+/// # Arguments
+/// * `df` - Data to describe
+/// * `id_detection` - Identifier detection settings, or None to summarize every numeric column
+///
+/// # Returns
+/// * `Result<Description>` - Count, mean, std, min, max and quartiles per
+///   numeric column; count, min and max by category order per ordered categorical
+pub fn describe(df: &DataFrame, id_detection: Option<&IdDetectionConfig>) -> Result<Description, InsightoraError> {
+    let id_decisions = match id_detection {
+        Some(config) => detect_identifiers(df, config)?,
+        None => Vec::new(),
+    };
+    let identifiers = identifier_columns(&id_decisions);
+
+    let numeric_columns: Vec<String> = df
+        .get_columns()
+        .iter()
+        .filter(|s| s.dtype().is_numeric() && !identifiers.iter().any(|id| id == s.name()))
+        .map(|s| s.name().to_string())
+        .collect();
+
+    let numeric = if numeric_columns.is_empty() {
+        Vec::new()
+    } else {
+        let mut stats = RunningStats::new(numeric_columns, vec![0.25, 0.5, 0.75], 0.01)?;
+        stats.update(df)?;
+        stats.snapshot()
+    };
+
+    let categorical = df
+        .get_columns()
+        .iter()
+        .filter_map(|series| category_order(series).map(|categories| summarize_categories(series, categories)))
+        .collect::<Result<Vec<_>, InsightoraError>>()?;
+
+    Ok(Description {
+        numeric,
+        categorical,
+        identifiers,
+        id_decisions,
+    })
+}
+
+fn summarize_categories(series: &Series, categories: Vec<String>) -> Result<CategorySummary, InsightoraError> {
+    let ranks = category_ranks(series)?;
+    let label = |rank: Option<u32>| rank.map(|r| categories[r as usize].clone());
+    Ok(CategorySummary {
+        column: series.name().to_string(),
+        count: (series.len() - series.null_count()) as u64,
+        null_count: series.null_count() as u64,
+        min: label(ranks.min()),
+        max: label(ranks.max()),
+        categories,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn stats() -> RunningStats {
+        RunningStats::new(vec!["value".to_string()], vec![0.5, 0.9], 0.01).unwrap()
+    }
+
+    fn batch(values: &[f64]) -> DataFrame {
+        df!("value" => values).unwrap()
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1e-9 * a.abs().max(b.abs()).max(1.0)
+    }
+
+    #[test]
+    fn test_update_and_snapshot() {
+        let mut rs = stats();
+        rs.update(&batch(&[1.0, 2.0, 3.0])).unwrap();
+        rs.update(&batch(&[4.0, 5.0])).unwrap();
+
+        let snap = &rs.snapshot()[0];
+        assert_eq!(snap.count, 5);
+        assert!(close(snap.mean.unwrap(), 3.0));
+        assert!(close(snap.variance.unwrap(), 2.5));
+        assert_eq!(snap.min, Some(1.0));
+        assert_eq!(snap.max, Some(5.0));
+        let median = snap.quantiles[0].1.unwrap();
+        assert!((median - 3.0).abs() <= 0.03 * 3.0);
+    }
+
+    #[test]
+    fn test_nulls_counted_separately() {
+        let mut rs = stats();
+        let df = df!("value" => &[Some(1.0), None, Some(f64::NAN), Some(3.0)]).unwrap();
+        rs.update(&df).unwrap();
+
+        let snap = &rs.snapshot()[0];
+        assert_eq!(snap.count, 2);
+        assert_eq!(snap.null_count, 2);
+        assert!(close(snap.mean.unwrap(), 2.0));
+    }
+
+    #[test]
+    fn test_missing_column_rejected() {
+        let mut rs = stats();
+        let df = df!("other" => &[1.0]).unwrap();
+        assert!(rs.update(&df).is_err());
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut rs = stats();
+        rs.update(&batch(&[1.0, 2.0])).unwrap();
+        rs.reset();
+
+        let snap = &rs.snapshot()[0];
+        assert_eq!(snap.count, 0);
+        assert_eq!(snap.mean, None);
+        assert_eq!(snap.quantiles[0].1, None);
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let mut rs = stats();
+        rs.update(&batch(&[-2.5, 0.0, 1.5, 1e6])).unwrap();
+
+        let restored = RunningStats::from_bytes(&rs.to_bytes()).unwrap();
+        assert_eq!(restored, rs);
+        assert!(RunningStats::from_bytes(b"junk").is_err());
+    }
+
+    #[test]
+    fn test_describe_lists_identifiers_separately() {
+        let df = df! {
+            "order_id" => (1..=20i64).collect::<Vec<_>>(),
+            "amount" => (1..=20).map(|i| i as f64 * 2.0).collect::<Vec<_>>(),
+            "region" => vec!["north"; 20],
+        }
+        .unwrap();
+
+        let described = describe(&df, Some(&IdDetectionConfig::default())).unwrap();
+        assert_eq!(described.identifiers, vec!["order_id"]);
+        assert_eq!(described.numeric.len(), 1);
+        assert_eq!(described.numeric[0].column, "amount");
+        assert!(close(described.numeric[0].mean.unwrap(), 21.0));
+        assert_eq!(described.id_decisions.len(), 3);
+
+        let everything = describe(&df, None).unwrap();
+        assert_eq!(everything.numeric.len(), 2);
+        assert!(everything.identifiers.is_empty());
+    }
+
+    #[test]
+    fn test_describe_ordered_categoricals_by_category_order() {
+        use crate::dataframe::transformations::{set_category_order, UnknownCategory};
+
+        let df = df!("severity" => &[Some("medium"), Some("high"), None, Some("medium")]).unwrap();
+        let order = ["low", "medium", "high", "critical"].map(String::from);
+        let df = set_category_order(&df, "severity", &order, UnknownCategory::Error).unwrap();
+
+        let described = describe(&df, None).unwrap();
+        let summary = &described.categorical[0];
+        assert_eq!((summary.count, summary.null_count), (3, 1));
+        // Alphabetically "high" < "medium"; by category order it is the maximum
+        assert_eq!(summary.min.as_deref(), Some("medium"));
+        assert_eq!(summary.max.as_deref(), Some("high"));
+        assert_eq!(summary.categories, order.to_vec());
+        assert!(described.numeric.is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_merged_shards_match_single_pass(
+            values in prop::collection::vec(-1e6f64..1e6, 1..400),
+            split_points in prop::collection::vec(0usize..400, 0..4),
+        ) {
+            let mut single = stats();
+            single.update(&batch(&values)).unwrap();
+
+            // Cut the values into disjoint shards at the generated points
+            let mut cuts: Vec<usize> = split_points.into_iter().map(|p| p % (values.len() + 1)).collect();
+            cuts.push(0);
+            cuts.push(values.len());
+            cuts.sort_unstable();
+
+            let mut merged = stats();
+            for window in cuts.windows(2) {
+                let mut shard = stats();
+                shard.update(&batch(&values[window[0]..window[1]])).unwrap();
+                merged.merge(&shard).unwrap();
+            }
+
+            let a = &single.snapshot()[0];
+            let b = &merged.snapshot()[0];
+            prop_assert_eq!(a.count, b.count);
+            prop_assert_eq!(a.min, b.min);
+            prop_assert_eq!(a.max, b.max);
+            prop_assert!(close(a.mean.unwrap(), b.mean.unwrap()));
+            if let (Some(va), Some(vb)) = (a.variance, b.variance) {
+                prop_assert!((va - vb).abs() <= 1e-6 * va.abs().max(1.0));
+            }
+            // Sketch bucket counts are additive, so quantiles match exactly
+            prop_assert_eq!(&a.quantiles, &b.quantiles);
+        }
+    }
+}
@@ -0,0 +1,367 @@
+// Plot downsampling
+// Largest-Triangle-Three-Buckets, min/max and mean reduction of (x, y) series for plotting
+
+use std::collections::HashMap;
+use polars::prelude::*;
+use crate::error::InsightoraError;
+
+/// Column holding how many source points each output point stands for
+pub const REPRESENTED_COLUMN: &str = "represented";
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// How buckets of points are reduced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DownsampleMethod {
+    /// Largest-Triangle-Three-Buckets (Steinarsson, 2013): one point per
+    /// bucket, chosen to keep the visual shape of the line
+    #[default]
+    Lttb,
+    /// The lowest and highest point of each bucket, so spikes survive
+    MinMax,
+    /// Mean x and y of each bucket
+    Mean,
+}
+
+impl DownsampleMethod {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "lttb" => Ok(DownsampleMethod::Lttb),
+            "minmax" | "min_max" => Ok(DownsampleMethod::MinMax),
+            "mean" => Ok(DownsampleMethod::Mean),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown downsample method '{}'; expected 'lttb', 'minmax' or 'mean'",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DownsampleMethod::Lttb => "lttb",
+            DownsampleMethod::MinMax => "minmax",
+            DownsampleMethod::Mean => "mean",
+        }
+    }
+}
+
+/// One output point: a source position (or a bucket mean) and its weight
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Reduced {
+    /// Position in the sorted points; the bucket's first point for `Mean`
+    index: usize,
+    x: f64,
+    y: f64,
+    represented: usize,
+}
+
+// ============================================================================
+// Downsampling
+// ============================================================================
+
+/// Reduce an (x, y) series to at most `target_points` for plotting
+///
+/// Rows with a missing or non-finite x or y are dropped and the rest sorted
+/// by x. Series longer than `target_points` come back with exactly
+/// `target_points` points (per group); shorter ones come back whole. The
+/// first and last points are always kept as they are. LTTB and min/max
+/// return source rows; mean returns bucket averages.
+///
+/// # Arguments
+/// * `x` - Numeric, Date or Datetime column to order by
+/// * `y` - Numeric column to reduce
+/// * `target_points` - Points to return per series (at least 2)
+/// * `method` - How each bucket is reduced
+/// * `group_by` - Downsample each value of this column separately, in order
+///   of first appearance
+///
+/// # Returns
+/// * `Result<DataFrame>` - The group column (if any), `x`, `y` and
+///   `represented`, the number of source points each output point stands for
+pub fn downsample_for_plot(
+    df: &DataFrame,
+    x: &str,
+    y: &str,
+    target_points: usize,
+    method: DownsampleMethod,
+    group_by: Option<&str>,
+) -> Result<DataFrame, InsightoraError> {
+    if target_points < 2 {
+        return Err(InsightoraError::ValidationError(
+            "target_points must be at least 2 so the first and last points are kept".to_string(),
+        ));
+    }
+    if [Some(x), Some(y), group_by].contains(&Some(REPRESENTED_COLUMN)) {
+        return Err(InsightoraError::ValidationError(format!(
+            "Column '{}' is reserved for the represented point counts",
+            REPRESENTED_COLUMN
+        )));
+    }
+    let x_series = df.column(x)?;
+    let y_series = df.column(y)?;
+    let temporal = matches!(x_series.dtype(), DataType::Date | DataType::Datetime(_, _));
+    if !temporal && !x_series.dtype().is_numeric() {
+        return Err(InsightoraError::InvalidDataType {
+            expected: "numeric, Date or Datetime".to_string(),
+            actual: format!("{} ({})", x_series.dtype(), x),
+        });
+    }
+    if !y_series.dtype().is_numeric() {
+        return Err(InsightoraError::InvalidDataType {
+            expected: "numeric".to_string(),
+            actual: format!("{} ({})", y_series.dtype(), y),
+        });
+    }
+    let xs = as_f64(&x_series.to_physical_repr())?;
+    let ys = as_f64(y_series)?;
+
+    // Source rows per series, each sorted by x
+    let mut series_rows = match group_by {
+        Some(column) => group_rows(df.column(column)?)?,
+        None => vec![(0..df.height()).collect()],
+    };
+    for rows in &mut series_rows {
+        rows.retain(|&r| matches!((xs[r], ys[r]), (Some(a), Some(b)) if a.is_finite() && b.is_finite()));
+        rows.sort_by(|&a, &b| xs[a].unwrap_or_default().total_cmp(&xs[b].unwrap_or_default()));
+    }
+
+    let mut rows = Vec::new();
+    let mut reduced_x = Vec::new();
+    let mut reduced_y = Vec::new();
+    let mut represented = Vec::new();
+    for series in &series_rows {
+        let points: Vec<(f64, f64)> = series
+            .iter()
+            .map(|&r| (xs[r].unwrap_or_default(), ys[r].unwrap_or_default()))
+            .collect();
+        for point in reduce(&points, target_points, method) {
+            rows.push(series[point.index] as IdxSize);
+            reduced_x.push(point.x);
+            reduced_y.push(point.y);
+            represented.push(point.represented as u64);
+        }
+    }
+
+    let taken = IdxCa::from_vec("", rows);
+    let mut columns = Vec::with_capacity(4);
+    if let Some(column) = group_by {
+        columns.push(df.column(column)?.take(&taken)?);
+    }
+    if method == DownsampleMethod::Mean {
+        columns.push(if temporal {
+            let physical: Vec<i64> = reduced_x.iter().map(|v| v.round() as i64).collect();
+            Series::new(x, physical).cast(x_series.dtype())?
+        } else {
+            Series::new(x, reduced_x)
+        });
+        columns.push(Series::new(y, reduced_y));
+    } else {
+        columns.push(x_series.take(&taken)?);
+        columns.push(y_series.take(&taken)?);
+    }
+    columns.push(Series::new(REPRESENTED_COLUMN, represented));
+    Ok(DataFrame::new(columns)?)
+}
+
+fn as_f64(series: &Series) -> Result<Vec<Option<f64>>, InsightoraError> {
+    let cast = series.cast(&DataType::Float64)?;
+    Ok(cast.f64()?.into_iter().collect())
+}
+
+/// Row positions per distinct value, in order of first appearance
+fn group_rows(keys: &Series) -> Result<Vec<Vec<usize>>, InsightoraError> {
+    let keys = keys.cast(&DataType::String)?;
+    let mut positions: HashMap<Option<&str>, usize> = HashMap::new();
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for (row, key) in keys.str()?.into_iter().enumerate() {
+        let group = *positions.entry(key).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[group].push(row);
+    }
+    Ok(groups)
+}
+
+/// Reduce points sorted by x to exactly `target` points (all of them when fewer)
+fn reduce(points: &[(f64, f64)], target: usize, method: DownsampleMethod) -> Vec<Reduced> {
+    let keep = |index: usize| Reduced { index, x: points[index].0, y: points[index].1, represented: 1 };
+    if points.len() <= target {
+        return (0..points.len()).map(keep).collect();
+    }
+
+    let last = points.len() - 1;
+    let mut reduced = vec![keep(0)];
+    match method {
+        DownsampleMethod::Lttb => reduced.extend(lttb(points, target)),
+        DownsampleMethod::MinMax => reduced.extend(min_max(points, target)),
+        DownsampleMethod::Mean => {
+            reduced.extend(buckets(points.len(), target - 2).map(|range| {
+                let n = range.len() as f64;
+                Reduced {
+                    index: range.start,
+                    x: points[range.clone()].iter().map(|p| p.0).sum::<f64>() / n,
+                    y: points[range.clone()].iter().map(|p| p.1).sum::<f64>() / n,
+                    represented: range.len(),
+                }
+            }));
+        }
+    }
+    reduced.push(keep(last));
+    // Without interior buckets the endpoints stand for everything between them
+    if target == 2 {
+        let between = points.len() - 2;
+        reduced[0].represented += between.div_ceil(2);
+        reduced[1].represented += between / 2;
+    }
+    reduced
+}
+
+/// Split the interior points `1..len - 1` into `count` contiguous buckets
+///
+/// Boundaries are `floor(i * every) + 1` as in the reference LTTB, so
+/// bucket sizes differ by at most one.
+fn buckets(len: usize, count: usize) -> impl Iterator<Item = std::ops::Range<usize>> {
+    let every = (len - 2) as f64 / count as f64;
+    let bound = move |i: usize| ((i as f64 * every).floor() as usize + 1).min(len - 1);
+    (0..count).map(move |i| bound(i)..bound(i + 1))
+}
+
+/// Interior points chosen by Largest-Triangle-Three-Buckets
+///
+/// Each bucket keeps the point forming the largest triangle with the point
+/// kept from the previous bucket and the mean of the next bucket (the last
+/// point for the final bucket). Ties keep the earliest point.
+fn lttb(points: &[(f64, f64)], target: usize) -> Vec<Reduced> {
+    let ranges: Vec<_> = buckets(points.len(), target - 2).collect();
+    let mut previous = points[0];
+    let mut chosen = Vec::with_capacity(ranges.len());
+    for (i, range) in ranges.iter().enumerate() {
+        let next = match ranges.get(i + 1) {
+            Some(next) => {
+                let n = next.len() as f64;
+                let (sx, sy) = points[next.clone()].iter().fold((0.0, 0.0), |(sx, sy), p| (sx + p.0, sy + p.1));
+                (sx / n, sy / n)
+            }
+            None => points[points.len() - 1],
+        };
+        let mut best = range.start;
+        let mut best_area = -1.0;
+        for j in range.clone() {
+            let (px, py) = points[j];
+            let area = ((previous.0 - next.0) * (py - previous.1) - (previous.0 - px) * (next.1 - previous.1)).abs();
+            if area > best_area {
+                best_area = area;
+                best = j;
+            }
+        }
+        previous = points[best];
+        chosen.push(Reduced { index: best, x: previous.0, y: previous.1, represented: range.len() });
+    }
+    chosen
+}
+
+/// Interior points kept by min/max reduction
+///
+/// Each bucket contributes its lowest and highest point in x order, each
+/// standing for half of the bucket. With an odd number of interior points
+/// the last bucket keeps only the extreme furthest from its mean. Buckets
+/// hold at least two points since the series is longer than `target`.
+fn min_max(points: &[(f64, f64)], target: usize) -> Vec<Reduced> {
+    let interior = target - 2;
+    let count = interior.div_ceil(2);
+    let ranges: Vec<_> = buckets(points.len(), count).collect();
+    let mut chosen = Vec::with_capacity(interior);
+    for (i, range) in ranges.iter().enumerate() {
+        let by_y = |a: &usize, b: &usize| points[*a].1.total_cmp(&points[*b].1);
+        let low = range.clone().min_by(by_y).unwrap_or(range.start);
+        let high = range.clone().rev().max_by(by_y).unwrap_or(range.start);
+        let point = |index: usize, represented: usize| Reduced {
+            index,
+            x: points[index].0,
+            y: points[index].1,
+            represented,
+        };
+
+        if i == ranges.len() - 1 && interior % 2 == 1 {
+            let mean = points[range.clone()].iter().map(|p| p.1).sum::<f64>() / range.len() as f64;
+            let extreme = if points[high].1 - mean > mean - points[low].1 { high } else { low };
+            chosen.push(point(extreme, range.len()));
+            continue;
+        }
+        // Buckets hold at least two points here, so a flat one keeps its ends
+        let (first, second) = match low.cmp(&high) {
+            std::cmp::Ordering::Less => (low, high),
+            std::cmp::Ordering::Greater => (high, low),
+            std::cmp::Ordering::Equal => (range.start, range.end - 1),
+        };
+        chosen.push(point(first, range.len().div_ceil(2)));
+        chosen.push(point(second, range.len() / 2));
+    }
+    chosen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 40 points with a spike at 17 and a dip at 29
+    fn fixture() -> DataFrame {
+        let y: Vec<i64> = (0..40i64)
+            .map(|i| (i * 7) % 11 + if i == 17 { 20 } else { 0 } - if i == 29 { 15 } else { 0 })
+            .collect();
+        let x: Vec<f64> = (0..40).map(|i| i as f64).collect();
+        df!("x" => x, "y" => y).unwrap()
+    }
+
+    fn column_f64(df: &DataFrame, name: &str) -> Vec<f64> {
+        df.column(name).unwrap().cast(&DataType::Float64).unwrap().f64().unwrap().into_no_null_iter().collect()
+    }
+
+    #[test]
+    fn test_lttb_matches_reference_implementation() {
+        let reduced = downsample_for_plot(&fixture(), "x", "y", 10, DownsampleMethod::Lttb, None).unwrap();
+        // Indices from the reference JavaScript/Python implementation
+        assert_eq!(column_f64(&reduced, "x"), [0.0, 3.0, 5.0, 11.0, 17.0, 22.0, 25.0, 29.0, 34.0, 39.0]);
+        let represented: Vec<u64> = reduced.column(REPRESENTED_COLUMN).unwrap().u64().unwrap().into_no_null_iter().collect();
+        assert_eq!(represented, [1, 4, 5, 5, 5, 4, 5, 5, 5, 1]);
+        assert_eq!(represented.iter().sum::<u64>(), 40);
+        assert_eq!(reduced.column("y").unwrap().dtype(), &DataType::Int64);
+    }
+
+    #[test]
+    fn test_sizes_endpoints_and_groups() {
+        let df = fixture();
+        for method in [DownsampleMethod::Lttb, DownsampleMethod::MinMax, DownsampleMethod::Mean] {
+            for target in [2, 3, 7, 10, 39, 40, 100] {
+                let reduced = downsample_for_plot(&df, "x", "y", target, method, None).unwrap();
+                let xs = column_f64(&reduced, "x");
+                assert_eq!(xs.len(), target.min(40), "{:?} {}", method, target);
+                assert_eq!((xs[0], xs[xs.len() - 1]), (0.0, 39.0), "{:?} {}", method, target);
+                let total: u64 = reduced.column(REPRESENTED_COLUMN).unwrap().u64().unwrap().into_no_null_iter().sum();
+                assert_eq!(total, 40, "{:?} {}", method, target);
+            }
+        }
+
+        // Min/max keeps both the spike and the dip
+        let minmax = downsample_for_plot(&df, "x", "y", 6, DownsampleMethod::MinMax, None).unwrap();
+        let ys = column_f64(&minmax, "y");
+        assert!(ys.contains(&29.0) && ys.contains(&-10.0), "{:?}", ys);
+
+        // Groups are reduced separately, unsorted input included
+        let grouped = df! {
+            "g" => ["b", "a", "b", "a", "b", "a", "b"],
+            "x" => [6.0, 1.0, 2.0, 0.0, 4.0, 2.0, 0.0],
+            "y" => [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0],
+        }
+        .unwrap();
+        let reduced = downsample_for_plot(&grouped, "x", "y", 3, DownsampleMethod::Lttb, Some("g")).unwrap();
+        let groups: Vec<&str> = reduced.column("g").unwrap().str().unwrap().into_no_null_iter().collect();
+        assert_eq!(groups, ["b", "b", "b", "a", "a", "a"]);
+        assert_eq!(column_f64(&reduced, "x"), [0.0, 2.0, 6.0, 0.0, 1.0, 2.0]);
+
+        assert!(downsample_for_plot(&df, "x", "y", 1, DownsampleMethod::Lttb, None).is_err());
+    }
+}
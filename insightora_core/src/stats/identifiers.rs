@@ -0,0 +1,328 @@
+// Identifier detection
+// Flags ID-like columns (order numbers, zip codes) so they stay out of numeric summaries
+
+use std::collections::HashMap;
+use rayon::prelude::*;
+use polars::prelude::*;
+use crate::error::InsightoraError;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Name tokens that mark a column as an identifier when they end its name
+const ID_NAME_TOKENS: &[&str] = &[
+    "id", "identifier", "key", "code", "zip", "zipcode", "postcode", "postal", "sku", "uuid",
+    "guid", "phone", "ssn", "ean", "upc", "isbn", "number", "no",
+];
+
+/// Score at which a column is flagged
+const FLAG_SCORE: u32 = 3;
+
+const NAME_WEIGHT: u32 = 2;
+const UNIQUE_WEIGHT: u32 = 1;
+const WIDTH_WEIGHT: u32 = 1;
+const SEQUENCE_WEIGHT: u32 = 1;
+const LEADING_ZERO_WEIGHT: u32 = 3;
+
+/// Heuristic settings for identifier detection
+#[derive(Debug, Clone)]
+pub struct IdDetectionConfig {
+    /// Distinct/non-null ratio counted as "near row count"
+    pub min_unique_ratio: f64,
+    /// Columns with fewer non-null values are never flagged
+    pub min_values: usize,
+    /// Per-column decisions that replace the heuristic (true = identifier)
+    pub overrides: HashMap<String, bool>,
+}
+
+impl Default for IdDetectionConfig {
+    fn default() -> Self {
+        Self {
+            min_unique_ratio: 0.95,
+            min_values: 10,
+            overrides: HashMap::new(),
+        }
+    }
+}
+
+/// Outcome of identifier detection for one column
+///
+/// `reasons` lists every signal that contributed to `score` (or why the column
+/// was ruled out), so the decision can be audited.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdentifierDecision {
+    pub column: String,
+    pub is_identifier: bool,
+    pub score: u32,
+    /// True when the decision came from `IdDetectionConfig::overrides`
+    pub overridden: bool,
+    pub reasons: Vec<String>,
+}
+
+// ============================================================================
+// Detection
+// ============================================================================
+
+/// Decide for every column whether it holds identifiers rather than quantities
+///
+/// Only integer columns and digit-only string columns (as read before type
+/// inference, which keeps leading zeros) can be identifiers. Signals and
+/// their weights:
+/// * name ends in an identifier token (`order_id`, `zipCode`, `sku`) - 2
+/// * leading zeros in digit strings - 3
+/// * distinct values near the non-null count - 1
+/// * values share one width (allowing for dropped leading zeros) - 1
+/// * integers form a consecutive sequence - 1
+///
+/// A column is flagged at a score of 3. Negative numbers rule a column out.
+///
+/// # Arguments
+/// * `df` - Data to inspect (a sample is enough)
+/// * `config` - Thresholds and per-column overrides
+///
+/// # Returns
+/// * `Result<Vec<IdentifierDecision>>` - One decision per column, in column order
+pub fn detect_identifiers(df: &DataFrame, config: &IdDetectionConfig) -> Result<Vec<IdentifierDecision>, InsightoraError> {
+    for column in config.overrides.keys() {
+        df.column(column)?;
+    }
+    df.get_columns()
+        .par_iter()
+        .map(|series| {
+            let mut decision = evaluate_column(series, config)?;
+            if let Some(&forced) = config.overrides.get(series.name()) {
+                decision.is_identifier = forced;
+                decision.overridden = true;
+                decision.reasons.push(format!(
+                    "overridden by user: {}",
+                    if forced { "identifier" } else { "not an identifier" }
+                ));
+            }
+            Ok(decision)
+        })
+        .collect()
+}
+
+/// Names of the flagged columns, in column order
+pub fn identifier_columns(decisions: &[IdentifierDecision]) -> Vec<String> {
+    decisions
+        .iter()
+        .filter(|d| d.is_identifier)
+        .map(|d| d.column.clone())
+        .collect()
+}
+
+fn evaluate_column(series: &Series, config: &IdDetectionConfig) -> Result<IdentifierDecision, InsightoraError> {
+    let mut decision = IdentifierDecision {
+        column: series.name().to_string(),
+        is_identifier: false,
+        score: 0,
+        overridden: false,
+        reasons: Vec::new(),
+    };
+
+    let values = match column_digits(series)? {
+        Ok(values) => values,
+        Err(reason) => {
+            decision.reasons.push(reason);
+            return Ok(decision);
+        }
+    };
+    if values.len() < config.min_values {
+        decision.reasons.push(format!(
+            "only {} non-null values; at least {} needed to judge",
+            values.len(),
+            config.min_values
+        ));
+        return Ok(decision);
+    }
+
+    if let Some(token) = identifier_name_token(series.name()) {
+        decision.score += NAME_WEIGHT;
+        decision.reasons.push(format!("name ends in identifier token '{}'", token));
+    }
+
+    if let Some(example) = values.iter().find(|v| v.len() > 1 && v.starts_with('0')) {
+        decision.score += LEADING_ZERO_WEIGHT;
+        decision.reasons.push(format!("leading zeros (e.g. '{}')", example));
+    }
+
+    let distinct = series.n_unique()? - usize::from(series.null_count() > 0);
+    let ratio = distinct as f64 / values.len() as f64;
+    if ratio >= config.min_unique_ratio {
+        decision.score += UNIQUE_WEIGHT;
+        decision.reasons.push(format!(
+            "{} of {} values distinct ({:.1}%)",
+            distinct,
+            values.len(),
+            ratio * 100.0
+        ));
+    }
+
+    if let Some(width) = shared_width(&values) {
+        decision.score += WIDTH_WEIGHT;
+        decision.reasons.push(format!("values are {} digits wide", width));
+    }
+
+    if series.dtype().is_integer() && distinct == values.len() {
+        let ints = series.cast(&DataType::Int64)?;
+        let ints = ints.i64()?;
+        if let (Some(min), Some(max)) = (ints.min(), ints.max()) {
+            if (max - min) as u64 + 1 == values.len() as u64 {
+                decision.score += SEQUENCE_WEIGHT;
+                decision.reasons.push(format!("consecutive sequence {}..={}", min, max));
+            }
+        }
+    }
+
+    decision.is_identifier = decision.score >= FLAG_SCORE;
+    if decision.reasons.is_empty() {
+        decision.reasons.push("no identifier signals".to_string());
+    }
+    Ok(decision)
+}
+
+/// Non-null values as digit strings, or the reason the column can't be an identifier
+fn column_digits(series: &Series) -> Result<Result<Vec<String>, String>, InsightoraError> {
+    match series.dtype() {
+        dtype if dtype.is_integer() => {
+            let ints = series.cast(&DataType::Int64)?;
+            let mut values = Vec::with_capacity(ints.len() - ints.null_count());
+            for value in ints.i64()?.into_iter().flatten() {
+                if value < 0 {
+                    return Ok(Err("negative values carry arithmetic meaning".to_string()));
+                }
+                values.push(value.to_string());
+            }
+            Ok(Ok(values))
+        }
+        DataType::String => {
+            let mut values = Vec::with_capacity(series.len() - series.null_count());
+            for value in series.str()?.into_iter().flatten() {
+                let value = value.trim();
+                if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+                    return Ok(Err("values are not plain digit strings".to_string()));
+                }
+                values.push(value.to_string());
+            }
+            Ok(Ok(values))
+        }
+        dtype => Ok(Err(format!("{} columns are never identifiers", dtype))),
+    }
+}
+
+/// Trailing name token (snake_case, kebab-case or camelCase) if it marks an identifier
+fn identifier_name_token(name: &str) -> Option<&'static str> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+            previous_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && previous_lower && !current.is_empty() {
+            tokens.push(std::mem::take(&mut current));
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    let last = tokens.last()?;
+    ID_NAME_TOKENS.iter().copied().find(|token| token == last)
+}
+
+/// Common width of at least three digits, tolerating up to two dropped leading zeros
+///
+/// Integers lose leading zeros on parse (zip 02134 becomes 2134), so a column
+/// counts as fixed-width when at least 90% of values have the widest width
+/// and the rest are at most two digits shorter.
+fn shared_width(values: &[String]) -> Option<usize> {
+    let width = values.iter().map(String::len).max()?;
+    if width < 3 {
+        return None;
+    }
+    let mut full = 0usize;
+    for value in values {
+        if value.len() == width {
+            full += 1;
+        } else if value.len() + 2 < width {
+            return None;
+        }
+    }
+    (full as f64 >= 0.9 * values.len() as f64).then_some(width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decision<'a>(decisions: &'a [IdentifierDecision], column: &str) -> &'a IdentifierDecision {
+        decisions.iter().find(|d| d.column == column).unwrap()
+    }
+
+    fn sample() -> DataFrame {
+        let rows = 40i64;
+        df! {
+            "order_id" => (1000..1000 + rows).collect::<Vec<_>>(),
+            // Integer zips with leading zeros dropped
+            "zip" => (0..rows).map(|i| if i % 20 == 0 { 2134 } else { 90210 + i % 5 }).collect::<Vec<_>>(),
+            "zip_text" => (0..rows).map(|i| format!("{:05}", 2134 + i % 3)).collect::<Vec<_>>(),
+            "quantity" => (0..rows).map(|i| 1 + i % 7).collect::<Vec<_>>(),
+            "amount" => (0..rows).map(|i| i as f64 * 12.5).collect::<Vec<_>>(),
+            "delta" => (0..rows).map(|i| i - 20).collect::<Vec<_>>(),
+        }
+        .unwrap()
+    }
+
+    #[test]
+    fn test_flags_identifier_columns_with_reasons() {
+        let decisions = detect_identifiers(&sample(), &IdDetectionConfig::default()).unwrap();
+        assert_eq!(identifier_columns(&decisions), vec!["order_id", "zip", "zip_text"]);
+
+        let order_id = decision(&decisions, "order_id");
+        assert_eq!(order_id.score, 5);
+        assert!(order_id.reasons.iter().any(|r| r.contains("'id'")));
+        assert!(order_id.reasons.iter().any(|r| r.contains("consecutive")));
+
+        let zip_text = decision(&decisions, "zip_text");
+        assert!(zip_text.reasons.iter().any(|r| r.contains("leading zeros (e.g. '02134')")));
+
+        assert!(decision(&decisions, "delta").reasons[0].contains("negative"));
+        assert!(decision(&decisions, "amount").reasons[0].contains("never identifiers"));
+        assert!(!decision(&decisions, "quantity").is_identifier);
+    }
+
+    #[test]
+    fn test_overrides_win_and_are_recorded() {
+        let config = IdDetectionConfig {
+            overrides: HashMap::from([("order_id".to_string(), false), ("quantity".to_string(), true)]),
+            ..Default::default()
+        };
+        let decisions = detect_identifiers(&sample(), &config).unwrap();
+        assert_eq!(identifier_columns(&decisions), vec!["zip", "zip_text", "quantity"]);
+        assert!(decision(&decisions, "order_id").overridden);
+
+        let missing = IdDetectionConfig {
+            overrides: HashMap::from([("nope".to_string(), true)]),
+            ..Default::default()
+        };
+        assert!(detect_identifiers(&sample(), &missing).is_err());
+    }
+
+    #[test]
+    fn test_name_tokens() {
+        assert_eq!(identifier_name_token("customerId"), Some("id"));
+        assert_eq!(identifier_name_token("Postal-Code"), Some("code"));
+        assert_eq!(identifier_name_token("ID"), Some("id"));
+        assert_eq!(identifier_name_token("paid"), None);
+        assert_eq!(identifier_name_token("id_count"), None);
+    }
+}
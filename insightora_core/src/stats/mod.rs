@@ -1,6 +1,11 @@
 // Statistical computations module
-// Provides descriptive statistics, correlation, outlier detection
+// Provides descriptive statistics, group comparisons, hypothesis tests, correlation,
+// outlier detection, identifier detection and plot downsampling
 
 pub mod descriptive;
+pub mod comparison;
+pub mod tests;
 pub mod correlation;
 pub mod outliers;
+pub mod identifiers;
+pub mod downsample;
@@ -1,2 +1,577 @@
-// Outlier detection implementation
-// TODO: Implement in task 15
+// Outlier detection
+// Distribution diagnostics and per-column suggestions for outlier thresholds
+
+use polars::prelude::*;
+use crate::error::InsightoraError;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Columns with fewer finite values get no recommendation
+const MIN_VALUES: usize = 8;
+
+/// Below this many values a recommendation is made with reduced confidence
+const CONFIDENT_VALUES: usize = 30;
+
+/// The dip is computed on at most this many evenly spaced order statistics
+const DIP_MAX_POINTS: usize = 1000;
+
+/// Candidate mode positions tried when fitting the unimodal envelope
+const DIP_MODE_CANDIDATES: usize = 64;
+
+/// sqrt(n) * dip above which a distribution counts as multimodal
+///
+/// The 95% point of the dip under the uniform null, Hartigan's least
+/// favourable unimodal distribution, so normal-ish data stays well below it.
+const DIP_CRITICAL: f64 = 0.53;
+
+/// (q99 - q01) / (q75 - q25) for a normal distribution
+const NORMAL_TAIL_RATIO: f64 = 3.449;
+
+/// Scales the MAD to a standard deviation for normal data
+const MAD_SCALE: f64 = 1.4826;
+
+/// Outlier rule with its parameter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutlierMethod {
+    /// Outside `[q1 - k * iqr, q3 + k * iqr]`
+    Iqr { k: f64 },
+    /// `|x - mean| / std` above the threshold
+    ZScore { threshold: f64 },
+    /// `|x - median| / (1.4826 * MAD)` above the threshold
+    ModifiedZScore { threshold: f64 },
+    /// IQR fences on `ln(x)`; strictly positive data only
+    LogIqr { k: f64 },
+}
+
+impl OutlierMethod {
+    /// Candidates evaluated for every column, in reporting order
+    pub const CANDIDATES: [OutlierMethod; 5] = [
+        OutlierMethod::Iqr { k: 1.5 },
+        OutlierMethod::Iqr { k: 3.0 },
+        OutlierMethod::ZScore { threshold: 3.0 },
+        OutlierMethod::ModifiedZScore { threshold: 3.5 },
+        OutlierMethod::LogIqr { k: 1.5 },
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutlierMethod::Iqr { .. } => "iqr",
+            OutlierMethod::ZScore { .. } => "zscore",
+            OutlierMethod::ModifiedZScore { .. } => "modified_zscore",
+            OutlierMethod::LogIqr { .. } => "log_iqr",
+        }
+    }
+
+    /// Fence multiplier or score threshold
+    pub fn parameter(&self) -> f64 {
+        match *self {
+            OutlierMethod::Iqr { k } | OutlierMethod::LogIqr { k } => k,
+            OutlierMethod::ZScore { threshold } | OutlierMethod::ModifiedZScore { threshold } => threshold,
+        }
+    }
+
+    /// Inclusive bounds of the non-outlier range for sorted finite values
+    ///
+    /// None when the rule doesn't apply: fewer than two values, zero spread,
+    /// or non-positive values for `LogIqr`.
+    pub fn bounds(&self, sorted: &[f64]) -> Option<(f64, f64)> {
+        if sorted.len() < 2 {
+            return None;
+        }
+        match *self {
+            OutlierMethod::Iqr { k } => {
+                let (q1, q3) = (quantile(sorted, 0.25), quantile(sorted, 0.75));
+                let iqr = q3 - q1;
+                (iqr > 0.0).then_some((q1 - k * iqr, q3 + k * iqr))
+            }
+            OutlierMethod::ZScore { threshold } => {
+                let (mean, variance) = mean_variance(sorted);
+                let std = (variance * sorted.len() as f64 / (sorted.len() - 1) as f64).sqrt();
+                (std > 0.0).then_some((mean - threshold * std, mean + threshold * std))
+            }
+            OutlierMethod::ModifiedZScore { threshold } => {
+                let median = quantile(sorted, 0.5);
+                let mut deviations: Vec<f64> = sorted.iter().map(|v| (v - median).abs()).collect();
+                deviations.sort_by(f64::total_cmp);
+                let scale = MAD_SCALE * quantile(&deviations, 0.5);
+                (scale > 0.0).then_some((median - threshold * scale, median + threshold * scale))
+            }
+            OutlierMethod::LogIqr { k } => {
+                if sorted[0] <= 0.0 {
+                    return None;
+                }
+                let logs: Vec<f64> = sorted.iter().map(|v| v.ln()).collect();
+                let (lower, upper) = OutlierMethod::Iqr { k }.bounds(&logs)?;
+                Some((lower.exp(), upper.exp()))
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Result Types
+// ============================================================================
+
+/// Shape diagnostics of a column's finite values
+#[derive(Debug, Clone, PartialEq)]
+pub struct DistributionShape {
+    /// Moment skewness
+    pub skewness: f64,
+    /// Excess kurtosis (0 for a normal distribution)
+    pub kurtosis: f64,
+    /// Approximate Hartigan dip statistic
+    pub dip: f64,
+    /// Dip above the critical value: more than one mode
+    pub multimodal: bool,
+    /// (q99 - q01) / (q75 - q25) relative to a normal distribution; above 1 means heavier tails
+    pub tail_weight: f64,
+    /// Skewness of ln(x), when every value is positive
+    pub log_skewness: Option<f64>,
+}
+
+/// How many points one candidate rule would flag
+#[derive(Debug, Clone, PartialEq)]
+pub struct CandidateOutcome {
+    pub method: OutlierMethod,
+    /// None when the rule doesn't apply to the column
+    pub bounds: Option<(f64, f64)>,
+    pub flagged: usize,
+}
+
+/// Suggestion confidence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuggestionConfidence {
+    High,
+    Medium,
+    Low,
+}
+
+impl SuggestionConfidence {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SuggestionConfidence::High => "high",
+            SuggestionConfidence::Medium => "medium",
+            SuggestionConfidence::Low => "low",
+        }
+    }
+
+    fn lowered(self) -> Self {
+        match self {
+            SuggestionConfidence::High => SuggestionConfidence::Medium,
+            _ => SuggestionConfidence::Low,
+        }
+    }
+}
+
+/// Recommended outlier rule for one column
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThresholdSuggestion {
+    pub column: String,
+    /// Finite values the suggestion is based on
+    pub count: usize,
+    /// None for columns too small or constant to characterise
+    pub shape: Option<DistributionShape>,
+    /// None when no rule can be recommended
+    pub recommended: Option<OutlierMethod>,
+    pub confidence: SuggestionConfidence,
+    /// Why the rule was picked, for display next to it
+    pub note: String,
+    /// Every candidate rule, in `OutlierMethod::CANDIDATES` order
+    pub candidates: Vec<CandidateOutcome>,
+}
+
+impl ThresholdSuggestion {
+    /// Outcome of the recommended rule
+    pub fn recommended_outcome(&self) -> Option<&CandidateOutcome> {
+        let method = self.recommended?;
+        self.candidates.iter().find(|c| c.method == method)
+    }
+}
+
+// ============================================================================
+// Suggestions
+// ============================================================================
+
+/// Recommend an outlier rule and parameters for each numeric column
+///
+/// Each column's finite values are characterised by skewness, excess
+/// kurtosis, tail weight and an approximate dip test for multimodality.
+/// The rule is picked deterministically:
+/// * more than one mode - IQR with k=3, low confidence (a global rule cuts
+///   between the modes; consider detecting per group)
+/// * near normal (|skew| <= 0.5, |kurtosis| <= 1) - z-score 3
+/// * positive, right-skewed, near normal after a log - log-scale IQR k=1.5
+/// * heavy tails, roughly symmetric - modified z-score 3.5
+/// * moderately skewed (|skew| <= 1) - IQR k=1.5, otherwise IQR k=3
+///
+/// Confidence drops a level below 30 values; under 8 values, or for a
+/// constant column, nothing is recommended. Every candidate rule's bounds and
+/// flagged count are reported so a UI can offer the alternatives.
+///
+/// # Arguments
+/// * `df` - Input DataFrame
+/// * `columns` - Columns to inspect (default: every numeric column)
+///
+/// # Returns
+/// * `Result<Vec<ThresholdSuggestion>>` - One suggestion per column, in
+///   column order, or an error for a missing or non-numeric column
+pub fn suggest_thresholds(df: &DataFrame, columns: Option<&[String]>) -> Result<Vec<ThresholdSuggestion>, InsightoraError> {
+    let selected: Vec<&Series> = match columns {
+        Some(cols) => cols.iter().map(|c| df.column(c)).collect::<Result<_, _>>()?,
+        None => df.get_columns().iter().filter(|s| s.dtype().is_numeric()).collect(),
+    };
+
+    selected
+        .into_iter()
+        .map(|series| {
+            if !series.dtype().is_numeric() {
+                return Err(InsightoraError::InvalidDataType {
+                    expected: "numeric".to_string(),
+                    actual: format!("{} ({})", series.dtype(), series.name()),
+                });
+            }
+            let cast = series.cast(&DataType::Float64)?;
+            let mut values: Vec<f64> = cast.f64()?.into_iter().flatten().filter(|v| v.is_finite()).collect();
+            values.sort_by(f64::total_cmp);
+            Ok(suggest_for_values(series.name(), &values))
+        })
+        .collect()
+}
+
+fn suggest_for_values(column: &str, sorted: &[f64]) -> ThresholdSuggestion {
+    let candidates = OutlierMethod::CANDIDATES
+        .iter()
+        .map(|&method| {
+            let bounds = method.bounds(sorted);
+            let flagged = bounds.map_or(0, |(lower, upper)| {
+                sorted.iter().filter(|&&v| v < lower || v > upper).count()
+            });
+            CandidateOutcome { method, bounds, flagged }
+        })
+        .collect();
+
+    let mut suggestion = ThresholdSuggestion {
+        column: column.to_string(),
+        count: sorted.len(),
+        shape: None,
+        recommended: None,
+        confidence: SuggestionConfidence::Low,
+        note: String::new(),
+        candidates,
+    };
+
+    if sorted.len() < MIN_VALUES {
+        suggestion.note = format!(
+            "only {} values; at least {} needed to suggest a threshold",
+            sorted.len(),
+            MIN_VALUES
+        );
+        return suggestion;
+    }
+    let Some(shape) = distribution_shape(sorted) else {
+        suggestion.note = "column is constant".to_string();
+        return suggestion;
+    };
+
+    let (method, confidence, note) = recommend(&shape);
+    // A rule that doesn't apply (zero IQR or MAD) falls back to the z-score
+    let (method, note) = if method.bounds(sorted).is_some() {
+        (method, note)
+    } else {
+        (
+            OutlierMethod::ZScore { threshold: 3.0 },
+            format!("{}; {} has zero spread here, using z-score 3 instead", note, method.name()),
+        )
+    };
+    suggestion.recommended = Some(method);
+    suggestion.confidence = confidence;
+    suggestion.note = note;
+    if sorted.len() < CONFIDENT_VALUES {
+        suggestion.confidence = suggestion.confidence.lowered();
+        suggestion.note.push_str(&format!("; only {} values", sorted.len()));
+    }
+    suggestion.shape = Some(shape);
+    suggestion
+}
+
+fn recommend(shape: &DistributionShape) -> (OutlierMethod, SuggestionConfidence, String) {
+    let skew = shape.skewness;
+    if shape.multimodal {
+        return (
+            OutlierMethod::Iqr { k: 3.0 },
+            SuggestionConfidence::Low,
+            format!(
+                "more than one mode (dip {:.3}); a global rule cuts between modes, consider detecting outliers per group",
+                shape.dip
+            ),
+        );
+    }
+    if skew.abs() <= 0.5 && shape.kurtosis.abs() <= 1.0 {
+        return (
+            OutlierMethod::ZScore { threshold: 3.0 },
+            SuggestionConfidence::High,
+            format!("approximately normal (skewness {:.2}, kurtosis {:.2})", skew, shape.kurtosis),
+        );
+    }
+    if let Some(log_skew) = shape.log_skewness {
+        if skew > 1.0 && log_skew.abs() <= 0.5 {
+            return (
+                OutlierMethod::LogIqr { k: 1.5 },
+                SuggestionConfidence::High,
+                format!("right-skewed (skewness {:.2}) and near symmetric on a log scale ({:.2})", skew, log_skew),
+            );
+        }
+    }
+    if skew.abs() <= 1.0 && (shape.kurtosis > 3.0 || shape.tail_weight > 1.5) {
+        return (
+            OutlierMethod::ModifiedZScore { threshold: 3.5 },
+            SuggestionConfidence::Medium,
+            format!(
+                "heavy tails (kurtosis {:.2}, tail weight {:.2}); median and MAD resist the extremes",
+                shape.kurtosis, shape.tail_weight
+            ),
+        );
+    }
+    if skew.abs() <= 1.0 {
+        (
+            OutlierMethod::Iqr { k: 1.5 },
+            SuggestionConfidence::Medium,
+            format!("moderately skewed (skewness {:.2}); IQR fences don't assume symmetry", skew),
+        )
+    } else {
+        (
+            OutlierMethod::Iqr { k: 3.0 },
+            SuggestionConfidence::Medium,
+            format!("strongly skewed (skewness {:.2}); wide IQR fences", skew),
+        )
+    }
+}
+
+// ============================================================================
+// Distribution Diagnostics
+// ============================================================================
+
+/// Shape of sorted finite values, or None when they have no spread
+fn distribution_shape(sorted: &[f64]) -> Option<DistributionShape> {
+    let (skewness, kurtosis) = moments(sorted)?;
+    let dip = dip_statistic(sorted);
+    let points = sorted.len().min(DIP_MAX_POINTS) as f64;
+
+    let iqr = quantile(sorted, 0.75) - quantile(sorted, 0.25);
+    let tail_weight = if iqr > 0.0 {
+        (quantile(sorted, 0.99) - quantile(sorted, 0.01)) / iqr / NORMAL_TAIL_RATIO
+    } else {
+        f64::INFINITY
+    };
+
+    let log_skewness = if sorted[0] > 0.0 {
+        let logs: Vec<f64> = sorted.iter().map(|v| v.ln()).collect();
+        moments(&logs).map(|(skew, _)| skew)
+    } else {
+        None
+    };
+
+    Some(DistributionShape {
+        skewness,
+        kurtosis,
+        dip,
+        multimodal: dip * points.sqrt() > DIP_CRITICAL,
+        tail_weight,
+        log_skewness,
+    })
+}
+
+/// Population mean and variance
+fn mean_variance(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    (mean, variance)
+}
+
+/// Moment skewness and excess kurtosis, or None for zero variance
+fn moments(values: &[f64]) -> Option<(f64, f64)> {
+    let (mean, variance) = mean_variance(values);
+    if variance <= 0.0 {
+        return None;
+    }
+    let n = values.len() as f64;
+    let (mut m3, mut m4) = (0.0, 0.0);
+    for v in values {
+        let d = v - mean;
+        m3 += d.powi(3);
+        m4 += d.powi(4);
+    }
+    Some((m3 / n / variance.powf(1.5), m4 / n / variance.powi(2) - 3.0))
+}
+
+/// Linearly interpolated quantile of sorted values
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let position = q * (sorted.len() - 1) as f64;
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (position - lower as f64)
+}
+
+/// Approximate Hartigan dip statistic of sorted values
+///
+/// The empirical CDF is compared with unimodal envelopes: the greatest convex
+/// minorant left of a candidate mode and the least concave majorant right of
+/// it. Half the largest gap is the dip for that mode; the smallest over a
+/// fixed grid of modes is returned. Large inputs are thinned to evenly
+/// spaced order statistics, so the result is deterministic and bounded in cost.
+fn dip_statistic(sorted: &[f64]) -> f64 {
+    let thinned: Vec<f64> = if sorted.len() > DIP_MAX_POINTS {
+        let step = (sorted.len() - 1) as f64 / (DIP_MAX_POINTS - 1) as f64;
+        (0..DIP_MAX_POINTS).map(|i| sorted[(i as f64 * step).round() as usize]).collect()
+    } else {
+        sorted.to_vec()
+    };
+    let n = thinned.len();
+    let points: Vec<(f64, f64)> = thinned
+        .into_iter()
+        .enumerate()
+        .map(|(i, x)| (x, (i as f64 + 0.5) / n as f64))
+        .collect();
+
+    (0..=DIP_MODE_CANDIDATES)
+        .map(|c| {
+            let mode = (c as f64 * (n - 1) as f64 / DIP_MODE_CANDIDATES as f64).round() as usize;
+            let left = hull_gap(&points[..=mode], true);
+            let right = hull_gap(&points[mode..], false);
+            left.max(right) / 2.0
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// Largest vertical gap between points and their lower (convex) or upper (concave) hull
+fn hull_gap(points: &[(f64, f64)], lower: bool) -> f64 {
+    let cross = |o: (f64, f64), a: (f64, f64), b: (f64, f64)| (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0);
+    let mut hull: Vec<usize> = Vec::new();
+    for (i, &point) in points.iter().enumerate() {
+        while hull.len() >= 2 {
+            let turn = cross(points[hull[hull.len() - 2]], points[hull[hull.len() - 1]], point);
+            if (lower && turn <= 0.0) || (!lower && turn >= 0.0) {
+                hull.pop();
+            } else {
+                break;
+            }
+        }
+        hull.push(i);
+    }
+
+    let mut gap = 0.0f64;
+    for edge in hull.windows(2) {
+        let ((x0, y0), (x1, y1)) = (points[edge[0]], points[edge[1]]);
+        for &(x, y) in &points[edge[0] + 1..edge[1]] {
+            let on_hull = if x1 == x0 { y0 } else { y0 + (y1 - y0) * (x - x0) / (x1 - x0) };
+            gap = gap.max(if lower { y - on_hull } else { on_hull - y });
+        }
+    }
+    gap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic standard normal draws (LCG + Box-Muller)
+    fn normals(n: usize, seed: u64) -> Vec<f64> {
+        let mut state = seed;
+        let mut uniform = move || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        };
+        let mut values = Vec::with_capacity(n + 1);
+        while values.len() < n {
+            let radius = (-2.0 * uniform().max(1e-12).ln()).sqrt();
+            let angle = 2.0 * std::f64::consts::PI * uniform();
+            values.push(radius * angle.cos());
+            values.push(radius * angle.sin());
+        }
+        values.truncate(n);
+        values
+    }
+
+    fn suggestion_for(df: &DataFrame, column: &str) -> ThresholdSuggestion {
+        suggest_thresholds(df, Some(&[column.to_string()])).unwrap().remove(0)
+    }
+
+    fn synthetic() -> DataFrame {
+        let n = 2000;
+        df! {
+            "normal" => normals(n, 7).iter().map(|z| 50.0 + 10.0 * z).collect::<Vec<_>>(),
+            "lognormal" => normals(n, 11).iter().map(|z| z.exp()).collect::<Vec<_>>(),
+            "bimodal" => normals(n, 13).iter().enumerate()
+                .map(|(i, z)| z + if i % 2 == 0 { 3.0 } else { -3.0 })
+                .collect::<Vec<_>>(),
+            "label" => (0..n).map(|i| format!("r{}", i)).collect::<Vec<_>>(),
+        }
+        .unwrap()
+    }
+
+    #[test]
+    fn test_recommendations_follow_distribution_shape() {
+        let df = synthetic();
+        let suggestions = suggest_thresholds(&df, None).unwrap();
+        let columns: Vec<&str> = suggestions.iter().map(|s| s.column.as_str()).collect();
+        assert_eq!(columns, vec!["normal", "lognormal", "bimodal"]);
+
+        let normal = &suggestions[0];
+        assert_eq!(normal.recommended, Some(OutlierMethod::ZScore { threshold: 3.0 }));
+        assert_eq!(normal.confidence, SuggestionConfidence::High);
+        assert!(!normal.shape.as_ref().unwrap().multimodal);
+        // About 0.27% of a normal sample lies beyond 3 sigma
+        let flagged = normal.recommended_outcome().unwrap().flagged;
+        assert!(flagged > 0 && flagged < 20, "flagged {}", flagged);
+
+        let lognormal = &suggestions[1];
+        assert_eq!(lognormal.recommended, Some(OutlierMethod::LogIqr { k: 1.5 }));
+        assert!(lognormal.shape.as_ref().unwrap().skewness > 1.0);
+        // The plain IQR fence flags the long right tail wholesale
+        let by_name = |s: &ThresholdSuggestion, m: OutlierMethod| s.candidates.iter().find(|c| c.method == m).unwrap().flagged;
+        assert!(by_name(lognormal, OutlierMethod::Iqr { k: 1.5 }) > 5 * by_name(lognormal, OutlierMethod::LogIqr { k: 1.5 }));
+
+        let bimodal = &suggestions[2];
+        assert!(bimodal.shape.as_ref().unwrap().multimodal);
+        assert_eq!(bimodal.recommended, Some(OutlierMethod::Iqr { k: 3.0 }));
+        assert_eq!(bimodal.confidence, SuggestionConfidence::Low);
+        assert!(bimodal.note.contains("per group"));
+        // Log IQR doesn't apply to data with negative values
+        assert_eq!(bimodal.candidates.iter().find(|c| c.method.name() == "log_iqr").unwrap().bounds, None);
+
+        // Deterministic: the same input gives the same suggestions
+        assert_eq!(suggest_thresholds(&df, None).unwrap(), suggestions);
+    }
+
+    #[test]
+    fn test_small_constant_and_invalid_columns() {
+        let mut few = vec![None; 12];
+        few[..3].copy_from_slice(&[Some(1.0), Some(2.0), Some(3.0)]);
+        let df = df! {
+            "few" => few,
+            "constant" => vec![4.0; 12],
+            "small" => normals(12, 3),
+            "text" => vec!["a"; 12],
+        }
+        .unwrap();
+
+        let few = suggestion_for(&df, "few");
+        assert_eq!(few.count, 3);
+        assert_eq!(few.recommended, None);
+        assert!(few.note.contains("only 3 values"));
+
+        let constant = suggestion_for(&df, "constant");
+        assert_eq!(constant.recommended, None);
+        assert_eq!(constant.note, "column is constant");
+
+        let small = suggestion_for(&df, "small");
+        assert!(small.recommended.is_some());
+        assert_ne!(small.confidence, SuggestionConfidence::High);
+
+        assert!(suggest_thresholds(&df, Some(&["text".to_string()])).is_err());
+        assert!(suggest_thresholds(&df, Some(&["missing".to_string()])).is_err());
+    }
+}
@@ -0,0 +1,552 @@
+// Hypothesis tests
+// t-tests, one-way ANOVA, two-proportion z-tests and two-sample Kolmogorov-Smirnov tests on grouped data
+
+use std::collections::BTreeMap;
+use polars::prelude::*;
+use crate::error::InsightoraError;
+use crate::stats::comparison::{chi_square_sf, ln_gamma};
+
+/// Largest group for which `ks_test` computes the exact p-value
+pub const KS_EXACT_MAX_N: usize = 10_000;
+
+// ============================================================================
+// Result Types
+// ============================================================================
+
+/// Values of one group that entered a test
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestGroup {
+    pub group: String,
+    /// Values used
+    pub count: usize,
+    /// Null and NaN values left out
+    pub null_count: usize,
+    pub mean: Option<f64>,
+}
+
+/// Outcome of a hypothesis test
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestResult {
+    /// "welch_t", "student_t", "anova", "proportion_z" or "ks"
+    pub test: &'static str,
+    /// None when undefined, e.g. a t-test on two groups without variance
+    pub statistic: Option<f64>,
+    /// The t-test's degrees of freedom, or ANOVA's between- and
+    /// within-group degrees of freedom; empty for the other tests
+    pub degrees_of_freedom: Vec<f64>,
+    /// Two-sided p-value; None when the statistic is undefined
+    pub p_value: Option<f64>,
+    /// Groups in sorted order of their text form
+    pub groups: Vec<TestGroup>,
+    /// Rows left out because their group is null
+    pub null_groups: usize,
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+/// Two-sample t-test of `value_column` between the two groups of `group_column`
+///
+/// Welch's test by default; with `equal_var` Student's test with the pooled
+/// variance. The statistic is for the first group minus the second, in
+/// sorted order. Null and NaN values, and rows with a null group, are left
+/// out and counted. Matches `scipy.stats.ttest_ind`.
+///
+/// # Arguments
+/// * `df` - Input DataFrame
+/// * `value_column` - Numeric column tested
+/// * `group_column` - Column with exactly two distinct non-null values
+/// * `equal_var` - Assume equal variances (Student) rather than not (Welch)
+///
+/// # Returns
+/// * `Result<TestResult>` - ValidationError unless there are two groups of
+///   at least two values each
+pub fn t_test(df: &DataFrame, value_column: &str, group_column: &str, equal_var: bool) -> Result<TestResult, InsightoraError> {
+    let (samples, null_groups) = grouped_values(df, value_column, group_column)?;
+    let (a, b) = two_groups(samples, "t_test", group_column)?;
+    for sample in [&a, &b] {
+        if sample.values.len() < 2 {
+            return Err(InsightoraError::ValidationError(format!(
+                "Group '{}' has {} values; a t-test needs at least 2 per group",
+                sample.group,
+                sample.values.len()
+            )));
+        }
+    }
+
+    let ((mean_a, var_a), (mean_b, var_b)) = (mean_var(&a.values), mean_var(&b.values));
+    let (na, nb) = (a.values.len() as f64, b.values.len() as f64);
+    let (se2, dof) = if equal_var {
+        let pooled = ((na - 1.0) * var_a + (nb - 1.0) * var_b) / (na + nb - 2.0);
+        (pooled * (1.0 / na + 1.0 / nb), na + nb - 2.0)
+    } else {
+        let (qa, qb) = (var_a / na, var_b / nb);
+        ((qa + qb), (qa + qb).powi(2) / (qa * qa / (na - 1.0) + qb * qb / (nb - 1.0)))
+    };
+    // Both groups constant: undefined, as in scipy
+    let statistic = (se2 > 0.0).then(|| (mean_a - mean_b) / se2.sqrt());
+
+    Ok(TestResult {
+        test: if equal_var { "student_t" } else { "welch_t" },
+        statistic,
+        degrees_of_freedom: if dof.is_finite() { vec![dof] } else { Vec::new() },
+        p_value: statistic.map(|t| student_t_two_sided(t, dof)),
+        groups: vec![a.summary(), b.summary()],
+        null_groups,
+    })
+}
+
+/// One-way ANOVA of `value_column` across the groups of `group_column`
+///
+/// Null and NaN values, and rows with a null group, are left out and
+/// counted. Matches `scipy.stats.f_oneway`.
+///
+/// # Returns
+/// * `Result<TestResult>` - F statistic with (between, within) degrees of
+///   freedom; ValidationError for fewer than two groups, an empty group or
+///   no more values than groups
+pub fn anova(df: &DataFrame, value_column: &str, group_column: &str) -> Result<TestResult, InsightoraError> {
+    let (samples, null_groups) = grouped_values(df, value_column, group_column)?;
+    if samples.len() < 2 {
+        return Err(InsightoraError::ValidationError(format!(
+            "anova needs at least 2 groups in column '{}'; found {}",
+            group_column,
+            samples.len()
+        )));
+    }
+    if let Some(empty) = samples.iter().find(|s| s.values.is_empty()) {
+        return Err(InsightoraError::ValidationError(format!("Group '{}' has no values", empty.group)));
+    }
+    let total: usize = samples.iter().map(|s| s.values.len()).sum();
+    if total <= samples.len() {
+        return Err(InsightoraError::ValidationError(format!(
+            "anova needs more values than groups; found {} values in {} groups",
+            total,
+            samples.len()
+        )));
+    }
+
+    let grand_mean = samples.iter().flat_map(|s| s.values.iter()).sum::<f64>() / total as f64;
+    let (mut between, mut within) = (0.0, 0.0);
+    for sample in &samples {
+        let mean = mean_var(&sample.values).0;
+        between += sample.values.len() as f64 * (mean - grand_mean).powi(2);
+        within += sample.values.iter().map(|v| (v - mean).powi(2)).sum::<f64>();
+    }
+    let (dof_between, dof_within) = ((samples.len() - 1) as f64, (total - samples.len()) as f64);
+    // Every group constant: undefined, as in scipy
+    let statistic = (within > 0.0).then(|| (between / dof_between) / (within / dof_within));
+
+    Ok(TestResult {
+        test: "anova",
+        statistic,
+        degrees_of_freedom: vec![dof_between, dof_within],
+        p_value: statistic.map(|f| f_sf(f, dof_between, dof_within)),
+        groups: samples.iter().map(Sample::summary).collect(),
+        null_groups,
+    })
+}
+
+/// Two-proportion z-test with the pooled proportion
+///
+/// Groups are reported as "a" and "b" with their success rate as the mean.
+///
+/// # Returns
+/// * `Result<TestResult>` - z statistic for a minus b; ValidationError for an
+///   empty group or more successes than trials. Undefined when every trial,
+///   or none, is a success
+pub fn proportion_test(successes_a: u64, n_a: u64, successes_b: u64, n_b: u64) -> Result<TestResult, InsightoraError> {
+    for (group, successes, n) in [("a", successes_a, n_a), ("b", successes_b, n_b)] {
+        if n == 0 || successes > n {
+            return Err(InsightoraError::ValidationError(format!(
+                "Group {} has {} successes in {} trials; expected at least 1 trial and no more successes than trials",
+                group, successes, n
+            )));
+        }
+    }
+    let (rate_a, rate_b) = (successes_a as f64 / n_a as f64, successes_b as f64 / n_b as f64);
+    let pooled = (successes_a + successes_b) as f64 / (n_a + n_b) as f64;
+    let se2 = pooled * (1.0 - pooled) * (1.0 / n_a as f64 + 1.0 / n_b as f64);
+    let statistic = (se2 > 0.0).then(|| (rate_a - rate_b) / se2.sqrt());
+    let group = |group: &str, n: u64, rate: f64| TestGroup {
+        group: group.to_string(),
+        count: n as usize,
+        null_count: 0,
+        mean: Some(rate),
+    };
+
+    Ok(TestResult {
+        test: "proportion_z",
+        statistic,
+        degrees_of_freedom: Vec::new(),
+        // z^2 is chi-square with one degree of freedom
+        p_value: statistic.map(|z| chi_square_sf(z * z, 1.0)),
+        groups: vec![group("a", n_a, rate_a), group("b", n_b, rate_b)],
+        null_groups: 0,
+    })
+}
+
+/// Two-sample Kolmogorov-Smirnov test of `column` between the two groups of `group_column`
+///
+/// The p-value is exact, as `scipy.stats.ks_2samp` computes it by default,
+/// while both groups have at most `KS_EXACT_MAX_N` values; for larger groups
+/// it comes from the asymptotic Kolmogorov distribution, which scipy refines
+/// further, so the two then agree to about three decimals.
+///
+/// # Returns
+/// * `Result<TestResult>` - The largest distance between the two empirical
+///   distribution functions; ValidationError unless there are two groups
+///   with values
+pub fn ks_test(df: &DataFrame, column: &str, group_column: &str) -> Result<TestResult, InsightoraError> {
+    let (samples, null_groups) = grouped_values(df, column, group_column)?;
+    let (a, b) = two_groups(samples, "ks_test", group_column)?;
+    if let Some(empty) = [&a, &b].into_iter().find(|s| s.values.is_empty()) {
+        return Err(InsightoraError::ValidationError(format!("Group '{}' has no values", empty.group)));
+    }
+
+    let (mut sorted_a, mut sorted_b) = (a.values.clone(), b.values.clone());
+    sorted_a.sort_by(|x, y| x.total_cmp(y));
+    sorted_b.sort_by(|x, y| x.total_cmp(y));
+    let (m, n) = (sorted_a.len(), sorted_b.len());
+    // Both step functions at each value, counting values <= it
+    let (mut i, mut j, mut distance) = (0, 0, 0.0f64);
+    while i < m && j < n {
+        let value = sorted_a[i].min(sorted_b[j]);
+        while i < m && sorted_a[i] <= value {
+            i += 1;
+        }
+        while j < n && sorted_b[j] <= value {
+            j += 1;
+        }
+        distance = distance.max((i as f64 / m as f64 - j as f64 / n as f64).abs());
+    }
+    // Past the end of one sample the other's distance only shrinks
+
+    let (statistic, p_value) = if m.max(n) <= KS_EXACT_MAX_N {
+        ks_exact(distance, m, n)
+    } else {
+        let scale = (m as f64 * n as f64 / (m + n) as f64).sqrt();
+        (distance, kolmogorov_sf(distance * scale))
+    };
+
+    Ok(TestResult {
+        test: "ks",
+        statistic: Some(statistic),
+        degrees_of_freedom: Vec::new(),
+        p_value: Some(p_value),
+        groups: vec![a.summary(), b.summary()],
+        null_groups,
+    })
+}
+
+// ============================================================================
+// Grouping
+// ============================================================================
+
+/// Non-null values of one group
+struct Sample {
+    group: String,
+    values: Vec<f64>,
+    null_count: usize,
+}
+
+impl Sample {
+    fn summary(&self) -> TestGroup {
+        TestGroup {
+            group: self.group.clone(),
+            count: self.values.len(),
+            null_count: self.null_count,
+            mean: (!self.values.is_empty()).then(|| mean_var(&self.values).0),
+        }
+    }
+}
+
+/// Values of `value_column` per group in sorted order, and the rows with a null group
+fn grouped_values(df: &DataFrame, value_column: &str, group_column: &str) -> Result<(Vec<Sample>, usize), InsightoraError> {
+    let values = df.column(value_column)?;
+    if !values.dtype().is_numeric() {
+        return Err(InsightoraError::InvalidDataType {
+            expected: "numeric column".to_string(),
+            actual: format!("{} ({})", values.dtype(), value_column),
+        });
+    }
+    let values = values.cast(&DataType::Float64)?;
+    let groups = df.column(group_column)?.cast(&DataType::String)?;
+
+    let mut by_group: BTreeMap<&str, Sample> = BTreeMap::new();
+    let mut null_groups = 0;
+    for (value, group) in values.f64()?.into_iter().zip(groups.str()?) {
+        let Some(group) = group else {
+            null_groups += 1;
+            continue;
+        };
+        let sample = by_group.entry(group).or_insert_with(|| Sample {
+            group: group.to_string(),
+            values: Vec::new(),
+            null_count: 0,
+        });
+        match value {
+            Some(value) if !value.is_nan() => sample.values.push(value),
+            _ => sample.null_count += 1,
+        }
+    }
+    Ok((by_group.into_values().collect(), null_groups))
+}
+
+fn two_groups(samples: Vec<Sample>, test: &str, group_column: &str) -> Result<(Sample, Sample), InsightoraError> {
+    if samples.len() != 2 {
+        let names: Vec<&str> = samples.iter().map(|s| s.group.as_str()).collect();
+        return Err(InsightoraError::ValidationError(format!(
+            "{} needs exactly 2 groups in column '{}'; found {}{}",
+            test,
+            group_column,
+            samples.len(),
+            if names.is_empty() { String::new() } else { format!(" ({})", names.join(", ")) }
+        )));
+    }
+    let mut samples = samples.into_iter();
+    Ok((samples.next().unwrap(), samples.next().unwrap()))
+}
+
+/// Mean and sample variance (n - 1 denominator), in two passes
+fn mean_var(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let ss: f64 = values.iter().map(|v| (v - mean).powi(2)).sum();
+    (mean, ss / (n - 1.0))
+}
+
+// ============================================================================
+// Distribution Functions
+// ============================================================================
+
+/// Two-sided p-value of Student's t distribution
+fn student_t_two_sided(t: f64, dof: f64) -> f64 {
+    let t2 = t * t;
+    regularized_beta(dof / 2.0, 0.5, dof / (dof + t2), t2 / (dof + t2))
+}
+
+/// Upper tail of the F distribution
+fn f_sf(f: f64, dof_num: f64, dof_den: f64) -> f64 {
+    let scaled = dof_num * f;
+    regularized_beta(dof_den / 2.0, dof_num / 2.0, dof_den / (dof_den + scaled), scaled / (dof_den + scaled))
+}
+
+/// Regularized incomplete beta function I_x(a, b)
+///
+/// `y` is 1 - x, passed separately so it keeps its precision when x is
+/// close to 1.
+fn regularized_beta(a: f64, b: f64, x: f64, y: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if y <= 0.0 {
+        return 1.0;
+    }
+    let log_prefix = ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * y.ln();
+    // The continued fraction converges quickly below the mean; use the
+    // symmetry I_x(a, b) = 1 - I_y(b, a) above it
+    if x < (a + 1.0) / (a + b + 2.0) {
+        (log_prefix.exp() * beta_fraction(a, b, x) / a).clamp(0.0, 1.0)
+    } else {
+        (1.0 - log_prefix.exp() * beta_fraction(b, a, y) / b).clamp(0.0, 1.0)
+    }
+}
+
+/// Continued fraction for the incomplete beta function (modified Lentz)
+fn beta_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const EPS: f64 = 1e-15;
+    const TINY: f64 = 1e-300;
+    const MAX_ITER: usize = 10_000;
+
+    let (qab, qap, qam) = (a + b, a + 1.0, a - 1.0);
+    let mut c = 1.0;
+    let mut d = 1.0 - qab * x / qap;
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut h = d;
+    for m in 1..MAX_ITER {
+        let m = m as f64;
+        let m2 = 2.0 * m;
+        // Even step
+        let aa = m * (b - m) * x / ((qam + m2) * (a + m2));
+        d = 1.0 + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        h *= d * c;
+        // Odd step
+        let aa = -(a + m) * (qab + m) * x / ((a + m2) * (qap + m2));
+        d = 1.0 + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPS {
+            break;
+        }
+    }
+    h
+}
+
+/// Exact two-sided p-value of the two-sample KS statistic, as scipy computes it
+///
+/// The distance is first rounded to a multiple of 1 / lcm(m, n), as the
+/// statistic can only take those values; P(D >= d) is then one minus the
+/// share of the lattice paths from (0, 0) to (m, n) that stay strictly
+/// within distance d of the diagonal. Returns the rounded distance too.
+fn ks_exact(distance: f64, m: usize, n: usize) -> (f64, f64) {
+    let g = gcd(m, n);
+    let lcm = (m / g) * n;
+    let h = (distance * lcm as f64).round() as usize;
+    let rounded = h as f64 / lcm as f64;
+    if h == 0 {
+        return (rounded, 1.0);
+    }
+    // |i/m - j/n| < h/lcm, in integers
+    let bound = (h * g) as i64;
+    let inside = |i: usize, j: usize| ((i * n) as i64 - (j * m) as i64).abs() < bound;
+
+    // Share of the paths to (i, j) that stayed inside, one column at a time;
+    // the path counts themselves would overflow
+    let mut share = vec![0.0f64; n + 1];
+    for i in 0..=m {
+        for j in 0..=n {
+            share[j] = if !inside(i, j) {
+                0.0
+            } else if i == 0 && j == 0 {
+                1.0
+            } else {
+                let from_left = if i > 0 { share[j] * i as f64 } else { 0.0 };
+                let from_below = if j > 0 { share[j - 1] * j as f64 } else { 0.0 };
+                (from_left + from_below) / (i + j) as f64
+            };
+        }
+    }
+    (rounded, (1.0 - share[n]).clamp(0.0, 1.0))
+}
+
+/// Upper tail of the asymptotic Kolmogorov distribution
+fn kolmogorov_sf(lambda: f64) -> f64 {
+    if lambda < 0.2 {
+        return 1.0;
+    }
+    let mut sum = 0.0;
+    for k in 1..=100 {
+        let term = (-2.0 * (k * k) as f64 * lambda * lambda).exp();
+        sum += if k % 2 == 1 { term } else { -term };
+        if term < 1e-16 {
+            break;
+        }
+    }
+    (2.0 * sum).clamp(0.0, 1.0)
+}
+
+fn gcd(mut a: usize, mut b: usize) -> usize {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests {
+    use super::*;
+
+    // Reference values from scipy.stats (ttest_ind, f_oneway, ks_2samp) and
+    // the normal tail for the proportion test
+    fn trial() -> DataFrame {
+        df!(
+            "variant" => [Some("a"), Some("a"), Some("a"), Some("a"), Some("a"), Some("a"),
+                          Some("b"), Some("b"), Some("b"), Some("b"), Some("b"), Some("b"), Some("b"), Some("b"), Some("b"),
+                          None],
+            "value" => [Some(12.1), Some(14.3), None, Some(11.8), Some(15.2), Some(13.7),
+                        Some(16.4), Some(15.9), Some(17.8), None, Some(14.6), Some(18.2), Some(16.1), Some(f64::NAN), Some(17.0),
+                        Some(99.0)]
+        )
+        .unwrap()
+    }
+
+    fn close(actual: Option<f64>, expected: f64) {
+        let actual = actual.unwrap();
+        assert!((actual - expected).abs() < 1e-8, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn test_t_tests_match_scipy_with_unbalanced_groups_and_nulls() {
+        let welch = t_test(&trial(), "value", "variant", false).unwrap();
+        assert_eq!(welch.test, "welch_t");
+        close(welch.statistic, -3.963_759_797_135_752_7);
+        close(welch.degrees_of_freedom.first().copied(), 7.756_398_397_204_349);
+        close(welch.p_value, 0.004_421_000_631_142_11);
+        let counts: Vec<(usize, usize)> = welch.groups.iter().map(|g| (g.count, g.null_count)).collect();
+        assert_eq!(counts, vec![(5, 1), (7, 2)]);
+        assert_eq!(welch.null_groups, 1);
+
+        let student = t_test(&trial(), "value", "variant", true).unwrap();
+        close(student.statistic, -4.089_293_370_505_746);
+        assert_eq!(student.degrees_of_freedom, vec![10.0]);
+        close(student.p_value, 0.002_181_649_715_933_393);
+
+        let constant = df!("g" => ["x", "x", "y", "y"], "v" => [1.0, 1.0, 2.0, 2.0]).unwrap();
+        let result = t_test(&constant, "v", "g", false).unwrap();
+        assert_eq!((result.statistic, result.p_value), (None, None));
+    }
+
+    #[test]
+    fn test_anova_proportion_and_ks_match_scipy() {
+        let mut df = trial();
+        let extra = df!(
+            "variant" => [Some("c"); 4],
+            "value" => [Some(13.0), Some(12.2), Some(14.8), Some(13.9)]
+        )
+        .unwrap();
+        df.vstack_mut(&extra).unwrap();
+        let result = anova(&df, "value", "variant").unwrap();
+        close(result.statistic, 11.857_066_966_234_346);
+        assert_eq!(result.degrees_of_freedom, vec![2.0, 13.0]);
+        close(result.p_value, 0.001_172_776_942_164_909_1);
+        let err = t_test(&df, "value", "variant", false).unwrap_err().to_string();
+        assert!(err.contains("t_test needs exactly 2 groups in column 'variant'; found 3 (a, b, c)"), "{}", err);
+
+        let result = proportion_test(45, 100, 30, 90).unwrap();
+        close(result.statistic, 1.642_726_612_871_929_2);
+        close(result.p_value, 0.100_439_509_979_635_5);
+        assert!(proportion_test(5, 4, 1, 10).is_err());
+        assert_eq!(proportion_test(0, 10, 0, 20).unwrap().p_value, None);
+
+        let result = ks_test(&trial(), "value", "variant").unwrap();
+        close(result.statistic, 6.0 / 7.0);
+        close(result.p_value, 0.015_151_515_151_515_152);
+
+        // Unequal sizes with ties, and equal sizes
+        let x = [0.61, 0.29, 0.06, 0.59, -1.73, -0.74, 0.51, -0.56, 0.39, 1.64, 0.05, -0.06, 0.64, -0.82, 0.37,
+                 1.77, 1.09, -1.28, 2.36, 1.31, 1.05, -0.32, -0.4, 1.06, -2.47];
+        let y = [2.2, 1.66, 1.38, 0.2, 1.36, 0.0, 0.0, 1.61, 1.45, 0.18, 0.08, 0.86, 1.39, 0.28, 0.69, 1.44, 1.94,
+                 0.56, 1.26, 1.03];
+        for (take, distance, p) in [(25, 0.37, 0.074_128_410_885_644_77), (20, 0.4, 0.081_057_711_613_401_51)] {
+            let groups: Vec<&str> = std::iter::repeat_n("x", take).chain(std::iter::repeat_n("y", 20)).collect();
+            let values: Vec<f64> = x[..take].iter().chain(&y).copied().collect();
+            let df = df!("group" => groups, "value" => values).unwrap();
+            let result = ks_test(&df, "value", "group").unwrap();
+            close(result.statistic, distance);
+            close(result.p_value, p);
+        }
+        assert!((kolmogorov_sf(1.358_099) - 0.05).abs() < 1e-6);
+    }
+}
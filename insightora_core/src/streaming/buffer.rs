@@ -1,2 +1,373 @@
 // Streaming buffer management
-// TODO: Implement in task 18
+// Bounded prefetch of CSV and Excel batches on a dedicated reader thread
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use polars::prelude::*;
+use crate::error::InsightoraError;
+use crate::io::csv_parser::{StreamingCsvConfig, StreamingCsvParser};
+use crate::io::excel_parser::StreamingExcelParser;
+
+/// Result of polling the prefetch buffer
+#[derive(Debug)]
+pub enum PrefetchPoll {
+    Batch(DataFrame),
+    Error(InsightoraError),
+    /// No batch arrived within the timeout
+    Pending,
+    /// The file is exhausted or the prefetcher was cancelled
+    Finished,
+}
+
+/// A batch and its estimated size in bytes
+type BatchResult = Result<(DataFrame, usize), InsightoraError>;
+
+/// Estimated bytes of the batches buffered and not yet taken
+///
+/// The reader reserves a batch's size before buffering it and waits while
+/// that would go over the limit; the consumer releases it on taking the
+/// batch. One batch is always allowed, however large, so a limit below the
+/// batch size slows the reader to lockstep rather than stalling it.
+struct MemoryBudget {
+    used: Mutex<usize>,
+    released: Condvar,
+    limit: usize,
+}
+
+impl MemoryBudget {
+    fn new(limit_mb: usize) -> Self {
+        Self { used: Mutex::new(0), released: Condvar::new(), limit: limit_mb.saturating_mul(1024 * 1024) }
+    }
+
+    /// Wait until `bytes` fits beside the buffered batches; false once cancelled
+    fn reserve(&self, bytes: usize, cancelled: &AtomicBool) -> bool {
+        let Ok(mut used) = self.used.lock() else {
+            return false;
+        };
+        while *used > 0 && used.saturating_add(bytes) > self.limit {
+            if cancelled.load(Ordering::Relaxed) {
+                return false;
+            }
+            used = match self.released.wait_timeout(used, Duration::from_millis(50)) {
+                Ok((used, _)) => used,
+                Err(_) => return false,
+            };
+        }
+        *used += bytes;
+        true
+    }
+
+    fn release(&self, bytes: usize) {
+        if let Ok(mut used) = self.used.lock() {
+            *used = used.saturating_sub(bytes);
+        }
+        self.released.notify_all();
+    }
+}
+
+/// Reads batches ahead of the consumer on a dedicated thread
+///
+/// At most `depth` completed batches are buffered, and no more than fit in
+/// the config's `memory_limit_mb` by their estimated size; once the buffer
+/// is full the reader thread blocks until the consumer takes one, so a slow
+/// consumer bounds memory use. Cancelling (or dropping) the prefetcher stops the reader
+/// at the next batch boundary and joins the thread, which closes the file.
+pub struct BatchPrefetcher {
+    receiver: Mutex<Option<Receiver<BatchResult>>>,
+    budget: Arc<MemoryBudget>,
+    cancelled: Arc<AtomicBool>,
+    batches_read: Arc<AtomicUsize>,
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl BatchPrefetcher {
+    /// Start reading `file_path` in the background
+    ///
+    /// # Arguments
+    /// * `file_path` - Path to the CSV file
+    /// * `config` - Streaming parser configuration (chunk_size is the batch size)
+    /// * `depth` - Number of batches to read ahead (at least 1)
+    pub fn spawn(file_path: &str, config: StreamingCsvConfig, depth: usize) -> Result<Self, InsightoraError> {
+        Self::spawn_parser(file_path, StreamingCsvParser::with_config(config), depth)
+    }
+
+    /// Start reading `file_path` in the background with `parser`, keeping
+    /// its progress callback and cancel flag
+    ///
+    /// A cancel flag set by the callback ends the batches as `cancel` does.
+    pub fn spawn_parser(file_path: &str, parser: StreamingCsvParser, depth: usize) -> Result<Self, InsightoraError> {
+        let memory_limit_mb = parser.config().memory_limit_mb;
+        let path = file_path.to_string();
+        Self::spawn_with(memory_limit_mb, depth, move |emit| parser.parse_batches(&path, emit))
+    }
+
+    /// Start reading an Excel worksheet in the background with `parser`,
+    /// keeping its progress callback and cancel flag
+    pub fn spawn_excel(file_path: &str, parser: StreamingExcelParser, depth: usize) -> Result<Self, InsightoraError> {
+        let memory_limit_mb = parser.config().memory_limit_mb;
+        let path = file_path.to_string();
+        Self::spawn_with(memory_limit_mb, depth, move |emit| parser.parse_batches(&path, emit))
+    }
+
+    /// Start `read`, which hands each batch it reads to the callback it is
+    /// given, on the reader thread
+    fn spawn_with<R>(memory_limit_mb: usize, depth: usize, read: R) -> Result<Self, InsightoraError>
+    where
+        R: FnOnce(&mut dyn FnMut(DataFrame) -> Result<(), InsightoraError>) -> Result<(), InsightoraError> + Send + 'static,
+    {
+        if depth == 0 {
+            return Err(InsightoraError::ValidationError(
+                "Prefetch depth must be at least 1".to_string(),
+            ));
+        }
+
+        let (sender, receiver) = sync_channel::<BatchResult>(depth);
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let batches_read = Arc::new(AtomicUsize::new(0));
+        let budget = Arc::new(MemoryBudget::new(memory_limit_mb));
+
+        let worker = {
+            let cancelled = Arc::clone(&cancelled);
+            let batches_read = Arc::clone(&batches_read);
+            let budget = Arc::clone(&budget);
+            std::thread::Builder::new()
+                .name("insightora-prefetch".to_string())
+                .spawn(move || {
+                    let result = read(&mut |batch: DataFrame| {
+                        let bytes = batch.estimated_size();
+                        if cancelled.load(Ordering::Relaxed) || !budget.reserve(bytes, &cancelled) {
+                            return Err(InsightoraError::Cancelled);
+                        }
+                        // Blocks while the buffer is full; fails once the consumer is gone
+                        sender.send(Ok((batch, bytes))).map_err(|_| InsightoraError::Cancelled)?;
+                        batches_read.fetch_add(1, Ordering::Relaxed);
+                        Ok(())
+                    });
+                    match result {
+                        Ok(()) | Err(InsightoraError::Cancelled) => {}
+                        Err(e) => {
+                            let _ = sender.send(Err(e));
+                        }
+                    }
+                })?
+        };
+
+        Ok(Self {
+            receiver: Mutex::new(Some(receiver)),
+            budget,
+            cancelled,
+            batches_read,
+            worker: Mutex::new(Some(worker)),
+        })
+    }
+
+    /// Wait up to `timeout` for the next batch
+    pub fn poll(&self, timeout: Duration) -> PrefetchPoll {
+        let guard = match self.receiver.lock() {
+            Ok(guard) => guard,
+            Err(_) => return PrefetchPoll::Finished,
+        };
+        let Some(receiver) = guard.as_ref() else {
+            return PrefetchPoll::Finished;
+        };
+        match receiver.recv_timeout(timeout) {
+            Ok(Ok((batch, bytes))) => {
+                self.budget.release(bytes);
+                PrefetchPoll::Batch(batch)
+            }
+            Ok(Err(e)) => PrefetchPoll::Error(e),
+            Err(RecvTimeoutError::Timeout) => PrefetchPoll::Pending,
+            Err(RecvTimeoutError::Disconnected) => PrefetchPoll::Finished,
+        }
+    }
+
+    /// Block until the next batch; None when finished or cancelled
+    ///
+    /// Polls in short slices so a concurrent `cancel` is noticed promptly.
+    pub fn next_batch(&self) -> Option<Result<DataFrame, InsightoraError>> {
+        loop {
+            if self.is_cancelled() {
+                return None;
+            }
+            match self.poll(Duration::from_millis(50)) {
+                PrefetchPoll::Batch(batch) => return Some(Ok(batch)),
+                PrefetchPoll::Error(e) => return Some(Err(e)),
+                PrefetchPoll::Finished => return None,
+                PrefetchPoll::Pending => continue,
+            }
+        }
+    }
+
+    /// Stop the reader thread and release the file
+    ///
+    /// Idempotent. Buffered batches are discarded.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        // Dropping the receiver unblocks a reader waiting on a full buffer
+        if let Ok(mut receiver) = self.receiver.lock() {
+            receiver.take();
+        }
+        if let Ok(mut worker) = self.worker.lock() {
+            if let Some(handle) = worker.take() {
+                let _ = handle.join();
+            }
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Batches handed to the buffer so far
+    pub fn batches_read(&self) -> usize {
+        self.batches_read.load(Ordering::Relaxed)
+    }
+
+    /// True once the reader thread has exited
+    pub fn is_reader_finished(&self) -> bool {
+        match self.worker.lock() {
+            Ok(worker) => match worker.as_ref() {
+                Some(handle) => handle.is_finished(),
+                None => true,
+            },
+            Err(_) => true,
+        }
+    }
+}
+
+impl Drop for BatchPrefetcher {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::time::Instant;
+    use tempfile::NamedTempFile;
+
+    fn csv_with_rows(rows: usize) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "id,value").unwrap();
+        for i in 0..rows {
+            writeln!(file, "{},{}", i, i * 2).unwrap();
+        }
+        file
+    }
+
+    fn config(chunk_size: usize) -> StreamingCsvConfig {
+        StreamingCsvConfig {
+            chunk_size,
+            ..Default::default()
+        }
+    }
+
+    fn wait_for(condition: impl Fn() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_reads_all_batches_in_order() {
+        let file = csv_with_rows(25);
+        let prefetcher = BatchPrefetcher::spawn(file.path().to_str().unwrap(), config(10), 2).unwrap();
+
+        let mut heights = Vec::new();
+        while let Some(batch) = prefetcher.next_batch() {
+            heights.push(batch.unwrap().height());
+        }
+        assert_eq!(heights, vec![10, 10, 5]);
+    }
+
+    #[test]
+    fn test_backpressure_stops_at_depth() {
+        let file = csv_with_rows(100);
+        let prefetcher = BatchPrefetcher::spawn(file.path().to_str().unwrap(), config(5), 3).unwrap();
+
+        wait_for(|| prefetcher.batches_read() >= 3);
+        std::thread::sleep(Duration::from_millis(100));
+        // Slow consumer: the reader is parked with the buffer full
+        assert_eq!(prefetcher.batches_read(), 3);
+
+        assert!(prefetcher.next_batch().is_some());
+        wait_for(|| prefetcher.batches_read() >= 4);
+        assert_eq!(prefetcher.batches_read(), 4);
+    }
+
+    #[test]
+    fn test_memory_limit_gates_buffered_batches() {
+        let file = csv_with_rows(100);
+        let config = StreamingCsvConfig { memory_limit_mb: 0, ..config(5) };
+        let prefetcher = BatchPrefetcher::spawn(file.path().to_str().unwrap(), config, 4).unwrap();
+
+        wait_for(|| prefetcher.batches_read() >= 1);
+        std::thread::sleep(Duration::from_millis(100));
+        // Room for four by depth, but a second batch would go over the limit
+        assert_eq!(prefetcher.batches_read(), 1);
+
+        let mut heights = Vec::new();
+        while let Some(batch) = prefetcher.next_batch() {
+            heights.push(batch.unwrap().height());
+        }
+        assert_eq!(heights, vec![5; 20]);
+    }
+
+    #[test]
+    fn test_cancel_stops_reader() {
+        let file = csv_with_rows(100);
+        let prefetcher = BatchPrefetcher::spawn(file.path().to_str().unwrap(), config(5), 2).unwrap();
+        wait_for(|| prefetcher.batches_read() >= 2);
+
+        prefetcher.cancel();
+        assert!(prefetcher.is_reader_finished());
+        assert!(prefetcher.next_batch().is_none());
+        assert!(prefetcher.batches_read() < 20);
+    }
+
+    /// Descriptors of this process open on `path`; always 0 off Linux
+    fn open_handles(path: &std::path::Path) -> usize {
+        let Ok(entries) = std::fs::read_dir("/proc/self/fd") else {
+            return 0;
+        };
+        let path = path.canonicalize().unwrap();
+        entries
+            .filter_map(|entry| std::fs::read_link(entry.ok()?.path()).ok())
+            .filter(|target| *target == path)
+            .count()
+    }
+
+    #[test]
+    fn test_cancel_releases_file() {
+        // Not a NamedTempFile, whose own handle would keep the file open
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("rows.csv");
+        let mut file = std::fs::File::create(&path).unwrap();
+        writeln!(file, "id,value").unwrap();
+        for i in 0..1000 {
+            writeln!(file, "{},{}", i, i * 2).unwrap();
+        }
+        drop(file);
+
+        let prefetcher = BatchPrefetcher::spawn(path.to_str().unwrap(), config(5), 1).unwrap();
+        wait_for(|| prefetcher.batches_read() >= 1);
+        prefetcher.cancel();
+
+        assert_eq!(open_handles(&path), 0);
+        // Fails on Windows while any handle is still open
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_missing_file_reports_error() {
+        let prefetcher = BatchPrefetcher::spawn("does_not_exist.csv", config(5), 2).unwrap();
+        assert!(matches!(prefetcher.next_batch(), Some(Err(_))));
+        assert!(prefetcher.next_batch().is_none());
+    }
+}
@@ -1,5 +1,5 @@
 // Real-time streaming module
-// Handles time-based window aggregations and buffering
+// Handles time-based window aggregations and prefetch buffering
 
 pub mod window;
 pub mod buffer;
@@ -0,0 +1,195 @@
+// Fast-path capabilities and degradations
+// Records when an operation falls back from SIMD kernels, memory mapping or parallelism, and enforces require_fast_paths
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::thread::{self, ThreadId};
+use once_cell::sync::Lazy;
+use crate::config::{get_current_config, RustConfig};
+use crate::error::InsightoraError;
+use crate::utils::simd::KernelPath;
+
+/// Degradations kept for `degradations()`; older ones are dropped first
+pub const DEGRADATION_LOG_SIZE: usize = 256;
+
+/// An accelerated path an operation may use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FastPath {
+    /// Vectorized (AVX2) numeric kernels
+    Simd,
+    /// Memory-mapped file input
+    Mmap,
+    /// Work spread over the Rayon thread pool
+    Parallel,
+}
+
+impl FastPath {
+    pub const ALL: [FastPath; 3] = [FastPath::Simd, FastPath::Mmap, FastPath::Parallel];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FastPath::Simd => "simd",
+            FastPath::Mmap => "mmap",
+            FastPath::Parallel => "parallel",
+        }
+    }
+}
+
+/// Whether a fast path can be used in this process and configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capability {
+    pub path: FastPath,
+    pub available: bool,
+    /// Why it is unavailable
+    pub reason: Option<String>,
+}
+
+/// A fallback taken by one operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Degradation {
+    pub operation: String,
+    pub path: FastPath,
+    /// What ran instead ("scalar", "in_memory", "single_thread")
+    pub fallback: String,
+    pub reason: String,
+    sequence: u64,
+    thread: ThreadId,
+}
+
+struct DegradationLog {
+    next_sequence: u64,
+    entries: VecDeque<Degradation>,
+}
+
+static LOG: Lazy<Mutex<DegradationLog>> = Lazy::new(|| {
+    Mutex::new(DegradationLog { next_sequence: 0, entries: VecDeque::new() })
+});
+
+/// Availability of one fast path
+pub fn capability(path: FastPath) -> Capability {
+    capability_with(&get_current_config(), path)
+}
+
+fn capability_with(config: &RustConfig, path: FastPath) -> Capability {
+    let reason = match path {
+        FastPath::Simd => match (config.enable_simd, KernelPath::best_available()) {
+            (false, _) => Some("disabled by configuration (enable_simd=False)".to_string()),
+            (true, KernelPath::Scalar) if cfg!(target_arch = "x86_64") => Some("CPU does not support AVX2".to_string()),
+            (true, KernelPath::Scalar) => Some(format!("no vectorized kernels for {}", std::env::consts::ARCH)),
+            (true, KernelPath::Avx2) => None,
+        },
+        FastPath::Mmap if cfg!(any(unix, windows)) => None,
+        FastPath::Mmap => Some(format!("memory mapping is not supported on {}", std::env::consts::OS)),
+        FastPath::Parallel => match rayon::current_num_threads() {
+            1 => Some("thread pool has a single thread (thread_count=1 or one CPU)".to_string()),
+            _ => None,
+        },
+    };
+    Capability { path, available: reason.is_none(), reason }
+}
+
+/// Availability of every fast path
+pub fn capabilities() -> Vec<Capability> {
+    FastPath::ALL.iter().map(|&path| capability(path)).collect()
+}
+
+/// Record a fallback taken by `operation`
+///
+/// With `require_fast_paths` set the fallback is not taken and this
+/// returns a `FastPathUnavailable` error instead.
+pub fn degrade(operation: &str, path: FastPath, fallback: &str, reason: &str) -> Result<(), InsightoraError> {
+    degrade_with(&get_current_config(), operation, path, fallback, reason)
+}
+
+fn degrade_with(config: &RustConfig, operation: &str, path: FastPath, fallback: &str, reason: &str) -> Result<(), InsightoraError> {
+    if config.require_fast_paths {
+        return Err(InsightoraError::FastPathUnavailable {
+            operation: operation.to_string(),
+            path: path.name().to_string(),
+            reason: reason.to_string(),
+        });
+    }
+    let mut log = LOG.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let sequence = log.next_sequence;
+    log.next_sequence += 1;
+    if log.entries.len() == DEGRADATION_LOG_SIZE {
+        log.entries.pop_front();
+    }
+    log.entries.push_back(Degradation {
+        operation: operation.to_string(),
+        path,
+        fallback: fallback.to_string(),
+        reason: reason.to_string(),
+        sequence,
+        thread: thread::current().id(),
+    });
+    Ok(())
+}
+
+/// Whether `operation` can use `path`, recording the fallback when it can't
+///
+/// # Returns
+/// * `Result<bool>` - True for the fast path, false for `fallback`; an
+///   error instead of false under `require_fast_paths`
+pub fn use_fast_path(operation: &str, path: FastPath, fallback: &str) -> Result<bool, InsightoraError> {
+    use_fast_path_with(&get_current_config(), operation, path, fallback)
+}
+
+fn use_fast_path_with(config: &RustConfig, operation: &str, path: FastPath, fallback: &str) -> Result<bool, InsightoraError> {
+    match capability_with(config, path).reason {
+        Some(reason) => degrade_with(config, operation, path, fallback, &reason).map(|_| false),
+        None => Ok(true),
+    }
+}
+
+/// Recent degradations from all threads, oldest first
+pub fn degradations() -> Vec<Degradation> {
+    let log = LOG.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    log.entries.iter().cloned().collect()
+}
+
+pub fn clear_degradations() {
+    LOG.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).entries.clear();
+}
+
+/// Sequence number the next degradation will get
+pub(crate) fn log_position() -> u64 {
+    LOG.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).next_sequence
+}
+
+/// Degradations recorded on the current thread since `position`
+pub(crate) fn degradations_since(position: u64) -> Vec<Degradation> {
+    let current = thread::current().id();
+    let log = LOG.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    log.entries
+        .iter()
+        .filter(|d| d.sequence >= position && d.thread == current)
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled_simd_is_reported_or_refused() {
+        // Explicit configs, so tests running alongside keep the global one
+        let no_simd = RustConfig { enable_simd: false, ..Default::default() };
+        let position = log_position();
+        assert!(!use_fast_path_with(&no_simd, "describe", FastPath::Simd, "scalar").unwrap());
+        let recorded = degradations_since(position);
+        assert_eq!(recorded.len(), 1);
+        assert_eq!((recorded[0].path, recorded[0].fallback.as_str()), (FastPath::Simd, "scalar"));
+        assert!(recorded[0].reason.contains("enable_simd=False"), "{}", recorded[0].reason);
+        assert!(degradations().contains(&recorded[0]));
+
+        let strict = RustConfig { require_fast_paths: true, ..no_simd };
+        let err = use_fast_path_with(&strict, "describe", FastPath::Simd, "scalar").unwrap_err();
+        assert!(matches!(err, InsightoraError::FastPathUnavailable { ref path, .. } if path == "simd"), "{}", err);
+        assert_eq!(degradations_since(position).len(), 1);
+
+        // Memory mapping doesn't depend on the configuration
+        assert!(capability_with(&strict, FastPath::Mmap).available);
+    }
+}
@@ -0,0 +1,294 @@
+// Column dictionary mapping
+// Matches source column names against canonical names and their aliases, reporting ambiguities instead of guessing
+
+use std::collections::HashSet;
+use regex::Regex;
+use crate::error::InsightoraError;
+
+/// Prefix marking an alias as a regex, e.g. `re:^qty\s*ordered$`
+pub const REGEX_PREFIX: &str = "re:";
+
+/// Prefix marking an alias that must match case included, e.g. `exact:QTY`
+pub const EXACT_PREFIX: &str = "exact:";
+
+/// One way a source column name can match a canonical name
+#[derive(Debug, Clone)]
+pub enum Alias {
+    /// Same text, case included
+    Exact(String),
+    /// Same text ignoring case and surrounding whitespace
+    IgnoreCase(String),
+    /// Regex searched for in the source name; anchor it for a full match
+    Regex(Regex),
+}
+
+impl Alias {
+    /// Read an alias as written in a dictionary
+    ///
+    /// `re:<pattern>` is a regex, `exact:<name>` is compared case
+    /// included, and anything else is compared ignoring case.
+    pub fn parse(alias: &str) -> Result<Self, InsightoraError> {
+        if let Some(pattern) = alias.strip_prefix(REGEX_PREFIX) {
+            return Regex::new(pattern).map(Alias::Regex).map_err(|e| {
+                InsightoraError::ValidationError(format!("Invalid regex alias '{}': {}", pattern, e))
+            });
+        }
+        Ok(match alias.strip_prefix(EXACT_PREFIX) {
+            Some(name) => Alias::Exact(name.to_string()),
+            None => Alias::IgnoreCase(alias.to_string()),
+        })
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            Alias::Exact(alias) => alias == name,
+            Alias::IgnoreCase(alias) => alias.trim().to_lowercase() == name.trim().to_lowercase(),
+            Alias::Regex(regex) => regex.is_match(name),
+        }
+    }
+
+    /// The alias as written, prefix included
+    pub fn text(&self) -> String {
+        match self {
+            Alias::Exact(name) => format!("{}{}", EXACT_PREFIX, name),
+            Alias::IgnoreCase(name) => name.clone(),
+            Alias::Regex(regex) => format!("{}{}", REGEX_PREFIX, regex.as_str()),
+        }
+    }
+}
+
+impl PartialEq for Alias {
+    fn eq(&self, other: &Self) -> bool {
+        self.text() == other.text()
+    }
+}
+
+/// Canonical column names with the aliases feeds use for them
+///
+/// A canonical name always matches itself, ignoring case.
+///
+/// # Example
+/// ```
+/// use insightora_core::api::ColumnDictionary;
+///
+/// # fn main() -> insightora_core::api::Result<()> {
+/// let dictionary = ColumnDictionary::new()
+///     .entry("quantity", &["qty", r"re:^qty\s*ordered$"])?
+///     .entry("unit_price", &["price", "exact:UP"])?
+///     .require(&["quantity"])?;
+/// let matched = dictionary.resolve(&["QTY".to_string(), "price".to_string(), "notes".to_string()]);
+/// assert_eq!(matched.renames, [("QTY".to_string(), "quantity".to_string()), ("price".to_string(), "unit_price".to_string())]);
+/// assert_eq!(matched.unmapped, ["notes"]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnDictionary {
+    entries: Vec<(String, Vec<Alias>)>,
+    required: Vec<String>,
+}
+
+impl ColumnDictionary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a canonical name and its aliases (builder style)
+    pub fn entry<S: AsRef<str>>(mut self, canonical: &str, aliases: &[S]) -> Result<Self, InsightoraError> {
+        if self.aliases(canonical).is_some() {
+            return Err(InsightoraError::ValidationError(format!(
+                "Canonical column '{}' is listed twice",
+                canonical
+            )));
+        }
+        let aliases = aliases.iter().map(|alias| Alias::parse(alias.as_ref())).collect::<Result<Vec<_>, _>>()?;
+        self.entries.push((canonical.to_string(), aliases));
+        Ok(self)
+    }
+
+    /// Canonical names that must each match exactly one source column
+    pub fn require<S: AsRef<str>>(mut self, canonical: &[S]) -> Result<Self, InsightoraError> {
+        for name in canonical {
+            let name = name.as_ref();
+            if self.aliases(name).is_none() {
+                return Err(InsightoraError::ValidationError(format!(
+                    "Required column '{}' is not in the dictionary",
+                    name
+                )));
+            }
+            if !self.required.iter().any(|r| r == name) {
+                self.required.push(name.to_string());
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn canonical_names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(name, _)| name.as_str())
+    }
+
+    pub fn aliases(&self, canonical: &str) -> Option<&[Alias]> {
+        self.entries.iter().find(|(name, _)| name == canonical).map(|(_, aliases)| aliases.as_slice())
+    }
+
+    pub fn required(&self) -> &[String] {
+        &self.required
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Match source column names against the dictionary
+    ///
+    /// A canonical name matched by several columns, and a column matching
+    /// several canonical names, are reported and left unrenamed.
+    pub fn resolve(&self, names: &[String]) -> ColumnMatch {
+        let candidates: Vec<Vec<&str>> = names
+            .iter()
+            .map(|name| {
+                self.entries
+                    .iter()
+                    .filter(|(canonical, aliases)| {
+                        Alias::IgnoreCase(canonical.clone()).matches(name) || aliases.iter().any(|a| a.matches(name))
+                    })
+                    .map(|(canonical, _)| canonical.as_str())
+                    .collect()
+            })
+            .collect();
+
+        let mut matched = ColumnMatch::default();
+        let mut conflicting = HashSet::new();
+        for (name, canonical) in names.iter().zip(&candidates) {
+            match canonical.len() {
+                0 => matched.unmapped.push(name.clone()),
+                1 => {}
+                _ => {
+                    conflicting.insert(name.as_str());
+                    matched.conflicts.push((name.clone(), canonical.iter().map(|c| c.to_string()).collect()));
+                }
+            }
+        }
+        for (canonical, _) in &self.entries {
+            let sources: Vec<&String> = names
+                .iter()
+                .zip(&candidates)
+                .filter(|(_, matches)| matches.contains(&canonical.as_str()))
+                .map(|(name, _)| name)
+                .collect();
+            match sources.as_slice() {
+                [] => matched.missing.push(canonical.clone()),
+                [source] if !conflicting.contains(source.as_str()) => {
+                    matched.renames.push(((*source).clone(), canonical.clone()));
+                }
+                [_] => {}
+                _ => matched.ambiguous.push((canonical.clone(), sources.into_iter().cloned().collect())),
+            }
+        }
+        // Report renames in source column order
+        matched.renames.sort_by_key(|(source, _)| names.iter().position(|name| name == source));
+        matched
+    }
+
+    /// `resolve`, failing unless every required name matched one column
+    ///
+    /// # Returns
+    /// * `Result<ColumnMatch>` - ValidationError listing every required name
+    ///   that is missing, ambiguous or contested, with the aliases tried
+    pub fn resolve_required(&self, names: &[String]) -> Result<ColumnMatch, InsightoraError> {
+        let matched = self.resolve(names);
+        let mut problems = Vec::new();
+        for canonical in &self.required {
+            if matched.renames.iter().any(|(_, c)| c == canonical) {
+                continue;
+            }
+            let quoted = |names: &[String]| names.iter().map(|n| format!("'{}'", n)).collect::<Vec<_>>().join(", ");
+            if let Some((_, sources)) = matched.ambiguous.iter().find(|(c, _)| c == canonical) {
+                problems.push(format!("required column '{}' is matched by {}", canonical, quoted(sources)));
+            } else if let Some((source, _)) = matched.conflicts.iter().find(|(_, c)| c.contains(canonical)) {
+                problems.push(format!("required column '{}' is matched by '{}', which also matches another column", canonical, source));
+            } else {
+                let mut tried = vec![canonical.clone()];
+                tried.extend(self.aliases(canonical).unwrap_or_default().iter().map(Alias::text));
+                problems.push(format!("required column '{}' not found (aliases: {})", canonical, tried.join(", ")));
+            }
+        }
+        if problems.is_empty() {
+            Ok(matched)
+        } else {
+            Err(InsightoraError::ValidationError(format!("Column mapping failed: {}", problems.join("; "))))
+        }
+    }
+}
+
+/// Outcome of matching source columns against a `ColumnDictionary`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnMatch {
+    /// (source, canonical) renames in source column order; a column
+    /// already named canonically maps to itself
+    pub renames: Vec<(String, String)>,
+    /// Source columns matching no canonical name, kept as they are
+    pub unmapped: Vec<String>,
+    /// Canonical names no source column matches
+    pub missing: Vec<String>,
+    /// Canonical names matched by several source columns, with those columns
+    pub ambiguous: Vec<(String, Vec<String>)>,
+    /// Source columns matching several canonical names, with those names
+    pub conflicts: Vec<(String, Vec<String>)>,
+}
+
+/// Match the columns of a frame or schema against a dictionary
+///
+/// # Returns
+/// * `Result<ColumnMatch>` - Renames and what didn't map; an error when a
+///   required canonical name isn't matched by exactly one column
+pub fn map_columns(names: &[String], dictionary: &ColumnDictionary) -> Result<ColumnMatch, InsightoraError> {
+    dictionary.resolve_required(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataframe::transformations::ColumnMapping;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_aliases_map_and_ambiguities_are_reported() {
+        let dictionary = ColumnDictionary::new()
+            .entry("quantity", &["qty", r"re:(?i)^qty\s*ordered$"])
+            .unwrap()
+            .entry("sku", &["exact:SKU", "item code"])
+            .unwrap()
+            .entry("region", &["area", "territory"])
+            .unwrap();
+
+        let matched = dictionary.resolve(&names(&["QTY ordered", "Item Code ", "notes", "sku"]));
+        assert_eq!(matched.renames, [("QTY ordered".to_string(), "quantity".to_string())]);
+        assert_eq!(matched.unmapped, ["notes"]);
+        assert_eq!(matched.missing, ["region"]);
+        // "Item Code " and "sku" both match sku: reported, neither renamed
+        assert_eq!(matched.ambiguous, [("sku".to_string(), names(&["Item Code ", "sku"]))]);
+
+        let overlapping = dictionary.clone().entry("ordered", &["re:ordered"]).unwrap();
+        let matched = overlapping.resolve(&names(&["qty ordered"]));
+        assert_eq!(matched.conflicts, [("qty ordered".to_string(), names(&["quantity", "ordered"]))]);
+        assert!(matched.renames.is_empty());
+
+        let required = dictionary.clone().require(&["quantity", "region"]).unwrap();
+        let err = map_columns(&names(&["qty", "zone"]), &required).unwrap_err().to_string();
+        assert!(err.contains("required column 'region' not found (aliases: region, area, territory)"), "{}", err);
+        assert!(dictionary.clone().require(&["price"]).is_err());
+        assert!(Alias::parse("re:(").is_err());
+
+        // As a rename at parse time
+        let mapping = ColumnMapping::Dictionary(required);
+        assert_eq!(mapping.apply_to_names(&names(&["Territory", "QTY", "id"]), true).unwrap(), names(&["region", "quantity", "id"]));
+    }
+}
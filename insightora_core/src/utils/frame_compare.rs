@@ -0,0 +1,459 @@
+// DataFrame comparison
+// Tolerant equality checks with a structured diff, for test suites and CI
+
+use std::cmp::Ordering;
+use std::fmt;
+use polars::prelude::*;
+
+// ============================================================================
+// Options and Results
+// ============================================================================
+
+/// How strictly two DataFrames are compared
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameCompareOptions {
+    /// Report columns whose dtypes differ
+    pub check_dtypes: bool,
+    /// Compare rows by position; when false rows are matched after sorting
+    /// both frames by their rendered values
+    pub check_order: bool,
+    /// Report shared columns that appear in a different order
+    pub check_column_order: bool,
+    /// Numbers match when `|a - b| <= tolerance * max(1, |a|, |b|)`
+    pub float_tolerance: f64,
+    /// Treat NaN and null as equal (they differ by default)
+    pub nan_equals_null: bool,
+    /// Compare a Categorical column with a String column by label; when false
+    /// such a pair is a dtype mismatch and its cells are not compared
+    pub categorical_as_string: bool,
+    /// Differing cells listed in the diff (all of them are counted)
+    pub max_cell_differences: usize,
+}
+
+impl Default for FrameCompareOptions {
+    fn default() -> Self {
+        Self {
+            check_dtypes: true,
+            check_order: true,
+            check_column_order: true,
+            float_tolerance: 1e-9,
+            nan_equals_null: false,
+            categorical_as_string: false,
+            max_cell_differences: 20,
+        }
+    }
+}
+
+/// A shared column whose dtypes differ
+#[derive(Debug, Clone, PartialEq)]
+pub struct DtypeMismatch {
+    pub column: String,
+    pub left: String,
+    pub right: String,
+}
+
+/// One differing cell; values are rendered as text, None for null
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellDifference {
+    /// Row in the left frame
+    pub row: usize,
+    /// Row in the right frame (differs from `row` when order is ignored)
+    pub right_row: usize,
+    pub column: String,
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+/// Everything that differs between two DataFrames
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FrameDiff {
+    /// (rows, columns)
+    pub left_shape: (usize, usize),
+    pub right_shape: (usize, usize),
+    pub left_only_columns: Vec<String>,
+    pub right_only_columns: Vec<String>,
+    /// Shared columns appear in a different order
+    pub column_order_differs: bool,
+    pub dtype_mismatches: Vec<DtypeMismatch>,
+    /// First differing cells in row-major order
+    pub cells: Vec<CellDifference>,
+    /// Total number of differing cells in the compared rows
+    pub differing_cells: usize,
+}
+
+impl FrameDiff {
+    /// True when nothing differs under the options used
+    pub fn is_equal(&self) -> bool {
+        self.left_shape == self.right_shape
+            && self.left_only_columns.is_empty()
+            && self.right_only_columns.is_empty()
+            && !self.column_order_differs
+            && self.dtype_mismatches.is_empty()
+            && self.differing_cells == 0
+    }
+}
+
+impl fmt::Display for FrameDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_equal() {
+            return write!(f, "frames are equal");
+        }
+        write!(f, "frames differ:")?;
+        if self.left_shape != self.right_shape {
+            write!(f, "\n  shape {:?} vs {:?}", self.left_shape, self.right_shape)?;
+        }
+        if !self.left_only_columns.is_empty() {
+            write!(f, "\n  columns only in left: {:?}", self.left_only_columns)?;
+        }
+        if !self.right_only_columns.is_empty() {
+            write!(f, "\n  columns only in right: {:?}", self.right_only_columns)?;
+        }
+        if self.column_order_differs {
+            write!(f, "\n  shared columns are in a different order")?;
+        }
+        for mismatch in &self.dtype_mismatches {
+            write!(f, "\n  dtype of '{}': {} vs {}", mismatch.column, mismatch.left, mismatch.right)?;
+        }
+        let render = |v: &Option<String>| v.clone().unwrap_or_else(|| "null".to_string());
+        for cell in &self.cells {
+            write!(f, "\n  row {}", cell.row)?;
+            if cell.right_row != cell.row {
+                write!(f, " (right row {})", cell.right_row)?;
+            }
+            write!(f, ", column '{}': {} vs {}", cell.column, render(&cell.left), render(&cell.right))?;
+        }
+        if self.differing_cells > self.cells.len() {
+            write!(f, "\n  ... and {} more differing cells", self.differing_cells - self.cells.len())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for FrameDiff {}
+
+// ============================================================================
+// Comparison
+// ============================================================================
+
+/// Check two DataFrames for equality, returning the diff when they differ
+///
+/// # Arguments
+/// * `left` / `right` - Frames to compare (typically actual and expected)
+/// * `options` - Tolerance and which differences count
+///
+/// # Returns
+/// * `Result<(), FrameDiff>` - Ok when equal; the diff displays as a
+///   readable report, so `assert_frames_equal(..).unwrap()` fails a test with it
+///
+/// # Example
+/// ```
+/// use insightora_core::api::{assert_frames_equal, FrameCompareOptions};
+/// use polars::prelude::*;
+///
+/// let actual = df!("x" => [1.0, 2.0 + 1e-12]).unwrap();
+/// let expected = df!("x" => [1.0, 2.0]).unwrap();
+/// assert_frames_equal(&actual, &expected, &FrameCompareOptions::default()).unwrap();
+/// ```
+// The diff is the whole point of the error and only built on failure
+#[allow(clippy::result_large_err)]
+pub fn assert_frames_equal(left: &DataFrame, right: &DataFrame, options: &FrameCompareOptions) -> Result<(), FrameDiff> {
+    let diff = frame_diff(left, right, options);
+    if diff.is_equal() {
+        Ok(())
+    } else {
+        Err(diff)
+    }
+}
+
+/// Compute the structured difference between two DataFrames
+///
+/// Columns are matched by name. When the heights differ, the shape mismatch
+/// is reported and the rows both frames have are still compared. Nulls equal
+/// nulls and NaN equals NaN; NaN against null follows `nan_equals_null`.
+/// Integers compare exactly, and against floats by value with the tolerance.
+pub fn frame_diff(left: &DataFrame, right: &DataFrame, options: &FrameCompareOptions) -> FrameDiff {
+    let mut diff = FrameDiff {
+        left_shape: left.shape(),
+        right_shape: right.shape(),
+        ..Default::default()
+    };
+
+    let left_names: Vec<&str> = left.get_column_names();
+    let right_names: Vec<&str> = right.get_column_names();
+    diff.left_only_columns = left_names.iter().filter(|n| !right_names.contains(n)).map(|n| n.to_string()).collect();
+    diff.right_only_columns = right_names.iter().filter(|n| !left_names.contains(n)).map(|n| n.to_string()).collect();
+    let shared: Vec<&str> = left_names.iter().copied().filter(|n| right_names.contains(n)).collect();
+    if options.check_column_order {
+        let right_order: Vec<&str> = right_names.iter().copied().filter(|n| left_names.contains(n)).collect();
+        diff.column_order_differs = shared != right_order;
+    }
+
+    // Columns whose cells can be compared, with the values of both sides
+    let mut compared: Vec<(&str, Vec<Cell>, Vec<Cell>)> = Vec::with_capacity(shared.len());
+    for name in shared {
+        let (Ok(l), Ok(r)) = (left.column(name), right.column(name)) else {
+            continue;
+        };
+        match dtype_relation(l.dtype(), r.dtype(), options) {
+            DtypeRelation::Same => {}
+            DtypeRelation::Differs { comparable } => {
+                diff.dtype_mismatches.push(DtypeMismatch {
+                    column: name.to_string(),
+                    left: l.dtype().to_string(),
+                    right: r.dtype().to_string(),
+                });
+                if !comparable {
+                    continue;
+                }
+            }
+        }
+        compared.push((name, series_cells(l), series_cells(r)));
+    }
+
+    let rows = left.height().min(right.height());
+    let (left_rows, right_rows) = if options.check_order {
+        ((0..left.height()).collect(), (0..right.height()).collect())
+    } else {
+        (
+            sorted_rows(compared.iter().map(|c| &c.1), left.height()),
+            sorted_rows(compared.iter().map(|c| &c.2), right.height()),
+        )
+    };
+
+    let limit = options.max_cell_differences;
+    let mut cells: Vec<(usize, usize, CellDifference)> = Vec::new();
+    for (column_index, (name, l, r)) in compared.iter().enumerate() {
+        let mut listed = 0;
+        for position in 0..rows {
+            let (lr, rr) = (left_rows[position], right_rows[position]);
+            if cells_equal(&l[lr], &r[rr], options) {
+                continue;
+            }
+            diff.differing_cells += 1;
+            // The first `limit` row-major differences are among the first `limit` of each column
+            if listed < limit {
+                listed += 1;
+                cells.push((position, column_index, CellDifference {
+                    row: lr,
+                    right_row: rr,
+                    column: name.to_string(),
+                    left: l[lr].render(),
+                    right: r[rr].render(),
+                }));
+            }
+        }
+    }
+    cells.sort_by_key(|(position, column, _)| (*position, *column));
+    diff.cells = cells.into_iter().take(limit).map(|(_, _, cell)| cell).collect();
+    diff
+}
+
+enum DtypeRelation {
+    Same,
+    Differs { comparable: bool },
+}
+
+fn dtype_relation(left: &DataType, right: &DataType, options: &FrameCompareOptions) -> DtypeRelation {
+    let is_categorical = |dtype: &DataType| matches!(dtype, DataType::Categorical(_, _));
+    let label_pair = (is_categorical(left) && *right == DataType::String)
+        || (*left == DataType::String && is_categorical(right));
+    if label_pair {
+        return if options.categorical_as_string {
+            DtypeRelation::Same
+        } else {
+            DtypeRelation::Differs { comparable: false }
+        };
+    }
+    let same = match (left, right) {
+        // Categoricals with different category orders still hold the same kind of values
+        (DataType::Categorical(_, a), DataType::Categorical(_, b)) => a == b,
+        _ => left == right,
+    };
+    if same || !options.check_dtypes {
+        DtypeRelation::Same
+    } else {
+        DtypeRelation::Differs { comparable: true }
+    }
+}
+
+/// One cell reduced to what comparison needs
+#[derive(Debug, Clone, PartialEq)]
+enum Cell {
+    Null,
+    Int(i128),
+    Float(f64),
+    Text(String),
+}
+
+impl Cell {
+    fn render(&self) -> Option<String> {
+        match self {
+            Cell::Null => None,
+            Cell::Int(v) => Some(v.to_string()),
+            Cell::Float(v) => Some(v.to_string()),
+            Cell::Text(v) => Some(v.clone()),
+        }
+    }
+
+    /// Sort key for matching rows when order is ignored
+    fn cmp_key(&self, other: &Cell) -> Ordering {
+        let rank = |cell: &Cell| match cell {
+            Cell::Null => 0,
+            Cell::Int(_) | Cell::Float(_) => 1,
+            Cell::Text(_) => 2,
+        };
+        match (self, other) {
+            (Cell::Int(a), Cell::Int(b)) => a.cmp(b),
+            (Cell::Text(a), Cell::Text(b)) => a.cmp(b),
+            (a, b) if rank(a) == 1 && rank(b) == 1 => a.as_f64().total_cmp(&b.as_f64()),
+            (a, b) => rank(a).cmp(&rank(b)),
+        }
+    }
+
+    fn as_f64(&self) -> f64 {
+        match self {
+            Cell::Int(v) => *v as f64,
+            Cell::Float(v) => *v,
+            _ => f64::NAN,
+        }
+    }
+}
+
+fn series_cells(series: &Series) -> Vec<Cell> {
+    let series = series.rechunk();
+    series
+        .iter()
+        .map(|value| match value {
+            AnyValue::Null => Cell::Null,
+            AnyValue::Int8(v) => Cell::Int(v.into()),
+            AnyValue::Int16(v) => Cell::Int(v.into()),
+            AnyValue::Int32(v) => Cell::Int(v.into()),
+            AnyValue::Int64(v) => Cell::Int(v.into()),
+            AnyValue::UInt8(v) => Cell::Int(v.into()),
+            AnyValue::UInt16(v) => Cell::Int(v.into()),
+            AnyValue::UInt32(v) => Cell::Int(v.into()),
+            AnyValue::UInt64(v) => Cell::Int(v.into()),
+            AnyValue::Float32(v) => Cell::Float(v.into()),
+            AnyValue::Float64(v) => Cell::Float(v),
+            AnyValue::String(v) => Cell::Text(v.to_string()),
+            AnyValue::Categorical(index, rev_map, _) => Cell::Text(rev_map.get(index).to_string()),
+            other => Cell::Text(other.to_string()),
+        })
+        .collect()
+}
+
+/// Row indices ordered by their cells, column by column
+fn sorted_rows<'a>(columns: impl Iterator<Item = &'a Vec<Cell>>, height: usize) -> Vec<usize> {
+    let columns: Vec<&Vec<Cell>> = columns.collect();
+    let mut rows: Vec<usize> = (0..height).collect();
+    rows.sort_by(|&a, &b| {
+        columns
+            .iter()
+            .map(|cells| cells[a].cmp_key(&cells[b]))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    });
+    rows
+}
+
+fn cells_equal(left: &Cell, right: &Cell, options: &FrameCompareOptions) -> bool {
+    match (left, right) {
+        (Cell::Null, Cell::Null) => true,
+        (Cell::Null, Cell::Float(v)) | (Cell::Float(v), Cell::Null) => options.nan_equals_null && v.is_nan(),
+        (Cell::Int(a), Cell::Int(b)) => a == b,
+        (Cell::Text(a), Cell::Text(b)) => a == b,
+        (Cell::Int(_) | Cell::Float(_), Cell::Int(_) | Cell::Float(_)) => {
+            let (a, b) = (left.as_f64(), right.as_f64());
+            if a.is_nan() || b.is_nan() {
+                return a.is_nan() && b.is_nan();
+            }
+            a == b || (a - b).abs() <= options.float_tolerance * 1f64.max(a.abs()).max(b.abs())
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> FrameCompareOptions {
+        FrameCompareOptions::default()
+    }
+
+    #[test]
+    fn test_tolerance_nan_and_null_policies() {
+        let left = df! {
+            "x" => [Some(1.0), Some(f64::NAN), None, Some(1e12)],
+            "label" => ["a", "b", "c", "d"],
+        }
+        .unwrap();
+        let right = df! {
+            "x" => [Some(1.0 + 1e-12), None, None, Some(1e12 + 1.0)],
+            "label" => ["a", "b", "c", "d"],
+        }
+        .unwrap();
+
+        let diff = frame_diff(&left, &right, &options());
+        assert_eq!(diff.differing_cells, 1);
+        assert_eq!(diff.cells[0].row, 1);
+        assert_eq!(diff.cells[0].left.as_deref(), Some("NaN"));
+        assert_eq!(diff.cells[0].right, None);
+
+        let lenient = FrameCompareOptions { nan_equals_null: true, ..options() };
+        assert!(assert_frames_equal(&left, &right, &lenient).is_ok());
+
+        let strict = FrameCompareOptions { float_tolerance: 0.0, nan_equals_null: true, ..options() };
+        let diff = assert_frames_equal(&left, &right, &strict).unwrap_err();
+        assert_eq!(diff.cells.iter().map(|c| c.row).collect::<Vec<_>>(), vec![0, 3]);
+        assert!(diff.to_string().contains("row 3, column 'x': 1000000000000 vs 1000000000001"));
+    }
+
+    #[test]
+    fn test_shape_columns_dtypes_and_order() {
+        let left = df! { "a" => [1i64, 2, 3], "b" => ["x", "y", "z"] }.unwrap();
+        let right = df! { "b" => ["z", "x", "y"], "a" => [3.0, 1.0, 2.0], "c" => [0, 0, 0] }.unwrap();
+
+        let diff = frame_diff(&left, &right, &options());
+        assert_eq!(diff.right_only_columns, vec!["c"]);
+        assert!(diff.column_order_differs);
+        assert_eq!(diff.dtype_mismatches.len(), 1);
+        assert_eq!(diff.dtype_mismatches[0].column, "a");
+        assert_eq!(diff.differing_cells, 6);
+
+        let relaxed = FrameCompareOptions {
+            check_dtypes: false,
+            check_order: false,
+            check_column_order: false,
+            ..options()
+        };
+        let diff = frame_diff(&left, &right.drop("c").unwrap(), &relaxed);
+        assert!(diff.is_equal(), "{}", diff);
+
+        let shorter = left.head(Some(2));
+        let diff = frame_diff(&left, &shorter, &options());
+        assert_eq!((diff.left_shape, diff.right_shape), ((3, 2), (2, 2)));
+        assert_eq!(diff.differing_cells, 0);
+        assert!(!diff.is_equal());
+    }
+
+    #[test]
+    fn test_categorical_policy_and_cell_limit() {
+        let labels = Series::new("g", &["a", "b", "a"]);
+        let categorical = DataFrame::new(vec![labels.cast(&DataType::Categorical(None, CategoricalOrdering::Physical)).unwrap()]).unwrap();
+        let strings = DataFrame::new(vec![labels]).unwrap();
+
+        let diff = frame_diff(&categorical, &strings, &options());
+        assert_eq!(diff.dtype_mismatches.len(), 1);
+        assert_eq!(diff.differing_cells, 0);
+        let by_label = FrameCompareOptions { categorical_as_string: true, ..options() };
+        assert!(frame_diff(&categorical, &strings, &by_label).is_equal());
+
+        let left = df! { "v" => (0..50).collect::<Vec<i32>>() }.unwrap();
+        let right = df! { "v" => (100..150).collect::<Vec<i32>>() }.unwrap();
+        let diff = frame_diff(&left, &right, &FrameCompareOptions { max_cell_differences: 3, ..options() });
+        assert_eq!(diff.differing_cells, 50);
+        assert_eq!(diff.cells.len(), 3);
+        assert!(diff.to_string().ends_with("... and 47 more differing cells"));
+    }
+}
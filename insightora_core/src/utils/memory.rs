@@ -1,2 +1,310 @@
 // Memory management utilities
-// TODO: Implement as needed
+// Runtime memory watchdog that aborts long operations at chunk boundaries before the OOM killer fires
+
+use std::cell::RefCell;
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+use once_cell::sync::Lazy;
+use crate::config::get_current_config;
+use crate::error::InsightoraError;
+
+/// Samples kept per operation for the error message (oldest dropped first)
+const TRAJECTORY_SAMPLES: usize = 32;
+
+// ============================================================================
+// Sampling
+// ============================================================================
+
+/// One reading of process and system memory
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MemorySample {
+    /// Resident set size of this process
+    pub rss_bytes: u64,
+    /// Memory in use against `limit_bytes`
+    pub used_bytes: u64,
+    /// Container (cgroup) limit when one is set, otherwise physical memory
+    pub limit_bytes: u64,
+}
+
+impl MemorySample {
+    pub fn used_pct(&self) -> f64 {
+        if self.limit_bytes == 0 { 0.0 } else { self.used_bytes as f64 * 100.0 / self.limit_bytes as f64 }
+    }
+}
+
+/// Read current memory figures, or None where /proc isn't available
+///
+/// Inside a container with a cgroup memory limit the limit and usage come
+/// from the cgroup (v2, then v1), since that is what the OOM killer
+/// enforces; otherwise from /proc/meminfo (total minus available).
+pub fn sample_memory() -> Option<MemorySample> {
+    let rss_bytes = proc_value("/proc/self/status", "VmRSS:")?;
+    let (used_bytes, limit_bytes) = cgroup_usage().or_else(|| {
+        let total = proc_value("/proc/meminfo", "MemTotal:")?;
+        let available = proc_value("/proc/meminfo", "MemAvailable:")?;
+        Some((total.saturating_sub(available), total))
+    })?;
+    Some(MemorySample { rss_bytes, used_bytes, limit_bytes })
+}
+
+/// A "Key:   123 kB" line of a /proc file, in bytes
+fn proc_value(path: &str, key: &str) -> Option<u64> {
+    let text = fs::read_to_string(path).ok()?;
+    let line = text.lines().find(|line| line.starts_with(key))?;
+    let kb: u64 = line[key.len()..].split_whitespace().next()?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+fn cgroup_usage() -> Option<(u64, u64)> {
+    let read = |path: &str| fs::read_to_string(path).ok().and_then(|text| text.trim().parse::<u64>().ok());
+    if let (Some(limit), Some(used)) = (read("/sys/fs/cgroup/memory.max"), read("/sys/fs/cgroup/memory.current")) {
+        return Some((used, limit));
+    }
+    let limit = read("/sys/fs/cgroup/memory/memory.limit_in_bytes")?;
+    // v1 reports an unset limit as a huge page-aligned number
+    if limit >= 1 << 60 {
+        return None;
+    }
+    Some((read("/sys/fs/cgroup/memory/memory.usage_in_bytes")?, limit))
+}
+
+// ============================================================================
+// Watchdog
+// ============================================================================
+
+/// When the watchdog aborts a watched operation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WatchdogSettings {
+    /// Abort once memory in use reaches this share of the limit (0-100)
+    pub threshold_pct: f64,
+    /// Time between samples
+    pub interval: Duration,
+    /// Operations younger than this are never aborted, so short calls are unaffected
+    pub grace: Duration,
+}
+
+impl Default for WatchdogSettings {
+    fn default() -> Self {
+        Self {
+            threshold_pct: 90.0,
+            interval: Duration::from_millis(100),
+            grace: Duration::from_millis(1000),
+        }
+    }
+}
+
+/// Shared state of one watched operation
+struct Watch {
+    operation: String,
+    settings: WatchdogSettings,
+    started: Instant,
+    aborted: AtomicBool,
+    /// (ms since start, sample)
+    trajectory: Mutex<Vec<(u64, MemorySample)>>,
+}
+
+struct Registry {
+    active: Mutex<Vec<Arc<Watch>>>,
+    wake: Condvar,
+    started: AtomicBool,
+}
+
+static REGISTRY: Lazy<Registry> = Lazy::new(|| Registry {
+    active: Mutex::new(Vec::new()),
+    wake: Condvar::new(),
+    started: AtomicBool::new(false),
+});
+
+thread_local! {
+    static CURRENT: RefCell<Option<Arc<Watch>>> = const { RefCell::new(None) };
+}
+
+/// Keeps an operation watched until dropped
+///
+/// Returned by `watch`. Dropping it unregisters the operation; a guard
+/// created while another is active on the same thread does nothing, so
+/// nested operations share the outermost watch.
+#[must_use = "the operation is only watched while the guard is alive"]
+pub struct WatchGuard {
+    watch: Option<Arc<Watch>>,
+}
+
+impl Drop for WatchGuard {
+    fn drop(&mut self) {
+        if let Some(watch) = self.watch.take() {
+            CURRENT.with(|current| current.borrow_mut().take());
+            let mut active = REGISTRY.active.lock().unwrap_or_else(|e| e.into_inner());
+            active.retain(|other| !Arc::ptr_eq(other, &watch));
+        }
+    }
+}
+
+/// Watch the calling thread's operation if `configure(memory_watchdog=True)`
+///
+/// Cheap when the watchdog is off (one config read). Call `checkpoint` at
+/// chunk or batch boundaries on the same thread to honour an abort.
+pub fn watch(operation: &str) -> WatchGuard {
+    let config = get_current_config();
+    if !config.memory_watchdog {
+        return WatchGuard { watch: None };
+    }
+    watch_with(operation, WatchdogSettings {
+        threshold_pct: config.watchdog_threshold_pct,
+        interval: Duration::from_millis(config.watchdog_interval_ms.max(1)),
+        grace: Duration::from_millis(config.watchdog_grace_ms),
+    })
+}
+
+/// Watch the calling thread's operation with explicit settings
+pub fn watch_with(operation: &str, settings: WatchdogSettings) -> WatchGuard {
+    if CURRENT.with(|current| current.borrow().is_some()) {
+        return WatchGuard { watch: None };
+    }
+    let watch = Arc::new(Watch {
+        operation: operation.to_string(),
+        settings,
+        started: Instant::now(),
+        aborted: AtomicBool::new(false),
+        trajectory: Mutex::new(Vec::new()),
+    });
+    CURRENT.with(|current| *current.borrow_mut() = Some(Arc::clone(&watch)));
+    REGISTRY.active.lock().unwrap_or_else(|e| e.into_inner()).push(Arc::clone(&watch));
+    if !REGISTRY.started.swap(true, Ordering::SeqCst) {
+        std::thread::Builder::new()
+            .name("insightora-memory-watchdog".to_string())
+            .spawn(run_watchdog)
+            .expect("Failed to start memory watchdog thread");
+    }
+    REGISTRY.wake.notify_one();
+    WatchGuard { watch: Some(watch) }
+}
+
+/// Fail if the watchdog has aborted the calling thread's operation
+///
+/// # Returns
+/// * `Result<()>` - MemoryWatchdog error with the RSS trajectory once aborted
+pub fn checkpoint() -> Result<(), InsightoraError> {
+    CURRENT.with(|current| match current.borrow().as_ref() {
+        Some(watch) if watch.aborted.load(Ordering::Relaxed) => {
+            let trajectory = watch.trajectory.lock().unwrap_or_else(|e| e.into_inner());
+            Err(InsightoraError::MemoryWatchdog {
+                operation: watch.operation.clone(),
+                used_pct: trajectory.last().map_or(0.0, |(_, sample)| sample.used_pct()),
+                threshold_pct: watch.settings.threshold_pct,
+                rss_trajectory_mb: trajectory.iter().map(|(ms, sample)| (*ms, sample.rss_bytes >> 20)).collect(),
+            })
+        }
+        _ => Ok(()),
+    })
+}
+
+/// Render `(ms, MB)` samples as "0ms 120MB -> 100ms 340MB -> ..."
+pub fn format_trajectory(trajectory: &[(u64, u64)]) -> String {
+    trajectory
+        .iter()
+        .map(|(ms, mb)| format!("{}ms {}MB", ms, mb))
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+/// Sampling loop; sleeps on the condvar while nothing is watched
+fn run_watchdog() {
+    loop {
+        let (watches, interval) = {
+            let mut active = REGISTRY.active.lock().unwrap_or_else(|e| e.into_inner());
+            while active.is_empty() {
+                active = REGISTRY.wake.wait(active).unwrap_or_else(|e| e.into_inner());
+            }
+            let interval = active.iter().map(|w| w.settings.interval).min().unwrap_or_default();
+            (active.clone(), interval)
+        };
+
+        if let Some(sample) = sample_memory() {
+            for watch in &watches {
+                let elapsed = watch.started.elapsed();
+                let mut trajectory = watch.trajectory.lock().unwrap_or_else(|e| e.into_inner());
+                if trajectory.len() == TRAJECTORY_SAMPLES {
+                    trajectory.remove(0);
+                }
+                trajectory.push((elapsed.as_millis() as u64, sample));
+                if elapsed >= watch.settings.grace && sample.used_pct() >= watch.settings.threshold_pct {
+                    watch.aborted.store(true, Ordering::Relaxed);
+                }
+            }
+        }
+        drop(watches);
+        std::thread::sleep(interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use crate::io::csv_parser::{StreamingCsvConfig, StreamingCsvParser};
+
+    fn hungry_csv(dir: &tempfile::TempDir) -> String {
+        let path = dir.path().join("hungry.csv");
+        let mut file = fs::File::create(&path).unwrap();
+        writeln!(file, "id,payload").unwrap();
+        for i in 0..20_000 {
+            writeln!(file, "{},{}", i, "x".repeat(64)).unwrap();
+        }
+        path.to_string_lossy().into_owned()
+    }
+
+    fn parse_holding_batches(path: &str) -> Result<usize, InsightoraError> {
+        let parser = StreamingCsvParser::with_config(StreamingCsvConfig { chunk_size: 500, ..Default::default() });
+        let mut held = Vec::new();
+        parser.parse_batches(path, |batch| {
+            held.push(batch);
+            // Give the sampler a chance to run between batches
+            std::thread::sleep(Duration::from_millis(2));
+            Ok(())
+        })?;
+        Ok(held.len())
+    }
+
+    #[test]
+    fn test_watchdog_aborts_hungry_parse_under_low_threshold() {
+        if sample_memory().is_none() {
+            return;
+        }
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = hungry_csv(&dir);
+        let settings = WatchdogSettings {
+            threshold_pct: 0.01,
+            interval: Duration::from_millis(1),
+            grace: Duration::ZERO,
+        };
+
+        let guard = watch_with("hungry parse", settings);
+        let err = parse_holding_batches(&path).unwrap_err();
+        drop(guard);
+        let InsightoraError::MemoryWatchdog { operation, rss_trajectory_mb, .. } = &err else {
+            panic!("expected a watchdog abort, got {}", err);
+        };
+        assert_eq!(operation, "hungry parse");
+        assert!(!rss_trajectory_mb.is_empty());
+        assert!(err.to_string().contains("MB"), "{}", err);
+
+        // The same parse inside the grace period is never aborted
+        let patient = WatchdogSettings { grace: Duration::from_secs(600), ..settings };
+        let _guard = watch_with("short parse", patient);
+        assert_eq!(parse_holding_batches(&path).unwrap(), 40);
+    }
+
+    #[test]
+    fn test_nested_watches_share_the_outer_one() {
+        let outer = watch_with("outer", WatchdogSettings::default());
+        let inner = watch_with("inner", WatchdogSettings::default());
+        assert!(inner.watch.is_none());
+        drop(inner);
+        assert!(CURRENT.with(|current| current.borrow().is_some()));
+        drop(outer);
+        assert!(CURRENT.with(|current| current.borrow().is_none()));
+        assert_eq!(format_trajectory(&[(0, 10), (100, 12)]), "0ms 10MB -> 100ms 12MB");
+    }
+}
@@ -1,2 +1,211 @@
 // Performance metrics utilities
-// TODO: Implement as needed
+// Per-call execution metrics (phases, row accounting, memory) behind result summaries
+
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+use polars::prelude::DataFrame;
+use crate::utils::capabilities::{self, Degradation};
+use crate::utils::warnings::AggregatedWarning;
+
+/// Record of what one operation did
+///
+/// Operations note numbers they already have (options in effect, rows read
+/// and kept, phase timings, frame sizes) as they go, so producing a summary
+/// costs nothing beyond copying them out. Data values are kept apart in
+/// `samples` so a summary can be shared without them.
+///
+/// # Example
+/// ```
+/// use insightora_core::api::ExecutionMetrics;
+///
+/// let mut metrics = ExecutionMetrics::new("parse_csv");
+/// metrics.option("delimiter", ",");
+/// let rows = metrics.time("parse", || 1_000);
+/// metrics.rows(rows, rows - 3);
+/// metrics.dropped("bad_lines", 3);
+/// assert_eq!(metrics.rows_dropped(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExecutionMetrics {
+    operation: String,
+    engine: String,
+    options: Vec<(String, String)>,
+    phases: Vec<(String, Duration)>,
+    rows_read: usize,
+    rows_kept: usize,
+    dropped: Vec<(String, usize)>,
+    adjusted: Vec<(String, usize)>,
+    chunks: usize,
+    peak_memory_bytes: usize,
+    samples: Vec<(String, String)>,
+    warnings: Vec<AggregatedWarning>,
+    started: Instant,
+    /// Degradation log position when the metrics were created
+    log_position: u64,
+}
+
+impl ExecutionMetrics {
+    pub fn new(operation: &str) -> Self {
+        Self {
+            operation: operation.to_string(),
+            engine: "in_memory".to_string(),
+            options: Vec::new(),
+            phases: Vec::new(),
+            rows_read: 0,
+            rows_kept: 0,
+            dropped: Vec::new(),
+            adjusted: Vec::new(),
+            chunks: 0,
+            peak_memory_bytes: 0,
+            samples: Vec::new(),
+            warnings: Vec::new(),
+            started: Instant::now(),
+            log_position: capabilities::log_position(),
+        }
+    }
+
+    /// Option in effect, including defaults the caller didn't pass
+    pub fn option(&mut self, name: &str, value: impl Display) {
+        self.options.push((name.to_string(), value.to_string()));
+    }
+
+    pub fn engine(&mut self, engine: &str) {
+        self.engine = engine.to_string();
+    }
+
+    /// Run `work` and record its duration as phase `name`
+    pub fn time<T>(&mut self, name: &str, work: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = work();
+        self.phases.push((name.to_string(), start.elapsed()));
+        result
+    }
+
+    pub fn rows(&mut self, read: usize, kept: usize) {
+        self.rows_read = read;
+        self.rows_kept = kept;
+    }
+
+    /// Rows removed for `reason` ("bad_lines", "duplicates", "unmatched", ...)
+    pub fn dropped(&mut self, reason: &str, rows: usize) {
+        self.dropped.push((reason.to_string(), rows));
+    }
+
+    /// Rows or cells changed but kept for `reason` ("rows_repaired", "null_cells", ...)
+    pub fn adjusted(&mut self, reason: &str, count: usize) {
+        self.adjusted.push((reason.to_string(), count));
+    }
+
+    pub fn chunks(&mut self, chunks: usize) {
+        self.chunks = chunks;
+    }
+
+    /// Track the largest estimated in-memory size seen
+    pub fn observe_memory(&mut self, bytes: usize) {
+        self.peak_memory_bytes = self.peak_memory_bytes.max(bytes);
+    }
+
+    pub fn observe_frame(&mut self, df: &DataFrame) {
+        self.observe_memory(df.estimated_size());
+    }
+
+    /// Data value shown only when samples are requested
+    pub fn sample(&mut self, label: &str, value: impl Display) {
+        self.samples.push((label.to_string(), value.to_string()));
+    }
+
+    /// Aggregated warnings the operation emitted; their examples are data
+    /// values and are shown only with samples
+    pub fn warn(&mut self, warnings: &[AggregatedWarning]) {
+        self.warnings.extend_from_slice(warnings);
+    }
+
+    pub fn operation(&self) -> &str {
+        &self.operation
+    }
+
+    pub fn engine_name(&self) -> &str {
+        &self.engine
+    }
+
+    pub fn options(&self) -> &[(String, String)] {
+        &self.options
+    }
+
+    pub fn phases(&self) -> &[(String, Duration)] {
+        &self.phases
+    }
+
+    pub fn rows_read(&self) -> usize {
+        self.rows_read
+    }
+
+    pub fn rows_kept(&self) -> usize {
+        self.rows_kept
+    }
+
+    pub fn rows_dropped(&self) -> usize {
+        self.dropped.iter().map(|(_, rows)| rows).sum()
+    }
+
+    /// Drop counts by reason
+    pub fn drop_reasons(&self) -> &[(String, usize)] {
+        &self.dropped
+    }
+
+    pub fn adjustments(&self) -> &[(String, usize)] {
+        &self.adjusted
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.chunks
+    }
+
+    pub fn peak_memory_bytes(&self) -> usize {
+        self.peak_memory_bytes
+    }
+
+    pub fn samples(&self) -> &[(String, String)] {
+        &self.samples
+    }
+
+    pub fn warnings(&self) -> &[AggregatedWarning] {
+        &self.warnings
+    }
+
+    /// Time since the metrics were created
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Fallbacks taken on this thread since the metrics were created
+    pub fn degradations(&self) -> Vec<Degradation> {
+        capabilities::degradations_since(self.log_position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_accumulate() {
+        let mut metrics = ExecutionMetrics::new("join_data");
+        metrics.option("how", "left");
+        metrics.engine("partitioned");
+        let sum = metrics.time("join", || (1..=4).sum::<i32>());
+        assert_eq!(sum, 10);
+        metrics.rows(10, 7);
+        metrics.dropped("unmatched", 2);
+        metrics.dropped("duplicates", 1);
+        metrics.observe_memory(512);
+        metrics.observe_memory(128);
+
+        assert_eq!(metrics.rows_dropped(), 3);
+        assert_eq!(metrics.peak_memory_bytes(), 512);
+        assert_eq!(metrics.phases()[0].0, "join");
+        assert_eq!(metrics.options(), &[("how".to_string(), "left".to_string())]);
+        assert_eq!(metrics.engine_name(), "partitioned");
+        assert!(metrics.samples().is_empty());
+    }
+}
@@ -1,5 +1,15 @@
 // Utility module
-// Provides memory management and performance metrics
+// Provides memory management, performance metrics, progress reporting, access policies, scratch directories
+// DataFrame comparison, vectorized numeric kernels, fast-path degradation tracking, column dictionaries
+// and warning aggregation
 
 pub mod memory;
 pub mod metrics;
+pub mod progress;
+pub mod sandbox;
+pub mod scratch;
+pub mod frame_compare;
+pub mod simd;
+pub mod capabilities;
+pub mod column_mapper;
+pub mod warnings;
@@ -0,0 +1,215 @@
+// Progress reporting
+// Aggregates weighted sub-stage progress into a single 0-100 signal
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Receives `(percent, stage, detail)`; percent is in 0..=100
+pub type ProgressSink = Arc<dyn Fn(f64, &str, &str) + Send + Sync>;
+
+/// Minimum time between updates inside a stage (stage starts and ends always emit)
+const MIN_INTERVAL: Duration = Duration::from_millis(100);
+
+struct Shared {
+    sink: ProgressSink,
+    state: Mutex<EmitState>,
+}
+
+#[derive(Default)]
+struct EmitState {
+    last_percent: f64,
+    last_emit: Option<Instant>,
+}
+
+/// Composite progress for multi-stage operations
+///
+/// A reporter covers a slice of the overall 0-100 range. `stages` splits that
+/// slice into weighted children, so nested operations (per-file parses inside
+/// a multi-file load) report into one coherent figure. Weights are relative;
+/// pass input sizes (bytes, cells) so the bar advances evenly. The reported
+/// percentage never goes backwards and updates are throttled.
+///
+/// A disabled reporter (the default) does no work beyond a branch per call.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use insightora_core::api::ProgressReporter;
+///
+/// let reporter = ProgressReporter::new(Arc::new(|percent, stage, detail| {
+///     println!("{:5.1}% {} {}", percent, stage, detail);
+/// }));
+/// let stages = reporter.stages(&[("parse", 800.0), ("export", 200.0)]);
+/// stages[0].report(0.5, "halfway");   // 40%
+/// stages[1].finish("done");           // 100%
+/// ```
+#[derive(Clone, Default)]
+pub struct ProgressReporter {
+    shared: Option<Arc<Shared>>,
+    stage: String,
+    start: f64,
+    span: f64,
+}
+
+impl ProgressReporter {
+    /// Reporter covering 0-100 that delivers updates to `sink`
+    pub fn new(sink: ProgressSink) -> Self {
+        Self {
+            shared: Some(Arc::new(Shared {
+                sink,
+                state: Mutex::new(EmitState::default()),
+            })),
+            stage: String::new(),
+            start: 0.0,
+            span: 100.0,
+        }
+    }
+
+    /// Reporter that ignores every update
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.shared.is_some()
+    }
+
+    /// Split this reporter's range into weighted stages
+    ///
+    /// Child stage names are prefixed with the parent's (`load > parse`).
+    /// Negative weights count as zero; if every weight is zero the range is
+    /// split evenly.
+    pub fn stages(&self, stages: &[(&str, f64)]) -> Vec<ProgressReporter> {
+        if self.shared.is_none() {
+            return vec![Self::disabled(); stages.len()];
+        }
+        let total: f64 = stages.iter().map(|(_, weight)| weight.max(0.0)).sum();
+        let mut offset = self.start;
+        stages
+            .iter()
+            .map(|(name, weight)| {
+                let span = if total > 0.0 {
+                    self.span * weight.max(0.0) / total
+                } else {
+                    self.span / stages.len() as f64
+                };
+                let child = ProgressReporter {
+                    shared: self.shared.clone(),
+                    stage: if self.stage.is_empty() {
+                        name.to_string()
+                    } else {
+                        format!("{} > {}", self.stage, name)
+                    },
+                    start: offset,
+                    span,
+                };
+                offset += span;
+                child
+            })
+            .collect()
+    }
+
+    /// Report that `fraction` (0-1) of this reporter's work is done
+    pub fn report(&self, fraction: f64, detail: &str) {
+        let Some(shared) = &self.shared else {
+            return;
+        };
+        let fraction = if fraction.is_finite() { fraction.clamp(0.0, 1.0) } else { 0.0 };
+        let boundary = fraction == 0.0 || fraction == 1.0;
+
+        let percent = {
+            let Ok(mut state) = shared.state.lock() else {
+                return;
+            };
+            let percent = (self.start + self.span * fraction).clamp(state.last_percent, 100.0);
+            let due = match state.last_emit {
+                Some(last) => last.elapsed() >= MIN_INTERVAL,
+                None => true,
+            };
+            if !boundary && !due {
+                return;
+            }
+            state.last_percent = percent;
+            state.last_emit = Some(Instant::now());
+            percent
+        };
+        // Called outside the lock so the sink may report re-entrantly
+        (shared.sink)(percent, &self.stage, detail);
+    }
+
+    /// Report this reporter's work as complete
+    pub fn finish(&self, detail: &str) {
+        self.report(1.0, detail);
+    }
+}
+
+impl fmt::Debug for ProgressReporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressReporter")
+            .field("enabled", &self.is_enabled())
+            .field("stage", &self.stage)
+            .field("start", &self.start)
+            .field("span", &self.span)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every (percent, stage, detail) reported, in order
+    type Events = Arc<Mutex<Vec<(f64, String, String)>>>;
+
+    fn recording() -> (ProgressReporter, Events) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink_events = Arc::clone(&events);
+        let reporter = ProgressReporter::new(Arc::new(move |percent, stage, detail| {
+            sink_events.lock().unwrap().push((percent, stage.to_string(), detail.to_string()));
+        }));
+        (reporter, events)
+    }
+
+    #[test]
+    fn test_weighted_stages_compose() {
+        let (reporter, events) = recording();
+        let stages = reporter.stages(&[("load", 3.0), ("export", 1.0)]);
+        let files = stages[0].stages(&[("a.csv", 1.0), ("b.csv", 1.0)]);
+
+        files[0].finish("a.csv");
+        files[1].finish("b.csv");
+        stages[1].report(0.0, "");
+        stages[1].finish("");
+
+        let events = events.lock().unwrap();
+        let percents: Vec<f64> = events.iter().map(|e| e.0).collect();
+        assert_eq!(percents, vec![37.5, 75.0, 75.0, 100.0]);
+        assert_eq!(events[0].1, "load > a.csv");
+        assert_eq!(events[3].1, "export");
+    }
+
+    #[test]
+    fn test_progress_is_monotonic_and_throttled() {
+        let (reporter, events) = recording();
+        reporter.report(0.5, "");
+        // Within the throttle window and not a boundary: dropped
+        reporter.report(0.6, "");
+        // Going backwards is clamped to the last figure
+        reporter.report(0.0, "");
+        reporter.finish("");
+
+        let percents: Vec<f64> = events.lock().unwrap().iter().map(|e| e.0).collect();
+        assert_eq!(percents, vec![50.0, 50.0, 100.0]);
+    }
+
+    #[test]
+    fn test_disabled_reporter_is_inert() {
+        let reporter = ProgressReporter::disabled();
+        assert!(!reporter.is_enabled());
+        let stages = reporter.stages(&[("a", 1.0), ("b", 0.0)]);
+        assert_eq!(stages.len(), 2);
+        assert!(!stages[0].is_enabled());
+        stages[1].finish("");
+    }
+}
@@ -0,0 +1,312 @@
+// Filesystem and URL access policies
+// Enforced at every entry point that opens a path or fetches a URL
+
+use std::path::{Component, Path, PathBuf};
+use crate::config::get_current_config;
+use crate::error::InsightoraError;
+
+/// Allow/deny lists for filesystem access
+///
+/// Paths are canonicalized before matching, so `..` segments and symlinks are
+/// resolved to where they really point. An empty allow-list permits every path
+/// that is not denied.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathPolicy {
+    allowed: Vec<PathBuf>,
+    denied: Vec<PathBuf>,
+}
+
+impl PathPolicy {
+    /// Build a policy from allowed and denied root directories or files
+    pub fn new<P: AsRef<Path>>(allowed: &[P], denied: &[P]) -> Result<Self, InsightoraError> {
+        let resolve_all = |paths: &[P]| -> Result<Vec<PathBuf>, InsightoraError> {
+            paths.iter().map(|p| resolve_path(p.as_ref())).collect()
+        };
+        Ok(Self {
+            allowed: resolve_all(allowed)?,
+            denied: resolve_all(denied)?,
+        })
+    }
+
+    /// True when no restrictions are configured
+    pub fn is_unrestricted(&self) -> bool {
+        self.allowed.is_empty() && self.denied.is_empty()
+    }
+
+    /// Resolve a path and check it against the policy
+    ///
+    /// # Returns
+    /// * `Result<PathBuf>` - Canonical path to open, or a PermissionDenied error
+    pub fn check(&self, path: &Path) -> Result<PathBuf, InsightoraError> {
+        if self.is_unrestricted() {
+            return Ok(path.to_path_buf());
+        }
+
+        let resolved = resolve_path(path)?;
+        if self.denied.iter().any(|root| resolved.starts_with(root)) {
+            return Err(InsightoraError::PermissionDenied(format!(
+                "Access to '{}' is blocked by the denied_paths policy",
+                path.display()
+            )));
+        }
+        if !self.allowed.is_empty() && !self.allowed.iter().any(|root| resolved.starts_with(root)) {
+            return Err(InsightoraError::PermissionDenied(format!(
+                "Access to '{}' is outside the allowed_paths policy",
+                path.display()
+            )));
+        }
+        Ok(resolved)
+    }
+}
+
+/// Resolve a path to an absolute canonical form
+///
+/// Existing paths are canonicalized by the OS (following symlinks). For paths
+/// that don't exist yet (output files), the deepest existing ancestor is
+/// canonicalized and the remaining components are appended one at a time,
+/// re-canonicalizing whenever the path so far exists again (after a `..`).
+pub fn resolve_path(path: &Path) -> Result<PathBuf, InsightoraError> {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+
+    if let Ok(canonical) = absolute.canonicalize() {
+        return Ok(canonical);
+    }
+
+    // Walk up to the deepest ancestor that exists
+    let mut existing = absolute.as_path();
+    let mut remainder = Vec::new();
+    loop {
+        match existing.parent() {
+            Some(parent) => {
+                // Last component may be a file name or a `..` segment
+                if let Some(last) = existing.components().next_back() {
+                    remainder.push(last.as_os_str().to_os_string());
+                }
+                existing = parent;
+                if let Ok(canonical) = existing.canonicalize() {
+                    return Ok(normalize_onto(canonical, remainder.iter().rev().map(Path::new)));
+                }
+            }
+            None => return Ok(normalize_onto(PathBuf::new(), std::iter::once(absolute.as_path()))),
+        }
+    }
+}
+
+/// Append path pieces onto a base, resolving `.` and `..` as they come
+///
+/// `missing/../linkdir` must follow `linkdir` if it is a symlink, so each
+/// prefix that exists is canonicalized before the next piece is added.
+fn normalize_onto<'a>(mut base: PathBuf, pieces: impl Iterator<Item = &'a Path>) -> PathBuf {
+    for piece in pieces {
+        for component in piece.components() {
+            match component {
+                Component::ParentDir => {
+                    base.pop();
+                }
+                Component::CurDir => {}
+                other => {
+                    base.push(other.as_os_str());
+                    if let Ok(canonical) = base.canonicalize() {
+                        base = canonical;
+                    }
+                }
+            }
+        }
+    }
+    base
+}
+
+/// Allow-lists for remote URL access
+///
+/// Empty host list means any host; schemes default to http and https.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlPolicy {
+    allowed_schemes: Vec<String>,
+    allowed_hosts: Vec<String>,
+}
+
+impl Default for UrlPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_schemes: vec!["http".to_string(), "https".to_string()],
+            allowed_hosts: Vec::new(),
+        }
+    }
+}
+
+impl UrlPolicy {
+    /// Build a URL policy; hosts may use a leading "*." wildcard for subdomains
+    pub fn new(allowed_schemes: Vec<String>, allowed_hosts: Vec<String>) -> Self {
+        Self {
+            allowed_schemes: allowed_schemes.into_iter().map(|s| s.to_ascii_lowercase()).collect(),
+            allowed_hosts: allowed_hosts.into_iter().map(|h| h.to_ascii_lowercase()).collect(),
+        }
+    }
+
+    pub fn allowed_schemes(&self) -> &[String] {
+        &self.allowed_schemes
+    }
+
+    pub fn allowed_hosts(&self) -> &[String] {
+        &self.allowed_hosts
+    }
+
+    /// Check a URL's scheme and host against the policy
+    pub fn check(&self, url: &str) -> Result<(), InsightoraError> {
+        let (scheme, rest) = url.split_once("://").ok_or_else(|| {
+            InsightoraError::ValidationError(format!("Invalid URL '{}': missing scheme", url))
+        })?;
+        let scheme = scheme.to_ascii_lowercase();
+        if !self.allowed_schemes.contains(&scheme) {
+            return Err(InsightoraError::PermissionDenied(format!(
+                "URL scheme '{}' is not permitted by the allowed_url_schemes policy",
+                scheme
+            )));
+        }
+
+        if self.allowed_hosts.is_empty() {
+            return Ok(());
+        }
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+        let host_port = authority.rsplit('@').next().unwrap_or(authority);
+        let host = if host_port.starts_with('[') {
+            // IPv6 literal
+            host_port.split(']').next().unwrap_or("").trim_start_matches('[')
+        } else {
+            host_port.split(':').next().unwrap_or("")
+        }
+        .to_ascii_lowercase();
+
+        let permitted = self.allowed_hosts.iter().any(|allowed| match allowed.strip_prefix("*.") {
+            Some(domain) => host.ends_with(&format!(".{}", domain)),
+            None => &host == allowed,
+        });
+        if !permitted {
+            return Err(InsightoraError::PermissionDenied(format!(
+                "Host '{}' is not permitted by the allowed_url_hosts policy",
+                host
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Check a filesystem path against the configured policy
+///
+/// Returns the path that should be opened (canonical when a policy is set).
+pub fn check_path_allowed(path: impl AsRef<Path>) -> Result<PathBuf, InsightoraError> {
+    get_current_config().path_policy.check(path.as_ref())
+}
+
+/// Check a URL against the configured policy
+pub fn check_url_allowed(url: &str) -> Result<(), InsightoraError> {
+    get_current_config().url_policy.check(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn sandbox() -> (TempDir, PathBuf, PathBuf) {
+        let root = TempDir::new().unwrap();
+        let allowed = root.path().join("allowed");
+        let secret = root.path().join("secret");
+        fs::create_dir_all(&allowed).unwrap();
+        fs::create_dir_all(&secret).unwrap();
+        fs::write(allowed.join("data.csv"), "a\n1\n").unwrap();
+        fs::write(secret.join("keys.csv"), "k\n1\n").unwrap();
+        (root, allowed, secret)
+    }
+
+    #[test]
+    fn test_unrestricted_policy_allows_everything() {
+        let policy = PathPolicy::default();
+        assert!(policy.check(Path::new("/etc/passwd")).is_ok());
+    }
+
+    #[test]
+    fn test_allowed_path() {
+        let (_root, allowed, _secret) = sandbox();
+        let policy = PathPolicy::new(&[&allowed], &[]).unwrap();
+        assert!(policy.check(&allowed.join("data.csv")).is_ok());
+        // Files that don't exist yet (outputs) are resolved too
+        assert!(policy.check(&allowed.join("new/out.csv")).is_ok());
+    }
+
+    #[test]
+    fn test_parent_dir_traversal_rejected() {
+        let (_root, allowed, _secret) = sandbox();
+        let policy = PathPolicy::new(&[&allowed], &[]).unwrap();
+
+        let escape = allowed.join("../secret/keys.csv");
+        let err = policy.check(&escape).unwrap_err();
+        assert!(matches!(err, InsightoraError::PermissionDenied(_)));
+        assert!(err.to_string().contains("allowed_paths"));
+
+        let missing_escape = allowed.join("nope/../../secret/new.csv");
+        assert!(policy.check(&missing_escape).is_err());
+    }
+
+    #[test]
+    fn test_relative_path_resolved_against_cwd() {
+        let (_root, allowed, _secret) = sandbox();
+        let policy = PathPolicy::new(&[&allowed], &[]).unwrap();
+        assert!(policy.check(Path::new("relative/data.csv")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_escape_rejected() {
+        let (_root, allowed, secret) = sandbox();
+        std::os::unix::fs::symlink(secret.join("keys.csv"), allowed.join("link.csv")).unwrap();
+        std::os::unix::fs::symlink(&secret, allowed.join("linkdir")).unwrap();
+
+        let policy = PathPolicy::new(&[&allowed], &[]).unwrap();
+        assert!(policy.check(&allowed.join("link.csv")).is_err());
+        assert!(policy.check(&allowed.join("linkdir/keys.csv")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_escape_after_missing_parent_rejected() {
+        let (_root, allowed, secret) = sandbox();
+        std::os::unix::fs::symlink(&secret, allowed.join("linkdir")).unwrap();
+
+        let policy = PathPolicy::new(&[&allowed], &[]).unwrap();
+        let err = policy.check(&allowed.join("missing/../linkdir/new.csv")).unwrap_err();
+        assert!(matches!(err, InsightoraError::PermissionDenied(_)));
+        assert!(policy.check(&allowed.join("missing/../linkdir/../data.csv")).is_err());
+        assert!(policy.check(&allowed.join("missing/../new/out.csv")).is_ok());
+    }
+
+    #[test]
+    fn test_denied_path_wins_and_hides_list() {
+        let (root, _allowed, secret) = sandbox();
+        let policy = PathPolicy::new(&[root.path()], &[secret.as_path()]).unwrap();
+
+        let err = policy.check(&secret.join("keys.csv")).unwrap_err();
+        assert!(matches!(err, InsightoraError::PermissionDenied(_)));
+        assert!(err.to_string().contains("denied_paths"));
+    }
+
+    #[test]
+    fn test_url_policy() {
+        let policy = UrlPolicy::new(
+            vec!["https".to_string()],
+            vec!["data.example.com".to_string(), "*.cdn.example.com".to_string()],
+        );
+        assert!(policy.check("https://data.example.com/a.csv").is_ok());
+        assert!(policy.check("https://user@eu.cdn.example.com:8443/a.csv").is_ok());
+        assert!(policy.check("http://data.example.com/a.csv").is_err());
+        assert!(policy.check("https://evil.com/data.example.com").is_err());
+        assert!(policy.check("https://data.example.com.evil.com/").is_err());
+        assert!(policy.check("file:///etc/passwd").is_err());
+        assert!(policy.check("not a url").is_err());
+    }
+}
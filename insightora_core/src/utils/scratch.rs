@@ -0,0 +1,144 @@
+// Scratch directories and files
+// Spill and temporary files, created exclusively under a sandbox-checked root
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use tempfile::{NamedTempFile, TempDir};
+use crate::config::get_current_config;
+use crate::error::InsightoraError;
+use crate::utils::sandbox::PathPolicy;
+
+/// Directory scratch space is created in, checked against the path policy
+///
+/// `root` falls back to `configure(temp_dir=...)`, then the system temp
+/// directory; it is created if needed.
+fn scratch_root(root: Option<&Path>, policy: &PathPolicy) -> Result<PathBuf, InsightoraError> {
+    let root = match root {
+        Some(root) => root.to_path_buf(),
+        None => get_current_config().temp_dir.unwrap_or_else(std::env::temp_dir),
+    };
+    let root = policy.check(&root)?;
+    std::fs::create_dir_all(&root)?;
+    Ok(root)
+}
+
+/// Temporary directory owned by one operation, removed on drop
+///
+/// The directory gets a random name and is created with `O_EXCL` semantics
+/// (owner-only permissions on Unix), so another user cannot pre-create or
+/// guess it.
+#[derive(Debug)]
+pub struct ScratchDir(TempDir);
+
+impl ScratchDir {
+    /// Create a scratch directory named `<prefix>-<random>`
+    ///
+    /// # Arguments
+    /// * `prefix` - Name prefix identifying the operation (e.g. "insightora-join")
+    /// * `root` - Parent directory; falls back to `configure(temp_dir=...)`,
+    ///   then the system temp directory
+    ///
+    /// # Returns
+    /// * `Result<ScratchDir>` - The new directory, or PermissionDenied when the
+    ///   root is outside the configured path policy
+    pub fn create(prefix: &str, root: Option<&Path>) -> Result<Self, InsightoraError> {
+        Self::create_under(prefix, root, &get_current_config().path_policy)
+    }
+
+    fn create_under(prefix: &str, root: Option<&Path>, policy: &PathPolicy) -> Result<Self, InsightoraError> {
+        let dir = tempfile::Builder::new()
+            .prefix(&format!("{}-", prefix))
+            .tempdir_in(scratch_root(root, policy)?)?;
+        Ok(Self(dir))
+    }
+
+    pub fn path(&self) -> &Path {
+        self.0.path()
+    }
+
+    /// Path of a file inside the directory
+    pub fn join(&self, name: impl AsRef<Path>) -> PathBuf {
+        self.0.path().join(name)
+    }
+}
+
+/// Temporary file owned by one operation, removed on drop
+///
+/// Created like `ScratchDir`, directly under the configured temp directory.
+#[derive(Debug)]
+pub struct ScratchFile(NamedTempFile);
+
+impl ScratchFile {
+    /// Create an empty file named `<prefix>-<random><suffix>`
+    pub fn create(prefix: &str, suffix: &str) -> Result<Self, InsightoraError> {
+        Self::create_under(prefix, suffix, None, &get_current_config().path_policy)
+    }
+
+    fn create_under(prefix: &str, suffix: &str, root: Option<&Path>, policy: &PathPolicy) -> Result<Self, InsightoraError> {
+        let file = tempfile::Builder::new()
+            .prefix(&format!("{}-", prefix))
+            .suffix(suffix)
+            .tempfile_in(scratch_root(root, policy)?)?;
+        Ok(Self(file))
+    }
+
+    pub fn path(&self) -> &Path {
+        self.0.path()
+    }
+
+    pub fn as_file_mut(&mut self) -> &mut File {
+        self.0.as_file_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scratch_dirs_are_unique_and_removed_on_drop() {
+        let root = TempDir::new().unwrap();
+        let policy = PathPolicy::default();
+        let first = ScratchDir::create_under("insightora-test", Some(root.path()), &policy).unwrap();
+        let second = ScratchDir::create_under("insightora-test", Some(root.path()), &policy).unwrap();
+        assert_ne!(first.path(), second.path());
+        assert!(first.path().starts_with(root.path()));
+        assert!(first
+            .path()
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("insightora-test-"));
+
+        let path = first.path().to_path_buf();
+        drop(first);
+        assert!(!path.exists());
+        assert!(second.path().exists());
+    }
+
+    #[test]
+    fn test_scratch_root_outside_policy_is_denied() {
+        let root = TempDir::new().unwrap();
+        let allowed = root.path().join("allowed");
+        let outside = root.path().join("outside");
+        std::fs::create_dir_all(&allowed).unwrap();
+        let policy = PathPolicy::new(&[&allowed], &[]).unwrap();
+
+        let err = ScratchDir::create_under("insightora-test", Some(outside.as_path()), &policy).unwrap_err();
+        assert!(matches!(err, InsightoraError::PermissionDenied(_)));
+        assert!(!outside.exists());
+        assert!(ScratchDir::create_under("insightora-test", Some(allowed.as_path()), &policy).is_ok());
+    }
+
+    #[test]
+    fn test_scratch_file_is_removed_on_drop() {
+        let root = TempDir::new().unwrap();
+        let mut file = ScratchFile::create_under("insightora-test", ".csv", Some(root.path()), &PathPolicy::default()).unwrap();
+        std::io::Write::write_all(file.as_file_mut(), b"a\n1\n").unwrap();
+        let path = file.path().to_path_buf();
+        assert_eq!(path.extension().unwrap(), "csv");
+        assert_eq!(std::fs::read(&path).unwrap(), b"a\n1\n");
+        drop(file);
+        assert!(!path.exists());
+    }
+}
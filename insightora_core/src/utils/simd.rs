@@ -0,0 +1,355 @@
+// Vectorized numeric kernels
+// AVX2 implementations of the hot reduction loops, with scalar fallbacks chosen at runtime
+
+use once_cell::sync::Lazy;
+use crate::error::InsightoraError;
+use crate::utils::capabilities::{use_fast_path, FastPath};
+
+/// Whether the CPU supports the AVX2 kernels (detected once)
+static AVX2_SUPPORTED: Lazy<bool> = Lazy::new(|| {
+    #[cfg(target_arch = "x86_64")]
+    {
+        is_x86_feature_detected!("avx2")
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        false
+    }
+});
+
+/// Which implementation a kernel call uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KernelPath {
+    Scalar,
+    /// 256-bit AVX2 lanes (x86_64 only)
+    Avx2,
+}
+
+impl KernelPath {
+    /// Path for the current configuration: vectorized when `enable_simd` is
+    /// set and the CPU supports it, scalar otherwise
+    ///
+    /// Falling back to scalar is recorded as a degradation of `operation`,
+    /// or refused under `require_fast_paths`.
+    pub fn select(operation: &str) -> Result<Self, InsightoraError> {
+        if use_fast_path(operation, FastPath::Simd, "scalar")? {
+            Ok(Self::best_available())
+        } else {
+            Ok(KernelPath::Scalar)
+        }
+    }
+
+    /// Fastest path this CPU supports, ignoring the configuration
+    pub fn best_available() -> Self {
+        if *AVX2_SUPPORTED {
+            KernelPath::Avx2
+        } else {
+            KernelPath::Scalar
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            KernelPath::Scalar => "scalar",
+            KernelPath::Avx2 => "avx2",
+        }
+    }
+}
+
+/// Count, mean and sum of squared deviations of a slice
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Moments {
+    pub count: u64,
+    pub mean: f64,
+    /// Sum of squared deviations from the mean
+    pub m2: f64,
+}
+
+// ============================================================================
+// Kernels
+// ============================================================================
+//
+// The vector paths reorder float additions, so `sum` and `moments` agree with
+// the scalar path within rounding; `min_max`, `count_nan` and `histogram` are
+// exact. A path the CPU lacks falls back to scalar.
+
+/// Sum of the values
+pub fn sum(values: &[f64], path: KernelPath) -> f64 {
+    match path {
+        #[cfg(target_arch = "x86_64")]
+        KernelPath::Avx2 if *AVX2_SUPPORTED => unsafe { avx2::sum(values) },
+        _ => values.iter().sum(),
+    }
+}
+
+/// Count, mean and squared deviations, computed in two passes for accuracy
+pub fn moments(values: &[f64], path: KernelPath) -> Moments {
+    if values.is_empty() {
+        return Moments { count: 0, mean: 0.0, m2: 0.0 };
+    }
+    let mean = sum(values, path) / values.len() as f64;
+    let m2 = match path {
+        #[cfg(target_arch = "x86_64")]
+        KernelPath::Avx2 if *AVX2_SUPPORTED => unsafe { avx2::squared_deviations(values, mean) },
+        _ => values.iter().map(|v| (v - mean) * (v - mean)).sum(),
+    };
+    Moments {
+        count: values.len() as u64,
+        mean,
+        m2,
+    }
+}
+
+/// Smallest and largest value; None for an empty slice. Values must not be NaN.
+pub fn min_max(values: &[f64], path: KernelPath) -> Option<(f64, f64)> {
+    if values.is_empty() {
+        return None;
+    }
+    Some(match path {
+        #[cfg(target_arch = "x86_64")]
+        KernelPath::Avx2 if *AVX2_SUPPORTED => unsafe { avx2::min_max(values) },
+        _ => values
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| (lo.min(v), hi.max(v))),
+    })
+}
+
+/// Number of NaN values
+pub fn count_nan(values: &[f64], path: KernelPath) -> usize {
+    match path {
+        #[cfg(target_arch = "x86_64")]
+        KernelPath::Avx2 if *AVX2_SUPPORTED => unsafe { avx2::count_nan(values) },
+        _ => values.iter().filter(|v| v.is_nan()).count(),
+    }
+}
+
+/// Add equal-width bin counts of the values to `counts`
+///
+/// Bin `i` covers `[min + i * width, min + (i + 1) * width)`; values below
+/// `min` land in the first bin and values at or above the top in the last.
+/// NaN values are skipped.
+pub fn histogram(values: &[f64], min: f64, width: f64, counts: &mut [u64], path: KernelPath) {
+    if counts.is_empty() || width.is_nan() || width <= 0.0 {
+        return;
+    }
+    match path {
+        #[cfg(target_arch = "x86_64")]
+        KernelPath::Avx2 if *AVX2_SUPPORTED => unsafe { avx2::histogram(values, min, 1.0 / width, counts) },
+        _ => scalar_histogram(values, min, 1.0 / width, counts),
+    }
+}
+
+#[inline]
+fn bin_index(value: f64, min: f64, inverse_width: f64, last: usize) -> usize {
+    let position = ((value - min) * inverse_width).floor();
+    if position <= 0.0 {
+        0
+    } else if position >= last as f64 {
+        last
+    } else {
+        position as usize
+    }
+}
+
+fn scalar_histogram(values: &[f64], min: f64, inverse_width: f64, counts: &mut [u64]) {
+    let last = counts.len() - 1;
+    for &value in values {
+        if !value.is_nan() {
+            counts[bin_index(value, min, inverse_width, last)] += 1;
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use std::arch::x86_64::*;
+
+    const LANES: usize = 4;
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn horizontal_sum(v: __m256d) -> f64 {
+        let mut lanes = [0.0f64; LANES];
+        _mm256_storeu_pd(lanes.as_mut_ptr(), v);
+        (lanes[0] + lanes[1]) + (lanes[2] + lanes[3])
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn sum(values: &[f64]) -> f64 {
+        // Four independent accumulators hide the add latency
+        let mut acc = [_mm256_setzero_pd(); 4];
+        let mut blocks = values.chunks_exact(LANES * 4);
+        for block in &mut blocks {
+            for (i, a) in acc.iter_mut().enumerate() {
+                *a = _mm256_add_pd(*a, _mm256_loadu_pd(block.as_ptr().add(i * LANES)));
+            }
+        }
+        let mut rest = blocks.remainder().chunks_exact(LANES);
+        for lane in &mut rest {
+            acc[0] = _mm256_add_pd(acc[0], _mm256_loadu_pd(lane.as_ptr()));
+        }
+        let total = _mm256_add_pd(_mm256_add_pd(acc[0], acc[1]), _mm256_add_pd(acc[2], acc[3]));
+        horizontal_sum(total) + rest.remainder().iter().sum::<f64>()
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn squared_deviations(values: &[f64], mean: f64) -> f64 {
+        let center = _mm256_set1_pd(mean);
+        let mut acc = [_mm256_setzero_pd(); 2];
+        let mut blocks = values.chunks_exact(LANES * 2);
+        for block in &mut blocks {
+            for (i, a) in acc.iter_mut().enumerate() {
+                let d = _mm256_sub_pd(_mm256_loadu_pd(block.as_ptr().add(i * LANES)), center);
+                *a = _mm256_add_pd(*a, _mm256_mul_pd(d, d));
+            }
+        }
+        let tail: f64 = blocks.remainder().iter().map(|v| (v - mean) * (v - mean)).sum();
+        horizontal_sum(_mm256_add_pd(acc[0], acc[1])) + tail
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn min_max(values: &[f64]) -> (f64, f64) {
+        let mut lo = _mm256_set1_pd(f64::INFINITY);
+        let mut hi = _mm256_set1_pd(f64::NEG_INFINITY);
+        let mut lanes = values.chunks_exact(LANES);
+        for lane in &mut lanes {
+            let v = _mm256_loadu_pd(lane.as_ptr());
+            lo = _mm256_min_pd(lo, v);
+            hi = _mm256_max_pd(hi, v);
+        }
+        let (mut lo_lanes, mut hi_lanes) = ([0.0f64; LANES], [0.0f64; LANES]);
+        _mm256_storeu_pd(lo_lanes.as_mut_ptr(), lo);
+        _mm256_storeu_pd(hi_lanes.as_mut_ptr(), hi);
+        lo_lanes
+            .iter()
+            .zip(hi_lanes.iter())
+            .chain(lanes.remainder().iter().map(|v| (v, v)))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(l, h), (a, b)| (l.min(*a), h.max(*b)))
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn count_nan(values: &[f64]) -> usize {
+        let mut count = 0usize;
+        let mut lanes = values.chunks_exact(LANES);
+        for lane in &mut lanes {
+            let v = _mm256_loadu_pd(lane.as_ptr());
+            // Unordered compare is true only for NaN lanes
+            let mask = _mm256_movemask_pd(_mm256_cmp_pd(v, v, _CMP_UNORD_Q));
+            count += mask.count_ones() as usize;
+        }
+        count + lanes.remainder().iter().filter(|v| v.is_nan()).count()
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn histogram(values: &[f64], min: f64, inverse_width: f64, counts: &mut [u64]) {
+        let last = counts.len() - 1;
+        let offset = _mm256_set1_pd(min);
+        let scale = _mm256_set1_pd(inverse_width);
+        let zero = _mm256_setzero_pd();
+        let top = _mm256_set1_pd(last as f64);
+        let mut positions = [0.0f64; LANES];
+        let mut lanes = values.chunks_exact(LANES);
+        for lane in &mut lanes {
+            let v = _mm256_loadu_pd(lane.as_ptr());
+            let position = _mm256_floor_pd(_mm256_mul_pd(_mm256_sub_pd(v, offset), scale));
+            // max/min return the second operand for NaN, so clamp before the NaN check below
+            let clamped = _mm256_min_pd(_mm256_max_pd(position, zero), top);
+            _mm256_storeu_pd(positions.as_mut_ptr(), clamped);
+            for (value, position) in lane.iter().zip(positions.iter()) {
+                if !value.is_nan() {
+                    counts[*position as usize] += 1;
+                }
+            }
+        }
+        super::scalar_histogram(lanes.remainder(), min, inverse_width, counts);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    /// Deterministic values spanning several magnitudes, with a few NaN
+    fn sample(n: usize) -> Vec<f64> {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        (0..n)
+            .map(|i| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                let unit = (state >> 11) as f64 / (1u64 << 53) as f64;
+                if i % 97 == 13 {
+                    f64::NAN
+                } else {
+                    (unit - 0.3) * 10f64.powi((i % 7) as i32)
+                }
+            })
+            .collect()
+    }
+
+    fn close(a: f64, b: f64, scale: f64) -> bool {
+        (a - b).abs() <= 1e-12 * scale.max(1.0)
+    }
+
+    #[test]
+    fn test_paths_agree() {
+        let best = KernelPath::best_available();
+        // Lengths around the block sizes exercise every tail
+        for n in [0, 1, 3, 4, 7, 16, 17, 33, 1000, 4099] {
+            let values = sample(n);
+            assert_eq!(count_nan(&values, best), count_nan(&values, KernelPath::Scalar), "n={}", n);
+
+            let finite: Vec<f64> = values.iter().copied().filter(|v| !v.is_nan()).collect();
+            assert_eq!(min_max(&finite, best), min_max(&finite, KernelPath::Scalar), "n={}", n);
+
+            let magnitude: f64 = finite.iter().map(|v| v.abs()).sum();
+            assert!(close(sum(&finite, best), sum(&finite, KernelPath::Scalar), magnitude), "n={}", n);
+            let (a, b) = (moments(&finite, best), moments(&finite, KernelPath::Scalar));
+            assert_eq!(a.count, b.count);
+            assert!(close(a.mean, b.mean, magnitude), "n={}", n);
+            assert!((a.m2 - b.m2).abs() <= 1e-10 * b.m2.max(1.0), "n={}", n);
+
+            let (mut vector_bins, mut scalar_bins) = (vec![0u64; 12], vec![0u64; 12]);
+            histogram(&values, -1000.0, 250.0, &mut vector_bins, best);
+            histogram(&values, -1000.0, 250.0, &mut scalar_bins, KernelPath::Scalar);
+            assert_eq!(vector_bins, scalar_bins, "n={}", n);
+            assert_eq!(vector_bins.iter().sum::<u64>() as usize, finite.len());
+        }
+    }
+
+    #[test]
+    fn test_histogram_edges() {
+        let values = [-5.0, 0.0, 0.999, 1.0, 2.5, 3.0, 100.0, f64::NAN];
+        for path in [KernelPath::Scalar, KernelPath::best_available()] {
+            let mut counts = vec![0u64; 3];
+            histogram(&values, 0.0, 1.0, &mut counts, path);
+            assert_eq!(counts, vec![3, 1, 3]);
+        }
+    }
+
+    /// Timing comparison; run with `cargo test --release -- --ignored simd`
+    #[test]
+    #[ignore = "benchmark; run with --release --ignored"]
+    fn bench_simd_kernels_beat_scalar() {
+        let best = KernelPath::best_available();
+        // Nothing to compare on CPUs without vector kernels
+        if best == KernelPath::Scalar {
+            return;
+        }
+        let values: Vec<f64> = sample(16 << 20).into_iter().filter(|v| !v.is_nan()).collect();
+        let time = |path: KernelPath| {
+            let started = Instant::now();
+            let mut sink = 0.0;
+            for _ in 0..5 {
+                sink += moments(&values, path).m2;
+                let (lo, hi) = min_max(&values, path).unwrap();
+                sink += lo + hi + count_nan(&values, path) as f64;
+            }
+            (started.elapsed(), sink)
+        };
+        let (scalar, _) = time(KernelPath::Scalar);
+        let (vector, _) = time(best);
+        assert!(vector < scalar, "vector path ({:?}) was not faster than scalar ({:?})", vector, scalar);
+    }
+}
@@ -0,0 +1,292 @@
+// Warning aggregation
+// Counts repeated per-row warnings by category, column and message so an operation reports each kind once
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use crate::config::get_current_config;
+use crate::error::InsightoraError;
+
+/// Examples kept per warning unless `warning_sample_size` is configured
+pub const DEFAULT_WARNING_SAMPLE_SIZE: usize = 5;
+
+/// Level aggregated warnings are logged at when an operation finishes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WarningLevel {
+    /// Not logged; still listed in result summaries and reports
+    Off,
+    Debug,
+    Info,
+    #[default]
+    Warning,
+    Error,
+}
+
+impl WarningLevel {
+    pub fn from_name(name: &str) -> Result<Self, InsightoraError> {
+        match name.to_ascii_lowercase().as_str() {
+            "off" => Ok(WarningLevel::Off),
+            "debug" => Ok(WarningLevel::Debug),
+            "info" => Ok(WarningLevel::Info),
+            "warning" => Ok(WarningLevel::Warning),
+            "error" => Ok(WarningLevel::Error),
+            other => Err(InsightoraError::ValidationError(format!(
+                "Unknown warning log level '{}'; expected 'off', 'debug', 'info', 'warning' or 'error'",
+                other
+            ))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WarningLevel::Off => "off",
+            WarningLevel::Debug => "debug",
+            WarningLevel::Info => "info",
+            WarningLevel::Warning => "warning",
+            WarningLevel::Error => "error",
+        }
+    }
+
+    /// Numeric level of Python's `logging` module; None when not logged
+    pub fn python_level(&self) -> Option<u32> {
+        match self {
+            WarningLevel::Off => None,
+            WarningLevel::Debug => Some(10),
+            WarningLevel::Info => Some(20),
+            WarningLevel::Warning => Some(30),
+            WarningLevel::Error => Some(40),
+        }
+    }
+}
+
+/// Every warning sharing a category, column and message template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AggregatedWarning {
+    /// Kind of warning, e.g. "coercion" or "repair"
+    pub category: &'static str,
+    pub column: String,
+    /// Message with `{count}` and `{column}` placeholders
+    pub template: &'static str,
+    pub count: usize,
+    /// The first concrete examples, in emission order
+    pub examples: Vec<String>,
+}
+
+impl AggregatedWarning {
+    /// The template filled in, without examples
+    pub fn summary(&self) -> String {
+        self.template
+            .replace("{count}", &grouped(self.count))
+            .replace("{column}", &self.column)
+    }
+
+    /// The summary followed by the examples, e.g.
+    /// "1,234,567 values coerced to null in column price; examples: 'n/a', 'TBD'"
+    pub fn message(&self) -> String {
+        if self.examples.is_empty() {
+            self.summary()
+        } else {
+            format!("{}; examples: {}", self.summary(), self.examples.join(", "))
+        }
+    }
+}
+
+/// Collects the warnings of one operation
+///
+/// Emitting a warning already seen costs a hash lookup and an increment;
+/// an example is only formatted while fewer than the sample size are kept,
+/// so emitting from a per-row loop is cheap. Warnings are kept in the order
+/// they were first emitted.
+///
+/// # Example
+/// ```
+/// use insightora_core::api::WarningCollector;
+///
+/// let mut warnings = WarningCollector::with_sample_size(2);
+/// for value in ["n/a", "TBD", "?"] {
+///     warnings.emit("coercion", "price", "{count} values coerced to null in column {column}", format_args!("'{}'", value));
+/// }
+/// let warnings = warnings.finish();
+/// assert_eq!(warnings[0].message(), "3 values coerced to null in column price; examples: 'n/a', 'TBD'");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarningCollector {
+    sample_size: usize,
+    /// (category, template) -> column -> position in `warnings`
+    index: HashMap<(&'static str, &'static str), HashMap<String, usize>>,
+    warnings: Vec<AggregatedWarning>,
+}
+
+impl Default for WarningCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WarningCollector {
+    /// Collector keeping the configured `warning_sample_size` examples
+    pub fn new() -> Self {
+        Self::with_sample_size(get_current_config().warning_sample_size)
+    }
+
+    pub fn with_sample_size(sample_size: usize) -> Self {
+        Self { sample_size, index: HashMap::new(), warnings: Vec::new() }
+    }
+
+    /// Count one warning, keeping `example` while the sample isn't full
+    pub fn emit(&mut self, category: &'static str, column: &str, template: &'static str, example: impl Display) {
+        let sample_size = self.sample_size;
+        let warning = self.entry(category, column, template);
+        warning.count += 1;
+        if warning.examples.len() < sample_size {
+            warning.examples.push(example.to_string());
+        }
+    }
+
+    /// Count `count` warnings at once, e.g. from a vectorized check
+    pub fn emit_many<I>(&mut self, category: &'static str, column: &str, template: &'static str, count: usize, examples: I)
+    where
+        I: IntoIterator,
+        I::Item: Display,
+    {
+        if count == 0 {
+            return;
+        }
+        let sample_size = self.sample_size;
+        let warning = self.entry(category, column, template);
+        warning.count += count;
+        let room = sample_size.saturating_sub(warning.examples.len());
+        warning.examples.extend(examples.into_iter().take(room).map(|e| e.to_string()));
+    }
+
+    /// Add the warnings of another collector, e.g. from a parallel chunk
+    pub fn merge(&mut self, other: WarningCollector) {
+        for warning in other.warnings {
+            let AggregatedWarning { category, column, template, count, examples } = warning;
+            self.emit_many(category, &column, template, count, examples);
+        }
+    }
+
+    /// Examples kept per warning
+    pub fn sample_size(&self) -> usize {
+        self.sample_size
+    }
+
+    pub fn warnings(&self) -> &[AggregatedWarning] {
+        &self.warnings
+    }
+
+    /// Total warnings emitted, across all keys
+    pub fn total(&self) -> usize {
+        self.warnings.iter().map(|w| w.count).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    pub fn finish(self) -> Vec<AggregatedWarning> {
+        self.warnings
+    }
+
+    fn entry(&mut self, category: &'static str, column: &str, template: &'static str) -> &mut AggregatedWarning {
+        let columns = self.index.entry((category, template)).or_default();
+        let position = match columns.get(column) {
+            Some(&position) => position,
+            None => {
+                columns.insert(column.to_string(), self.warnings.len());
+                self.warnings.push(AggregatedWarning {
+                    category,
+                    column: column.to_string(),
+                    template,
+                    count: 0,
+                    examples: Vec::new(),
+                });
+                self.warnings.len() - 1
+            }
+        };
+        &mut self.warnings[position]
+    }
+}
+
+/// Count with thousands separators: 1234567 -> "1,234,567"
+fn grouped(count: usize) -> String {
+    let digits = count.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_warnings_are_counted_once_with_bounded_samples() {
+        const COERCED: &str = "{count} values coerced to null in column {column}";
+        let mut warnings = WarningCollector::with_sample_size(3);
+        for row in 0..1_234_567 {
+            warnings.emit("coercion", "price", COERCED, format_args!("row {}", row));
+        }
+        warnings.emit("coercion", "qty", COERCED, "'x'");
+
+        let mut chunk = WarningCollector::with_sample_size(3);
+        chunk.emit_many("coercion", "qty", COERCED, 2, ["'y'", "'z'", "'w'"]);
+        chunk.emit_many("repair", "comment", "{count} rows repaired in column {column}", 0, Vec::<String>::new());
+        warnings.merge(chunk);
+
+        assert_eq!(warnings.total(), 1_234_570);
+        let warnings = warnings.finish();
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(
+            warnings[0].message(),
+            "1,234,567 values coerced to null in column price; examples: row 0, row 1, row 2"
+        );
+        assert_eq!(warnings[1].count, 3);
+        assert_eq!(warnings[1].examples, ["'x'", "'y'", "'z'"]);
+        assert_eq!(warnings[1].summary(), "3 values coerced to null in column qty");
+
+        assert_eq!(grouped(999), "999");
+        assert_eq!(grouped(1000), "1,000");
+        assert_eq!(WarningLevel::from_name("INFO").unwrap().python_level(), Some(20));
+        assert!(WarningLevel::from_name("loud").is_err());
+    }
+
+    #[test]
+    #[ignore = "benchmark; run with --ignored --nocapture"]
+    fn bench_emission_in_a_parse_loop() {
+        const COERCED: &str = "{count} values coerced to null in column {column}";
+        // Every third value fails to parse, as in a badly typed column
+        let values: Vec<String> = (0..3_000_000)
+            .map(|i| if i % 3 == 0 { format!("n/a-{}", i) } else { i.to_string() })
+            .collect();
+
+        let start = std::time::Instant::now();
+        let mut parsed = 0i64;
+        for value in &values {
+            parsed += value.parse::<i64>().unwrap_or(0);
+        }
+        let baseline = start.elapsed();
+
+        let mut warnings = WarningCollector::with_sample_size(DEFAULT_WARNING_SAMPLE_SIZE);
+        let start = std::time::Instant::now();
+        let mut parsed_warned = 0i64;
+        for value in &values {
+            match value.parse::<i64>() {
+                Ok(number) => parsed_warned += number,
+                Err(_) => warnings.emit("coercion", "price", COERCED, value),
+            }
+        }
+        let warned = start.elapsed();
+
+        assert_eq!(parsed, parsed_warned);
+        assert_eq!(warnings.total(), 1_000_000);
+        let per_warning = warned.saturating_sub(baseline) / 1_000_000;
+        println!("parse: {:?}, parse + warnings: {:?} ({:?}/warning)", baseline, warned, per_warning);
+        assert!(per_warning < std::time::Duration::from_micros(1));
+    }
+}